@@ -0,0 +1,514 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//! Load generator of the `quickwit tool bench` commands.
+
+use std::fmt::Write as _;
+use std::future::Future;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Context};
+use bytesize::ByteSize;
+use futures::{stream, StreamExt};
+use quickwit_common::uri::Uri;
+use quickwit_rest_client::models::IngestSource;
+use quickwit_rest_client::rest_client::CommitType;
+use quickwit_serve::SearchRequestQueryString;
+use quickwit_storage::{load_file, StorageResolver};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use tabled::Tabled;
+use tokio::time::{interval, MissedTickBehavior};
+use tracing::debug;
+
+use crate::make_table;
+use crate::stats::percentile;
+use crate::tool::{BenchIngestArgs, BenchSearchArgs};
+
+/// Words picked by the `{{random_word}}` placeholder.
+const RANDOM_WORDS: &[&str] = &[
+    "alpha", "bravo", "charlie", "delta", "echo", "foxtrot", "golf", "hotel", "india", "juliett",
+    "kilo", "lima", "mike", "november", "oscar", "papa",
+];
+
+#[derive(Debug, Eq, PartialEq)]
+enum TemplateToken {
+    Literal(String),
+    /// Sequence number of the document, starting at 0.
+    Seq,
+    /// Current Unix timestamp in seconds.
+    Timestamp,
+    /// Random integer between 0 and 1,000,000.
+    RandomInt,
+    /// Random word from [`RANDOM_WORDS`].
+    RandomWord,
+}
+
+/// Template of the synthetic documents generated by `quickwit tool bench ingest`. The template is
+/// a JSON document in which the `{{seq}}`, `{{timestamp}}`, `{{random_int}}`, and
+/// `{{random_word}}` placeholders are substituted for each document.
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) struct DocTemplate {
+    tokens: Vec<TemplateToken>,
+}
+
+impl DocTemplate {
+    pub fn parse(template: &str) -> anyhow::Result<Self> {
+        // The documents are sent as NDJSON, so the template must fit on a single line.
+        let template = template.trim().replace(['\r', '\n'], " ");
+        let mut tokens = Vec::new();
+        let mut remaining = template.as_str();
+
+        while let Some(start) = remaining.find("{{") {
+            if start > 0 {
+                tokens.push(TemplateToken::Literal(remaining[..start].to_string()));
+            }
+            let end = remaining[start..]
+                .find("}}")
+                .map(|offset| start + offset)
+                .context("failed to parse doc template: unclosed placeholder")?;
+            let token = match remaining[start + 2..end].trim() {
+                "seq" => TemplateToken::Seq,
+                "timestamp" => TemplateToken::Timestamp,
+                "random_int" => TemplateToken::RandomInt,
+                "random_word" => TemplateToken::RandomWord,
+                placeholder => {
+                    bail!("failed to parse doc template: unknown placeholder `{placeholder}`")
+                }
+            };
+            tokens.push(token);
+            remaining = &remaining[end + 2..];
+        }
+        if !remaining.is_empty() {
+            tokens.push(TemplateToken::Literal(remaining.to_string()));
+        }
+        if tokens.is_empty() {
+            bail!("failed to parse doc template: template is empty");
+        }
+        Ok(Self { tokens })
+    }
+
+    /// Renders the document `seq` and appends it to `buffer` followed by a newline.
+    fn render(&self, seq: u64, rng: &mut impl Rng, buffer: &mut String) {
+        for token in &self.tokens {
+            match token {
+                TemplateToken::Literal(literal) => buffer.push_str(literal),
+                TemplateToken::Seq => {
+                    let _ = write!(buffer, "{seq}");
+                }
+                TemplateToken::Timestamp => {
+                    let timestamp = SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .map(|duration| duration.as_secs())
+                        .unwrap_or_default();
+                    let _ = write!(buffer, "{timestamp}");
+                }
+                TemplateToken::RandomInt => {
+                    let random_int = rng.gen_range(0..=1_000_000u32);
+                    let _ = write!(buffer, "{random_int}");
+                }
+                TemplateToken::RandomWord => {
+                    let random_word = RANDOM_WORDS[rng.gen_range(0..RANDOM_WORDS.len())];
+                    buffer.push_str(random_word);
+                }
+            }
+        }
+        buffer.push('\n');
+    }
+}
+
+/// Source of the documents sent by `quickwit tool bench ingest`.
+enum BenchDocSource {
+    Template(DocTemplate),
+    /// Documents replayed in a loop from an NDJSON file.
+    Replay(Vec<String>),
+}
+
+impl BenchDocSource {
+    async fn load(args: &BenchIngestArgs) -> anyhow::Result<Self> {
+        match (&args.template_uri_opt, &args.input_uri_opt) {
+            (Some(template_uri), None) => {
+                let template_bytes = load_bytes(template_uri).await?;
+                let template = std::str::from_utf8(&template_bytes)
+                    .context("failed to parse doc template: template is not valid UTF-8")?;
+                let doc_template = DocTemplate::parse(template)?;
+                Ok(Self::Template(doc_template))
+            }
+            (None, Some(input_uri)) => {
+                let input_bytes = load_bytes(input_uri).await?;
+                let docs: Vec<String> = String::from_utf8_lossy(&input_bytes)
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .map(str::to_string)
+                    .collect();
+                if docs.is_empty() {
+                    bail!("input file `{input_uri}` does not contain any document");
+                }
+                Ok(Self::Replay(docs))
+            }
+            _ => bail!("exactly one of `--template` or `--input-path` must be provided"),
+        }
+    }
+
+    fn build_batch(&self, first_seq: u64, num_docs: u64, rng: &mut impl Rng) -> String {
+        let mut batch = String::new();
+
+        for seq in first_seq..first_seq + num_docs {
+            match self {
+                Self::Template(doc_template) => doc_template.render(seq, rng, &mut batch),
+                Self::Replay(docs) => {
+                    batch.push_str(&docs[seq as usize % docs.len()]);
+                    batch.push('\n');
+                }
+            }
+        }
+        batch
+    }
+}
+
+async fn load_bytes(uri: &Uri) -> anyhow::Result<Vec<u8>> {
+    let storage_resolver = StorageResolver::unconfigured();
+    let bytes = load_file(&storage_resolver, uri).await?;
+    Ok(bytes.as_slice().to_vec())
+}
+
+/// Latencies and errors of the requests sent during a benchmark.
+#[derive(Debug, Default)]
+pub(crate) struct BenchStats {
+    latencies_micros: Vec<u64>,
+    num_errors: u64,
+    last_error_opt: Option<String>,
+    elapsed: Duration,
+}
+
+impl BenchStats {
+    fn record(&mut self, latency: Duration, result: anyhow::Result<()>) {
+        match result {
+            Ok(()) => self
+                .latencies_micros
+                .push(latency.as_micros().try_into().unwrap_or(u64::MAX)),
+            Err(error) => {
+                self.num_errors += 1;
+                self.last_error_opt = Some(format!("{error:#}"));
+            }
+        }
+    }
+
+    fn num_requests(&self) -> u64 {
+        self.latencies_micros.len() as u64 + self.num_errors
+    }
+
+    /// Returns the successful requests per second.
+    fn throughput(&self) -> f64 {
+        let elapsed_secs = self.elapsed.as_secs_f64();
+        if elapsed_secs == 0.0 {
+            return 0.0;
+        }
+        self.latencies_micros.len() as f64 / elapsed_secs
+    }
+
+    /// Returns the `percent`-th percentile of the latencies of the successful requests.
+    fn latency_percentile(&self, percent: usize) -> Option<Duration> {
+        if self.latencies_micros.is_empty() {
+            return None;
+        }
+        let latency_micros = percentile(&self.latencies_micros, percent);
+        Some(Duration::from_micros(latency_micros as u64))
+    }
+}
+
+/// Sends requests at the target rate, if any, with at most `concurrency` requests in flight until
+/// `duration` has elapsed. When the cluster cannot keep up with the target rate, the requests are
+/// delayed, so the actual throughput must be read from the returned stats.
+async fn run_load<F, Fut>(
+    requests_per_sec_opt: Option<f64>,
+    concurrency: usize,
+    duration: Duration,
+    send_request: F,
+) -> BenchStats
+where
+    F: Fn(u64) -> Fut,
+    Fut: Future<Output = anyhow::Result<()>>,
+{
+    let start = Instant::now();
+    let deadline = start + duration;
+
+    let interval_opt = requests_per_sec_opt.map(|requests_per_sec| {
+        let mut interval = interval(Duration::from_secs_f64(1.0 / requests_per_sec));
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        interval
+    });
+    let request_ids = stream::unfold(
+        (0u64, interval_opt),
+        move |(request_id, mut interval_opt)| async move {
+            if let Some(interval) = interval_opt.as_mut() {
+                interval.tick().await;
+            }
+            if Instant::now() >= deadline {
+                return None;
+            }
+            Some((request_id, (request_id + 1, interval_opt)))
+        },
+    );
+    let stats = Mutex::new(BenchStats::default());
+
+    request_ids
+        .for_each_concurrent(concurrency, |request_id| {
+            let request_future = send_request(request_id);
+            let stats = &stats;
+            async move {
+                let request_start = Instant::now();
+                let result = request_future.await;
+                let latency = request_start.elapsed();
+                stats
+                    .lock()
+                    .expect("lock should not be poisoned")
+                    .record(latency, result);
+            }
+        })
+        .await;
+
+    let mut stats = stats.into_inner().expect("lock should not be poisoned");
+    stats.latencies_micros.sort_unstable();
+    stats.elapsed = start.elapsed();
+    stats
+}
+
+#[derive(Tabled)]
+struct BenchReportRow {
+    #[tabled(rename = "Requests")]
+    num_requests: u64,
+    #[tabled(rename = "Errors")]
+    num_errors: u64,
+    #[tabled(rename = "Throughput")]
+    throughput: String,
+    #[tabled(rename = "Latency p50")]
+    latency_p50: String,
+    #[tabled(rename = "Latency p90")]
+    latency_p90: String,
+    #[tabled(rename = "Latency p99")]
+    latency_p99: String,
+    #[tabled(rename = "Latency max")]
+    latency_max: String,
+}
+
+fn print_report(header: &str, stats: &BenchStats, throughput: String) {
+    let format_latency = |percent: usize| {
+        stats
+            .latency_percentile(percent)
+            .map(|latency| format!("{:.1}ms", latency.as_secs_f64() * 1_000.0))
+            .unwrap_or_else(|| "-".to_string())
+    };
+    let row = BenchReportRow {
+        num_requests: stats.num_requests(),
+        num_errors: stats.num_errors,
+        throughput,
+        latency_p50: format_latency(50),
+        latency_p90: format_latency(90),
+        latency_p99: format_latency(99),
+        latency_max: format_latency(100),
+    };
+    println!("{}", make_table(header, [row], true));
+
+    if let Some(last_error) = &stats.last_error_opt {
+        println!("Last error: {last_error}");
+    }
+}
+
+pub async fn bench_ingest_cli(args: BenchIngestArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "bench-ingest");
+    let doc_source = BenchDocSource::load(&args).await?;
+
+    println!(
+        "❯ Ingesting documents into `{}` for {}...",
+        args.index_id,
+        humantime::format_duration(args.duration)
+    );
+    let qw_client = args.client_args.client();
+    let batch_num_docs = args.batch_num_docs as u64;
+    let num_bytes = AtomicU64::new(0);
+
+    let requests_per_sec_opt = args
+        .docs_per_sec_opt
+        .map(|docs_per_sec| docs_per_sec as f64 / batch_num_docs as f64);
+
+    let stats = run_load(
+        requests_per_sec_opt,
+        args.concurrency,
+        args.duration,
+        |request_id| {
+            let mut rng = StdRng::seed_from_u64(request_id);
+            let batch =
+                doc_source.build_batch(request_id * batch_num_docs, batch_num_docs, &mut rng);
+            let batch_num_bytes = batch.len() as u64;
+            let qw_client = &qw_client;
+            let index_id = &args.index_id;
+            let num_bytes = &num_bytes;
+            async move {
+                qw_client
+                    .ingest(
+                        index_id,
+                        IngestSource::Str(batch),
+                        None,
+                        None,
+                        CommitType::Auto,
+                    )
+                    .await?;
+                num_bytes.fetch_add(batch_num_bytes, Ordering::Relaxed);
+                Ok(())
+            }
+        },
+    )
+    .await;
+
+    let elapsed_secs = stats.elapsed.as_secs_f64().max(f64::EPSILON);
+    let bytes_per_sec = num_bytes.load(Ordering::Relaxed) as f64 / elapsed_secs;
+    let throughput = format!(
+        "{:.0} docs/s, {}/s",
+        stats.throughput() * batch_num_docs as f64,
+        ByteSize(bytes_per_sec as u64)
+    );
+    print_report("Ingest Benchmark", &stats, throughput);
+    Ok(())
+}
+
+pub async fn bench_search_cli(args: BenchSearchArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "bench-search");
+    if args.queries.is_empty() {
+        bail!("at least one query must be provided");
+    }
+    println!(
+        "❯ Searching `{}` for {}...",
+        args.index_id,
+        humantime::format_duration(args.duration)
+    );
+    let qw_client = args.client_args.client();
+    let requests_per_sec_opt = args
+        .queries_per_sec_opt
+        .map(|queries_per_sec| queries_per_sec as f64);
+
+    let stats = run_load(
+        requests_per_sec_opt,
+        args.concurrency,
+        args.duration,
+        |request_id| {
+            // The queries are sent in a round-robin fashion.
+            let query = args.queries[request_id as usize % args.queries.len()].clone();
+            let search_request = SearchRequestQueryString {
+                query,
+                max_hits: args.max_hits as u64,
+                ..Default::default()
+            };
+            let qw_client = &qw_client;
+            let index_id = &args.index_id;
+            async move {
+                qw_client.search(index_id, search_request).await?;
+                Ok(())
+            }
+        },
+    )
+    .await;
+
+    let throughput = format!("{:.1} queries/s", stats.throughput());
+    print_report("Search Benchmark", &stats, throughput);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_doc_template_parse_and_render() {
+        let doc_template =
+            DocTemplate::parse("{\n  \"id\": {{seq}},\n  \"body\": \"{{ random_word }}\"\n}\n")
+                .unwrap();
+        assert_eq!(
+            doc_template.tokens,
+            [
+                TemplateToken::Literal("{   \"id\": ".to_string()),
+                TemplateToken::Seq,
+                TemplateToken::Literal(",   \"body\": \"".to_string()),
+                TemplateToken::RandomWord,
+                TemplateToken::Literal("\" }".to_string()),
+            ]
+        );
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut buffer = String::new();
+        doc_template.render(42, &mut rng, &mut buffer);
+
+        assert!(buffer.ends_with('\n'));
+        let doc: serde_json::Value = serde_json::from_str(&buffer).unwrap();
+        assert_eq!(doc["id"], 42);
+        assert!(RANDOM_WORDS.contains(&doc["body"].as_str().unwrap()));
+
+        let error = DocTemplate::parse("{\"id\": {{seq}").unwrap_err();
+        assert!(error.to_string().contains("unclosed placeholder"));
+
+        let error = DocTemplate::parse("{\"id\": {{uuid}}}").unwrap_err();
+        assert!(error.to_string().contains("unknown placeholder `uuid`"));
+    }
+
+    #[test]
+    fn test_bench_doc_source_build_batch() {
+        let mut rng = StdRng::seed_from_u64(0);
+
+        let doc_source =
+            BenchDocSource::Replay(vec!["{\"a\": 1}".to_string(), "{\"a\": 2}".to_string()]);
+        let batch = doc_source.build_batch(1, 3, &mut rng);
+        assert_eq!(batch, "{\"a\": 2}\n{\"a\": 1}\n{\"a\": 2}\n");
+
+        let doc_template = DocTemplate::parse("{\"id\": {{seq}}}").unwrap();
+        let doc_source = BenchDocSource::Template(doc_template);
+        let batch = doc_source.build_batch(10, 2, &mut rng);
+        assert_eq!(batch, "{\"id\": 10}\n{\"id\": 11}\n");
+    }
+
+    #[tokio::test]
+    async fn test_run_load() {
+        let stats = run_load(
+            None,
+            4,
+            Duration::from_millis(50),
+            |request_id| async move {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+                if request_id % 10 == 0 {
+                    bail!("request {request_id} failed");
+                }
+                Ok(())
+            },
+        )
+        .await;
+        assert!(stats.num_requests() > 0);
+        assert!(stats.num_errors > 0);
+        assert!(stats.last_error_opt.unwrap().starts_with("request"));
+
+        let stats = run_load(Some(100.0), 4, Duration::from_millis(200), |_| async move {
+            Ok(())
+        })
+        .await;
+        // The target rate caps the number of requests.
+        assert!(stats.num_requests() <= 21);
+        assert_eq!(stats.num_errors, 0);
+        assert!(stats.latency_percentile(99).is_some());
+    }
+}
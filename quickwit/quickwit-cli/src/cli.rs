@@ -21,6 +21,7 @@ use anyhow::{bail, Context};
 use clap::{arg, Arg, ArgAction, ArgMatches, Command};
 use tracing::Level;
 
+use crate::config::{build_config_command, ConfigCliCommand};
 use crate::index::{build_index_command, IndexCliCommand};
 use crate::service::{build_run_command, RunCliCommand};
 use crate::source::{build_source_command, SourceCliCommand};
@@ -51,6 +52,7 @@ pub fn build_cli() -> Command {
         .subcommand(build_source_command().display_order(3))
         .subcommand(build_split_command().display_order(4))
         .subcommand(build_tool_command().display_order(5))
+        .subcommand(build_config_command().display_order(6))
         .arg_required_else_help(true)
         .disable_help_subcommand(true)
         .subcommand_required(true)
@@ -63,6 +65,7 @@ pub enum CliCommand {
     Split(SplitCliCommand),
     Source(SourceCliCommand),
     Tool(ToolCliCommand),
+    Config(ConfigCliCommand),
 }
 
 impl CliCommand {
@@ -73,6 +76,7 @@ impl CliCommand {
             CliCommand::Source(_) => Level::ERROR,
             CliCommand::Split(_) => Level::ERROR,
             CliCommand::Tool(_) => Level::ERROR,
+            CliCommand::Config(_) => Level::ERROR,
         }
     }
 
@@ -81,6 +85,7 @@ impl CliCommand {
             .remove_subcommand()
             .context("failed to parse command")?;
         match subcommand.as_str() {
+            "config" => ConfigCliCommand::parse_cli_args(submatches).map(CliCommand::Config),
             "index" => IndexCliCommand::parse_cli_args(submatches).map(CliCommand::Index),
             "run" => RunCliCommand::parse_cli_args(submatches).map(CliCommand::Run),
             "source" => SourceCliCommand::parse_cli_args(submatches).map(CliCommand::Source),
@@ -97,6 +102,7 @@ impl CliCommand {
             CliCommand::Source(subcommand) => subcommand.execute().await,
             CliCommand::Split(subcommand) => subcommand.execute().await,
             CliCommand::Tool(subcommand) => subcommand.execute().await,
+            CliCommand::Config(subcommand) => subcommand.execute().await,
         }
     }
 }
@@ -0,0 +1,750 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{bail, Context};
+use clap::{arg, ArgMatches, Command};
+use colored::Colorize;
+use quickwit_common::uri::Uri;
+use quickwit_config::{
+    load_index_config_from_user_config, load_source_config_from_user_config, ConfigFormat,
+    IndexConfig, NodeConfig, SourceConfig,
+};
+use quickwit_indexing::source::EMIT_BATCHES_TIMEOUT;
+use quickwit_storage::{load_file, StorageResolver};
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+/// Index configs linted without a node config are resolved against this root URI.
+const LINT_DEFAULT_INDEX_ROOT_URI: &str = "ram:///indexes";
+
+/// Config versions that are still accepted but superseded by the current one.
+const DEPRECATED_CONFIG_VERSIONS: [&str; 2] = ["0.4", "0.5"];
+
+pub fn build_config_command() -> Command {
+    Command::new("config")
+        .about("Validates node, index, and source config files.")
+        .subcommand(
+            Command::new("lint")
+                .about("Detects invalid, deprecated, and suspicious settings across one or multiple config files.")
+                .args(&[
+                    arg!(<FILES> ... "Location of the config files to lint. The kind of each config (node, index, or source) is inferred from its content.")
+                        .display_order(1),
+                    arg!(--"output-format" <OUTPUT_FORMAT> "Output format. Possible values are `text` and `json`.")
+                        .alias("format")
+                        .display_order(2)
+                        .required(false),
+                    arg!(--"deny-warnings" "Exits with a non-zero status code if any warning is reported.")
+                        .display_order(3)
+                        .required(false),
+                ])
+            )
+        .arg_required_else_help(true)
+}
+
+#[derive(Debug, Eq, PartialEq)]
+enum OutputFormat {
+    Text, // Default
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(output_format_str: &str) -> anyhow::Result<Self> {
+        match output_format_str {
+            "json" => Ok(OutputFormat::Json),
+            "text" => Ok(OutputFormat::Text),
+            _ => bail!(
+                "unknown output format `{output_format_str}`. supported formats are: `text` and \
+                 `json`"
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct LintConfigArgs {
+    pub config_uris: Vec<Uri>,
+    pub deny_warnings: bool,
+    output_format: OutputFormat,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum ConfigCliCommand {
+    Lint(LintConfigArgs),
+}
+
+impl ConfigCliCommand {
+    pub fn parse_cli_args(mut matches: ArgMatches) -> anyhow::Result<Self> {
+        let (subcommand, submatches) = matches
+            .remove_subcommand()
+            .context("failed to parse config subcommand")?;
+        match subcommand.as_str() {
+            "lint" => Self::parse_lint_args(submatches),
+            _ => bail!("unknown config subcommand `{subcommand}`"),
+        }
+    }
+
+    fn parse_lint_args(mut matches: ArgMatches) -> anyhow::Result<Self> {
+        let config_uris = matches
+            .remove_many::<String>("FILES")
+            .expect("`FILES` should be a required arg.")
+            .map(|uri_str| Uri::from_str(&uri_str))
+            .collect::<anyhow::Result<Vec<Uri>>>()?;
+        let output_format = matches
+            .remove_one::<String>("output-format")
+            .map(|output_format_str| OutputFormat::from_str(&output_format_str))
+            .transpose()?
+            .unwrap_or(OutputFormat::Text);
+        let deny_warnings = matches.get_flag("deny-warnings");
+        Ok(Self::Lint(LintConfigArgs {
+            config_uris,
+            deny_warnings,
+            output_format,
+        }))
+    }
+
+    pub async fn execute(self) -> anyhow::Result<()> {
+        match self {
+            Self::Lint(args) => lint_config_cli(args).await,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum ConfigKind {
+    Node,
+    Index,
+    Source,
+}
+
+impl ConfigKind {
+    /// Infers the kind of a config from the identifier it declares.
+    fn infer(config_value: &JsonValue) -> Self {
+        if config_value.get("index_id").is_some() {
+            ConfigKind::Index
+        } else if config_value.get("source_id").is_some() {
+            ConfigKind::Source
+        } else {
+            ConfigKind::Node
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Severity {
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "{}", "warning".yellow()),
+            Severity::Error => write!(f, "{}", "error".red()),
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Serialize)]
+struct Diagnostic {
+    file: String,
+    severity: Severity,
+    /// Stable identifier of the check that produced the diagnostic, so that CI gates can filter
+    /// on it.
+    code: &'static str,
+    message: String,
+}
+
+impl Diagnostic {
+    fn error(file: &Uri, code: &'static str, message: String) -> Self {
+        Self {
+            file: file.to_string(),
+            severity: Severity::Error,
+            code,
+            message,
+        }
+    }
+
+    fn warning(file: &Uri, code: &'static str, message: String) -> Self {
+        Self {
+            file: file.to_string(),
+            severity: Severity::Warning,
+            code,
+            message,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct LintedFile {
+    file: String,
+    kind: Option<ConfigKind>,
+}
+
+#[derive(Debug, Serialize)]
+struct LintReport {
+    files: Vec<LintedFile>,
+    diagnostics: Vec<Diagnostic>,
+    num_errors: usize,
+    num_warnings: usize,
+}
+
+/// A config file read from disk whose content is syntactically valid.
+struct RawConfig {
+    uri: Uri,
+    kind: ConfigKind,
+    format: ConfigFormat,
+    content: Vec<u8>,
+    value: JsonValue,
+}
+
+async fn lint_config_cli(args: LintConfigArgs) -> anyhow::Result<()> {
+    let storage_resolver = StorageResolver::unconfigured();
+    let mut files = Vec::with_capacity(args.config_uris.len());
+    let mut raw_configs = Vec::with_capacity(args.config_uris.len());
+    let mut diagnostics = Vec::new();
+
+    for config_uri in args.config_uris {
+        match read_raw_config(&storage_resolver, &config_uri).await {
+            Ok(raw_config) => {
+                files.push(LintedFile {
+                    file: config_uri.to_string(),
+                    kind: Some(raw_config.kind),
+                });
+                raw_configs.push(raw_config);
+            }
+            Err(error) => {
+                files.push(LintedFile {
+                    file: config_uri.to_string(),
+                    kind: None,
+                });
+                diagnostics.push(Diagnostic::error(
+                    &config_uri,
+                    "invalid-syntax",
+                    format!("{error:#}"),
+                ));
+            }
+        }
+    }
+    diagnostics.extend(lint_raw_configs(&raw_configs).await);
+
+    let report = build_lint_report(files, diagnostics);
+    let output = match args.output_format {
+        OutputFormat::Json => serde_json::to_string_pretty(&report)?,
+        OutputFormat::Text => format_lint_report(&report),
+    };
+    println!("{output}");
+
+    if report.num_errors > 0 {
+        bail!("config lint found {} error(s)", report.num_errors);
+    }
+    if args.deny_warnings && report.num_warnings > 0 {
+        bail!(
+            "config lint found {} warning(s) and `--deny-warnings` is set",
+            report.num_warnings
+        );
+    }
+    Ok(())
+}
+
+async fn read_raw_config(
+    storage_resolver: &StorageResolver,
+    config_uri: &Uri,
+) -> anyhow::Result<RawConfig> {
+    let format = ConfigFormat::sniff_from_uri(config_uri)?;
+    let content = load_file(storage_resolver, config_uri)
+        .await
+        .context("failed to load config file")?
+        .to_vec();
+    let value: JsonValue = format.parse(&content)?;
+    let kind = ConfigKind::infer(&value);
+    Ok(RawConfig {
+        uri: config_uri.clone(),
+        kind,
+        format,
+        content,
+        value,
+    })
+}
+
+/// Runs the per-file and cross-file checks over a set of syntactically valid configs.
+async fn lint_raw_configs(raw_configs: &[RawConfig]) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    for raw_config in raw_configs {
+        lint_deprecations(raw_config, &mut diagnostics);
+    }
+    // Node configs are loaded first so that index URIs can be resolved against the root URI of
+    // the cluster they are going to be created in.
+    let mut node_configs: Vec<(&Uri, NodeConfig)> = Vec::new();
+
+    for raw_config in raw_configs {
+        if raw_config.kind != ConfigKind::Node {
+            continue;
+        }
+        match NodeConfig::parse(raw_config.format, &raw_config.content).await {
+            Ok(node_config) => node_configs.push((&raw_config.uri, node_config)),
+            Err(error) => diagnostics.push(Diagnostic::error(
+                &raw_config.uri,
+                "invalid-config",
+                format!("{error:#}"),
+            )),
+        }
+    }
+    let default_index_root_uri = resolve_default_index_root_uri(&node_configs);
+
+    let mut index_configs: Vec<(&Uri, IndexConfig)> = Vec::new();
+    let mut source_configs: Vec<(&Uri, SourceConfig)> = Vec::new();
+
+    for raw_config in raw_configs {
+        match raw_config.kind {
+            ConfigKind::Node => {}
+            ConfigKind::Index => match load_index_config_from_user_config(
+                raw_config.format,
+                &raw_config.content,
+                &default_index_root_uri,
+            ) {
+                Ok(index_config) => index_configs.push((&raw_config.uri, index_config)),
+                Err(error) => diagnostics.push(Diagnostic::error(
+                    &raw_config.uri,
+                    "invalid-config",
+                    format!("{error:#}"),
+                )),
+            },
+            ConfigKind::Source => {
+                match load_source_config_from_user_config(raw_config.format, &raw_config.content) {
+                    Ok(source_config) => source_configs.push((&raw_config.uri, source_config)),
+                    Err(error) => diagnostics.push(Diagnostic::error(
+                        &raw_config.uri,
+                        "invalid-config",
+                        format!("{error:#}"),
+                    )),
+                }
+            }
+        }
+    }
+    for (index_config_uri, index_config) in &index_configs {
+        lint_index_config(index_config_uri, index_config, &mut diagnostics);
+    }
+    for (source_config_uri, source_config) in &source_configs {
+        lint_source_config(source_config_uri, source_config, &mut diagnostics);
+    }
+    lint_node_configs_consistency(&node_configs, &mut diagnostics);
+    lint_index_configs_consistency(&index_configs, &mut diagnostics);
+    lint_source_configs_consistency(&source_configs, &mut diagnostics);
+    diagnostics
+}
+
+/// Returns the default index root URI shared by all the linted node configs, if any.
+fn resolve_default_index_root_uri(node_configs: &[(&Uri, NodeConfig)]) -> Uri {
+    let mut root_uris = node_configs
+        .iter()
+        .map(|(_, node_config)| &node_config.default_index_root_uri);
+
+    if let Some(first_root_uri) = root_uris.next() {
+        if root_uris.all(|root_uri| root_uri == first_root_uri) {
+            return first_root_uri.clone();
+        }
+    }
+    Uri::from_str(LINT_DEFAULT_INDEX_ROOT_URI).expect("URI should be valid")
+}
+
+fn lint_deprecations(raw_config: &RawConfig, diagnostics: &mut Vec<Diagnostic>) {
+    // YAML and TOML configs may declare the version as a number.
+    let version_opt = raw_config
+        .value
+        .get("version")
+        .map(|version| match version {
+            JsonValue::String(version_str) => version_str.clone(),
+            _ => version.to_string(),
+        });
+    if let Some(version) = version_opt {
+        if DEPRECATED_CONFIG_VERSIONS.contains(&version.as_str()) {
+            diagnostics.push(Diagnostic::warning(
+                &raw_config.uri,
+                "deprecated-version",
+                format!("config version `{version}` is deprecated, use `0.6` instead"),
+            ));
+        }
+    }
+    let deprecated_fields: &[(&str, &str)] = match raw_config.kind {
+        ConfigKind::Node => &[("rest_listen_port", "rest.listen_port")],
+        ConfigKind::Index => &[],
+        ConfigKind::Source => &[("num_pipelines", "max_num_pipelines_per_indexer")],
+    };
+    for (deprecated_field, replacement_field) in deprecated_fields {
+        if raw_config.value.get(deprecated_field).is_some() {
+            diagnostics.push(Diagnostic::warning(
+                &raw_config.uri,
+                "deprecated-field",
+                format!("`{deprecated_field}` is deprecated, use `{replacement_field}` instead"),
+            ));
+        }
+    }
+}
+
+fn lint_index_config(
+    index_config_uri: &Uri,
+    index_config: &IndexConfig,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let commit_timeout = index_config.indexing_settings.commit_timeout();
+
+    if commit_timeout < EMIT_BATCHES_TIMEOUT {
+        diagnostics.push(Diagnostic::warning(
+            index_config_uri,
+            "commit-timeout-too-low",
+            format!(
+                "`indexing_settings.commit_timeout_secs` ({}s) is lower than the interval at \
+                 which sources emit batches ({}s): splits will not be committed faster than that",
+                commit_timeout.as_secs(),
+                EMIT_BATCHES_TIMEOUT.as_secs()
+            ),
+        ));
+    }
+}
+
+fn lint_source_config(
+    source_config_uri: &Uri,
+    source_config: &SourceConfig,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    if source_config.max_num_pipelines_per_indexer > source_config.desired_num_pipelines {
+        diagnostics.push(Diagnostic::warning(
+            source_config_uri,
+            "unreachable-setting",
+            format!(
+                "`max_num_pipelines_per_indexer` ({}) is greater than `desired_num_pipelines` \
+                 ({}) and will never be reached",
+                source_config.max_num_pipelines_per_indexer, source_config.desired_num_pipelines
+            ),
+        ));
+    }
+}
+
+fn lint_node_configs_consistency(
+    node_configs: &[(&Uri, NodeConfig)],
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let mut clusters: HashMap<&str, Vec<&(&Uri, NodeConfig)>> = HashMap::new();
+
+    for node_config_entry in node_configs {
+        clusters
+            .entry(node_config_entry.1.cluster_id.as_str())
+            .or_default()
+            .push(node_config_entry);
+    }
+    for (cluster_id, cluster_node_configs) in clusters {
+        let mut node_ids: HashMap<&str, &Uri> = HashMap::new();
+        let (first_node_config_uri, first_node_config) = cluster_node_configs[0];
+
+        for (node_config_uri, node_config) in cluster_node_configs {
+            if let Some(other_node_config_uri) =
+                node_ids.insert(node_config.node_id.as_str(), node_config_uri)
+            {
+                diagnostics.push(Diagnostic::error(
+                    node_config_uri,
+                    "duplicate-node-id",
+                    format!(
+                        "node ID `{}` of cluster `{cluster_id}` is also used in \
+                         `{other_node_config_uri}`",
+                        node_config.node_id
+                    ),
+                ));
+            }
+            if node_config.metastore_uri != first_node_config.metastore_uri {
+                diagnostics.push(Diagnostic::error(
+                    node_config_uri,
+                    "inconsistent-metastore-uri",
+                    format!(
+                        "metastore URI `{}` differs from `{}` used in `{first_node_config_uri}` \
+                         for cluster `{cluster_id}`",
+                        node_config.metastore_uri, first_node_config.metastore_uri
+                    ),
+                ));
+            }
+            if node_config.default_index_root_uri != first_node_config.default_index_root_uri {
+                diagnostics.push(Diagnostic::warning(
+                    node_config_uri,
+                    "inconsistent-index-root-uri",
+                    format!(
+                        "default index root URI `{}` differs from `{}` used in \
+                         `{first_node_config_uri}` for cluster `{cluster_id}`",
+                        node_config.default_index_root_uri,
+                        first_node_config.default_index_root_uri
+                    ),
+                ));
+            }
+        }
+    }
+}
+
+fn lint_index_configs_consistency(
+    index_configs: &[(&Uri, IndexConfig)],
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let mut index_ids: HashMap<&str, &Uri> = HashMap::new();
+    let mut index_uris: HashMap<&Uri, &str> = HashMap::new();
+
+    for (index_config_uri, index_config) in index_configs {
+        if let Some(other_index_config_uri) =
+            index_ids.insert(index_config.index_id.as_str(), index_config_uri)
+        {
+            diagnostics.push(Diagnostic::error(
+                index_config_uri,
+                "duplicate-index-id",
+                format!(
+                    "index ID `{}` is also used in `{other_index_config_uri}`",
+                    index_config.index_id
+                ),
+            ));
+        }
+        if let Some(other_index_id) =
+            index_uris.insert(&index_config.index_uri, index_config.index_id.as_str())
+        {
+            if other_index_id != index_config.index_id {
+                diagnostics.push(Diagnostic::error(
+                    index_config_uri,
+                    "duplicate-index-uri",
+                    format!(
+                        "index URI `{}` is shared with index `{other_index_id}`",
+                        index_config.index_uri
+                    ),
+                ));
+            }
+        }
+    }
+}
+
+fn lint_source_configs_consistency(
+    source_configs: &[(&Uri, SourceConfig)],
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let mut source_ids: HashMap<&str, &Uri> = HashMap::new();
+
+    for (source_config_uri, source_config) in source_configs {
+        if let Some(other_source_config_uri) =
+            source_ids.insert(source_config.source_id.as_str(), source_config_uri)
+        {
+            // Source IDs are scoped to an index, which source configs do not reference, so this
+            // is only an error if both files target the same index.
+            diagnostics.push(Diagnostic::warning(
+                source_config_uri,
+                "duplicate-source-id",
+                format!(
+                    "source ID `{}` is also used in `{other_source_config_uri}`",
+                    source_config.source_id
+                ),
+            ));
+        }
+    }
+}
+
+fn build_lint_report(files: Vec<LintedFile>, mut diagnostics: Vec<Diagnostic>) -> LintReport {
+    // Sorting is stable, so diagnostics keep the order in which they were found within a file.
+    diagnostics.sort_by(|left, right| left.file.cmp(&right.file));
+
+    let num_errors = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.severity == Severity::Error)
+        .count();
+    let num_warnings = diagnostics.len() - num_errors;
+    LintReport {
+        files,
+        diagnostics,
+        num_errors,
+        num_warnings,
+    }
+}
+
+fn format_lint_report(report: &LintReport) -> String {
+    let mut output = String::new();
+
+    for diagnostic in &report.diagnostics {
+        output.push_str(&format!(
+            "{}: {}[{}]: {}\n",
+            diagnostic.file, diagnostic.severity, diagnostic.code, diagnostic.message
+        ));
+    }
+    output.push_str(&format!(
+        "Linted {} file(s): {} error(s), {} warning(s).",
+        report.files.len(),
+        report.num_errors,
+        report.num_warnings
+    ));
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raw_config(uri: &str, content: &str) -> RawConfig {
+        let uri = Uri::from_str(uri).unwrap();
+        let format = ConfigFormat::sniff_from_uri(&uri).unwrap();
+        let value: JsonValue = format.parse(content.as_bytes()).unwrap();
+        let kind = ConfigKind::infer(&value);
+        RawConfig {
+            uri,
+            kind,
+            format,
+            content: content.as_bytes().to_vec(),
+            value,
+        }
+    }
+
+    fn diagnostic_codes(diagnostics: &[Diagnostic]) -> Vec<(&str, &'static str)> {
+        diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.file.as_str(), diagnostic.code))
+            .collect()
+    }
+
+    #[test]
+    fn test_config_kind_infer() {
+        let index_config = raw_config("file:///index.yaml", "version: 0.6\nindex_id: foo\n");
+        assert_eq!(index_config.kind, ConfigKind::Index);
+
+        let source_config = raw_config("file:///source.yaml", "version: 0.6\nsource_id: foo\n");
+        assert_eq!(source_config.kind, ConfigKind::Source);
+
+        let node_config = raw_config("file:///node.yaml", "version: 0.6\nnode_id: foo\n");
+        assert_eq!(node_config.kind, ConfigKind::Node);
+    }
+
+    #[tokio::test]
+    async fn test_lint_valid_configs() {
+        let raw_configs = [
+            raw_config(
+                "file:///node.yaml",
+                "version: 0.6\nnode_id: node-1\nmetastore_uri: ram:///metastore\n",
+            ),
+            raw_config(
+                "file:///index.yaml",
+                "version: 0.6\nindex_id: hdfs-logs\ndoc_mapping:\n  field_mappings: []\n",
+            ),
+            raw_config(
+                "file:///source.yaml",
+                "version: 0.6\nsource_id: my-source\nsource_type: void\nparams: {}\n",
+            ),
+        ];
+        let diagnostics = lint_raw_configs(&raw_configs).await;
+        assert!(diagnostics.is_empty(), "{diagnostics:?}");
+    }
+
+    #[tokio::test]
+    async fn test_lint_invalid_and_deprecated_configs() {
+        let raw_configs = [
+            raw_config(
+                "file:///index.yaml",
+                "version: 0.5\nindex_id: hdfs-logs\ndoc_mapping:\n  field_mappings: \
+                 []\nindexing_settings:\n  commit_timeout_secs: 0\n",
+            ),
+            raw_config(
+                "file:///source.yaml",
+                "version: 0.6\nsource_id: my-source\nsource_type: kafka\nparams:\n  topic: \
+                 my-topic\nnum_pipelines: 2\n",
+            ),
+            raw_config(
+                "file:///invalid-index.yaml",
+                "version: 0.6\nindex_id: hdfs-logs\ndoc_mapping:\n  field_mappings: \
+                 []\nindexing_settings:\n  commit_timeout_secs: soon\n",
+            ),
+        ];
+        let diagnostics = lint_raw_configs(&raw_configs).await;
+        assert_eq!(
+            diagnostic_codes(&diagnostics),
+            [
+                ("file:///index.yaml", "deprecated-version"),
+                ("file:///source.yaml", "deprecated-field"),
+                ("file:///invalid-index.yaml", "invalid-config"),
+                ("file:///index.yaml", "commit-timeout-too-low"),
+                ("file:///source.yaml", "unreachable-setting"),
+            ]
+        );
+        assert_eq!(diagnostics[2].severity, Severity::Error);
+    }
+
+    #[tokio::test]
+    async fn test_lint_cross_file_inconsistencies() {
+        let raw_configs = [
+            raw_config(
+                "file:///node-1.yaml",
+                "version: 0.6\nnode_id: node\nmetastore_uri: \
+                 ram:///metastore\ndefault_index_root_uri: ram:///indexes\n",
+            ),
+            raw_config(
+                "file:///node-2.yaml",
+                "version: 0.6\nnode_id: node\nmetastore_uri: \
+                 ram:///other-metastore\ndefault_index_root_uri: ram:///indexes\n",
+            ),
+            raw_config(
+                "file:///index-1.yaml",
+                "version: 0.6\nindex_id: hdfs-logs\ndoc_mapping:\n  field_mappings: []\n",
+            ),
+            raw_config(
+                "file:///index-2.yaml",
+                "version: 0.6\nindex_id: hdfs-logs\ndoc_mapping:\n  field_mappings: []\n",
+            ),
+            raw_config(
+                "file:///index-3.yaml",
+                "version: 0.6\nindex_id: other-logs\nindex_uri: \
+                 ram:///indexes/hdfs-logs\ndoc_mapping:\n  field_mappings: []\n",
+            ),
+        ];
+        let diagnostics = lint_raw_configs(&raw_configs).await;
+        assert_eq!(
+            diagnostic_codes(&diagnostics),
+            [
+                ("file:///node-2.yaml", "duplicate-node-id"),
+                ("file:///node-2.yaml", "inconsistent-metastore-uri"),
+                ("file:///index-2.yaml", "duplicate-index-id"),
+                ("file:///index-3.yaml", "duplicate-index-uri"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_lint_report() {
+        let file_uri = Uri::from_str("file:///index.yaml").unwrap();
+        let files = vec![LintedFile {
+            file: file_uri.to_string(),
+            kind: Some(ConfigKind::Index),
+        }];
+        let diagnostics = vec![
+            Diagnostic::warning(&file_uri, "deprecated-version", "foo".to_string()),
+            Diagnostic::error(&file_uri, "invalid-config", "bar".to_string()),
+        ];
+        let report = build_lint_report(files, diagnostics);
+        assert_eq!(report.num_errors, 1);
+        assert_eq!(report.num_warnings, 1);
+
+        let report_json = serde_json::to_value(&report).unwrap();
+        assert_eq!(report_json["files"][0]["kind"], "index");
+        assert_eq!(report_json["diagnostics"][1]["severity"], "error");
+        assert_eq!(report_json["diagnostics"][1]["code"], "invalid-config");
+    }
+}
@@ -18,12 +18,14 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 use std::borrow::Cow;
-use std::collections::VecDeque;
+use std::collections::{HashSet, VecDeque};
 use std::fmt::Display;
-use std::io::{stdout, Stdout, Write};
+use std::fs::File;
+use std::io::{stdin, stdout, BufRead, BufReader, Stdout, Write};
 use std::ops::Div;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{Duration, Instant};
 use std::{fmt, io};
 
@@ -37,24 +39,30 @@ use itertools::Itertools;
 use numfmt::{Formatter, Scales};
 use quickwit_actors::ActorHandle;
 use quickwit_common::uri::Uri;
-use quickwit_config::{ConfigFormat, IndexConfig};
+use quickwit_config::{
+    build_doc_mapper, load_index_config_from_user_config, ConfigFormat, IndexConfig,
+};
+use quickwit_datetime::{parse_date_time_str, parse_timestamp, DateTimeInputFormat};
+use quickwit_doc_mapper::{DocMapper, DocParsingError};
 use quickwit_indexing::models::IndexingStatistics;
 use quickwit_indexing::IndexingPipeline;
 use quickwit_metastore::{IndexMetadata, Split, SplitState};
 use quickwit_proto::search::{CountHits, SortField, SortOrder};
 use quickwit_rest_client::models::IngestSource;
 use quickwit_rest_client::rest_client::{CommitType, IngestEvent};
-use quickwit_search::SearchResponseRest;
+use quickwit_search::{SearchCostEstimate, SearchResponseRest};
 use quickwit_serve::{ListSplitsQueryParams, SearchRequestQueryString, SortBy};
 use quickwit_storage::{load_file, StorageResolver};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
 use tabled::settings::object::{FirstRow, Rows, Segment};
 use tabled::settings::panel::Footer;
 use tabled::settings::{Alignment, Disable, Format, Modify, Panel, Rotate, Style};
 use tabled::{Table, Tabled};
 use thousands::Separable;
-use tracing::{debug, Level};
+use tracing::{debug, warn, Level};
 
-use crate::checklist::GREEN_COLOR;
+use crate::checklist::{GREEN_COLOR, RED_COLOR};
 use crate::stats::{mean, percentile, std_deviation};
 use crate::{client_args, make_table, prompt_confirmation, ClientArgs, THROUGHPUT_WINDOW_SIZE};
 
@@ -151,6 +159,11 @@ pub fn build_index_command() -> Command {
                         .help("Duration of the commit timeout operation.")
                         .required(false)
                         .global(true),
+                    Arg::new("resume")
+                        .long("resume")
+                        .help("Resume a failed ingestion of the input file from its last checkpoint instead of starting over.")
+                        .requires("input-path")
+                        .action(ArgAction::SetTrue),
                 ])
             )
         .subcommand(
@@ -184,6 +197,95 @@ pub fn build_index_command() -> Command {
                         .required(false),
                     arg!(--"sort-by-score" "Sorts documents by their BM25 score.")
                         .required(false),
+                    arg!(--estimate "Estimates the cost of the query (splits to search, bytes to download, and latency band) instead of executing it.")
+                        .required(false),
+                    arg!(--follow "Prints the most recent hits, then repeats the search periodically and prints the new hits as they are indexed, like `tail -f` (time-series indexes only).")
+                        .conflicts_with_all(["aggregation", "end-timestamp", "estimate", "sort-by-score", "start-offset"])
+                        .required(false),
+                ])
+            )
+        .subcommand(
+            Command::new("pause-merges")
+                .display_order(8)
+                .about("Pauses the merges of an index.")
+                .long_about("Pauses the merges of an index on every indexer. The pause is recorded in the index metadata and survives restarts. Ongoing merges run to completion and the splits published in the meantime are merged once merges are resumed.")
+                .args(&[
+                    arg!(--index <INDEX> "ID of the target index")
+                        .display_order(1)
+                        .required(true),
+                ])
+            )
+        .subcommand(
+            Command::new("resume-merges")
+                .display_order(9)
+                .about("Resumes the merges of an index.")
+                .args(&[
+                    arg!(--index <INDEX> "ID of the target index")
+                        .display_order(1)
+                        .required(true),
+                ])
+            )
+        .subcommand(
+            Command::new("validate-docs")
+                .display_order(10)
+                .about("Checks NDJSON documents against the doc mapping of an index without indexing them.")
+                .long_about("Reads NDJSON documents from a file or streamed from stdin, runs each document through the doc mapper of an index without indexing it, and reports the documents that would be rejected (unknown fields in strict mode, invalid timestamps, type conflicts, etc.). The doc mapping is fetched from the cluster or read from a local index config file.")
+                .args(&[
+                    arg!(--index <INDEX> "ID of the target index")
+                        .display_order(1)
+                        .required_unless_present("index-config")
+                        .conflicts_with("index-config"),
+                    arg!(--"index-config" <INDEX_CONFIG> "Location of a local index config file to validate the documents against instead of the config of an existing index.")
+                        .required(false),
+                    arg!(--"input-path" <INPUT_PATH> "Location of the input file. Defaults to stdin.")
+                        .required(false),
+                    arg!(--"max-errors" <MAX_ERRORS> "Maximum number of document errors printed.")
+                        .default_value("100")
+                        .required(false),
+                ])
+            )
+        .subcommand(
+            Command::new("snapshot")
+                .display_order(11)
+                .about("Takes a snapshot of an index.")
+                .long_about("Takes a consistent snapshot of the config, sources, checkpoints, and published splits of an index and writes it to a local or remote directory. The snapshot does not depend on the metastore backend and can be restored into another cluster with `quickwit index restore`.")
+                .args(&[
+                    arg!(--index <INDEX> "ID of the target index")
+                        .display_order(1)
+                        .required(true),
+                    arg!(--"output-uri" <OUTPUT_URI> "URI of the directory the snapshot is written to, e.g. s3://backup/my-index.")
+                        .display_order(2)
+                        .required(true),
+                    arg!(--"copy-split-files" "Copies the split files into the snapshot. Otherwise, the snapshot references the split files of the index.")
+                        .required(false),
+                ])
+            )
+        .subcommand(
+            Command::new("restore")
+                .display_order(12)
+                .about("Restores an index from a snapshot.")
+                .long_about("Restores an index from a snapshot taken with `quickwit index snapshot`. The index must not exist. The checkpoints of the ingest API sources are not restored.")
+                .args(&[
+                    arg!(--"snapshot-uri" <SNAPSHOT_URI> "URI of the directory of the snapshot.")
+                        .display_order(1)
+                        .required(true),
+                    arg!(--"index-uri" <INDEX_URI> "URI of the restored index. Defaults to the URI of the snapshotted index.")
+                        .display_order(2)
+                        .required(false),
+                ])
+            )
+        .subcommand(
+            Command::new("clone")
+                .display_order(13)
+                .about("Clones an index without copying its data.")
+                .long_about("Creates a new index with the config of an index and publishes its splits in the new index. The split files are shared by both indexes and not copied: they are only deleted once no index references them anymore. The sources of the index are not cloned.")
+                .args(&[
+                    arg!(--index <INDEX> "ID of the target index")
+                        .display_order(1)
+                        .required(true),
+                    arg!(--"clone-index" <CLONE_INDEX> "ID of the clone.")
+                        .display_order(2)
+                        .required(true),
                 ])
             )
         .arg_required_else_help(true)
@@ -217,6 +319,7 @@ pub struct IngestDocsArgs {
     pub input_path_opt: Option<PathBuf>,
     pub batch_size_limit_opt: Option<ByteSize>,
     pub commit_type: CommitType,
+    pub resume: bool,
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -232,6 +335,8 @@ pub struct SearchIndexArgs {
     pub start_timestamp: Option<i64>,
     pub end_timestamp: Option<i64>,
     pub sort_by_score: bool,
+    pub estimate: bool,
+    pub follow: bool,
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -247,15 +352,58 @@ pub struct ListIndexesArgs {
     pub client_args: ClientArgs,
 }
 
+#[derive(Debug, Eq, PartialEq)]
+pub struct IndexMergesArgs {
+    pub client_args: ClientArgs,
+    pub index_id: String,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct ValidateDocsArgs {
+    pub client_args: ClientArgs,
+    pub index_id_opt: Option<String>,
+    pub index_config_uri_opt: Option<Uri>,
+    pub input_path_opt: Option<PathBuf>,
+    pub max_errors: usize,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct SnapshotIndexArgs {
+    pub client_args: ClientArgs,
+    pub index_id: String,
+    pub output_uri: Uri,
+    pub copy_split_files: bool,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct RestoreIndexArgs {
+    pub client_args: ClientArgs,
+    pub snapshot_uri: Uri,
+    pub index_uri_opt: Option<Uri>,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct CloneIndexArgs {
+    pub client_args: ClientArgs,
+    pub index_id: String,
+    pub clone_index_id: String,
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub enum IndexCliCommand {
     Clear(ClearIndexArgs),
+    Clone(CloneIndexArgs),
     Create(CreateIndexArgs),
     Delete(DeleteIndexArgs),
     Describe(DescribeIndexArgs),
     Ingest(IngestDocsArgs),
     List(ListIndexesArgs),
+    PauseMerges(IndexMergesArgs),
+    Restore(RestoreIndexArgs),
+    ResumeMerges(IndexMergesArgs),
     Search(SearchIndexArgs),
+    Snapshot(SnapshotIndexArgs),
+    ValidateDocs(ValidateDocsArgs),
 }
 
 impl IndexCliCommand {
@@ -272,12 +420,18 @@ impl IndexCliCommand {
             .context("failed to parse index subcommand")?;
         match subcommand.as_str() {
             "clear" => Self::parse_clear_args(submatches),
+            "clone" => Self::parse_clone_args(submatches),
             "create" => Self::parse_create_args(submatches),
             "delete" => Self::parse_delete_args(submatches),
             "describe" => Self::parse_describe_args(submatches),
             "ingest" => Self::parse_ingest_args(submatches),
             "list" => Self::parse_list_args(submatches),
+            "pause-merges" => Self::parse_merges_args(submatches).map(Self::PauseMerges),
+            "restore" => Self::parse_restore_args(submatches),
+            "resume-merges" => Self::parse_merges_args(submatches).map(Self::ResumeMerges),
             "search" => Self::parse_search_args(submatches),
+            "snapshot" => Self::parse_snapshot_args(submatches),
+            "validate-docs" => Self::parse_validate_docs_args(submatches),
             _ => bail!("unknown index subcommand `{subcommand}`"),
         }
     }
@@ -323,6 +477,67 @@ impl IndexCliCommand {
         }))
     }
 
+    fn parse_merges_args(mut matches: ArgMatches) -> anyhow::Result<IndexMergesArgs> {
+        let client_args = ClientArgs::parse(&mut matches)?;
+        let index_id = matches
+            .remove_one::<String>("index")
+            .expect("`index` should be a required arg.");
+        Ok(IndexMergesArgs {
+            client_args,
+            index_id,
+        })
+    }
+
+    fn parse_snapshot_args(mut matches: ArgMatches) -> anyhow::Result<Self> {
+        let client_args = ClientArgs::parse(&mut matches)?;
+        let index_id = matches
+            .remove_one::<String>("index")
+            .expect("`index` should be a required arg.");
+        let output_uri = matches
+            .remove_one::<String>("output-uri")
+            .map(|uri| Uri::from_str(&uri))
+            .expect("`output-uri` should be a required arg.")?;
+        let copy_split_files = matches.get_flag("copy-split-files");
+        Ok(Self::Snapshot(SnapshotIndexArgs {
+            client_args,
+            index_id,
+            output_uri,
+            copy_split_files,
+        }))
+    }
+
+    fn parse_restore_args(mut matches: ArgMatches) -> anyhow::Result<Self> {
+        let client_args = ClientArgs::parse(&mut matches)?;
+        let snapshot_uri = matches
+            .remove_one::<String>("snapshot-uri")
+            .map(|uri| Uri::from_str(&uri))
+            .expect("`snapshot-uri` should be a required arg.")?;
+        let index_uri_opt = matches
+            .remove_one::<String>("index-uri")
+            .map(|uri| Uri::from_str(&uri))
+            .transpose()?;
+        Ok(Self::Restore(RestoreIndexArgs {
+            client_args,
+            snapshot_uri,
+            index_uri_opt,
+        }))
+    }
+
+    fn parse_clone_args(mut matches: ArgMatches) -> anyhow::Result<Self> {
+        let client_args = ClientArgs::parse(&mut matches)?;
+        let index_id = matches
+            .remove_one::<String>("index")
+            .expect("`index` should be a required arg.");
+        let clone_index_id = matches
+            .remove_one::<String>("clone-index")
+            .expect("`clone-index` should be a required arg.");
+        Ok(Self::Clone(CloneIndexArgs {
+            client_args,
+            index_id,
+            clone_index_id,
+        }))
+    }
+
     fn parse_list_args(mut matches: ArgMatches) -> anyhow::Result<Self> {
         let client_args = ClientArgs::parse(&mut matches)?;
         Ok(Self::List(ListIndexesArgs { client_args }))
@@ -356,6 +571,7 @@ impl IndexCliCommand {
         if commit_type == CommitType::Auto && client_args.commit_timeout.is_some() {
             bail!("`--commit-timeout` can only be used with --wait or --force options");
         }
+        let resume = matches.get_flag("resume");
 
         Ok(Self::Ingest(IngestDocsArgs {
             client_args,
@@ -363,6 +579,7 @@ impl IndexCliCommand {
             input_path_opt,
             batch_size_limit_opt,
             commit_type,
+            resume,
         }))
     }
 
@@ -390,6 +607,8 @@ impl IndexCliCommand {
             .remove_many::<String>("snippet-fields")
             .map(|values| values.collect());
         let sort_by_score = matches.get_flag("sort-by-score");
+        let estimate = matches.get_flag("estimate");
+        let follow = matches.get_flag("follow");
         let start_timestamp = matches
             .remove_one::<String>("start-timestamp")
             .map(|ts| ts.parse())
@@ -411,6 +630,35 @@ impl IndexCliCommand {
             end_timestamp,
             client_args,
             sort_by_score,
+            estimate,
+            follow,
+        }))
+    }
+
+    fn parse_validate_docs_args(mut matches: ArgMatches) -> anyhow::Result<Self> {
+        let client_args = ClientArgs::parse(&mut matches)?;
+        let index_id_opt = matches.remove_one::<String>("index");
+        let index_config_uri_opt = matches
+            .remove_one::<String>("index-config")
+            .map(|uri| Uri::from_str(&uri))
+            .transpose()?;
+        let input_path_opt = if let Some(input_path) = matches.remove_one::<String>("input-path") {
+            Uri::from_str(&input_path)?
+                .filepath()
+                .map(|path| path.to_path_buf())
+        } else {
+            None
+        };
+        let max_errors = matches
+            .remove_one::<String>("max-errors")
+            .expect("`max-errors` should have a default value.")
+            .parse()?;
+        Ok(Self::ValidateDocs(ValidateDocsArgs {
+            client_args,
+            index_id_opt,
+            index_config_uri_opt,
+            input_path_opt,
+            max_errors,
         }))
     }
 
@@ -432,12 +680,18 @@ impl IndexCliCommand {
     pub async fn execute(self) -> anyhow::Result<()> {
         match self {
             Self::Clear(args) => clear_index_cli(args).await,
+            Self::Clone(args) => clone_index_cli(args).await,
             Self::Create(args) => create_index_cli(args).await,
             Self::Delete(args) => delete_index_cli(args).await,
             Self::Describe(args) => describe_index_cli(args).await,
             Self::Ingest(args) => ingest_docs_cli(args).await,
             Self::List(args) => list_index_cli(args).await,
+            Self::PauseMerges(args) => pause_merges_cli(args).await,
+            Self::Restore(args) => restore_index_cli(args).await,
+            Self::ResumeMerges(args) => resume_merges_cli(args).await,
             Self::Search(args) => search_index_cli(args).await,
+            Self::Snapshot(args) => snapshot_index_cli(args).await,
+            Self::ValidateDocs(args) => validate_docs_cli(args).await,
         }
     }
 }
@@ -460,6 +714,86 @@ pub async fn clear_index_cli(args: ClearIndexArgs) -> anyhow::Result<()> {
     Ok(())
 }
 
+pub async fn pause_merges_cli(args: IndexMergesArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "pause-merges");
+    let qw_client = args.client_args.client();
+    let merges_status = qw_client.indexes().pause_merges(&args.index_id).await?;
+    println!(
+        "{} Merges of index `{}` paused ({} merge pipeline(s) on this node).",
+        "✔".color(GREEN_COLOR),
+        merges_status.index_id,
+        merges_status.num_merge_pipelines
+    );
+    Ok(())
+}
+
+pub async fn resume_merges_cli(args: IndexMergesArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "resume-merges");
+    let qw_client = args.client_args.client();
+    let merges_status = qw_client.indexes().resume_merges(&args.index_id).await?;
+    println!(
+        "{} Merges of index `{}` resumed ({} merge pipeline(s) on this node).",
+        "✔".color(GREEN_COLOR),
+        merges_status.index_id,
+        merges_status.num_merge_pipelines
+    );
+    Ok(())
+}
+
+pub async fn snapshot_index_cli(args: SnapshotIndexArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "snapshot-index");
+    println!("❯ Taking snapshot of index...");
+    let qw_client = args.client_args.client();
+    let snapshot_summary = qw_client
+        .indexes()
+        .snapshot(&args.index_id, &args.output_uri, args.copy_split_files)
+        .await?;
+    println!(
+        "{} Snapshot of index `{}` written to `{}` ({} split(s), {} document(s), {} copied).",
+        "✔".color(GREEN_COLOR),
+        snapshot_summary.index_id,
+        snapshot_summary.snapshot_uri,
+        snapshot_summary.num_splits,
+        snapshot_summary.num_docs,
+        ByteSize(snapshot_summary.num_copied_bytes)
+    );
+    Ok(())
+}
+
+pub async fn restore_index_cli(args: RestoreIndexArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "restore-index");
+    println!("❯ Restoring index...");
+    let qw_client = args.client_args.client();
+    let index_metadata = qw_client
+        .indexes()
+        .restore(&args.snapshot_uri, args.index_uri_opt.as_ref())
+        .await?;
+    println!(
+        "{} Index `{}` successfully restored to `{}`.",
+        "✔".color(GREEN_COLOR),
+        index_metadata.index_id(),
+        index_metadata.index_uri()
+    );
+    Ok(())
+}
+
+pub async fn clone_index_cli(args: CloneIndexArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "clone-index");
+    println!("❯ Cloning index...");
+    let qw_client = args.client_args.client();
+    let index_metadata = qw_client
+        .indexes()
+        .clone_index(&args.index_id, &args.clone_index_id)
+        .await?;
+    println!(
+        "{} Index `{}` successfully cloned into `{}`.",
+        "✔".color(GREEN_COLOR),
+        args.index_id,
+        index_metadata.index_id()
+    );
+    Ok(())
+}
+
 pub async fn create_index_cli(args: CreateIndexArgs) -> anyhow::Result<()> {
     debug!(args=?args, "create-index");
     println!("❯ Creating index...");
@@ -921,6 +1255,51 @@ impl Tabled for Quantiles {
     }
 }
 
+/// Client-side checkpoint of an ingestion from a file, saved next to the input file so that a
+/// failed ingestion can be resumed with `--resume`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct IngestCheckpoint {
+    index_id: String,
+    /// Offset in the input file up to which documents have been ingested.
+    offset: u64,
+    /// Number of document batches acknowledged by the cluster.
+    num_acked_batches: u64,
+}
+
+impl IngestCheckpoint {
+    fn path(input_path: &Path) -> PathBuf {
+        let mut checkpoint_path = input_path.as_os_str().to_owned();
+        checkpoint_path.push(".ingest-checkpoint");
+        PathBuf::from(checkpoint_path)
+    }
+
+    fn load(checkpoint_path: &Path) -> anyhow::Result<Option<Self>> {
+        let checkpoint_json = match std::fs::read(checkpoint_path) {
+            Ok(checkpoint_json) => checkpoint_json,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error.into()),
+        };
+        let checkpoint = serde_json::from_slice(&checkpoint_json).with_context(|| {
+            format!(
+                "failed to parse ingest checkpoint file `{}`",
+                checkpoint_path.display()
+            )
+        })?;
+        Ok(Some(checkpoint))
+    }
+
+    /// Writes the checkpoint to a temporary file first, then renames it, so that a crash never
+    /// leaves a truncated checkpoint file behind.
+    fn save(&self, checkpoint_path: &Path) -> anyhow::Result<()> {
+        let checkpoint_json = serde_json::to_vec(self)?;
+        let mut temp_checkpoint_path = checkpoint_path.as_os_str().to_owned();
+        temp_checkpoint_path.push(".tmp");
+        std::fs::write(&temp_checkpoint_path, checkpoint_json)?;
+        std::fs::rename(&temp_checkpoint_path, checkpoint_path)?;
+        Ok(())
+    }
+}
+
 pub async fn ingest_docs_cli(args: IngestDocsArgs) -> anyhow::Result<()> {
     debug!(args=?args, "ingest-docs");
     if let Some(input_path) = &args.input_path_opt {
@@ -928,35 +1307,95 @@ pub async fn ingest_docs_cli(args: IngestDocsArgs) -> anyhow::Result<()> {
     } else {
         println!("❯ Ingesting documents from stdin.");
     }
+    let checkpoint_path_opt = args.input_path_opt.as_deref().map(IngestCheckpoint::path);
+    let initial_checkpoint = match &checkpoint_path_opt {
+        Some(checkpoint_path) if args.resume => {
+            let checkpoint = IngestCheckpoint::load(checkpoint_path)?.with_context(|| {
+                format!(
+                    "failed to resume ingestion: checkpoint file `{}` does not exist",
+                    checkpoint_path.display()
+                )
+            })?;
+            if checkpoint.index_id != args.index_id {
+                bail!(
+                    "failed to resume ingestion: checkpoint file `{}` belongs to index `{}`",
+                    checkpoint_path.display(),
+                    checkpoint.index_id
+                );
+            }
+            println!(
+                "❯ Resuming ingestion from byte offset {} ({} batches already ingested).",
+                checkpoint.offset, checkpoint.num_acked_batches
+            );
+            checkpoint
+        }
+        _ => IngestCheckpoint {
+            index_id: args.index_id.clone(),
+            ..Default::default()
+        },
+    };
     let progress_bar = match &args.input_path_opt {
         Some(filepath) => {
             let file_len = std::fs::metadata(filepath).context("file not found")?.len();
+            if initial_checkpoint.offset > file_len {
+                bail!(
+                    "failed to resume ingestion: checkpoint offset {} exceeds the length of the \
+                     input file ({file_len} bytes)",
+                    initial_checkpoint.offset
+                );
+            }
             ProgressBar::new(file_len)
         }
         None => ProgressBar::new_spinner(),
     };
+    progress_bar.set_position(initial_checkpoint.offset);
     progress_bar.enable_steady_tick(Duration::from_millis(100));
     progress_bar.set_style(progress_bar_style());
     progress_bar.set_message("0MiB/s");
+
+    let num_acked_batches = AtomicU64::new(initial_checkpoint.num_acked_batches);
+    let last_checkpoint_offset = AtomicU64::new(initial_checkpoint.offset);
     let update_progress_bar = |ingest_event: IngestEvent| {
         match ingest_event {
-            IngestEvent::IngestedDocBatch(num_bytes) => progress_bar.inc(num_bytes as u64),
+            IngestEvent::IngestedDocBatch(num_bytes) => {
+                num_acked_batches.fetch_add(1, Ordering::Relaxed);
+                progress_bar.inc(num_bytes as u64)
+            }
+            IngestEvent::Checkpoint(offset) => {
+                last_checkpoint_offset.store(offset, Ordering::Relaxed);
+
+                if let Some(checkpoint_path) = &checkpoint_path_opt {
+                    let checkpoint = IngestCheckpoint {
+                        index_id: args.index_id.clone(),
+                        offset,
+                        num_acked_batches: num_acked_batches.load(Ordering::Relaxed),
+                    };
+                    if let Err(error) = checkpoint.save(checkpoint_path) {
+                        warn!(error=?error, "failed to save ingest checkpoint");
+                    }
+                }
+            }
             IngestEvent::Sleep => {} // To
         };
-        let throughput =
-            progress_bar.position() as f64 / progress_bar.elapsed().as_secs_f64() / 1024.0 / 1024.0;
+        let throughput = (progress_bar.position() - initial_checkpoint.offset) as f64
+            / progress_bar.elapsed().as_secs_f64()
+            / 1024.0
+            / 1024.0;
         progress_bar.set_message(format!("{throughput:.1} MiB/s"));
     };
 
     let qw_client = args.client_args.client();
-    let ingest_source = match args.input_path_opt {
-        Some(filepath) => IngestSource::File(filepath),
+    let ingest_source = match &args.input_path_opt {
+        Some(filepath) if initial_checkpoint.offset > 0 => {
+            IngestSource::FileAtOffset(filepath.clone(), initial_checkpoint.offset)
+        }
+        Some(filepath) => IngestSource::File(filepath.clone()),
         None => IngestSource::Stdin,
     };
     let batch_size_limit_opt = args
         .batch_size_limit_opt
         .map(|batch_size_limit| batch_size_limit.as_u64() as usize);
-    qw_client
+    let ingest_result = qw_client
         .ingest(
             &args.index_id,
             ingest_source,
@@ -964,8 +1403,28 @@ pub async fn ingest_docs_cli(args: IngestDocsArgs) -> anyhow::Result<()> {
             Some(&update_progress_bar),
             args.commit_type,
         )
-        .await?;
+        .await;
+    if let Err(error) = ingest_result {
+        progress_bar.abandon();
+
+        if checkpoint_path_opt.is_some() {
+            println!(
+                "Ingestion failed after byte offset {}. Run the same command with `--resume` to \
+                 continue from there.",
+                last_checkpoint_offset.load(Ordering::Relaxed)
+            );
+        }
+        return Err(error.into());
+    }
     progress_bar.finish();
+
+    if let Some(checkpoint_path) = &checkpoint_path_opt {
+        if let Err(error) = std::fs::remove_file(checkpoint_path) {
+            if error.kind() != io::ErrorKind::NotFound {
+                warn!(error=?error, "failed to remove ingest checkpoint");
+            }
+        }
+    }
     println!(
         "Ingested {} documents successfully.",
         "✔".color(GREEN_COLOR)
@@ -981,9 +1440,10 @@ fn progress_bar_style() -> ProgressStyle {
     .tick_strings(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"])
 }
 
-pub async fn search_index(args: SearchIndexArgs) -> anyhow::Result<SearchResponseRest> {
+fn search_request_from_args(args: &SearchIndexArgs) -> anyhow::Result<SearchRequestQueryString> {
     let aggs: Option<serde_json::Value> = args
         .aggregation
+        .as_ref()
         .map(|aggs_string| {
             serde_json::from_str(&aggs_string).context("failed to deserialize aggregations")
         })
@@ -999,7 +1459,7 @@ pub async fn search_index(args: SearchIndexArgs) -> anyhow::Result<SearchRespons
         })
         .unwrap_or_default();
     let search_request = SearchRequestQueryString {
-        query: args.query,
+        query: args.query.clone(),
         aggs,
         search_fields: args.search_fields.clone(),
         snippet_fields: args.snippet_fields.clone(),
@@ -1011,19 +1471,279 @@ pub async fn search_index(args: SearchIndexArgs) -> anyhow::Result<SearchRespons
         count_all: CountHits::CountAll,
         ..Default::default()
     };
+    Ok(search_request)
+}
+
+pub async fn search_index(args: SearchIndexArgs) -> anyhow::Result<SearchResponseRest> {
+    let search_request = search_request_from_args(&args)?;
     let qw_client = args.client_args.client();
     let search_response = qw_client.search(&args.index_id, search_request).await?;
     Ok(search_response)
 }
 
+pub async fn estimate_search_cost(args: SearchIndexArgs) -> anyhow::Result<SearchCostEstimate> {
+    let search_request = search_request_from_args(&args)?;
+    let qw_client = args.client_args.client();
+    let search_cost_estimate = qw_client
+        .search_estimate(&args.index_id, search_request)
+        .await?;
+    Ok(search_cost_estimate)
+}
+
 pub async fn search_index_cli(args: SearchIndexArgs) -> anyhow::Result<()> {
     debug!(args=?args, "search-index");
-    let search_response_rest = search_index(args).await?;
-    let search_response_json = serde_json::to_string_pretty(&search_response_rest)?;
-    println!("{search_response_json}");
+    if args.follow {
+        return follow_search_index(args).await;
+    }
+    let response_json = if args.estimate {
+        let search_cost_estimate = estimate_search_cost(args).await?;
+        serde_json::to_string_pretty(&search_cost_estimate)?
+    } else {
+        let search_response_rest = search_index(args).await?;
+        serde_json::to_string_pretty(&search_response_rest)?
+    };
+    println!("{response_json}");
     Ok(())
 }
 
+/// Interval between two searches of `quickwit index search --follow`.
+const FOLLOW_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Prints the most recent hits of the search, then repeats the search every
+/// [`FOLLOW_POLL_INTERVAL`] from the timestamp of the last hit printed and prints the new hits, one
+/// JSON document per line.
+async fn follow_search_index(args: SearchIndexArgs) -> anyhow::Result<()> {
+    let qw_client = args.client_args.client();
+    let index_config = qw_client
+        .indexes()
+        .get(&args.index_id)
+        .await?
+        .into_index_config();
+    let timestamp_field = index_config.doc_mapping.timestamp_field.with_context(|| {
+        format!(
+            "index `{}` has no timestamp field, which `--follow` requires",
+            args.index_id
+        )
+    })?;
+    let mut follow_cursor = FollowCursor::new(timestamp_field.clone());
+
+    let mut search_request = search_request_from_args(&args)?;
+    search_request.sort_by = sort_by_timestamp(&timestamp_field, SortOrder::Desc);
+    search_request.count_all = CountHits::Underestimate;
+    let search_response = qw_client.search(&args.index_id, search_request).await?;
+
+    // The most recent hits are printed in chronological order, like `tail`.
+    for hit in search_response.hits.iter().rev() {
+        if let Some(hit_json) = follow_cursor.observe(hit)? {
+            println!("{hit_json}");
+        }
+    }
+    loop {
+        tokio::time::sleep(FOLLOW_POLL_INTERVAL).await;
+
+        let mut search_after = None;
+
+        loop {
+            let mut search_request = search_request_from_args(&args)?;
+            search_request.start_timestamp =
+                follow_cursor.last_timestamp_secs.max(args.start_timestamp);
+            search_request.sort_by = sort_by_timestamp(&timestamp_field, SortOrder::Asc);
+            search_request.count_all = CountHits::Underestimate;
+            search_request.search_after = search_after;
+            let search_response = qw_client.search(&args.index_id, search_request).await?;
+
+            for hit in &search_response.hits {
+                if let Some(hit_json) = follow_cursor.observe(hit)? {
+                    println!("{hit_json}");
+                }
+            }
+            // More new hits than `max_hits` were indexed since the previous search: fetch the
+            // next page.
+            let Some(next_search_after) = search_response.next_search_after else {
+                break;
+            };
+            search_after = Some(next_search_after.split(',').map(String::from).collect());
+        }
+    }
+}
+
+fn sort_by_timestamp(timestamp_field: &str, sort_order: SortOrder) -> SortBy {
+    SortBy {
+        sort_fields: vec![SortField {
+            field_name: timestamp_field.to_string(),
+            sort_order: sort_order as i32,
+            sort_datetime_format: None,
+        }],
+    }
+}
+
+/// Keeps track of the hits printed by `quickwit index search --follow`.
+struct FollowCursor {
+    timestamp_field: String,
+    /// Timestamp in seconds of the most recent hit printed.
+    last_timestamp_secs: Option<i64>,
+    /// Hits printed within the second of `last_timestamp_secs`. The start timestamp of the
+    /// searches has a one second resolution and is inclusive, so these hits are returned again by
+    /// the next search.
+    last_hits: HashSet<String>,
+}
+
+impl FollowCursor {
+    fn new(timestamp_field: String) -> Self {
+        Self {
+            timestamp_field,
+            last_timestamp_secs: None,
+            last_hits: HashSet::new(),
+        }
+    }
+
+    /// Records a hit and returns it serialized as JSON if it has not been printed yet.
+    fn observe(&mut self, hit: &JsonValue) -> anyhow::Result<Option<String>> {
+        let hit_json = serde_json::to_string(hit)?;
+        let timestamp_secs = hit_timestamp_secs(hit, &self.timestamp_field)?;
+
+        match self.last_timestamp_secs {
+            Some(last_timestamp_secs) if timestamp_secs < last_timestamp_secs => {
+                return Ok(None);
+            }
+            Some(last_timestamp_secs) if timestamp_secs == last_timestamp_secs => {
+                if !self.last_hits.insert(hit_json.clone()) {
+                    return Ok(None);
+                }
+            }
+            _ => {
+                self.last_timestamp_secs = Some(timestamp_secs);
+                self.last_hits.clear();
+                self.last_hits.insert(hit_json.clone());
+            }
+        }
+        Ok(Some(hit_json))
+    }
+}
+
+/// Extracts the value of the timestamp field from a hit, formatted either as a Unix timestamp or
+/// as an RFC 3339, ISO 8601, or RFC 2822 string, and returns it in seconds.
+fn hit_timestamp_secs(hit: &JsonValue, timestamp_field: &str) -> anyhow::Result<i64> {
+    let timestamp_value = hit
+        .get(timestamp_field)
+        .or_else(|| {
+            timestamp_field
+                .split('.')
+                .try_fold(hit, |value, key| value.get(key))
+        })
+        .with_context(|| format!("hit has no value for timestamp field `{timestamp_field}`"))?;
+    let date_time_result = match timestamp_value {
+        JsonValue::Number(timestamp) => timestamp
+            .as_i64()
+            .ok_or_else(|| format!("timestamp `{timestamp}` is not an integer"))
+            .and_then(parse_timestamp),
+        JsonValue::String(date_time_str) => parse_date_time_str(
+            date_time_str,
+            &[
+                DateTimeInputFormat::Rfc3339,
+                DateTimeInputFormat::Iso8601,
+                DateTimeInputFormat::Rfc2822,
+            ],
+        ),
+        _ => Err(format!("unexpected timestamp value `{timestamp_value}`")),
+    };
+    let date_time = date_time_result.map_err(|error| {
+        anyhow!("failed to parse timestamp field `{timestamp_field}` of hit: {error}")
+    })?;
+    Ok(date_time.into_timestamp_secs())
+}
+
+pub async fn validate_docs_cli(args: ValidateDocsArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "validate-docs");
+    let index_config = if let Some(index_config_uri) = &args.index_config_uri_opt {
+        let storage_resolver = StorageResolver::unconfigured();
+        let file_content = load_file(&storage_resolver, index_config_uri).await?;
+        let config_format = ConfigFormat::sniff_from_uri(index_config_uri)?;
+        // The index URI plays no part in the validation of documents.
+        let default_index_root_uri = Uri::from_str("ram:///indexes")?;
+        load_index_config_from_user_config(config_format, &file_content, &default_index_root_uri)
+            .with_context(|| format!("failed to parse index config `{index_config_uri}`"))?
+    } else {
+        let index_id = args
+            .index_id_opt
+            .as_ref()
+            .expect("`index` or `index-config` should be set.");
+        let qw_client = args.client_args.client();
+        qw_client.indexes().get(index_id).await?.into_index_config()
+    };
+    let doc_mapper = build_doc_mapper(&index_config.doc_mapping, &index_config.search_settings)?;
+
+    let reader: Box<dyn BufRead> = if let Some(input_path) = &args.input_path_opt {
+        println!("❯ Validating documents from {}.", input_path.display());
+        let input_file = File::open(input_path)
+            .with_context(|| format!("failed to open input file `{}`", input_path.display()))?;
+        Box::new(BufReader::new(input_file))
+    } else {
+        println!("❯ Validating documents from stdin.");
+        Box::new(stdin().lock())
+    };
+    let mut num_printed_errors = 0;
+    let summary = validate_docs(reader, &*doc_mapper, |line_number, error| {
+        if num_printed_errors < args.max_errors {
+            println!("{} line {line_number}: {error}", "✘".color(RED_COLOR));
+            num_printed_errors += 1;
+        }
+    })?;
+    if summary.num_invalid_docs == 0 {
+        println!(
+            "{} All {} documents are valid for index `{}`.",
+            "✔".color(GREEN_COLOR),
+            summary.num_valid_docs,
+            index_config.index_id
+        );
+        return Ok(());
+    }
+    if summary.num_invalid_docs > num_printed_errors as u64 {
+        println!(
+            "... {} more errors not displayed.",
+            summary.num_invalid_docs - num_printed_errors as u64
+        );
+    }
+    bail!(
+        "{} out of {} documents are invalid for index `{}`",
+        summary.num_invalid_docs,
+        summary.num_valid_docs + summary.num_invalid_docs,
+        index_config.index_id
+    );
+}
+
+#[derive(Debug, Default, Eq, PartialEq)]
+struct DocValidationSummary {
+    num_valid_docs: u64,
+    num_invalid_docs: u64,
+}
+
+/// Runs each NDJSON document of `reader` through the doc mapper. Blank lines are skipped.
+/// `on_error` is called with the 1-based line number of each invalid document.
+fn validate_docs(
+    reader: impl BufRead,
+    doc_mapper: &dyn DocMapper,
+    mut on_error: impl FnMut(usize, DocParsingError),
+) -> io::Result<DocValidationSummary> {
+    let mut summary = DocValidationSummary::default();
+
+    for (line_idx, line_res) in reader.lines().enumerate() {
+        let line = line_res?;
+
+        if line.trim().is_empty() {
+            continue;
+        }
+        match doc_mapper.doc_from_json_str(&line) {
+            Ok(_) => summary.num_valid_docs += 1,
+            Err(error) => {
+                summary.num_invalid_docs += 1;
+                on_error(line_idx + 1, error);
+            }
+        }
+    }
+    Ok(summary)
+}
+
 pub async fn delete_index_cli(args: DeleteIndexArgs) -> anyhow::Result<()> {
     debug!(args=?args, "delete-index");
     if !args.dry_run && !args.assume_yes {
@@ -1261,6 +1981,49 @@ mod test {
         split_metadata
     }
 
+    #[test]
+    fn test_hit_timestamp_secs() {
+        let hit = serde_json::json!({"ts": 1_700_000_000});
+        assert_eq!(hit_timestamp_secs(&hit, "ts").unwrap(), 1_700_000_000);
+
+        let hit = serde_json::json!({"ts": 1_700_000_000_123i64});
+        assert_eq!(hit_timestamp_secs(&hit, "ts").unwrap(), 1_700_000_000);
+
+        let hit = serde_json::json!({"ts": "2023-11-14T22:13:20Z"});
+        assert_eq!(hit_timestamp_secs(&hit, "ts").unwrap(), 1_700_000_000);
+
+        let hit = serde_json::json!({"attributes": {"ts": "2023-11-14T22:13:20.5Z"}});
+        assert_eq!(
+            hit_timestamp_secs(&hit, "attributes.ts").unwrap(),
+            1_700_000_000
+        );
+
+        let hit = serde_json::json!({"body": "foo"});
+        hit_timestamp_secs(&hit, "ts").unwrap_err();
+    }
+
+    #[test]
+    fn test_follow_cursor() {
+        let mut follow_cursor = FollowCursor::new("ts".to_string());
+
+        let hit_0 = serde_json::json!({"ts": 1_700_000_000, "body": "foo"});
+        let hit_1 = serde_json::json!({"ts": 1_700_000_001, "body": "bar"});
+        let hit_2 = serde_json::json!({"ts": 1_700_000_001, "body": "baz"});
+        let hit_3 = serde_json::json!({"ts": 1_700_000_002, "body": "qux"});
+
+        assert!(follow_cursor.observe(&hit_0).unwrap().is_some());
+        assert!(follow_cursor.observe(&hit_1).unwrap().is_some());
+        assert_eq!(follow_cursor.last_timestamp_secs, Some(1_700_000_001));
+
+        // The next search starts at the timestamp of the last hit and returns it again.
+        assert!(follow_cursor.observe(&hit_1).unwrap().is_none());
+        assert!(follow_cursor.observe(&hit_2).unwrap().is_some());
+        assert!(follow_cursor.observe(&hit_0).unwrap().is_none());
+        assert!(follow_cursor.observe(&hit_3).unwrap().is_some());
+        assert!(follow_cursor.observe(&hit_2).unwrap().is_none());
+        assert_eq!(follow_cursor.last_timestamp_secs, Some(1_700_000_002));
+    }
+
     #[test]
     fn test_index_stats() -> anyhow::Result<()> {
         let index_id = "index-stats-env".to_string();
@@ -1310,6 +2073,29 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_ingest_checkpoint_save_and_load() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let input_path = temp_dir.path().join("docs.ndjson");
+        let checkpoint_path = IngestCheckpoint::path(&input_path);
+        assert_eq!(
+            checkpoint_path,
+            temp_dir.path().join("docs.ndjson.ingest-checkpoint")
+        );
+        assert!(IngestCheckpoint::load(&checkpoint_path).unwrap().is_none());
+
+        let checkpoint = IngestCheckpoint {
+            index_id: "test-index".to_string(),
+            offset: 1024,
+            num_acked_batches: 3,
+        };
+        checkpoint.save(&checkpoint_path).unwrap();
+        assert_eq!(
+            IngestCheckpoint::load(&checkpoint_path).unwrap().unwrap(),
+            checkpoint
+        );
+    }
+
     #[test]
     fn test_descriptive_stats() -> anyhow::Result<()> {
         let split_id = "stat-test-split".to_string();
@@ -1374,4 +2160,54 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn test_validate_docs() {
+        let index_config_yaml = r#"
+            version: 0.6
+            index_id: test-index
+            doc_mapping:
+              mode: strict
+              field_mappings:
+                - name: body
+                  type: text
+                - name: severity
+                  type: u64
+                - name: timestamp
+                  type: datetime
+                  fast: true
+              timestamp_field: timestamp
+        "#;
+        let index_config = load_index_config_from_user_config(
+            ConfigFormat::Yaml,
+            index_config_yaml.as_bytes(),
+            &Uri::for_test("ram:///indexes"),
+        )
+        .unwrap();
+        let doc_mapper =
+            build_doc_mapper(&index_config.doc_mapping, &index_config.search_settings).unwrap();
+        let ndjson = [
+            r#"{"body": "hello", "severity": 1, "timestamp": 1684993001}"#,
+            "",
+            r#"{"body": "unknown field", "timestamp": 1684993001, "host": "localhost"}"#,
+            r#"{"body": "type conflict", "severity": "high", "timestamp": 1684993001}"#,
+            r#"{"body": "bad timestamp", "timestamp": "yesterday"}"#,
+            "not json",
+            r#"{"body": "world", "timestamp": 1684993002}"#,
+        ]
+        .join("\n");
+        let mut error_line_numbers = Vec::new();
+        let summary = validate_docs(ndjson.as_bytes(), &*doc_mapper, |line_number, _error| {
+            error_line_numbers.push(line_number);
+        })
+        .unwrap();
+        assert_eq!(
+            summary,
+            DocValidationSummary {
+                num_valid_docs: 2,
+                num_invalid_docs: 4,
+            }
+        );
+        assert_eq!(error_line_numbers, [3, 4, 5, 6]);
+    }
 }
@@ -22,7 +22,7 @@
 use std::collections::HashSet;
 use std::str::FromStr;
 
-use anyhow::Context;
+use anyhow::{bail, Context};
 use clap::{arg, Arg, ArgMatches};
 use dialoguer::theme::ColorfulTheme;
 use dialoguer::Confirm;
@@ -48,8 +48,10 @@ use tracing::info;
 
 use crate::checklist::run_checklist;
 
+mod bench;
 pub mod checklist;
 pub mod cli;
+pub mod config;
 pub mod index;
 #[cfg(feature = "jemalloc")]
 pub mod jemalloc;
@@ -81,7 +83,7 @@ fn config_cli_arg() -> Arg {
 
 fn client_args() -> Vec<Arg> {
     vec![
-        arg!(--"endpoint" <QW_CLUSTER_ENDPOINT> "Quickwit cluster endpoint.")
+        arg!(--"endpoint" <QW_CLUSTER_ENDPOINT> "Quickwit cluster endpoint. Accepts a comma-separated list of node endpoints to spread requests across and fail over between.")
             .default_value("http://127.0.0.1:7280")
             .env("QW_CLUSTER_ENDPOINT")
             .required(false)
@@ -105,6 +107,9 @@ fn client_args() -> Vec<Arg> {
 #[derive(Debug, Eq, PartialEq)]
 pub struct ClientArgs {
     pub cluster_endpoint: Url,
+    /// Endpoints of other nodes of the cluster. Requests are load balanced across all the
+    /// endpoints and fail over to the next one when a node cannot be reached.
+    pub additional_endpoints: Vec<Url>,
     pub connect_timeout: Option<Timeout>,
     pub timeout: Option<Timeout>,
     pub commit_timeout: Option<Timeout>,
@@ -115,6 +120,7 @@ impl Default for ClientArgs {
     fn default() -> Self {
         Self {
             cluster_endpoint: Url::parse(DEFAULT_BASE_URL).unwrap(),
+            additional_endpoints: Vec::new(),
             connect_timeout: None,
             timeout: None,
             commit_timeout: None,
@@ -126,6 +132,9 @@ impl Default for ClientArgs {
 impl ClientArgs {
     pub fn client(self) -> QuickwitClient {
         let mut builder = QuickwitClientBuilder::new(self.cluster_endpoint);
+        for endpoint in self.additional_endpoints {
+            builder = builder.add_endpoint(endpoint);
+        }
         if let Some(connect_timeout) = self.connect_timeout {
             builder = builder.connect_timeout(connect_timeout);
         }
@@ -152,10 +161,20 @@ impl ClientArgs {
     }
 
     fn parse_inner(matches: &mut ArgMatches, process_ingest: bool) -> anyhow::Result<Self> {
-        let cluster_endpoint = matches
+        let endpoints_str = matches
             .remove_one::<String>("endpoint")
-            .map(|endpoint_str| Url::from_str(&endpoint_str))
-            .expect("`endpoint` should be a required arg.")?;
+            .expect("`endpoint` should be a required arg.");
+        let mut endpoints = endpoints_str
+            .split(',')
+            .map(str::trim)
+            .filter(|endpoint_str| !endpoint_str.is_empty())
+            .map(Url::from_str)
+            .collect::<Result<Vec<Url>, _>>()?;
+        if endpoints.is_empty() {
+            bail!("at least one cluster endpoint must be provided");
+        }
+        let cluster_endpoint = endpoints.remove(0);
+        let additional_endpoints = endpoints;
         let connect_timeout =
             if let Some(duration) = matches.remove_one::<String>("connect-timeout") {
                 Some(parse_duration_or_none(&duration)?)
@@ -183,6 +202,7 @@ impl ClientArgs {
         };
         Ok(Self {
             cluster_endpoint,
+            additional_endpoints,
             connect_timeout,
             timeout,
             commit_timeout,
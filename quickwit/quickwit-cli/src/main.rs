@@ -94,18 +94,21 @@ mod tests {
 
     use bytesize::ByteSize;
     use quickwit_cli::cli::{build_cli, CliCommand};
+    use quickwit_cli::config::{ConfigCliCommand, LintConfigArgs};
     use quickwit_cli::index::{
-        ClearIndexArgs, CreateIndexArgs, DeleteIndexArgs, DescribeIndexArgs, IndexCliCommand,
-        IngestDocsArgs, SearchIndexArgs,
+        ClearIndexArgs, CloneIndexArgs, CreateIndexArgs, DeleteIndexArgs, DescribeIndexArgs,
+        IndexCliCommand, IndexMergesArgs, IngestDocsArgs, RestoreIndexArgs, SearchIndexArgs,
+        SnapshotIndexArgs, ValidateDocsArgs,
     };
     use quickwit_cli::split::{DescribeSplitArgs, SplitCliCommand};
     use quickwit_cli::tool::{
-        ExtractSplitArgs, GarbageCollectIndexArgs, LocalIngestDocsArgs, LocalSearchArgs, MergeArgs,
+        BenchIngestArgs, BenchSearchArgs, ExportApiSpecsArgs, ExtractSplitArgs,
+        GarbageCollectIndexArgs, InferMappingArgs, LocalIngestDocsArgs, LocalSearchArgs, MergeArgs,
         ToolCliCommand,
     };
     use quickwit_cli::ClientArgs;
     use quickwit_common::uri::Uri;
-    use quickwit_config::SourceInputFormat;
+    use quickwit_config::{CsvConfig, SourceInputFormat};
     use quickwit_rest_client::models::Timeout;
     use quickwit_rest_client::rest_client::CommitType;
     use reqwest::Url;
@@ -197,6 +200,7 @@ mod tests {
                     input_path_opt: None,
                     batch_size_limit_opt: None,
                     commit_type: CommitType::Auto,
+                    resume: false,
                 })) if &index_id == "wikipedia"
                 && client_args.timeout.is_none()
                 && client_args.connect_timeout.is_none()
@@ -207,6 +211,48 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_parse_client_args_with_multiple_endpoints() -> anyhow::Result<()> {
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "index",
+            "ingest",
+            "--index",
+            "wikipedia",
+            "--endpoint",
+            "http://127.0.0.1:7280, http://127.0.0.1:7281,http://127.0.0.1:7282",
+        ])?;
+        let command = CliCommand::parse_cli_args(matches)?;
+        let CliCommand::Index(IndexCliCommand::Ingest(IngestDocsArgs { client_args, .. })) =
+            command
+        else {
+            panic!("expected an ingest command");
+        };
+        assert_eq!(
+            client_args.cluster_endpoint,
+            Url::from_str("http://127.0.0.1:7280").unwrap()
+        );
+        assert_eq!(
+            client_args.additional_endpoints,
+            vec![
+                Url::from_str("http://127.0.0.1:7281").unwrap(),
+                Url::from_str("http://127.0.0.1:7282").unwrap(),
+            ]
+        );
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "index",
+            "ingest",
+            "--index",
+            "wikipedia",
+            "--endpoint",
+            ",",
+        ])?;
+        CliCommand::parse_cli_args(matches).unwrap_err();
+        Ok(())
+    }
+
     #[test]
     fn test_parse_ingest_args() -> anyhow::Result<()> {
         let app = build_cli().no_binary_name(true);
@@ -228,6 +274,7 @@ mod tests {
                     input_path_opt: None,
                     batch_size_limit_opt: None,
                     commit_type: CommitType::Auto,
+                    resume: false,
                 })) if &index_id == "wikipedia"
                 && client_args.timeout.is_none()
                 && client_args.connect_timeout.is_none()
@@ -256,6 +303,7 @@ mod tests {
                     input_path_opt: None,
                     batch_size_limit_opt: Some(batch_size_limit),
                     commit_type: CommitType::Force,
+                    resume: false,
                 })) if &index_id == "wikipedia"
                         && client_args.cluster_endpoint == Url::from_str("http://127.0.0.1:7280").unwrap()
                         && client_args.timeout.is_none()
@@ -285,6 +333,7 @@ mod tests {
                     input_path_opt: None,
                     batch_size_limit_opt: Some(batch_size_limit),
                     commit_type: CommitType::WaitFor,
+                    resume: false,
                 })) if &index_id == "wikipedia"
                     && client_args.cluster_endpoint == Url::from_str("http://127.0.0.1:7280").unwrap()
                     && client_args.timeout.is_none()
@@ -315,6 +364,7 @@ mod tests {
                     input_path_opt: None,
                     batch_size_limit_opt: None,
                     commit_type: CommitType::Auto,
+                    resume: false,
                 })) if &index_id == "wikipedia"
                         && client_args.cluster_endpoint == Url::from_str("http://127.0.0.1:7280").unwrap()
                         && client_args.timeout == Some(Timeout::from_secs(10))
@@ -346,6 +396,7 @@ mod tests {
                     input_path_opt: None,
                     batch_size_limit_opt: None,
                     commit_type: CommitType::WaitFor,
+                    resume: false,
                 })) if &index_id == "wikipedia"
                         && client_args.cluster_endpoint == Url::from_str("http://127.0.0.1:7280").unwrap()
                         && client_args.timeout == Some(Timeout::none())
@@ -353,6 +404,37 @@ mod tests {
                         && client_args.commit_timeout == Some(Timeout::from_hours(4))
         ));
 
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "index",
+            "ingest",
+            "--index",
+            "wikipedia",
+            "--input-path",
+            "/data/wikipedia.ndjson",
+            "--resume",
+        ])?;
+        let command = CliCommand::parse_cli_args(matches)?;
+        assert!(matches!(
+            command,
+            CliCommand::Index(IndexCliCommand::Ingest(
+                IngestDocsArgs {
+                    index_id,
+                    input_path_opt: Some(input_path),
+                    resume: true,
+                    ..
+                })) if &index_id == "wikipedia"
+                        && input_path == PathBuf::from("/data/wikipedia.ndjson")
+        ));
+
+        let app = build_cli().no_binary_name(true);
+        assert_eq!(
+            app.try_get_matches_from(["index", "ingest", "--index", "wikipedia", "--resume"])
+                .unwrap_err()
+                .kind(),
+            clap::error::ErrorKind::MissingRequiredArgument
+        );
+
         let app = build_cli().no_binary_name(true);
         assert_eq!(
             app.try_get_matches_from([
@@ -397,7 +479,10 @@ mod tests {
                     config_uri,
                     index_id,
                     input_path_opt: None,
+                    watch: false,
+                    file_pattern_opt: None,
                     input_format,
+                    csv_config,
                     overwrite,
                     vrl_script: Some(vrl_script),
                     clear_cache,
@@ -406,8 +491,54 @@ mod tests {
                        && vrl_script == ".message = downcase(string!(.message))"
                        && overwrite
                        && !clear_cache
-                       && input_format == SourceInputFormat::PlainText,
+                       && input_format == SourceInputFormat::PlainText
+                       && csv_config == CsvConfig::default(),
+        ));
+    }
+
+    #[test]
+    fn test_parse_local_ingest_watch_args() {
+        let app = build_cli().no_binary_name(true);
+        let matches = app
+            .try_get_matches_from([
+                "tool",
+                "local-ingest",
+                "--index",
+                "app-logs",
+                "--config",
+                "/config.yaml",
+                "--input-path",
+                "/var/log/app",
+                "--watch",
+                "--file-pattern",
+                "*.log",
+            ])
+            .unwrap();
+        let command = CliCommand::parse_cli_args(matches).unwrap();
+        assert!(matches!(
+            command,
+            CliCommand::Tool(ToolCliCommand::LocalIngest(
+                LocalIngestDocsArgs {
+                    input_path_opt: Some(input_path),
+                    watch: true,
+                    file_pattern_opt: Some(file_pattern),
+                    ..
+                })) if input_path == PathBuf::from("/var/log/app") && file_pattern == "*.log"
         ));
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app
+            .try_get_matches_from([
+                "tool",
+                "local-ingest",
+                "--index",
+                "app-logs",
+                "--config",
+                "/config.yaml",
+                "--watch",
+            ])
+            .unwrap();
+        CliCommand::parse_cli_args(matches).unwrap_err();
     }
 
     #[test]
@@ -475,11 +606,46 @@ mod tests {
                 start_timestamp: Some(0),
                 end_timestamp: Some(1),
                 sort_by_score: false,
+                estimate: false,
+                follow: false,
             })) if &index_id == "wikipedia"
                   && query == "Barack Obama"
                   && search_field_names == vec!["title".to_string(), "url".to_string()]
                   && snippet_field_names == vec!["body".to_string()]
         ));
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "index",
+            "search",
+            "--index",
+            "hdfs-logs",
+            "--query",
+            "severity_text:ERROR",
+            "--follow",
+        ])?;
+        let command = CliCommand::parse_cli_args(matches)?;
+        assert!(matches!(
+            command,
+            CliCommand::Index(IndexCliCommand::Search(SearchIndexArgs {
+                index_id,
+                follow: true,
+                ..
+            })) if &index_id == "hdfs-logs"
+        ));
+
+        let app = build_cli().no_binary_name(true);
+        app.try_get_matches_from([
+            "index",
+            "search",
+            "--index",
+            "hdfs-logs",
+            "--query",
+            "severity_text:ERROR",
+            "--follow",
+            "--estimate",
+        ])
+        .unwrap_err();
         Ok(())
     }
 
@@ -608,6 +774,167 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_parse_pause_and_resume_merges_args() {
+        let app = build_cli().no_binary_name(true);
+        let matches = app
+            .try_get_matches_from(["index", "pause-merges", "--index", "wikipedia"])
+            .unwrap();
+        let command = CliCommand::parse_cli_args(matches).unwrap();
+        assert!(matches!(
+            command,
+            CliCommand::Index(IndexCliCommand::PauseMerges(IndexMergesArgs {
+                index_id,
+                ..
+            })) if &index_id == "wikipedia"
+        ));
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app
+            .try_get_matches_from(["index", "resume-merges", "--index", "wikipedia"])
+            .unwrap();
+        let command = CliCommand::parse_cli_args(matches).unwrap();
+        assert!(matches!(
+            command,
+            CliCommand::Index(IndexCliCommand::ResumeMerges(IndexMergesArgs {
+                index_id,
+                ..
+            })) if &index_id == "wikipedia"
+        ));
+    }
+
+    #[test]
+    fn test_parse_snapshot_and_restore_args() -> anyhow::Result<()> {
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "index",
+            "snapshot",
+            "--index",
+            "wikipedia",
+            "--output-uri",
+            "s3://backup/wikipedia",
+            "--copy-split-files",
+        ])?;
+        let command = CliCommand::parse_cli_args(matches)?;
+        let expected_output_uri = Uri::from_str("s3://backup/wikipedia")?;
+        assert!(matches!(
+            command,
+            CliCommand::Index(IndexCliCommand::Snapshot(SnapshotIndexArgs {
+                index_id,
+                output_uri,
+                copy_split_files: true,
+                ..
+            })) if &index_id == "wikipedia" && output_uri == expected_output_uri
+        ));
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "index",
+            "restore",
+            "--snapshot-uri",
+            "s3://backup/wikipedia",
+        ])?;
+        let command = CliCommand::parse_cli_args(matches)?;
+        assert!(matches!(
+            command,
+            CliCommand::Index(IndexCliCommand::Restore(RestoreIndexArgs {
+                snapshot_uri,
+                index_uri_opt: None,
+                ..
+            })) if snapshot_uri == expected_output_uri
+        ));
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "index",
+            "restore",
+            "--snapshot-uri",
+            "s3://backup/wikipedia",
+            "--index-uri",
+            "s3://indexes/wikipedia",
+        ])?;
+        let command = CliCommand::parse_cli_args(matches)?;
+        let expected_index_uri = Uri::from_str("s3://indexes/wikipedia")?;
+        assert!(matches!(
+            command,
+            CliCommand::Index(IndexCliCommand::Restore(RestoreIndexArgs {
+                index_uri_opt: Some(index_uri),
+                ..
+            })) if index_uri == expected_index_uri
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_clone_args() -> anyhow::Result<()> {
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "index",
+            "clone",
+            "--index",
+            "wikipedia",
+            "--clone-index",
+            "wikipedia-experiment",
+        ])?;
+        let command = CliCommand::parse_cli_args(matches)?;
+        assert!(matches!(
+            command,
+            CliCommand::Index(IndexCliCommand::Clone(CloneIndexArgs {
+                index_id,
+                clone_index_id,
+                ..
+            })) if &index_id == "wikipedia" && &clone_index_id == "wikipedia-experiment"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_validate_docs_args() -> anyhow::Result<()> {
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "index",
+            "validate-docs",
+            "--index",
+            "wikipedia",
+            "--input-path",
+            "/data/wiki.json",
+        ])?;
+        let command = CliCommand::parse_cli_args(matches)?;
+        let expected_command = CliCommand::Index(IndexCliCommand::ValidateDocs(ValidateDocsArgs {
+            client_args: ClientArgs::default(),
+            index_id_opt: Some("wikipedia".to_string()),
+            index_config_uri_opt: None,
+            input_path_opt: Some(PathBuf::from("/data/wiki.json")),
+            max_errors: 100,
+        }));
+        assert_eq!(command, expected_command);
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "index",
+            "validate-docs",
+            "--index-config",
+            "file:///config/wiki.yaml",
+            "--max-errors",
+            "10",
+        ])?;
+        let command = CliCommand::parse_cli_args(matches)?;
+        let expected_command = CliCommand::Index(IndexCliCommand::ValidateDocs(ValidateDocsArgs {
+            client_args: ClientArgs::default(),
+            index_id_opt: None,
+            index_config_uri_opt: Some(Uri::from_str("file:///config/wiki.yaml")?),
+            input_path_opt: None,
+            max_errors: 10,
+        }));
+        assert_eq!(command, expected_command);
+
+        let app = build_cli().no_binary_name(true);
+        assert!(app
+            .try_get_matches_from(["index", "validate-docs"])
+            .is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_parse_split_describe_args() -> anyhow::Result<()> {
         let app = build_cli().no_binary_name(true);
@@ -632,6 +959,38 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_parse_config_lint_args() -> anyhow::Result<()> {
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "config",
+            "lint",
+            "/config/quickwit.yaml",
+            "/config/index.yaml",
+            "--output-format",
+            "json",
+            "--deny-warnings",
+        ])?;
+        let command = CliCommand::parse_cli_args(matches)?;
+        let expected_config_uris = vec![
+            Uri::from_str("file:///config/quickwit.yaml").unwrap(),
+            Uri::from_str("file:///config/index.yaml").unwrap(),
+        ];
+        assert!(matches!(
+            command,
+            CliCommand::Config(ConfigCliCommand::Lint(LintConfigArgs {
+                config_uris,
+                deny_warnings: true,
+                ..
+            })) if config_uris == expected_config_uris
+        ));
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from(["config", "lint", "--output-format", "json"]);
+        assert!(matches.is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_parse_split_extract_args() -> anyhow::Result<()> {
         let app = build_cli().no_binary_name(true);
@@ -733,6 +1092,133 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_parse_infer_mapping_args() -> anyhow::Result<()> {
+        let app = build_cli().no_binary_name(true);
+        let matches =
+            app.try_get_matches_from(["tool", "infer-mapping", "--input", "/data/logs.ndjson"])?;
+        let command = CliCommand::parse_cli_args(matches)?;
+        let expected_command = CliCommand::Tool(ToolCliCommand::InferMapping(InferMappingArgs {
+            input_path: PathBuf::from("/data/logs.ndjson"),
+            index_id: "logs".to_string(),
+            max_docs: 10_000,
+            output_path_opt: None,
+        }));
+        assert_eq!(command, expected_command);
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "tool",
+            "infer-mapping",
+            "--input",
+            "/data/logs.ndjson",
+            "--index",
+            "my-logs",
+            "--max-docs",
+            "100",
+            "--output",
+            "/data/my-logs.yaml",
+        ])?;
+        let command = CliCommand::parse_cli_args(matches)?;
+        let expected_command = CliCommand::Tool(ToolCliCommand::InferMapping(InferMappingArgs {
+            input_path: PathBuf::from("/data/logs.ndjson"),
+            index_id: "my-logs".to_string(),
+            max_docs: 100,
+            output_path_opt: Some(PathBuf::from("/data/my-logs.yaml")),
+        }));
+        assert_eq!(command, expected_command);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_export_api_specs_args() -> anyhow::Result<()> {
+        let app = build_cli().no_binary_name(true);
+        let matches =
+            app.try_get_matches_from(["tool", "export-api-specs", "--output-dir", "/tmp/specs"])?;
+        let command = CliCommand::parse_cli_args(matches)?;
+        let expected_command =
+            CliCommand::Tool(ToolCliCommand::ExportApiSpecs(ExportApiSpecsArgs {
+                output_dir: PathBuf::from("/tmp/specs"),
+            }));
+        assert_eq!(command, expected_command);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_bench_args() -> anyhow::Result<()> {
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "tool",
+            "bench",
+            "ingest",
+            "--index",
+            "wikipedia",
+            "--template",
+            "/docs/template.json",
+            "--rate",
+            "5000",
+            "--duration",
+            "30s",
+        ])?;
+        let command = CliCommand::parse_cli_args(matches)?;
+        let expected_command = CliCommand::Tool(ToolCliCommand::BenchIngest(BenchIngestArgs {
+            client_args: ClientArgs::default(),
+            index_id: "wikipedia".to_string(),
+            template_uri_opt: Some(Uri::from_str("file:///docs/template.json")?),
+            input_uri_opt: None,
+            docs_per_sec_opt: Some(5000),
+            batch_num_docs: 1000,
+            concurrency: 4,
+            duration: Duration::from_secs(30),
+        }));
+        assert_eq!(command, expected_command);
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "tool",
+            "bench",
+            "ingest",
+            "--index",
+            "wikipedia",
+            "--template",
+            "/docs/template.json",
+            "--input-path",
+            "/docs/wikipedia.json",
+        ])?;
+        CliCommand::parse_cli_args(matches).unwrap_err();
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "tool",
+            "bench",
+            "search",
+            "--endpoint",
+            "http://127.0.0.1:8000",
+            "--index",
+            "wikipedia",
+            "--query",
+            "barack",
+            "obama",
+            "--concurrency",
+            "16",
+        ])?;
+        let command = CliCommand::parse_cli_args(matches)?;
+        let expected_command = CliCommand::Tool(ToolCliCommand::BenchSearch(BenchSearchArgs {
+            client_args: ClientArgs {
+                cluster_endpoint: Url::from_str("http://127.0.0.1:8000").unwrap(),
+                ..Default::default()
+            },
+            index_id: "wikipedia".to_string(),
+            queries: vec!["barack".to_string(), "obama".to_string()],
+            max_hits: 20,
+            queries_per_sec_opt: None,
+            concurrency: 16,
+            duration: Duration::from_secs(60),
+        }));
+        assert_eq!(command, expected_command);
+        Ok(())
+    }
+
     #[test]
     fn test_parse_no_color() {
         let previous_no_color_res = std::env::var("NO_COLOR");
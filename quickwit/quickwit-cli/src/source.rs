@@ -26,6 +26,7 @@ use itertools::Itertools;
 use quickwit_common::uri::Uri;
 use quickwit_config::{validate_identifier, ConfigFormat, SourceConfig};
 use quickwit_metastore::checkpoint::SourceCheckpoint;
+use quickwit_proto::control_plane::PartitionBacklog;
 use quickwit_storage::{load_file, StorageResolver};
 use serde_json::Value as JsonValue;
 use tabled::{Table, Tabled};
@@ -113,6 +114,18 @@ pub fn build_source_command() -> Command {
                         .required(true),
                 ])
             )
+        .subcommand(
+            Command::new("backlog")
+                .about("Displays the number of records of each partition of a source that have not been indexed yet.")
+                .args(&[
+                    arg!(--index <INDEX_ID> "ID of the target index")
+                        .display_order(1)
+                        .required(true),
+                    arg!(--source <SOURCE_ID> "ID of the source.")
+                        .display_order(2)
+                        .required(true),
+                ])
+            )
         .subcommand(
             Command::new("list")
                 .about("Lists the sources of an index.")
@@ -169,6 +182,13 @@ pub struct DescribeSourceArgs {
     pub source_id: String,
 }
 
+#[derive(Debug, Eq, PartialEq)]
+pub struct SourceBacklogArgs {
+    pub client_args: ClientArgs,
+    pub index_id: String,
+    pub source_id: String,
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub struct ListSourcesArgs {
     pub client_args: ClientArgs,
@@ -189,6 +209,7 @@ pub enum SourceCliCommand {
     ToggleSource(ToggleSourceArgs),
     DeleteSource(DeleteSourceArgs),
     DescribeSource(DescribeSourceArgs),
+    SourceBacklog(SourceBacklogArgs),
     ListSources(ListSourcesArgs),
     ResetCheckpoint(ResetCheckpointArgs),
 }
@@ -200,6 +221,7 @@ impl SourceCliCommand {
             Self::ToggleSource(args) => toggle_source_cli(args).await,
             Self::DeleteSource(args) => delete_source_cli(args).await,
             Self::DescribeSource(args) => describe_source_cli(args).await,
+            Self::SourceBacklog(args) => source_backlog_cli(args).await,
             Self::ListSources(args) => list_sources_cli(args).await,
             Self::ResetCheckpoint(args) => reset_checkpoint_cli(args).await,
         }
@@ -219,6 +241,7 @@ impl SourceCliCommand {
             }
             "delete" => Self::parse_delete_args(submatches).map(Self::DeleteSource),
             "describe" => Self::parse_describe_args(submatches).map(Self::DescribeSource),
+            "backlog" => Self::parse_backlog_args(submatches).map(Self::SourceBacklog),
             "list" => Self::parse_list_args(submatches).map(Self::ListSources),
             "reset-checkpoint" => {
                 Self::parse_reset_checkpoint_args(submatches).map(Self::ResetCheckpoint)
@@ -295,6 +318,21 @@ impl SourceCliCommand {
         })
     }
 
+    fn parse_backlog_args(mut matches: ArgMatches) -> anyhow::Result<SourceBacklogArgs> {
+        let client_args = ClientArgs::parse(&mut matches)?;
+        let index_id = matches
+            .remove_one::<String>("index")
+            .expect("`index` should be a required arg.");
+        let source_id = matches
+            .remove_one::<String>("source")
+            .expect("`source` should be a required arg.");
+        Ok(SourceBacklogArgs {
+            client_args,
+            index_id,
+            source_id,
+        })
+    }
+
     fn parse_list_args(mut matches: ArgMatches) -> anyhow::Result<ListSourcesArgs> {
         let client_args = ClientArgs::parse(&mut matches)?;
         let index_id = matches
@@ -441,6 +479,32 @@ where
     Ok((source_table, params_table, checkpoint_table))
 }
 
+async fn source_backlog_cli(args: SourceBacklogArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "source-backlog");
+    let qw_client = args.client_args.client();
+    let source_backlog = qw_client
+        .sources(&args.index_id)
+        .backlog(&args.source_id)
+        .await
+        .context("failed to fetch source backlog")?;
+    println!("Total backlog: {}", source_backlog.total_backlog);
+    let table = make_source_backlog_table(source_backlog.partition_backlogs);
+    display_tables(&[table]);
+    Ok(())
+}
+
+fn make_source_backlog_table<I>(partition_backlogs: I) -> Table
+where I: IntoIterator<Item = PartitionBacklog> {
+    let rows = partition_backlogs
+        .into_iter()
+        .map(|partition_backlog| PartitionBacklogRow {
+            partition_id: partition_backlog.partition_id,
+            node_id: partition_backlog.node_id,
+            backlog: partition_backlog.backlog,
+        });
+    make_table("Backlog", rows, false)
+}
+
 async fn list_sources_cli(args: ListSourcesArgs) -> anyhow::Result<()> {
     let qw_client = args.client_args.client();
     let index_metadata = qw_client
@@ -484,6 +548,16 @@ struct ParamsRow {
     value: JsonValue,
 }
 
+#[derive(Tabled)]
+struct PartitionBacklogRow {
+    #[tabled(rename = "Partition ID")]
+    partition_id: String,
+    #[tabled(rename = "Node ID")]
+    node_id: String,
+    #[tabled(rename = "Backlog")]
+    backlog: u64,
+}
+
 #[derive(Tabled)]
 struct CheckpointRow {
     #[tabled(rename = "Partition ID")]
@@ -699,6 +773,29 @@ mod tests {
         assert_eq!(command, expected_command);
     }
 
+    #[test]
+    fn test_parse_source_backlog_args() {
+        let app = build_cli().no_binary_name(true);
+        let matches = app
+            .try_get_matches_from(vec![
+                "source",
+                "backlog",
+                "--index",
+                "hdfs-logs",
+                "--source",
+                "hdfs-logs-source",
+            ])
+            .unwrap();
+        let command = CliCommand::parse_cli_args(matches).unwrap();
+        let expected_command =
+            CliCommand::Source(SourceCliCommand::SourceBacklog(SourceBacklogArgs {
+                client_args: ClientArgs::default(),
+                index_id: "hdfs-logs".to_string(),
+                source_id: "hdfs-logs-source".to_string(),
+            }));
+        assert_eq!(command, expected_command);
+    }
+
     #[test]
     fn test_parse_reset_checkpoint_args() {
         let app = build_cli().no_binary_name(true);
@@ -747,6 +844,12 @@ mod tests {
             source_params: SourceParams::file("path/to/file"),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            csv_config: Default::default(),
+            dead_letter_queue: None,
+            supervision: Default::default(),
+            fetch_stream: Default::default(),
+            placement_constraints: Default::default(),
+            priority: Default::default(),
         }];
         let expected_source = vec![SourceRow {
             source_id: "foo-source".to_string(),
@@ -808,6 +911,12 @@ mod tests {
                 source_params: SourceParams::stdin(),
                 transform_config: None,
                 input_format: SourceInputFormat::Json,
+                csv_config: Default::default(),
+                dead_letter_queue: None,
+                supervision: Default::default(),
+                fetch_stream: Default::default(),
+                placement_constraints: Default::default(),
+                priority: Default::default(),
             },
             SourceConfig {
                 source_id: "bar-source".to_string(),
@@ -817,6 +926,12 @@ mod tests {
                 source_params: SourceParams::stdin(),
                 transform_config: None,
                 input_format: SourceInputFormat::Json,
+                csv_config: Default::default(),
+                dead_letter_queue: None,
+                supervision: Default::default(),
+                fetch_stream: Default::default(),
+                placement_constraints: Default::default(),
+                priority: Default::default(),
             },
         ];
         let expected_sources = [
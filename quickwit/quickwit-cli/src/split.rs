@@ -63,14 +63,13 @@ pub fn build_split_command() -> Command {
                     arg!(--"end-date" <END_DATE> "Selects the splits that contain documents before this date (time-series indexes only).")
                         .display_order(7)
                         .required(false),
-                    // See #2762:
-                    // arg!(--tags <TAGS> "Selects the splits whose tags are all included in this comma-separated list of tags.")
-                    //     .display_order(6)
-                    //     .required(false)
-                    //     .use_value_delimiter(true),
+                    arg!(--tags <TAGS> "Selects the splits that may contain documents carrying all the tags of this comma-separated list of `key:value` tags.")
+                        .display_order(8)
+                        .required(false)
+                        .value_delimiter(','),
                     arg!(--"output-format" <OUTPUT_FORMAT> "Output format. Possible values are `table`, `json`, and `pretty-json`.")
                         .alias("format")
-                        .display_order(8)
+                        .display_order(9)
                         .required(false)
                 ])
             )
@@ -140,7 +139,7 @@ pub struct ListSplitArgs {
     pub create_date: Option<OffsetDateTime>,
     pub start_date: Option<OffsetDateTime>,
     pub end_date: Option<OffsetDateTime>,
-    // pub tags: Option<TagFilterAst>,
+    pub tags: Option<Vec<String>>,
     output_format: OutputFormat,
 }
 
@@ -213,17 +212,9 @@ impl SplitCliCommand {
             .remove_one::<String>("end-date")
             .map(|date_str| parse_date(&date_str, "end"))
             .transpose()?;
-        // let tags = matches.values_of("tags").map(|values| {
-        //     TagFilterAst::And(
-        //         values
-        //             .into_iter()
-        //             .map(|value| TagFilterAst::Tag {
-        //                 get_flag: true,
-        //                 tag: value.to_string(),
-        //             })
-        //             .collect(),
-        //     )
-        // });
+        let tags = matches
+            .remove_many::<String>("tags")
+            .map(|values| values.collect());
         let output_format = matches
             .remove_one::<String>("output-format")
             .map(|s| OutputFormat::from_str(s.as_str()))
@@ -238,7 +229,7 @@ impl SplitCliCommand {
             start_date,
             end_date,
             create_date,
-            // tags,
+            tags,
             output_format,
         }))
     }
@@ -298,11 +289,8 @@ async fn list_split_cli(args: ListSplitArgs) -> anyhow::Result<()> {
         start_timestamp: args.start_date.map(OffsetDateTime::unix_timestamp),
         end_timestamp: args.end_date.map(OffsetDateTime::unix_timestamp),
         end_create_timestamp: args.create_date.map(OffsetDateTime::unix_timestamp),
+        tags: args.tags,
     };
-    // TODO: plug tags.
-    // if let Some(tags) = args.tags {
-    //     query = query.with_tags_filter(tags);
-    // }
     let splits = qw_client
         .splits(&args.index_id)
         .list(list_splits_query_params)
@@ -509,8 +497,8 @@ mod tests {
             "2020-12-24",
             "--end-date",
             "2020-12-25T12:42",
-            // "--tags",
-            // "tenant:a,service:zk",
+            "--tags",
+            "tenant:a,service:zk",
             "--format",
             "json",
         ])?;
@@ -520,16 +508,7 @@ mod tests {
         let expected_create_date = Some(datetime!(2020-12-24 00:00 UTC));
         let expected_start_date = Some(datetime!(2020-12-24 00:00 UTC));
         let expected_end_date = Some(datetime!(2020-12-25 12:42 UTC));
-        // let expected_tags = Some(TagFilterAst::And(vec![
-        //     TagFilterAst::Tag {
-        //         get_flag: true,
-        //         tag: "tenant:a".to_string(),
-        //     },
-        //     TagFilterAst::Tag {
-        //         get_flag: true,
-        //         tag: "service:zk".to_string(),
-        //     },
-        // ]));
+        let expected_tags = Some(vec!["tenant:a".to_string(), "service:zk".to_string()]);
         let expected_output_format = OutputFormat::Json;
         assert!(matches!(
             command,
@@ -539,7 +518,7 @@ mod tests {
                 create_date,
                 start_date,
                 end_date,
-                // tags,
+                tags,
                 output_format,
                 ..
             })) if index_id == "hdfs"
@@ -547,7 +526,7 @@ mod tests {
                    && create_date == expected_create_date
                    && start_date == expected_start_date
                    && end_date == expected_end_date
-                   // && tags == expected_tags
+                   && tags == expected_tags
                    && output_format == expected_output_format
         ));
         Ok(())
@@ -18,9 +18,10 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 use std::collections::{HashSet, VecDeque};
-use std::io::{stdout, IsTerminal, Stdout, Write};
+use std::fs::File;
+use std::io::{stdout, BufRead, BufReader, IsTerminal, Stdout, Write};
 use std::num::NonZeroUsize;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::time::{Duration, Instant};
 use std::{env, fmt, io};
@@ -36,8 +37,11 @@ use quickwit_common::runtimes::RuntimesConfig;
 use quickwit_common::uri::Uri;
 use quickwit_config::service::QuickwitService;
 use quickwit_config::{
-    IndexerConfig, NodeConfig, SourceConfig, SourceInputFormat, SourceParams, TransformConfig,
-    VecSourceParams, CLI_INGEST_SOURCE_ID,
+    validate_identifier, CsvConfig, FileSourceParams, IndexerConfig, NodeConfig, SourceConfig,
+    SourceInputFormat, SourceParams, TransformConfig, VecSourceParams, CLI_INGEST_SOURCE_ID,
+};
+use quickwit_doc_mapper::{
+    suggest_doc_mapping, DocMappingSuggestion, FieldMappingEntry, JsonObject,
 };
 use quickwit_index_management::{clear_cache_directory, IndexService};
 use quickwit_indexing::actors::{IndexingService, MergePipeline, MergePipelineId};
@@ -48,21 +52,25 @@ use quickwit_indexing::IndexingPipeline;
 use quickwit_ingest::IngesterPool;
 use quickwit_metastore::IndexMetadataResponseExt;
 use quickwit_proto::indexing::CpuCapacity;
+use quickwit_proto::ingest::INGEST_V2_PROTOS;
 use quickwit_proto::metastore::{IndexMetadataRequest, MetastoreService, MetastoreServiceClient};
-use quickwit_proto::search::{CountHits, SearchResponse};
+use quickwit_proto::search::{CountHits, Federation, SearchResponse};
 use quickwit_proto::types::{NodeId, PipelineUid};
 use quickwit_search::{single_node_search, SearchResponseRest};
 use quickwit_serve::{
-    search_request_from_api_request, BodyFormat, SearchRequestQueryString, SortBy,
+    build_docs, search_request_from_api_request, BodyFormat, SearchRequestQueryString, SortBy,
 };
 use quickwit_storage::{BundleStorage, Storage};
+use serde::Serialize;
+use tabled::{Table, Tabled};
 use thousands::Separable;
 use tracing::{debug, info};
 
+use crate::bench::{bench_ingest_cli, bench_search_cli};
 use crate::checklist::{GREEN_COLOR, RED_COLOR};
 use crate::{
-    config_cli_arg, get_resolvers, load_node_config, run_index_checklist, start_actor_runtimes,
-    THROUGHPUT_WINDOW_SIZE,
+    client_args, config_cli_arg, get_resolvers, load_node_config, make_table, run_index_checklist,
+    start_actor_runtimes, ClientArgs, THROUGHPUT_WINDOW_SIZE,
 };
 
 pub fn build_tool_command() -> Command {
@@ -80,9 +88,16 @@ pub fn build_tool_command() -> Command {
                         .required(true),
                     arg!(--"input-path" <INPUT_PATH> "Location of the input file.")
                         .required(false),
+                    arg!(--watch "Watches the input path, a local directory, for new and rotated files, and indexes them until interrupted.")
+                        .required(false),
+                    arg!(--"file-pattern" <FILE_PATTERN> "Glob pattern matched against the names of the files of the watched directory. Only used with `--watch`.")
+                        .required(false),
                     arg!(--"input-format" <INPUT_FORMAT> "Format of the input data.")
                         .default_value("json")
                         .required(false),
+                    arg!(--"csv-delimiter" <CSV_DELIMITER> "Character separating the fields of the CSV records. Only used by the `csv` input format.")
+                        .default_value(",")
+                        .required(false),
                     arg!(--overwrite "Overwrites pre-existing index.")
                         .required(false),
                     arg!(--"transform-script" <SCRIPT> "VRL program to transform docs before ingesting.")
@@ -166,6 +181,91 @@ pub fn build_tool_command() -> Command {
                         .required(true),
                 ])
             )
+        .subcommand(
+            Command::new("infer-mapping")
+                .display_order(10)
+                .about("Infers a draft index config from a sample of NDJSON documents.")
+                .long_about("Scans a sample of NDJSON documents, reports the type, presence, and cardinality of their fields, and generates a draft index config with a doc mapping and a timestamp field suggested from the sample. Does not require a running cluster.")
+                .args(&[
+                    arg!(--input <INPUT_PATH> "Location of the NDJSON sample file.")
+                        .display_order(1)
+                        .required(true),
+                    arg!(--index <INDEX> "ID of the index in the draft index config. Defaults to the name of the input file.")
+                        .required(false),
+                    arg!(--"max-docs" <MAX_DOCS> "Maximum number of documents scanned.")
+                        .default_value("10000")
+                        .required(false),
+                    arg!(--output <OUTPUT_PATH> "Location of the draft index config file. Defaults to stdout.")
+                        .required(false),
+                ])
+            )
+        .subcommand(
+            Command::new("export-api-specs")
+                .display_order(10)
+                .about("Exports the OpenAPI spec of the REST API and the protobuf definitions of the ingest v2 API.")
+                .long_about("Writes the OpenAPI spec of the REST API to `openapi.json` and the protobuf definitions of the ingest v2 gRPC API to `protos/` in the output directory, for generating client libraries. Does not require a running cluster.")
+                .args(&[
+                    arg!(--"output-dir" <OUTPUT_DIR> "Directory where the specs are written. Created if it does not exist.")
+                        .required(true),
+                ])
+            )
+        .subcommand(
+            Command::new("bench")
+                .display_order(10)
+                .about("Benchmarks the ingest and search APIs of a cluster.")
+                .long_about("Sends ingest or search requests to a running cluster at a target rate and concurrency, then reports the throughput and latency percentiles of the requests.")
+                .args(client_args())
+                .subcommand(
+                    Command::new("ingest")
+                        .about("Ingests synthetic or replayed documents.")
+                        .long_about("Ingests documents generated from a template or replayed in a loop from an NDJSON file. The template is a JSON document in which the `{{seq}}`, `{{timestamp}}`, `{{random_int}}`, and `{{random_word}}` placeholders are substituted for each document.")
+                        .args(&[
+                            arg!(--index <INDEX> "ID of the target index")
+                                .display_order(1)
+                                .required(true),
+                            arg!(--template <TEMPLATE> "Location of the doc template file.")
+                                .required(false),
+                            arg!(--"input-path" <INPUT_PATH> "Location of the NDJSON file replayed in a loop.")
+                                .required(false),
+                            arg!(--rate <DOCS_PER_SEC> "Target number of documents ingested per second. Unbounded by default.")
+                                .required(false),
+                            arg!(--"batch-size" <NUM_DOCS> "Number of documents per ingest request.")
+                                .default_value("1000")
+                                .required(false),
+                            arg!(--concurrency <CONCURRENCY> "Maximum number of requests in flight.")
+                                .default_value("4")
+                                .required(false),
+                            arg!(--duration <DURATION> "Duration of the benchmark.")
+                                .default_value("1m")
+                                .required(false),
+                        ])
+                )
+                .subcommand(
+                    Command::new("search")
+                        .about("Sends search requests.")
+                        .long_about("Sends the queries in a round-robin fashion.")
+                        .args(&[
+                            arg!(--index <INDEX> "ID of the target index")
+                                .display_order(1)
+                                .required(true),
+                            arg!(--query <QUERY> "Queries expressed in natural query language. Space-separated list.")
+                                .num_args(1..)
+                                .required(true),
+                            arg!(--"max-hits" <MAX_HITS> "Maximum number of hits returned per query.")
+                                .default_value("20")
+                                .required(false),
+                            arg!(--rate <QUERIES_PER_SEC> "Target number of queries per second. Unbounded by default.")
+                                .required(false),
+                            arg!(--concurrency <CONCURRENCY> "Maximum number of requests in flight.")
+                                .default_value("4")
+                                .required(false),
+                            arg!(--duration <DURATION> "Duration of the benchmark.")
+                                .default_value("1m")
+                                .required(false),
+                        ])
+                )
+                .arg_required_else_help(true)
+            )
         .arg_required_else_help(true)
 }
 
@@ -174,7 +274,10 @@ pub struct LocalIngestDocsArgs {
     pub config_uri: Uri,
     pub index_id: String,
     pub input_path_opt: Option<PathBuf>,
+    pub watch: bool,
+    pub file_pattern_opt: Option<String>,
     pub input_format: SourceInputFormat,
+    pub csv_config: CsvConfig,
     pub overwrite: bool,
     pub vrl_script: Option<String>,
     pub clear_cache: bool,
@@ -218,6 +321,42 @@ pub struct ExtractSplitArgs {
     pub target_dir: PathBuf,
 }
 
+#[derive(Debug, Eq, PartialEq)]
+pub struct InferMappingArgs {
+    pub input_path: PathBuf,
+    pub index_id: String,
+    pub max_docs: usize,
+    pub output_path_opt: Option<PathBuf>,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct ExportApiSpecsArgs {
+    pub output_dir: PathBuf,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct BenchIngestArgs {
+    pub client_args: ClientArgs,
+    pub index_id: String,
+    pub template_uri_opt: Option<Uri>,
+    pub input_uri_opt: Option<Uri>,
+    pub docs_per_sec_opt: Option<u64>,
+    pub batch_num_docs: usize,
+    pub concurrency: usize,
+    pub duration: Duration,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct BenchSearchArgs {
+    pub client_args: ClientArgs,
+    pub index_id: String,
+    pub queries: Vec<String>,
+    pub max_hits: usize,
+    pub queries_per_sec_opt: Option<u64>,
+    pub concurrency: usize,
+    pub duration: Duration,
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub enum ToolCliCommand {
     GarbageCollect(GarbageCollectIndexArgs),
@@ -225,6 +364,10 @@ pub enum ToolCliCommand {
     LocalSearch(LocalSearchArgs),
     Merge(MergeArgs),
     ExtractSplit(ExtractSplitArgs),
+    InferMapping(InferMappingArgs),
+    ExportApiSpecs(ExportApiSpecsArgs),
+    BenchIngest(BenchIngestArgs),
+    BenchSearch(BenchSearchArgs),
 }
 
 impl ToolCliCommand {
@@ -238,6 +381,9 @@ impl ToolCliCommand {
             "local-search" => Self::parse_local_search_args(submatches),
             "merge" => Self::parse_merge_args(submatches),
             "extract-split" => Self::parse_extract_split_args(submatches),
+            "infer-mapping" => Self::parse_infer_mapping_args(submatches),
+            "export-api-specs" => Self::parse_export_api_specs_args(submatches),
+            "bench" => Self::parse_bench_args(submatches),
             _ => bail!("unknown tool subcommand `{subcommand}`"),
         }
     }
@@ -257,12 +403,34 @@ impl ToolCliCommand {
         } else {
             None
         };
+        let watch = matches.get_flag("watch");
+        let file_pattern_opt = matches.remove_one::<String>("file-pattern");
 
+        if watch && input_path_opt.is_none() {
+            bail!("`--watch` requires `--input-path` to be a local directory");
+        }
+        if file_pattern_opt.is_some() && !watch {
+            bail!("`--file-pattern` can only be used with `--watch`");
+        }
         let input_format = matches
             .remove_one::<String>("input-format")
             .map(|input_format| SourceInputFormat::from_str(&input_format))
             .expect("`input-format` should have a default value.")
             .map_err(|err| anyhow::anyhow!(err))?;
+        let csv_delimiter = matches
+            .remove_one::<String>("csv-delimiter")
+            .expect("`csv-delimiter` should have a default value.");
+        let mut csv_delimiter_chars = csv_delimiter.chars();
+        let (Some(delimiter), None) = (csv_delimiter_chars.next(), csv_delimiter_chars.next())
+        else {
+            bail!("CSV delimiter must be a single character, got `{csv_delimiter}`");
+        };
+        let csv_config = CsvConfig { delimiter };
+        csv_config.validate()?;
+
+        if watch && input_format == SourceInputFormat::Csv {
+            bail!("`--watch` cannot be used with the `csv` input format");
+        }
         let overwrite = matches.get_flag("overwrite");
         let vrl_script = matches.remove_one::<String>("transform-script");
         let clear_cache = !matches.get_flag("keep-cache");
@@ -271,7 +439,10 @@ impl ToolCliCommand {
             config_uri,
             index_id,
             input_path_opt,
+            watch,
+            file_pattern_opt,
             input_format,
+            csv_config,
             overwrite,
             vrl_script,
             clear_cache,
@@ -390,6 +561,116 @@ impl ToolCliCommand {
         }))
     }
 
+    fn parse_infer_mapping_args(mut matches: ArgMatches) -> anyhow::Result<Self> {
+        let input_path = matches
+            .remove_one::<String>("input")
+            .map(PathBuf::from)
+            .expect("`input` should be a required arg.");
+        let index_id = matches
+            .remove_one::<String>("index")
+            .unwrap_or_else(|| default_draft_index_id(&input_path));
+        let max_docs = matches
+            .remove_one::<String>("max-docs")
+            .expect("`max-docs` should have a default value.")
+            .parse()?;
+        let output_path_opt = matches.remove_one::<String>("output").map(PathBuf::from);
+        Ok(Self::InferMapping(InferMappingArgs {
+            input_path,
+            index_id,
+            max_docs,
+            output_path_opt,
+        }))
+    }
+
+    fn parse_export_api_specs_args(mut matches: ArgMatches) -> anyhow::Result<Self> {
+        let output_dir = matches
+            .remove_one::<String>("output-dir")
+            .map(PathBuf::from)
+            .expect("`output-dir` should be a required arg.");
+        Ok(Self::ExportApiSpecs(ExportApiSpecsArgs { output_dir }))
+    }
+
+    fn parse_bench_args(mut matches: ArgMatches) -> anyhow::Result<Self> {
+        let (subcommand, mut submatches) = matches
+            .remove_subcommand()
+            .context("failed to parse bench subcommand")?;
+        let client_args = ClientArgs::parse(&mut submatches)?;
+        let index_id = submatches
+            .remove_one::<String>("index")
+            .expect("`index` should be a required arg.");
+        let requests_per_sec_opt = submatches
+            .remove_one::<String>("rate")
+            .map(|rate_str| rate_str.parse::<u64>())
+            .transpose()?;
+        if requests_per_sec_opt == Some(0) {
+            bail!("`--rate` must be strictly positive");
+        }
+        let concurrency = submatches
+            .remove_one::<String>("concurrency")
+            .expect("`concurrency` should have a default value.")
+            .parse::<usize>()?;
+        if concurrency == 0 {
+            bail!("`--concurrency` must be strictly positive");
+        }
+        let duration = submatches
+            .remove_one::<String>("duration")
+            .map(|duration_str| humantime::parse_duration(&duration_str))
+            .expect("`duration` should have a default value.")?;
+
+        match subcommand.as_str() {
+            "ingest" => {
+                let template_uri_opt = submatches
+                    .remove_one::<String>("template")
+                    .map(|uri_str| Uri::from_str(&uri_str))
+                    .transpose()?;
+                let input_uri_opt = submatches
+                    .remove_one::<String>("input-path")
+                    .map(|uri_str| Uri::from_str(&uri_str))
+                    .transpose()?;
+                if template_uri_opt.is_some() == input_uri_opt.is_some() {
+                    bail!("exactly one of `--template` or `--input-path` must be provided");
+                }
+                let batch_num_docs = submatches
+                    .remove_one::<String>("batch-size")
+                    .expect("`batch-size` should have a default value.")
+                    .parse::<usize>()?;
+                if batch_num_docs == 0 {
+                    bail!("`--batch-size` must be strictly positive");
+                }
+                Ok(Self::BenchIngest(BenchIngestArgs {
+                    client_args,
+                    index_id,
+                    template_uri_opt,
+                    input_uri_opt,
+                    docs_per_sec_opt: requests_per_sec_opt,
+                    batch_num_docs,
+                    concurrency,
+                    duration,
+                }))
+            }
+            "search" => {
+                let queries = submatches
+                    .remove_many::<String>("query")
+                    .expect("`query` should be a required arg.")
+                    .collect();
+                let max_hits = submatches
+                    .remove_one::<String>("max-hits")
+                    .expect("`max-hits` should have a default value.")
+                    .parse::<usize>()?;
+                Ok(Self::BenchSearch(BenchSearchArgs {
+                    client_args,
+                    index_id,
+                    queries,
+                    max_hits,
+                    queries_per_sec_opt: requests_per_sec_opt,
+                    concurrency,
+                    duration,
+                }))
+            }
+            _ => bail!("unknown bench subcommand `{subcommand}`"),
+        }
+    }
+
     pub async fn execute(self) -> anyhow::Result<()> {
         match self {
             Self::GarbageCollect(args) => garbage_collect_index_cli(args).await,
@@ -397,6 +678,10 @@ impl ToolCliCommand {
             Self::LocalSearch(args) => local_search_cli(args).await,
             Self::Merge(args) => merge_cli(args).await,
             Self::ExtractSplit(args) => extract_split_cli(args).await,
+            Self::InferMapping(args) => infer_mapping_cli(args).await,
+            Self::ExportApiSpecs(args) => export_api_specs_cli(args),
+            Self::BenchIngest(args) => bench_ingest_cli(args).await,
+            Self::BenchSearch(args) => bench_search_cli(args).await,
         }
     }
 }
@@ -411,7 +696,11 @@ pub async fn local_ingest_docs_cli(args: LocalIngestDocsArgs) -> anyhow::Result<
     let mut metastore = metastore_resolver.resolve(&config.metastore_uri).await?;
 
     let source_params = if let Some(filepath) = args.input_path_opt.as_ref() {
-        SourceParams::file(filepath)
+        if args.watch {
+            SourceParams::File(FileSourceParams::watch(filepath, args.file_pattern_opt))
+        } else {
+            SourceParams::file(filepath)
+        }
     } else {
         SourceParams::stdin()
     };
@@ -426,6 +715,12 @@ pub async fn local_ingest_docs_cli(args: LocalIngestDocsArgs) -> anyhow::Result<
         source_params,
         transform_config,
         input_format: args.input_format,
+        csv_config: args.csv_config,
+        dead_letter_queue: None,
+        supervision: Default::default(),
+        fetch_stream: Default::default(),
+        placement_constraints: Default::default(),
+        priority: Default::default(),
     };
     run_index_checklist(
         &mut metastore,
@@ -483,7 +778,9 @@ pub async fn local_ingest_docs_cli(args: LocalIngestDocsArgs) -> anyhow::Result<
         .ask_for_res(DetachIndexingPipeline { pipeline_id })
         .await?;
 
-    if args.input_path_opt.is_none() && io::stdin().is_terminal() {
+    if args.watch {
+        println!("Watching for new files. Press CTRL+C to stop.");
+    } else if args.input_path_opt.is_none() && io::stdin().is_terminal() {
         let eof_shortcut = match env::consts::OS {
             "windows" => "CTRL+Z",
             _ => "CTRL+D",
@@ -551,6 +848,16 @@ pub async fn local_search_cli(args: LocalSearchArgs) -> anyhow::Result<()> {
         format: BodyFormat::Json,
         sort_by,
         count_all: CountHits::CountAll,
+        dedup_field: None,
+        dedup_count: None,
+        group_by: None,
+        group_size: None,
+        max_hits_per_index: None,
+        federation: Federation::Global,
+        preview: false,
+        split_tags: None,
+        search_after: None,
+        allow_partial_results: None,
     };
     let search_request =
         search_request_from_api_request(vec![args.index_id], search_request_query_string)?;
@@ -610,6 +917,12 @@ pub async fn merge_cli(args: MergeArgs) -> anyhow::Result<()> {
                 source_params: SourceParams::Vec(VecSourceParams::default()),
                 transform_config: None,
                 input_format: SourceInputFormat::Json,
+                csv_config: Default::default(),
+                dead_letter_queue: None,
+                supervision: Default::default(),
+                fetch_stream: Default::default(),
+                placement_constraints: Default::default(),
+                priority: Default::default(),
             },
             pipeline_uid: PipelineUid::from_u128(0u128),
         })
@@ -727,7 +1040,12 @@ async fn extract_split_cli(args: ExtractSplitArgs) -> anyhow::Result<()> {
         .index_metadata(IndexMetadataRequest::for_index_id(args.index_id))
         .await?
         .deserialize_index_metadata()?;
-    let index_storage = storage_resolver.resolve(index_metadata.index_uri()).await?;
+    let index_storage = storage_resolver
+        .resolve_index_storage(
+            index_metadata.index_uri(),
+            index_metadata.index_config.encryption.as_ref(),
+        )
+        .await?;
     let split_file = PathBuf::from(format!("{}.split", args.split_id));
     let split_data = index_storage.get_all(split_file.as_path()).await?;
     let (_hotcache_bytes, bundle_storage) = BundleStorage::open_from_split_data_with_owned_bytes(
@@ -749,6 +1067,225 @@ async fn extract_split_cli(args: ExtractSplitArgs) -> anyhow::Result<()> {
 
 /// Starts a tokio task that displays the indexing statistics
 /// every once in awhile.
+/// Index ID used in the draft index config when none is provided: the name of the input file if
+/// it is a valid index ID.
+fn default_draft_index_id(input_path: &Path) -> String {
+    input_path
+        .file_stem()
+        .and_then(|file_stem| file_stem.to_str())
+        .filter(|file_stem| validate_identifier("index ID", file_stem).is_ok())
+        .unwrap_or("my-index")
+        .to_string()
+}
+
+async fn infer_mapping_cli(args: InferMappingArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "infer-mapping");
+    let input_file = File::open(&args.input_path)
+        .with_context(|| format!("failed to open input file `{}`", args.input_path.display()))?;
+    let json_objs = read_ndjson_sample(BufReader::new(input_file), args.max_docs)?;
+    if json_objs.is_empty() {
+        bail!(
+            "input file `{}` does not contain any document",
+            args.input_path.display()
+        );
+    }
+    let doc_mapping_suggestion = suggest_doc_mapping(&json_objs)?;
+
+    eprintln!(
+        "{}",
+        make_field_summary_table(&doc_mapping_suggestion, json_objs.len())
+    );
+    if doc_mapping_suggestion.timestamp_field_candidates.is_empty() {
+        eprintln!("No timestamp field candidate found.");
+    } else {
+        eprintln!(
+            "Timestamp field candidates: {}",
+            doc_mapping_suggestion.timestamp_field_candidates.join(", ")
+        );
+    }
+    let draft_index_config_yaml =
+        build_draft_index_config_yaml(&args.index_id, &doc_mapping_suggestion, json_objs.len())?;
+
+    if let Some(output_path) = &args.output_path_opt {
+        std::fs::write(output_path, draft_index_config_yaml).with_context(|| {
+            format!(
+                "failed to write draft index config to `{}`",
+                output_path.display()
+            )
+        })?;
+        eprintln!(
+            "{} Draft index config written to `{}`. Review it before creating the index.",
+            "✔".color(GREEN_COLOR),
+            output_path.display()
+        );
+    } else {
+        println!("{draft_index_config_yaml}");
+    }
+    Ok(())
+}
+
+fn export_api_specs_cli(args: ExportApiSpecsArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "export-api-specs");
+    let openapi_path = args.output_dir.join("openapi.json");
+    let openapi_json = build_docs().to_pretty_json()?;
+    write_api_spec(&openapi_path, &openapi_json)?;
+
+    for (proto_path, proto) in INGEST_V2_PROTOS {
+        write_api_spec(&args.output_dir.join("protos").join(proto_path), proto)?;
+    }
+    eprintln!(
+        "{} API specs written to `{}`.",
+        "✔".color(GREEN_COLOR),
+        args.output_dir.display()
+    );
+    Ok(())
+}
+
+fn write_api_spec(path: &Path, content: &str) -> anyhow::Result<()> {
+    if let Some(parent_dir) = path.parent() {
+        std::fs::create_dir_all(parent_dir)
+            .with_context(|| format!("failed to create directory `{}`", parent_dir.display()))?;
+    }
+    std::fs::write(path, content)
+        .with_context(|| format!("failed to write API spec to `{}`", path.display()))
+}
+
+/// Reads up to `max_docs` documents from an NDJSON input. Blank lines are skipped.
+fn read_ndjson_sample(reader: impl BufRead, max_docs: usize) -> anyhow::Result<Vec<JsonObject>> {
+    let mut json_objs: Vec<JsonObject> = Vec::new();
+
+    for (line_idx, line_res) in reader.lines().enumerate() {
+        if json_objs.len() >= max_docs {
+            break;
+        }
+        let line = line_res?;
+
+        if line.trim().is_empty() {
+            continue;
+        }
+        let json_obj: JsonObject = serde_json::from_str(&line)
+            .with_context(|| format!("line {} is not a valid JSON object", line_idx + 1))?;
+        json_objs.push(json_obj);
+    }
+    Ok(json_objs)
+}
+
+#[derive(Tabled)]
+struct FieldSummaryRow {
+    #[tabled(rename = "Field")]
+    path: String,
+    #[tabled(rename = "Type")]
+    field_type: String,
+    #[tabled(rename = "Presence")]
+    presence: String,
+    #[tabled(rename = "Distinct values")]
+    num_distinct_values: String,
+}
+
+fn make_field_summary_table(
+    doc_mapping_suggestion: &DocMappingSuggestion,
+    num_sampled_docs: usize,
+) -> Table {
+    let rows = doc_mapping_suggestion
+        .field_summaries
+        .iter()
+        .map(|field_summary| {
+            let presence = if field_summary.num_parent_docs == 0 {
+                0.0
+            } else {
+                field_summary.num_docs as f64 * 100.0 / field_summary.num_parent_docs as f64
+            };
+            let num_distinct_values = if field_summary.is_high_cardinality {
+                "high".to_string()
+            } else if field_summary.num_distinct_strs == 0 {
+                "-".to_string()
+            } else {
+                field_summary.num_distinct_strs.to_string()
+            };
+            FieldSummaryRow {
+                path: field_summary.path.clone(),
+                field_type: field_summary
+                    .suggested_type
+                    .clone()
+                    .unwrap_or_else(|| "dynamic".to_string()),
+                presence: format!("{presence:.1}%"),
+                num_distinct_values,
+            }
+        });
+    make_table(
+        &format!("Fields inferred from {num_sampled_docs} documents"),
+        rows,
+        false,
+    )
+}
+
+#[derive(Serialize)]
+struct DraftIndexConfig<'a> {
+    version: &'static str,
+    index_id: &'a str,
+    doc_mapping: DraftDocMapping<'a>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    search_settings: Option<DraftSearchSettings>,
+}
+
+#[derive(Serialize)]
+struct DraftDocMapping<'a> {
+    // Fields left out of the suggestion, such as fields with mixed types, are still indexed by the
+    // dynamic mapping.
+    mode: &'static str,
+    field_mappings: &'a [FieldMappingEntry],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timestamp_field: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct DraftSearchSettings {
+    default_search_fields: Vec<String>,
+}
+
+fn build_draft_index_config_yaml(
+    index_id: &str,
+    doc_mapping_suggestion: &DocMappingSuggestion,
+    num_sampled_docs: usize,
+) -> anyhow::Result<String> {
+    // The full-text fields are the natural default search fields.
+    let default_search_fields: Vec<String> =
+        serde_json::to_value(&doc_mapping_suggestion.field_mappings)?
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|field_mapping| {
+                field_mapping["type"] == "text" && field_mapping["tokenizer"] == "default"
+            })
+            .filter_map(|field_mapping| field_mapping["name"].as_str().map(str::to_string))
+            .collect();
+    let draft_index_config = DraftIndexConfig {
+        version: "0.6",
+        index_id,
+        doc_mapping: DraftDocMapping {
+            mode: "dynamic",
+            field_mappings: &doc_mapping_suggestion.field_mappings,
+            timestamp_field: doc_mapping_suggestion
+                .timestamp_field_candidates
+                .first()
+                .map(String::as_str),
+        },
+        search_settings: if default_search_fields.is_empty() {
+            None
+        } else {
+            Some(DraftSearchSettings {
+                default_search_fields,
+            })
+        },
+    };
+    let draft_index_config_yaml = format!(
+        "# Draft index config inferred from {num_sampled_docs} documents by `quickwit tool \
+         infer-mapping`.\n{}",
+        serde_yaml::to_string(&draft_index_config)?
+    );
+    Ok(draft_index_config_yaml)
+}
+
 pub async fn start_statistics_reporting_loop(
     pipeline_handle: ActorHandle<IndexingPipeline>,
     is_stdin: bool,
@@ -939,6 +1476,7 @@ async fn create_empty_cluster(config: &NodeConfig) -> anyhow::Result<Cluster> {
         grpc_advertise_addr: config.grpc_advertise_addr,
         indexing_cpu_capacity: CpuCapacity::zero(),
         indexing_tasks: Vec::new(),
+        node_labels: Default::default(),
     };
     let cluster = Cluster::join(
         config.cluster_id.clone(),
@@ -952,3 +1490,64 @@ async fn create_empty_cluster(config: &NodeConfig) -> anyhow::Result<Cluster> {
 
     Ok(cluster)
 }
+
+#[cfg(test)]
+mod tests {
+    use quickwit_config::{load_index_config_from_user_config, ConfigFormat};
+
+    use super::*;
+
+    #[test]
+    fn test_default_draft_index_id() {
+        assert_eq!(
+            default_draft_index_id(Path::new("/data/hdfs-logs.ndjson")),
+            "hdfs-logs"
+        );
+        assert_eq!(
+            default_draft_index_id(Path::new("/data/1 sample.json")),
+            "my-index"
+        );
+    }
+
+    #[test]
+    fn test_read_ndjson_sample() {
+        let input = "{\"a\": 1}\n\n{\"a\": 2}\n{\"a\": 3}\n";
+        let json_objs = read_ndjson_sample(input.as_bytes(), 2).unwrap();
+        assert_eq!(json_objs.len(), 2);
+        assert_eq!(json_objs[1]["a"], 2);
+
+        let error = read_ndjson_sample("{\"a\": 1}\n[1, 2]\n".as_bytes(), 10).unwrap_err();
+        assert_eq!(error.to_string(), "line 2 is not a valid JSON object");
+    }
+
+    #[test]
+    fn test_build_draft_index_config_yaml() {
+        let input = r#"{"ts": "2023-10-10T10:10:10Z", "level": "INFO", "message": "user logged in", "status": 200}
+{"ts": "2023-10-10T10:10:11Z", "level": "WARN", "message": "disk almost full", "status": 507}
+"#;
+        let json_objs = read_ndjson_sample(input.as_bytes(), 10).unwrap();
+        let doc_mapping_suggestion = suggest_doc_mapping(&json_objs).unwrap();
+        let draft_index_config_yaml =
+            build_draft_index_config_yaml("test-index", &doc_mapping_suggestion, json_objs.len())
+                .unwrap();
+        assert!(draft_index_config_yaml.starts_with("# Draft index config inferred from 2"));
+
+        let default_index_root_uri = Uri::from_str("ram:///indexes").unwrap();
+        let index_config = load_index_config_from_user_config(
+            ConfigFormat::Yaml,
+            draft_index_config_yaml.as_bytes(),
+            &default_index_root_uri,
+        )
+        .unwrap();
+        assert_eq!(index_config.index_id, "test-index");
+        assert_eq!(
+            index_config.doc_mapping.timestamp_field.as_deref(),
+            Some("ts")
+        );
+        assert_eq!(index_config.doc_mapping.field_mappings.len(), 4);
+        assert_eq!(
+            index_config.search_settings.default_search_fields,
+            ["message"]
+        );
+    }
+}
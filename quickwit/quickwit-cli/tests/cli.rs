@@ -40,7 +40,7 @@ use quickwit_cli::ClientArgs;
 use quickwit_common::fs::get_cache_directory_path;
 use quickwit_common::rand::append_random_suffix;
 use quickwit_common::uri::Uri;
-use quickwit_config::{SourceInputFormat, CLI_INGEST_SOURCE_ID};
+use quickwit_config::{CsvConfig, SourceInputFormat, CLI_INGEST_SOURCE_ID};
 use quickwit_metastore::{
     ListSplitsRequestExt, MetastoreResolver, MetastoreServiceExt, MetastoreServiceStreamSplitsExt,
     SplitMetadata, SplitState, StageSplitsRequestExt,
@@ -72,7 +72,10 @@ async fn local_ingest_docs(input_path: &Path, test_env: &TestEnv) -> anyhow::Res
         config_uri: test_env.config_uri.clone(),
         index_id: test_env.index_id.clone(),
         input_path_opt: Some(input_path.to_path_buf()),
+        watch: false,
+        file_pattern_opt: None,
         input_format: SourceInputFormat::Json,
+        csv_config: CsvConfig::default(),
         overwrite: false,
         clear_cache: true,
         vrl_script: None,
@@ -184,7 +187,10 @@ async fn test_cmd_ingest_on_non_existing_index() {
         config_uri: test_env.config_uri,
         index_id: "index-does-not-exist".to_string(),
         input_path_opt: Some(test_env.resource_files["logs"].clone()),
+        watch: false,
+        file_pattern_opt: None,
         input_format: SourceInputFormat::Json,
+        csv_config: CsvConfig::default(),
         overwrite: false,
         clear_cache: true,
         vrl_script: None,
@@ -214,7 +220,10 @@ async fn test_ingest_docs_cli_keep_cache() {
         config_uri: test_env.config_uri,
         index_id,
         input_path_opt: Some(test_env.resource_files["logs"].clone()),
+        watch: false,
+        file_pattern_opt: None,
         input_format: SourceInputFormat::Json,
+        csv_config: CsvConfig::default(),
         overwrite: false,
         clear_cache: false,
         vrl_script: None,
@@ -241,7 +250,10 @@ async fn test_ingest_docs_cli() {
         config_uri: test_env.config_uri.clone(),
         index_id: index_id.clone(),
         input_path_opt: Some(test_env.resource_files["logs"].clone()),
+        watch: false,
+        file_pattern_opt: None,
         input_format: SourceInputFormat::Json,
+        csv_config: CsvConfig::default(),
         overwrite: false,
         clear_cache: true,
         vrl_script: None,
@@ -272,7 +284,10 @@ async fn test_ingest_docs_cli() {
         config_uri: test_env.config_uri,
         index_id: test_env.index_id,
         input_path_opt: Some(test_env.data_dir_path.join("file-does-not-exist.json")),
+        watch: false,
+        file_pattern_opt: None,
         input_format: SourceInputFormat::Json,
+        csv_config: CsvConfig::default(),
         overwrite: false,
         clear_cache: true,
         vrl_script: None,
@@ -384,6 +399,8 @@ async fn test_cmd_search_aggregation() {
             ..Default::default()
         },
         sort_by_score: false,
+        estimate: false,
+        follow: false,
     };
     let search_response = search_index(args).await.unwrap();
 
@@ -467,6 +484,8 @@ async fn test_cmd_search_with_snippets() -> Result<()> {
             ..Default::default()
         },
         sort_by_score: false,
+        estimate: false,
+        follow: false,
     };
     let search_response = search_index(args).await.unwrap();
     assert_eq!(search_response.hits.len(), 1);
@@ -506,6 +525,8 @@ async fn test_search_index_cli() {
         start_timestamp: None,
         end_timestamp: None,
         sort_by_score: false,
+        estimate: false,
+        follow: false,
     };
 
     local_ingest_docs(test_env.resource_files["logs"].as_path(), &test_env)
@@ -989,6 +1010,8 @@ async fn test_all_with_s3_localstack_cli() {
         start_timestamp: None,
         end_timestamp: None,
         sort_by_score: false,
+        estimate: false,
+        follow: false,
     };
 
     let search_res = search_index(args).await.unwrap();
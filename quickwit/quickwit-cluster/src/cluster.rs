@@ -32,9 +32,11 @@ use chitchat::{
 };
 use futures::Stream;
 use itertools::Itertools;
+use quickwit_common::shared_consts::INDEXER_SOURCE_BACKLOG_PREFIX;
 use quickwit_proto::indexing::{IndexingPipelineId, IndexingTask, PipelineMetrics};
 use quickwit_proto::types::{NodeId, PipelineUid, ShardId};
 use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
 use tokio::sync::{mpsc, watch, Mutex, RwLock};
 use tokio::time::timeout;
 use tokio_stream::wrappers::{UnboundedReceiverStream, WatchStream};
@@ -42,10 +44,11 @@ use tokio_stream::StreamExt;
 use tracing::{info, warn};
 
 use crate::change::{compute_cluster_change_events, ClusterChange};
+use crate::event_history::{ClusterEvent, ClusterEventHistory};
 use crate::member::{
     build_cluster_member, ClusterMember, NodeStateExt, ENABLED_SERVICES_KEY,
-    GRPC_ADVERTISE_ADDR_KEY, PIPELINE_METRICS_PREFIX, READINESS_KEY, READINESS_VALUE_NOT_READY,
-    READINESS_VALUE_READY,
+    GRPC_ADVERTISE_ADDR_KEY, NODE_LABEL_PREFIX, PIPELINE_METRICS_PREFIX, READINESS_KEY,
+    READINESS_VALUE_NOT_READY, READINESS_VALUE_READY,
 };
 use crate::ClusterNode;
 
@@ -137,29 +140,29 @@ impl Cluster {
             gossip_interval: GOSSIP_INTERVAL,
             marked_for_deletion_grace_period: MARKED_FOR_DELETION_GRACE_PERIOD,
         };
-        let chitchat_handle = spawn_chitchat(
-            chitchat_config,
-            vec![
-                (
-                    ENABLED_SERVICES_KEY.to_string(),
-                    self_node
-                        .enabled_services
-                        .iter()
-                        .map(|service| service.as_str())
-                        .join(","),
-                ),
-                (
-                    GRPC_ADVERTISE_ADDR_KEY.to_string(),
-                    self_node.grpc_advertise_addr.to_string(),
-                ),
-                (
-                    READINESS_KEY.to_string(),
-                    READINESS_VALUE_NOT_READY.to_string(),
-                ),
-            ],
-            transport,
-        )
-        .await?;
+        let mut initial_key_values = vec![
+            (
+                ENABLED_SERVICES_KEY.to_string(),
+                self_node
+                    .enabled_services
+                    .iter()
+                    .map(|service| service.as_str())
+                    .join(","),
+            ),
+            (
+                GRPC_ADVERTISE_ADDR_KEY.to_string(),
+                self_node.grpc_advertise_addr.to_string(),
+            ),
+            (
+                READINESS_KEY.to_string(),
+                READINESS_VALUE_NOT_READY.to_string(),
+            ),
+        ];
+        for (label, value) in &self_node.node_labels {
+            initial_key_values.push((format!("{NODE_LABEL_PREFIX}{label}"), value.clone()));
+        }
+        let chitchat_handle =
+            spawn_chitchat(chitchat_config, initial_key_values, transport).await?;
 
         let chitchat = chitchat_handle.chitchat();
         let live_nodes_stream = chitchat.lock().await.live_nodes_watcher();
@@ -172,6 +175,7 @@ impl Cluster {
             chitchat_handle,
             live_nodes: BTreeMap::new(),
             change_stream_subscribers: Vec::new(),
+            event_history: ClusterEventHistory::default(),
             ready_members_rx,
         };
         let cluster = Cluster {
@@ -327,6 +331,35 @@ impl Cluster {
         }
     }
 
+    /// Returns the latest membership and state changes observed by the node, from the oldest to
+    /// the most recent.
+    pub async fn events(&self) -> Vec<ClusterEvent> {
+        self.inner.read().await.event_history.events()
+    }
+
+    /// Returns the key-value state of each live node, tombstoned keys excluded.
+    pub async fn live_nodes_key_values(&self) -> Vec<NodeKeyValues> {
+        let chitchat = self.chitchat().await;
+        let chitchat_guard = chitchat.lock().await;
+
+        chitchat_guard
+            .live_nodes()
+            .flat_map(|chitchat_id| {
+                let node_state = chitchat_guard.node_state(chitchat_id)?;
+                let key_values = node_state
+                    .iter_prefix("")
+                    .filter(|(_key, versioned_value)| versioned_value.tombstone.is_none())
+                    .map(|(key, versioned_value)| (key.to_string(), versioned_value.value.clone()))
+                    .collect();
+                Some(NodeKeyValues {
+                    node_id: chitchat_id.node_id.clone(),
+                    generation_id: chitchat_id.generation_id,
+                    key_values,
+                })
+            })
+            .collect()
+    }
+
     /// Leaves the cluster.
     pub async fn shutdown(self) {
         info!(
@@ -363,6 +396,37 @@ impl Cluster {
         }
     }
 
+    /// Exposes in chitchat the backlogs of the partitions consumed by the indexing pipelines of the
+    /// node. The backlogs are exposed as follows:
+    /// Key:        indexer.source_backlog:<index_uid>:<source_id>:<pipeline_uid>
+    /// Value:      {"<partition_id>":<backlog>,...}
+    /// Keys of pipelines that no longer report a backlog are marked for deletion.
+    pub async fn update_self_node_source_backlogs(
+        &self,
+        source_backlogs: &HashMap<&IndexingPipelineId, &BTreeMap<String, u64>>,
+    ) {
+        let chitchat = self.chitchat().await;
+        let mut chitchat_guard = chitchat.lock().await;
+        let node_state = chitchat_guard.self_node_state();
+        let mut current_backlog_keys: HashSet<String> = node_state
+            .iter_prefix(INDEXER_SOURCE_BACKLOG_PREFIX)
+            .map(|(key, _)| key.to_string())
+            .collect();
+        for (pipeline_id, partition_backlogs) in source_backlogs {
+            let key = format!(
+                "{INDEXER_SOURCE_BACKLOG_PREFIX}{}:{}:{}",
+                pipeline_id.index_uid, pipeline_id.source_id, pipeline_id.pipeline_uid
+            );
+            current_backlog_keys.remove(&key);
+            let value = serde_json::to_string(partition_backlogs)
+                .expect("partition backlogs should be JSON serializable");
+            node_state.set(key, value);
+        }
+        for obsolete_backlog_key in current_backlog_keys {
+            node_state.mark_for_deletion(&obsolete_backlog_key);
+        }
+    }
+
     /// Updates indexing tasks in chitchat state.
     /// Tasks are grouped by (index_id, source_id), each group is stored in a key as follows:
     /// - key: `{INDEXING_TASK_PREFIX}{index_id}{INDEXING_TASK_SEPARATOR}{source_id}`
@@ -522,6 +586,11 @@ async fn spawn_ready_nodes_change_stream_task(cluster: Cluster) {
                 break;
             };
             let mut cluster_guard = cluster.write().await;
+            cluster_guard.event_history.record_changes(
+                OffsetDateTime::now_utc().unix_timestamp(),
+                &previous_live_node_states,
+                &new_live_node_states,
+            );
             let previous_live_nodes = &mut cluster_guard.live_nodes;
 
             let events = compute_cluster_change_events(
@@ -553,6 +622,7 @@ struct InnerCluster {
     chitchat_handle: ChitchatHandle,
     live_nodes: BTreeMap<NodeId, ClusterNode>,
     change_stream_subscribers: Vec<mpsc::UnboundedSender<ClusterChange>>,
+    event_history: ClusterEventHistory,
     ready_members_rx: watch::Receiver<Vec<ClusterMember>>,
 }
 
@@ -608,6 +678,28 @@ pub struct ClusterSnapshot {
     pub chitchat_state_snapshot: ClusterStateSnapshot,
 }
 
+/// Key-value state of a live node.
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct NodeKeyValues {
+    #[schema(example = "node-1")]
+    /// The unique identifier of the node in the cluster.
+    pub node_id: String,
+
+    #[schema(example = "1683736537")]
+    /// A numeric identifier incremented every time the node leaves and rejoins the cluster.
+    pub generation_id: u64,
+
+    #[schema(
+        value_type = Object,
+        example = json!({
+            "grpc_advertise_addr": "127.0.0.1:8080",
+            "enabled_services": "searcher",
+        })
+    )]
+    /// The key-value pairs of the node state.
+    pub key_values: BTreeMap<String, String>,
+}
+
 /// Computes the gRPC port from the listen address for tests.
 #[cfg(any(test, feature = "testsuite"))]
 pub fn grpc_addr_from_listen_addr_for_test(listen_addr: SocketAddr) -> SocketAddr {
@@ -636,6 +728,7 @@ pub async fn create_cluster_for_test_with_id(
         grpc_advertise_addr: grpc_addr_from_listen_addr_for_test(gossip_advertise_addr),
         indexing_tasks: Vec::new(),
         indexing_cpu_capacity: PIPELINE_FULL_CAPACITY,
+        node_labels: Default::default(),
     };
     let failure_detector_config = create_failure_detector_config_for_test();
     let cluster = Cluster::join(
@@ -704,10 +797,12 @@ mod tests {
     use itertools::Itertools;
     use quickwit_common::test_utils::wait_until_predicate;
     use quickwit_config::service::QuickwitService;
+    use quickwit_config::NodeLabels;
     use quickwit_proto::indexing::IndexingTask;
     use rand::Rng;
 
     use super::*;
+    use crate::ClusterEventKind;
 
     #[tokio::test]
     async fn test_single_node_cluster_readiness() {
@@ -840,6 +935,57 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_cluster_events() {
+        let transport = ChannelTransport::default();
+        let node_1 = create_cluster_for_test(Vec::new(), &[], &transport, true)
+            .await
+            .unwrap();
+        let peer_seeds = vec![node_1.gossip_listen_addr.to_string()];
+        let node_2 = create_cluster_for_test(peer_seeds, &[], &transport, true)
+            .await
+            .unwrap();
+        node_2.set_self_key_value("foo", "bar").await;
+
+        wait_until_predicate(
+            || async {
+                node_1.events().await.iter().any(|event| {
+                    event.node_id == node_2.self_node_id()
+                        && event.kind
+                            == ClusterEventKind::KeyUpdated {
+                                key: "foo".to_string(),
+                                value: "bar".to_string(),
+                            }
+                })
+            },
+            Duration::from_secs(5),
+            Duration::from_millis(100),
+        )
+        .await
+        .unwrap();
+
+        let events = node_1.events().await;
+        assert!(events.iter().any(|event| {
+            event.node_id == node_2.self_node_id() && event.kind == ClusterEventKind::NodeJoined
+        }));
+        assert!(events
+            .windows(2)
+            .all(|window| window[0].timestamp <= window[1].timestamp));
+
+        let live_nodes_key_values = node_1.live_nodes_key_values().await;
+        assert_eq!(live_nodes_key_values.len(), 2);
+
+        let node_2_key_values = live_nodes_key_values
+            .iter()
+            .find(|node_key_values| node_key_values.node_id == node_2.self_node_id())
+            .unwrap();
+        assert_eq!(node_2_key_values.key_values.get("foo").unwrap(), "bar");
+        assert_eq!(
+            node_2_key_values.key_values.get(READINESS_KEY).unwrap(),
+            READINESS_VALUE_READY
+        );
+    }
+
     #[tokio::test]
     async fn test_multi_node_cluster_readiness() {
         let transport = ChannelTransport::default();
@@ -910,6 +1056,9 @@ mod tests {
         cluster2
             .set_self_key_value(GRPC_ADVERTISE_ADDR_KEY, "127.0.0.1:1001")
             .await;
+        cluster2
+            .set_self_key_value(format!("{NODE_LABEL_PREFIX}zone"), "eu-west-1a")
+            .await;
         cluster2
             .update_self_node_indexing_tasks(&[indexing_task1.clone(), indexing_task2.clone()])
             .await
@@ -932,6 +1081,7 @@ mod tests {
             HashSet::from_iter([QuickwitService::Indexer])
         );
         assert!(member_node_1.indexing_tasks.is_empty());
+        assert!(member_node_1.node_labels.is_empty());
         assert_eq!(
             member_node_2.grpc_advertise_addr,
             ([127, 0, 0, 1], 1001).into()
@@ -945,6 +1095,10 @@ mod tests {
             &member_node_2.indexing_tasks,
             &[indexing_task1, indexing_task2]
         );
+        assert_eq!(
+            member_node_2.node_labels,
+            NodeLabels::from_iter([("zone".to_string(), "eu-west-1a".to_string())])
+        );
     }
 
     #[tokio::test]
@@ -1105,6 +1259,41 @@ mod tests {
         assert_eq!(ready_members[0].indexing_tasks.len(), 1);
     }
 
+    #[tokio::test]
+    async fn test_update_self_node_source_backlogs() {
+        let transport = ChannelTransport::default();
+        let node = create_cluster_for_test(Vec::new(), &["indexer"], &transport, true)
+            .await
+            .unwrap();
+        let pipeline_id = IndexingPipelineId {
+            node_id: "test-node".to_string(),
+            index_uid: "test-index:0".into(),
+            source_id: "test-source".to_string(),
+            pipeline_uid: PipelineUid::from_u128(1),
+        };
+        let partition_backlogs = BTreeMap::from_iter([("0".to_string(), 10), ("1".to_string(), 0)]);
+        let source_backlogs = HashMap::from_iter([(&pipeline_id, &partition_backlogs)]);
+        node.update_self_node_source_backlogs(&source_backlogs)
+            .await;
+
+        let key = format!(
+            "{INDEXER_SOURCE_BACKLOG_PREFIX}test-index:0:test-source:{}",
+            pipeline_id.pipeline_uid
+        );
+        {
+            let chitchat_handle = node.chitchat().await;
+            let mut chitchat_guard = chitchat_handle.lock().await;
+            let value = chitchat_guard.self_node_state().get(&key).unwrap();
+            assert_eq!(value, r#"{"0":10,"1":0}"#);
+        }
+        node.update_self_node_source_backlogs(&HashMap::new()).await;
+        {
+            let chitchat_handle = node.chitchat().await;
+            let mut chitchat_guard = chitchat_handle.lock().await;
+            assert!(chitchat_guard.self_node_state().get(&key).is_none());
+        }
+    }
+
     #[tokio::test]
     async fn test_cluster_id_isolation() -> anyhow::Result<()> {
         quickwit_common::setup_logging_for_tests();
@@ -0,0 +1,213 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{BTreeMap, VecDeque};
+
+use chitchat::{ChitchatId, NodeState};
+use quickwit_common::sorted_iter::{KeyDiff, SortedByKeyIterator};
+use serde::{Deserialize, Serialize};
+
+/// Maximum number of events retained by the cluster event history. Older events are evicted
+/// first.
+const CLUSTER_EVENT_HISTORY_CAPACITY: usize = 1_000;
+
+/// Membership or state change observed by the node.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClusterEventKind {
+    /// The node has joined the cluster, or rejoined it with a new generation ID.
+    NodeJoined,
+    /// The node has left the cluster, i.e. it is considered dead by the failure detector.
+    NodeLeft,
+    /// A key of the node state was set.
+    KeyUpdated { key: String, value: String },
+    /// A key of the node state was marked for deletion.
+    KeyDeleted { key: String },
+}
+
+/// Timestamped membership or state change observed by the node.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ClusterEvent {
+    /// Unix timestamp (seconds) at which the change was observed.
+    pub timestamp: i64,
+    /// ID of the node affected by the change.
+    pub node_id: String,
+    /// Generation ID of the node affected by the change.
+    pub generation_id: u64,
+    #[serde(flatten)]
+    pub kind: ClusterEventKind,
+}
+
+/// Ring buffer of the latest cluster events.
+pub(crate) struct ClusterEventHistory {
+    events: VecDeque<ClusterEvent>,
+    capacity: usize,
+}
+
+impl Default for ClusterEventHistory {
+    fn default() -> Self {
+        Self::with_capacity(CLUSTER_EVENT_HISTORY_CAPACITY)
+    }
+}
+
+impl ClusterEventHistory {
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            events: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Returns the retained events, from the oldest to the most recent.
+    pub fn events(&self) -> Vec<ClusterEvent> {
+        self.events.iter().cloned().collect()
+    }
+
+    fn push(&mut self, timestamp: i64, chitchat_id: &ChitchatId, kind: ClusterEventKind) {
+        if self.events.len() == self.capacity {
+            self.events.pop_front();
+        }
+        let event = ClusterEvent {
+            timestamp,
+            node_id: chitchat_id.node_id.clone(),
+            generation_id: chitchat_id.generation_id,
+            kind,
+        };
+        self.events.push_back(event);
+    }
+
+    /// Compares the previous and new states of the live nodes and records the nodes that joined
+    /// or left the cluster and the keys that were updated or deleted since.
+    pub fn record_changes(
+        &mut self,
+        timestamp: i64,
+        previous_node_states: &BTreeMap<ChitchatId, NodeState>,
+        new_node_states: &BTreeMap<ChitchatId, NodeState>,
+    ) {
+        for key_diff in previous_node_states
+            .iter()
+            .diff_by_key(new_node_states.iter())
+        {
+            match key_diff {
+                KeyDiff::Added(chitchat_id, _node_state) => {
+                    self.push(timestamp, chitchat_id, ClusterEventKind::NodeJoined);
+                }
+                KeyDiff::Unchanged(chitchat_id, previous_node_state, new_node_state) => {
+                    let previous_max_version = previous_node_state.max_version();
+
+                    if previous_max_version == new_node_state.max_version() {
+                        continue;
+                    }
+                    for (key, versioned_value) in new_node_state.iter_prefix("") {
+                        if versioned_value.version <= previous_max_version {
+                            continue;
+                        }
+                        let kind = if versioned_value.tombstone.is_some() {
+                            ClusterEventKind::KeyDeleted {
+                                key: key.to_string(),
+                            }
+                        } else {
+                            ClusterEventKind::KeyUpdated {
+                                key: key.to_string(),
+                                value: versioned_value.value.clone(),
+                            }
+                        };
+                        self.push(timestamp, chitchat_id, kind);
+                    }
+                }
+                KeyDiff::Removed(chitchat_id, _node_state) => {
+                    self.push(timestamp, chitchat_id, ClusterEventKind::NodeLeft);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cluster_event_history_record_changes() {
+        let mut history = ClusterEventHistory::default();
+
+        let chitchat_id_1 = ChitchatId::for_local_test(1_001);
+        let chitchat_id_2 = ChitchatId::for_local_test(1_002);
+
+        let mut node_state_1 = NodeState::for_test();
+        node_state_1.set("foo", "bar");
+        let previous_node_states = BTreeMap::from_iter([(chitchat_id_1.clone(), node_state_1)]);
+
+        let mut new_node_states = previous_node_states.clone();
+        let new_node_state_1 = new_node_states.get_mut(&chitchat_id_1).unwrap();
+        new_node_state_1.set("qux", "quux");
+        new_node_state_1.mark_for_deletion("foo");
+        new_node_states.insert(chitchat_id_2.clone(), NodeState::for_test());
+
+        history.record_changes(1, &previous_node_states, &new_node_states);
+        history.record_changes(2, &new_node_states, &new_node_states);
+        history.record_changes(3, &new_node_states, &previous_node_states);
+
+        let events = history.events();
+        assert_eq!(events.len(), 4);
+
+        assert_eq!(events[0].node_id, chitchat_id_1.node_id);
+        assert_eq!(events[0].timestamp, 1);
+        assert_eq!(
+            events[0].kind,
+            ClusterEventKind::KeyDeleted {
+                key: "foo".to_string()
+            }
+        );
+        assert_eq!(events[1].node_id, chitchat_id_1.node_id);
+        assert_eq!(
+            events[1].kind,
+            ClusterEventKind::KeyUpdated {
+                key: "qux".to_string(),
+                value: "quux".to_string()
+            }
+        );
+        assert_eq!(events[2].node_id, chitchat_id_2.node_id);
+        assert_eq!(events[2].timestamp, 1);
+        assert_eq!(events[2].kind, ClusterEventKind::NodeJoined);
+
+        assert_eq!(events[3].node_id, chitchat_id_2.node_id);
+        assert_eq!(events[3].timestamp, 3);
+        assert_eq!(events[3].kind, ClusterEventKind::NodeLeft);
+    }
+
+    #[test]
+    fn test_cluster_event_history_evicts_oldest_events() {
+        let mut history = ClusterEventHistory::with_capacity(2);
+
+        let chitchat_id = ChitchatId::for_local_test(1_001);
+        let node_states = BTreeMap::from_iter([(chitchat_id, NodeState::for_test())]);
+
+        history.record_changes(1, &BTreeMap::new(), &node_states);
+        history.record_changes(2, &node_states, &BTreeMap::new());
+        history.record_changes(3, &BTreeMap::new(), &node_states);
+
+        let events = history.events();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].timestamp, 2);
+        assert_eq!(events[0].kind, ClusterEventKind::NodeLeft);
+        assert_eq!(events[1].timestamp, 3);
+        assert_eq!(events[1].kind, ClusterEventKind::NodeJoined);
+    }
+}
@@ -21,6 +21,7 @@
 
 mod change;
 mod cluster;
+mod event_history;
 mod member;
 mod node;
 
@@ -38,7 +39,8 @@ pub use crate::change::ClusterChange;
 pub use crate::cluster::{
     create_cluster_for_test, create_cluster_for_test_with_id, grpc_addr_from_listen_addr_for_test,
 };
-pub use crate::cluster::{Cluster, ClusterSnapshot, NodeIdSchema};
+pub use crate::cluster::{Cluster, ClusterSnapshot, NodeIdSchema, NodeKeyValues};
+pub use crate::event_history::{ClusterEvent, ClusterEventKind};
 pub use crate::member::{ClusterMember, INDEXING_CPU_CAPACITY_KEY};
 pub use crate::node::ClusterNode;
 
@@ -84,6 +86,7 @@ pub async fn start_cluster_service(node_config: &NodeConfig) -> anyhow::Result<C
         grpc_advertise_addr: node_config.grpc_advertise_addr,
         indexing_tasks,
         indexing_cpu_capacity,
+        node_labels: node_config.node_labels.clone(),
     };
     let cluster = Cluster::join(
         cluster_id,
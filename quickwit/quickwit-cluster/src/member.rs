@@ -23,6 +23,7 @@ use std::str::FromStr;
 
 use anyhow::Context;
 use chitchat::{ChitchatId, NodeState};
+use quickwit_config::NodeLabels;
 use quickwit_proto::indexing::{CpuCapacity, IndexingTask};
 use quickwit_proto::types::NodeId;
 use tracing::{error, warn};
@@ -34,6 +35,7 @@ use crate::{GenerationId, QuickwitService};
 pub(crate) const GRPC_ADVERTISE_ADDR_KEY: &str = "grpc_advertise_addr";
 pub(crate) const ENABLED_SERVICES_KEY: &str = "enabled_services";
 pub(crate) const PIPELINE_METRICS_PREFIX: &str = "pipeline_metrics:";
+pub(crate) const NODE_LABEL_PREFIX: &str = "node_label:";
 
 // Readiness key and values used to store node's readiness in Chitchat state.
 pub(crate) const READINESS_KEY: &str = "readiness";
@@ -92,6 +94,9 @@ pub struct ClusterMember {
     pub indexing_tasks: Vec<IndexingTask>,
     /// Indexing cpu capacity of the node expressed in milli cpu.
     pub indexing_cpu_capacity: CpuCapacity,
+    /// Labels declared in the node config, matched against the placement constraints of the
+    /// indexes and sources.
+    pub node_labels: NodeLabels,
     pub is_ready: bool,
 }
 
@@ -123,6 +128,17 @@ fn parse_indexing_cpu_capacity(node_state: &NodeState) -> CpuCapacity {
     }
 }
 
+fn parse_node_labels(node_state: &NodeState) -> NodeLabels {
+    node_state
+        .iter_prefix(NODE_LABEL_PREFIX)
+        .filter(|(_, versioned_value)| versioned_value.tombstone.is_none())
+        .map(|(key, versioned_value)| {
+            let label = key.strip_prefix(NODE_LABEL_PREFIX).unwrap_or(key);
+            (label.to_string(), versioned_value.value.clone())
+        })
+        .collect()
+}
+
 // Builds a cluster member from a [`NodeState`].
 pub(crate) fn build_cluster_member(
     chitchat_id: ChitchatId,
@@ -144,6 +160,7 @@ pub(crate) fn build_cluster_member(
     let grpc_advertise_addr = node_state.grpc_advertise_addr()?;
     let indexing_tasks = parse_indexing_tasks(node_state);
     let indexing_cpu_capacity = parse_indexing_cpu_capacity(node_state);
+    let node_labels = parse_node_labels(node_state);
     let member = ClusterMember {
         node_id: chitchat_id.node_id.into(),
         generation_id: chitchat_id.generation_id.into(),
@@ -153,6 +170,7 @@ pub(crate) fn build_cluster_member(
         grpc_advertise_addr,
         indexing_tasks,
         indexing_cpu_capacity,
+        node_labels,
     };
     Ok(member)
 }
@@ -24,6 +24,7 @@ use std::sync::Arc;
 
 use chitchat::{ChitchatId, NodeState};
 use quickwit_config::service::QuickwitService;
+use quickwit_config::NodeLabels;
 use quickwit_proto::indexing::{CpuCapacity, IndexingTask};
 use tonic::transport::Channel;
 
@@ -50,6 +51,7 @@ impl ClusterNode {
             grpc_advertise_addr: member.grpc_advertise_addr,
             indexing_tasks: member.indexing_tasks,
             indexing_capacity: member.indexing_cpu_capacity,
+            node_labels: member.node_labels,
             is_ready: member.is_ready,
             is_self_node,
         };
@@ -111,6 +113,10 @@ impl ClusterNode {
         self.inner.indexing_capacity
     }
 
+    pub fn node_labels(&self) -> &NodeLabels {
+        &self.inner.node_labels
+    }
+
     pub fn is_ready(&self) -> bool {
         self.inner.is_ready
     }
@@ -149,6 +155,7 @@ struct InnerNode {
     grpc_advertise_addr: SocketAddr,
     indexing_tasks: Vec<IndexingTask>,
     indexing_capacity: CpuCapacity,
+    node_labels: NodeLabels,
     is_ready: bool,
     is_self_node: bool,
 }
@@ -642,7 +642,7 @@ where
         request: HelloRequest,
     ) -> crate::HelloResult<HelloResponse> {
         self.inner
-            .hello(request)
+            .hello(quickwit_common::deadline::make_grpc_request(request))
             .await
             .map(|response| response.into_inner())
             .map_err(|error| error.into())
@@ -652,7 +652,7 @@ where
         request: GoodbyeRequest,
     ) -> crate::HelloResult<GoodbyeResponse> {
         self.inner
-            .goodbye(request)
+            .goodbye(quickwit_common::deadline::make_grpc_request(request))
             .await
             .map(|response| response.into_inner())
             .map_err(|error| error.into())
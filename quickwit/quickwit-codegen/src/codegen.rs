@@ -1179,10 +1179,17 @@ fn generate_grpc_client_adapter_methods(context: &CodegenContext) -> TokenStream
         } else {
             quote! { |response| response.into_inner() }
         };
+        // Unary calls inherit the deadline of the request being served, if any. Streams are
+        // long-lived and are not bound by it.
+        let request = if syn_method.client_streaming || syn_method.server_streaming {
+            quote! { request }
+        } else {
+            quote! { quickwit_common::deadline::make_grpc_request(request) }
+        };
         let method = quote! {
             async fn #method_name(&mut self, request: #request_type) -> #result_type<#response_type> {
                 self.inner
-                    .#method_name(request)
+                    .#method_name(#request)
                     .await
                     .map(#into_response_type)
                     .map_err(|error| error.into())
@@ -0,0 +1,174 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use anyhow::{bail, ensure};
+
+const FORMAT_VERSION: u8 = 1;
+
+const HEADER_NUM_BYTES: usize = 10;
+
+const MAX_NUM_HASHES: u8 = 16;
+
+/// A Bloom filter: a compact set of byte strings that can answer "definitely absent" or "maybe
+/// present". It never returns false negatives.
+///
+/// The hash functions are stable, so a filter serialized with [`BloomFilter::to_bytes`] can be
+/// queried by any other node or version.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BloomFilter {
+    num_hashes: u8,
+    num_bits: u64,
+    words: Vec<u64>,
+}
+
+impl BloomFilter {
+    /// Creates an empty filter sized to hold `num_items` items with the given false positive rate.
+    pub fn with_false_positive_rate(num_items: usize, false_positive_rate: f64) -> Self {
+        let false_positive_rate = false_positive_rate.clamp(1e-9, 0.5);
+        let num_items = num_items.max(1) as f64;
+        let ln_2 = std::f64::consts::LN_2;
+        let num_bits = (-num_items * false_positive_rate.ln() / (ln_2 * ln_2)).ceil() as u64;
+        let num_words = (num_bits.max(64) + 63) / 64;
+        let num_bits = num_words * 64;
+        let num_hashes = ((num_bits as f64 / num_items) * ln_2)
+            .round()
+            .clamp(1.0, MAX_NUM_HASHES as f64) as u8;
+        Self {
+            num_hashes,
+            num_bits,
+            words: vec![0; num_words as usize],
+        }
+    }
+
+    pub fn insert(&mut self, item: &[u8]) {
+        for bit in self.bit_positions(item) {
+            self.words[(bit / 64) as usize] |= 1 << (bit % 64);
+        }
+    }
+
+    /// Returns `false` if the item was definitely not inserted in the filter.
+    pub fn contains(&self, item: &[u8]) -> bool {
+        self.bit_positions(item)
+            .all(|bit| self.words[(bit / 64) as usize] & (1 << (bit % 64)) != 0)
+    }
+
+    fn bit_positions(&self, item: &[u8]) -> impl Iterator<Item = u64> {
+        // Double hashing: the `i`-th hash is `h1 + i * h2`.
+        let h1 = mix64(fnv1a(item, 0xcbf2_9ce4_8422_2325));
+        let h2 = mix64(fnv1a(item, 0x9e37_79b9_7f4a_7c15)) | 1;
+        let num_bits = self.num_bits;
+        (0..self.num_hashes as u64).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % num_bits)
+    }
+
+    /// Serializes the filter.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_NUM_BYTES + self.words.len() * 8);
+        bytes.push(FORMAT_VERSION);
+        bytes.push(self.num_hashes);
+        bytes.extend_from_slice(&self.num_bits.to_le_bytes());
+
+        for word in &self.words {
+            bytes.extend_from_slice(&word.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Deserializes a filter serialized with [`BloomFilter::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        ensure!(bytes.len() >= HEADER_NUM_BYTES, "bloom filter is truncated");
+
+        if bytes[0] != FORMAT_VERSION {
+            bail!("unsupported bloom filter format version `{}`", bytes[0]);
+        }
+        let num_hashes = bytes[1];
+        ensure!(
+            (1..=MAX_NUM_HASHES).contains(&num_hashes),
+            "invalid bloom filter number of hashes `{num_hashes}`"
+        );
+        let num_bits = u64::from_le_bytes(bytes[2..HEADER_NUM_BYTES].try_into()?);
+        let words_bytes = &bytes[HEADER_NUM_BYTES..];
+        ensure!(
+            num_bits > 0 && num_bits % 64 == 0 && words_bytes.len() as u64 * 8 == num_bits,
+            "bloom filter is corrupted"
+        );
+        let words = words_bytes
+            .chunks_exact(8)
+            .map(|word_bytes| u64::from_le_bytes(word_bytes.try_into().unwrap()))
+            .collect();
+        Ok(Self {
+            num_hashes,
+            num_bits,
+            words,
+        })
+    }
+}
+
+fn fnv1a(bytes: &[u8], offset_basis: u64) -> u64 {
+    bytes.iter().fold(offset_basis, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Finalizer of `splitmix64`, improving the distribution of the low bits of FNV hashes.
+fn mix64(mut hash: u64) -> u64 {
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    hash ^ (hash >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bloom_filter() {
+        let mut bloom_filter = BloomFilter::with_false_positive_rate(1_000, 0.01);
+
+        for i in 0..1_000 {
+            bloom_filter.insert(format!("doc-{i}").as_bytes());
+        }
+        for i in 0..1_000 {
+            assert!(bloom_filter.contains(format!("doc-{i}").as_bytes()));
+        }
+        let num_false_positives = (1_000..11_000)
+            .filter(|i| bloom_filter.contains(format!("doc-{i}").as_bytes()))
+            .count();
+        assert!(num_false_positives < 200, "{num_false_positives}");
+    }
+
+    #[test]
+    fn test_bloom_filter_serialization() {
+        let mut bloom_filter = BloomFilter::with_false_positive_rate(10, 0.01);
+        bloom_filter.insert(b"foo");
+        bloom_filter.insert(b"bar");
+
+        let bloom_filter_bytes = bloom_filter.to_bytes();
+        let deserialized_bloom_filter = BloomFilter::from_bytes(&bloom_filter_bytes).unwrap();
+        assert_eq!(deserialized_bloom_filter, bloom_filter);
+        assert!(deserialized_bloom_filter.contains(b"foo"));
+        assert!(deserialized_bloom_filter.contains(b"bar"));
+
+        BloomFilter::from_bytes(&bloom_filter_bytes[..5]).unwrap_err();
+        BloomFilter::from_bytes(&bloom_filter_bytes[..bloom_filter_bytes.len() - 1]).unwrap_err();
+
+        let mut bloom_filter_bytes = bloom_filter_bytes;
+        bloom_filter_bytes[0] = 0;
+        BloomFilter::from_bytes(&bloom_filter_bytes).unwrap_err();
+    }
+}
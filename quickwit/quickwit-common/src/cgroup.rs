@@ -0,0 +1,186 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Resource limits enforced by cgroup v2 on the current process, typically set by the container
+//! runtime.
+
+use std::path::{Path, PathBuf};
+
+use bytesize::ByteSize;
+use once_cell::sync::OnceCell;
+use serde::Serialize;
+
+const CGROUP_V2_MOUNT_PATH: &str = "/sys/fs/cgroup";
+
+const PROC_SELF_CGROUP_PATH: &str = "/proc/self/cgroup";
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Serialize)]
+pub struct CgroupLimits {
+    /// Maximum CPU bandwidth the process may use, in milli-CPUs (1000 = one CPU), read from
+    /// `cpu.max`.
+    pub cpu_limit_millis: Option<u64>,
+    /// Maximum amount of memory the process may use before being OOM-killed, read from
+    /// `memory.max`.
+    pub memory_limit: Option<ByteSize>,
+}
+
+impl CgroupLimits {
+    /// Returns the limits of the cgroup of the current process. They are read once, the first
+    /// time this function is called. Both limits are `None` if the host does not use cgroup v2.
+    pub fn get() -> &'static CgroupLimits {
+        static INSTANCE: OnceCell<CgroupLimits> = OnceCell::new();
+
+        INSTANCE.get_or_init(|| {
+            if !cfg!(target_os = "linux") {
+                return CgroupLimits::default();
+            }
+            let mount_path = Path::new(CGROUP_V2_MOUNT_PATH);
+            let cgroup_dir = std::fs::read_to_string(PROC_SELF_CGROUP_PATH)
+                .ok()
+                .and_then(|proc_self_cgroup| {
+                    parse_cgroup_path(&proc_self_cgroup)
+                        .map(|cgroup_path| mount_path.join(cgroup_path.trim_start_matches('/')))
+                })
+                .unwrap_or_else(|| mount_path.to_path_buf());
+            CgroupLimits::read(mount_path, &cgroup_dir)
+        })
+    }
+
+    /// Reads the limits of the cgroup located in `cgroup_dir` and of its ancestors up to
+    /// `mount_path`, keeping the most restrictive ones.
+    fn read(mount_path: &Path, cgroup_dir: &Path) -> CgroupLimits {
+        let mut cgroup_limits = CgroupLimits::default();
+
+        for dir in cgroup_dir
+            .ancestors()
+            .take_while(|dir| dir.starts_with(mount_path))
+        {
+            let cpu_limit_millis_opt =
+                read_cgroup_file(dir, "cpu.max").and_then(|content| parse_cpu_max(&content));
+            cgroup_limits.cpu_limit_millis =
+                min_opt(cgroup_limits.cpu_limit_millis, cpu_limit_millis_opt);
+
+            let memory_limit_opt =
+                read_cgroup_file(dir, "memory.max").and_then(|content| parse_memory_max(&content));
+            cgroup_limits.memory_limit = min_opt(cgroup_limits.memory_limit, memory_limit_opt);
+        }
+        cgroup_limits
+    }
+
+    /// Returns the number of CPUs the process may use, rounded up.
+    pub fn num_cpus(&self) -> Option<usize> {
+        self.cpu_limit_millis
+            .map(|cpu_limit_millis| ((cpu_limit_millis + 999) / 1_000).max(1) as usize)
+    }
+}
+
+fn read_cgroup_file(dir: &Path, file_name: &str) -> Option<String> {
+    let file_path: PathBuf = dir.join(file_name);
+    std::fs::read_to_string(file_path).ok()
+}
+
+fn min_opt<T: Ord>(left_opt: Option<T>, right_opt: Option<T>) -> Option<T> {
+    match (left_opt, right_opt) {
+        (Some(left), Some(right)) => Some(left.min(right)),
+        (left_opt, right_opt) => left_opt.or(right_opt),
+    }
+}
+
+/// Parses the content of `/proc/self/cgroup`. With cgroup v2, it consists of a single line
+/// formatted as `0::<path>`.
+fn parse_cgroup_path(proc_self_cgroup: &str) -> Option<&str> {
+    proc_self_cgroup
+        .lines()
+        .find_map(|line| line.strip_prefix("0::"))
+}
+
+/// Parses the content of `cpu.max`, formatted as `<quota> <period>` where the quota is `max` if
+/// the CPU bandwidth is not limited.
+fn parse_cpu_max(cpu_max: &str) -> Option<u64> {
+    let mut tokens = cpu_max.split_whitespace();
+    let quota: u64 = tokens.next()?.parse().ok()?;
+    let period: u64 = tokens.next()?.parse().ok()?;
+
+    if period == 0 {
+        return None;
+    }
+    Some(quota * 1_000 / period)
+}
+
+/// Parses the content of `memory.max`, which is `max` if the memory is not limited.
+fn parse_memory_max(memory_max: &str) -> Option<ByteSize> {
+    memory_max.trim().parse::<u64>().ok().map(ByteSize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cgroup_path() {
+        assert_eq!(parse_cgroup_path("0::/\n"), Some("/"));
+        assert_eq!(
+            parse_cgroup_path("0::/kubepods/pod-1/container-1\n"),
+            Some("/kubepods/pod-1/container-1")
+        );
+        assert_eq!(parse_cgroup_path("12:cpu,cpuacct:/docker/foo\n"), None);
+    }
+
+    #[test]
+    fn test_parse_cpu_max() {
+        assert_eq!(parse_cpu_max("max 100000\n"), None);
+        assert_eq!(parse_cpu_max("150000 100000\n"), Some(1_500));
+        assert_eq!(parse_cpu_max("50000 100000\n"), Some(500));
+        assert_eq!(parse_cpu_max("50000 0\n"), None);
+        assert_eq!(parse_cpu_max(""), None);
+    }
+
+    #[test]
+    fn test_parse_memory_max() {
+        assert_eq!(parse_memory_max("max\n"), None);
+        assert_eq!(parse_memory_max("1073741824\n"), Some(ByteSize::gib(1)));
+    }
+
+    #[test]
+    fn test_cgroup_limits_read() {
+        let mount_dir = tempfile::tempdir().unwrap();
+        let mount_path = mount_dir.path();
+        let cgroup_dir = mount_path.join("kubepods").join("container-1");
+        std::fs::create_dir_all(&cgroup_dir).unwrap();
+
+        let cgroup_limits = CgroupLimits::read(mount_path, &cgroup_dir);
+        assert_eq!(cgroup_limits, CgroupLimits::default());
+        assert_eq!(cgroup_limits.num_cpus(), None);
+
+        std::fs::write(mount_path.join("cpu.max"), "max 100000\n").unwrap();
+        std::fs::write(mount_path.join("memory.max"), "max\n").unwrap();
+        std::fs::write(
+            mount_path.join("kubepods").join("memory.max"),
+            "2147483648\n",
+        )
+        .unwrap();
+        std::fs::write(cgroup_dir.join("cpu.max"), "250000 100000\n").unwrap();
+        std::fs::write(cgroup_dir.join("memory.max"), "4294967296\n").unwrap();
+
+        let cgroup_limits = CgroupLimits::read(mount_path, &cgroup_dir);
+        assert_eq!(cgroup_limits.cpu_limit_millis, Some(2_500));
+        assert_eq!(cgroup_limits.memory_limit, Some(ByteSize::gib(2)));
+        assert_eq!(cgroup_limits.num_cpus(), Some(3));
+    }
+}
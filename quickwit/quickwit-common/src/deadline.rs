@@ -0,0 +1,237 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+//! Per-request deadlines.
+//!
+//! A deadline is attached to the task serving a request and is automatically propagated to the
+//! downstream gRPC calls issued while serving it, via the standard `grpc-timeout` header.
+//! Services can therefore give up on work that the caller is no longer waiting for.
+
+use std::time::Duration;
+
+use futures::Future;
+use http::{HeaderMap, HeaderValue};
+use tokio::time::Instant;
+
+/// Standard gRPC header carrying the timeout of a call.
+pub const GRPC_TIMEOUT_HEADER: &str = "grpc-timeout";
+
+/// Header that REST clients can set to bound the processing time of a request. The value is
+/// expressed as a human-readable duration, for instance `500ms` or `10s`.
+pub const REST_TIMEOUT_HEADER: &str = "x-quickwit-timeout";
+
+// The gRPC spec limits timeout values to 8 digits.
+const MAX_GRPC_TIMEOUT_VALUE: u128 = 99_999_999;
+
+tokio::task_local! {
+    static DEADLINE: Instant;
+}
+
+/// Runs `future` with the given deadline. If a deadline is already set for the current task,
+/// the earliest of the two deadlines wins.
+pub async fn with_deadline<F>(deadline: Instant, future: F) -> F::Output
+where F: Future {
+    let deadline = match current_deadline() {
+        Some(current_deadline) => current_deadline.min(deadline),
+        None => deadline,
+    };
+    DEADLINE.scope(deadline, future).await
+}
+
+/// Returns the deadline of the request served by the current task, if any.
+pub fn current_deadline() -> Option<Instant> {
+    DEADLINE.try_with(|deadline| *deadline).ok()
+}
+
+/// Returns the time left before the deadline of the request served by the current task, if any.
+/// Returns [`Duration::ZERO`] if the deadline has already passed.
+pub fn remaining_time() -> Option<Duration> {
+    current_deadline().map(|deadline| deadline.saturating_duration_since(Instant::now()))
+}
+
+/// Sets the `grpc-timeout` header of an outgoing request from the deadline of the current task.
+/// The header is left untouched when no deadline is set or when the caller already set a
+/// shorter timeout.
+pub fn propagate_deadline(headers: &mut HeaderMap) {
+    let Some(remaining_time) = remaining_time() else {
+        return;
+    };
+    if let Some(timeout) = headers
+        .get(GRPC_TIMEOUT_HEADER)
+        .and_then(parse_grpc_timeout)
+    {
+        if timeout <= remaining_time {
+            return;
+        }
+    }
+    headers.insert(GRPC_TIMEOUT_HEADER, format_grpc_timeout(remaining_time));
+}
+
+/// Wraps a message into a gRPC request bound by the deadline of the current task, if any.
+pub fn make_grpc_request<T>(message: T) -> tonic::Request<T> {
+    let mut request = tonic::Request::new(message);
+    if let Some(remaining_time) = remaining_time() {
+        request.set_timeout(remaining_time);
+    }
+    request
+}
+
+/// Parses the value of a `grpc-timeout` header, for instance `250m` or `10S`.
+pub fn parse_grpc_timeout(header_value: &HeaderValue) -> Option<Duration> {
+    let header_value = header_value.to_str().ok()?;
+    if header_value.len() < 2 || header_value.len() > 9 {
+        return None;
+    }
+    let (value, unit) = header_value.split_at(header_value.len() - 1);
+    let value: u64 = value.parse().ok()?;
+    let timeout = match unit {
+        "H" => Duration::from_secs(value * 3_600),
+        "M" => Duration::from_secs(value * 60),
+        "S" => Duration::from_secs(value),
+        "m" => Duration::from_millis(value),
+        "u" => Duration::from_micros(value),
+        "n" => Duration::from_nanos(value),
+        _ => return None,
+    };
+    Some(timeout)
+}
+
+/// Formats a timeout as a `grpc-timeout` header value using the finest unit that fits in the
+/// eight digits allowed by the gRPC spec.
+pub fn format_grpc_timeout(timeout: Duration) -> HeaderValue {
+    let header_value = if timeout.as_millis() <= MAX_GRPC_TIMEOUT_VALUE {
+        format!("{}m", timeout.as_millis())
+    } else if timeout.as_secs() as u128 <= MAX_GRPC_TIMEOUT_VALUE {
+        format!("{}S", timeout.as_secs())
+    } else {
+        format!(
+            "{}H",
+            (timeout.as_secs() / 3_600).min(MAX_GRPC_TIMEOUT_VALUE as u64)
+        )
+    };
+    HeaderValue::from_str(&header_value).expect("header value should be valid")
+}
+
+/// Parses the value of the `x-quickwit-timeout` header, for instance `500ms` or `10s`.
+pub fn parse_rest_timeout(header_value: &HeaderValue) -> Option<Duration> {
+    let header_value = header_value.to_str().ok()?;
+    humantime::parse_duration(header_value.trim()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_grpc_timeout() {
+        let parse = |value: &'static str| parse_grpc_timeout(&HeaderValue::from_static(value));
+        assert_eq!(parse("2H"), Some(Duration::from_secs(7_200)));
+        assert_eq!(parse("3M"), Some(Duration::from_secs(180)));
+        assert_eq!(parse("10S"), Some(Duration::from_secs(10)));
+        assert_eq!(parse("250m"), Some(Duration::from_millis(250)));
+        assert_eq!(parse("42u"), Some(Duration::from_micros(42)));
+        assert_eq!(parse("7n"), Some(Duration::from_nanos(7)));
+        assert_eq!(parse("m"), None);
+        assert_eq!(parse("10s"), None);
+        assert_eq!(parse("-1S"), None);
+        assert_eq!(parse("123456789S"), None);
+    }
+
+    #[test]
+    fn test_format_grpc_timeout() {
+        assert_eq!(format_grpc_timeout(Duration::ZERO), "0m");
+        assert_eq!(format_grpc_timeout(Duration::from_millis(1_500)), "1500m");
+        assert_eq!(format_grpc_timeout(Duration::from_secs(200_000)), "200000S");
+        assert_eq!(
+            format_grpc_timeout(Duration::from_secs(200_000_000)),
+            "55555H"
+        );
+    }
+
+    #[test]
+    fn test_parse_rest_timeout() {
+        let parse = |value: &'static str| parse_rest_timeout(&HeaderValue::from_static(value));
+        assert_eq!(parse("500ms"), Some(Duration::from_millis(500)));
+        assert_eq!(parse("10s"), Some(Duration::from_secs(10)));
+        assert_eq!(parse("ten seconds"), None);
+    }
+
+    #[tokio::test]
+    async fn test_with_deadline() {
+        assert!(current_deadline().is_none());
+        assert!(remaining_time().is_none());
+
+        let now = Instant::now();
+        let deadline = now + Duration::from_secs(10);
+
+        with_deadline(deadline, async move {
+            assert_eq!(current_deadline(), Some(deadline));
+            assert!(remaining_time().unwrap() <= Duration::from_secs(10));
+
+            // The earliest deadline wins.
+            with_deadline(now + Duration::from_secs(5), async move {
+                assert_eq!(current_deadline(), Some(now + Duration::from_secs(5)));
+            })
+            .await;
+
+            with_deadline(now + Duration::from_secs(20), async move {
+                assert_eq!(current_deadline(), Some(deadline));
+            })
+            .await;
+        })
+        .await;
+
+        assert!(current_deadline().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_propagate_deadline() {
+        let mut headers = HeaderMap::new();
+        propagate_deadline(&mut headers);
+        assert!(headers.get(GRPC_TIMEOUT_HEADER).is_none());
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+
+        with_deadline(deadline, async move {
+            let mut headers = HeaderMap::new();
+            propagate_deadline(&mut headers);
+            let timeout = parse_grpc_timeout(headers.get(GRPC_TIMEOUT_HEADER).unwrap()).unwrap();
+            assert!(timeout <= Duration::from_secs(10));
+            assert!(timeout > Duration::from_secs(5));
+
+            // A shorter timeout set by the caller is preserved.
+            let mut headers = HeaderMap::new();
+            headers.insert(GRPC_TIMEOUT_HEADER, HeaderValue::from_static("100m"));
+            propagate_deadline(&mut headers);
+            assert_eq!(headers.get(GRPC_TIMEOUT_HEADER).unwrap(), "100m");
+
+            // A longer one is shortened.
+            let mut headers = HeaderMap::new();
+            headers.insert(GRPC_TIMEOUT_HEADER, HeaderValue::from_static("1M"));
+            propagate_deadline(&mut headers);
+            assert_ne!(headers.get(GRPC_TIMEOUT_HEADER).unwrap(), "1M");
+
+            let request = make_grpc_request(());
+            assert!(request.metadata().get(GRPC_TIMEOUT_HEADER).is_some());
+        })
+        .await;
+
+        let request = make_grpc_request(());
+        assert!(request.metadata().get(GRPC_TIMEOUT_HEADER).is_none());
+    }
+}
@@ -22,6 +22,9 @@
 mod coolid;
 
 pub mod binary_heap;
+pub mod bloom_filter;
+pub mod cgroup;
+pub mod deadline;
 pub mod fs;
 pub mod io;
 mod kill_switch;
@@ -76,6 +79,11 @@ pub fn split_file(split_id: impl Display) -> String {
     format!("{split_id}.split")
 }
 
+/// Returns the name of the file storing the doc IDs Bloom filter of a split.
+pub fn doc_ids_sidecar_file(split_id: impl Display) -> String {
+    format!("{split_id}.docids")
+}
+
 pub fn get_from_env<T: FromStr + Debug>(key: &str, default_value: T) -> T {
     if let Ok(value_str) = std::env::var(key) {
         if let Ok(value) = T::from_str(&value_str) {
@@ -18,7 +18,10 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 use std::borrow::{Borrow, Cow};
+use std::collections::HashSet;
+use std::sync::RwLock;
 
+use once_cell::sync::OnceCell;
 use prometheus::{Encoder, HistogramOpts, Opts, TextEncoder};
 pub use prometheus::{
     Histogram, HistogramTimer, HistogramVec as PrometheusHistogramVec, IntCounter,
@@ -156,6 +159,68 @@ impl Drop for GaugeGuard {
     }
 }
 
+/// Label value under which the metrics of the indexes beyond the index label cardinality limit are
+/// reported.
+pub const OTHER_INDEX_LABEL: &str = "other";
+
+/// Bounds the cardinality of the `index` label: the first `max_index_labels` index IDs observed
+/// are used as label values, while the following ones are collapsed into [`OTHER_INDEX_LABEL`].
+pub struct IndexLabels {
+    max_index_labels: usize,
+    index_ids: RwLock<HashSet<String>>,
+}
+
+impl IndexLabels {
+    pub fn new(max_index_labels: usize) -> Self {
+        Self {
+            max_index_labels,
+            index_ids: RwLock::new(HashSet::new()),
+        }
+    }
+
+    /// Returns the `index` label value for `index_id`.
+    pub fn label<'a>(&self, index_id: &'a str) -> &'a str {
+        if self
+            .index_ids
+            .read()
+            .expect("lock should not be poisoned")
+            .contains(index_id)
+        {
+            return index_id;
+        }
+        let mut index_ids = self.index_ids.write().expect("lock should not be poisoned");
+
+        if index_ids.contains(index_id) {
+            return index_id;
+        }
+        if index_ids.len() < self.max_index_labels {
+            index_ids.insert(index_id.to_string());
+            return index_id;
+        }
+        OTHER_INDEX_LABEL
+    }
+}
+
+static INDEX_LABELS: OnceCell<IndexLabels> = OnceCell::new();
+
+/// Enables the metrics labeled by index ID. This function should be called once at startup.
+pub fn enable_index_labels(max_index_labels: usize) {
+    if INDEX_LABELS
+        .set(IndexLabels::new(max_index_labels))
+        .is_err()
+    {
+        tracing::warn!("index labels are already enabled");
+    }
+}
+
+/// Returns the `index` label value for `index_id`, or `None` if the metrics labeled by index ID
+/// are disabled.
+pub fn index_label(index_id: &str) -> Option<&str> {
+    INDEX_LABELS
+        .get()
+        .map(|index_labels| index_labels.label(index_id))
+}
+
 pub fn metrics_text_payload() -> String {
     let metric_families = prometheus::gather();
     let mut buffer = Vec::new();
@@ -163,3 +228,21 @@ pub fn metrics_text_payload() -> String {
     let _ = encoder.encode(&metric_families, &mut buffer); // TODO avoid ignoring the error.
     String::from_utf8_lossy(&buffer).to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_labels() {
+        let index_labels = IndexLabels::new(2);
+        assert_eq!(index_labels.label("index-1"), "index-1");
+        assert_eq!(index_labels.label("index-2"), "index-2");
+        assert_eq!(index_labels.label("index-3"), OTHER_INDEX_LABEL);
+        assert_eq!(index_labels.label("index-1"), "index-1");
+        assert_eq!(index_labels.label("index-2"), "index-2");
+
+        let index_labels = IndexLabels::new(0);
+        assert_eq!(index_labels.label("index-1"), OTHER_INDEX_LABEL);
+    }
+}
@@ -39,5 +39,17 @@ pub const SCROLL_BATCH_LEN: usize = 1_000;
 /// Prefix used in chitchat to broadcast the list of primary shards hosted by a leader.
 pub const INGESTER_PRIMARY_SHARDS_PREFIX: &str = "ingester.primary_shards:";
 
+/// Key used in chitchat to broadcast whether the write-ahead log of an ingester exceeds its disk
+/// high watermark.
+pub const INGESTER_DISK_WATERMARK_EXCEEDED_KEY: &str = "ingester.disk_watermark_exceeded";
+
+/// Prefix used in chitchat to broadcast the backlogs of the partitions consumed by the indexing
+/// pipelines of an indexer.
+pub const INDEXER_SOURCE_BACKLOG_PREFIX: &str = "indexer.source_backlog:";
+
+/// Key used in chitchat to broadcast the overrides of the searcher config applied at runtime with
+/// the searcher config API.
+pub const SEARCHER_CONFIG_OVERRIDES_KEY: &str = "searcher.config_overrides";
+
 /// File name for the encoded list of fields in the split
 pub const SPLIT_FIELDS_FILE_NAME: &str = "split_fields";
@@ -0,0 +1,179 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use http::{HeaderValue, Request, Response, StatusCode};
+use tokio::time::Instant;
+use tower::{Layer, Service};
+
+use super::BoxFuture;
+use crate::deadline::{
+    parse_grpc_timeout, parse_rest_timeout, with_deadline, GRPC_TIMEOUT_HEADER, REST_TIMEOUT_HEADER,
+};
+
+/// Attaches the deadline carried by the headers of incoming requests to the task serving them so
+/// that it is propagated to downstream gRPC calls.
+#[derive(Clone)]
+pub struct Deadline<S> {
+    inner: S,
+    header_name: &'static str,
+    parse_timeout_fn: fn(&HeaderValue) -> Option<Duration>,
+    enforce: bool,
+}
+
+impl<S, B, ResBody> Service<Request<B>> for Deadline<S>
+where
+    S: Service<Request<B>, Response = Response<ResBody>>,
+    S::Future: Send + 'static,
+    S::Error: Send + 'static,
+    ResBody: Default + Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<S::Response, S::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<B>) -> Self::Future {
+        let timeout_opt = request
+            .headers()
+            .get(self.header_name)
+            .and_then(self.parse_timeout_fn);
+        let inner = self.inner.call(request);
+
+        let Some(timeout) = timeout_opt else {
+            return Box::pin(inner);
+        };
+        let deadline = Instant::now() + timeout;
+
+        if !self.enforce {
+            return Box::pin(with_deadline(deadline, inner));
+        }
+        Box::pin(async move {
+            match tokio::time::timeout_at(deadline, with_deadline(deadline, inner)).await {
+                Ok(result) => result,
+                Err(_elapsed) => {
+                    let mut response = Response::new(ResBody::default());
+                    *response.status_mut() = StatusCode::GATEWAY_TIMEOUT;
+                    Ok(response)
+                }
+            }
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DeadlineLayer {
+    header_name: &'static str,
+    parse_timeout_fn: fn(&HeaderValue) -> Option<Duration>,
+    enforce: bool,
+}
+
+impl DeadlineLayer {
+    /// Reads the deadline of gRPC requests from the standard `grpc-timeout` header. The timeout
+    /// itself is enforced by the gRPC server.
+    pub fn grpc() -> Self {
+        Self {
+            header_name: GRPC_TIMEOUT_HEADER,
+            parse_timeout_fn: parse_grpc_timeout,
+            enforce: false,
+        }
+    }
+
+    /// Reads the deadline of REST requests from the `x-quickwit-timeout` header and responds
+    /// with a `504 Gateway Timeout` when it elapses.
+    pub fn rest() -> Self {
+        Self {
+            header_name: REST_TIMEOUT_HEADER,
+            parse_timeout_fn: parse_rest_timeout,
+            enforce: true,
+        }
+    }
+}
+
+impl<S> Layer<S> for DeadlineLayer {
+    type Service = Deadline<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Deadline {
+            inner,
+            header_name: self.header_name,
+            parse_timeout_fn: self.parse_timeout_fn,
+            enforce: self.enforce,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::deadline::remaining_time;
+
+    #[tokio::test]
+    async fn test_deadline_layer_grpc() {
+        let service = DeadlineLayer::grpc().layer(tower::service_fn(|_: Request<()>| async {
+            let status = if remaining_time().is_some() {
+                StatusCode::OK
+            } else {
+                StatusCode::NO_CONTENT
+            };
+            let mut response = Response::new(());
+            *response.status_mut() = status;
+            Ok::<_, Infallible>(response)
+        }));
+        let request = Request::new(());
+        let response = service.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        let request = Request::builder()
+            .header(GRPC_TIMEOUT_HEADER, "10S")
+            .body(())
+            .unwrap();
+        let response = service.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_deadline_layer_rest() {
+        let service = DeadlineLayer::rest().layer(tower::service_fn(|_: Request<()>| async {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            Ok::<_, Infallible>(Response::new(()))
+        }));
+        let request = Request::builder()
+            .header(REST_TIMEOUT_HEADER, "10ms")
+            .body(())
+            .unwrap();
+        let response = service.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+
+        let request = Request::builder()
+            .header(REST_TIMEOUT_HEADER, "10s")
+            .body(())
+            .unwrap();
+        let response = service.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
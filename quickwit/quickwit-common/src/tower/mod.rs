@@ -21,6 +21,7 @@ mod box_layer;
 mod box_service;
 mod buffer;
 mod change;
+mod deadline;
 mod estimate_rate;
 mod event_listener;
 mod metrics;
@@ -38,6 +39,7 @@ pub use box_layer::BoxLayer;
 pub use box_service::BoxService;
 pub use buffer::{Buffer, BufferError, BufferLayer};
 pub use change::Change;
+pub use deadline::{Deadline, DeadlineLayer};
 pub use estimate_rate::{EstimateRate, EstimateRateLayer};
 pub use event_listener::{EventListener, EventListenerLayer};
 use futures::Future;
@@ -47,7 +49,7 @@ pub use rate::{ConstantRate, Rate};
 pub use rate_estimator::{RateEstimator, SmaRateEstimator};
 pub use rate_limit::{RateLimit, RateLimitLayer};
 pub use retry::{RetryLayer, RetryPolicy};
-pub use transport::{make_channel, warmup_channel, BalanceChannel};
+pub use transport::{enable_client_tls, make_channel, warmup_channel, BalanceChannel};
 
 pub type BoxError = Box<dyn error::Error + Send + Sync + 'static>;
 
@@ -29,9 +29,10 @@ use std::time::Duration;
 use futures::stream::once;
 use futures::{Stream, StreamExt};
 use http::Uri;
+use once_cell::sync::OnceCell;
 use tokio::sync::{mpsc, watch};
 use tokio_stream::wrappers::UnboundedReceiverStream;
-use tonic::transport::{Channel, Endpoint};
+use tonic::transport::{Channel, ClientTlsConfig, Endpoint};
 use tower::balance::p2c::Balance;
 use tower::buffer::Buffer;
 use tower::discover::Change as TowerChange;
@@ -184,20 +185,49 @@ where K: Hash + Eq + Clone + Send + Sync + 'static
     }
 }
 
+static CLIENT_TLS_CONFIG: OnceCell<ClientTlsConfig> = OnceCell::new();
+
+/// Enables TLS on the channels created with [`make_channel`]. This function should be called once
+/// at startup, before any channel is created.
+pub fn enable_client_tls(
+    client_tls_config: ClientTlsConfig,
+) -> Result<(), tonic::transport::Error> {
+    // Building the TLS connector parses the CA certificates, so we do it once here to surface
+    // invalid configurations early rather than when the channels are created.
+    Endpoint::from_static("https://localhost").tls_config(client_tls_config.clone())?;
+
+    if CLIENT_TLS_CONFIG.set(client_tls_config).is_err() {
+        tracing::warn!("client TLS is already enabled");
+    }
+    Ok(())
+}
+
 /// Creates a channel from a socket address.
 ///
 /// The function is marked as `async` because it requires an executor (`connect_lazy`).
 pub async fn make_channel(socket_addr: SocketAddr) -> Channel {
+    let client_tls_config_opt = CLIENT_TLS_CONFIG.get();
+    let scheme = if client_tls_config_opt.is_some() {
+        "https"
+    } else {
+        "http"
+    };
     let uri = Uri::builder()
-        .scheme("http")
+        .scheme(scheme)
         .authority(socket_addr.to_string())
         .path_and_query("/")
         .build()
         .expect("The provided arguments should be valid.");
-    Endpoint::from(uri)
+    let mut endpoint = Endpoint::from(uri)
         .connect_timeout(Duration::from_secs(5))
-        .timeout(Duration::from_secs(30))
-        .connect_lazy()
+        .timeout(Duration::from_secs(30));
+
+    if let Some(client_tls_config) = client_tls_config_opt {
+        endpoint = endpoint
+            .tls_config(client_tls_config.clone())
+            .expect("The client TLS config should have been validated by `enable_client_tls`.");
+    }
+    endpoint.connect_lazy()
 }
 
 /// Forces a channel to initiate the underlying HTTP connection. Calling this function only makes
@@ -42,6 +42,7 @@ pub enum Protocol {
     PostgreSQL = 5,
     Ram = 6,
     S3 = 7,
+    Hdfs = 8,
 }
 
 impl Protocol {
@@ -54,6 +55,7 @@ impl Protocol {
             Protocol::PostgreSQL => "postgresql",
             Protocol::Ram => "ram",
             Protocol::S3 => "s3",
+            Protocol::Hdfs => "hdfs",
         }
     }
 
@@ -92,6 +94,7 @@ impl FromStr for Protocol {
             "pg" | "postgres" | "postgresql" => Ok(Protocol::PostgreSQL),
             "ram" => Ok(Protocol::Ram),
             "s3" => Ok(Protocol::S3),
+            "hdfs" => Ok(Protocol::Hdfs),
             _ => bail!("unknown URI protocol `{protocol}`"),
         }
     }
@@ -179,7 +182,7 @@ impl Uri {
         let path = self.path();
         let protocol = self.protocol();
 
-        if protocol == Protocol::S3 && path.components().count() < 2 {
+        if matches!(protocol, Protocol::S3 | Protocol::Hdfs) && path.components().count() < 2 {
             return None;
         }
         if protocol == Protocol::Azure && path.components().count() < 3 {
@@ -204,7 +207,8 @@ impl Uri {
         }
         let path = self.path();
 
-        if self.protocol() == Protocol::S3 && path.components().count() < 2 {
+        if matches!(self.protocol(), Protocol::S3 | Protocol::Hdfs) && path.components().count() < 2
+        {
             return None;
         }
         if self.protocol() == Protocol::Azure && path.components().count() < 3 {
@@ -491,6 +495,10 @@ mod tests {
         assert_eq!(Uri::for_test("file:///home").protocol(), Protocol::File);
         assert_eq!(Uri::for_test("ram:///in-memory").protocol(), Protocol::Ram);
         assert_eq!(Uri::for_test("s3://bucket/key").protocol(), Protocol::S3);
+        assert_eq!(
+            Uri::for_test("hdfs://namenode:9870/path").protocol(),
+            Protocol::Hdfs
+        );
         assert_eq!(
             Uri::for_test("azure://account/bucket/key").protocol(),
             Protocol::Azure
@@ -594,6 +602,18 @@ mod tests {
             Uri::for_test("s3://bucket/foo/bar/").parent().unwrap(),
             "s3://bucket/foo"
         );
+        assert!(Uri::for_test("hdfs://namenode:9870").parent().is_none());
+        assert!(Uri::for_test("hdfs://namenode:9870/").parent().is_none());
+        assert_eq!(
+            Uri::for_test("hdfs://namenode:9870/foo").parent().unwrap(),
+            "hdfs://namenode:9870"
+        );
+        assert_eq!(
+            Uri::for_test("hdfs://namenode:9870/foo/bar")
+                .parent()
+                .unwrap(),
+            "hdfs://namenode:9870/foo"
+        );
         assert!(Uri::for_test("azure://account/").parent().is_none());
         assert!(Uri::for_test("azure://account").parent().is_none());
         assert!(Uri::for_test("azure://account/container/")
@@ -656,6 +676,13 @@ mod tests {
             Uri::for_test("s3://bucket/foo/").file_name().unwrap(),
             Path::new("foo"),
         );
+        assert!(Uri::for_test("hdfs://namenode:9870").file_name().is_none());
+        assert_eq!(
+            Uri::for_test("hdfs://namenode:9870/foo/bar")
+                .file_name()
+                .unwrap(),
+            Path::new("bar"),
+        );
         assert!(Uri::for_test("azure://account").file_name().is_none());
         assert!(Uri::for_test("azure://account/").file_name().is_none());
         assert!(Uri::for_test("azure://account/container")
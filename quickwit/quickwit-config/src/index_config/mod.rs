@@ -19,7 +19,7 @@
 
 pub(crate) mod serialize;
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::num::NonZeroU32;
 use std::str::FromStr;
 use std::sync::Arc;
@@ -30,18 +30,24 @@ use bytesize::ByteSize;
 use chrono::Utc;
 use cron::Schedule;
 use humantime::parse_duration;
+use quickwit_common::is_false;
+use quickwit_common::shared_consts::DELETION_GRACE_PERIOD;
 use quickwit_common::uri::Uri;
 use quickwit_doc_mapper::{
     DefaultDocMapper, DefaultDocMapperBuilder, DocMapper, FieldMappingEntry, Mode, ModeType,
     QuickwitJsonOptions, TokenizerEntry,
 };
+use quickwit_proto::search::SortOrder;
 use quickwit_proto::types::IndexId;
 use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
 pub use serialize::load_index_config_from_user_config;
 
 use crate::index_config::serialize::VersionedIndexConfig;
-use crate::merge_policy_config::{MergePolicyConfig, StableLogMergePolicyConfig};
-use crate::TestableForRegression;
+use crate::merge_policy_config::{
+    parse_human_duration, serialize_duration, MergePolicyConfig, StableLogMergePolicyConfig,
+};
+use crate::{PlacementConstraints, TestableForRegression};
 
 // Note(fmassot): `DocMapping` is a struct only used for
 // serialization/deserialization of `DocMapper` parameters.
@@ -64,6 +70,23 @@ pub struct DocMapping {
     #[schema(value_type = Vec<String>)]
     #[serde(default)]
     pub tag_fields: BTreeSet<String>,
+    /// Fast fields for which splits record the min and max values and the number of distinct
+    /// values. Range queries on these fields skip the splits that cannot match.
+    #[schema(value_type = Vec<String>)]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    pub stats_fields: BTreeSet<String>,
+    /// Field uniquely identifying the documents. Documents can be deleted by ID, and when several
+    /// documents share the same ID, merges only keep the latest one.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub doc_id_field: Option<String>,
+    /// Alternative names resolved to existing fields at search time, mapping each alias to its
+    /// target field. For instance, `message: body` lets queries and aggregations written against
+    /// `message` run on `body`.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub field_aliases: BTreeMap<String, String>,
     #[serde(default)]
     pub store_source: bool,
     #[serde(default)]
@@ -91,6 +114,105 @@ pub struct DocMapping {
     pub tokenizers: Vec<TokenizerEntry>,
 }
 
+impl DocMapping {
+    /// Checks that `new_doc_mapping` is an additive update of this doc mapping: existing fields
+    /// and tokenizers must be left untouched, and the other doc mapping parameters cannot be
+    /// modified. New fields, including new subfields of existing object fields, and new tokenizers
+    /// can be added.
+    pub fn validate_additive_update(&self, new_doc_mapping: &DocMapping) -> anyhow::Result<()> {
+        let mut current_json = serde_json::to_value(self)?;
+        let mut new_json = serde_json::to_value(new_doc_mapping)?;
+        let (Some(current_obj), Some(new_obj)) =
+            (current_json.as_object_mut(), new_json.as_object_mut())
+        else {
+            anyhow::bail!("doc mapping should serialize to a JSON object");
+        };
+        let current_field_mappings = current_obj.remove("field_mappings").unwrap_or_default();
+        let new_field_mappings = new_obj.remove("field_mappings").unwrap_or_default();
+        validate_additive_field_mappings("", &current_field_mappings, &new_field_mappings)?;
+
+        let current_tokenizers = current_obj.remove("tokenizers").unwrap_or_default();
+        let new_tokenizers = new_obj.remove("tokenizers").unwrap_or_default();
+        for current_tokenizer in current_tokenizers.as_array().into_iter().flatten() {
+            if !new_tokenizers
+                .as_array()
+                .into_iter()
+                .flatten()
+                .any(|new_tokenizer| new_tokenizer == current_tokenizer)
+            {
+                anyhow::bail!(
+                    "tokenizer `{}` cannot be modified or removed",
+                    current_tokenizer["name"].as_str().unwrap_or_default()
+                );
+            }
+        }
+        // Stats fields only affect the splits created after the update.
+        current_obj.remove("stats_fields");
+        new_obj.remove("stats_fields");
+
+        for (key, current_value) in current_obj.iter() {
+            if new_obj.get(key) != Some(current_value) {
+                anyhow::bail!("doc mapping parameter `{key}` cannot be modified");
+            }
+        }
+        if let Some(key) = new_obj.keys().find(|key| !current_obj.contains_key(*key)) {
+            anyhow::bail!("doc mapping parameter `{key}` cannot be modified");
+        }
+        Ok(())
+    }
+}
+
+fn validate_additive_field_mappings(
+    parent_path: &str,
+    current_field_mappings: &JsonValue,
+    new_field_mappings: &JsonValue,
+) -> anyhow::Result<()> {
+    let new_field_mappings: Vec<&JsonValue> = new_field_mappings
+        .as_array()
+        .into_iter()
+        .flatten()
+        .collect();
+
+    for current_field_mapping in current_field_mappings.as_array().into_iter().flatten() {
+        let field_name = current_field_mapping["name"].as_str().unwrap_or_default();
+        let field_path = if parent_path.is_empty() {
+            field_name.to_string()
+        } else {
+            format!("{parent_path}.{field_name}")
+        };
+        let Some(new_field_mapping) = new_field_mappings
+            .iter()
+            .find(|new_field_mapping| new_field_mapping["name"] == current_field_mapping["name"])
+        else {
+            anyhow::bail!("field `{field_path}` cannot be removed");
+        };
+        if current_field_mapping["type"] == "object" && new_field_mapping["type"] == "object" {
+            let (Some(current_obj), Some(new_obj)) = (
+                current_field_mapping.as_object(),
+                new_field_mapping.as_object(),
+            ) else {
+                anyhow::bail!("field mapping `{field_path}` should be a JSON object");
+            };
+            let other_params_changed = current_obj
+                .keys()
+                .chain(new_obj.keys())
+                .filter(|key| *key != "field_mappings")
+                .any(|key| current_obj.get(key) != new_obj.get(key));
+            if other_params_changed {
+                anyhow::bail!("field `{field_path}` cannot be modified");
+            }
+            validate_additive_field_mappings(
+                &field_path,
+                &current_field_mapping["field_mappings"],
+                &new_field_mapping["field_mappings"],
+            )?;
+        } else if current_field_mapping != *new_field_mapping {
+            anyhow::bail!("field `{field_path}` cannot be modified");
+        }
+    }
+    Ok(())
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, utoipa::ToSchema)]
 #[serde(deny_unknown_fields)]
 pub struct IndexingResources {
@@ -136,12 +258,59 @@ impl Default for IndexingResources {
     }
 }
 
+/// Compression codec of the doc store.
+#[derive(
+    Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Serialize, Deserialize, utoipa::ToSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum DocstoreCompression {
+    /// Blocks are stored uncompressed.
+    None,
+    /// Fast compression and decompression, at the cost of a lower compression ratio.
+    Lz4,
+    /// High compression ratio, tunable with `docstore_compression_level`.
+    #[default]
+    Zstd,
+}
+
+/// Codec effectively used to compress the doc store of a split.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(tag = "codec", rename_all = "snake_case")]
+pub enum DocstoreCodec {
+    None,
+    Lz4,
+    Zstd { level: i32 },
+}
+
+/// Order in which the documents of the splits are sorted at indexing time. Leaf searches sorting
+/// hits in the same order can stop scanning a segment as soon as they have collected enough hits.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Serialize, Deserialize, utoipa::ToSchema)]
+pub enum IndexSortBy {
+    #[serde(rename = "timestamp asc")]
+    TimestampAsc,
+    #[serde(rename = "timestamp desc")]
+    TimestampDesc,
+}
+
+impl IndexSortBy {
+    /// Returns the order in which the documents are sorted by timestamp.
+    pub fn sort_order(&self) -> SortOrder {
+        match self {
+            IndexSortBy::TimestampAsc => SortOrder::Asc,
+            IndexSortBy::TimestampDesc => SortOrder::Desc,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, utoipa::ToSchema)]
 #[serde(deny_unknown_fields)]
 pub struct IndexingSettings {
     #[schema(default = 60)]
     #[serde(default = "IndexingSettings::default_commit_timeout_secs")]
     pub commit_timeout_secs: usize,
+    #[serde(default)]
+    pub docstore_compression: DocstoreCompression,
+    /// Compression level of the doc store. Only used by the `zstd` codec.
     #[schema(default = 8)]
     #[serde(default = "IndexingSettings::default_docstore_compression_level")]
     pub docstore_compression_level: i32,
@@ -155,10 +324,27 @@ pub struct IndexingSettings {
     /// `split_num_docs_target` are considered mature and never merged.
     #[serde(default = "IndexingSettings::default_split_num_docs_target")]
     pub split_num_docs_target: usize,
+    /// Target size of the splits produced by the indexer once serialized. When set, the indexer
+    /// commits as soon as the estimated size of the splits being built reaches it, or as soon as
+    /// they reach `split_num_docs_target`, whichever comes first.
+    #[schema(value_type = Option<String>)]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub split_size_bytes_target: Option<ByteSize>,
     #[serde(default)]
     pub merge_policy: MergePolicyConfig,
     #[serde(default)]
     pub resources: IndexingResources,
+    /// Sorts the documents of the splits by timestamp. Requires a timestamp field.
+    #[schema(value_type = Option<String>)]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort_by: Option<IndexSortBy>,
+    /// Node labels that the indexers must declare to run the indexing pipelines and host the
+    /// ingest shards of the index. Sources can override them.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "PlacementConstraints::is_empty")]
+    pub placement_constraints: PlacementConstraints,
 }
 
 impl IndexingSettings {
@@ -170,6 +356,17 @@ impl IndexingSettings {
         60
     }
 
+    /// Returns the codec used to compress the doc store of the splits.
+    pub fn docstore_codec(&self) -> DocstoreCodec {
+        match self.docstore_compression {
+            DocstoreCompression::None => DocstoreCodec::None,
+            DocstoreCompression::Lz4 => DocstoreCodec::Lz4,
+            DocstoreCompression::Zstd => DocstoreCodec::Zstd {
+                level: self.docstore_compression_level,
+            },
+        }
+    }
+
     pub fn default_docstore_blocksize() -> usize {
         1_000_000
     }
@@ -196,10 +393,14 @@ impl Default for IndexingSettings {
         Self {
             commit_timeout_secs: Self::default_commit_timeout_secs(),
             docstore_blocksize: Self::default_docstore_blocksize(),
+            docstore_compression: DocstoreCompression::default(),
             docstore_compression_level: Self::default_docstore_compression_level(),
             split_num_docs_target: Self::default_split_num_docs_target(),
+            split_size_bytes_target: None,
             merge_policy: MergePolicyConfig::default(),
             resources: IndexingResources::default(),
+            sort_by: None,
+            placement_constraints: PlacementConstraints::default(),
         }
     }
 }
@@ -209,6 +410,51 @@ impl Default for IndexingSettings {
 pub struct SearchSettings {
     #[serde(default)]
     pub default_search_fields: Vec<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "SearchGuardrails::is_default")]
+    pub guardrails: SearchGuardrails,
+}
+
+/// Limits enforced on the search requests targeting an index. The root searcher rejects the
+/// requests breaking them before executing them.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SearchGuardrails {
+    /// Rejects the wildcard queries whose pattern starts with a wildcard, e.g. `*error`: they
+    /// scan the whole term dictionary of the field.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub forbid_leading_wildcard: bool,
+    /// Maximum number of buckets an aggregation request may produce.
+    #[schema(value_type = Option<u32>)]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_aggregation_buckets: Option<NonZeroU32>,
+    /// Maximum time range of the search requests, expressed in a human-friendly way (`1 day`,
+    /// `7 days`, ...). When set, the search requests must be restricted to a time range.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_time_range: Option<String>,
+}
+
+impl SearchGuardrails {
+    pub(crate) fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn max_time_range(&self) -> anyhow::Result<Option<Duration>> {
+        let Some(max_time_range) = &self.max_time_range else {
+            return Ok(None);
+        };
+        let max_time_range = parse_duration(max_time_range)
+            .with_context(|| format!("failed to parse max time range `{max_time_range}`"))?;
+        Ok(Some(max_time_range))
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        self.max_time_range()?;
+        Ok(())
+    }
 }
 
 #[derive(Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
@@ -292,6 +538,267 @@ fn prepend_at_char(schedule: &str) -> String {
     trimmed_schedule.to_string()
 }
 
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct IngestSettings {
+    #[serde(default)]
+    pub shard_scaling: ShardScalingSettings,
+}
+
+impl IngestSettings {
+    pub(crate) fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        self.shard_scaling.validate()
+    }
+}
+
+const DEFAULT_SCALE_UP_COOLDOWN_SECS: u64 = 12;
+
+const DEFAULT_SCALE_UP_BURST_LIMIT: u64 = 5;
+
+/// Controls how the control plane scales the number of open shards of the ingest v2 sources of
+/// an index.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ShardScalingSettings {
+    /// Minimum number of open shards per source.
+    #[schema(default = 1)]
+    #[serde(default = "ShardScalingSettings::default_min_shards")]
+    pub min_shards: usize,
+    /// Maximum number of open shards per source. Unbounded if not set.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_shards: Option<usize>,
+    /// Ingestion throughput in bytes/sec a shard is expected to sustain. The control plane scales
+    /// up when the average throughput of the open shards exceeds 80% of this target and scales
+    /// down when it drops below 20%.
+    #[schema(value_type = String, default = "5 MiB")]
+    #[serde(default = "ShardScalingSettings::default_target_throughput_per_shard")]
+    pub target_throughput_per_shard: ByteSize,
+    /// Minimum delay between two scale-up operations on a source. If not set, the control plane
+    /// can open up to 5 shards at once, then one shard every 12 seconds.
+    #[schema(value_type = Option<u64>)]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scale_up_cooldown_secs: Option<u64>,
+    /// Minimum delay between two scale-down operations on a source.
+    #[schema(default = 60)]
+    #[serde(default = "ShardScalingSettings::default_scale_down_cooldown_secs")]
+    pub scale_down_cooldown_secs: u64,
+}
+
+impl ShardScalingSettings {
+    fn default_min_shards() -> usize {
+        1
+    }
+
+    fn default_target_throughput_per_shard() -> ByteSize {
+        ByteSize::mib(5)
+    }
+
+    fn default_scale_down_cooldown_secs() -> u64 {
+        60
+    }
+
+    pub fn scale_up_cooldown(&self) -> Duration {
+        Duration::from_secs(
+            self.scale_up_cooldown_secs
+                .unwrap_or(DEFAULT_SCALE_UP_COOLDOWN_SECS),
+        )
+    }
+
+    /// Returns the number of scale-up operations that can be performed back to back. Explicitly
+    /// configuring the scale-up cool-down disables bursting.
+    pub fn scale_up_burst_limit(&self) -> u64 {
+        if self.scale_up_cooldown_secs.is_some() {
+            1
+        } else {
+            DEFAULT_SCALE_UP_BURST_LIMIT
+        }
+    }
+
+    pub fn scale_down_cooldown(&self) -> Duration {
+        Duration::from_secs(self.scale_down_cooldown_secs)
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        if self.min_shards == 0 {
+            anyhow::bail!("`ingest.shard_scaling.min_shards` must be strictly positive");
+        }
+        if let Some(max_shards) = self.max_shards {
+            if max_shards < self.min_shards {
+                anyhow::bail!(
+                    "`ingest.shard_scaling.max_shards` ({max_shards}) must be greater than or \
+                     equal to `ingest.shard_scaling.min_shards` ({})",
+                    self.min_shards
+                );
+            }
+        }
+        if self.target_throughput_per_shard.as_u64() == 0 {
+            anyhow::bail!(
+                "`ingest.shard_scaling.target_throughput_per_shard` must be strictly positive"
+            );
+        }
+        if self.scale_up_cooldown_secs == Some(0) || self.scale_down_cooldown_secs == 0 {
+            anyhow::bail!("`ingest.shard_scaling` cool-downs must be strictly positive");
+        }
+        Ok(())
+    }
+}
+
+impl Default for ShardScalingSettings {
+    fn default() -> Self {
+        Self {
+            min_shards: Self::default_min_shards(),
+            max_shards: None,
+            target_throughput_per_shard: Self::default_target_throughput_per_shard(),
+            scale_up_cooldown_secs: None,
+            scale_down_cooldown_secs: Self::default_scale_down_cooldown_secs(),
+        }
+    }
+}
+
+/// Controls how often the janitor garbage collects the splits of an index and how long it waits
+/// before deleting them.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GarbageCollectionSettings {
+    /// Interval between two garbage collection runs on the index.
+    #[schema(value_type = String, default = "10m")]
+    #[serde(default = "GarbageCollectionSettings::default_interval")]
+    #[serde(deserialize_with = "parse_human_duration")]
+    #[serde(serialize_with = "serialize_duration")]
+    pub interval: Duration,
+    /// Period after which a split left in the staged state, for instance after an indexing
+    /// failure, is marked for deletion.
+    #[schema(value_type = String, default = "1day")]
+    #[serde(default = "GarbageCollectionSettings::default_staged_grace_period")]
+    #[serde(deserialize_with = "parse_human_duration")]
+    #[serde(serialize_with = "serialize_duration")]
+    pub staged_grace_period: Duration,
+    /// Period after which a split marked for deletion is deleted from the storage and the
+    /// metastore. In-flight and scroll queries may still read a split marked for deletion, so
+    /// this should remain larger than the longest queries.
+    #[schema(value_type = String, default = "32m")]
+    #[serde(default = "GarbageCollectionSettings::default_deletion_grace_period")]
+    #[serde(deserialize_with = "parse_human_duration")]
+    #[serde(serialize_with = "serialize_duration")]
+    pub deletion_grace_period: Duration,
+}
+
+impl GarbageCollectionSettings {
+    fn default_interval() -> Duration {
+        Duration::from_secs(10 * 60) // 10 minutes
+    }
+
+    fn default_staged_grace_period() -> Duration {
+        Duration::from_secs(24 * 60 * 60) // 24 hours
+    }
+
+    fn default_deletion_grace_period() -> Duration {
+        DELETION_GRACE_PERIOD
+    }
+
+    pub(crate) fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        if self.interval.is_zero() {
+            anyhow::bail!("`garbage_collection.interval` must be strictly positive");
+        }
+        Ok(())
+    }
+}
+
+impl Default for GarbageCollectionSettings {
+    fn default() -> Self {
+        Self {
+            interval: Self::default_interval(),
+            staged_grace_period: Self::default_staged_grace_period(),
+            deletion_grace_period: Self::default_deletion_grace_period(),
+        }
+    }
+}
+
+/// Defines the actions applied to an index as it ages.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct LifecyclePolicy {
+    /// Age, counted from the creation of the index, after which the index is frozen: its sources
+    /// are disabled and it no longer accepts writes.
+    #[schema(value_type = String)]
+    #[serde(deserialize_with = "parse_human_duration")]
+    #[serde(serialize_with = "serialize_duration")]
+    pub freeze_after: Duration,
+}
+
+impl LifecyclePolicy {
+    fn validate(&self) -> anyhow::Result<()> {
+        if self.freeze_after.is_zero() {
+            anyhow::bail!("`lifecycle.freeze_after` must be strictly positive");
+        }
+        Ok(())
+    }
+}
+
+/// Client-side encryption of the split files of an index.
+///
+/// The splits are encrypted with a data key specific to the index, which is generated at index
+/// creation and stored wrapped (encrypted) by a master key managed outside of Quickwit.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct IndexEncryptionConfig {
+    /// Master key wrapping the data key of the index: `aws-kms:<key ID or ARN>` for an AWS KMS
+    /// key or `env:<variable>` for a base64-encoded 256-bit key read from an environment
+    /// variable.
+    pub master_key_id: String,
+    /// Wrapped data key of the index. Generated by Quickwit when the index is created.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_key: Option<WrappedDataKey>,
+}
+
+impl IndexEncryptionConfig {
+    const MASTER_KEY_ID_PREFIXES: [&'static str; 2] = ["aws-kms:", "env:"];
+
+    /// Returns the ID of the data key of the index, if it has been generated.
+    pub fn data_key_id(&self) -> Option<&str> {
+        self.data_key
+            .as_ref()
+            .map(|wrapped_data_key| wrapped_data_key.key_id.as_str())
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        let is_valid_master_key_id = Self::MASTER_KEY_ID_PREFIXES.iter().any(|prefix| {
+            self.master_key_id
+                .strip_prefix(prefix)
+                .map_or(false, |key_id| !key_id.is_empty())
+        });
+        if !is_valid_master_key_id {
+            anyhow::bail!(
+                "`encryption.master_key_id` must be of the form `aws-kms:<key-id>` or \
+                 `env:<variable>`, got `{}`",
+                self.master_key_id
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Data key of an index encrypted by the master key of the index.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct WrappedDataKey {
+    /// Identifier of the data key, recorded in the metadata of the splits it encrypts.
+    pub key_id: String,
+    /// Base64-encoded ciphertext of the data key.
+    pub ciphertext: String,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 #[serde(deny_unknown_fields)]
 #[serde(into = "VersionedIndexConfig")]
@@ -303,6 +810,10 @@ pub struct IndexConfig {
     pub indexing_settings: IndexingSettings,
     pub search_settings: SearchSettings,
     pub retention_policy: Option<RetentionPolicy>,
+    pub ingest_settings: IngestSettings,
+    pub gc_settings: GarbageCollectionSettings,
+    pub lifecycle_policy: Option<LifecyclePolicy>,
+    pub encryption: Option<IndexEncryptionConfig>,
 }
 
 impl IndexConfig {
@@ -380,6 +891,7 @@ impl IndexConfig {
                 r#"attributes.server"#.to_string(),
                 r"attributes.server\.status".to_string(),
             ],
+            ..Default::default()
         };
         IndexConfig {
             index_id: index_id.to_string(),
@@ -388,6 +900,10 @@ impl IndexConfig {
             indexing_settings,
             search_settings,
             retention_policy: Default::default(),
+            ingest_settings: IngestSettings::default(),
+            gc_settings: GarbageCollectionSettings::default(),
+            lifecycle_policy: None,
+            encryption: None,
         }
     }
 }
@@ -447,6 +963,9 @@ impl TestableForRegression for IndexConfig {
                 .into_iter()
                 .map(|tag_field| tag_field.to_string())
                 .collect::<BTreeSet<String>>(),
+            stats_fields: BTreeSet::new(),
+            doc_id_field: None,
+            field_aliases: BTreeMap::new(),
             store_source: true,
             mode: Mode::default(),
             partition_key: Some("tenant_id".to_string()),
@@ -477,6 +996,7 @@ impl TestableForRegression for IndexConfig {
         };
         let search_settings = SearchSettings {
             default_search_fields: vec!["message".to_string()],
+            ..Default::default()
         };
         IndexConfig {
             index_id: "my-index".to_string(),
@@ -485,6 +1005,10 @@ impl TestableForRegression for IndexConfig {
             indexing_settings,
             retention_policy,
             search_settings,
+            ingest_settings: IngestSettings::default(),
+            gc_settings: GarbageCollectionSettings::default(),
+            lifecycle_policy: None,
+            encryption: None,
         }
     }
 
@@ -526,6 +1050,9 @@ pub fn build_doc_mapper(
         timestamp_field: doc_mapping.timestamp_field.clone(),
         field_mappings: doc_mapping.field_mappings.clone(),
         tag_fields: doc_mapping.tag_fields.iter().cloned().collect(),
+        stats_fields: doc_mapping.stats_fields.iter().cloned().collect(),
+        doc_id_field: doc_mapping.doc_id_field.clone(),
+        field_aliases: doc_mapping.field_aliases.clone(),
         mode: doc_mapping.mode.clone(),
         partition_key: doc_mapping.partition_key.clone(),
         max_num_partitions: doc_mapping.max_num_partitions,
@@ -617,6 +1144,7 @@ mod tests {
             index_config.search_settings,
             SearchSettings {
                 default_search_fields: vec!["severity_text".to_string(), "body".to_string()],
+                ..Default::default()
             }
         );
     }
@@ -659,6 +1187,7 @@ mod tests {
                 index_config.search_settings,
                 SearchSettings {
                     default_search_fields: vec!["body".to_string()],
+                    ..Default::default()
                 }
             );
         }
@@ -693,6 +1222,7 @@ mod tests {
                 index_config.search_settings,
                 SearchSettings {
                     default_search_fields: vec!["body".to_string()],
+                    ..Default::default()
                 }
             );
         }
@@ -755,6 +1285,91 @@ mod tests {
             .contains("failed to parse human-readable duration `x`"));
     }
 
+    #[test]
+    fn test_indexing_settings_docstore_codec() {
+        let indexing_settings = IndexingSettings::default();
+        assert_eq!(
+            indexing_settings.docstore_codec(),
+            DocstoreCodec::Zstd { level: 8 }
+        );
+
+        let indexing_settings_yaml = r#"
+            docstore_compression: lz4
+            docstore_compression_level: 3
+        "#;
+        let indexing_settings: IndexingSettings =
+            serde_yaml::from_str(indexing_settings_yaml).unwrap();
+        assert_eq!(indexing_settings.docstore_codec(), DocstoreCodec::Lz4);
+
+        let indexing_settings_yaml = r#"
+            docstore_compression: zstd
+            docstore_compression_level: 3
+        "#;
+        let indexing_settings: IndexingSettings =
+            serde_yaml::from_str(indexing_settings_yaml).unwrap();
+        assert_eq!(
+            indexing_settings.docstore_codec(),
+            DocstoreCodec::Zstd { level: 3 }
+        );
+
+        let indexing_settings: IndexingSettings =
+            serde_yaml::from_str("docstore_compression: none").unwrap();
+        assert_eq!(indexing_settings.docstore_codec(), DocstoreCodec::None);
+
+        serde_yaml::from_str::<IndexingSettings>("docstore_compression: brotli").unwrap_err();
+    }
+
+    #[test]
+    fn test_indexing_settings_sort_by() {
+        let indexing_settings = IndexingSettings::default();
+        assert!(indexing_settings.sort_by.is_none());
+
+        let indexing_settings: IndexingSettings =
+            serde_yaml::from_str("sort_by: timestamp desc").unwrap();
+        let sort_by = indexing_settings.sort_by.unwrap();
+        assert_eq!(sort_by, IndexSortBy::TimestampDesc);
+        assert_eq!(sort_by.sort_order(), SortOrder::Desc);
+
+        let indexing_settings: IndexingSettings =
+            serde_yaml::from_str("sort_by: timestamp asc").unwrap();
+        let sort_by = indexing_settings.sort_by.unwrap();
+        assert_eq!(sort_by, IndexSortBy::TimestampAsc);
+        assert_eq!(sort_by.sort_order(), SortOrder::Asc);
+
+        serde_yaml::from_str::<IndexingSettings>("sort_by: body desc").unwrap_err();
+    }
+
+    #[test]
+    fn test_indexing_settings_split_size_bytes_target() {
+        let indexing_settings = IndexingSettings::default();
+        assert!(indexing_settings.split_size_bytes_target.is_none());
+
+        let indexing_settings: IndexingSettings =
+            serde_yaml::from_str("split_size_bytes_target: 500M").unwrap();
+        assert_eq!(
+            indexing_settings.split_size_bytes_target,
+            Some(ByteSize::mb(500))
+        );
+
+        let index_config_yaml = r#"
+            version: 0.6
+            index_id: hdfs-logs
+            doc_mapping: {}
+            indexing_settings:
+                split_size_bytes_target: 0
+        "#;
+        let parsing_config_error = load_index_config_from_user_config(
+            ConfigFormat::Yaml,
+            index_config_yaml.as_bytes(),
+            &Uri::for_test("s3://my-index"),
+        )
+        .unwrap_err();
+        assert!(parsing_config_error
+            .root_cause()
+            .to_string()
+            .contains("`split_size_bytes_target` must be strictly positive"));
+    }
+
     #[test]
     fn test_retention_policy_serialization() {
         let retention_policy = RetentionPolicy {
@@ -916,4 +1531,129 @@ mod tests {
         schedule_test_helper_fn("monthly");
         schedule_test_helper_fn("* * * ? * ?");
     }
+
+    #[test]
+    fn test_doc_mapping_validate_additive_update() {
+        let current_doc_mapping: DocMapping = serde_json::from_str(
+            r#"{
+                "field_mappings": [
+                    {"name": "body", "type": "text"},
+                    {
+                        "name": "attributes",
+                        "type": "object",
+                        "field_mappings": [{"name": "host", "type": "text", "tokenizer": "raw"}]
+                    }
+                ],
+                "tag_fields": ["attributes.host"]
+            }"#,
+        )
+        .unwrap();
+        current_doc_mapping
+            .validate_additive_update(&current_doc_mapping)
+            .unwrap();
+        {
+            let new_doc_mapping: DocMapping = serde_json::from_str(
+                r#"{
+                    "field_mappings": [
+                        {"name": "body", "type": "text"},
+                        {
+                            "name": "attributes",
+                            "type": "object",
+                            "field_mappings": [
+                                {"name": "host", "type": "text", "tokenizer": "raw"},
+                                {"name": "pid", "type": "u64"}
+                            ]
+                        },
+                        {"name": "severity", "type": "text", "tokenizer": "raw"}
+                    ],
+                    "tag_fields": ["attributes.host"]
+                }"#,
+            )
+            .unwrap();
+            current_doc_mapping
+                .validate_additive_update(&new_doc_mapping)
+                .unwrap();
+        }
+        {
+            let new_doc_mapping: DocMapping = serde_json::from_str(
+                r#"{
+                    "field_mappings": [
+                        {"name": "body", "type": "text"}
+                    ],
+                    "tag_fields": ["attributes.host"]
+                }"#,
+            )
+            .unwrap();
+            let error = current_doc_mapping
+                .validate_additive_update(&new_doc_mapping)
+                .unwrap_err();
+            assert_eq!(error.to_string(), "field `attributes` cannot be removed");
+        }
+        {
+            let new_doc_mapping: DocMapping = serde_json::from_str(
+                r#"{
+                    "field_mappings": [
+                        {"name": "body", "type": "text"},
+                        {
+                            "name": "attributes",
+                            "type": "object",
+                            "field_mappings": [{"name": "host", "type": "text", "fast": true}]
+                        }
+                    ],
+                    "tag_fields": ["attributes.host"]
+                }"#,
+            )
+            .unwrap();
+            let error = current_doc_mapping
+                .validate_additive_update(&new_doc_mapping)
+                .unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                "field `attributes.host` cannot be modified"
+            );
+        }
+        {
+            let new_doc_mapping: DocMapping = serde_json::from_str(
+                r#"{
+                    "field_mappings": [
+                        {"name": "body", "type": "text"},
+                        {
+                            "name": "attributes",
+                            "type": "object",
+                            "field_mappings": [{"name": "host", "type": "text", "tokenizer": "raw"}]
+                        }
+                    ],
+                    "store_source": true
+                }"#,
+            )
+            .unwrap();
+            let error = current_doc_mapping
+                .validate_additive_update(&new_doc_mapping)
+                .unwrap_err();
+            assert!(error.to_string().contains("cannot be modified"));
+        }
+        {
+            let new_doc_mapping: DocMapping = serde_json::from_str(
+                r#"{
+                    "field_mappings": [
+                        {"name": "body", "type": "text"},
+                        {
+                            "name": "attributes",
+                            "type": "object",
+                            "field_mappings": [
+                                {"name": "host", "type": "text", "tokenizer": "raw"},
+                                {"name": "pid", "type": "u64", "fast": true}
+                            ]
+                        }
+                    ],
+                    "tag_fields": ["attributes.host"],
+                    "stats_fields": ["attributes.pid"]
+                }"#,
+            )
+            .unwrap();
+            current_doc_mapping
+                .validate_additive_update(&new_doc_mapping)
+                .unwrap();
+        }
+    }
 }
@@ -18,12 +18,14 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 use anyhow::Context;
+use bytesize::ByteSize;
 use quickwit_common::uri::Uri;
 use serde::{Deserialize, Serialize};
 use tracing::info;
 
 use crate::{
-    build_doc_mapper, validate_identifier, ConfigFormat, DocMapping, IndexConfig, IndexingSettings,
+    build_doc_mapper, validate_identifier, ConfigFormat, DocMapping, GarbageCollectionSettings,
+    IndexConfig, IndexEncryptionConfig, IndexingSettings, IngestSettings, LifecyclePolicy,
     RetentionPolicy, SearchSettings,
 };
 
@@ -103,7 +105,42 @@ impl IndexConfigForSerialization {
         // TODO see if we should store the byproducton the IndexConfig.
         build_doc_mapper(&self.doc_mapping, &self.search_settings)?;
 
+        self.search_settings.guardrails.validate()?;
+
+        if self.search_settings.guardrails.max_time_range.is_some()
+            && self.doc_mapping.timestamp_field.is_none()
+        {
+            anyhow::bail!(
+                "failed to validate index config. the max time range search guardrail requires a \
+                 timestamp field, but the doc mapping does not declare one"
+            );
+        }
+
+        if self.indexing_settings.sort_by.is_some() && self.doc_mapping.timestamp_field.is_none() {
+            anyhow::bail!(
+                "failed to validate index config. sorting the splits by timestamp requires a \
+                 timestamp field, but the doc mapping does not declare one"
+            );
+        }
+
+        if self.indexing_settings.split_size_bytes_target == Some(ByteSize(0)) {
+            anyhow::bail!(
+                "failed to validate index config. `split_size_bytes_target` must be strictly \
+                 positive"
+            );
+        }
+
         self.indexing_settings.merge_policy.validate()?;
+        self.indexing_settings.placement_constraints.validate()?;
+        self.ingest_settings.validate()?;
+        self.gc_settings.validate()?;
+
+        if let Some(lifecycle_policy) = &self.lifecycle_policy {
+            lifecycle_policy.validate()?;
+        }
+        if let Some(encryption) = &self.encryption {
+            encryption.validate()?;
+        }
 
         Ok(IndexConfig {
             index_id: self.index_id,
@@ -112,6 +149,10 @@ impl IndexConfigForSerialization {
             indexing_settings: self.indexing_settings,
             search_settings: self.search_settings,
             retention_policy: self.retention_policy,
+            ingest_settings: self.ingest_settings,
+            gc_settings: self.gc_settings,
+            lifecycle_policy: self.lifecycle_policy,
+            encryption: self.encryption,
         })
     }
 }
@@ -147,6 +188,21 @@ pub struct IndexConfigV0_6 {
     #[serde(rename = "retention")]
     #[serde(default)]
     pub retention_policy: Option<RetentionPolicy>,
+    #[serde(rename = "ingest")]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "IngestSettings::is_default")]
+    pub ingest_settings: IngestSettings,
+    #[serde(rename = "garbage_collection")]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "GarbageCollectionSettings::is_default")]
+    pub gc_settings: GarbageCollectionSettings,
+    #[serde(rename = "lifecycle")]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lifecycle_policy: Option<LifecyclePolicy>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encryption: Option<IndexEncryptionConfig>,
 }
 
 impl From<IndexConfig> for IndexConfigV0_6 {
@@ -158,14 +214,23 @@ impl From<IndexConfig> for IndexConfigV0_6 {
             indexing_settings: index_config.indexing_settings,
             search_settings: index_config.search_settings,
             retention_policy: index_config.retention_policy,
+            ingest_settings: index_config.ingest_settings,
+            gc_settings: index_config.gc_settings,
+            lifecycle_policy: index_config.lifecycle_policy,
+            encryption: index_config.encryption,
         }
     }
 }
 
 #[cfg(test)]
 mod test {
+    use std::time::Duration;
+
+    use bytesize::ByteSize;
+
     use super::*;
     use crate::merge_policy_config::{MergePolicyConfig, StableLogMergePolicyConfig};
+    use crate::IndexSortBy;
 
     fn minimal_index_config_for_serialization() -> IndexConfigForSerialization {
         serde_yaml::from_str(
@@ -225,6 +290,239 @@ mod test {
         assert!(validation_err.contains("the retention policy requires a timestamp field"));
     }
 
+    #[test]
+    fn test_validate_search_guardrails() {
+        let index_config_yaml = r#"
+            index_id: hdfs-logs
+            index_uri: s3://quickwit-indexes/hdfs-logs
+            doc_mapping:
+                field_mappings:
+                    - name: body
+                      type: text
+                    - name: timestamp
+                      type: datetime
+                      fast: true
+                timestamp_field: timestamp
+            search_settings:
+                guardrails:
+                    forbid_leading_wildcard: true
+                    max_aggregation_buckets: 1000
+                    max_time_range: 7 days
+        "#;
+        let index_config: IndexConfigForSerialization =
+            serde_yaml::from_str(index_config_yaml).unwrap();
+        let index_config = index_config.validate_and_build(None).unwrap();
+        let guardrails = &index_config.search_settings.guardrails;
+        assert!(guardrails.forbid_leading_wildcard);
+        assert_eq!(
+            guardrails
+                .max_aggregation_buckets
+                .map(|max_buckets| max_buckets.get()),
+            Some(1000)
+        );
+        assert_eq!(
+            guardrails.max_time_range().unwrap(),
+            Some(Duration::from_secs(7 * 24 * 3600))
+        );
+
+        let mut invalid_index_config = minimal_index_config_for_serialization();
+        invalid_index_config
+            .search_settings
+            .guardrails
+            .max_time_range = Some("7 days".to_string());
+        let validation_err = invalid_index_config
+            .validate_and_build(None)
+            .unwrap_err()
+            .to_string();
+        assert!(validation_err.contains("the max time range search guardrail requires a timestamp"));
+
+        let mut invalid_index_config = minimal_index_config_for_serialization();
+        invalid_index_config
+            .search_settings
+            .guardrails
+            .max_time_range = Some("7 dayz".to_string());
+        let validation_err = invalid_index_config
+            .validate_and_build(None)
+            .unwrap_err()
+            .to_string();
+        assert!(validation_err.contains("failed to parse max time range"));
+    }
+
+    #[test]
+    fn test_validate_indexing_sort_by() {
+        let index_config_yaml = r#"
+            index_id: hdfs-logs
+            index_uri: s3://quickwit-indexes/hdfs-logs
+            doc_mapping:
+                field_mappings:
+                    - name: body
+                      type: text
+                    - name: timestamp
+                      type: datetime
+                      fast: true
+                timestamp_field: timestamp
+            indexing_settings:
+                sort_by: timestamp desc
+        "#;
+        let index_config: IndexConfigForSerialization =
+            serde_yaml::from_str(index_config_yaml).unwrap();
+        let index_config = index_config.validate_and_build(None).unwrap();
+        assert_eq!(
+            index_config.indexing_settings.sort_by,
+            Some(IndexSortBy::TimestampDesc)
+        );
+
+        let mut invalid_index_config = minimal_index_config_for_serialization();
+        invalid_index_config.indexing_settings.sort_by = Some(IndexSortBy::TimestampAsc);
+        let validation_err = invalid_index_config
+            .validate_and_build(None)
+            .unwrap_err()
+            .to_string();
+        assert!(validation_err.contains("sorting the splits by timestamp requires a timestamp"));
+    }
+
+    #[test]
+    fn test_validate_ingest_settings() {
+        let index_config_yaml = r#"
+            index_id: hdfs-logs
+            index_uri: s3://quickwit-indexes/hdfs-logs
+            doc_mapping:
+                field_mappings:
+                    - name: body
+                      type: text
+            ingest:
+                shard_scaling:
+                    min_shards: 2
+                    target_throughput_per_shard: 2 MiB
+        "#;
+        let index_config: IndexConfigForSerialization =
+            serde_yaml::from_str(index_config_yaml).unwrap();
+        let index_config = index_config.validate_and_build(None).unwrap();
+        let shard_scaling = &index_config.ingest_settings.shard_scaling;
+        assert_eq!(shard_scaling.min_shards, 2);
+        assert_eq!(shard_scaling.max_shards, None);
+        assert_eq!(shard_scaling.target_throughput_per_shard, ByteSize::mib(2));
+        assert_eq!(shard_scaling.scale_up_cooldown_secs, None);
+        assert_eq!(shard_scaling.scale_up_cooldown(), Duration::from_secs(12));
+        assert_eq!(shard_scaling.scale_up_burst_limit(), 5);
+        assert_eq!(shard_scaling.scale_down_cooldown_secs, 60);
+
+        let mut invalid_index_config: IndexConfigForSerialization =
+            minimal_index_config_for_serialization();
+        invalid_index_config
+            .ingest_settings
+            .shard_scaling
+            .min_shards = 4;
+        invalid_index_config
+            .ingest_settings
+            .shard_scaling
+            .max_shards = Some(2);
+        let validation_err = invalid_index_config
+            .validate_and_build(None)
+            .unwrap_err()
+            .to_string();
+        assert!(validation_err.contains("`ingest.shard_scaling.max_shards` (2)"));
+    }
+
+    #[test]
+    fn test_validate_gc_settings() {
+        let index_config_yaml = r#"
+            index_id: hdfs-logs
+            index_uri: s3://quickwit-indexes/hdfs-logs
+            doc_mapping:
+                field_mappings:
+                    - name: body
+                      type: text
+            garbage_collection:
+                interval: 1 hour
+                deletion_grace_period: 5 minutes
+        "#;
+        let index_config: IndexConfigForSerialization =
+            serde_yaml::from_str(index_config_yaml).unwrap();
+        let index_config = index_config.validate_and_build(None).unwrap();
+        let gc_settings = &index_config.gc_settings;
+        assert_eq!(gc_settings.interval, Duration::from_secs(3600));
+        assert_eq!(
+            gc_settings.staged_grace_period,
+            Duration::from_secs(24 * 3600)
+        );
+        assert_eq!(gc_settings.deletion_grace_period, Duration::from_secs(300));
+
+        let mut invalid_index_config: IndexConfigForSerialization =
+            minimal_index_config_for_serialization();
+        invalid_index_config.gc_settings.interval = Duration::ZERO;
+        let validation_err = invalid_index_config
+            .validate_and_build(None)
+            .unwrap_err()
+            .to_string();
+        assert!(validation_err.contains("`garbage_collection.interval` must be strictly positive"));
+    }
+
+    #[test]
+    fn test_validate_lifecycle_policy() {
+        let index_config_yaml = r#"
+            index_id: hdfs-logs
+            index_uri: s3://quickwit-indexes/hdfs-logs
+            doc_mapping:
+                field_mappings:
+                    - name: body
+                      type: text
+            lifecycle:
+                freeze_after: 2 days
+        "#;
+        let index_config: IndexConfigForSerialization =
+            serde_yaml::from_str(index_config_yaml).unwrap();
+        let index_config = index_config.validate_and_build(None).unwrap();
+        let lifecycle_policy = index_config.lifecycle_policy.unwrap();
+        assert_eq!(
+            lifecycle_policy.freeze_after,
+            Duration::from_secs(2 * 24 * 3600)
+        );
+
+        let mut invalid_index_config: IndexConfigForSerialization =
+            minimal_index_config_for_serialization();
+        invalid_index_config.lifecycle_policy = Some(LifecyclePolicy {
+            freeze_after: Duration::ZERO,
+        });
+        let validation_err = invalid_index_config
+            .validate_and_build(None)
+            .unwrap_err()
+            .to_string();
+        assert!(validation_err.contains("`lifecycle.freeze_after` must be strictly positive"));
+    }
+
+    #[test]
+    fn test_validate_encryption() {
+        let index_config_yaml = r#"
+            index_id: hdfs-logs
+            index_uri: s3://quickwit-indexes/hdfs-logs
+            doc_mapping:
+                field_mappings:
+                    - name: body
+                      type: text
+            encryption:
+                master_key_id: aws-kms:alias/quickwit
+        "#;
+        let index_config: IndexConfigForSerialization =
+            serde_yaml::from_str(index_config_yaml).unwrap();
+        let index_config = index_config.validate_and_build(None).unwrap();
+        let encryption = index_config.encryption.unwrap();
+        assert_eq!(encryption.master_key_id, "aws-kms:alias/quickwit");
+        assert!(encryption.data_key.is_none());
+
+        let mut invalid_index_config: IndexConfigForSerialization =
+            minimal_index_config_for_serialization();
+        invalid_index_config.encryption = Some(IndexEncryptionConfig {
+            master_key_id: "vault:quickwit".to_string(),
+            data_key: None,
+        });
+        let validation_err = invalid_index_config
+            .validate_and_build(None)
+            .unwrap_err()
+            .to_string();
+        assert!(validation_err.contains("`encryption.master_key_id` must be of the form"));
+    }
+
     #[test]
     fn test_minimal_index_config_missing_root_uri_no_default_uri() {
         let config_yaml = r#"
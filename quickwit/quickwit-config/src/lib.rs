@@ -33,6 +33,7 @@ mod index_config;
 pub mod merge_policy_config;
 mod metastore_config;
 mod node_config;
+mod placement;
 mod qw_env_vars;
 pub mod service;
 mod source_config;
@@ -43,17 +44,22 @@ mod templating;
 // See #2048
 use index_config::serialize::{IndexConfigV0_6, VersionedIndexConfig};
 pub use index_config::{
-    build_doc_mapper, load_index_config_from_user_config, DocMapping, IndexConfig,
-    IndexingResources, IndexingSettings, RetentionPolicy, SearchSettings,
+    build_doc_mapper, load_index_config_from_user_config, DocMapping, DocstoreCodec,
+    DocstoreCompression, GarbageCollectionSettings, IndexConfig, IndexEncryptionConfig,
+    IndexSortBy, IndexingResources, IndexingSettings, IngestSettings, LifecyclePolicy,
+    RetentionPolicy, SearchGuardrails, SearchSettings, ShardScalingSettings, WrappedDataKey,
 };
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use serde_json::Value as JsonValue;
 pub use source_config::{
-    load_source_config_from_user_config, FileSourceParams, GcpPubSubSourceParams,
-    KafkaSourceParams, KinesisSourceParams, PulsarSourceAuth, PulsarSourceParams, RegionOrEndpoint,
-    SourceConfig, SourceInputFormat, SourceParams, TransformConfig, VecSourceParams,
-    VoidSourceParams, CLI_INGEST_SOURCE_ID, INGEST_API_SOURCE_ID, INGEST_SOURCE_ID,
+    load_source_config_from_user_config, AzureEventHubsSourceParams, BackoffStrategy, CsvConfig,
+    DeadLetterQueueConfig, FetchDecompression, FetchStreamConfig, FileSourceParams,
+    GcpPubSubSourceParams, KafkaHeaderFieldMapping, KafkaHeaderFilter, KafkaSourceParams,
+    KinesisSourceParams, PulsarSourceAuth, PulsarSourceParams, RegionOrEndpoint, SourceConfig,
+    SourceInputFormat, SourceParams, SourcePriority, SupervisionPolicy, TransformConfig,
+    VecSourceParams, VoidSourceParams, CLI_INGEST_SOURCE_ID, INGEST_API_SOURCE_ID,
+    INGEST_SOURCE_ID,
 };
 use tracing::warn;
 
@@ -64,32 +70,54 @@ pub use crate::metastore_config::{
     MetastoreBackend, MetastoreConfig, MetastoreConfigs, PostgresMetastoreConfig,
 };
 pub use crate::node_config::{
-    IndexerConfig, IngestApiConfig, JaegerConfig, NodeConfig, SearcherConfig, SplitCacheLimits,
+    ApiKeyQueryLimitsConfig, ApiKeyQueryLimitsOverride, IndexerConfig, IngestApiConfig,
+    IngestRoutingRule, JaegerConfig, JanitorConfig, MetricsConfig, NodeConfig, RestConfig,
+    RouteHeadersConfig, SearcherConfig, SearcherConfigOverrides, SplitCacheLimits, TlsConfig,
     DEFAULT_QW_CONFIG_PATH,
 };
+pub use crate::placement::{NodeLabels, PlacementConstraints};
 use crate::source_config::serialize::{SourceConfigV0_6, VersionedSourceConfig};
 pub use crate::storage_config::{
-    AzureStorageConfig, FileStorageConfig, RamStorageConfig, S3StorageConfig, StorageBackend,
-    StorageBackendFlavor, StorageConfig, StorageConfigs,
+    AzureStorageConfig, FileStorageConfig, HdfsStorageConfig, RamStorageConfig, S3AssumeRoleConfig,
+    S3StorageConfig, StorageBackend, StorageBackendFlavor, StorageConfig, StorageConfigs,
 };
 
 #[derive(utoipa::OpenApi)]
 #[openapi(components(schemas(
     IndexingResources,
     IndexingSettings,
+    DocstoreCompression,
     SearchSettings,
+    SearchGuardrails,
     RetentionPolicy,
+    IngestSettings,
+    ShardScalingSettings,
+    GarbageCollectionSettings,
+    LifecyclePolicy,
+    IndexEncryptionConfig,
+    WrappedDataKey,
+    PlacementConstraints,
     MergePolicyConfig,
     DocMapping,
     VersionedSourceConfig,
     SourceConfigV0_6,
+    DeadLetterQueueConfig,
+    SupervisionPolicy,
+    SourcePriority,
+    BackoffStrategy,
+    FetchStreamConfig,
+    FetchDecompression,
     VersionedIndexConfig,
     IndexConfigV0_6,
     SourceInputFormat,
+    CsvConfig,
     SourceParams,
+    AzureEventHubsSourceParams,
     FileSourceParams,
     GcpPubSubSourceParams,
     KafkaSourceParams,
+    KafkaHeaderFilter,
+    KafkaHeaderFieldMapping,
     KinesisSourceParams,
     PulsarSourceParams,
     PulsarSourceAuth,
@@ -42,6 +42,7 @@ pub enum MetastoreBackend {
 ///
 ///   postgres:
 ///     max_num_connections: 12
+///     enable_change_notifications: true
 /// ```
 #[serde_as]
 #[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
@@ -153,12 +154,19 @@ impl From<PostgresMetastoreConfig> for MetastoreConfig {
 pub struct PostgresMetastoreConfig {
     #[serde(default = "PostgresMetastoreConfig::default_max_num_connections")]
     pub max_num_connections: NonZeroUsize,
+    /// Whether to listen to the index changes notified by the database (`LISTEN/NOTIFY`) in order
+    /// to learn about the indexes created, updated, or deleted by other nodes. Disabled by default
+    /// because notifications are not delivered through connection poolers running in transaction
+    /// mode such as PgBouncer.
+    #[serde(default)]
+    pub enable_change_notifications: bool,
 }
 
 impl Default for PostgresMetastoreConfig {
     fn default() -> Self {
         Self {
             max_num_connections: Self::default_max_num_connections(),
+            enable_change_notifications: false,
         }
     }
 }
@@ -187,12 +195,14 @@ mod tests {
         let metastore_configs_yaml = r#"
                 postgres:
                     max_num_connections: 12
+                    enable_change_notifications: true
             "#;
         let metastore_configs: MetastoreConfigs =
             serde_yaml::from_str(metastore_configs_yaml).unwrap();
 
         let expected_metastore_configs = MetastoreConfigs(vec![PostgresMetastoreConfig {
             max_num_connections: NonZeroUsize::new(12).expect("12 is always non-zero."),
+            enable_change_notifications: true,
         }
         .into()]);
         assert_eq!(metastore_configs, expected_metastore_configs);
@@ -203,10 +213,12 @@ mod tests {
         let metastore_configs = MetastoreConfigs(vec![
             PostgresMetastoreConfig {
                 max_num_connections: NonZeroUsize::new(12).expect("12 is always non-zero."),
+                ..Default::default()
             }
             .into(),
             PostgresMetastoreConfig {
                 max_num_connections: NonZeroUsize::new(12).expect("12 is always non-zero."),
+                ..Default::default()
             }
             .into(),
         ]);
@@ -224,6 +236,20 @@ mod tests {
 
             let expected_pg_metastore_config = PostgresMetastoreConfig {
                 max_num_connections: NonZeroUsize::new(12).expect("12 is always non-zero."),
+                enable_change_notifications: false,
+            };
+            assert_eq!(pg_metastore_config, expected_pg_metastore_config);
+        }
+        {
+            let pg_metastore_config_yaml = r#"
+                enable_change_notifications: true
+            "#;
+            let pg_metastore_config: PostgresMetastoreConfig =
+                serde_yaml::from_str(pg_metastore_config_yaml).unwrap();
+
+            let expected_pg_metastore_config = PostgresMetastoreConfig {
+                max_num_connections: PostgresMetastoreConfig::default_max_num_connections(),
+                enable_change_notifications: true,
             };
             assert_eq!(pg_metastore_config, expected_pg_metastore_config);
         }
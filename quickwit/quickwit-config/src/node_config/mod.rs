@@ -20,11 +20,11 @@
 mod serialize;
 
 use std::collections::{HashMap, HashSet};
-use std::env;
 use std::net::SocketAddr;
 use std::num::{NonZeroU32, NonZeroU64, NonZeroUsize};
 use std::path::PathBuf;
 use std::time::Duration;
+use std::{env, fmt};
 
 use anyhow::{bail, ensure};
 use bytesize::ByteSize;
@@ -36,9 +36,10 @@ use serde::{Deserialize, Serialize};
 use tracing::warn;
 
 use crate::node_config::serialize::load_node_config_with_env;
+use crate::placement::NodeLabels;
 use crate::service::QuickwitService;
 use crate::storage_config::StorageConfigs;
-use crate::{ConfigFormat, MetastoreConfigs};
+use crate::{validate_identifier, ConfigFormat, MetastoreConfigs};
 
 pub const DEFAULT_QW_CONFIG_PATH: &str = "config/quickwit.yaml";
 
@@ -47,8 +48,23 @@ pub const DEFAULT_QW_CONFIG_PATH: &str = "config/quickwit.yaml";
 pub struct RestConfig {
     pub listen_addr: SocketAddr,
     pub cors_allow_origins: Vec<String>,
+    /// Request headers allowed in CORS requests. `*` allows any header.
+    pub cors_allow_headers: Vec<String>,
+    /// Methods allowed in CORS requests.
+    pub cors_allow_methods: Vec<String>,
     #[serde(with = "http_serde::header_map")]
     pub extra_headers: HeaderMap,
+    /// Headers added to the responses of the routes matching a path prefix.
+    pub route_headers: Vec<RouteHeadersConfig>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RouteHeadersConfig {
+    /// Path prefix of the routes, for instance `/api/v1/_elastic`.
+    pub path_prefix: String,
+    #[serde(with = "http_serde::header_map")]
+    pub headers: HeaderMap,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
@@ -138,6 +154,13 @@ pub struct SplitCacheLimits {
     pub max_num_splits: NonZeroU32,
     #[serde(default = "SplitCacheLimits::default_num_concurrent_downloads")]
     pub num_concurrent_downloads: NonZeroU32,
+    /// Disk space dedicated to the byte ranges read from splits that are not cached in full,
+    /// in addition to `max_num_bytes`. Partial range caching is disabled if `0`.
+    #[serde(default)]
+    pub max_num_bytes_partial_ranges: ByteSize,
+    /// Byte ranges larger than this limit are never cached as partial ranges.
+    #[serde(default = "SplitCacheLimits::default_max_partial_range_num_bytes")]
+    pub max_partial_range_num_bytes: ByteSize,
 }
 
 impl SplitCacheLimits {
@@ -148,6 +171,10 @@ impl SplitCacheLimits {
     fn default_num_concurrent_downloads() -> NonZeroU32 {
         NonZeroU32::new(1).unwrap()
     }
+
+    fn default_max_partial_range_num_bytes() -> ByteSize {
+        ByteSize::mb(1)
+    }
 }
 
 impl Default for SplitCacheLimits {
@@ -156,6 +183,8 @@ impl Default for SplitCacheLimits {
             max_num_bytes: ByteSize::gb(1),
             max_num_splits: NonZeroU32::new(100).unwrap(),
             num_concurrent_downloads: NonZeroU32::new(1).unwrap(),
+            max_num_bytes_partial_ranges: ByteSize::default(),
+            max_partial_range_num_bytes: SplitCacheLimits::default_max_partial_range_num_bytes(),
         }
     }
 }
@@ -165,16 +194,39 @@ impl Default for SplitCacheLimits {
 pub struct SearcherConfig {
     pub aggregation_memory_limit: ByteSize,
     pub aggregation_bucket_limit: u32,
+    /// Directory where the aggregations exceeding `aggregation_bucket_limit` buckets in memory
+    /// spill their partial buckets. Defaults to the temporary directory of the system.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aggregation_spill_dir: Option<PathBuf>,
+    /// Maximum number of bytes an aggregation can spill to disk. Spilling is disabled if zero.
+    pub aggregation_spill_budget: ByteSize,
     pub fast_field_cache_capacity: ByteSize,
     pub split_footer_cache_capacity: ByteSize,
     pub partial_request_cache_capacity: ByteSize,
     pub max_num_concurrent_split_searches: usize,
     pub max_num_concurrent_split_streams: usize,
+    /// Maximum number of searches of an Elasticsearch-compatible multi search request executed
+    /// concurrently. Requests can lower it with the `max_concurrent_searches` parameter.
+    pub max_num_concurrent_multi_searches: usize,
+    /// Maximum number of fetch docs requests the root sends concurrently to a single searcher.
+    pub max_num_concurrent_fetch_docs_requests_per_node: usize,
+    /// Maximum number of splits whose documents are fetched by a single fetch docs request. The
+    /// hits of a searcher spanning more splits are fetched with several requests.
+    pub max_num_splits_per_fetch_docs_request: usize,
+    /// Maximum number of states of the automaton compiled for a regex query. Regex queries
+    /// exceeding this limit are rejected.
+    pub max_regex_automaton_states: usize,
+    /// Number of most recent splits searched by preview search requests.
+    pub preview_num_splits: usize,
     // Strangely, if None, this will also have the effect of not forwarding
     // to searcher.
     // TODO document and fix if necessary.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub split_cache: Option<SplitCacheLimits>,
+    /// Limits the number of concurrent search queries submitted with the same API key via the
+    /// REST API. Disabled if `None`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_query_limits: Option<ApiKeyQueryLimitsConfig>,
 }
 
 impl Default for SearcherConfig {
@@ -185,13 +237,219 @@ impl Default for SearcherConfig {
             partial_request_cache_capacity: ByteSize::mb(64),
             max_num_concurrent_split_streams: 100,
             max_num_concurrent_split_searches: 100,
+            max_num_concurrent_multi_searches: 10,
+            max_num_concurrent_fetch_docs_requests_per_node: 4,
+            max_num_splits_per_fetch_docs_request: 20,
+            max_regex_automaton_states: 10_000,
+            preview_num_splits: 10,
             aggregation_memory_limit: ByteSize::mb(500),
             aggregation_bucket_limit: 65000,
+            aggregation_spill_dir: None,
+            aggregation_spill_budget: ByteSize::gb(1),
             split_cache: None,
+            api_key_query_limits: None,
+        }
+    }
+}
+
+impl SearcherConfig {
+    fn validate(&self) -> anyhow::Result<()> {
+        if self.max_num_concurrent_multi_searches == 0 {
+            bail!("`searcher.max_num_concurrent_multi_searches` must be strictly positive");
+        }
+        if self.max_num_concurrent_fetch_docs_requests_per_node == 0 {
+            bail!(
+                "`searcher.max_num_concurrent_fetch_docs_requests_per_node` must be strictly \
+                 positive"
+            );
+        }
+        if self.max_num_splits_per_fetch_docs_request == 0 {
+            bail!("`searcher.max_num_splits_per_fetch_docs_request` must be strictly positive");
+        }
+        if self.max_regex_automaton_states == 0 {
+            bail!("`searcher.max_regex_automaton_states` must be strictly positive");
+        }
+        if self.preview_num_splits == 0 {
+            bail!("`searcher.preview_num_splits` must be strictly positive");
+        }
+        if let Some(api_key_query_limits) = &self.api_key_query_limits {
+            api_key_query_limits.validate()?;
+        }
+        Ok(())
+    }
+
+    fn redact(&mut self) {
+        if let Some(api_key_query_limits) = self.api_key_query_limits.as_mut() {
+            api_key_query_limits.redact();
+        }
+    }
+}
+
+/// Searcher settings adjusted at runtime across the cluster with the searcher config API. Unset
+/// settings fall back to the searcher config of each node.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SearcherConfigOverrides {
+    /// Overrides `searcher.aggregation_memory_limit`.
+    #[schema(value_type = Option<String>)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aggregation_memory_limit: Option<ByteSize>,
+    /// Overrides `searcher.aggregation_bucket_limit`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aggregation_bucket_limit: Option<u32>,
+    /// Overrides `searcher.max_num_concurrent_split_searches`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_num_concurrent_split_searches: Option<usize>,
+    /// Overrides `searcher.split_cache.max_num_bytes`. Ignored by the nodes without split cache.
+    #[schema(value_type = Option<String>)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub split_cache_max_num_bytes: Option<ByteSize>,
+}
+
+impl SearcherConfigOverrides {
+    /// Returns the current value of the settings that can be overridden.
+    pub fn from_searcher_config(searcher_config: &SearcherConfig) -> Self {
+        Self {
+            aggregation_memory_limit: Some(searcher_config.aggregation_memory_limit),
+            aggregation_bucket_limit: Some(searcher_config.aggregation_bucket_limit),
+            max_num_concurrent_split_searches: Some(
+                searcher_config.max_num_concurrent_split_searches,
+            ),
+            split_cache_max_num_bytes: searcher_config
+                .split_cache
+                .as_ref()
+                .map(|split_cache_limits| split_cache_limits.max_num_bytes),
+        }
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.aggregation_bucket_limit == Some(0) {
+            bail!("`aggregation_bucket_limit` must be strictly positive");
+        }
+        if self.max_num_concurrent_split_searches == Some(0) {
+            bail!("`max_num_concurrent_split_searches` must be strictly positive");
+        }
+        Ok(())
+    }
+
+    /// Applies the overrides to a searcher config.
+    pub fn apply(&self, searcher_config: &mut SearcherConfig) {
+        if let Some(aggregation_memory_limit) = self.aggregation_memory_limit {
+            searcher_config.aggregation_memory_limit = aggregation_memory_limit;
+        }
+        if let Some(aggregation_bucket_limit) = self.aggregation_bucket_limit {
+            searcher_config.aggregation_bucket_limit = aggregation_bucket_limit;
+        }
+        if let Some(max_num_concurrent_split_searches) = self.max_num_concurrent_split_searches {
+            searcher_config.max_num_concurrent_split_searches = max_num_concurrent_split_searches;
+        }
+        if let (Some(split_cache_max_num_bytes), Some(split_cache_limits)) = (
+            self.split_cache_max_num_bytes,
+            searcher_config.split_cache.as_mut(),
+        ) {
+            split_cache_limits.max_num_bytes = split_cache_max_num_bytes;
         }
     }
 }
 
+/// Per-API-key in-flight query limits enforced by the REST API.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiKeyQueryLimitsConfig {
+    /// Name of the HTTP header carrying the API key. Requests without this header share the
+    /// limits of the empty API key.
+    #[serde(default = "ApiKeyQueryLimitsConfig::default_api_key_header")]
+    pub api_key_header: String,
+    /// Maximum number of queries executed concurrently for a given API key.
+    pub max_num_concurrent_queries: NonZeroUsize,
+    /// Maximum number of queries waiting for a slot for a given API key. Queries exceeding this
+    /// limit are rejected with a `429 Too Many Requests` response.
+    #[serde(default)]
+    pub max_num_queued_queries: usize,
+    /// Value of the `Retry-After` header returned along with rejected queries.
+    #[serde(default = "ApiKeyQueryLimitsConfig::default_retry_after_secs")]
+    pub retry_after_secs: NonZeroU64,
+    /// Limits overriding the default ones for specific API keys.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub overrides: Vec<ApiKeyQueryLimitsOverride>,
+}
+
+impl ApiKeyQueryLimitsConfig {
+    fn default_api_key_header() -> String {
+        "x-api-key".to_string()
+    }
+
+    fn default_retry_after_secs() -> NonZeroU64 {
+        NonZeroU64::new(1).unwrap()
+    }
+
+    /// Returns the `(max_num_concurrent_queries, max_num_queued_queries)` limits applying to
+    /// `api_key`.
+    pub fn limits_for_api_key(&self, api_key: &str) -> (NonZeroUsize, usize) {
+        self.overrides
+            .iter()
+            .find(|api_key_override| api_key_override.api_key == api_key)
+            .map(|api_key_override| {
+                (
+                    api_key_override.max_num_concurrent_queries,
+                    api_key_override
+                        .max_num_queued_queries
+                        .unwrap_or(self.max_num_queued_queries),
+                )
+            })
+            .unwrap_or((self.max_num_concurrent_queries, self.max_num_queued_queries))
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        ensure!(
+            http::HeaderName::from_bytes(self.api_key_header.as_bytes()).is_ok(),
+            "API key header `{}` is not a valid HTTP header name",
+            self.api_key_header
+        );
+        let mut api_keys = HashSet::with_capacity(self.overrides.len());
+
+        for api_key_override in &self.overrides {
+            ensure!(
+                !api_key_override.api_key.is_empty(),
+                "API key query limits overrides must define a non-empty API key"
+            );
+            ensure!(
+                api_keys.insert(&api_key_override.api_key),
+                "API key query limits overrides must define each API key at most once"
+            );
+        }
+        Ok(())
+    }
+
+    fn redact(&mut self) {
+        for api_key_override in self.overrides.iter_mut() {
+            api_key_override.api_key = "***redacted***".to_string();
+        }
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiKeyQueryLimitsOverride {
+    pub api_key: String,
+    pub max_num_concurrent_queries: NonZeroUsize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_num_queued_queries: Option<usize>,
+}
+
+impl fmt::Debug for ApiKeyQueryLimitsOverride {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ApiKeyQueryLimitsOverride")
+            .field("api_key", &"***redacted***")
+            .field(
+                "max_num_concurrent_queries",
+                &self.max_num_concurrent_queries,
+            )
+            .field("max_num_queued_queries", &self.max_num_queued_queries)
+            .finish()
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(deny_unknown_fields, default)]
 pub struct IngestApiConfig {
@@ -199,6 +457,23 @@ pub struct IngestApiConfig {
     pub max_queue_disk_usage: ByteSize,
     pub replication_factor: usize,
     pub content_length_limit: ByteSize,
+    /// How long the response of an ingest request carrying an idempotency key is retained, i.e.
+    /// the time window during which a retried request is deduplicated.
+    pub idempotency_key_ttl_secs: u64,
+    /// Delay after which the router sends a persist request still in flight to the follower of its
+    /// shards as well, keeping whichever response comes first. Hedging is disabled if not set.
+    pub persist_hedging_delay_ms: Option<u64>,
+    /// Rules routing the documents sent to an index to indexes computed from their content.
+    pub routing_rules: Vec<IngestRoutingRule>,
+    /// Disk usage of the write-ahead log, as a percentage of `max_queue_disk_usage`, above which
+    /// the ingester stops accepting writes and closes its shards.
+    pub disk_high_watermark_percent: u8,
+    /// Disk usage of the write-ahead log, as a percentage of `max_queue_disk_usage`, below which
+    /// the ingester accepts writes again after having exceeded the high watermark.
+    pub disk_low_watermark_percent: u8,
+    /// Duration after which a shard that has not received any write is closed, which releases the
+    /// resources it holds on the ingester once it has been indexed.
+    pub shard_idle_timeout_secs: u64,
 }
 
 impl Default for IngestApiConfig {
@@ -208,11 +483,37 @@ impl Default for IngestApiConfig {
             max_queue_disk_usage: ByteSize::gib(4),   // TODO maybe we want more?
             replication_factor: 1,
             content_length_limit: ByteSize::mib(10),
+            idempotency_key_ttl_secs: 3_600,
+            persist_hedging_delay_ms: None,
+            routing_rules: Vec::new(),
+            disk_high_watermark_percent: 90,
+            disk_low_watermark_percent: 80,
+            shard_idle_timeout_secs: 1_800,
         }
     }
 }
 
 impl IngestApiConfig {
+    pub fn idempotency_key_ttl(&self) -> Duration {
+        Duration::from_secs(self.idempotency_key_ttl_secs)
+    }
+
+    pub fn persist_hedging_delay(&self) -> Option<Duration> {
+        self.persist_hedging_delay_ms.map(Duration::from_millis)
+    }
+
+    pub fn shard_idle_timeout(&self) -> Duration {
+        Duration::from_secs(self.shard_idle_timeout_secs)
+    }
+
+    pub fn disk_high_watermark(&self) -> ByteSize {
+        percent_of(self.max_queue_disk_usage, self.disk_high_watermark_percent)
+    }
+
+    pub fn disk_low_watermark(&self) -> ByteSize {
+        percent_of(self.max_queue_disk_usage, self.disk_low_watermark_percent)
+    }
+
     pub fn replication_factor(&self) -> anyhow::Result<NonZeroUsize> {
         if let Ok(replication_factor_str) = env::var("QW_INGEST_REPLICATION_FACTOR") {
             let replication_factor = match replication_factor_str.trim() {
@@ -236,8 +537,220 @@ impl IngestApiConfig {
 
     fn validate(&self) -> anyhow::Result<()> {
         self.replication_factor()?;
+
+        ensure!(
+            self.disk_high_watermark_percent <= 100,
+            "disk high watermark must be a percentage between 0 and 100, got `{}`",
+            self.disk_high_watermark_percent
+        );
+        ensure!(
+            self.disk_low_watermark_percent < self.disk_high_watermark_percent,
+            "disk low watermark (`{}`) must be lower than disk high watermark (`{}`)",
+            self.disk_low_watermark_percent,
+            self.disk_high_watermark_percent
+        );
+        ensure!(
+            self.shard_idle_timeout_secs > 0,
+            "shard idle timeout must be strictly positive, got `{}`",
+            self.shard_idle_timeout_secs
+        );
+        if let Some(persist_hedging_delay_ms) = self.persist_hedging_delay_ms {
+            ensure!(
+                persist_hedging_delay_ms > 0,
+                "persist hedging delay must be strictly positive, got `{persist_hedging_delay_ms}`"
+            );
+        }
+        let mut routed_index_ids = HashSet::with_capacity(self.routing_rules.len());
+
+        for routing_rule in &self.routing_rules {
+            routing_rule.validate()?;
+
+            if !routed_index_ids.insert(&routing_rule.index_id) {
+                bail!(
+                    "index `{}` is the target of several routing rules",
+                    routing_rule.index_id
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+fn percent_of(num_bytes: ByteSize, percent: u8) -> ByteSize {
+    ByteSize(num_bytes.as_u64() / 100 * percent as u64)
+}
+
+/// Routes the documents of the ingest requests targeting `index_id` to the index returned by a
+/// [VRL] expression evaluated against each document.
+///
+/// [VRL]: https://vector.dev/docs/reference/vrl/
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IngestRoutingRule {
+    /// ID targeted by the ingest requests routed by the rule. It does not have to be the ID of an
+    /// existing index.
+    pub index_id: String,
+    /// VRL expression returning the ID of the target index of a document.
+    pub expression: String,
+    /// Index receiving the documents for which the expression fails, does not return a valid
+    /// index ID, or returns the ID of an index that does not exist.
+    pub fallback_index_id: String,
+}
+
+impl IngestRoutingRule {
+    fn validate(&self) -> anyhow::Result<()> {
+        validate_identifier("routed index ID", &self.index_id)?;
+        validate_identifier("fallback index ID", &self.fallback_index_id)?;
+        ensure!(
+            self.fallback_index_id != self.index_id,
+            "fallback index of routing rule for index `{}` must be a different index",
+            self.index_id
+        );
+        self.validate_expression()
+    }
+
+    #[cfg(feature = "vrl")]
+    fn validate_expression(&self) -> anyhow::Result<()> {
+        self.compile_expression()?;
+        Ok(())
+    }
+
+    #[cfg(not(feature = "vrl"))]
+    fn validate_expression(&self) -> anyhow::Result<()> {
+        // Same as for the transforms, the missing VRL feature is reported when the router is
+        // instantiated.
         Ok(())
     }
+
+    /// Compiles the expression to a VRL [`Program`](vrl::compiler::Program).
+    #[cfg(feature = "vrl")]
+    pub fn compile_expression(&self) -> anyhow::Result<vrl::compiler::Program> {
+        let functions = vrl::stdlib::all();
+
+        match vrl::compiler::compile(&self.expression, &functions) {
+            Ok(compilation_res) => Ok(compilation_res.program),
+            Err(diagnostics) => {
+                let mut formatter = vrl::diagnostic::Formatter::new(&self.expression, diagnostics);
+                formatter.enable_colors(!quickwit_common::no_color());
+                bail!(
+                    "failed to compile expression of routing rule for index `{}`:\n {formatter}",
+                    self.index_id
+                )
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MetricsConfig {
+    /// Labels the ingest and search metrics with the ID of the index they relate to.
+    #[serde(default)]
+    pub per_index_labels: bool,
+    /// Maximum number of index IDs used as label values. The metrics of the indexes beyond this
+    /// limit are reported under the `other` label.
+    #[serde(default = "MetricsConfig::default_max_index_labels")]
+    pub max_index_labels: usize,
+}
+
+impl MetricsConfig {
+    fn default_max_index_labels() -> usize {
+        100
+    }
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            per_index_labels: false,
+            max_index_labels: Self::default_max_index_labels(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JanitorConfig {
+    /// Maximum number of delete operations executed concurrently on the node, across all indexes.
+    #[serde(default = "JanitorConfig::default_max_concurrent_delete_operations")]
+    pub max_concurrent_delete_operations: NonZeroUsize,
+    /// Maximum number of bytes rewritten per hour by the delete operations of the node, across
+    /// all indexes. When set, this limit supersedes the indexes' `max_merge_write_throughput`
+    /// for delete operations.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_delete_bytes_per_hour: Option<ByteSize>,
+    /// Starts the delete task service paused. Delete tasks are still accepted but not executed
+    /// until the service is resumed.
+    #[serde(default)]
+    pub pause_delete_tasks: bool,
+    /// Interval in hours between two deep scans of the indexes' storage looking for orphan split
+    /// files, i.e. split files that the metastore does not reference. Deep scans are disabled
+    /// when unset.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub orphan_files_scan_interval_hours: Option<NonZeroU64>,
+    /// Period in hours after which the orphan split files found by a deep scan are deleted.
+    /// Orphan files are only reported when unset.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub orphan_files_grace_period_hours: Option<NonZeroU64>,
+}
+
+impl JanitorConfig {
+    fn default_max_concurrent_delete_operations() -> NonZeroUsize {
+        NonZeroUsize::new(2).unwrap()
+    }
+
+    /// Returns the throughput limit of the delete operations in bytes per second.
+    pub fn delete_throughput_limit(&self) -> Option<f64> {
+        self.max_delete_bytes_per_hour
+            .map(|bytes_per_hour| bytes_per_hour.as_u64() as f64 / 3600.0)
+    }
+
+    pub fn orphan_files_scan_interval(&self) -> Option<Duration> {
+        self.orphan_files_scan_interval_hours
+            .map(|interval_hours| Duration::from_secs(interval_hours.get() * 3600))
+    }
+
+    pub fn orphan_files_grace_period(&self) -> Option<Duration> {
+        self.orphan_files_grace_period_hours
+            .map(|grace_period_hours| Duration::from_secs(grace_period_hours.get() * 3600))
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        if let Some(max_delete_bytes_per_hour) = self.max_delete_bytes_per_hour {
+            ensure!(
+                max_delete_bytes_per_hour.as_u64() > 0,
+                "`janitor.max_delete_bytes_per_hour` must be strictly positive"
+            );
+        }
+        if let Some(orphan_files_grace_period_hours) = self.orphan_files_grace_period_hours {
+            ensure!(
+                self.orphan_files_scan_interval_hours.is_some(),
+                "`janitor.orphan_files_grace_period_hours` requires \
+                 `janitor.orphan_files_scan_interval_hours` to be set"
+            );
+            // Uploads and publishes can take a while, so we keep orphan files for at least a day.
+            ensure!(
+                orphan_files_grace_period_hours.get() >= 24,
+                "`janitor.orphan_files_grace_period_hours` must be greater than or equal to 24"
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Default for JanitorConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent_delete_operations: Self::default_max_concurrent_delete_operations(),
+            max_delete_bytes_per_hour: None,
+            pause_delete_tasks: false,
+            orphan_files_scan_interval_hours: None,
+            orphan_files_grace_period_hours: None,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
@@ -309,6 +822,37 @@ impl Default for JaegerConfig {
     }
 }
 
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TlsConfig {
+    /// Path to the PEM-encoded certificate chain presented by the REST and gRPC listeners.
+    pub cert_path: PathBuf,
+    /// Path to the PEM-encoded private key of the certificate.
+    pub key_path: PathBuf,
+    /// Path to the PEM-encoded CA certificates used to verify the certificates presented by the
+    /// other nodes of the cluster when connecting to them over gRPC.
+    pub ca_path: PathBuf,
+    /// Domain name expected in the certificates presented by the other nodes. When unset, the
+    /// certificates must be issued for the gRPC advertise address of the nodes.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub domain_name: Option<String>,
+    /// Interval in seconds between two checks of the certificate and private key files for
+    /// changes. The files are also reloaded when the process receives a `SIGHUP` signal.
+    #[serde(default = "TlsConfig::default_reload_interval_secs")]
+    pub reload_interval_secs: NonZeroU64,
+}
+
+impl TlsConfig {
+    fn default_reload_interval_secs() -> NonZeroU64 {
+        NonZeroU64::new(60).unwrap()
+    }
+
+    pub fn reload_interval(&self) -> Duration {
+        Duration::from_secs(self.reload_interval_secs.get())
+    }
+}
+
 #[derive(Clone, Debug, Serialize)]
 pub struct NodeConfig {
     pub cluster_id: String,
@@ -319,6 +863,9 @@ pub struct NodeConfig {
     pub gossip_advertise_addr: SocketAddr,
     pub grpc_advertise_addr: SocketAddr,
     pub peer_seeds: Vec<String>,
+    /// Labels gossiped to the rest of the cluster and matched against the placement constraints
+    /// of the indexes and sources by the indexing scheduler.
+    pub node_labels: NodeLabels,
     pub data_dir_path: PathBuf,
     pub metastore_uri: Uri,
     pub default_index_root_uri: Uri,
@@ -329,6 +876,11 @@ pub struct NodeConfig {
     pub searcher_config: SearcherConfig,
     pub ingest_api_config: IngestApiConfig,
     pub jaeger_config: JaegerConfig,
+    pub metrics_config: MetricsConfig,
+    pub janitor_config: JanitorConfig,
+    /// TLS settings of the REST and gRPC listeners and of the gRPC clients connecting to the other
+    /// nodes. TLS is disabled when unset.
+    pub tls_config: Option<TlsConfig>,
 }
 
 impl NodeConfig {
@@ -338,8 +890,7 @@ impl NodeConfig {
 
     /// Parses and validates a [`NodeConfig`] from a given URI and config content.
     pub async fn load(config_format: ConfigFormat, config_content: &[u8]) -> anyhow::Result<Self> {
-        let env_vars = env::vars().collect::<HashMap<_, _>>();
-        let config = load_node_config_with_env(config_format, config_content, &env_vars).await?;
+        let config = Self::parse(config_format, config_content).await?;
         if !config.data_dir_path.try_exists()? {
             bail!(
                 "data dir `{}` does not exist",
@@ -349,6 +900,13 @@ impl NodeConfig {
         Ok(config)
     }
 
+    /// Parses and validates a node config without checking that its data dir exists on the local
+    /// host.
+    pub async fn parse(config_format: ConfigFormat, config_content: &[u8]) -> anyhow::Result<Self> {
+        let env_vars = env::vars().collect::<HashMap<_, _>>();
+        load_node_config_with_env(config_format, config_content, &env_vars).await
+    }
+
     /// Returns the list of peer seed addresses. The addresses MUST NOT be resolved. Otherwise, the
     /// DNS-based discovery mechanism implemented in Chitchat will not work correctly.
     pub async fn peer_seed_addrs(&self) -> anyhow::Result<Vec<String>> {
@@ -380,6 +938,7 @@ impl NodeConfig {
         self.metastore_uri.redact();
         self.storage_configs.redact();
         self.metastore_configs.redact();
+        self.searcher_config.redact();
     }
 
     #[cfg(any(test, feature = "testsuite"))]
@@ -390,9 +949,10 @@ impl NodeConfig {
 
 #[cfg(test)]
 mod tests {
+    use bytesize::ByteSize;
     use quickwit_proto::indexing::CpuCapacity;
 
-    use crate::IndexerConfig;
+    use crate::{IndexerConfig, SearcherConfig, SearcherConfigOverrides, SplitCacheLimits};
 
     #[test]
     fn test_index_config_serialization() {
@@ -437,4 +997,43 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_searcher_config_overrides() {
+        let overrides: SearcherConfigOverrides = serde_json::from_str(
+            r#"{"aggregation_memory_limit": "1GB", "max_num_concurrent_split_searches": 50, "split_cache_max_num_bytes": "10GB"}"#,
+        )
+        .unwrap();
+        overrides.validate().unwrap();
+
+        let mut searcher_config = SearcherConfig::default();
+        overrides.apply(&mut searcher_config);
+        assert_eq!(searcher_config.aggregation_memory_limit, ByteSize::gb(1));
+        assert_eq!(searcher_config.aggregation_bucket_limit, 65_000);
+        assert_eq!(searcher_config.max_num_concurrent_split_searches, 50);
+        assert!(searcher_config.split_cache.is_none());
+
+        let split_cache_limits: SplitCacheLimits =
+            serde_json::from_str(r#"{"max_num_bytes": "1GB"}"#).unwrap();
+        searcher_config.split_cache = Some(split_cache_limits);
+        overrides.apply(&mut searcher_config);
+        assert_eq!(
+            searcher_config.split_cache.as_ref().unwrap().max_num_bytes,
+            ByteSize::gb(10)
+        );
+        let current_settings = SearcherConfigOverrides::from_searcher_config(&searcher_config);
+        assert_eq!(current_settings.aggregation_bucket_limit, Some(65_000));
+        assert_eq!(
+            current_settings.split_cache_max_num_bytes,
+            Some(ByteSize::gb(10))
+        );
+
+        let invalid_overrides = SearcherConfigOverrides {
+            max_num_concurrent_split_searches: Some(0),
+            ..Default::default()
+        };
+        invalid_overrides.validate().unwrap_err();
+
+        serde_json::from_str::<SearcherConfigOverrides>(r#"{"unknown": 1}"#).unwrap_err();
+    }
 }
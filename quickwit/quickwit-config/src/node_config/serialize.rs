@@ -29,15 +29,17 @@ use quickwit_common::uri::Uri;
 use serde::{Deserialize, Serialize};
 use tracing::{info, warn};
 
-use super::RestConfig;
+use super::{RestConfig, RouteHeadersConfig};
 use crate::config_value::ConfigValue;
+use crate::placement::{validate_node_labels, NodeLabels};
 use crate::qw_env_vars::*;
 use crate::service::QuickwitService;
 use crate::storage_config::StorageConfigs;
 use crate::templating::render_config;
 use crate::{
     validate_identifier, validate_node_id, ConfigFormat, IndexerConfig, IngestApiConfig,
-    JaegerConfig, MetastoreConfigs, NodeConfig, SearcherConfig,
+    JaegerConfig, JanitorConfig, MetastoreConfigs, MetricsConfig, NodeConfig, SearcherConfig,
+    TlsConfig,
 };
 
 pub const DEFAULT_CLUSTER_ID: &str = "quickwit-default-cluster";
@@ -176,6 +178,8 @@ struct NodeConfigBuilder {
     grpc_listen_port: ConfigValue<u16, QW_GRPC_LISTEN_PORT>,
     #[serde(default)]
     peer_seeds: ConfigValue<List, QW_PEER_SEEDS>,
+    #[serde(default)]
+    node_labels: NodeLabels,
     #[serde(rename = "data_dir")]
     #[serde(default = "default_data_dir_uri")]
     data_dir_uri: ConfigValue<Uri, QW_DATA_DIR>,
@@ -202,6 +206,15 @@ struct NodeConfigBuilder {
     #[serde(rename = "jaeger")]
     #[serde(default)]
     jaeger_config: JaegerConfig,
+    #[serde(rename = "metrics")]
+    #[serde(default)]
+    metrics_config: MetricsConfig,
+    #[serde(rename = "janitor")]
+    #[serde(default)]
+    janitor_config: JanitorConfig,
+    #[serde(rename = "tls")]
+    #[serde(default)]
+    tls_config: Option<TlsConfig>,
 }
 
 impl NodeConfigBuilder {
@@ -282,6 +295,8 @@ impl NodeConfigBuilder {
         self.storage_configs.validate()?;
         self.storage_configs.apply_flavors();
         self.ingest_api_config.validate()?;
+        self.searcher_config.validate()?;
+        self.janitor_config.validate()?;
 
         let node_config = NodeConfig {
             cluster_id: self.cluster_id.resolve(env_vars)?,
@@ -292,6 +307,7 @@ impl NodeConfigBuilder {
             gossip_advertise_addr,
             grpc_advertise_addr,
             peer_seeds: self.peer_seeds.resolve(env_vars)?.0,
+            node_labels: self.node_labels,
             data_dir_path,
             metastore_uri,
             default_index_root_uri,
@@ -302,6 +318,9 @@ impl NodeConfigBuilder {
             searcher_config: self.searcher_config,
             ingest_api_config: self.ingest_api_config,
             jaeger_config: self.jaeger_config,
+            metrics_config: self.metrics_config,
+            janitor_config: self.janitor_config,
+            tls_config: self.tls_config,
         };
 
         validate(&node_config)?;
@@ -312,6 +331,7 @@ impl NodeConfigBuilder {
 fn validate(node_config: &NodeConfig) -> anyhow::Result<()> {
     validate_identifier("Cluster ID", &node_config.cluster_id)?;
     validate_node_id(&node_config.node_id)?;
+    validate_node_labels(&node_config.node_labels)?;
 
     if node_config.cluster_id == DEFAULT_CLUSTER_ID {
         warn!(
@@ -338,6 +358,7 @@ impl Default for NodeConfigBuilder {
             grpc_listen_port: ConfigValue::none(),
             advertise_address: ConfigValue::none(),
             peer_seeds: ConfigValue::with_default(List::default()),
+            node_labels: NodeLabels::new(),
             data_dir_uri: default_data_dir_uri(),
             metastore_uri: ConfigValue::none(),
             default_index_root_uri: ConfigValue::none(),
@@ -348,6 +369,9 @@ impl Default for NodeConfigBuilder {
             searcher_config: SearcherConfig::default(),
             ingest_api_config: IngestApiConfig::default(),
             jaeger_config: JaegerConfig::default(),
+            metrics_config: MetricsConfig::default(),
+            janitor_config: JanitorConfig::default(),
+            tls_config: None,
         }
     }
 }
@@ -361,11 +385,22 @@ struct RestConfigBuilder {
     #[serde(default)]
     #[serde_as(deserialize_as = "serde_with::OneOrMany<_>")]
     pub cors_allow_origins: Vec<String>,
+    #[serde(default)]
+    #[serde_as(deserialize_as = "serde_with::OneOrMany<_>")]
+    pub cors_allow_headers: Vec<String>,
+    #[serde(default)]
+    #[serde_as(deserialize_as = "serde_with::OneOrMany<_>")]
+    pub cors_allow_methods: Vec<String>,
     #[serde(with = "http_serde::header_map")]
     #[serde(default)]
     pub extra_headers: HeaderMap,
+    #[serde(default)]
+    pub route_headers: Vec<RouteHeadersConfig>,
 }
 
+/// Methods allowed in CORS requests if `cors_allow_methods` is not set.
+const DEFAULT_CORS_ALLOW_METHODS: [&str; 5] = ["GET", "POST", "PUT", "DELETE", "OPTIONS"];
+
 impl RestConfigBuilder {
     fn build_and_validate(
         self,
@@ -378,10 +413,47 @@ impl RestConfigBuilder {
             listen_port_from_config_or_default,
         )
         .resolve(env_vars)?;
+        for origin in &self.cors_allow_origins {
+            if origin != "*" && http::HeaderValue::from_str(origin).is_err() {
+                bail!("invalid CORS origin `{origin}`");
+            }
+        }
+        for header in &self.cors_allow_headers {
+            if header != "*" && http::HeaderName::from_str(header).is_err() {
+                bail!("invalid CORS header `{header}`");
+            }
+        }
+        let cors_allow_methods = if self.cors_allow_methods.is_empty() {
+            DEFAULT_CORS_ALLOW_METHODS
+                .iter()
+                .map(|method| method.to_string())
+                .collect()
+        } else {
+            self.cors_allow_methods
+                .into_iter()
+                .map(|method| method.to_ascii_uppercase())
+                .collect()
+        };
+        for method in &cors_allow_methods {
+            if http::Method::from_str(method).is_err() {
+                bail!("invalid CORS method `{method}`");
+            }
+        }
+        for route_headers in &self.route_headers {
+            if !route_headers.path_prefix.starts_with('/') {
+                bail!(
+                    "invalid route headers path prefix `{}`: path prefixes must start with `/`",
+                    route_headers.path_prefix
+                );
+            }
+        }
         let rest_config = RestConfig {
             listen_addr: SocketAddr::new(listen_ip, listen_port),
             cors_allow_origins: self.cors_allow_origins,
+            cors_allow_headers: self.cors_allow_headers,
+            cors_allow_methods,
             extra_headers: self.extra_headers,
+            route_headers: self.route_headers,
         };
         Ok(rest_config)
     }
@@ -418,7 +490,13 @@ pub fn node_config_for_test() -> NodeConfig {
     let rest_config = RestConfig {
         listen_addr: rest_listen_addr,
         cors_allow_origins: Vec::new(),
+        cors_allow_headers: Vec::new(),
+        cors_allow_methods: DEFAULT_CORS_ALLOW_METHODS
+            .iter()
+            .map(|method| method.to_string())
+            .collect(),
         extra_headers: HeaderMap::new(),
+        route_headers: Vec::new(),
     };
     NodeConfig {
         cluster_id: default_cluster_id().unwrap(),
@@ -429,6 +507,7 @@ pub fn node_config_for_test() -> NodeConfig {
         gossip_listen_addr,
         grpc_listen_addr,
         peer_seeds: Vec::new(),
+        node_labels: NodeLabels::new(),
         data_dir_path,
         metastore_uri,
         default_index_root_uri,
@@ -439,6 +518,9 @@ pub fn node_config_for_test() -> NodeConfig {
         searcher_config: SearcherConfig::default(),
         ingest_api_config: IngestApiConfig::default(),
         jaeger_config: JaegerConfig::default(),
+        metrics_config: MetricsConfig::default(),
+        janitor_config: JanitorConfig::default(),
+        tls_config: None,
     }
 }
 
@@ -446,14 +528,16 @@ pub fn node_config_for_test() -> NodeConfig {
 mod tests {
     use std::env;
     use std::net::Ipv4Addr;
-    use std::num::NonZeroU64;
-    use std::path::Path;
+    use std::num::{NonZeroU64, NonZeroUsize};
+    use std::path::{Path, PathBuf};
+    use std::time::Duration;
 
     use bytesize::ByteSize;
     use itertools::Itertools;
 
     use super::*;
     use crate::storage_config::StorageBackendFlavor;
+    use crate::IngestRoutingRule;
 
     fn get_config_filepath(config_filename: &str) -> String {
         format!(
@@ -510,6 +594,13 @@ mod tests {
                 "quickwit-searcher-1.local".to_string()
             ]
         );
+        assert_eq!(
+            config.node_labels,
+            NodeLabels::from_iter([
+                ("zone".to_string(), "eu-west-1a".to_string()),
+                ("tier".to_string(), "high-mem".to_string()),
+            ])
+        );
         assert_eq!(config.data_dir_path, Path::new("/opt/quickwit/data"));
         assert_eq!(
             config.metastore_uri,
@@ -559,12 +650,20 @@ mod tests {
             SearcherConfig {
                 aggregation_memory_limit: ByteSize::gb(1),
                 aggregation_bucket_limit: 500_000,
+                aggregation_spill_dir: Some(PathBuf::from("/opt/quickwit/aggregation-spill")),
+                aggregation_spill_budget: ByteSize::gb(2),
                 fast_field_cache_capacity: ByteSize::gb(10),
                 split_footer_cache_capacity: ByteSize::gb(1),
                 partial_request_cache_capacity: ByteSize::mb(64),
                 max_num_concurrent_split_searches: 150,
                 max_num_concurrent_split_streams: 120,
+                max_num_concurrent_multi_searches: 20,
+                max_num_concurrent_fetch_docs_requests_per_node: 8,
+                max_num_splits_per_fetch_docs_request: 50,
+                max_regex_automaton_states: 5_000,
+                preview_num_splits: 20,
                 split_cache: None,
+                api_key_query_limits: None,
             }
         );
         assert_eq!(
@@ -789,6 +888,9 @@ mod tests {
         assert_eq!(config.searcher_config, SearcherConfig::default());
         assert_eq!(config.ingest_api_config, IngestApiConfig::default());
         assert_eq!(config.jaeger_config, JaegerConfig::default());
+        assert_eq!(config.metrics_config, MetricsConfig::default());
+        assert_eq!(config.janitor_config, JanitorConfig::default());
+        assert!(config.tls_config.is_none());
     }
 
     #[tokio::test]
@@ -1054,6 +1156,121 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_metrics_config_serde() {
+        let metrics_config_yaml = r#"
+            per_index_labels: true
+        "#;
+        let metrics_config = serde_yaml::from_str::<MetricsConfig>(metrics_config_yaml).unwrap();
+        assert!(metrics_config.per_index_labels);
+        assert_eq!(metrics_config.max_index_labels, 100);
+    }
+
+    #[test]
+    fn test_janitor_config_serde() {
+        let janitor_config_yaml = r#"
+            max_concurrent_delete_operations: 4
+            max_delete_bytes_per_hour: 36MB
+        "#;
+        let janitor_config = serde_yaml::from_str::<JanitorConfig>(janitor_config_yaml).unwrap();
+        assert_eq!(janitor_config.max_concurrent_delete_operations.get(), 4);
+        assert_eq!(
+            janitor_config.max_delete_bytes_per_hour,
+            Some(ByteSize::mb(36))
+        );
+        assert_eq!(janitor_config.delete_throughput_limit(), Some(10_000.0));
+        assert!(!janitor_config.pause_delete_tasks);
+        janitor_config.validate().unwrap();
+
+        let janitor_config_yaml = r#"
+            max_delete_bytes_per_hour: 0B
+        "#;
+        let janitor_config = serde_yaml::from_str::<JanitorConfig>(janitor_config_yaml).unwrap();
+        janitor_config.validate().unwrap_err();
+
+        let janitor_config_yaml = r#"
+            max_concurrent_delete_operations: 0
+        "#;
+        serde_yaml::from_str::<JanitorConfig>(janitor_config_yaml).unwrap_err();
+
+        let janitor_config_yaml = r#"
+            orphan_files_scan_interval_hours: 24
+            orphan_files_grace_period_hours: 72
+        "#;
+        let janitor_config = serde_yaml::from_str::<JanitorConfig>(janitor_config_yaml).unwrap();
+        assert_eq!(
+            janitor_config.orphan_files_scan_interval(),
+            Some(Duration::from_secs(24 * 3600))
+        );
+        assert_eq!(
+            janitor_config.orphan_files_grace_period(),
+            Some(Duration::from_secs(72 * 3600))
+        );
+        janitor_config.validate().unwrap();
+
+        let janitor_config_yaml = r#"
+            orphan_files_scan_interval_hours: 24
+            orphan_files_grace_period_hours: 1
+        "#;
+        let janitor_config = serde_yaml::from_str::<JanitorConfig>(janitor_config_yaml).unwrap();
+        janitor_config.validate().unwrap_err();
+
+        let janitor_config_yaml = r#"
+            orphan_files_grace_period_hours: 72
+        "#;
+        let janitor_config = serde_yaml::from_str::<JanitorConfig>(janitor_config_yaml).unwrap();
+        janitor_config.validate().unwrap_err();
+    }
+
+    #[test]
+    fn test_tls_config_serde() {
+        let tls_config_yaml = r#"
+            cert_path: /etc/quickwit/tls/node.crt
+            key_path: /etc/quickwit/tls/node.key
+            ca_path: /etc/quickwit/tls/ca.crt
+        "#;
+        let tls_config = serde_yaml::from_str::<TlsConfig>(tls_config_yaml).unwrap();
+        assert_eq!(
+            tls_config.cert_path,
+            PathBuf::from("/etc/quickwit/tls/node.crt")
+        );
+        assert_eq!(
+            tls_config.key_path,
+            PathBuf::from("/etc/quickwit/tls/node.key")
+        );
+        assert_eq!(
+            tls_config.ca_path,
+            PathBuf::from("/etc/quickwit/tls/ca.crt")
+        );
+        assert!(tls_config.domain_name.is_none());
+        assert_eq!(tls_config.reload_interval(), Duration::from_secs(60));
+
+        let tls_config_yaml = r#"
+            cert_path: /etc/quickwit/tls/node.crt
+            key_path: /etc/quickwit/tls/node.key
+            ca_path: /etc/quickwit/tls/ca.crt
+            domain_name: quickwit.internal
+            reload_interval_secs: 10
+        "#;
+        let tls_config = serde_yaml::from_str::<TlsConfig>(tls_config_yaml).unwrap();
+        assert_eq!(tls_config.domain_name.as_deref(), Some("quickwit.internal"));
+        assert_eq!(tls_config.reload_interval(), Duration::from_secs(10));
+
+        let tls_config_yaml = r#"
+            cert_path: /etc/quickwit/tls/node.crt
+            key_path: /etc/quickwit/tls/node.key
+        "#;
+        serde_yaml::from_str::<TlsConfig>(tls_config_yaml).unwrap_err();
+
+        let tls_config_yaml = r#"
+            cert_path: /etc/quickwit/tls/node.crt
+            key_path: /etc/quickwit/tls/node.key
+            ca_path: /etc/quickwit/tls/ca.crt
+            reload_interval_secs: 0
+        "#;
+        serde_yaml::from_str::<TlsConfig>(tls_config_yaml).unwrap_err();
+    }
+
     #[test]
     fn test_jaeger_config_rejects_null_values() {
         let jaeger_config_yaml = r#"
@@ -1191,6 +1408,108 @@ mod tests {
         .expect_err("Config should not allow empty origins.");
     }
 
+    #[tokio::test]
+    async fn test_rest_config_cors_headers_and_methods() {
+        let rest_config_yaml = r#"
+            version: 0.6
+            rest:
+              cors_allow_origins: '*'
+        "#;
+        let config = load_node_config_with_env(
+            ConfigFormat::Yaml,
+            rest_config_yaml.as_bytes(),
+            &Default::default(),
+        )
+        .await
+        .unwrap();
+        assert!(config.rest_config.cors_allow_headers.is_empty());
+        assert_eq!(
+            config.rest_config.cors_allow_methods,
+            ["GET", "POST", "PUT", "DELETE", "OPTIONS"]
+        );
+
+        let rest_config_yaml = r#"
+            version: 0.6
+            rest:
+              cors_allow_origins: '*'
+              cors_allow_headers:
+                - content-type
+                - authorization
+              cors_allow_methods:
+                - get
+                - post
+        "#;
+        let config = load_node_config_with_env(
+            ConfigFormat::Yaml,
+            rest_config_yaml.as_bytes(),
+            &Default::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            config.rest_config.cors_allow_headers,
+            ["content-type", "authorization"]
+        );
+        assert_eq!(config.rest_config.cors_allow_methods, ["GET", "POST"]);
+
+        let rest_config_yaml = r#"
+            version: 0.6
+            rest:
+              cors_allow_headers: 'invalid header'
+        "#;
+        let error = load_node_config_with_env(
+            ConfigFormat::Yaml,
+            rest_config_yaml.as_bytes(),
+            &Default::default(),
+        )
+        .await
+        .unwrap_err();
+        assert!(error.to_string().contains("invalid CORS header"));
+    }
+
+    #[tokio::test]
+    async fn test_rest_config_route_headers() {
+        let rest_config_yaml = r#"
+            version: 0.6
+            rest:
+              route_headers:
+                - path_prefix: /api/v1/_elastic
+                  headers:
+                    cache-control: no-store
+        "#;
+        let config = load_node_config_with_env(
+            ConfigFormat::Yaml,
+            rest_config_yaml.as_bytes(),
+            &Default::default(),
+        )
+        .await
+        .unwrap();
+        let route_headers = &config.rest_config.route_headers;
+        assert_eq!(route_headers.len(), 1);
+        assert_eq!(route_headers[0].path_prefix, "/api/v1/_elastic");
+        assert_eq!(
+            route_headers[0].headers.get("cache-control").unwrap(),
+            "no-store"
+        );
+
+        let rest_config_yaml = r#"
+            version: 0.6
+            rest:
+              route_headers:
+                - path_prefix: api/v1
+                  headers:
+                    cache-control: no-store
+        "#;
+        let error = load_node_config_with_env(
+            ConfigFormat::Yaml,
+            rest_config_yaml.as_bytes(),
+            &Default::default(),
+        )
+        .await
+        .unwrap_err();
+        assert!(error.to_string().contains("must start with `/`"));
+    }
+
     #[tokio::test]
     async fn test_node_config_validates_ingest_config() {
         let ingest_config = IngestApiConfig {
@@ -1207,6 +1526,35 @@ mod tests {
         let error_message = ingest_config.validate().unwrap_err().to_string();
         assert!(error_message.contains("either 1 or 2, got `3`"));
 
+        let ingest_config = IngestApiConfig {
+            disk_high_watermark_percent: 101,
+            ..Default::default()
+        };
+        let error_message = ingest_config.validate().unwrap_err().to_string();
+        assert!(error_message.contains("between 0 and 100, got `101`"));
+
+        let ingest_config = IngestApiConfig {
+            disk_high_watermark_percent: 80,
+            disk_low_watermark_percent: 80,
+            ..Default::default()
+        };
+        let error_message = ingest_config.validate().unwrap_err().to_string();
+        assert!(error_message.contains("must be lower than disk high watermark"));
+
+        let ingest_config = IngestApiConfig {
+            persist_hedging_delay_ms: Some(0),
+            ..Default::default()
+        };
+        let error_message = ingest_config.validate().unwrap_err().to_string();
+        assert!(error_message.contains("persist hedging delay must be strictly positive"));
+
+        let ingest_config = IngestApiConfig {
+            shard_idle_timeout_secs: 0,
+            ..Default::default()
+        };
+        let error_message = ingest_config.validate().unwrap_err().to_string();
+        assert!(error_message.contains("shard idle timeout must be strictly positive"));
+
         let node_config_yaml = r#"
             version: 0.6
             ingest_api:
@@ -1222,4 +1570,132 @@ mod tests {
         .to_string();
         assert!(error_message.contains("replication factor"));
     }
+
+    #[test]
+    fn test_ingest_config_disk_watermarks() {
+        let ingest_config = IngestApiConfig {
+            max_queue_disk_usage: ByteSize::gb(4),
+            ..Default::default()
+        };
+        assert_eq!(ingest_config.disk_high_watermark(), ByteSize::mb(3_600));
+        assert_eq!(ingest_config.disk_low_watermark(), ByteSize::mb(3_200));
+    }
+
+    #[tokio::test]
+    async fn test_node_config_ingest_routing_rules() {
+        let node_config_yaml = r#"
+            version: 0.6
+            ingest_api:
+              routing_rules:
+                - index_id: logs
+                  expression: '"logs-" + string!(.k8s.namespace)'
+                  fallback_index_id: logs-unrouted
+        "#;
+        let node_config = load_node_config_with_env(
+            ConfigFormat::Yaml,
+            node_config_yaml.as_bytes(),
+            &Default::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            node_config.ingest_api_config.routing_rules,
+            [IngestRoutingRule {
+                index_id: "logs".to_string(),
+                expression: r#""logs-" + string!(.k8s.namespace)"#.to_string(),
+                fallback_index_id: "logs-unrouted".to_string(),
+            }]
+        );
+
+        let routing_rule = IngestRoutingRule {
+            index_id: "logs".to_string(),
+            expression: r#""logs-" + string!(.k8s.namespace)"#.to_string(),
+            fallback_index_id: "logs".to_string(),
+        };
+        let ingest_config = IngestApiConfig {
+            routing_rules: vec![routing_rule.clone()],
+            ..Default::default()
+        };
+        let error_message = ingest_config.validate().unwrap_err().to_string();
+        assert!(error_message.contains("must be a different index"));
+
+        let routing_rule = IngestRoutingRule {
+            fallback_index_id: "logs-unrouted".to_string(),
+            ..routing_rule
+        };
+        let ingest_config = IngestApiConfig {
+            routing_rules: vec![routing_rule.clone(), routing_rule],
+            ..Default::default()
+        };
+        let error_message = ingest_config.validate().unwrap_err().to_string();
+        assert!(error_message.contains("several routing rules"));
+    }
+
+    #[tokio::test]
+    async fn test_node_config_api_key_query_limits() {
+        let node_config_yaml = r#"
+            version: 0.6
+            searcher:
+              api_key_query_limits:
+                max_num_concurrent_queries: 4
+                max_num_queued_queries: 8
+                overrides:
+                  - api_key: dashboard-key
+                    max_num_concurrent_queries: 1
+        "#;
+        let node_config = load_node_config_with_env(
+            ConfigFormat::Yaml,
+            node_config_yaml.as_bytes(),
+            &Default::default(),
+        )
+        .await
+        .unwrap();
+        let api_key_query_limits = node_config
+            .searcher_config
+            .api_key_query_limits
+            .as_ref()
+            .unwrap();
+        assert_eq!(api_key_query_limits.api_key_header, "x-api-key");
+        assert_eq!(api_key_query_limits.retry_after_secs.get(), 1);
+        assert_eq!(
+            api_key_query_limits.limits_for_api_key("dashboard-key"),
+            (NonZeroUsize::new(1).unwrap(), 8)
+        );
+        assert_eq!(
+            api_key_query_limits.limits_for_api_key("other-key"),
+            (NonZeroUsize::new(4).unwrap(), 8)
+        );
+        let mut redacted_node_config = node_config.clone();
+        redacted_node_config.redact();
+        assert_eq!(
+            redacted_node_config
+                .searcher_config
+                .api_key_query_limits
+                .unwrap()
+                .overrides[0]
+                .api_key,
+            "***redacted***"
+        );
+
+        let node_config_yaml = r#"
+            version: 0.6
+            searcher:
+              api_key_query_limits:
+                max_num_concurrent_queries: 4
+                overrides:
+                  - api_key: dashboard-key
+                    max_num_concurrent_queries: 1
+                  - api_key: dashboard-key
+                    max_num_concurrent_queries: 2
+        "#;
+        let error_message = load_node_config_with_env(
+            ConfigFormat::Yaml,
+            node_config_yaml.as_bytes(),
+            &Default::default(),
+        )
+        .await
+        .unwrap_err()
+        .to_string();
+        assert!(error_message.contains("at most once"));
+    }
 }
@@ -0,0 +1,178 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use anyhow::bail;
+use serde::{Deserialize, Serialize};
+
+/// Labels declared by a node in its config (e.g. `zone: eu-west-1a`, `tier: high-mem`) and
+/// gossiped to the rest of the cluster.
+pub type NodeLabels = BTreeMap<String, String>;
+
+/// Set of `label: value` pairs that a node must declare for the indexing pipelines and ingest
+/// shards of an index or a source to be placed on it.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(transparent)]
+pub struct PlacementConstraints(BTreeMap<String, String>);
+
+impl PlacementConstraints {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns whether a node declaring the given labels satisfies all the constraints.
+    pub fn is_satisfied_by(&self, node_labels: &NodeLabels) -> bool {
+        self.0
+            .iter()
+            .all(|(label, value)| node_labels.get(label) == Some(value))
+    }
+
+    /// Merges the constraints of an index with the constraints of one of its sources. The
+    /// constraints of the source take precedence over the constraints of the index for the same
+    /// label.
+    pub fn merge(index_constraints: &Self, source_constraints: &Self) -> Self {
+        let mut merged_constraints = index_constraints.0.clone();
+        merged_constraints.extend(source_constraints.0.clone());
+        Self(merged_constraints)
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        for (label, value) in &self.0 {
+            validate_label(label, value)?;
+        }
+        Ok(())
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for PlacementConstraints
+where
+    K: Into<String>,
+    V: Into<String>,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self(
+            iter.into_iter()
+                .map(|(label, value)| (label.into(), value.into()))
+                .collect(),
+        )
+    }
+}
+
+impl fmt::Display for PlacementConstraints {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (label, value)) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{label}={value}")?;
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn validate_node_labels(node_labels: &NodeLabels) -> anyhow::Result<()> {
+    for (label, value) in node_labels {
+        validate_label(label, value)?;
+    }
+    Ok(())
+}
+
+/// Labels and values are gossiped and displayed as `label=value` pairs, so they must be non-empty
+/// and cannot contain whitespaces, `=`, or `,`.
+fn validate_label(label: &str, value: &str) -> anyhow::Result<()> {
+    let is_valid = |token: &str| {
+        !token.is_empty()
+            && !token
+                .chars()
+                .any(|ch| ch.is_whitespace() || ch == '=' || ch == ',')
+    };
+    if !is_valid(label) {
+        bail!(
+            "invalid label `{label}`: labels must be non-empty and cannot contain `=`, `,`, or \
+             whitespaces"
+        );
+    }
+    if !is_valid(value) {
+        bail!(
+            "invalid value `{value}` for label `{label}`: values must be non-empty and cannot \
+             contain `=`, `,`, or whitespaces"
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_placement_constraints_is_satisfied_by() {
+        let node_labels = NodeLabels::from_iter([
+            ("zone".to_string(), "eu-west-1a".to_string()),
+            ("tier".to_string(), "high-mem".to_string()),
+        ]);
+        assert!(PlacementConstraints::default().is_satisfied_by(&node_labels));
+        assert!(PlacementConstraints::default().is_satisfied_by(&NodeLabels::new()));
+
+        let constraints = PlacementConstraints::from_iter([("zone", "eu-west-1a")]);
+        assert!(constraints.is_satisfied_by(&node_labels));
+        assert!(!constraints.is_satisfied_by(&NodeLabels::new()));
+
+        let constraints =
+            PlacementConstraints::from_iter([("zone", "eu-west-1a"), ("tier", "high-cpu")]);
+        assert!(!constraints.is_satisfied_by(&node_labels));
+    }
+
+    #[test]
+    fn test_placement_constraints_merge() {
+        let index_constraints =
+            PlacementConstraints::from_iter([("zone", "eu-west-1a"), ("tier", "high-mem")]);
+        let source_constraints = PlacementConstraints::from_iter([("tier", "high-cpu")]);
+        let merged_constraints =
+            PlacementConstraints::merge(&index_constraints, &source_constraints);
+        assert_eq!(
+            merged_constraints,
+            PlacementConstraints::from_iter([("zone", "eu-west-1a"), ("tier", "high-cpu")])
+        );
+        assert_eq!(
+            merged_constraints.to_string(),
+            "tier=high-cpu,zone=eu-west-1a"
+        );
+    }
+
+    #[test]
+    fn test_placement_constraints_serde() {
+        let constraints: PlacementConstraints =
+            serde_yaml::from_str("zone: eu-west-1a\ntier: high-mem").unwrap();
+        assert_eq!(
+            constraints,
+            PlacementConstraints::from_iter([("zone", "eu-west-1a"), ("tier", "high-mem")])
+        );
+        constraints.validate().unwrap();
+
+        let invalid_constraints = PlacementConstraints::from_iter([("zone", "eu west")]);
+        let error = invalid_constraints.validate().unwrap_err();
+        assert!(error.to_string().contains("invalid value `eu west`"));
+
+        let invalid_constraints = PlacementConstraints::from_iter([("", "eu-west-1a")]);
+        invalid_constraints.validate().unwrap_err();
+    }
+}
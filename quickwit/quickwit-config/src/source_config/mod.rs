@@ -22,10 +22,13 @@ pub(crate) mod serialize;
 use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::time::Duration;
 
 use bytes::Bytes;
+use bytesize::ByteSize;
 use quickwit_common::is_false;
 use quickwit_common::uri::Uri;
+use quickwit_proto::ingest::ShardPriority;
 use quickwit_proto::metastore::SourceType;
 use serde::de::Error;
 use serde::{Deserialize, Deserializer, Serialize};
@@ -34,7 +37,7 @@ pub use serialize::load_source_config_from_user_config;
 // For backward compatibility.
 use serialize::VersionedSourceConfig;
 
-use crate::TestableForRegression;
+use crate::{PlacementConstraints, TestableForRegression};
 
 /// Reserved source ID for the `quickwit index ingest` CLI command.
 pub const CLI_INGEST_SOURCE_ID: &str = "_ingest-cli-source";
@@ -87,11 +90,43 @@ pub struct SourceConfig {
     // Denotes the input data format.
     #[serde(default)]
     pub input_format: SourceInputFormat,
+
+    /// Options of the `csv` input format.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "CsvConfig::is_default")]
+    #[serde(rename = "csv")]
+    pub csv_config: CsvConfig,
+
+    /// Sink receiving the documents rejected by the doc processor. If `None`, invalid documents
+    /// are only counted and dropped.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dead_letter_queue: Option<DeadLetterQueueConfig>,
+
+    /// Policy applied by the indexing pipeline supervisor when the pipelines of the source fail.
+    #[serde(default)]
+    pub supervision: SupervisionPolicy,
+
+    /// Tuning of the streams fetching records from the ingesters. Only applies to `ingest`
+    /// sources.
+    #[serde(default)]
+    pub fetch_stream: FetchStreamConfig,
+
+    /// Node labels that the indexers must declare to run the indexing pipelines and host the
+    /// ingest shards of the source. They take precedence over the placement constraints of the
+    /// index.
+    #[serde(default)]
+    pub placement_constraints: PlacementConstraints,
+
+    /// Priority class of the source. Backfill sources yield the indexing and ingest resources of
+    /// the nodes they share with realtime sources.
+    #[serde(default)]
+    pub priority: SourcePriority,
 }
 
 impl SourceConfig {
     pub fn source_type(&self) -> SourceType {
         match self.source_params {
+            SourceParams::AzureEventHubs(_) => SourceType::AzureEventHubs,
             SourceParams::File(_) => SourceType::File,
             SourceParams::GcpPubSub(_) => SourceType::GcpPubsub,
             SourceParams::Ingest => SourceType::IngestV2,
@@ -108,6 +143,7 @@ impl SourceConfig {
     // TODO: Remove after source factory refactor.
     pub fn params(&self) -> JsonValue {
         match &self.source_params {
+            SourceParams::AzureEventHubs(params) => serde_json::to_value(params),
             SourceParams::File(params) => serde_json::to_value(params),
             SourceParams::GcpPubSub(params) => serde_json::to_value(params),
             SourceParams::Ingest => serde_json::to_value(()),
@@ -132,6 +168,12 @@ impl SourceConfig {
             source_params: SourceParams::Ingest,
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            csv_config: CsvConfig::default(),
+            dead_letter_queue: None,
+            supervision: SupervisionPolicy::default(),
+            fetch_stream: FetchStreamConfig::default(),
+            placement_constraints: PlacementConstraints::default(),
+            priority: SourcePriority::default(),
         }
     }
 
@@ -145,6 +187,12 @@ impl SourceConfig {
             source_params: SourceParams::IngestApi,
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            csv_config: CsvConfig::default(),
+            dead_letter_queue: None,
+            supervision: SupervisionPolicy::default(),
+            fetch_stream: FetchStreamConfig::default(),
+            placement_constraints: PlacementConstraints::default(),
+            priority: SourcePriority::default(),
         }
     }
 
@@ -158,6 +206,12 @@ impl SourceConfig {
             source_params: SourceParams::IngestCli,
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            csv_config: CsvConfig::default(),
+            dead_letter_queue: None,
+            supervision: SupervisionPolicy::default(),
+            fetch_stream: FetchStreamConfig::default(),
+            placement_constraints: PlacementConstraints::default(),
+            priority: SourcePriority::default(),
         }
     }
 
@@ -171,6 +225,12 @@ impl SourceConfig {
             source_params,
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            csv_config: CsvConfig::default(),
+            dead_letter_queue: None,
+            supervision: SupervisionPolicy::default(),
+            fetch_stream: FetchStreamConfig::default(),
+            placement_constraints: PlacementConstraints::default(),
+            priority: SourcePriority::default(),
         }
     }
 }
@@ -187,12 +247,20 @@ impl TestableForRegression for SourceConfig {
                 client_log_level: None,
                 client_params: serde_json::json!({}),
                 enable_backfill_mode: false,
+                header_filters: Vec::new(),
+                header_field_mappings: Vec::new(),
             }),
             transform_config: Some(TransformConfig {
                 vrl_script: ".message = downcase(string!(.message))".to_string(),
                 timezone: default_timezone(),
             }),
             input_format: SourceInputFormat::Json,
+            csv_config: CsvConfig::default(),
+            dead_letter_queue: None,
+            supervision: SupervisionPolicy::default(),
+            fetch_stream: FetchStreamConfig::default(),
+            placement_constraints: PlacementConstraints::default(),
+            priority: SourcePriority::default(),
         }
     }
 
@@ -201,6 +269,243 @@ impl TestableForRegression for SourceConfig {
     }
 }
 
+/// Dead letter queue configuration of a source.
+///
+/// Documents that fail parsing, transformation, or doc mapping are written to the configured sink
+/// along with the reason of the failure.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum DeadLetterQueueConfig {
+    /// Stores invalid documents as NDJSON files on the index storage, under
+    /// `dead-letter-queue/<source_id>/`.
+    File,
+    /// Ingests invalid documents into another index via the ingest API.
+    Index { index_id: String },
+}
+
+/// Supervision policy of the indexing pipelines of a source.
+///
+/// When a pipeline fails, the supervisor restarts it after a delay given by the backoff strategy.
+/// It gives up after `max_consecutive_failures` failures in a row, and optionally disables the
+/// source in the metastore.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SupervisionPolicy {
+    #[serde(default)]
+    pub backoff: BackoffStrategy,
+    /// Number of consecutive failures after which the supervisor stops restarting the pipeline.
+    /// If `None`, the pipeline is restarted indefinitely.
+    #[schema(value_type = Option<usize>)]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_consecutive_failures: Option<NonZeroUsize>,
+    /// Disables the source once `max_consecutive_failures` is reached.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub auto_disable: bool,
+}
+
+impl SupervisionPolicy {
+    pub(crate) fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        self.backoff.validate()?;
+        if self.auto_disable && self.max_consecutive_failures.is_none() {
+            anyhow::bail!("`auto_disable` requires `max_consecutive_failures` to be set");
+        }
+        Ok(())
+    }
+}
+
+/// Tuning of the streams fetching records from the ingesters (ingest v2).
+///
+/// Larger batches and more bytes in flight increase the indexing throughput at the expense of the
+/// memory of the indexers.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct FetchStreamConfig {
+    /// Maximum number of bytes fetched from the ingesters and not yet consumed by the indexing
+    /// pipeline.
+    #[schema(value_type = String, default = "3 MiB")]
+    #[serde(default = "FetchStreamConfig::default_max_in_flight_bytes")]
+    pub max_in_flight_bytes: ByteSize,
+    /// Maximum size of a batch of records fetched from an ingester.
+    #[schema(value_type = String, default = "1 MiB")]
+    #[serde(default = "FetchStreamConfig::default_batch_num_bytes")]
+    pub batch_num_bytes: ByteSize,
+    /// Node decompressing the record batches.
+    #[serde(default)]
+    pub decompression: FetchDecompression,
+}
+
+impl FetchStreamConfig {
+    fn default_max_in_flight_bytes() -> ByteSize {
+        ByteSize::mib(3)
+    }
+
+    fn default_batch_num_bytes() -> ByteSize {
+        ByteSize::mib(1)
+    }
+
+    pub(crate) fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        if self.batch_num_bytes.as_u64() == 0 {
+            anyhow::bail!("fetch stream `batch_num_bytes` must be strictly positive");
+        }
+        if self.batch_num_bytes > self.max_in_flight_bytes {
+            anyhow::bail!(
+                "fetch stream `batch_num_bytes` ({}) must be lower than or equal to \
+                 `max_in_flight_bytes` ({})",
+                self.batch_num_bytes,
+                self.max_in_flight_bytes
+            );
+        }
+        if self.max_in_flight_bytes > ByteSize::gib(1) {
+            anyhow::bail!(
+                "fetch stream `max_in_flight_bytes` ({}) must be lower than or equal to 1 GiB",
+                self.max_in_flight_bytes
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Default for FetchStreamConfig {
+    fn default() -> Self {
+        Self {
+            max_in_flight_bytes: Self::default_max_in_flight_bytes(),
+            batch_num_bytes: Self::default_batch_num_bytes(),
+            decompression: FetchDecompression::default(),
+        }
+    }
+}
+
+/// Node decompressing the record batches of a fetch stream.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FetchDecompression {
+    /// The ingester sends the records uncompressed.
+    #[default]
+    Ingester,
+    /// The ingester compresses the record batches with zstd and the indexer decompresses them,
+    /// which saves network bandwidth at the expense of CPU.
+    Indexer,
+}
+
+/// Delay between two restarts of a failed indexing pipeline.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum BackoffStrategy {
+    /// Doubles the delay after each consecutive failure, from `initial_delay_secs` up to
+    /// `max_delay_secs`.
+    Exponential {
+        #[serde(default = "default_backoff_initial_delay_secs")]
+        initial_delay_secs: u64,
+        #[serde(default = "default_backoff_max_delay_secs")]
+        max_delay_secs: u64,
+    },
+    /// Waits `delay_secs` before each restart.
+    Fixed { delay_secs: u64 },
+}
+
+fn default_backoff_initial_delay_secs() -> u64 {
+    1
+}
+
+fn default_backoff_max_delay_secs() -> u64 {
+    600
+}
+
+impl Default for BackoffStrategy {
+    fn default() -> Self {
+        BackoffStrategy::Exponential {
+            initial_delay_secs: default_backoff_initial_delay_secs(),
+            max_delay_secs: default_backoff_max_delay_secs(),
+        }
+    }
+}
+
+impl BackoffStrategy {
+    /// Returns the delay to wait before the `retry_count`-th restart (starting at 0).
+    pub fn delay(&self, retry_count: usize) -> Duration {
+        match *self {
+            BackoffStrategy::Exponential {
+                initial_delay_secs,
+                max_delay_secs,
+            } => {
+                // Protect against a `retry_count` that will lead to an overflow.
+                let max_power = (retry_count as u32).min(31);
+                let delay_secs = initial_delay_secs.saturating_mul(2u64.pow(max_power));
+                Duration::from_secs(delay_secs.min(max_delay_secs))
+            }
+            BackoffStrategy::Fixed { delay_secs } => Duration::from_secs(delay_secs),
+        }
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        if let BackoffStrategy::Exponential {
+            initial_delay_secs,
+            max_delay_secs,
+        } = self
+        {
+            if initial_delay_secs > max_delay_secs {
+                anyhow::bail!(
+                    "backoff `initial_delay_secs` ({initial_delay_secs}) must be lower than or \
+                     equal to `max_delay_secs` ({max_delay_secs})"
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Priority class of a source.
+///
+/// On the indexers, backfill pipelines can hold all the cooperative indexing permits but one, so
+/// that realtime pipelines always make progress. On the ingesters, the shards of backfill sources
+/// stop accepting writes when the write-ahead log is close to full, leaving the remaining capacity
+/// to the shards of realtime sources.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SourcePriority {
+    /// Live data, such as application logs, that must be searchable as soon as possible.
+    #[default]
+    Realtime,
+    /// Historical data that can be indexed at a slower pace.
+    Backfill,
+}
+
+impl SourcePriority {
+    pub(crate) fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn is_backfill(&self) -> bool {
+        *self == SourcePriority::Backfill
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SourcePriority::Realtime => "realtime",
+            SourcePriority::Backfill => "backfill",
+        }
+    }
+}
+
+impl From<SourcePriority> for ShardPriority {
+    fn from(source_priority: SourcePriority) -> Self {
+        match source_priority {
+            SourcePriority::Realtime => ShardPriority::Realtime,
+            SourcePriority::Backfill => ShardPriority::Backfill,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum SourceInputFormat {
@@ -211,6 +516,9 @@ pub enum SourceInputFormat {
     OtlpTraceProtobuf,
     #[serde(alias = "plain")]
     PlainText,
+    /// Comma-separated values. The first record is a header naming the fields of the subsequent
+    /// records.
+    Csv,
 }
 
 impl FromStr for SourceInputFormat {
@@ -220,14 +528,55 @@ impl FromStr for SourceInputFormat {
         match format_str {
             "json" => Ok(Self::Json),
             "plain" => Ok(Self::PlainText),
+            "csv" => Ok(Self::Csv),
             unknown => Err(format!("unknown source input format: `{unknown}`")),
         }
     }
 }
 
+/// Options of the `csv` input format.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct CsvConfig {
+    /// Character separating the fields of a record.
+    #[schema(value_type = String, default = ",")]
+    #[serde(default = "CsvConfig::default_delimiter")]
+    pub delimiter: char,
+}
+
+impl CsvConfig {
+    fn default_delimiter() -> char {
+        ','
+    }
+
+    pub(crate) fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if !self.delimiter.is_ascii() || ['"', '\r', '\n'].contains(&self.delimiter) {
+            anyhow::bail!(
+                "CSV delimiter must be an ASCII character other than a double quote or a line \
+                 break, got `{}`",
+                self.delimiter.escape_debug()
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Default for CsvConfig {
+    fn default() -> Self {
+        Self {
+            delimiter: Self::default_delimiter(),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
 #[serde(tag = "source_type", content = "params", rename_all = "snake_case")]
 pub enum SourceParams {
+    AzureEventHubs(AzureEventHubsSourceParams),
     File(FileSourceParams),
     GcpPubSub(GcpPubSubSourceParams),
     Ingest,
@@ -265,6 +614,17 @@ pub struct FileSourceParams {
     #[serde(default)]
     #[serde(deserialize_with = "absolute_filepath_from_str")]
     pub filepath: Option<PathBuf>, //< If None read from stdin.
+    /// When enabled, `filepath` designates a local directory watched for new and rotated files.
+    /// Each file is a partition of the source, and the source never exits.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub watch: bool,
+    /// Glob pattern matched against the names of the files of the watched directory. All the files
+    /// are read if None. Only used in watch mode.
+    #[schema(value_type = String)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub file_pattern: Option<String>,
 }
 
 /// Deserializing as an URI first to validate the input.
@@ -286,11 +646,52 @@ impl FileSourceParams {
     pub fn file<P: AsRef<Path>>(filepath: P) -> Self {
         FileSourceParams {
             filepath: Some(filepath.as_ref().to_path_buf()),
+            watch: false,
+            file_pattern: None,
+        }
+    }
+
+    /// Creates the params of a file source watching the directory `dir_path` for files whose
+    /// names match `file_pattern`.
+    pub fn watch<P: AsRef<Path>>(dir_path: P, file_pattern: Option<String>) -> Self {
+        FileSourceParams {
+            filepath: Some(dir_path.as_ref().to_path_buf()),
+            watch: true,
+            file_pattern,
         }
     }
 
     pub fn stdin() -> Self {
-        FileSourceParams { filepath: None }
+        FileSourceParams {
+            filepath: None,
+            watch: false,
+            file_pattern: None,
+        }
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let Some(filepath) = &self.filepath else {
+            if self.watch {
+                anyhow::bail!("file source cannot watch stdin");
+            }
+            return Ok(());
+        };
+        if !self.watch {
+            if self.file_pattern.is_some() {
+                anyhow::bail!("`file_pattern` can only be set in watch mode");
+            }
+            return Ok(());
+        }
+        let uri = Uri::from_str(&filepath.to_string_lossy())?;
+        if uri.filepath().is_none() {
+            anyhow::bail!("file source can only watch local directories, got `{uri}`");
+        }
+        if let Some(file_pattern) = &self.file_pattern {
+            if let Err(error) = glob::Pattern::new(file_pattern) {
+                anyhow::bail!("invalid file pattern `{file_pattern}`: {error}");
+            }
+        }
+        Ok(())
     }
 }
 
@@ -312,6 +713,171 @@ pub struct KafkaSourceParams {
     #[serde(default)]
     #[serde(skip_serializing_if = "is_false")]
     pub enable_backfill_mode: bool,
+    /// Filters applied to the message headers. Messages that do not pass all the filters are
+    /// skipped.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub header_filters: Vec<KafkaHeaderFilter>,
+    /// Message headers copied into the documents.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub header_field_mappings: Vec<KafkaHeaderFieldMapping>,
+}
+
+impl KafkaSourceParams {
+    pub(crate) fn validate(&self) -> anyhow::Result<()> {
+        for header_filter in &self.header_filters {
+            anyhow::ensure!(
+                !header_filter.header.is_empty(),
+                "Kafka header filters must define a non-empty header name"
+            );
+            anyhow::ensure!(
+                !header_filter.values.is_empty(),
+                "Kafka header filter on `{}` must define at least one value",
+                header_filter.header
+            );
+        }
+        let mut fields = HashSet::with_capacity(self.header_field_mappings.len());
+
+        for header_field_mapping in &self.header_field_mappings {
+            anyhow::ensure!(
+                !header_field_mapping.header.is_empty() && !header_field_mapping.field.is_empty(),
+                "Kafka header field mappings must define a non-empty header and field"
+            );
+            anyhow::ensure!(
+                fields.insert(&header_field_mapping.field),
+                "Kafka header field mappings must target each field at most once, got `{}` \
+                 several times",
+                header_field_mapping.field
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Keeps only the Kafka messages whose `header` has one of the accepted `values`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct KafkaHeaderFilter {
+    /// Name of the header.
+    pub header: String,
+    /// Accepted values of the header.
+    pub values: Vec<String>,
+}
+
+/// Copies the value of a Kafka message header into a field of the document.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct KafkaHeaderFieldMapping {
+    /// Name of the header.
+    pub header: String,
+    /// Name of the document field receiving the header value.
+    pub field: String,
+}
+
+/// Port of the Kafka-compatible endpoint of Azure Event Hubs namespaces.
+const AZURE_EVENT_HUBS_KAFKA_PORT: u16 = 9093;
+
+/// Consumes an Azure Event Hubs event hub through the Kafka-compatible endpoint of its namespace.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AzureEventHubsSourceParams {
+    /// Connection string of the Event Hubs namespace or of the event hub, for instance
+    /// `Endpoint=sb://my-namespace.servicebus.windows.net/;SharedAccessKeyName=...;
+    /// SharedAccessKey=...`.
+    pub connection_string: String,
+    /// Name of the event hub that the source consumes. Defaults to the `EntityPath` of the
+    /// connection string.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_hub: Option<String>,
+    /// Kafka client log level. Possible values are `debug`, `info`, `warn`, and `error`.
+    #[schema(value_type = String)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_log_level: Option<String>,
+    /// Additional Kafka client configuration parameters. They take precedence over the parameters
+    /// derived from the connection string.
+    #[schema(value_type = Object)]
+    #[serde(default = "serde_json::Value::default")]
+    #[serde(skip_serializing_if = "serde_json::Value::is_null")]
+    pub client_params: JsonValue,
+    /// When backfill mode is enabled, the source exits after reaching the end of the event hub.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub enable_backfill_mode: bool,
+}
+
+impl AzureEventHubsSourceParams {
+    /// Translates the parameters into the parameters of a Kafka source consuming the
+    /// Kafka-compatible endpoint of the namespace.
+    pub fn to_kafka_source_params(&self) -> anyhow::Result<KafkaSourceParams> {
+        let mut endpoint_opt: Option<&str> = None;
+        let mut entity_path_opt: Option<&str> = None;
+
+        for key_value in self.connection_string.split(';') {
+            let Some((key, value)) = key_value.split_once('=') else {
+                continue;
+            };
+            match key.trim() {
+                "Endpoint" => endpoint_opt = Some(value.trim()),
+                "EntityPath" => entity_path_opt = Some(value.trim()),
+                _ => {}
+            }
+        }
+        let Some(endpoint) = endpoint_opt else {
+            anyhow::bail!("Azure Event Hubs connection string must contain an `Endpoint`");
+        };
+        let host = endpoint
+            .strip_prefix("sb://")
+            .unwrap_or(endpoint)
+            .trim_end_matches('/');
+        if host.is_empty() {
+            anyhow::bail!("Azure Event Hubs connection string `Endpoint` must not be empty");
+        }
+        let Some(event_hub) = self.event_hub.as_deref().or(entity_path_opt) else {
+            anyhow::bail!(
+                "Azure Event Hubs source must define an `event_hub` when the connection string                  has no `EntityPath`"
+            );
+        };
+        let mut client_params = serde_json::Map::new();
+        client_params.insert(
+            "bootstrap.servers".to_string(),
+            JsonValue::String(format!("{host}:{AZURE_EVENT_HUBS_KAFKA_PORT}")),
+        );
+        client_params.insert(
+            "security.protocol".to_string(),
+            JsonValue::String("SASL_SSL".to_string()),
+        );
+        client_params.insert(
+            "sasl.mechanism".to_string(),
+            JsonValue::String("PLAIN".to_string()),
+        );
+        client_params.insert(
+            "sasl.username".to_string(),
+            JsonValue::String("$ConnectionString".to_string()),
+        );
+        client_params.insert(
+            "sasl.password".to_string(),
+            JsonValue::String(self.connection_string.clone()),
+        );
+        match &self.client_params {
+            JsonValue::Null => {}
+            JsonValue::Object(extra_client_params) => {
+                for (key, value) in extra_client_params {
+                    client_params.insert(key.clone(), value.clone());
+                }
+            }
+            _ => anyhow::bail!("Azure Event Hubs source `client_params` must be a JSON object"),
+        }
+        Ok(KafkaSourceParams {
+            topic: event_hub.to_string(),
+            client_log_level: self.client_log_level.clone(),
+            client_params: JsonValue::Object(client_params),
+            enable_backfill_mode: self.enable_backfill_mode,
+            header_filters: Vec::new(),
+            header_field_mappings: Vec::new(),
+        })
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
@@ -579,12 +1145,20 @@ mod tests {
                 client_log_level: None,
                 client_params: json! {{"bootstrap.servers": "localhost:9092"}},
                 enable_backfill_mode: false,
+                header_filters: Vec::new(),
+                header_field_mappings: Vec::new(),
             }),
             transform_config: Some(TransformConfig {
                 vrl_script: ".message = downcase(string!(.message))".to_string(),
                 timezone: "local".to_string(),
             }),
             input_format: SourceInputFormat::Json,
+            csv_config: CsvConfig::default(),
+            dead_letter_queue: None,
+            supervision: SupervisionPolicy::default(),
+            fetch_stream: FetchStreamConfig::default(),
+            placement_constraints: PlacementConstraints::default(),
+            priority: SourcePriority::default(),
         };
         assert_eq!(source_config, expected_source_config);
         assert_eq!(source_config.desired_num_pipelines.get(), 2);
@@ -598,6 +1172,8 @@ mod tests {
                 client_log_level: None,
                 client_params: json!(null),
                 enable_backfill_mode: false,
+                header_filters: Vec::new(),
+                header_field_mappings: Vec::new(),
             };
             let params_yaml = serde_yaml::to_string(&params).unwrap();
 
@@ -612,6 +1188,8 @@ mod tests {
                 client_log_level: Some("info".to_string()),
                 client_params: json! {{"bootstrap.servers": "localhost:9092"}},
                 enable_backfill_mode: false,
+                header_filters: Vec::new(),
+                header_field_mappings: Vec::new(),
             };
             let params_yaml = serde_yaml::to_string(&params).unwrap();
 
@@ -622,6 +1200,74 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_azure_event_hubs_source_params_to_kafka_source_params() {
+        let connection_string = "Endpoint=sb://my-namespace.servicebus.windows.net/;\
+                                 SharedAccessKeyName=listen;SharedAccessKey=c2VjcmV0=;\
+                                 EntityPath=my-event-hub";
+        let yaml = format!(
+            r#"
+                connection_string: "{connection_string}"
+                client_params:
+                    group.id: my-group
+                enable_backfill_mode: true
+            "#
+        );
+        let params = serde_yaml::from_str::<AzureEventHubsSourceParams>(&yaml).unwrap();
+        assert_eq!(params.event_hub, None);
+
+        let kafka_params = params.to_kafka_source_params().unwrap();
+        assert_eq!(
+            kafka_params,
+            KafkaSourceParams {
+                topic: "my-event-hub".to_string(),
+                client_log_level: None,
+                client_params: json!({
+                    "bootstrap.servers": "my-namespace.servicebus.windows.net:9093",
+                    "security.protocol": "SASL_SSL",
+                    "sasl.mechanism": "PLAIN",
+                    "sasl.username": "$ConnectionString",
+                    "sasl.password": connection_string,
+                    "group.id": "my-group",
+                }),
+                enable_backfill_mode: true,
+                header_filters: Vec::new(),
+                header_field_mappings: Vec::new(),
+            }
+        );
+        let params = AzureEventHubsSourceParams {
+            event_hub: Some("other-event-hub".to_string()),
+            ..params
+        };
+        assert_eq!(
+            params.to_kafka_source_params().unwrap().topic,
+            "other-event-hub"
+        );
+    }
+
+    #[test]
+    fn test_azure_event_hubs_source_params_validation() {
+        let params = AzureEventHubsSourceParams {
+            connection_string: "Endpoint=sb://my-namespace.servicebus.windows.net/;\
+                                SharedAccessKeyName=listen;SharedAccessKey=secret"
+                .to_string(),
+            event_hub: None,
+            client_log_level: None,
+            client_params: json!(null),
+            enable_backfill_mode: false,
+        };
+        let error = params.to_kafka_source_params().unwrap_err();
+        assert!(error.to_string().contains("must define an `event_hub`"));
+
+        let params = AzureEventHubsSourceParams {
+            connection_string: "SharedAccessKeyName=listen;SharedAccessKey=secret".to_string(),
+            event_hub: Some("my-event-hub".to_string()),
+            ..params
+        };
+        let error = params.to_kafka_source_params().unwrap_err();
+        assert!(error.to_string().contains("must contain an `Endpoint`"));
+    }
+
     #[test]
     fn test_kafka_source_params_deserialization() {
         {
@@ -635,6 +1281,8 @@ mod tests {
                     client_log_level: None,
                     client_params: json!(null),
                     enable_backfill_mode: false,
+                    header_filters: Vec::new(),
+                    header_field_mappings: Vec::new(),
                 }
             );
         }
@@ -653,9 +1301,71 @@ mod tests {
                     client_log_level: Some("info".to_string()),
                     client_params: json! {{"bootstrap.servers": "localhost:9092"}},
                     enable_backfill_mode: true,
+                    header_filters: Vec::new(),
+                    header_field_mappings: Vec::new(),
                 }
             );
         }
+        {
+            let yaml = r#"
+                    topic: my-topic
+                    header_filters:
+                      - header: type
+                        values: [audit]
+                    header_field_mappings:
+                      - header: tenant
+                        field: tenant_id
+                "#;
+            let params = serde_yaml::from_str::<KafkaSourceParams>(yaml).unwrap();
+            assert_eq!(
+                params.header_filters,
+                [KafkaHeaderFilter {
+                    header: "type".to_string(),
+                    values: vec!["audit".to_string()],
+                }]
+            );
+            assert_eq!(
+                params.header_field_mappings,
+                [KafkaHeaderFieldMapping {
+                    header: "tenant".to_string(),
+                    field: "tenant_id".to_string(),
+                }]
+            );
+            params.validate().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_kafka_source_params_validation() {
+        let mut params = KafkaSourceParams {
+            topic: "my-topic".to_string(),
+            client_log_level: None,
+            client_params: json!(null),
+            enable_backfill_mode: false,
+            header_filters: vec![KafkaHeaderFilter {
+                header: "type".to_string(),
+                values: Vec::new(),
+            }],
+            header_field_mappings: Vec::new(),
+        };
+        params.validate().unwrap_err();
+
+        params.header_filters.clear();
+        params.header_field_mappings = vec![
+            KafkaHeaderFieldMapping {
+                header: "tenant".to_string(),
+                field: "tenant_id".to_string(),
+            },
+            KafkaHeaderFieldMapping {
+                header: "tenant-id".to_string(),
+                field: "tenant_id".to_string(),
+            },
+        ];
+        let error = params.validate().unwrap_err();
+        assert!(error.to_string().contains("at most once"));
+
+        params.header_field_mappings.pop();
+        params.validate().unwrap();
     }
 
     #[tokio::test]
@@ -681,6 +1391,12 @@ mod tests {
                 timezone: "local".to_string(),
             }),
             input_format: SourceInputFormat::Json,
+            csv_config: CsvConfig::default(),
+            dead_letter_queue: None,
+            supervision: SupervisionPolicy::default(),
+            fetch_stream: FetchStreamConfig::default(),
+            placement_constraints: PlacementConstraints::default(),
+            priority: SourcePriority::default(),
         };
         assert_eq!(source_config, expected_source_config);
         assert_eq!(source_config.desired_num_pipelines.get(), 1);
@@ -810,6 +1526,46 @@ mod tests {
                 Path::new(uri.as_str())
             );
         }
+        {
+            let yaml = r#"
+                filepath: /var/log/app
+                watch: true
+                file_pattern: "*.log"
+            "#;
+            let file_params = serde_yaml::from_str::<FileSourceParams>(yaml).unwrap();
+            assert!(file_params.watch);
+            assert_eq!(file_params.file_pattern.as_deref(), Some("*.log"));
+            file_params.validate().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_file_source_params_validate() {
+        FileSourceParams::file("/var/log/app.log")
+            .validate()
+            .unwrap();
+        FileSourceParams::watch("/var/log/app", None)
+            .validate()
+            .unwrap();
+
+        let error = FileSourceParams::watch("s3://bucket/logs", None)
+            .validate()
+            .unwrap_err();
+        assert!(error.to_string().contains("local directories"));
+
+        let error = FileSourceParams::watch("/var/log/app", Some("[*.log".to_string()))
+            .validate()
+            .unwrap_err();
+        assert!(error.to_string().contains("invalid file pattern"));
+
+        let mut file_params = FileSourceParams::file("/var/log/app.log");
+        file_params.file_pattern = Some("*.log".to_string());
+        let error = file_params.validate().unwrap_err();
+        assert!(error.to_string().contains("only be set in watch mode"));
+
+        let mut stdin_params = FileSourceParams::stdin();
+        stdin_params.watch = true;
+        stdin_params.validate().unwrap_err();
     }
 
     #[test]
@@ -1085,6 +1841,12 @@ mod tests {
                 timezone: default_timezone(),
             }),
             input_format: SourceInputFormat::Json,
+            csv_config: CsvConfig::default(),
+            dead_letter_queue: None,
+            supervision: SupervisionPolicy::default(),
+            fetch_stream: FetchStreamConfig::default(),
+            placement_constraints: PlacementConstraints::default(),
+            priority: SourcePriority::default(),
         };
         assert_eq!(source_config, expected_source_config);
         assert_eq!(source_config.desired_num_pipelines.get(), 1);
@@ -1204,4 +1966,313 @@ mod tests {
                 .unwrap();
         assert_eq!(source_config.input_format, SourceInputFormat::PlainText);
     }
+
+    #[tokio::test]
+    async fn test_source_config_csv_input_format() {
+        {
+            let file_content = r#"{
+                "version": "0.6",
+                "source_id": "logs-file-source",
+                "source_type": "file",
+                "params": {"filepath": "/test_corpus.csv"},
+                "input_format": "csv"
+            }"#;
+            let source_config =
+                load_source_config_from_user_config(ConfigFormat::Json, file_content.as_bytes())
+                    .unwrap();
+            assert_eq!(source_config.input_format, SourceInputFormat::Csv);
+            assert_eq!(source_config.csv_config.delimiter, ',');
+        }
+        {
+            let file_content = r#"{
+                "version": "0.6",
+                "source_id": "logs-file-source",
+                "source_type": "file",
+                "params": {"filepath": "/test_corpus.tsv"},
+                "input_format": "csv",
+                "csv": {"delimiter": "\t"}
+            }"#;
+            let source_config =
+                load_source_config_from_user_config(ConfigFormat::Json, file_content.as_bytes())
+                    .unwrap();
+            assert_eq!(source_config.csv_config.delimiter, '\t');
+        }
+        {
+            let file_content = r#"{
+                "version": "0.6",
+                "source_id": "logs-file-source",
+                "source_type": "file",
+                "params": {"filepath": "/test_corpus.csv"},
+                "input_format": "csv",
+                "csv": {"delimiter": "\""}
+            }"#;
+            let error =
+                load_source_config_from_user_config(ConfigFormat::Json, file_content.as_bytes())
+                    .unwrap_err();
+            assert!(error.to_string().contains("CSV delimiter"));
+        }
+        {
+            let file_content = r#"{
+                "version": "0.6",
+                "source_id": "logs-file-source",
+                "source_type": "file",
+                "params": {"filepath": "/test_corpus.json"},
+                "csv": {"delimiter": ";"}
+            }"#;
+            let error =
+                load_source_config_from_user_config(ConfigFormat::Json, file_content.as_bytes())
+                    .unwrap_err();
+            assert!(error.to_string().contains("cannot set `csv`"));
+        }
+        {
+            let file_content = r#"{
+                "version": "0.6",
+                "source_id": "kafka-source",
+                "source_type": "kafka",
+                "params": {"topic": "my-topic"},
+                "input_format": "csv"
+            }"#;
+            let error =
+                load_source_config_from_user_config(ConfigFormat::Json, file_content.as_bytes())
+                    .unwrap_err();
+            assert!(error
+                .to_string()
+                .contains("cannot use the `csv` input format"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_source_config_supervision_policy() {
+        {
+            let file_content = r#"{
+                "version": "0.6",
+                "source_id": "void-source",
+                "source_type": "void",
+                "params": {}
+            }"#;
+            let source_config =
+                load_source_config_from_user_config(ConfigFormat::Json, file_content.as_bytes())
+                    .unwrap();
+            assert_eq!(source_config.supervision, SupervisionPolicy::default());
+        }
+        {
+            let file_content = r#"{
+                "version": "0.6",
+                "source_id": "void-source",
+                "source_type": "void",
+                "params": {},
+                "supervision": {
+                    "backoff": {"type": "fixed", "delay_secs": 30},
+                    "max_consecutive_failures": 5,
+                    "auto_disable": true
+                }
+            }"#;
+            let source_config =
+                load_source_config_from_user_config(ConfigFormat::Json, file_content.as_bytes())
+                    .unwrap();
+            let expected_supervision_policy = SupervisionPolicy {
+                backoff: BackoffStrategy::Fixed { delay_secs: 30 },
+                max_consecutive_failures: Some(NonZeroUsize::new(5).unwrap()),
+                auto_disable: true,
+            };
+            assert_eq!(source_config.supervision, expected_supervision_policy);
+        }
+        {
+            let file_content = r#"{
+                "version": "0.6",
+                "source_id": "void-source",
+                "source_type": "void",
+                "params": {},
+                "supervision": {"auto_disable": true}
+            }"#;
+            let error =
+                load_source_config_from_user_config(ConfigFormat::Json, file_content.as_bytes())
+                    .unwrap_err();
+            assert!(error
+                .to_string()
+                .contains("requires `max_consecutive_failures`"));
+        }
+        {
+            let file_content = r#"{
+                "version": "0.6",
+                "source_id": "void-source",
+                "source_type": "void",
+                "params": {},
+                "supervision": {
+                    "backoff": {"type": "exponential", "initial_delay_secs": 60, "max_delay_secs": 10}
+                }
+            }"#;
+            let error =
+                load_source_config_from_user_config(ConfigFormat::Json, file_content.as_bytes())
+                    .unwrap_err();
+            assert!(error.to_string().contains("initial_delay_secs"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_source_config_fetch_stream() {
+        {
+            let file_content = r#"{
+                "version": "0.6",
+                "source_id": "ingest-source",
+                "source_type": "ingest"
+            }"#;
+            let source_config =
+                load_source_config_from_user_config(ConfigFormat::Json, file_content.as_bytes())
+                    .unwrap();
+            assert_eq!(source_config.fetch_stream, FetchStreamConfig::default());
+        }
+        {
+            let file_content = r#"{
+                "version": "0.6",
+                "source_id": "ingest-source",
+                "source_type": "ingest",
+                "fetch_stream": {
+                    "max_in_flight_bytes": "512 KiB",
+                    "batch_num_bytes": "128 KiB",
+                    "decompression": "indexer"
+                }
+            }"#;
+            let source_config =
+                load_source_config_from_user_config(ConfigFormat::Json, file_content.as_bytes())
+                    .unwrap();
+            let expected_fetch_stream_config = FetchStreamConfig {
+                max_in_flight_bytes: ByteSize::kib(512),
+                batch_num_bytes: ByteSize::kib(128),
+                decompression: FetchDecompression::Indexer,
+            };
+            assert_eq!(source_config.fetch_stream, expected_fetch_stream_config);
+        }
+        {
+            let file_content = r#"{
+                "version": "0.6",
+                "source_id": "ingest-source",
+                "source_type": "ingest",
+                "fetch_stream": {
+                    "max_in_flight_bytes": "1 MiB",
+                    "batch_num_bytes": "2 MiB"
+                }
+            }"#;
+            let error =
+                load_source_config_from_user_config(ConfigFormat::Json, file_content.as_bytes())
+                    .unwrap_err();
+            assert!(error.to_string().contains("batch_num_bytes"));
+        }
+        {
+            let file_content = r#"{
+                "version": "0.6",
+                "source_id": "void-source",
+                "source_type": "void",
+                "params": {},
+                "fetch_stream": {"decompression": "indexer"}
+            }"#;
+            let error =
+                load_source_config_from_user_config(ConfigFormat::Json, file_content.as_bytes())
+                    .unwrap_err();
+            assert!(error.to_string().contains("only sources of type `ingest`"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_source_config_placement_constraints() {
+        {
+            let file_content = r#"{
+                "version": "0.6",
+                "source_id": "ingest-source",
+                "source_type": "ingest"
+            }"#;
+            let source_config =
+                load_source_config_from_user_config(ConfigFormat::Json, file_content.as_bytes())
+                    .unwrap();
+            assert!(source_config.placement_constraints.is_empty());
+        }
+        {
+            let file_content = r#"{
+                "version": "0.6",
+                "source_id": "ingest-source",
+                "source_type": "ingest",
+                "placement_constraints": {"zone": "eu-west-1a"}
+            }"#;
+            let source_config =
+                load_source_config_from_user_config(ConfigFormat::Json, file_content.as_bytes())
+                    .unwrap();
+            assert_eq!(
+                source_config.placement_constraints,
+                PlacementConstraints::from_iter([("zone", "eu-west-1a")])
+            );
+        }
+        {
+            let file_content = r#"{
+                "version": "0.6",
+                "source_id": "ingest-source",
+                "source_type": "ingest",
+                "placement_constraints": {"zone": "eu-west-1a,eu-west-1b"}
+            }"#;
+            let error =
+                load_source_config_from_user_config(ConfigFormat::Json, file_content.as_bytes())
+                    .unwrap_err();
+            assert!(error.to_string().contains("invalid value"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_source_config_priority() {
+        {
+            let file_content = r#"{
+                "version": "0.6",
+                "source_id": "ingest-source",
+                "source_type": "ingest"
+            }"#;
+            let source_config =
+                load_source_config_from_user_config(ConfigFormat::Json, file_content.as_bytes())
+                    .unwrap();
+            assert_eq!(source_config.priority, SourcePriority::Realtime);
+
+            let source_config_json = serde_json::to_value(&source_config).unwrap();
+            assert!(source_config_json.get("priority").is_none());
+        }
+        {
+            let file_content = r#"{
+                "version": "0.6",
+                "source_id": "ingest-source",
+                "source_type": "ingest",
+                "priority": "backfill"
+            }"#;
+            let source_config =
+                load_source_config_from_user_config(ConfigFormat::Json, file_content.as_bytes())
+                    .unwrap();
+            assert_eq!(source_config.priority, SourcePriority::Backfill);
+            assert_eq!(
+                ShardPriority::from(source_config.priority),
+                ShardPriority::Backfill
+            );
+        }
+        {
+            let file_content = r#"{
+                "version": "0.6",
+                "source_id": "ingest-source",
+                "source_type": "ingest",
+                "priority": "urgent"
+            }"#;
+            let error =
+                load_source_config_from_user_config(ConfigFormat::Json, file_content.as_bytes())
+                    .unwrap_err();
+            assert!(error.to_string().contains("unknown variant"));
+        }
+    }
+
+    #[test]
+    fn test_backoff_strategy_delay() {
+        let exponential_backoff = BackoffStrategy::default();
+        assert_eq!(exponential_backoff.delay(0), Duration::from_secs(1));
+        assert_eq!(exponential_backoff.delay(1), Duration::from_secs(2));
+        assert_eq!(exponential_backoff.delay(3), Duration::from_secs(8));
+        assert_eq!(exponential_backoff.delay(9), Duration::from_secs(512));
+        assert_eq!(exponential_backoff.delay(10), Duration::from_secs(600));
+        assert_eq!(exponential_backoff.delay(1_000), Duration::from_secs(600));
+
+        let fixed_backoff = BackoffStrategy::Fixed { delay_secs: 5 };
+        assert_eq!(fixed_backoff.delay(0), Duration::from_secs(5));
+        assert_eq!(fixed_backoff.delay(42), Duration::from_secs(5));
+    }
 }
@@ -22,8 +22,14 @@ use std::num::NonZeroUsize;
 use anyhow::bail;
 use serde::{Deserialize, Serialize};
 
-use super::{TransformConfig, RESERVED_SOURCE_IDS};
-use crate::{validate_identifier, ConfigFormat, SourceConfig, SourceInputFormat, SourceParams};
+use super::{
+    CsvConfig, DeadLetterQueueConfig, FetchStreamConfig, SourcePriority, SupervisionPolicy,
+    TransformConfig, RESERVED_SOURCE_IDS,
+};
+use crate::{
+    validate_identifier, ConfigFormat, PlacementConstraints, SourceConfig, SourceInputFormat,
+    SourceParams,
+};
 
 type SourceConfigForSerialization = SourceConfigV0_6;
 
@@ -87,8 +93,19 @@ impl SourceConfigForSerialization {
                         self.source_id
                     )
                 }
+                file_params.validate()?;
+                if file_params.watch && self.input_format == SourceInputFormat::Csv {
+                    bail!(
+                        "source `{}` cannot use the `csv` input format in watch mode",
+                        self.source_id
+                    );
+                }
             }
-            SourceParams::Kafka(_) | SourceParams::Kinesis(_) | SourceParams::Pulsar(_) => {
+            SourceParams::AzureEventHubs(azure_event_hubs_params) => {
+                azure_event_hubs_params.to_kafka_source_params()?;
+            }
+            SourceParams::Kafka(kafka_params) => kafka_params.validate()?,
+            SourceParams::Kinesis(_) | SourceParams::Pulsar(_) => {
                 // TODO consider any validation opportunity
             }
             SourceParams::GcpPubSub(_)
@@ -99,10 +116,12 @@ impl SourceConfigForSerialization {
             | SourceParams::Void(_) => {}
         }
         match &self.source_params {
-            SourceParams::GcpPubSub(_) | SourceParams::Kafka(_) => {}
+            SourceParams::AzureEventHubs(_)
+            | SourceParams::GcpPubSub(_)
+            | SourceParams::Kafka(_) => {}
             _ => {
                 if self.desired_num_pipelines > 1 || self.max_num_pipelines_per_indexer > 1 {
-                    bail!("Quickwit currently supports multiple pipelines only for GCP PubSub or Kafka sources. open an issue https://github.com/quickwit-oss/quickwit/issues if you need the feature for other source types");
+                    bail!("Quickwit currently supports multiple pipelines only for Azure Event Hubs, GCP PubSub, or Kafka sources. open an issue https://github.com/quickwit-oss/quickwit/issues if you need the feature for other source types");
                 }
             }
         }
@@ -116,6 +135,36 @@ impl SourceConfigForSerialization {
             }
             transform_config.validate_vrl_script()?;
         }
+        if self.input_format == SourceInputFormat::Csv {
+            if !matches!(
+                self.source_params,
+                SourceParams::File(_) | SourceParams::IngestCli | SourceParams::Vec(_)
+            ) {
+                bail!(
+                    "source `{}` cannot use the `csv` input format: only sources of type `file` \
+                     read the header row of CSV files",
+                    self.source_id
+                );
+            }
+            self.csv.validate()?;
+        } else if !self.csv.is_default() {
+            bail!(
+                "source `{}` cannot set `csv`: its input format is not `csv`",
+                self.source_id
+            );
+        }
+        if let Some(DeadLetterQueueConfig::Index { index_id }) = &self.dead_letter_queue {
+            validate_identifier("Dead letter queue index ID", index_id)?;
+        }
+        self.supervision.validate()?;
+        if !self.fetch_stream.is_default() && !matches!(self.source_params, SourceParams::Ingest) {
+            bail!(
+                "source `{}` cannot set `fetch_stream`: only sources of type `ingest` support it",
+                self.source_id
+            );
+        }
+        self.fetch_stream.validate()?;
+        self.placement_constraints.validate()?;
 
         Ok(SourceConfig {
             source_id: self.source_id,
@@ -125,6 +174,12 @@ impl SourceConfigForSerialization {
             source_params: self.source_params,
             transform_config: self.transform,
             input_format: self.input_format,
+            csv_config: self.csv,
+            dead_letter_queue: self.dead_letter_queue,
+            supervision: self.supervision,
+            fetch_stream: self.fetch_stream,
+            placement_constraints: self.placement_constraints,
+            priority: self.priority,
         })
     }
 }
@@ -139,6 +194,12 @@ impl From<SourceConfig> for SourceConfigV0_6 {
             source_params: source_config.source_params,
             transform: source_config.transform_config,
             input_format: source_config.input_format,
+            csv_config: source_config.csv_config,
+            dead_letter_queue: source_config.dead_letter_queue,
+            supervision: source_config.supervision,
+            fetch_stream: source_config.fetch_stream,
+            placement_constraints: source_config.placement_constraints,
+            priority: source_config.priority,
         }
     }
 }
@@ -196,4 +257,28 @@ pub struct SourceConfigV0_6 {
     // Denotes the input data format.
     #[serde(default)]
     pub input_format: SourceInputFormat,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "CsvConfig::is_default")]
+    pub csv: CsvConfig,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dead_letter_queue: Option<DeadLetterQueueConfig>,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "SupervisionPolicy::is_default")]
+    pub supervision: SupervisionPolicy,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "FetchStreamConfig::is_default")]
+    pub fetch_stream: FetchStreamConfig,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "PlacementConstraints::is_empty")]
+    pub placement_constraints: PlacementConstraints,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "SourcePriority::is_default")]
+    pub priority: SourcePriority,
 }
@@ -33,6 +33,8 @@ pub enum StorageBackend {
     Azure,
     /// Local file system
     File,
+    /// Hadoop Distributed File System, accessed via the WebHDFS REST API
+    Hdfs,
     /// In-memory storage, for testing purposes
     Ram,
     /// Amazon S3 or S3-compatible storage
@@ -89,6 +91,9 @@ impl StorageConfigs {
     }
 
     pub fn validate(&self) -> anyhow::Result<()> {
+        if let Some(s3_storage_config) = self.find_s3() {
+            s3_storage_config.validate()?;
+        }
         let backends: Vec<StorageBackend> = self
             .0
             .iter()
@@ -123,6 +128,15 @@ impl StorageConfigs {
             })
     }
 
+    pub fn find_hdfs(&self) -> Option<&HdfsStorageConfig> {
+        self.0
+            .iter()
+            .find_map(|storage_config| match storage_config {
+                StorageConfig::Hdfs(hdfs_storage_config) => Some(hdfs_storage_config),
+                _ => None,
+            })
+    }
+
     pub fn find_ram(&self) -> Option<&RamStorageConfig> {
         self.0
             .iter()
@@ -155,6 +169,7 @@ impl Deref for StorageConfigs {
 pub enum StorageConfig {
     Azure(AzureStorageConfig),
     File(FileStorageConfig),
+    Hdfs(HdfsStorageConfig),
     Ram(RamStorageConfig),
     S3(S3StorageConfig),
 }
@@ -163,7 +178,7 @@ impl StorageConfig {
     pub fn redact(&mut self) {
         match self {
             Self::Azure(azure_storage_config) => azure_storage_config.redact(),
-            Self::File(_) | Self::Ram(_) => {}
+            Self::File(_) | Self::Hdfs(_) | Self::Ram(_) => {}
             Self::S3(s3_storage_config) => s3_storage_config.redact(),
         }
     }
@@ -182,6 +197,13 @@ impl StorageConfig {
         }
     }
 
+    pub fn as_hdfs(&self) -> Option<&HdfsStorageConfig> {
+        match self {
+            Self::Hdfs(hdfs_storage_config) => Some(hdfs_storage_config),
+            _ => None,
+        }
+    }
+
     pub fn as_ram(&self) -> Option<&RamStorageConfig> {
         match self {
             Self::Ram(ram_storage_config) => Some(ram_storage_config),
@@ -209,6 +231,12 @@ impl From<FileStorageConfig> for StorageConfig {
     }
 }
 
+impl From<HdfsStorageConfig> for StorageConfig {
+    fn from(hdfs_storage_config: HdfsStorageConfig) -> Self {
+        Self::Hdfs(hdfs_storage_config)
+    }
+}
+
 impl From<RamStorageConfig> for StorageConfig {
     fn from(ram_storage_config: RamStorageConfig) -> Self {
         Self::Ram(ram_storage_config)
@@ -226,6 +254,7 @@ impl StorageConfig {
         match self {
             Self::Azure(_) => StorageBackend::Azure,
             Self::File(_) => StorageBackend::File,
+            Self::Hdfs(_) => StorageBackend::Hdfs,
             Self::Ram(_) => StorageBackend::Ram,
             Self::S3(_) => StorageBackend::S3,
         }
@@ -306,6 +335,14 @@ pub struct S3StorageConfig {
     pub disable_multi_object_delete: bool,
     #[serde(default)]
     pub disable_multipart_upload: bool,
+    /// Disables the merging of nearby byte range reads into fewer, larger GET requests.
+    #[serde(default)]
+    pub disable_read_coalescing: bool,
+    /// IAM roles to assume when accessing the objects stored under a given URI prefix, for
+    /// instance, an index whose splits live in another AWS account.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub assume_roles: Vec<S3AssumeRoleConfig>,
 }
 
 impl S3StorageConfig {
@@ -336,6 +373,35 @@ impl S3StorageConfig {
         }
     }
 
+    fn validate(&self) -> anyhow::Result<()> {
+        for assume_role_config in &self.assume_roles {
+            assume_role_config.validate()?;
+        }
+        let uri_prefixes: Vec<&str> = self
+            .assume_roles
+            .iter()
+            .map(|assume_role_config| assume_role_config.uri_prefix.as_str())
+            .sorted()
+            .collect();
+
+        for (left, right) in uri_prefixes.iter().zip(uri_prefixes.iter().skip(1)) {
+            ensure!(
+                left != right,
+                "S3 assume role config for URI prefix `{left}` is defined multiple times",
+            );
+        }
+        Ok(())
+    }
+
+    /// Returns the IAM role to assume for the given storage URI, if any. When several URI prefixes
+    /// match, the longest one wins.
+    pub fn find_assume_role(&self, uri: &str) -> Option<&S3AssumeRoleConfig> {
+        self.assume_roles
+            .iter()
+            .filter(|assume_role_config| assume_role_config.matches(uri))
+            .max_by_key(|assume_role_config| assume_role_config.uri_prefix.len())
+    }
+
     pub fn endpoint(&self) -> Option<String> {
         env::var("QW_S3_ENDPOINT")
             .ok()
@@ -362,14 +428,119 @@ impl fmt::Debug for S3StorageConfig {
                 "disable_multi_object_delete",
                 &self.disable_multi_object_delete,
             )
+            .field("assume_roles", &self.assume_roles)
             .finish()
     }
 }
 
+/// Describes an IAM role to assume via STS when accessing the objects stored under `uri_prefix`.
+///
+/// ```yaml
+/// storage:
+///   s3:
+///     assume_roles:
+///       - uri_prefix: s3://other-account-bucket/indexes
+///         role_arn: arn:aws:iam::123456789012:role/quickwit
+///         external_id: quickwit-external-id
+/// ```
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct S3AssumeRoleConfig {
+    /// Storage URI prefix, for instance a bucket or an index URI, to which the role applies.
+    pub uri_prefix: String,
+    pub role_arn: String,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_id: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_name: Option<String>,
+}
+
+impl S3AssumeRoleConfig {
+    pub const DEFAULT_SESSION_NAME: &'static str = "quickwit";
+
+    fn validate(&self) -> anyhow::Result<()> {
+        ensure!(
+            self.uri_prefix.starts_with("s3://"),
+            "S3 assume role URI prefix `{}` must start with `s3://`",
+            self.uri_prefix
+        );
+        ensure!(
+            self.role_arn.starts_with("arn:"),
+            "S3 assume role ARN `{}` for URI prefix `{}` is invalid",
+            self.role_arn,
+            self.uri_prefix
+        );
+        Ok(())
+    }
+
+    /// Returns whether `uri` is equal to or located under `uri_prefix`.
+    fn matches(&self, uri: &str) -> bool {
+        let uri_prefix = self.uri_prefix.trim_end_matches('/');
+
+        match uri.strip_prefix(uri_prefix) {
+            Some(suffix) => suffix.is_empty() || suffix.starts_with('/'),
+            None => false,
+        }
+    }
+
+    pub fn session_name(&self) -> &str {
+        self.session_name
+            .as_deref()
+            .unwrap_or(Self::DEFAULT_SESSION_NAME)
+    }
+}
+
 #[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct FileStorageConfig;
 
+/// Configures the access to HDFS via the WebHDFS REST API.
+///
+/// ```yaml
+/// storage:
+///   hdfs:
+///     endpoint: http://namenode:9870
+///     user_name: quickwit
+/// ```
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HdfsStorageConfig {
+    /// WebHDFS endpoint of the namenode (or HttpFS gateway). When not set, the endpoint is derived
+    /// from the authority of `hdfs://` URIs.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+    /// User name passed to the namenode for simple (pseudo) authentication.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_name: Option<String>,
+    #[serde(default)]
+    pub disable_multipart_upload: bool,
+}
+
+impl HdfsStorageConfig {
+    pub const HDFS_ENDPOINT_ENV_VAR: &'static str = "QW_HDFS_ENDPOINT";
+
+    pub const HADOOP_USER_NAME_ENV_VAR: &'static str = "HADOOP_USER_NAME";
+
+    /// Attempts to find the endpoint in the environment variable `QW_HDFS_ENDPOINT` or the config.
+    pub fn endpoint(&self) -> Option<String> {
+        env::var(Self::HDFS_ENDPOINT_ENV_VAR)
+            .ok()
+            .or_else(|| self.endpoint.clone())
+    }
+
+    /// Attempts to find the user name in the environment variable `HADOOP_USER_NAME` or the
+    /// config.
+    pub fn resolve_user_name(&self) -> Option<String> {
+        env::var(Self::HADOOP_USER_NAME_ENV_VAR)
+            .ok()
+            .or_else(|| self.user_name.clone())
+    }
+}
+
 #[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct RamStorageConfig;
@@ -502,6 +673,35 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_storage_hdfs_config_serde() {
+        let hdfs_storage_config_yaml = r#"
+                endpoint: http://namenode:9870
+                user_name: quickwit
+            "#;
+        let hdfs_storage_config: HdfsStorageConfig =
+            serde_yaml::from_str(hdfs_storage_config_yaml).unwrap();
+
+        let expected_hdfs_config = HdfsStorageConfig {
+            endpoint: Some("http://namenode:9870".to_string()),
+            user_name: Some("quickwit".to_string()),
+            disable_multipart_upload: false,
+        };
+        assert_eq!(hdfs_storage_config, expected_hdfs_config);
+
+        let storage_configs_yaml = r#"
+                hdfs:
+                    disable_multipart_upload: true
+            "#;
+        let storage_configs: StorageConfigs = serde_yaml::from_str(storage_configs_yaml).unwrap();
+        assert!(
+            storage_configs
+                .find_hdfs()
+                .unwrap()
+                .disable_multipart_upload
+        );
+    }
+
     #[test]
     fn test_storage_azure_config_serde() {
         {
@@ -555,6 +755,7 @@ mod tests {
                 force_path_style_access: true
                 disable_multi_object_delete_requests: true
                 disable_multipart_upload: true
+                disable_read_coalescing: true
             "#;
             let s3_storage_config: S3StorageConfig =
                 serde_yaml::from_str(s3_storage_config_yaml).unwrap();
@@ -565,12 +766,135 @@ mod tests {
                 force_path_style_access: true,
                 disable_multi_object_delete: true,
                 disable_multipart_upload: true,
+                disable_read_coalescing: true,
                 ..Default::default()
             };
             assert_eq!(s3_storage_config, expected_s3_config);
         }
     }
 
+    #[test]
+    fn test_storage_s3_config_assume_roles_serde() {
+        let s3_storage_config_yaml = r#"
+            assume_roles:
+              - uri_prefix: s3://other-account-bucket
+                role_arn: arn:aws:iam::123456789012:role/quickwit
+              - uri_prefix: s3://shared-bucket/indexes/my-index
+                role_arn: arn:aws:iam::210987654321:role/quickwit
+                external_id: test-external-id
+                session_name: test-session
+        "#;
+        let s3_storage_config: S3StorageConfig =
+            serde_yaml::from_str(s3_storage_config_yaml).unwrap();
+
+        let expected_s3_config = S3StorageConfig {
+            assume_roles: vec![
+                S3AssumeRoleConfig {
+                    uri_prefix: "s3://other-account-bucket".to_string(),
+                    role_arn: "arn:aws:iam::123456789012:role/quickwit".to_string(),
+                    ..Default::default()
+                },
+                S3AssumeRoleConfig {
+                    uri_prefix: "s3://shared-bucket/indexes/my-index".to_string(),
+                    role_arn: "arn:aws:iam::210987654321:role/quickwit".to_string(),
+                    external_id: Some("test-external-id".to_string()),
+                    session_name: Some("test-session".to_string()),
+                },
+            ],
+            ..Default::default()
+        };
+        assert_eq!(s3_storage_config, expected_s3_config);
+        assert_eq!(s3_storage_config.assume_roles[0].session_name(), "quickwit");
+        assert_eq!(
+            s3_storage_config.assume_roles[1].session_name(),
+            "test-session"
+        );
+    }
+
+    #[test]
+    fn test_storage_s3_config_validate_assume_roles() {
+        let assume_role_config = S3AssumeRoleConfig {
+            uri_prefix: "s3://bucket".to_string(),
+            role_arn: "arn:aws:iam::123456789012:role/quickwit".to_string(),
+            ..Default::default()
+        };
+        let s3_storage_config = S3StorageConfig {
+            assume_roles: vec![assume_role_config.clone()],
+            ..Default::default()
+        };
+        s3_storage_config.validate().unwrap();
+
+        let s3_storage_config = S3StorageConfig {
+            assume_roles: vec![assume_role_config.clone(), assume_role_config.clone()],
+            ..Default::default()
+        };
+        s3_storage_config.validate().unwrap_err();
+
+        let s3_storage_config = S3StorageConfig {
+            assume_roles: vec![S3AssumeRoleConfig {
+                uri_prefix: "azure://container".to_string(),
+                ..assume_role_config.clone()
+            }],
+            ..Default::default()
+        };
+        s3_storage_config.validate().unwrap_err();
+
+        let s3_storage_config = S3StorageConfig {
+            assume_roles: vec![S3AssumeRoleConfig {
+                role_arn: "quickwit".to_string(),
+                ..assume_role_config
+            }],
+            ..Default::default()
+        };
+        s3_storage_config.validate().unwrap_err();
+    }
+
+    #[test]
+    fn test_storage_s3_config_find_assume_role() {
+        let s3_storage_config = S3StorageConfig {
+            assume_roles: vec![
+                S3AssumeRoleConfig {
+                    uri_prefix: "s3://bucket/".to_string(),
+                    role_arn: "arn:aws:iam::123456789012:role/bucket".to_string(),
+                    ..Default::default()
+                },
+                S3AssumeRoleConfig {
+                    uri_prefix: "s3://bucket/indexes/foo".to_string(),
+                    role_arn: "arn:aws:iam::123456789012:role/foo".to_string(),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        assert!(s3_storage_config
+            .find_assume_role("s3://other-bucket/indexes/foo")
+            .is_none());
+        assert!(s3_storage_config
+            .find_assume_role("s3://bucket-suffix/indexes")
+            .is_none());
+        assert_eq!(
+            s3_storage_config
+                .find_assume_role("s3://bucket")
+                .unwrap()
+                .role_arn,
+            "arn:aws:iam::123456789012:role/bucket"
+        );
+        assert_eq!(
+            s3_storage_config
+                .find_assume_role("s3://bucket/indexes/foobar")
+                .unwrap()
+                .role_arn,
+            "arn:aws:iam::123456789012:role/bucket"
+        );
+        assert_eq!(
+            s3_storage_config
+                .find_assume_role("s3://bucket/indexes/foo/splits")
+                .unwrap()
+                .role_arn,
+            "arn:aws:iam::123456789012:role/foo"
+        );
+    }
+
     #[test]
     fn test_storage_s3_config_flavor_serde() {
         {
@@ -28,22 +28,25 @@ use quickwit_actors::{
 };
 use quickwit_common::pubsub::EventSubscriber;
 use quickwit_config::SourceConfig;
-use quickwit_ingest::{IngesterPool, LocalShardsUpdate};
-use quickwit_metastore::IndexMetadata;
+use quickwit_ingest::{IngesterDiskWatermarkUpdate, IngesterPool, LocalShardsUpdate};
+use quickwit_metastore::{IndexMetadata, IndexMetadataResponseExt};
 use quickwit_proto::control_plane::{
-    ControlPlaneError, ControlPlaneResult, GetOrCreateOpenShardsRequest,
-    GetOrCreateOpenShardsResponse,
+    ControlPlaneError, ControlPlaneResult, DrainNodeRequest, DrainNodeResponse,
+    GetOrCreateOpenShardsRequest, GetOrCreateOpenShardsResponse, GetPlacementViolationsRequest,
+    GetPlacementViolationsResponse, GetShardScalingStateRequest, GetShardScalingStateResponse,
+    GetSourceBacklogRequest, GetSourceBacklogResponse,
 };
-use quickwit_proto::indexing::ShardPositionsUpdate;
+use quickwit_proto::indexing::{ShardPositionsUpdate, SourceBacklogUpdate};
+use quickwit_proto::metastore::events::{IndexChangeEvent, IndexChangeType};
 use quickwit_proto::metastore::{
     serde_utils as metastore_serde_utils, AddSourceRequest, CreateIndexRequest,
     CreateIndexResponse, DeleteIndexRequest, DeleteShardsRequest, DeleteShardsSubrequest,
-    DeleteSourceRequest, EmptyResponse, MetastoreError, MetastoreService, MetastoreServiceClient,
-    ToggleSourceRequest,
+    DeleteSourceRequest, EmptyResponse, EntityKind, IndexMetadataRequest, MetastoreError,
+    MetastoreService, MetastoreServiceClient, SourceType, ToggleSourceRequest,
 };
 use quickwit_proto::types::{IndexUid, NodeId, ShardId, SourceUid};
 use serde::Serialize;
-use tracing::error;
+use tracing::{error, info};
 
 use crate::indexing_scheduler::{IndexingScheduler, IndexingSchedulerState};
 use crate::ingest::IngestController;
@@ -91,8 +94,12 @@ impl ControlPlane {
                 self_node_id.clone(),
                 indexer_pool.clone(),
             );
-            let ingest_controller =
-                IngestController::new(metastore.clone(), ingester_pool.clone(), replication_factor);
+            let ingest_controller = IngestController::new(
+                metastore.clone(),
+                ingester_pool.clone(),
+                indexer_pool.clone(),
+                replication_factor,
+            );
             ControlPlane {
                 model: Default::default(),
                 metastore: metastore.clone(),
@@ -184,6 +191,10 @@ impl Handler<ShardPositionsUpdate> for ControlPlane {
         shard_positions_update: ShardPositionsUpdate,
         _ctx: &ActorContext<Self>,
     ) -> Result<(), ActorExitStatus> {
+        self.model.update_publish_positions(
+            &shard_positions_update.source_uid,
+            &shard_positions_update.shard_positions,
+        );
         let Some(shard_entries) = self.model.list_shards(&shard_positions_update.source_uid) else {
             // The source no longer exists.
             return Ok(());
@@ -436,6 +447,53 @@ impl Handler<DeleteSourceRequest> for ControlPlane {
     }
 }
 
+// This handler is a metastore callback: the index may have been changed by another node or
+// cluster sharing the same metastore, so we read its current metadata and sync the model.
+#[async_trait]
+impl Handler<IndexChangeEvent> for ControlPlane {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        index_change_event: IndexChangeEvent,
+        _ctx: &ActorContext<Self>,
+    ) -> Result<(), ActorExitStatus> {
+        let index_uid = index_change_event.index_uid;
+
+        let index_metadata_opt = if index_change_event.change_type == IndexChangeType::Deleted {
+            None
+        } else {
+            let index_metadata_request = IndexMetadataRequest::for_index_uid(index_uid.clone());
+
+            match self.metastore.index_metadata(index_metadata_request).await {
+                Ok(response) => {
+                    let index_metadata = response
+                        .deserialize_index_metadata()
+                        .context("failed to deserialize index metadata")?;
+                    // The index may have been deleted and recreated in the meantime.
+                    Some(index_metadata)
+                        .filter(|index_metadata| index_metadata.index_uid == index_uid)
+                }
+                Err(MetastoreError::NotFound(_)) => None,
+                Err(metastore_error) => {
+                    // We do not know whether the model is in sync anymore: let's restart the
+                    // control plane so that it reloads its state from the metastore.
+                    error!(error=%metastore_error, index_uid=%index_uid, "failed to fetch index metadata");
+                    return Err(ActorExitStatus::from(anyhow::anyhow!(metastore_error)));
+                }
+            }
+        };
+        let has_changed = self.model.sync_index(&index_uid, index_metadata_opt);
+
+        if has_changed {
+            info!(index_uid=%index_uid, change_type=?index_change_event.change_type, "synced index change notified by metastore");
+            self.indexing_scheduler
+                .schedule_indexing_plan_if_needed(&self.model);
+        }
+        Ok(())
+    }
+}
+
 // This is neither a proxied call nor a metastore callback.
 #[async_trait]
 impl Handler<GetOrCreateOpenShardsRequest> for ControlPlane {
@@ -466,6 +524,119 @@ impl Handler<GetOrCreateOpenShardsRequest> for ControlPlane {
     }
 }
 
+// This is a read-only call: it does not mutate the model.
+#[async_trait]
+impl Handler<GetShardScalingStateRequest> for ControlPlane {
+    type Reply = ControlPlaneResult<GetShardScalingStateResponse>;
+
+    async fn handle(
+        &mut self,
+        request: GetShardScalingStateRequest,
+        _ctx: &ActorContext<Self>,
+    ) -> Result<Self::Reply, ActorExitStatus> {
+        let response = self
+            .ingest_controller
+            .get_shard_scaling_state(request, &self.model);
+        Ok(response)
+    }
+}
+
+// This is a read-only call: it does not mutate the model, except for pruning the backlogs reported
+// by pipelines that are no longer running.
+#[async_trait]
+impl Handler<GetSourceBacklogRequest> for ControlPlane {
+    type Reply = ControlPlaneResult<GetSourceBacklogResponse>;
+
+    async fn handle(
+        &mut self,
+        request: GetSourceBacklogRequest,
+        _ctx: &ActorContext<Self>,
+    ) -> Result<Self::Reply, ActorExitStatus> {
+        let Some(index_uid) = self.model.index_uid(&request.index_id) else {
+            let error = MetastoreError::NotFound(EntityKind::Index {
+                index_id: request.index_id,
+            });
+            return Ok(Err(error.into()));
+        };
+        let source_uid = SourceUid {
+            index_uid,
+            source_id: request.source_id,
+        };
+        let Some(source_config) = self.model.source_config(&source_uid) else {
+            let error = MetastoreError::NotFound(EntityKind::Source {
+                index_id: request.index_id,
+                source_id: source_uid.source_id,
+            });
+            return Ok(Err(error.into()));
+        };
+        let partition_backlogs = if source_config.source_type() == SourceType::IngestV2 {
+            self.model.shard_backlogs(&source_uid).unwrap_or_default()
+        } else {
+            let pipeline_uids = self.indexing_scheduler.running_pipeline_uids(&source_uid);
+            self.model.partition_backlogs(&source_uid, &pipeline_uids)
+        };
+        let total_backlog = partition_backlogs
+            .iter()
+            .map(|partition_backlog| partition_backlog.backlog)
+            .sum();
+        let response = GetSourceBacklogResponse {
+            index_uid: source_uid.index_uid.into(),
+            source_id: source_uid.source_id,
+            total_backlog,
+            partition_backlogs,
+        };
+        Ok(Ok(response))
+    }
+}
+
+// This is a read-only call.
+#[async_trait]
+impl Handler<GetPlacementViolationsRequest> for ControlPlane {
+    type Reply = ControlPlaneResult<GetPlacementViolationsResponse>;
+
+    async fn handle(
+        &mut self,
+        _request: GetPlacementViolationsRequest,
+        _ctx: &ActorContext<Self>,
+    ) -> Result<Self::Reply, ActorExitStatus> {
+        let placement_violations = self.indexing_scheduler.placement_violations(&self.model);
+        let response = GetPlacementViolationsResponse {
+            placement_violations,
+        };
+        Ok(Ok(response))
+    }
+}
+
+// This is neither a proxied call nor a metastore callback. Draining a node is idempotent: the
+// request can be sent again to poll the drain progress.
+#[async_trait]
+impl Handler<DrainNodeRequest> for ControlPlane {
+    type Reply = ControlPlaneResult<DrainNodeResponse>;
+
+    async fn handle(
+        &mut self,
+        request: DrainNodeRequest,
+        ctx: &ActorContext<Self>,
+    ) -> Result<Self::Reply, ActorExitStatus> {
+        let node_id: NodeId = request.node_id.into();
+
+        self.indexing_scheduler.drain_node(&node_id, &self.model);
+        self.ingest_controller
+            .drain_node(&node_id, ctx.progress())
+            .await;
+
+        let num_indexing_tasks = self.indexing_scheduler.num_indexing_tasks(&node_id);
+        let num_shards = self.model.num_shards_hosted_by(&node_id);
+        let response = DrainNodeResponse {
+            node_id: node_id.into(),
+            num_indexing_tasks: num_indexing_tasks as u32,
+            num_shards: num_shards as u32,
+            completed: num_indexing_tasks == 0 && num_shards == 0,
+        };
+        Ok(Ok(response))
+    }
+}
+
 #[async_trait]
 impl Handler<LocalShardsUpdate> for ControlPlane {
     type Reply = ControlPlaneResult<()>;
@@ -484,6 +655,35 @@ impl Handler<LocalShardsUpdate> for ControlPlane {
     }
 }
 
+#[async_trait]
+impl Handler<IngesterDiskWatermarkUpdate> for ControlPlane {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        disk_watermark_update: IngesterDiskWatermarkUpdate,
+        _ctx: &ActorContext<Self>,
+    ) -> Result<Self::Reply, ActorExitStatus> {
+        self.ingest_controller
+            .handle_disk_watermark_update(disk_watermark_update);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Handler<SourceBacklogUpdate> for ControlPlane {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        source_backlog_update: SourceBacklogUpdate,
+        _ctx: &ActorContext<Self>,
+    ) -> Result<Self::Reply, ActorExitStatus> {
+        self.model.update_partition_backlogs(source_backlog_update);
+        Ok(())
+    }
+}
+
 #[derive(Clone)]
 pub struct ControlPlaneEventSubscriber(WeakMailbox<ControlPlane>);
 
@@ -507,6 +707,20 @@ impl EventSubscriber<LocalShardsUpdate> for ControlPlaneEventSubscriber {
     }
 }
 
+#[async_trait]
+impl EventSubscriber<IngesterDiskWatermarkUpdate> for ControlPlaneEventSubscriber {
+    async fn handle_event(&mut self, disk_watermark_update: IngesterDiskWatermarkUpdate) {
+        if let Some(control_plane_mailbox) = self.0.upgrade() {
+            if let Err(error) = control_plane_mailbox
+                .send_message(disk_watermark_update)
+                .await
+            {
+                error!(error=%error, "failed to forward disk watermark update to control plane");
+            }
+        }
+    }
+}
+
 #[async_trait]
 impl EventSubscriber<ShardPositionsUpdate> for ControlPlaneEventSubscriber {
     async fn handle_event(&mut self, shard_positions_update: ShardPositionsUpdate) {
@@ -521,22 +735,55 @@ impl EventSubscriber<ShardPositionsUpdate> for ControlPlaneEventSubscriber {
     }
 }
 
+#[async_trait]
+impl EventSubscriber<IndexChangeEvent> for ControlPlaneEventSubscriber {
+    async fn handle_event(&mut self, index_change_event: IndexChangeEvent) {
+        if let Some(control_plane_mailbox) = self.0.upgrade() {
+            if let Err(error) = control_plane_mailbox.send_message(index_change_event).await {
+                error!(error=%error, "failed to forward index change event to control plane");
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl EventSubscriber<SourceBacklogUpdate> for ControlPlaneEventSubscriber {
+    async fn handle_event(&mut self, source_backlog_update: SourceBacklogUpdate) {
+        if let Some(control_plane_mailbox) = self.0.upgrade() {
+            if let Err(error) = control_plane_mailbox
+                .send_message(source_backlog_update)
+                .await
+            {
+                error!(error=%error, "failed to forward source backlog update to control plane");
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeMap;
+
     use quickwit_actors::{AskError, Observe, SupervisorMetrics};
-    use quickwit_config::{IndexConfig, SourceParams, INGEST_SOURCE_ID};
+    use quickwit_config::{
+        IndexConfig, NodeLabels, PlacementConstraints, SourceParams, INGEST_SOURCE_ID,
+    };
     use quickwit_indexing::IndexingService;
     use quickwit_metastore::{
-        CreateIndexRequestExt, IndexMetadata, ListIndexesMetadataResponseExt,
+        CreateIndexRequestExt, IndexMetadata, IndexMetadataResponseExt,
+        ListIndexesMetadataResponseExt,
+    };
+    use quickwit_proto::control_plane::{GetOrCreateOpenShardsSubrequest, PlacementViolationKind};
+    use quickwit_proto::indexing::{
+        ApplyIndexingPlanRequest, CpuCapacity, IndexingServiceClient, IndexingTask,
     };
-    use quickwit_proto::control_plane::GetOrCreateOpenShardsSubrequest;
-    use quickwit_proto::indexing::{ApplyIndexingPlanRequest, CpuCapacity, IndexingServiceClient};
     use quickwit_proto::ingest::{Shard, ShardState};
     use quickwit_proto::metastore::{
-        DeleteShardsResponse, EntityKind, ListIndexesMetadataRequest, ListIndexesMetadataResponse,
-        ListShardsRequest, ListShardsResponse, ListShardsSubresponse, MetastoreError, SourceType,
+        DeleteShardsResponse, EntityKind, IndexMetadataResponse, ListIndexesMetadataRequest,
+        ListIndexesMetadataResponse, ListShardsRequest, ListShardsResponse, ListShardsSubresponse,
+        MetastoreError, SourceType,
     };
-    use quickwit_proto::types::Position;
+    use quickwit_proto::types::{PipelineUid, Position};
 
     use super::*;
     use crate::IndexerNodeInfo;
@@ -819,6 +1066,77 @@ mod tests {
         universe.assert_quit().await;
     }
 
+    #[tokio::test]
+    async fn test_control_plane_index_change_event() {
+        let universe = Universe::with_accelerated_time();
+
+        let cluster_id = "test-cluster".to_string();
+        let self_node_id: NodeId = "test-node".into();
+        let indexer_pool = IndexerPool::default();
+        let ingester_pool = IngesterPool::default();
+
+        let mut mock_metastore = MetastoreServiceClient::mock();
+        mock_metastore
+            .expect_list_indexes_metadata()
+            .returning(|_| {
+                Ok(ListIndexesMetadataResponse::try_from_indexes_metadata(Vec::new()).unwrap())
+            });
+        mock_metastore
+            .expect_index_metadata()
+            .withf(|index_metadata_request| {
+                assert_eq!(
+                    index_metadata_request.index_uid.as_deref(),
+                    Some("test-index:0")
+                );
+                true
+            })
+            .returning(|_| {
+                let mut index_metadata = IndexMetadata::for_test("test-index", "ram:///test-index");
+                let source_config = SourceConfig::for_test("test-source", SourceParams::void());
+                index_metadata.add_source(source_config).unwrap();
+                Ok(IndexMetadataResponse::try_from_index_metadata(index_metadata).unwrap())
+            });
+        let replication_factor = 1;
+
+        let (control_plane_mailbox, _control_plane_handle) = ControlPlane::spawn(
+            &universe,
+            cluster_id,
+            self_node_id,
+            indexer_pool,
+            ingester_pool,
+            MetastoreServiceClient::from(mock_metastore),
+            replication_factor,
+        );
+        let index_change_event = IndexChangeEvent {
+            index_uid: "test-index:0".into(),
+            change_type: IndexChangeType::Created,
+        };
+        control_plane_mailbox.ask(index_change_event).await.unwrap();
+
+        let get_source_backlog_request = GetSourceBacklogRequest {
+            index_id: "test-index".to_string(),
+            source_id: "test-source".to_string(),
+        };
+        let get_source_backlog_response = control_plane_mailbox
+            .ask_for_res(get_source_backlog_request.clone())
+            .await
+            .unwrap();
+        assert_eq!(get_source_backlog_response.index_uid, "test-index:0");
+
+        let index_change_event = IndexChangeEvent {
+            index_uid: "test-index:0".into(),
+            change_type: IndexChangeType::Deleted,
+        };
+        control_plane_mailbox.ask(index_change_event).await.unwrap();
+
+        control_plane_mailbox
+            .ask_for_res(get_source_backlog_request)
+            .await
+            .unwrap_err();
+
+        universe.assert_quit().await;
+    }
+
     #[tokio::test]
     async fn test_control_plane_get_or_create_open_shards() {
         let universe = Universe::with_accelerated_time();
@@ -1038,6 +1356,7 @@ mod tests {
             client,
             indexing_tasks: Vec::new(),
             indexing_capacity: CpuCapacity::from_cpu_millis(4_000),
+            node_labels: Default::default(),
         };
         indexer_pool.insert("indexer-node-1".to_string(), indexer_node_info);
         let ingester_pool = IngesterPool::default();
@@ -1173,6 +1492,7 @@ mod tests {
             client,
             indexing_tasks: Vec::new(),
             indexing_capacity: CpuCapacity::from_cpu_millis(4_000),
+            node_labels: Default::default(),
         };
         indexer_pool.insert("indexer-node-1".to_string(), indexer_node_info);
         let ingester_pool = IngesterPool::default();
@@ -1246,4 +1566,288 @@ mod tests {
 
         universe.assert_quit().await;
     }
+
+    #[tokio::test]
+    async fn test_control_plane_drain_node() {
+        let universe = Universe::with_accelerated_time();
+
+        let cluster_id = "test-cluster".to_string();
+        let self_node_id: NodeId = "test-node".into();
+        let indexer_pool = IndexerPool::default();
+        let (client_mailbox, client_inbox) = universe.create_test_mailbox();
+        let client = IndexingServiceClient::from_mailbox::<IndexingService>(client_mailbox);
+        let indexer_node_info = IndexerNodeInfo {
+            client,
+            indexing_tasks: vec![IndexingTask {
+                index_uid: "test-index:0".to_string(),
+                source_id: INGEST_SOURCE_ID.to_string(),
+                pipeline_uid: None,
+                shard_ids: vec![1],
+            }],
+            indexing_capacity: CpuCapacity::from_cpu_millis(4_000),
+            node_labels: Default::default(),
+        };
+        indexer_pool.insert("test-indexer".to_string(), indexer_node_info);
+
+        let ingester_pool = IngesterPool::default();
+
+        let mut mock_metastore = MetastoreServiceClient::mock();
+        mock_metastore
+            .expect_list_indexes_metadata()
+            .returning(|_| {
+                let mut index_metadata = IndexMetadata::for_test("test-index", "ram:///test-index");
+                let mut source_config = SourceConfig::ingest_v2_default();
+                source_config.enabled = true;
+                index_metadata.add_source(source_config).unwrap();
+                Ok(
+                    ListIndexesMetadataResponse::try_from_indexes_metadata(vec![index_metadata])
+                        .unwrap(),
+                )
+            });
+        mock_metastore.expect_list_shards().returning(|_| {
+            let subresponses = vec![ListShardsSubresponse {
+                index_uid: "test-index:0".to_string(),
+                source_id: INGEST_SOURCE_ID.to_string(),
+                shards: vec![Shard {
+                    index_uid: "test-index:0".to_string(),
+                    source_id: INGEST_SOURCE_ID.to_string(),
+                    shard_id: 1,
+                    leader_id: "test-indexer".to_string(),
+                    shard_state: ShardState::Open as i32,
+                    ..Default::default()
+                }],
+                next_shard_id: 2,
+            }];
+            let response = ListShardsResponse { subresponses };
+            Ok(response)
+        });
+        let replication_factor = 1;
+
+        let (control_plane_mailbox, _control_plane_handle) = ControlPlane::spawn(
+            &universe,
+            cluster_id,
+            self_node_id,
+            indexer_pool,
+            ingester_pool,
+            MetastoreServiceClient::from(mock_metastore),
+            replication_factor,
+        );
+        let drain_node_request = DrainNodeRequest {
+            node_id: "test-indexer".to_string(),
+        };
+        let drain_node_response = control_plane_mailbox
+            .ask_for_res(drain_node_request)
+            .await
+            .unwrap();
+        assert_eq!(drain_node_response.node_id, "test-indexer");
+        assert_eq!(drain_node_response.num_indexing_tasks, 1);
+        assert_eq!(drain_node_response.num_shards, 1);
+        assert!(!drain_node_response.completed);
+
+        // The indexer may first receive the plan applied when the control plane started.
+        loop {
+            let apply_plan_request = client_inbox
+                .recv_typed_message::<ApplyIndexingPlanRequest>()
+                .await
+                .unwrap();
+            if apply_plan_request.indexing_tasks.is_empty() {
+                break;
+            }
+        }
+
+        let control_plane_obs: ControlPlaneObservableState =
+            control_plane_mailbox.ask(Observe).await.unwrap();
+        assert_eq!(
+            control_plane_obs.indexing_scheduler.drained_node_ids,
+            ["test-indexer".to_string()]
+        );
+        universe.assert_quit().await;
+    }
+
+    #[tokio::test]
+    async fn test_control_plane_get_source_backlog() {
+        let universe = Universe::with_accelerated_time();
+
+        let cluster_id = "test-cluster".to_string();
+        let self_node_id: NodeId = "test-node".into();
+        let indexer_pool = IndexerPool::default();
+        let (client_mailbox, _client_inbox) = universe.create_test_mailbox();
+        let client = IndexingServiceClient::from_mailbox::<IndexingService>(client_mailbox);
+        let indexer_node_info = IndexerNodeInfo {
+            client,
+            indexing_tasks: vec![IndexingTask {
+                index_uid: "test-index:0".to_string(),
+                source_id: "test-source".to_string(),
+                pipeline_uid: Some(PipelineUid::from_u128(1)),
+                shard_ids: Vec::new(),
+            }],
+            indexing_capacity: CpuCapacity::from_cpu_millis(4_000),
+            node_labels: Default::default(),
+        };
+        indexer_pool.insert("test-indexer".to_string(), indexer_node_info);
+
+        let ingester_pool = IngesterPool::default();
+
+        let mut mock_metastore = MetastoreServiceClient::mock();
+        mock_metastore
+            .expect_list_indexes_metadata()
+            .returning(|_| {
+                let mut index_metadata = IndexMetadata::for_test("test-index", "ram:///test-index");
+                let source_config = SourceConfig::for_test("test-source", SourceParams::void());
+                index_metadata.add_source(source_config).unwrap();
+                Ok(
+                    ListIndexesMetadataResponse::try_from_indexes_metadata(vec![index_metadata])
+                        .unwrap(),
+                )
+            });
+        let replication_factor = 1;
+
+        let (control_plane_mailbox, _control_plane_handle) = ControlPlane::spawn(
+            &universe,
+            cluster_id,
+            self_node_id,
+            indexer_pool,
+            ingester_pool,
+            MetastoreServiceClient::from(mock_metastore),
+            replication_factor,
+        );
+        let source_uid = SourceUid {
+            index_uid: "test-index:0".into(),
+            source_id: "test-source".to_string(),
+        };
+        control_plane_mailbox
+            .ask(SourceBacklogUpdate {
+                indexer_id: "test-indexer".into(),
+                source_uid: source_uid.clone(),
+                pipeline_uid: PipelineUid::from_u128(1),
+                partition_backlogs: BTreeMap::from_iter([
+                    ("0".to_string(), 10),
+                    ("1".to_string(), 32),
+                ]),
+            })
+            .await
+            .unwrap();
+        // This pipeline is not running: its report is discarded.
+        control_plane_mailbox
+            .ask(SourceBacklogUpdate {
+                indexer_id: "test-indexer".into(),
+                source_uid,
+                pipeline_uid: PipelineUid::from_u128(2),
+                partition_backlogs: BTreeMap::from_iter([("2".to_string(), 100)]),
+            })
+            .await
+            .unwrap();
+
+        let get_source_backlog_request = GetSourceBacklogRequest {
+            index_id: "test-index".to_string(),
+            source_id: "test-source".to_string(),
+        };
+        let get_source_backlog_response = control_plane_mailbox
+            .ask_for_res(get_source_backlog_request)
+            .await
+            .unwrap();
+        assert_eq!(get_source_backlog_response.index_uid, "test-index:0");
+        assert_eq!(get_source_backlog_response.source_id, "test-source");
+        assert_eq!(get_source_backlog_response.total_backlog, 42);
+
+        let partition_backlogs = &get_source_backlog_response.partition_backlogs;
+        assert_eq!(partition_backlogs.len(), 2);
+        assert_eq!(partition_backlogs[0].partition_id, "0");
+        assert_eq!(partition_backlogs[0].node_id, "test-indexer");
+        assert_eq!(partition_backlogs[0].backlog, 10);
+        assert_eq!(partition_backlogs[1].partition_id, "1");
+        assert_eq!(partition_backlogs[1].backlog, 32);
+
+        let get_source_backlog_request = GetSourceBacklogRequest {
+            index_id: "test-index".to_string(),
+            source_id: "source-not-found".to_string(),
+        };
+        let error = control_plane_mailbox
+            .ask_for_res(get_source_backlog_request)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            AskError::ErrorReply(ControlPlaneError::Metastore(MetastoreError::NotFound(_)))
+        ));
+        universe.assert_quit().await;
+    }
+
+    #[tokio::test]
+    async fn test_control_plane_get_placement_violations() {
+        let universe = Universe::with_accelerated_time();
+
+        let cluster_id = "test-cluster".to_string();
+        let self_node_id: NodeId = "test-node".into();
+        let indexer_pool = IndexerPool::default();
+        let (client_mailbox, _client_inbox) = universe.create_test_mailbox();
+        let client = IndexingServiceClient::from_mailbox::<IndexingService>(client_mailbox);
+        let indexer_node_info = IndexerNodeInfo {
+            client,
+            indexing_tasks: vec![IndexingTask {
+                index_uid: "test-index:0".to_string(),
+                source_id: "test-source".to_string(),
+                pipeline_uid: Some(PipelineUid::from_u128(1)),
+                shard_ids: Vec::new(),
+            }],
+            indexing_capacity: CpuCapacity::from_cpu_millis(4_000),
+            node_labels: NodeLabels::from_iter([("zone".to_string(), "us-east-1a".to_string())]),
+        };
+        indexer_pool.insert("test-indexer".to_string(), indexer_node_info);
+
+        let ingester_pool = IngesterPool::default();
+
+        let mut mock_metastore = MetastoreServiceClient::mock();
+        mock_metastore
+            .expect_list_indexes_metadata()
+            .returning(|_| {
+                let mut index_metadata = IndexMetadata::for_test("test-index", "ram:///test-index");
+                let mut source_config = SourceConfig::for_test("test-source", SourceParams::void());
+                source_config.placement_constraints =
+                    PlacementConstraints::from_iter([("zone", "eu-west-1a")]);
+                index_metadata.add_source(source_config).unwrap();
+                Ok(
+                    ListIndexesMetadataResponse::try_from_indexes_metadata(vec![index_metadata])
+                        .unwrap(),
+                )
+            });
+        let replication_factor = 1;
+
+        let (control_plane_mailbox, _control_plane_handle) = ControlPlane::spawn(
+            &universe,
+            cluster_id,
+            self_node_id,
+            indexer_pool,
+            ingester_pool,
+            MetastoreServiceClient::from(mock_metastore),
+            replication_factor,
+        );
+        let get_placement_violations_response = control_plane_mailbox
+            .ask_for_res(GetPlacementViolationsRequest {})
+            .await
+            .unwrap();
+        let placement_violations = &get_placement_violations_response.placement_violations;
+        assert_eq!(placement_violations.len(), 2);
+
+        assert_eq!(placement_violations[0].index_uid, "test-index:0");
+        assert_eq!(placement_violations[0].source_id, "test-source");
+        assert_eq!(
+            placement_violations[0].placement_constraints,
+            "zone=eu-west-1a"
+        );
+        assert_eq!(
+            placement_violations[0].kind,
+            PlacementViolationKind::NoEligibleNode as i32
+        );
+        assert!(placement_violations[0].node_id.is_empty());
+
+        assert_eq!(
+            placement_violations[1].kind,
+            PlacementViolationKind::IndexingPipeline as i32
+        );
+        assert_eq!(placement_violations[1].node_id, "test-indexer");
+        assert!(placement_violations[1].shard_id.is_none());
+
+        universe.assert_quit().await;
+    }
 }
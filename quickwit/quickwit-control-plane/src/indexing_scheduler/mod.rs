@@ -26,11 +26,13 @@ use std::time::{Duration, Instant};
 
 use fnv::{FnvHashMap, FnvHashSet};
 use itertools::Itertools;
+use quickwit_config::{NodeLabels, PlacementConstraints};
+use quickwit_proto::control_plane::{PlacementViolation, PlacementViolationKind};
 use quickwit_proto::indexing::{
     ApplyIndexingPlanRequest, CpuCapacity, IndexingService, IndexingTask, PIPELINE_FULL_CAPACITY,
 };
 use quickwit_proto::metastore::SourceType;
-use quickwit_proto::types::{NodeId, ShardId};
+use quickwit_proto::types::{NodeId, PipelineUid, ShardId, SourceUid};
 use scheduling::{SourceToSchedule, SourceToScheduleType};
 use serde::Serialize;
 use tracing::{debug, error, info, warn};
@@ -54,6 +56,8 @@ pub struct IndexingSchedulerState {
     pub last_applied_physical_plan: Option<PhysicalIndexingPlan>,
     #[serde(skip)]
     pub last_applied_plan_timestamp: Option<Instant>,
+    /// Indexers that are being drained and must not be assigned any indexing tasks.
+    pub drained_node_ids: Vec<String>,
 }
 
 /// The [`IndexingScheduler`] is responsible for listing indexing tasks and assiging them to
@@ -99,6 +103,7 @@ pub struct IndexingScheduler {
     cluster_id: String,
     self_node_id: NodeId,
     indexer_pool: IndexerPool,
+    drained_node_ids: FnvHashSet<String>,
     state: IndexingSchedulerState,
 }
 
@@ -122,6 +127,7 @@ fn get_sources_to_schedule(model: &ControlPlaneModel) -> Vec<SourceToSchedule> {
         if !source_config.enabled {
             continue;
         }
+        let placement_constraints = model.placement_constraints(&source_uid);
         match source_config.source_type() {
             SourceType::Cli
             | SourceType::File
@@ -135,6 +141,7 @@ fn get_sources_to_schedule(model: &ControlPlaneModel) -> Vec<SourceToSchedule> {
                 sources.push(SourceToSchedule {
                     source_uid,
                     source_type: SourceToScheduleType::IngestV1,
+                    placement_constraints,
                 });
             }
             SourceType::IngestV2 => {
@@ -152,9 +159,11 @@ fn get_sources_to_schedule(model: &ControlPlaneModel) -> Vec<SourceToSchedule> {
                         // FIXME
                         load_per_shard: NonZeroU32::new(250u32).unwrap(),
                     },
+                    placement_constraints,
                 });
             }
-            SourceType::Kafka
+            SourceType::AzureEventHubs
+            | SourceType::Kafka
             | SourceType::Kinesis
             | SourceType::GcpPubsub
             | SourceType::Nats
@@ -167,6 +176,7 @@ fn get_sources_to_schedule(model: &ControlPlaneModel) -> Vec<SourceToSchedule> {
                         load_per_pipeline: NonZeroU32::new(PIPELINE_FULL_CAPACITY.cpu_millis())
                             .unwrap(),
                     },
+                    placement_constraints,
                 });
             }
         }
@@ -180,12 +190,163 @@ impl IndexingScheduler {
             cluster_id,
             self_node_id,
             indexer_pool,
+            drained_node_ids: FnvHashSet::default(),
             state: IndexingSchedulerState::default(),
         }
     }
 
     pub fn observable_state(&self) -> IndexingSchedulerState {
-        self.state.clone()
+        let mut state = self.state.clone();
+        state.drained_node_ids = self.drained_node_ids.iter().cloned().sorted().collect();
+        state
+    }
+
+    /// Marks an indexer as drained: its indexing tasks are moved to the other indexers and it
+    /// will not be assigned new ones for as long as it remains in the indexer pool.
+    pub(crate) fn drain_node(&mut self, node_id: &str, model: &ControlPlaneModel) {
+        if self.drained_node_ids.insert(node_id.to_string()) {
+            info!(node_id=%node_id, "draining indexer");
+            self.schedule_indexing_plan_if_needed(model);
+        }
+        self.stop_drained_indexers();
+    }
+
+    /// Returns the number of indexing tasks the indexer reports as running.
+    pub(crate) fn num_indexing_tasks(&self, node_id: &str) -> usize {
+        self.indexer_pool
+            .get(node_id)
+            .map(|indexer_node_info| indexer_node_info.indexing_tasks.len())
+            .unwrap_or(0)
+    }
+
+    /// Returns the UIDs of the indexing pipelines of a source that the indexers report as running.
+    pub(crate) fn running_pipeline_uids(&self, source_uid: &SourceUid) -> FnvHashSet<PipelineUid> {
+        self.indexer_pool
+            .values()
+            .into_iter()
+            .flat_map(|indexer_node_info| indexer_node_info.indexing_tasks)
+            .filter(|indexing_task| {
+                indexing_task.index_uid == source_uid.index_uid.as_str()
+                    && indexing_task.source_id == source_uid.source_id
+            })
+            .map(|indexing_task| indexing_task.pipeline_uid())
+            .collect()
+    }
+
+    /// Returns the sources whose placement constraints no indexer satisfies, the indexing pipelines
+    /// running on indexers, and the open shards led by ingesters that do not satisfy the placement
+    /// constraints of their index or source.
+    pub(crate) fn placement_violations(
+        &self,
+        model: &ControlPlaneModel,
+    ) -> Vec<PlacementViolation> {
+        let indexers: Vec<(String, IndexerNodeInfo)> = self.indexer_pool.pairs();
+        let indexer_id_to_node_labels: FnvHashMap<&str, &NodeLabels> = indexers
+            .iter()
+            .map(|(indexer_id, indexer_node_info)| {
+                (indexer_id.as_str(), &indexer_node_info.node_labels)
+            })
+            .collect();
+        let satisfies_constraints =
+            |node_id: &str, placement_constraints: &PlacementConstraints| {
+                indexer_id_to_node_labels
+                    .get(node_id)
+                    .map(|node_labels| placement_constraints.is_satisfied_by(node_labels))
+                    .unwrap_or(false)
+            };
+        let mut placement_violations = Vec::new();
+
+        for (source_uid, source_config) in model.get_source_configs() {
+            if !source_config.enabled {
+                continue;
+            }
+            let placement_constraints = model.placement_constraints(&source_uid);
+
+            if placement_constraints.is_empty() {
+                continue;
+            }
+            let new_placement_violation = |kind: PlacementViolationKind| PlacementViolation {
+                index_uid: source_uid.index_uid.to_string(),
+                source_id: source_uid.source_id.clone(),
+                placement_constraints: placement_constraints.to_string(),
+                kind: kind as i32,
+                node_id: String::new(),
+                shard_id: None,
+            };
+            if !indexer_id_to_node_labels
+                .values()
+                .any(|node_labels| placement_constraints.is_satisfied_by(node_labels))
+            {
+                placement_violations.push(new_placement_violation(
+                    PlacementViolationKind::NoEligibleNode,
+                ));
+            }
+            for (indexer_id, indexer_node_info) in &indexers {
+                if satisfies_constraints(indexer_id, &placement_constraints) {
+                    continue;
+                }
+                let num_indexing_tasks = indexer_node_info
+                    .indexing_tasks
+                    .iter()
+                    .filter(|indexing_task| {
+                        indexing_task.index_uid == source_uid.index_uid.as_str()
+                            && indexing_task.source_id == source_uid.source_id
+                    })
+                    .count();
+
+                for _ in 0..num_indexing_tasks {
+                    placement_violations.push(PlacementViolation {
+                        node_id: indexer_id.clone(),
+                        ..new_placement_violation(PlacementViolationKind::IndexingPipeline)
+                    });
+                }
+            }
+            let Some(shard_entries) = model.list_shards(&source_uid) else {
+                continue;
+            };
+            for shard_entry in shard_entries {
+                if !shard_entry.is_open()
+                    || satisfies_constraints(&shard_entry.leader_id, &placement_constraints)
+                {
+                    continue;
+                }
+                placement_violations.push(PlacementViolation {
+                    node_id: shard_entry.leader_id.clone(),
+                    shard_id: Some(shard_entry.shard_id),
+                    ..new_placement_violation(PlacementViolationKind::Shard)
+                });
+            }
+        }
+        placement_violations
+    }
+
+    /// Sends an empty indexing plan to the drained indexers that still run indexing tasks and
+    /// forgets about the drained indexers that have left the cluster.
+    fn stop_drained_indexers(&mut self) {
+        self.drained_node_ids
+            .retain(|node_id| self.indexer_pool.contains_key(node_id));
+
+        for node_id in &self.drained_node_ids {
+            let Some(indexer_node_info) = self.indexer_pool.get(node_id) else {
+                continue;
+            };
+            if indexer_node_info.indexing_tasks.is_empty() {
+                continue;
+            }
+            let node_id = node_id.clone();
+            tokio::spawn(async move {
+                if let Err(error) = indexer_node_info
+                    .client
+                    .clone()
+                    .apply_indexing_plan(ApplyIndexingPlanRequest {
+                        indexing_tasks: Vec::new(),
+                    })
+                    .await
+                {
+                    error!(indexer_node_id=%node_id, err=?error, "error occurred when stopping indexing tasks on drained indexer");
+                }
+            });
+        }
     }
 
     // Should be called whenever a change in the list of index/shard
@@ -206,10 +367,20 @@ impl IndexingScheduler {
                 (indexer_id.to_string(), indexer_node_info.indexing_capacity)
             })
             .collect();
+        let indexer_id_to_node_labels: FnvHashMap<String, NodeLabels> = indexers
+            .iter()
+            .map(|(indexer_id, indexer_node_info)| {
+                (
+                    indexer_id.to_string(),
+                    indexer_node_info.node_labels.clone(),
+                )
+            })
+            .collect();
 
         let new_physical_plan = build_physical_indexing_plan(
             &sources,
             &indexer_id_to_cpu_capacities,
+            &indexer_id_to_node_labels,
             self.state.last_applied_physical_plan.as_ref(),
         );
         if let Some(last_applied_plan) = &self.state.last_applied_physical_plan {
@@ -249,6 +420,7 @@ impl IndexingScheduler {
                 return;
             }
         }
+        self.stop_drained_indexers();
 
         let mut indexers: Vec<(String, IndexerNodeInfo)> = self.get_indexers_from_indexer_pool();
         let running_indexing_tasks_by_node_id: FnvHashMap<String, Vec<IndexingTask>> = indexers
@@ -273,7 +445,11 @@ impl IndexingScheduler {
     }
 
     fn get_indexers_from_indexer_pool(&self) -> Vec<(String, IndexerNodeInfo)> {
-        self.indexer_pool.pairs()
+        self.indexer_pool
+            .pairs()
+            .into_iter()
+            .filter(|(node_id, _)| !self.drained_node_ids.contains(node_id))
+            .collect()
     }
 
     fn apply_physical_indexing_plan(
@@ -640,6 +816,8 @@ mod tests {
             client_log_level: None,
             client_params: serde_json::json!({}),
             enable_backfill_mode: false,
+            header_filters: Vec::new(),
+            header_field_mappings: Vec::new(),
         };
         let index_metadata = IndexMetadata::for_test("test-index", "ram:///test-index");
         let index_uid = index_metadata.index_uid.clone();
@@ -655,6 +833,12 @@ mod tests {
                     source_params: SourceParams::Kafka(kafka_source_params.clone()),
                     transform_config: None,
                     input_format: Default::default(),
+                    csv_config: Default::default(),
+                    dead_letter_queue: None,
+                    supervision: Default::default(),
+                    fetch_stream: Default::default(),
+                    placement_constraints: Default::default(),
+                    priority: Default::default(),
                 },
             )
             .unwrap();
@@ -669,6 +853,12 @@ mod tests {
                     source_params: SourceParams::Kafka(kafka_source_params.clone()),
                     transform_config: None,
                     input_format: Default::default(),
+                    csv_config: Default::default(),
+                    dead_letter_queue: None,
+                    supervision: Default::default(),
+                    fetch_stream: Default::default(),
+                    placement_constraints: Default::default(),
+                    priority: Default::default(),
                 },
             )
             .unwrap();
@@ -684,6 +874,12 @@ mod tests {
                     source_params: SourceParams::IngestApi,
                     transform_config: None,
                     input_format: Default::default(),
+                    csv_config: Default::default(),
+                    dead_letter_queue: None,
+                    supervision: Default::default(),
+                    fetch_stream: Default::default(),
+                    placement_constraints: Default::default(),
+                    priority: Default::default(),
                 },
             )
             .unwrap();
@@ -699,6 +895,12 @@ mod tests {
                     source_params: SourceParams::Ingest,
                     transform_config: None,
                     input_format: Default::default(),
+                    csv_config: Default::default(),
+                    dead_letter_queue: None,
+                    supervision: Default::default(),
+                    fetch_stream: Default::default(),
+                    placement_constraints: Default::default(),
+                    priority: Default::default(),
                 },
             )
             .unwrap();
@@ -714,6 +916,12 @@ mod tests {
                     source_params: SourceParams::IngestCli,
                     transform_config: None,
                     input_format: Default::default(),
+                    csv_config: Default::default(),
+                    dead_letter_queue: None,
+                    supervision: Default::default(),
+                    fetch_stream: Default::default(),
+                    placement_constraints: Default::default(),
+                    priority: Default::default(),
                 },
             )
             .unwrap();
@@ -738,6 +946,7 @@ mod tests {
                     num_pipelines: 3,
                     load_per_pipeline: NonZeroU32::new(1_000).unwrap(),
                 },
+                placement_constraints: PlacementConstraints::default(),
             },
             SourceToSchedule {
                 source_uid: source_2.clone(),
@@ -745,12 +954,18 @@ mod tests {
                     num_pipelines: 2,
                     load_per_pipeline: NonZeroU32::new(1_000).unwrap(),
                 },
+                placement_constraints: PlacementConstraints::default(),
             },
         ];
         let mut indexer_max_loads = FnvHashMap::default();
         indexer_max_loads.insert("indexer1".to_string(), mcpu(3_000));
         indexer_max_loads.insert("indexer2".to_string(), mcpu(3_000));
-        let physical_plan = build_physical_indexing_plan(&sources[..], &indexer_max_loads, None);
+        let physical_plan = build_physical_indexing_plan(
+            &sources[..],
+            &indexer_max_loads,
+            &FnvHashMap::default(),
+            None,
+        );
         assert_eq!(physical_plan.indexing_tasks_per_indexer().len(), 2);
         let indexing_tasks_1 = physical_plan.indexer("indexer1").unwrap();
         assert_eq!(indexing_tasks_1.len(), 2);
@@ -780,7 +995,7 @@ mod tests {
                 let indexer_id = format!("indexer-{i}");
                 indexer_max_loads.insert(indexer_id, mcpu(4_000));
             }
-            let physical_indexing_plan = build_physical_indexing_plan(&sources, &indexer_max_loads, None);
+            let physical_indexing_plan = build_physical_indexing_plan(&sources, &indexer_max_loads, &FnvHashMap::default(), None);
             let source_map: FnvHashMap<&SourceUid, &SourceToSchedule> = sources
                 .iter()
                 .map(|source| (&source.source_uid, source))
@@ -825,6 +1040,8 @@ mod tests {
                 "bootstrap.servers": "localhost:9092",
             }),
             enable_backfill_mode: true,
+            header_filters: Vec::new(),
+            header_field_mappings: Vec::new(),
         })
     }
 
@@ -841,6 +1058,12 @@ mod tests {
               source_params: kafka_source_params_for_test(),
               transform_config: None,
               input_format: SourceInputFormat::Json,
+              csv_config: Default::default(),
+              dead_letter_queue: None,
+              supervision: Default::default(),
+              fetch_stream: Default::default(),
+              placement_constraints: Default::default(),
+              priority: Default::default(),
           })
       }
     }
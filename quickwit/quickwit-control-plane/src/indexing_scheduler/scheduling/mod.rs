@@ -20,9 +20,11 @@
 pub mod scheduling_logic;
 pub mod scheduling_logic_model;
 
+use std::collections::BTreeSet;
 use std::num::NonZeroU32;
 
 use fnv::{FnvHashMap, FnvHashSet};
+use quickwit_config::{NodeLabels, PlacementConstraints};
 use quickwit_proto::indexing::{CpuCapacity, IndexingTask};
 use quickwit_proto::types::{IndexUid, PipelineUid, ShardId, SourceUid};
 use scheduling_logic_model::{IndexerOrd, SourceOrd};
@@ -135,6 +137,8 @@ fn convert_physical_plan_to_solution(
 pub struct SourceToSchedule {
     pub source_uid: SourceUid,
     pub source_type: SourceToScheduleType,
+    /// Labels that an indexer must declare to run the pipelines of the source.
+    pub placement_constraints: PlacementConstraints,
 }
 
 #[derive(Debug)]
@@ -187,6 +191,9 @@ fn convert_scheduling_solution_to_physical_plan_single_node_single_source(
             shard_ids,
             load_per_shard,
         } => {
+            if remaining_num_shards_to_schedule_on_node == 0 {
+                return Vec::new();
+            }
             // For the moment we do something voluntarily suboptimal.
             let max_num_pipelines = quickwit_common::div_ceil_u32(
                 shard_ids.len() as u32 * load_per_shard.get(),
@@ -242,8 +249,11 @@ fn convert_scheduling_solution_to_physical_plan_single_node_single_source(
             indexing_tasks
         }
         SourceToScheduleType::IngestV1 => {
-            // Ingest V1 is simple. One pipeline per indexer node.
-            if let Some(indexing_task) = previous_tasks.first() {
+            // Ingest V1 is simple. One pipeline per indexer node, unless the node does not satisfy
+            // the placement constraints of the source.
+            if remaining_num_shards_to_schedule_on_node == 0 {
+                Vec::new()
+            } else if let Some(indexing_task) = previous_tasks.first() {
                 // The pipeline already exists, let's reuse it.
                 vec![(*indexing_task).clone()]
             } else {
@@ -261,6 +271,7 @@ fn convert_scheduling_solution_to_physical_plan_single_node_single_source(
 
 fn convert_scheduling_solution_to_physical_plan_single_node(
     indexer_assigment: &IndexerAssignment,
+    indexer_node_labels: &NodeLabels,
     previous_tasks: &[IndexingTask],
     sources: &[SourceToSchedule],
     id_to_ord_map: &IdToOrdMap,
@@ -270,9 +281,14 @@ fn convert_scheduling_solution_to_physical_plan_single_node(
         let source_num_shards =
             if let Some(source_ord) = id_to_ord_map.source_ord(&source.source_uid) {
                 indexer_assigment.num_shards(source_ord)
-            } else {
+            } else if source
+                .placement_constraints
+                .is_satisfied_by(indexer_node_labels)
+            {
                 // This can happen for IngestV1
                 1u32
+            } else {
+                0u32
             };
         let source_pipelines: Vec<&IndexingTask> = previous_tasks
             .iter()
@@ -313,8 +329,10 @@ fn convert_scheduling_solution_to_physical_plan(
     mut solution: SchedulingSolution,
     id_to_ord_map: &IdToOrdMap,
     sources: &[SourceToSchedule],
+    indexer_id_to_node_labels: &FnvHashMap<String, NodeLabels>,
     previous_plan_opt: Option<&PhysicalIndexingPlan>,
 ) -> PhysicalIndexingPlan {
+    let no_node_labels = NodeLabels::new();
     let mut new_physical_plan = PhysicalIndexingPlan::with_indexer_ids(&id_to_ord_map.indexer_ids);
     for (indexer_id, indexer_assignment) in id_to_ord_map
         .indexer_ids
@@ -324,10 +342,14 @@ fn convert_scheduling_solution_to_physical_plan(
         let previous_tasks_for_indexer = previous_plan_opt
             .and_then(|previous_plan| previous_plan.indexer(indexer_id))
             .unwrap_or(&[]);
+        let indexer_node_labels = indexer_id_to_node_labels
+            .get(indexer_id)
+            .unwrap_or(&no_node_labels);
         // First we attempt to recycle existing pipelines.
         let new_plan_indexing_tasks_for_indexer: Vec<IndexingTask> =
             convert_scheduling_solution_to_physical_plan_single_node(
                 indexer_assignment,
+                indexer_node_labels,
                 previous_tasks_for_indexer,
                 sources,
                 id_to_ord_map,
@@ -451,10 +473,15 @@ fn add_shard_to_indexer(
 /// - 3) compute the new scheduling solution.
 /// - 4) convert the new scheduling solution back to the real world by reallocating the shard ids.
 ///
+/// The pipelines of a source are only placed on the indexers whose labels satisfy the placement
+/// constraints of the source. Indexers missing from `indexer_id_to_node_labels` are considered to
+/// declare no labels.
+///
 /// TODO cut into pipelines.
 pub fn build_physical_indexing_plan(
     sources: &[SourceToSchedule],
     indexer_id_to_cpu_capacities: &FnvHashMap<String, CpuCapacity>,
+    indexer_id_to_node_labels: &FnvHashMap<String, NodeLabels>,
     previous_plan_opt: Option<&PhysicalIndexingPlan>,
 ) -> PhysicalIndexingPlan {
     // Convert our problem to a scheduling problem.
@@ -474,6 +501,24 @@ pub fn build_physical_indexing_plan(
         if let Some(source_ord) = populate_problem(source, &mut problem) {
             let registered_source_ord = id_to_ord_map.add_source_uid(source.source_uid.clone());
             assert_eq!(source_ord, registered_source_ord);
+
+            if !source.placement_constraints.is_empty() {
+                let allowed_indexer_ords: BTreeSet<IndexerOrd> = id_to_ord_map
+                    .indexer_ids
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, indexer_id)| {
+                        indexer_id_to_node_labels
+                            .get(*indexer_id)
+                            .map(|node_labels| {
+                                source.placement_constraints.is_satisfied_by(node_labels)
+                            })
+                            .unwrap_or(false)
+                    })
+                    .map(|(indexer_ord, _)| indexer_ord)
+                    .collect();
+                problem.restrict_source_to_indexers(source_ord, allowed_indexer_ords);
+            }
         }
     }
 
@@ -498,6 +543,7 @@ pub fn build_physical_indexing_plan(
         new_solution,
         &id_to_ord_map,
         sources,
+        indexer_id_to_node_labels,
         previous_plan_opt,
     )
 }
@@ -509,6 +555,7 @@ mod tests {
     use std::sync::atomic::{AtomicUsize, Ordering};
 
     use fnv::FnvHashMap;
+    use quickwit_config::{NodeLabels, PlacementConstraints};
     use quickwit_proto::indexing::{mcpu, CpuCapacity, IndexingTask};
     use quickwit_proto::types::{IndexUid, PipelineUid, ShardId, SourceUid};
 
@@ -538,6 +585,7 @@ mod tests {
                 shard_ids: vec![0, 1, 2, 3, 4, 5, 6, 7],
                 load_per_shard: NonZeroU32::new(1_000).unwrap(),
             },
+            placement_constraints: PlacementConstraints::default(),
         };
         let source_1 = SourceToSchedule {
             source_uid: source_uid1.clone(),
@@ -545,10 +593,12 @@ mod tests {
                 num_pipelines: 2,
                 load_per_pipeline: NonZeroU32::new(3_200).unwrap(),
             },
+            placement_constraints: PlacementConstraints::default(),
         };
         let source_2 = SourceToSchedule {
             source_uid: source_uid2.clone(),
             source_type: SourceToScheduleType::IngestV1,
+            placement_constraints: PlacementConstraints::default(),
         };
         let mut indexer_id_to_cpu_capacities = FnvHashMap::default();
         indexer_id_to_cpu_capacities.insert(indexer1.clone(), mcpu(16_000));
@@ -556,6 +606,7 @@ mod tests {
         let indexing_plan = build_physical_indexing_plan(
             &[source_0, source_1, source_2],
             &indexer_id_to_cpu_capacities,
+            &FnvHashMap::default(),
             None,
         );
         assert_eq!(indexing_plan.indexing_tasks_per_indexer().len(), 2);
@@ -582,6 +633,70 @@ mod tests {
         assert_eq!(&node2_plan[3].source_id, &source_uid2.source_id);
     }
 
+    #[test]
+    fn test_build_physical_plan_with_placement_constraints() {
+        let indexer1 = "indexer1".to_string();
+        let indexer2 = "indexer2".to_string();
+        let source_uid0 = source_id();
+        let source_uid1 = source_id();
+        let source_uid2 = source_id();
+        let placement_constraints = PlacementConstraints::from_iter([("zone", "eu-west-1a")]);
+        let source_0 = SourceToSchedule {
+            source_uid: source_uid0.clone(),
+            source_type: SourceToScheduleType::Sharded {
+                shard_ids: vec![0, 1, 2, 3],
+                load_per_shard: NonZeroU32::new(1_000).unwrap(),
+            },
+            placement_constraints: placement_constraints.clone(),
+        };
+        let source_1 = SourceToSchedule {
+            source_uid: source_uid1.clone(),
+            source_type: SourceToScheduleType::NonSharded {
+                num_pipelines: 2,
+                load_per_pipeline: NonZeroU32::new(1_000).unwrap(),
+            },
+            placement_constraints: PlacementConstraints::from_iter([("zone", "us-east-1a")]),
+        };
+        let source_2 = SourceToSchedule {
+            source_uid: source_uid2.clone(),
+            source_type: SourceToScheduleType::IngestV1,
+            placement_constraints,
+        };
+        let mut indexer_id_to_cpu_capacities = FnvHashMap::default();
+        indexer_id_to_cpu_capacities.insert(indexer1.clone(), mcpu(16_000));
+        indexer_id_to_cpu_capacities.insert(indexer2.clone(), mcpu(16_000));
+
+        let mut indexer_id_to_node_labels = FnvHashMap::default();
+        indexer_id_to_node_labels.insert(
+            indexer1.clone(),
+            NodeLabels::from_iter([("zone".to_string(), "eu-west-1a".to_string())]),
+        );
+        let indexing_plan = build_physical_indexing_plan(
+            &[source_0, source_1, source_2],
+            &indexer_id_to_cpu_capacities,
+            &indexer_id_to_node_labels,
+            None,
+        );
+        let node1_plan = indexing_plan.indexer(&indexer1).unwrap();
+        assert_eq!(node1_plan.len(), 3);
+
+        let source_0_tasks: Vec<&IndexingTask> = node1_plan
+            .iter()
+            .filter(|task| task.source_id == source_uid0.source_id)
+            .collect();
+        assert_eq!(source_0_tasks.len(), 2);
+        assert_eq!(&source_0_tasks[0].shard_ids, &[0, 1, 2]);
+        assert_eq!(&source_0_tasks[1].shard_ids, &[3]);
+
+        assert!(node1_plan
+            .iter()
+            .any(|task| task.source_id == source_uid2.source_id));
+
+        // No indexer satisfies the placement constraints of source 1.
+        let node2_plan = indexing_plan.indexer(&indexer2).unwrap();
+        assert!(node2_plan.is_empty());
+    }
+
     #[tokio::test]
     async fn test_build_physical_indexing_plan_with_not_enough_indexers() {
         let source_uid1 = source_id();
@@ -591,6 +706,7 @@ mod tests {
                 num_pipelines: 2,
                 load_per_pipeline: NonZeroU32::new(1000).unwrap(),
             },
+            placement_constraints: PlacementConstraints::default(),
         };
         let sources = vec![source_1];
 
@@ -599,7 +715,12 @@ mod tests {
         {
             indexer_max_loads.insert(indexer1.clone(), mcpu(1_999));
             // This test what happens when there isn't enough capacity on the cluster.
-            let physical_plan = build_physical_indexing_plan(&sources, &indexer_max_loads, None);
+            let physical_plan = build_physical_indexing_plan(
+                &sources,
+                &indexer_max_loads,
+                &FnvHashMap::default(),
+                None,
+            );
             assert_eq!(physical_plan.indexing_tasks_per_indexer().len(), 1);
             let expected_tasks = physical_plan.indexer(&indexer1).unwrap();
             assert_eq!(expected_tasks.len(), 1);
@@ -608,7 +729,12 @@ mod tests {
         {
             indexer_max_loads.insert(indexer1.clone(), mcpu(2_000));
             // This test what happens when there isn't enough capacity on the cluster.
-            let physical_plan = build_physical_indexing_plan(&sources, &indexer_max_loads, None);
+            let physical_plan = build_physical_indexing_plan(
+                &sources,
+                &indexer_max_loads,
+                &FnvHashMap::default(),
+                None,
+            );
             assert_eq!(physical_plan.indexing_tasks_per_indexer().len(), 1);
             let expected_tasks = physical_plan.indexer(&indexer1).unwrap();
             assert_eq!(expected_tasks.len(), 2);
@@ -651,6 +777,7 @@ mod tests {
                 shard_ids: vec![0, 1, 3, 4, 5],
                 load_per_shard: NonZeroU32::new(1_000).unwrap(),
             },
+            placement_constraints: PlacementConstraints::default(),
         }];
         let mut indexer_id_to_cpu_capacities = FnvHashMap::default();
         indexer_id_to_cpu_capacities.insert("node1".to_string(), mcpu(10_000));
@@ -661,6 +788,7 @@ mod tests {
         let new_plan = build_physical_indexing_plan(
             &sources,
             &indexer_id_to_cpu_capacities,
+            &FnvHashMap::default(),
             Some(&indexing_plan),
         );
         let indexing_tasks = new_plan.indexer("node1").unwrap();
@@ -682,6 +810,7 @@ mod tests {
                 shard_ids: shard_ids.to_vec(),
                 load_per_shard: NonZeroU32::new(load_per_shard.cpu_millis()).unwrap(),
             },
+            placement_constraints: PlacementConstraints::default(),
         }];
         const NODE: &str = "node1";
         let mut indexer_id_to_cpu_capacities = FnvHashMap::default();
@@ -693,6 +822,7 @@ mod tests {
         let new_plan = build_physical_indexing_plan(
             &sources,
             &indexer_id_to_cpu_capacities,
+            &FnvHashMap::default(),
             Some(&indexing_plan),
         );
         let mut indexing_tasks = new_plan.indexer(NODE).unwrap().to_vec();
@@ -18,6 +18,7 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 use std::cmp::Reverse;
+use std::collections::binary_heap::PeekMut;
 use std::collections::btree_map::Entry;
 use std::collections::{BTreeMap, BinaryHeap, HashMap};
 
@@ -44,6 +45,7 @@ pub fn solve(
 ) -> (SchedulingSolution, BTreeMap<SourceOrd, u32>) {
     let mut solution = previous_solution;
     check_contract_conditions(problem, &solution);
+    remove_shards_on_disallowed_indexers(problem, &mut solution);
     remove_extraneous_shards(problem, &mut solution);
     enforce_indexers_cpu_capacity(problem, &mut solution);
     let still_unassigned = place_unassigned_shards(problem, &mut solution);
@@ -52,6 +54,23 @@ pub fn solve(
     (solution, still_unassigned)
 }
 
+// -------------------------------------------------------------------------
+// Phase 0
+// Remove shards in solution that are placed on indexers not allowed to run them, for instance
+// because the placement constraints of their source have changed.
+
+fn remove_shards_on_disallowed_indexers(
+    problem: &SchedulingProblem,
+    solution: &mut SchedulingSolution,
+) {
+    for indexer_assignment in &mut solution.indexer_assignments {
+        let indexer_ord = indexer_assignment.indexer_ord;
+        indexer_assignment
+            .num_shards_per_source
+            .retain(|&source_ord, _| problem.is_indexer_allowed(source_ord, indexer_ord));
+    }
+}
+
 // -------------------------------------------------------------------------
 // Phase 1
 // Remove shards in solution that are not needed anymore
@@ -208,6 +227,7 @@ fn place_unassigned_shards(
     let mut unassignable_shards = BTreeMap::new();
     for source in unassigned_shards {
         let num_shards_unassigned = place_unassigned_shards_single_source(
+            problem,
             &source,
             &mut indexers_with_most_available_capacity,
             solution,
@@ -248,6 +268,9 @@ fn assert_place_unassigned_shards_post_condition(
             indexer_assignment.indexer_available_capacity(problem);
         for (&source_ord, &num_shards) in unassigned_shards {
             assert!(num_shards > 0);
+            if !problem.is_indexer_allowed(source_ord, indexer_assignment.indexer_ord) {
+                continue;
+            }
             let source = problem.source(source_ord);
             assert!(source.load_per_shard.get() > available_capacity.cpu_millis());
         }
@@ -255,16 +278,24 @@ fn assert_place_unassigned_shards_post_condition(
 }
 
 fn place_unassigned_shards_single_source(
+    problem: &SchedulingProblem,
     source: &Source,
     indexer_available_capacities: &mut BinaryHeap<(CpuCapacity, IndexerOrd)>,
     solution: &mut SchedulingSolution,
 ) -> u32 {
+    // Indexers that are not allowed to run the source are set aside and put back in the heap once
+    // the source has been placed.
+    let mut disallowed_indexers: Vec<(CpuCapacity, IndexerOrd)> = Vec::new();
     let mut num_shards = source.num_shards;
     while num_shards > 0 {
         let Some(mut node_with_most_capacity) = indexer_available_capacities.peek_mut() else {
             break;
         };
         let node_id = node_with_most_capacity.1;
+        if !problem.is_indexer_allowed(source.source_ord, node_id) {
+            disallowed_indexers.push(PeekMut::pop(node_with_most_capacity));
+            continue;
+        }
         let available_capacity = &mut node_with_most_capacity.0;
         let num_placable_shards = available_capacity.cpu_millis() / source.load_per_shard;
         let num_shards_to_place = num_placable_shards.min(num_shards);
@@ -279,6 +310,7 @@ fn place_unassigned_shards_single_source(
             - CpuCapacity::from_cpu_millis(num_shards_to_place * source.load_per_shard.get());
         num_shards -= num_shards_to_place;
     }
+    indexer_available_capacities.extend(disallowed_indexers);
     num_shards
 }
 
@@ -318,6 +350,7 @@ fn compute_indexer_available_capacity(
 }
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeSet;
     use std::num::NonZeroU32;
 
     use proptest::prelude::*;
@@ -523,6 +556,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_place_unassigned_shards_with_placement_constraints() {
+        let mut problem = SchedulingProblem::with_indexer_cpu_capacities(vec![
+            mcpu(8_000),
+            mcpu(4_000),
+            mcpu(4_000),
+        ]);
+        problem.add_source(6, NonZeroU32::new(1_000).unwrap());
+        problem.add_source(2, NonZeroU32::new(1_000).unwrap());
+        problem.restrict_source_to_indexers(0, BTreeSet::from_iter([1, 2]));
+        let mut solution = problem.new_solution();
+        let unassigned_shards = place_unassigned_shards(&problem, &mut solution);
+        assert_eq!(solution.indexer_assignments[0].num_shards(0), 0);
+        assert_eq!(solution.indexer_assignments[1].num_shards(0), 2);
+        assert_eq!(solution.indexer_assignments[2].num_shards(0), 4);
+        assert_eq!(solution.indexer_assignments[0].num_shards(1), 2);
+        assert!(unassigned_shards.is_empty());
+    }
+
+    #[test]
+    fn test_solve_with_placement_constraints() {
+        let mut problem =
+            SchedulingProblem::with_indexer_cpu_capacities(vec![mcpu(4_000), mcpu(4_000)]);
+        problem.add_source(6, NonZeroU32::new(1_000).unwrap());
+        problem.restrict_source_to_indexers(0, BTreeSet::from_iter([1]));
+        let mut previous_solution = problem.new_solution();
+        previous_solution.indexer_assignments[0].add_shards(0, 3);
+        previous_solution.indexer_assignments[1].add_shards(0, 3);
+        let (solution, unassigned_shards) = solve(&problem, previous_solution);
+        assert_eq!(solution.indexer_assignments[0].num_shards(0), 0);
+        assert_eq!(solution.indexer_assignments[1].num_shards(0), 4);
+        assert_eq!(unassigned_shards.len(), 1);
+        assert_eq!(unassigned_shards[&0], 2);
+    }
+
     #[test]
     fn test_solve() {
         let mut problem = SchedulingProblem::with_indexer_cpu_capacities(vec![mcpu(800)]);
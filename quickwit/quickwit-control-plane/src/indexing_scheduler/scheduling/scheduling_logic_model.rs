@@ -18,7 +18,7 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 use std::collections::btree_map::Entry;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::num::NonZeroU32;
 
 use quickwit_proto::indexing::CpuCapacity;
@@ -37,6 +37,9 @@ pub struct Source {
 pub struct SchedulingProblem {
     sources: Vec<Source>,
     indexer_cpu_capacities: Vec<CpuCapacity>,
+    // Indexers allowed to run the shards of each source. `None` means that all the indexers are
+    // allowed.
+    source_allowed_indexers: Vec<Option<BTreeSet<IndexerOrd>>>,
 }
 
 impl SchedulingProblem {
@@ -54,6 +57,7 @@ impl SchedulingProblem {
         SchedulingProblem {
             sources: Vec::new(),
             indexer_cpu_capacities,
+            source_allowed_indexers: Vec::new(),
         }
     }
 
@@ -72,9 +76,27 @@ impl SchedulingProblem {
             num_shards,
             load_per_shard,
         });
+        self.source_allowed_indexers.push(None);
         source_ord
     }
 
+    /// Restricts the shards of a source to the given indexers, for instance because the other
+    /// indexers do not satisfy the placement constraints of the source.
+    pub fn restrict_source_to_indexers(
+        &mut self,
+        source_ord: SourceOrd,
+        indexer_ords: BTreeSet<IndexerOrd>,
+    ) {
+        self.source_allowed_indexers[source_ord as usize] = Some(indexer_ords);
+    }
+
+    pub fn is_indexer_allowed(&self, source_ord: SourceOrd, indexer_ord: IndexerOrd) -> bool {
+        self.source_allowed_indexers[source_ord as usize]
+            .as_ref()
+            .map(|indexer_ords| indexer_ords.contains(&indexer_ord))
+            .unwrap_or(true)
+    }
+
     pub fn source_load_per_shard(&self, source_ord: SourceOrd) -> NonZeroU32 {
         self.sources[source_ord as usize].load_per_shard
     }
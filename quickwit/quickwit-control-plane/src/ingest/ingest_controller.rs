@@ -24,35 +24,39 @@ use std::time::Duration;
 use fnv::{FnvHashMap, FnvHashSet};
 use itertools::Itertools;
 use quickwit_common::{PrettySample, Progress};
-use quickwit_ingest::{IngesterPool, LocalShardsUpdate};
+use quickwit_config::PlacementConstraints;
+use quickwit_ingest::{IngesterDiskWatermarkUpdate, IngesterPool, LocalShardsUpdate};
 use quickwit_proto::control_plane::{
     ControlPlaneError, ControlPlaneResult, GetOrCreateOpenShardsFailure,
     GetOrCreateOpenShardsFailureReason, GetOrCreateOpenShardsRequest,
-    GetOrCreateOpenShardsResponse, GetOrCreateOpenShardsSuccess,
+    GetOrCreateOpenShardsResponse, GetOrCreateOpenShardsSuccess, GetShardScalingStateRequest,
+    GetShardScalingStateResponse, ShardScalingDecision, ShardScalingMode,
 };
 use quickwit_proto::ingest::ingester::{
-    CloseShardsRequest, IngesterService, InitShardsRequest, PingRequest,
+    CloseShardsRequest, DecommissionRequest, IngesterService, InitShardsRequest, PingRequest,
 };
 use quickwit_proto::ingest::{IngestV2Error, Shard, ShardIds, ShardState};
 use quickwit_proto::metastore;
-use quickwit_proto::metastore::{MetastoreService, MetastoreServiceClient};
+use quickwit_proto::metastore::{
+    EntityKind, MetastoreError, MetastoreService, MetastoreServiceClient,
+};
 use quickwit_proto::types::{IndexUid, NodeId, ShardId, SourceUid};
 use rand::seq::SliceRandom;
+use time::OffsetDateTime;
 use tokio::time::timeout;
 use tracing::{info, warn};
 
 use crate::metrics::CONTROL_PLANE_METRICS;
 use crate::model::{ControlPlaneModel, ScalingMode, ShardEntry, ShardStats};
+use crate::IndexerPool;
 
-const MAX_SHARD_INGESTION_THROUGHPUT_MIB_PER_SEC: f32 = 5.;
+/// Fraction of the target throughput per shard above which we increase the number of shards.
+const SCALE_UP_SHARDS_THRESHOLD_RATIO: f32 = 0.8;
 
-/// Threshold in MiB/s above which we increase the number of shards.
-const SCALE_UP_SHARDS_THRESHOLD_MIB_PER_SEC: f32 =
-    MAX_SHARD_INGESTION_THROUGHPUT_MIB_PER_SEC * 8. / 10.;
+/// Fraction of the target throughput per shard below which we decrease the number of shards.
+const SCALE_DOWN_SHARDS_THRESHOLD_RATIO: f32 = 0.2;
 
-/// Threshold in MiB/s below which we decrease the number of shards.
-const SCALE_DOWN_SHARDS_THRESHOLD_MIB_PER_SEC: f32 =
-    MAX_SHARD_INGESTION_THROUGHPUT_MIB_PER_SEC * 2. / 10.;
+const ONE_MIB: f32 = 1024. * 1024.;
 
 const PING_LEADER_TIMEOUT: Duration = if cfg!(test) {
     Duration::from_millis(50)
@@ -63,7 +67,13 @@ const PING_LEADER_TIMEOUT: Duration = if cfg!(test) {
 pub struct IngestController {
     metastore: MetastoreServiceClient,
     ingester_pool: IngesterPool,
+    // Used to look up the labels of the ingesters, which run on the indexer nodes, when placing
+    // the shards of sources with placement constraints.
+    indexer_pool: IndexerPool,
     replication_factor: usize,
+    // Ingesters whose write-ahead log disk usage exceeds the high watermark. They do not accept
+    // new shards.
+    disk_watermark_exceeded_ingesters: FnvHashSet<NodeId>,
 }
 
 impl fmt::Debug for IngestController {
@@ -72,6 +82,10 @@ impl fmt::Debug for IngestController {
             .field("replication", &self.metastore)
             .field("ingester_pool", &self.ingester_pool)
             .field("replication_factor", &self.replication_factor)
+            .field(
+                "disk_watermark_exceeded_ingesters",
+                &self.disk_watermark_exceeded_ingesters,
+            )
             .finish()
     }
 }
@@ -80,12 +94,33 @@ impl IngestController {
     pub fn new(
         metastore: MetastoreServiceClient,
         ingester_pool: IngesterPool,
+        indexer_pool: IndexerPool,
         replication_factor: usize,
     ) -> Self {
         IngestController {
             metastore,
             ingester_pool,
+            indexer_pool,
             replication_factor,
+            disk_watermark_exceeded_ingesters: FnvHashSet::default(),
+        }
+    }
+
+    pub(crate) fn handle_disk_watermark_update(
+        &mut self,
+        disk_watermark_update: IngesterDiskWatermarkUpdate,
+    ) {
+        let ingester_id = disk_watermark_update.ingester_id;
+
+        if disk_watermark_update.disk_watermark_exceeded {
+            if self
+                .disk_watermark_exceeded_ingesters
+                .insert(ingester_id.clone())
+            {
+                warn!(ingester_id=%ingester_id, "ingester disk usage exceeds high watermark");
+            }
+        } else if self.disk_watermark_exceeded_ingesters.remove(&ingester_id) {
+            info!(ingester_id=%ingester_id, "ingester disk usage fell below low watermark");
         }
     }
 
@@ -126,18 +161,41 @@ impl IngestController {
         Ok(())
     }
 
+    /// Returns whether the labels of an ingester satisfy the given placement constraints.
+    fn satisfies_placement_constraints(
+        &self,
+        ingester_id: &NodeId,
+        placement_constraints: &PlacementConstraints,
+    ) -> bool {
+        if placement_constraints.is_empty() {
+            return true;
+        }
+        self.indexer_pool
+            .get(ingester_id.as_str())
+            .map(|indexer_node_info| {
+                placement_constraints.is_satisfied_by(&indexer_node_info.node_labels)
+            })
+            .unwrap_or(false)
+    }
+
     /// Finds an available leader-follower pair to host a shard. If the replication factor is set to
-    /// 1, only a leader is returned. If no nodes are available, `None` is returned.
+    /// 1, only a leader is returned. Only the ingesters satisfying the placement constraints of the
+    /// source are considered. If no nodes are available, `None` is returned.
     async fn find_leader_and_follower(
         &mut self,
         unavailable_ingesters: &mut FnvHashSet<NodeId>,
+        placement_constraints: &PlacementConstraints,
         progress: &Progress,
     ) -> Option<(NodeId, Option<NodeId>)> {
         let mut candidates: Vec<NodeId> = self
             .ingester_pool
             .keys()
             .into_iter()
-            .filter(|node_id| !unavailable_ingesters.contains(node_id))
+            .filter(|node_id| {
+                !unavailable_ingesters.contains(node_id)
+                    && !self.disk_watermark_exceeded_ingesters.contains(node_id)
+                    && self.satisfies_placement_constraints(node_id, placement_constraints)
+            })
             .collect();
         candidates.shuffle(&mut rand::thread_rng());
 
@@ -219,11 +277,30 @@ impl IngestController {
             &local_shards_update.source_uid,
             &local_shards_update.shard_infos,
         );
-        if shard_stats.avg_ingestion_rate >= SCALE_UP_SHARDS_THRESHOLD_MIB_PER_SEC {
+        let shard_scaling_settings = model.shard_scaling_settings(&local_shards_update.source_uid);
+        let target_throughput_mib_per_sec =
+            shard_scaling_settings.target_throughput_per_shard.as_u64() as f32 / ONE_MIB;
+        let num_open_shards = shard_stats.num_open_shards;
+
+        let can_scale_up = shard_scaling_settings
+            .max_shards
+            .map_or(true, |max_shards| num_open_shards < max_shards);
+        // The minimum number of shards is only enforced once the source is active, i.e. once the
+        // routers have requested a first shard for it.
+        let is_below_min_shards =
+            num_open_shards > 0 && num_open_shards < shard_scaling_settings.min_shards;
+        let can_scale_down = num_open_shards > shard_scaling_settings.min_shards;
+
+        if can_scale_up
+            && (is_below_min_shards
+                || shard_stats.avg_ingestion_rate
+                    >= target_throughput_mib_per_sec * SCALE_UP_SHARDS_THRESHOLD_RATIO)
+        {
             self.try_scale_up_shards(local_shards_update.source_uid, shard_stats, model, progress)
                 .await;
-        } else if shard_stats.avg_ingestion_rate <= SCALE_DOWN_SHARDS_THRESHOLD_MIB_PER_SEC
-            && shard_stats.num_open_shards > 1
+        } else if can_scale_down
+            && shard_stats.avg_ingestion_rate
+                <= target_throughput_mib_per_sec * SCALE_DOWN_SHARDS_THRESHOLD_RATIO
         {
             self.try_scale_down_shards(
                 local_shards_update.source_uid,
@@ -235,6 +312,57 @@ impl IngestController {
         }
     }
 
+    /// Returns the number of open shards of a source and the most recent scaling decisions made for
+    /// it.
+    pub(crate) fn get_shard_scaling_state(
+        &self,
+        request: GetShardScalingStateRequest,
+        model: &ControlPlaneModel,
+    ) -> ControlPlaneResult<GetShardScalingStateResponse> {
+        let index_uid = model.index_uid(&request.index_id).ok_or_else(|| {
+            MetastoreError::NotFound(EntityKind::Index {
+                index_id: request.index_id.clone(),
+            })
+        })?;
+        let source_uid = SourceUid {
+            index_uid,
+            source_id: request.source_id,
+        };
+        let recent_decisions = model.recent_scaling_decisions(&source_uid).ok_or_else(|| {
+            MetastoreError::NotFound(EntityKind::Source {
+                index_id: request.index_id,
+                source_id: source_uid.source_id.clone(),
+            })
+        })?;
+        let num_open_shards = model
+            .list_shards(&source_uid)
+            .map(|shards| shards.filter(|shard| shard.is_open()).count())
+            .unwrap_or_default();
+        let response = GetShardScalingStateResponse {
+            index_uid: source_uid.index_uid.into(),
+            source_id: source_uid.source_id,
+            num_open_shards: num_open_shards as u32,
+            recent_decisions,
+        };
+        Ok(response)
+    }
+
+    /// Decommissions the ingester running on `node_id`, if any: the ingester closes its shards,
+    /// stops accepting new ones, and keeps serving its queues until they are fully consumed.
+    pub(crate) async fn drain_node(&self, node_id: &NodeId, progress: &Progress) {
+        let Some(mut ingester) = self.ingester_pool.get(node_id) else {
+            return;
+        };
+        info!(node_id=%node_id, "decommissioning ingester");
+
+        if let Err(error) = progress
+            .protect_future(ingester.decommission(DecommissionRequest {}))
+            .await
+        {
+            warn!(node_id=%node_id, "failed to decommission ingester: {error}");
+        }
+    }
+
     fn handle_unavailable_leaders(
         &self,
         unavailable_leaders: &FnvHashSet<NodeId>,
@@ -324,13 +452,29 @@ impl IngestController {
                 };
                 get_or_create_open_shards_successes.push(get_or_create_open_shards_success);
             } else {
+                let source_uid = SourceUid {
+                    index_uid: index_uid.clone(),
+                    source_id: get_open_shards_subrequest.source_id.clone(),
+                };
+                let placement_constraints = model.placement_constraints(&source_uid);
                 // TODO: Find leaders in batches.
                 // TODO: Round-robin leader-follower pairs or choose according to load.
                 let (leader_id, follower_id) = self
-                    .find_leader_and_follower(&mut unavailable_leaders, progress)
+                    .find_leader_and_follower(
+                        &mut unavailable_leaders,
+                        &placement_constraints,
+                        progress,
+                    )
                     .await
                     .ok_or_else(|| {
-                        ControlPlaneError::Unavailable("no ingester available".to_string())
+                        if placement_constraints.is_empty() {
+                            ControlPlaneError::Unavailable("no ingester available".to_string())
+                        } else {
+                            ControlPlaneError::Unavailable(format!(
+                                "no ingester available satisfying placement constraints \
+                                 `{placement_constraints}`"
+                            ))
+                        }
                     })?;
                 let open_shards_subrequest = metastore::OpenShardsSubrequest {
                     subrequest_id: get_open_shards_subrequest.subrequest_id,
@@ -339,6 +483,7 @@ impl IngestController {
                     leader_id: leader_id.into(),
                     follower_id: follower_id.map(|follower_id| follower_id.into()),
                     next_shard_id,
+                    priority: model.shard_priority(&source_uid) as i32,
                 };
                 open_shards_subrequests.push(open_shards_subrequest);
             }
@@ -450,9 +595,10 @@ impl IngestController {
             .expect("source should exist");
 
         let mut unavailable_leaders: FnvHashSet<NodeId> = FnvHashSet::default();
+        let placement_constraints = model.placement_constraints(&source_uid);
 
         let Some((leader_id, follower_id)) = self
-            .find_leader_and_follower(&mut unavailable_leaders, progress)
+            .find_leader_and_follower(&mut unavailable_leaders, &placement_constraints, progress)
             .await
         else {
             warn!("failed to scale up number of shards: no ingester available");
@@ -466,6 +612,7 @@ impl IngestController {
             leader_id: leader_id.into(),
             follower_id: follower_id.map(Into::into),
             next_shard_id,
+            priority: model.shard_priority(&source_uid) as i32,
         };
         let open_shards_request = metastore::OpenShardsRequest {
             subrequests: vec![open_shards_subrequest],
@@ -497,6 +644,13 @@ impl IngestController {
                 open_shards_subresponse.next_shard_id,
             );
         }
+        record_scaling_decision(
+            &source_uid,
+            ShardScalingMode::Up,
+            shard_stats,
+            new_num_open_shards,
+            model,
+        );
         let label_values = [source_uid.index_uid.index_id(), &source_uid.source_id];
         CONTROL_PLANE_METRICS
             .open_shards_total
@@ -553,6 +707,13 @@ impl IngestController {
         }
         model.close_shards(&source_uid, &[shard_id]);
 
+        record_scaling_decision(
+            &source_uid,
+            ShardScalingMode::Down,
+            shard_stats,
+            new_num_open_shards,
+            model,
+        );
         let label_values = [source_uid.index_uid.index_id(), &source_uid.source_id];
         CONTROL_PLANE_METRICS
             .open_shards_total
@@ -561,6 +722,23 @@ impl IngestController {
     }
 }
 
+fn record_scaling_decision(
+    source_uid: &SourceUid,
+    scaling_mode: ShardScalingMode,
+    shard_stats: ShardStats,
+    new_num_open_shards: usize,
+    model: &mut ControlPlaneModel,
+) {
+    let scaling_decision = ShardScalingDecision {
+        timestamp: OffsetDateTime::now_utc().unix_timestamp(),
+        scaling_mode: scaling_mode as i32,
+        avg_ingestion_rate: shard_stats.avg_ingestion_rate,
+        num_open_shards_before: shard_stats.num_open_shards as u32,
+        num_open_shards_after: new_num_open_shards as u32,
+    };
+    model.record_scaling_decision(source_uid, scaling_decision);
+}
+
 /// Finds the shard with the highest ingestion rate on the ingester with the least number of open
 /// shards.
 fn find_scale_down_candidate(
@@ -600,19 +778,21 @@ mod tests {
 
     use std::collections::BTreeSet;
 
-    use quickwit_config::{SourceConfig, SourceParams, INGEST_SOURCE_ID};
+    use bytesize::ByteSize;
+    use quickwit_config::{NodeLabels, SourceConfig, SourceParams, INGEST_SOURCE_ID};
     use quickwit_ingest::{RateMibPerSec, ShardInfo};
     use quickwit_metastore::IndexMetadata;
     use quickwit_proto::control_plane::GetOrCreateOpenShardsSubrequest;
+    use quickwit_proto::indexing::{CpuCapacity, IndexingServiceClient};
     use quickwit_proto::ingest::ingester::{
-        CloseShardsResponse, IngesterServiceClient, InitShardsResponse, MockIngesterService,
-        PingResponse,
+        CloseShardsResponse, DecommissionResponse, IngesterServiceClient, InitShardsResponse,
+        MockIngesterService, PingResponse,
     };
     use quickwit_proto::ingest::{Shard, ShardState};
-    use quickwit_proto::metastore::MetastoreError;
-    use quickwit_proto::types::SourceId;
+    use quickwit_proto::types::{Position, SourceId};
 
     use super::*;
+    use crate::IndexerNodeInfo;
 
     #[tokio::test]
     async fn test_ingest_controller_ping_leader() {
@@ -624,6 +804,7 @@ mod tests {
         let mut ingest_controller = IngestController::new(
             MetastoreServiceClient::from(mock_metastore),
             ingester_pool.clone(),
+            IndexerPool::default(),
             replication_factor,
         );
 
@@ -689,6 +870,33 @@ mod tests {
         assert!(matches!(error, PingError::FollowerUnavailable));
     }
 
+    #[tokio::test]
+    async fn test_ingest_controller_drain_node() {
+        let progress = Progress::default();
+
+        let mock_metastore = MetastoreServiceClient::mock();
+        let ingester_pool = IngesterPool::default();
+        let replication_factor = 1;
+        let ingest_controller = IngestController::new(
+            MetastoreServiceClient::from(mock_metastore),
+            ingester_pool.clone(),
+            IndexerPool::default(),
+            replication_factor,
+        );
+        let node_id: NodeId = "test-ingester-0".into();
+        ingest_controller.drain_node(&node_id, &progress).await;
+
+        let mut mock_ingester = MockIngesterService::default();
+        mock_ingester
+            .expect_decommission()
+            .once()
+            .returning(|_| Ok(DecommissionResponse {}));
+        let ingester: IngesterServiceClient = mock_ingester.into();
+        ingester_pool.insert(node_id.clone(), ingester);
+
+        ingest_controller.drain_node(&node_id, &progress).await;
+    }
+
     #[tokio::test]
     async fn test_ingest_controller_find_leader_replication_factor_1() {
         let progress = Progress::default();
@@ -699,11 +907,16 @@ mod tests {
         let mut ingest_controller = IngestController::new(
             MetastoreServiceClient::from(mock_metastore),
             ingester_pool.clone(),
+            IndexerPool::default(),
             replication_factor,
         );
 
         let leader_follower_pair = ingest_controller
-            .find_leader_and_follower(&mut FnvHashSet::default(), &progress)
+            .find_leader_and_follower(
+                &mut FnvHashSet::default(),
+                &PlacementConstraints::default(),
+                &progress,
+            )
             .await;
         assert!(leader_follower_pair.is_none());
 
@@ -718,7 +931,11 @@ mod tests {
         ingester_pool.insert("test-ingester-0".into(), ingester.clone());
 
         let leader_follower_pair = ingest_controller
-            .find_leader_and_follower(&mut FnvHashSet::default(), &progress)
+            .find_leader_and_follower(
+                &mut FnvHashSet::default(),
+                &PlacementConstraints::default(),
+                &progress,
+            )
             .await;
         assert!(leader_follower_pair.is_none());
 
@@ -733,13 +950,131 @@ mod tests {
         ingester_pool.insert("test-ingester-1".into(), ingester);
 
         let (leader_id, follower_id) = ingest_controller
-            .find_leader_and_follower(&mut FnvHashSet::default(), &progress)
+            .find_leader_and_follower(
+                &mut FnvHashSet::default(),
+                &PlacementConstraints::default(),
+                &progress,
+            )
             .await
             .unwrap();
         assert_eq!(leader_id.as_str(), "test-ingester-1");
         assert!(follower_id.is_none());
     }
 
+    #[tokio::test]
+    async fn test_ingest_controller_find_leader_skips_disk_watermark_exceeded_ingesters() {
+        let progress = Progress::default();
+
+        let mock_metastore = MetastoreServiceClient::mock();
+        let ingester_pool = IngesterPool::default();
+        let replication_factor = 1;
+        let mut ingest_controller = IngestController::new(
+            MetastoreServiceClient::from(mock_metastore),
+            ingester_pool.clone(),
+            IndexerPool::default(),
+            replication_factor,
+        );
+        let mut mock_ingester_0 = MockIngesterService::default();
+        mock_ingester_0.expect_ping().once().returning(|request| {
+            assert_eq!(request.leader_id, "test-ingester-0");
+            Ok(PingResponse {})
+        });
+        ingester_pool.insert("test-ingester-0".into(), mock_ingester_0.into());
+
+        let mut mock_ingester_1 = MockIngesterService::default();
+        mock_ingester_1.expect_ping().once().returning(|request| {
+            assert_eq!(request.leader_id, "test-ingester-1");
+            Ok(PingResponse {})
+        });
+        ingester_pool.insert("test-ingester-1".into(), mock_ingester_1.into());
+
+        ingest_controller.handle_disk_watermark_update(IngesterDiskWatermarkUpdate {
+            ingester_id: "test-ingester-0".into(),
+            disk_watermark_exceeded: true,
+        });
+        let (leader_id, _) = ingest_controller
+            .find_leader_and_follower(
+                &mut FnvHashSet::default(),
+                &PlacementConstraints::default(),
+                &progress,
+            )
+            .await
+            .unwrap();
+        assert_eq!(leader_id.as_str(), "test-ingester-1");
+
+        ingest_controller.handle_disk_watermark_update(IngesterDiskWatermarkUpdate {
+            ingester_id: "test-ingester-0".into(),
+            disk_watermark_exceeded: false,
+        });
+        let (leader_id, _) = ingest_controller
+            .find_leader_and_follower(
+                &mut FnvHashSet::default(),
+                &PlacementConstraints::default(),
+                &progress,
+            )
+            .await
+            .unwrap();
+        assert_eq!(leader_id.as_str(), "test-ingester-0");
+    }
+
+    #[tokio::test]
+    async fn test_ingest_controller_find_leader_satisfies_placement_constraints() {
+        let progress = Progress::default();
+
+        let mock_metastore = MetastoreServiceClient::mock();
+        let ingester_pool = IngesterPool::default();
+        let indexer_pool = IndexerPool::default();
+        let replication_factor = 1;
+        let mut ingest_controller = IngestController::new(
+            MetastoreServiceClient::from(mock_metastore),
+            ingester_pool.clone(),
+            indexer_pool.clone(),
+            replication_factor,
+        );
+        let mock_ingester_0 = MockIngesterService::default();
+        ingester_pool.insert("test-ingester-0".into(), mock_ingester_0.into());
+
+        let mut mock_ingester_1 = MockIngesterService::default();
+        mock_ingester_1.expect_ping().once().returning(|request| {
+            assert_eq!(request.leader_id, "test-ingester-1");
+            Ok(PingResponse {})
+        });
+        ingester_pool.insert("test-ingester-1".into(), mock_ingester_1.into());
+
+        for (node_id, zone) in [
+            ("test-ingester-0", "us-east-1a"),
+            ("test-ingester-1", "eu-west-1a"),
+        ] {
+            let indexer_node_info = IndexerNodeInfo {
+                client: IndexingServiceClient::from(IndexingServiceClient::mock()),
+                indexing_tasks: Vec::new(),
+                indexing_capacity: CpuCapacity::from_cpu_millis(4_000),
+                node_labels: NodeLabels::from_iter([("zone".to_string(), zone.to_string())]),
+            };
+            indexer_pool.insert(node_id.to_string(), indexer_node_info);
+        }
+        let placement_constraints = PlacementConstraints::from_iter([("zone", "eu-west-1a")]);
+        let (leader_id, _) = ingest_controller
+            .find_leader_and_follower(
+                &mut FnvHashSet::default(),
+                &placement_constraints,
+                &progress,
+            )
+            .await
+            .unwrap();
+        assert_eq!(leader_id.as_str(), "test-ingester-1");
+
+        let placement_constraints = PlacementConstraints::from_iter([("zone", "ap-south-1a")]);
+        let leader_follower_pair = ingest_controller
+            .find_leader_and_follower(
+                &mut FnvHashSet::default(),
+                &placement_constraints,
+                &progress,
+            )
+            .await;
+        assert!(leader_follower_pair.is_none());
+    }
+
     #[tokio::test]
     async fn test_ingest_controller_find_leader_replication_factor_2() {
         let progress = Progress::default();
@@ -750,11 +1085,16 @@ mod tests {
         let mut ingest_controller = IngestController::new(
             MetastoreServiceClient::from(mock_metastore),
             ingester_pool.clone(),
+            IndexerPool::default(),
             replication_factor,
         );
 
         let leader_follower_pair = ingest_controller
-            .find_leader_and_follower(&mut FnvHashSet::default(), &progress)
+            .find_leader_and_follower(
+                &mut FnvHashSet::default(),
+                &PlacementConstraints::default(),
+                &progress,
+            )
             .await;
         assert!(leader_follower_pair.is_none());
 
@@ -778,7 +1118,11 @@ mod tests {
         ingester_pool.insert("test-ingester-1".into(), ingester.clone());
 
         let leader_follower_pair = ingest_controller
-            .find_leader_and_follower(&mut FnvHashSet::default(), &progress)
+            .find_leader_and_follower(
+                &mut FnvHashSet::default(),
+                &PlacementConstraints::default(),
+                &progress,
+            )
             .await;
         assert!(leader_follower_pair.is_none());
 
@@ -808,7 +1152,11 @@ mod tests {
         ingester_pool.insert("test-ingester-2".into(), ingester.clone());
 
         let (leader_id, follower_id) = ingest_controller
-            .find_leader_and_follower(&mut FnvHashSet::default(), &progress)
+            .find_leader_and_follower(
+                &mut FnvHashSet::default(),
+                &PlacementConstraints::default(),
+                &progress,
+            )
             .await
             .unwrap();
         assert_eq!(leader_id.as_str(), "test-ingester-0");
@@ -888,6 +1236,7 @@ mod tests {
         let mut ingest_controller = IngestController::new(
             MetastoreServiceClient::from(mock_metastore),
             ingester_pool.clone(),
+            IndexerPool::default(),
             replication_factor,
         );
 
@@ -1017,8 +1366,12 @@ mod tests {
         let ingester_pool = IngesterPool::default();
         let replication_factor = 2;
 
-        let mut ingest_controller =
-            IngestController::new(metastore, ingester_pool, replication_factor);
+        let mut ingest_controller = IngestController::new(
+            metastore,
+            ingester_pool,
+            IndexerPool::default(),
+            replication_factor,
+        );
         let mut model = ControlPlaneModel::default();
 
         let index_uid: IndexUid = "test-index-0:0".into();
@@ -1065,8 +1418,12 @@ mod tests {
 
         let replication_factor = 2;
 
-        let mut ingest_controller =
-            IngestController::new(metastore, ingester_pool.clone(), replication_factor);
+        let mut ingest_controller = IngestController::new(
+            metastore,
+            ingester_pool.clone(),
+            IndexerPool::default(),
+            replication_factor,
+        );
         let mut model = ControlPlaneModel::default();
 
         let index_uid: IndexUid = "test-index-0:0".into();
@@ -1131,8 +1488,12 @@ mod tests {
         let ingester_pool = IngesterPool::default();
         let replication_factor = 1;
 
-        let mut ingest_controller =
-            IngestController::new(metastore, ingester_pool.clone(), replication_factor);
+        let mut ingest_controller = IngestController::new(
+            metastore,
+            ingester_pool.clone(),
+            IndexerPool::default(),
+            replication_factor,
+        );
 
         let index_uid: IndexUid = "test-index:0".into();
         let source_id: SourceId = "test-source".into();
@@ -1164,6 +1525,7 @@ mod tests {
             shard_id: 1,
             shard_state: ShardState::Open,
             ingestion_rate: RateMibPerSec(1),
+            replication_position_inclusive: Position::Beginning,
         }]);
         let local_shards_update = LocalShardsUpdate {
             leader_id: "test-ingester".into(),
@@ -1220,11 +1582,13 @@ mod tests {
                 shard_id: 1,
                 shard_state: ShardState::Open,
                 ingestion_rate: RateMibPerSec(1),
+                replication_position_inclusive: Position::Beginning,
             },
             ShardInfo {
                 shard_id: 2,
                 shard_state: ShardState::Open,
                 ingestion_rate: RateMibPerSec(1),
+                replication_position_inclusive: Position::Beginning,
             },
         ]);
         let local_shards_update = LocalShardsUpdate {
@@ -1242,11 +1606,13 @@ mod tests {
                 shard_id: 1,
                 shard_state: ShardState::Open,
                 ingestion_rate: RateMibPerSec(4),
+                replication_position_inclusive: Position::Beginning,
             },
             ShardInfo {
                 shard_id: 2,
                 shard_state: ShardState::Open,
                 ingestion_rate: RateMibPerSec(4),
+                replication_position_inclusive: Position::Beginning,
             },
         ]);
         let local_shards_update = LocalShardsUpdate {
@@ -1308,6 +1674,7 @@ mod tests {
         let mut ingest_controller = IngestController::new(
             mock_metastore.into(),
             ingester_pool.clone(),
+            IndexerPool::default(),
             replication_factor,
         );
 
@@ -1398,8 +1765,12 @@ mod tests {
         let ingester_pool = IngesterPool::default();
         let replication_factor = 1;
 
-        let ingest_controller =
-            IngestController::new(metastore, ingester_pool.clone(), replication_factor);
+        let ingest_controller = IngestController::new(
+            metastore,
+            ingester_pool.clone(),
+            IndexerPool::default(),
+            replication_factor,
+        );
 
         let index_uid: IndexUid = "test-index:0".into();
         let source_id: SourceId = "test-source".into();
@@ -1488,6 +1859,222 @@ mod tests {
         assert!(model.all_shards_mut().any(|shard| shard.is_open()));
     }
 
+    #[tokio::test]
+    async fn test_ingest_controller_shard_scaling_settings() {
+        let metastore = MetastoreServiceClient::mock().into();
+        let ingester_pool = IngesterPool::default();
+        let replication_factor = 1;
+
+        let mut ingest_controller = IngestController::new(
+            metastore,
+            ingester_pool.clone(),
+            IndexerPool::default(),
+            replication_factor,
+        );
+
+        let index_uid: IndexUid = "test-index:0".into();
+        let source_id: SourceId = INGEST_SOURCE_ID.to_string();
+
+        let source_uid = SourceUid {
+            index_uid: index_uid.clone(),
+            source_id: source_id.clone(),
+        };
+        let mut model = ControlPlaneModel::default();
+        let mut index_metadata =
+            IndexMetadata::for_test(index_uid.index_id(), "ram://indexes/test-index:0");
+        let shard_scaling_settings = &mut index_metadata.index_config.ingest_settings.shard_scaling;
+        shard_scaling_settings.min_shards = 1;
+        shard_scaling_settings.max_shards = Some(2);
+        shard_scaling_settings.target_throughput_per_shard = ByteSize::mib(10);
+        model.add_index(index_metadata);
+
+        let souce_config = SourceConfig::ingest_v2_default();
+        model.add_source(&index_uid, souce_config).unwrap();
+
+        let shards = vec![
+            Shard {
+                shard_id: 1,
+                leader_id: "test-ingester".to_string(),
+                shard_state: ShardState::Open as i32,
+                ..Default::default()
+            },
+            Shard {
+                shard_id: 2,
+                leader_id: "test-ingester".to_string(),
+                shard_state: ShardState::Open as i32,
+                ..Default::default()
+            },
+        ];
+        model.insert_newly_opened_shards(&index_uid, &source_id, shards, 3);
+
+        let mut ingester_mock = IngesterServiceClient::mock();
+        // Scaling up is not allowed because the source has already reached `max_shards`.
+        ingester_mock.expect_ping().never();
+        ingester_mock
+            .expect_close_shards()
+            .once()
+            .returning(|request| {
+                assert_eq!(request.shards.len(), 1);
+                Ok(CloseShardsResponse {})
+            });
+        ingester_pool.insert("test-ingester".into(), ingester_mock.into());
+
+        let progress = Progress::default();
+
+        // 7 MiB/s is below the scale-up threshold of a 10 MiB/s target, and so would 5 MiB/s be
+        // with the default settings: no scaling.
+        let shard_infos = BTreeSet::from_iter([
+            ShardInfo {
+                shard_id: 1,
+                shard_state: ShardState::Open,
+                ingestion_rate: RateMibPerSec(7),
+                replication_position_inclusive: Position::Beginning,
+            },
+            ShardInfo {
+                shard_id: 2,
+                shard_state: ShardState::Open,
+                ingestion_rate: RateMibPerSec(7),
+                replication_position_inclusive: Position::Beginning,
+            },
+        ]);
+        let local_shards_update = LocalShardsUpdate {
+            leader_id: "test-ingester".into(),
+            source_uid: source_uid.clone(),
+            shard_infos,
+        };
+        ingest_controller
+            .handle_local_shards_update(local_shards_update, &mut model, &progress)
+            .await;
+
+        // 9 MiB/s is above the scale-up threshold but the source has reached `max_shards`.
+        let shard_infos = BTreeSet::from_iter([
+            ShardInfo {
+                shard_id: 1,
+                shard_state: ShardState::Open,
+                ingestion_rate: RateMibPerSec(9),
+                replication_position_inclusive: Position::Beginning,
+            },
+            ShardInfo {
+                shard_id: 2,
+                shard_state: ShardState::Open,
+                ingestion_rate: RateMibPerSec(9),
+                replication_position_inclusive: Position::Beginning,
+            },
+        ]);
+        let local_shards_update = LocalShardsUpdate {
+            leader_id: "test-ingester".into(),
+            source_uid: source_uid.clone(),
+            shard_infos,
+        };
+        ingest_controller
+            .handle_local_shards_update(local_shards_update, &mut model, &progress)
+            .await;
+
+        let request = GetShardScalingStateRequest {
+            index_id: "test-index".to_string(),
+            source_id: source_id.clone(),
+        };
+        let response = ingest_controller
+            .get_shard_scaling_state(request, &model)
+            .unwrap();
+        assert_eq!(response.num_open_shards, 2);
+        assert!(response.recent_decisions.is_empty());
+
+        // 1 MiB/s is below the scale-down threshold.
+        let shard_infos = BTreeSet::from_iter([
+            ShardInfo {
+                shard_id: 1,
+                shard_state: ShardState::Open,
+                ingestion_rate: RateMibPerSec(1),
+                replication_position_inclusive: Position::Beginning,
+            },
+            ShardInfo {
+                shard_id: 2,
+                shard_state: ShardState::Open,
+                ingestion_rate: RateMibPerSec(1),
+                replication_position_inclusive: Position::Beginning,
+            },
+        ]);
+        let local_shards_update = LocalShardsUpdate {
+            leader_id: "test-ingester".into(),
+            source_uid: source_uid.clone(),
+            shard_infos,
+        };
+        ingest_controller
+            .handle_local_shards_update(local_shards_update, &mut model, &progress)
+            .await;
+
+        let request = GetShardScalingStateRequest {
+            index_id: "test-index".to_string(),
+            source_id: source_id.clone(),
+        };
+        let response = ingest_controller
+            .get_shard_scaling_state(request, &model)
+            .unwrap();
+        assert_eq!(response.index_uid, "test-index:0");
+        assert_eq!(response.num_open_shards, 1);
+        assert_eq!(response.recent_decisions.len(), 1);
+
+        let scaling_decision = &response.recent_decisions[0];
+        assert_eq!(scaling_decision.scaling_mode(), ShardScalingMode::Down);
+        assert_eq!(scaling_decision.avg_ingestion_rate, 1.);
+        assert_eq!(scaling_decision.num_open_shards_before, 2);
+        assert_eq!(scaling_decision.num_open_shards_after, 1);
+
+        // The source has reached `min_shards`: no more scaling down. Give back the scale-down
+        // permit first so that the rate limiter does not prevent scaling down on its own.
+        model.release_scaling_permits(&source_uid, ScalingMode::Down, 1);
+
+        let shard_infos = BTreeSet::from_iter([
+            ShardInfo {
+                shard_id: 1,
+                shard_state: ShardState::Open,
+                ingestion_rate: RateMibPerSec(0),
+                replication_position_inclusive: Position::Beginning,
+            },
+            ShardInfo {
+                shard_id: 2,
+                shard_state: ShardState::Open,
+                ingestion_rate: RateMibPerSec(0),
+                replication_position_inclusive: Position::Beginning,
+            },
+        ]);
+        let local_shards_update = LocalShardsUpdate {
+            leader_id: "test-ingester".into(),
+            source_uid: source_uid.clone(),
+            shard_infos,
+        };
+        ingest_controller
+            .handle_local_shards_update(local_shards_update, &mut model, &progress)
+            .await;
+
+        let request = GetShardScalingStateRequest {
+            index_id: "test-index".to_string(),
+            source_id: source_id.clone(),
+        };
+        let response = ingest_controller
+            .get_shard_scaling_state(request, &model)
+            .unwrap();
+        assert_eq!(response.num_open_shards, 1);
+        assert_eq!(response.recent_decisions.len(), 1);
+        // The scale-down permit is still available.
+        assert!(model
+            .acquire_scaling_permits(&source_uid, ScalingMode::Down, 1)
+            .unwrap());
+
+        let request = GetShardScalingStateRequest {
+            index_id: "test-index".to_string(),
+            source_id: "unknown-source".to_string(),
+        };
+        let error = ingest_controller
+            .get_shard_scaling_state(request, &model)
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            ControlPlaneError::Metastore(MetastoreError::NotFound(EntityKind::Source { .. }))
+        ));
+    }
+
     #[test]
     fn test_find_scale_down_candidate() {
         let index_uid: IndexUid = "test-index:0".into();
@@ -1546,31 +2133,37 @@ mod tests {
                 shard_id: 1,
                 shard_state: ShardState::Open,
                 ingestion_rate: quickwit_ingest::RateMibPerSec(1),
+                replication_position_inclusive: Position::Beginning,
             },
             ShardInfo {
                 shard_id: 2,
                 shard_state: ShardState::Open,
                 ingestion_rate: quickwit_ingest::RateMibPerSec(2),
+                replication_position_inclusive: Position::Beginning,
             },
             ShardInfo {
                 shard_id: 3,
                 shard_state: ShardState::Open,
                 ingestion_rate: quickwit_ingest::RateMibPerSec(3),
+                replication_position_inclusive: Position::Beginning,
             },
             ShardInfo {
                 shard_id: 4,
                 shard_state: ShardState::Open,
                 ingestion_rate: quickwit_ingest::RateMibPerSec(4),
+                replication_position_inclusive: Position::Beginning,
             },
             ShardInfo {
                 shard_id: 5,
                 shard_state: ShardState::Open,
                 ingestion_rate: quickwit_ingest::RateMibPerSec(5),
+                replication_position_inclusive: Position::Beginning,
             },
             ShardInfo {
                 shard_id: 6,
                 shard_state: ShardState::Open,
                 ingestion_rate: quickwit_ingest::RateMibPerSec(6),
+                replication_position_inclusive: Position::Beginning,
             },
         ]);
         model.update_shards(&source_uid, &shard_infos);
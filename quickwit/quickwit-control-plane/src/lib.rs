@@ -25,6 +25,7 @@ pub(crate) mod metrics;
 pub(crate) mod model;
 
 use quickwit_common::tower::Pool;
+use quickwit_config::NodeLabels;
 use quickwit_proto::indexing::{CpuCapacity, IndexingServiceClient, IndexingTask};
 
 /// Indexer-node specific information stored in the pool of available indexer nodes
@@ -33,6 +34,7 @@ pub struct IndexerNodeInfo {
     pub client: IndexingServiceClient,
     pub indexing_tasks: Vec<IndexingTask>,
     pub indexing_capacity: CpuCapacity,
+    pub node_labels: NodeLabels,
 }
 
 pub type IndexerPool = Pool<String, IndexerNodeInfo>;
@@ -0,0 +1,173 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+use fnv::{FnvHashMap, FnvHashSet};
+use quickwit_proto::control_plane::PartitionBacklog;
+use quickwit_proto::indexing::SourceBacklogUpdate;
+use quickwit_proto::types::{IndexUid, NodeId, PipelineUid, SourceUid};
+
+#[derive(Debug)]
+struct PipelineBacklog {
+    indexer_id: NodeId,
+    partition_backlogs: BTreeMap<String, u64>,
+    updated_at: Instant,
+}
+
+/// Keeps track of the backlogs of the partitions of the sources consumed by the indexers directly
+/// (e.g. Kafka), as reported by the indexing pipelines.
+#[derive(Debug, Default)]
+pub(crate) struct BacklogTable {
+    table_entries: FnvHashMap<SourceUid, FnvHashMap<PipelineUid, PipelineBacklog>>,
+}
+
+impl BacklogTable {
+    pub fn update(&mut self, source_backlog_update: SourceBacklogUpdate) {
+        let SourceBacklogUpdate {
+            indexer_id,
+            source_uid,
+            pipeline_uid,
+            partition_backlogs,
+        } = source_backlog_update;
+
+        let pipeline_backlog = PipelineBacklog {
+            indexer_id,
+            partition_backlogs,
+            updated_at: Instant::now(),
+        };
+        self.table_entries
+            .entry(source_uid)
+            .or_default()
+            .insert(pipeline_uid, pipeline_backlog);
+    }
+
+    pub fn delete_index(&mut self, index_uid: &IndexUid) {
+        self.table_entries
+            .retain(|source_uid, _| source_uid.index_uid != *index_uid);
+    }
+
+    pub fn delete_source(&mut self, source_uid: &SourceUid) {
+        self.table_entries.remove(source_uid);
+    }
+
+    /// Returns the backlogs of the partitions of a source reported by the given pipelines. Reports
+    /// of other pipelines are stale and discarded. When several pipelines report the same
+    /// partition, for instance after a rebalance of a Kafka consumer group, the most recent report
+    /// wins.
+    pub fn partition_backlogs(
+        &mut self,
+        source_uid: &SourceUid,
+        pipeline_uids: &FnvHashSet<PipelineUid>,
+    ) -> Vec<PartitionBacklog> {
+        let Some(table_entry) = self.table_entries.get_mut(source_uid) else {
+            return Vec::new();
+        };
+        table_entry.retain(|pipeline_uid, _| pipeline_uids.contains(pipeline_uid));
+
+        let mut partition_backlogs: BTreeMap<&str, (Instant, PartitionBacklog)> = BTreeMap::new();
+
+        for pipeline_backlog in table_entry.values() {
+            for (partition_id, backlog) in &pipeline_backlog.partition_backlogs {
+                let partition_backlog = PartitionBacklog {
+                    partition_id: partition_id.clone(),
+                    node_id: pipeline_backlog.indexer_id.to_string(),
+                    backlog: *backlog,
+                };
+                match partition_backlogs.entry(partition_id) {
+                    Entry::Occupied(mut entry) => {
+                        if entry.get().0 < pipeline_backlog.updated_at {
+                            entry.insert((pipeline_backlog.updated_at, partition_backlog));
+                        }
+                    }
+                    Entry::Vacant(entry) => {
+                        entry.insert((pipeline_backlog.updated_at, partition_backlog));
+                    }
+                }
+            }
+        }
+        partition_backlogs
+            .into_values()
+            .map(|(_, partition_backlog)| partition_backlog)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backlog_table() {
+        let mut backlog_table = BacklogTable::default();
+
+        let source_uid = SourceUid {
+            index_uid: "test-index:0".into(),
+            source_id: "test-source".to_string(),
+        };
+        let pipeline_uid_0 = PipelineUid::from_u128(0);
+        let pipeline_uid_1 = PipelineUid::from_u128(1);
+        let pipeline_uid_2 = PipelineUid::from_u128(2);
+        let pipeline_uids = FnvHashSet::from_iter([pipeline_uid_0, pipeline_uid_1]);
+
+        let partition_backlogs = backlog_table.partition_backlogs(&source_uid, &pipeline_uids);
+        assert!(partition_backlogs.is_empty());
+
+        backlog_table.update(SourceBacklogUpdate {
+            indexer_id: "test-indexer-0".into(),
+            source_uid: source_uid.clone(),
+            pipeline_uid: pipeline_uid_0,
+            partition_backlogs: BTreeMap::from_iter([("0".to_string(), 10), ("1".to_string(), 0)]),
+        });
+        backlog_table.update(SourceBacklogUpdate {
+            indexer_id: "test-indexer-1".into(),
+            source_uid: source_uid.clone(),
+            pipeline_uid: pipeline_uid_1,
+            partition_backlogs: BTreeMap::from_iter([("1".to_string(), 3), ("2".to_string(), 5)]),
+        });
+        // This pipeline is no longer running.
+        backlog_table.update(SourceBacklogUpdate {
+            indexer_id: "test-indexer-2".into(),
+            source_uid: source_uid.clone(),
+            pipeline_uid: pipeline_uid_2,
+            partition_backlogs: BTreeMap::from_iter([("3".to_string(), 7)]),
+        });
+        let partition_backlogs = backlog_table.partition_backlogs(&source_uid, &pipeline_uids);
+        assert_eq!(partition_backlogs.len(), 3);
+
+        assert_eq!(partition_backlogs[0].partition_id, "0");
+        assert_eq!(partition_backlogs[0].node_id, "test-indexer-0");
+        assert_eq!(partition_backlogs[0].backlog, 10);
+
+        assert_eq!(partition_backlogs[1].partition_id, "1");
+        assert_eq!(partition_backlogs[1].node_id, "test-indexer-1");
+        assert_eq!(partition_backlogs[1].backlog, 3);
+
+        assert_eq!(partition_backlogs[2].partition_id, "2");
+        assert_eq!(partition_backlogs[2].node_id, "test-indexer-1");
+        assert_eq!(partition_backlogs[2].backlog, 5);
+
+        backlog_table.delete_source(&source_uid);
+
+        let partition_backlogs = backlog_table.partition_backlogs(&source_uid, &pipeline_uids);
+        assert!(partition_backlogs.is_empty());
+    }
+}
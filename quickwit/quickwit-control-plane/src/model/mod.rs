@@ -17,23 +17,28 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+mod backlog_table;
 mod shard_table;
 
 use std::time::Instant;
 
 use anyhow::bail;
+use backlog_table::BacklogTable;
 use fnv::{FnvHashMap, FnvHashSet};
 use quickwit_common::Progress;
-use quickwit_config::SourceConfig;
+use quickwit_config::{PlacementConstraints, ShardScalingSettings, SourceConfig};
 use quickwit_ingest::ShardInfos;
 use quickwit_metastore::{IndexMetadata, ListIndexesMetadataResponseExt};
-use quickwit_proto::control_plane::ControlPlaneResult;
-use quickwit_proto::ingest::Shard;
+use quickwit_proto::control_plane::{ControlPlaneResult, PartitionBacklog, ShardScalingDecision};
+use quickwit_proto::indexing::SourceBacklogUpdate;
+use quickwit_proto::ingest::{Shard, ShardPriority};
 use quickwit_proto::metastore::{
     self, EntityKind, ListIndexesMetadataRequest, ListShardsSubrequest, MetastoreError,
     MetastoreService, MetastoreServiceClient, SourceType,
 };
-use quickwit_proto::types::{IndexId, IndexUid, NodeId, ShardId, SourceId, SourceUid};
+use quickwit_proto::types::{
+    IndexId, IndexUid, NodeId, PipelineUid, Position, ShardId, SourceId, SourceUid,
+};
 use serde::Serialize;
 pub(super) use shard_table::{
     NextShardId, ScalingMode, ShardEntry, ShardStats, ShardTable, ShardTableEntry,
@@ -43,7 +48,9 @@ use tracing::{info, instrument, warn};
 /// The control plane maintains a model in sync with the metastore.
 ///
 /// The model stays consistent with the metastore, because all
-/// of the mutations go through the control plane.
+/// of the mutations go through the control plane. Index changes made
+/// behind its back (e.g. by another cluster sharing the same metastore)
+/// are synced when the metastore notifies them.
 ///
 /// If a mutation yields an error, the control plane is killed
 /// and restarted.
@@ -54,6 +61,7 @@ pub(crate) struct ControlPlaneModel {
     index_uid_table: FnvHashMap<IndexId, IndexUid>,
     index_table: FnvHashMap<IndexUid, IndexMetadata>,
     shard_table: ShardTable,
+    backlog_table: BacklogTable,
 }
 
 #[derive(Clone, Copy, Debug, Default, Serialize)]
@@ -185,6 +193,7 @@ impl ControlPlaneModel {
         // TODO: We need to let the routers and ingesters know.
         self.index_table.remove(index_uid);
         self.shard_table.delete_index(index_uid.index_id());
+        self.backlog_table.delete_index(index_uid);
     }
 
     /// Adds a source to a given index. Returns an error if a source with the same source_id already
@@ -208,6 +217,11 @@ impl ControlPlaneModel {
     pub(crate) fn delete_source(&mut self, index_uid: &IndexUid, source_id: &SourceId) {
         // Removing shards from shard table.
         self.shard_table.delete_source(index_uid, source_id);
+        let source_uid = SourceUid {
+            index_uid: index_uid.clone(),
+            source_id: source_id.clone(),
+        };
+        self.backlog_table.delete_source(&source_uid);
         // Remove source from index config.
         let Some(index_model) = self.index_table.get_mut(index_uid) else {
             warn!(index_uid=%index_uid, source_id=%source_id, "delete source: index not found");
@@ -218,6 +232,14 @@ impl ControlPlaneModel {
         };
     }
 
+    /// Returns the config of a source. Returns `None` if the index or the source does not exist.
+    pub(crate) fn source_config(&self, source_uid: &SourceUid) -> Option<&SourceConfig> {
+        self.index_table
+            .get(&source_uid.index_uid)?
+            .sources
+            .get(&source_uid.source_id)
+    }
+
     /// Returns `true` if the source status has changed, `false` otherwise.
     /// Returns an error if the source could not be found.
     pub(crate) fn toggle_source(
@@ -237,10 +259,72 @@ impl ControlPlaneModel {
         Ok(has_changed)
     }
 
+    /// Syncs the model with the metadata of an index as currently stored in the metastore. `None`
+    /// means that the index no longer exists. Returns `true` if the model has changed.
+    pub(crate) fn sync_index(
+        &mut self,
+        index_uid: &IndexUid,
+        index_metadata_opt: Option<IndexMetadata>,
+    ) -> bool {
+        let Some(index_metadata) = index_metadata_opt else {
+            if !self.index_table.contains_key(index_uid) {
+                return false;
+            }
+            self.delete_index(index_uid);
+            return true;
+        };
+        let Some(current_index_metadata) = self.index_table.get(index_uid) else {
+            for source_id in index_metadata.sources.keys() {
+                self.shard_table.add_source(index_uid, source_id);
+            }
+            self.add_index(index_metadata);
+            return true;
+        };
+        if current_index_metadata.index_config == index_metadata.index_config
+            && current_index_metadata.sources == index_metadata.sources
+        {
+            return false;
+        }
+        let deleted_source_ids: Vec<SourceId> = current_index_metadata
+            .sources
+            .keys()
+            .filter(|source_id| !index_metadata.sources.contains_key(*source_id))
+            .cloned()
+            .collect();
+        let added_source_ids: Vec<SourceId> = index_metadata
+            .sources
+            .keys()
+            .filter(|source_id| !current_index_metadata.sources.contains_key(*source_id))
+            .cloned()
+            .collect();
+        for source_id in &deleted_source_ids {
+            self.delete_source(index_uid, source_id);
+        }
+        for source_id in &added_source_ids {
+            self.shard_table.add_source(index_uid, source_id);
+        }
+        self.add_index(index_metadata);
+        true
+    }
+
+    pub fn all_shards(&self) -> impl Iterator<Item = &ShardEntry> + '_ {
+        self.shard_table.all_shards()
+    }
+
     pub fn all_shards_mut(&mut self) -> impl Iterator<Item = &mut ShardEntry> + '_ {
         self.shard_table.all_shards_mut()
     }
 
+    /// Returns the number of shards for which the node is either the leader or the follower.
+    pub fn num_shards_hosted_by(&self, node_id: &str) -> usize {
+        self.all_shards()
+            .filter(|shard_entry| {
+                shard_entry.leader_id == node_id
+                    || shard_entry.follower_id.as_deref() == Some(node_id)
+            })
+            .count()
+    }
+
     /// Lists the shards of a given source. Returns `None` if the source does not exist.
     pub fn list_shards(&self, source_uid: &SourceUid) -> Option<impl Iterator<Item = &ShardEntry>> {
         self.shard_table.list_shards(source_uid)
@@ -283,6 +367,38 @@ impl ControlPlaneModel {
         self.shard_table.update_shards(source_uid, shard_infos)
     }
 
+    /// Updates the publish positions of the shards according to the given shard positions.
+    pub fn update_publish_positions(
+        &mut self,
+        source_uid: &SourceUid,
+        shard_positions: &[(ShardId, Position)],
+    ) {
+        self.shard_table
+            .update_publish_positions(source_uid, shard_positions);
+    }
+
+    /// Returns the backlogs of the shards of a source. Returns `None` if the source does not
+    /// exist.
+    pub fn shard_backlogs(&self, source_uid: &SourceUid) -> Option<Vec<PartitionBacklog>> {
+        self.shard_table.shard_backlogs(source_uid)
+    }
+
+    /// Records the partition backlogs reported by an indexing pipeline.
+    pub fn update_partition_backlogs(&mut self, source_backlog_update: SourceBacklogUpdate) {
+        self.backlog_table.update(source_backlog_update);
+    }
+
+    /// Returns the backlogs of the partitions of a source reported by the given indexing
+    /// pipelines.
+    pub fn partition_backlogs(
+        &mut self,
+        source_uid: &SourceUid,
+        pipeline_uids: &FnvHashSet<PipelineUid>,
+    ) -> Vec<PartitionBacklog> {
+        self.backlog_table
+            .partition_backlogs(source_uid, pipeline_uids)
+    }
+
     /// Sets the state of the shards identified by their index UID, source ID, and shard IDs to
     /// `Closed`.
     pub fn close_shards(&mut self, source_uid: &SourceUid, shard_ids: &[ShardId]) -> Vec<ShardId> {
@@ -300,8 +416,13 @@ impl ControlPlaneModel {
         scaling_mode: ScalingMode,
         num_permits: u64,
     ) -> Option<bool> {
-        self.shard_table
-            .acquire_scaling_permits(source_uid, scaling_mode, num_permits)
+        let shard_scaling_settings = self.shard_scaling_settings(source_uid);
+        self.shard_table.acquire_scaling_permits(
+            source_uid,
+            scaling_mode,
+            num_permits,
+            &shard_scaling_settings,
+        )
     }
 
     pub fn release_scaling_permits(
@@ -313,6 +434,63 @@ impl ControlPlaneModel {
         self.shard_table
             .release_scaling_permits(source_uid, scaling_mode, num_permits)
     }
+
+    /// Returns the placement constraints of a source, merged with the placement constraints of its
+    /// index. Returns empty constraints if the index or the source does not exist.
+    pub fn placement_constraints(&self, source_uid: &SourceUid) -> PlacementConstraints {
+        let Some(index_metadata) = self.index_table.get(&source_uid.index_uid) else {
+            return PlacementConstraints::default();
+        };
+        let index_constraints = &index_metadata
+            .index_config
+            .indexing_settings
+            .placement_constraints;
+        let Some(source_config) = index_metadata.sources.get(&source_uid.source_id) else {
+            return index_constraints.clone();
+        };
+        PlacementConstraints::merge(index_constraints, &source_config.placement_constraints)
+    }
+
+    /// Returns the priority of the shards of a source. Falls back to the realtime priority if the
+    /// index or the source does not exist.
+    pub fn shard_priority(&self, source_uid: &SourceUid) -> ShardPriority {
+        self.source_config(source_uid)
+            .map(|source_config| source_config.priority.into())
+            .unwrap_or(ShardPriority::Realtime)
+    }
+
+    /// Returns the shard scaling settings of the index of the given source. Falls back to the
+    /// default settings if the index does not exist.
+    pub fn shard_scaling_settings(&self, source_uid: &SourceUid) -> ShardScalingSettings {
+        self.index_table
+            .get(&source_uid.index_uid)
+            .map(|index_metadata| {
+                index_metadata
+                    .index_config
+                    .ingest_settings
+                    .shard_scaling
+                    .clone()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn record_scaling_decision(
+        &mut self,
+        source_uid: &SourceUid,
+        scaling_decision: ShardScalingDecision,
+    ) {
+        self.shard_table
+            .record_scaling_decision(source_uid, scaling_decision);
+    }
+
+    /// Returns the most recent scaling decisions of a source, most recent first. Returns `None` if
+    /// the source does not exist.
+    pub fn recent_scaling_decisions(
+        &self,
+        source_uid: &SourceUid,
+    ) -> Option<Vec<ShardScalingDecision>> {
+        self.shard_table.recent_scaling_decisions(source_uid)
+    }
 }
 
 #[cfg(test)]
@@ -479,4 +657,123 @@ mod tests {
             assert!(!has_changed);
         }
     }
+
+    #[test]
+    fn test_control_plane_model_sync_index() {
+        let mut model = ControlPlaneModel::default();
+        let mut index_metadata = IndexMetadata::for_test("test-index", "ram://");
+        let index_uid = index_metadata.index_uid.clone();
+        let source_config = SourceConfig::for_test("test-source-foo", SourceParams::void());
+        index_metadata.add_source(source_config).unwrap();
+
+        let has_changed = model.sync_index(&index_uid, Some(index_metadata.clone()));
+        assert!(has_changed);
+        assert_eq!(model.index_uid("test-index").unwrap(), index_uid);
+
+        let source_uid_foo = SourceUid {
+            index_uid: index_uid.clone(),
+            source_id: "test-source-foo".to_string(),
+        };
+        assert!(model.list_shards(&source_uid_foo).is_some());
+
+        let has_changed = model.sync_index(&index_uid, Some(index_metadata.clone()));
+        assert!(!has_changed);
+
+        // Changes to anything but the index config and the sources are ignored.
+        index_metadata.create_timestamp += 1;
+        let has_changed = model.sync_index(&index_uid, Some(index_metadata.clone()));
+        assert!(!has_changed);
+
+        index_metadata.sources.clear();
+        let source_config = SourceConfig::for_test("test-source-bar", SourceParams::void());
+        index_metadata.add_source(source_config).unwrap();
+
+        let has_changed = model.sync_index(&index_uid, Some(index_metadata.clone()));
+        assert!(has_changed);
+        assert!(model.list_shards(&source_uid_foo).is_none());
+
+        let source_uid_bar = SourceUid {
+            index_uid: index_uid.clone(),
+            source_id: "test-source-bar".to_string(),
+        };
+        assert!(model.list_shards(&source_uid_bar).is_some());
+        assert!(model.source_config(&source_uid_bar).is_some());
+
+        index_metadata
+            .index_config
+            .indexing_settings
+            .commit_timeout_secs += 1;
+        let has_changed = model.sync_index(&index_uid, Some(index_metadata));
+        assert!(has_changed);
+
+        let has_changed = model.sync_index(&index_uid, None);
+        assert!(has_changed);
+        assert!(model.index_table.is_empty());
+        assert!(model.list_shards(&source_uid_bar).is_none());
+
+        let has_changed = model.sync_index(&index_uid, None);
+        assert!(!has_changed);
+    }
+
+    #[test]
+    fn test_control_plane_model_placement_constraints() {
+        let mut model = ControlPlaneModel::default();
+        let mut index_metadata = IndexMetadata::for_test("test-index", "ram://");
+        index_metadata
+            .index_config
+            .indexing_settings
+            .placement_constraints =
+            PlacementConstraints::from_iter([("zone", "eu-west-1a"), ("tier", "high-mem")]);
+        let index_uid = index_metadata.index_uid.clone();
+        model.add_index(index_metadata);
+
+        let mut source_config = SourceConfig::for_test("test-source", SourceParams::void());
+        source_config.placement_constraints =
+            PlacementConstraints::from_iter([("tier", "high-cpu")]);
+        model.add_source(&index_uid, source_config).unwrap();
+
+        let source_uid = SourceUid {
+            index_uid: index_uid.clone(),
+            source_id: "test-source".to_string(),
+        };
+        assert_eq!(
+            model.placement_constraints(&source_uid),
+            PlacementConstraints::from_iter([("zone", "eu-west-1a"), ("tier", "high-cpu")])
+        );
+        let missing_source_uid = SourceUid {
+            index_uid: "test-index-missing:0".into(),
+            source_id: "test-source".to_string(),
+        };
+        assert!(model.placement_constraints(&missing_source_uid).is_empty());
+    }
+
+    #[test]
+    fn test_control_plane_model_num_shards_hosted_by() {
+        let mut model = ControlPlaneModel::default();
+        let index_uid: IndexUid = "test-index:0".into();
+        let source_id = INGEST_SOURCE_ID.to_string();
+
+        let shard_01 = Shard {
+            index_uid: index_uid.clone().into(),
+            source_id: source_id.clone(),
+            shard_id: 1,
+            leader_id: "test-ingester-0".to_string(),
+            follower_id: Some("test-ingester-1".to_string()),
+            shard_state: ShardState::Open as i32,
+            ..Default::default()
+        };
+        let shard_02 = Shard {
+            index_uid: index_uid.clone().into(),
+            source_id: source_id.clone(),
+            shard_id: 2,
+            leader_id: "test-ingester-1".to_string(),
+            shard_state: ShardState::Closed as i32,
+            ..Default::default()
+        };
+        model.insert_newly_opened_shards(&index_uid, &source_id, vec![shard_01, shard_02], 3);
+
+        assert_eq!(model.num_shards_hosted_by("test-ingester-0"), 1);
+        assert_eq!(model.num_shards_hosted_by("test-ingester-1"), 2);
+        assert_eq!(model.num_shards_hosted_by("test-ingester-2"), 0);
+    }
 }
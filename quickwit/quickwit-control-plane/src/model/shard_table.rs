@@ -18,30 +18,33 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 use std::collections::hash_map::Entry;
+use std::collections::VecDeque;
 use std::ops::{Deref, DerefMut};
 use std::time::Duration;
 
 use fnv::{FnvHashMap, FnvHashSet};
+use itertools::Itertools;
 use quickwit_common::rate_limiter::{RateLimiter, RateLimiterSettings};
 use quickwit_common::tower::ConstantRate;
+use quickwit_config::ShardScalingSettings;
 use quickwit_ingest::{RateMibPerSec, ShardInfo, ShardInfos};
+use quickwit_proto::control_plane::{PartitionBacklog, ShardScalingDecision};
 use quickwit_proto::ingest::{Shard, ShardState};
-use quickwit_proto::types::{IndexUid, NodeId, ShardId, SourceId, SourceUid};
+use quickwit_proto::types::{IndexUid, NodeId, Position, ShardId, SourceId, SourceUid};
 use tracing::{error, warn};
 
-/// Limits the number of shards that can be opened for scaling up a source to 5 per minute.
-const SCALING_UP_RATE_LIMITER_SETTINGS: RateLimiterSettings = RateLimiterSettings {
-    burst_limit: 5,
-    rate_limit: ConstantRate::new(5, Duration::from_secs(60)),
-    refill_period: Duration::from_secs(12),
-};
+/// Maximum number of scaling decisions kept in memory per source.
+const MAX_NUM_SCALING_DECISIONS: usize = 10;
 
-/// Limits the number of shards that can be closed for scaling down a source to 1 per minute.
-const SCALING_DOWN_RATE_LIMITER_SETTINGS: RateLimiterSettings = RateLimiterSettings {
-    burst_limit: 1,
-    rate_limit: ConstantRate::new(1, Duration::from_secs(60)),
-    refill_period: Duration::from_secs(60),
-};
+/// Allows one scaling operation per cool-down period, after an initial burst of up to
+/// `burst_limit` operations.
+fn scaling_rate_limiter_settings(cooldown: Duration, burst_limit: u64) -> RateLimiterSettings {
+    RateLimiterSettings {
+        burst_limit,
+        rate_limit: ConstantRate::new(burst_limit, cooldown * burst_limit as u32),
+        refill_period: cooldown,
+    }
+}
 
 #[derive(Debug, Clone, Copy)]
 pub(crate) enum ScalingMode {
@@ -84,19 +87,35 @@ impl From<Shard> for ShardEntry {
 pub(crate) struct ShardTableEntry {
     shard_entries: FnvHashMap<ShardId, ShardEntry>,
     next_shard_id: NextShardId,
+    scaling_up_cooldown: Duration,
+    scaling_up_burst_limit: u64,
     scaling_up_rate_limiter: RateLimiter,
+    scaling_down_cooldown: Duration,
     scaling_down_rate_limiter: RateLimiter,
+    scaling_decisions: VecDeque<ShardScalingDecision>,
 }
 
 impl Default for ShardTableEntry {
     fn default() -> Self {
+        let shard_scaling_settings = ShardScalingSettings::default();
+        let scaling_up_cooldown = shard_scaling_settings.scale_up_cooldown();
+        let scaling_up_burst_limit = shard_scaling_settings.scale_up_burst_limit();
+        let scaling_down_cooldown = shard_scaling_settings.scale_down_cooldown();
         Self {
             shard_entries: Default::default(),
             next_shard_id: Self::DEFAULT_NEXT_SHARD_ID,
-            scaling_up_rate_limiter: RateLimiter::from_settings(SCALING_UP_RATE_LIMITER_SETTINGS),
-            scaling_down_rate_limiter: RateLimiter::from_settings(
-                SCALING_DOWN_RATE_LIMITER_SETTINGS,
-            ),
+            scaling_up_cooldown,
+            scaling_up_burst_limit,
+            scaling_up_rate_limiter: RateLimiter::from_settings(scaling_rate_limiter_settings(
+                scaling_up_cooldown,
+                scaling_up_burst_limit,
+            )),
+            scaling_down_cooldown,
+            scaling_down_rate_limiter: RateLimiter::from_settings(scaling_rate_limiter_settings(
+                scaling_down_cooldown,
+                1,
+            )),
+            scaling_decisions: VecDeque::new(),
         }
     }
 }
@@ -127,6 +146,45 @@ impl ShardTableEntry {
     fn is_default(&self) -> bool {
         self.is_empty() && self.next_shard_id == Self::DEFAULT_NEXT_SHARD_ID
     }
+
+    /// Returns the rate limiter for the given scaling mode. The rate limiter is rebuilt if the
+    /// cool-down or the burst limit configured for the index has changed.
+    fn scaling_rate_limiter(
+        &mut self,
+        scaling_mode: ScalingMode,
+        shard_scaling_settings: &ShardScalingSettings,
+    ) -> &mut RateLimiter {
+        let (cooldown, scaling_rate_limiter, target_cooldown, burst_limit_changed, burst_limit) =
+            match scaling_mode {
+                ScalingMode::Up => {
+                    let target_burst_limit = shard_scaling_settings.scale_up_burst_limit();
+                    let burst_limit_changed = self.scaling_up_burst_limit != target_burst_limit;
+                    self.scaling_up_burst_limit = target_burst_limit;
+                    (
+                        &mut self.scaling_up_cooldown,
+                        &mut self.scaling_up_rate_limiter,
+                        shard_scaling_settings.scale_up_cooldown(),
+                        burst_limit_changed,
+                        target_burst_limit,
+                    )
+                }
+                ScalingMode::Down => (
+                    &mut self.scaling_down_cooldown,
+                    &mut self.scaling_down_rate_limiter,
+                    shard_scaling_settings.scale_down_cooldown(),
+                    false,
+                    1,
+                ),
+            };
+        if *cooldown != target_cooldown || burst_limit_changed {
+            *cooldown = target_cooldown;
+            *scaling_rate_limiter = RateLimiter::from_settings(scaling_rate_limiter_settings(
+                target_cooldown,
+                burst_limit,
+            ));
+        }
+        scaling_rate_limiter
+    }
 }
 
 // A table that keeps track of the existing shards for each index and source.
@@ -171,6 +229,12 @@ impl ShardTable {
         self.table_entries.remove(&source_uid);
     }
 
+    pub fn all_shards(&self) -> impl Iterator<Item = &ShardEntry> + '_ {
+        self.table_entries
+            .values()
+            .flat_map(|table_entry| table_entry.shard_entries.values())
+    }
+
     pub fn all_shards_mut(&mut self) -> impl Iterator<Item = &mut ShardEntry> + '_ {
         self.table_entries
             .values_mut()
@@ -273,10 +337,18 @@ impl ShardTable {
                     shard_id,
                     shard_state,
                     ingestion_rate,
+                    replication_position_inclusive,
                 } = shard_info;
 
                 if let Some(shard_entry) = table_entry.shard_entries.get_mut(shard_id) {
                     shard_entry.ingestion_rate = *ingestion_rate;
+
+                    if shard_entry.replication_position_inclusive.as_ref()
+                        < Some(replication_position_inclusive)
+                    {
+                        shard_entry.replication_position_inclusive =
+                            Some(replication_position_inclusive.clone());
+                    }
                     // `ShardInfos` are broadcasted via Chitchat and eventually consistent. As a
                     // result, we can only trust the `Closed` state, which is final.
                     if shard_state.is_closed() {
@@ -320,6 +392,47 @@ impl ShardTable {
         closed_shard_ids
     }
 
+    /// Updates the publish positions of the shards of a source, as reported by the indexers.
+    pub fn update_publish_positions(
+        &mut self,
+        source_uid: &SourceUid,
+        shard_positions: &[(ShardId, Position)],
+    ) {
+        if let Some(table_entry) = self.table_entries.get_mut(source_uid) {
+            for (shard_id, publish_position) in shard_positions {
+                if let Some(shard_entry) = table_entry.shard_entries.get_mut(shard_id) {
+                    if shard_entry.publish_position_inclusive.as_ref() < Some(publish_position) {
+                        shard_entry.publish_position_inclusive = Some(publish_position.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the backlog of each shard of a source, i.e. the number of records between the
+    /// publish position and the replication position of the shard. Returns `None` if the source
+    /// does not exist.
+    pub fn shard_backlogs(&self, source_uid: &SourceUid) -> Option<Vec<PartitionBacklog>> {
+        let table_entry = self.table_entries.get(source_uid)?;
+        let shard_backlogs = table_entry
+            .shard_entries
+            .values()
+            .sorted_by_key(|shard_entry| shard_entry.shard_id)
+            .map(|shard_entry| {
+                let num_replicated_records =
+                    num_records_up_to(shard_entry.replication_position_inclusive.as_ref());
+                let num_published_records =
+                    num_records_up_to(shard_entry.publish_position_inclusive.as_ref());
+                PartitionBacklog {
+                    partition_id: shard_entry.shard_id.to_string(),
+                    node_id: shard_entry.leader_id.clone(),
+                    backlog: num_replicated_records.saturating_sub(num_published_records),
+                }
+            })
+            .collect();
+        Some(shard_backlogs)
+    }
+
     /// Removes the shards identified by their index UID, source ID, and shard IDs.
     pub fn delete_shards(&mut self, source_uid: &SourceUid, shard_ids: &[ShardId]) {
         if let Some(table_entry) = self.table_entries.get_mut(source_uid) {
@@ -336,12 +449,11 @@ impl ShardTable {
         source_uid: &SourceUid,
         scaling_mode: ScalingMode,
         num_permits: u64,
+        shard_scaling_settings: &ShardScalingSettings,
     ) -> Option<bool> {
         let table_entry = self.table_entries.get_mut(source_uid)?;
-        let scaling_rate_limiter = match scaling_mode {
-            ScalingMode::Up => &mut table_entry.scaling_up_rate_limiter,
-            ScalingMode::Down => &mut table_entry.scaling_down_rate_limiter,
-        };
+        let scaling_rate_limiter =
+            table_entry.scaling_rate_limiter(scaling_mode, shard_scaling_settings);
         Some(scaling_rate_limiter.acquire(num_permits))
     }
 
@@ -359,6 +471,43 @@ impl ShardTable {
             scaling_rate_limiter.release(num_permits);
         }
     }
+
+    /// Records a scaling decision, evicting the oldest one if the history is full.
+    pub fn record_scaling_decision(
+        &mut self,
+        source_uid: &SourceUid,
+        scaling_decision: ShardScalingDecision,
+    ) {
+        if let Some(table_entry) = self.table_entries.get_mut(source_uid) {
+            if table_entry.scaling_decisions.len() == MAX_NUM_SCALING_DECISIONS {
+                table_entry.scaling_decisions.pop_front();
+            }
+            table_entry.scaling_decisions.push_back(scaling_decision);
+        }
+    }
+
+    /// Returns the most recent scaling decisions of a source, most recent first. Returns `None`
+    /// if the source does not exist.
+    pub fn recent_scaling_decisions(
+        &self,
+        source_uid: &SourceUid,
+    ) -> Option<Vec<ShardScalingDecision>> {
+        let table_entry = self.table_entries.get(source_uid)?;
+        let scaling_decisions = table_entry
+            .scaling_decisions
+            .iter()
+            .rev()
+            .cloned()
+            .collect();
+        Some(scaling_decisions)
+    }
+}
+
+/// Returns the number of records of a shard up to the given position, inclusive.
+fn num_records_up_to(position_opt: Option<&Position>) -> u64 {
+    position_opt
+        .and_then(|position| position.as_u64())
+        .map_or(0, |offset| offset + 1)
 }
 
 #[derive(Clone, Copy, Default)]
@@ -371,7 +520,6 @@ pub(crate) struct ShardStats {
 mod tests {
     use std::collections::BTreeSet;
 
-    use itertools::Itertools;
     use quickwit_proto::ingest::Shard;
 
     use super::*;
@@ -655,26 +803,31 @@ mod tests {
                 shard_id: 1,
                 shard_state: ShardState::Open,
                 ingestion_rate: RateMibPerSec(1),
+                replication_position_inclusive: Position::Beginning,
             },
             ShardInfo {
                 shard_id: 2,
                 shard_state: ShardState::Open,
                 ingestion_rate: RateMibPerSec(2),
+                replication_position_inclusive: Position::Beginning,
             },
             ShardInfo {
                 shard_id: 3,
                 shard_state: ShardState::Open,
                 ingestion_rate: RateMibPerSec(3),
+                replication_position_inclusive: Position::Beginning,
             },
             ShardInfo {
                 shard_id: 4,
                 shard_state: ShardState::Closed,
                 ingestion_rate: RateMibPerSec(4),
+                replication_position_inclusive: Position::Beginning,
             },
             ShardInfo {
                 shard_id: 5,
                 shard_state: ShardState::Open,
                 ingestion_rate: RateMibPerSec(5),
+                replication_position_inclusive: Position::Beginning,
             },
         ]);
         let shard_stats = shard_table.update_shards(&source_uid, &shard_infos);
@@ -709,6 +862,77 @@ mod tests {
         assert_eq!(shard_entries[3].ingestion_rate, RateMibPerSec(4));
     }
 
+    #[test]
+    fn test_shard_table_shard_backlogs() {
+        let index_uid: IndexUid = "test-index:0".into();
+        let source_id = "test-source".to_string();
+        let source_uid = SourceUid {
+            index_uid: index_uid.clone(),
+            source_id: source_id.clone(),
+        };
+        let mut shard_table = ShardTable::default();
+        assert!(shard_table.shard_backlogs(&source_uid).is_none());
+
+        let shard_01 = Shard {
+            index_uid: index_uid.clone().into(),
+            source_id: source_id.clone(),
+            shard_id: 1,
+            leader_id: "test-leader-0".to_string(),
+            shard_state: ShardState::Open as i32,
+            ..Default::default()
+        };
+        let shard_02 = Shard {
+            index_uid: index_uid.clone().into(),
+            source_id: source_id.clone(),
+            shard_id: 2,
+            leader_id: "test-leader-1".to_string(),
+            shard_state: ShardState::Open as i32,
+            publish_position_inclusive: Some(Position::offset(9u64)),
+            ..Default::default()
+        };
+        shard_table.insert_newly_opened_shards(&index_uid, &source_id, vec![shard_01, shard_02], 3);
+        let shard_infos = BTreeSet::from_iter([
+            ShardInfo {
+                shard_id: 1,
+                shard_state: ShardState::Open,
+                ingestion_rate: RateMibPerSec(1),
+                replication_position_inclusive: Position::offset(41u64),
+            },
+            ShardInfo {
+                shard_id: 2,
+                shard_state: ShardState::Open,
+                ingestion_rate: RateMibPerSec(1),
+                replication_position_inclusive: Position::offset(19u64),
+            },
+        ]);
+        shard_table.update_shards(&source_uid, &shard_infos);
+
+        // Shard positions are eventually consistent: stale positions are ignored.
+        shard_table.update_publish_positions(
+            &source_uid,
+            &[
+                (1, Position::offset(11u64)),
+                (2, Position::offset(4u64)),
+                (3, Position::offset(1u64)),
+            ],
+        );
+        let shard_backlogs = shard_table.shard_backlogs(&source_uid).unwrap();
+        assert_eq!(shard_backlogs.len(), 2);
+
+        assert_eq!(shard_backlogs[0].partition_id, "1");
+        assert_eq!(shard_backlogs[0].node_id, "test-leader-0");
+        assert_eq!(shard_backlogs[0].backlog, 30);
+
+        assert_eq!(shard_backlogs[1].partition_id, "2");
+        assert_eq!(shard_backlogs[1].node_id, "test-leader-1");
+        assert_eq!(shard_backlogs[1].backlog, 10);
+
+        shard_table.update_publish_positions(&source_uid, &[(1, Position::eof(41u64))]);
+
+        let shard_backlogs = shard_table.shard_backlogs(&source_uid).unwrap();
+        assert_eq!(shard_backlogs[0].backlog, 0);
+    }
+
     #[test]
     fn test_shard_table_close_shards() {
         let index_uid_0: IndexUid = "test-index:0".into();
@@ -838,7 +1062,7 @@ mod tests {
             source_id: source_id.clone(),
         };
         assert!(shard_table
-            .acquire_scaling_permits(&source_uid, ScalingMode::Up, 1)
+            .acquire_scaling_permits(&source_uid, ScalingMode::Up, 1, &Default::default())
             .is_none());
 
         shard_table.add_source(&index_uid, &source_id);
@@ -851,7 +1075,7 @@ mod tests {
             .available_permits();
 
         assert!(shard_table
-            .acquire_scaling_permits(&source_uid, ScalingMode::Up, 1)
+            .acquire_scaling_permits(&source_uid, ScalingMode::Up, 1, &Default::default())
             .unwrap());
 
         let new_available_permits = shard_table
@@ -876,7 +1100,7 @@ mod tests {
             source_id: source_id.clone(),
         };
         assert!(shard_table
-            .acquire_scaling_permits(&source_uid, ScalingMode::Down, 1)
+            .acquire_scaling_permits(&source_uid, ScalingMode::Down, 1, &Default::default())
             .is_none());
 
         shard_table.add_source(&index_uid, &source_id);
@@ -889,7 +1113,7 @@ mod tests {
             .available_permits();
 
         assert!(shard_table
-            .acquire_scaling_permits(&source_uid, ScalingMode::Down, 1)
+            .acquire_scaling_permits(&source_uid, ScalingMode::Down, 1, &Default::default())
             .unwrap());
 
         let new_available_permits = shard_table
@@ -923,7 +1147,7 @@ mod tests {
             .available_permits();
 
         assert!(shard_table
-            .acquire_scaling_permits(&source_uid, ScalingMode::Up, 1)
+            .acquire_scaling_permits(&source_uid, ScalingMode::Up, 1, &Default::default())
             .unwrap());
 
         shard_table.release_scaling_permits(&source_uid, ScalingMode::Up, 1);
@@ -959,7 +1183,7 @@ mod tests {
             .available_permits();
 
         assert!(shard_table
-            .acquire_scaling_permits(&source_uid, ScalingMode::Down, 1)
+            .acquire_scaling_permits(&source_uid, ScalingMode::Down, 1, &Default::default())
             .unwrap());
 
         shard_table.release_scaling_permits(&source_uid, ScalingMode::Down, 1);
@@ -973,4 +1197,105 @@ mod tests {
 
         assert_eq!(new_available_permits, previous_available_permits);
     }
+
+    #[test]
+    fn test_shard_table_scaling_cooldown_settings() {
+        let mut shard_table = ShardTable::default();
+
+        let index_uid: IndexUid = "test-index:0".into();
+        let source_id = "test-source".to_string();
+
+        shard_table.add_source(&index_uid, &source_id);
+
+        let source_uid = SourceUid {
+            index_uid: index_uid.clone(),
+            source_id: source_id.clone(),
+        };
+        let shard_scaling_settings = ShardScalingSettings::default();
+
+        // By default, up to 5 shards can be opened back to back.
+        for _ in 0..5 {
+            assert!(shard_table
+                .acquire_scaling_permits(&source_uid, ScalingMode::Up, 1, &shard_scaling_settings)
+                .unwrap());
+        }
+        assert!(!shard_table
+            .acquire_scaling_permits(&source_uid, ScalingMode::Up, 1, &shard_scaling_settings)
+            .unwrap());
+
+        assert!(shard_table
+            .acquire_scaling_permits(&source_uid, ScalingMode::Down, 1, &shard_scaling_settings)
+            .unwrap());
+        assert!(!shard_table
+            .acquire_scaling_permits(&source_uid, ScalingMode::Down, 1, &shard_scaling_settings)
+            .unwrap());
+
+        // Changing the cool-down resets the rate limiter, and an explicit cool-down disables
+        // bursting.
+        let shard_scaling_settings = ShardScalingSettings {
+            scale_up_cooldown_secs: Some(30),
+            ..Default::default()
+        };
+        assert!(shard_table
+            .acquire_scaling_permits(&source_uid, ScalingMode::Up, 1, &shard_scaling_settings)
+            .unwrap());
+        assert!(!shard_table
+            .acquire_scaling_permits(&source_uid, ScalingMode::Up, 1, &shard_scaling_settings)
+            .unwrap());
+
+        let table_entry = shard_table.table_entries.get(&source_uid).unwrap();
+        assert_eq!(table_entry.scaling_up_cooldown, Duration::from_secs(30));
+        assert_eq!(table_entry.scaling_up_burst_limit, 1);
+        assert_eq!(table_entry.scaling_down_cooldown, Duration::from_secs(60));
+
+        // Explicitly setting the default cool-down still disables bursting.
+        let shard_scaling_settings = ShardScalingSettings {
+            scale_up_cooldown_secs: Some(12),
+            ..Default::default()
+        };
+        assert!(shard_table
+            .acquire_scaling_permits(&source_uid, ScalingMode::Up, 1, &shard_scaling_settings)
+            .unwrap());
+        assert!(!shard_table
+            .acquire_scaling_permits(&source_uid, ScalingMode::Up, 1, &shard_scaling_settings)
+            .unwrap());
+    }
+
+    #[test]
+    fn test_shard_table_record_scaling_decisions() {
+        let mut shard_table = ShardTable::default();
+
+        let index_uid: IndexUid = "test-index:0".into();
+        let source_id = "test-source".to_string();
+
+        let source_uid = SourceUid {
+            index_uid: index_uid.clone(),
+            source_id: source_id.clone(),
+        };
+        assert!(shard_table.recent_scaling_decisions(&source_uid).is_none());
+
+        shard_table.add_source(&index_uid, &source_id);
+        assert!(shard_table
+            .recent_scaling_decisions(&source_uid)
+            .unwrap()
+            .is_empty());
+
+        for timestamp in 0..MAX_NUM_SCALING_DECISIONS as i64 + 2 {
+            let scaling_decision = ShardScalingDecision {
+                timestamp,
+                ..Default::default()
+            };
+            shard_table.record_scaling_decision(&source_uid, scaling_decision);
+        }
+        let scaling_decisions = shard_table.recent_scaling_decisions(&source_uid).unwrap();
+        assert_eq!(scaling_decisions.len(), MAX_NUM_SCALING_DECISIONS);
+        assert_eq!(
+            scaling_decisions[0].timestamp,
+            MAX_NUM_SCALING_DECISIONS as i64 + 1
+        );
+        assert_eq!(
+            scaling_decisions[MAX_NUM_SCALING_DECISIONS - 1].timestamp,
+            2
+        );
+    }
 }
@@ -60,9 +60,17 @@ fn index_metadata_for_test(
             "bootstrap.servers": "localhost:9092",
             }),
             enable_backfill_mode: true,
+            header_filters: Vec::new(),
+            header_field_mappings: Vec::new(),
         }),
         transform_config: None,
         input_format: SourceInputFormat::Json,
+        csv_config: Default::default(),
+        dead_letter_queue: None,
+        supervision: Default::default(),
+        fetch_stream: Default::default(),
+        placement_constraints: Default::default(),
+        priority: Default::default(),
     };
     index_metadata
         .sources
@@ -91,6 +99,7 @@ pub fn test_indexer_change_stream(
                             client,
                             indexing_tasks,
                             indexing_capacity: CpuCapacity::from_cpu_millis(4_000),
+                            node_labels: Default::default(),
                         },
                     ))
                 }
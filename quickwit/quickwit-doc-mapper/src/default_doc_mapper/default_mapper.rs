@@ -40,11 +40,12 @@ use crate::default_doc_mapper::mapping_tree::{build_mapping_tree, MappingNode};
 use crate::default_doc_mapper::FieldMappingType;
 pub use crate::default_doc_mapper::QuickwitJsonOptions;
 use crate::doc_mapper::{JsonObject, Partition};
+use crate::partition_pruning::extract_required_values;
 use crate::query_builder::build_query;
 use crate::routing_expression::RoutingExpr;
 use crate::{
-    Cardinality, DocMapper, DocParsingError, Mode, QueryParserError, TokenizerEntry, WarmupInfo,
-    DYNAMIC_FIELD_NAME, FIELD_PRESENCE_FIELD_NAME, SOURCE_FIELD_NAME,
+    Cardinality, DocMapper, DocParsingError, Mode, ModeType, QueryParserError, TokenizerEntry,
+    WarmupInfo, DYNAMIC_FIELD_NAME, FIELD_PRESENCE_FIELD_NAME, SOURCE_FIELD_NAME,
 };
 
 const FIELD_PRESENCE_FIELD: Field = Field::from_field_id(0u32);
@@ -78,6 +79,12 @@ pub struct DefaultDocMapper {
     schema: Schema,
     /// List of field names used for tagging.
     tag_field_names: BTreeSet<String>,
+    /// List of fast field names for which splits record statistics.
+    stats_field_names: BTreeSet<String>,
+    /// Name of the field uniquely identifying the documents.
+    doc_id_field_name: Option<String>,
+    /// Alternative field names resolved at search time, mapping each alias to its target field.
+    field_aliases: BTreeMap<String, String>,
     /// The partition key is a DSL used to route documents
     /// into specific splits.
     partition_key: RoutingExpr,
@@ -110,6 +117,28 @@ impl DefaultDocMapper {
     pub fn default_max_num_partitions() -> NonZeroU32 {
         NonZeroU32::new(200).unwrap()
     }
+
+    /// Returns true if the values of the field are guaranteed to be single JSON strings equal to
+    /// their indexed term, so that a term query on the field pins the value used to compute the
+    /// partition ID.
+    fn is_single_raw_text_field(&self, field_name: &str) -> bool {
+        if !matches!(
+            self.field_mappings.find_field_mapping_type(field_name),
+            Some(FieldMappingType::Text(_, Cardinality::SingleValue))
+        ) {
+            return false;
+        }
+        let Ok(field) = self.schema.get_field(field_name) else {
+            return false;
+        };
+        let FieldType::Str(text_options) = self.schema.get_field_entry(field).field_type() else {
+            return false;
+        };
+        text_options
+            .get_indexing_options()
+            .map(|text_indexing_options| text_indexing_options.tokenizer())
+            == Some(RAW_TOKENIZER_NAME)
+    }
 }
 
 fn validate_timestamp_field(
@@ -234,6 +263,26 @@ impl TryFrom<DefaultDocMapperBuilder> for DefaultDocMapper {
             validate_tag(tag_field_name, &schema)?;
         }
 
+        // Resolve stats fields
+        let stats_field_names: BTreeSet<String> = builder.stats_fields.iter().cloned().collect();
+        for stats_field_name in &stats_field_names {
+            validate_stats_field(stats_field_name, &schema)?;
+        }
+
+        if let Some(doc_id_field_name) = builder.doc_id_field.as_ref() {
+            validate_doc_id_field(doc_id_field_name, &schema)?;
+        }
+
+        for (alias, target) in &builder.field_aliases {
+            validate_field_alias(
+                alias,
+                target,
+                &builder.field_aliases,
+                &schema,
+                &builder.mode,
+            )?;
+        }
+
         let partition_key_expr: &str = builder.partition_key.as_deref().unwrap_or("");
         let partition_key = RoutingExpr::new(partition_key_expr).with_context(|| {
             format!("failed to interpret the partition key: `{partition_key_expr}`")
@@ -256,6 +305,9 @@ impl TryFrom<DefaultDocMapperBuilder> for DefaultDocMapper {
             timestamp_field_name: builder.timestamp_field,
             field_mappings,
             tag_field_names,
+            stats_field_names,
+            doc_id_field_name: builder.doc_id_field,
+            field_aliases: builder.field_aliases,
             required_fields,
             partition_key,
             max_num_partitions: builder.max_num_partitions,
@@ -318,6 +370,89 @@ fn validate_tag(tag_field_name: &str, schema: &Schema) -> Result<(), anyhow::Err
     Ok(())
 }
 
+/// Checks that a given field name is a valid candidate for split statistics.
+///
+/// The field must be a `i64`, `u64`, `f64`, or `datetime` fast field.
+fn validate_stats_field(stats_field_name: &str, schema: &Schema) -> Result<(), anyhow::Error> {
+    let field = schema
+        .get_field(stats_field_name)
+        .with_context(|| format!("unknown stats field: `{stats_field_name}`"))?;
+    let field_type = schema.get_field_entry(field).field_type();
+    match field_type {
+        FieldType::I64(_) | FieldType::U64(_) | FieldType::F64(_) | FieldType::Date(_) => {}
+        _ => bail!(
+            "stats collection is not allowed on `{}` fields",
+            field_type.value_type().name().to_lowercase()
+        ),
+    }
+    if !field_type.is_fast() {
+        bail!("stats field `{stats_field_name}` should be a fast field");
+    }
+    Ok(())
+}
+
+/// Checks that a given field name is a valid candidate for identifying documents.
+///
+/// The field must be an indexed and fast `u64`, `i64`, or `text` field. Text fields must use the
+/// `raw` tokenizer so that their terms are the whole document IDs.
+fn validate_doc_id_field(doc_id_field_name: &str, schema: &Schema) -> Result<(), anyhow::Error> {
+    let field = schema
+        .get_field(doc_id_field_name)
+        .with_context(|| format!("unknown doc ID field: `{doc_id_field_name}`"))?;
+    let field_type = schema.get_field_entry(field).field_type();
+    match field_type {
+        FieldType::U64(_) | FieldType::I64(_) => {}
+        FieldType::Str(text_options) => {
+            let tokenizer_opt = text_options
+                .get_indexing_options()
+                .map(|indexing_options| indexing_options.tokenizer());
+            if tokenizer_opt.is_some_and(|tokenizer| tokenizer != "raw") {
+                bail!("doc ID field `{doc_id_field_name}` should use the `raw` tokenizer");
+            }
+            let fast_tokenizer_opt = text_options.get_fast_field_tokenizer_name();
+            if fast_tokenizer_opt.is_some_and(|tokenizer| tokenizer != "raw") {
+                bail!(
+                    "doc ID field `{doc_id_field_name}` should use the `raw` fast field normalizer"
+                );
+            }
+        }
+        _ => bail!("doc ID field `{doc_id_field_name}` should be a `u64`, `i64`, or `text` field"),
+    }
+    if !field_type.is_indexed() {
+        bail!("doc ID field `{doc_id_field_name}` should be indexed");
+    }
+    if !field_type.is_fast() {
+        bail!("doc ID field `{doc_id_field_name}` should be a fast field");
+    }
+    Ok(())
+}
+
+/// Checks that a field alias can be resolved.
+///
+/// The alias must not shadow a field of the schema and must point to a field of the schema, or to
+/// a path of the dynamic field in dynamic mode. Aliases cannot point to other aliases.
+fn validate_field_alias(
+    alias: &str,
+    target: &str,
+    field_aliases: &BTreeMap<String, String>,
+    schema: &Schema,
+    mode: &Mode,
+) -> Result<(), anyhow::Error> {
+    if alias.is_empty() {
+        bail!("field alias for `{target}` should not be empty");
+    }
+    if schema.get_field(alias).is_ok() {
+        bail!("field alias `{alias}` conflicts with an existing field");
+    }
+    if field_aliases.contains_key(target) {
+        bail!("field alias `{alias}` points to another alias `{target}`");
+    }
+    if schema.find_field(target).is_none() && !matches!(mode, Mode::Dynamic(_)) {
+        bail!("field alias `{alias}` points to an unknown field `{target}`");
+    }
+    Ok(())
+}
+
 /// Checks that a given text/json field name has a registered tokenizer.
 fn validate_fields_tokenizers(
     schema: &Schema,
@@ -362,6 +497,9 @@ impl From<DefaultDocMapper> for DefaultDocMapperBuilder {
                 .map(ToString::to_string),
             field_mappings: default_doc_mapper.field_mappings.into(),
             tag_fields: default_doc_mapper.tag_field_names.into_iter().collect(),
+            stats_fields: default_doc_mapper.stats_field_names.into_iter().collect(),
+            doc_id_field: default_doc_mapper.doc_id_field_name,
+            field_aliases: default_doc_mapper.field_aliases,
             default_search_fields: default_doc_mapper.default_search_field_names,
             mode: default_doc_mapper.mode,
             partition_key: partition_key_opt,
@@ -508,10 +646,24 @@ impl DocMapper for DefaultDocMapper {
         &self,
         json_obj: JsonObject,
     ) -> Result<(Partition, Document), DocParsingError> {
+        let mode = self.mode.mode_type();
+        let mut field_path = Vec::new();
+
+        if mode == ModeType::StrictReject {
+            let mut unmapped_fields = Vec::new();
+            self.field_mappings.collect_unmapped_fields(
+                &json_obj,
+                &mut field_path,
+                &mut unmapped_fields,
+            );
+            if !unmapped_fields.is_empty() {
+                unmapped_fields.sort();
+                return Err(DocParsingError::UnmappedFields(unmapped_fields));
+            }
+        }
         let partition: Partition = self.partition_key.eval_hash(&json_obj);
 
         let mut dynamic_json_obj = serde_json::Map::default();
-        let mut field_path = Vec::new();
         let mut document = Document::default();
 
         if let Some(source_field) = self.source_field {
@@ -525,7 +677,6 @@ impl DocMapper for DefaultDocMapper {
             );
         }
 
-        let mode = self.mode.mode_type();
         self.field_mappings.doc_from_json(
             json_obj,
             mode,
@@ -638,10 +789,43 @@ impl DocMapper for DefaultDocMapper {
         self.tag_field_names.clone()
     }
 
+    fn stats_field_names(&self) -> BTreeSet<String> {
+        self.stats_field_names.clone()
+    }
+
+    fn doc_id_field_name(&self) -> Option<&str> {
+        self.doc_id_field_name.as_deref()
+    }
+
+    fn field_aliases(&self) -> BTreeMap<String, String> {
+        self.field_aliases.clone()
+    }
+
     fn max_num_partitions(&self) -> NonZeroU32 {
         self.max_num_partitions
     }
 
+    fn query_partition_id(&self, query_ast: &QueryAst) -> Option<u64> {
+        let partition_field_names = self.partition_key.field_names();
+        if partition_field_names.is_empty() {
+            return None;
+        }
+        let required_values = extract_required_values(query_ast);
+        let mut partition_json_obj = JsonObject::new();
+
+        for field_name in partition_field_names {
+            if !self.is_single_raw_text_field(&field_name) {
+                return None;
+            }
+            let required_value = required_values
+                .get(field_name.as_str())
+                .copied()
+                .flatten()?;
+            partition_json_obj.insert(field_name, JsonValue::String(required_value.to_string()));
+        }
+        Some(self.partition_key.eval_hash(&partition_json_obj))
+    }
+
     fn tokenizer_manager(&self) -> &TokenizerManager {
         &self.tokenizer_manager
     }
@@ -1285,6 +1469,85 @@ mod tests {
         assert_eq!(tag_fields, vec!["city", "division", "service",]);
     }
 
+    #[test]
+    fn test_query_partition_id() {
+        let doc_mapper = r#"{
+            "default_search_fields": [],
+            "partition_key": "hash_mod((tenant,app), 50)",
+            "field_mappings": [
+                {
+                    "name": "tenant",
+                    "type": "text",
+                    "tokenizer": "raw"
+                },
+                {
+                    "name": "app",
+                    "type": "text",
+                    "tokenizer": "raw"
+                },
+                {
+                    "name": "body",
+                    "type": "text"
+                }
+            ]
+        }"#;
+        let doc_mapper = serde_json::from_str::<DefaultDocMapper>(doc_mapper).unwrap();
+        let query_partition_id = |user_query: &str| {
+            let query_ast = query_ast_from_user_text(user_query, None)
+                .parse_user_query(&[])
+                .unwrap();
+            doc_mapper.query_partition_id(&query_ast)
+        };
+        let (partition_id, _) = doc_mapper
+            .doc_from_json_str(r#"{"tenant": "acme", "app": "web", "body": "hello"}"#)
+            .unwrap();
+
+        assert_eq!(
+            query_partition_id("tenant:acme AND app:web AND body:hello"),
+            Some(partition_id)
+        );
+        assert_eq!(
+            query_partition_id("app:web AND (tenant:acme AND NOT body:bye)"),
+            Some(partition_id)
+        );
+        assert_ne!(query_partition_id("tenant:acme AND app:mobile"), None);
+        assert_ne!(
+            query_partition_id("tenant:acme AND app:mobile"),
+            Some(partition_id)
+        );
+        assert_eq!(query_partition_id("tenant:acme"), None);
+        assert_eq!(query_partition_id("tenant:acme OR app:web"), None);
+        assert_eq!(
+            query_partition_id("(tenant:acme AND app:web) OR body:hello"),
+            None
+        );
+        assert_eq!(
+            query_partition_id("tenant:acme AND app:web AND app:mobile"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_query_partition_id_requires_single_raw_text_fields() {
+        for field_mapping in [
+            r#"{"name": "tenant", "type": "text"}"#,
+            r#"{"name": "tenant", "type": "array<text>", "tokenizer": "raw"}"#,
+            r#"{"name": "tenant", "type": "u64"}"#,
+        ] {
+            let doc_mapper = format!(
+                r#"{{
+                    "partition_key": "tenant",
+                    "field_mappings": [{field_mapping}]
+                }}"#
+            );
+            let doc_mapper = serde_json::from_str::<DefaultDocMapper>(&doc_mapper).unwrap();
+            let query_ast = query_ast_from_user_text("tenant:42", None)
+                .parse_user_query(&[])
+                .unwrap();
+            assert_eq!(doc_mapper.query_partition_id(&query_ast), None);
+        }
+    }
+
     #[test]
     fn test_partition_key_in_tags_without_explicit_tags() {
         let doc_mapper = r#"{
@@ -1396,6 +1659,189 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_build_doc_mapper_with_stats_fields() {
+        let doc_mapper = r#"{
+            "stats_fields": ["status_code", "timestamp"],
+            "field_mappings": [
+                {
+                    "name": "status_code",
+                    "type": "u64",
+                    "fast": true
+                },
+                {
+                    "name": "timestamp",
+                    "type": "datetime",
+                    "fast": true
+                },
+                {
+                    "name": "latency",
+                    "type": "f64"
+                },
+                {
+                    "name": "service",
+                    "type": "text",
+                    "tokenizer": "raw",
+                    "fast": true
+                }
+            ]
+        }"#;
+        let builder = serde_json::from_str::<DefaultDocMapperBuilder>(doc_mapper).unwrap();
+        let doc_mapper = builder.clone().try_build().unwrap();
+        let stats_field_names: Vec<String> = doc_mapper.stats_field_names().into_iter().collect();
+        assert_eq!(stats_field_names, ["status_code", "timestamp"]);
+
+        let stats_named_fields = doc_mapper.stats_named_fields().unwrap();
+        assert_eq!(stats_named_fields.len(), 2);
+        assert_eq!(stats_named_fields[0].name, "status_code");
+
+        let mut builder_latency = builder.clone();
+        builder_latency.stats_fields = vec!["latency".to_string()];
+        assert_eq!(
+            builder_latency.try_build().unwrap_err().to_string(),
+            "stats field `latency` should be a fast field"
+        );
+        let mut builder_service = builder.clone();
+        builder_service.stats_fields = vec!["service".to_string()];
+        assert_eq!(
+            builder_service.try_build().unwrap_err().to_string(),
+            "stats collection is not allowed on `str` fields"
+        );
+        let mut builder_unknown = builder;
+        builder_unknown.stats_fields = vec!["unknown".to_string()];
+        assert_eq!(
+            builder_unknown.try_build().unwrap_err().to_string(),
+            "unknown stats field: `unknown`"
+        );
+    }
+
+    #[test]
+    fn test_build_doc_mapper_with_doc_id_field() {
+        let doc_mapper = r#"{
+            "doc_id_field": "id",
+            "field_mappings": [
+                {
+                    "name": "id",
+                    "type": "text",
+                    "tokenizer": "raw",
+                    "fast": true
+                },
+                {
+                    "name": "seq",
+                    "type": "u64",
+                    "fast": true
+                },
+                {
+                    "name": "body",
+                    "type": "text",
+                    "fast": true
+                },
+                {
+                    "name": "latency",
+                    "type": "f64",
+                    "fast": true
+                },
+                {
+                    "name": "slow_id",
+                    "type": "u64"
+                }
+            ]
+        }"#;
+        let builder = serde_json::from_str::<DefaultDocMapperBuilder>(doc_mapper).unwrap();
+        let doc_mapper = builder.clone().try_build().unwrap();
+        assert_eq!(doc_mapper.doc_id_field_name(), Some("id"));
+
+        let doc_id_named_field = doc_mapper.doc_id_named_field().unwrap().unwrap();
+        assert_eq!(doc_id_named_field.name, "id");
+
+        let mut builder_seq = builder.clone();
+        builder_seq.doc_id_field = Some("seq".to_string());
+        builder_seq.try_build().unwrap();
+
+        let mut builder_body = builder.clone();
+        builder_body.doc_id_field = Some("body".to_string());
+        assert_eq!(
+            builder_body.try_build().unwrap_err().to_string(),
+            "doc ID field `body` should use the `raw` tokenizer"
+        );
+        let mut builder_latency = builder.clone();
+        builder_latency.doc_id_field = Some("latency".to_string());
+        assert_eq!(
+            builder_latency.try_build().unwrap_err().to_string(),
+            "doc ID field `latency` should be a `u64`, `i64`, or `text` field"
+        );
+        let mut builder_slow_id = builder.clone();
+        builder_slow_id.doc_id_field = Some("slow_id".to_string());
+        assert_eq!(
+            builder_slow_id.try_build().unwrap_err().to_string(),
+            "doc ID field `slow_id` should be a fast field"
+        );
+        let mut builder_unknown = builder;
+        builder_unknown.doc_id_field = Some("unknown".to_string());
+        assert_eq!(
+            builder_unknown.try_build().unwrap_err().to_string(),
+            "unknown doc ID field: `unknown`"
+        );
+    }
+
+    #[test]
+    fn test_build_doc_mapper_with_field_aliases() {
+        let doc_mapper = r#"{
+            "mode": "strict",
+            "field_aliases": {
+                "message": "body",
+                "@timestamp": "timestamp",
+                "labels": "attributes.labels"
+            },
+            "field_mappings": [
+                {
+                    "name": "body",
+                    "type": "text"
+                },
+                {
+                    "name": "timestamp",
+                    "type": "datetime",
+                    "fast": true
+                },
+                {
+                    "name": "attributes",
+                    "type": "json"
+                }
+            ]
+        }"#;
+        let builder = serde_json::from_str::<DefaultDocMapperBuilder>(doc_mapper).unwrap();
+        let doc_mapper = builder.clone().try_build().unwrap();
+        let field_aliases = doc_mapper.field_aliases();
+        assert_eq!(field_aliases.len(), 3);
+        assert_eq!(field_aliases["message"], "body");
+        assert_eq!(field_aliases["@timestamp"], "timestamp");
+
+        let mut builder_shadowing = builder.clone();
+        builder_shadowing
+            .field_aliases
+            .insert("body".to_string(), "timestamp".to_string());
+        assert_eq!(
+            builder_shadowing.try_build().unwrap_err().to_string(),
+            "field alias `body` conflicts with an existing field"
+        );
+        let mut builder_chained = builder.clone();
+        builder_chained
+            .field_aliases
+            .insert("msg".to_string(), "message".to_string());
+        assert_eq!(
+            builder_chained.try_build().unwrap_err().to_string(),
+            "field alias `msg` points to another alias `message`"
+        );
+        let mut builder_unknown = builder;
+        builder_unknown
+            .field_aliases
+            .insert("host".to_string(), "hostname".to_string());
+        assert_eq!(
+            builder_unknown.try_build().unwrap_err().to_string(),
+            "field alias `host` points to an unknown field `hostname`"
+        );
+    }
+
     // See #1132
     #[test]
     fn test_by_default_store_source_is_false_and_fields_are_stored_individually() {
@@ -1508,6 +1954,52 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_strict_reject_mode() {
+        let default_doc_mapper: DefaultDocMapper = serde_json::from_str(
+            r#"{
+            "field_mappings": [
+                {
+                    "name": "some_obj",
+                    "type": "object",
+                    "field_mappings": [
+                        {
+                            "name": "child_a",
+                            "type": "text"
+                        }
+                    ]
+                },
+                {
+                    "name": "body",
+                    "type": "u64"
+                }
+            ],
+            "mode": "strict_reject"
+        }"#,
+        )
+        .unwrap();
+        assert_eq!(default_doc_mapper.mode.mode_type(), ModeType::StrictReject);
+        assert!(default_doc_mapper
+            .doc_from_json_str(r#"{ "some_obj": { "child_a": "hello" }, "body": 1 }"#)
+            .is_ok());
+
+        // All the unmapped fields are reported, even if the document contains invalid values.
+        let parsing_err = default_doc_mapper
+            .doc_from_json_str(
+                r#"{ "some_obj": { "child_a": "hello", "child_b": 6 }, "body": "abc", "a": 1 }"#,
+            )
+            .unwrap_err();
+        assert_eq!(
+            parsing_err,
+            DocParsingError::UnmappedFields(vec!["a".to_string(), "some_obj.child_b".to_string()])
+        );
+        assert_eq!(
+            parsing_err.to_string(),
+            "the document contains fields that are not declared in the doc mapping: a, \
+             some_obj.child_b"
+        );
+    }
+
     #[test]
     fn test_lenient_mode_simple() {
         let default_doc_mapper: DefaultDocMapper =
@@ -17,6 +17,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::BTreeMap;
 use std::num::NonZeroU32;
 
 use serde::{Deserialize, Serialize};
@@ -55,6 +56,18 @@ pub struct DefaultDocMapperBuilder {
     /// Name of the fields that are tagged.
     #[serde(default)]
     pub tag_fields: Vec<String>,
+    /// Name of the fast fields for which splits record statistics.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub stats_fields: Vec<String>,
+    /// Name of the field uniquely identifying the documents.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub doc_id_field: Option<String>,
+    /// Alternative field names resolved at search time, mapping each alias to its target field.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub field_aliases: BTreeMap<String, String>,
     /// The partition key is a DSL used to route documents
     /// into specific splits.
     #[serde(default)]
@@ -89,6 +102,9 @@ pub enum Mode {
     Lenient,
     /// Strict mode: when parsing a document with an unmapped field, an error is yielded.
     Strict,
+    /// Strict reject mode: documents containing unmapped fields are rejected with an error
+    /// listing all of their unmapped fields.
+    StrictReject,
     /// Dynamic mode: unmapped fields are captured and handled according to the provided
     /// configuration.
     Dynamic(QuickwitJsonOptions),
@@ -100,6 +116,7 @@ impl Mode {
         match self {
             Mode::Lenient => ModeType::Lenient,
             Mode::Strict => ModeType::Strict,
+            Mode::StrictReject => ModeType::StrictReject,
             Mode::Dynamic(_) => ModeType::Dynamic,
         }
     }
@@ -112,6 +129,7 @@ impl Mode {
         Ok(match (mode, dynamic_mapping) {
             (ModeType::Lenient, None) => Mode::Lenient,
             (ModeType::Strict, None) => Mode::Strict,
+            (ModeType::StrictReject, None) => Mode::StrictReject,
             (ModeType::Dynamic, Some(dynamic_mapping)) => Mode::Dynamic(dynamic_mapping),
             (ModeType::Dynamic, None) => Mode::default(), // Dynamic with default options
             (_, Some(_)) => anyhow::bail!(
@@ -126,6 +144,7 @@ impl Mode {
         match self {
             Mode::Lenient => (ModeType::Lenient, None),
             Mode::Strict => (ModeType::Strict, None),
+            Mode::StrictReject => (ModeType::StrictReject, None),
             Mode::Dynamic(json_options) => (ModeType::Dynamic, Some(json_options)),
         }
     }
@@ -145,6 +164,10 @@ pub enum ModeType {
     Lenient,
     /// Strict mode: when parsing a document with an unmapped field, an error is yielded.
     Strict,
+    /// Strict reject mode: documents containing unmapped fields are rejected with an error
+    /// listing all of their unmapped fields.
+    #[serde(rename = "strict_reject")]
+    StrictReject,
     /// Dynamic mode: unmapped fields are captured and handled according to the
     /// `dynamic_mapping` configuration.
     #[default]
@@ -177,6 +200,9 @@ mod tests {
         assert!(default_mapper_builder.default_search_fields.is_empty());
         assert!(default_mapper_builder.field_mappings.is_empty());
         assert!(default_mapper_builder.tag_fields.is_empty());
+        assert!(default_mapper_builder.stats_fields.is_empty());
+        assert!(default_mapper_builder.doc_id_field.is_none());
+        assert!(default_mapper_builder.field_aliases.is_empty());
         assert_eq!(default_mapper_builder.mode.mode_type(), ModeType::Dynamic);
         assert_eq!(default_mapper_builder.store_source, false);
         assert!(default_mapper_builder.timestamp_field.is_none());
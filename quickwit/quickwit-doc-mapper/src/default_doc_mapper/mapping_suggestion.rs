@@ -0,0 +1,579 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{BTreeMap, HashSet};
+
+use quickwit_datetime::{parse_date_time_str, parse_timestamp, DateTimeInputFormat};
+use serde::Serialize;
+use serde_json::{json, Value as JsonValue};
+
+use super::{validate_field_mapping_name, FieldMappingEntry};
+use crate::JsonObject;
+
+/// Text fields with at most this many distinct values in the sample are mapped as keywords.
+const MAX_KEYWORD_NUM_DISTINCT_VALUES: usize = 256;
+
+/// Field mappings and timestamp field candidates suggested from a sample of documents.
+#[derive(Debug, Serialize)]
+pub struct DocMappingSuggestion {
+    /// The suggested field mappings.
+    pub field_mappings: Vec<FieldMappingEntry>,
+    /// The fields that can serve as timestamp field, most likely first.
+    pub timestamp_field_candidates: Vec<String>,
+    /// Statistics about every field observed in the sample, nested fields included.
+    pub field_summaries: Vec<FieldSummary>,
+}
+
+/// Statistics about a field observed in the sample.
+#[derive(Debug, Serialize)]
+pub struct FieldSummary {
+    /// The path of the field. The names of nested fields are separated by dots.
+    pub path: String,
+    /// The suggested type of the field, or `None` if the field is left to the dynamic mapping.
+    pub suggested_type: Option<String>,
+    /// The number of documents, or parent objects for nested fields, in which the field is present
+    /// and not null.
+    pub num_docs: usize,
+    /// The number of parent documents or objects.
+    pub num_parent_docs: usize,
+    /// The number of distinct string values, counted up to a limit beyond which the field is
+    /// considered high-cardinality.
+    pub num_distinct_strs: usize,
+    /// Whether the field has too many distinct string values to be mapped as a keyword.
+    pub is_high_cardinality: bool,
+}
+
+/// Statistics about the values observed for a field in the sample.
+#[derive(Debug, Default)]
+struct FieldStats {
+    num_docs: usize,
+    num_bools: usize,
+    num_u64s: usize,
+    num_i64s: usize,
+    num_f64s: usize,
+    num_strs: usize,
+    num_objects: usize,
+    num_array_objects: usize,
+    num_unsupported: usize,
+    is_array: bool,
+    // Integers that are not valid Unix timestamps.
+    num_non_timestamp_ints: usize,
+    // Strings that are not valid RFC 3339 datetimes.
+    num_non_datetime_strs: usize,
+    has_whitespace: bool,
+    distinct_strs: HashSet<String>,
+    children: BTreeMap<String, FieldStats>,
+}
+
+impl FieldStats {
+    fn record_json_obj(&mut self, json_obj: &JsonObject) {
+        for (key, value) in json_obj {
+            if value.is_null() {
+                continue;
+            }
+            let child_stats = self.children.entry(key.clone()).or_default();
+            child_stats.num_docs += 1;
+            child_stats.record_value(value);
+        }
+    }
+
+    fn record_value(&mut self, value: &JsonValue) {
+        match value {
+            JsonValue::Null => {}
+            JsonValue::Array(values) => {
+                self.is_array = true;
+
+                for value in values {
+                    match value {
+                        JsonValue::Array(_) => self.num_unsupported += 1,
+                        JsonValue::Object(_) => self.num_array_objects += 1,
+                        _ => self.record_value(value),
+                    }
+                }
+            }
+            JsonValue::Object(json_obj) => {
+                self.num_objects += 1;
+                self.record_json_obj(json_obj);
+            }
+            JsonValue::Bool(_) => self.num_bools += 1,
+            JsonValue::Number(number) => {
+                if let Some(number_u64) = number.as_u64() {
+                    self.num_u64s += 1;
+                    self.record_int(i64::try_from(number_u64).ok());
+                } else if let Some(number_i64) = number.as_i64() {
+                    self.num_i64s += 1;
+                    self.record_int(Some(number_i64));
+                } else {
+                    self.num_f64s += 1;
+                }
+            }
+            JsonValue::String(text) => {
+                self.num_strs += 1;
+
+                if parse_date_time_str(text, &[DateTimeInputFormat::Rfc3339]).is_err() {
+                    self.num_non_datetime_strs += 1;
+                }
+                if text.contains(char::is_whitespace) {
+                    self.has_whitespace = true;
+                }
+                if self.distinct_strs.len() <= MAX_KEYWORD_NUM_DISTINCT_VALUES {
+                    self.distinct_strs.insert(text.clone());
+                }
+            }
+        }
+    }
+
+    fn record_int(&mut self, int_opt: Option<i64>) {
+        if int_opt.map_or(true, |int| parse_timestamp(int).is_err()) {
+            self.num_non_timestamp_ints += 1;
+        }
+    }
+
+    fn num_ints(&self) -> usize {
+        self.num_u64s + self.num_i64s
+    }
+
+    fn num_scalars(&self) -> usize {
+        self.num_bools + self.num_ints() + self.num_f64s + self.num_strs
+    }
+}
+
+/// Integer fields are only considered timestamps if their name hints at it, because many
+/// counters and identifiers also fall within the range of valid Unix timestamps.
+fn is_timestamp_like_name(field_name: &str) -> bool {
+    let field_name = field_name.to_lowercase();
+    field_name.contains("time")
+        || field_name.contains("date")
+        || field_name == "ts"
+        || field_name.ends_with("_ts")
+        || field_name.ends_with("_at")
+}
+
+#[derive(Debug, PartialEq)]
+enum SuggestedType {
+    Bool,
+    U64,
+    I64,
+    F64,
+    Datetime(&'static str),
+    Keyword,
+    Text,
+    Json,
+    Object,
+}
+
+/// Returns `None` for the fields that should be left to the dynamic mapping, such as fields with
+/// mixed types.
+fn suggest_type(field_name: &str, stats: &FieldStats) -> Option<SuggestedType> {
+    let num_scalars = stats.num_scalars();
+
+    if stats.num_unsupported > 0 {
+        return None;
+    }
+    if stats.num_array_objects > 0 {
+        if stats.num_objects > 0 || num_scalars > 0 {
+            return None;
+        }
+        return Some(SuggestedType::Json);
+    }
+    if stats.num_objects > 0 {
+        if stats.is_array || num_scalars > 0 {
+            return None;
+        }
+        if stats.children.is_empty() {
+            return Some(SuggestedType::Json);
+        }
+        return Some(SuggestedType::Object);
+    }
+    if num_scalars == 0 {
+        return None;
+    }
+    if stats.num_bools == num_scalars {
+        return Some(SuggestedType::Bool);
+    }
+    if stats.num_ints() == num_scalars {
+        if stats.num_non_timestamp_ints == 0 && is_timestamp_like_name(field_name) {
+            return Some(SuggestedType::Datetime("unix_timestamp"));
+        }
+        if stats.num_i64s > 0 {
+            return Some(SuggestedType::I64);
+        }
+        return Some(SuggestedType::U64);
+    }
+    if stats.num_ints() + stats.num_f64s == num_scalars {
+        return Some(SuggestedType::F64);
+    }
+    if stats.num_strs == num_scalars {
+        if stats.num_non_datetime_strs == 0 {
+            return Some(SuggestedType::Datetime("rfc3339"));
+        }
+        if !stats.has_whitespace && stats.distinct_strs.len() <= MAX_KEYWORD_NUM_DISTINCT_VALUES {
+            return Some(SuggestedType::Keyword);
+        }
+        return Some(SuggestedType::Text);
+    }
+    None
+}
+
+fn type_name(suggested_type: &SuggestedType, is_array: bool) -> String {
+    let type_name = match suggested_type {
+        SuggestedType::Bool => "bool",
+        SuggestedType::U64 => "u64",
+        SuggestedType::I64 => "i64",
+        SuggestedType::F64 => "f64",
+        SuggestedType::Datetime(_) => "datetime",
+        SuggestedType::Keyword | SuggestedType::Text => "text",
+        SuggestedType::Json => "json",
+        SuggestedType::Object => "object",
+    };
+    if is_array {
+        format!("array<{type_name}>")
+    } else {
+        type_name.to_string()
+    }
+}
+
+/// Fields with dots in their names or invalid names are left to the dynamic mapping.
+fn is_mappable_field_name(field_name: &str) -> bool {
+    !field_name.contains('.') && validate_field_mapping_name(field_name).is_ok()
+}
+
+fn suggest_field_mapping_json(
+    field_name: &str,
+    stats: &FieldStats,
+    num_parent_docs: usize,
+    is_root: bool,
+    timestamp_field_candidates: &mut Vec<String>,
+) -> Option<JsonValue> {
+    let suggested_type = suggest_type(field_name, stats)?;
+
+    let mut field_mapping_json = json!({
+        "name": field_name,
+        "type": type_name(&suggested_type, stats.is_array),
+    });
+    let field_mapping_obj = field_mapping_json
+        .as_object_mut()
+        .expect("field mapping should be a JSON object");
+
+    match suggested_type {
+        SuggestedType::Bool | SuggestedType::U64 | SuggestedType::I64 | SuggestedType::F64 => {
+            field_mapping_obj.insert("fast".to_string(), JsonValue::Bool(true));
+        }
+        SuggestedType::Datetime(input_format) => {
+            field_mapping_obj.insert("input_formats".to_string(), json!([input_format]));
+            field_mapping_obj.insert("fast".to_string(), JsonValue::Bool(true));
+
+            // The timestamp field must be single-valued and should be present in every document.
+            if is_root && !stats.is_array && stats.num_docs == num_parent_docs {
+                timestamp_field_candidates.push(field_name.to_string());
+            }
+        }
+        SuggestedType::Keyword => {
+            field_mapping_obj.insert("tokenizer".to_string(), json!("raw"));
+            field_mapping_obj.insert("fast".to_string(), JsonValue::Bool(true));
+        }
+        SuggestedType::Text => {
+            field_mapping_obj.insert("tokenizer".to_string(), json!("default"));
+            field_mapping_obj.insert("record".to_string(), json!("position"));
+        }
+        SuggestedType::Json => {}
+        SuggestedType::Object => {
+            let field_mappings: Vec<JsonValue> =
+                suggest_field_mappings_json(stats, false, timestamp_field_candidates);
+            field_mapping_obj.insert("field_mappings".to_string(), json!(field_mappings));
+        }
+    }
+    Some(field_mapping_json)
+}
+
+fn suggest_field_mappings_json(
+    stats: &FieldStats,
+    is_root: bool,
+    timestamp_field_candidates: &mut Vec<String>,
+) -> Vec<JsonValue> {
+    let num_docs = if is_root {
+        stats.num_docs
+    } else {
+        stats.num_objects
+    };
+    stats
+        .children
+        .iter()
+        .filter(|(field_name, _)| is_mappable_field_name(field_name))
+        .filter_map(|(field_name, child_stats)| {
+            suggest_field_mapping_json(
+                field_name,
+                child_stats,
+                num_docs,
+                is_root,
+                timestamp_field_candidates,
+            )
+        })
+        .collect()
+}
+
+fn summarize_fields(
+    stats: &FieldStats,
+    num_parent_docs: usize,
+    parent_path: Option<&str>,
+    field_summaries: &mut Vec<FieldSummary>,
+) {
+    for (field_name, child_stats) in &stats.children {
+        let path = if let Some(parent_path) = parent_path {
+            format!("{parent_path}.{field_name}")
+        } else {
+            field_name.clone()
+        };
+        let suggested_type_opt = if is_mappable_field_name(field_name) {
+            suggest_type(field_name, child_stats)
+        } else {
+            None
+        };
+        field_summaries.push(FieldSummary {
+            path: path.clone(),
+            suggested_type: suggested_type_opt
+                .as_ref()
+                .map(|suggested_type| type_name(suggested_type, child_stats.is_array)),
+            num_docs: child_stats.num_docs,
+            num_parent_docs,
+            num_distinct_strs: child_stats.distinct_strs.len(),
+            is_high_cardinality: child_stats.distinct_strs.len() > MAX_KEYWORD_NUM_DISTINCT_VALUES,
+        });
+        if suggested_type_opt == Some(SuggestedType::Object) {
+            summarize_fields(
+                child_stats,
+                child_stats.num_objects,
+                Some(&path),
+                field_summaries,
+            );
+        }
+    }
+}
+
+/// Suggests field mappings for a sample of JSON documents, using heuristics similar to those
+/// of the dynamic mapping:
+/// - numbers and booleans are mapped to fast numeric and boolean fields;
+/// - strings that are all RFC 3339 datetimes, and integers that are all valid Unix timestamps in
+///   fields with a timestamp-like name, are mapped to fast datetime fields;
+/// - strings with few distinct values and no whitespace are mapped to fast `raw` text fields, the
+///   others to full-text fields;
+/// - objects are mapped to object fields and arrays of objects to JSON fields;
+/// - fields with mixed types are left to the dynamic mapping.
+pub fn suggest_doc_mapping(json_objs: &[JsonObject]) -> anyhow::Result<DocMappingSuggestion> {
+    let mut root_stats = FieldStats {
+        num_docs: json_objs.len(),
+        ..Default::default()
+    };
+    for json_obj in json_objs {
+        root_stats.record_json_obj(json_obj);
+    }
+    let mut timestamp_field_candidates = Vec::new();
+    let field_mappings =
+        suggest_field_mappings_json(&root_stats, true, &mut timestamp_field_candidates)
+            .into_iter()
+            .map(serde_json::from_value)
+            .collect::<Result<Vec<FieldMappingEntry>, _>>()?;
+
+    // Fields named after timestamps come first.
+    timestamp_field_candidates.sort_by_key(|field_name| {
+        let field_name = field_name.to_lowercase();
+        (
+            !field_name.contains("timestamp"),
+            !field_name.contains("time"),
+            field_name,
+        )
+    });
+    let mut field_summaries = Vec::new();
+    summarize_fields(&root_stats, root_stats.num_docs, None, &mut field_summaries);
+
+    Ok(DocMappingSuggestion {
+        field_mappings,
+        timestamp_field_candidates,
+        field_summaries,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn suggest_from_docs(docs: JsonValue) -> (JsonValue, Vec<String>) {
+        let json_objs: Vec<JsonObject> = serde_json::from_value(docs).unwrap();
+        let suggestion = suggest_doc_mapping(&json_objs).unwrap();
+        let field_mappings_json = serde_json::to_value(&suggestion.field_mappings).unwrap();
+        (field_mappings_json, suggestion.timestamp_field_candidates)
+    }
+
+    fn find_field_mapping<'a>(field_mappings_json: &'a JsonValue, name: &str) -> &'a JsonValue {
+        field_mappings_json
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|field_mapping| field_mapping["name"] == name)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_suggest_doc_mapping() {
+        let (field_mappings_json, timestamp_field_candidates) = suggest_from_docs(json!([
+            {
+                "timestamp": "2023-10-10T10:10:10Z",
+                "created_at": 1697000000,
+                "severity": "INFO",
+                "message": "user logged in",
+                "latency": 1.5,
+                "status": 200,
+                "retry": false,
+                "tags": ["a", "b"],
+                "resource": {"service": "auth", "pid": 12},
+                "attributes": {"foo": 1},
+                "spans": [{"id": 1}],
+            },
+            {
+                "timestamp": "2023-10-10T10:10:11Z",
+                "created_at": 1697000001,
+                "severity": "WARN",
+                "message": "disk almost full",
+                "latency": 2,
+                "status": -1,
+                "retry": true,
+                "tags": [],
+                "resource": {"service": "auth", "pid": 13},
+                "attributes": "bar",
+                "spans": [],
+                "bad name": 1,
+            },
+        ]));
+        let timestamp = find_field_mapping(&field_mappings_json, "timestamp");
+        assert_eq!(timestamp["type"], "datetime");
+        assert_eq!(timestamp["input_formats"], json!(["rfc3339"]));
+        assert_eq!(timestamp["fast"], true);
+
+        let created_at = find_field_mapping(&field_mappings_json, "created_at");
+        assert_eq!(created_at["type"], "datetime");
+        assert_eq!(created_at["input_formats"], json!(["unix_timestamp"]));
+
+        let severity = find_field_mapping(&field_mappings_json, "severity");
+        assert_eq!(severity["type"], "text");
+        assert_eq!(severity["tokenizer"], "raw");
+        assert_eq!(severity["fast"], json!({"normalizer": "raw"}));
+
+        let message = find_field_mapping(&field_mappings_json, "message");
+        assert_eq!(message["type"], "text");
+        assert_eq!(message["tokenizer"], "default");
+        assert_eq!(message["record"], "position");
+
+        let latency = find_field_mapping(&field_mappings_json, "latency");
+        assert_eq!(latency["type"], "f64");
+        assert_eq!(latency["fast"], true);
+
+        let status = find_field_mapping(&field_mappings_json, "status");
+        assert_eq!(status["type"], "i64");
+
+        let retry = find_field_mapping(&field_mappings_json, "retry");
+        assert_eq!(retry["type"], "bool");
+
+        let tags = find_field_mapping(&field_mappings_json, "tags");
+        assert_eq!(tags["type"], "array<text>");
+
+        let resource = find_field_mapping(&field_mappings_json, "resource");
+        assert_eq!(resource["type"], "object");
+        let pid = find_field_mapping(&resource["field_mappings"], "pid");
+        assert_eq!(pid["type"], "u64");
+
+        let spans = find_field_mapping(&field_mappings_json, "spans");
+        assert_eq!(spans["type"], "array<json>");
+
+        // `attributes` has mixed types and `bad name` is not a valid field name.
+        assert_eq!(field_mappings_json.as_array().unwrap().len(), 10);
+        assert_eq!(timestamp_field_candidates, ["timestamp", "created_at"]);
+    }
+
+    #[test]
+    fn test_suggest_doc_mapping_timestamp_candidates() {
+        let (_, timestamp_field_candidates) = suggest_from_docs(json!([
+            {"event_time": "2023-10-10T10:10:10Z", "count": 1697000000, "date": "2023-10-10T10:10:10Z"},
+            {"event_time": "2023-10-10T10:10:11Z", "count": 1697000001},
+        ]));
+        // `date` is missing from some documents and `count` does not look like a timestamp.
+        assert_eq!(timestamp_field_candidates, ["event_time"]);
+    }
+
+    #[test]
+    fn test_suggest_doc_mapping_field_summaries() {
+        let json_objs: Vec<JsonObject> = (0..300)
+            .map(|doc_idx| {
+                let mut doc = json!({
+                    "request_id": format!("req-{doc_idx}"),
+                    "level": if doc_idx % 2 == 0 { "INFO" } else { "WARN" },
+                    "http": {"status": 200},
+                    "mixed": if doc_idx == 0 { json!(1) } else { json!("one") },
+                });
+                if doc_idx % 3 == 0 {
+                    doc["user"] = json!("alice");
+                }
+                serde_json::from_value(doc).unwrap()
+            })
+            .collect();
+        let suggestion = suggest_doc_mapping(&json_objs).unwrap();
+        let field_summaries: Vec<(&str, Option<&str>, usize, usize, bool)> = suggestion
+            .field_summaries
+            .iter()
+            .map(|field_summary| {
+                (
+                    field_summary.path.as_str(),
+                    field_summary.suggested_type.as_deref(),
+                    field_summary.num_docs,
+                    field_summary.num_distinct_strs,
+                    field_summary.is_high_cardinality,
+                )
+            })
+            .collect();
+        assert_eq!(
+            field_summaries,
+            [
+                ("http", Some("object"), 300, 0, false),
+                ("http.status", Some("u64"), 300, 0, false),
+                ("level", Some("text"), 300, 2, false),
+                ("mixed", None, 300, 1, false),
+                (
+                    "request_id",
+                    Some("text"),
+                    300,
+                    MAX_KEYWORD_NUM_DISTINCT_VALUES + 1,
+                    true
+                ),
+                ("user", Some("text"), 100, 1, false),
+            ]
+        );
+        assert!(suggestion
+            .field_summaries
+            .iter()
+            .all(|field_summary| field_summary.num_parent_docs == 300));
+    }
+
+    #[test]
+    fn test_suggest_doc_mapping_empty_sample() {
+        let (field_mappings_json, timestamp_field_candidates) = suggest_from_docs(json!([]));
+        assert_eq!(field_mappings_json, json!([]));
+        assert!(timestamp_field_candidates.is_empty());
+    }
+}
@@ -424,7 +424,7 @@ impl MappingNode {
                             get_or_insert_path(path, dynamic_json_obj);
                         dynamic_json_obj_after_path.insert(field_name, val);
                     }
-                    ModeType::Strict => {
+                    ModeType::Strict | ModeType::StrictReject => {
                         path.push(field_name);
                         let field_path = path.join(".");
                         return Err(DocParsingError::NoSuchFieldInSchema(field_path));
@@ -435,6 +435,29 @@ impl MappingNode {
         Ok(())
     }
 
+    /// Collects the paths of the fields of `json_obj` that are not declared in the mapping.
+    pub fn collect_unmapped_fields(
+        &self,
+        json_obj: &serde_json::Map<String, JsonValue>,
+        path: &mut Vec<String>,
+        unmapped_fields: &mut Vec<String>,
+    ) {
+        for (field_name, val) in json_obj {
+            path.push(field_name.clone());
+
+            match self.branches.get(field_name) {
+                Some(MappingTree::Node(child_node)) => {
+                    if let JsonValue::Object(child_json_obj) = val {
+                        child_node.collect_unmapped_fields(child_json_obj, path, unmapped_fields);
+                    }
+                }
+                Some(MappingTree::Leaf(_)) => {}
+                None => unmapped_fields.push(path.join(".")),
+            }
+            path.pop();
+        }
+    }
+
     pub fn populate_json<'a>(
         &'a self,
         named_doc: &mut BTreeMap<String, Vec<TantivyValue>>,
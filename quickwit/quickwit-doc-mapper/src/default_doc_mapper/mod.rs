@@ -22,6 +22,7 @@ mod default_mapper;
 mod default_mapper_builder;
 mod field_mapping_entry;
 mod field_mapping_type;
+mod mapping_suggestion;
 mod mapping_tree;
 mod tokenizer_entry;
 
@@ -39,9 +40,13 @@ pub(crate) use self::field_mapping_entry::{
     FieldMappingEntryForSerialization, IndexRecordOptionSchema, QuickwitTextTokenizer,
 };
 pub(crate) use self::field_mapping_type::FieldMappingType;
-pub use self::tokenizer_entry::{analyze_text, TokenizerConfig, TokenizerEntry};
+pub use self::mapping_suggestion::{suggest_doc_mapping, DocMappingSuggestion, FieldSummary};
+pub use self::tokenizer_entry::{
+    analyze_text, StopWordsFilterOption, TokenizerConfig, TokenizerEntry,
+};
 pub(crate) use self::tokenizer_entry::{
-    NgramTokenizerOption, RegexTokenizerOption, TokenFilterType, TokenizerType,
+    EdgeNgramTokenizerOption, NgramTokenizerOption, RegexTokenizerOption, StemmerFilterOption,
+    StemmerLanguage, TokenFilterType, TokenizerType,
 };
 use crate::QW_RESERVED_FIELD_NAMES;
 
@@ -56,7 +61,8 @@ pub const FIELD_MAPPING_NAME_PATTERN: &str = r"^[_\-a-zA-Z][_\.\-a-zA-Z0-9]{0,25
 /// - may only contain uppercase and lowercase ASCII letters `[a-zA-Z]`, digits `[0-9]`, hyphens
 ///   `-`, and underscores `_`;
 /// - must not start with a dot or a digit;
-/// - must be different from Quickwit's reserved field mapping names `_source`, `_dynamic`;
+/// - must be different from Quickwit's reserved field mapping names `_source`, `_dynamic`,
+///   `_split_tags`;
 /// - must not be longer than 255 characters.
 pub fn validate_field_mapping_name(field_mapping_name: &str) -> anyhow::Result<()> {
     static FIELD_MAPPING_NAME_PTN: Lazy<Regex> =
@@ -18,11 +18,12 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 use anyhow::Context;
+use quickwit_common::uri::Uri;
 use quickwit_query::{CodeTokenizer, DEFAULT_REMOVE_TOKEN_LENGTH};
 use serde::{Deserialize, Serialize};
 use tantivy::tokenizer::{
-    AsciiFoldingFilter, LowerCaser, NgramTokenizer, RegexTokenizer, RemoveLongFilter,
-    SimpleTokenizer, TextAnalyzer, Token,
+    AsciiFoldingFilter, Language, LowerCaser, NgramTokenizer, RegexTokenizer, RemoveLongFilter,
+    SimpleTokenizer, Stemmer, StopWordFilter, TextAnalyzer, Token,
 };
 
 /// A `TokenizerEntry` defines a custom tokenizer with its name and configuration.
@@ -35,6 +36,20 @@ pub struct TokenizerEntry {
     pub(crate) config: TokenizerConfig,
 }
 
+impl TokenizerEntry {
+    /// Returns the options of the stop words filters of the tokenizer, for instance, to load the
+    /// word lists referenced by URI.
+    pub fn stop_words_filters_mut(&mut self) -> impl Iterator<Item = &mut StopWordsFilterOption> {
+        self.config
+            .filters
+            .iter_mut()
+            .filter_map(|filter| match filter {
+                TokenFilterType::StopWords(options) => Some(options),
+                _ => None,
+            })
+    }
+}
+
 /// Tokenizer configuration.
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq, utoipa::ToSchema)]
 pub struct TokenizerConfig {
@@ -60,6 +75,11 @@ impl TokenizerConfig {
                         .with_context(|| "invalid ngram tokenizer".to_string())?;
                 TextAnalyzer::builder(tokenizer).dynamic()
             }
+            TokenizerType::EdgeNgram(options) => {
+                let tokenizer = NgramTokenizer::new(options.min_gram, options.max_gram, true)
+                    .with_context(|| "invalid edge ngram tokenizer".to_string())?;
+                TextAnalyzer::builder(tokenizer).dynamic()
+            }
             TokenizerType::Regex(options) => {
                 let tokenizer = RegexTokenizer::new(&options.pattern)
                     .with_context(|| "invalid regex tokenizer".to_string())?;
@@ -77,6 +97,12 @@ impl TokenizerConfig {
                 TantivyTokenFilterEnum::AsciiFolding(token_filter) => {
                     text_analyzer_builder = text_analyzer_builder.filter_dynamic(token_filter);
                 }
+                TantivyTokenFilterEnum::Stemmer(token_filter) => {
+                    text_analyzer_builder = text_analyzer_builder.filter_dynamic(token_filter);
+                }
+                TantivyTokenFilterEnum::StopWords(token_filter) => {
+                    text_analyzer_builder = text_analyzer_builder.filter_dynamic(token_filter);
+                }
             }
         }
         Ok(text_analyzer_builder.build())
@@ -100,6 +126,8 @@ pub enum TokenFilterType {
     RemoveLong,
     LowerCaser,
     AsciiFolding,
+    Stemmer(StemmerFilterOption),
+    StopWords(StopWordsFilterOption),
 }
 
 /// Tantivy token filter enum to build
@@ -108,6 +136,8 @@ enum TantivyTokenFilterEnum {
     RemoveLong(RemoveLongFilter),
     LowerCaser(LowerCaser),
     AsciiFolding(AsciiFoldingFilter),
+    Stemmer(Stemmer),
+    StopWords(StopWordFilter),
 }
 
 impl TokenFilterType {
@@ -118,16 +148,105 @@ impl TokenFilterType {
             )),
             Self::LowerCaser => TantivyTokenFilterEnum::LowerCaser(LowerCaser),
             Self::AsciiFolding => TantivyTokenFilterEnum::AsciiFolding(AsciiFoldingFilter),
+            Self::Stemmer(options) => {
+                TantivyTokenFilterEnum::Stemmer(Stemmer::new(options.language.into()))
+            }
+            Self::StopWords(options) => {
+                TantivyTokenFilterEnum::StopWords(StopWordFilter::remove(options.words.clone()))
+            }
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct StemmerFilterOption {
+    pub language: StemmerLanguage,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StemmerLanguage {
+    Arabic,
+    Danish,
+    Dutch,
+    English,
+    Finnish,
+    French,
+    German,
+    Greek,
+    Hungarian,
+    Italian,
+    Norwegian,
+    Portuguese,
+    Romanian,
+    Russian,
+    Spanish,
+    Swedish,
+    Tamil,
+    Turkish,
+}
+
+impl From<StemmerLanguage> for Language {
+    fn from(language: StemmerLanguage) -> Self {
+        match language {
+            StemmerLanguage::Arabic => Language::Arabic,
+            StemmerLanguage::Danish => Language::Danish,
+            StemmerLanguage::Dutch => Language::Dutch,
+            StemmerLanguage::English => Language::English,
+            StemmerLanguage::Finnish => Language::Finnish,
+            StemmerLanguage::French => Language::French,
+            StemmerLanguage::German => Language::German,
+            StemmerLanguage::Greek => Language::Greek,
+            StemmerLanguage::Hungarian => Language::Hungarian,
+            StemmerLanguage::Italian => Language::Italian,
+            StemmerLanguage::Norwegian => Language::Norwegian,
+            StemmerLanguage::Portuguese => Language::Portuguese,
+            StemmerLanguage::Romanian => Language::Romanian,
+            StemmerLanguage::Russian => Language::Russian,
+            StemmerLanguage::Spanish => Language::Spanish,
+            StemmerLanguage::Swedish => Language::Swedish,
+            StemmerLanguage::Tamil => Language::Tamil,
+            StemmerLanguage::Turkish => Language::Turkish,
         }
     }
 }
 
+/// Options of the stop words filter. The stop words are either listed inline in `words` or loaded
+/// from the file at `uri`, one word per line, when the index is created or its doc mapping is
+/// updated. The loaded words are then stored in `words`.
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct StopWordsFilterOption {
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub words: Vec<String>,
+    #[schema(value_type = Option<String>)]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uri: Option<Uri>,
+}
+
+impl StopWordsFilterOption {
+    /// Replaces the stop words with the words of `word_list`, one word per line. Blank lines and
+    /// lines starting with `#` are ignored.
+    pub fn set_words_from_word_list(&mut self, word_list: &str) {
+        self.words = word_list
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(ToString::to_string)
+            .collect();
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum TokenizerType {
     #[cfg(any(test, feature = "multilang"))]
     Multilang,
     Ngram(NgramTokenizerOption),
+    EdgeNgram(EdgeNgramTokenizerOption),
     Regex(RegexTokenizerOption),
     Simple,
     SourceCode,
@@ -142,6 +261,13 @@ pub struct NgramTokenizerOption {
     pub prefix_only: bool,
 }
 
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct EdgeNgramTokenizerOption {
+    pub min_gram: usize,
+    pub max_gram: usize,
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq, utoipa::ToSchema)]
 #[serde(deny_unknown_fields)]
 pub struct RegexTokenizerOption {
@@ -150,7 +276,7 @@ pub struct RegexTokenizerOption {
 
 #[cfg(test)]
 mod tests {
-    use super::{NgramTokenizerOption, TokenizerType};
+    use super::{analyze_text, NgramTokenizerOption, StopWordsFilterOption, TokenizerType};
     use crate::default_doc_mapper::RegexTokenizerOption;
     use crate::TokenizerEntry;
 
@@ -243,4 +369,73 @@ mod tests {
             _ => panic!("Unexpected tokenizer type"),
         }
     }
+
+    #[test]
+    fn test_tokenizer_entry_with_stemmer_and_stop_words() {
+        let mut tokenizer_config_entry = serde_json::from_str::<TokenizerEntry>(
+            r#"
+            {
+                "name": "my_tokenizer",
+                "type": "simple",
+                "filters": [
+                    "lower_caser",
+                    {"stop_words": {"words": ["les", "des"]}},
+                    {"stemmer": {"language": "french"}}
+                ]
+            }
+            "#,
+        )
+        .unwrap();
+        let tokens = analyze_text("Les chevaux des voisins", &tokenizer_config_entry.config)
+            .unwrap()
+            .into_iter()
+            .map(|token| token.text)
+            .collect::<Vec<_>>();
+        assert_eq!(tokens, ["cheval", "voisin"]);
+
+        let stop_words_filters: Vec<&mut StopWordsFilterOption> =
+            tokenizer_config_entry.stop_words_filters_mut().collect();
+        assert_eq!(stop_words_filters.len(), 1);
+        assert_eq!(stop_words_filters[0].words, ["les", "des"]);
+    }
+
+    #[test]
+    fn test_tokenizer_entry_edge_ngram() {
+        let tokenizer_config_entry = serde_json::from_str::<TokenizerEntry>(
+            r#"
+            {
+                "name": "my_tokenizer",
+                "type": "edge_ngram",
+                "min_gram": 2,
+                "max_gram": 4
+            }
+            "#,
+        )
+        .unwrap();
+        let tokens = analyze_text("quickwit", &tokenizer_config_entry.config)
+            .unwrap()
+            .into_iter()
+            .map(|token| token.text)
+            .collect::<Vec<_>>();
+        assert_eq!(tokens, ["qu", "qui", "quic"]);
+    }
+
+    #[test]
+    fn test_stop_words_filter_set_words_from_word_list() {
+        let mut stop_words_filter: StopWordsFilterOption =
+            serde_json::from_str(r#"{"uri": "s3://my-bucket/stop-words.txt"}"#).unwrap();
+        assert!(stop_words_filter.words.is_empty());
+
+        stop_words_filter.set_words_from_word_list("# French stop words\nle\n\n  la \nles\n");
+        assert_eq!(stop_words_filter.words, ["le", "la", "les"]);
+
+        let stop_words_filter_json = serde_json::to_value(&stop_words_filter).unwrap();
+        assert_eq!(
+            stop_words_filter_json,
+            serde_json::json!({
+                "words": ["le", "la", "les"],
+                "uri": "s3://my-bucket/stop-words.txt",
+            })
+        );
+    }
 }
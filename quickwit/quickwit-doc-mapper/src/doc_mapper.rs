@@ -126,25 +126,52 @@ pub trait DocMapper: Send + Sync + Debug + DynClone + 'static {
     /// Returns the tag `NameField`s on the current schema.
     /// Returns an error if a tag field is not found in this schema.
     fn tag_named_fields(&self) -> anyhow::Result<Vec<NamedField>> {
-        let index_schema = self.schema();
-        self.tag_field_names()
-            .iter()
-            .map(|field_name| {
-                index_schema
-                    .get_field(field_name)
-                    .context(format!("field `{field_name}` must exist in the schema"))
-                    .map(|field| NamedField {
-                        name: field_name.clone(),
-                        field,
-                        field_type: index_schema.get_field_entry(field).field_type().clone(),
-                    })
-            })
-            .collect::<Result<Vec<_>, _>>()
+        named_fields(&self.schema(), &self.tag_field_names())
+    }
+
+    /// Returns the names of the fast fields for which splits record statistics.
+    fn stats_field_names(&self) -> BTreeSet<String> {
+        Default::default()
+    }
+
+    /// Returns the stats `NamedField`s on the current schema.
+    /// Returns an error if a stats field is not found in this schema.
+    fn stats_named_fields(&self) -> anyhow::Result<Vec<NamedField>> {
+        named_fields(&self.schema(), &self.stats_field_names())
+    }
+
+    /// Returns the name of the field uniquely identifying the documents, if any.
+    fn doc_id_field_name(&self) -> Option<&str> {
+        None
+    }
+
+    /// Returns the doc ID `NamedField` on the current schema, if any.
+    /// Returns an error if the doc ID field is not found in this schema.
+    fn doc_id_named_field(&self) -> anyhow::Result<Option<NamedField>> {
+        let Some(doc_id_field_name) = self.doc_id_field_name() else {
+            return Ok(None);
+        };
+        let field_names = BTreeSet::from([doc_id_field_name.to_string()]);
+        Ok(named_fields(&self.schema(), &field_names)?.pop())
+    }
+
+    /// Returns the field aliases, mapping each alias to its target field.
+    fn field_aliases(&self) -> BTreeMap<String, String> {
+        BTreeMap::new()
     }
 
     /// Returns the maximum number of partitions.
     fn max_num_partitions(&self) -> NonZeroU32;
 
+    /// Returns the partition ID of all the documents matching `query_ast`, if the query requires
+    /// every field of the partition key to have one exact value.
+    ///
+    /// Splits with a different partition ID, except the ones gathering the overflowing partitions
+    /// (see [`crate::OTHER_PARTITION_ID`]), cannot contain any matching document.
+    fn query_partition_id(&self, _query_ast: &QueryAst) -> Option<u64> {
+        None
+    }
+
     /// Returns the tokenizer manager.
     fn tokenizer_manager(&self) -> &TokenizerManager;
 }
@@ -162,6 +189,25 @@ pub struct NamedField {
 
 clone_trait_object!(DocMapper);
 
+fn named_fields(
+    index_schema: &Schema,
+    field_names: &BTreeSet<String>,
+) -> anyhow::Result<Vec<NamedField>> {
+    field_names
+        .iter()
+        .map(|field_name| {
+            index_schema
+                .get_field(field_name)
+                .context(format!("field `{field_name}` must exist in the schema"))
+                .map(|field| NamedField {
+                    name: field_name.clone(),
+                    field,
+                    field_type: index_schema.get_field_entry(field).field_type().clone(),
+                })
+        })
+        .collect::<Result<Vec<_>, _>>()
+}
+
 /// Bounds for a range of terms, with an optional max count of terms being matched.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct TermRange {
@@ -57,6 +57,12 @@ pub enum DocParsingError {
     /// The document does not contain a field that is required.
     #[error("the document must contain field {0:?}")]
     RequiredField(String),
+    /// The json-document contains fields that are not declared in the doc mapping.
+    #[error(
+        "the document contains fields that are not declared in the doc mapping: {}",
+        .0.join(", ")
+    )]
+    UnmappedFields(Vec<String>),
 }
 
 impl From<TantivyDocParsingError> for DocParsingError {
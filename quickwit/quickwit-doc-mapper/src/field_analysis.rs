@@ -0,0 +1,202 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use anyhow::{bail, Context};
+use quickwit_query::query_ast::resolve_field_alias;
+use quickwit_query::{find_field_or_hit_dynamic, get_quickwit_fastfield_normalizer_manager};
+use serde::Serialize;
+use tantivy::schema::FieldType;
+use tantivy::tokenizer::{TextAnalyzer, Token};
+
+use crate::DocMapper;
+
+/// Tokens produced by the analyzers of a field of an index for a given text.
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct FieldAnalysis {
+    /// Name of the analyzed field, once its alias, if any, is resolved.
+    pub field: String,
+    /// Tokenizer of the field, if the field is indexed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tokenizer: Option<String>,
+    /// Terms recorded in the inverted index, which the full-text queries match.
+    #[schema(value_type = Option<Vec<Object>>)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tokens: Option<Vec<Token>>,
+    /// Normalizer of the fast field, if the field is fast.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fast_field_normalizer: Option<String>,
+    /// Values recorded in the fast field, which the aggregations and sorts operate on.
+    #[schema(value_type = Option<Vec<Object>>)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fast_field_tokens: Option<Vec<Token>>,
+}
+
+/// Runs a text through the tokenizer and the fast field normalizer configured for a field of a doc
+/// mapping, as the indexer would do with the field values.
+///
+/// The field can be an alias, a path within a JSON field, or, in dynamic mode, a path captured by
+/// the dynamic mapping.
+pub fn analyze_field_text(
+    doc_mapper: &dyn DocMapper,
+    field_name: &str,
+    text: &str,
+) -> anyhow::Result<FieldAnalysis> {
+    let resolved_field_name =
+        resolve_field_alias(field_name.to_string(), &doc_mapper.field_aliases());
+    let schema = doc_mapper.schema();
+    let (_field, field_entry, _path) = find_field_or_hit_dynamic(&resolved_field_name, &schema)?;
+
+    let (tokenizer_name_opt, fast_field_normalizer_name_opt) = match field_entry.field_type() {
+        FieldType::Str(text_options) => (
+            text_options
+                .get_indexing_options()
+                .map(|text_indexing_options| text_indexing_options.tokenizer()),
+            text_options.get_fast_field_tokenizer_name(),
+        ),
+        FieldType::JsonObject(json_options) => (
+            json_options
+                .get_text_indexing_options()
+                .map(|text_indexing_options| text_indexing_options.tokenizer()),
+            json_options.get_fast_field_tokenizer_name(),
+        ),
+        field_type => bail!(
+            "field `{resolved_field_name}` is a `{}` field, only `text` and `json` fields are \
+             analyzed",
+            field_type.value_type().name().to_lowercase()
+        ),
+    };
+    let tokens_opt = tokenizer_name_opt
+        .map(|tokenizer_name| {
+            let text_analyzer = doc_mapper
+                .tokenizer_manager()
+                .get_tokenizer(tokenizer_name)
+                .with_context(|| format!("unknown tokenizer `{tokenizer_name}`"))?;
+            anyhow::Ok(tokenize(text_analyzer, text))
+        })
+        .transpose()?;
+    let fast_field_tokens_opt = fast_field_normalizer_name_opt
+        .map(|normalizer_name| {
+            let text_analyzer = get_quickwit_fastfield_normalizer_manager()
+                .get_tokenizer(normalizer_name)
+                .with_context(|| format!("unknown fast field normalizer `{normalizer_name}`"))?;
+            anyhow::Ok(tokenize(text_analyzer, text))
+        })
+        .transpose()?;
+
+    Ok(FieldAnalysis {
+        field: resolved_field_name,
+        tokenizer: tokenizer_name_opt.map(ToString::to_string),
+        tokens: tokens_opt,
+        fast_field_normalizer: fast_field_normalizer_name_opt.map(ToString::to_string),
+        fast_field_tokens: fast_field_tokens_opt,
+    })
+}
+
+fn tokenize(mut text_analyzer: TextAnalyzer, text: &str) -> Vec<Token> {
+    let mut token_stream = text_analyzer.token_stream(text);
+    let mut tokens = Vec::new();
+    token_stream.process(&mut |token| {
+        tokens.push(token.clone());
+    });
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DefaultDocMapper;
+
+    fn tokens_text(tokens: &[Token]) -> Vec<&str> {
+        tokens.iter().map(|token| token.text.as_str()).collect()
+    }
+
+    #[test]
+    fn test_analyze_field_text() {
+        let doc_mapper: DefaultDocMapper = serde_json::from_str(
+            r#"{
+                "field_mappings": [
+                    {
+                        "name": "body",
+                        "type": "text",
+                        "tokenizer": "default"
+                    },
+                    {
+                        "name": "service",
+                        "type": "text",
+                        "tokenizer": "raw",
+                        "fast": {"normalizer": "lowercase"}
+                    },
+                    {
+                        "name": "attributes",
+                        "type": "json",
+                        "tokenizer": "raw"
+                    },
+                    {
+                        "name": "latency",
+                        "type": "u64"
+                    }
+                ],
+                "field_aliases": {"message": "body"},
+                "mode": "dynamic"
+            }"#,
+        )
+        .unwrap();
+
+        let field_analysis = analyze_field_text(&doc_mapper, "message", "Hello, World!").unwrap();
+        assert_eq!(field_analysis.field, "body");
+        assert_eq!(field_analysis.tokenizer.as_deref(), Some("default"));
+        assert_eq!(
+            tokens_text(field_analysis.tokens.as_ref().unwrap()),
+            ["hello", "world"]
+        );
+        assert!(field_analysis.fast_field_normalizer.is_none());
+        assert!(field_analysis.fast_field_tokens.is_none());
+
+        let field_analysis = analyze_field_text(&doc_mapper, "service", "Auth-API").unwrap();
+        assert_eq!(
+            tokens_text(field_analysis.tokens.as_ref().unwrap()),
+            ["Auth-API"]
+        );
+        assert_eq!(
+            field_analysis.fast_field_normalizer.as_deref(),
+            Some("lowercase")
+        );
+        assert_eq!(
+            tokens_text(field_analysis.fast_field_tokens.as_ref().unwrap()),
+            ["auth-api"]
+        );
+
+        let field_analysis =
+            analyze_field_text(&doc_mapper, "attributes.color", "Dark Blue").unwrap();
+        assert_eq!(field_analysis.field, "attributes.color");
+        assert_eq!(
+            tokens_text(field_analysis.tokens.as_ref().unwrap()),
+            ["Dark Blue"]
+        );
+
+        let field_analysis = analyze_field_text(&doc_mapper, "unmapped", "Dark Blue").unwrap();
+        assert_eq!(field_analysis.tokenizer.as_deref(), Some("raw"));
+
+        let error = analyze_field_text(&doc_mapper, "latency", "10").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "field `latency` is a `u64` field, only `text` and `json` fields are analyzed"
+        );
+    }
+}
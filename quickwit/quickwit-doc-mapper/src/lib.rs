@@ -28,6 +28,8 @@
 mod default_doc_mapper;
 mod doc_mapper;
 mod error;
+mod field_analysis;
+mod partition_pruning;
 mod query_builder;
 mod routing_expression;
 
@@ -35,29 +37,40 @@ mod routing_expression;
 pub mod tag_pruning;
 
 pub use default_doc_mapper::{
-    analyze_text, DefaultDocMapper, DefaultDocMapperBuilder, FieldMappingEntry, Mode, ModeType,
-    QuickwitJsonOptions, TokenizerConfig, TokenizerEntry,
+    analyze_text, suggest_doc_mapping, DefaultDocMapper, DefaultDocMapperBuilder,
+    DocMappingSuggestion, FieldMappingEntry, FieldSummary, Mode, ModeType, QuickwitJsonOptions,
+    StopWordsFilterOption, TokenizerConfig, TokenizerEntry,
 };
 use default_doc_mapper::{
-    FastFieldOptions, FieldMappingEntryForSerialization, IndexRecordOptionSchema,
-    NgramTokenizerOption, QuickwitTextNormalizer, QuickwitTextTokenizer, RegexTokenizerOption,
-    TokenFilterType, TokenizerType,
+    EdgeNgramTokenizerOption, FastFieldOptions, FieldMappingEntryForSerialization,
+    IndexRecordOptionSchema, NgramTokenizerOption, QuickwitTextNormalizer, QuickwitTextTokenizer,
+    RegexTokenizerOption, StemmerFilterOption, StemmerLanguage, TokenFilterType, TokenizerType,
 };
 pub use doc_mapper::{DocMapper, JsonObject, NamedField, TermRange, WarmupInfo};
 pub use error::{DocParsingError, QueryParserError};
+pub use field_analysis::{analyze_field_text, FieldAnalysis};
 use quickwit_common::shared_consts::FIELD_PRESENCE_FIELD_NAME;
 
+/// Partition ID of the splits gathering the documents of the partitions exceeding the maximum
+/// number of partitions of an index.
+pub const OTHER_PARTITION_ID: u64 = 3264326757911759461u64;
+
 /// Field name reserved for storing the source document.
 pub const SOURCE_FIELD_NAME: &str = "_source";
 
 /// Field name reserved for storing the dynamically indexed fields.
 pub const DYNAMIC_FIELD_NAME: &str = "_dynamic";
 
+/// Field name reserved for the custom tags of the split containing the document. It is removed
+/// from the document before indexing.
+pub const SPLIT_TAGS_FIELD_NAME: &str = "_split_tags";
+
 /// Quickwit reserved field names.
 const QW_RESERVED_FIELD_NAMES: &[&str] = &[
     SOURCE_FIELD_NAME,
     DYNAMIC_FIELD_NAME,
     FIELD_PRESENCE_FIELD_NAME,
+    SPLIT_TAGS_FIELD_NAME,
 ];
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -68,6 +81,7 @@ pub(crate) enum Cardinality {
 
 #[derive(utoipa::OpenApi)]
 #[openapi(components(schemas(
+    EdgeNgramTokenizerOption,
     FastFieldOptions,
     FieldMappingEntryForSerialization,
     IndexRecordOptionSchema,
@@ -77,6 +91,9 @@ pub(crate) enum Cardinality {
     QuickwitTextNormalizer,
     QuickwitTextTokenizer,
     RegexTokenizerOption,
+    StemmerFilterOption,
+    StemmerLanguage,
+    StopWordsFilterOption,
     TokenFilterType,
     TokenizerConfig,
     TokenizerEntry,
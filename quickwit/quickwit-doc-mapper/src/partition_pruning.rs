@@ -0,0 +1,135 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+use std::collections::HashMap;
+
+use quickwit_query::query_ast::QueryAst;
+
+/// Collects the exact values that any document matching `query_ast` must have, per field.
+///
+/// Only the conjunctive part of the query (`must` and `filter` clauses) is inspected. A field
+/// required to have two different values maps to `None`: no document can match the query in that
+/// case, but we do not try to be clever about it.
+pub(crate) fn extract_required_values(query_ast: &QueryAst) -> HashMap<&str, Option<&str>> {
+    let mut required_values = HashMap::new();
+    collect_required_values(query_ast, &mut required_values);
+    required_values
+}
+
+fn collect_required_values<'a>(
+    query_ast: &'a QueryAst,
+    required_values: &mut HashMap<&'a str, Option<&'a str>>,
+) {
+    match query_ast {
+        QueryAst::Bool(bool_query) => {
+            for child_ast in bool_query.must.iter().chain(&bool_query.filter) {
+                collect_required_values(child_ast, required_values);
+            }
+        }
+        QueryAst::Term(term_query) => {
+            add_required_value(&term_query.field, &term_query.value, required_values);
+        }
+        // A full-text query only matches the exact text on fields using the `raw` tokenizer.
+        // Callers are expected to check the tokenizer of the field.
+        QueryAst::FullText(full_text_query)
+            if full_text_query.params.tokenizer.is_none() && !full_text_query.text.is_empty() =>
+        {
+            add_required_value(
+                &full_text_query.field,
+                &full_text_query.text,
+                required_values,
+            );
+        }
+        QueryAst::TermSet(term_set_query) if term_set_query.terms_per_field.len() == 1 => {
+            let (field, terms) = term_set_query.terms_per_field.iter().next().unwrap();
+            if terms.len() == 1 {
+                let term = terms.iter().next().unwrap();
+                add_required_value(field, term, required_values);
+            }
+        }
+        QueryAst::Boost { underlying, .. } => {
+            collect_required_values(underlying, required_values);
+        }
+        _ => {}
+    }
+}
+
+fn add_required_value<'a>(
+    field: &'a str,
+    value: &'a str,
+    required_values: &mut HashMap<&'a str, Option<&'a str>>,
+) {
+    required_values
+        .entry(field)
+        .and_modify(|required_value_opt| {
+            if *required_value_opt != Some(value) {
+                *required_value_opt = None;
+            }
+        })
+        .or_insert(Some(value));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use quickwit_query::query_ast::{QueryAst, UserInputQuery};
+    use quickwit_query::BooleanOperand;
+
+    use super::extract_required_values;
+
+    fn extract_required_values_helper(user_query: &str) -> HashMap<String, Option<String>> {
+        let query_ast: QueryAst = UserInputQuery {
+            user_text: user_query.to_string(),
+            default_fields: None,
+            default_operator: BooleanOperand::Or,
+        }
+        .into();
+        let parsed_query_ast = query_ast.parse_user_query(&[]).unwrap();
+        extract_required_values(&parsed_query_ast)
+            .into_iter()
+            .map(|(field, value_opt)| (field.to_string(), value_opt.map(str::to_string)))
+            .collect()
+    }
+
+    #[test]
+    fn test_extract_required_values() {
+        assert!(extract_required_values_helper("*").is_empty());
+        assert!(extract_required_values_helper("tenant:acme OR body:foo").is_empty());
+        assert!(extract_required_values_helper("NOT tenant:acme").is_empty());
+
+        let required_values = extract_required_values_helper("tenant:acme");
+        assert_eq!(required_values.len(), 1);
+        assert_eq!(required_values["tenant"].as_deref(), Some("acme"));
+
+        let required_values =
+            extract_required_values_helper("tenant:acme AND (body:foo OR body:bar) AND app:web");
+        assert_eq!(required_values.len(), 2);
+        assert_eq!(required_values["tenant"].as_deref(), Some("acme"));
+        assert_eq!(required_values["app"].as_deref(), Some("web"));
+
+        let required_values = extract_required_values_helper("tenant:acme AND tenant:globex");
+        assert_eq!(required_values.len(), 1);
+        assert_eq!(required_values["tenant"], None);
+
+        let required_values = extract_required_values_helper("tenant: IN [acme]");
+        assert_eq!(required_values["tenant"].as_deref(), Some("acme"));
+
+        assert!(extract_required_values_helper("tenant: IN [acme globex]").is_empty());
+    }
+}
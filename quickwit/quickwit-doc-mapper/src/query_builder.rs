@@ -23,7 +23,7 @@ use std::ops::Bound;
 
 use quickwit_query::query_ast::{
     FieldPresenceQuery, FullTextQuery, PhrasePrefixQuery, QueryAst, QueryAstVisitor, RangeQuery,
-    TermSetQuery, WildcardQuery,
+    RegexQuery, TermSetQuery, WildcardQuery,
 };
 use quickwit_query::tokenizers::TokenizerManager;
 use quickwit_query::{find_field_or_hit_dynamic, InvalidQuery};
@@ -100,9 +100,11 @@ pub(crate) fn build_query(
         with_validation,
     )?;
 
-    let term_set_query_fields = extract_term_set_query_fields(query_ast, &schema)?;
+    // Without validation, the fields missing from the schema of the split, for instance fields
+    // added to the doc mapping after the split was created, match nothing and are not warmed up.
+    let term_set_query_fields = extract_term_set_query_fields(query_ast, &schema, with_validation)?;
     let term_ranges_grouped_by_field =
-        extract_prefix_term_ranges(query_ast, &schema, tokenizer_manager)?;
+        extract_prefix_term_ranges(query_ast, &schema, tokenizer_manager, with_validation)?;
 
     let mut terms_grouped_by_field: HashMap<Field, HashMap<_, bool>> = Default::default();
     query.query_terms(&mut |term, need_position| {
@@ -135,13 +137,15 @@ fn is_fast_field(schema: &Schema, field_name: &str) -> bool {
 struct ExtractTermSetFields<'a> {
     term_dict_fields_to_warm_up: HashSet<Field>,
     schema: &'a Schema,
+    with_validation: bool,
 }
 
 impl<'a> ExtractTermSetFields<'a> {
-    fn new(schema: &'a Schema) -> Self {
+    fn new(schema: &'a Schema, with_validation: bool) -> Self {
         ExtractTermSetFields {
             term_dict_fields_to_warm_up: HashSet::new(),
             schema,
+            with_validation,
         }
     }
 }
@@ -154,19 +158,32 @@ impl<'a, 'b> QueryAstVisitor<'a> for ExtractTermSetFields<'b> {
             if let Ok((field, _field_entry, _path)) = find_field_or_hit_dynamic(field, self.schema)
             {
                 self.term_dict_fields_to_warm_up.insert(field);
-            } else {
+            } else if self.with_validation {
                 anyhow::bail!("field does not exist: {}", field);
             }
         }
         Ok(())
     }
+
+    // Regex queries walk the whole term dictionary of the field.
+    fn visit_regex(&mut self, regex_query: &'a RegexQuery) -> anyhow::Result<()> {
+        if let Ok((field, _field_entry, _path)) =
+            find_field_or_hit_dynamic(&regex_query.field, self.schema)
+        {
+            self.term_dict_fields_to_warm_up.insert(field);
+        } else if self.with_validation {
+            anyhow::bail!("field does not exist: {}", regex_query.field);
+        }
+        Ok(())
+    }
 }
 
 fn extract_term_set_query_fields(
     query_ast: &QueryAst,
     schema: &Schema,
+    with_validation: bool,
 ) -> anyhow::Result<HashSet<Field>> {
-    let mut visitor = ExtractTermSetFields::new(schema);
+    let mut visitor = ExtractTermSetFields::new(schema, with_validation);
     visitor.visit(query_ast)?;
     Ok(visitor.term_dict_fields_to_warm_up)
 }
@@ -193,18 +210,30 @@ type PositionNeeded = bool;
 struct ExtractPrefixTermRanges<'a> {
     schema: &'a Schema,
     tokenizer_manager: &'a TokenizerManager,
+    with_validation: bool,
     term_ranges_to_warm_up: HashMap<Field, HashMap<TermRange, PositionNeeded>>,
 }
 
 impl<'a> ExtractPrefixTermRanges<'a> {
-    fn with_schema(schema: &'a Schema, tokenizer_manager: &'a TokenizerManager) -> Self {
+    fn with_schema(
+        schema: &'a Schema,
+        tokenizer_manager: &'a TokenizerManager,
+        with_validation: bool,
+    ) -> Self {
         ExtractPrefixTermRanges {
             schema,
             tokenizer_manager,
+            with_validation,
             term_ranges_to_warm_up: HashMap::new(),
         }
     }
 
+    /// Returns whether an error should be ignored because the queried field does not exist in the
+    /// schema and the query is not validated.
+    fn ignore_missing_field(&self, invalid_query: &InvalidQuery) -> bool {
+        !self.with_validation && matches!(invalid_query, InvalidQuery::FieldDoesNotExist { .. })
+    }
+
     fn add_prefix_term(
         &mut self,
         term: Term,
@@ -244,7 +273,11 @@ impl<'a, 'b: 'a> QueryAstVisitor<'a> for ExtractPrefixTermRanges<'b> {
         &mut self,
         phrase_prefix: &'a PhrasePrefixQuery,
     ) -> Result<(), Self::Err> {
-        let (_, terms) = phrase_prefix.get_terms(self.schema, self.tokenizer_manager)?;
+        let (_, terms) = match phrase_prefix.get_terms(self.schema, self.tokenizer_manager) {
+            Ok(field_and_terms) => field_and_terms,
+            Err(invalid_query) if self.ignore_missing_field(&invalid_query) => return Ok(()),
+            Err(invalid_query) => return Err(invalid_query),
+        };
         if let Some((_, term)) = terms.last() {
             self.add_prefix_term(term.clone(), phrase_prefix.max_expansions, terms.len() > 1);
         }
@@ -252,7 +285,12 @@ impl<'a, 'b: 'a> QueryAstVisitor<'a> for ExtractPrefixTermRanges<'b> {
     }
 
     fn visit_wildcard(&mut self, wildcard_query: &'a WildcardQuery) -> Result<(), Self::Err> {
-        let (_, term) = wildcard_query.extract_prefix_term(self.schema, self.tokenizer_manager)?;
+        let (_, term) =
+            match wildcard_query.extract_prefix_term(self.schema, self.tokenizer_manager) {
+                Ok(field_and_term) => field_and_term,
+                Err(invalid_query) if self.ignore_missing_field(&invalid_query) => return Ok(()),
+                Err(invalid_query) => return Err(invalid_query),
+            };
         self.add_prefix_term(term, u32::MAX, false);
         Ok(())
     }
@@ -262,8 +300,10 @@ fn extract_prefix_term_ranges(
     query_ast: &QueryAst,
     schema: &Schema,
     tokenizer_manager: &TokenizerManager,
+    with_validation: bool,
 ) -> anyhow::Result<HashMap<Field, HashMap<TermRange, PositionNeeded>>> {
-    let mut visitor = ExtractPrefixTermRanges::with_schema(schema, tokenizer_manager);
+    let mut visitor =
+        ExtractPrefixTermRanges::with_schema(schema, tokenizer_manager, with_validation);
     visitor.visit(query_ast)?;
     Ok(visitor.term_ranges_to_warm_up)
 }
@@ -647,6 +687,41 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_build_query_missing_field_without_validation() {
+        let tokenizer_manager = create_default_quickwit_tokenizer_manager();
+
+        for user_query in [
+            "missing: IN [hello]",
+            "missing:hel*",
+            "missing:\"hello wor\"*",
+        ] {
+            let query_ast = query_ast_from_user_text(user_query, None)
+                .parse_user_query(&[])
+                .unwrap();
+            build_query(
+                &query_ast,
+                make_schema(false),
+                &tokenizer_manager,
+                &[],
+                true,
+            )
+            .unwrap_err();
+
+            let (query, warmup_info) = build_query(
+                &query_ast,
+                make_schema(false),
+                &tokenizer_manager,
+                &[],
+                false,
+            )
+            .unwrap();
+            assert_eq!(format!("{query:?}"), "EmptyQuery", "query `{user_query}`");
+            assert!(warmup_info.term_dict_fields.is_empty());
+            assert!(warmup_info.term_ranges_grouped_by_field.is_empty());
+        }
+    }
+
     #[test]
     fn test_build_query_warmup_info() {
         let query_with_set = query_ast_from_user_text("desc: IN [hello]", None)
@@ -678,5 +753,21 @@ mod test {
         )
         .unwrap();
         assert!(warmup_info.term_dict_fields.is_empty());
+
+        let query_with_regex = query_ast_from_user_text("desc:/hel+o/", None)
+            .parse_user_query(&[])
+            .unwrap();
+        let (_, warmup_info) = build_query(
+            &query_with_regex,
+            make_schema(true),
+            &create_default_quickwit_tokenizer_manager(),
+            &[],
+            true,
+        )
+        .unwrap();
+        assert_eq!(warmup_info.term_dict_fields.len(), 1);
+        assert!(warmup_info
+            .term_dict_fields
+            .contains(&tantivy::schema::Field::from_field_id(1)));
     }
 }
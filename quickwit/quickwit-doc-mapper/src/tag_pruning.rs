@@ -17,9 +17,10 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::Display;
 
+use anyhow::{bail, Context};
 use quickwit_query::query_ast::QueryAst;
 use serde::{Deserialize, Serialize};
 use tantivy::query_grammar::Occur;
@@ -113,11 +114,14 @@ fn extract_unsimplified_tags_filter_ast(query_ast: QueryAst) -> UnsimplifiedTagF
                 value: wildcard_query.value,
             }
         }
+        QueryAst::Regex(_) => UnsimplifiedTagFilterAst::Uninformative,
         QueryAst::Boost { underlying, .. } => extract_unsimplified_tags_filter_ast(*underlying),
         QueryAst::UserInput(_user_text_query) => {
             panic!("Extract unsimplified should only be called on AST without UserInputQuery.");
         }
         QueryAst::FieldPresence(_) => UnsimplifiedTagFilterAst::Uninformative,
+        // Terms lookups are resolved into term sets by the root searcher.
+        QueryAst::TermsLookup(_) => UnsimplifiedTagFilterAst::Uninformative,
     }
 }
 
@@ -166,6 +170,52 @@ pub fn append_to_tag_set(field_name: &str, values: &[String], tag_set: &mut BTre
     }
 }
 
+/// Builds a TagFilterAst from a list of split tags of the form `{key}:{value}`. The predicate
+/// only rejects splits that are guaranteed not to contain any document carrying all these tags.
+/// Several values for the same key are interpreted as alternatives.
+///
+/// Returns `None` if the list is empty.
+pub fn tag_filter_from_split_tags(split_tags: &[String]) -> anyhow::Result<Option<TagFilterAst>> {
+    let mut values_per_key: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+
+    for split_tag in split_tags {
+        let (key, value) = split_tag
+            .split_once(':')
+            .with_context(|| format!("split tag `{split_tag}` is not of the form `key:value`"))?;
+        if key.is_empty() {
+            bail!("split tag `{split_tag}` has an empty key");
+        }
+        values_per_key.entry(key).or_default().push(value);
+    }
+    let mut children: Vec<TagFilterAst> = values_per_key
+        .into_iter()
+        .map(|(key, values)| {
+            let mut key_children = Vec::with_capacity(values.len() + 1);
+            key_children.push(no_tag(field_tag(key)));
+            key_children.extend(values.into_iter().map(|value| tag(term_tag(key, value))));
+            TagFilterAst::Or(key_children)
+        })
+        .collect();
+    let tag_filter_ast = match children.len() {
+        0 => return Ok(None),
+        1 => children.pop().unwrap(),
+        _ => TagFilterAst::And(children),
+    };
+    Ok(Some(tag_filter_ast))
+}
+
+/// Combines two optional tag filters with a conjunction. `None` stands for a predicate always
+/// evaluating to true.
+pub fn and_tag_filters(
+    left_opt: Option<TagFilterAst>,
+    right_opt: Option<TagFilterAst>,
+) -> Option<TagFilterAst> {
+    match (left_opt, right_opt) {
+        (Some(left), Some(right)) => Some(TagFilterAst::And(vec![left, right])),
+        (left_opt, right_opt) => left_opt.or(right_opt),
+    }
+}
+
 /// Represents a predicate over the set of tags associated with a given split.
 #[allow(missing_docs)]
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
@@ -507,4 +557,48 @@ mod test {
         assert!(!super::match_tag_field_name("tagfield", "tagfiele:val"));
         assert!(!super::match_tag_field_name("tagfield", "t:val"));
     }
+
+    #[test]
+    fn test_tag_filter_from_split_tags() {
+        use std::collections::BTreeSet;
+
+        use super::{and_tag_filters, tag_filter_from_split_tags};
+
+        assert_eq!(tag_filter_from_split_tags(&[]).unwrap(), None);
+
+        let split_tags = vec![
+            "env:prod".to_string(),
+            "dc:us-east".to_string(),
+            "dc:us-west".to_string(),
+        ];
+        let tag_filter_ast = tag_filter_from_split_tags(&split_tags).unwrap().unwrap();
+        assert_eq!(
+            tag_filter_ast.to_string(),
+            "(¬dc! ∨ dc:us-east ∨ dc:us-west) ∧ (¬env! ∨ env:prod)"
+        );
+        let tag_set = |tags: &[&str]| -> BTreeSet<String> {
+            tags.iter().map(|tag| tag.to_string()).collect()
+        };
+        assert!(tag_filter_ast.evaluate(&tag_set(&[])));
+        assert!(tag_filter_ast.evaluate(&tag_set(&["env!", "env:prod"])));
+        assert!(!tag_filter_ast.evaluate(&tag_set(&["env!", "env:dev"])));
+        assert!(tag_filter_ast.evaluate(&tag_set(&["dc!", "dc:us-west", "env!", "env:prod"])));
+        assert!(!tag_filter_ast.evaluate(&tag_set(&["dc!", "dc:eu-west", "env!", "env:prod"])));
+
+        tag_filter_from_split_tags(&["env".to_string()]).unwrap_err();
+        tag_filter_from_split_tags(&[":prod".to_string()]).unwrap_err();
+
+        let query_tag_filter_ast = extract_tags_from_query_helper("user:bart");
+        let split_tag_filter_ast = tag_filter_from_split_tags(&["env:prod".to_string()]).unwrap();
+        assert_eq!(
+            and_tag_filters(query_tag_filter_ast.clone(), None),
+            query_tag_filter_ast
+        );
+        assert_eq!(
+            and_tag_filters(query_tag_filter_ast, split_tag_filter_ast)
+                .unwrap()
+                .to_string(),
+            "(¬user! ∨ user:bart) ∧ (¬env! ∨ env:prod)"
+        );
+    }
 }
@@ -25,18 +25,18 @@ use std::time::Duration;
 use futures::Future;
 use quickwit_common::{PrettySample, Progress, ServiceStream};
 use quickwit_metastore::{
-    ListSplitsQuery, ListSplitsRequestExt, MetastoreServiceStreamSplitsExt, SplitInfo,
-    SplitMetadata, SplitState,
+    IndexMetadata, ListSplitsQuery, ListSplitsRequestExt, MetastoreServiceStreamSplitsExt,
+    SplitInfo, SplitMetadata, SplitState,
 };
 use quickwit_proto::metastore::{
     DeleteSplitsRequest, ListSplitsRequest, ListSplitsResponse, MarkSplitsForDeletionRequest,
     MetastoreError, MetastoreResult, MetastoreService, MetastoreServiceClient,
 };
 use quickwit_proto::types::{IndexUid, SplitId};
-use quickwit_storage::{BulkDeleteError, Storage};
+use quickwit_storage::{BulkDeleteError, Storage, StorageFileEntry};
 use thiserror::Error;
 use time::OffsetDateTime;
-use tracing::{error, instrument};
+use tracing::{error, instrument, warn};
 
 /// The maximum number of splits that the GC should delete per attempt.
 const DELETE_SPLITS_BATCH_SIZE: usize = 1000;
@@ -72,21 +72,59 @@ pub struct SplitRemovalInfo {
     pub failed_splits: Vec<SplitInfo>,
 }
 
+/// Information on the orphan split files found by a deep scan of the storage of an index.
+#[derive(Debug, Default)]
+pub struct OrphanFilesScanInfo {
+    /// The split files present in the storage that are not referenced by the metastore.
+    pub orphan_files: Vec<StorageFileEntry>,
+    /// The orphan files that have been deleted.
+    pub deleted_files: Vec<PathBuf>,
+    /// The orphan files that were attempted to be deleted, but were unsuccessful.
+    pub failed_files: Vec<PathBuf>,
+}
+
+/// Returns the UIDs of the indexes, other than the index `index_uid`, that store their splits under
+/// the same URI, i.e. its clones and the index it was cloned from. These indexes may reference
+/// the same split files, which must not be deleted until no index references them anymore.
+pub fn find_indexes_sharing_storage(
+    indexes_metadata: &[IndexMetadata],
+    index_uid: &IndexUid,
+) -> Vec<IndexUid> {
+    let Some(index_metadata) = indexes_metadata
+        .iter()
+        .find(|index_metadata| index_metadata.index_uid == *index_uid)
+    else {
+        return Vec::new();
+    };
+    indexes_metadata
+        .iter()
+        .filter(|other_index_metadata| {
+            other_index_metadata.index_uid != *index_uid
+                && other_index_metadata.index_uri() == index_metadata.index_uri()
+        })
+        .map(|other_index_metadata| other_index_metadata.index_uid.clone())
+        .collect()
+}
+
 /// Detect all dangling splits and associated files from the index and removes them.
 ///
 /// * `index_id` - The target index id.
 /// * `storage - The storage managing the target index.
 /// * `metastore` - The metastore managing the target index.
+/// * `shared_index_uids` - The indexes sharing the storage of the target index (see
+///   [`find_indexes_sharing_storage`]).
 /// * `staged_grace_period` -  Threshold period after which a staged split can be safely garbage
 ///   collected.
 /// * `deletion_grace_period` -  Threshold period after which a marked as deleted split can be
 ///   safely deleted.
 /// * `dry_run` - Should this only return a list of affected files without performing deletion.
 /// * `progress` - For reporting progress (useful when called from within a quickwit actor).
+#[allow(clippy::too_many_arguments)]
 pub async fn run_garbage_collect(
     index_uid: IndexUid,
     storage: Arc<dyn Storage>,
     mut metastore: MetastoreServiceClient,
+    shared_index_uids: &[IndexUid],
     staged_grace_period: Duration,
     deletion_grace_period: Duration,
     dry_run: bool,
@@ -158,12 +196,153 @@ pub async fn run_garbage_collect(
         updated_before_timestamp,
         storage,
         metastore,
+        shared_index_uids,
         progress_opt,
     )
     .await;
 
     Ok(deleted_splits)
 }
+
+/// Returns the ID of the split whose file is located at `path`, if `path` is the path of a split
+/// file or of a doc IDs sidecar file, i.e. `<split_id>.split` or `<split_id>.docids` at the root of
+/// the index storage.
+fn split_id_from_file_path(path: &Path) -> Option<SplitId> {
+    if path.parent() != Some(Path::new("")) {
+        return None;
+    }
+    let extension = path.extension()?;
+
+    if extension != "split" && extension != "docids" {
+        return None;
+    }
+    path.file_stem()?
+        .to_str()
+        .map(|split_id| split_id.to_string())
+}
+
+/// Lists the split files and the doc IDs sidecar files stored under the index URI and detects the
+/// orphan ones, i.e. the files of the splits that no index sharing the storage references in the
+/// metastore anymore, for instance because a publish crashed after the split upload.
+///
+/// Orphan files are only reported unless `orphan_files_grace_period_opt` is set, in which case the
+/// orphan files last modified before `now - orphan_files_grace_period` are deleted. The files
+/// whose modification time is unknown are never deleted.
+///
+/// * `index_id` - The target index id.
+/// * `storage - The storage managing the target index.
+/// * `metastore` - The metastore managing the target index.
+/// * `shared_index_uids` - The indexes sharing the storage of the target index (see
+///   [`find_indexes_sharing_storage`]).
+/// * `orphan_files_grace_period_opt` - Threshold period after which an orphan file can be safely
+///   deleted.
+/// * `progress` - For reporting progress (useful when called from within a quickwit actor).
+pub async fn run_orphan_files_scan(
+    index_uid: IndexUid,
+    storage: Arc<dyn Storage>,
+    mut metastore: MetastoreServiceClient,
+    shared_index_uids: &[IndexUid],
+    orphan_files_grace_period_opt: Option<Duration>,
+    progress_opt: Option<&Progress>,
+) -> anyhow::Result<OrphanFilesScanInfo> {
+    // The files are listed before the splits: a split is staged before its file is uploaded, so
+    // the split of any listed file is known to the metastore by the time we list the splits,
+    // unless it is actually orphan.
+    let file_entries = protect_future(progress_opt, storage.list(Path::new(""))).await?;
+
+    let split_file_entries: Vec<(SplitId, StorageFileEntry)> = file_entries
+        .into_iter()
+        .filter_map(|file_entry| {
+            let split_id = split_id_from_file_path(&file_entry.path)?;
+            Some((split_id, file_entry))
+        })
+        .collect();
+
+    if split_file_entries.is_empty() {
+        return Ok(OrphanFilesScanInfo::default());
+    }
+    let index_uids: Vec<IndexUid> = std::iter::once(index_uid.clone())
+        .chain(shared_index_uids.iter().cloned())
+        .collect();
+    let query = ListSplitsQuery::try_from_index_uids(index_uids)?;
+    let list_splits_request = ListSplitsRequest::try_from_list_splits_query(query)?;
+    let known_split_ids: HashSet<SplitId> =
+        protect_future(progress_opt, metastore.list_splits(list_splits_request))
+            .await?
+            .collect_split_ids()
+            .await?
+            .into_iter()
+            .collect();
+
+    let mut orphan_files: Vec<StorageFileEntry> = split_file_entries
+        .into_iter()
+        .filter(|(split_id, _)| !known_split_ids.contains(split_id))
+        .map(|(_, file_entry)| file_entry)
+        .collect();
+    orphan_files.sort_by(|left, right| left.path.cmp(&right.path));
+
+    let Some(orphan_files_grace_period) = orphan_files_grace_period_opt else {
+        return Ok(OrphanFilesScanInfo {
+            orphan_files,
+            ..Default::default()
+        });
+    };
+    let modified_before_timestamp =
+        OffsetDateTime::now_utc().unix_timestamp() - orphan_files_grace_period.as_secs() as i64;
+
+    let deletable_paths: Vec<&Path> = orphan_files
+        .iter()
+        .filter(|file_entry| {
+            file_entry
+                .last_modified_timestamp_opt
+                .map_or(false, |timestamp| timestamp <= modified_before_timestamp)
+        })
+        .map(|file_entry| file_entry.path.as_path())
+        .collect();
+
+    if deletable_paths.is_empty() {
+        return Ok(OrphanFilesScanInfo {
+            orphan_files,
+            ..Default::default()
+        });
+    }
+    let delete_result = protect_future(progress_opt, storage.bulk_delete(&deletable_paths)).await;
+
+    let (deleted_files, failed_files) = match delete_result {
+        Ok(_) => {
+            let deleted_files = deletable_paths
+                .iter()
+                .map(|path| path.to_path_buf())
+                .collect();
+            (deleted_files, Vec::new())
+        }
+        Err(bulk_delete_error) => {
+            let success_paths: HashSet<&Path> = bulk_delete_error
+                .successes
+                .iter()
+                .map(|path| path.as_path())
+                .collect();
+            let failed_files: Vec<PathBuf> = deletable_paths
+                .iter()
+                .filter(|path| !success_paths.contains(*path))
+                .map(|path| path.to_path_buf())
+                .collect();
+            error!(
+                error=?bulk_delete_error.error,
+                index_id=index_uid.index_id(),
+                "Failed to delete orphan file(s) {:?} from storage.",
+                PrettySample::new(&failed_files, 5),
+            );
+            (bulk_delete_error.successes, failed_files)
+        }
+    };
+    Ok(OrphanFilesScanInfo {
+        orphan_files,
+        deleted_files,
+        failed_files,
+    })
+}
+
 #[instrument(skip(storage, metastore, progress_opt))]
 /// Removes any splits marked for deletion which haven't been
 /// updated after `updated_before_timestamp` in batches of 1000 splits.
@@ -175,6 +354,7 @@ async fn delete_splits_marked_for_deletion(
     updated_before_timestamp: i64,
     storage: Arc<dyn Storage>,
     mut metastore: MetastoreServiceClient,
+    shared_index_uids: &[IndexUid],
     progress_opt: Option<&Progress>,
 ) -> SplitRemovalInfo {
     let mut removed_splits = Vec::new();
@@ -222,6 +402,7 @@ async fn delete_splits_marked_for_deletion(
             index_uid.clone(),
             storage.clone(),
             metastore.clone(),
+            shared_index_uids,
             splits_metadata_to_delete,
             progress_opt,
         )
@@ -245,32 +426,101 @@ async fn delete_splits_marked_for_deletion(
     }
 }
 
+/// Returns the IDs of the splits among `split_ids` that are also referenced by one of the
+/// `shared_index_uids` indexes.
+async fn list_shared_split_ids(
+    metastore: &mut MetastoreServiceClient,
+    shared_index_uids: &[IndexUid],
+    split_ids: Vec<SplitId>,
+) -> MetastoreResult<HashSet<SplitId>> {
+    if shared_index_uids.is_empty() || split_ids.is_empty() {
+        return Ok(HashSet::new());
+    }
+    let query =
+        ListSplitsQuery::try_from_index_uids(shared_index_uids.to_vec())?.with_split_ids(split_ids);
+    let list_splits_request = ListSplitsRequest::try_from_list_splits_query(query)?;
+    let shared_split_ids = metastore
+        .list_splits(list_splits_request)
+        .await?
+        .collect_split_ids()
+        .await?
+        .into_iter()
+        .collect();
+    Ok(shared_split_ids)
+}
+
 /// Delete a list of splits from the storage and the metastore.
 /// It should leave the index and the metastore in good state.
 ///
+/// The files of the splits still referenced by one of the `shared_index_uids` indexes are kept in
+/// the storage: these splits are only deleted from the metastore and are not reported as deleted.
+///
 /// * `index_id` - The target index id.
 /// * `storage - The storage managing the target index.
 /// * `metastore` - The metastore managing the target index.
+/// * `shared_index_uids` - The indexes sharing the storage of the target index.
 /// * `splits`  - The list of splits to delete.
 /// * `progress` - For reporting progress (useful when called from within a quickwit actor).
 pub async fn delete_splits_from_storage_and_metastore(
     index_uid: IndexUid,
     storage: Arc<dyn Storage>,
     mut metastore: MetastoreServiceClient,
+    shared_index_uids: &[IndexUid],
     splits: Vec<SplitMetadata>,
     progress_opt: Option<&Progress>,
 ) -> anyhow::Result<Vec<SplitInfo>, DeleteSplitsError> {
+    let split_ids: Vec<SplitId> = splits.iter().map(|split| split.split_id.clone()).collect();
+    let shared_split_ids_res = protect_future(
+        progress_opt,
+        list_shared_split_ids(&mut metastore, shared_index_uids, split_ids),
+    )
+    .await;
+    let shared_split_ids = match shared_split_ids_res {
+        Ok(shared_split_ids) => shared_split_ids,
+        Err(metastore_error) => {
+            error!(
+                error=?metastore_error,
+                index_id=index_uid.index_id(),
+                "failed to list the splits shared with other indexes"
+            );
+            let delete_splits_error = DeleteSplitsError {
+                successes: Vec::new(),
+                storage_error: None,
+                storage_failures: Vec::new(),
+                metastore_error: Some(metastore_error),
+                metastore_failures: splits
+                    .into_iter()
+                    .map(|split| split.as_split_info())
+                    .collect(),
+            };
+            return Err(delete_splits_error);
+        }
+    };
     let mut split_infos: HashMap<PathBuf, SplitInfo> = HashMap::with_capacity(splits.len());
+    let mut shared_split_infos = Vec::with_capacity(shared_split_ids.len());
+    let mut split_ids_with_doc_ids_sidecar: HashSet<SplitId> = HashSet::new();
 
     for split in splits {
         let split_info = split.as_split_info();
-        split_infos.insert(split_info.file_name.clone(), split_info);
+
+        if shared_split_ids.contains(&split.split_id) {
+            shared_split_infos.push(split_info);
+        } else {
+            if split.has_doc_ids_sidecar {
+                split_ids_with_doc_ids_sidecar.insert(split.split_id.clone());
+            }
+            split_infos.insert(split_info.file_name.clone(), split_info);
+        }
     }
     let split_paths = split_infos
         .keys()
         .map(|split_path_buf| split_path_buf.as_path())
         .collect::<Vec<&Path>>();
-    let delete_result = protect_future(progress_opt, storage.bulk_delete(&split_paths)).await;
+    let delete_result = if split_paths.is_empty() {
+        Ok(())
+    } else {
+        protect_future(progress_opt, storage.bulk_delete(&split_paths)).await
+    };
 
     if let Some(progress) = progress_opt {
         progress.record_progress();
@@ -304,9 +554,19 @@ pub async fn delete_splits_from_storage_and_metastore(
             storage_error = Some(bulk_delete_error);
         }
     };
-    if !successes.is_empty() {
+    delete_doc_ids_sidecars(
+        &index_uid,
+        &*storage,
+        &successes,
+        &split_ids_with_doc_ids_sidecar,
+        progress_opt,
+    )
+    .await;
+
+    if !successes.is_empty() || !shared_split_infos.is_empty() {
         let split_ids: Vec<SplitId> = successes
             .iter()
+            .chain(shared_split_infos.iter())
             .map(|split_info| split_info.split_id.to_string())
             .collect();
         let delete_splits_request = DeleteSplitsRequest {
@@ -323,12 +583,15 @@ pub async fn delete_splits_from_storage_and_metastore(
                 "failed to delete split(s) {:?} from metastore",
                 PrettySample::new(&split_ids, 5),
             );
+            let mut metastore_failures = successes;
+            metastore_failures.extend(shared_split_infos);
+
             let delete_splits_error = DeleteSplitsError {
                 successes: Vec::new(),
                 storage_error,
                 storage_failures,
                 metastore_error: Some(metastore_error),
-                metastore_failures: successes,
+                metastore_failures,
             };
             return Err(delete_splits_error);
         }
@@ -346,6 +609,39 @@ pub async fn delete_splits_from_storage_and_metastore(
     Ok(successes)
 }
 
+/// Deletes the doc IDs sidecar files of the deleted splits that have one. Failures are only
+/// logged: an orphan sidecar is harmless and is never read again.
+async fn delete_doc_ids_sidecars(
+    index_uid: &IndexUid,
+    storage: &dyn Storage,
+    deleted_splits: &[SplitInfo],
+    split_ids_with_doc_ids_sidecar: &HashSet<SplitId>,
+    progress_opt: Option<&Progress>,
+) {
+    let sidecar_paths: Vec<PathBuf> = deleted_splits
+        .iter()
+        .filter(|split_info| split_ids_with_doc_ids_sidecar.contains(&split_info.split_id))
+        .map(|split_info| {
+            PathBuf::from(quickwit_common::doc_ids_sidecar_file(&split_info.split_id))
+        })
+        .collect();
+    if sidecar_paths.is_empty() {
+        return;
+    }
+    let sidecar_paths_ref: Vec<&Path> = sidecar_paths.iter().map(PathBuf::as_path).collect();
+
+    if let Err(bulk_delete_error) =
+        protect_future(progress_opt, storage.bulk_delete(&sidecar_paths_ref)).await
+    {
+        warn!(
+            error=?bulk_delete_error.error,
+            index_id=index_uid.index_id(),
+            "failed to delete doc IDs sidecar file(s) {:?} from storage",
+            PrettySample::new(&sidecar_paths_ref, 5),
+        );
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::time::Duration;
@@ -366,6 +662,37 @@ mod tests {
     use super::*;
     use crate::run_garbage_collect;
 
+    #[test]
+    fn test_find_indexes_sharing_storage() {
+        let index_metadata = IndexMetadata::for_test("test-index", "ram:///indexes/test-index");
+        let clone_index_metadata =
+            IndexMetadata::for_test("test-clone", "ram:///indexes/test-index");
+        let other_index_metadata =
+            IndexMetadata::for_test("other-index", "ram:///indexes/other-index");
+        let indexes_metadata = vec![
+            index_metadata.clone(),
+            clone_index_metadata.clone(),
+            other_index_metadata.clone(),
+        ];
+        assert_eq!(
+            find_indexes_sharing_storage(&indexes_metadata, &index_metadata.index_uid),
+            vec![clone_index_metadata.index_uid.clone()]
+        );
+        assert_eq!(
+            find_indexes_sharing_storage(&indexes_metadata, &clone_index_metadata.index_uid),
+            vec![index_metadata.index_uid.clone()]
+        );
+        assert!(
+            find_indexes_sharing_storage(&indexes_metadata, &other_index_metadata.index_uid)
+                .is_empty()
+        );
+        assert!(find_indexes_sharing_storage(
+            &indexes_metadata,
+            &IndexUid::new_with_random_ulid("unknown-index")
+        )
+        .is_empty());
+    }
+
     #[tokio::test]
     async fn test_run_gc_marks_stale_staged_splits_for_deletion_after_grace_period() {
         let storage = storage_for_test();
@@ -412,6 +739,7 @@ mod tests {
             index_uid.clone(),
             storage.clone(),
             metastore.clone(),
+            &[],
             Duration::from_secs(30),
             Duration::from_secs(30),
             false,
@@ -440,6 +768,7 @@ mod tests {
             index_uid.clone(),
             storage.clone(),
             metastore.clone(),
+            &[],
             Duration::from_secs(0),
             Duration::from_secs(30),
             false,
@@ -516,6 +845,7 @@ mod tests {
             index_uid.clone(),
             storage.clone(),
             metastore.clone(),
+            &[],
             Duration::from_secs(30),
             Duration::from_secs(30),
             false,
@@ -544,6 +874,7 @@ mod tests {
             index_uid.clone(),
             storage.clone(),
             metastore.clone(),
+            &[],
             Duration::from_secs(30),
             Duration::from_secs(0),
             false,
@@ -580,6 +911,7 @@ mod tests {
             IndexUid::new_with_random_ulid("index-test-gc-deletes"),
             storage.clone(),
             MetastoreServiceClient::from(metastore),
+            &[],
             Duration::from_secs(30),
             Duration::from_secs(30),
             false,
@@ -589,6 +921,208 @@ mod tests {
         .unwrap();
     }
 
+    #[test]
+    fn test_split_id_from_file_path() {
+        assert_eq!(
+            split_id_from_file_path(Path::new("split-0.split")).unwrap(),
+            "split-0"
+        );
+        assert!(split_id_from_file_path(Path::new("split-0.json")).is_none());
+        assert!(split_id_from_file_path(Path::new("split-0")).is_none());
+        assert!(split_id_from_file_path(Path::new("scratch/split-0.split")).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_run_orphan_files_scan() {
+        let mut metastore = metastore_for_test();
+
+        let index_id = "test-run-orphan-files-scan--index";
+        let index_uri = format!("ram:///indexes/{index_id}");
+        let index_config = IndexConfig::for_test(index_id, &index_uri);
+        let create_index_request = CreateIndexRequest::try_from_index_config(index_config).unwrap();
+        let index_uid: IndexUid = metastore
+            .create_index(create_index_request)
+            .await
+            .unwrap()
+            .index_uid
+            .into();
+
+        let split_metadata = SplitMetadata {
+            split_id: "split-known".to_string(),
+            index_uid: index_uid.clone(),
+            ..Default::default()
+        };
+        let stage_splits_request =
+            StageSplitsRequest::try_from_split_metadata(index_uid.clone(), split_metadata).unwrap();
+        metastore.stage_splits(stage_splits_request).await.unwrap();
+
+        let now_timestamp = OffsetDateTime::now_utc().unix_timestamp();
+        let file_entries = vec![
+            StorageFileEntry {
+                path: PathBuf::from("split-known.split"),
+                num_bytes: 1,
+                last_modified_timestamp_opt: Some(now_timestamp - 7_200),
+            },
+            StorageFileEntry {
+                path: PathBuf::from("split-known.docids"),
+                num_bytes: 1,
+                last_modified_timestamp_opt: Some(now_timestamp - 7_200),
+            },
+            StorageFileEntry {
+                path: PathBuf::from("split-old-orphan.split"),
+                num_bytes: 2,
+                last_modified_timestamp_opt: Some(now_timestamp - 7_200),
+            },
+            StorageFileEntry {
+                path: PathBuf::from("split-old-orphan.docids"),
+                num_bytes: 2,
+                last_modified_timestamp_opt: Some(now_timestamp - 7_200),
+            },
+            StorageFileEntry {
+                path: PathBuf::from("split-new-orphan.split"),
+                num_bytes: 3,
+                last_modified_timestamp_opt: Some(now_timestamp),
+            },
+            StorageFileEntry {
+                path: PathBuf::from("split-undated-orphan.split"),
+                num_bytes: 4,
+                last_modified_timestamp_opt: None,
+            },
+            StorageFileEntry {
+                path: PathBuf::from("delete-task.json"),
+                num_bytes: 5,
+                last_modified_timestamp_opt: Some(now_timestamp - 7_200),
+            },
+        ];
+        let mut mock_storage = MockStorage::new();
+        mock_storage
+            .expect_list()
+            .times(2)
+            .returning(move |prefix| {
+                assert_eq!(prefix, Path::new(""));
+                Ok(file_entries.clone())
+            });
+        mock_storage
+            .expect_bulk_delete()
+            .return_once(|paths: &[&Path]| {
+                assert_eq!(
+                    paths,
+                    [
+                        Path::new("split-old-orphan.docids"),
+                        Path::new("split-old-orphan.split")
+                    ]
+                );
+                Ok(())
+            });
+        let storage = Arc::new(mock_storage);
+
+        // Without grace period, the orphan files are only reported.
+        let scan_info = run_orphan_files_scan(
+            index_uid.clone(),
+            storage.clone(),
+            metastore.clone(),
+            &[],
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        let orphan_paths: Vec<&Path> = scan_info
+            .orphan_files
+            .iter()
+            .map(|file_entry| file_entry.path.as_path())
+            .collect();
+        assert_eq!(
+            orphan_paths,
+            [
+                Path::new("split-new-orphan.split"),
+                Path::new("split-old-orphan.docids"),
+                Path::new("split-old-orphan.split"),
+                Path::new("split-undated-orphan.split"),
+            ]
+        );
+        assert!(scan_info.deleted_files.is_empty());
+        assert!(scan_info.failed_files.is_empty());
+
+        // Only the orphan files older than the grace period are deleted.
+        let scan_info = run_orphan_files_scan(
+            index_uid,
+            storage,
+            metastore,
+            &[],
+            Some(Duration::from_secs(3_600)),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(scan_info.orphan_files.len(), 4);
+        assert_eq!(
+            scan_info.deleted_files,
+            [
+                PathBuf::from("split-old-orphan.docids"),
+                PathBuf::from("split-old-orphan.split")
+            ]
+        );
+        assert!(scan_info.failed_files.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_run_orphan_files_scan_keeps_files_of_shared_splits() {
+        let mut metastore = metastore_for_test();
+
+        let index_uri = "ram:///indexes/test-run-orphan-files-scan-shared--index";
+        let mut index_uids = Vec::new();
+
+        for index_id in [
+            "test-run-orphan-files-scan-shared--index",
+            "test-run-orphan-files-scan-shared--clone",
+        ] {
+            let index_config = IndexConfig::for_test(index_id, index_uri);
+            let create_index_request =
+                CreateIndexRequest::try_from_index_config(index_config).unwrap();
+            let index_uid: IndexUid = metastore
+                .create_index(create_index_request)
+                .await
+                .unwrap()
+                .index_uid
+                .into();
+            index_uids.push(index_uid);
+        }
+        let split_metadata = SplitMetadata {
+            split_id: "split-shared".to_string(),
+            index_uid: index_uids[1].clone(),
+            ..Default::default()
+        };
+        let stage_splits_request =
+            StageSplitsRequest::try_from_split_metadata(index_uids[1].clone(), split_metadata)
+                .unwrap();
+        metastore.stage_splits(stage_splits_request).await.unwrap();
+
+        let mut mock_storage = MockStorage::new();
+        mock_storage.expect_list().return_once(|_| {
+            Ok(vec![StorageFileEntry {
+                path: PathBuf::from("split-shared.split"),
+                num_bytes: 1,
+                last_modified_timestamp_opt: Some(0),
+            }])
+        });
+        mock_storage.expect_bulk_delete().never();
+        let storage = Arc::new(mock_storage);
+
+        let scan_info = run_orphan_files_scan(
+            index_uids[0].clone(),
+            storage,
+            metastore,
+            &index_uids[1..],
+            Some(Duration::ZERO),
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(scan_info.orphan_files.is_empty());
+        assert!(scan_info.deleted_files.is_empty());
+    }
+
     #[tokio::test]
     async fn test_delete_splits_from_storage_and_metastore_happy_path() {
         let storage = storage_for_test();
@@ -641,6 +1175,7 @@ mod tests {
             index_uid.clone(),
             storage.clone(),
             metastore.clone(),
+            &[],
             vec![split_metadata],
             None,
         )
@@ -738,6 +1273,7 @@ mod tests {
             index_uid.clone(),
             storage.clone(),
             metastore.clone(),
+            &[],
             vec![split_metadata_0, split_metadata_1],
             None,
         )
@@ -811,6 +1347,7 @@ mod tests {
             index_uid.clone(),
             storage.clone(),
             MetastoreServiceClient::from(mock_metastore),
+            &[],
             vec![split_metadata_0, split_metadata_1],
             None,
         )
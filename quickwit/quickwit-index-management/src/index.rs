@@ -17,37 +17,60 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
+use futures::{stream, StreamExt};
 use quickwit_common::fs::{empty_dir, get_cache_directory_path};
-use quickwit_config::{validate_identifier, IndexConfig, SourceConfig};
+use quickwit_common::split_file;
+use quickwit_common::uri::Uri;
+use quickwit_config::{
+    validate_identifier, DocMapping, IndexConfig, RetentionPolicy, SearchSettings, SourceConfig,
+};
 use quickwit_indexing::check_source_connectivity;
+use quickwit_metastore::checkpoint::{IndexCheckpointDelta, SourceCheckpointDelta};
 use quickwit_metastore::{
     AddSourceRequestExt, CreateIndexRequestExt, IndexMetadata, IndexMetadataResponseExt,
-    ListSplitsQuery, ListSplitsRequestExt, MetastoreServiceStreamSplitsExt, SplitInfo,
-    SplitMetadata, SplitState,
+    ListIndexesMetadataResponseExt, ListSplitsQuery, ListSplitsRequestExt,
+    MetastoreServiceStreamSplitsExt, SplitInfo, SplitMetadata, SplitState, StageSplitsRequestExt,
 };
 use quickwit_proto::metastore::{
-    AddSourceRequest, CreateIndexRequest, DeleteIndexRequest, EntityKind, IndexMetadataRequest,
-    ListSplitsRequest, MarkSplitsForDeletionRequest, MetastoreError, MetastoreService,
-    MetastoreServiceClient, ResetSourceCheckpointRequest,
+    AddSourceRequest, CreateIndexRequest, DeleteIndexRequest, EntityKind, FreezeIndexRequest,
+    IndexMetadataRequest, ListIndexesMetadataRequest, ListSplitsRequest,
+    MarkSplitsForDeletionRequest, MetastoreError, MetastoreResult, MetastoreService,
+    MetastoreServiceClient, PublishSplitsRequest, ResetSourceCheckpointRequest, StageSplitsRequest,
 };
-use quickwit_proto::types::{IndexUid, SplitId};
+use quickwit_proto::types::{IndexUid, Position, SplitId};
 use quickwit_proto::{ServiceError, ServiceErrorCode};
-use quickwit_storage::{StorageResolver, StorageResolverError};
+use quickwit_storage::{
+    generate_data_key, StorageError, StorageErrorKind, StorageResolver, StorageResolverError,
+};
 use thiserror::Error;
+use time::OffsetDateTime;
 use tracing::{error, info};
 
 use crate::garbage_collection::{
-    delete_splits_from_storage_and_metastore, run_garbage_collect, DeleteSplitsError,
-    SplitRemovalInfo,
+    delete_splits_from_storage_and_metastore, find_indexes_sharing_storage, run_garbage_collect,
+    DeleteSplitsError, SplitRemovalInfo,
+};
+use crate::snapshot::{
+    copy_file, is_checkpoint_restorable, snapshot_split_path, IndexSnapshot, IndexSnapshotSummary,
+    INDEX_SNAPSHOT_MANIFEST_FILE_NAME,
 };
 
+/// Maximum number of attempts to read the metadata and splits of an index without a concurrent
+/// publish in between.
+const MAX_SNAPSHOT_ATTEMPTS: usize = 5;
+
+/// Maximum number of split files copied concurrently by a snapshot or a restore.
+const MAX_CONCURRENT_SPLIT_FILE_COPIES: usize = 8;
+
 #[derive(Error, Debug)]
 pub enum IndexServiceError {
     #[error("failed to resolve the storage `{0}`")]
     Storage(#[from] StorageResolverError),
+    #[error("storage error `{0}`")]
+    StorageError(#[from] StorageError),
     #[error("metastore error `{0}`")]
     Metastore(#[from] MetastoreError),
     #[error("split deletion error `{0}`")]
@@ -72,6 +95,10 @@ impl ServiceError for IndexServiceError {
             Self::OperationNotAllowed(_) => ServiceErrorCode::MethodNotAllowed,
             Self::SplitDeletion(_) => ServiceErrorCode::Internal,
             Self::Storage(_) => ServiceErrorCode::Internal,
+            Self::StorageError(error) => match error.kind() {
+                StorageErrorKind::NotFound => ServiceErrorCode::NotFound,
+                _ => ServiceErrorCode::Internal,
+            },
         }
     }
 }
@@ -96,15 +123,56 @@ impl IndexService {
         self.metastore.clone()
     }
 
+    /// Loads the word lists referenced by URI in the custom tokenizers of `doc_mapping`, so that
+    /// the doc mapping stored in the metastore is self-contained: indexers and searchers build the
+    /// tokenizers without accessing the storage.
+    pub async fn load_tokenizer_word_lists(
+        &self,
+        doc_mapping: &mut DocMapping,
+    ) -> Result<(), IndexServiceError> {
+        for tokenizer_entry in &mut doc_mapping.tokenizers {
+            for stop_words_filter in tokenizer_entry.stop_words_filters_mut() {
+                let Some(word_list_uri) = stop_words_filter.uri.clone() else {
+                    continue;
+                };
+                let word_list =
+                    load_word_list(&self.storage_resolver, &word_list_uri)
+                        .await
+                        .map_err(|error| {
+                            IndexServiceError::InvalidConfig(error.context(format!(
+                                "failed to load stop words from `{word_list_uri}`"
+                            )))
+                        })?;
+                stop_words_filter.set_words_from_word_list(&word_list);
+            }
+        }
+        Ok(())
+    }
+
     /// Creates an index from `IndexConfig`.
     pub async fn create_index(
         &mut self,
-        index_config: IndexConfig,
+        mut index_config: IndexConfig,
         overwrite: bool,
     ) -> Result<IndexMetadata, IndexServiceError> {
         validate_storage_uri(&self.storage_resolver, &index_config)
             .await
             .map_err(IndexServiceError::InvalidConfig)?;
+        self.load_tokenizer_word_lists(&mut index_config.doc_mapping)
+            .await?;
+
+        if let Some(encryption_config) = &mut index_config.encryption {
+            if encryption_config.data_key.is_none() {
+                let data_key = generate_data_key(&encryption_config.master_key_id)
+                    .await
+                    .map_err(|error| {
+                        IndexServiceError::InvalidConfig(
+                            error.context("failed to generate the data key of the index"),
+                        )
+                    })?;
+                encryption_config.data_key = Some(data_key);
+            }
+        }
 
         // Delete existing index if it exists.
         if overwrite {
@@ -212,10 +280,12 @@ impl IndexService {
             .collect_splits_metadata()
             .await?;
 
+        let shared_index_uids = self.find_indexes_sharing_storage(&index_uid).await?;
         let deleted_splits = delete_splits_from_storage_and_metastore(
             index_uid.clone(),
             storage,
             self.metastore.clone(),
+            &shared_index_uids,
             splits_metadata_to_delete,
             None,
         )
@@ -251,11 +321,13 @@ impl IndexService {
             .storage_resolver
             .resolve(&index_config.index_uri)
             .await?;
+        let shared_index_uids = self.find_indexes_sharing_storage(&index_uid).await?;
 
         let deleted_entries = run_garbage_collect(
             index_uid,
             storage,
             self.metastore.clone(),
+            &shared_index_uids,
             grace_period,
             // deletion_grace_period of zero, so that a cli call directly deletes splits after
             // marking to be deleted.
@@ -268,6 +340,47 @@ impl IndexService {
         Ok(deleted_entries)
     }
 
+    /// Runs the garbage collection on the index right away, honoring the grace periods defined
+    /// in the `garbage_collection` settings of the index.
+    ///
+    /// * `index_id` - The target index Id.
+    pub async fn run_index_garbage_collection(
+        &mut self,
+        index_id: &str,
+    ) -> Result<SplitRemovalInfo, IndexServiceError> {
+        let index_metadata_request = IndexMetadataRequest::for_index_id(index_id.to_string());
+        let index_metadata = self
+            .metastore
+            .index_metadata(index_metadata_request)
+            .await?
+            .deserialize_index_metadata()?;
+        let index_uid = index_metadata.index_uid.clone();
+        let index_config = index_metadata.into_index_config();
+        let storage = self
+            .storage_resolver
+            .resolve(&index_config.index_uri)
+            .await?;
+        let gc_settings = index_config.gc_settings;
+        let shared_index_uids = self.find_indexes_sharing_storage(&index_uid).await?;
+
+        let removal_info = run_garbage_collect(
+            index_uid,
+            storage,
+            self.metastore.clone(),
+            &shared_index_uids,
+            gc_settings.staged_grace_period,
+            gc_settings.deletion_grace_period,
+            false,
+            None,
+        )
+        .await
+        .map_err(|error| match error.downcast::<MetastoreError>() {
+            Ok(metastore_error) => IndexServiceError::Metastore(metastore_error),
+            Err(error) => IndexServiceError::Internal(format!("{error:#}")),
+        })?;
+        Ok(removal_info)
+    }
+
     /// Clears the index by applying the following actions:
     /// - mark all splits for deletion in the metastore.
     /// - delete the files of all splits marked for deletion using garbage collection.
@@ -305,11 +418,13 @@ impl IndexService {
         self.metastore
             .mark_splits_for_deletion(mark_splits_for_deletion_request)
             .await?;
+        let shared_index_uids = self.find_indexes_sharing_storage(&index_uid).await?;
         // FIXME: return an error.
         if let Err(err) = delete_splits_from_storage_and_metastore(
             index_uid.clone(),
             storage,
             self.metastore.clone(),
+            &shared_index_uids,
             splits_metadata,
             None,
         )
@@ -394,6 +509,418 @@ impl IndexService {
 
         Ok(source_config)
     }
+
+    /// Takes a snapshot of the index specified with `index_id` and writes it to `output_uri`.
+    ///
+    /// The snapshot captures the config, sources, and checkpoints of the index along with its
+    /// published splits. If `copy_split_files` is set, the split files are copied into the
+    /// snapshot. Otherwise, the snapshot references the split files stored under the URI of the
+    /// index, so they must not be deleted before the snapshot is restored.
+    ///
+    /// * `index_id` - The target index Id.
+    /// * `output_uri` - URI of the directory the snapshot is written to.
+    /// * `copy_split_files` - Whether to copy the split files into the snapshot.
+    pub async fn snapshot_index(
+        &mut self,
+        index_id: &str,
+        output_uri: &Uri,
+        copy_split_files: bool,
+    ) -> Result<IndexSnapshotSummary, IndexServiceError> {
+        let output_storage = self.storage_resolver.resolve(output_uri).await?;
+        let manifest_path = Path::new(INDEX_SNAPSHOT_MANIFEST_FILE_NAME);
+
+        if output_storage.exists(manifest_path).await? {
+            return Err(IndexServiceError::OperationNotAllowed(format!(
+                "a snapshot already exists at `{output_uri}`"
+            )));
+        }
+        let (index_metadata, splits) = self.fetch_index_metadata_and_splits(index_id).await?;
+        let num_docs = splits.iter().map(|split| split.num_docs).sum();
+        let mut num_copied_bytes = 0;
+
+        if copy_split_files {
+            let index_storage = self
+                .storage_resolver
+                .resolve(index_metadata.index_uri())
+                .await?;
+            let index_storage = &index_storage;
+            let output_storage = &output_storage;
+            let mut copy_stream = stream::iter(splits.iter().map(|split| async move {
+                let split_path = PathBuf::from(split_file(&split.split_id));
+                let snapshot_split_path = snapshot_split_path(&split.split_id);
+                copy_file(
+                    index_storage.as_ref(),
+                    &split_path,
+                    output_storage.as_ref(),
+                    &snapshot_split_path,
+                )
+                .await
+            }))
+            .buffer_unordered(MAX_CONCURRENT_SPLIT_FILE_COPIES);
+
+            while let Some(num_bytes_res) = copy_stream.next().await {
+                num_copied_bytes += num_bytes_res?;
+            }
+        }
+        let summary = IndexSnapshotSummary {
+            index_id: index_id.to_string(),
+            snapshot_uri: output_uri.to_string(),
+            num_splits: splits.len(),
+            num_docs,
+            num_copied_bytes,
+        };
+        let snapshot = IndexSnapshot {
+            index_metadata,
+            splits,
+            split_files_copied: copy_split_files,
+            create_timestamp: OffsetDateTime::now_utc().unix_timestamp(),
+        };
+        let snapshot_json = serde_json::to_vec_pretty(&snapshot).map_err(|error| {
+            IndexServiceError::Internal(format!("failed to serialize snapshot: {error}"))
+        })?;
+        // The manifest is written last so that a snapshot is never observed half-written.
+        output_storage
+            .put(manifest_path, Box::new(snapshot_json))
+            .await?;
+        info!(
+            index_id=%index_id,
+            snapshot_uri=%output_uri,
+            num_splits=summary.num_splits,
+            "index snapshot successfully taken"
+        );
+        Ok(summary)
+    }
+
+    /// Restores an index from the snapshot stored at `snapshot_uri`.
+    ///
+    /// The index is created with the config and sources of the snapshot, then its splits are
+    /// published along with the checkpoints of its sources. The checkpoints of the ingest sources
+    /// are not restored because they refer to the queues of the original cluster. The split files
+    /// are copied into the storage of the index unless the snapshot references them and the index
+    /// is restored at its original URI.
+    ///
+    /// * `snapshot_uri` - URI of the directory of the snapshot.
+    /// * `index_uri_opt` - URI of the restored index, if different from the original one.
+    pub async fn restore_index(
+        &mut self,
+        snapshot_uri: &Uri,
+        index_uri_opt: Option<Uri>,
+    ) -> Result<IndexMetadata, IndexServiceError> {
+        let snapshot_storage = self.storage_resolver.resolve(snapshot_uri).await?;
+        let snapshot_json = snapshot_storage
+            .get_all(Path::new(INDEX_SNAPSHOT_MANIFEST_FILE_NAME))
+            .await?;
+        let snapshot: IndexSnapshot = serde_json::from_slice(&snapshot_json).map_err(|error| {
+            IndexServiceError::InvalidConfig(anyhow::anyhow!(
+                "failed to parse snapshot `{snapshot_uri}`: {error}"
+            ))
+        })?;
+        let IndexSnapshot {
+            index_metadata: snapshot_index_metadata,
+            splits,
+            split_files_copied,
+            ..
+        } = snapshot;
+
+        let mut index_config = snapshot_index_metadata.index_config.clone();
+
+        if let Some(index_uri) = index_uri_opt {
+            index_config.index_uri = index_uri;
+        }
+        validate_storage_uri(&self.storage_resolver, &index_config)
+            .await
+            .map_err(IndexServiceError::InvalidConfig)?;
+        let index_storage = self
+            .storage_resolver
+            .resolve(&index_config.index_uri)
+            .await?;
+        let index_id = index_config.index_id.clone();
+        let restore_split_files =
+            split_files_copied || index_config.index_uri != *snapshot_index_metadata.index_uri();
+
+        let mut metastore = self.metastore.clone();
+        let create_index_request = CreateIndexRequest::try_from_index_config(index_config)?;
+        let index_uid: IndexUid = metastore
+            .create_index(create_index_request)
+            .await?
+            .index_uid
+            .into();
+        let mut source_configs: Vec<&SourceConfig> =
+            snapshot_index_metadata.sources.values().collect();
+        source_configs.sort_by(|left, right| left.source_id.cmp(&right.source_id));
+
+        for source_config in source_configs {
+            let add_source_request =
+                AddSourceRequest::try_from_source_config(index_uid.clone(), source_config.clone())?;
+            metastore.add_source(add_source_request).await?;
+        }
+        // The splits are staged before their files are copied so that the garbage collector
+        // cleans up after a restore that fails midway.
+        let split_ids: Vec<SplitId> = splits.iter().map(|split| split.split_id.clone()).collect();
+
+        if !splits.is_empty() {
+            let splits_metadata: Vec<SplitMetadata> = splits
+                .into_iter()
+                .map(|mut split| {
+                    split.index_uid = index_uid.clone();
+                    split
+                })
+                .collect();
+            let stage_splits_request =
+                StageSplitsRequest::try_from_splits_metadata(index_uid.clone(), splits_metadata)?;
+            metastore.stage_splits(stage_splits_request).await?;
+        }
+        if restore_split_files && !split_ids.is_empty() {
+            let src_storage = if split_files_copied {
+                snapshot_storage
+            } else {
+                self.storage_resolver
+                    .resolve(snapshot_index_metadata.index_uri())
+                    .await?
+            };
+            let src_storage = &src_storage;
+            let index_storage = &index_storage;
+            let mut copy_stream = stream::iter(split_ids.iter().map(|split_id| async move {
+                let src_split_path = if split_files_copied {
+                    snapshot_split_path(split_id)
+                } else {
+                    PathBuf::from(split_file(split_id))
+                };
+                let split_path = PathBuf::from(split_file(split_id));
+                copy_file(
+                    src_storage.as_ref(),
+                    &src_split_path,
+                    index_storage.as_ref(),
+                    &split_path,
+                )
+                .await
+            }))
+            .buffer_unordered(MAX_CONCURRENT_SPLIT_FILE_COPIES);
+
+            while let Some(num_bytes_res) = copy_stream.next().await {
+                num_bytes_res?;
+            }
+        }
+        let mut checkpoint_deltas = Vec::new();
+        let mut source_ids: Vec<&String> = snapshot_index_metadata.sources.keys().collect();
+        source_ids.sort();
+
+        for source_id in source_ids {
+            if !is_checkpoint_restorable(source_id) {
+                continue;
+            }
+            let Some(source_checkpoint) = snapshot_index_metadata
+                .checkpoint
+                .source_checkpoint(source_id)
+            else {
+                continue;
+            };
+            let mut source_delta = SourceCheckpointDelta::default();
+
+            for (partition_id, position) in source_checkpoint.iter() {
+                if position == Position::Beginning {
+                    continue;
+                }
+                source_delta
+                    .record_partition_delta(partition_id, Position::Beginning, position)
+                    .map_err(|error| {
+                        IndexServiceError::Internal(format!(
+                            "failed to restore checkpoint of source `{source_id}`: {error}"
+                        ))
+                    })?;
+            }
+            if !source_delta.is_empty() {
+                checkpoint_deltas.push(IndexCheckpointDelta {
+                    source_id: source_id.clone(),
+                    source_delta,
+                });
+            }
+        }
+        // A publish request carries the checkpoint delta of a single source, so the splits are
+        // published along with the first delta and the other deltas are published on their own.
+        let mut staged_split_ids = split_ids;
+        let mut checkpoint_deltas = checkpoint_deltas.into_iter();
+
+        loop {
+            let checkpoint_delta_opt = checkpoint_deltas.next();
+
+            if staged_split_ids.is_empty() && checkpoint_delta_opt.is_none() {
+                break;
+            }
+            let index_checkpoint_delta_json_opt = checkpoint_delta_opt
+                .map(|checkpoint_delta| serde_json::to_string(&checkpoint_delta))
+                .transpose()
+                .map_err(|error| {
+                    IndexServiceError::Internal(format!(
+                        "failed to serialize checkpoint delta: {error}"
+                    ))
+                })?;
+            let publish_splits_request = PublishSplitsRequest {
+                index_uid: index_uid.to_string(),
+                staged_split_ids: std::mem::take(&mut staged_split_ids),
+                replaced_split_ids: Vec::new(),
+                index_checkpoint_delta_json_opt,
+                publish_token_opt: None,
+            };
+            metastore.publish_splits(publish_splits_request).await?;
+        }
+        if snapshot_index_metadata.frozen {
+            let freeze_index_request = FreezeIndexRequest {
+                index_uid: index_uid.to_string(),
+            };
+            metastore.freeze_index(freeze_index_request).await?;
+        }
+        info!(
+            index_id=%index_id,
+            snapshot_uri=%snapshot_uri,
+            "index successfully restored from snapshot"
+        );
+        let index_metadata_request = IndexMetadataRequest::for_index_uid(index_uid);
+        let index_metadata = metastore
+            .index_metadata(index_metadata_request)
+            .await?
+            .deserialize_index_metadata()?;
+        Ok(index_metadata)
+    }
+
+    /// Clones the index `index_id` into a new index `clone_index_id` without copying any data.
+    ///
+    /// The clone has the config of the index, optionally with different search settings and
+    /// retention policy, and shares its storage URI: the published splits of the index are
+    /// published in the clone as well and keep referencing the same split files. The garbage
+    /// collector only deletes a split file once no index references it anymore. The sources of the
+    /// index are not cloned, the clone only has the default ingest sources.
+    ///
+    /// * `index_id` - The target index Id.
+    /// * `clone_index_id` - The Id of the clone.
+    /// * `search_settings_opt` - Search settings of the clone, if different from the index's.
+    /// * `retention_policy_opt` - Retention policy of the clone, if different from the index's.
+    pub async fn clone_index(
+        &mut self,
+        index_id: &str,
+        clone_index_id: &str,
+        search_settings_opt: Option<SearchSettings>,
+        retention_policy_opt: Option<RetentionPolicy>,
+    ) -> Result<IndexMetadata, IndexServiceError> {
+        validate_identifier("Index ID", clone_index_id).map_err(|_| {
+            IndexServiceError::InvalidIdentifier(format!("invalid index ID: `{clone_index_id}`"))
+        })?;
+        if let Some(retention_policy) = &retention_policy_opt {
+            retention_policy
+                .retention_period()
+                .and_then(|_| retention_policy.evaluation_schedule())
+                .map_err(IndexServiceError::InvalidConfig)?;
+        }
+        let (index_metadata, splits) = self.fetch_index_metadata_and_splits(index_id).await?;
+        let mut index_config = index_metadata.into_index_config();
+        index_config.index_id = clone_index_id.to_string();
+
+        if let Some(search_settings) = search_settings_opt {
+            index_config.search_settings = search_settings;
+        }
+        if retention_policy_opt.is_some() {
+            index_config.retention_policy = retention_policy_opt;
+        }
+        let clone_index_metadata = self.create_index(index_config, false).await?;
+        let clone_index_uid = clone_index_metadata.index_uid.clone();
+
+        if splits.is_empty() {
+            return Ok(clone_index_metadata);
+        }
+        let split_ids: Vec<SplitId> = splits.iter().map(|split| split.split_id.clone()).collect();
+        let splits_metadata: Vec<SplitMetadata> = splits
+            .into_iter()
+            .map(|mut split| {
+                split.index_uid = clone_index_uid.clone();
+                // The delete tasks of the index do not apply to the clone, whose delete tasks
+                // must be applied to all its splits.
+                split.delete_opstamp = 0;
+                split
+            })
+            .collect();
+        let stage_splits_request =
+            StageSplitsRequest::try_from_splits_metadata(clone_index_uid.clone(), splits_metadata)?;
+        self.metastore.stage_splits(stage_splits_request).await?;
+
+        let publish_splits_request = PublishSplitsRequest {
+            index_uid: clone_index_uid.to_string(),
+            staged_split_ids: split_ids,
+            replaced_split_ids: Vec::new(),
+            index_checkpoint_delta_json_opt: None,
+            publish_token_opt: None,
+        };
+        self.metastore
+            .publish_splits(publish_splits_request)
+            .await?;
+
+        info!(
+            index_id=%index_id,
+            clone_index_id=%clone_index_id,
+            "index successfully cloned"
+        );
+        let index_metadata_request = IndexMetadataRequest::for_index_uid(clone_index_uid);
+        let clone_index_metadata = self
+            .metastore
+            .index_metadata(index_metadata_request)
+            .await?
+            .deserialize_index_metadata()?;
+        Ok(clone_index_metadata)
+    }
+
+    /// Returns the UIDs of the indexes sharing the storage of the index `index_uid`, i.e. its
+    /// clones and the index it was cloned from.
+    async fn find_indexes_sharing_storage(
+        &mut self,
+        index_uid: &IndexUid,
+    ) -> MetastoreResult<Vec<IndexUid>> {
+        let indexes_metadata = self
+            .metastore
+            .list_indexes_metadata(ListIndexesMetadataRequest::all())
+            .await?
+            .deserialize_indexes_metadata()?;
+        Ok(find_indexes_sharing_storage(&indexes_metadata, index_uid))
+    }
+
+    /// Fetches the metadata and the published splits of an index such that the checkpoints of the
+    /// metadata account for exactly the published splits. The metadata is fetched again after the
+    /// splits, and the operation is retried if a publish happened in between.
+    async fn fetch_index_metadata_and_splits(
+        &mut self,
+        index_id: &str,
+    ) -> Result<(IndexMetadata, Vec<SplitMetadata>), IndexServiceError> {
+        let index_metadata_request = IndexMetadataRequest::for_index_id(index_id.to_string());
+        let mut index_metadata = self
+            .metastore
+            .index_metadata(index_metadata_request.clone())
+            .await?
+            .deserialize_index_metadata()?;
+
+        for _ in 0..MAX_SNAPSHOT_ATTEMPTS {
+            let query = ListSplitsQuery::for_index(index_metadata.index_uid.clone())
+                .with_split_state(SplitState::Published);
+            let list_splits_request = ListSplitsRequest::try_from_list_splits_query(query)?;
+            let splits: Vec<SplitMetadata> = self
+                .metastore
+                .list_splits(list_splits_request)
+                .await?
+                .collect_splits_metadata()
+                .await?;
+            let new_index_metadata = self
+                .metastore
+                .index_metadata(index_metadata_request.clone())
+                .await?
+                .deserialize_index_metadata()?;
+
+            if new_index_metadata == index_metadata {
+                return Ok((index_metadata, splits));
+            }
+            index_metadata = new_index_metadata;
+        }
+        Err(IndexServiceError::Internal(format!(
+            "failed to read the splits of index `{index_id}`: splits kept being published \
+             concurrently"
+        )))
+    }
 }
 
 /// Clears the cache directory of a given source.
@@ -407,6 +934,20 @@ pub async fn clear_cache_directory(data_dir_path: &Path) -> anyhow::Result<()> {
     Ok(())
 }
 
+async fn load_word_list(
+    storage_resolver: &StorageResolver,
+    word_list_uri: &Uri,
+) -> anyhow::Result<String> {
+    let (Some(parent_uri), Some(file_name)) = (word_list_uri.parent(), word_list_uri.file_name())
+    else {
+        anyhow::bail!("URI must point to a file");
+    };
+    let storage = storage_resolver.resolve(&parent_uri).await?;
+    let word_list_bytes = storage.get_all(file_name).await?;
+    let word_list = String::from_utf8(word_list_bytes.to_vec())?;
+    Ok(word_list)
+}
+
 /// Validates the storage URI by effectively resolving it.
 pub async fn validate_storage_uri(
     storage_resolver: &StorageResolver,
@@ -419,12 +960,8 @@ pub async fn validate_storage_uri(
 #[cfg(test)]
 mod tests {
 
-    use quickwit_common::uri::Uri;
-    use quickwit_config::IndexConfig;
-    use quickwit_metastore::{
-        metastore_for_test, MetastoreServiceExt, SplitMetadata, StageSplitsRequestExt,
-    };
-    use quickwit_proto::metastore::StageSplitsRequest;
+    use quickwit_config::{IndexConfig, SourceParams};
+    use quickwit_metastore::{metastore_for_test, MetastoreServiceExt};
     use quickwit_storage::PutPayload;
 
     use super::*;
@@ -468,6 +1005,61 @@ mod tests {
         assert!(index_metadata_0.index_uid != index_metadata_1.index_uid);
     }
 
+    #[tokio::test]
+    async fn test_create_index_loads_tokenizer_word_lists() {
+        let metastore = metastore_for_test();
+        let storage_resolver = StorageResolver::for_test();
+        let storage = storage_resolver
+            .resolve(&Uri::for_test("ram:///tokenizers"))
+            .await
+            .unwrap();
+        storage
+            .put(
+                Path::new("stop-words.txt"),
+                Box::new(b"# English stop words\nthe\nof\n".to_vec()),
+            )
+            .await
+            .unwrap();
+        let mut index_service = IndexService::new(metastore, storage_resolver);
+
+        let mut index_config = IndexConfig::for_test("test-index", "ram:///indexes/test-index");
+        index_config.doc_mapping.tokenizers.push(
+            serde_json::from_value(serde_json::json!({
+                "name": "no_stop_words",
+                "type": "simple",
+                "filters": [
+                    "lower_caser",
+                    {"stop_words": {"uri": "ram:///tokenizers/stop-words.txt"}}
+                ]
+            }))
+            .unwrap(),
+        );
+        let index_metadata = index_service
+            .create_index(index_config.clone(), false)
+            .await
+            .unwrap();
+        let tokenizer_json =
+            serde_json::to_value(&index_metadata.index_config.doc_mapping.tokenizers[0]).unwrap();
+        assert_eq!(
+            tokenizer_json["filters"][1]["stop_words"]["words"],
+            serde_json::json!(["the", "of"])
+        );
+
+        index_config.index_id = "test-index-missing-word-list".to_string();
+        index_config.index_uri = Uri::for_test("ram:///indexes/test-index-missing-word-list");
+        index_config.doc_mapping.tokenizers[0] = serde_json::from_value(serde_json::json!({
+            "name": "no_stop_words",
+            "type": "simple",
+            "filters": [{"stop_words": {"uri": "ram:///tokenizers/missing.txt"}}]
+        }))
+        .unwrap();
+        let error = index_service
+            .create_index(index_config, false)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, IndexServiceError::InvalidConfig(_)));
+    }
+
     #[tokio::test]
     async fn test_delete_index() {
         let mut metastore = metastore_for_test();
@@ -528,4 +1120,235 @@ mod tests {
         assert!(splits.is_empty());
         assert!(!storage.exists(split_path).await.unwrap());
     }
+
+    #[tokio::test]
+    async fn test_clone_index() {
+        let mut metastore = metastore_for_test();
+        let storage_resolver = StorageResolver::for_test();
+        let storage = storage_resolver
+            .resolve(&Uri::for_test("ram:///indexes/test-index"))
+            .await
+            .unwrap();
+        let mut index_service = IndexService::new(metastore.clone(), storage_resolver);
+        let index_config = IndexConfig::for_test("test-index", "ram:///indexes/test-index");
+        let index_uid = index_service
+            .create_index(index_config, false)
+            .await
+            .unwrap()
+            .index_uid;
+
+        let split_metadata = SplitMetadata {
+            split_id: "test-split".to_string(),
+            index_uid: index_uid.clone(),
+            num_docs: 10,
+            delete_opstamp: 3,
+            ..Default::default()
+        };
+        let stage_splits_request =
+            StageSplitsRequest::try_from_splits_metadata(index_uid.clone(), vec![split_metadata])
+                .unwrap();
+        metastore.stage_splits(stage_splits_request).await.unwrap();
+        let publish_splits_request = PublishSplitsRequest {
+            index_uid: index_uid.to_string(),
+            staged_split_ids: vec!["test-split".to_string()],
+            ..Default::default()
+        };
+        metastore
+            .publish_splits(publish_splits_request)
+            .await
+            .unwrap();
+        let split_path = Path::new("test-split.split");
+        storage
+            .put(split_path, Box::new(b"split-data".to_vec()))
+            .await
+            .unwrap();
+
+        let error = index_service
+            .clone_index("test-index", "invalid index id", None, None)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, IndexServiceError::InvalidIdentifier(_)));
+
+        let search_settings = SearchSettings {
+            default_search_fields: vec!["body".to_string()],
+            ..Default::default()
+        };
+        let clone_index_metadata = index_service
+            .clone_index(
+                "test-index",
+                "test-clone",
+                Some(search_settings.clone()),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(clone_index_metadata.index_id(), "test-clone");
+        assert_eq!(
+            clone_index_metadata.index_uri(),
+            &Uri::for_test("ram:///indexes/test-index")
+        );
+        assert_eq!(
+            clone_index_metadata.index_config.search_settings,
+            search_settings
+        );
+        let clone_index_uid = clone_index_metadata.index_uid.clone();
+        let clone_splits = metastore
+            .list_splits(ListSplitsRequest::try_from_index_uid(clone_index_uid).unwrap())
+            .await
+            .unwrap()
+            .collect_splits()
+            .await
+            .unwrap();
+        assert_eq!(clone_splits.len(), 1);
+        assert_eq!(clone_splits[0].split_state, SplitState::Published);
+        assert_eq!(clone_splits[0].split_metadata.split_id, "test-split");
+        assert_eq!(clone_splits[0].split_metadata.delete_opstamp, 0);
+
+        // The clone still references the split file, so it is kept.
+        let split_infos = index_service
+            .delete_index("test-index", false)
+            .await
+            .unwrap();
+        assert!(split_infos.is_empty());
+        assert!(storage.exists(split_path).await.unwrap());
+
+        let split_infos = index_service
+            .delete_index("test-clone", false)
+            .await
+            .unwrap();
+        assert_eq!(split_infos.len(), 1);
+        assert!(!storage.exists(split_path).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_and_restore_index() {
+        let mut metastore = metastore_for_test();
+        let storage_resolver = StorageResolver::for_test();
+        let mut index_service = IndexService::new(metastore.clone(), storage_resolver.clone());
+        let index_id = "test-index";
+        let index_config = IndexConfig::for_test(index_id, "ram:///indexes/test-index");
+        let index_uid = index_service
+            .create_index(index_config, false)
+            .await
+            .unwrap()
+            .index_uid;
+        let source_config = SourceConfig::for_test("test-source", SourceParams::void());
+        let add_source_request =
+            AddSourceRequest::try_from_source_config(index_uid.clone(), source_config).unwrap();
+        metastore.add_source(add_source_request).await.unwrap();
+
+        let split_metadata = SplitMetadata {
+            split_id: "test-split".to_string(),
+            index_uid: index_uid.clone(),
+            num_docs: 10,
+            ..Default::default()
+        };
+        let stage_splits_request =
+            StageSplitsRequest::try_from_splits_metadata(index_uid.clone(), vec![split_metadata])
+                .unwrap();
+        metastore.stage_splits(stage_splits_request).await.unwrap();
+        let checkpoint_delta = IndexCheckpointDelta::for_test("test-source", 0..10);
+        let publish_splits_request = PublishSplitsRequest {
+            index_uid: index_uid.to_string(),
+            staged_split_ids: vec!["test-split".to_string()],
+            index_checkpoint_delta_json_opt: Some(
+                serde_json::to_string(&checkpoint_delta).unwrap(),
+            ),
+            ..Default::default()
+        };
+        metastore
+            .publish_splits(publish_splits_request)
+            .await
+            .unwrap();
+
+        let index_storage = storage_resolver
+            .resolve(&Uri::for_test("ram:///indexes/test-index"))
+            .await
+            .unwrap();
+        index_storage
+            .put(
+                Path::new("test-split.split"),
+                Box::new(b"split-data".to_vec()),
+            )
+            .await
+            .unwrap();
+
+        let snapshot_uri = Uri::for_test("ram:///snapshots/test-index");
+        let summary = index_service
+            .snapshot_index(index_id, &snapshot_uri, true)
+            .await
+            .unwrap();
+        assert_eq!(summary.num_splits, 1);
+        assert_eq!(summary.num_docs, 10);
+        assert_eq!(summary.num_copied_bytes, 10);
+
+        let error = index_service
+            .snapshot_index(index_id, &snapshot_uri, true)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, IndexServiceError::OperationNotAllowed(_)));
+
+        // Restore the snapshot into another metastore.
+        let mut restore_metastore = metastore_for_test();
+        let mut restore_index_service =
+            IndexService::new(restore_metastore.clone(), storage_resolver.clone());
+        let restored_index_uri = Uri::for_test("ram:///indexes/restored-index");
+        let restored_index_metadata = restore_index_service
+            .restore_index(&snapshot_uri, Some(restored_index_uri.clone()))
+            .await
+            .unwrap();
+        assert_eq!(restored_index_metadata.index_id(), index_id);
+        assert_eq!(restored_index_metadata.index_uri(), &restored_index_uri);
+        assert!(restored_index_metadata.sources.contains_key("test-source"));
+
+        let original_index_metadata = metastore
+            .index_metadata(IndexMetadataRequest::for_index_id(index_id.to_string()))
+            .await
+            .unwrap()
+            .deserialize_index_metadata()
+            .unwrap();
+        assert_eq!(
+            restored_index_metadata
+                .checkpoint
+                .source_checkpoint("test-source"),
+            original_index_metadata
+                .checkpoint
+                .source_checkpoint("test-source")
+        );
+        let query = ListSplitsQuery::for_index(restored_index_metadata.index_uid.clone())
+            .with_split_state(SplitState::Published);
+        let restored_splits = restore_metastore
+            .list_splits(ListSplitsRequest::try_from_list_splits_query(query).unwrap())
+            .await
+            .unwrap()
+            .collect_splits_metadata()
+            .await
+            .unwrap();
+        assert_eq!(restored_splits.len(), 1);
+        assert_eq!(restored_splits[0].split_id, "test-split");
+        assert_eq!(
+            restored_splits[0].index_uid,
+            restored_index_metadata.index_uid
+        );
+
+        let restored_index_storage = storage_resolver.resolve(&restored_index_uri).await.unwrap();
+        assert_eq!(
+            restored_index_storage
+                .get_all(Path::new("test-split.split"))
+                .await
+                .unwrap()
+                .as_slice(),
+            b"split-data"
+        );
+
+        // Restoring an index that already exists fails.
+        let error = restore_index_service
+            .restore_index(&snapshot_uri, Some(restored_index_uri))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            IndexServiceError::Metastore(MetastoreError::AlreadyExists(_))
+        ));
+    }
 }
@@ -19,6 +19,10 @@
 
 mod garbage_collection;
 mod index;
+mod snapshot;
 
-pub use garbage_collection::run_garbage_collect;
+pub use garbage_collection::{
+    run_garbage_collect, run_orphan_files_scan, OrphanFilesScanInfo, SplitRemovalInfo,
+};
 pub use index::{clear_cache_directory, validate_storage_uri, IndexService, IndexServiceError};
+pub use snapshot::{IndexSnapshot, IndexSnapshotSummary, INDEX_SNAPSHOT_MANIFEST_FILE_NAME};
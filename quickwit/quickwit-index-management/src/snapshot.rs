@@ -0,0 +1,206 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::path::{Path, PathBuf};
+
+use quickwit_common::split_file;
+use quickwit_config::{CLI_INGEST_SOURCE_ID, INGEST_API_SOURCE_ID, INGEST_SOURCE_ID};
+use quickwit_metastore::{IndexMetadata, SplitMetadata};
+use quickwit_storage::{FilePayload, PutPayload, Storage, StorageResult};
+use serde::{Deserialize, Serialize};
+
+/// Name of the manifest file stored at the root of a snapshot.
+pub const INDEX_SNAPSHOT_MANIFEST_FILE_NAME: &str = "snapshot.json";
+
+/// Directory of the snapshot into which the split files are copied.
+const SNAPSHOT_SPLITS_DIR_NAME: &str = "splits";
+
+/// Snapshot of an index: its config, sources, checkpoints, and published splits.
+///
+/// The snapshot does not depend on the metastore backend it was taken from, so it can be restored
+/// into a cluster using a different backend.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(into = "VersionedIndexSnapshot")]
+#[serde(from = "VersionedIndexSnapshot")]
+pub struct IndexSnapshot {
+    /// Metadata of the index, including its config, sources, and checkpoints.
+    pub index_metadata: IndexMetadata,
+    /// Metadata of the published splits of the index.
+    pub splits: Vec<SplitMetadata>,
+    /// Whether the split files were copied into the snapshot. Otherwise, the snapshot references
+    /// the split files stored under the URI of the index.
+    pub split_files_copied: bool,
+    /// Time at which the snapshot was taken.
+    pub create_timestamp: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "version")]
+enum VersionedIndexSnapshot {
+    #[serde(rename = "0.6")]
+    V0_6(IndexSnapshotV0_6),
+}
+
+#[derive(Serialize, Deserialize)]
+struct IndexSnapshotV0_6 {
+    index_metadata: IndexMetadata,
+    splits: Vec<SplitMetadata>,
+    split_files_copied: bool,
+    create_timestamp: i64,
+}
+
+impl From<IndexSnapshot> for VersionedIndexSnapshot {
+    fn from(snapshot: IndexSnapshot) -> Self {
+        VersionedIndexSnapshot::V0_6(IndexSnapshotV0_6 {
+            index_metadata: snapshot.index_metadata,
+            splits: snapshot.splits,
+            split_files_copied: snapshot.split_files_copied,
+            create_timestamp: snapshot.create_timestamp,
+        })
+    }
+}
+
+impl From<VersionedIndexSnapshot> for IndexSnapshot {
+    fn from(versioned_snapshot: VersionedIndexSnapshot) -> Self {
+        match versioned_snapshot {
+            VersionedIndexSnapshot::V0_6(snapshot) => IndexSnapshot {
+                index_metadata: snapshot.index_metadata,
+                splits: snapshot.splits,
+                split_files_copied: snapshot.split_files_copied,
+                create_timestamp: snapshot.create_timestamp,
+            },
+        }
+    }
+}
+
+/// Summary of a snapshot, returned once it has been taken.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct IndexSnapshotSummary {
+    /// ID of the snapshotted index.
+    pub index_id: String,
+    /// URI of the snapshot.
+    pub snapshot_uri: String,
+    /// Number of published splits in the snapshot.
+    pub num_splits: usize,
+    /// Number of documents in the snapshot.
+    pub num_docs: usize,
+    /// Number of bytes of split files copied into the snapshot.
+    pub num_copied_bytes: u64,
+}
+
+/// Returns the path, relative to the root of the snapshot, of a split file copied into the
+/// snapshot.
+pub(crate) fn snapshot_split_path(split_id: &str) -> PathBuf {
+    Path::new(SNAPSHOT_SPLITS_DIR_NAME).join(split_file(split_id))
+}
+
+/// Returns whether the checkpoint of a source can be restored into another cluster. The
+/// positions of the ingest sources refer to the queues and shards of the original cluster, so
+/// they are not restored.
+pub(crate) fn is_checkpoint_restorable(source_id: &str) -> bool {
+    ![CLI_INGEST_SOURCE_ID, INGEST_API_SOURCE_ID, INGEST_SOURCE_ID].contains(&source_id)
+}
+
+/// Copies a file from one storage to another through a local temporary file. Returns the number
+/// of bytes copied.
+pub(crate) async fn copy_file(
+    src_storage: &dyn Storage,
+    src_path: &Path,
+    dst_storage: &dyn Storage,
+    dst_path: &Path,
+) -> StorageResult<u64> {
+    let temp_dir = tempfile::tempdir()?;
+    let temp_file_path = temp_dir.path().join("file");
+    src_storage.copy_to_file(src_path, &temp_file_path).await?;
+    let file_payload = FilePayload::open(&temp_file_path)?;
+    let num_bytes = file_payload.len();
+    dst_storage.put(dst_path, Box::new(file_payload)).await?;
+    Ok(num_bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_config::IndexConfig;
+    use quickwit_storage::RamStorage;
+
+    use super::*;
+
+    #[test]
+    fn test_index_snapshot_serde() {
+        let index_config = IndexConfig::for_test("test-index", "ram:///indexes/test-index");
+        let index_metadata = IndexMetadata::new(index_config);
+        let split_metadata = SplitMetadata {
+            split_id: "test-split".to_string(),
+            index_uid: index_metadata.index_uid.clone(),
+            num_docs: 10,
+            ..Default::default()
+        };
+        let snapshot = IndexSnapshot {
+            index_metadata,
+            splits: vec![split_metadata],
+            split_files_copied: true,
+            create_timestamp: 1_700_000_000,
+        };
+        let snapshot_json = serde_json::to_value(&snapshot).unwrap();
+        assert_eq!(snapshot_json["version"], "0.6");
+
+        let deserialized_snapshot: IndexSnapshot = serde_json::from_value(snapshot_json).unwrap();
+        assert_eq!(
+            deserialized_snapshot.index_metadata,
+            snapshot.index_metadata
+        );
+        assert_eq!(deserialized_snapshot.splits, snapshot.splits);
+        assert!(deserialized_snapshot.split_files_copied);
+        assert_eq!(deserialized_snapshot.create_timestamp, 1_700_000_000);
+    }
+
+    #[test]
+    fn test_is_checkpoint_restorable() {
+        assert!(is_checkpoint_restorable("kafka-source"));
+        assert!(!is_checkpoint_restorable(CLI_INGEST_SOURCE_ID));
+        assert!(!is_checkpoint_restorable(INGEST_API_SOURCE_ID));
+        assert!(!is_checkpoint_restorable(INGEST_SOURCE_ID));
+    }
+
+    #[tokio::test]
+    async fn test_copy_file() {
+        let src_storage = RamStorage::default();
+        let dst_storage = RamStorage::default();
+        src_storage
+            .put(Path::new("foo.split"), Box::new(b"split-data".to_vec()))
+            .await
+            .unwrap();
+        let dst_path = snapshot_split_path("foo");
+        assert_eq!(dst_path, Path::new("splits/foo.split"));
+
+        let num_bytes = copy_file(
+            &src_storage,
+            Path::new("foo.split"),
+            &dst_storage,
+            &dst_path,
+        )
+        .await
+        .unwrap();
+        assert_eq!(num_bytes, 10);
+        assert_eq!(
+            dst_storage.get_all(&dst_path).await.unwrap().as_slice(),
+            b"split-data"
+        );
+    }
+}
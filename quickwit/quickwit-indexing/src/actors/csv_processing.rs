@@ -0,0 +1,170 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use quickwit_config::CsvConfig;
+use quickwit_doc_mapper::JsonObject;
+use serde_json::Value as JsonValue;
+
+use super::doc_processor::DocProcessorError;
+
+/// Parses CSV records, one per raw document, into JSON objects. The first record is the header
+/// row: it names the fields of the subsequent records.
+pub(super) struct CsvParser {
+    delimiter: char,
+    header_opt: Option<Vec<String>>,
+}
+
+impl CsvParser {
+    pub fn new(csv_config: CsvConfig) -> Self {
+        Self {
+            delimiter: csv_config.delimiter,
+            header_opt: None,
+        }
+    }
+
+    /// Returns whether the header row has not been received yet.
+    pub fn is_missing_header(&self) -> bool {
+        self.header_opt.is_none()
+    }
+
+    pub fn set_header(&mut self, raw_record: &[u8]) -> Result<(), DocProcessorError> {
+        let field_names = split_record(raw_record, self.delimiter)?;
+
+        if field_names.iter().any(|field_name| field_name.is_empty()) {
+            return Err(DocProcessorError::Parse(
+                "CSV header contains an empty field name".to_string(),
+            ));
+        }
+        self.header_opt = Some(field_names);
+        Ok(())
+    }
+
+    /// Parses a record into a JSON object mapping the field names of the header to the values of
+    /// the record. Empty values are omitted. Values are kept as strings: the doc mapper coerces
+    /// them into the types of the fields.
+    pub fn parse_record(&self, raw_record: &[u8]) -> Result<JsonObject, DocProcessorError> {
+        let Some(field_names) = &self.header_opt else {
+            return Err(DocProcessorError::Parse(
+                "CSV record received before the header row".to_string(),
+            ));
+        };
+        let values = split_record(raw_record, self.delimiter)?;
+
+        if values.len() != field_names.len() {
+            return Err(DocProcessorError::Parse(format!(
+                "CSV record has {} fields but the header has {}",
+                values.len(),
+                field_names.len()
+            )));
+        }
+        let mut json_obj = JsonObject::with_capacity(field_names.len());
+
+        for (field_name, value) in field_names.iter().zip(values) {
+            if !value.is_empty() {
+                json_obj.insert(field_name.clone(), JsonValue::String(value));
+            }
+        }
+        Ok(json_obj)
+    }
+}
+
+/// Splits a CSV record into its fields, following RFC 4180: fields may be enclosed in double
+/// quotes, in which case they can contain delimiters and escaped double quotes (`""`). Records
+/// spanning several lines are not supported.
+fn split_record(raw_record: &[u8], delimiter: char) -> Result<Vec<String>, DocProcessorError> {
+    let record = std::str::from_utf8(raw_record)
+        .map_err(|error| DocProcessorError::Parse(error.to_string()))?
+        .trim_end_matches(['\r', '\n']);
+
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut chars = record.chars().peekable();
+    let mut in_quotes = false;
+
+    while let Some(ch) = chars.next() {
+        if in_quotes {
+            if ch == '"' {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    field.push('"');
+                } else {
+                    in_quotes = false;
+                }
+            } else {
+                field.push(ch);
+            }
+        } else if ch == '"' && field.is_empty() {
+            in_quotes = true;
+        } else if ch == delimiter {
+            fields.push(std::mem::take(&mut field));
+        } else {
+            field.push(ch);
+        }
+    }
+    if in_quotes {
+        return Err(DocProcessorError::Parse(
+            "CSV record contains an unterminated quoted field".to_string(),
+        ));
+    }
+    fields.push(field);
+    Ok(fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_record() {
+        assert_eq!(split_record(b"a,b,c\n", ',').unwrap(), ["a", "b", "c"]);
+        assert_eq!(split_record(b"a,,c\r\n", ',').unwrap(), ["a", "", "c"]);
+        assert_eq!(split_record(b"a;b,c", ';').unwrap(), ["a", "b,c"]);
+        assert_eq!(
+            split_record(br#""a,b","say ""hi""",c"#, ',').unwrap(),
+            ["a,b", r#"say "hi""#, "c"]
+        );
+        assert_eq!(split_record(b"", ',').unwrap(), [""]);
+        split_record(br#""a,b"#, ',').unwrap_err();
+        split_record(b"\xff", ',').unwrap_err();
+    }
+
+    #[test]
+    fn test_csv_parser() {
+        let mut csv_parser = CsvParser::new(CsvConfig::default());
+        assert!(csv_parser.is_missing_header());
+
+        csv_parser.parse_record(b"1,foo\n").unwrap_err();
+        csv_parser.set_header(b",name\n").unwrap_err();
+        assert!(csv_parser.is_missing_header());
+
+        csv_parser.set_header(b"id,name\n").unwrap();
+        assert!(!csv_parser.is_missing_header());
+
+        let json_obj = csv_parser.parse_record(b"1,foo\n").unwrap();
+        assert_eq!(
+            JsonValue::Object(json_obj),
+            serde_json::json!({"id": "1", "name": "foo"})
+        );
+        let json_obj = csv_parser.parse_record(b"2,\n").unwrap();
+        assert_eq!(JsonValue::Object(json_obj), serde_json::json!({"id": "2"}));
+
+        let error = csv_parser.parse_record(b"3,bar,baz\n").unwrap_err();
+        assert!(error.reason().contains("3 fields but the header has 2"));
+    }
+}
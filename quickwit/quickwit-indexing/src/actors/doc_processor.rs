@@ -26,8 +26,8 @@ use async_trait::async_trait;
 use bytes::Bytes;
 use quickwit_actors::{Actor, ActorContext, ActorExitStatus, Handler, Mailbox, QueueCapacity};
 use quickwit_common::runtimes::RuntimeType;
-use quickwit_config::{SourceInputFormat, TransformConfig};
-use quickwit_doc_mapper::{DocMapper, DocParsingError, JsonObject};
+use quickwit_config::{CsvConfig, SourceInputFormat, TransformConfig};
+use quickwit_doc_mapper::{DocMapper, DocParsingError, JsonObject, SPLIT_TAGS_FIELD_NAME};
 use quickwit_opentelemetry::otlp::{
     parse_otlp_spans_json, parse_otlp_spans_protobuf, JsonSpanIterator, OtlpTraceError,
 };
@@ -38,11 +38,14 @@ use tantivy::{DateTime, TantivyDocument};
 use tokio::runtime::Handle;
 use tracing::warn;
 
+use super::csv_processing::CsvParser;
 #[cfg(feature = "vrl")]
 use super::vrl_processing::*;
 use crate::actors::Indexer;
+use crate::dead_letter_queue::DeadLetterQueue;
 use crate::models::{
-    NewPublishLock, NewPublishToken, ProcessedDoc, ProcessedDocBatch, PublishLock, RawDocBatch,
+    DocMetadata, NewPublishLock, NewPublishToken, ProcessedDoc, ProcessedDocBatch, PublishLock,
+    RawDocBatch,
 };
 
 const PLAIN_TEXT: &str = "plain_text";
@@ -66,7 +69,9 @@ impl JsonDoc {
     ) -> Result<Self, DocProcessorError> {
         match json_value {
             JsonValue::Object(json_obj) => Ok(Self::new(json_obj, num_bytes)),
-            _ => Err(DocProcessorError::Parse),
+            _ => Err(DocProcessorError::Parse(
+                "document must be a JSON object".to_string(),
+            )),
         }
     }
 
@@ -79,21 +84,35 @@ impl JsonDoc {
 
 #[derive(Debug)]
 pub enum DocProcessorError {
-    Parse,
-    Schema,
+    Parse(String),
+    Schema(String),
     #[cfg(feature = "vrl")]
     Transform(VrlTerminate),
 }
 
+impl DocProcessorError {
+    /// Returns a human-readable description of the error, recorded in the dead letter queue.
+    pub fn reason(&self) -> String {
+        match self {
+            DocProcessorError::Parse(message) => format!("parse error: {message}"),
+            DocProcessorError::Schema(message) => format!("schema error: {message}"),
+            #[cfg(feature = "vrl")]
+            DocProcessorError::Transform(transform_error) => {
+                format!("transform error: {transform_error}")
+            }
+        }
+    }
+}
+
 impl From<serde_json::Error> for DocProcessorError {
-    fn from(_error: serde_json::Error) -> Self {
-        DocProcessorError::Parse
+    fn from(error: serde_json::Error) -> Self {
+        DocProcessorError::Parse(error.to_string())
     }
 }
 
 impl From<FromUtf8Error> for DocProcessorError {
-    fn from(_error: FromUtf8Error) -> Self {
-        DocProcessorError::Parse
+    fn from(error: FromUtf8Error) -> Self {
+        DocProcessorError::Parse(error.to_string())
     }
 }
 
@@ -102,9 +121,15 @@ fn try_into_vrl_doc(
     input_format: SourceInputFormat,
     raw_doc: Bytes,
     num_bytes: usize,
+    csv_parser_opt: Option<&CsvParser>,
 ) -> Result<VrlDoc, DocProcessorError> {
     let vrl_value = match input_format {
         SourceInputFormat::Json => serde_json::from_slice::<VrlValue>(&raw_doc)?,
+        SourceInputFormat::Csv => {
+            let csv_parser = csv_parser_opt.expect("CSV parser should be set for CSV input");
+            let json_obj = csv_parser.parse_record(&raw_doc)?;
+            serde_json::from_value::<VrlValue>(JsonValue::Object(json_obj))?
+        }
         SourceInputFormat::PlainText => {
             let mut map = std::collections::BTreeMap::new();
             let key = PLAIN_TEXT.to_string();
@@ -124,6 +149,7 @@ fn try_into_json_docs(
     input_format: SourceInputFormat,
     raw_doc: Bytes,
     num_bytes: usize,
+    csv_parser_opt: Option<&CsvParser>,
 ) -> JsonDocIterator {
     match input_format {
         SourceInputFormat::Json => {
@@ -148,6 +174,13 @@ fn try_into_json_docs(
             });
             JsonDocIterator::from(json_doc_result)
         }
+        SourceInputFormat::Csv => {
+            let csv_parser = csv_parser_opt.expect("CSV parser should be set for CSV input");
+            let json_doc_result = csv_parser
+                .parse_record(&raw_doc)
+                .map(|json_obj| JsonDoc::new(json_obj, num_bytes));
+            JsonDocIterator::from(json_doc_result)
+        }
     }
 }
 
@@ -156,13 +189,15 @@ fn parse_raw_doc(
     input_format: SourceInputFormat,
     raw_doc: Bytes,
     num_bytes: usize,
+    csv_parser_opt: Option<&CsvParser>,
+    doc_metadata_opt: Option<&DocMetadata>,
     vrl_program_opt: Option<&mut VrlProgram>,
 ) -> JsonDocIterator {
     let Some(vrl_program) = vrl_program_opt else {
-        return try_into_json_docs(input_format, raw_doc, num_bytes);
+        return try_into_json_docs(input_format, raw_doc, num_bytes, csv_parser_opt);
     };
-    let json_doc_result = try_into_vrl_doc(input_format, raw_doc, num_bytes)
-        .and_then(|vrl_doc| vrl_program.transform_doc(vrl_doc))
+    let json_doc_result = try_into_vrl_doc(input_format, raw_doc, num_bytes, csv_parser_opt)
+        .and_then(|vrl_doc| vrl_program.transform_doc(vrl_doc, doc_metadata_opt))
         .and_then(JsonDoc::try_from_vrl_doc);
 
     JsonDocIterator::from(json_doc_result)
@@ -173,9 +208,11 @@ fn parse_raw_doc(
     input_format: SourceInputFormat,
     raw_doc: Bytes,
     num_bytes: usize,
+    csv_parser_opt: Option<&CsvParser>,
+    _doc_metadata_opt: Option<&DocMetadata>,
     _vrl_program_opt: Option<&mut VrlProgram>,
 ) -> JsonDocIterator {
-    try_into_json_docs(input_format, raw_doc, num_bytes)
+    try_into_json_docs(input_format, raw_doc, num_bytes, csv_parser_opt)
 }
 
 enum JsonDocIterator {
@@ -211,7 +248,7 @@ impl From<Result<JsonSpanIterator, OtlpTraceError>> for JsonDocIterator {
     fn from(result: Result<JsonSpanIterator, OtlpTraceError>) -> Self {
         match result {
             Ok(json_doc) => Self::Spans(json_doc),
-            Err(_) => Self::One(Some(Err(DocProcessorError::Parse))),
+            Err(error) => Self::One(Some(Err(DocProcessorError::Parse(error.to_string())))),
         }
     }
 }
@@ -282,13 +319,13 @@ impl DocProcessorCounters {
             .inc_by(num_bytes);
     }
 
-    pub fn record_error(&self, error: DocProcessorError, num_bytes: u64) {
+    pub fn record_error(&self, error: &DocProcessorError, num_bytes: u64) {
         let label = match error {
-            DocProcessorError::Parse => {
+            DocProcessorError::Parse(_) => {
                 self.num_parse_errors.fetch_add(1, Ordering::Relaxed);
                 "parse_error"
             }
-            DocProcessorError::Schema => {
+            DocProcessorError::Schema(_) => {
                 self.num_schema_errors.fetch_add(1, Ordering::Relaxed);
                 "schema_error"
             }
@@ -321,6 +358,8 @@ pub struct DocProcessor {
     #[cfg(feature = "vrl")]
     transform_opt: Option<VrlProgram>,
     input_format: SourceInputFormat,
+    csv_parser_opt: Option<CsvParser>,
+    dead_letter_queue_opt: Option<DeadLetterQueue>,
 }
 
 impl DocProcessor {
@@ -347,10 +386,28 @@ impl DocProcessor {
                 .map(VrlProgram::try_from_transform_config)
                 .transpose()?,
             input_format,
+            csv_parser_opt: (input_format == SourceInputFormat::Csv)
+                .then(|| CsvParser::new(CsvConfig::default())),
+            dead_letter_queue_opt: None,
         };
         Ok(doc_processor)
     }
 
+    /// Sets the options used to parse the documents of the `csv` input format.
+    pub fn with_csv_config(mut self, csv_config: CsvConfig) -> Self {
+        if self.input_format == SourceInputFormat::Csv {
+            self.csv_parser_opt = Some(CsvParser::new(csv_config));
+        }
+        self
+    }
+
+    /// Forwards the documents rejected by the doc processor to the given dead letter queue
+    /// instead of dropping them.
+    pub fn with_dead_letter_queue(mut self, dead_letter_queue: DeadLetterQueue) -> Self {
+        self.dead_letter_queue_opt = Some(dead_letter_queue);
+        self
+    }
+
     // Extract a timestamp from a tantivy document.
     //
     // If the timestamp is set up in the docmapper and the timestamp is missing,
@@ -365,19 +422,47 @@ impl DocProcessor {
         let timestamp = doc
             .get_first(timestamp_field)
             .and_then(|val| val.as_datetime())
-            .ok_or(DocProcessorError::Schema)?;
+            .ok_or_else(|| {
+                DocProcessorError::Schema("missing or invalid timestamp field".to_string())
+            })?;
         Ok(Some(timestamp))
     }
 
-    fn process_raw_doc(&mut self, raw_doc: Bytes, processed_docs: &mut Vec<ProcessedDoc>) {
+    fn process_raw_doc(
+        &mut self,
+        raw_doc: Bytes,
+        doc_metadata_opt: Option<&DocMetadata>,
+        processed_docs: &mut Vec<ProcessedDoc>,
+    ) {
         let num_bytes = raw_doc.len();
 
+        if let Some(csv_parser) = self.csv_parser_opt.as_mut() {
+            if csv_parser.is_missing_header() {
+                // The header row is not a document: it is neither counted nor indexed.
+                if let Err(error) = csv_parser.set_header(&raw_doc) {
+                    warn!(index_id=self.counters.index_id, source_id=self.counters.source_id, error=?error, "failed to parse CSV header");
+                    self.counters.record_error(&error, num_bytes as u64);
+                }
+                return;
+            }
+        }
+        // Cloning `Bytes` is cheap: the buffer is reference counted.
+        let raw_doc_opt = self.dead_letter_queue_opt.as_ref().map(|_| raw_doc.clone());
+
         #[cfg(feature = "vrl")]
         let transform_opt = self.transform_opt.as_mut();
         #[cfg(not(feature = "vrl"))]
         let transform_opt: Option<&mut VrlProgram> = None;
 
-        for json_doc_result in parse_raw_doc(self.input_format, raw_doc, num_bytes, transform_opt) {
+        let json_doc_results = parse_raw_doc(
+            self.input_format,
+            raw_doc,
+            num_bytes,
+            self.csv_parser_opt.as_ref(),
+            doc_metadata_opt,
+            transform_opt,
+        );
+        for json_doc_result in json_doc_results {
             let processed_doc_result =
                 json_doc_result.and_then(|json_doc| self.process_json_doc(json_doc));
 
@@ -387,7 +472,13 @@ impl DocProcessor {
                     processed_docs.push(processed_doc);
                 }
                 Err(error) => {
-                    self.counters.record_error(error, num_bytes as u64);
+                    self.counters.record_error(&error, num_bytes as u64);
+
+                    if let (Some(dead_letter_queue), Some(raw_doc)) =
+                        (self.dead_letter_queue_opt.as_mut(), raw_doc_opt.as_ref())
+                    {
+                        dead_letter_queue.record(raw_doc.clone(), error.reason());
+                    }
                 }
             }
         }
@@ -395,15 +486,21 @@ impl DocProcessor {
 
     fn process_json_doc(&self, json_doc: JsonDoc) -> Result<ProcessedDoc, DocProcessorError> {
         let num_bytes = json_doc.num_bytes;
+        let mut json_obj = json_doc.json_obj;
+        let split_tags = extract_split_tags(&mut json_obj)?;
 
         let (partition, doc) = self
             .doc_mapper
-            .doc_from_json_obj(json_doc.json_obj)
+            .doc_from_json_obj(json_obj)
             .map_err(|error| {
                 warn!(index_id=self.counters.index_id, source_id=self.counters.source_id, error=?error);
                 match error {
-                    DocParsingError::RequiredField(_) => DocProcessorError::Schema,
-                    _ => DocProcessorError::Parse,
+                    DocParsingError::RequiredField(_)
+                    | DocParsingError::NoSuchFieldInSchema(_)
+                    | DocParsingError::UnmappedFields(_) => {
+                        DocProcessorError::Schema(error.to_string())
+                    }
+                    _ => DocProcessorError::Parse(error.to_string()),
                 }
             })?;
         let timestamp_opt = self.extract_timestamp(&doc)?;
@@ -412,10 +509,55 @@ impl DocProcessor {
             timestamp_opt,
             partition,
             num_bytes,
+            split_tags,
         })
     }
 }
 
+/// Removes the reserved `_split_tags` field from a document and returns the custom split tags it
+/// defines, as `(key, value)` pairs. The field must be an object whose values are scalars or
+/// arrays of scalars.
+fn extract_split_tags(
+    json_obj: &mut JsonObject,
+) -> Result<Vec<(String, String)>, DocProcessorError> {
+    let Some(split_tags_value) = json_obj.remove(SPLIT_TAGS_FIELD_NAME) else {
+        return Ok(Vec::new());
+    };
+    let JsonValue::Object(split_tags_obj) = split_tags_value else {
+        return Err(DocProcessorError::Parse(format!(
+            "`{SPLIT_TAGS_FIELD_NAME}` must be a JSON object"
+        )));
+    };
+    let mut split_tags = Vec::with_capacity(split_tags_obj.len());
+
+    for (key, value) in split_tags_obj {
+        if key.is_empty() || key.contains(':') || key.ends_with('!') {
+            return Err(DocProcessorError::Parse(format!(
+                "invalid split tag key `{key}`: keys must be non-empty, must not contain `:`, and \
+                 must not end with `!`"
+            )));
+        }
+        let values = match value {
+            JsonValue::Array(values) => values,
+            value => vec![value],
+        };
+        for value in values {
+            let value = match value {
+                JsonValue::Null => continue,
+                JsonValue::String(value) => value,
+                JsonValue::Bool(_) | JsonValue::Number(_) => value.to_string(),
+                JsonValue::Array(_) | JsonValue::Object(_) => {
+                    return Err(DocProcessorError::Parse(format!(
+                        "invalid value for split tag `{key}`: values must be scalars"
+                    )));
+                }
+            };
+            split_tags.push((key.clone(), value));
+        }
+    }
+    Ok(split_tags)
+}
+
 fn extract_timestamp_field(doc_mapper: &dyn DocMapper) -> anyhow::Result<Option<Field>> {
     let schema = doc_mapper.schema();
     let Some(timestamp_field_name) = doc_mapper.timestamp_field_name() else {
@@ -456,6 +598,10 @@ impl Actor for DocProcessor {
         exit_status: &ActorExitStatus,
         ctx: &ActorContext<Self>,
     ) -> anyhow::Result<()> {
+        if let Some(dead_letter_queue) = self.dead_letter_queue_opt.take() {
+            // The remaining dead letters are flushed to the sink before the pipeline exits.
+            dead_letter_queue.close().await;
+        }
         match exit_status {
             ActorExitStatus::DownstreamClosed
             | ActorExitStatus::Killed
@@ -482,9 +628,12 @@ impl Handler<RawDocBatch> for DocProcessor {
             return Ok(());
         }
         let mut processed_docs: Vec<ProcessedDoc> = Vec::with_capacity(raw_doc_batch.docs.len());
+        let mut doc_metadatas = raw_doc_batch.doc_metadatas.into_iter();
+
         for raw_doc in raw_doc_batch.docs {
             let _protected_zone_guard = ctx.protect_zone();
-            self.process_raw_doc(raw_doc, &mut processed_docs);
+            let doc_metadata_opt = doc_metadatas.next().flatten();
+            self.process_raw_doc(raw_doc, doc_metadata_opt.as_ref(), &mut processed_docs);
             ctx.record_progress();
         }
         let processed_doc_batch = ProcessedDocBatch {
@@ -530,23 +679,26 @@ impl Handler<NewPublishToken> for DocProcessor {
 
 #[cfg(test)]
 mod tests {
+    use std::path::Path;
     use std::sync::Arc;
 
     use bytes::Bytes;
     use prost::Message;
     use quickwit_actors::Universe;
     use quickwit_common::uri::Uri;
-    use quickwit_config::{build_doc_mapper, SearchSettings};
+    use quickwit_config::{build_doc_mapper, DeadLetterQueueConfig, SearchSettings};
     use quickwit_doc_mapper::{default_doc_mapper_for_test, DefaultDocMapper};
     use quickwit_metastore::checkpoint::SourceCheckpointDelta;
     use quickwit_opentelemetry::otlp::OtlpGrpcTracesService;
     use quickwit_proto::opentelemetry::proto::collector::trace::v1::ExportTraceServiceRequest;
     use quickwit_proto::opentelemetry::proto::trace::v1::{ResourceSpans, ScopeSpans, Span};
+    use quickwit_storage::RamStorage;
     use serde_json::Value as JsonValue;
     use tantivy::schema::NamedFieldDocument;
     use tantivy::Document;
 
     use super::*;
+    use crate::dead_letter_queue::DeadLetterSamples;
     use crate::models::{PublishLock, RawDocBatch};
 
     #[tokio::test]
@@ -626,6 +778,133 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_doc_processor_dead_letter_queue() {
+        let universe = Universe::with_accelerated_time();
+        let doc_mapper = Arc::new(default_doc_mapper_for_test());
+        let (indexer_mailbox, _indexer_inbox) = universe.create_test_mailbox();
+        let storage = Arc::new(RamStorage::default());
+        let dead_letter_samples = DeadLetterSamples::default();
+        let dead_letter_queue = DeadLetterQueue::try_new(
+            "my-index".to_string(),
+            "my-source".to_string(),
+            &DeadLetterQueueConfig::File,
+            storage.clone(),
+            Path::new("/unused"),
+            dead_letter_samples.clone(),
+        )
+        .await
+        .unwrap();
+        let doc_processor = DocProcessor::try_new(
+            "my-index".to_string(),
+            "my-source".to_string(),
+            doc_mapper,
+            indexer_mailbox,
+            None,
+            SourceInputFormat::Json,
+        )
+        .unwrap()
+        .with_dead_letter_queue(dead_letter_queue);
+        let (doc_processor_mailbox, doc_processor_handle) =
+            universe.spawn_builder().spawn(doc_processor);
+        doc_processor_mailbox
+            .send_message(RawDocBatch::for_test(
+                &[
+                    r#"{"body": "happy", "timestamp": 1628837062, "response_date": "2021-12-19T16:39:59+00:00", "response_time": 2, "response_payload": "YWJj"}"#, // ok
+                    r#"{"body": "happy", "response_date": "2021-12-19T16:39:57+00:00", "response_time": 12, "response_payload": "YWJj"}"#, // missing timestamp
+                    "{", // invalid json
+                ],
+                0..3,
+            ))
+            .await
+            .unwrap();
+        let counters = doc_processor_handle
+            .process_pending_and_observe()
+            .await
+            .state;
+        assert_eq!(counters.num_valid_docs.load(Ordering::Relaxed), 1);
+        assert_eq!(counters.num_invalid_docs(), 2);
+
+        let dead_letters = dead_letter_samples.recent(10);
+        assert_eq!(dead_letters.len(), 2);
+        assert_eq!(dead_letters[0].doc, "{");
+        assert!(dead_letters[0].reason.starts_with("parse error"));
+        assert!(dead_letters[1].reason.starts_with("schema error"));
+
+        // The dead letters are written to the sink at the latest when the doc processor exits.
+        universe
+            .send_exit_with_success(&doc_processor_mailbox)
+            .await
+            .unwrap();
+        let (exit_status, _) = doc_processor_handle.join().await;
+        assert!(matches!(exit_status, ActorExitStatus::Success));
+        assert_eq!(storage.list_files().await.len(), 1);
+        universe.assert_quit().await;
+    }
+
+    #[tokio::test]
+    async fn test_doc_processor_strict_reject_mode() {
+        let universe = Universe::with_accelerated_time();
+        let doc_mapper: DefaultDocMapper = serde_json::from_str(
+            r#"{
+                "field_mappings": [{"name": "body", "type": "text"}],
+                "mode": "strict_reject"
+            }"#,
+        )
+        .unwrap();
+        let (indexer_mailbox, _indexer_inbox) = universe.create_test_mailbox();
+        let storage = Arc::new(RamStorage::default());
+        let dead_letter_samples = DeadLetterSamples::default();
+        let dead_letter_queue = DeadLetterQueue::try_new(
+            "my-index".to_string(),
+            "my-source".to_string(),
+            &DeadLetterQueueConfig::File,
+            storage,
+            Path::new("/unused"),
+            dead_letter_samples.clone(),
+        )
+        .await
+        .unwrap();
+        let doc_processor = DocProcessor::try_new(
+            "my-index".to_string(),
+            "my-source".to_string(),
+            Arc::new(doc_mapper),
+            indexer_mailbox,
+            None,
+            SourceInputFormat::Json,
+        )
+        .unwrap()
+        .with_dead_letter_queue(dead_letter_queue);
+        let (doc_processor_mailbox, doc_processor_handle) =
+            universe.spawn_builder().spawn(doc_processor);
+        doc_processor_mailbox
+            .send_message(RawDocBatch::for_test(
+                &[
+                    r#"{"body": "happy"}"#,
+                    r#"{"body": "happy", "user": {"name": "bob"}, "level": 1}"#,
+                ],
+                0..2,
+            ))
+            .await
+            .unwrap();
+        let counters = doc_processor_handle
+            .process_pending_and_observe()
+            .await
+            .state;
+        assert_eq!(counters.num_valid_docs.load(Ordering::Relaxed), 1);
+        assert_eq!(counters.num_schema_errors.load(Ordering::Relaxed), 1);
+        assert_eq!(counters.num_parse_errors.load(Ordering::Relaxed), 0);
+
+        let dead_letters = dead_letter_samples.recent(10);
+        assert_eq!(dead_letters.len(), 1);
+        assert_eq!(
+            dead_letters[0].reason,
+            "schema error: the document contains fields that are not declared in the doc mapping: \
+             level, user"
+        );
+        universe.assert_quit().await;
+    }
+
     const DOCMAPPER_WITH_PARTITION_JSON: &str = r#"
         {
             "tag_fields": ["tenant"],
@@ -670,6 +949,7 @@ mod tests {
                         br#"{"tenant": "tenant_2", "body": "second doc for tenant 2"}"#,
                     ),
                 ],
+                doc_metadatas: Vec::new(),
                 checkpoint_delta: SourceCheckpointDelta::from_range(0..2),
                 force_commit: false,
             })
@@ -694,6 +974,46 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_extract_split_tags() {
+        let mut json_obj = serde_json::json!({"body": "hello"})
+            .as_object()
+            .unwrap()
+            .clone();
+        assert!(extract_split_tags(&mut json_obj).unwrap().is_empty());
+
+        let mut json_obj = serde_json::json!({
+            "body": "hello",
+            "_split_tags": {"env": "prod", "dc": ["us-east", "us-west"], "shard": 3, "rack": null}
+        })
+        .as_object()
+        .unwrap()
+        .clone();
+        let mut split_tags = extract_split_tags(&mut json_obj).unwrap();
+        split_tags.sort();
+        assert!(!json_obj.contains_key(SPLIT_TAGS_FIELD_NAME));
+        assert_eq!(
+            split_tags,
+            [
+                ("dc".to_string(), "us-east".to_string()),
+                ("dc".to_string(), "us-west".to_string()),
+                ("env".to_string(), "prod".to_string()),
+                ("shard".to_string(), "3".to_string()),
+            ]
+        );
+        for invalid_split_tags in [
+            serde_json::json!("env:prod"),
+            serde_json::json!({"env:prod": "prod"}),
+            serde_json::json!({"env!": "prod"}),
+            serde_json::json!({"": "prod"}),
+            serde_json::json!({"env": {"name": "prod"}}),
+        ] {
+            let mut json_obj = JsonObject::new();
+            json_obj.insert(SPLIT_TAGS_FIELD_NAME.to_string(), invalid_split_tags);
+            extract_split_tags(&mut json_obj).unwrap_err();
+        }
+    }
+
     #[tokio::test]
     async fn test_doc_processor_forward_publish_lock() {
         let doc_mapper = Arc::new(default_doc_mapper_for_test());
@@ -819,6 +1139,7 @@ mod tests {
 
         let raw_doc_batch = RawDocBatch {
             docs: vec![raw_doc],
+            doc_metadatas: Vec::new(),
             checkpoint_delta: SourceCheckpointDelta::from_range(0..2),
             force_commit: false,
         };
@@ -901,6 +1222,7 @@ mod tests {
 
         let raw_doc_batch = RawDocBatch {
             docs: vec![raw_doc],
+            doc_metadatas: Vec::new(),
             checkpoint_delta: SourceCheckpointDelta::from_range(0..2),
             force_commit: false,
         };
@@ -929,6 +1251,75 @@ mod tests {
         assert!(matches!(exit_status, ActorExitStatus::Success));
         universe.assert_quit().await;
     }
+
+    #[tokio::test]
+    async fn test_doc_processor_csv_input() {
+        let universe = Universe::with_accelerated_time();
+        let doc_mapper: DefaultDocMapper = serde_json::from_str(
+            r#"{
+                "field_mappings": [
+                    {"name": "id", "type": "u64"},
+                    {"name": "body", "type": "text"}
+                ],
+                "mode": "strict"
+            }"#,
+        )
+        .unwrap();
+        let doc_mapper = Arc::new(doc_mapper);
+        let (indexer_mailbox, indexer_inbox) = universe.create_test_mailbox();
+        let doc_processor = DocProcessor::try_new(
+            "my-index".to_string(),
+            "my-source".to_string(),
+            doc_mapper.clone(),
+            indexer_mailbox,
+            None,
+            SourceInputFormat::Csv,
+        )
+        .unwrap()
+        .with_csv_config(CsvConfig { delimiter: ';' });
+        let (doc_processor_mailbox, doc_processor_handle) =
+            universe.spawn_builder().spawn(doc_processor);
+        doc_processor_mailbox
+            .send_message(RawDocBatch::for_test(
+                &[
+                    "id;body\n",
+                    "1;\"hello; world\"\n",
+                    "2;\n",
+                    "3;happy;extra\n", // too many fields
+                    "four;happy\n",    // invalid u64
+                ],
+                0..5,
+            ))
+            .await
+            .unwrap();
+        let counters = doc_processor_handle
+            .process_pending_and_observe()
+            .await
+            .state;
+        assert_eq!(counters.num_parse_errors.load(Ordering::Relaxed), 2);
+        assert_eq!(counters.num_schema_errors.load(Ordering::Relaxed), 0);
+        assert_eq!(counters.num_valid_docs.load(Ordering::Relaxed), 2);
+
+        let batch = indexer_inbox.drain_for_test_typed::<ProcessedDocBatch>();
+        assert_eq!(batch.len(), 1);
+        assert_eq!(batch[0].docs.len(), 2);
+        assert_eq!(
+            batch[0].checkpoint_delta,
+            SourceCheckpointDelta::from_range(0..5)
+        );
+        let schema = doc_mapper.schema();
+        let NamedFieldDocument(named_field_doc_map) = batch[0].docs[0].doc.to_named_doc(&schema);
+        let doc_json = JsonValue::Object(doc_mapper.doc_to_json(named_field_doc_map).unwrap());
+        assert_eq!(doc_json["id"], 1);
+        assert_eq!(doc_json["body"], "hello; world");
+
+        let NamedFieldDocument(named_field_doc_map) = batch[0].docs[1].doc.to_named_doc(&schema);
+        let doc_json = JsonValue::Object(doc_mapper.doc_to_json(named_field_doc_map).unwrap());
+        assert_eq!(doc_json["id"], 2);
+        assert!(doc_json.get("body").is_none());
+
+        universe.assert_quit().await;
+    }
 }
 
 #[cfg(feature = "vrl")]
@@ -1022,6 +1413,59 @@ mod tests_vrl {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_doc_processor_vrl_with_doc_metadata() {
+        let universe = Universe::with_accelerated_time();
+        let (indexer_mailbox, indexer_inbox) = universe.create_test_mailbox();
+        let doc_mapper = Arc::new(default_doc_mapper_for_test());
+        let transform_config = TransformConfig::for_test(
+            r#".body = string!(%metadata.topic) + "/" + to_string!(%metadata.offset)"#,
+        );
+        let doc_processor = DocProcessor::try_new(
+            "my-index".to_string(),
+            "my-source".to_string(),
+            doc_mapper.clone(),
+            indexer_mailbox,
+            Some(transform_config),
+            SourceInputFormat::Json,
+        )
+        .unwrap();
+        let (doc_processor_mailbox, doc_processor_handle) =
+            universe.spawn_builder().spawn(doc_processor);
+
+        let raw_doc = r#"{"body": "happy", "timestamp": 1628837062, "response_date": "2021-12-19T16:39:59+00:00", "response_time": 2, "response_payload": "YWJj"}"#;
+        let mut raw_doc_batch = RawDocBatch::for_test(&[], 0..2);
+        let doc_metadata = serde_json::json!({"topic": "my-topic", "offset": 42});
+        let JsonValue::Object(doc_metadata) = doc_metadata else {
+            unreachable!();
+        };
+        raw_doc_batch.push_doc_with_metadata(Bytes::from(raw_doc), Arc::new(doc_metadata));
+        // This document has no metadata, so the transform fails.
+        raw_doc_batch.docs.push(Bytes::from(raw_doc));
+
+        doc_processor_mailbox
+            .send_message(raw_doc_batch)
+            .await
+            .unwrap();
+        let counters = doc_processor_handle
+            .process_pending_and_observe()
+            .await
+            .state;
+        assert_eq!(counters.num_transform_errors.load(Ordering::Relaxed), 1);
+        assert_eq!(counters.num_valid_docs.load(Ordering::Relaxed), 1);
+
+        let batches: Vec<ProcessedDocBatch> = indexer_inbox.drain_for_test_typed();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].docs.len(), 1);
+
+        let schema = doc_mapper.schema();
+        let NamedFieldDocument(named_field_doc_map) = batches[0].docs[0].doc.to_named_doc(&schema);
+        let doc_json = JsonValue::Object(doc_mapper.doc_to_json(named_field_doc_map).unwrap());
+        assert_eq!(doc_json["body"], "my-topic/42");
+
+        universe.assert_quit().await;
+    }
+
     #[tokio::test]
     async fn test_doc_processor_with_plain_text_input() {
         let index_id = "my-index";
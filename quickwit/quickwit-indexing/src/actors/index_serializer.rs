@@ -17,15 +17,22 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
 use async_trait::async_trait;
+use bytesize::ByteSize;
 use quickwit_actors::{Actor, ActorContext, ActorExitStatus, Handler, Mailbox, QueueCapacity};
 use quickwit_common::io::IoControls;
 use quickwit_common::runtimes::RuntimeType;
 use tokio::runtime::Handle;
-use tracing::instrument;
+use tracing::{instrument, warn};
 
 use crate::actors::Packager;
-use crate::models::{EmptySplit, IndexedSplit, IndexedSplitBatch, IndexedSplitBatchBuilder};
+use crate::models::{
+    EmptySplit, IndexedSplit, IndexedSplitBatch, IndexedSplitBatchBuilder, SplitSizeEstimator,
+};
 
 /// The index serializer takes a non-serialized split,
 /// and serializes it before passing it to the packager.
@@ -37,11 +44,25 @@ use crate::models::{EmptySplit, IndexedSplit, IndexedSplitBatch, IndexedSplitBat
 /// it can range from medium IO to IO heavy.
 pub struct IndexSerializer {
     packager_mailbox: Mailbox<Packager>,
+    split_size_estimator: Arc<SplitSizeEstimator>,
 }
 
 impl IndexSerializer {
     pub fn new(packager_mailbox: Mailbox<Packager>) -> Self {
-        Self { packager_mailbox }
+        Self {
+            packager_mailbox,
+            split_size_estimator: Arc::default(),
+        }
+    }
+
+    /// Sets the estimator of the serialized size of the splits, fed with the size of the splits
+    /// serialized by this actor.
+    pub fn with_split_size_estimator(
+        mut self,
+        split_size_estimator: Arc<SplitSizeEstimator>,
+    ) -> Self {
+        self.split_size_estimator = split_size_estimator;
+        self
     }
 }
 
@@ -90,7 +111,22 @@ impl Handler<IndexedSplitBatchBuilder> for IndexSerializer {
                     );
                 controlled_directory.set_io_controls(io_controls);
             }
+            let memory_usage = ByteSize(split_builder.index_writer.mem_usage() as u64);
             let split = split_builder.finalize()?;
+
+            match directory_num_bytes(split.split_scratch_directory.path()) {
+                Ok(serialized_size) => {
+                    self.split_size_estimator
+                        .record_serialized_split(memory_usage, serialized_size);
+                }
+                Err(error) => {
+                    warn!(
+                        split_id=%split.split_id(),
+                        error=?error,
+                        "failed to measure serialized split size"
+                    );
+                }
+            }
             splits.push(split);
         }
         let indexed_split_batch = IndexedSplitBatch {
@@ -107,6 +143,20 @@ impl Handler<IndexedSplitBatchBuilder> for IndexSerializer {
     }
 }
 
+/// Returns the total size of the files of a directory, excluding its subdirectories.
+fn directory_num_bytes(directory_path: &Path) -> io::Result<ByteSize> {
+    let mut num_bytes = 0;
+
+    for dir_entry_res in std::fs::read_dir(directory_path)? {
+        let metadata = dir_entry_res?.metadata()?;
+
+        if metadata.is_file() {
+            num_bytes += metadata.len();
+        }
+    }
+    Ok(ByteSize(num_bytes))
+}
+
 #[async_trait]
 impl Handler<EmptySplit> for IndexSerializer {
     type Reply = ();
@@ -35,8 +35,8 @@ use quickwit_actors::{
 use quickwit_common::io::IoControls;
 use quickwit_common::runtimes::RuntimeType;
 use quickwit_common::temp_dir::TempDirectory;
-use quickwit_config::IndexingSettings;
-use quickwit_doc_mapper::DocMapper;
+use quickwit_config::{DocstoreCodec, IndexSortBy, IndexingSettings, SourcePriority};
+use quickwit_doc_mapper::{DocMapper, OTHER_PARTITION_ID};
 use quickwit_metastore::checkpoint::{IndexCheckpointDelta, SourceCheckpointDelta};
 use quickwit_proto::indexing::{
     CpuCapacity, IndexingPipelineId, PipelineMetrics, PIPELINE_FULL_CAPACITY,
@@ -50,7 +50,7 @@ use serde::Serialize;
 use tantivy::schema::Schema;
 use tantivy::store::{Compressor, ZstdCompressor};
 use tantivy::tokenizer::TokenizerManager;
-use tantivy::{DateTime, IndexBuilder, IndexSettings};
+use tantivy::{DateTime, IndexBuilder, IndexSettings, IndexSortByField, Order};
 use tokio::runtime::Handle;
 use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 use tracing::{info, info_span, warn, Span};
@@ -59,12 +59,9 @@ use ulid::Ulid;
 use crate::actors::IndexSerializer;
 use crate::models::{
     CommitTrigger, EmptySplit, IndexedSplitBatchBuilder, IndexedSplitBuilder, NewPublishLock,
-    NewPublishToken, ProcessedDoc, ProcessedDocBatch, PublishLock,
+    NewPublishToken, ProcessedDoc, ProcessedDocBatch, PublishLock, SplitSizeEstimator,
 };
 
-// Random partition ID used to gather partitions exceeding the maximum number of partitions.
-const OTHER_PARTITION_ID: u64 = 3264326757911759461u64;
-
 #[derive(Debug)]
 struct CommitTimeout {
     workbench_id: Ulid,
@@ -99,6 +96,11 @@ struct IndexerState {
     max_num_partitions: NonZeroU32,
     index_settings: IndexSettings,
     cooperative_indexing_permits: Option<Arc<Semaphore>>,
+    priority: SourcePriority,
+    // Limits the number of cooperative indexing permits held by the backfill pipelines of the
+    // node. Only set for backfill pipelines.
+    backfill_indexing_permits: Option<Arc<Semaphore>>,
+    doc_mapping_version: u64,
 }
 
 impl IndexerState {
@@ -126,6 +128,9 @@ impl IndexerState {
         let indexed_split = IndexedSplitBuilder::new_in_dir(
             self.pipeline_id.clone(),
             partition_id,
+            self.doc_mapping_version,
+            self.indexing_settings.docstore_codec(),
+            self.indexing_settings.sort_by,
             last_delete_opstamp,
             self.indexing_directory.clone(),
             index_builder,
@@ -190,12 +195,29 @@ impl IndexerState {
             workbench_id=%workbench_id,
         );
         let indexing_span = info_span!(parent: batch_parent_span.id(), "indexer");
+        let acquire_permits_instant = Instant::now();
+        // Backfill pipelines acquire a backfill permit before a cooperative indexing permit so
+        // that they can never hold all the cooperative indexing permits of the node.
+        let backfill_indexing_permit =
+            if let Some(backfill_indexing_permits) = &self.backfill_indexing_permits {
+                let backfill_indexing_permit: OwnedSemaphorePermit = ctx
+                    .protect_future(backfill_indexing_permits.clone().acquire_owned())
+                    .await
+                    .expect("The semaphore should never be closed.");
+                Some(backfill_indexing_permit)
+            } else {
+                None
+            };
         let indexing_permit =
             if let Some(cooperative_indexing_permits) = &self.cooperative_indexing_permits {
                 let indexing_permit: OwnedSemaphorePermit = ctx
                     .protect_future(cooperative_indexing_permits.clone().acquire_owned())
                     .await
                     .expect("The semaphore should never be closed.");
+                crate::metrics::INDEXER_METRICS
+                    .cooperative_indexing_permit_wait_micros
+                    .with_label_values([self.priority.as_str()])
+                    .inc_by(acquire_permits_instant.elapsed().as_micros() as u64);
                 Some(indexing_permit)
             } else {
                 None
@@ -229,6 +251,7 @@ impl IndexerState {
             other_indexed_split_opt: None,
             checkpoint_delta,
             indexing_permit,
+            backfill_indexing_permit,
             publish_lock,
             publish_token_opt,
             last_delete_opstamp,
@@ -298,6 +321,7 @@ impl IndexerState {
                 timestamp_opt,
                 partition,
                 num_bytes,
+                split_tags,
             } = doc;
             counters.num_docs_in_workbench += 1;
             let indexed_split: &mut IndexedSplitBuilder = self.get_or_create_indexed_split(
@@ -314,6 +338,14 @@ impl IndexerState {
             if let Some(timestamp) = timestamp_opt {
                 record_timestamp(timestamp, &mut indexed_split.split_attrs.time_range);
             }
+            for (key, value) in split_tags {
+                indexed_split
+                    .split_attrs
+                    .split_tags
+                    .entry(key)
+                    .or_default()
+                    .insert(value);
+            }
             let _protect_guard = ctx.protect_zone();
             indexed_split
                 .index_writer
@@ -343,6 +375,7 @@ struct IndexingWorkbench {
 
     checkpoint_delta: IndexCheckpointDelta,
     indexing_permit: Option<OwnedSemaphorePermit>,
+    backfill_indexing_permit: Option<OwnedSemaphorePermit>,
     publish_lock: PublishLock,
     publish_token_opt: Option<PublishToken>,
     // On workbench creation, we fetch from the metastore the last delete task opstamp.
@@ -357,6 +390,7 @@ pub struct Indexer {
     index_serializer_mailbox: Mailbox<IndexSerializer>,
     indexing_workbench_opt: Option<IndexingWorkbench>,
     counters: IndexerCounters,
+    split_size_estimator: Arc<SplitSizeEstimator>,
 }
 
 #[async_trait]
@@ -446,6 +480,27 @@ fn record_timestamp(timestamp: DateTime, time_range: &mut Option<RangeInclusive<
     *time_range = Some(new_timestamp_range);
 }
 
+fn index_sort_by_field(sort_by: IndexSortBy, timestamp_field_name: &str) -> IndexSortByField {
+    let order = match sort_by {
+        IndexSortBy::TimestampAsc => Order::Asc,
+        IndexSortBy::TimestampDesc => Order::Desc,
+    };
+    IndexSortByField {
+        field: timestamp_field_name.to_string(),
+        order,
+    }
+}
+
+fn docstore_compressor(docstore_codec: DocstoreCodec) -> Compressor {
+    match docstore_codec {
+        DocstoreCodec::None => Compressor::None,
+        DocstoreCodec::Lz4 => Compressor::Lz4,
+        DocstoreCodec::Zstd { level } => Compressor::Zstd(ZstdCompressor {
+            compression_level: Some(level),
+        }),
+    }
+}
+
 #[async_trait]
 impl Handler<CommitTimeout> for Indexer {
     type Reply = ();
@@ -522,10 +577,17 @@ impl Indexer {
     ) -> Self {
         let schema = doc_mapper.schema();
         let tokenizer_manager = doc_mapper.tokenizer_manager().clone();
-        let docstore_compression = Compressor::Zstd(ZstdCompressor {
-            compression_level: Some(indexing_settings.docstore_compression_level),
-        });
+        let docstore_compression = docstore_compressor(indexing_settings.docstore_codec());
+        // The index config validation ensures that the doc mapping declares a timestamp field
+        // whenever the splits are sorted.
+        let sort_by_field = indexing_settings
+            .sort_by
+            .zip(doc_mapper.timestamp_field_name())
+            .map(|(sort_by, timestamp_field_name)| {
+                index_sort_by_field(sort_by, timestamp_field_name)
+            });
         let index_settings = IndexSettings {
+            sort_by_field,
             docstore_blocksize: indexing_settings.docstore_blocksize,
             docstore_compression,
             docstore_compress_dedicated_thread: true,
@@ -544,13 +606,49 @@ impl Indexer {
                 index_settings,
                 max_num_partitions: doc_mapper.max_num_partitions(),
                 cooperative_indexing_permits,
+                priority: SourcePriority::default(),
+                backfill_indexing_permits: None,
+                doc_mapping_version: 0,
             },
             index_serializer_mailbox,
             indexing_workbench_opt: None,
             counters: IndexerCounters::default(),
+            split_size_estimator: Arc::default(),
         }
     }
 
+    /// Sets the version of the doc mapping the splits produced by this indexer are built with.
+    pub fn with_doc_mapping_version(mut self, doc_mapping_version: u64) -> Self {
+        self.indexer_state.doc_mapping_version = doc_mapping_version;
+        self
+    }
+
+    /// Sets the priority of the source indexed by this indexer. Backfill indexers acquire one of
+    /// the `backfill_indexing_permits` before a cooperative indexing permit.
+    pub fn with_priority(
+        mut self,
+        priority: SourcePriority,
+        backfill_indexing_permits: Option<Arc<Semaphore>>,
+    ) -> Self {
+        self.indexer_state.priority = priority;
+        self.indexer_state.backfill_indexing_permits = if priority.is_backfill() {
+            backfill_indexing_permits
+        } else {
+            None
+        };
+        self
+    }
+
+    /// Sets the estimator of the serialized size of the splits, shared with the index serializer
+    /// that learns their compression ratio.
+    pub fn with_split_size_estimator(
+        mut self,
+        split_size_estimator: Arc<SplitSizeEstimator>,
+    ) -> Self {
+        self.split_size_estimator = split_size_estimator;
+        self
+    }
+
     fn update_pipeline_metrics(&mut self, elapsed: Duration, uncompressed_num_bytes: u64) {
         let commit_timeout = self.indexer_state.indexing_settings.commit_timeout();
         let pipeline_throughput_fraction =
@@ -596,6 +694,17 @@ impl Indexer {
             self.send_to_serializer(CommitTrigger::NumDocsLimit, ctx)
                 .await?;
         }
+        if let Some(split_size_bytes_target) =
+            self.indexer_state.indexing_settings.split_size_bytes_target
+        {
+            let estimated_split_size = self
+                .split_size_estimator
+                .estimate_serialized_size(self.memory_usage());
+            if estimated_split_size >= split_size_bytes_target {
+                self.send_to_serializer(CommitTrigger::SplitSizeLimit, ctx)
+                    .await?;
+            }
+        }
         if force_commit {
             self.send_to_serializer(CommitTrigger::ForceCommit, ctx)
                 .await?;
@@ -618,13 +727,15 @@ impl Indexer {
             publish_token_opt,
             batch_parent_span,
             indexing_permit,
+            backfill_indexing_permit,
             ..
         }) = self.indexing_workbench_opt.take()
         else {
             return Ok(());
         };
-        // Dropping the indexing permit explicitly here for enhanced readability.
+        // Dropping the indexing permits explicitly here for enhanced readability.
         drop(indexing_permit);
+        drop(backfill_indexing_permit);
 
         let mut splits: Vec<IndexedSplitBuilder> = indexed_splits.into_values().collect();
 
@@ -717,6 +828,24 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_docstore_compressor() {
+        assert!(matches!(
+            docstore_compressor(DocstoreCodec::None),
+            Compressor::None
+        ));
+        assert!(matches!(
+            docstore_compressor(DocstoreCodec::Lz4),
+            Compressor::Lz4
+        ));
+        assert!(matches!(
+            docstore_compressor(DocstoreCodec::Zstd { level: 3 }),
+            Compressor::Zstd(ZstdCompressor {
+                compression_level: Some(3)
+            })
+        ));
+    }
+
     #[tokio::test]
     async fn test_indexer_triggers_commit_on_target_num_docs() -> anyhow::Result<()> {
         let index_uid = IndexUid::new_with_random_ulid("test-index");
@@ -767,6 +896,7 @@ mod tests {
                         timestamp_opt: Some(DateTime::from_timestamp_secs(1_662_529_435)),
                         partition: 1,
                         num_bytes: 30,
+                        split_tags: Vec::new(),
                     },
                     ProcessedDoc {
                         doc: doc!(
@@ -776,6 +906,7 @@ mod tests {
                         timestamp_opt: Some(DateTime::from_timestamp_secs(1_662_529_435)),
                         partition: 1,
                         num_bytes: 30,
+                        split_tags: Vec::new(),
                     },
                 ],
                 checkpoint_delta: SourceCheckpointDelta::from_range(4..6),
@@ -793,6 +924,7 @@ mod tests {
                         timestamp_opt: Some(DateTime::from_timestamp_secs(1_662_529_435i64)),
                         partition: 1,
                         num_bytes: 30,
+                        split_tags: Vec::new(),
                     },
                     ProcessedDoc {
                         doc: doc!(
@@ -802,6 +934,7 @@ mod tests {
                         timestamp_opt: Some(DateTime::from_timestamp_secs(1_662_529_435)),
                         partition: 1,
                         num_bytes: 30,
+                        split_tags: Vec::new(),
                     },
                 ],
                 checkpoint_delta: SourceCheckpointDelta::from_range(6..8),
@@ -818,6 +951,7 @@ mod tests {
                     timestamp_opt: Some(DateTime::from_timestamp_secs(1_662_529_435)),
                     partition: 1,
                     num_bytes: 30,
+                    split_tags: Vec::new(),
                 }],
                 checkpoint_delta: SourceCheckpointDelta::from_range(8..9),
                 force_commit: false,
@@ -903,6 +1037,7 @@ mod tests {
                 timestamp_opt: None,
                 partition: 0,
                 num_bytes,
+                split_tags: Vec::new(),
             }
         };
         for i in 0..10_000 {
@@ -931,6 +1066,92 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_indexer_triggers_commit_on_split_size_target() -> anyhow::Result<()> {
+        let universe = Universe::new();
+        let index_uid = IndexUid::new_with_random_ulid("test-index");
+        let pipeline_id = IndexingPipelineId {
+            index_uid: index_uid.clone(),
+            source_id: "test-source".to_string(),
+            node_id: "test-node".to_string(),
+            pipeline_uid: PipelineUid::default(),
+        };
+        let doc_mapper = Arc::new(default_doc_mapper_for_test());
+        let last_delete_opstamp = 10;
+        let schema = doc_mapper.schema();
+        let body_field = schema.get_field("body").unwrap();
+        let indexing_directory = TempDirectory::for_test();
+        let mut indexing_settings = IndexingSettings::for_test();
+        indexing_settings.resources.heap_size = ByteSize::mb(10);
+        indexing_settings.split_size_bytes_target = Some(ByteSize::mb(2));
+        let (index_serializer_mailbox, index_serializer_inbox) = universe.create_test_mailbox();
+        let mut metastore = MetastoreServiceClient::mock();
+        metastore
+            .expect_last_delete_opstamp()
+            .times(1..=2)
+            .returning(move |last_delete_opstamp_request| {
+                assert_eq!(last_delete_opstamp_request.index_uid, index_uid.to_string());
+                Ok(LastDeleteOpstampResponse::new(last_delete_opstamp))
+            });
+        metastore.expect_publish_splits().never();
+
+        // The splits serialized so far were half the size of their indexing memory usage.
+        let split_size_estimator = Arc::new(SplitSizeEstimator::default());
+        split_size_estimator.record_serialized_split(ByteSize::mb(10), ByteSize::mb(5));
+
+        let indexer = Indexer::new(
+            pipeline_id,
+            doc_mapper,
+            MetastoreServiceClient::from(metastore),
+            indexing_directory,
+            indexing_settings,
+            None,
+            index_serializer_mailbox,
+        )
+        .with_split_size_estimator(split_size_estimator);
+        let (indexer_mailbox, indexer_handle) = universe.spawn_builder().spawn(indexer);
+
+        let make_doc = |i: u64| {
+            let mut body = String::new();
+            for val in 100 * i..100 * (i + 1) {
+                write!(&mut body, "{val} ").unwrap();
+            }
+            let num_bytes = body.len() * 2;
+            ProcessedDoc {
+                doc: doc!(body_field=>body),
+                timestamp_opt: None,
+                partition: 0,
+                num_bytes,
+                split_tags: Vec::new(),
+            }
+        };
+        for i in 0..10_000 {
+            indexer_mailbox
+                .send_message(ProcessedDocBatch {
+                    docs: vec![make_doc(i)],
+                    checkpoint_delta: SourceCheckpointDelta::from_range(i..i + 1),
+                    force_commit: false,
+                })
+                .await?;
+            indexer_handle.process_pending_and_observe().await;
+
+            let output_messages: Vec<IndexedSplitBatchBuilder> =
+                index_serializer_inbox.drain_for_test_typed();
+            if !output_messages.is_empty() {
+                assert_eq!(output_messages.len(), 1);
+                assert_eq!(
+                    output_messages[0].commit_trigger,
+                    CommitTrigger::SplitSizeLimit
+                );
+                let memory_usage = output_messages[0].splits[0].index_writer.mem_usage() as u64;
+                assert!(memory_usage >= ByteSize::mb(4).as_u64());
+                break;
+            }
+        }
+        universe.assert_quit().await;
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_indexer_triggers_commit_on_timeout() -> anyhow::Result<()> {
         let universe = Universe::new();
@@ -981,6 +1202,7 @@ mod tests {
                             timestamp_opt: Some(DateTime::from_timestamp_secs(1_662_529_435)),
                             partition: 1,
                             num_bytes: 30,
+                            split_tags: Vec::new(),
                         }],
                         force_commit: false,
                         checkpoint_delta: SourceCheckpointDelta::from_range(position..position + 1),
@@ -1059,6 +1281,7 @@ mod tests {
                     timestamp_opt: Some(DateTime::from_timestamp_secs(1_662_529_435)),
                     partition: 1,
                     num_bytes: 30,
+                    split_tags: Vec::new(),
                 }],
                 checkpoint_delta: SourceCheckpointDelta::from_range(8..9),
                 force_commit: false,
@@ -1090,6 +1313,85 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_indexer_backfill_priority_leaves_cooperative_permit_to_realtime() {
+        let universe = Universe::new();
+        let pipeline_id = IndexingPipelineId {
+            index_uid: IndexUid::new_with_random_ulid("test-index"),
+            source_id: "test-backfill-source".to_string(),
+            node_id: "test-node".to_string(),
+            pipeline_uid: PipelineUid::default(),
+        };
+        let doc_mapper = Arc::new(default_doc_mapper_for_test());
+        let schema = doc_mapper.schema();
+        let body_field = schema.get_field("body").unwrap();
+        let timestamp_field = schema.get_field("timestamp").unwrap();
+        let indexing_directory = TempDirectory::for_test();
+        let indexing_settings = IndexingSettings::for_test();
+        let (index_serializer_mailbox, index_serializer_inbox) = universe.create_test_mailbox();
+        let mut metastore = MetastoreServiceClient::mock();
+        metastore
+            .expect_last_delete_opstamp()
+            .returning(move |_last_delete_opstamp_request| Ok(LastDeleteOpstampResponse::new(10)));
+        let cooperative_indexing_permits = Arc::new(Semaphore::new(2));
+        let backfill_indexing_permits = Arc::new(Semaphore::new(1));
+
+        // Another backfill pipeline holds the only backfill permit.
+        let backfill_indexing_permit = backfill_indexing_permits
+            .clone()
+            .try_acquire_owned()
+            .unwrap();
+
+        let indexer = Indexer::new(
+            pipeline_id,
+            doc_mapper,
+            MetastoreServiceClient::from(metastore),
+            indexing_directory,
+            indexing_settings,
+            Some(cooperative_indexing_permits.clone()),
+            index_serializer_mailbox,
+        )
+        .with_priority(
+            SourcePriority::Backfill,
+            Some(backfill_indexing_permits.clone()),
+        );
+        let (indexer_mailbox, _indexer_handle) = universe.spawn_builder().spawn(indexer);
+        indexer_mailbox
+            .send_message(ProcessedDocBatch {
+                docs: vec![ProcessedDoc {
+                    doc: doc!(
+                        body_field=>"this is a test document",
+                        timestamp_field=>DateTime::from_timestamp_secs(1_662_529_435)
+                    ),
+                    timestamp_opt: Some(DateTime::from_timestamp_secs(1_662_529_435)),
+                    partition: 1,
+                    num_bytes: 30,
+                    split_tags: Vec::new(),
+                }],
+                checkpoint_delta: SourceCheckpointDelta::from_range(0..1),
+                force_commit: false,
+            })
+            .await
+            .unwrap();
+        universe.sleep(Duration::from_secs(1)).await;
+
+        // The indexer waits for the backfill permit and leaves the cooperative permits to the
+        // realtime pipelines.
+        assert_eq!(cooperative_indexing_permits.available_permits(), 2);
+        assert!(index_serializer_inbox.drain_for_test().is_empty());
+
+        drop(backfill_indexing_permit);
+        universe.sleep(Duration::from_secs(1)).await;
+
+        let indexed_split_batches: Vec<IndexedSplitBatchBuilder> =
+            index_serializer_inbox.drain_for_test_typed();
+        assert_eq!(indexed_split_batches.len(), 1);
+        assert_eq!(indexed_split_batches[0].splits[0].split_attrs.num_docs, 1);
+        assert_eq!(cooperative_indexing_permits.available_permits(), 2);
+        assert_eq!(backfill_indexing_permits.available_permits(), 1);
+        universe.assert_quit().await;
+    }
+
     #[tokio::test]
     async fn test_indexer_triggers_commit_on_quit() -> anyhow::Result<()> {
         let universe = Universe::with_accelerated_time();
@@ -1133,6 +1435,7 @@ mod tests {
                     timestamp_opt: Some(DateTime::from_timestamp_secs(1_662_529_435)),
                     partition: 1,
                     num_bytes: 30,
+                    split_tags: Vec::new(),
                 }],
                 checkpoint_delta: SourceCheckpointDelta::from_range(8..9),
                 force_commit: false,
@@ -1156,6 +1459,93 @@ mod tests {
         assert_eq!(output_messages.len(), 1);
         assert_eq!(output_messages[0].commit_trigger, CommitTrigger::NoMoreDocs);
         assert_eq!(output_messages[0].splits[0].split_attrs.num_docs, 1);
+        assert_eq!(
+            output_messages[0].splits[0].split_attrs.docstore_codec,
+            Some(DocstoreCodec::Zstd { level: 8 })
+        );
+        universe.assert_quit().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_indexer_sorts_splits_by_timestamp() -> anyhow::Result<()> {
+        let universe = Universe::new();
+        let index_uid = IndexUid::new_with_random_ulid("test-index");
+        let pipeline_id = IndexingPipelineId {
+            index_uid: index_uid.clone(),
+            source_id: "test-source".to_string(),
+            node_id: "test-node".to_string(),
+            pipeline_uid: PipelineUid::default(),
+        };
+        let doc_mapper = Arc::new(default_doc_mapper_for_test());
+        let schema = doc_mapper.schema();
+        let body_field = schema.get_field("body").unwrap();
+        let timestamp_field = schema.get_field("timestamp").unwrap();
+        let indexing_directory = TempDirectory::for_test();
+        let indexing_settings = IndexingSettings {
+            sort_by: Some(IndexSortBy::TimestampDesc),
+            ..IndexingSettings::for_test()
+        };
+        let (index_serializer_mailbox, index_serializer_inbox) = universe.create_test_mailbox();
+        let mut metastore = MetastoreServiceClient::mock();
+        metastore
+            .expect_last_delete_opstamp()
+            .once()
+            .returning(move |_last_delete_opstamp_request| Ok(LastDeleteOpstampResponse::new(10)));
+        metastore.expect_publish_splits().never();
+        let indexer = Indexer::new(
+            pipeline_id,
+            doc_mapper,
+            MetastoreServiceClient::from(metastore),
+            indexing_directory,
+            indexing_settings,
+            None,
+            index_serializer_mailbox,
+        );
+        let (indexer_mailbox, indexer_handle) = universe.spawn_builder().spawn(indexer);
+        let docs = [1_662_529_435, 1_662_529_437, 1_662_529_436]
+            .into_iter()
+            .map(|timestamp_secs| ProcessedDoc {
+                doc: doc!(
+                    body_field=>"this is a test document",
+                    timestamp_field=>DateTime::from_timestamp_secs(timestamp_secs)
+                ),
+                timestamp_opt: Some(DateTime::from_timestamp_secs(timestamp_secs)),
+                partition: 1,
+                num_bytes: 30,
+                split_tags: Vec::new(),
+            })
+            .collect();
+        indexer_mailbox
+            .send_message(ProcessedDocBatch {
+                docs,
+                checkpoint_delta: SourceCheckpointDelta::from_range(0..3),
+                force_commit: false,
+            })
+            .await?;
+        universe.send_exit_with_success(&indexer_mailbox).await?;
+        let (exit_status, _indexer_counters) = indexer_handle.join().await;
+        assert!(exit_status.is_success());
+
+        let mut output_messages: Vec<IndexedSplitBatchBuilder> =
+            index_serializer_inbox.drain_for_test_typed();
+        assert_eq!(output_messages.len(), 1);
+        let split = output_messages[0].splits.pop().unwrap();
+        assert_eq!(split.split_attrs.sort_by, Some(IndexSortBy::TimestampDesc));
+
+        let split = split.finalize()?;
+        let sort_by_field = split.index.settings().sort_by_field.clone().unwrap();
+        assert_eq!(sort_by_field.field, "timestamp");
+        assert_eq!(sort_by_field.order, Order::Desc);
+
+        let searcher = split.index.reader()?.searcher();
+        let segment_reader = searcher.segment_reader(0);
+        let timestamp_column = segment_reader.fast_fields().date("timestamp")?;
+        let timestamps: Vec<i64> = (0..segment_reader.max_doc())
+            .flat_map(|doc_id| timestamp_column.values_for_doc(doc_id))
+            .map(|timestamp| timestamp.into_timestamp_secs())
+            .collect();
+        assert_eq!(timestamps, [1_662_529_437, 1_662_529_436, 1_662_529_435]);
         universe.assert_quit().await;
         Ok(())
     }
@@ -1216,6 +1606,7 @@ mod tests {
                         timestamp_opt: None,
                         partition: 1,
                         num_bytes: 30,
+                        split_tags: Vec::new(),
                     },
                     ProcessedDoc {
                         doc: doc!(
@@ -1225,6 +1616,7 @@ mod tests {
                         timestamp_opt: None,
                         partition: 3,
                         num_bytes: 30,
+                        split_tags: Vec::new(),
                     },
                 ],
                 checkpoint_delta: SourceCheckpointDelta::from_range(8..9),
@@ -1307,6 +1699,7 @@ mod tests {
                         timestamp_opt: None,
                         partition,
                         num_bytes: 30,
+                        split_tags: Vec::new(),
                     }],
                     checkpoint_delta: SourceCheckpointDelta::from_range(partition..partition + 1),
                     force_commit: false,
@@ -1385,6 +1778,7 @@ mod tests {
                         timestamp_opt: None,
                         partition: 0,
                         num_bytes: 30,
+                        split_tags: Vec::new(),
                     }],
                     checkpoint_delta: SourceCheckpointDelta::from_range(0..1),
                     force_commit: false,
@@ -1456,6 +1850,7 @@ mod tests {
                     timestamp_opt: None,
                     partition: 0,
                     num_bytes: 30,
+                    split_tags: Vec::new(),
                 }],
                 checkpoint_delta: SourceCheckpointDelta::from_range(0..1),
                 force_commit: false,
@@ -1512,6 +1907,7 @@ mod tests {
                     timestamp_opt: None,
                     partition: 0,
                     num_bytes: 30,
+                    split_tags: Vec::new(),
                 }],
                 checkpoint_delta: SourceCheckpointDelta::from_range(0..1),
                 force_commit: true,
@@ -24,24 +24,25 @@ use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
 use quickwit_actors::{
-    Actor, ActorContext, ActorExitStatus, ActorHandle, Handler, Health, Mailbox, QueueCapacity,
-    Supervisable, HEARTBEAT,
+    Actor, ActorContext, ActorExitStatus, ActorHandle, ActorState, Handler, Health, Mailbox,
+    QueueCapacity, Supervisable, HEARTBEAT,
 };
 use quickwit_common::pubsub::EventBroker;
 use quickwit_common::temp_dir::TempDirectory;
 use quickwit_common::KillSwitch;
-use quickwit_config::{IndexingSettings, SourceConfig};
+use quickwit_config::{build_doc_mapper, IndexingSettings, SourceConfig};
 use quickwit_doc_mapper::DocMapper;
 use quickwit_ingest::IngesterPool;
 use quickwit_metastore::IndexMetadataResponseExt;
 use quickwit_proto::indexing::IndexingPipelineId;
 use quickwit_proto::metastore::{
     IndexMetadataRequest, MetastoreError, MetastoreService, MetastoreServiceClient,
+    ToggleSourceRequest,
 };
 use quickwit_proto::types::ShardId;
 use quickwit_storage::{Storage, StorageResolver};
 use tokio::sync::Semaphore;
-use tracing::{debug, error, info, instrument};
+use tracing::{debug, error, info, instrument, warn};
 
 use super::MergePlanner;
 use crate::actors::doc_processor::DocProcessor;
@@ -50,11 +51,13 @@ use crate::actors::publisher::PublisherType;
 use crate::actors::sequencer::Sequencer;
 use crate::actors::uploader::UploaderType;
 use crate::actors::{Indexer, Packager, Publisher, Uploader};
+use crate::dead_letter_queue::{DeadLetterQueue, DeadLetterSamples};
 use crate::merge_policy::MergePolicy;
-use crate::models::IndexingStatistics;
+use crate::models::{IndexingStatistics, SplitSizeEstimator};
 use crate::source::{
     quickwit_supported_sources, AssignShards, Assignment, SourceActor, SourceRuntimeArgs,
 };
+use crate::source_health::{SourceHealth, SourceHealthStatus};
 use crate::split_store::IndexingSplitStore;
 use crate::SplitsUpdateMailbox;
 
@@ -62,9 +65,15 @@ const SUPERVISE_INTERVAL: Duration = Duration::from_secs(1);
 
 const MAX_RETRY_DELAY: Duration = Duration::from_secs(600); // 10 min.
 
+/// Interval at which the pipeline checks whether the doc mapping of its index has been updated.
+const CHECK_DOC_MAPPING_INTERVAL: Duration = Duration::from_secs(30);
+
 #[derive(Debug)]
 struct SuperviseLoop;
 
+#[derive(Debug)]
+struct CheckDocMapping;
+
 /// Calculates the wait time based on retry count.
 // retry_count, wait_time
 // 0   1s
@@ -144,6 +153,8 @@ impl Actor for IndexingPipeline {
     async fn initialize(&mut self, ctx: &ActorContext<Self>) -> Result<(), ActorExitStatus> {
         self.handle(Spawn::default(), ctx).await?;
         self.handle(SuperviseLoop, ctx).await?;
+        ctx.schedule_self_msg(CHECK_DOC_MAPPING_INTERVAL, CheckDocMapping)
+            .await;
         Ok(())
     }
 
@@ -242,6 +253,24 @@ impl IndexingPipeline {
         Health::Healthy
     }
 
+    /// Describes why the pipeline is failing, for the source health report.
+    fn failure_message(&self) -> String {
+        let failed_actors: Vec<&str> = self
+            .supervisables()
+            .into_iter()
+            .filter(|supervisable| supervisable.state() == ActorState::Failure)
+            .map(|supervisable| supervisable.name())
+            .collect();
+        if failed_actors.is_empty() {
+            "indexing pipeline actors stopped making progress".to_string()
+        } else {
+            format!(
+                "indexing pipeline actor(s) exited with failure: {}",
+                failed_actors.join(", ")
+            )
+        }
+    }
+
     fn generation(&self) -> usize {
         self.statistics.generation
     }
@@ -250,6 +279,7 @@ impl IndexingPipeline {
         let Some(handles) = &self.handles_opt else {
             return;
         };
+        handles.source_handle.refresh_observe();
         handles.doc_processor.refresh_observe();
         handles.indexer.refresh_observe();
         handles.uploader.refresh_observe();
@@ -268,6 +298,12 @@ impl IndexingPipeline {
         let pipeline_metrics_opt = handles.indexer.last_observation().pipeline_metrics_opt;
         self.statistics.pipeline_metrics_opt = pipeline_metrics_opt;
         self.statistics.shard_ids = self.shard_ids.clone();
+        self.statistics.partition_backlogs = handles
+            .source_handle
+            .last_observation()
+            .get("partition_backlogs")
+            .and_then(|partition_backlogs| serde_json::from_value(partition_backlogs.clone()).ok())
+            .unwrap_or_default();
         ctx.observe(self);
     }
 
@@ -286,12 +322,16 @@ impl IndexingPipeline {
         let check_for_progress = handles.should_check_for_progress();
         let health = self.healthcheck(check_for_progress);
         match health {
-            Health::Healthy => {}
+            Health::Healthy => {
+                // The pipeline ran for a full heartbeat without failing.
+                if check_for_progress {
+                    self.params.source_health.record_recovery();
+                }
+            }
             Health::FailureOrUnhealthy => {
+                let failure_message = self.failure_message();
                 self.terminate().await;
-                let first_retry_delay = wait_duration_before_retry(0);
-                ctx.schedule_self_msg(first_retry_delay, Spawn { retry_count: 0 })
-                    .await;
+                self.handle_failure(failure_message, 0, ctx).await;
             }
             Health::Success => {
                 return Err(ActorExitStatus::Success);
@@ -324,6 +364,25 @@ impl IndexingPipeline {
             pipeline_uid=%self.params.pipeline_id.pipeline_uid,
             "spawning indexing pipeline",
         );
+        // Fetch index_metadata to be sure to have the last updated checkpoint and doc mapping.
+        let index_metadata_request = IndexMetadataRequest::for_index_id(index_id.to_string());
+        let index_metadata = ctx
+            .protect_future(self.params.metastore.index_metadata(index_metadata_request))
+            .await?
+            .deserialize_index_metadata()?;
+
+        if index_metadata.doc_mapping_version > self.params.doc_mapping_version {
+            let index_config = index_metadata.index_config();
+            self.params.doc_mapper =
+                build_doc_mapper(&index_config.doc_mapping, &index_config.search_settings)?;
+            self.params.doc_mapping_version = index_metadata.doc_mapping_version;
+            info!(
+                index_id=%index_id,
+                source_id=%source_id,
+                doc_mapping_version=self.params.doc_mapping_version,
+                "loaded updated doc mapping"
+            );
+        }
         let (source_mailbox, source_inbox) = ctx
             .spawn_ctx()
             .create_mailbox::<SourceActor>("SourceActor", QueueCapacity::Unbounded);
@@ -378,14 +437,27 @@ impl IndexingPipeline {
 
         // Packager
         let tag_fields = self.params.doc_mapper.tag_named_fields()?;
-        let packager = Packager::new("Packager", tag_fields, uploader_mailbox);
+        let stats_fields = self.params.doc_mapper.stats_named_fields()?;
+        let doc_id_field_opt = self.params.doc_mapper.doc_id_named_field()?;
+        let packager = Packager::new(
+            "Packager",
+            tag_fields,
+            stats_fields,
+            doc_id_field_opt,
+            uploader_mailbox,
+        );
         let (packager_mailbox, packager_handle) = ctx
             .spawn_actor()
             .set_kill_switch(self.kill_switch.clone())
             .spawn(packager);
 
+        // The index serializer learns the compression ratio of the splits, from which the indexer
+        // estimates their serialized size.
+        let split_size_estimator = Arc::new(SplitSizeEstimator::default());
+
         // Index Serializer
-        let index_serializer = IndexSerializer::new(packager_mailbox);
+        let index_serializer = IndexSerializer::new(packager_mailbox)
+            .with_split_size_estimator(split_size_estimator.clone());
         let (index_serializer_mailbox, index_serializer_handle) = ctx
             .spawn_actor()
             .set_kill_switch(self.kill_switch.clone())
@@ -400,6 +472,12 @@ impl IndexingPipeline {
             self.params.indexing_settings.clone(),
             self.params.cooperative_indexing_permits.clone(),
             index_serializer_mailbox,
+        )
+        .with_doc_mapping_version(self.params.doc_mapping_version)
+        .with_split_size_estimator(split_size_estimator)
+        .with_priority(
+            self.params.source_config.priority,
+            self.params.backfill_indexing_permits.clone(),
         );
         let (indexer_mailbox, indexer_handle) = ctx
             .spawn_actor()
@@ -411,14 +489,27 @@ impl IndexingPipeline {
             .set_kill_switch(self.kill_switch.clone())
             .spawn(indexer);
 
-        let doc_processor = DocProcessor::try_new(
+        let mut doc_processor = DocProcessor::try_new(
             index_id.to_string(),
             source_id.to_string(),
             self.params.doc_mapper.clone(),
             indexer_mailbox,
             self.params.source_config.transform_config.clone(),
             self.params.source_config.input_format,
-        )?;
+        )?
+        .with_csv_config(self.params.source_config.csv_config);
+        if let Some(dead_letter_queue_config) = &self.params.source_config.dead_letter_queue {
+            let dead_letter_queue = DeadLetterQueue::try_new(
+                index_id.to_string(),
+                source_id.to_string(),
+                dead_letter_queue_config,
+                self.params.storage.clone(),
+                &self.params.queues_dir_path,
+                self.params.dead_letter_samples.clone(),
+            )
+            .await?;
+            doc_processor = doc_processor.with_dead_letter_queue(dead_letter_queue);
+        }
         let (doc_processor_mailbox, doc_processor_handle) = ctx
             .spawn_actor()
             .set_backpressure_micros_counter(
@@ -429,12 +520,6 @@ impl IndexingPipeline {
             .set_kill_switch(self.kill_switch.clone())
             .spawn(doc_processor);
 
-        // Fetch index_metadata to be sure to have the last updated checkpoint.
-        let index_metadata_request = IndexMetadataRequest::for_index_id(index_id.to_string());
-        let index_metadata = ctx
-            .protect_future(self.params.metastore.index_metadata(index_metadata_request))
-            .await?
-            .deserialize_index_metadata()?;
         let source_checkpoint = index_metadata
             .checkpoint
             .source_checkpoint(source_id)
@@ -486,6 +571,69 @@ impl IndexingPipeline {
         Ok(())
     }
 
+    /// Records a failure in the source health and schedules a respawn of the pipeline according to
+    /// the supervision policy of the source. Once the maximum number of consecutive failures is
+    /// reached, the pipeline is no longer respawned and the source is optionally disabled.
+    async fn handle_failure(
+        &self,
+        failure_message: String,
+        retry_count: usize,
+        ctx: &ActorContext<Self>,
+    ) {
+        let supervision_policy = self.params.source_config.supervision.clone();
+        let num_consecutive_failures = self.params.source_health.record_failure(failure_message);
+
+        if let Some(max_consecutive_failures) = supervision_policy.max_consecutive_failures {
+            if num_consecutive_failures >= max_consecutive_failures.get() {
+                error!(
+                    pipeline_id=?self.params.pipeline_id,
+                    num_consecutive_failures,
+                    "indexing pipeline reached the maximum number of consecutive failures, giving \
+                     up"
+                );
+                if supervision_policy.auto_disable {
+                    self.disable_source(ctx).await;
+                } else {
+                    self.params
+                        .source_health
+                        .set_status(SourceHealthStatus::Failed);
+                }
+                return;
+            }
+        }
+        let retry_delay = supervision_policy
+            .backoff
+            .delay(num_consecutive_failures - 1);
+        self.params.source_health.record_restart();
+        ctx.schedule_self_msg(retry_delay, Spawn { retry_count })
+            .await;
+    }
+
+    async fn disable_source(&self, ctx: &ActorContext<Self>) {
+        let toggle_source_request = ToggleSourceRequest {
+            index_uid: self.params.pipeline_id.index_uid.to_string(),
+            source_id: self.params.pipeline_id.source_id.clone(),
+            enable: false,
+        };
+        let toggle_source_res = ctx
+            .protect_future(self.params.metastore.toggle_source(toggle_source_request))
+            .await;
+        match toggle_source_res {
+            Ok(_) => {
+                warn!(pipeline_id=?self.params.pipeline_id, "disabled source");
+                self.params
+                    .source_health
+                    .set_status(SourceHealthStatus::Disabled);
+            }
+            Err(error) => {
+                error!(pipeline_id=?self.params.pipeline_id, error=?error, "failed to disable source");
+                self.params
+                    .source_health
+                    .set_status(SourceHealthStatus::Failed);
+            }
+        }
+    }
+
     async fn terminate(&mut self) {
         self.kill_switch.kill();
         if let Some(handles) = self.handles_opt.take() {
@@ -516,6 +664,47 @@ impl Handler<SuperviseLoop> for IndexingPipeline {
     }
 }
 
+#[async_trait]
+impl Handler<CheckDocMapping> for IndexingPipeline {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        check_doc_mapping_token: CheckDocMapping,
+        ctx: &ActorContext<Self>,
+    ) -> Result<(), ActorExitStatus> {
+        // If the pipeline is not running, it will load the latest doc mapping when respawned.
+        if self.handles_opt.is_some() {
+            let index_id = self.params.pipeline_id.index_uid.index_id().to_string();
+            let index_metadata_request = IndexMetadataRequest::for_index_id(index_id);
+            let index_metadata_res = ctx
+                .protect_future(self.params.metastore.index_metadata(index_metadata_request))
+                .await
+                .and_then(|response| response.deserialize_index_metadata());
+            match index_metadata_res {
+                Ok(index_metadata)
+                    if index_metadata.doc_mapping_version > self.params.doc_mapping_version =>
+                {
+                    info!(
+                        pipeline_id=?self.params.pipeline_id,
+                        doc_mapping_version=index_metadata.doc_mapping_version,
+                        "doc mapping has been updated, respawning indexing pipeline"
+                    );
+                    self.terminate().await;
+                    self.handle(Spawn::default(), ctx).await?;
+                }
+                Ok(_) => {}
+                Err(error) => {
+                    warn!(error=?error, "failed to check for doc mapping updates");
+                }
+            }
+        }
+        ctx.schedule_self_msg(CHECK_DOC_MAPPING_INTERVAL, check_doc_mapping_token)
+            .await;
+        Ok(())
+    }
+}
+
 #[async_trait]
 impl Handler<Spawn> for IndexingPipeline {
     type Reply = ();
@@ -536,15 +725,10 @@ impl Handler<Spawn> for IndexingPipeline {
                 info!(error = ?spawn_error, "could not spawn pipeline, index might have been deleted");
                 return Err(ActorExitStatus::Success);
             }
-            let retry_delay = wait_duration_before_retry(spawn.retry_count + 1);
-            error!(error = ?spawn_error, retry_count = spawn.retry_count, retry_delay = ?retry_delay, "error while spawning indexing pipeline, retrying after some time");
-            ctx.schedule_self_msg(
-                retry_delay,
-                Spawn {
-                    retry_count: spawn.retry_count + 1,
-                },
-            )
-            .await;
+            error!(error = ?spawn_error, retry_count = spawn.retry_count, "error while spawning indexing pipeline");
+            let failure_message = format!("failed to spawn indexing pipeline: {spawn_error:#}");
+            self.handle_failure(failure_message, spawn.retry_count + 1, ctx)
+                .await;
         }
         Ok(())
     }
@@ -585,11 +769,13 @@ pub struct IndexingPipelineParams {
 
     // Indexing-related parameters
     pub doc_mapper: Arc<dyn DocMapper>,
+    pub doc_mapping_version: u64,
     pub indexing_directory: TempDirectory,
     pub indexing_settings: IndexingSettings,
     pub split_store: IndexingSplitStore,
     pub max_concurrent_split_uploads_index: usize,
     pub cooperative_indexing_permits: Option<Arc<Semaphore>>,
+    pub backfill_indexing_permits: Option<Arc<Semaphore>>,
 
     // Merge-related parameters
     pub merge_policy: Arc<dyn MergePolicy>,
@@ -601,6 +787,8 @@ pub struct IndexingPipelineParams {
     pub source_storage_resolver: StorageResolver,
     pub ingester_pool: IngesterPool,
     pub queues_dir_path: PathBuf,
+    pub dead_letter_samples: DeadLetterSamples,
+    pub source_health: SourceHealth,
 
     pub event_broker: EventBroker,
 }
@@ -613,7 +801,10 @@ mod tests {
 
     use quickwit_actors::{Command, Universe};
     use quickwit_common::ServiceStream;
-    use quickwit_config::{IndexingSettings, SourceInputFormat, SourceParams, VoidSourceParams};
+    use quickwit_config::{
+        BackoffStrategy, IndexingSettings, SourceInputFormat, SourceParams, SupervisionPolicy,
+        VoidSourceParams,
+    };
     use quickwit_doc_mapper::{default_doc_mapper_for_test, DefaultDocMapper};
     use quickwit_metastore::checkpoint::IndexCheckpointDelta;
     use quickwit_metastore::{IndexMetadata, PublishSplitsRequestExt};
@@ -702,6 +893,12 @@ mod tests {
             source_params: SourceParams::file(PathBuf::from("data/test_corpus.json")),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            csv_config: Default::default(),
+            dead_letter_queue: None,
+            supervision: Default::default(),
+            fetch_stream: Default::default(),
+            placement_constraints: Default::default(),
+            priority: Default::default(),
         };
         let storage = Arc::new(RamStorage::default());
         let split_store = IndexingSplitStore::create_without_local_store_for_test(storage.clone());
@@ -710,6 +907,7 @@ mod tests {
         let pipeline_params = IndexingPipelineParams {
             pipeline_id,
             doc_mapper: Arc::new(default_doc_mapper_for_test()),
+            doc_mapping_version: 0,
             source_config,
             source_storage_resolver: StorageResolver::for_test(),
             indexing_directory: TempDirectory::for_test(),
@@ -720,9 +918,12 @@ mod tests {
             split_store,
             merge_policy: default_merge_policy(),
             queues_dir_path: PathBuf::from("./queues"),
+            dead_letter_samples: DeadLetterSamples::default(),
+            source_health: SourceHealth::default(),
             max_concurrent_split_uploads_index: 4,
             max_concurrent_split_uploads_merge: 5,
             cooperative_indexing_permits: None,
+            backfill_indexing_permits: None,
             merge_planner_mailbox,
             event_broker,
         };
@@ -746,6 +947,80 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_indexing_pipeline_supervision_policy_auto_disable() {
+        let universe = Universe::with_accelerated_time();
+        let mut metastore = MetastoreServiceClient::mock();
+        metastore.expect_index_metadata().times(3).returning(|_| {
+            Err(MetastoreError::Connection {
+                message: "MetastoreError Alarm".to_string(),
+            })
+        });
+        metastore
+            .expect_toggle_source()
+            .withf(|toggle_source_request| {
+                toggle_source_request.source_id == "test-source" && !toggle_source_request.enable
+            })
+            .times(1)
+            .returning(|_| Ok(EmptyResponse {}));
+        let pipeline_id = IndexingPipelineId {
+            index_uid: "test-index:11111111111111111111111111".to_string().into(),
+            source_id: "test-source".to_string(),
+            node_id: "test-node".to_string(),
+            pipeline_uid: PipelineUid::from_u128(0u128),
+        };
+        let mut source_config =
+            SourceConfig::for_test("test-source", SourceParams::Void(VoidSourceParams));
+        source_config.supervision = SupervisionPolicy {
+            backoff: BackoffStrategy::Fixed { delay_secs: 1 },
+            max_consecutive_failures: Some(NonZeroUsize::new(3).unwrap()),
+            auto_disable: true,
+        };
+        let storage = Arc::new(RamStorage::default());
+        let split_store = IndexingSplitStore::create_without_local_store_for_test(storage.clone());
+        let (merge_planner_mailbox, _) = universe.create_test_mailbox();
+        let source_health = SourceHealth::default();
+        let pipeline_params = IndexingPipelineParams {
+            pipeline_id,
+            doc_mapper: Arc::new(default_doc_mapper_for_test()),
+            doc_mapping_version: 0,
+            source_config,
+            source_storage_resolver: StorageResolver::for_test(),
+            indexing_directory: TempDirectory::for_test(),
+            indexing_settings: IndexingSettings::for_test(),
+            ingester_pool: IngesterPool::default(),
+            metastore: MetastoreServiceClient::from(metastore),
+            storage,
+            split_store,
+            merge_policy: default_merge_policy(),
+            queues_dir_path: PathBuf::from("./queues"),
+            dead_letter_samples: DeadLetterSamples::default(),
+            source_health: source_health.clone(),
+            max_concurrent_split_uploads_index: 4,
+            max_concurrent_split_uploads_merge: 5,
+            cooperative_indexing_permits: None,
+            backfill_indexing_permits: None,
+            merge_planner_mailbox,
+            event_broker: EventBroker::default(),
+        };
+        let pipeline = IndexingPipeline::new(pipeline_params);
+        let (_pipeline_mailbox, pipeline_handle) = universe.spawn_builder().spawn(pipeline);
+
+        universe.sleep(Duration::from_secs(10)).await;
+        let pipeline_statistics = pipeline_handle.process_pending_and_observe().await;
+        assert_eq!(pipeline_statistics.generation, 0);
+
+        let source_health_report = source_health.report();
+        assert_eq!(source_health_report.status, SourceHealthStatus::Disabled);
+        assert_eq!(source_health_report.num_consecutive_failures, 3);
+        assert_eq!(source_health_report.num_restarts, 2);
+        assert_eq!(source_health_report.last_errors.len(), 3);
+        assert!(source_health_report.last_errors[0]
+            .message
+            .contains("failed to spawn indexing pipeline"));
+        universe.assert_quit().await;
+    }
+
     #[tokio::test]
     async fn test_indexing_pipeline_simple() -> anyhow::Result<()> {
         let mut metastore = MetastoreServiceClient::mock();
@@ -802,6 +1077,12 @@ mod tests {
             source_params: SourceParams::file(PathBuf::from("data/test_corpus.json")),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            csv_config: Default::default(),
+            dead_letter_queue: None,
+            supervision: Default::default(),
+            fetch_stream: Default::default(),
+            placement_constraints: Default::default(),
+            priority: Default::default(),
         };
         let storage = Arc::new(RamStorage::default());
         let split_store = IndexingSplitStore::create_without_local_store_for_test(storage.clone());
@@ -809,6 +1090,7 @@ mod tests {
         let pipeline_params = IndexingPipelineParams {
             pipeline_id,
             doc_mapper: Arc::new(default_doc_mapper_for_test()),
+            doc_mapping_version: 0,
             source_config,
             source_storage_resolver: StorageResolver::for_test(),
             indexing_directory: TempDirectory::for_test(),
@@ -816,12 +1098,15 @@ mod tests {
             ingester_pool: IngesterPool::default(),
             metastore: MetastoreServiceClient::from(metastore),
             queues_dir_path: PathBuf::from("./queues"),
+            dead_letter_samples: DeadLetterSamples::default(),
+            source_health: SourceHealth::default(),
             storage,
             split_store,
             merge_policy: default_merge_policy(),
             max_concurrent_split_uploads_index: 4,
             max_concurrent_split_uploads_merge: 5,
             cooperative_indexing_permits: None,
+            backfill_indexing_permits: None,
             merge_planner_mailbox,
             event_broker: Default::default(),
         };
@@ -869,6 +1154,12 @@ mod tests {
             source_params: SourceParams::Void(VoidSourceParams),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            csv_config: Default::default(),
+            dead_letter_queue: None,
+            supervision: Default::default(),
+            fetch_stream: Default::default(),
+            placement_constraints: Default::default(),
+            priority: Default::default(),
         };
         let metastore = MetastoreServiceClient::from(mock_metastore);
         let storage = Arc::new(RamStorage::default());
@@ -882,6 +1173,7 @@ mod tests {
             merge_policy: default_merge_policy(),
             max_concurrent_split_uploads: 2,
             merge_max_io_num_bytes_per_sec: None,
+            merge_concurrency_permits: None,
             event_broker: Default::default(),
         };
         let merge_pipeline = MergePipeline::new(merge_pipeline_params, universe.spawn_ctx());
@@ -891,6 +1183,7 @@ mod tests {
         let indexing_pipeline_params = IndexingPipelineParams {
             pipeline_id,
             doc_mapper,
+            doc_mapping_version: 0,
             source_config,
             source_storage_resolver: StorageResolver::for_test(),
             indexing_directory: TempDirectory::for_test(),
@@ -898,12 +1191,15 @@ mod tests {
             ingester_pool: IngesterPool::default(),
             metastore,
             queues_dir_path: PathBuf::from("./queues"),
+            dead_letter_samples: DeadLetterSamples::default(),
+            source_health: SourceHealth::default(),
             storage,
             split_store,
             merge_policy: default_merge_policy(),
             max_concurrent_split_uploads_index: 4,
             max_concurrent_split_uploads_merge: 5,
             cooperative_indexing_permits: None,
+            backfill_indexing_permits: None,
             merge_planner_mailbox: merge_planner_mailbox.clone(),
             event_broker: Default::default(),
         };
@@ -987,6 +1283,12 @@ mod tests {
             source_params: SourceParams::file(PathBuf::from("data/test_corpus.json")),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            csv_config: Default::default(),
+            dead_letter_queue: None,
+            supervision: Default::default(),
+            fetch_stream: Default::default(),
+            placement_constraints: Default::default(),
+            priority: Default::default(),
         };
         let storage = Arc::new(RamStorage::default());
         let split_store = IndexingSplitStore::create_without_local_store_for_test(storage.clone());
@@ -1012,6 +1314,7 @@ mod tests {
         let pipeline_params = IndexingPipelineParams {
             pipeline_id,
             doc_mapper: Arc::new(broken_mapper),
+            doc_mapping_version: 0,
             source_config,
             source_storage_resolver: StorageResolver::for_test(),
             indexing_directory: TempDirectory::for_test(),
@@ -1019,12 +1322,15 @@ mod tests {
             ingester_pool: IngesterPool::default(),
             metastore: MetastoreServiceClient::from(metastore),
             queues_dir_path: PathBuf::from("./queues"),
+            dead_letter_samples: DeadLetterSamples::default(),
+            source_health: SourceHealth::default(),
             storage,
             split_store,
             merge_policy: default_merge_policy(),
             max_concurrent_split_uploads_index: 4,
             max_concurrent_split_uploads_merge: 5,
             cooperative_indexing_permits: None,
+            backfill_indexing_permits: None,
             merge_planner_mailbox,
             event_broker: Default::default(),
         };
@@ -17,10 +17,11 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::{Debug, Display, Formatter};
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::Context;
 use async_trait::async_trait;
@@ -32,6 +33,7 @@ use quickwit_actors::{
     Observation,
 };
 use quickwit_cluster::Cluster;
+use quickwit_common::cgroup::CgroupLimits;
 use quickwit_common::fs::get_cache_directory_path;
 use quickwit_common::pubsub::EventBroker;
 use quickwit_common::temp_dir;
@@ -48,8 +50,9 @@ use quickwit_proto::indexing::{
 };
 use quickwit_proto::metastore::{
     IndexMetadataRequest, ListIndexesMetadataRequest, MetastoreService, MetastoreServiceClient,
+    SetMergesPausedRequest,
 };
-use quickwit_proto::types::{IndexId, IndexUid, PipelineUid};
+use quickwit_proto::types::{IndexId, IndexUid, PipelineUid, SourceId};
 use quickwit_storage::StorageResolver;
 use serde::{Deserialize, Serialize};
 use tokio::sync::Semaphore;
@@ -57,14 +60,28 @@ use tracing::{debug, error, info, warn};
 
 use super::merge_pipeline::{MergePipeline, MergePipelineParams};
 use super::MergePlanner;
-use crate::models::{DetachIndexingPipeline, DetachMergePipeline, ObservePipeline, SpawnPipeline};
+use crate::autotuning::IndexerAutotuning;
+use crate::dead_letter_queue::{DeadLetter, DeadLetterSamples};
+use crate::models::{
+    DetachIndexingPipeline, DetachMergePipeline, GetDeadLetters, GetSourceHealth, ObservePipeline,
+    PauseMerges, ResumeMerges, SetMergesPaused, SpawnPipeline,
+};
 use crate::source::{AssignShards, Assignment};
+use crate::source_health::{SourceHealth, SourceHealthReport};
 use crate::split_store::{LocalSplitStore, SplitStoreQuota};
 use crate::{IndexingPipeline, IndexingPipelineParams, IndexingSplitStore, IndexingStatistics};
 
 /// Name of the indexing directory, usually located at `<data_dir_path>/indexing`.
 pub const INDEXING_DIR_NAME: &str = "indexing";
 
+/// Interval at which the merges paused flags of the indexes with merge pipelines on the node are
+/// reloaded from the metastore.
+const REFRESH_MERGES_PAUSED_INTERVAL: Duration = if cfg!(test) {
+    Duration::from_millis(100)
+} else {
+    Duration::from_secs(30)
+};
+
 #[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
 pub struct IndexingServiceCounters {
     pub num_running_pipelines: usize,
@@ -128,7 +145,23 @@ pub struct IndexingService {
     max_concurrent_split_uploads: usize,
     merge_pipeline_handles: HashMap<MergePipelineId, MergePipelineHandle>,
     cooperative_indexing_permits: Option<Arc<Semaphore>>,
+    // Limits the number of cooperative indexing permits held by backfill pipelines so that
+    // realtime pipelines always have at least one permit available.
+    backfill_indexing_permits: Option<Arc<Semaphore>>,
+    // Limits the number of merges executing concurrently across the merge pipelines of the node.
+    merge_concurrency_permits: Option<Arc<Semaphore>>,
+    autotuning: IndexerAutotuning,
     event_broker: EventBroker,
+    // Recent dead letters per (index ID, source ID). They outlive the pipelines so that they
+    // remain available after a pipeline respawn or exit, and are removed once the last pipeline of
+    // the source is shut down on the node.
+    dead_letter_samples: HashMap<(IndexId, SourceId), DeadLetterSamples>,
+    // Health of the pipelines per (index ID, source ID). Like the dead letters, it outlives the
+    // pipelines so that restart counters and recent errors survive respawns.
+    source_healths: HashMap<(IndexId, SourceId), SourceHealth>,
+    // Indexes whose merges are paused, as last applied to the merge pipelines of this node. The
+    // source of truth is the `merges_paused` flag of the index metadata.
+    merges_paused_index_ids: HashSet<IndexId>,
 }
 
 impl Debug for IndexingService {
@@ -166,11 +199,28 @@ impl IndexingService {
         let indexing_root_directory =
             temp_dir::create_or_purge_directory(&data_dir_path.join(INDEXING_DIR_NAME)).await?;
         let queue_dir_path = data_dir_path.join(QUEUES_DIR_NAME);
+        let autotuning = IndexerAutotuning::new(num_blocking_threads, CgroupLimits::get());
+        info!(autotuning=?autotuning, "autotuned indexer resources");
+
         let cooperative_indexing_permits = if indexer_config.enable_cooperative_indexing {
-            Some(Arc::new(Semaphore::new(num_blocking_threads)))
+            Some(Arc::new(Semaphore::new(
+                autotuning.num_cooperative_indexing_permits,
+            )))
         } else {
             None
         };
+        // With a single cooperative indexing permit, backfill pipelines cannot be restricted
+        // without being starved.
+        let backfill_indexing_permits = cooperative_indexing_permits.as_ref().map(|_| {
+            let num_backfill_indexing_permits = autotuning
+                .num_cooperative_indexing_permits
+                .saturating_sub(1)
+                .max(1);
+            Arc::new(Semaphore::new(num_backfill_indexing_permits))
+        });
+        let merge_concurrency_permits = autotuning
+            .max_concurrent_merges
+            .map(|max_concurrent_merges| Arc::new(Semaphore::new(max_concurrent_merges)));
         Ok(IndexingService {
             node_id,
             indexing_root_directory,
@@ -186,7 +236,13 @@ impl IndexingService {
             max_concurrent_split_uploads: indexer_config.max_concurrent_split_uploads,
             merge_pipeline_handles: HashMap::new(),
             cooperative_indexing_permits,
+            backfill_indexing_permits,
+            merge_concurrency_permits,
+            autotuning,
             event_broker,
+            dead_letter_samples: HashMap::new(),
+            source_healths: HashMap::new(),
+            merges_paused_index_ids: HashSet::new(),
         })
     }
 
@@ -199,9 +255,32 @@ impl IndexingService {
             .remove(&pipeline_uid)
             .ok_or(IndexingError::MissingPipeline { pipeline_uid })?;
         self.counters.num_running_pipelines -= 1;
+        self.prune_source_states();
         Ok(pipeline_handle.handle)
     }
 
+    /// Removes the dead letters and the health of the sources that no longer have any indexing
+    /// pipeline on the node. Called when pipelines are detached or shut down, for instance when
+    /// their index or source is deleted.
+    fn prune_source_states(&mut self) {
+        let running_sources: HashSet<(&str, &str)> = self
+            .indexing_pipelines
+            .values()
+            .map(|pipeline_handle| {
+                let pipeline_id = &pipeline_handle.indexing_pipeline_id;
+                (
+                    pipeline_id.index_uid.index_id(),
+                    pipeline_id.source_id.as_str(),
+                )
+            })
+            .collect();
+        let is_running = |(index_id, source_id): &(IndexId, SourceId)| {
+            running_sources.contains(&(index_id.as_str(), source_id.as_str()))
+        };
+        self.dead_letter_samples.retain(|key, _| is_running(key));
+        self.source_healths.retain(|key, _| is_running(key));
+    }
+
     async fn detach_merge_pipeline(
         &mut self,
         pipeline_id: &MergePipelineId,
@@ -243,9 +322,18 @@ impl IndexingService {
             node_id: self.node_id.clone(),
             pipeline_uid,
         };
+        let doc_mapping_version = index_metadata.doc_mapping_version;
+        let merges_paused = index_metadata.merges_paused;
         let index_config = index_metadata.into_index_config();
-        self.spawn_pipeline_inner(ctx, pipeline_id.clone(), index_config, source_config)
-            .await?;
+        self.spawn_pipeline_inner(
+            ctx,
+            pipeline_id.clone(),
+            index_config,
+            doc_mapping_version,
+            merges_paused,
+            source_config,
+        )
+        .await?;
         Ok(pipeline_id)
     }
 
@@ -254,6 +342,8 @@ impl IndexingService {
         ctx: &ActorContext<Self>,
         pipeline_id: IndexingPipelineId,
         index_config: IndexConfig,
+        doc_mapping_version: u64,
+        merges_paused: bool,
         source_config: SourceConfig,
     ) -> Result<(), IndexingError> {
         if self
@@ -274,14 +364,19 @@ impl IndexingService {
             .join(&pipeline_uid_str)
             .tempdir_in(&self.indexing_root_directory)
             .map_err(IndexingError::Io)?;
+        let encryption_config_opt = index_config.encryption.as_ref();
         let storage = self
             .storage_resolver
-            .resolve(&index_config.index_uri)
+            .resolve_index_storage(&index_config.index_uri, encryption_config_opt)
             .await
             .map_err(|err| IndexingError::StorageResolverError(err.to_string()))?;
         let merge_policy =
             crate::merge_policy::merge_policy_from_settings(&index_config.indexing_settings);
-        let split_store = IndexingSplitStore::new(storage.clone(), self.local_split_store.clone());
+        let encryption_key_id_opt = encryption_config_opt
+            .and_then(|encryption_config| encryption_config.data_key_id())
+            .map(str::to_string);
+        let split_store = IndexingSplitStore::new(storage.clone(), self.local_split_store.clone())
+            .with_encryption_key_id(encryption_key_id_opt);
 
         let doc_mapper = build_doc_mapper(&index_config.doc_mapping, &index_config.search_settings)
             .map_err(IndexingError::InvalidParams)?;
@@ -298,11 +393,12 @@ impl IndexingService {
                 .resources
                 .max_merge_write_throughput,
             max_concurrent_split_uploads: self.max_concurrent_split_uploads,
+            merge_concurrency_permits: self.merge_concurrency_permits.clone(),
             event_broker: self.event_broker.clone(),
         };
 
         let merge_planner_mailbox = self
-            .get_or_create_merge_pipeline(merge_pipeline_params, ctx)
+            .get_or_create_merge_pipeline(merge_pipeline_params, merges_paused, ctx)
             .await?;
 
         // The concurrent uploads budget is split in 2: 1/2 for the indexing pipeline, 1/2 for the
@@ -311,17 +407,49 @@ impl IndexingService {
         let max_concurrent_split_uploads_merge =
             (self.max_concurrent_split_uploads - max_concurrent_split_uploads_index).max(1);
 
+        let dead_letter_samples = self
+            .dead_letter_samples
+            .entry((
+                pipeline_id.index_uid.index_id().to_string(),
+                pipeline_id.source_id.clone(),
+            ))
+            .or_default()
+            .clone();
+        let source_health = self
+            .source_healths
+            .entry((
+                pipeline_id.index_uid.index_id().to_string(),
+                pipeline_id.source_id.clone(),
+            ))
+            .or_default()
+            .clone();
+
+        let mut indexing_settings = index_config.indexing_settings.clone();
+        let heap_size = indexing_settings.resources.heap_size;
+        let heap_size_per_workbench = self.autotuning.heap_size_per_workbench(heap_size);
+
+        if heap_size_per_workbench < heap_size {
+            info!(
+                index_id=%pipeline_id.index_uid.index_id(),
+                heap_size=%heap_size,
+                heap_size_per_workbench=%heap_size_per_workbench,
+                "capping heap size to fit within the memory limit of the node"
+            );
+            indexing_settings.resources.heap_size = heap_size_per_workbench;
+        }
         let pipeline_params = IndexingPipelineParams {
             pipeline_id: pipeline_id.clone(),
             metastore: self.metastore.clone(),
             storage,
             // Indexing-related parameters
             doc_mapper,
+            doc_mapping_version,
             indexing_directory,
-            indexing_settings: index_config.indexing_settings.clone(),
+            indexing_settings,
             split_store,
             max_concurrent_split_uploads_index,
             cooperative_indexing_permits: self.cooperative_indexing_permits.clone(),
+            backfill_indexing_permits: self.backfill_indexing_permits.clone(),
             // Merge-related parameters
             merge_policy,
             max_concurrent_split_uploads_merge,
@@ -332,6 +460,8 @@ impl IndexingService {
             ingester_pool: self.ingester_pool.clone(),
             queues_dir_path: self.queue_dir_path.clone(),
             source_storage_resolver: self.storage_resolver.clone(),
+            dead_letter_samples,
+            source_health,
 
             event_broker: self.event_broker.clone(),
         };
@@ -439,21 +569,50 @@ impl IndexingService {
         self.cluster
             .update_self_node_pipeline_metrics(&pipeline_metrics)
             .await;
+
+        let partition_backlogs: Vec<(&IndexingPipelineId, BTreeMap<String, u64>)> = self
+            .indexing_pipelines
+            .values()
+            .filter_map(|pipeline_handle| {
+                let indexing_statistics = pipeline_handle.handle.last_observation();
+                if indexing_statistics.partition_backlogs.is_empty() {
+                    return None;
+                }
+                let partition_backlogs = indexing_statistics.partition_backlogs.clone();
+                Some((&pipeline_handle.indexing_pipeline_id, partition_backlogs))
+            })
+            .collect();
+        let source_backlogs: HashMap<&IndexingPipelineId, &BTreeMap<String, u64>> =
+            partition_backlogs
+                .iter()
+                .map(|(pipeline_id, partition_backlogs)| (*pipeline_id, partition_backlogs))
+                .collect();
+        self.cluster
+            .update_self_node_source_backlogs(&source_backlogs)
+            .await;
         Ok(())
     }
 
     async fn get_or_create_merge_pipeline(
         &mut self,
         merge_pipeline_params: MergePipelineParams,
+        merges_paused: bool,
         ctx: &ActorContext<Self>,
     ) -> Result<Mailbox<MergePlanner>, IndexingError> {
         let merge_pipeline_id = MergePipelineId::from(&merge_pipeline_params.pipeline_id);
+        let index_id = merge_pipeline_id.index_uid.index_id();
+
+        if self.merges_paused_index_ids.contains(index_id) != merges_paused {
+            self.set_merges_paused(index_id.to_string(), merges_paused)
+                .await;
+        }
         if let Some(merge_pipeline_mailbox_handle) =
             self.merge_pipeline_handles.get(&merge_pipeline_id)
         {
             return Ok(merge_pipeline_mailbox_handle.mailbox.clone());
         }
-        let merge_pipeline = MergePipeline::new(merge_pipeline_params, ctx.spawn_ctx());
+        let merge_pipeline = MergePipeline::new(merge_pipeline_params, ctx.spawn_ctx())
+            .with_merges_paused(merges_paused);
         let merge_planner_mailbox = merge_pipeline.merge_planner_mailbox().clone();
         let (_pipeline_mailbox, pipeline_handle) = ctx.spawn_actor().spawn(merge_pipeline);
         let merge_pipeline_mailbox_handle = MergePipelineHandle {
@@ -466,6 +625,94 @@ impl IndexingService {
         Ok(merge_planner_mailbox)
     }
 
+    /// Pauses or resumes the merges of an index. The flag is persisted in the index metadata, from
+    /// where the indexers running merge pipelines for the index pick it up, then applied to the
+    /// merge pipelines of this node right away. Returns the number of merge pipelines of the index
+    /// running on this node.
+    async fn persist_merges_paused(
+        &mut self,
+        ctx: &ActorContext<Self>,
+        index_id: String,
+        merges_paused: bool,
+    ) -> Result<usize, IndexingError> {
+        let index_metadata = self.index_metadata(ctx, &index_id).await?;
+        let set_merges_paused_request = SetMergesPausedRequest {
+            index_uid: index_metadata.index_uid.to_string(),
+            merges_paused,
+        };
+        let _protect_guard = ctx.protect_zone();
+        self.metastore
+            .set_merges_paused(set_merges_paused_request)
+            .await
+            .map_err(|error| IndexingError::MetastoreError(error.to_string()))?;
+        let num_merge_pipelines = self.set_merges_paused(index_id, merges_paused).await;
+        Ok(num_merge_pipelines)
+    }
+
+    /// Reloads from the metastore the merges paused flags of the indexes with merge pipelines on
+    /// this node and applies the ones that changed, for instance because merges were paused or
+    /// resumed through another node.
+    async fn refresh_merges_paused(&mut self, ctx: &ActorContext<Self>) -> anyhow::Result<()> {
+        let index_id_patterns: Vec<String> = self
+            .merge_pipeline_handles
+            .keys()
+            .map(|merge_pipeline_id| merge_pipeline_id.index_uid.index_id().to_string())
+            .unique()
+            .collect();
+        if index_id_patterns.is_empty() {
+            return Ok(());
+        }
+        let list_indexes_metadata_request = ListIndexesMetadataRequest { index_id_patterns };
+        let indexes_metadata = ctx
+            .protect_future(
+                self.metastore
+                    .list_indexes_metadata(list_indexes_metadata_request),
+            )
+            .await?
+            .deserialize_indexes_metadata()?;
+
+        for index_metadata in indexes_metadata {
+            let index_id = index_metadata.index_id();
+
+            if self.merges_paused_index_ids.contains(index_id) != index_metadata.merges_paused {
+                self.set_merges_paused(index_id.to_string(), index_metadata.merges_paused)
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Pauses or resumes the merges of the merge pipelines of an index running on this node and
+    /// returns the number of merge pipelines affected.
+    async fn set_merges_paused(&mut self, index_id: String, merges_paused: bool) -> usize {
+        let mut num_merge_pipelines = 0;
+
+        for (merge_pipeline_id, merge_pipeline_handle) in &self.merge_pipeline_handles {
+            if merge_pipeline_id.index_uid.index_id() != index_id {
+                continue;
+            }
+            let set_merges_paused = SetMergesPaused { merges_paused };
+
+            if merge_pipeline_handle
+                .handle
+                .mailbox()
+                .send_message(set_merges_paused)
+                .await
+                .is_ok()
+            {
+                num_merge_pipelines += 1;
+            }
+        }
+        if merges_paused {
+            info!(index_id=%index_id, num_merge_pipelines, "pausing merges");
+            self.merges_paused_index_ids.insert(index_id);
+        } else {
+            info!(index_id=%index_id, num_merge_pipelines, "resuming merges");
+            self.merges_paused_index_ids.remove(&index_id);
+        }
+        num_merge_pipelines
+    }
+
     async fn find_and_shutdown_decommissioned_pipelines(&mut self, tasks: &[IndexingTask]) {
         let pipeline_uids_in_plan: FnvHashSet<PipelineUid> = tasks
             .iter()
@@ -589,6 +836,8 @@ impl IndexingService {
                             ctx,
                             new_pipeline_id.clone(),
                             index_metadata.index_config.clone(),
+                            index_metadata.doc_mapping_version,
+                            index_metadata.merges_paused,
                             source_config.clone(),
                         )
                         .await
@@ -743,6 +992,40 @@ impl Handler<ObservePipeline> for IndexingService {
     }
 }
 
+#[async_trait]
+impl Handler<GetDeadLetters> for IndexingService {
+    type Reply = Option<Vec<DeadLetter>>;
+
+    async fn handle(
+        &mut self,
+        msg: GetDeadLetters,
+        _ctx: &ActorContext<Self>,
+    ) -> Result<Self::Reply, ActorExitStatus> {
+        let dead_letters_opt = self
+            .dead_letter_samples
+            .get(&(msg.index_id, msg.source_id))
+            .map(|dead_letter_samples| dead_letter_samples.recent(msg.limit));
+        Ok(dead_letters_opt)
+    }
+}
+
+#[async_trait]
+impl Handler<GetSourceHealth> for IndexingService {
+    type Reply = Option<SourceHealthReport>;
+
+    async fn handle(
+        &mut self,
+        msg: GetSourceHealth,
+        _ctx: &ActorContext<Self>,
+    ) -> Result<Self::Reply, ActorExitStatus> {
+        let source_health_report_opt = self
+            .source_healths
+            .get(&(msg.index_id, msg.source_id))
+            .map(|source_health| source_health.report());
+        Ok(source_health_report_opt)
+    }
+}
+
 #[async_trait]
 impl Handler<DetachIndexingPipeline> for IndexingService {
     type Reply = Result<ActorHandle<IndexingPipeline>, IndexingError>;
@@ -769,6 +1052,53 @@ impl Handler<DetachMergePipeline> for IndexingService {
     }
 }
 
+#[async_trait]
+impl Handler<PauseMerges> for IndexingService {
+    type Reply = Result<usize, IndexingError>;
+
+    async fn handle(
+        &mut self,
+        msg: PauseMerges,
+        ctx: &ActorContext<Self>,
+    ) -> Result<Self::Reply, ActorExitStatus> {
+        Ok(self.persist_merges_paused(ctx, msg.index_id, true).await)
+    }
+}
+
+#[async_trait]
+impl Handler<ResumeMerges> for IndexingService {
+    type Reply = Result<usize, IndexingError>;
+
+    async fn handle(
+        &mut self,
+        msg: ResumeMerges,
+        ctx: &ActorContext<Self>,
+    ) -> Result<Self::Reply, ActorExitStatus> {
+        Ok(self.persist_merges_paused(ctx, msg.index_id, false).await)
+    }
+}
+
+#[derive(Debug)]
+struct RefreshMergesPausedLoop;
+
+#[async_trait]
+impl Handler<RefreshMergesPausedLoop> for IndexingService {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        _message: RefreshMergesPausedLoop,
+        ctx: &ActorContext<Self>,
+    ) -> Result<(), ActorExitStatus> {
+        if let Err(error) = self.refresh_merges_paused(ctx).await {
+            warn!(error=?error, "failed to refresh merges paused flags");
+        }
+        ctx.schedule_self_msg(REFRESH_MERGES_PAUSED_INTERVAL, RefreshMergesPausedLoop)
+            .await;
+        Ok(())
+    }
+}
+
 #[derive(Debug)]
 struct SuperviseLoop;
 
@@ -798,6 +1128,8 @@ impl Actor for IndexingService {
 
     async fn initialize(&mut self, ctx: &ActorContext<Self>) -> Result<(), ActorExitStatus> {
         self.run_ingest_api_queues_gc().await?;
+        ctx.schedule_self_msg(REFRESH_MERGES_PAUSED_INTERVAL, RefreshMergesPausedLoop)
+            .await;
         self.handle(SuperviseLoop, ctx).await
     }
 }
@@ -857,7 +1189,7 @@ mod tests {
     use std::path::Path;
     use std::time::Duration;
 
-    use quickwit_actors::{Health, ObservationType, Supervisable, Universe, HEARTBEAT};
+    use quickwit_actors::{Health, ObservationType, Observe, Supervisable, Universe, HEARTBEAT};
     use quickwit_cluster::{create_cluster_for_test, ChannelTransport};
     use quickwit_common::rand::append_random_suffix;
     use quickwit_common::ServiceStream;
@@ -953,6 +1285,12 @@ mod tests {
             source_params: SourceParams::void(),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            csv_config: Default::default(),
+            dead_letter_queue: None,
+            supervision: Default::default(),
+            fetch_stream: Default::default(),
+            placement_constraints: Default::default(),
+            priority: Default::default(),
         };
         let spawn_pipeline_msg = SpawnPipeline {
             index_id: index_id.clone(),
@@ -990,6 +1328,14 @@ mod tests {
         assert_eq!(observation.generation, 1);
         assert_eq!(observation.num_spawn_attempts, 1);
 
+        let get_dead_letters = || GetDeadLetters {
+            index_id: index_id.clone(),
+            source_id: source_config_0.source_id.clone(),
+            limit: 10,
+        };
+        let dead_letters_opt = indexing_service.ask(get_dead_letters()).await.unwrap();
+        assert_eq!(dead_letters_opt, Some(Vec::new()));
+
         // Test detach.
         let pipeline_handle = indexing_service
             .ask_for_res(DetachIndexingPipeline {
@@ -998,6 +1344,20 @@ mod tests {
             .await
             .unwrap();
         pipeline_handle.kill().await;
+
+        // The dead letters and the health of the source are removed along with its last pipeline.
+        let dead_letters_opt = indexing_service.ask(get_dead_letters()).await.unwrap();
+        assert!(dead_letters_opt.is_none());
+
+        let source_health_opt = indexing_service
+            .ask(GetSourceHealth {
+                index_id: index_id.clone(),
+                source_id: source_config_0.source_id.clone(),
+            })
+            .await
+            .unwrap();
+        assert!(source_health_opt.is_none());
+
         let _merge_pipeline = indexing_service
             .ask_for_res(DetachMergePipeline {
                 pipeline_id: MergePipelineId::from(&pipeline_id),
@@ -1044,6 +1404,12 @@ mod tests {
             }),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            csv_config: Default::default(),
+            dead_letter_queue: None,
+            supervision: Default::default(),
+            fetch_stream: Default::default(),
+            placement_constraints: Default::default(),
+            priority: Default::default(),
         };
         indexing_service
             .ask_for_res(SpawnPipeline {
@@ -1110,6 +1476,12 @@ mod tests {
             source_params: SourceParams::void(),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            csv_config: Default::default(),
+            dead_letter_queue: None,
+            supervision: Default::default(),
+            fetch_stream: Default::default(),
+            placement_constraints: Default::default(),
+            priority: Default::default(),
         };
         let add_source_request =
             AddSourceRequest::try_from_source_config(index_uid.clone(), source_config_1.clone())
@@ -1152,6 +1524,8 @@ mod tests {
             client_log_level: None,
             client_params: serde_json::Value::Null,
             enable_backfill_mode: false,
+            header_filters: Vec::new(),
+            header_field_mappings: Vec::new(),
         };
         let source_config_2 = SourceConfig {
             source_id: "test-indexing-service--source-2".to_string(),
@@ -1161,6 +1535,12 @@ mod tests {
             source_params: SourceParams::Kafka(kafka_params),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            csv_config: Default::default(),
+            dead_letter_queue: None,
+            supervision: Default::default(),
+            fetch_stream: Default::default(),
+            placement_constraints: Default::default(),
+            priority: Default::default(),
         };
         let add_source_request_2 =
             AddSourceRequest::try_from_source_config(index_uid.clone(), source_config_2.clone())
@@ -1319,6 +1699,12 @@ mod tests {
             source_params: SourceParams::void(),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            csv_config: Default::default(),
+            dead_letter_queue: None,
+            supervision: Default::default(),
+            fetch_stream: Default::default(),
+            placement_constraints: Default::default(),
+            priority: Default::default(),
         };
         let create_index_request = CreateIndexRequest::try_from_index_config(index_config).unwrap();
         let index_uid: IndexUid = metastore
@@ -1395,6 +1781,172 @@ mod tests {
         universe.quit().await;
     }
 
+    async fn observe_merges_paused(universe: &Universe) -> bool {
+        // Lets the merge pipeline spawn the merge planner or forward it the flag.
+        universe.sleep(Duration::from_secs(1)).await;
+        let merge_planner_mailbox = universe.get_one::<MergePlanner>().unwrap();
+        merge_planner_mailbox
+            .ask(Observe)
+            .await
+            .unwrap()
+            .merges_paused
+    }
+
+    #[tokio::test]
+    async fn test_indexing_service_pause_and_resume_merges() {
+        quickwit_common::setup_logging_for_tests();
+        let transport = ChannelTransport::default();
+        let cluster = create_cluster_for_test(Vec::new(), &["indexer"], &transport, true)
+            .await
+            .unwrap();
+        let mut metastore = metastore_for_test();
+
+        let index_id = append_random_suffix("test-indexing-service");
+        let index_uri = format!("ram:///indexes/{index_id}");
+        let index_config = IndexConfig::for_test(&index_id, &index_uri);
+
+        let source_config = SourceConfig {
+            source_id: "test-indexing-service--source".to_string(),
+            max_num_pipelines_per_indexer: NonZeroUsize::new(1).unwrap(),
+            desired_num_pipelines: NonZeroUsize::new(1).unwrap(),
+            enabled: true,
+            source_params: SourceParams::void(),
+            transform_config: None,
+            input_format: SourceInputFormat::Json,
+            csv_config: Default::default(),
+            dead_letter_queue: None,
+            supervision: Default::default(),
+            fetch_stream: Default::default(),
+            placement_constraints: Default::default(),
+            priority: Default::default(),
+        };
+        let create_index_request = CreateIndexRequest::try_from_index_config(index_config).unwrap();
+        let index_uid: IndexUid = metastore
+            .create_index(create_index_request)
+            .await
+            .unwrap()
+            .index_uid
+            .into();
+        let add_source_request =
+            AddSourceRequest::try_from_source_config(index_uid.clone(), source_config.clone())
+                .unwrap();
+        metastore.add_source(add_source_request).await.unwrap();
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let data_dir_path = temp_dir.path().to_path_buf();
+        let indexer_config = IndexerConfig::for_test().unwrap();
+        let num_blocking_threads = 1;
+        let storage_resolver = StorageResolver::unconfigured();
+        let universe = Universe::with_accelerated_time();
+        let queues_dir_path = data_dir_path.join(QUEUES_DIR_NAME);
+        let ingest_api_service =
+            init_ingest_api(&universe, &queues_dir_path, &IngestApiConfig::default())
+                .await
+                .unwrap();
+        let indexing_server = IndexingService::new(
+            "test-node".to_string(),
+            data_dir_path,
+            indexer_config,
+            num_blocking_threads,
+            cluster.clone(),
+            metastore.clone(),
+            Some(ingest_api_service),
+            IngesterPool::default(),
+            storage_resolver.clone(),
+            EventBroker::default(),
+        )
+        .await
+        .unwrap();
+        let (indexing_server_mailbox, _indexing_server_handle) =
+            universe.spawn_builder().spawn(indexing_server);
+
+        // Pausing the merges of an index without merge pipelines is persisted in the index
+        // metadata for the merge pipelines spawned later on.
+        let num_merge_pipelines = indexing_server_mailbox
+            .ask_for_res(PauseMerges {
+                index_id: index_id.clone(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(num_merge_pipelines, 0);
+
+        let index_metadata = metastore
+            .index_metadata(IndexMetadataRequest::for_index_id(index_id.clone()))
+            .await
+            .unwrap()
+            .deserialize_index_metadata()
+            .unwrap();
+        assert!(index_metadata.merges_paused);
+
+        indexing_server_mailbox
+            .ask_for_res(SpawnPipeline {
+                index_id: index_id.clone(),
+                source_config: source_config.clone(),
+                pipeline_uid: PipelineUid::default(),
+            })
+            .await
+            .unwrap();
+        assert!(observe_merges_paused(&universe).await);
+
+        let error = indexing_server_mailbox
+            .ask_for_res(PauseMerges {
+                index_id: "unknown-index".to_string(),
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(error, IndexingError::MetastoreError(_)));
+
+        let num_merge_pipelines = indexing_server_mailbox
+            .ask_for_res(ResumeMerges {
+                index_id: index_id.clone(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(num_merge_pipelines, 1);
+
+        assert!(!observe_merges_paused(&universe).await);
+
+        let num_merge_pipelines = indexing_server_mailbox
+            .ask_for_res(PauseMerges {
+                index_id: index_id.clone(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(num_merge_pipelines, 1);
+
+        universe.quit().await;
+
+        // The pause survives the respawn of the pipelines on another indexer.
+        let universe = Universe::with_accelerated_time();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let (indexing_server_mailbox, _indexing_server_handle) =
+            spawn_indexing_service_for_test(temp_dir.path(), &universe, metastore.clone(), cluster)
+                .await;
+        indexing_server_mailbox
+            .ask_for_res(SpawnPipeline {
+                index_id: index_id.clone(),
+                source_config,
+                pipeline_uid: PipelineUid::default(),
+            })
+            .await
+            .unwrap();
+        assert!(observe_merges_paused(&universe).await);
+
+        // Merges resumed through another node are picked up from the index metadata.
+        metastore
+            .set_merges_paused(SetMergesPausedRequest {
+                index_uid: index_uid.to_string(),
+                merges_paused: false,
+            })
+            .await
+            .unwrap();
+        universe.sleep(REFRESH_MERGES_PAUSED_INTERVAL * 2).await;
+
+        assert!(!observe_merges_paused(&universe).await);
+
+        universe.quit().await;
+    }
+
     #[derive(Debug)]
     struct FreezePipeline;
     #[async_trait]
@@ -1447,6 +1999,12 @@ mod tests {
             source_params: SourceParams::void(),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            csv_config: Default::default(),
+            dead_letter_queue: None,
+            supervision: Default::default(),
+            fetch_stream: Default::default(),
+            placement_constraints: Default::default(),
+            priority: Default::default(),
         };
         index_metadata
             .sources
@@ -17,7 +17,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::ops::RangeInclusive;
 use std::path::Path;
 use std::sync::Arc;
@@ -32,6 +32,7 @@ use quickwit_common::io::IoControls;
 use quickwit_common::runtimes::RuntimeType;
 use quickwit_common::temp_dir::TempDirectory;
 use quickwit_directories::UnionDirectory;
+use quickwit_doc_mapper::tag_pruning::field_tag;
 use quickwit_doc_mapper::DocMapper;
 use quickwit_metastore::SplitMetadata;
 use quickwit_proto::indexing::IndexingPipelineId;
@@ -44,12 +45,17 @@ use quickwit_query::get_quickwit_fastfield_normalizer_manager;
 use quickwit_query::query_ast::QueryAst;
 use tantivy::directory::{Advice, DirectoryClone, MmapDirectory, RamDirectory};
 use tantivy::tokenizer::TokenizerManager;
-use tantivy::{DateTime, Directory, Index, IndexMeta, IndexWriter, SegmentId, SegmentReader};
+use tantivy::{
+    DateTime, Directory, Index, IndexMeta, IndexReader, IndexWriter, ReloadPolicy, SegmentId,
+    SegmentReader,
+};
 use tokio::runtime::Handle;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 use tracing::{debug, info, instrument, warn};
 
 use crate::actors::Packager;
 use crate::controlled_directory::ControlledDirectory;
+use crate::doc_id_dedup::{find_superseded_docs, DocListQuery};
 use crate::merge_policy::MergeOperationType;
 use crate::models::{IndexedSplit, IndexedSplitBatch, MergeScratch, PublishLock, SplitAttrs};
 
@@ -60,6 +66,8 @@ pub struct MergeExecutor {
     doc_mapper: Arc<dyn DocMapper>,
     io_controls: IoControls,
     merge_packager_mailbox: Mailbox<Packager>,
+    /// Permits shared among executors to bound the number of operations running concurrently.
+    concurrency_permits: Option<Arc<Semaphore>>,
 }
 
 #[async_trait]
@@ -91,6 +99,15 @@ impl Handler<MergeScratch> for MergeExecutor {
         merge_scratch: MergeScratch,
         ctx: &ActorContext<Self>,
     ) -> Result<(), ActorExitStatus> {
+        let _concurrency_permit = if let Some(concurrency_permits) = &self.concurrency_permits {
+            let concurrency_permit: OwnedSemaphorePermit = ctx
+                .protect_future(concurrency_permits.clone().acquire_owned())
+                .await
+                .expect("The semaphore should never be closed.");
+            Some(concurrency_permit)
+        } else {
+            None
+        };
         let start = Instant::now();
         let merge_op = merge_scratch.merge_operation;
         let indexed_split_opt: Option<IndexedSplit> = match merge_op.operation_type {
@@ -151,6 +168,12 @@ impl Handler<MergeScratch> for MergeExecutor {
 fn combine_index_meta(mut index_metas: Vec<IndexMeta>) -> anyhow::Result<IndexMeta> {
     let mut union_index_meta = index_metas.pop().with_context(|| "only one IndexMeta")?;
     for index_meta in index_metas {
+        // Merging segments sorted differently would produce a segment that is not sorted, so the
+        // merged split is only sorted if all the splits are sorted the same way.
+        if index_meta.index_settings.sort_by_field != union_index_meta.index_settings.sort_by_field
+        {
+            union_index_meta.index_settings.sort_by_field = None;
+        }
         union_index_meta.segments.extend(index_meta.segments);
     }
     Ok(union_index_meta)
@@ -173,6 +196,24 @@ fn open_split_directories(
     Ok((union_index_meta, directories))
 }
 
+/// Maps the segments of the splits to merge to the creation timestamp of their split.
+fn segment_create_timestamps(
+    splits: &[SplitMetadata],
+    tantivy_dirs: &[Box<dyn Directory>],
+    tokenizer_manager: &TokenizerManager,
+) -> anyhow::Result<HashMap<SegmentId, i64>> {
+    let mut segment_create_timestamps = HashMap::new();
+
+    for (split, tantivy_dir) in splits.iter().zip(tantivy_dirs) {
+        let index_meta = open_index(tantivy_dir.clone(), tokenizer_manager)?.load_metas()?;
+
+        for segment_meta in index_meta.segments {
+            segment_create_timestamps.insert(segment_meta.id(), split.create_timestamp);
+        }
+    }
+    Ok(segment_create_timestamps)
+}
+
 /// Creates a directory with a single `meta.json` file describe in `index_meta`
 fn create_shadowing_meta_json_directory(index_meta: IndexMeta) -> anyhow::Result<RamDirectory> {
     let union_index_meta_json = serde_json::to_string_pretty(&index_meta)?;
@@ -249,6 +290,23 @@ pub fn merge_split_attrs(
         .map(|split| split.delete_opstamp)
         .min()
         .unwrap_or(0);
+    // The merge planner only merges splits sharing the same doc mapping version.
+    let doc_mapping_version = splits
+        .first()
+        .map(|split| split.doc_mapping_version)
+        .unwrap_or(0);
+    // The merged split is written with the index settings of the last split, see
+    // `combine_index_meta`.
+    let docstore_codec = splits.last().and_then(|split| split.docstore_codec);
+    // The merged split is only sorted if all the splits are sorted the same way, see
+    // `combine_index_meta`.
+    let sort_by = splits
+        .iter()
+        .map(|split| split.sort_by)
+        .all_equal_value()
+        .ok()
+        .flatten();
+    let split_tags = merge_split_tags(splits);
     SplitAttrs {
         split_id: merge_split_id,
         partition_id,
@@ -259,9 +317,37 @@ pub fn merge_split_attrs(
         uncompressed_docs_size_in_bytes,
         delete_opstamp,
         num_merge_ops: max_merge_ops(splits) + 1,
+        doc_mapping_version,
+        docstore_codec,
+        sort_by,
+        split_tags,
     }
 }
 
+/// Collects the tags of the splits so that the custom split tags, which cannot be recomputed
+/// from the merged index, are carried over. A key is only kept if all the splits are tagged
+/// with it: a split missing the special tag `{key}!` may contain documents with any value.
+/// The packager discards the keys matching a tag field.
+fn merge_split_tags(splits: &[SplitMetadata]) -> BTreeMap<String, BTreeSet<String>> {
+    let mut split_tags: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+
+    for split in splits {
+        for tag in &split.tags {
+            if let Some((key, value)) = tag.split_once(':') {
+                split_tags
+                    .entry(key.to_string())
+                    .or_default()
+                    .insert(value.to_string());
+            }
+        }
+    }
+    split_tags.retain(|key, _| {
+        let key_tag = field_tag(key);
+        splits.iter().all(|split| split.tags.contains(&key_tag))
+    });
+    split_tags
+}
+
 fn max_merge_ops(splits: &[SplitMetadata]) -> usize {
     splits
         .iter()
@@ -284,9 +370,17 @@ impl MergeExecutor {
             doc_mapper,
             io_controls,
             merge_packager_mailbox,
+            concurrency_permits: None,
         }
     }
 
+    /// Bounds the number of operations executed concurrently by the executors sharing the
+    /// `concurrency_permits` semaphore.
+    pub fn set_concurrency_permits(mut self, concurrency_permits: Arc<Semaphore>) -> Self {
+        self.concurrency_permits = Some(concurrency_permits);
+        self
+    }
+
     async fn process_merge(
         &mut self,
         merge_split_id: String,
@@ -299,6 +393,17 @@ impl MergeExecutor {
             &tantivy_dirs,
             self.doc_mapper.tokenizer_manager().tantivy_manager(),
         )?;
+        // Superseded versions of the documents are only dropped if the index has a doc ID field.
+        let segment_create_timestamps_opt = if self.doc_mapper.doc_id_field_name().is_some() {
+            Some(segment_create_timestamps(
+                &splits,
+                &tantivy_dirs,
+                self.doc_mapper.tokenizer_manager().tantivy_manager(),
+            )?)
+        } else {
+            None
+        };
+        let dedup_by_doc_id = segment_create_timestamps_opt.is_some();
         // TODO it would be nice if tantivy could let us run the merge in the current thread.
         fail_point!("before-merge-split");
         let controlled_directory = self
@@ -307,6 +412,7 @@ impl MergeExecutor {
                 split_directories,
                 Vec::new(),
                 None,
+                segment_create_timestamps_opt,
                 merge_scratch_directory.path(),
                 ctx,
             )
@@ -321,7 +427,24 @@ impl MergeExecutor {
         )?;
         ctx.record_progress();
 
-        let split_attrs = merge_split_attrs(merge_split_id, &self.pipeline_id, &splits);
+        let mut split_attrs = merge_split_attrs(merge_split_id, &self.pipeline_id, &splits);
+        if dedup_by_doc_id {
+            let num_docs: u64 = merged_index
+                .searchable_segment_metas()?
+                .iter()
+                .map(|segment_meta| segment_meta.num_docs() as u64)
+                .sum();
+            if num_docs < split_attrs.num_docs {
+                info!(
+                    num_superseded_docs = split_attrs.num_docs - num_docs,
+                    "dropped-superseded-docs"
+                );
+                split_attrs.uncompressed_docs_size_in_bytes =
+                    (num_docs as f64 * split_attrs.uncompressed_docs_size_in_bytes as f64
+                        / split_attrs.num_docs as f64) as u64;
+                split_attrs.num_docs = num_docs;
+            }
+        }
         Ok(IndexedSplit {
             split_attrs,
             index: merged_index,
@@ -373,6 +496,7 @@ impl MergeExecutor {
                 split_directories,
                 delete_tasks,
                 Some(self.doc_mapper.clone()),
+                None,
                 merge_scratch_directory.path(),
                 ctx,
             )
@@ -429,6 +553,7 @@ impl MergeExecutor {
             None
         };
 
+        let split_tags = merge_split_tags(std::slice::from_ref(&split));
         let index_pipeline_id = IndexingPipelineId {
             index_uid: split.index_uid,
             node_id: split.node_id.clone(),
@@ -446,6 +571,10 @@ impl MergeExecutor {
                 uncompressed_docs_size_in_bytes,
                 delete_opstamp: last_delete_opstamp,
                 num_merge_ops: split.num_merge_ops,
+                doc_mapping_version: split.doc_mapping_version,
+                docstore_codec: split.docstore_codec,
+                sort_by: split.sort_by,
+                split_tags,
             },
             index: merged_index,
             split_scratch_directory: merge_scratch_directory,
@@ -454,12 +583,17 @@ impl MergeExecutor {
         Ok(Some(indexed_split))
     }
 
+    /// Merges the split directories after applying the delete tasks and, if
+    /// `segment_create_timestamps_opt` is set, dropping the superseded versions of the documents
+    /// sharing a doc ID.
+    #[allow(clippy::too_many_arguments)]
     async fn merge_split_directories(
         &self,
         union_index_meta: IndexMeta,
         split_directories: Vec<Box<dyn Directory>>,
         delete_tasks: Vec<DeleteTask>,
         doc_mapper_opt: Option<Arc<dyn DocMapper>>,
+        segment_create_timestamps_opt: Option<HashMap<SegmentId, i64>>,
         output_path: &Path,
         ctx: &ActorContext<MergeExecutor>,
     ) -> anyhow::Result<ControlledDirectory> {
@@ -516,6 +650,29 @@ impl MergeExecutor {
             debug!("commit-delete-operations");
             index_writer.commit()?;
         }
+        let mut has_superseded_docs = false;
+
+        if let (Some(doc_id_field_name), Some(segment_create_timestamps)) = (
+            self.doc_mapper.doc_id_field_name(),
+            segment_create_timestamps_opt,
+        ) {
+            let index_reader: IndexReader = union_index
+                .reader_builder()
+                .reload_policy(ReloadPolicy::Manual)
+                .try_into()?;
+            let superseded_docs = find_superseded_docs(
+                &index_reader.searcher(),
+                doc_id_field_name,
+                self.doc_mapper.timestamp_field_name(),
+                &segment_create_timestamps,
+            )?;
+            if !superseded_docs.is_empty() {
+                debug!("commit-superseded-docs-deletes");
+                index_writer.delete_query(Box::new(DocListQuery::new(superseded_docs)))?;
+                index_writer.commit()?;
+                has_superseded_docs = true;
+            }
+        }
 
         let segment_ids: Vec<SegmentId> = union_index
             .searchable_segment_metas()?
@@ -524,7 +681,7 @@ impl MergeExecutor {
             .collect();
 
         // A merge is useless if there is no delete and only one segment.
-        if num_delete_tasks == 0 && segment_ids.len() <= 1 {
+        if num_delete_tasks == 0 && !has_superseded_docs && segment_ids.len() <= 1 {
             return Ok(output_directory);
         }
 
@@ -559,6 +716,7 @@ fn open_index<T: Into<Box<dyn Directory>>>(
 mod tests {
     use quickwit_actors::Universe;
     use quickwit_common::split_file;
+    use quickwit_config::IndexSortBy;
     use quickwit_metastore::{
         ListSplitsRequestExt, MetastoreServiceStreamSplitsExt, SplitMetadata, StageSplitsRequestExt,
     };
@@ -566,7 +724,7 @@ mod tests {
         DeleteQuery, ListSplitsRequest, PublishSplitsRequest, StageSplitsRequest,
     };
     use serde_json::Value as JsonValue;
-    use tantivy::{Document, Inventory, ReloadPolicy, TantivyDocument};
+    use tantivy::{Document, Inventory, TantivyDocument};
 
     use super::*;
     use crate::merge_policy::MergeOperation;
@@ -664,6 +822,234 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_merge_executor_mixed_sorted_and_unsorted_splits() -> anyhow::Result<()> {
+        let doc_mapping_yaml = r#"
+            field_mappings:
+              - name: body
+                type: text
+              - name: ts
+                type: datetime
+                input_formats:
+                - unix_timestamp
+                fast: true
+            timestamp_field: ts
+        "#;
+        let sorted_test_sandbox = TestSandbox::create(
+            "test-index-sorted",
+            doc_mapping_yaml,
+            "sort_by: timestamp desc",
+            &["body"],
+        )
+        .await?;
+        let unsorted_test_sandbox =
+            TestSandbox::create("test-index-unsorted", doc_mapping_yaml, "", &["body"]).await?;
+        let pipeline_id = IndexingPipelineId {
+            index_uid: sorted_test_sandbox.index_uid(),
+            source_id: "test-source".to_string(),
+            node_id: "test-node".to_string(),
+            pipeline_uid: PipelineUid::from_u128(0u128),
+        };
+        let merge_scratch_directory = TempDirectory::for_test();
+        let downloaded_splits_directory =
+            merge_scratch_directory.named_temp_child("downloaded-splits-")?;
+        let mut split_metas: Vec<SplitMetadata> = Vec::new();
+        let mut tantivy_dirs: Vec<Box<dyn Directory>> = Vec::new();
+
+        for test_sandbox in [&sorted_test_sandbox, &unsorted_test_sandbox] {
+            let docs = (0..2u64).map(|doc_id| {
+                serde_json::json!({"body": format!("doc{doc_id}"), "ts": 1631072713u64 + doc_id})
+            });
+            test_sandbox.add_documents(docs).await?;
+
+            let list_splits_request =
+                ListSplitsRequest::try_from_index_uid(test_sandbox.index_uid()).unwrap();
+            let split_meta = test_sandbox
+                .metastore()
+                .list_splits(list_splits_request)
+                .await
+                .unwrap()
+                .collect_splits_metadata()
+                .await
+                .unwrap()
+                .pop()
+                .unwrap();
+            let split_filename = split_file(split_meta.split_id());
+            let dest_filepath = downloaded_splits_directory.path().join(&split_filename);
+            test_sandbox
+                .storage()
+                .copy_to_file(Path::new(&split_filename), &dest_filepath)
+                .await?;
+            tantivy_dirs.push(get_tantivy_directory_from_split_bundle(&dest_filepath).unwrap());
+            split_metas.push(split_meta);
+        }
+        assert_eq!(split_metas[0].sort_by, Some(IndexSortBy::TimestampDesc));
+        assert_eq!(split_metas[1].sort_by, None);
+
+        let merge_ops_inventory = Inventory::new();
+        let merge_operation =
+            merge_ops_inventory.track(MergeOperation::new_merge_operation(split_metas));
+        let merge_scratch = MergeScratch {
+            merge_operation,
+            tantivy_dirs,
+            merge_scratch_directory,
+            downloaded_splits_directory,
+        };
+        let (merge_packager_mailbox, merge_packager_inbox) =
+            sorted_test_sandbox.universe().create_test_mailbox();
+        let merge_executor = MergeExecutor::new(
+            pipeline_id,
+            sorted_test_sandbox.metastore(),
+            sorted_test_sandbox.doc_mapper(),
+            IoControls::default(),
+            merge_packager_mailbox,
+        );
+        let (merge_executor_mailbox, merge_executor_handle) = sorted_test_sandbox
+            .universe()
+            .spawn_builder()
+            .spawn(merge_executor);
+        merge_executor_mailbox.send_message(merge_scratch).await?;
+        merge_executor_handle.process_pending_and_observe().await;
+
+        // The merged split is not sorted since one of the splits is not.
+        let packager_msgs: Vec<IndexedSplitBatch> = merge_packager_inbox.drain_for_test_typed();
+        assert_eq!(packager_msgs.len(), 1);
+        let merged_split = &packager_msgs[0].splits[0];
+        assert_eq!(merged_split.split_attrs.num_docs, 4);
+        assert_eq!(merged_split.split_attrs.sort_by, None);
+        assert!(merged_split.index.settings().sort_by_field.is_none());
+
+        sorted_test_sandbox.assert_quit().await;
+        unsorted_test_sandbox.assert_quit().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_merge_executor_drops_superseded_docs() -> anyhow::Result<()> {
+        let doc_mapping_yaml = r#"
+            field_mappings:
+              - name: id
+                type: text
+                tokenizer: raw
+                fast: true
+              - name: body
+                type: text
+              - name: ts
+                type: datetime
+                input_formats:
+                - unix_timestamp
+                fast: true
+            timestamp_field: ts
+            doc_id_field: id
+        "#;
+        let test_sandbox =
+            TestSandbox::create("test-index", doc_mapping_yaml, "", &["body"]).await?;
+        let index_uid = test_sandbox.index_uid();
+        let pipeline_id = IndexingPipelineId {
+            index_uid: index_uid.clone(),
+            source_id: "test-source".to_string(),
+            node_id: "test-node".to_string(),
+            pipeline_uid: PipelineUid::from_u128(0u128),
+        };
+        let docs_per_split = [
+            vec![
+                serde_json::json!({"id": "doc-1", "body": "v1", "ts": 1631072713u64}),
+                serde_json::json!({"id": "doc-2", "body": "v1", "ts": 1631072713u64}),
+            ],
+            vec![serde_json::json!({"id": "doc-1", "body": "v3", "ts": 1631072715u64})],
+            vec![serde_json::json!({"id": "doc-1", "body": "v2", "ts": 1631072714u64})],
+        ];
+        for docs in docs_per_split {
+            test_sandbox.add_documents(docs).await?;
+        }
+        let mut metastore = test_sandbox.metastore();
+        let list_splits_request = ListSplitsRequest::try_from_index_uid(index_uid).unwrap();
+        let split_metas: Vec<SplitMetadata> = metastore
+            .list_splits(list_splits_request)
+            .await
+            .unwrap()
+            .collect_splits_metadata()
+            .await
+            .unwrap();
+        assert_eq!(split_metas.len(), 3);
+        let merge_scratch_directory = TempDirectory::for_test();
+        let downloaded_splits_directory =
+            merge_scratch_directory.named_temp_child("downloaded-splits-")?;
+        let mut tantivy_dirs: Vec<Box<dyn Directory>> = Vec::new();
+        for split_meta in &split_metas {
+            let split_filename = split_file(split_meta.split_id());
+            let dest_filepath = downloaded_splits_directory.path().join(&split_filename);
+            test_sandbox
+                .storage()
+                .copy_to_file(Path::new(&split_filename), &dest_filepath)
+                .await?;
+            tantivy_dirs.push(get_tantivy_directory_from_split_bundle(&dest_filepath).unwrap())
+        }
+        let merge_ops_inventory = Inventory::new();
+        let merge_operation =
+            merge_ops_inventory.track(MergeOperation::new_merge_operation(split_metas));
+        let merge_scratch = MergeScratch {
+            merge_operation,
+            tantivy_dirs,
+            merge_scratch_directory,
+            downloaded_splits_directory,
+        };
+        let (merge_packager_mailbox, merge_packager_inbox) =
+            test_sandbox.universe().create_test_mailbox();
+        let merge_executor = MergeExecutor::new(
+            pipeline_id,
+            metastore,
+            test_sandbox.doc_mapper(),
+            IoControls::default(),
+            merge_packager_mailbox,
+        );
+        let (merge_executor_mailbox, merge_executor_handle) = test_sandbox
+            .universe()
+            .spawn_builder()
+            .spawn(merge_executor);
+        merge_executor_mailbox.send_message(merge_scratch).await?;
+        merge_executor_handle.process_pending_and_observe().await;
+        let packager_msgs: Vec<IndexedSplitBatch> = merge_packager_inbox.drain_for_test_typed();
+        assert_eq!(packager_msgs.len(), 1);
+        let split = &packager_msgs[0].splits[0];
+        assert_eq!(split.split_attrs.num_docs, 2);
+
+        let reader = split
+            .index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        let searcher = reader.searcher();
+        assert_eq!(searcher.num_docs(), 2);
+
+        let mut bodies_per_doc_id = searcher
+            .search(
+                &tantivy::query::AllQuery,
+                &tantivy::collector::TopDocs::with_limit(3),
+            )?
+            .into_iter()
+            .map(|(_, doc_address)| {
+                let doc: TantivyDocument = searcher.doc(doc_address).unwrap();
+                let doc_json: JsonValue =
+                    serde_json::from_str(&doc.to_json(searcher.schema())).unwrap();
+                (
+                    doc_json["id"][0].as_str().unwrap().to_string(),
+                    doc_json["body"][0].as_str().unwrap().to_string(),
+                )
+            })
+            .collect::<Vec<_>>();
+        bodies_per_doc_id.sort();
+        assert_eq!(
+            bodies_per_doc_id,
+            [
+                ("doc-1".to_string(), "v3".to_string()),
+                ("doc-2".to_string(), "v1".to_string())
+            ]
+        );
+        test_sandbox.assert_quit().await;
+        Ok(())
+    }
+
     #[test]
     fn test_combine_partition_ids_singleton_unchanged() {
         assert_eq!(combine_partition_ids_aux([17]), 17);
@@ -693,6 +1079,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_merge_split_tags() {
+        let split_with_tags = |tags: &[&str]| SplitMetadata {
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            ..Default::default()
+        };
+        let splits = [
+            split_with_tags(&["env!", "env:prod", "dc!", "dc:us-east"]),
+            split_with_tags(&["env!", "env:dev", "dc!", "dc:us-west"]),
+            split_with_tags(&["env!", "env:prod"]),
+        ];
+        let split_tags = merge_split_tags(&splits);
+        assert_eq!(split_tags.len(), 1);
+        assert_eq!(
+            split_tags["env"].iter().collect::<Vec<_>>(),
+            ["dev", "prod"]
+        );
+        assert!(merge_split_tags(&[]).is_empty());
+    }
+
     async fn aux_test_delete_and_merge_executor(
         index_id: &str,
         docs: Vec<JsonValue>,
@@ -39,6 +39,7 @@ use quickwit_proto::metastore::{
     ListSplitsRequest, MetastoreError, MetastoreService, MetastoreServiceClient,
 };
 use time::OffsetDateTime;
+use tokio::sync::Semaphore;
 use tracing::{debug, error, info, instrument};
 
 use crate::actors::indexing_pipeline::wait_duration_before_retry;
@@ -46,7 +47,7 @@ use crate::actors::merge_split_downloader::MergeSplitDownloader;
 use crate::actors::publisher::PublisherType;
 use crate::actors::{MergeExecutor, MergePlanner, Packager, Publisher, Uploader, UploaderType};
 use crate::merge_policy::MergePolicy;
-use crate::models::MergeStatistics;
+use crate::models::{MergeStatistics, SetMergesPaused};
 use crate::split_store::IndexingSplitStore;
 
 #[derive(Debug)]
@@ -90,6 +91,8 @@ pub struct MergePipeline {
     statistics: MergeStatistics,
     handles_opt: Option<MergePipelineHandles>,
     kill_switch: KillSwitch,
+    // Kept here so that the merge planner respawned after a failure inherits it.
+    merges_paused: bool,
 }
 
 #[async_trait]
@@ -125,9 +128,15 @@ impl MergePipeline {
             statistics: MergeStatistics::default(),
             merge_planner_inbox,
             merge_planner_mailbox,
+            merges_paused: false,
         }
     }
 
+    pub fn with_merges_paused(mut self, merges_paused: bool) -> Self {
+        self.merges_paused = merges_paused;
+        self
+    }
+
     pub fn merge_planner_mailbox(&self) -> &Mailbox<MergePlanner> {
         &self.merge_planner_mailbox
     }
@@ -264,7 +273,15 @@ impl MergePipeline {
 
         // Merge Packager
         let tag_fields = self.params.doc_mapper.tag_named_fields()?;
-        let merge_packager = Packager::new("MergePackager", tag_fields, merge_uploader_mailbox);
+        let stats_fields = self.params.doc_mapper.stats_named_fields()?;
+        let doc_id_field_opt = self.params.doc_mapper.doc_id_named_field()?;
+        let merge_packager = Packager::new(
+            "MergePackager",
+            tag_fields,
+            stats_fields,
+            doc_id_field_opt,
+            merge_uploader_mailbox,
+        );
         let (merge_packager_mailbox, merge_packager_handler) = ctx
             .spawn_actor()
             .set_kill_switch(self.kill_switch.clone())
@@ -290,13 +307,17 @@ impl MergePipeline {
             .clone()
             .set_index_and_component(self.params.pipeline_id.index_uid.index_id(), "merger");
 
-        let merge_executor = MergeExecutor::new(
+        let mut merge_executor = MergeExecutor::new(
             self.params.pipeline_id.clone(),
             self.params.metastore.clone(),
             self.params.doc_mapper.clone(),
             merge_executor_io_controls,
             merge_packager_mailbox,
         );
+        if let Some(merge_concurrency_permits) = &self.params.merge_concurrency_permits {
+            merge_executor =
+                merge_executor.set_concurrency_permits(merge_concurrency_permits.clone());
+        }
         let (merge_executor_mailbox, merge_executor_handler) = ctx
             .spawn_actor()
             .set_kill_switch(self.kill_switch.clone())
@@ -327,7 +348,8 @@ impl MergePipeline {
             published_splits_metadata,
             self.params.merge_policy.clone(),
             merge_split_downloader_mailbox,
-        );
+        )
+        .with_merges_paused(self.merges_paused);
         let (_, merge_planner_handler) = ctx
             .spawn_actor()
             .set_kill_switch(self.kill_switch.clone())
@@ -467,6 +489,27 @@ impl Handler<Spawn> for MergePipeline {
     }
 }
 
+#[async_trait]
+impl Handler<SetMergesPaused> for MergePipeline {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        set_merges_paused: SetMergesPaused,
+        _ctx: &ActorContext<Self>,
+    ) -> Result<(), ActorExitStatus> {
+        self.merges_paused = set_merges_paused.merges_paused;
+
+        // The merge planner mailbox is recycled across respawns, so the message is processed
+        // by the current merge planner or the next one. The high priority queue is unbounded,
+        // which prevents this actor from blocking on a busy merge planner.
+        let _ = self
+            .merge_planner_mailbox
+            .send_message_with_high_priority(set_merges_paused);
+        Ok(())
+    }
+}
+
 #[derive(Clone)]
 pub struct MergePipelineParams {
     pub pipeline_id: IndexingPipelineId,
@@ -477,6 +520,9 @@ pub struct MergePipelineParams {
     pub merge_policy: Arc<dyn MergePolicy>,
     pub max_concurrent_split_uploads: usize, //< TODO share with the indexing pipeline.
     pub merge_max_io_num_bytes_per_sec: Option<ByteSize>,
+    /// Limits the number of merges executing concurrently across the merge pipelines of the
+    /// node, if any.
+    pub merge_concurrency_permits: Option<Arc<Semaphore>>,
     pub event_broker: EventBroker,
 }
 
@@ -537,6 +583,7 @@ mod tests {
             merge_policy: default_merge_policy(),
             max_concurrent_split_uploads: 2,
             merge_max_io_num_bytes_per_sec: None,
+            merge_concurrency_permits: None,
             event_broker: Default::default(),
         };
         let pipeline = MergePipeline::new(pipeline_params, universe.spawn_ctx());
@@ -26,6 +26,7 @@ use async_trait::async_trait;
 use itertools::Itertools;
 use quickwit_actors::channel_with_priority::TrySendError;
 use quickwit_actors::{Actor, ActorContext, ActorExitStatus, Handler, Mailbox, QueueCapacity};
+use quickwit_config::IndexSortBy;
 use quickwit_metastore::SplitMetadata;
 use quickwit_proto::indexing::IndexingPipelineId;
 use serde::Serialize;
@@ -36,7 +37,7 @@ use tracing::{info, warn};
 use crate::actors::MergeSplitDownloader;
 use crate::merge_policy::MergeOperation;
 use crate::metrics::INDEXER_METRICS;
-use crate::models::NewSplits;
+use crate::models::{NewSplits, SetMergesPaused};
 use crate::MergePolicy;
 
 /// The merge planner decides when to start a merge task.
@@ -45,7 +46,11 @@ pub struct MergePlanner {
 
     /// A young split is a split that has not reached maturity
     /// yet and can be candidate to merge operations.
-    partitioned_young_splits: HashMap<u64, Vec<SplitMetadata>>,
+    ///
+    /// Young splits are grouped by partition ID, doc mapping version, and sort order: splits built
+    /// with different doc mappings have different schemas and cannot be merged together, and
+    /// merging splits sorted differently would produce a split that is not sorted.
+    partitioned_young_splits: HashMap<(u64, u64, Option<IndexSortBy>), Vec<SplitMetadata>>,
 
     /// This set contains all of the split ids that we "acknowledged".
     /// The point of this set is to rapidly dismiss redundant `NewSplit` message.
@@ -81,6 +86,10 @@ pub struct MergePlanner {
     /// In particular, it is necessary to avoid ever increasing the number
     /// `RefreshMetrics` loop, every time the `MergePlanner` is respawned.
     incarnation_started_at: Instant,
+
+    /// When merges are paused, new splits are still recorded but no merge operation is planned
+    /// until merges are resumed.
+    merges_paused: bool,
 }
 
 #[async_trait]
@@ -96,6 +105,7 @@ impl Actor for MergePlanner {
             .collect_vec();
         MergePlannerState {
             ongoing_merge_operations,
+            merges_paused: self.merges_paused,
         }
     }
 
@@ -203,11 +213,17 @@ impl MergePlanner {
             merge_split_downloader_mailbox,
             ongoing_merge_operations_inventory: Inventory::default(),
             incarnation_started_at: Instant::now(),
+            merges_paused: false,
         };
         merge_planner.record_splits_if_necessary(published_splits);
         merge_planner
     }
 
+    pub fn with_merges_paused(mut self, merges_paused: bool) -> Self {
+        self.merges_paused = merges_paused;
+        self
+    }
+
     fn rebuild_known_split_ids(&self) -> HashSet<String> {
         let mut known_split_ids: HashSet<String> =
             HashSet::with_capacity(self.num_known_splits_rebuild_threshold());
@@ -252,9 +268,13 @@ impl MergePlanner {
         if cfg!(test) {
             let merge_operation = self.ongoing_merge_operations_inventory.list();
             let mut young_splits = HashSet::new();
-            for (&partition_id, young_splits_in_partition) in &self.partitioned_young_splits {
+            for (&(partition_id, doc_mapping_version, sort_by), young_splits_in_partition) in
+                &self.partitioned_young_splits
+            {
                 for split_metadata in young_splits_in_partition {
                     assert_eq!(split_metadata.partition_id, partition_id);
+                    assert_eq!(split_metadata.doc_mapping_version, doc_mapping_version);
+                    assert_eq!(split_metadata.sort_by, sort_by);
                     young_splits.insert(split_metadata.split_id());
                 }
             }
@@ -289,7 +309,11 @@ impl MergePlanner {
     fn record_split(&mut self, new_split: SplitMetadata) {
         let splits_for_partition: &mut Vec<SplitMetadata> = self
             .partitioned_young_splits
-            .entry(new_split.partition_id)
+            .entry((
+                new_split.partition_id,
+                new_split.doc_mapping_version,
+                new_split.sort_by,
+            ))
             .or_default();
         splits_for_partition.push(new_split);
     }
@@ -342,6 +366,9 @@ impl MergePlanner {
     }
 
     async fn send_merge_ops(&mut self, ctx: &ActorContext<Self>) -> Result<(), ActorExitStatus> {
+        if self.merges_paused {
+            return Ok(());
+        }
         // We do not want to simply schedule all available merge operations here.
         //
         // The reason is that in presence of partitioning, it is very possible
@@ -420,6 +447,40 @@ struct PlanMerge {
     incarnation_started_at: Instant,
 }
 
+#[async_trait]
+impl Handler<SetMergesPaused> for MergePlanner {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        set_merges_paused: SetMergesPaused,
+        ctx: &ActorContext<Self>,
+    ) -> Result<(), ActorExitStatus> {
+        if self.merges_paused == set_merges_paused.merges_paused {
+            return Ok(());
+        }
+        self.merges_paused = set_merges_paused.merges_paused;
+
+        if self.merges_paused {
+            info!(
+                index_id=%self.pipeline_id.index_uid.index_id(),
+                source_id=%self.pipeline_id.source_id,
+                "paused merges"
+            );
+        } else {
+            info!(
+                index_id=%self.pipeline_id.index_uid.index_id(),
+                source_id=%self.pipeline_id.source_id,
+                num_young_splits=self.num_young_splits(),
+                "resumed merges"
+            );
+            // Plan the merge operations for the splits recorded while merges were paused.
+            self.send_merge_ops(ctx).await?;
+        }
+        Ok(())
+    }
+}
+
 #[async_trait]
 impl Handler<RefreshMetrics> for MergePlanner {
     type Reply = ();
@@ -455,6 +516,7 @@ impl Handler<RefreshMetrics> for MergePlanner {
 #[derive(Clone, Debug, Serialize)]
 pub struct MergePlannerState {
     pub(crate) ongoing_merge_operations: Vec<MergeOperation>,
+    pub(crate) merges_paused: bool,
 }
 
 #[cfg(test)]
@@ -467,7 +529,7 @@ mod tests {
     use quickwit_config::merge_policy_config::{
         ConstWriteAmplificationMergePolicyConfig, MergePolicyConfig, StableLogMergePolicyConfig,
     };
-    use quickwit_config::IndexingSettings;
+    use quickwit_config::{IndexSortBy, IndexingSettings};
     use quickwit_metastore::{SplitMaturity, SplitMetadata};
     use quickwit_proto::indexing::IndexingPipelineId;
     use quickwit_proto::types::{IndexUid, PipelineUid};
@@ -478,7 +540,7 @@ mod tests {
     use crate::merge_policy::{
         merge_policy_from_settings, MergeOperation, MergePolicy, StableLogMergePolicy,
     };
-    use crate::models::NewSplits;
+    use crate::models::{NewSplits, SetMergesPaused};
 
     fn split_metadata_for_test(
         index_uid: &IndexUid,
@@ -881,6 +943,181 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_merge_planner_does_not_merge_splits_with_different_doc_mapping_versions(
+    ) -> anyhow::Result<()> {
+        let universe = Universe::with_accelerated_time();
+        let (merge_split_downloader_mailbox, merge_split_downloader_inbox) = universe
+            .spawn_ctx()
+            .create_mailbox("MergeSplitDownloader", QueueCapacity::Bounded(2));
+        let index_uid = IndexUid::new_with_random_ulid("test-index");
+        let pipeline_id = IndexingPipelineId {
+            index_uid: index_uid.clone(),
+            source_id: "test-source".to_string(),
+            node_id: "test-node".to_string(),
+            pipeline_uid: PipelineUid::default(),
+        };
+        let merge_policy_config = ConstWriteAmplificationMergePolicyConfig {
+            merge_factor: 2,
+            max_merge_factor: 2,
+            max_merge_ops: 3,
+            ..Default::default()
+        };
+        let indexing_settings = IndexingSettings {
+            merge_policy: MergePolicyConfig::ConstWriteAmplification(merge_policy_config),
+            ..Default::default()
+        };
+        let split_a = split_metadata_for_test(
+            &index_uid, "a_small", 0, // partition_id
+            1_000_000, 2,
+        );
+        let mut split_b = split_metadata_for_test(
+            &index_uid, "b_small", 0, // partition_id
+            1_000_000, 2,
+        );
+        split_b.doc_mapping_version = 1;
+
+        let merge_policy: Arc<dyn MergePolicy> = merge_policy_from_settings(&indexing_settings);
+        let merge_planner = MergePlanner::new(
+            pipeline_id,
+            vec![split_a, split_b],
+            merge_policy,
+            merge_split_downloader_mailbox,
+        );
+        let (merge_planner_mailbox, merge_planner_handle) =
+            universe.spawn_builder().spawn(merge_planner);
+        universe.sleep(Duration::from_secs(10)).await;
+        merge_planner_mailbox.send_message(Command::Quit).await?;
+        let (exit_status, _last_state) = merge_planner_handle.join().await;
+        assert!(matches!(exit_status, ActorExitStatus::Quit));
+        let merge_ops =
+            merge_split_downloader_inbox.drain_for_test_typed::<TrackedObject<MergeOperation>>();
+
+        assert!(merge_ops.is_empty());
+        universe.assert_quit().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_merge_planner_does_not_merge_splits_with_different_sort_orders(
+    ) -> anyhow::Result<()> {
+        let universe = Universe::with_accelerated_time();
+        let (merge_split_downloader_mailbox, merge_split_downloader_inbox) = universe
+            .spawn_ctx()
+            .create_mailbox("MergeSplitDownloader", QueueCapacity::Bounded(2));
+        let index_uid = IndexUid::new_with_random_ulid("test-index");
+        let pipeline_id = IndexingPipelineId {
+            index_uid: index_uid.clone(),
+            source_id: "test-source".to_string(),
+            node_id: "test-node".to_string(),
+            pipeline_uid: PipelineUid::default(),
+        };
+        let merge_policy_config = ConstWriteAmplificationMergePolicyConfig {
+            merge_factor: 2,
+            max_merge_factor: 2,
+            max_merge_ops: 3,
+            ..Default::default()
+        };
+        let indexing_settings = IndexingSettings {
+            merge_policy: MergePolicyConfig::ConstWriteAmplification(merge_policy_config),
+            ..Default::default()
+        };
+        let mut split_a = split_metadata_for_test(
+            &index_uid, "a_small", 0, // partition_id
+            1_000_000, 2,
+        );
+        let mut split_b = split_metadata_for_test(
+            &index_uid, "b_small", 0, // partition_id
+            1_000_000, 2,
+        );
+        split_a.sort_by = Some(IndexSortBy::TimestampDesc);
+        split_b.sort_by = None;
+
+        let merge_policy: Arc<dyn MergePolicy> = merge_policy_from_settings(&indexing_settings);
+        let merge_planner = MergePlanner::new(
+            pipeline_id,
+            vec![split_a, split_b],
+            merge_policy,
+            merge_split_downloader_mailbox,
+        );
+        let (merge_planner_mailbox, merge_planner_handle) =
+            universe.spawn_builder().spawn(merge_planner);
+        universe.sleep(Duration::from_secs(10)).await;
+        merge_planner_mailbox.send_message(Command::Quit).await?;
+        let (exit_status, _last_state) = merge_planner_handle.join().await;
+        assert!(matches!(exit_status, ActorExitStatus::Quit));
+        let merge_ops =
+            merge_split_downloader_inbox.drain_for_test_typed::<TrackedObject<MergeOperation>>();
+
+        assert!(merge_ops.is_empty());
+        universe.assert_quit().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_merge_planner_pause_and_resume_merges() -> anyhow::Result<()> {
+        let universe = Universe::with_accelerated_time();
+        let (merge_split_downloader_mailbox, merge_split_downloader_inbox) = universe
+            .spawn_ctx()
+            .create_mailbox("MergeSplitDownloader", QueueCapacity::Bounded(2));
+        let index_uid = IndexUid::new_with_random_ulid("test-index");
+        let pipeline_id = IndexingPipelineId {
+            index_uid: index_uid.clone(),
+            source_id: "test-source".to_string(),
+            node_id: "test-node".to_string(),
+            pipeline_uid: PipelineUid::default(),
+        };
+        let merge_policy_config = ConstWriteAmplificationMergePolicyConfig {
+            merge_factor: 2,
+            max_merge_factor: 2,
+            max_merge_ops: 3,
+            ..Default::default()
+        };
+        let indexing_settings = IndexingSettings {
+            merge_policy: MergePolicyConfig::ConstWriteAmplification(merge_policy_config),
+            ..Default::default()
+        };
+        let merge_policy: Arc<dyn MergePolicy> = merge_policy_from_settings(&indexing_settings);
+        let merge_planner = MergePlanner::new(
+            pipeline_id,
+            Vec::new(),
+            merge_policy,
+            merge_split_downloader_mailbox,
+        )
+        .with_merges_paused(true);
+        let (merge_planner_mailbox, merge_planner_handle) =
+            universe.spawn_builder().spawn(merge_planner);
+
+        let new_splits = NewSplits {
+            new_splits: vec![
+                split_metadata_for_test(&index_uid, "a_small", 0, 1_000_000, 2),
+                split_metadata_for_test(&index_uid, "b_small", 0, 1_000_000, 2),
+            ],
+        };
+        merge_planner_mailbox.ask(new_splits).await?;
+        universe.sleep(Duration::from_secs(10)).await;
+
+        let merge_ops =
+            merge_split_downloader_inbox.drain_for_test_typed::<TrackedObject<MergeOperation>>();
+        assert!(merge_ops.is_empty());
+
+        merge_planner_mailbox
+            .ask(SetMergesPaused {
+                merges_paused: false,
+            })
+            .await?;
+        let merge_ops =
+            merge_split_downloader_inbox.drain_for_test_typed::<TrackedObject<MergeOperation>>();
+        assert_eq!(merge_ops.len(), 1);
+        assert_eq!(merge_ops[0].splits_as_slice().len(), 2);
+
+        merge_planner_mailbox.send_message(Command::Quit).await?;
+        let (exit_status, _last_state) = merge_planner_handle.join().await;
+        assert!(matches!(exit_status, ActorExitStatus::Quit));
+        universe.assert_quit().await;
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_merge_planner_inherit_mailbox_with_splits_bug_3847() -> anyhow::Result<()> {
         let universe = Universe::with_accelerated_time();
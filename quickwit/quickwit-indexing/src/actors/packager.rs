@@ -17,8 +17,9 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::io;
+use std::ops::RangeInclusive;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
@@ -27,13 +28,17 @@ use async_trait::async_trait;
 use fail::fail_point;
 use itertools::Itertools;
 use quickwit_actors::{Actor, ActorContext, ActorExitStatus, Handler, Mailbox, QueueCapacity};
+use quickwit_common::bloom_filter::BloomFilter;
 use quickwit_common::runtimes::RuntimeType;
 use quickwit_common::temp_dir::TempDirectory;
 use quickwit_directories::write_hotcache;
 use quickwit_doc_mapper::tag_pruning::append_to_tag_set;
 use quickwit_doc_mapper::NamedField;
+use quickwit_metastore::{FieldStats, FieldValueRange};
+use tantivy::columnar::{DynamicColumn, HasAssociatedColumnType};
+use tantivy::fastfield::Column;
 use tantivy::schema::FieldType;
-use tantivy::{InvertedIndexReader, ReloadPolicy, SegmentMeta};
+use tantivy::{DateTime, InvertedIndexReader, ReloadPolicy, SegmentMeta, SegmentReader};
 use tokio::runtime::Handle;
 use tracing::{debug, info, instrument, warn};
 
@@ -44,6 +49,9 @@ const MAX_VALUES_PER_TAG_FIELD: usize = if cfg!(any(test, feature = "testsuite")
     1000
 };
 
+/// False positive rate of the doc IDs Bloom filter stored next to each split.
+const DOC_IDS_FALSE_POSITIVE_RATE: f64 = 0.01;
+
 use crate::actors::Uploader;
 use crate::models::{
     serialize_split_fields, EmptySplit, IndexedSplit, IndexedSplitBatch, PackagedSplit,
@@ -67,18 +75,26 @@ pub struct Packager {
     uploader_mailbox: Mailbox<Uploader>,
     /// List of tag fields ([`Vec<NamedField>`]) defined in the index config.
     tag_fields: Vec<NamedField>,
+    /// List of stats fields ([`Vec<NamedField>`]) defined in the index config.
+    stats_fields: Vec<NamedField>,
+    /// Doc ID field ([`NamedField`]) defined in the index config, if any.
+    doc_id_field_opt: Option<NamedField>,
 }
 
 impl Packager {
     pub fn new(
         actor_name: &'static str,
         tag_fields: Vec<NamedField>,
+        stats_fields: Vec<NamedField>,
+        doc_id_field_opt: Option<NamedField>,
         uploader_mailbox: Mailbox<Uploader>,
     ) -> Packager {
         Packager {
             actor_name,
             uploader_mailbox,
             tag_fields,
+            stats_fields,
+            doc_id_field_opt,
         }
     }
 
@@ -89,8 +105,14 @@ impl Packager {
     ) -> anyhow::Result<PackagedSplit> {
         let segment_metas = split.index.searchable_segment_metas()?;
         assert_eq!(segment_metas.len(), 1);
-        let packaged_split =
-            create_packaged_split(&segment_metas[..], split, &self.tag_fields, ctx)?;
+        let packaged_split = create_packaged_split(
+            &segment_metas[..],
+            split,
+            &self.tag_fields,
+            &self.stats_fields,
+            self.doc_id_field_opt.as_ref(),
+            ctx,
+        )?;
         Ok(packaged_split)
     }
 }
@@ -241,39 +263,166 @@ fn try_extract_terms(
             max_terms
         );
     }
+    if let FieldType::Bytes(_) = named_field.field_type {
+        bail!("tags collection is not allowed on `bytes` fields")
+    }
     let mut terms = Vec::with_capacity(num_terms);
     for inv_index in inv_indexes {
         let mut terms_streamer = inv_index.terms().stream()?;
         while let Some((term_data, _)) = terms_streamer.next() {
-            let term = match named_field.field_type {
-                FieldType::U64(_) => u64_from_term_data(term_data)?.to_string(),
-                FieldType::I64(_) => {
-                    tantivy::u64_to_i64(u64_from_term_data(term_data)?).to_string()
-                }
-                FieldType::F64(_) => {
-                    tantivy::u64_to_f64(u64_from_term_data(term_data)?).to_string()
-                }
-                FieldType::Bool(_) => match u64_from_term_data(term_data)? {
-                    0 => false,
-                    1 => true,
-                    _ => bail!("invalid boolean value"),
-                }
-                .to_string(),
-                FieldType::Bytes(_) => {
-                    bail!("tags collection is not allowed on `bytes` fields")
-                }
-                _ => std::str::from_utf8(term_data)?.to_string(),
-            };
-            terms.push(term);
+            terms.push(term_to_string(&named_field.field_type, term_data)?);
         }
     }
     Ok(terms)
 }
 
+/// Converts the raw bytes of a term of the dictionary of a field into its string representation.
+fn term_to_string(field_type: &FieldType, term_data: &[u8]) -> anyhow::Result<String> {
+    let term = match field_type {
+        FieldType::U64(_) => u64_from_term_data(term_data)?.to_string(),
+        FieldType::I64(_) => tantivy::u64_to_i64(u64_from_term_data(term_data)?).to_string(),
+        FieldType::F64(_) => tantivy::u64_to_f64(u64_from_term_data(term_data)?).to_string(),
+        FieldType::Bool(_) => match u64_from_term_data(term_data)? {
+            0 => false,
+            1 => true,
+            _ => bail!("invalid boolean value"),
+        }
+        .to_string(),
+        FieldType::Bytes(_) => bail!("`bytes` terms have no string representation"),
+        _ => std::str::from_utf8(term_data)?.to_string(),
+    };
+    Ok(term)
+}
+
+/// Builds the Bloom filter of the values of the doc ID field of a split, keyed by their string
+/// representation.
+fn build_doc_ids_bloom_filter(
+    doc_id_field: &NamedField,
+    segment_readers: &[SegmentReader],
+) -> anyhow::Result<BloomFilter> {
+    let mut inverted_indexes = Vec::with_capacity(segment_readers.len());
+    for segment_reader in segment_readers {
+        inverted_indexes.push(segment_reader.inverted_index(doc_id_field.field)?);
+    }
+    let num_terms = inverted_indexes
+        .iter()
+        .map(|inverted_index| inverted_index.terms().num_terms())
+        .sum::<usize>();
+    let mut bloom_filter =
+        BloomFilter::with_false_positive_rate(num_terms, DOC_IDS_FALSE_POSITIVE_RATE);
+
+    for inverted_index in &inverted_indexes {
+        let mut terms_streamer = inverted_index.terms().stream()?;
+        while let Some((term_data, _)) = terms_streamer.next() {
+            let doc_id = term_to_string(&doc_id_field.field_type, term_data)?;
+            bloom_filter.insert(doc_id.as_bytes());
+        }
+    }
+    Ok(bloom_filter)
+}
+
+/// Computes the min and max values of a stats field over the segments of a split and, if the
+/// field is indexed, its number of distinct values.
+///
+/// Returns `None` if the field has no value in the split.
+fn compute_field_stats(
+    named_field: &NamedField,
+    segment_readers: &[SegmentReader],
+) -> anyhow::Result<Option<FieldStats>> {
+    let field_name = &named_field.name;
+    let range_opt = match named_field.field_type {
+        FieldType::I64(_) => {
+            column_range::<i64>(field_name, segment_readers)?.map(|range| FieldValueRange::I64 {
+                min: *range.start(),
+                max: *range.end(),
+            })
+        }
+        FieldType::U64(_) => {
+            column_range::<u64>(field_name, segment_readers)?.map(|range| FieldValueRange::U64 {
+                min: *range.start(),
+                max: *range.end(),
+            })
+        }
+        FieldType::F64(_) => {
+            column_range::<f64>(field_name, segment_readers)?.map(|range| FieldValueRange::F64 {
+                min: *range.start(),
+                max: *range.end(),
+            })
+        }
+        FieldType::Date(_) => column_range::<DateTime>(field_name, segment_readers)?.map(|range| {
+            FieldValueRange::Datetime {
+                min: range.start().into_timestamp_micros(),
+                max: range.end().into_timestamp_micros(),
+            }
+        }),
+        _ => bail!(
+            "stats collection is not allowed on `{}` fields",
+            named_field.field_type.value_type().name().to_lowercase()
+        ),
+    };
+    let Some(range) = range_opt else {
+        return Ok(None);
+    };
+    // Each distinct value of a numeric field is a term of its dictionary. Summing over the
+    // segments overestimates the cardinality when they share values.
+    let cardinality = if named_field.field_type.is_indexed() {
+        let mut num_terms = 0;
+        for segment_reader in segment_readers {
+            let inverted_index = segment_reader.inverted_index(named_field.field)?;
+            num_terms += inverted_index.terms().num_terms() as u64;
+        }
+        Some(num_terms)
+    } else {
+        None
+    };
+    Ok(Some(FieldStats { range, cardinality }))
+}
+
+fn column_range<T>(
+    field_name: &str,
+    segment_readers: &[SegmentReader],
+) -> anyhow::Result<Option<RangeInclusive<T>>>
+where
+    T: HasAssociatedColumnType,
+    DynamicColumn: Into<Option<Column<T>>>,
+{
+    let mut range_opt: Option<RangeInclusive<T>> = None;
+
+    for segment_reader in segment_readers {
+        let Some(column) = segment_reader.fast_fields().column_opt::<T>(field_name)? else {
+            continue;
+        };
+        if column.values.num_vals() == 0 {
+            continue;
+        }
+        let (column_min, column_max) = (column.min_value(), column.max_value());
+
+        range_opt = Some(match range_opt {
+            Some(range) => {
+                let min = if column_min < *range.start() {
+                    column_min
+                } else {
+                    *range.start()
+                };
+                let max = if column_max > *range.end() {
+                    column_max
+                } else {
+                    *range.end()
+                };
+                min..=max
+            }
+            None => column_min..=column_max,
+        });
+    }
+    Ok(range_opt)
+}
+
 fn create_packaged_split(
     segment_metas: &[SegmentMeta],
     split: IndexedSplit,
     tag_fields: &[NamedField],
+    stats_fields: &[NamedField],
+    doc_id_field_opt: Option<&NamedField>,
     ctx: &ActorContext<Packager>,
 ) -> anyhow::Result<PackagedSplit> {
     info!(split_id = split.split_id(), "create-packaged-split");
@@ -308,9 +457,54 @@ fn create_packaged_split(
             }
         }
     }
+    for (key, values) in &split.split_attrs.split_tags {
+        // Tag fields take precedence over the custom split tags sharing their name.
+        if tag_fields
+            .iter()
+            .any(|named_field| &named_field.name == key)
+        {
+            continue;
+        }
+        if values.len() > MAX_VALUES_PER_TAG_FIELD {
+            warn!(
+                key = %key,
+                num_values = values.len(),
+                "too many values for split tag, no value will be registered in the split metadata"
+            );
+            continue;
+        }
+        let values: Vec<String> = values.iter().cloned().collect();
+        append_to_tag_set(key, &values, &mut tags);
+    }
+
+    debug!(split_id = split.split_id(), stats_fields =? stats_fields, "compute-field-stats");
+    let searcher = index_reader.searcher();
+    let mut field_stats = BTreeMap::new();
 
+    for named_field in stats_fields {
+        match compute_field_stats(named_field, searcher.segment_readers()) {
+            Ok(Some(stats)) => {
+                field_stats.insert(named_field.name.clone(), stats);
+            }
+            Ok(None) => {}
+            Err(stats_error) => {
+                warn!(err=?stats_error, field=%named_field.name, "no field stats will be registered in the split metadata");
+            }
+        }
+    }
     ctx.record_progress();
 
+    // Unlike tags and stats, the doc IDs sidecar is required for correctness: deletes by ID skip
+    // the splits whose filter does not contain the ID, so any error fails the packaging.
+    let doc_ids_sidecar_opt = if let Some(doc_id_field) = doc_id_field_opt {
+        debug!(split_id = split.split_id(), "build-doc-ids-sidecar");
+        let bloom_filter = build_doc_ids_bloom_filter(doc_id_field, searcher.segment_readers())?;
+        ctx.record_progress();
+        Some(bloom_filter.to_bytes())
+    } else {
+        None
+    };
+
     debug!(split_id = split.split_id(), "build-hotcache");
     let mut hotcache_bytes = Vec::new();
     build_hotcache(split.split_scratch_directory.path(), &mut hotcache_bytes)?;
@@ -322,6 +516,8 @@ fn create_packaged_split(
         split_attrs: split.split_attrs,
         split_scratch_directory: split.split_scratch_directory,
         tags,
+        field_stats,
+        doc_ids_sidecar_opt,
         split_files,
         hotcache_bytes,
     };
@@ -369,6 +565,10 @@ mod tests {
             schema_builder.add_f64_field("tag_f64", NumericOptions::default().set_indexed());
         let tag_bool =
             schema_builder.add_bool_field("tag_bool", NumericOptions::default().set_indexed());
+        let stats_i64 = schema_builder.add_i64_field(
+            "stats_i64",
+            NumericOptions::default().set_indexed().set_fast(),
+        );
         let schema = schema_builder.build();
         let index_builder = IndexBuilder::new()
             .settings(IndexSettings::default())
@@ -399,6 +599,7 @@ mod tests {
                     tag_i64 => -42i64,
                     tag_f64 => -42.02f64,
                     tag_bool => true,
+                    stats_i64 => num as i64 * 10,
                 );
                 index_writer.add_document(doc)?;
                 num_docs += 1;
@@ -434,6 +635,10 @@ mod tests {
                 replaced_split_ids: Vec::new(),
                 delete_opstamp: 0,
                 num_merge_ops: 0,
+                doc_mapping_version: 0,
+                docstore_codec: None,
+                sort_by: None,
+                split_tags: Default::default(),
             },
             index,
             split_scratch_directory,
@@ -472,7 +677,7 @@ mod tests {
                 "tag_str", "tag_many", "tag_u64", "tag_i64", "tag_f64", "tag_bool",
             ],
         );
-        let packager = Packager::new("TestPackager", tag_fields, mailbox);
+        let packager = Packager::new("TestPackager", tag_fields, Vec::new(), None, mailbox);
         let (packager_mailbox, packager_handle) = universe.spawn_builder().spawn(packager);
         packager_mailbox
             .send_message(IndexedSplitBatch {
@@ -519,4 +724,134 @@ mod tests {
         universe.assert_quit().await;
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_packager_split_tags() -> anyhow::Result<()> {
+        let universe = Universe::with_accelerated_time();
+        let (mailbox, inbox) = universe.create_test_mailbox();
+        let mut indexed_split =
+            make_indexed_split_for_test(&[DateTime::from_timestamp_secs(1628203589)])?;
+        let split_tags = &mut indexed_split.split_attrs.split_tags;
+        split_tags.insert(
+            "env".to_string(),
+            ["prod".to_string()].into_iter().collect(),
+        );
+        split_tags.insert(
+            "tag_str".to_string(),
+            ["other".to_string()].into_iter().collect(),
+        );
+        split_tags.insert(
+            "dc".to_string(),
+            (0..=MAX_VALUES_PER_TAG_FIELD)
+                .map(|dc_id| format!("dc-{dc_id}"))
+                .collect(),
+        );
+        let tag_fields = get_tag_fields(indexed_split.index.schema(), &["tag_str"]);
+        let packager = Packager::new("TestPackager", tag_fields, Vec::new(), None, mailbox);
+        let (packager_mailbox, packager_handle) = universe.spawn_builder().spawn(packager);
+        packager_mailbox
+            .send_message(IndexedSplitBatch {
+                splits: vec![indexed_split],
+                checkpoint_delta_opt: IndexCheckpointDelta::for_test("source_id", 10..20).into(),
+                publish_lock: PublishLock::default(),
+                publish_token_opt: None,
+                merge_operation_opt: None,
+                batch_parent_span: Span::none(),
+            })
+            .await?;
+        packager_handle.process_pending_and_observe().await;
+        let packaged_splits = inbox.drain_for_test();
+        let packaged_split = packaged_splits[0]
+            .downcast_ref::<PackagedSplitBatch>()
+            .unwrap();
+        let split = &packaged_split.splits[0];
+        assert_eq!(
+            &split.tags.iter().map(|s| s.as_str()).collect::<Vec<&str>>(),
+            &["env!", "env:prod", "tag_str!", "tag_str:value"]
+        );
+        universe.assert_quit().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_packager_field_stats() -> anyhow::Result<()> {
+        let universe = Universe::with_accelerated_time();
+        let (mailbox, inbox) = universe.create_test_mailbox();
+        let indexed_split = make_indexed_split_for_test(&[
+            DateTime::from_timestamp_secs(1628203589),
+            DateTime::from_timestamp_secs(1628203640),
+        ])?;
+        // `tag_u64` is not a fast field, so no stats are recorded for it.
+        let stats_fields = get_tag_fields(indexed_split.index.schema(), &["stats_i64", "tag_u64"]);
+        let packager = Packager::new("TestPackager", Vec::new(), stats_fields, None, mailbox);
+        let (packager_mailbox, packager_handle) = universe.spawn_builder().spawn(packager);
+        packager_mailbox
+            .send_message(IndexedSplitBatch {
+                splits: vec![indexed_split],
+                checkpoint_delta_opt: IndexCheckpointDelta::for_test("source_id", 10..20).into(),
+                publish_lock: PublishLock::default(),
+                publish_token_opt: None,
+                merge_operation_opt: None,
+                batch_parent_span: Span::none(),
+            })
+            .await?;
+        packager_handle.process_pending_and_observe().await;
+        let packaged_splits = inbox.drain_for_test();
+        let packaged_split = packaged_splits[0]
+            .downcast_ref::<PackagedSplitBatch>()
+            .unwrap();
+        let split = &packaged_split.splits[0];
+        assert_eq!(split.field_stats.len(), 1);
+        assert_eq!(
+            split.field_stats["stats_i64"],
+            FieldStats {
+                range: FieldValueRange::I64 { min: 10, max: 90 },
+                cardinality: Some(9),
+            }
+        );
+        universe.assert_quit().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_packager_doc_ids_sidecar() -> anyhow::Result<()> {
+        let universe = Universe::with_accelerated_time();
+        let (mailbox, inbox) = universe.create_test_mailbox();
+        let indexed_split =
+            make_indexed_split_for_test(&[DateTime::from_timestamp_secs(1628203589)])?;
+        let doc_id_field = get_tag_fields(indexed_split.index.schema(), &["tag_many"]).pop();
+        let packager = Packager::new(
+            "TestPackager",
+            Vec::new(),
+            Vec::new(),
+            doc_id_field,
+            mailbox,
+        );
+        let (packager_mailbox, packager_handle) = universe.spawn_builder().spawn(packager);
+        packager_mailbox
+            .send_message(IndexedSplitBatch {
+                splits: vec![indexed_split],
+                checkpoint_delta_opt: IndexCheckpointDelta::for_test("source_id", 10..20).into(),
+                publish_lock: PublishLock::default(),
+                publish_token_opt: None,
+                merge_operation_opt: None,
+                batch_parent_span: Span::none(),
+            })
+            .await?;
+        packager_handle.process_pending_and_observe().await;
+        let packaged_splits = inbox.drain_for_test();
+        let packaged_split = packaged_splits[0]
+            .downcast_ref::<PackagedSplitBatch>()
+            .unwrap();
+        let split = &packaged_split.splits[0];
+        let doc_ids_sidecar = split.doc_ids_sidecar_opt.as_ref().unwrap();
+        let bloom_filter = BloomFilter::from_bytes(doc_ids_sidecar).unwrap();
+
+        for num in 1..10 {
+            assert!(bloom_filter.contains(format!("many-{num}").as_bytes()));
+        }
+        assert!(!bloom_filter.contains(b"unknown-doc-id"));
+        universe.assert_quit().await;
+        Ok(())
+    }
 }
@@ -321,17 +321,26 @@ impl Handler<PackagedSplitBatch> for Uploader {
                         &packaged_split.serialized_split_fields,
                         &packaged_split.hotcache_bytes,
                     )?;
-                    let split_metadata = create_split_metadata(
+                    let mut split_metadata = create_split_metadata(
                         &merge_policy,
                         &packaged_split.split_attrs,
                         packaged_split.tags.clone(),
                         split_streamer.footer_range.start..split_streamer.footer_range.end,
                     );
+                    split_metadata.encryption_key_id =
+                        split_store.encryption_key_id().map(str::to_string);
+                    split_metadata.field_stats = packaged_split.field_stats.clone();
+                    split_metadata.has_doc_ids_sidecar =
+                        packaged_split.doc_ids_sidecar_opt.is_some();
 
-                    report_splits.push(ReportSplit {
-                        storage_uri: split_store.remote_uri().to_string(),
-                        split_id: packaged_split.split_id().to_string(),
-                    });
+                    // Searchers do not cache the splits of encrypted indexes, so there is no
+                    // point in having them download the splits.
+                    if split_metadata.encryption_key_id.is_none() {
+                        report_splits.push(ReportSplit {
+                            storage_uri: split_store.remote_uri().to_string(),
+                            split_id: packaged_split.split_id().to_string(),
+                        });
+                    }
 
                     split_metadata_list.push(split_metadata);
 
@@ -470,6 +479,12 @@ async fn upload_split(
         &packaged_split.hotcache_bytes,
     )?;
 
+    // The sidecar is uploaded first so that a published split flagged as having one always does.
+    if let Some(doc_ids_sidecar) = &packaged_split.doc_ids_sidecar_opt {
+        split_store
+            .store_doc_ids_sidecar(packaged_split.split_id(), doc_ids_sidecar.clone())
+            .await?;
+    }
     split_store
         .store_split(
             split_metadata,
@@ -562,10 +577,16 @@ mod tests {
                         split_id: "test-split".to_string(),
                         delete_opstamp: 10,
                         num_merge_ops: 0,
+                        doc_mapping_version: 0,
+                        docstore_codec: None,
+                        sort_by: None,
+                        split_tags: Default::default(),
                     },
                     serialized_split_fields: Vec::new(),
                     split_scratch_directory,
                     tags: Default::default(),
+                    field_stats: Default::default(),
+                    doc_ids_sidecar_opt: None,
                     hotcache_bytes: Vec::new(),
                     split_files: Vec::new(),
                 }],
@@ -674,10 +695,16 @@ mod tests {
                 ],
                 delete_opstamp: 0,
                 num_merge_ops: 0,
+                doc_mapping_version: 0,
+                docstore_codec: None,
+                sort_by: None,
+                split_tags: Default::default(),
             },
             serialized_split_fields: Vec::new(),
             split_scratch_directory: split_scratch_directory_1,
             tags: Default::default(),
+            field_stats: Default::default(),
+            doc_ids_sidecar_opt: None,
             split_files: Vec::new(),
             hotcache_bytes: Vec::new(),
         };
@@ -698,10 +725,16 @@ mod tests {
                 ],
                 delete_opstamp: 0,
                 num_merge_ops: 0,
+                doc_mapping_version: 0,
+                docstore_codec: None,
+                sort_by: None,
+                split_tags: Default::default(),
             },
             serialized_split_fields: Vec::new(),
             split_scratch_directory: split_scratch_directory_2,
             tags: Default::default(),
+            field_stats: Default::default(),
+            doc_ids_sidecar_opt: None,
             split_files: Vec::new(),
             hotcache_bytes: Vec::new(),
         };
@@ -816,10 +849,16 @@ mod tests {
                         split_id: "test-split".to_string(),
                         delete_opstamp: 10,
                         num_merge_ops: 0,
+                        doc_mapping_version: 0,
+                        docstore_codec: None,
+                        sort_by: None,
+                        split_tags: Default::default(),
                     },
                     serialized_split_fields: Vec::new(),
                     split_scratch_directory,
                     tags: Default::default(),
+                    field_stats: Default::default(),
+                    doc_ids_sidecar_opt: None,
                     hotcache_bytes: Vec::new(),
                     split_files: Vec::new(),
                 }],
@@ -995,10 +1034,16 @@ mod tests {
                         split_id: SPLIT_ULID_STR.to_string(),
                         delete_opstamp: 10,
                         num_merge_ops: 0,
+                        doc_mapping_version: 0,
+                        docstore_codec: None,
+                        sort_by: None,
+                        split_tags: Default::default(),
                     },
                     serialized_split_fields: Vec::new(),
                     split_scratch_directory,
                     tags: Default::default(),
+                    field_stats: Default::default(),
+                    doc_ids_sidecar_opt: None,
                     hotcache_bytes: Vec::new(),
                     split_files: Vec::new(),
                 }],
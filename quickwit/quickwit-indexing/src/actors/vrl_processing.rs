@@ -20,6 +20,7 @@
 use std::collections::BTreeMap;
 
 use quickwit_config::TransformConfig;
+use serde_json::Value as JsonValue;
 use tracing::warn;
 use vrl::compiler::runtime::Runtime;
 pub use vrl::compiler::runtime::Terminate as VrlTerminate;
@@ -28,6 +29,11 @@ use vrl::compiler::{Program, TargetValueRef, TimeZone};
 pub use vrl::value::{Secrets as VrlSecrets, Value as VrlValue};
 
 use super::doc_processor::DocProcessorError;
+use crate::models::DocMetadata;
+
+/// Key of the metadata of the documents in the metadata of the VRL program, so that the program
+/// reads it from `%metadata`.
+const DOC_METADATA_KEY: &str = "metadata";
 
 pub(super) struct VrlDoc {
     pub vrl_value: VrlValue,
@@ -52,12 +58,26 @@ pub(super) struct VrlProgram {
 }
 
 impl VrlProgram {
-    pub fn transform_doc(&mut self, vrl_doc: VrlDoc) -> Result<VrlDoc, DocProcessorError> {
+    /// Runs the program on a document. The metadata of the document, if any, is exposed to the
+    /// program as `%metadata`.
+    pub fn transform_doc(
+        &mut self,
+        vrl_doc: VrlDoc,
+        doc_metadata_opt: Option<&DocMetadata>,
+    ) -> Result<VrlDoc, DocProcessorError> {
         let VrlDoc {
             mut vrl_value,
             num_bytes,
         } = vrl_doc;
 
+        if let (Some(doc_metadata), VrlValue::Object(metadata)) =
+            (doc_metadata_opt, &mut self.metadata)
+        {
+            let doc_metadata_value: VrlValue =
+                serde_json::from_value(JsonValue::Object(doc_metadata.as_ref().clone()))?;
+            metadata.insert(DOC_METADATA_KEY.to_string(), doc_metadata_value);
+        }
+
         let mut target = TargetValueRef {
             value: &mut vrl_value,
             metadata: &mut self.metadata,
@@ -0,0 +1,129 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use bytesize::ByteSize;
+use quickwit_common::cgroup::CgroupLimits;
+use serde::Serialize;
+
+/// Floor of the heap size of a workbench, below which splits would be too small to be worth
+/// producing.
+const MIN_HEAP_SIZE_PER_WORKBENCH: ByteSize = ByteSize::mb(50);
+
+/// Indexer resource settings derived from the cgroup limits of the node, so that indexers running
+/// in containers stay within their CPU and memory budget without hand-tuning the indexing
+/// settings of every index.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct IndexerAutotuning {
+    /// Number of workbenches allowed to index concurrently when cooperative indexing is enabled.
+    pub num_cooperative_indexing_permits: usize,
+    /// Upper bound of the `heap_size` indexing setting, applied when the memory is limited.
+    #[schema(value_type = Option<String>)]
+    pub max_heap_size_per_workbench: Option<ByteSize>,
+    /// Maximum number of merges executing concurrently on the node, applied when the CPU is
+    /// limited.
+    pub max_concurrent_merges: Option<usize>,
+}
+
+impl IndexerAutotuning {
+    pub fn new(num_blocking_threads: usize, cgroup_limits: &CgroupLimits) -> Self {
+        let num_cpus_opt = cgroup_limits.num_cpus();
+
+        let num_cooperative_indexing_permits = num_cpus_opt
+            .map_or(num_blocking_threads, |num_cpus| {
+                num_blocking_threads.min(num_cpus)
+            })
+            .max(1);
+        // Half of the memory goes to the workbenches. The other half is left to merges, doc
+        // processing, caches, and the runtime.
+        let max_heap_size_per_workbench = cgroup_limits.memory_limit.map(|memory_limit| {
+            let heap_size_per_workbench =
+                ByteSize(memory_limit.as_u64() / 2 / num_cooperative_indexing_permits as u64);
+            heap_size_per_workbench.max(MIN_HEAP_SIZE_PER_WORKBENCH)
+        });
+        // Merges are CPU-intensive: they must not starve the indexing pipelines.
+        let max_concurrent_merges = num_cpus_opt.map(|num_cpus| (num_cpus / 2).max(1));
+
+        IndexerAutotuning {
+            num_cooperative_indexing_permits,
+            max_heap_size_per_workbench,
+            max_concurrent_merges,
+        }
+    }
+
+    /// Returns the heap size of a workbench, given the `heap_size` indexing setting of the index.
+    pub fn heap_size_per_workbench(&self, heap_size: ByteSize) -> ByteSize {
+        self.max_heap_size_per_workbench
+            .map_or(heap_size, |max_heap_size| heap_size.min(max_heap_size))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_indexer_autotuning_without_limits() {
+        let autotuning = IndexerAutotuning::new(7, &CgroupLimits::default());
+        assert_eq!(autotuning.num_cooperative_indexing_permits, 7);
+        assert_eq!(autotuning.max_heap_size_per_workbench, None);
+        assert_eq!(autotuning.max_concurrent_merges, None);
+        assert_eq!(
+            autotuning.heap_size_per_workbench(ByteSize::gb(2)),
+            ByteSize::gb(2)
+        );
+    }
+
+    #[test]
+    fn test_indexer_autotuning_with_limits() {
+        let cgroup_limits = CgroupLimits {
+            cpu_limit_millis: Some(3_500),
+            memory_limit: Some(ByteSize::gib(4)),
+        };
+        let autotuning = IndexerAutotuning::new(15, &cgroup_limits);
+        assert_eq!(autotuning.num_cooperative_indexing_permits, 4);
+        assert_eq!(
+            autotuning.max_heap_size_per_workbench,
+            Some(ByteSize::mib(512))
+        );
+        assert_eq!(autotuning.max_concurrent_merges, Some(2));
+        assert_eq!(
+            autotuning.heap_size_per_workbench(ByteSize::gb(2)),
+            ByteSize::mib(512)
+        );
+        assert_eq!(
+            autotuning.heap_size_per_workbench(ByteSize::mb(100)),
+            ByteSize::mb(100)
+        );
+    }
+
+    #[test]
+    fn test_indexer_autotuning_with_tight_limits() {
+        let cgroup_limits = CgroupLimits {
+            cpu_limit_millis: Some(500),
+            memory_limit: Some(ByteSize::mb(64)),
+        };
+        let autotuning = IndexerAutotuning::new(1, &cgroup_limits);
+        assert_eq!(autotuning.num_cooperative_indexing_permits, 1);
+        assert_eq!(
+            autotuning.max_heap_size_per_workbench,
+            Some(MIN_HEAP_SIZE_PER_WORKBENCH)
+        );
+        assert_eq!(autotuning.max_concurrent_merges, Some(1));
+    }
+}
@@ -0,0 +1,414 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use bytes::Bytes;
+use quickwit_actors::Mailbox;
+use quickwit_config::DeadLetterQueueConfig;
+use quickwit_ingest::{
+    get_ingest_api_service, CommitType, DocBatchBuilder, IngestApiService, IngestRequest,
+};
+use quickwit_storage::Storage;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::{Instant, MissedTickBehavior};
+use tracing::warn;
+
+/// Maximum number of recent dead letters kept in memory per source.
+const MAX_NUM_DEAD_LETTER_SAMPLES: usize = 100;
+
+/// Maximum number of dead letters waiting to be handed over to the writer. Dead letters recorded
+/// while the writer lags behind are dropped.
+const DEAD_LETTER_CHANNEL_CAPACITY: usize = 10_000;
+
+/// Number of bytes of buffered dead letters above which the writer flushes them to the sink.
+const MAX_PENDING_NUM_BYTES: usize = 5 * 1024 * 1024; // 5 MiB
+
+/// Interval at which the writer flushes the buffered dead letters to the sink.
+const FLUSH_INTERVAL: Duration = Duration::from_millis(if cfg!(test) { 100 } else { 10_000 });
+
+/// Directory of the index storage where the `file` sink writes dead letters.
+pub const DEAD_LETTER_QUEUE_DIR_NAME: &str = "dead-letter-queue";
+
+/// A document rejected by the doc processor, along with the reason of the rejection.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct DeadLetter {
+    /// Unix timestamp (in seconds) at which the document was rejected.
+    pub timestamp: i64,
+    pub index_id: String,
+    pub source_id: String,
+    pub reason: String,
+    /// Raw document, decoded as UTF-8 (invalid sequences are replaced).
+    pub doc: String,
+}
+
+/// Bounded buffer of the most recent dead letters of a source. It is shared between the doc
+/// processor(s) of the source and the indexing service, which exposes it for sampling.
+#[derive(Clone, Debug, Default)]
+pub struct DeadLetterSamples {
+    inner: Arc<Mutex<VecDeque<DeadLetter>>>,
+}
+
+impl DeadLetterSamples {
+    fn push(&self, dead_letter: DeadLetter) {
+        let mut samples = self.inner.lock().expect("lock should not be poisoned");
+        if samples.len() == MAX_NUM_DEAD_LETTER_SAMPLES {
+            samples.pop_front();
+        }
+        samples.push_back(dead_letter);
+    }
+
+    /// Returns at most `limit` dead letters, most recent first.
+    pub fn recent(&self, limit: usize) -> Vec<DeadLetter> {
+        let samples = self.inner.lock().expect("lock should not be poisoned");
+        samples.iter().rev().take(limit).cloned().collect()
+    }
+}
+
+enum DeadLetterSink {
+    File {
+        storage: Arc<dyn Storage>,
+        dir_path: PathBuf,
+    },
+    Index {
+        index_id: String,
+        ingest_api_service: Mailbox<IngestApiService>,
+    },
+}
+
+/// Collects the documents rejected by a doc processor. The dead letters are handed over to a
+/// background writer, which buffers them and flushes them to the sink configured for the source
+/// once they exceed [`MAX_PENDING_NUM_BYTES`] or every [`FLUSH_INTERVAL`], so that writing them
+/// never blocks the doc processor.
+pub struct DeadLetterQueue {
+    index_id: String,
+    source_id: String,
+    samples: DeadLetterSamples,
+    dead_letter_tx: mpsc::Sender<DeadLetter>,
+    writer_handle: JoinHandle<()>,
+}
+
+impl DeadLetterQueue {
+    pub async fn try_new(
+        index_id: String,
+        source_id: String,
+        dead_letter_queue_config: &DeadLetterQueueConfig,
+        index_storage: Arc<dyn Storage>,
+        queues_dir_path: &Path,
+        samples: DeadLetterSamples,
+    ) -> anyhow::Result<Self> {
+        let sink = match dead_letter_queue_config {
+            DeadLetterQueueConfig::File => DeadLetterSink::File {
+                storage: index_storage,
+                dir_path: Path::new(DEAD_LETTER_QUEUE_DIR_NAME).join(&source_id),
+            },
+            DeadLetterQueueConfig::Index {
+                index_id: dead_letter_index_id,
+            } => DeadLetterSink::Index {
+                index_id: dead_letter_index_id.clone(),
+                ingest_api_service: get_ingest_api_service(queues_dir_path).await?,
+            },
+        };
+        let (dead_letter_tx, dead_letter_rx) = mpsc::channel(DEAD_LETTER_CHANNEL_CAPACITY);
+        let writer = DeadLetterWriter {
+            index_id: index_id.clone(),
+            source_id: source_id.clone(),
+            sink,
+            pending_dead_letters: Vec::new(),
+            pending_num_bytes: 0,
+        };
+        let writer_handle = tokio::spawn(writer.run(dead_letter_rx));
+
+        Ok(Self {
+            index_id,
+            source_id,
+            samples,
+            dead_letter_tx,
+            writer_handle,
+        })
+    }
+
+    /// Records a rejected document. The document is written to the sink by the background writer.
+    pub(crate) fn record(&mut self, raw_doc: Bytes, reason: String) {
+        let dead_letter = DeadLetter {
+            timestamp: OffsetDateTime::now_utc().unix_timestamp(),
+            index_id: self.index_id.clone(),
+            source_id: self.source_id.clone(),
+            reason,
+            doc: String::from_utf8_lossy(&raw_doc).into_owned(),
+        };
+        self.samples.push(dead_letter.clone());
+
+        if self.dead_letter_tx.try_send(dead_letter).is_err() {
+            crate::metrics::INDEXER_METRICS
+                .dead_letter_docs_total
+                .with_label_values([&self.index_id, &self.source_id, "failure"])
+                .inc();
+        }
+    }
+
+    /// Closes the queue and waits for the writer to flush the remaining dead letters to the sink.
+    /// Dropping the queue instead lets the writer flush them in the background.
+    pub(crate) async fn close(self) {
+        drop(self.dead_letter_tx);
+
+        if let Err(join_error) = self.writer_handle.await {
+            warn!(
+                index_id=%self.index_id,
+                source_id=%self.source_id,
+                error=?join_error,
+                "dead letter queue writer failed"
+            );
+        }
+    }
+}
+
+/// Background task buffering the dead letters of a source and writing them to its sink.
+struct DeadLetterWriter {
+    index_id: String,
+    source_id: String,
+    sink: DeadLetterSink,
+    pending_dead_letters: Vec<DeadLetter>,
+    pending_num_bytes: usize,
+}
+
+impl DeadLetterWriter {
+    async fn run(mut self, mut dead_letter_rx: mpsc::Receiver<DeadLetter>) {
+        let mut flush_interval =
+            tokio::time::interval_at(Instant::now() + FLUSH_INTERVAL, FLUSH_INTERVAL);
+        flush_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            tokio::select! {
+                dead_letter_opt = dead_letter_rx.recv() => {
+                    let Some(dead_letter) = dead_letter_opt else {
+                        break;
+                    };
+                    self.pending_num_bytes += dead_letter.doc.len() + dead_letter.reason.len();
+                    self.pending_dead_letters.push(dead_letter);
+
+                    if self.pending_num_bytes >= MAX_PENDING_NUM_BYTES {
+                        self.flush().await;
+                    }
+                }
+                _ = flush_interval.tick() => self.flush().await,
+            }
+        }
+        // The queue is closed: the remaining dead letters are flushed before the writer exits.
+        self.flush().await;
+    }
+
+    /// Writes the pending dead letters to the sink. Failures are logged and do not interrupt
+    /// indexing: the dead letters are dropped.
+    async fn flush(&mut self) {
+        if self.pending_dead_letters.is_empty() {
+            return;
+        }
+        let dead_letters = std::mem::take(&mut self.pending_dead_letters);
+        self.pending_num_bytes = 0;
+        let num_dead_letters = dead_letters.len() as u64;
+
+        if let Err(error) = self.write_dead_letters(dead_letters).await {
+            warn!(
+                index_id=%self.index_id,
+                source_id=%self.source_id,
+                error=?error,
+                "failed to write {num_dead_letters} document(s) to dead letter queue"
+            );
+            crate::metrics::INDEXER_METRICS
+                .dead_letter_docs_total
+                .with_label_values([&self.index_id, &self.source_id, "failure"])
+                .inc_by(num_dead_letters);
+            return;
+        }
+        crate::metrics::INDEXER_METRICS
+            .dead_letter_docs_total
+            .with_label_values([&self.index_id, &self.source_id, "success"])
+            .inc_by(num_dead_letters);
+    }
+
+    async fn write_dead_letters(&self, dead_letters: Vec<DeadLetter>) -> anyhow::Result<()> {
+        match &self.sink {
+            DeadLetterSink::File { storage, dir_path } => {
+                let mut payload = Vec::new();
+
+                for dead_letter in &dead_letters {
+                    serde_json::to_writer(&mut payload, dead_letter)?;
+                    payload.push(b'\n');
+                }
+                let file_name = format!("{}.ndjson", ulid::Ulid::new());
+                storage
+                    .put(&dir_path.join(file_name), Box::new(payload))
+                    .await?;
+            }
+            DeadLetterSink::Index {
+                index_id,
+                ingest_api_service,
+            } => {
+                let mut doc_batch_builder = DocBatchBuilder::new(index_id.clone()).json_writer();
+
+                for dead_letter in &dead_letters {
+                    doc_batch_builder.ingest_doc(dead_letter)?;
+                }
+                let ingest_request = IngestRequest {
+                    doc_batches: vec![doc_batch_builder.build()],
+                    commit: CommitType::Auto.into(),
+                    idempotency_key: None,
+                    return_positions: false,
+                };
+                ingest_api_service.ask_for_res(ingest_request).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_storage::RamStorage;
+
+    use super::*;
+
+    #[test]
+    fn test_dead_letter_samples_are_bounded() {
+        let samples = DeadLetterSamples::default();
+
+        for i in 0..MAX_NUM_DEAD_LETTER_SAMPLES + 10 {
+            samples.push(DeadLetter {
+                timestamp: i as i64,
+                index_id: "test-index".to_string(),
+                source_id: "test-source".to_string(),
+                reason: "parse error".to_string(),
+                doc: "{".to_string(),
+            });
+        }
+        let recent = samples.recent(usize::MAX);
+        assert_eq!(recent.len(), MAX_NUM_DEAD_LETTER_SAMPLES);
+        assert_eq!(recent[0].timestamp, MAX_NUM_DEAD_LETTER_SAMPLES as i64 + 9);
+
+        let recent = samples.recent(2);
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[1].timestamp, MAX_NUM_DEAD_LETTER_SAMPLES as i64 + 8);
+    }
+
+    #[tokio::test]
+    async fn test_dead_letter_queue_file_sink() {
+        let storage = Arc::new(RamStorage::default());
+        let samples = DeadLetterSamples::default();
+        let mut dead_letter_queue = DeadLetterQueue::try_new(
+            "test-index".to_string(),
+            "test-source".to_string(),
+            &DeadLetterQueueConfig::File,
+            storage.clone(),
+            Path::new("/unused"),
+            samples.clone(),
+        )
+        .await
+        .unwrap();
+
+        dead_letter_queue.record(Bytes::from_static(b"{"), "parse error: EOF".to_string());
+        dead_letter_queue.record(Bytes::from_static(b"[]"), "parse error: array".to_string());
+        dead_letter_queue.close().await;
+
+        let files = storage.list_files().await;
+        assert_eq!(files.len(), 1);
+        assert!(files[0].starts_with("dead-letter-queue/test-source"));
+
+        let payload = storage.get_all(&files[0]).await.unwrap();
+        let dead_letters: Vec<DeadLetter> = std::str::from_utf8(payload.as_slice())
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(dead_letters.len(), 2);
+        assert_eq!(dead_letters[0].doc, "{");
+        assert_eq!(dead_letters[0].reason, "parse error: EOF");
+        assert_eq!(dead_letters[1].doc, "[]");
+
+        let recent = samples.recent(10);
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].doc, "[]");
+    }
+
+    #[tokio::test]
+    async fn test_dead_letter_queue_flushes_periodically() {
+        let storage = Arc::new(RamStorage::default());
+        let mut dead_letter_queue = DeadLetterQueue::try_new(
+            "test-index".to_string(),
+            "test-source".to_string(),
+            &DeadLetterQueueConfig::File,
+            storage.clone(),
+            Path::new("/unused"),
+            DeadLetterSamples::default(),
+        )
+        .await
+        .unwrap();
+
+        tokio::time::sleep(FLUSH_INTERVAL * 2).await;
+        assert!(storage.list_files().await.is_empty());
+
+        dead_letter_queue.record(Bytes::from_static(b"{"), "parse error: EOF".to_string());
+
+        for _ in 0..100 {
+            if !storage.list_files().await.is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(storage.list_files().await.len(), 1);
+
+        // The dead letters are written once.
+        tokio::time::sleep(FLUSH_INTERVAL * 2).await;
+        assert_eq!(storage.list_files().await.len(), 1);
+
+        dead_letter_queue.close().await;
+        assert_eq!(storage.list_files().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_dead_letter_queue_flushes_large_batches() {
+        let storage = Arc::new(RamStorage::default());
+        let mut dead_letter_queue = DeadLetterQueue::try_new(
+            "test-index".to_string(),
+            "test-source".to_string(),
+            &DeadLetterQueueConfig::File,
+            storage.clone(),
+            Path::new("/unused"),
+            DeadLetterSamples::default(),
+        )
+        .await
+        .unwrap();
+
+        let raw_doc = Bytes::from(vec![b'x'; MAX_PENDING_NUM_BYTES / 2]);
+        dead_letter_queue.record(raw_doc.clone(), "parse error".to_string());
+        dead_letter_queue.record(raw_doc.clone(), "parse error".to_string());
+        dead_letter_queue.record(raw_doc, "parse error".to_string());
+        dead_letter_queue.close().await;
+
+        // The first two dead letters exceed the size threshold and are flushed together, the
+        // last one is flushed when the queue is closed.
+        assert_eq!(storage.list_files().await.len(), 2);
+    }
+}
@@ -0,0 +1,262 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! "Latest version wins" deduplication of the documents sharing the same doc ID, applied when
+//! merging the splits of an index with a `doc_id_field`.
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use tantivy::columnar::{ColumnType, MonotonicallyMappableToU64, StrColumn};
+use tantivy::fastfield::Column;
+use tantivy::query::{ConstScorer, EmptyScorer, EnableScoring, Explanation, Query, Scorer, Weight};
+use tantivy::{
+    DateTime, DocId, DocSet, Score, Searcher, SegmentId, SegmentReader, TantivyError, TERMINATED,
+};
+
+/// Rank of a document among the documents sharing its doc ID: timestamp, creation timestamp of
+/// its split, and doc ID within its segment. The document with the greatest rank wins.
+type DocRank = (i64, i64, DocId);
+
+struct Candidate {
+    rank: DocRank,
+    segment_id: SegmentId,
+    doc: DocId,
+}
+
+/// Finds the documents superseded by a more recent version of the same doc ID.
+///
+/// The most recent version is the one with the greatest timestamp, if the index has a timestamp
+/// field, then the one from the most recently created split, then the one added last.
+///
+/// Returns the sorted IDs of the superseded documents per segment.
+pub(crate) fn find_superseded_docs(
+    searcher: &Searcher,
+    doc_id_field_name: &str,
+    timestamp_field_name_opt: Option<&str>,
+    segment_create_timestamps: &HashMap<SegmentId, i64>,
+) -> tantivy::Result<HashMap<SegmentId, Vec<DocId>>> {
+    let mut winners: HashMap<String, Candidate> = HashMap::new();
+    let mut superseded_docs: HashMap<SegmentId, Vec<DocId>> = HashMap::new();
+
+    for segment_reader in searcher.segment_readers() {
+        let segment_id = segment_reader.segment_id();
+        let Some(doc_id_column) = DocIdColumn::open(segment_reader, doc_id_field_name)? else {
+            continue;
+        };
+        let timestamp_column_opt: Option<Column<DateTime>> = match timestamp_field_name_opt {
+            Some(timestamp_field_name) => segment_reader
+                .fast_fields()
+                .column_opt(timestamp_field_name)?,
+            None => None,
+        };
+        let create_timestamp = segment_create_timestamps
+            .get(&segment_id)
+            .copied()
+            .unwrap_or_default();
+
+        // Doc IDs are first deduplicated within the segment using their column key, which is
+        // cheaper to compare than their string representation.
+        let mut segment_winners: HashMap<u64, (DocRank, DocId)> = HashMap::new();
+
+        for doc in segment_reader.doc_ids_alive() {
+            let Some(key) = doc_id_column.key(doc) else {
+                continue;
+            };
+            let timestamp = timestamp_column_opt
+                .as_ref()
+                .and_then(|timestamp_column| timestamp_column.first(doc))
+                .map(|timestamp| timestamp.into_timestamp_nanos())
+                .unwrap_or(i64::MIN);
+            let rank = (timestamp, create_timestamp, doc);
+
+            match segment_winners.entry(key) {
+                Entry::Occupied(mut entry) => {
+                    let (winner_rank, winner_doc) = entry.get_mut();
+                    let superseded_doc = if rank > *winner_rank {
+                        *winner_rank = rank;
+                        std::mem::replace(winner_doc, doc)
+                    } else {
+                        doc
+                    };
+                    superseded_docs
+                        .entry(segment_id)
+                        .or_default()
+                        .push(superseded_doc);
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert((rank, doc));
+                }
+            }
+        }
+        for (key, (rank, doc)) in segment_winners {
+            let doc_id = doc_id_column.key_to_string(key)?;
+            let candidate = Candidate {
+                rank,
+                segment_id,
+                doc,
+            };
+            match winners.entry(doc_id) {
+                Entry::Occupied(mut entry) => {
+                    let superseded = if candidate.rank > entry.get().rank {
+                        std::mem::replace(entry.get_mut(), candidate)
+                    } else {
+                        candidate
+                    };
+                    superseded_docs
+                        .entry(superseded.segment_id)
+                        .or_default()
+                        .push(superseded.doc);
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(candidate);
+                }
+            }
+        }
+    }
+    for docs in superseded_docs.values_mut() {
+        docs.sort_unstable();
+    }
+    Ok(superseded_docs)
+}
+
+/// Fast field column holding the doc IDs of the documents of a segment.
+enum DocIdColumn {
+    Str(StrColumn),
+    Numeric {
+        column: Column<u64>,
+        column_type: ColumnType,
+    },
+}
+
+impl DocIdColumn {
+    fn open(segment_reader: &SegmentReader, field_name: &str) -> tantivy::Result<Option<Self>> {
+        let fast_fields = segment_reader.fast_fields();
+
+        if let Some(str_column) = fast_fields.str(field_name)? {
+            return Ok(Some(Self::Str(str_column)));
+        }
+        if let Some((column, column_type)) = fast_fields.u64_lenient(field_name)? {
+            return Ok(Some(Self::Numeric {
+                column,
+                column_type,
+            }));
+        }
+        Ok(None)
+    }
+
+    fn key(&self, doc: DocId) -> Option<u64> {
+        match self {
+            Self::Str(str_column) => str_column.ords().first(doc),
+            Self::Numeric { column, .. } => column.first(doc),
+        }
+    }
+
+    fn key_to_string(&self, key: u64) -> tantivy::Result<String> {
+        match self {
+            Self::Str(str_column) => {
+                let mut buffer = String::new();
+                str_column.ord_to_str(key, &mut buffer)?;
+                Ok(buffer)
+            }
+            Self::Numeric { column_type, .. } => {
+                let key_str = match column_type {
+                    ColumnType::I64 => i64::from_u64(key).to_string(),
+                    _ => key.to_string(),
+                };
+                Ok(key_str)
+            }
+        }
+    }
+}
+
+/// Query matching an explicit list of documents per segment.
+#[derive(Clone, Debug)]
+pub(crate) struct DocListQuery {
+    docs_per_segment: Arc<HashMap<SegmentId, Vec<DocId>>>,
+}
+
+impl DocListQuery {
+    /// Creates a query matching the given documents. The documents of each segment must be
+    /// sorted.
+    pub fn new(docs_per_segment: HashMap<SegmentId, Vec<DocId>>) -> Self {
+        Self {
+            docs_per_segment: Arc::new(docs_per_segment),
+        }
+    }
+}
+
+impl Query for DocListQuery {
+    fn weight(&self, _enable_scoring: EnableScoring<'_>) -> tantivy::Result<Box<dyn Weight>> {
+        Ok(Box::new(DocListWeight {
+            docs_per_segment: self.docs_per_segment.clone(),
+        }))
+    }
+}
+
+struct DocListWeight {
+    docs_per_segment: Arc<HashMap<SegmentId, Vec<DocId>>>,
+}
+
+impl Weight for DocListWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> tantivy::Result<Box<dyn Scorer>> {
+        let Some(docs) = self.docs_per_segment.get(&reader.segment_id()) else {
+            return Ok(Box::new(EmptyScorer));
+        };
+        let doc_set = SortedDocSet {
+            docs: docs.clone(),
+            cursor: 0,
+        };
+        Ok(Box::new(ConstScorer::new(doc_set, boost)))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> tantivy::Result<Explanation> {
+        let is_match = self
+            .docs_per_segment
+            .get(&reader.segment_id())
+            .map_or(false, |docs| docs.binary_search(&doc).is_ok());
+        if !is_match {
+            return Err(TantivyError::InvalidArgument(format!(
+                "document #({doc}) does not match"
+            )));
+        }
+        Ok(Explanation::new("DocListQuery", 1.0))
+    }
+}
+
+struct SortedDocSet {
+    docs: Vec<DocId>,
+    cursor: usize,
+}
+
+impl DocSet for SortedDocSet {
+    fn advance(&mut self) -> DocId {
+        self.cursor += 1;
+        self.doc()
+    }
+
+    fn doc(&self) -> DocId {
+        self.docs.get(self.cursor).copied().unwrap_or(TERMINATED)
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.docs.len() as u32
+    }
+}
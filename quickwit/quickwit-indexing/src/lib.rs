@@ -33,16 +33,27 @@ pub use crate::actors::{
     IndexingError, IndexingPipeline, IndexingPipelineParams, IndexingService, PublisherType,
     Sequencer, SplitsUpdateMailbox,
 };
+pub use crate::autotuning::IndexerAutotuning;
 pub use crate::controlled_directory::ControlledDirectory;
+pub use crate::dead_letter_queue::{DeadLetter, DeadLetterSamples};
 use crate::models::IndexingStatistics;
+pub use crate::source_backlog::setup_source_backlog_listener;
+pub use crate::source_health::{
+    PipelineFailure, SourceHealth, SourceHealthReport, SourceHealthStatus,
+};
 pub use crate::split_store::{get_tantivy_directory_from_split_bundle, IndexingSplitStore};
 
 pub mod actors;
+mod autotuning;
 mod controlled_directory;
+mod dead_letter_queue;
+mod doc_id_dedup;
 pub mod merge_policy;
 mod metrics;
 pub mod models;
 pub mod source;
+mod source_backlog;
+mod source_health;
 mod split_store;
 #[cfg(any(test, feature = "testsuite"))]
 mod test_utils;
@@ -55,7 +66,15 @@ use self::merge_policy::MergePolicy;
 pub use self::source::check_source_connectivity;
 
 #[derive(utoipa::OpenApi)]
-#[openapi(components(schemas(IndexingStatistics, PipelineMetrics, CpuCapacity)))]
+#[openapi(components(schemas(
+    IndexingStatistics,
+    PipelineMetrics,
+    CpuCapacity,
+    DeadLetter,
+    SourceHealthReport,
+    SourceHealthStatus,
+    PipelineFailure
+)))]
 /// Schema used for the OpenAPI generation which are apart of this crate.
 pub struct IndexingApiSchemas;
 
@@ -23,7 +23,9 @@ use quickwit_common::metrics::{new_counter_vec, new_gauge_vec, IntCounterVec, In
 pub struct IndexerMetrics {
     pub processed_docs_total: IntCounterVec<3>,
     pub processed_bytes: IntCounterVec<3>,
+    pub dead_letter_docs_total: IntCounterVec<3>,
     pub backpressure_micros: IntCounterVec<2>,
+    pub cooperative_indexing_permit_wait_micros: IntCounterVec<1>,
     pub available_concurrent_upload_permits: IntGaugeVec<1>,
     pub ongoing_merge_operations: IntGaugeVec<2>,
 }
@@ -45,6 +47,13 @@ impl Default for IndexerMetrics {
                 "quickwit_indexing",
                 ["index", "source", "docs_processed_status"],
             ),
+            dead_letter_docs_total: new_counter_vec(
+                "dead_letter_docs_total",
+                "Number of invalid docs forwarded to the dead letter queue by index, source and \
+                 write status in [success, failure]",
+                "quickwit_indexing",
+                ["index", "source", "status"],
+            ),
             backpressure_micros: new_counter_vec(
                 "backpressure_micros",
                 "Amount of time spent in backpressure (in micros). This time only includes the \
@@ -52,6 +61,13 @@ impl Default for IndexerMetrics {
                 "quickwit_indexing",
                 ["index", "actor_name"],
             ),
+            cooperative_indexing_permit_wait_micros: new_counter_vec(
+                "cooperative_indexing_permit_wait_micros",
+                "Amount of time spent by the indexers waiting for a cooperative indexing permit \
+                 (in micros), by source priority in [realtime, backfill]",
+                "quickwit_indexing",
+                ["priority"],
+            ),
             available_concurrent_upload_permits: new_gauge_vec(
                 "concurrent_upload_available_permits_num",
                 "Number of available concurrent upload permits by component in [merger, indexer]",
@@ -22,6 +22,7 @@ use std::path::Path;
 
 use quickwit_common::io::IoControls;
 use quickwit_common::temp_dir::TempDirectory;
+use quickwit_config::{DocstoreCodec, IndexSortBy};
 use quickwit_metastore::checkpoint::IndexCheckpointDelta;
 use quickwit_proto::indexing::IndexingPipelineId;
 use quickwit_proto::types::{IndexUid, PublishToken};
@@ -80,6 +81,9 @@ impl IndexedSplitBuilder {
     pub fn new_in_dir(
         pipeline_id: IndexingPipelineId,
         partition_id: u64,
+        doc_mapping_version: u64,
+        docstore_codec: DocstoreCodec,
+        sort_by: Option<IndexSortBy>,
         last_delete_opstamp: u64,
         scratch_directory: TempDirectory,
         index_builder: IndexBuilder,
@@ -110,6 +114,10 @@ impl IndexedSplitBuilder {
                 time_range: None,
                 delete_opstamp: last_delete_opstamp,
                 num_merge_ops: 0,
+                doc_mapping_version,
+                docstore_codec: Some(docstore_codec),
+                sort_by,
+                split_tags: Default::default(),
             },
             index_writer,
             split_scratch_directory,
@@ -172,6 +180,7 @@ pub enum CommitTrigger {
     MemoryLimit,
     NoMoreDocs,
     NumDocsLimit,
+    SplitSizeLimit,
     Timeout,
 }
 
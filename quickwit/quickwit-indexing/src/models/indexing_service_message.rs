@@ -63,3 +63,37 @@ pub struct DetachMergePipeline {
 pub struct ObservePipeline {
     pub pipeline_id: IndexingPipelineId,
 }
+
+/// Pauses the merges of an index. The flag is persisted in the index metadata so that the merge
+/// pipelines of the index apply it on every indexer. In-flight merges run to completion, while new
+/// splits keep accumulating until merges are resumed. The reply is the number of merge pipelines
+/// of the index running on this node.
+#[derive(Debug)]
+pub struct PauseMerges {
+    pub index_id: String,
+}
+
+/// Resumes the merges of an index on every indexer. The reply is the number of merge pipelines of
+/// the index running on this node.
+#[derive(Debug)]
+pub struct ResumeMerges {
+    pub index_id: String,
+}
+
+/// Samples the most recent documents rejected by the pipelines of a source running on this node.
+/// The reply is `None` if no pipeline of the source was spawned on this node or if they were all
+/// shut down.
+#[derive(Debug)]
+pub struct GetDeadLetters {
+    pub index_id: String,
+    pub source_id: String,
+    pub limit: usize,
+}
+
+/// Reports the health of the pipelines of a source running on this node. The reply is `None` if
+/// no pipeline of the source was spawned on this node or if they were all shut down.
+#[derive(Debug)]
+pub struct GetSourceHealth {
+    pub index_id: String,
+    pub source_id: String,
+}
@@ -17,7 +17,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::sync::atomic::Ordering;
 
 use quickwit_proto::indexing::PipelineMetrics;
@@ -56,6 +56,9 @@ pub struct IndexingStatistics {
     // List of shard ids.
     #[schema(value_type = Vec<u64>)]
     pub shard_ids: BTreeSet<ShardId>,
+    // Number of records of each partition that the source has not indexed yet. Only reported by
+    // the sources that can estimate it.
+    pub partition_backlogs: BTreeMap<String, u64>,
 }
 
 impl IndexingStatistics {
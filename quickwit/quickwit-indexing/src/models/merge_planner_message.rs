@@ -23,3 +23,10 @@ use quickwit_metastore::SplitMetadata;
 pub struct NewSplits {
     pub new_splits: Vec<SplitMetadata>,
 }
+
+/// Pauses or resumes the planning of merge operations. Splits published while merges are paused
+/// are still recorded and become merge candidates once merges are resumed.
+#[derive(Clone, Copy, Debug)]
+pub struct SetMergesPaused {
+    pub merges_paused: bool,
+}
@@ -33,16 +33,18 @@ mod raw_doc_batch;
 mod shard_positions;
 mod split_attrs;
 mod split_fields;
+mod split_size_estimator;
 
 pub use indexed_split::{
     CommitTrigger, EmptySplit, IndexedSplit, IndexedSplitBatch, IndexedSplitBatchBuilder,
     IndexedSplitBuilder,
 };
 pub use indexing_service_message::{
-    DetachIndexingPipeline, DetachMergePipeline, ObservePipeline, SpawnPipeline,
+    DetachIndexingPipeline, DetachMergePipeline, GetDeadLetters, GetSourceHealth, ObservePipeline,
+    PauseMerges, ResumeMerges, SpawnPipeline,
 };
 pub use indexing_statistics::IndexingStatistics;
-pub use merge_planner_message::NewSplits;
+pub use merge_planner_message::{NewSplits, SetMergesPaused};
 pub use merge_scratch::MergeScratch;
 pub use merge_statistics::MergeStatistics;
 pub use packaged_split::{PackagedSplit, PackagedSplitBatch};
@@ -50,11 +52,12 @@ pub use processed_doc::{ProcessedDoc, ProcessedDocBatch};
 pub use publish_lock::{NewPublishLock, PublishLock};
 pub use publisher_message::SplitsUpdate;
 use quickwit_proto::types::PublishToken;
-pub use raw_doc_batch::RawDocBatch;
+pub use raw_doc_batch::{DocMetadata, RawDocBatch};
 pub(crate) use shard_positions::LocalShardPositionsUpdate;
 pub use shard_positions::ShardPositionsService;
 pub use split_attrs::{create_split_metadata, SplitAttrs};
 pub use split_fields::{read_split_fields, serialize_split_fields, FieldConfig};
+pub use split_size_estimator::SplitSizeEstimator;
 
 #[derive(Debug)]
 pub struct NewPublishToken(pub PublishToken);
@@ -17,12 +17,13 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
 
 use itertools::Itertools;
 use quickwit_common::temp_dir::TempDirectory;
 use quickwit_metastore::checkpoint::IndexCheckpointDelta;
+use quickwit_metastore::FieldStats;
 use quickwit_proto::types::{IndexUid, PublishToken, SplitId};
 use tantivy::TrackedObject;
 use tracing::Span;
@@ -35,6 +36,9 @@ pub struct PackagedSplit {
     pub split_attrs: SplitAttrs,
     pub split_scratch_directory: TempDirectory,
     pub tags: BTreeSet<String>,
+    pub field_stats: BTreeMap<String, FieldStats>,
+    /// Serialized Bloom filter of the values of the doc ID field, if the index defines one.
+    pub doc_ids_sidecar_opt: Option<Vec<u8>>,
     pub split_files: Vec<std::path::PathBuf>,
     pub hotcache_bytes: Vec<u8>,
 }
@@ -55,6 +59,7 @@ impl fmt::Debug for PackagedSplit {
             .field("split_attrs", &self.split_attrs)
             .field("split_scratch_directory", &self.split_scratch_directory)
             .field("tags", &self.tags)
+            .field("field_stats", &self.field_stats)
             .field("split_files", &self.split_files)
             .finish()
     }
@@ -27,6 +27,8 @@ pub struct ProcessedDoc {
     pub timestamp_opt: Option<DateTime>,
     pub partition: u64,
     pub num_bytes: usize,
+    /// Custom split tags, as `(key, value)` pairs, set with the `_split_tags` field.
+    pub split_tags: Vec<(String, String)>,
 }
 
 impl fmt::Debug for ProcessedDoc {
@@ -35,6 +37,7 @@ impl fmt::Debug for ProcessedDoc {
             .field("timestamp_opt", &self.timestamp_opt)
             .field("partition", &self.partition)
             .field("num_bytes", &self.num_bytes)
+            .field("split_tags", &self.split_tags)
             .finish()
     }
 }
@@ -18,13 +18,22 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 use std::fmt;
+use std::sync::Arc;
 
 use bytes::Bytes;
+use quickwit_doc_mapper::JsonObject;
 use quickwit_metastore::checkpoint::SourceCheckpointDelta;
 
+/// Metadata of the source message a document was read from, for instance the topic, partition, and
+/// offset of a Kafka message. The VRL transform of the source reads it from `%metadata`.
+pub type DocMetadata = Arc<JsonObject>;
+
 #[derive(Default)]
 pub struct RawDocBatch {
     pub docs: Vec<Bytes>,
+    /// Metadata of the documents: `doc_metadatas[i]` is the metadata of `docs[i]`. This vector may
+    /// be shorter than `docs`, in which case the trailing documents have no metadata.
+    pub doc_metadatas: Vec<Option<DocMetadata>>,
     pub checkpoint_delta: SourceCheckpointDelta,
     pub force_commit: bool,
 }
@@ -37,6 +46,7 @@ impl RawDocBatch {
     ) -> Self {
         Self {
             docs,
+            doc_metadatas: Vec::new(),
             checkpoint_delta,
             force_commit,
         }
@@ -45,6 +55,7 @@ impl RawDocBatch {
     pub fn with_capacity(capacity: usize) -> Self {
         Self {
             docs: Vec::with_capacity(capacity),
+            doc_metadatas: Vec::new(),
             checkpoint_delta: SourceCheckpointDelta::default(),
             force_commit: false,
         }
@@ -54,6 +65,13 @@ impl RawDocBatch {
         self.docs.len()
     }
 
+    /// Appends a document along with its metadata.
+    pub fn push_doc_with_metadata(&mut self, doc: Bytes, doc_metadata: DocMetadata) {
+        self.doc_metadatas.resize(self.docs.len(), None);
+        self.doc_metadatas.push(Some(doc_metadata));
+        self.docs.push(doc);
+    }
+
     #[cfg(any(test, feature = "testsuite"))]
     pub fn for_test(docs: &[&str], range: std::ops::Range<u64>) -> Self {
         let docs = docs
@@ -64,6 +82,7 @@ impl RawDocBatch {
 
         Self {
             docs,
+            doc_metadatas: Vec::new(),
             checkpoint_delta,
             force_commit: false,
         }
@@ -17,11 +17,12 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
 use std::ops::{Range, RangeInclusive};
 use std::sync::Arc;
 
+use quickwit_config::{DocstoreCodec, IndexSortBy};
 use quickwit_metastore::SplitMetadata;
 use quickwit_proto::indexing::IndexingPipelineId;
 use tantivy::DateTime;
@@ -63,6 +64,19 @@ pub struct SplitAttrs {
 
     // Number of merge operation the split has been through so far.
     pub num_merge_ops: usize,
+
+    /// Version of the doc mapping the split is built with.
+    pub doc_mapping_version: u64,
+
+    /// Codec used to compress the doc store of the split, if known.
+    pub docstore_codec: Option<DocstoreCodec>,
+
+    /// Order in which the documents of the split are sorted, if they are.
+    pub sort_by: Option<IndexSortBy>,
+
+    /// Custom tags of the split, grouped by key. They are recorded from the `_split_tags` field
+    /// of the documents, or carried over from the replaced splits on merges.
+    pub split_tags: BTreeMap<String, BTreeSet<String>>,
 }
 
 impl fmt::Debug for SplitAttrs {
@@ -78,6 +92,10 @@ impl fmt::Debug for SplitAttrs {
             )
             .field("num_docs", &self.num_docs)
             .field("num_merge_ops", &self.num_merge_ops)
+            .field("doc_mapping_version", &self.doc_mapping_version)
+            .field("docstore_codec", &self.docstore_codec)
+            .field("sort_by", &self.sort_by)
+            .field("split_tags", &self.split_tags)
             .finish()
     }
 }
@@ -109,5 +127,9 @@ pub fn create_split_metadata(
         footer_offsets,
         delete_opstamp: split_attrs.delete_opstamp,
         num_merge_ops: split_attrs.num_merge_ops,
+        doc_mapping_version: split_attrs.doc_mapping_version,
+        docstore_codec: split_attrs.docstore_codec,
+        sort_by: split_attrs.sort_by,
+        encryption_key_id: None,
     }
 }
@@ -0,0 +1,100 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Mutex;
+
+use bytesize::ByteSize;
+
+/// Ratio assumed until the first split is serialized. Overestimating the size of the splits
+/// produces smaller splits rather than oversized ones.
+const INITIAL_COMPRESSION_RATIO: f64 = 1.0;
+
+/// Weight of the last serialized split in the estimated compression ratio.
+const SMOOTHING_FACTOR: f64 = 0.3;
+
+/// Estimates the size of the splits once serialized from the memory used by tantivy to index
+/// their documents.
+///
+/// The ratio between the two is learned from the splits serialized by the pipeline: it is updated
+/// by the index serializer and read by the indexer.
+#[derive(Debug, Default)]
+pub struct SplitSizeEstimator {
+    compression_ratio_opt: Mutex<Option<f64>>,
+}
+
+impl SplitSizeEstimator {
+    /// Returns the estimated ratio between the serialized size of a split and the memory used to
+    /// index its documents.
+    pub fn compression_ratio(&self) -> f64 {
+        self.compression_ratio_opt
+            .lock()
+            .unwrap()
+            .unwrap_or(INITIAL_COMPRESSION_RATIO)
+    }
+
+    /// Estimates the serialized size of splits whose documents use `memory_usage` bytes once
+    /// indexed.
+    pub fn estimate_serialized_size(&self, memory_usage: ByteSize) -> ByteSize {
+        ByteSize((memory_usage.as_u64() as f64 * self.compression_ratio()) as u64)
+    }
+
+    /// Records the serialized size of a split whose documents used `memory_usage` bytes once
+    /// indexed.
+    pub fn record_serialized_split(&self, memory_usage: ByteSize, serialized_size: ByteSize) {
+        if memory_usage.as_u64() == 0 {
+            return;
+        }
+        let compression_ratio = serialized_size.as_u64() as f64 / memory_usage.as_u64() as f64;
+        let mut compression_ratio_guard = self.compression_ratio_opt.lock().unwrap();
+
+        let new_compression_ratio = match *compression_ratio_guard {
+            Some(previous_compression_ratio) => {
+                previous_compression_ratio * (1.0 - SMOOTHING_FACTOR)
+                    + compression_ratio * SMOOTHING_FACTOR
+            }
+            None => compression_ratio,
+        };
+        *compression_ratio_guard = Some(new_compression_ratio);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_size_estimator() {
+        let split_size_estimator = SplitSizeEstimator::default();
+        assert_eq!(
+            split_size_estimator.estimate_serialized_size(ByteSize::mb(100)),
+            ByteSize::mb(100)
+        );
+        split_size_estimator.record_serialized_split(ByteSize(0), ByteSize::mb(1));
+        assert_eq!(split_size_estimator.compression_ratio(), 1.0);
+
+        split_size_estimator.record_serialized_split(ByteSize::mb(100), ByteSize::mb(20));
+        assert_eq!(split_size_estimator.compression_ratio(), 0.2);
+        assert_eq!(
+            split_size_estimator.estimate_serialized_size(ByteSize::mb(100)),
+            ByteSize::mb(20)
+        );
+        split_size_estimator.record_serialized_split(ByteSize::mb(100), ByteSize::mb(120));
+        assert!((split_size_estimator.compression_ratio() - 0.5).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,53 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use quickwit_config::AzureEventHubsSourceParams;
+use quickwit_metastore::checkpoint::SourceCheckpoint;
+
+use super::kafka_source;
+use crate::source::{KafkaSource, SourceRuntimeArgs, TypedSourceFactory};
+
+/// Factory for instantiating a source consuming an Azure Event Hubs event hub.
+///
+/// Event Hubs namespaces expose a Kafka-compatible endpoint, so the source is a `KafkaSource`
+/// configured from the connection string. Event hub partitions are checkpointed like Kafka
+/// partitions.
+pub struct AzureEventHubsSourceFactory;
+
+#[async_trait]
+impl TypedSourceFactory for AzureEventHubsSourceFactory {
+    type Source = KafkaSource;
+    type Params = AzureEventHubsSourceParams;
+
+    async fn typed_create_source(
+        ctx: Arc<SourceRuntimeArgs>,
+        params: AzureEventHubsSourceParams,
+        checkpoint: SourceCheckpoint,
+    ) -> anyhow::Result<Self::Source> {
+        let kafka_params = params.to_kafka_source_params()?;
+        KafkaSource::try_new(ctx, kafka_params, checkpoint).await
+    }
+}
+
+pub(super) async fn check_connectivity(params: &AzureEventHubsSourceParams) -> anyhow::Result<()> {
+    let kafka_params = params.to_kafka_source_params()?;
+    kafka_source::check_connectivity(kafka_params).await
+}
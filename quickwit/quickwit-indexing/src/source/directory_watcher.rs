@@ -0,0 +1,546 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::io::SeekFrom;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use anyhow::Context;
+use glob::Pattern;
+use quickwit_metastore::checkpoint::{PartitionId, SourceCheckpoint};
+use quickwit_proto::types::Position;
+use tokio::fs::File;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, Take};
+use tracing::{info, warn};
+
+/// Interval at which the watched directory is listed again once all its files have been read.
+pub(crate) const WATCH_POLL_INTERVAL: Duration =
+    Duration::from_millis(if cfg!(test) { 100 } else { 5_000 });
+
+/// Entry of the ledger of the files read by the source. The ledger is keyed by file path and
+/// stored in the source checkpoint: each file is a partition whose position encodes its entry.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+struct LedgerEntry {
+    /// Incremented every time the content of the file is replaced, so that the position of the
+    /// partition keeps increasing while the file is read again from the beginning.
+    generation: u64,
+    /// Number of bytes of the file read by the source, i.e. the size of the file once it has
+    /// been fully read.
+    num_bytes_read: u64,
+    inode: u64,
+    /// Modification time of the file, in nanoseconds since the Unix epoch, when it was listed
+    /// before its last read. Refreshed every time the source resumes reading an appended file.
+    modified_nanos: u64,
+}
+
+impl LedgerEntry {
+    fn to_position(self) -> Position {
+        let position_str = format!(
+            "{:0>20}:{:0>20}:{}:{}",
+            self.generation, self.num_bytes_read, self.inode, self.modified_nanos
+        );
+        Position::offset(position_str.as_str())
+    }
+
+    fn from_position(position: &Position) -> Option<Self> {
+        let Position::Offset(offset) = position else {
+            return None;
+        };
+        let mut parts = offset.as_str().split(':');
+        let ledger_entry = LedgerEntry {
+            generation: parts.next()?.parse().ok()?,
+            num_bytes_read: parts.next()?.parse().ok()?,
+            inode: parts.next()?.parse().ok()?,
+            modified_nanos: parts.next()?.parse().ok()?,
+        };
+        Some(ledger_entry)
+    }
+}
+
+/// File of the watched directory as it was listed.
+struct ListedFile {
+    file_name: String,
+    path: PathBuf,
+    num_bytes: u64,
+    inode: u64,
+    modified: SystemTime,
+}
+
+impl ListedFile {
+    fn modified_nanos(&self) -> u64 {
+        self.modified
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|duration| duration.as_nanos() as u64)
+            .unwrap_or(0)
+    }
+}
+
+/// File being read by the source.
+struct CurrentFile {
+    file_name: String,
+    ledger_entry: LedgerEntry,
+}
+
+/// File of the watched directory, opened at the offset the source must resume from. Reading
+/// stops at the size the file had when the directory was listed, so that the ledger entry
+/// recorded once the file is read matches its modification time.
+pub(crate) struct OpenedFile {
+    pub partition_id: PartitionId,
+    pub offset: u64,
+    pub file: Take<File>,
+}
+
+/// Lists the files of a local directory watched by a file source in watch mode. Each file is a
+/// partition of the source, keyed by its path. The position of a partition records the size,
+/// inode, and modification time of the file read: unchanged files are skipped, including after a
+/// restart, whereas files whose content was replaced are read again. Files renamed by a log
+/// rotation are recognized by their inode and are not read again. Directories on object storage
+/// are not supported: their objects have no inode and would have to be tracked by ETag instead.
+pub(crate) struct DirectoryWatcher {
+    dir_path: PathBuf,
+    file_pattern_opt: Option<Pattern>,
+    // Ledger of the files read by the source, indexed by file name.
+    ledger: HashMap<String, LedgerEntry>,
+    current_file_opt: Option<CurrentFile>,
+}
+
+impl DirectoryWatcher {
+    /// Creates a watcher for the directory `dir_path`, resuming from the ledger recorded in
+    /// `checkpoint`.
+    pub fn new(
+        dir_path: &Path,
+        file_pattern_opt: Option<&str>,
+        checkpoint: &SourceCheckpoint,
+    ) -> anyhow::Result<Self> {
+        let file_pattern_opt = file_pattern_opt
+            .map(|file_pattern| {
+                Pattern::new(file_pattern)
+                    .with_context(|| format!("invalid file pattern `{file_pattern}`"))
+            })
+            .transpose()?;
+        let mut watcher = DirectoryWatcher {
+            dir_path: dir_path.to_path_buf(),
+            file_pattern_opt,
+            ledger: HashMap::new(),
+            current_file_opt: None,
+        };
+        for (partition_id, position) in checkpoint.iter() {
+            let Some(file_name) = watcher.parse_partition_id(&partition_id) else {
+                continue;
+            };
+            let Some(ledger_entry) = LedgerEntry::from_position(&position) else {
+                warn!(partition_id=%partition_id, position=%position, "invalid file source ledger entry");
+                continue;
+            };
+            watcher.ledger.insert(file_name, ledger_entry);
+        }
+        Ok(watcher)
+    }
+
+    fn partition_id(&self, file_name: &str) -> PartitionId {
+        PartitionId::from(self.dir_path.join(file_name).display().to_string())
+    }
+
+    fn parse_partition_id(&self, partition_id: &PartitionId) -> Option<String> {
+        let path = Path::new(partition_id.0.as_str());
+
+        if path.parent()? != self.dir_path {
+            return None;
+        }
+        Some(path.file_name()?.to_str()?.to_string())
+    }
+
+    async fn list_files(&self) -> anyhow::Result<Vec<ListedFile>> {
+        let mut read_dir = tokio::fs::read_dir(&self.dir_path).await.with_context(|| {
+            format!(
+                "failed to list watched directory `{}`",
+                self.dir_path.display()
+            )
+        })?;
+        let mut listed_files = Vec::new();
+
+        while let Some(entry) = read_dir.next_entry().await? {
+            let Ok(file_name) = entry.file_name().into_string() else {
+                continue;
+            };
+            if let Some(file_pattern) = &self.file_pattern_opt {
+                if !file_pattern.matches(&file_name) {
+                    continue;
+                }
+            }
+            let path = entry.path();
+            // The file may have been deleted since the directory was listed.
+            let Ok(metadata) = tokio::fs::metadata(&path).await else {
+                continue;
+            };
+            if !metadata.is_file() {
+                continue;
+            }
+            let listed_file = ListedFile {
+                file_name,
+                path,
+                num_bytes: metadata.len(),
+                inode: metadata.ino(),
+                modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+            };
+            listed_files.push(listed_file);
+        }
+        Ok(listed_files)
+    }
+
+    /// Returns the ledger entry the source must resume reading `listed_file` from.
+    fn resume_ledger_entry(
+        &self,
+        listed_file: &ListedFile,
+        rotated_entries: &HashMap<u64, LedgerEntry>,
+    ) -> LedgerEntry {
+        let recorded_entry_opt = self.ledger.get(&listed_file.file_name);
+        let next_generation = recorded_entry_opt
+            .map(|recorded_entry| recorded_entry.generation + 1)
+            .unwrap_or(0);
+        let new_entry = LedgerEntry {
+            generation: next_generation,
+            num_bytes_read: 0,
+            inode: listed_file.inode,
+            modified_nanos: listed_file.modified_nanos(),
+        };
+        match recorded_entry_opt {
+            Some(recorded_entry) if recorded_entry.inode == listed_file.inode => {
+                let is_truncated = listed_file.num_bytes < recorded_entry.num_bytes_read;
+                let is_rewritten = listed_file.num_bytes == recorded_entry.num_bytes_read
+                    && listed_file.modified_nanos() != recorded_entry.modified_nanos;
+
+                if is_truncated || is_rewritten {
+                    return new_entry;
+                }
+                // The data appended to the file is read up to the size it has now, so the entry
+                // must record its current modification time. Otherwise, the file would be deemed
+                // rewritten on the next listing and read again from the beginning.
+                LedgerEntry {
+                    modified_nanos: listed_file.modified_nanos(),
+                    ..*recorded_entry
+                }
+            }
+            _ => {
+                // The file may have been renamed by a log rotation, in which case the source
+                // resumes from where it stopped reading it under its previous name.
+                if let Some(rotated_entry) = rotated_entries.get(&listed_file.inode) {
+                    if listed_file.num_bytes >= rotated_entry.num_bytes_read {
+                        return LedgerEntry {
+                            num_bytes_read: rotated_entry.num_bytes_read,
+                            ..new_entry
+                        };
+                    }
+                }
+                new_entry
+            }
+        }
+    }
+
+    /// Lists the watched directory and opens the least recently modified file that has not been
+    /// fully read. Returns `None` if all the files have been read.
+    pub async fn open_next_file(&mut self) -> anyhow::Result<Option<OpenedFile>> {
+        self.current_file_opt = None;
+
+        let listed_files = self.list_files().await?;
+
+        // Ledger entries of the files that no longer are at the path they were read from.
+        let mut rotated_entries: HashMap<u64, LedgerEntry> = HashMap::new();
+
+        for (file_name, ledger_entry) in &self.ledger {
+            let is_at_path = listed_files.iter().any(|listed_file| {
+                listed_file.file_name == *file_name && listed_file.inode == ledger_entry.inode
+            });
+            if is_at_path {
+                continue;
+            }
+            let rotated_entry = rotated_entries.entry(ledger_entry.inode).or_default();
+
+            if ledger_entry.num_bytes_read >= rotated_entry.num_bytes_read {
+                *rotated_entry = *ledger_entry;
+            }
+        }
+        let mut candidates: Vec<(SystemTime, usize, LedgerEntry)> = Vec::new();
+
+        for (listed_file_idx, listed_file) in listed_files.iter().enumerate() {
+            let ledger_entry = self.resume_ledger_entry(listed_file, &rotated_entries);
+
+            if listed_file.num_bytes > ledger_entry.num_bytes_read {
+                candidates.push((listed_file.modified, listed_file_idx, ledger_entry));
+            }
+        }
+        let Some((_, listed_file_idx, ledger_entry)) = candidates
+            .into_iter()
+            .min_by_key(|(modified, listed_file_idx, _)| (*modified, *listed_file_idx))
+        else {
+            return Ok(None);
+        };
+        let listed_file = &listed_files[listed_file_idx];
+        let mut file = File::open(&listed_file.path).await?;
+
+        // The file may have been rotated since the directory was listed: it will be picked up on
+        // the next listing.
+        if file.metadata().await?.ino() != listed_file.inode {
+            return Ok(None);
+        }
+        file.seek(SeekFrom::Start(ledger_entry.num_bytes_read))
+            .await?;
+
+        let is_replaced = self
+            .ledger
+            .get(&listed_file.file_name)
+            .map(|recorded_entry| recorded_entry.generation != ledger_entry.generation)
+            .unwrap_or(false);
+        if is_replaced && ledger_entry.num_bytes_read == 0 {
+            warn!(path=%listed_file.path.display(), "file was truncated or replaced, reading it from the beginning");
+        }
+        let partition_id = self.partition_id(&listed_file.file_name);
+        info!(path=%listed_file.path.display(), offset=ledger_entry.num_bytes_read, "reading watched file");
+
+        let opened_file = OpenedFile {
+            partition_id,
+            offset: ledger_entry.num_bytes_read,
+            file: file.take(listed_file.num_bytes - ledger_entry.num_bytes_read),
+        };
+        self.current_file_opt = Some(CurrentFile {
+            file_name: listed_file.file_name.clone(),
+            ledger_entry,
+        });
+        Ok(Some(opened_file))
+    }
+
+    /// Records that the file being read has been read up to `offset`. Returns the positions
+    /// delimiting the corresponding checkpoint delta of the file partition.
+    pub fn record_progress(&mut self, offset: u64) -> (Position, Position) {
+        let current_file = self
+            .current_file_opt
+            .as_mut()
+            .expect("a file should be open");
+        current_file.ledger_entry.num_bytes_read = offset;
+
+        let from_position = self
+            .ledger
+            .insert(current_file.file_name.clone(), current_file.ledger_entry)
+            .map(LedgerEntry::to_position)
+            .unwrap_or_default();
+        let to_position = current_file.ledger_entry.to_position();
+        (from_position, to_position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    async fn read_to_string(opened_file: &mut OpenedFile) -> String {
+        let mut content = String::new();
+        opened_file.file.read_to_string(&mut content).await.unwrap();
+        content
+    }
+
+    #[tokio::test]
+    async fn test_directory_watcher() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir_path = temp_dir.path();
+        let mut watcher =
+            DirectoryWatcher::new(dir_path, Some("*.log"), &SourceCheckpoint::default()).unwrap();
+        assert!(watcher.open_next_file().await.unwrap().is_none());
+
+        std::fs::write(dir_path.join("app.txt"), "ignored\n").unwrap();
+        std::fs::write(dir_path.join("app.log"), "line-1\n").unwrap();
+
+        let mut opened_file = watcher.open_next_file().await.unwrap().unwrap();
+        assert_eq!(opened_file.partition_id, watcher.partition_id("app.log"));
+        assert_eq!(opened_file.offset, 0);
+        assert_eq!(read_to_string(&mut opened_file).await, "line-1\n");
+        let (from_position, to_position) = watcher.record_progress(7);
+        assert_eq!(from_position, Position::Beginning);
+        assert!(to_position > from_position);
+        assert!(watcher.open_next_file().await.unwrap().is_none());
+
+        // The file is rotated: the source keeps reading the renamed file.
+        let mut rotated_file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(dir_path.join("app.log"))
+            .unwrap();
+        rotated_file.write_all(b"line-2\n").unwrap();
+        std::fs::rename(dir_path.join("app.log"), dir_path.join("app.1.log")).unwrap();
+
+        let mut opened_file = watcher.open_next_file().await.unwrap().unwrap();
+        assert_eq!(opened_file.partition_id, watcher.partition_id("app.1.log"));
+        assert_eq!(opened_file.offset, 7);
+        assert_eq!(read_to_string(&mut opened_file).await, "line-2\n");
+        watcher.record_progress(14);
+
+        // The file is truncated: the source reads it from the beginning.
+        std::fs::write(dir_path.join("app.1.log"), "line-3\n").unwrap();
+
+        let mut opened_file = watcher.open_next_file().await.unwrap().unwrap();
+        assert_eq!(opened_file.partition_id, watcher.partition_id("app.1.log"));
+        assert_eq!(opened_file.offset, 0);
+        assert_eq!(read_to_string(&mut opened_file).await, "line-3\n");
+        let (from_position, to_position) = watcher.record_progress(7);
+        assert!(to_position > from_position);
+    }
+
+    #[tokio::test]
+    async fn test_directory_watcher_ledger() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir_path = temp_dir.path();
+        std::fs::write(dir_path.join("a.log"), "line-1\n").unwrap();
+
+        let mut watcher =
+            DirectoryWatcher::new(dir_path, None, &SourceCheckpoint::default()).unwrap();
+        let mut opened_file = watcher.open_next_file().await.unwrap().unwrap();
+        assert_eq!(read_to_string(&mut opened_file).await, "line-1\n");
+        let (_, to_position) = watcher.record_progress(7);
+
+        let checkpoint: SourceCheckpoint = [(opened_file.partition_id, to_position.clone())]
+            .into_iter()
+            .collect();
+
+        // Unchanged files are skipped.
+        let mut watcher = DirectoryWatcher::new(dir_path, None, &checkpoint).unwrap();
+        assert!(watcher.open_next_file().await.unwrap().is_none());
+
+        // New files are read.
+        std::fs::write(dir_path.join("b.log"), "line-2\n").unwrap();
+
+        let mut opened_file = watcher.open_next_file().await.unwrap().unwrap();
+        assert_eq!(opened_file.partition_id, watcher.partition_id("b.log"));
+        assert_eq!(opened_file.offset, 0);
+        assert_eq!(read_to_string(&mut opened_file).await, "line-2\n");
+        watcher.record_progress(7);
+        assert!(watcher.open_next_file().await.unwrap().is_none());
+
+        // Files uploaded again with a new content are read again.
+        std::fs::write(dir_path.join("a.log.tmp"), "line-3\n").unwrap();
+        std::fs::rename(dir_path.join("a.log.tmp"), dir_path.join("a.log")).unwrap();
+
+        let mut opened_file = watcher.open_next_file().await.unwrap().unwrap();
+        assert_eq!(opened_file.partition_id, watcher.partition_id("a.log"));
+        assert_eq!(opened_file.offset, 0);
+        assert_eq!(read_to_string(&mut opened_file).await, "line-3\n");
+        let (from_position, to_position) = watcher.record_progress(7);
+        assert_eq!(from_position, checkpoint.iter().next().unwrap().1);
+        assert!(to_position > from_position);
+        assert!(watcher.open_next_file().await.unwrap().is_none());
+
+        // Files modified in place are read again, even if their size does not change.
+        std::thread::sleep(Duration::from_millis(50));
+        std::fs::write(dir_path.join("a.log"), "line-4\n").unwrap();
+
+        let mut opened_file = watcher.open_next_file().await.unwrap().unwrap();
+        assert_eq!(opened_file.partition_id, watcher.partition_id("a.log"));
+        assert_eq!(opened_file.offset, 0);
+        assert_eq!(read_to_string(&mut opened_file).await, "line-4\n");
+        let (from_position, to_position) = watcher.record_progress(7);
+        assert!(to_position > from_position);
+        assert!(watcher.open_next_file().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_directory_watcher_resumes_from_checkpoint() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir_path = temp_dir.path();
+        std::fs::write(dir_path.join("app.log"), "line-1\nline-2\n").unwrap();
+        let inode = std::fs::metadata(dir_path.join("app.log")).unwrap().ino();
+
+        let watcher = DirectoryWatcher::new(dir_path, None, &SourceCheckpoint::default()).unwrap();
+        let ledger_entry = LedgerEntry {
+            generation: 1,
+            num_bytes_read: 7,
+            inode,
+            modified_nanos: 0,
+        };
+        let checkpoint: SourceCheckpoint = [
+            (watcher.partition_id("app.log"), ledger_entry.to_position()),
+            (PartitionId::from("other-partition"), Position::offset(3u64)),
+        ]
+        .into_iter()
+        .collect();
+
+        let mut watcher = DirectoryWatcher::new(dir_path, None, &checkpoint).unwrap();
+        let mut opened_file = watcher.open_next_file().await.unwrap().unwrap();
+        assert_eq!(opened_file.offset, 7);
+        assert_eq!(opened_file.partition_id, watcher.partition_id("app.log"));
+        assert_eq!(read_to_string(&mut opened_file).await, "line-2\n");
+
+        let (from_position, to_position) = watcher.record_progress(14);
+        assert_eq!(from_position, ledger_entry.to_position());
+        let ledger_entry = LedgerEntry::from_position(&to_position).unwrap();
+        assert_eq!(ledger_entry.generation, 1);
+        assert_eq!(ledger_entry.num_bytes_read, 14);
+        assert_eq!(ledger_entry.inode, inode);
+    }
+
+    #[tokio::test]
+    async fn test_directory_watcher_does_not_read_appended_file_again() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir_path = temp_dir.path();
+        let file_path = dir_path.join("app.log");
+        std::fs::write(&file_path, "line-1\n").unwrap();
+
+        let mut watcher =
+            DirectoryWatcher::new(dir_path, None, &SourceCheckpoint::default()).unwrap();
+        let mut opened_file = watcher.open_next_file().await.unwrap().unwrap();
+        assert_eq!(read_to_string(&mut opened_file).await, "line-1\n");
+        watcher.record_progress(7);
+        assert!(watcher.open_next_file().await.unwrap().is_none());
+
+        // Ensures the modification time of the file changes.
+        std::thread::sleep(Duration::from_millis(50));
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&file_path)
+            .unwrap();
+        file.write_all(b"line-2\n").unwrap();
+        let modified_nanos = std::fs::metadata(&file_path)
+            .unwrap()
+            .modified()
+            .unwrap()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos() as u64;
+
+        let mut opened_file = watcher.open_next_file().await.unwrap().unwrap();
+        assert_eq!(opened_file.offset, 7);
+        assert_eq!(read_to_string(&mut opened_file).await, "line-2\n");
+        let (_, to_position) = watcher.record_progress(14);
+
+        let ledger_entry = LedgerEntry::from_position(&to_position).unwrap();
+        assert_eq!(ledger_entry.generation, 0);
+        assert_eq!(ledger_entry.num_bytes_read, 14);
+        assert_eq!(ledger_entry.modified_nanos, modified_nanos);
+
+        // The appended file is not read again when the directory is listed again, including
+        // after a restart.
+        assert!(watcher.open_next_file().await.unwrap().is_none());
+
+        let checkpoint: SourceCheckpoint = [(opened_file.partition_id, to_position)]
+            .into_iter()
+            .collect();
+        let mut watcher = DirectoryWatcher::new(dir_path, None, &checkpoint).unwrap();
+        assert!(watcher.open_next_file().await.unwrap().is_none());
+    }
+}
@@ -19,7 +19,8 @@
 
 use std::fmt;
 use std::ops::Range;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -28,15 +29,18 @@ use async_trait::async_trait;
 use bytes::Bytes;
 use quickwit_actors::{ActorExitStatus, Mailbox};
 use quickwit_common::uri::Uri;
-use quickwit_config::FileSourceParams;
+use quickwit_config::{FileSourceParams, SourceInputFormat};
+use quickwit_doc_mapper::JsonObject;
 use quickwit_metastore::checkpoint::{PartitionId, SourceCheckpoint};
 use quickwit_proto::types::Position;
 use serde::Serialize;
+use serde_json::Value as JsonValue;
 use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
 use tracing::info;
 
+use super::directory_watcher::{DirectoryWatcher, WATCH_POLL_INTERVAL};
 use crate::actors::DocProcessor;
-use crate::models::RawDocBatch;
+use crate::models::{DocMetadata, RawDocBatch};
 use crate::source::{Source, SourceContext, SourceRuntimeArgs, TypedSourceFactory};
 
 /// Number of bytes after which a new batch is cut.
@@ -51,9 +55,18 @@ pub struct FileSourceCounters {
 
 pub struct FileSource {
     source_id: String,
-    params: FileSourceParams,
     counters: FileSourceCounters,
-    reader: BufReader<Box<dyn AsyncRead + Send + Unpin>>,
+    // `None` in watch mode when all the files of the watched directory have been read.
+    reader_opt: Option<BufReader<Box<dyn AsyncRead + Send + Unpin>>>,
+    // Partition of the file being read. `None` when reading from stdin.
+    partition_id_opt: Option<PartitionId>,
+    // Header row of a CSV file read from the beginning of the file when the source resumes past
+    // it. It is emitted ahead of the first batch so the doc processor can name the fields.
+    csv_header_opt: Option<Bytes>,
+    // Watcher of the directory read in watch mode.
+    watcher_opt: Option<DirectoryWatcher>,
+    // Whether the path of the file is attached to the documents as `%metadata.filename`.
+    collect_doc_metadata: bool,
 }
 
 impl fmt::Debug for FileSource {
@@ -62,6 +75,40 @@ impl fmt::Debug for FileSource {
     }
 }
 
+impl FileSource {
+    /// Opens the next file of the watched directory that has not been fully read. Returns `false`
+    /// if there is none.
+    async fn open_next_watched_file(&mut self) -> anyhow::Result<bool> {
+        let watcher = self
+            .watcher_opt
+            .as_mut()
+            .expect("file source should be in watch mode");
+        let Some(opened_file) = watcher.open_next_file().await? else {
+            return Ok(false);
+        };
+        self.counters.previous_offset = opened_file.offset;
+        self.counters.current_offset = opened_file.offset;
+        self.partition_id_opt = Some(opened_file.partition_id);
+        self.reader_opt = Some(BufReader::new(Box::new(opened_file.file)));
+        Ok(true)
+    }
+
+    /// Returns the metadata of the documents of the file being read, if it should be collected.
+    /// Documents read from stdin have no metadata.
+    fn doc_metadata_opt(&self) -> Option<DocMetadata> {
+        if !self.collect_doc_metadata {
+            return None;
+        }
+        let partition_id = self.partition_id_opt.as_ref()?;
+        let mut doc_metadata = JsonObject::with_capacity(1);
+        doc_metadata.insert(
+            "filename".to_string(),
+            JsonValue::String(partition_id.to_string()),
+        );
+        Some(Arc::new(doc_metadata))
+    }
+}
+
 #[async_trait]
 impl Source for FileSource {
     async fn emit_batches(
@@ -69,45 +116,73 @@ impl Source for FileSource {
         doc_processor_mailbox: &Mailbox<DocProcessor>,
         ctx: &SourceContext,
     ) -> Result<Duration, ActorExitStatus> {
+        if self.reader_opt.is_none() && !self.open_next_watched_file().await? {
+            return Ok(WATCH_POLL_INTERVAL);
+        }
+        let reader = self.reader_opt.as_mut().expect("reader should be open");
+        let is_watching = self.watcher_opt.is_some();
+
         // We collect batches of documents before sending them to the indexer.
         let limit_num_bytes = self.counters.previous_offset + BATCH_NUM_BYTES_LIMIT;
         let mut reached_eof = false;
         let mut doc_batch = RawDocBatch::default();
+        let doc_metadata_opt = self.doc_metadata_opt();
+
         while self.counters.current_offset < limit_num_bytes {
             let mut doc_line = String::new();
             // guard the zone in case of slow read, such as reading from someone
             // typing to stdin
             let num_bytes = ctx
-                .protect_future(self.reader.read_line(&mut doc_line))
+                .protect_future(reader.read_line(&mut doc_line))
                 .await
                 .map_err(anyhow::Error::from)?;
-            if num_bytes == 0 {
+            // In watch mode, a line without a trailing newline is being written: it is read again
+            // once complete.
+            if num_bytes == 0 || is_watching && !doc_line.ends_with('\n') {
                 reached_eof = true;
                 break;
             }
-            doc_batch.docs.push(Bytes::from(doc_line));
+            if let Some(doc_metadata) = &doc_metadata_opt {
+                doc_batch.push_doc_with_metadata(Bytes::from(doc_line), doc_metadata.clone());
+            } else {
+                doc_batch.docs.push(Bytes::from(doc_line));
+            }
             self.counters.current_offset += num_bytes as u64;
             self.counters.num_lines_processed += 1;
         }
         if !doc_batch.docs.is_empty() {
-            if let Some(filepath) = &self.params.filepath {
-                let filepath_str = filepath
-                    .to_str()
-                    .context("path is invalid utf-8")?
-                    .to_string();
-                let partition_id = PartitionId::from(filepath_str);
-                doc_batch
-                    .checkpoint_delta
-                    .record_partition_delta(
-                        partition_id,
+            if let Some(csv_header) = self.csv_header_opt.take() {
+                doc_batch.docs.insert(0, csv_header);
+
+                if !doc_batch.doc_metadatas.is_empty() {
+                    doc_batch.doc_metadatas.insert(0, None);
+                }
+            }
+            if let Some(partition_id) = &self.partition_id_opt {
+                // In watch mode, the position of the partition is the entry of the file in the
+                // ledger of the watcher.
+                let (from_position, to_position) = if let Some(watcher) = &mut self.watcher_opt {
+                    watcher.record_progress(self.counters.current_offset)
+                } else {
+                    (
                         Position::offset(self.counters.previous_offset),
                         Position::offset(self.counters.current_offset),
                     )
+                };
+                doc_batch
+                    .checkpoint_delta
+                    .record_partition_delta(partition_id.clone(), from_position, to_position)
                     .unwrap();
             }
             self.counters.previous_offset = self.counters.current_offset;
             ctx.send_message(doc_processor_mailbox, doc_batch).await?;
         }
+        if reached_eof && is_watching {
+            // The watched directory is listed again to pick up the data appended to the file, or
+            // the next file.
+            self.reader_opt = None;
+            return Ok(Duration::default());
+        }
         if reached_eof {
             info!("EOF");
             ctx.send_exit_with_success(doc_processor_mailbox).await?;
@@ -138,9 +213,34 @@ impl TypedSourceFactory for FileSourceFactory {
         params: FileSourceParams,
         checkpoint: SourceCheckpoint,
     ) -> anyhow::Result<FileSource> {
+        if params.watch {
+            let filepath = params
+                .filepath
+                .as_ref()
+                .context("file source cannot watch stdin")?;
+            let dir_path = watched_dir_path(filepath)?;
+            let watcher =
+                DirectoryWatcher::new(&dir_path, params.file_pattern.as_deref(), &checkpoint)?;
+            let file_source = FileSource {
+                source_id: ctx.source_id().to_string(),
+                counters: FileSourceCounters::default(),
+                reader_opt: None,
+                partition_id_opt: None,
+                csv_header_opt: None,
+                watcher_opt: Some(watcher),
+                collect_doc_metadata: ctx.collect_doc_metadata(),
+            };
+            return Ok(file_source);
+        }
         let mut offset = 0;
+        let mut csv_header_opt = None;
+        let mut partition_id_opt = None;
         let reader: Box<dyn AsyncRead + Send + Unpin> = if let Some(filepath) = &params.filepath {
-            let partition_id = PartitionId::from(filepath.to_string_lossy().to_string());
+            let filepath_str = filepath
+                .to_str()
+                .context("path is invalid utf-8")?
+                .to_string();
+            let partition_id = PartitionId::from(filepath_str);
             offset = checkpoint
                 .position_for_partition(&partition_id)
                 .map(|position| {
@@ -149,9 +249,27 @@ impl TypedSourceFactory for FileSourceFactory {
                         .expect("file offset should be stored as usize")
                 })
                 .unwrap_or(0);
+            partition_id_opt = Some(partition_id);
             let (dir_uri, file_name) = dir_and_filename(filepath)?;
             let storage = ctx.storage_resolver.resolve(&dir_uri).await?;
             let file_size = storage.file_num_bytes(file_name).await?.try_into().unwrap();
+
+            if offset > 0 && ctx.source_config.input_format == SourceInputFormat::Csv {
+                let header_stream = storage
+                    .get_slice_stream(
+                        file_name,
+                        Range {
+                            start: 0,
+                            end: file_size,
+                        },
+                    )
+                    .await?;
+                let mut csv_header = String::new();
+                BufReader::new(header_stream)
+                    .read_line(&mut csv_header)
+                    .await?;
+                csv_header_opt = Some(Bytes::from(csv_header));
+            }
             storage
                 .get_slice_stream(
                     file_name,
@@ -172,13 +290,25 @@ impl TypedSourceFactory for FileSourceFactory {
                 current_offset: offset as u64,
                 num_lines_processed: 0,
             },
-            reader: BufReader::new(reader),
-            params,
+            reader_opt: Some(BufReader::new(reader)),
+            partition_id_opt,
+            csv_header_opt,
+            watcher_opt: None,
+            collect_doc_metadata: ctx.collect_doc_metadata(),
         };
         Ok(file_source)
     }
 }
 
+/// Returns the local path of the directory watched by a file source in watch mode.
+pub(crate) fn watched_dir_path(filepath: &Path) -> anyhow::Result<PathBuf> {
+    let uri = Uri::from_str(&filepath.to_string_lossy())?;
+    let dir_path = uri
+        .filepath()
+        .with_context(|| format!("file source can only watch local directories, got `{uri}`"))?;
+    Ok(dir_path.to_path_buf())
+}
+
 pub(crate) fn dir_and_filename(filepath: &Path) -> anyhow::Result<(Uri, &Path)> {
     let dir_uri: Uri = filepath
         .parent()
@@ -220,6 +350,12 @@ mod tests {
             source_params: SourceParams::File(params.clone()),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            csv_config: Default::default(),
+            dead_letter_queue: None,
+            supervision: Default::default(),
+            fetch_stream: Default::default(),
+            placement_constraints: Default::default(),
+            priority: Default::default(),
         };
         let metastore = metastore_for_test();
         let file_source = FileSourceFactory::typed_create_source(
@@ -287,6 +423,12 @@ mod tests {
             source_params: SourceParams::File(params.clone()),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            csv_config: Default::default(),
+            dead_letter_queue: None,
+            supervision: Default::default(),
+            fetch_stream: Default::default(),
+            placement_constraints: Default::default(),
+            priority: Default::default(),
         };
         let metastore = metastore_for_test();
         let source = FileSourceFactory::typed_create_source(
@@ -378,6 +520,12 @@ mod tests {
             source_params: SourceParams::File(params.clone()),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            csv_config: Default::default(),
+            dead_letter_queue: None,
+            supervision: Default::default(),
+            fetch_stream: Default::default(),
+            placement_constraints: Default::default(),
+            priority: Default::default(),
         };
         let metastore = metastore_for_test();
         let source = FileSourceFactory::typed_create_source(
@@ -411,4 +559,141 @@ mod tests {
         let indexer_messages: Vec<RawDocBatch> = doc_processor_inbox.drain_for_test_typed();
         assert!(&indexer_messages[0].docs[0].starts_with(b"2\n"));
     }
+    #[tokio::test]
+    async fn test_file_source_csv_resume_from_checkpoint() {
+        let universe = Universe::with_accelerated_time();
+        let (doc_processor_mailbox, doc_processor_inbox) = universe.create_test_mailbox();
+        use tempfile::NamedTempFile;
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(b"id,name\n1,foo\n2,bar\n").unwrap();
+        temp_file.flush().unwrap();
+        let temp_file_path = temp_file.path().canonicalize().unwrap();
+        let params = FileSourceParams::file(&temp_file_path);
+        let mut checkpoint = SourceCheckpoint::default();
+        let partition_id = PartitionId::from(temp_file_path.to_string_lossy().to_string());
+        let checkpoint_delta = SourceCheckpointDelta::from_partition_delta(
+            partition_id,
+            Position::offset(0u64),
+            Position::offset(14u64),
+        )
+        .unwrap();
+        checkpoint.try_apply_delta(checkpoint_delta).unwrap();
+
+        let mut source_config =
+            SourceConfig::for_test("test-file-source", SourceParams::File(params.clone()));
+        source_config.input_format = SourceInputFormat::Csv;
+        let metastore = metastore_for_test();
+        let source = FileSourceFactory::typed_create_source(
+            SourceRuntimeArgs::for_test(
+                IndexUid::new_with_random_ulid("test-index"),
+                source_config,
+                metastore,
+                PathBuf::from("./queues"),
+            ),
+            params,
+            checkpoint,
+        )
+        .await
+        .unwrap();
+        let file_source_actor = SourceActor {
+            source: Box::new(source),
+            doc_processor_mailbox,
+        };
+        let (_file_source_mailbox, file_source_handle) =
+            universe.spawn_builder().spawn(file_source_actor);
+        let (actor_termination, counters) = file_source_handle.join().await;
+        assert!(actor_termination.is_success());
+        assert_eq!(
+            counters,
+            serde_json::json!({
+                "previous_offset": 20u64,
+                "current_offset": 20u64,
+                "num_lines_processed": 1u64
+            })
+        );
+        let indexer_messages: Vec<RawDocBatch> = doc_processor_inbox.drain_for_test_typed();
+        assert_eq!(indexer_messages.len(), 1);
+        assert_eq!(indexer_messages[0].docs.len(), 2);
+        assert_eq!(&indexer_messages[0].docs[0][..], b"id,name\n");
+        assert_eq!(&indexer_messages[0].docs[1][..], b"2,bar\n");
+        assert_eq!(
+            indexer_messages[0].checkpoint_delta,
+            SourceCheckpointDelta::from_partition_delta(
+                PartitionId::from(temp_file_path.to_string_lossy().to_string()),
+                Position::offset(14u64),
+                Position::offset(20u64),
+            )
+            .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_file_source_watch() {
+        let universe = Universe::with_accelerated_time();
+        let (doc_processor_mailbox, doc_processor_inbox) = universe.create_test_mailbox();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir_path = temp_dir.path().canonicalize().unwrap();
+        std::fs::write(dir_path.join("app.log"), "1\n2\n").unwrap();
+        std::fs::write(dir_path.join("app.txt"), "ignored\n").unwrap();
+
+        let params = FileSourceParams::watch(&dir_path, Some("*.log".to_string()));
+        let source_config =
+            SourceConfig::for_test("test-file-source", SourceParams::File(params.clone()));
+        let metastore = metastore_for_test();
+        let source = FileSourceFactory::typed_create_source(
+            SourceRuntimeArgs::for_test(
+                IndexUid::new_with_random_ulid("test-index"),
+                source_config,
+                metastore,
+                PathBuf::from("./queues"),
+            ),
+            params,
+            SourceCheckpoint::default(),
+        )
+        .await
+        .unwrap();
+        let file_source_actor = SourceActor {
+            source: Box::new(source),
+            doc_processor_mailbox,
+        };
+        let (_file_source_mailbox, file_source_handle) =
+            universe.spawn_builder().spawn(file_source_actor);
+
+        universe.sleep(Duration::from_secs(1)).await;
+        let indexer_messages: Vec<RawDocBatch> = doc_processor_inbox.drain_for_test_typed();
+        assert_eq!(indexer_messages.len(), 1);
+        assert_eq!(indexer_messages[0].docs.len(), 2);
+
+        // The last line is being written: it is not emitted until it is complete.
+        let mut log_file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(dir_path.join("app.log"))
+            .unwrap();
+        log_file.write_all(b"3\n4").unwrap();
+        universe.sleep(Duration::from_secs(1)).await;
+
+        let indexer_messages: Vec<RawDocBatch> = doc_processor_inbox.drain_for_test_typed();
+        assert_eq!(indexer_messages.len(), 1);
+        assert_eq!(indexer_messages[0].docs.len(), 1);
+        assert_eq!(&indexer_messages[0].docs[0][..], b"3\n");
+
+        // A rotated file is not read again.
+        log_file.write_all(b"\n").unwrap();
+        std::fs::rename(dir_path.join("app.log"), dir_path.join("app.1.log")).unwrap();
+        std::fs::write(dir_path.join("app.log"), "5\n").unwrap();
+        universe.sleep(Duration::from_secs(1)).await;
+
+        let indexer_messages: Vec<RawDocBatch> = doc_processor_inbox.drain_for_test_typed();
+        let docs: Vec<&[u8]> = indexer_messages
+            .iter()
+            .flat_map(|doc_batch| doc_batch.docs.iter().map(|doc| &doc[..]))
+            .collect();
+        assert_eq!(docs, [&b"4\n"[..], &b"5\n"[..]]);
+
+        let counters = file_source_handle.process_pending_and_observe().await.state;
+        assert_eq!(counters["num_lines_processed"], 5);
+
+        file_source_handle.quit().await;
+        universe.assert_quit().await;
+    }
 }
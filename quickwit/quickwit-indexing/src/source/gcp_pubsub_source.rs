@@ -325,6 +325,12 @@ mod gcp_pubsub_emulator_tests {
             }),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            csv_config: Default::default(),
+            dead_letter_queue: None,
+            supervision: Default::default(),
+            fetch_stream: Default::default(),
+            placement_constraints: Default::default(),
+            priority: Default::default(),
         }
     }
 
@@ -17,6 +17,8 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+mod truncator;
+
 use std::collections::BTreeSet;
 use std::fmt;
 use std::sync::Arc;
@@ -29,14 +31,9 @@ use itertools::Itertools;
 use quickwit_actors::{ActorExitStatus, Mailbox};
 use quickwit_common::pubsub::EventBroker;
 use quickwit_common::retry::RetryParams;
-use quickwit_ingest::{
-    decoded_mrecords, FetchStreamError, IngesterPool, MRecord, MultiFetchStream,
-};
+use quickwit_ingest::{decoded_mrecords, FetchStreamError, MRecord, MultiFetchStream};
 use quickwit_metastore::checkpoint::{PartitionId, SourceCheckpoint};
-use quickwit_proto::ingest::ingester::{
-    fetch_message, FetchEof, FetchPayload, IngesterService, TruncateShardsRequest,
-    TruncateShardsSubrequest,
-};
+use quickwit_proto::ingest::ingester::{fetch_message, FetchEof, FetchPayload};
 use quickwit_proto::ingest::IngestV2Error;
 use quickwit_proto::metastore::{
     AcquireShardsRequest, AcquireShardsSubrequest, AcquireShardsSubresponse, MetastoreService,
@@ -51,6 +48,7 @@ use tokio::time;
 use tracing::{debug, error, info, warn};
 use ulid::Ulid;
 
+use self::truncator::ShardTruncator;
 use super::{
     BatchBuilder, Source, SourceContext, SourceRuntimeArgs, TypedSourceFactory,
     BATCH_NUM_BYTES_LIMIT, EMIT_BATCHES_TIMEOUT,
@@ -146,12 +144,12 @@ struct AssignedShard {
 pub struct IngestSource {
     client_id: ClientId,
     metastore: MetastoreServiceClient,
-    ingester_pool: IngesterPool,
     assigned_shards: FnvHashMap<ShardId, AssignedShard>,
     fetch_stream: MultiFetchStream,
     publish_lock: PublishLock,
     publish_token: PublishToken,
     event_broker: EventBroker,
+    truncator: ShardTruncator,
 }
 
 impl fmt::Debug for IngestSource {
@@ -182,21 +180,23 @@ impl IngestSource {
             client_id.to_string(),
             ingester_pool.clone(),
             retry_params,
+            runtime_args.source_config.fetch_stream,
         );
         // We start as dead. The first reset with a non-empty list of shards will create an alive
         // publish lock.
         let publish_lock = PublishLock::dead();
         let publish_token = client_id.new_publish_token();
+        let truncator = ShardTruncator::new(client_id.source_uid.clone(), ingester_pool);
 
         Ok(IngestSource {
             client_id,
             metastore,
-            ingester_pool,
             assigned_shards,
             fetch_stream,
             publish_lock,
             publish_token,
             event_broker: runtime_args.event_broker.clone(),
+            truncator,
         })
     }
 
@@ -299,7 +299,7 @@ impl IngestSource {
         Ok(())
     }
 
-    async fn truncate(&mut self, truncate_up_to_positions: Vec<(ShardId, Position)>) {
+    fn truncate(&mut self, truncate_up_to_positions: Vec<(ShardId, Position)>) {
         let shard_positions_update = LocalShardPositionsUpdate::new(
             self.client_id.source_uid.clone(),
             truncate_up_to_positions.clone(),
@@ -317,74 +317,9 @@ impl IngestSource {
         // We publish the event to the event broker.
         self.event_broker.publish(shard_positions_update);
 
-        // Finally, we push the information to ingesters in a best effort manner.
-        // If the request fail, we just log an error.
-        let mut per_ingester_truncate_subrequests: FnvHashMap<
-            &NodeId,
-            Vec<TruncateShardsSubrequest>,
-        > = FnvHashMap::default();
-
-        for (shard_id, truncate_up_to_position_inclusive) in truncate_up_to_positions {
-            if matches!(truncate_up_to_position_inclusive, Position::Beginning) {
-                continue;
-            }
-            let Some(shard) = self.assigned_shards.get(&shard_id) else {
-                warn!("failed to truncate shard `{shard_id}`: shard is no longer assigned");
-                continue;
-            };
-            let truncate_shards_subrequest = TruncateShardsSubrequest {
-                index_uid: self.client_id.source_uid.index_uid.clone().into(),
-                source_id: self.client_id.source_uid.source_id.clone(),
-                shard_id,
-                truncate_up_to_position_inclusive: Some(truncate_up_to_position_inclusive),
-            };
-            if let Some(follower_id) = &shard.follower_id_opt {
-                per_ingester_truncate_subrequests
-                    .entry(follower_id)
-                    .or_default()
-                    .push(truncate_shards_subrequest.clone());
-            }
-            per_ingester_truncate_subrequests
-                .entry(&shard.leader_id)
-                .or_default()
-                .push(truncate_shards_subrequest);
-        }
-        for (ingester_id, truncate_subrequests) in per_ingester_truncate_subrequests {
-            let Some(mut ingester) = self.ingester_pool.get(ingester_id) else {
-                warn!("failed to truncate shard(s): ingester `{ingester_id}` is unavailable");
-                continue;
-            };
-            let truncate_shards_request = TruncateShardsRequest {
-                ingester_id: ingester_id.clone().into(),
-                subrequests: truncate_subrequests,
-            };
-            let truncate_future = async move {
-                let retry_params = RetryParams {
-                    base_delay: Duration::from_secs(1),
-                    max_delay: Duration::from_secs(10),
-                    max_attempts: 5,
-                };
-                for num_attempts in 1..=retry_params.max_attempts {
-                    let Err(error) = ingester
-                        .truncate_shards(truncate_shards_request.clone())
-                        .await
-                    else {
-                        return;
-                    };
-                    let delay = retry_params.compute_delay(num_attempts);
-                    time::sleep(delay).await;
-
-                    if num_attempts == retry_params.max_attempts {
-                        warn!(
-                            ingester_id=%truncate_shards_request.ingester_id,
-                            "failed to truncate shard(s): {error}"
-                        );
-                    }
-                }
-            };
-            // Truncation is best-effort, so fire and forget.
-            tokio::spawn(truncate_future);
-        }
+        // Finally, we push the information to ingesters in a best effort manner. The truncations
+        // are batched per ingester and sent periodically.
+        self.truncator.record(truncate_up_to_positions);
     }
 
     /// If the new assignment removes a shard that we were in the middle of indexing (ie they have
@@ -517,6 +452,11 @@ impl Source for IngestSource {
             let message = batch_builder.build();
             ctx.send_message(doc_processor_mailbox, message).await?;
         }
+        // Sends the truncations deferred by `suggest_truncate` once the batch interval has
+        // elapsed.
+        self.truncator.flush_if_due(&self.assigned_shards);
+        self.truncator.reconcile_if_due(&self.assigned_shards);
+
         Ok(Duration::default())
     }
 
@@ -618,7 +558,9 @@ impl Source for IngestSource {
             };
             self.assigned_shards.insert(shard_id, assigned_shard);
         }
-        self.truncate(truncate_up_to_positions).await;
+        self.truncate(truncate_up_to_positions);
+        // The newly assigned shards are truncated right away.
+        self.truncator.flush(&self.assigned_shards);
 
         Ok(())
     }
@@ -635,7 +577,8 @@ impl Source for IngestSource {
             let shard_id = partition_id.as_u64().expect("shard ID should be a u64");
             truncate_up_to_positions.push((shard_id, position));
         }
-        self.truncate(truncate_up_to_positions).await;
+        self.truncate(truncate_up_to_positions);
+        self.truncator.flush_if_due(&self.assigned_shards);
         Ok(())
     }
 
@@ -673,6 +616,7 @@ mod tests {
     use quickwit_actors::{ActorContext, Universe};
     use quickwit_common::ServiceStream;
     use quickwit_config::{SourceConfig, SourceParams};
+    use quickwit_ingest::IngesterPool;
     use quickwit_proto::indexing::IndexingPipelineId;
     use quickwit_proto::ingest::ingester::{
         FetchMessage, IngesterServiceClient, TruncateShardsResponse,
@@ -730,6 +674,7 @@ mod tests {
                             shard_state: ShardState::Open as i32,
                             publish_position_inclusive: Some(Position::offset(10u64)),
                             publish_token: Some(publish_token.to_string()),
+                            ..Default::default()
                         }],
                     }],
                 };
@@ -762,6 +707,7 @@ mod tests {
                             shard_state: ShardState::Open as i32,
                             publish_position_inclusive: Some(Position::offset(11u64)),
                             publish_token: Some(publish_token.to_string()),
+                            ..Default::default()
                         }],
                     }],
                 };
@@ -795,6 +741,7 @@ mod tests {
                                 shard_state: ShardState::Open as i32,
                                 publish_position_inclusive: Some(Position::offset(11u64)),
                                 publish_token: Some(publish_token.to_string()),
+                                ..Default::default()
                             },
                             Shard {
                                 leader_id: "test-ingester-0".to_string(),
@@ -805,6 +752,7 @@ mod tests {
                                 shard_state: ShardState::Open as i32,
                                 publish_position_inclusive: Some(Position::offset(12u64)),
                                 publish_token: Some(publish_token.to_string()),
+                                ..Default::default()
                             },
                         ],
                     }],
@@ -1101,6 +1049,7 @@ mod tests {
                                 shard_state: ShardState::Open as i32,
                                 publish_position_inclusive: Some(Position::eof(11u64)),
                                 publish_token: Some(publish_token.to_string()),
+                                ..Default::default()
                             },
                             Shard {
                                 leader_id: "test-ingester-0".to_string(),
@@ -1111,6 +1060,7 @@ mod tests {
                                 shard_state: ShardState::Open as i32,
                                 publish_position_inclusive: Some(Position::Beginning.as_eof()),
                                 publish_token: Some(publish_token.to_string()),
+                                ..Default::default()
                             },
                         ],
                     }],
@@ -1244,6 +1194,7 @@ mod tests {
                                 shard_state: ShardState::Open as i32,
                                 publish_position_inclusive: Some(Position::offset(11u64)),
                                 publish_token: Some(publish_token.to_string()),
+                                ..Default::default()
                             },
                             Shard {
                                 leader_id: "test-ingester-0".to_string(),
@@ -1254,6 +1205,7 @@ mod tests {
                                 shard_state: ShardState::Closed as i32,
                                 publish_position_inclusive: Some(Position::eof(22u64)),
                                 publish_token: Some(publish_token.to_string()),
+                                ..Default::default()
                             },
                         ],
                     }],
@@ -1441,6 +1393,7 @@ mod tests {
             ]),
             from_position_exclusive: Some(Position::offset(11u64)),
             to_position_inclusive: Some(Position::offset(14u64)),
+            is_compressed: false,
         };
         let fetch_message = FetchMessage::new_payload(fetch_payload);
         fetch_message_tx.send(Ok(fetch_message)).await.unwrap();
@@ -1452,6 +1405,7 @@ mod tests {
             mrecord_batch: MRecordBatch::for_test(["\0\0test-doc-qux"]),
             from_position_exclusive: Some(Position::offset(22u64)),
             to_position_inclusive: Some(Position::offset(23u64)),
+            is_compressed: false,
         };
         let fetch_message = FetchMessage::new_payload(fetch_payload);
         fetch_message_tx.send(Ok(fetch_message)).await.unwrap();
@@ -1525,6 +1479,7 @@ mod tests {
             mrecord_batch: MRecordBatch::for_test(["\0\0test-doc-baz"]),
             from_position_exclusive: Some(Position::offset(14u64)),
             to_position_inclusive: Some(Position::offset(15u64)),
+            is_compressed: false,
         };
         let fetch_message = FetchMessage::new_payload(fetch_payload);
         fetch_message_tx.send(Ok(fetch_message)).await.unwrap();
@@ -1574,6 +1529,7 @@ mod tests {
                             shard_state: ShardState::Open as i32,
                             publish_position_inclusive: Some(Position::Beginning),
                             publish_token: Some(publish_token.to_string()),
+                            ..Default::default()
                         }],
                     }],
                 };
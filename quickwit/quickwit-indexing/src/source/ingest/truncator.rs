@@ -0,0 +1,377 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use fnv::FnvHashMap;
+use quickwit_common::retry::RetryParams;
+use quickwit_ingest::IngesterPool;
+use quickwit_proto::ingest::ingester::{
+    IngesterService, IngesterServiceClient, TruncateShardsRequest, TruncateShardsSubrequest,
+};
+use quickwit_proto::types::{NodeId, Position, ShardId, SourceUid};
+use tokio::time::{self, Instant};
+use tracing::warn;
+
+use super::AssignedShard;
+
+/// Minimum interval between two batches of truncation requests. The truncations suggested in the
+/// meantime are merged into the next batch.
+const TRUNCATE_BATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Interval at which the last truncation positions of the assigned shards are sent again, in case
+/// some truncation requests failed despite the retries or were lost by a restarting ingester.
+const TRUNCATE_RECONCILIATION_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Batches the truncation requests of the shards assigned to an ingest source. With hundreds of
+/// shards per pipeline, truncating the shards on every publish floods the ingesters with
+/// requests. Instead, the truncation positions are accumulated and sent at most once per
+/// [`TRUNCATE_BATCH_INTERVAL`] with one request per ingester.
+pub(super) struct ShardTruncator {
+    source_uid: SourceUid,
+    ingester_pool: IngesterPool,
+    // The truncation positions not sent yet, sorted by shard ID.
+    pending_positions: BTreeMap<ShardId, Position>,
+    // The last truncation positions sent, sent again by the reconciliation sweep.
+    truncated_positions: BTreeMap<ShardId, Position>,
+    last_flush_opt: Option<Instant>,
+    last_reconciliation: Instant,
+}
+
+impl ShardTruncator {
+    pub fn new(source_uid: SourceUid, ingester_pool: IngesterPool) -> Self {
+        Self {
+            source_uid,
+            ingester_pool,
+            pending_positions: BTreeMap::new(),
+            truncated_positions: BTreeMap::new(),
+            last_flush_opt: None,
+            last_reconciliation: Instant::now(),
+        }
+    }
+
+    /// Records the positions up to which the shards can be truncated. They are sent with the next
+    /// batch.
+    pub fn record(&mut self, truncate_up_to_positions: Vec<(ShardId, Position)>) {
+        for (shard_id, truncate_up_to_position_inclusive) in truncate_up_to_positions {
+            if matches!(truncate_up_to_position_inclusive, Position::Beginning) {
+                continue;
+            }
+            self.pending_positions
+                .insert(shard_id, truncate_up_to_position_inclusive);
+        }
+    }
+
+    /// Sends the pending truncations if the previous batch was sent more than
+    /// [`TRUNCATE_BATCH_INTERVAL`] ago.
+    pub fn flush_if_due(&mut self, assigned_shards: &FnvHashMap<ShardId, AssignedShard>) {
+        let is_due = self.last_flush_opt.map_or(true, |last_flush| {
+            last_flush.elapsed() >= TRUNCATE_BATCH_INTERVAL
+        });
+        if is_due {
+            self.flush(assigned_shards);
+        }
+    }
+
+    /// Sends the pending truncations, one request per ingester.
+    pub fn flush(&mut self, assigned_shards: &FnvHashMap<ShardId, AssignedShard>) {
+        if self.pending_positions.is_empty() {
+            return;
+        }
+        self.last_flush_opt = Some(Instant::now());
+
+        let pending_positions = std::mem::take(&mut self.pending_positions);
+        let mut truncate_up_to_positions = Vec::with_capacity(pending_positions.len());
+
+        for (shard_id, truncate_up_to_position_inclusive) in pending_positions {
+            if !assigned_shards.contains_key(&shard_id) {
+                warn!("failed to truncate shard `{shard_id}`: shard is no longer assigned");
+                continue;
+            }
+            self.truncated_positions
+                .insert(shard_id, truncate_up_to_position_inclusive.clone());
+            truncate_up_to_positions.push((shard_id, truncate_up_to_position_inclusive));
+        }
+        self.send(truncate_up_to_positions, assigned_shards);
+    }
+
+    /// Sends again the last truncation positions of the assigned shards if the previous
+    /// reconciliation occurred more than [`TRUNCATE_RECONCILIATION_INTERVAL`] ago.
+    pub fn reconcile_if_due(&mut self, assigned_shards: &FnvHashMap<ShardId, AssignedShard>) {
+        if self.last_reconciliation.elapsed() < TRUNCATE_RECONCILIATION_INTERVAL {
+            return;
+        }
+        self.last_reconciliation = Instant::now();
+
+        // Forget the shards that are no longer assigned.
+        self.truncated_positions
+            .retain(|shard_id, _| assigned_shards.contains_key(shard_id));
+
+        let truncate_up_to_positions: Vec<(ShardId, Position)> = self
+            .truncated_positions
+            .iter()
+            .filter(|(shard_id, _)| !self.pending_positions.contains_key(shard_id))
+            .map(|(shard_id, position)| (*shard_id, position.clone()))
+            .collect();
+        self.send(truncate_up_to_positions, assigned_shards);
+    }
+
+    fn send(
+        &self,
+        truncate_up_to_positions: Vec<(ShardId, Position)>,
+        assigned_shards: &FnvHashMap<ShardId, AssignedShard>,
+    ) {
+        let mut per_ingester_truncate_subrequests: FnvHashMap<
+            &NodeId,
+            Vec<TruncateShardsSubrequest>,
+        > = FnvHashMap::default();
+
+        for (shard_id, truncate_up_to_position_inclusive) in truncate_up_to_positions {
+            let Some(shard) = assigned_shards.get(&shard_id) else {
+                continue;
+            };
+            let truncate_shards_subrequest = TruncateShardsSubrequest {
+                index_uid: self.source_uid.index_uid.clone().into(),
+                source_id: self.source_uid.source_id.clone(),
+                shard_id,
+                truncate_up_to_position_inclusive: Some(truncate_up_to_position_inclusive),
+            };
+            if let Some(follower_id) = &shard.follower_id_opt {
+                per_ingester_truncate_subrequests
+                    .entry(follower_id)
+                    .or_default()
+                    .push(truncate_shards_subrequest.clone());
+            }
+            per_ingester_truncate_subrequests
+                .entry(&shard.leader_id)
+                .or_default()
+                .push(truncate_shards_subrequest);
+        }
+        for (ingester_id, truncate_subrequests) in per_ingester_truncate_subrequests {
+            let Some(ingester) = self.ingester_pool.get(ingester_id) else {
+                warn!("failed to truncate shard(s): ingester `{ingester_id}` is unavailable");
+                continue;
+            };
+            let truncate_shards_request = TruncateShardsRequest {
+                ingester_id: ingester_id.clone().into(),
+                subrequests: truncate_subrequests,
+            };
+            // Truncation is best-effort, so fire and forget.
+            tokio::spawn(truncate_shards_with_retry(
+                ingester,
+                truncate_shards_request,
+            ));
+        }
+    }
+}
+
+async fn truncate_shards_with_retry(
+    mut ingester: IngesterServiceClient,
+    truncate_shards_request: TruncateShardsRequest,
+) {
+    let retry_params = RetryParams {
+        base_delay: Duration::from_secs(1),
+        max_delay: Duration::from_secs(10),
+        max_attempts: 5,
+    };
+    for num_attempts in 1..=retry_params.max_attempts {
+        let Err(error) = ingester
+            .truncate_shards(truncate_shards_request.clone())
+            .await
+        else {
+            return;
+        };
+        let delay = retry_params.compute_delay(num_attempts);
+        time::sleep(delay).await;
+
+        if num_attempts == retry_params.max_attempts {
+            warn!(
+                ingester_id=%truncate_shards_request.ingester_id,
+                "failed to truncate shard(s): {error}"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_proto::ingest::ingester::TruncateShardsResponse;
+    use tokio::sync::mpsc;
+
+    use super::*;
+    use crate::source::ingest::IndexingStatus;
+
+    fn assigned_shard_for_test(
+        shard_id: ShardId,
+        leader_id: &str,
+        follower_id_opt: Option<&str>,
+    ) -> AssignedShard {
+        AssignedShard {
+            leader_id: leader_id.into(),
+            follower_id_opt: follower_id_opt.map(Into::into),
+            partition_id: shard_id.into(),
+            current_position_inclusive: Position::Beginning,
+            status: IndexingStatus::Active,
+        }
+    }
+
+    fn ingester_for_test(
+        truncate_request_tx: mpsc::UnboundedSender<TruncateShardsRequest>,
+    ) -> IngesterServiceClient {
+        let mut ingester_mock = IngesterServiceClient::mock();
+        ingester_mock
+            .expect_truncate_shards()
+            .returning(move |request| {
+                truncate_request_tx.send(request).unwrap();
+                Ok(TruncateShardsResponse {})
+            });
+        ingester_mock.into()
+    }
+
+    #[tokio::test]
+    async fn test_shard_truncator_batches_truncations() {
+        let source_uid = SourceUid {
+            index_uid: "test-index:0".into(),
+            source_id: "test-source".to_string(),
+        };
+        let ingester_pool = IngesterPool::default();
+
+        let (truncate_request_tx_0, mut truncate_request_rx_0) = mpsc::unbounded_channel();
+        ingester_pool.insert(
+            "test-ingester-0".into(),
+            ingester_for_test(truncate_request_tx_0),
+        );
+        let (truncate_request_tx_1, mut truncate_request_rx_1) = mpsc::unbounded_channel();
+        ingester_pool.insert(
+            "test-ingester-1".into(),
+            ingester_for_test(truncate_request_tx_1),
+        );
+        let mut assigned_shards = FnvHashMap::default();
+        assigned_shards.insert(
+            1,
+            assigned_shard_for_test(1, "test-ingester-0", Some("test-ingester-1")),
+        );
+        assigned_shards.insert(2, assigned_shard_for_test(2, "test-ingester-0", None));
+
+        let mut truncator = ShardTruncator::new(source_uid, ingester_pool);
+
+        // The first batch is sent right away.
+        truncator.record(vec![
+            (2, Position::offset(20u64)),
+            (1, Position::offset(10u64)),
+        ]);
+        truncator.flush_if_due(&assigned_shards);
+
+        let truncate_request = truncate_request_rx_0.recv().await.unwrap();
+        assert_eq!(truncate_request.ingester_id, "test-ingester-0");
+        assert_eq!(truncate_request.subrequests.len(), 2);
+        assert_eq!(truncate_request.subrequests[0].shard_id, 1);
+        assert_eq!(truncate_request.subrequests[1].shard_id, 2);
+
+        let truncate_request = truncate_request_rx_1.recv().await.unwrap();
+        assert_eq!(truncate_request.ingester_id, "test-ingester-1");
+        assert_eq!(truncate_request.subrequests.len(), 1);
+        assert_eq!(truncate_request.subrequests[0].shard_id, 1);
+
+        // The following truncations are deferred until the batch interval has elapsed and only
+        // the latest position of each shard is sent.
+        truncator.record(vec![(1, Position::offset(11u64))]);
+        truncator.flush_if_due(&assigned_shards);
+
+        truncator.record(vec![
+            (1, Position::offset(12u64)),
+            (3, Position::offset(30u64)),
+        ]);
+        truncator.flush_if_due(&assigned_shards);
+
+        tokio::task::yield_now().await;
+        assert!(truncate_request_rx_0.try_recv().is_err());
+        assert!(truncate_request_rx_1.try_recv().is_err());
+
+        truncator.last_flush_opt = Instant::now().checked_sub(TRUNCATE_BATCH_INTERVAL);
+        truncator.flush_if_due(&assigned_shards);
+
+        let truncate_request = truncate_request_rx_0.recv().await.unwrap();
+        assert_eq!(truncate_request.subrequests.len(), 1);
+        assert_eq!(truncate_request.subrequests[0].shard_id, 1);
+        assert_eq!(
+            truncate_request.subrequests[0].truncate_up_to_position_inclusive(),
+            Position::offset(12u64)
+        );
+        let truncate_request = truncate_request_rx_1.recv().await.unwrap();
+        assert_eq!(truncate_request.subrequests.len(), 1);
+        assert_eq!(
+            truncate_request.subrequests[0].truncate_up_to_position_inclusive(),
+            Position::offset(12u64)
+        );
+        assert!(truncator.pending_positions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_shard_truncator_reconciliation() {
+        let source_uid = SourceUid {
+            index_uid: "test-index:0".into(),
+            source_id: "test-source".to_string(),
+        };
+        let ingester_pool = IngesterPool::default();
+
+        let (truncate_request_tx, mut truncate_request_rx) = mpsc::unbounded_channel();
+        ingester_pool.insert(
+            "test-ingester-0".into(),
+            ingester_for_test(truncate_request_tx),
+        );
+        let mut assigned_shards = FnvHashMap::default();
+        assigned_shards.insert(1, assigned_shard_for_test(1, "test-ingester-0", None));
+        assigned_shards.insert(2, assigned_shard_for_test(2, "test-ingester-0", None));
+
+        let mut truncator = ShardTruncator::new(source_uid, ingester_pool);
+        truncator.record(vec![
+            (1, Position::offset(10u64)),
+            (2, Position::offset(20u64)),
+        ]);
+        truncator.flush(&assigned_shards);
+
+        let truncate_request = truncate_request_rx.recv().await.unwrap();
+        assert_eq!(truncate_request.subrequests.len(), 2);
+
+        // The reconciliation is not due yet.
+        truncator.reconcile_if_due(&assigned_shards);
+
+        tokio::task::yield_now().await;
+        assert!(truncate_request_rx.try_recv().is_err());
+
+        // The shard 2 is no longer assigned, so only the shard 1 is truncated again.
+        assigned_shards.remove(&2);
+
+        truncator.last_reconciliation = Instant::now()
+            .checked_sub(TRUNCATE_RECONCILIATION_INTERVAL)
+            .unwrap();
+        truncator.reconcile_if_due(&assigned_shards);
+
+        let truncate_request = truncate_request_rx.recv().await.unwrap();
+        assert_eq!(truncate_request.subrequests.len(), 1);
+        assert_eq!(truncate_request.subrequests[0].shard_id, 1);
+        assert_eq!(
+            truncate_request.subrequests[0].truncate_up_to_position_inclusive(),
+            Position::offset(10u64)
+        );
+        assert_eq!(truncator.truncated_positions.len(), 1);
+    }
+}
@@ -274,6 +274,8 @@ mod tests {
         IngestRequest {
             doc_batches,
             commit: commit_type.into(),
+            idempotency_key: None,
+            return_positions: false,
         }
     }
 
@@ -286,6 +288,12 @@ mod tests {
             source_params: SourceParams::IngestApi,
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            csv_config: Default::default(),
+            dead_letter_queue: None,
+            supervision: Default::default(),
+            fetch_stream: Default::default(),
+            placement_constraints: Default::default(),
+            priority: Default::default(),
         }
     }
 
@@ -17,7 +17,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -28,7 +28,8 @@ use bytes::Bytes;
 use itertools::Itertools;
 use oneshot;
 use quickwit_actors::{ActorExitStatus, Mailbox};
-use quickwit_config::KafkaSourceParams;
+use quickwit_config::{KafkaHeaderFieldMapping, KafkaHeaderFilter, KafkaSourceParams};
+use quickwit_doc_mapper::JsonObject;
 use quickwit_metastore::checkpoint::{PartitionId, SourceCheckpoint};
 use quickwit_metastore::IndexMetadataResponseExt;
 use quickwit_proto::metastore::{IndexMetadataRequest, MetastoreService};
@@ -38,7 +39,7 @@ use rdkafka::consumer::{
     BaseConsumer, CommitMode, Consumer, ConsumerContext, DefaultConsumerContext, Rebalance,
 };
 use rdkafka::error::KafkaError;
-use rdkafka::message::BorrowedMessage;
+use rdkafka::message::{BorrowedMessage, Headers};
 use rdkafka::util::Timeout;
 use rdkafka::{ClientContext, Message, Offset, TopicPartitionList};
 use serde_json::{json, Value as JsonValue};
@@ -48,7 +49,7 @@ use tokio::time;
 use tracing::{debug, info, warn};
 
 use crate::actors::DocProcessor;
-use crate::models::{NewPublishLock, PublishLock};
+use crate::models::{DocMetadata, NewPublishLock, PublishLock};
 use crate::source::{
     BatchBuilder, Source, SourceContext, SourceRuntimeArgs, TypedSourceFactory,
     BATCH_NUM_BYTES_LIMIT, EMIT_BATCHES_TIMEOUT,
@@ -90,22 +91,167 @@ enum KafkaEvent {
 #[derive(Debug)]
 struct KafkaMessage {
     doc_opt: Option<Bytes>,
+    // Whether the message was discarded by the header filters.
+    is_filtered_out: bool,
     payload_len: u64,
     partition: i32,
     offset: i64,
+    /// Offset of the next message that will be produced to the partition, as last fetched by the
+    /// consumer.
+    high_watermark_opt: Option<i64>,
+    doc_metadata_opt: Option<DocMetadata>,
 }
 
-impl From<BorrowedMessage<'_>> for KafkaMessage {
-    fn from(message: BorrowedMessage<'_>) -> Self {
+impl KafkaMessage {
+    fn new(
+        message: BorrowedMessage<'_>,
+        high_watermark_opt: Option<i64>,
+        header_rules: &KafkaHeaderRules,
+        collect_doc_metadata: bool,
+    ) -> Self {
+        let mut is_filtered_out = false;
+        let mut doc_opt = None;
+
+        if header_rules.is_empty() {
+            doc_opt = message_payload_to_doc(&message);
+        } else {
+            let headers = message_headers(&message);
+
+            if header_rules.accepts(&headers) {
+                doc_opt = message_payload_to_doc(&message)
+                    .and_then(|doc| header_rules.apply_field_mappings(doc, &headers));
+            } else {
+                is_filtered_out = true;
+            }
+        }
+        let doc_metadata_opt = if collect_doc_metadata && doc_opt.is_some() {
+            Some(message_metadata(&message))
+        } else {
+            None
+        };
         Self {
-            doc_opt: message_payload_to_doc(&message),
+            doc_opt,
+            is_filtered_out,
             payload_len: message.payload_len() as u64,
             partition: message.partition(),
             offset: message.offset(),
+            high_watermark_opt,
+            doc_metadata_opt,
         }
     }
 }
 
+type KafkaHeader<'a> = (&'a str, Option<&'a [u8]>);
+
+fn message_headers<'a>(message: &'a BorrowedMessage) -> Vec<KafkaHeader<'a>> {
+    let Some(headers) = message.headers() else {
+        return Vec::new();
+    };
+    headers
+        .iter()
+        .map(|header| (header.key, header.value))
+        .collect()
+}
+
+/// Returns the topic, partition, offset, timestamp, key, and headers of a message. The key and
+/// header values are decoded as UTF-8, replacing the invalid sequences.
+fn message_metadata(message: &BorrowedMessage) -> DocMetadata {
+    let mut doc_metadata = JsonObject::with_capacity(6);
+    doc_metadata.insert("topic".to_string(), JsonValue::from(message.topic()));
+    doc_metadata.insert(
+        "partition".to_string(),
+        JsonValue::from(message.partition()),
+    );
+    doc_metadata.insert("offset".to_string(), JsonValue::from(message.offset()));
+
+    if let Some(timestamp_millis) = message.timestamp().to_millis() {
+        doc_metadata.insert(
+            "timestamp_millis".to_string(),
+            JsonValue::from(timestamp_millis),
+        );
+    }
+    if let Some(key) = message.key() {
+        doc_metadata.insert(
+            "key".to_string(),
+            JsonValue::String(String::from_utf8_lossy(key).into_owned()),
+        );
+    }
+    let headers: JsonObject = message_headers(message)
+        .into_iter()
+        .map(|(header_key, header_value_opt)| {
+            let header_value = header_value_opt
+                .map(|value| JsonValue::String(String::from_utf8_lossy(value).into_owned()))
+                .unwrap_or(JsonValue::Null);
+            (header_key.to_string(), header_value)
+        })
+        .collect();
+    doc_metadata.insert("headers".to_string(), JsonValue::Object(headers));
+    Arc::new(doc_metadata)
+}
+
+/// Header filters and header-to-field mappings of the source, evaluated in the consumer poll
+/// loop before the messages are batched.
+#[derive(Debug, Default)]
+struct KafkaHeaderRules {
+    header_filters: Vec<KafkaHeaderFilter>,
+    header_field_mappings: Vec<KafkaHeaderFieldMapping>,
+}
+
+impl KafkaHeaderRules {
+    fn is_empty(&self) -> bool {
+        self.header_filters.is_empty() && self.header_field_mappings.is_empty()
+    }
+
+    /// Returns whether the message headers pass all the filters.
+    fn accepts(&self, headers: &[KafkaHeader]) -> bool {
+        self.header_filters.iter().all(|header_filter| {
+            headers.iter().any(|(key, value_opt)| {
+                let Some(value) = value_opt else {
+                    return false;
+                };
+                *key == header_filter.header
+                    && header_filter
+                        .values
+                        .iter()
+                        .any(|accepted_value| accepted_value.as_bytes() == *value)
+            })
+        })
+    }
+
+    /// Inserts the values of the mapped headers into the document, which must be a JSON object.
+    /// Returns `None` if the document cannot be parsed.
+    fn apply_field_mappings(&self, doc: Bytes, headers: &[KafkaHeader]) -> Option<Bytes> {
+        if self.header_field_mappings.is_empty() {
+            return Some(doc);
+        }
+        let mut doc_json: serde_json::Map<String, JsonValue> = match serde_json::from_slice(&doc) {
+            Ok(doc_json) => doc_json,
+            Err(error) => {
+                debug!(error=?error, "Failed to parse document as a JSON object.");
+                return None;
+            }
+        };
+        for header_field_mapping in &self.header_field_mappings {
+            // When a header is repeated, the last value wins.
+            let header_value_opt = headers
+                .iter()
+                .rev()
+                .find(|(key, _)| *key == header_field_mapping.header)
+                .and_then(|(_, value_opt)| *value_opt);
+
+            if let Some(header_value) = header_value_opt {
+                let header_value = String::from_utf8_lossy(header_value).into_owned();
+                doc_json.insert(
+                    header_field_mapping.field.clone(),
+                    JsonValue::String(header_value),
+                );
+            }
+        }
+        let doc = serde_json::to_vec(&doc_json).expect("JSON map should be serializable");
+        Some(Bytes::from(doc))
+    }
+}
+
 struct RdKafkaContext {
     topic: String,
     events_tx: mpsc::Sender<KafkaEvent>,
@@ -198,6 +344,8 @@ pub struct KafkaSourceState {
     pub assigned_partitions: HashMap<i32, PartitionId>,
     /// Offset for each partition of the last message received.
     pub current_positions: HashMap<i32, Position>,
+    /// Number of messages of each partition that the source has not consumed yet.
+    pub partition_backlogs: HashMap<i32, u64>,
     /// Number of inactive partitions, i.e., that have reached EOF.
     pub num_inactive_partitions: usize,
     /// Number of bytes processed by the source.
@@ -206,6 +354,8 @@ pub struct KafkaSourceState {
     pub num_messages_processed: u64,
     // Number of invalid messages, i.e., that were empty or could not be parsed.
     pub num_invalid_messages: u64,
+    /// Number of messages discarded by the header filters.
+    pub num_filtered_messages: u64,
     /// Number of rebalances the consumer went through.
     pub num_rebalances: usize,
 }
@@ -243,6 +393,10 @@ impl KafkaSource {
     ) -> anyhow::Result<Self> {
         let topic = params.topic.clone();
         let backfill_mode_enabled = params.enable_backfill_mode;
+        let header_rules = KafkaHeaderRules {
+            header_filters: params.header_filters.clone(),
+            header_field_mappings: params.header_field_mappings.clone(),
+        };
 
         let (events_tx, events_rx) = mpsc::channel(100);
         let (truncate_tx, truncate_rx) = watch::channel(SourceCheckpoint::default());
@@ -256,8 +410,14 @@ impl KafkaSource {
             .get("max.poll.interval.ms")?
             .parse::<u64>()?;
 
-        let poll_loop_jh =
-            spawn_consumer_poll_loop(consumer, topic.clone(), events_tx, truncate_rx);
+        let poll_loop_jh = spawn_consumer_poll_loop(
+            consumer,
+            topic.clone(),
+            header_rules,
+            ctx.collect_doc_metadata(),
+            events_tx,
+            truncate_rx,
+        );
         let publish_lock = PublishLock::default();
 
         info!(
@@ -296,20 +456,33 @@ impl KafkaSource {
     ) -> anyhow::Result<()> {
         let KafkaMessage {
             doc_opt,
+            is_filtered_out,
             payload_len,
             partition,
             offset,
-            ..
+            high_watermark_opt,
+            doc_metadata_opt,
         } = message;
 
-        if let Some(doc) = doc_opt {
-            batch.add_doc(doc);
+        if is_filtered_out {
+            self.state.num_filtered_messages += 1;
+        } else if let Some(doc) = doc_opt {
+            if let Some(doc_metadata) = doc_metadata_opt {
+                batch.add_doc_with_metadata(doc, doc_metadata);
+            } else {
+                batch.add_doc(doc);
+            }
         } else {
             self.state.num_invalid_messages += 1;
         }
         self.state.num_bytes_processed += payload_len;
         self.state.num_messages_processed += 1;
 
+        if let Some(high_watermark) = high_watermark_opt {
+            let backlog = (high_watermark - offset - 1).max(0) as u64;
+            self.state.partition_backlogs.insert(partition, backlog);
+        }
+
         let partition_id = self
             .state
             .assigned_partitions
@@ -361,6 +534,7 @@ impl KafkaSource {
 
         self.state.assigned_partitions.clear();
         self.state.current_positions.clear();
+        self.state.partition_backlogs.clear();
         self.state.num_inactive_partitions = 0;
 
         let mut next_offsets: Vec<(i32, Offset)> = Vec::with_capacity(partitions.len());
@@ -432,6 +606,7 @@ impl KafkaSource {
 
     fn process_partition_eof(&mut self, partition: i32) {
         self.state.num_inactive_partitions += 1;
+        self.state.partition_backlogs.insert(partition, 0);
 
         info!(
             topic=%self.topic,
@@ -549,16 +724,24 @@ impl Source for KafkaSource {
             .map(|(partition, position)| (partition, position))
             .sorted()
             .collect();
+        let partition_backlogs: BTreeMap<String, u64> = self
+            .state
+            .partition_backlogs
+            .iter()
+            .map(|(partition, backlog)| (partition.to_string(), *backlog))
+            .collect();
         json!({
             "index_id": self.ctx.index_id(),
             "source_id": self.ctx.source_id(),
             "topic": self.topic,
             "assigned_partitions": assigned_partitions,
             "current_positions": current_positions,
+            "partition_backlogs": partition_backlogs,
             "num_inactive_partitions": self.state.num_inactive_partitions,
             "num_bytes_processed": self.state.num_bytes_processed,
             "num_messages_processed": self.state.num_messages_processed,
             "num_invalid_messages": self.state.num_invalid_messages,
+            "num_filtered_messages": self.state.num_filtered_messages,
             "num_rebalances": self.state.num_rebalances,
         })
     }
@@ -572,6 +755,8 @@ impl Source for KafkaSource {
 fn spawn_consumer_poll_loop(
     consumer: RdKafkaConsumer,
     topic: String,
+    header_rules: KafkaHeaderRules,
+    collect_doc_metadata: bool,
     events_tx: mpsc::Sender<KafkaEvent>,
     mut truncate_rx: watch::Receiver<SourceCheckpoint>,
 ) -> JoinHandle<()> {
@@ -590,7 +775,19 @@ fn spawn_consumer_poll_loop(
         while !events_tx.is_closed() {
             if let Some(message_res) = consumer.poll(Some(Duration::from_secs(1))) {
                 let event = match message_res {
-                    Ok(message) => KafkaEvent::Message(message.into()),
+                    Ok(message) => {
+                        // The watermarks are cached by the consumer: this call does not block.
+                        let high_watermark_opt = consumer
+                            .get_watermark_offsets(&topic, message.partition())
+                            .ok()
+                            .map(|(_low_watermark, high_watermark)| high_watermark);
+                        KafkaEvent::Message(KafkaMessage::new(
+                            message,
+                            high_watermark_opt,
+                            &header_rules,
+                            collect_doc_metadata,
+                        ))
+                    }
                     Err(KafkaError::PartitionEOF(partition)) => KafkaEvent::PartitionEOF(partition),
                     Err(error) => KafkaEvent::Error(anyhow!(error)),
                 };
@@ -775,6 +972,64 @@ fn message_payload_to_doc(message: &BorrowedMessage) -> Option<Bytes> {
     None
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header_rules_for_test() -> KafkaHeaderRules {
+        KafkaHeaderRules {
+            header_filters: vec![KafkaHeaderFilter {
+                header: "type".to_string(),
+                values: vec!["audit".to_string(), "security".to_string()],
+            }],
+            header_field_mappings: vec![KafkaHeaderFieldMapping {
+                header: "tenant".to_string(),
+                field: "tenant_id".to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_kafka_header_rules_accepts() {
+        let header_rules = header_rules_for_test();
+        assert!(header_rules.accepts(&[("type", Some(&b"audit"[..]))]));
+        assert!(header_rules.accepts(&[("tenant", None), ("type", Some(&b"security"[..]))]));
+        assert!(!header_rules.accepts(&[("type", Some(&b"debug"[..]))]));
+        assert!(!header_rules.accepts(&[("type", None)]));
+        assert!(!header_rules.accepts(&[]));
+
+        let header_rules = KafkaHeaderRules::default();
+        assert!(header_rules.is_empty());
+        assert!(header_rules.accepts(&[]));
+    }
+
+    #[test]
+    fn test_kafka_header_rules_apply_field_mappings() {
+        let header_rules = header_rules_for_test();
+        let doc = Bytes::from_static(br#"{"message": "login"}"#);
+        let headers: [KafkaHeader; 3] = [
+            ("type", Some(&b"audit"[..])),
+            ("tenant", Some(&b"acme"[..])),
+            ("tenant", Some(&b"globex"[..])),
+        ];
+        let doc = header_rules.apply_field_mappings(doc, &headers).unwrap();
+        let doc_json: JsonValue = serde_json::from_slice(&doc).unwrap();
+        assert_eq!(doc_json, json!({"message": "login", "tenant_id": "globex"}));
+
+        let doc = Bytes::from_static(br#"{"message": "login"}"#);
+        let doc = header_rules
+            .apply_field_mappings(doc, &[("type", Some(&b"audit"[..]))])
+            .unwrap();
+        let doc_json: JsonValue = serde_json::from_slice(&doc).unwrap();
+        assert_eq!(doc_json, json!({"message": "login"}));
+
+        let doc = Bytes::from_static(b"not-json");
+        assert!(header_rules
+            .apply_field_mappings(doc, &[("tenant", Some(&b"acme"[..]))])
+            .is_none());
+    }
+}
+
 #[cfg(all(test, feature = "kafka-broker-tests"))]
 mod kafka_broker_tests {
     use std::num::NonZeroUsize;
@@ -912,9 +1167,17 @@ mod kafka_broker_tests {
                     "bootstrap.servers": "localhost:9092",
                 }),
                 enable_backfill_mode: true,
+                header_filters: Vec::new(),
+                header_field_mappings: Vec::new(),
             }),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            csv_config: Default::default(),
+            dead_letter_queue: None,
+            supervision: Default::default(),
+            fetch_stream: Default::default(),
+            placement_constraints: Default::default(),
+            priority: Default::default(),
         };
         (source_id, source_config)
     }
@@ -1031,9 +1294,12 @@ mod kafka_broker_tests {
 
         let message = KafkaMessage {
             doc_opt: None,
+            is_filtered_out: false,
             payload_len: 7,
             partition: 1,
             offset: 0,
+            high_watermark_opt: None,
+            doc_metadata_opt: None,
         };
         kafka_source
             .process_message(message, &mut batch)
@@ -1052,9 +1318,12 @@ mod kafka_broker_tests {
 
         let message = KafkaMessage {
             doc_opt: Some(Bytes::from_static(b"test-doc")),
+            is_filtered_out: false,
             payload_len: 8,
             partition: 1,
             offset: 1,
+            high_watermark_opt: None,
+            doc_metadata_opt: None,
         };
         kafka_source
             .process_message(message, &mut batch)
@@ -1074,9 +1343,15 @@ mod kafka_broker_tests {
 
         let message = KafkaMessage {
             doc_opt: Some(Bytes::from_static(b"test-doc")),
+            is_filtered_out: false,
             payload_len: 8,
             partition: 2,
             offset: 42,
+            high_watermark_opt: Some(50),
+            doc_metadata_opt: Some(Arc::new(JsonObject::from_iter([(
+                "offset".to_string(),
+                json!(42),
+            )]))),
         };
         kafka_source
             .process_message(message, &mut batch)
@@ -1086,14 +1361,43 @@ mod kafka_broker_tests {
         assert_eq!(batch.docs.len(), 2);
         assert_eq!(batch.docs[1], "test-doc");
         assert_eq!(batch.num_bytes, 16);
+        assert_eq!(batch.doc_metadatas.len(), 2);
+        assert!(batch.doc_metadatas[0].is_none());
+        assert_eq!(batch.doc_metadatas[1].as_ref().unwrap()["offset"], 42);
         assert_eq!(
             kafka_source.state.current_positions.get(&2).unwrap(),
             &Position::offset(42u64)
         );
+        assert_eq!(kafka_source.state.partition_backlogs.get(&2).unwrap(), &7);
         assert_eq!(kafka_source.state.num_bytes_processed, 23);
         assert_eq!(kafka_source.state.num_messages_processed, 3);
         assert_eq!(kafka_source.state.num_invalid_messages, 1);
 
+        // Message discarded by the header filters
+        let message = KafkaMessage {
+            doc_opt: None,
+            is_filtered_out: true,
+            payload_len: 8,
+            partition: 2,
+            offset: 43,
+            high_watermark_opt: None,
+            doc_metadata_opt: None,
+        };
+        kafka_source
+            .process_message(message, &mut batch)
+            .await
+            .unwrap();
+
+        assert_eq!(batch.docs.len(), 2);
+        assert_eq!(
+            kafka_source.state.current_positions.get(&2).unwrap(),
+            &Position::offset(43u64)
+        );
+        assert_eq!(kafka_source.state.num_bytes_processed, 31);
+        assert_eq!(kafka_source.state.num_messages_processed, 4);
+        assert_eq!(kafka_source.state.num_invalid_messages, 1);
+        assert_eq!(kafka_source.state.num_filtered_messages, 1);
+
         let mut expected_checkpoint_delta = SourceCheckpointDelta::default();
         expected_checkpoint_delta
             .record_partition_delta(partition_id_1, Position::Beginning, Position::offset(1u64))
@@ -1102,7 +1406,7 @@ mod kafka_broker_tests {
             .record_partition_delta(
                 partition_id_2,
                 Position::offset(41u64),
-                Position::offset(42u64),
+                Position::offset(43u64),
             )
             .unwrap();
         assert_eq!(batch.checkpoint_delta, expected_checkpoint_delta);
@@ -1110,9 +1414,12 @@ mod kafka_broker_tests {
         // Message from unassigned partition
         let message = KafkaMessage {
             doc_opt: Some(Bytes::from_static(b"test-doc")),
+            is_filtered_out: false,
             payload_len: 8,
             partition: 3,
             offset: 42,
+            high_watermark_opt: None,
+            doc_metadata_opt: None,
         };
         kafka_source
             .process_message(message, &mut batch)
@@ -1402,6 +1709,7 @@ mod kafka_broker_tests {
                 "topic":  topic,
                 "assigned_partitions": vec![0, 1, 2],
                 "current_positions": json!([]),
+                "partition_backlogs": json!({"0": 0, "1": 0, "2": 0}),
                 "num_inactive_partitions": 3,
                 "num_bytes_processed": 0,
                 "num_messages_processed": 0,
@@ -1482,6 +1790,7 @@ mod kafka_broker_tests {
                 "topic":  topic,
                 "assigned_partitions": vec![0, 1, 2],
                 "current_positions":  vec![(0, "00000000000000000002"), (1, "00000000000000000002"), (2, "00000000000000000002")],
+                "partition_backlogs": json!({"0": 0, "1": 0, "2": 0}),
                 "num_inactive_partitions": 3,
                 "num_bytes_processed": 72,
                 "num_messages_processed": 9,
@@ -1549,6 +1858,7 @@ mod kafka_broker_tests {
                 "topic":  topic,
                 "assigned_partitions": vec![0, 1, 2],
                 "current_positions":  vec![(0, "00000000000000000002"), (1, "00000000000000000002"), (2, "00000000000000000002")],
+                "partition_backlogs": json!({"0": 0, "1": 0, "2": 0}),
                 "num_inactive_partitions": 3,
                 "num_bytes_processed": 36,
                 "num_messages_processed": 5,
@@ -1592,6 +1902,7 @@ mod kafka_broker_tests {
                 "topic":  topic,
                 "assigned_partitions": vec![0, 1, 2],
                 "current_positions": json!([]),
+                "partition_backlogs": json!({"0": 0, "1": 0, "2": 0}),
                 "num_inactive_partitions": 3,
                 "num_bytes_processed": 0,
                 "num_messages_processed": 0,
@@ -1617,6 +1928,8 @@ mod kafka_broker_tests {
             client_log_level: None,
             client_params: json!({ "bootstrap.servers": bootstrap_servers }),
             enable_backfill_mode: true,
+            header_filters: Vec::new(),
+            header_field_mappings: Vec::new(),
         })
         .await
         .unwrap();
@@ -1628,6 +1941,8 @@ mod kafka_broker_tests {
             client_log_level: None,
             client_params: json!({ "bootstrap.servers": bootstrap_servers }),
             enable_backfill_mode: true,
+            header_filters: Vec::new(),
+            header_field_mappings: Vec::new(),
         })
         .await
         .unwrap_err();
@@ -1640,6 +1955,8 @@ mod kafka_broker_tests {
                 "bootstrap.servers": "192.0.2.10:9092"
             }),
             enable_backfill_mode: true,
+            header_filters: Vec::new(),
+            header_field_mappings: Vec::new(),
         })
         .await
         .unwrap_err();
@@ -311,11 +311,7 @@ impl Source for KinesisSource {
             }
         }
         if !checkpoint_delta.is_empty() {
-            let batch = RawDocBatch {
-                docs,
-                checkpoint_delta,
-                force_commit: false,
-            };
+            let batch = RawDocBatch::new(docs, checkpoint_delta, false);
             ctx.send_message(indexer_mailbox, batch).await?;
         }
         if self.state.shard_consumers.is_empty() {
@@ -57,6 +57,9 @@
 //!   that file.
 //! - the kafka source: the partition id is a kafka topic partition id, and the position is a kafka
 //!   offset.
+#[cfg(feature = "kafka")]
+mod azure_event_hubs_source;
+mod directory_watcher;
 mod file_source;
 #[cfg(feature = "gcp-pubsub")]
 mod gcp_pubsub_source;
@@ -77,6 +80,8 @@ use std::path::PathBuf;
 use std::time::Duration;
 
 use async_trait::async_trait;
+#[cfg(feature = "kafka")]
+pub use azure_event_hubs_source::AzureEventHubsSourceFactory;
 use bytes::Bytes;
 use bytesize::ByteSize;
 pub use file_source::{FileSource, FileSourceFactory};
@@ -106,9 +111,9 @@ use tracing::error;
 pub use vec_source::{VecSource, VecSourceFactory};
 pub use void_source::{VoidSource, VoidSourceFactory};
 
-use self::file_source::dir_and_filename;
+use self::file_source::{dir_and_filename, watched_dir_path};
 use crate::actors::DocProcessor;
-use crate::models::RawDocBatch;
+use crate::models::{DocMetadata, RawDocBatch};
 use crate::source::ingest::IngestSourceFactory;
 use crate::source::ingest_api_source::IngestApiSourceFactory;
 
@@ -125,7 +130,9 @@ use crate::source::ingest_api_source::IngestApiSourceFactory;
 /// 5MB seems like a good one size fits all value.
 const BATCH_NUM_BYTES_LIMIT: u64 = ByteSize::mib(5).as_u64();
 
-const EMIT_BATCHES_TIMEOUT: Duration = Duration::from_millis(if cfg!(test) { 100 } else { 1_000 });
+/// Maximum amount of time a source waits before emitting the batch it is accumulating.
+pub const EMIT_BATCHES_TIMEOUT: Duration =
+    Duration::from_millis(if cfg!(test) { 100 } else { 1_000 });
 
 /// Runtime configuration used during execution of a source actor.
 pub struct SourceRuntimeArgs {
@@ -160,6 +167,13 @@ impl SourceRuntimeArgs {
         self.pipeline_id.pipeline_uid
     }
 
+    /// Whether the source should attach the metadata of the messages it reads to the documents.
+    /// The metadata is only consumed by the VRL transform, so it is collected only when the source
+    /// has one.
+    pub fn collect_doc_metadata(&self) -> bool {
+        self.source_config.transform_config.is_some()
+    }
+
     #[cfg(test)]
     fn for_test(
         index_uid: IndexUid,
@@ -383,6 +397,8 @@ pub fn quickwit_supported_sources() -> &'static SourceLoader {
     static SOURCE_LOADER: OnceCell<SourceLoader> = OnceCell::new();
     SOURCE_LOADER.get_or_init(|| {
         let mut source_factory = SourceLoader::default();
+        #[cfg(feature = "kafka")]
+        source_factory.add_source("azure_event_hubs", AzureEventHubsSourceFactory);
         source_factory.add_source("file", FileSourceFactory);
         #[cfg(feature = "gcp-pubsub")]
         source_factory.add_source("gcp_pubsub", GcpPubSubSourceFactory);
@@ -407,6 +423,16 @@ pub async fn check_source_connectivity(
     match &source_config.source_params {
         SourceParams::File(params) => {
             if let Some(filepath) = &params.filepath {
+                if params.watch {
+                    let dir_path = watched_dir_path(filepath)?;
+                    let metadata = tokio::fs::metadata(&dir_path).await?;
+                    anyhow::ensure!(
+                        metadata.is_dir(),
+                        "watched path `{}` is not a directory",
+                        dir_path.display()
+                    );
+                    return Ok(());
+                }
                 let (dir_uri, file_name) = dir_and_filename(filepath)?;
                 let storage = storage_resolver.resolve(&dir_uri).await?;
                 storage.file_num_bytes(file_name).await?;
@@ -414,6 +440,17 @@ pub async fn check_source_connectivity(
             Ok(())
         }
         #[allow(unused_variables)]
+        SourceParams::AzureEventHubs(params) => {
+            #[cfg(not(feature = "kafka"))]
+            anyhow::bail!("Quickwit binary was not compiled with the `kafka` feature");
+
+            #[cfg(feature = "kafka")]
+            {
+                azure_event_hubs_source::check_connectivity(params).await?;
+                Ok(())
+            }
+        }
+        #[allow(unused_variables)]
         SourceParams::Kafka(params) => {
             #[cfg(not(feature = "kafka"))]
             anyhow::bail!("Quickwit binary was not compiled with the `kafka` feature");
@@ -475,6 +512,7 @@ impl Handler<SuggestTruncate> for SourceActor {
 #[derive(Debug, Default)]
 pub(crate) struct BatchBuilder {
     docs: Vec<Bytes>,
+    doc_metadatas: Vec<Option<DocMetadata>>,
     num_bytes: u64,
     checkpoint_delta: SourceCheckpointDelta,
     force_commit: bool,
@@ -486,6 +524,13 @@ impl BatchBuilder {
         self.docs.push(doc);
     }
 
+    #[cfg(any(feature = "kafka", feature = "pulsar"))]
+    pub fn add_doc_with_metadata(&mut self, doc: Bytes, doc_metadata: DocMetadata) {
+        self.doc_metadatas.resize(self.docs.len(), None);
+        self.doc_metadatas.push(Some(doc_metadata));
+        self.add_doc(doc);
+    }
+
     pub fn force_commit(&mut self) {
         self.force_commit = true;
     }
@@ -493,6 +538,7 @@ impl BatchBuilder {
     pub fn build(self) -> RawDocBatch {
         RawDocBatch {
             docs: self.docs,
+            doc_metadatas: self.doc_metadatas,
             checkpoint_delta: self.checkpoint_delta,
             force_commit: self.force_commit,
         }
@@ -501,6 +547,7 @@ impl BatchBuilder {
     #[cfg(feature = "kafka")]
     pub fn clear(&mut self) {
         self.docs.clear();
+        self.doc_metadatas.clear();
         self.num_bytes = 0;
         self.checkpoint_delta = SourceCheckpointDelta::default();
     }
@@ -526,6 +573,12 @@ mod tests {
                 source_params: SourceParams::void(),
                 transform_config: None,
                 input_format: SourceInputFormat::Json,
+                csv_config: Default::default(),
+                dead_letter_queue: None,
+                supervision: Default::default(),
+                fetch_stream: Default::default(),
+                placement_constraints: Default::default(),
+                priority: Default::default(),
             };
             check_source_connectivity(&StorageResolver::for_test(), &source_config).await?;
         }
@@ -538,6 +591,12 @@ mod tests {
                 source_params: SourceParams::Vec(VecSourceParams::default()),
                 transform_config: None,
                 input_format: SourceInputFormat::Json,
+                csv_config: Default::default(),
+                dead_letter_queue: None,
+                supervision: Default::default(),
+                fetch_stream: Default::default(),
+                placement_constraints: Default::default(),
+                priority: Default::default(),
             };
             check_source_connectivity(&StorageResolver::for_test(), &source_config).await?;
         }
@@ -550,6 +609,12 @@ mod tests {
                 source_params: SourceParams::file("file-does-not-exist.json"),
                 transform_config: None,
                 input_format: SourceInputFormat::Json,
+                csv_config: Default::default(),
+                dead_letter_queue: None,
+                supervision: Default::default(),
+                fetch_stream: Default::default(),
+                placement_constraints: Default::default(),
+                priority: Default::default(),
             };
             assert!(
                 check_source_connectivity(&StorageResolver::for_test(), &source_config)
@@ -566,6 +631,12 @@ mod tests {
                 source_params: SourceParams::file("data/test_corpus.json"),
                 transform_config: None,
                 input_format: SourceInputFormat::Json,
+                csv_config: Default::default(),
+                dead_letter_queue: None,
+                supervision: Default::default(),
+                fetch_stream: Default::default(),
+                placement_constraints: Default::default(),
+                priority: Default::default(),
             };
             assert!(
                 check_source_connectivity(&StorageResolver::for_test(), &source_config)
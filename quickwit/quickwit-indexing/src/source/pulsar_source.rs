@@ -33,6 +33,7 @@ use pulsar::{
 };
 use quickwit_actors::{ActorContext, ActorExitStatus, Mailbox};
 use quickwit_config::{PulsarSourceAuth, PulsarSourceParams};
+use quickwit_doc_mapper::JsonObject;
 use quickwit_metastore::checkpoint::{PartitionId, SourceCheckpoint};
 use quickwit_proto::types::{IndexUid, Position};
 use serde_json::{json, Value as JsonValue};
@@ -40,6 +41,7 @@ use tokio::time;
 use tracing::{debug, info, warn};
 
 use crate::actors::DocProcessor;
+use crate::models::DocMetadata;
 use crate::source::{
     BatchBuilder, Source, SourceActor, SourceContext, SourceRuntimeArgs, TypedSourceFactory,
     BATCH_NUM_BYTES_LIMIT, EMIT_BATCHES_TIMEOUT,
@@ -144,7 +146,18 @@ impl PulsarSource {
     ) -> anyhow::Result<()> {
         let current_position = msg_id_to_position(message.message_id());
         let doc = message.deserialize();
-        self.add_doc_to_batch(&message.topic, current_position, doc, batch)
+        let doc_metadata_opt = if self.ctx.collect_doc_metadata() {
+            Some(message_metadata(&message))
+        } else {
+            None
+        };
+        self.add_doc_to_batch(
+            &message.topic,
+            current_position,
+            doc,
+            doc_metadata_opt,
+            batch,
+        )
     }
 
     fn add_doc_to_batch(
@@ -152,6 +165,7 @@ impl PulsarSource {
         topic: &str,
         msg_position: Position,
         doc: Bytes,
+        doc_metadata_opt: Option<DocMetadata>,
         batch: &mut BatchBuilder,
     ) -> anyhow::Result<()> {
         if doc.is_empty() {
@@ -183,7 +197,12 @@ impl PulsarSource {
             .checkpoint_delta
             .record_partition_delta(partition, current_position, msg_position)
             .context("failed to record partition delta")?;
-        batch.add_doc(doc);
+
+        if let Some(doc_metadata) = doc_metadata_opt {
+            batch.add_doc_with_metadata(doc, doc_metadata);
+        } else {
+            batch.add_doc(doc);
+        }
 
         self.state.num_bytes_processed += num_bytes;
         self.state.num_messages_processed += 1;
@@ -324,6 +343,35 @@ async fn create_pulsar_consumer(
     Ok(consumer)
 }
 
+/// Returns the topic, publish time, partition key, and properties of a message.
+fn message_metadata(message: &Message<PulsarMessage>) -> DocMetadata {
+    let message_metadata = message.metadata();
+    let mut doc_metadata = JsonObject::with_capacity(4);
+    doc_metadata.insert("topic".to_string(), JsonValue::from(message.topic.as_str()));
+    doc_metadata.insert(
+        "publish_time_millis".to_string(),
+        JsonValue::from(message_metadata.publish_time),
+    );
+    if let Some(partition_key) = &message_metadata.partition_key {
+        doc_metadata.insert(
+            "partition_key".to_string(),
+            JsonValue::from(partition_key.as_str()),
+        );
+    }
+    let properties: JsonObject = message_metadata
+        .properties
+        .iter()
+        .map(|property| {
+            (
+                property.key.clone(),
+                JsonValue::from(property.value.as_str()),
+            )
+        })
+        .collect();
+    doc_metadata.insert("properties".to_string(), JsonValue::Object(properties));
+    Arc::new(doc_metadata)
+}
+
 fn msg_id_to_position(msg: &MessageIdData) -> Position {
     // The order of these fields are important as they affect the sorting
     // of the checkpoint positions.
@@ -554,6 +602,12 @@ mod pulsar_broker_tests {
             }),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            csv_config: Default::default(),
+            dead_letter_queue: None,
+            supervision: Default::default(),
+            fetch_stream: Default::default(),
+            placement_constraints: Default::default(),
+            priority: Default::default(),
         };
         (source_id, source_config)
     }
@@ -837,7 +891,7 @@ mod pulsar_broker_tests {
         let position = Position::Beginning;
         let mut batch = BatchBuilder::default();
         pulsar_source
-            .add_doc_to_batch(&topic, position, Bytes::from_static(b""), &mut batch)
+            .add_doc_to_batch(&topic, position, Bytes::from_static(b""), None, &mut batch)
             .expect("Add batch should not error on empty doc.");
         assert_eq!(pulsar_source.state.num_invalid_messages, 1);
         assert_eq!(pulsar_source.state.num_messages_processed, 0);
@@ -850,7 +904,7 @@ mod pulsar_broker_tests {
         let mut batch = BatchBuilder::default();
         let doc = Bytes::from_static(b"some-demo-data");
         pulsar_source
-            .add_doc_to_batch(&topic, position, doc, &mut batch)
+            .add_doc_to_batch(&topic, position, doc, None, &mut batch)
             .expect("Add batch should not error on empty doc.");
 
         assert_eq!(pulsar_source.state.num_invalid_messages, 1);
@@ -867,7 +921,7 @@ mod pulsar_broker_tests {
         let mut batch = BatchBuilder::default();
         let doc = Bytes::from_static(b"some-demo-data-2");
         pulsar_source
-            .add_doc_to_batch(&topic, position, doc, &mut batch)
+            .add_doc_to_batch(&topic, position, doc, None, &mut batch)
             .expect("Add batch should not error on empty doc.");
         assert_eq!(pulsar_source.state.num_invalid_messages, 1);
         assert_eq!(pulsar_source.state.num_messages_processed, 2);
@@ -164,6 +164,12 @@ mod tests {
             source_params: SourceParams::Vec(params.clone()),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            csv_config: Default::default(),
+            dead_letter_queue: None,
+            supervision: Default::default(),
+            fetch_stream: Default::default(),
+            placement_constraints: Default::default(),
+            priority: Default::default(),
         };
         let metastore = metastore_for_test();
         let vec_source = VecSourceFactory::typed_create_source(
@@ -225,6 +231,12 @@ mod tests {
             source_params: SourceParams::Vec(params.clone()),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            csv_config: Default::default(),
+            dead_letter_queue: None,
+            supervision: Default::default(),
+            fetch_stream: Default::default(),
+            placement_constraints: Default::default(),
+            priority: Default::default(),
         };
         let metastore = metastore_for_test();
         let vec_source = VecSourceFactory::typed_create_source(
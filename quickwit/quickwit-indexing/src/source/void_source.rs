@@ -94,6 +94,12 @@ mod tests {
             source_params: SourceParams::void(),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            csv_config: Default::default(),
+            dead_letter_queue: None,
+            supervision: Default::default(),
+            fetch_stream: Default::default(),
+            placement_constraints: Default::default(),
+            priority: Default::default(),
         };
         let metastore = metastore_for_test();
         let ctx = SourceRuntimeArgs::for_test(
@@ -120,6 +126,12 @@ mod tests {
             source_params: SourceParams::void(),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            csv_config: Default::default(),
+            dead_letter_queue: None,
+            supervision: Default::default(),
+            fetch_stream: Default::default(),
+            placement_constraints: Default::default(),
+            priority: Default::default(),
         };
         let metastore = metastore_for_test();
         let void_source = VoidSourceFactory::typed_create_source(
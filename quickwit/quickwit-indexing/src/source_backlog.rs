@@ -0,0 +1,142 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+
+use quickwit_cluster::{Cluster, ListenerHandle};
+use quickwit_common::pubsub::EventBroker;
+use quickwit_common::shared_consts::INDEXER_SOURCE_BACKLOG_PREFIX;
+use quickwit_proto::indexing::SourceBacklogUpdate;
+use quickwit_proto::types::{NodeId, PipelineUid, SourceUid};
+use tracing::warn;
+
+/// Parses a key of the form `<index_uid>:<source_id>:<pipeline_uid>`.
+fn parse_key(key: &str) -> Option<(SourceUid, PipelineUid)> {
+    let (source_uid_str, pipeline_uid_str) = key.rsplit_once(':')?;
+    let (index_uid_str, source_id_str) = source_uid_str.rsplit_once(':')?;
+    let pipeline_uid = pipeline_uid_str.parse().ok()?;
+
+    let source_uid = SourceUid {
+        index_uid: index_uid_str.into(),
+        source_id: source_id_str.to_string(),
+    };
+    Some((source_uid, pipeline_uid))
+}
+
+/// Publishes a [`SourceBacklogUpdate`] event whenever an indexer broadcasts the partition backlogs
+/// of one of its indexing pipelines.
+pub async fn setup_source_backlog_listener(
+    cluster: Cluster,
+    event_broker: EventBroker,
+) -> ListenerHandle {
+    cluster
+        .subscribe(INDEXER_SOURCE_BACKLOG_PREFIX, move |event| {
+            let Some((source_uid, pipeline_uid)) = parse_key(event.key) else {
+                warn!("failed to parse source backlog key `{}`", event.key);
+                return;
+            };
+            let Ok(partition_backlogs) = serde_json::from_str::<BTreeMap<String, u64>>(event.value)
+            else {
+                warn!("failed to parse partition backlogs `{}`", event.value);
+                return;
+            };
+            let indexer_id: NodeId = event.node.node_id.clone().into();
+
+            let source_backlog_update = SourceBacklogUpdate {
+                indexer_id,
+                source_uid,
+                pipeline_uid,
+                partition_backlogs,
+            };
+            event_broker.publish(source_backlog_update);
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use quickwit_cluster::{create_cluster_for_test, ChannelTransport};
+    use quickwit_proto::indexing::IndexingPipelineId;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_key() {
+        let pipeline_uid = PipelineUid::from_u128(1);
+        let key = format!("test-index:0:test-source:{pipeline_uid}");
+        let (source_uid, parsed_pipeline_uid) = parse_key(&key).unwrap();
+        assert_eq!(source_uid.index_uid.as_str(), "test-index:0");
+        assert_eq!(source_uid.source_id, "test-source");
+        assert_eq!(parsed_pipeline_uid, pipeline_uid);
+
+        assert!(parse_key("test-index:0:test-source:not-a-pipeline-uid").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_source_backlog_listener() {
+        let transport = ChannelTransport::default();
+        let cluster = create_cluster_for_test(Vec::new(), &["indexer"], &transport, true)
+            .await
+            .unwrap();
+        let event_broker = EventBroker::default();
+
+        let source_backlog_updates = Arc::new(Mutex::new(Vec::new()));
+        let source_backlog_updates_clone = source_backlog_updates.clone();
+
+        event_broker
+            .subscribe(move |event: SourceBacklogUpdate| {
+                source_backlog_updates_clone.lock().unwrap().push(event);
+            })
+            .forever();
+
+        setup_source_backlog_listener(cluster.clone(), event_broker.clone())
+            .await
+            .forever();
+
+        let pipeline_id = IndexingPipelineId {
+            node_id: cluster.self_node_id().to_string(),
+            index_uid: "test-index:0".into(),
+            source_id: "test-source".to_string(),
+            pipeline_uid: PipelineUid::from_u128(1),
+        };
+        let partition_backlogs = BTreeMap::from_iter([("0".to_string(), 42)]);
+        let source_backlogs = HashMap::from_iter([(&pipeline_id, &partition_backlogs)]);
+        cluster
+            .update_self_node_source_backlogs(&source_backlogs)
+            .await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let source_backlog_updates = source_backlog_updates.lock().unwrap();
+        assert_eq!(source_backlog_updates.len(), 1);
+
+        let source_backlog_update = &source_backlog_updates[0];
+        assert_eq!(source_backlog_update.indexer_id, cluster.self_node_id());
+        assert_eq!(
+            source_backlog_update.source_uid.index_uid.as_str(),
+            "test-index:0"
+        );
+        assert_eq!(source_backlog_update.source_uid.source_id, "test-source");
+        assert_eq!(source_backlog_update.pipeline_uid, pipeline_id.pipeline_uid);
+        assert_eq!(source_backlog_update.partition_backlogs, partition_backlogs);
+    }
+}
@@ -0,0 +1,150 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+/// Maximum number of recent pipeline failures kept in memory per source.
+const MAX_NUM_RECENT_FAILURES: usize = 10;
+
+/// A failure of an indexing pipeline detected by the pipeline supervisor.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct PipelineFailure {
+    /// Unix timestamp (in seconds) at which the failure was detected.
+    pub timestamp: i64,
+    pub message: String,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceHealthStatus {
+    /// The pipelines of the source are running.
+    #[default]
+    Healthy,
+    /// A pipeline failed and is about to be restarted.
+    Restarting,
+    /// A pipeline reached the maximum number of consecutive failures of the supervision policy
+    /// and is no longer restarted.
+    Failed,
+    /// Same as `Failed`, and the source was disabled in the metastore.
+    Disabled,
+}
+
+/// Health of the indexing pipelines of a source running on a node.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct SourceHealthReport {
+    pub status: SourceHealthStatus,
+    /// Number of times the pipelines were restarted after a failure.
+    pub num_restarts: usize,
+    /// Number of failures since the pipelines last ran successfully.
+    pub num_consecutive_failures: usize,
+    /// Most recent failures, most recent first.
+    pub last_errors: Vec<PipelineFailure>,
+}
+
+/// Health of a source, shared between the indexing pipeline(s) of the source, which update it,
+/// and the indexing service, which exposes it.
+#[derive(Clone, Debug, Default)]
+pub struct SourceHealth {
+    inner: Arc<Mutex<SourceHealthReport>>,
+}
+
+impl SourceHealth {
+    /// Records a pipeline failure and returns the number of consecutive failures.
+    pub(crate) fn record_failure(&self, message: String) -> usize {
+        let mut report = self.inner.lock().expect("lock should not be poisoned");
+        let failure = PipelineFailure {
+            timestamp: OffsetDateTime::now_utc().unix_timestamp(),
+            message,
+        };
+        report.last_errors.insert(0, failure);
+        report.last_errors.truncate(MAX_NUM_RECENT_FAILURES);
+        report.num_consecutive_failures += 1;
+        report.status = SourceHealthStatus::Restarting;
+        report.num_consecutive_failures
+    }
+
+    pub(crate) fn record_restart(&self) {
+        let mut report = self.inner.lock().expect("lock should not be poisoned");
+        report.num_restarts += 1;
+    }
+
+    /// Resets the consecutive failures counter once a pipeline runs successfully again.
+    pub(crate) fn record_recovery(&self) {
+        let mut report = self.inner.lock().expect("lock should not be poisoned");
+        if report.status == SourceHealthStatus::Restarting {
+            report.status = SourceHealthStatus::Healthy;
+            report.num_consecutive_failures = 0;
+        }
+    }
+
+    pub(crate) fn set_status(&self, status: SourceHealthStatus) {
+        let mut report = self.inner.lock().expect("lock should not be poisoned");
+        report.status = status;
+    }
+
+    pub fn report(&self) -> SourceHealthReport {
+        self.inner
+            .lock()
+            .expect("lock should not be poisoned")
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_health() {
+        let source_health = SourceHealth::default();
+        assert_eq!(source_health.report(), SourceHealthReport::default());
+
+        for i in 0..12 {
+            let num_consecutive_failures = source_health.record_failure(format!("error-{i}"));
+            assert_eq!(num_consecutive_failures, i + 1);
+        }
+        source_health.record_restart();
+
+        let report = source_health.report();
+        assert_eq!(report.status, SourceHealthStatus::Restarting);
+        assert_eq!(report.num_restarts, 1);
+        assert_eq!(report.num_consecutive_failures, 12);
+        assert_eq!(report.last_errors.len(), MAX_NUM_RECENT_FAILURES);
+        assert_eq!(report.last_errors[0].message, "error-11");
+
+        source_health.record_recovery();
+
+        let report = source_health.report();
+        assert_eq!(report.status, SourceHealthStatus::Healthy);
+        assert_eq!(report.num_restarts, 1);
+        assert_eq!(report.num_consecutive_failures, 0);
+        assert_eq!(report.last_errors.len(), MAX_NUM_RECENT_FAILURES);
+
+        source_health.record_failure("error".to_string());
+        source_health.set_status(SourceHealthStatus::Failed);
+        source_health.record_recovery();
+
+        let report = source_health.report();
+        assert_eq!(report.status, SourceHealthStatus::Failed);
+        assert_eq!(report.num_consecutive_failures, 1);
+    }
+}
@@ -66,6 +66,8 @@ struct InnerIndexingSplitStore {
     /// The remote storage.
     remote_storage: Arc<dyn Storage>,
     local_split_store: Arc<LocalSplitStore>,
+    /// ID of the data key the remote storage encrypts the splits with, if any.
+    encryption_key_id: Option<String>,
 }
 
 pub struct WeakIndexingSplitStore {
@@ -88,6 +90,20 @@ impl IndexingSplitStore {
         let inner = InnerIndexingSplitStore {
             remote_storage,
             local_split_store,
+            encryption_key_id: None,
+        };
+        Self {
+            inner: Arc::new(inner),
+        }
+    }
+
+    /// Records the ID of the data key the remote storage encrypts the splits with, so that it
+    /// ends up in the metadata of the uploaded splits.
+    pub fn with_encryption_key_id(self, encryption_key_id: Option<String>) -> Self {
+        let inner = InnerIndexingSplitStore {
+            remote_storage: self.inner.remote_storage.clone(),
+            local_split_store: self.inner.local_split_store.clone(),
+            encryption_key_id,
         };
         Self {
             inner: Arc::new(inner),
@@ -100,6 +116,7 @@ impl IndexingSplitStore {
         let inner = InnerIndexingSplitStore {
             remote_storage,
             local_split_store: Arc::new(LocalSplitStore::no_caching()),
+            encryption_key_id: None,
         };
         IndexingSplitStore {
             inner: Arc::new(inner),
@@ -110,6 +127,11 @@ impl IndexingSplitStore {
         self.inner.remote_storage.uri()
     }
 
+    /// Returns the ID of the data key the splits are encrypted with, if the index is encrypted.
+    pub fn encryption_key_id(&self) -> Option<&str> {
+        self.inner.encryption_key_id.as_deref()
+    }
+
     fn split_path(&self, split_id: &str) -> PathBuf {
         PathBuf::from(quickwit_common::split_file(split_id))
     }
@@ -176,6 +198,28 @@ impl IndexingSplitStore {
         Ok(())
     }
 
+    /// Stores the doc IDs sidecar of a split in the remote storage. The sidecar is never cached
+    /// locally.
+    pub async fn store_doc_ids_sidecar(
+        &self,
+        split_id: &str,
+        sidecar_bytes: Vec<u8>,
+    ) -> anyhow::Result<()> {
+        let key = PathBuf::from(quickwit_common::doc_ids_sidecar_file(split_id));
+        self.inner
+            .remote_storage
+            .put(&key, Box::new(sidecar_bytes))
+            .await
+            .with_context(|| {
+                format!(
+                    "failed uploading key {} in bucket {}",
+                    key.display(),
+                    self.inner.remote_storage.uri()
+                )
+            })?;
+        Ok(())
+    }
+
     /// Gets a split from the split store, and makes it available to the given `output_path`.
     /// If the split is available in the local disk cache, then it will be moved
     /// from the cache to the `output_dir_path`.
@@ -23,6 +23,26 @@ fn main() {
     // Legacy ingest codegen
     let mut prost_config = ProstConfig::default();
     prost_config.bytes(["DocBatch.doc_buffer"]);
+    prost_config
+        .field_attribute("IngestRequest.return_positions", "#[serde(default)]")
+        .field_attribute(
+            "BatchPosition.source_id",
+            "#[serde(skip_serializing_if = \"Option::is_none\")]",
+        )
+        .field_attribute(
+            "BatchPosition.shard_id",
+            "#[serde(skip_serializing_if = \"Option::is_none\")]",
+        )
+        .field_attribute("IngestResponse.positions", "#[serde(default)]")
+        .field_attribute(
+            "IngestResponse.positions",
+            "#[serde(skip_serializing_if = \"Vec::is_empty\")]",
+        )
+        .field_attribute("IngestResponse.failures", "#[serde(default)]")
+        .field_attribute(
+            "IngestResponse.failures",
+            "#[serde(skip_serializing_if = \"Vec::is_empty\")]",
+        );
 
     Codegen::builder()
         .with_protos(&["src/ingest_service.proto"])
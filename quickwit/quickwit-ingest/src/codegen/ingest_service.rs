@@ -34,6 +34,51 @@ pub struct IngestRequest {
     pub doc_batches: ::prost::alloc::vec::Vec<DocBatch>,
     #[prost(enumeration = "CommitType", tag = "2")]
     pub commit: i32,
+    /// Optional key identifying the request. Retrying a request with the same key within the
+    /// idempotency key TTL returns the response of the original request without ingesting the
+    /// documents again.
+    #[prost(string, optional, tag = "3")]
+    pub idempotency_key: ::core::option::Option<::prost::alloc::string::String>,
+    /// If set, the response includes the position assigned to each accepted batch.
+    #[prost(bool, tag = "4")]
+    #[serde(default)]
+    pub return_positions: bool,
+}
+/// Position assigned to a batch of documents accepted by the ingest API.
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BatchPosition {
+    #[prost(string, tag = "1")]
+    pub index_id: ::prost::alloc::string::String,
+    /// Source and shard the batch was persisted to. Only set by the ingest V2 API.
+    #[prost(string, optional, tag = "2")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_id: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(uint64, optional, tag = "3")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shard_id: ::core::option::Option<u64>,
+    /// Position of the last document of the batch in its queue or shard, inclusive.
+    #[prost(uint64, tag = "4")]
+    pub position_inclusive: u64,
+}
+/// Batch of documents rejected by the ingest V2 API while the rest of the request succeeded.
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BatchFailure {
+    #[prost(string, tag = "1")]
+    pub index_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub source_id: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "3")]
+    pub num_docs: u64,
+    /// Reason of the failure, for instance `rate_limited` or `no_shards_available`.
+    #[prost(string, tag = "4")]
+    pub reason: ::prost::alloc::string::String,
+    /// Whether retrying the rejected documents may succeed.
+    #[prost(bool, tag = "5")]
+    pub retryable: bool,
 }
 #[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -41,6 +86,18 @@ pub struct IngestRequest {
 pub struct IngestResponse {
     #[prost(uint64, tag = "1")]
     pub num_docs_for_processing: u64,
+    /// Positions of the accepted batches, in the order of the request doc batches. Only populated
+    /// if `return_positions` is set in the request.
+    #[prost(message, repeated, tag = "2")]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub positions: ::prost::alloc::vec::Vec<BatchPosition>,
+    /// Batches of documents that were not ingested. Only populated by the ingest V2 API when the
+    /// request partially succeeds.
+    #[prost(message, repeated, tag = "3")]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub failures: ::prost::alloc::vec::Vec<BatchFailure>,
 }
 /// Fetch messages with position strictly after `start_after`.
 #[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
@@ -22,6 +22,7 @@ use std::io;
 use mrecordlog::error::*;
 use quickwit_actors::AskError;
 use quickwit_common::tower::BufferError;
+use quickwit_proto::ingest::IngestV2Error;
 use quickwit_proto::{tonic, ServiceError, ServiceErrorCode};
 use serde::Serialize;
 
@@ -64,6 +65,20 @@ impl From<BufferError> for IngestServiceError {
     }
 }
 
+impl From<IngestV2Error> for IngestServiceError {
+    fn from(error: IngestV2Error) -> Self {
+        match error {
+            IngestV2Error::IngesterUnavailable { .. }
+            | IngestV2Error::Timeout
+            | IngestV2Error::Transport(_) => IngestServiceError::Unavailable,
+            IngestV2Error::TooManyRequests => IngestServiceError::RateLimited,
+            IngestV2Error::Internal(_) | IngestV2Error::ShardNotFound { .. } => {
+                IngestServiceError::Internal(error.to_string())
+            }
+        }
+    }
+}
+
 impl From<io::Error> for IngestServiceError {
     fn from(io_error: io::Error) -> Self {
         IngestServiceError::IoError(io_error.to_string())
@@ -100,7 +115,7 @@ impl ServiceError for IngestServiceError {
             IngestServiceError::InvalidPosition(_) => ServiceErrorCode::BadRequest,
             IngestServiceError::IoError { .. } => ServiceErrorCode::Internal,
             IngestServiceError::RateLimited => ServiceErrorCode::RateLimited,
-            IngestServiceError::Unavailable => ServiceErrorCode::Internal,
+            IngestServiceError::Unavailable => ServiceErrorCode::Unavailable,
         }
     }
 }
@@ -0,0 +1,239 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::IngestResponse;
+
+/// Returns the number of seconds elapsed since UNIX_EPOCH.
+///
+/// If the system clock is set before 1970, returns 0.
+pub(crate) fn unixtime() -> u64 {
+    match UNIX_EPOCH.elapsed() {
+        Ok(duration) => duration.as_secs(),
+        Err(_) => 0u64,
+    }
+}
+
+/// Record persisted in the ingest queue log for each ingest request carrying an idempotency key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct IdempotencyKeyRecord {
+    idempotency_key: String,
+    response: IngestResponse,
+    expires_at: u64,
+}
+
+impl IdempotencyKeyRecord {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("idempotency key record should be JSON serializable")
+    }
+}
+
+/// Keeps track of the responses returned to the ingest requests carrying an idempotency key so
+/// that retried requests return the original response instead of ingesting the documents twice.
+///
+/// Records expire after `ttl`. Since they are appended to the log in chronological order, the
+/// expired records always form a prefix of the log, which can be truncated. Records that could not
+/// be appended to the log are only tracked in memory and have no position.
+pub(crate) struct IdempotencyKeys {
+    ttl: Duration,
+    responses: HashMap<String, (IngestResponse, u64)>,
+    // Positions, expiration timestamps, and keys of the records, in insertion order.
+    records: VecDeque<(Option<u64>, u64, String)>,
+}
+
+impl IdempotencyKeys {
+    /// Rebuilds the idempotency keys from the records stored in the log.
+    pub fn from_records(
+        ttl: Duration,
+        records: impl IntoIterator<Item = (u64, Vec<u8>)>,
+        now: u64,
+    ) -> Self {
+        let mut idempotency_keys = Self {
+            ttl,
+            responses: HashMap::new(),
+            records: VecDeque::new(),
+        };
+        for (position, record_bytes) in records {
+            match serde_json::from_slice::<IdempotencyKeyRecord>(&record_bytes) {
+                Ok(record) => {
+                    if record.expires_at > now {
+                        idempotency_keys.insert(Some(position), record);
+                    } else {
+                        idempotency_keys.records.push_back((
+                            Some(position),
+                            record.expires_at,
+                            record.idempotency_key,
+                        ));
+                    }
+                }
+                Err(error) => {
+                    warn!(
+                        position=%position,
+                        error=?error,
+                        "failed to deserialize idempotency key record"
+                    );
+                }
+            }
+        }
+        idempotency_keys
+    }
+
+    /// Returns the response of the request identified by `idempotency_key` if it has not
+    /// expired yet.
+    pub fn get(&self, idempotency_key: &str, now: u64) -> Option<&IngestResponse> {
+        self.responses
+            .get(idempotency_key)
+            .filter(|(_, expires_at)| *expires_at > now)
+            .map(|(response, _)| response)
+    }
+
+    pub fn new_record(
+        &self,
+        idempotency_key: String,
+        response: IngestResponse,
+        now: u64,
+    ) -> IdempotencyKeyRecord {
+        IdempotencyKeyRecord {
+            idempotency_key,
+            response,
+            expires_at: now + self.ttl.as_secs(),
+        }
+    }
+
+    /// Tracks a record appended to the log at `position_opt`, or kept in memory only if `None`.
+    pub fn insert(&mut self, position_opt: Option<u64>, record: IdempotencyKeyRecord) {
+        self.records.push_back((
+            position_opt,
+            record.expires_at,
+            record.idempotency_key.clone(),
+        ));
+        self.responses
+            .insert(record.idempotency_key, (record.response, record.expires_at));
+    }
+
+    /// Forgets the expired records and returns the position up to which the log can be
+    /// truncated, if any.
+    pub fn evict_expired(&mut self, now: u64) -> Option<u64> {
+        let mut truncate_up_to_position_opt = None;
+
+        while let Some((position, expires_at, _)) = self.records.front() {
+            if *expires_at > now {
+                break;
+            }
+            if position.is_some() {
+                truncate_up_to_position_opt = *position;
+            }
+            let (_, _, idempotency_key) = self.records.pop_front().expect("record should exist");
+
+            // The key may have been reused after its expiration, in which case the newer
+            // response must be kept.
+            if let Some((_, expires_at)) = self.responses.get(&idempotency_key) {
+                if *expires_at <= now {
+                    self.responses.remove(&idempotency_key);
+                }
+            }
+        }
+        truncate_up_to_position_opt
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(num_docs_for_processing: u64) -> IngestResponse {
+        IngestResponse {
+            num_docs_for_processing,
+            positions: Vec::new(),
+            failures: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_idempotency_keys() {
+        let mut idempotency_keys =
+            IdempotencyKeys::from_records(Duration::from_secs(10), Vec::new(), 0);
+        assert!(idempotency_keys.get("key-1", 0).is_none());
+
+        let record = idempotency_keys.new_record("key-1".to_string(), response(3), 0);
+        idempotency_keys.insert(Some(0), record);
+
+        let record = idempotency_keys.new_record("key-2".to_string(), response(5), 5);
+        idempotency_keys.insert(Some(1), record);
+
+        assert_eq!(idempotency_keys.get("key-1", 9).unwrap(), &response(3));
+        assert_eq!(idempotency_keys.get("key-2", 9).unwrap(), &response(5));
+        assert!(idempotency_keys.get("key-1", 10).is_none());
+
+        assert_eq!(idempotency_keys.evict_expired(9), None);
+        assert_eq!(idempotency_keys.evict_expired(10), Some(0));
+        assert_eq!(idempotency_keys.responses.len(), 1);
+
+        // Reusing an expired key.
+        let record = idempotency_keys.new_record("key-2".to_string(), response(7), 15);
+        idempotency_keys.insert(Some(2), record);
+
+        assert_eq!(idempotency_keys.evict_expired(15), Some(1));
+        assert_eq!(idempotency_keys.get("key-2", 15).unwrap(), &response(7));
+
+        // A record kept in memory only does not move the truncation position.
+        let record = idempotency_keys.new_record("key-3".to_string(), response(9), 20);
+        idempotency_keys.insert(None, record);
+        assert_eq!(idempotency_keys.get("key-3", 20).unwrap(), &response(9));
+
+        assert_eq!(idempotency_keys.evict_expired(25), Some(2));
+        assert_eq!(idempotency_keys.responses.len(), 1);
+
+        assert_eq!(idempotency_keys.evict_expired(30), None);
+        assert_eq!(idempotency_keys.responses.len(), 0);
+    }
+
+    #[test]
+    fn test_idempotency_keys_from_records() {
+        let idempotency_keys =
+            IdempotencyKeys::from_records(Duration::from_secs(10), Vec::new(), 0);
+        let records = vec![
+            (
+                0,
+                idempotency_keys
+                    .new_record("key-1".to_string(), response(3), 0)
+                    .to_bytes(),
+            ),
+            (1, b"not-a-record".to_vec()),
+            (
+                2,
+                idempotency_keys
+                    .new_record("key-2".to_string(), response(5), 5)
+                    .to_bytes(),
+            ),
+        ];
+        let mut idempotency_keys =
+            IdempotencyKeys::from_records(Duration::from_secs(10), records, 12);
+        assert!(idempotency_keys.get("key-1", 12).is_none());
+        assert_eq!(idempotency_keys.get("key-2", 12).unwrap(), &response(5));
+        assert_eq!(idempotency_keys.responses.len(), 1);
+
+        assert_eq!(idempotency_keys.evict_expired(12), Some(0));
+    }
+}
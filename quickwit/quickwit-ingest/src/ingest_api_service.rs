@@ -18,6 +18,7 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 use std::path::Path;
+use std::time::Duration;
 use std::{fmt, iter};
 
 use async_trait::async_trait;
@@ -27,16 +28,24 @@ use quickwit_actors::{
 };
 use quickwit_common::runtimes::RuntimeType;
 use quickwit_common::tower::Cost;
-use tracing::info;
+use tracing::{error, info, warn};
 use ulid::Ulid;
 
+use crate::idempotency::{unixtime, IdempotencyKeys};
 use crate::metrics::INGEST_METRICS;
 use crate::notifications::Notifications;
 use crate::{
-    CommitType, CreateQueueIfNotExistsRequest, CreateQueueRequest, DocCommand, DropQueueRequest,
-    FetchRequest, FetchResponse, IngestRequest, IngestResponse, IngestServiceError,
-    ListQueuesRequest, ListQueuesResponse, MemoryCapacity, Queues, SuggestTruncateRequest,
-    TailRequest,
+    BatchPosition, CommitType, CreateQueueIfNotExistsRequest, CreateQueueRequest, DocCommand,
+    DropQueueRequest, FetchRequest, FetchResponse, IngestRequest, IngestResponse,
+    IngestServiceError, ListQueuesRequest, ListQueuesResponse, MemoryCapacity, Queues,
+    SuggestTruncateRequest, TailRequest,
+};
+
+/// Interval at which the expired idempotency keys are evicted and truncated from the log.
+const EVICT_EXPIRED_IDEMPOTENCY_KEYS_INTERVAL: Duration = if cfg!(test) {
+    Duration::from_secs(1)
+} else {
+    Duration::from_secs(60)
 };
 
 impl Cost for IngestRequest {
@@ -55,6 +64,7 @@ pub struct IngestApiService {
     disk_limit: usize,
     memory_capacity: MemoryCapacity,
     notifications: Notifications,
+    idempotency_keys: IdempotencyKeys,
 }
 
 impl fmt::Debug for IngestApiService {
@@ -97,11 +107,17 @@ impl IngestApiService {
         queues_dir_path: &Path,
         memory_limit: usize,
         disk_limit: usize,
+        idempotency_key_ttl: Duration,
     ) -> crate::Result<Self> {
         let queues = Queues::open(queues_dir_path).await?;
         let partition_id = get_or_initialize_partition_id(queues_dir_path).await?;
         let memory_capacity = MemoryCapacity::new(memory_limit);
         let notifications = Notifications::new();
+        let idempotency_keys = IdempotencyKeys::from_records(
+            idempotency_key_ttl,
+            queues.idempotency_key_records()?,
+            unixtime(),
+        );
         info!(ingest_partition_id=%partition_id, "Ingest API partition id");
         Ok(Self {
             partition_id,
@@ -110,6 +126,7 @@ impl IngestApiService {
             disk_limit,
             memory_capacity,
             notifications,
+            idempotency_keys,
         })
     }
 
@@ -119,9 +136,26 @@ impl IngestApiService {
         reply: impl FnOnce(crate::Result<IngestResponse>) + Send + Sync + 'static,
         ctx: &ActorContext<Self>,
     ) -> Result<(), ActorExitStatus> {
+        if let Some(idempotency_key) = &request.idempotency_key {
+            if let Some(response) = self.idempotency_keys.get(idempotency_key, unixtime()) {
+                info!(idempotency_key=%idempotency_key, "replaying ingest response");
+                reply(Ok(response.clone()));
+                return Ok(());
+            }
+        }
+        let idempotency_key_opt = request.idempotency_key.clone();
         let notification = self.ingest_inner(request, ctx).await;
         match notification {
             Ok((response, index_positions)) => {
+                if let Some(idempotency_key) = idempotency_key_opt {
+                    if let Err(error) = self
+                        .record_idempotency_key(idempotency_key, &response, ctx)
+                        .await
+                    {
+                        reply(Err(error));
+                        return Ok(());
+                    }
+                }
                 if index_positions.is_empty() {
                     reply(Ok(response));
                 } else {
@@ -174,6 +208,7 @@ impl IngestApiService {
         }
         let mut num_docs = 0usize;
         let mut notifications = Vec::new();
+        let mut positions = Vec::new();
         for doc_batch in &request.doc_batches {
             // TODO better error handling.
             // If there is an error, we probably want a transactional behavior.
@@ -184,6 +219,14 @@ impl IngestApiService {
                 .await?;
             let commit = request.commit();
             if let Some(max_position) = max_position {
+                if request.return_positions {
+                    positions.push(BatchPosition {
+                        index_id: doc_batch.index_id.clone(),
+                        source_id: None,
+                        shard_id: None,
+                        position_inclusive: max_position,
+                    });
+                }
                 if commit != CommitType::Auto {
                     if commit == CommitType::Force {
                         self.queues
@@ -201,22 +244,68 @@ impl IngestApiService {
             let batch_num_docs = doc_batch.num_docs();
             let batch_num_bytes = doc_batch.num_bytes();
             num_docs += batch_num_docs;
-            INGEST_METRICS
-                .ingested_num_bytes
-                .inc_by(batch_num_bytes as u64);
-            INGEST_METRICS
-                .ingested_num_docs
-                .inc_by(batch_num_docs as u64);
+            INGEST_METRICS.record_ingested_docs(
+                &doc_batch.index_id,
+                batch_num_docs as u64,
+                batch_num_bytes as u64,
+            );
         }
         // TODO we could fsync here and disable autosync to have better i/o perfs.
         Ok((
             IngestResponse {
                 num_docs_for_processing: num_docs as u64,
+                positions,
+                failures: Vec::new(),
             },
             notifications,
         ))
     }
 
+    /// Persists the response of a request carrying an idempotency key. The record is appended to
+    /// the log after the documents, so the request fails if it cannot be persisted. The key is
+    /// still tracked in memory in that case so that retrying the request on this node does not
+    /// ingest the documents twice.
+    async fn record_idempotency_key(
+        &mut self,
+        idempotency_key: String,
+        response: &IngestResponse,
+        ctx: &ActorContext<Self>,
+    ) -> crate::Result<()> {
+        let now = unixtime();
+        let record = self
+            .idempotency_keys
+            .new_record(idempotency_key, response.clone(), now);
+        match self
+            .queues
+            .append_idempotency_key_record(&record.to_bytes(), ctx)
+            .await
+        {
+            Ok(position) => {
+                self.idempotency_keys.insert(Some(position), record);
+                Ok(())
+            }
+            Err(error) => {
+                error!(error=?error, "failed to persist idempotency key");
+                self.idempotency_keys.insert(None, record);
+                Err(IngestServiceError::Internal(format!(
+                    "failed to persist idempotency key: {error}"
+                )))
+            }
+        }
+    }
+
+    async fn evict_expired_idempotency_keys(
+        &mut self,
+        ctx: &ActorContext<Self>,
+    ) -> crate::Result<()> {
+        if let Some(up_to_position_included) = self.idempotency_keys.evict_expired(unixtime()) {
+            self.queues
+                .truncate_idempotency_key_records(up_to_position_included, ctx)
+                .await?;
+        }
+        Ok(())
+    }
+
     fn fetch(&mut self, fetch_req: FetchRequest) -> crate::Result<FetchResponse> {
         let num_bytes_limit_opt: Option<usize> = fetch_req
             .num_bytes_limit
@@ -262,6 +351,34 @@ impl Actor for IngestApiService {
     fn queue_capacity(&self) -> QueueCapacity {
         QueueCapacity::Bounded(3)
     }
+
+    async fn initialize(&mut self, ctx: &ActorContext<Self>) -> Result<(), ActorExitStatus> {
+        self.handle(EvictExpiredIdempotencyKeysLoop, ctx).await
+    }
+}
+
+#[derive(Debug)]
+struct EvictExpiredIdempotencyKeysLoop;
+
+#[async_trait]
+impl Handler<EvictExpiredIdempotencyKeysLoop> for IngestApiService {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        _message: EvictExpiredIdempotencyKeysLoop,
+        ctx: &ActorContext<Self>,
+    ) -> Result<(), ActorExitStatus> {
+        if let Err(error) = self.evict_expired_idempotency_keys(ctx).await {
+            warn!(error=?error, "failed to evict expired idempotency keys");
+        }
+        ctx.schedule_self_msg(
+            EVICT_EXPIRED_IDEMPOTENCY_KEYS_INTERVAL,
+            EvictExpiredIdempotencyKeysLoop,
+        )
+        .await;
+        Ok(())
+    }
 }
 
 #[derive(Debug)]
@@ -410,6 +527,7 @@ mod tests {
     use bytes::Bytes;
     use quickwit_actors::Universe;
     use quickwit_config::IngestApiConfig;
+    use tokio::sync::watch;
 
     use super::*;
     use crate::{init_ingest_api, DocBatch, DocBatchBuilder};
@@ -430,6 +548,8 @@ mod tests {
                 },
             ],
             commit: CommitType::Auto.into(),
+            idempotency_key: None,
+            return_positions: false,
         };
         assert_eq!(ingest_request.cost(), 9);
     }
@@ -459,6 +579,8 @@ mod tests {
         let ingest_request = IngestRequest {
             doc_batches: vec![batch.build()],
             commit: CommitType::Force.into(),
+            idempotency_key: None,
+            return_positions: false,
         };
         let ingest_response = ingest_api_service
             .send_message(ingest_request)
@@ -493,6 +615,56 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_ingest_api_service_returns_positions() -> anyhow::Result<()> {
+        let universe = Universe::with_accelerated_time();
+        let temp_dir = tempfile::tempdir()?;
+        let queues_dir_path = temp_dir.path();
+
+        let ingest_api_service =
+            init_ingest_api(&universe, queues_dir_path, &IngestApiConfig::default()).await?;
+
+        let create_queue_req = CreateQueueIfNotExistsRequest {
+            queue_id: "index-1".to_string(),
+        };
+        ingest_api_service.ask_for_res(create_queue_req).await?;
+
+        let mut batch_1 = DocBatchBuilder::new("index-1".to_string());
+        batch_1.ingest_doc(Bytes::from_static(b"Test1"));
+        batch_1.ingest_doc(Bytes::from_static(b"Test2"));
+
+        let mut batch_2 = DocBatchBuilder::new("index-1".to_string());
+        batch_2.ingest_doc(Bytes::from_static(b"Test3"));
+
+        let ingest_request = IngestRequest {
+            doc_batches: vec![batch_1.build(), batch_2.build()],
+            commit: CommitType::Auto.into(),
+            idempotency_key: None,
+            return_positions: true,
+        };
+        let ingest_response = ingest_api_service.ask_for_res(ingest_request).await?;
+        assert_eq!(ingest_response.num_docs_for_processing, 3);
+        assert_eq!(
+            ingest_response.positions,
+            [
+                BatchPosition {
+                    index_id: "index-1".to_string(),
+                    source_id: None,
+                    shard_id: None,
+                    position_inclusive: 1,
+                },
+                BatchPosition {
+                    index_id: "index-1".to_string(),
+                    source_id: None,
+                    shard_id: None,
+                    position_inclusive: 2,
+                },
+            ]
+        );
+        universe.assert_quit().await;
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_ingest_api_service_with_wait() -> anyhow::Result<()> {
         let universe = Universe::with_accelerated_time();
@@ -518,6 +690,8 @@ mod tests {
         let ingest_request = IngestRequest {
             doc_batches: vec![batch.build()],
             commit: CommitType::WaitFor.into(),
+            idempotency_key: None,
+            return_positions: false,
         };
         let ingest_response = ingest_api_service
             .send_message(ingest_request)
@@ -547,4 +721,96 @@ mod tests {
         universe.assert_quit().await;
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_ingest_api_service_with_idempotency_key() -> anyhow::Result<()> {
+        let universe = Universe::with_accelerated_time();
+        let temp_dir = tempfile::tempdir()?;
+        let queues_dir_path = temp_dir.path();
+
+        let ingest_api_service =
+            init_ingest_api(&universe, queues_dir_path, &IngestApiConfig::default()).await?;
+
+        let create_queue_req = CreateQueueIfNotExistsRequest {
+            queue_id: "index-1".to_string(),
+        };
+        ingest_api_service.ask_for_res(create_queue_req).await?;
+
+        let mut batch = DocBatchBuilder::new("index-1".to_string());
+        batch.ingest_doc(Bytes::from_static(b"Test1"));
+        batch.ingest_doc(Bytes::from_static(b"Test2"));
+
+        let ingest_request = IngestRequest {
+            doc_batches: vec![batch.build()],
+            commit: CommitType::Auto.into(),
+            idempotency_key: Some("my-idempotency-key".to_string()),
+            return_positions: false,
+        };
+        let ingest_response = ingest_api_service
+            .ask_for_res(ingest_request.clone())
+            .await?;
+        assert_eq!(ingest_response.num_docs_for_processing, 2);
+
+        let replayed_ingest_response = ingest_api_service.ask_for_res(ingest_request).await?;
+        assert_eq!(replayed_ingest_response, ingest_response);
+
+        let fetch_request = FetchRequest {
+            index_id: "index-1".to_string(),
+            start_after: None,
+            num_bytes_limit: None,
+        };
+        let fetch_response = ingest_api_service.ask_for_res(fetch_request).await?;
+        assert_eq!(fetch_response.doc_batch.unwrap().num_docs(), 2);
+
+        universe.assert_quit().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_ingest_api_service_evicts_expired_idempotency_keys() {
+        let universe = Universe::with_accelerated_time();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let (mailbox, _inbox) = universe.create_test_mailbox();
+        let (observable_state_tx, _observable_state_rx) = watch::channel(());
+        let ctx = ActorContext::for_test(&universe, mailbox, observable_state_tx);
+
+        let mut ingest_api_service = IngestApiService::with_queues_dir(
+            temp_dir.path(),
+            1_000_000,
+            1_000_000,
+            Duration::ZERO,
+        )
+        .await
+        .unwrap();
+        let response = IngestResponse {
+            num_docs_for_processing: 1,
+            positions: Vec::new(),
+            failures: Vec::new(),
+        };
+        ingest_api_service
+            .record_idempotency_key("my-idempotency-key".to_string(), &response, &ctx)
+            .await
+            .unwrap();
+        assert_eq!(
+            ingest_api_service
+                .queues
+                .idempotency_key_records()
+                .unwrap()
+                .len(),
+            1
+        );
+        ingest_api_service
+            .handle(EvictExpiredIdempotencyKeysLoop, &ctx)
+            .await
+            .unwrap();
+        assert!(ingest_api_service
+            .queues
+            .idempotency_key_records()
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            ingest_api_service.idempotency_keys.evict_expired(u64::MAX),
+            None
+        );
+    }
 }
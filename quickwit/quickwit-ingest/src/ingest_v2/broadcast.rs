@@ -24,11 +24,13 @@ use std::time::Duration;
 use bytesize::ByteSize;
 use quickwit_cluster::{Cluster, ListenerHandle};
 use quickwit_common::pubsub::{Event, EventBroker};
-use quickwit_common::shared_consts::INGESTER_PRIMARY_SHARDS_PREFIX;
+use quickwit_common::shared_consts::{
+    INGESTER_DISK_WATERMARK_EXCEEDED_KEY, INGESTER_PRIMARY_SHARDS_PREFIX,
+};
 use quickwit_common::sorted_iter::{KeyDiff, SortedByKeyIterator};
 use quickwit_common::tower::Rate;
 use quickwit_proto::ingest::ShardState;
-use quickwit_proto::types::{split_queue_id, NodeId, QueueId, ShardId, SourceUid};
+use quickwit_proto::types::{split_queue_id, NodeId, Position, QueueId, ShardId, SourceUid};
 use serde::{Deserialize, Serialize, Serializer};
 use tokio::sync::RwLock;
 use tokio::task::JoinHandle;
@@ -47,21 +49,31 @@ const BROADCAST_INTERVAL_PERIOD: Duration = if cfg!(test) {
 const ONE_MIB: ByteSize = ByteSize::mib(1);
 
 /// Broadcasted information about a primary shard.
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
 pub struct ShardInfo {
     pub shard_id: ShardId,
     pub shard_state: ShardState,
     /// Shard ingestion rate in MiB/s.
     pub ingestion_rate: RateMibPerSec,
+    /// Position of the last record written in the shard.
+    pub replication_position_inclusive: Position,
 }
 
 impl Serialize for ShardInfo {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // Only the offset is broadcasted to keep the value short: the end of a shard is
+        // already conveyed by its state.
+        let replication_offset_str = self
+            .replication_position_inclusive
+            .as_u64()
+            .map(|offset| offset.to_string())
+            .unwrap_or_default();
         serializer.serialize_str(&format!(
-            "{}:{}:{}",
+            "{}:{}:{}:{}",
             self.shard_id,
             self.shard_state.as_json_str_name(),
             self.ingestion_rate.0,
+            replication_offset_str,
         ))
     }
 }
@@ -91,10 +103,20 @@ impl<'de> Deserialize<'de> for ShardInfo {
             .map(RateMibPerSec)
             .map_err(|_| serde::de::Error::custom("invalid shard ingestion rate"))?;
 
+        // Ingesters running an older version do not broadcast the replication position.
+        let replication_position_inclusive = match parts.next() {
+            None | Some("") => Position::Beginning,
+            Some(offset_str) => offset_str
+                .parse::<u64>()
+                .map(Position::offset)
+                .map_err(|_| serde::de::Error::custom("invalid shard replication position"))?,
+        };
+
         Ok(Self {
             shard_id,
             shard_state,
             ingestion_rate,
+            replication_position_inclusive,
         })
     }
 }
@@ -106,6 +128,7 @@ pub type ShardInfos = BTreeSet<ShardInfo>;
 #[derive(Debug, Default, Eq, PartialEq)]
 struct LocalShardsSnapshot {
     per_source_shard_infos: BTreeMap<SourceUid, ShardInfos>,
+    disk_watermark_exceeded: bool,
 }
 
 #[derive(Debug)]
@@ -166,21 +189,30 @@ impl BroadcastLocalShardsTask {
         let state = self.weak_state.upgrade()?;
         let mut state_guard = state.write().await;
 
+        // Without persist requests, the disk usage can only decrease, so the watermark must also be
+        // checked periodically for the ingester to accept writes again.
+        state_guard.check_disk_watermark();
+        let disk_watermark_exceeded = state_guard.disk_watermark.is_exceeded();
+
         let mut per_source_shard_infos: BTreeMap<SourceUid, ShardInfos> = BTreeMap::new();
 
-        let queue_ids: Vec<(QueueId, ShardState)> = state_guard
+        let queue_ids: Vec<(QueueId, ShardState, Position)> = state_guard
             .shards
             .iter()
             .filter_map(|(queue_id, shard)| {
                 if !shard.is_replica() {
-                    Some((queue_id.clone(), shard.shard_state))
+                    Some((
+                        queue_id.clone(),
+                        shard.shard_state,
+                        shard.replication_position_inclusive.clone(),
+                    ))
                 } else {
                     None
                 }
             })
             .collect();
 
-        for (queue_id, shard_state) in queue_ids {
+        for (queue_id, shard_state, replication_position_inclusive) in queue_ids {
             let Some((_rate_limiter, rate_meter)) = state_guard.rate_trackers.get_mut(&queue_id)
             else {
                 warn!("rate limiter `{queue_id}` not found",);
@@ -203,6 +235,7 @@ impl BroadcastLocalShardsTask {
                 shard_id,
                 shard_state,
                 ingestion_rate,
+                replication_position_inclusive,
             };
             per_source_shard_infos
                 .entry(source_uid)
@@ -231,6 +264,7 @@ impl BroadcastLocalShardsTask {
         }
         let snapshot = LocalShardsSnapshot {
             per_source_shard_infos,
+            disk_watermark_exceeded,
         };
         Some(snapshot)
     }
@@ -257,11 +291,26 @@ impl BroadcastLocalShardsTask {
                 }
             }
         }
+        if previous_snapshot.disk_watermark_exceeded != new_snapshot.disk_watermark_exceeded {
+            self.broadcast_disk_watermark(new_snapshot.disk_watermark_exceeded)
+                .await;
+        }
+    }
+
+    async fn broadcast_disk_watermark(&self, disk_watermark_exceeded: bool) {
+        self.cluster
+            .set_self_key_value(
+                INGESTER_DISK_WATERMARK_EXCEEDED_KEY,
+                disk_watermark_exceeded,
+            )
+            .await;
     }
 
     async fn run(&mut self) {
         let mut interval = tokio::time::interval(BROADCAST_INTERVAL_PERIOD);
         let mut previous_snapshot = LocalShardsSnapshot::default();
+        self.broadcast_disk_watermark(previous_snapshot.disk_watermark_exceeded)
+            .await;
 
         loop {
             interval.tick().await;
@@ -330,10 +379,41 @@ pub async fn setup_local_shards_update_listener(
         .await
 }
 
+/// Event published when an ingester exceeds its disk high watermark or falls back below its low
+/// watermark.
+#[derive(Debug, Clone)]
+pub struct IngesterDiskWatermarkUpdate {
+    pub ingester_id: NodeId,
+    pub disk_watermark_exceeded: bool,
+}
+
+impl Event for IngesterDiskWatermarkUpdate {}
+
+pub async fn setup_ingester_disk_watermark_listener(
+    cluster: Cluster,
+    event_broker: EventBroker,
+) -> ListenerHandle {
+    cluster
+        .subscribe(INGESTER_DISK_WATERMARK_EXCEEDED_KEY, move |event| {
+            let Ok(disk_watermark_exceeded) = event.value.parse::<bool>() else {
+                warn!("failed to parse disk watermark state `{}`", event.value);
+                return;
+            };
+            let ingester_id: NodeId = event.node.node_id.clone().into();
+
+            let disk_watermark_update = IngesterDiskWatermarkUpdate {
+                ingester_id,
+                disk_watermark_exceeded,
+            };
+            event_broker.publish(disk_watermark_update);
+        })
+        .await
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
-    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
     use std::sync::Arc;
 
     use mrecordlog::MultiRecordLog;
@@ -341,11 +421,13 @@ mod tests {
     use quickwit_common::rate_limiter::{RateLimiter, RateLimiterSettings};
     use quickwit_proto::ingest::ingester::{IngesterStatus, ObservationMessage};
     use quickwit_proto::ingest::ShardState;
-    use quickwit_proto::types::{queue_id, Position};
+    use quickwit_proto::types::queue_id;
     use tokio::sync::watch;
 
     use super::*;
     use crate::ingest_v2::models::IngesterShard;
+    use crate::ingest_v2::mrecordlog_utils::DiskWatermark;
+    use crate::ingest_v2::persisted_batches::PersistedBatches;
     use crate::ingest_v2::rate_meter::RateMeter;
 
     #[test]
@@ -354,12 +436,29 @@ mod tests {
             shard_id: 1,
             shard_state: ShardState::Open,
             ingestion_rate: RateMibPerSec(42),
+            replication_position_inclusive: Position::offset(1337u64),
+        };
+        let serialized = serde_json::to_string(&shard_info).unwrap();
+        assert_eq!(serialized, r#""1:open:42:1337""#);
+
+        let deserialized = serde_json::from_str::<ShardInfo>(&serialized).unwrap();
+        assert_eq!(deserialized, shard_info);
+
+        let shard_info = ShardInfo {
+            shard_id: 1,
+            shard_state: ShardState::Open,
+            ingestion_rate: RateMibPerSec(42),
+            replication_position_inclusive: Position::Beginning,
         };
         let serialized = serde_json::to_string(&shard_info).unwrap();
-        assert_eq!(serialized, r#""1:open:42""#);
+        assert_eq!(serialized, r#""1:open:42:""#);
 
         let deserialized = serde_json::from_str::<ShardInfo>(&serialized).unwrap();
         assert_eq!(deserialized, shard_info);
+
+        // Shard info broadcasted by an ingester running an older version.
+        let deserialized = serde_json::from_str::<ShardInfo>(r#""1:open:42""#).unwrap();
+        assert_eq!(deserialized, shard_info);
     }
 
     #[test]
@@ -380,12 +479,14 @@ mod tests {
                     shard_id: 1,
                     shard_state: ShardState::Open,
                     ingestion_rate: RateMibPerSec(42),
+                    replication_position_inclusive: Position::Beginning,
                 }]
                 .into_iter()
                 .collect(),
             )]
             .into_iter()
             .collect(),
+            disk_watermark_exceeded: false,
         };
         let changes = previous_snapshot
             .diff(&current_snapshot)
@@ -420,12 +521,14 @@ mod tests {
                     shard_id: 1,
                     shard_state: ShardState::Closed,
                     ingestion_rate: RateMibPerSec(42),
+                    replication_position_inclusive: Position::Beginning,
                 }]
                 .into_iter()
                 .collect(),
             )]
             .into_iter()
             .collect(),
+            disk_watermark_exceeded: false,
         };
         let changes = previous_snapshot
             .diff(&current_snapshot)
@@ -482,6 +585,8 @@ mod tests {
             replication_tasks: HashMap::new(),
             status: IngesterStatus::Ready,
             observation_tx,
+            disk_watermark: DiskWatermark::default(),
+            persisted_batches: PersistedBatches::default(),
         }));
         let weak_state = Arc::downgrade(&state);
         let task = BroadcastLocalShardsTask {
@@ -571,6 +676,10 @@ mod tests {
                 assert_eq!(shard_info.shard_id, 1);
                 assert_eq!(shard_info.shard_state, ShardState::Open);
                 assert_eq!(shard_info.ingestion_rate, 42u16);
+                assert_eq!(
+                    shard_info.replication_position_inclusive,
+                    Position::offset(1337u64)
+                );
             })
             .forever();
 
@@ -587,6 +696,7 @@ mod tests {
             shard_id: 1,
             shard_state: ShardState::Open,
             ingestion_rate: RateMibPerSec(42),
+            replication_position_inclusive: Position::offset(1337u64),
         }])
         .unwrap();
 
@@ -595,4 +705,34 @@ mod tests {
 
         assert_eq!(local_shards_update_counter.load(Ordering::Acquire), 1);
     }
+
+    #[tokio::test]
+    async fn test_ingester_disk_watermark_listener() {
+        let transport = ChannelTransport::default();
+        let cluster = create_cluster_for_test(Vec::new(), &["indexer"], &transport, true)
+            .await
+            .unwrap();
+        let event_broker = EventBroker::default();
+
+        let disk_watermark_exceeded = Arc::new(AtomicBool::new(false));
+        let disk_watermark_exceeded_clone = disk_watermark_exceeded.clone();
+
+        event_broker
+            .subscribe(move |event: IngesterDiskWatermarkUpdate| {
+                disk_watermark_exceeded_clone
+                    .store(event.disk_watermark_exceeded, Ordering::Release);
+            })
+            .forever();
+
+        setup_ingester_disk_watermark_listener(cluster.clone(), event_broker.clone())
+            .await
+            .forever();
+
+        cluster
+            .set_self_key_value(INGESTER_DISK_WATERMARK_EXCEEDED_KEY, true)
+            .await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        assert!(disk_watermark_exceeded.load(Ordering::Acquire));
+    }
 }
@@ -0,0 +1,458 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{BTreeMap, HashMap};
+use std::mem;
+
+use bytes::{BufMut, BytesMut};
+use quickwit_config::IngestRoutingRule;
+use quickwit_proto::ingest::router::{IngestFailureReason, IngestResponseV2, IngestSubrequest};
+use quickwit_proto::ingest::DocBatchV2;
+use quickwit_proto::types::{IndexId, SubrequestId};
+
+use super::metrics::INGEST_V2_METRICS;
+
+/// Routes the documents of the subrequests targeting the index of a routing rule to the indexes
+/// returned by the expression of the rule.
+#[derive(Debug, Default)]
+pub(super) struct DocRouter {
+    routing_rules: HashMap<IndexId, CompiledRoutingRule>,
+}
+
+impl DocRouter {
+    pub fn try_new(routing_rules: &[IngestRoutingRule]) -> anyhow::Result<Self> {
+        let mut compiled_routing_rules = HashMap::with_capacity(routing_rules.len());
+
+        for routing_rule in routing_rules {
+            let compiled_routing_rule = CompiledRoutingRule::try_new(routing_rule)?;
+            compiled_routing_rules.insert(routing_rule.index_id.clone(), compiled_routing_rule);
+        }
+        Ok(Self {
+            routing_rules: compiled_routing_rules,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.routing_rules.is_empty()
+    }
+
+    /// Splits the subrequests targeting the index of a routing rule into one subrequest per
+    /// target index. The other subrequests are left untouched. All the subrequests are assigned
+    /// new IDs, which are mapped back to the original ones by
+    /// [`RoutedSubrequests::restore_subrequest_ids`].
+    pub fn route(&self, subrequests: Vec<IngestSubrequest>) -> RoutedSubrequests {
+        let mut routed_subrequests = RoutedSubrequests::default();
+
+        for subrequest in subrequests {
+            let Some(routing_rule) = self.routing_rules.get(&subrequest.index_id) else {
+                routed_subrequests.push(subrequest, None);
+                continue;
+            };
+            let Some(doc_batch) = &subrequest.doc_batch else {
+                routed_subrequests.push(subrequest, None);
+                continue;
+            };
+            let mut doc_batch_builders: BTreeMap<IndexId, DocBatchV2Builder> = BTreeMap::new();
+            let mut num_fallback_docs = 0;
+
+            for doc in doc_batch.docs() {
+                let index_id = routing_rule.eval(&doc).unwrap_or_else(|| {
+                    num_fallback_docs += 1;
+                    routing_rule.fallback_index_id.clone()
+                });
+                doc_batch_builders
+                    .entry(index_id)
+                    .or_default()
+                    .add_doc(&doc);
+            }
+            let num_routed_docs = doc_batch.num_docs() as u64 - num_fallback_docs;
+            INGEST_V2_METRICS
+                .router_routed_docs_total
+                .with_label_values(["routed"])
+                .inc_by(num_routed_docs);
+            INGEST_V2_METRICS
+                .router_routed_docs_total
+                .with_label_values(["fallback"])
+                .inc_by(num_fallback_docs);
+
+            for (index_id, doc_batch_builder) in doc_batch_builders {
+                let fallback_index_id_opt = if index_id != routing_rule.fallback_index_id {
+                    Some(routing_rule.fallback_index_id.clone())
+                } else {
+                    None
+                };
+                let routed_subrequest = IngestSubrequest {
+                    subrequest_id: subrequest.subrequest_id,
+                    index_id,
+                    source_id: subrequest.source_id.clone(),
+                    doc_batch: Some(doc_batch_builder.build()),
+                };
+                routed_subrequests.push(routed_subrequest, fallback_index_id_opt);
+            }
+        }
+        routed_subrequests
+    }
+}
+
+#[derive(Debug)]
+struct CompiledRoutingRule {
+    #[cfg(feature = "vrl")]
+    program: vrl::compiler::Program,
+    #[cfg(feature = "vrl")]
+    timezone: vrl::compiler::TimeZone,
+    fallback_index_id: IndexId,
+}
+
+impl CompiledRoutingRule {
+    #[cfg(feature = "vrl")]
+    fn try_new(routing_rule: &IngestRoutingRule) -> anyhow::Result<Self> {
+        let program = routing_rule.compile_expression()?;
+        let timezone =
+            vrl::compiler::TimeZone::parse("UTC").expect("`UTC` should be a valid timezone");
+        Ok(Self {
+            program,
+            timezone,
+            fallback_index_id: routing_rule.fallback_index_id.clone(),
+        })
+    }
+
+    #[cfg(not(feature = "vrl"))]
+    fn try_new(routing_rule: &IngestRoutingRule) -> anyhow::Result<Self> {
+        anyhow::bail!(
+            "routing rule for index `{}` requires Quickwit to be compiled with the `vrl` feature",
+            routing_rule.index_id
+        )
+    }
+
+    /// Evaluates the expression against a document and returns the ID of its target index, or
+    /// `None` if the document is not valid JSON, the evaluation fails, or the expression does not
+    /// return a valid index ID.
+    #[cfg(feature = "vrl")]
+    fn eval(&self, doc: &[u8]) -> Option<IndexId> {
+        use vrl::compiler::runtime::Runtime;
+        use vrl::compiler::state::RuntimeState;
+        use vrl::compiler::TargetValueRef;
+        use vrl::value::{Secrets, Value as VrlValue};
+
+        let mut value: VrlValue = serde_json::from_slice(doc).ok()?;
+        let mut metadata = VrlValue::Object(BTreeMap::new());
+        let mut secrets = Secrets::default();
+        let mut target = TargetValueRef {
+            value: &mut value,
+            metadata: &mut metadata,
+            secrets: &mut secrets,
+        };
+        let mut runtime = Runtime::new(RuntimeState::default());
+
+        let VrlValue::Bytes(index_id_bytes) = runtime
+            .resolve(&mut target, &self.program, &self.timezone)
+            .ok()?
+        else {
+            return None;
+        };
+        let index_id = String::from_utf8(index_id_bytes.to_vec()).ok()?;
+        quickwit_config::validate_identifier("index ID", &index_id).ok()?;
+        Some(index_id)
+    }
+
+    #[cfg(not(feature = "vrl"))]
+    fn eval(&self, _doc: &[u8]) -> Option<IndexId> {
+        None
+    }
+}
+
+#[derive(Default)]
+struct DocBatchV2Builder {
+    doc_buffer: BytesMut,
+    doc_lengths: Vec<u32>,
+}
+
+impl DocBatchV2Builder {
+    fn add_doc(&mut self, doc: &[u8]) {
+        self.doc_buffer.put(doc);
+        self.doc_lengths.push(doc.len() as u32);
+    }
+
+    fn build(self) -> DocBatchV2 {
+        DocBatchV2 {
+            doc_buffer: self.doc_buffer.freeze(),
+            doc_lengths: self.doc_lengths,
+        }
+    }
+}
+
+struct SubrequestOrigin {
+    subrequest_id: SubrequestId,
+    /// Fallback subrequest persisted if the target index of the subrequest does not exist. Only
+    /// set for the subrequests created by a routing rule.
+    fallback_subrequest_opt: Option<IngestSubrequest>,
+}
+
+/// Subrequests of an ingest request after routing.
+#[derive(Default)]
+pub(super) struct RoutedSubrequests {
+    subrequests: Vec<IngestSubrequest>,
+    origins: HashMap<SubrequestId, SubrequestOrigin>,
+}
+
+impl RoutedSubrequests {
+    fn push(&mut self, mut subrequest: IngestSubrequest, fallback_index_id_opt: Option<IndexId>) {
+        let origin_subrequest_id = subrequest.subrequest_id;
+        subrequest.subrequest_id = self.origins.len() as SubrequestId;
+
+        let fallback_subrequest_opt =
+            fallback_index_id_opt.map(|fallback_index_id| IngestSubrequest {
+                subrequest_id: origin_subrequest_id,
+                index_id: fallback_index_id,
+                source_id: subrequest.source_id.clone(),
+                doc_batch: subrequest.doc_batch.clone(),
+            });
+        let origin = SubrequestOrigin {
+            subrequest_id: origin_subrequest_id,
+            fallback_subrequest_opt,
+        };
+        self.origins.insert(subrequest.subrequest_id, origin);
+        self.subrequests.push(subrequest);
+    }
+
+    pub fn take_subrequests(&mut self) -> Vec<IngestSubrequest> {
+        mem::take(&mut self.subrequests)
+    }
+
+    /// Removes the failures of the routed subrequests whose target index does not exist from the
+    /// response and returns the subrequests routing their documents to the fallback indexes.
+    pub fn take_fallback_subrequests(
+        &mut self,
+        response: &mut IngestResponseV2,
+    ) -> Vec<IngestSubrequest> {
+        let mut fallback_subrequests = Vec::new();
+
+        response.failures.retain(|failure| {
+            if failure.reason() != IngestFailureReason::IndexNotFound {
+                return true;
+            }
+            let Some(fallback_subrequest) = self
+                .origins
+                .get_mut(&failure.subrequest_id)
+                .and_then(|origin| origin.fallback_subrequest_opt.take())
+            else {
+                return true;
+            };
+            fallback_subrequests.push(fallback_subrequest);
+            false
+        });
+        for fallback_subrequest in fallback_subrequests.iter_mut() {
+            let origin = SubrequestOrigin {
+                subrequest_id: fallback_subrequest.subrequest_id,
+                fallback_subrequest_opt: None,
+            };
+            fallback_subrequest.subrequest_id = self.origins.len() as SubrequestId;
+            self.origins
+                .insert(fallback_subrequest.subrequest_id, origin);
+        }
+        fallback_subrequests
+    }
+
+    /// Replaces the IDs of the routed subrequests in the response with the IDs of the original
+    /// subrequests. A subrequest split by a routing rule may thus yield several successes and
+    /// failures.
+    pub fn restore_subrequest_ids(&self, response: &mut IngestResponseV2) {
+        let subrequest_ids = response
+            .successes
+            .iter_mut()
+            .map(|success| &mut success.subrequest_id)
+            .chain(
+                response
+                    .failures
+                    .iter_mut()
+                    .map(|failure| &mut failure.subrequest_id),
+            );
+        for subrequest_id in subrequest_ids {
+            if let Some(origin) = self.origins.get(subrequest_id) {
+                *subrequest_id = origin.subrequest_id;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_proto::ingest::router::{IngestFailure, IngestSuccess};
+
+    use super::*;
+
+    fn routing_rule() -> IngestRoutingRule {
+        IngestRoutingRule {
+            index_id: "logs".to_string(),
+            expression: r#""logs-" + string!(.namespace)"#.to_string(),
+            fallback_index_id: "logs-unrouted".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_doc_router_routes_unrouted_subrequests_untouched() {
+        let doc_router = DocRouter::default();
+        assert!(doc_router.is_empty());
+
+        let subrequest = IngestSubrequest {
+            subrequest_id: 7,
+            index_id: "test-index".to_string(),
+            source_id: "test-source".to_string(),
+            doc_batch: Some(DocBatchV2::for_test(["test-doc-foo"])),
+        };
+        let mut routed_subrequests = doc_router.route(vec![subrequest.clone()]);
+        let subrequests = routed_subrequests.take_subrequests();
+        assert_eq!(subrequests.len(), 1);
+        assert_eq!(subrequests[0].subrequest_id, 0);
+        assert_eq!(subrequests[0].index_id, "test-index");
+        assert_eq!(subrequests[0].doc_batch, subrequest.doc_batch);
+
+        let mut response = IngestResponseV2 {
+            successes: vec![IngestSuccess {
+                subrequest_id: 0,
+                ..Default::default()
+            }],
+            failures: Vec::new(),
+        };
+        assert!(routed_subrequests
+            .take_fallback_subrequests(&mut response)
+            .is_empty());
+        routed_subrequests.restore_subrequest_ids(&mut response);
+        assert_eq!(response.successes[0].subrequest_id, 7);
+    }
+
+    #[cfg(feature = "vrl")]
+    #[test]
+    fn test_doc_router_routes_docs() {
+        let doc_router = DocRouter::try_new(&[routing_rule()]).unwrap();
+        assert!(!doc_router.is_empty());
+
+        let subrequest = IngestSubrequest {
+            subrequest_id: 3,
+            index_id: "logs".to_string(),
+            source_id: "_ingest-source".to_string(),
+            doc_batch: Some(DocBatchV2::for_test([
+                r#"{"namespace": "foo", "message": "a"}"#,
+                r#"{"namespace": "bar", "message": "b"}"#,
+                r#"{"message": "c"}"#,
+                r#"{"namespace": "Not an index ID!"}"#,
+                r#"not JSON"#,
+                r#"{"namespace": "foo", "message": "d"}"#,
+            ])),
+        };
+        let mut routed_subrequests = doc_router.route(vec![subrequest]);
+        let subrequests = routed_subrequests.take_subrequests();
+        assert_eq!(subrequests.len(), 3);
+
+        assert_eq!(subrequests[0].subrequest_id, 0);
+        assert_eq!(subrequests[0].index_id, "logs-bar");
+        assert_eq!(subrequests[0].source_id, "_ingest-source");
+        assert_eq!(subrequests[0].doc_batch.as_ref().unwrap().num_docs(), 1);
+
+        assert_eq!(subrequests[1].subrequest_id, 1);
+        assert_eq!(subrequests[1].index_id, "logs-foo");
+        assert_eq!(
+            subrequests[1].doc_batch,
+            Some(DocBatchV2::for_test([
+                r#"{"namespace": "foo", "message": "a"}"#,
+                r#"{"namespace": "foo", "message": "d"}"#,
+            ]))
+        );
+        assert_eq!(subrequests[2].subrequest_id, 2);
+        assert_eq!(subrequests[2].index_id, "logs-unrouted");
+        assert_eq!(subrequests[2].doc_batch.as_ref().unwrap().num_docs(), 3);
+
+        // `logs-foo` does not exist.
+        let mut response = IngestResponseV2 {
+            successes: vec![
+                IngestSuccess {
+                    subrequest_id: 0,
+                    ..Default::default()
+                },
+                IngestSuccess {
+                    subrequest_id: 2,
+                    ..Default::default()
+                },
+            ],
+            failures: vec![IngestFailure {
+                subrequest_id: 1,
+                index_id: "logs-foo".to_string(),
+                source_id: "_ingest-source".to_string(),
+                reason: IngestFailureReason::IndexNotFound as i32,
+                num_docs: 2,
+            }],
+        };
+        let fallback_subrequests = routed_subrequests.take_fallback_subrequests(&mut response);
+        assert!(response.failures.is_empty());
+        assert_eq!(fallback_subrequests.len(), 1);
+        assert_eq!(fallback_subrequests[0].subrequest_id, 3);
+        assert_eq!(fallback_subrequests[0].index_id, "logs-unrouted");
+        assert_eq!(fallback_subrequests[0].doc_batch, subrequests[1].doc_batch);
+
+        response.successes.push(IngestSuccess {
+            subrequest_id: 3,
+            ..Default::default()
+        });
+        routed_subrequests.restore_subrequest_ids(&mut response);
+
+        for success in &response.successes {
+            assert_eq!(success.subrequest_id, 3);
+        }
+    }
+
+    #[cfg(feature = "vrl")]
+    #[test]
+    fn test_doc_router_does_not_fall_back_twice() {
+        let doc_router = DocRouter::try_new(&[routing_rule()]).unwrap();
+
+        let subrequest = IngestSubrequest {
+            subrequest_id: 0,
+            index_id: "logs".to_string(),
+            source_id: "_ingest-source".to_string(),
+            doc_batch: Some(DocBatchV2::for_test([r#"{"message": "a"}"#])),
+        };
+        let mut routed_subrequests = doc_router.route(vec![subrequest]);
+        let subrequests = routed_subrequests.take_subrequests();
+        assert_eq!(subrequests.len(), 1);
+        assert_eq!(subrequests[0].index_id, "logs-unrouted");
+
+        // The fallback index does not exist either.
+        let mut response = IngestResponseV2 {
+            successes: Vec::new(),
+            failures: vec![IngestFailure {
+                subrequest_id: 0,
+                index_id: "logs-unrouted".to_string(),
+                source_id: "_ingest-source".to_string(),
+                reason: IngestFailureReason::IndexNotFound as i32,
+                num_docs: 1,
+            }],
+        };
+        assert!(routed_subrequests
+            .take_fallback_subrequests(&mut response)
+            .is_empty());
+        assert_eq!(response.failures.len(), 1);
+    }
+
+    #[cfg(not(feature = "vrl"))]
+    #[test]
+    fn test_doc_router_requires_vrl_feature() {
+        let error = DocRouter::try_new(&[routing_rule()]).unwrap_err();
+        assert!(error.to_string().contains("`vrl` feature"));
+    }
+}
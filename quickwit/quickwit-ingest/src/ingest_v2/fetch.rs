@@ -23,20 +23,22 @@ use std::collections::HashMap;
 use std::fmt;
 use std::sync::Arc;
 
-use bytes::{BufMut, BytesMut};
+use bytes::{BufMut, Bytes, BytesMut};
 use futures::StreamExt;
 use quickwit_common::retry::RetryParams;
 use quickwit_common::ServiceStream;
+use quickwit_config::{FetchDecompression, FetchStreamConfig};
 use quickwit_proto::ingest::ingester::{
     fetch_message, FetchEof, FetchMessage, FetchPayload, IngesterService, OpenFetchStreamRequest,
 };
 use quickwit_proto::ingest::{IngestV2Error, IngestV2Result, MRecordBatch};
 use quickwit_proto::types::{queue_id, IndexUid, NodeId, Position, QueueId, ShardId, SourceId};
-use tokio::sync::{mpsc, watch, RwLock};
+use tokio::sync::{mpsc, watch, RwLock, Semaphore};
 use tokio::task::JoinHandle;
 use tracing::{debug, error, warn};
 
 use super::ingester::IngesterState;
+use super::metrics::INGEST_V2_METRICS;
 use super::models::ShardStatus;
 use crate::{with_lock_metrics, ClientId, IngesterPool};
 
@@ -57,6 +59,11 @@ pub(super) struct FetchStreamTask {
     /// task does not need to grab the lock and poll the mrecordlog queue unnecessarily.
     shard_status_rx: watch::Receiver<ShardStatus>,
     batch_num_bytes: usize,
+    /// Whether the record batches are compressed with zstd before being sent.
+    compress_batches: bool,
+    /// Number of records written to the shard and not fetched yet, as last reported to the
+    /// `fetch_stream_lag_records` metric.
+    num_lagging_records: i64,
 }
 
 impl fmt::Debug for FetchStreamTask {
@@ -72,6 +79,8 @@ impl fmt::Debug for FetchStreamTask {
 
 impl FetchStreamTask {
     pub const DEFAULT_BATCH_NUM_BYTES: usize = 1024 * 1024; // 1 MiB
+    pub const MAX_BATCH_NUM_BYTES: usize = 16 * 1024 * 1024; // 16 MiB
+    const ZSTD_COMPRESSION_LEVEL: i32 = 3;
 
     pub fn spawn(
         open_fetch_stream_request: OpenFetchStreamRequest,
@@ -84,6 +93,7 @@ impl FetchStreamTask {
             .as_u64()
             .map(|offset| offset + 1)
             .unwrap_or_default();
+        let compress_batches = open_fetch_stream_request.compress_batches;
         let (fetch_message_tx, fetch_stream) = ServiceStream::new_bounded(3);
         let mut fetch_task = Self {
             queue_id: open_fetch_stream_request.queue_id(),
@@ -96,8 +106,13 @@ impl FetchStreamTask {
             fetch_message_tx,
             shard_status_rx,
             batch_num_bytes,
+            compress_batches,
+            num_lagging_records: 0,
+        };
+        let future = async move {
+            fetch_task.run().await;
+            fetch_task.report_lag(0);
         };
-        let future = async move { fetch_task.run().await };
         let fetch_task_handle: JoinHandle<()> = tokio::spawn(future);
         (fetch_stream, fetch_task_handle)
     }
@@ -156,8 +171,15 @@ impl FetchStreamTask {
 
                 to_position_inclusive = Position::offset(self.from_position_inclusive - 1);
 
+                let replication_position = self.shard_status_rx.borrow().1.clone();
+                self.report_lag(num_records_between(
+                    &to_position_inclusive,
+                    &replication_position,
+                ));
+
+                let (mrecord_buffer, is_compressed) = self.maybe_compress(mrecord_buffer.freeze());
                 let mrecord_batch = MRecordBatch {
-                    mrecord_buffer: mrecord_buffer.freeze(),
+                    mrecord_buffer,
                     mrecord_lengths,
                 };
                 let fetch_payload = FetchPayload {
@@ -167,6 +189,7 @@ impl FetchStreamTask {
                     mrecord_batch: Some(mrecord_batch),
                     from_position_exclusive: Some(from_position_exclusive),
                     to_position_inclusive: Some(to_position_inclusive.clone()),
+                    is_compressed,
                 };
                 let fetch_message = FetchMessage::new_payload(fetch_payload);
 
@@ -224,6 +247,54 @@ impl FetchStreamTask {
     }
 }
 
+impl FetchStreamTask {
+    /// Compresses the buffer of a record batch if the client requested it. Falls back to sending
+    /// the buffer uncompressed if the compression fails.
+    fn maybe_compress(&self, mrecord_buffer: Bytes) -> (Bytes, bool) {
+        if !self.compress_batches {
+            return (mrecord_buffer, false);
+        }
+        match zstd::bulk::compress(&mrecord_buffer, Self::ZSTD_COMPRESSION_LEVEL) {
+            Ok(compressed_mrecord_buffer) => (Bytes::from(compressed_mrecord_buffer), true),
+            Err(error) => {
+                warn!(
+                    client_id=%self.client_id,
+                    index_uid=%self.index_uid,
+                    source_id=%self.source_id,
+                    shard_id=%self.shard_id,
+                    error=%error,
+                    "failed to compress record batch"
+                );
+                (mrecord_buffer, false)
+            }
+        }
+    }
+
+    fn report_lag(&mut self, num_lagging_records: u64) {
+        let num_lagging_records = num_lagging_records as i64;
+        INGEST_V2_METRICS
+            .fetch_stream_lag_records
+            .add(num_lagging_records - self.num_lagging_records);
+        self.num_lagging_records = num_lagging_records;
+    }
+}
+
+/// Returns the number of records of a shard in the range `(from_position_exclusive,
+/// to_position_inclusive]`.
+fn num_records_between(
+    from_position_exclusive: &Position,
+    to_position_inclusive: &Position,
+) -> u64 {
+    match (
+        from_position_exclusive.as_u64(),
+        to_position_inclusive.as_u64(),
+    ) {
+        (Some(from_offset), Some(to_offset)) => to_offset.saturating_sub(from_offset),
+        (None, Some(to_offset)) => to_offset + 1,
+        _ => 0,
+    }
+}
+
 #[derive(Debug)]
 pub struct FetchStreamError {
     pub index_uid: IndexUid,
@@ -232,6 +303,81 @@ pub struct FetchStreamError {
     pub ingest_error: IngestV2Error,
 }
 
+/// Flow control settings of the fetch tasks of a multi fetch stream.
+#[derive(Clone)]
+struct FetchStreamSettings {
+    batch_num_bytes: u64,
+    compress_batches: bool,
+    max_in_flight_bytes: usize,
+    /// Holds one permit per byte that can be fetched and not yet consumed. The fetch tasks
+    /// acquire permits before pushing a payload into the stream and `next()` releases them.
+    in_flight_bytes_semaphore: Arc<Semaphore>,
+}
+
+impl FetchStreamSettings {
+    fn new(fetch_stream_config: &FetchStreamConfig) -> Self {
+        let max_in_flight_bytes = fetch_stream_config.max_in_flight_bytes.as_u64() as usize;
+        Self {
+            batch_num_bytes: fetch_stream_config.batch_num_bytes.as_u64(),
+            compress_batches: fetch_stream_config.decompression == FetchDecompression::Indexer,
+            max_in_flight_bytes,
+            in_flight_bytes_semaphore: Arc::new(Semaphore::new(max_in_flight_bytes)),
+        }
+    }
+
+    /// Returns the capacity of the channel buffering the fetch messages, which is large enough
+    /// for the bytes in flight to be the limiting factor.
+    fn channel_capacity(&self) -> usize {
+        let batch_num_bytes = self.batch_num_bytes.max(1) as usize;
+        ((self.max_in_flight_bytes + batch_num_bytes - 1) / batch_num_bytes).max(3)
+    }
+
+    /// Number of permits held by a payload, capped so that a single payload larger than the
+    /// limit cannot block the stream forever.
+    fn num_permits(&self, fetch_payload: &FetchPayload) -> usize {
+        fetch_payload
+            .mrecord_batch
+            .as_ref()
+            .map(|mrecord_batch| mrecord_batch.mrecord_buffer.len())
+            .unwrap_or_default()
+            .min(self.max_in_flight_bytes)
+    }
+
+    fn num_in_flight_bytes(&self) -> usize {
+        self.max_in_flight_bytes
+            .saturating_sub(self.in_flight_bytes_semaphore.available_permits())
+    }
+
+    /// Waits until `num_permits` bytes can be pushed into the stream.
+    async fn acquire_permits(&self, num_permits: usize) {
+        if let Ok(permit) = self
+            .in_flight_bytes_semaphore
+            .acquire_many(num_permits as u32)
+            .await
+        {
+            permit.forget();
+            INGEST_V2_METRICS
+                .fetch_stream_in_flight_bytes
+                .add(num_permits as i64);
+        }
+    }
+
+    fn release_permits(&self, num_permits: usize) {
+        // Payloads pushed directly into the stream in tests do not hold any permits.
+        let num_permits = num_permits.min(self.num_in_flight_bytes());
+        self.in_flight_bytes_semaphore.add_permits(num_permits);
+        INGEST_V2_METRICS
+            .fetch_stream_in_flight_bytes
+            .sub(num_permits as i64);
+    }
+}
+
+impl Default for FetchStreamSettings {
+    fn default() -> Self {
+        Self::new(&FetchStreamConfig::default())
+    }
+}
+
 /// Combines multiple fetch streams originating from different ingesters into a single stream. It
 /// tolerates the failure of ingesters and automatically fails over to replica shards.
 pub struct MultiFetchStream {
@@ -239,6 +385,8 @@ pub struct MultiFetchStream {
     client_id: ClientId,
     ingester_pool: IngesterPool,
     retry_params: RetryParams,
+    fetch_stream_config: FetchStreamConfig,
+    fetch_stream_settings: FetchStreamSettings,
     fetch_task_handles: HashMap<QueueId, JoinHandle<()>>,
     fetch_message_rx: mpsc::Receiver<Result<FetchMessage, FetchStreamError>>,
     fetch_message_tx: mpsc::Sender<Result<FetchMessage, FetchStreamError>>,
@@ -250,13 +398,18 @@ impl MultiFetchStream {
         client_id: ClientId,
         ingester_pool: IngesterPool,
         retry_params: RetryParams,
+        fetch_stream_config: FetchStreamConfig,
     ) -> Self {
-        let (fetch_message_tx, fetch_message_rx) = mpsc::channel(3);
+        let fetch_stream_settings = FetchStreamSettings::new(&fetch_stream_config);
+        let (fetch_message_tx, fetch_message_rx) =
+            mpsc::channel(fetch_stream_settings.channel_capacity());
         Self {
             self_node_id,
             client_id,
             ingester_pool,
             retry_params,
+            fetch_stream_config,
+            fetch_stream_settings,
             fetch_task_handles: HashMap::new(),
             fetch_message_rx,
             fetch_message_tx,
@@ -305,6 +458,7 @@ impl MultiFetchStream {
             ingester_ids,
             self.ingester_pool.clone(),
             self.retry_params,
+            self.fetch_stream_settings.clone(),
             self.fetch_message_tx.clone(),
         );
         let fetch_task_handle = tokio::spawn(fetch_stream_future);
@@ -334,10 +488,20 @@ impl MultiFetchStream {
     pub async fn next(&mut self) -> Result<FetchMessage, FetchStreamError> {
         // Because we always hold a sender and never call `close()` on the receiver, the channel is
         // always open.
-        self.fetch_message_rx
+        let fetch_message_result = self
+            .fetch_message_rx
             .recv()
             .await
-            .expect("the channel should be open")
+            .expect("the channel should be open");
+
+        if let Ok(FetchMessage {
+            message: Some(fetch_message::Message::Payload(fetch_payload)),
+        }) = &fetch_message_result
+        {
+            let num_permits = self.fetch_stream_settings.num_permits(fetch_payload);
+            self.fetch_stream_settings.release_permits(num_permits);
+        }
+        fetch_message_result
     }
 
     /// Resets the stream by aborting all the active fetch tasks and dropping all queued responses.
@@ -348,7 +512,14 @@ impl MultiFetchStream {
         for (_queue_id, fetch_stream_handle) in self.fetch_task_handles.drain() {
             fetch_stream_handle.abort();
         }
-        let (fetch_message_tx, fetch_message_rx) = mpsc::channel(3);
+        // The queued responses are dropped along with the permits they hold.
+        INGEST_V2_METRICS
+            .fetch_stream_in_flight_bytes
+            .sub(self.fetch_stream_settings.num_in_flight_bytes() as i64);
+        self.fetch_stream_settings = FetchStreamSettings::new(&self.fetch_stream_config);
+
+        let (fetch_message_tx, fetch_message_rx) =
+            mpsc::channel(self.fetch_stream_settings.channel_capacity());
         self.fetch_message_tx = fetch_message_tx;
         self.fetch_message_rx = fetch_message_rx;
     }
@@ -393,6 +564,7 @@ async fn retrying_fetch_stream(
     ingester_ids: Vec<NodeId>,
     ingester_pool: IngesterPool,
     retry_params: RetryParams,
+    fetch_stream_settings: FetchStreamSettings,
     fetch_message_tx: mpsc::Sender<Result<FetchMessage, FetchStreamError>>,
 ) {
     for num_attempts in 1..=retry_params.max_attempts {
@@ -404,6 +576,7 @@ async fn retrying_fetch_stream(
             &mut from_position_exclusive,
             &ingester_ids,
             ingester_pool.clone(),
+            &fetch_stream_settings,
             fetch_message_tx.clone(),
         )
         .await;
@@ -427,6 +600,7 @@ async fn fault_tolerant_fetch_stream(
     from_position_exclusive: &mut Position,
     ingester_ids: &[NodeId],
     ingester_pool: IngesterPool,
+    fetch_stream_settings: &FetchStreamSettings,
     fetch_message_tx: mpsc::Sender<Result<FetchMessage, FetchStreamError>>,
 ) {
     // TODO: We can probably simplify this code by breaking it into smaller functions.
@@ -472,6 +646,8 @@ async fn fault_tolerant_fetch_stream(
             source_id: source_id.clone(),
             shard_id,
             from_position_exclusive: Some(from_position_exclusive.clone()),
+            batch_num_bytes: Some(fetch_stream_settings.batch_num_bytes),
+            compress_batches: fetch_stream_settings.compress_batches,
         };
         let mut fetch_stream = match ingester.open_fetch_stream(open_fetch_stream_request).await {
             Ok(fetch_stream) => fetch_stream,
@@ -526,10 +702,34 @@ async fn fault_tolerant_fetch_stream(
         };
         while let Some(fetch_message_result) = fetch_stream.next().await {
             match fetch_message_result {
-                Ok(fetch_message) => match &fetch_message.message {
-                    Some(fetch_message::Message::Payload(fetch_payload)) => {
+                Ok(fetch_message) => match fetch_message.message {
+                    Some(fetch_message::Message::Payload(mut fetch_payload)) => {
                         let to_position_inclusive = fetch_payload.to_position_inclusive();
 
+                        if let Err(ingest_error) = decompress_fetch_payload(&mut fetch_payload) {
+                            error!(
+                                client_id=%client_id,
+                                index_uid=%index_uid,
+                                source_id=%source_id,
+                                shard_id=%shard_id,
+                                error=%ingest_error,
+                                "failed to decompress records fetched from ingester `{ingester_id}`: closing fetch stream"
+                            );
+                            let fetch_stream_error = FetchStreamError {
+                                index_uid,
+                                source_id,
+                                shard_id,
+                                ingest_error,
+                            };
+                            let _ = fetch_message_tx.send(Err(fetch_stream_error)).await;
+                            return;
+                        }
+                        let num_permits = fetch_stream_settings.num_permits(&fetch_payload);
+                        fetch_stream_settings.acquire_permits(num_permits).await;
+
+                        let fetch_message = FetchMessage {
+                            message: Some(fetch_message::Message::Payload(fetch_payload)),
+                        };
                         if fetch_message_tx.send(Ok(fetch_message)).await.is_err() {
                             // The consumer was dropped.
                             return;
@@ -538,7 +738,9 @@ async fn fault_tolerant_fetch_stream(
                     }
                     Some(fetch_message::Message::Eof(fetch_eof)) => {
                         let eof_position = fetch_eof.eof_position();
-
+                        let fetch_message = FetchMessage {
+                            message: Some(fetch_message::Message::Eof(fetch_eof)),
+                        };
                         // We ignore the send error if the consumer was dropped because we're going
                         // to return anyway.
                         let _ = fetch_message_tx.send(Ok(fetch_message)).await;
@@ -586,11 +788,40 @@ async fn fault_tolerant_fetch_stream(
     }
 }
 
+/// Decompresses the record batch of a payload compressed by the ingester.
+fn decompress_fetch_payload(fetch_payload: &mut FetchPayload) -> IngestV2Result<()> {
+    if !fetch_payload.is_compressed {
+        return Ok(());
+    }
+    let Some(mrecord_batch) = &mut fetch_payload.mrecord_batch else {
+        return Ok(());
+    };
+    let num_bytes = mrecord_batch
+        .mrecord_lengths
+        .iter()
+        .map(|mrecord_length| *mrecord_length as usize)
+        .sum();
+    let mrecord_buffer =
+        zstd::bulk::decompress(&mrecord_batch.mrecord_buffer, num_bytes).map_err(|error| {
+            IngestV2Error::Internal(format!("failed to decompress record batch: {error}"))
+        })?;
+    if mrecord_buffer.len() != num_bytes {
+        return Err(IngestV2Error::Internal(format!(
+            "decompressed record batch is {} bytes long, expected {num_bytes} bytes",
+            mrecord_buffer.len()
+        )));
+    }
+    mrecord_batch.mrecord_buffer = Bytes::from(mrecord_buffer);
+    fetch_payload.is_compressed = false;
+    Ok(())
+}
+
 #[cfg(test)]
 pub(super) mod tests {
     use std::time::Duration;
 
     use bytes::Bytes;
+    use bytesize::ByteSize;
     use mrecordlog::MultiRecordLog;
     use quickwit_proto::ingest::ingester::{
         IngesterServiceClient, IngesterStatus, ObservationMessage,
@@ -600,6 +831,8 @@ pub(super) mod tests {
     use tokio::time::timeout;
 
     use super::*;
+    use crate::ingest_v2::mrecordlog_utils::DiskWatermark;
+    use crate::ingest_v2::persisted_batches::PersistedBatches;
     use crate::MRecord;
 
     pub fn into_fetch_payload(fetch_message: FetchMessage) -> FetchPayload {
@@ -629,6 +862,8 @@ pub(super) mod tests {
             source_id: source_id.clone(),
             shard_id: 1,
             from_position_exclusive: None,
+            batch_num_bytes: None,
+            compress_batches: false,
         };
         let (observation_tx, _observation_rx) = watch::channel(Ok(ObservationMessage::default()));
         let state = Arc::new(RwLock::new(IngesterState {
@@ -639,6 +874,8 @@ pub(super) mod tests {
             replication_tasks: HashMap::new(),
             status: IngesterStatus::Ready,
             observation_tx,
+            disk_watermark: DiskWatermark::default(),
+            persisted_batches: PersistedBatches::default(),
         }));
         let (shard_status_tx, shard_status_rx) = watch::channel(ShardStatus::default());
         let (mut fetch_stream, fetch_task_handle) = FetchStreamTask::spawn(
@@ -820,6 +1057,8 @@ pub(super) mod tests {
             source_id: source_id.clone(),
             shard_id: 1,
             from_position_exclusive: None,
+            batch_num_bytes: None,
+            compress_batches: false,
         };
         let (observation_tx, _observation_rx) = watch::channel(Ok(ObservationMessage::default()));
         let state = Arc::new(RwLock::new(IngesterState {
@@ -830,6 +1069,8 @@ pub(super) mod tests {
             replication_tasks: HashMap::new(),
             status: IngesterStatus::Ready,
             observation_tx,
+            disk_watermark: DiskWatermark::default(),
+            persisted_batches: PersistedBatches::default(),
         }));
         let (shard_status_tx, shard_status_rx) = watch::channel(ShardStatus::default());
         let (mut fetch_stream, fetch_task_handle) = FetchStreamTask::spawn(
@@ -884,6 +1125,8 @@ pub(super) mod tests {
             source_id: source_id.clone(),
             shard_id: 1,
             from_position_exclusive: Some(Position::offset(0u64)),
+            batch_num_bytes: None,
+            compress_batches: false,
         };
         let (observation_tx, _observation_rx) = watch::channel(Ok(ObservationMessage::default()));
         let state = Arc::new(RwLock::new(IngesterState {
@@ -894,6 +1137,8 @@ pub(super) mod tests {
             replication_tasks: HashMap::new(),
             status: IngesterStatus::Ready,
             observation_tx,
+            disk_watermark: DiskWatermark::default(),
+            persisted_batches: PersistedBatches::default(),
         }));
         let (shard_status_tx, shard_status_rx) = watch::channel(ShardStatus::default());
         let (mut fetch_stream, _fetch_task_handle) = FetchStreamTask::spawn(
@@ -990,6 +1235,8 @@ pub(super) mod tests {
             source_id: source_id.clone(),
             shard_id: 1,
             from_position_exclusive: None,
+            batch_num_bytes: None,
+            compress_batches: false,
         };
         let (observation_tx, _observation_rx) = watch::channel(Ok(ObservationMessage::default()));
         let state = Arc::new(RwLock::new(IngesterState {
@@ -1000,6 +1247,8 @@ pub(super) mod tests {
             replication_tasks: HashMap::new(),
             status: IngesterStatus::Ready,
             observation_tx,
+            disk_watermark: DiskWatermark::default(),
+            persisted_batches: PersistedBatches::default(),
         }));
         let (_shard_status_tx, shard_status_rx) = watch::channel(ShardStatus::default());
         let (mut fetch_stream, fetch_task_handle) = FetchStreamTask::spawn(
@@ -1031,6 +1280,8 @@ pub(super) mod tests {
             source_id: source_id.clone(),
             shard_id: 1,
             from_position_exclusive: None,
+            batch_num_bytes: None,
+            compress_batches: false,
         };
         let (observation_tx, _observation_rx) = watch::channel(Ok(ObservationMessage::default()));
         let state = Arc::new(RwLock::new(IngesterState {
@@ -1041,6 +1292,8 @@ pub(super) mod tests {
             replication_tasks: HashMap::new(),
             status: IngesterStatus::Ready,
             observation_tx,
+            disk_watermark: DiskWatermark::default(),
+            persisted_batches: PersistedBatches::default(),
         }));
         let (shard_status_tx, shard_status_rx) = watch::channel(ShardStatus::default());
         let (mut fetch_stream, _fetch_task_handle) = FetchStreamTask::spawn(
@@ -1117,6 +1370,163 @@ pub(super) mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_fetch_task_compress_batches() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let mrecordlog = MultiRecordLog::open(tempdir.path()).await.unwrap();
+        let index_uid = "test-index:0".to_string();
+        let source_id = "test-source".to_string();
+        let open_fetch_stream_request = OpenFetchStreamRequest {
+            client_id: "test-client".to_string(),
+            index_uid: index_uid.clone(),
+            source_id: source_id.clone(),
+            shard_id: 1,
+            from_position_exclusive: None,
+            batch_num_bytes: None,
+            compress_batches: true,
+        };
+        let (observation_tx, _observation_rx) = watch::channel(Ok(ObservationMessage::default()));
+        let state = Arc::new(RwLock::new(IngesterState {
+            mrecordlog,
+            shards: HashMap::new(),
+            rate_trackers: HashMap::new(),
+            replication_streams: HashMap::new(),
+            replication_tasks: HashMap::new(),
+            status: IngesterStatus::Ready,
+            observation_tx,
+            disk_watermark: DiskWatermark::default(),
+            persisted_batches: PersistedBatches::default(),
+        }));
+        let (shard_status_tx, shard_status_rx) = watch::channel(ShardStatus::default());
+        let (mut fetch_stream, _fetch_task_handle) = FetchStreamTask::spawn(
+            open_fetch_stream_request,
+            state.clone(),
+            shard_status_rx,
+            1024,
+        );
+        let queue_id = queue_id(&index_uid, &source_id, 1);
+
+        let mut state_guard = state.write().await;
+
+        state_guard
+            .mrecordlog
+            .create_queue(&queue_id)
+            .await
+            .unwrap();
+
+        let records = [
+            Bytes::from_static(b"test-doc-foo"),
+            Bytes::from_static(b"test-doc-bar"),
+        ]
+        .into_iter();
+
+        state_guard
+            .mrecordlog
+            .append_records(&queue_id, None, records)
+            .await
+            .unwrap();
+        drop(state_guard);
+
+        let shard_status = (ShardState::Open, Position::offset(1u64));
+        shard_status_tx.send(shard_status).unwrap();
+
+        let fetch_message = timeout(Duration::from_millis(100), fetch_stream.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        let mut fetch_payload = into_fetch_payload(fetch_message);
+
+        assert!(fetch_payload.is_compressed);
+        assert_ne!(
+            fetch_payload.mrecord_batch.as_ref().unwrap().mrecord_buffer,
+            "test-doc-footest-doc-bar"
+        );
+        decompress_fetch_payload(&mut fetch_payload).unwrap();
+
+        assert!(!fetch_payload.is_compressed);
+        assert_eq!(
+            fetch_payload
+                .mrecord_batch
+                .as_ref()
+                .unwrap()
+                .mrecord_lengths,
+            [12, 12]
+        );
+        assert_eq!(
+            fetch_payload.mrecord_batch.as_ref().unwrap().mrecord_buffer,
+            "test-doc-footest-doc-bar"
+        );
+    }
+
+    #[test]
+    fn test_num_records_between() {
+        assert_eq!(
+            num_records_between(&Position::Beginning, &Position::Beginning),
+            0
+        );
+        assert_eq!(
+            num_records_between(&Position::Beginning, &Position::offset(2u64)),
+            3
+        );
+        assert_eq!(
+            num_records_between(&Position::offset(2u64), &Position::offset(2u64)),
+            0
+        );
+        assert_eq!(
+            num_records_between(&Position::offset(2u64), &Position::offset(5u64)),
+            3
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_stream_settings_flow_control() {
+        let fetch_stream_config = FetchStreamConfig {
+            max_in_flight_bytes: ByteSize(20),
+            batch_num_bytes: ByteSize(10),
+            decompression: FetchDecompression::Indexer,
+        };
+        let fetch_stream_settings = FetchStreamSettings::new(&fetch_stream_config);
+        assert_eq!(fetch_stream_settings.batch_num_bytes, 10);
+        assert!(fetch_stream_settings.compress_batches);
+        assert_eq!(fetch_stream_settings.channel_capacity(), 3);
+
+        let fetch_payload = FetchPayload {
+            index_uid: "test-index:0".to_string(),
+            source_id: "test-source".to_string(),
+            shard_id: 1,
+            mrecord_batch: MRecordBatch::for_test(["\0\0test-doc-foo"]),
+            from_position_exclusive: Some(Position::offset(0u64)),
+            to_position_inclusive: Some(Position::offset(1u64)),
+            is_compressed: false,
+        };
+        let num_permits = fetch_stream_settings.num_permits(&fetch_payload);
+        assert_eq!(num_permits, 14);
+
+        fetch_stream_settings.acquire_permits(num_permits).await;
+        assert_eq!(fetch_stream_settings.num_in_flight_bytes(), 14);
+
+        // The second payload does not fit until the first one is consumed.
+        timeout(
+            Duration::from_millis(50),
+            fetch_stream_settings.acquire_permits(num_permits),
+        )
+        .await
+        .unwrap_err();
+
+        fetch_stream_settings.release_permits(num_permits);
+        assert_eq!(fetch_stream_settings.num_in_flight_bytes(), 0);
+
+        // Releasing permits that were never acquired is a no-op.
+        fetch_stream_settings.release_permits(num_permits);
+        assert_eq!(
+            fetch_stream_settings
+                .in_flight_bytes_semaphore
+                .available_permits(),
+            20
+        );
+    }
+
     #[test]
     fn test_select_preferred_and_failover_ingesters() {
         let self_node_id: NodeId = "test-ingester-0".into();
@@ -1180,6 +1590,7 @@ pub(super) mod tests {
             mrecord_batch: MRecordBatch::for_test(["\0\0test-doc-foo"]),
             from_position_exclusive: Some(Position::offset(0u64)),
             to_position_inclusive: Some(Position::offset(1u64)),
+            is_compressed: false,
         };
         let fetch_message = FetchMessage::new_payload(fetch_payload);
         service_stream_tx_1.send(Ok(fetch_message)).unwrap();
@@ -1201,6 +1612,7 @@ pub(super) mod tests {
             &mut from_position_exclusive,
             &ingester_ids,
             ingester_pool,
+            &FetchStreamSettings::default(),
             fetch_message_tx,
         )
         .await;
@@ -1290,6 +1702,7 @@ pub(super) mod tests {
             mrecord_batch: MRecordBatch::for_test(["\0\0test-doc-foo"]),
             from_position_exclusive: Some(Position::offset(0u64)),
             to_position_inclusive: Some(Position::offset(1u64)),
+            is_compressed: false,
         };
         let fetch_message = FetchMessage::new_payload(fetch_payload);
         service_stream_tx_1.send(Ok(fetch_message)).unwrap();
@@ -1311,6 +1724,7 @@ pub(super) mod tests {
             &mut from_position_exclusive,
             &ingester_ids,
             ingester_pool,
+            &FetchStreamSettings::default(),
             fetch_message_tx,
         )
         .await;
@@ -1399,6 +1813,7 @@ pub(super) mod tests {
             mrecord_batch: MRecordBatch::for_test(["\0\0test-doc-foo"]),
             from_position_exclusive: Some(Position::offset(0u64)),
             to_position_inclusive: Some(Position::offset(1u64)),
+            is_compressed: false,
         };
         let fetch_message = FetchMessage::new_payload(fetch_payload);
         service_stream_tx_0.send(Ok(fetch_message)).unwrap();
@@ -1423,6 +1838,7 @@ pub(super) mod tests {
             &mut from_position_exclusive,
             &ingester_ids,
             ingester_pool,
+            &FetchStreamSettings::default(),
             fetch_message_tx,
         )
         .await;
@@ -1494,6 +1910,7 @@ pub(super) mod tests {
             &mut from_position_exclusive,
             &ingester_ids,
             ingester_pool,
+            &FetchStreamSettings::default(),
             fetch_message_tx,
         )
         .await;
@@ -1579,6 +1996,7 @@ pub(super) mod tests {
             mrecord_batch: MRecordBatch::for_test(["\0\0test-doc-foo"]),
             from_position_exclusive: Some(Position::offset(0u64)),
             to_position_inclusive: Some(Position::offset(1u64)),
+            is_compressed: false,
         };
         let fetch_message = FetchMessage::new_payload(fetch_payload);
         service_stream_tx_1.send(Ok(fetch_message)).unwrap();
@@ -1593,6 +2011,7 @@ pub(super) mod tests {
             mrecord_batch: MRecordBatch::for_test(["\0\0test-doc-bar"]),
             from_position_exclusive: Some(Position::offset(1u64)),
             to_position_inclusive: Some(Position::offset(2u64)),
+            is_compressed: false,
         };
         let fetch_message = FetchMessage::new_payload(fetch_payload);
         service_stream_tx_2.send(Ok(fetch_message)).unwrap();
@@ -1609,6 +2028,7 @@ pub(super) mod tests {
             ingester_ids,
             ingester_pool,
             retry_params,
+            FetchStreamSettings::default(),
             fetch_message_tx,
         )
         .await;
@@ -1685,8 +2105,13 @@ pub(super) mod tests {
         let client_id = "test-client".to_string();
         let ingester_pool = IngesterPool::default();
         let retry_params = RetryParams::for_test();
-        let _multi_fetch_stream =
-            MultiFetchStream::new(self_node_id, client_id, ingester_pool, retry_params);
+        let _multi_fetch_stream = MultiFetchStream::new(
+            self_node_id,
+            client_id,
+            ingester_pool,
+            retry_params,
+            FetchStreamConfig::default(),
+        );
         // TODO: Backport from original branch.
     }
 }
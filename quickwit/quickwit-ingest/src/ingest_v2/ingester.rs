@@ -23,7 +23,7 @@ use std::fmt;
 use std::iter::once;
 use std::path::Path;
 use std::sync::{Arc, Weak};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
 use bytesize::ByteSize;
@@ -48,15 +48,19 @@ use quickwit_proto::ingest::ingester::{
     TruncateShardsRequest, TruncateShardsResponse,
 };
 use quickwit_proto::ingest::{CommitTypeV2, IngestV2Error, IngestV2Result, Shard, ShardState};
-use quickwit_proto::types::{queue_id, NodeId, Position, QueueId};
+use quickwit_proto::types::{queue_id, NodeId, Position, QueueId, SubrequestId};
 use tokio::sync::{watch, RwLock};
+use tokio::task::JoinHandle;
 use tracing::{debug, error, info, warn};
 
 use super::fetch::FetchStreamTask;
 use super::metrics::INGEST_V2_METRICS;
 use super::models::IngesterShard;
 use super::mrecord::MRecord;
-use super::mrecordlog_utils::{check_enough_capacity, force_delete_queue};
+use super::mrecordlog_utils::{
+    capacity_for_priority, check_enough_capacity, force_delete_queue, DiskWatermark,
+};
+use super::persisted_batches::PersistedBatches;
 use super::rate_meter::RateMeter;
 use super::replication::{
     ReplicationClient, ReplicationStreamTask, ReplicationStreamTaskHandle, ReplicationTask,
@@ -76,6 +80,13 @@ pub(super) const PERSIST_REQUEST_TIMEOUT: Duration = if cfg!(any(test, feature =
     Duration::from_secs(6)
 };
 
+/// Period at which the ingester looks for idle shards to close.
+const CLOSE_IDLE_SHARDS_INTERVAL: Duration = if cfg!(test) {
+    Duration::from_millis(50)
+} else {
+    Duration::from_secs(60)
+};
+
 #[derive(Clone)]
 pub struct Ingester {
     self_node_id: NodeId,
@@ -106,17 +117,24 @@ pub(super) struct IngesterState {
     pub replication_tasks: HashMap<LeaderId, ReplicationTaskHandle>,
     pub status: IngesterStatus,
     pub observation_tx: watch::Sender<IngestV2Result<ObservationMessage>>,
+    pub disk_watermark: DiskWatermark,
+    // Batches recently persisted or replicated, used to deduplicate retried persist requests and
+    // to acknowledge hedged persist requests.
+    pub persisted_batches: PersistedBatches,
 }
 
 impl Ingester {
+    #[allow(clippy::too_many_arguments)]
     pub async fn try_new(
         cluster: Cluster,
         ingester_pool: Pool<NodeId, IngesterServiceClient>,
         wal_dir_path: &Path,
         disk_capacity: ByteSize,
         memory_capacity: ByteSize,
+        disk_watermark: DiskWatermark,
         rate_limiter_settings: RateLimiterSettings,
         replication_factor: usize,
+        shard_idle_timeout: Duration,
     ) -> IngestV2Result<Self> {
         let self_node_id: NodeId = cluster.self_node_id().into();
         let mrecordlog = MultiRecordLog::open_with_prefs(
@@ -145,6 +163,8 @@ impl Ingester {
             replication_tasks: HashMap::new(),
             status: IngesterStatus::Ready,
             observation_tx,
+            disk_watermark,
+            persisted_batches: PersistedBatches::default(),
         };
         let ingester = Self {
             self_node_id,
@@ -164,7 +184,8 @@ impl Ingester {
         ingester.init().await?;
 
         let weak_state = Arc::downgrade(&ingester.state);
-        BroadcastLocalShardsTask::spawn(cluster, weak_state);
+        BroadcastLocalShardsTask::spawn(cluster, weak_state.clone());
+        spawn_close_idle_shards_task(weak_state, shard_idle_timeout);
 
         Ok(ingester)
     }
@@ -259,6 +280,7 @@ impl Ingester {
         shard: Shard,
     ) -> IngestV2Result<()> {
         let queue_id = shard.queue_id();
+        let priority = shard.priority();
 
         let Entry::Vacant(entry) = state.shards.entry(queue_id.clone()) else {
             return Ok(());
@@ -310,7 +332,7 @@ impl Ingester {
         } else {
             IngesterShard::new_solo(ShardState::Open, Position::Beginning, Position::Beginning)
         };
-        entry.insert(primary_shard);
+        entry.insert(primary_shard.with_priority(priority));
         Ok(())
     }
 
@@ -385,14 +407,13 @@ impl Ingester {
         persist_request: PersistRequest,
     ) -> IngestV2Result<PersistResponse> {
         if persist_request.leader_id != self.self_node_id {
-            return Err(IngestV2Error::Internal(format!(
-                "routing error: expected leader ID `{}`, got `{}`",
-                self.self_node_id, persist_request.leader_id,
-            )));
+            return self.persist_hedged(persist_request).await;
         }
         let mut persist_successes = Vec::with_capacity(persist_request.subrequests.len());
         let mut persist_failures = Vec::new();
         let mut replicate_subrequests: HashMap<NodeId, Vec<ReplicateSubrequest>> = HashMap::new();
+        // Batch UIDs of the subrequests being replicated, recorded once the replication succeeds.
+        let mut replicated_batch_uids: HashMap<SubrequestId, String> = HashMap::new();
 
         let commit_type = persist_request.commit_type();
         let force_commit = commit_type == CommitTypeV2::Force;
@@ -420,7 +441,39 @@ impl Ingester {
             };
             return Ok(persist_response);
         }
+        state_guard.check_disk_watermark();
+
+        if state_guard.disk_watermark.is_exceeded() {
+            persist_failures.reserve_exact(persist_request.subrequests.len());
+
+            for subrequest in persist_request.subrequests {
+                let persist_failure = PersistFailure {
+                    subrequest_id: subrequest.subrequest_id,
+                    index_uid: subrequest.index_uid,
+                    source_id: subrequest.source_id,
+                    shard_id: subrequest.shard_id,
+                    reason: PersistFailureReason::DiskWatermarkExceeded as i32,
+                };
+                persist_failures.push(persist_failure);
+            }
+            let persist_response = PersistResponse {
+                leader_id: leader_id.into(),
+                successes: Vec::new(),
+                failures: persist_failures,
+            };
+            return Ok(persist_response);
+        }
         for subrequest in persist_request.subrequests {
+            if let Some(batch_uid) = &subrequest.batch_uid {
+                if let Some(persist_success) = state_guard
+                    .persisted_batches
+                    .get(batch_uid, subrequest.subrequest_id)
+                {
+                    debug!("batch `{batch_uid}` was already persisted");
+                    persist_successes.push(persist_success);
+                    continue;
+                }
+            }
             let queue_id = subrequest.queue_id();
 
             let Some(shard) = state_guard.shards.get_mut(&queue_id) else {
@@ -447,6 +500,7 @@ impl Ingester {
             }
             let follower_id_opt = shard.follower_id_opt().cloned();
             let from_position_exclusive = shard.replication_position_inclusive.clone();
+            let priority = shard.priority;
 
             let doc_batch = match subrequest.doc_batch {
                 Some(doc_batch) if !doc_batch.is_empty() => doc_batch,
@@ -468,10 +522,15 @@ impl Ingester {
             };
             let requested_capacity = estimate_size(&doc_batch);
 
+            // The shards of backfill sources can only use a fraction of the capacity of the WAL so
+            // that they never starve the shards of realtime sources.
+            let (disk_capacity, memory_capacity) =
+                capacity_for_priority(self.disk_capacity, self.memory_capacity, priority);
+
             let current_usage = match check_enough_capacity(
                 &state_guard.mrecordlog,
-                self.disk_capacity,
-                self.memory_capacity,
+                disk_capacity,
+                memory_capacity,
                 requested_capacity,
             ) {
                 Ok(usage) => usage,
@@ -480,6 +539,11 @@ impl Ingester {
                         "failed to persist records to ingester `{}`: {error}",
                         self.self_node_id
                     );
+                    INGEST_V2_METRICS
+                        .persist_rejections_total
+                        .with_label_values([priority.as_json_str_name(), "resource_exhausted"])
+                        .inc();
+
                     let persist_failure = PersistFailure {
                         subrequest_id: subrequest.subrequest_id,
                         index_uid: subrequest.index_uid,
@@ -498,6 +562,10 @@ impl Ingester {
 
             if !rate_limiter.acquire_bytes(requested_capacity) {
                 debug!("failed to persist records to shard `{queue_id}`: rate limited");
+                INGEST_V2_METRICS
+                    .persist_rejections_total
+                    .with_label_values([priority.as_json_str_name(), "rate_limited"])
+                    .inc();
 
                 let persist_failure = PersistFailure {
                     subrequest_id: subrequest.subrequest_id,
@@ -513,6 +581,10 @@ impl Ingester {
             let batch_num_docs = doc_batch.num_docs() as u64;
 
             rate_meter.update(batch_num_bytes);
+            INGEST_V2_METRICS
+                .persisted_bytes_total
+                .with_label_values([priority.as_json_str_name()])
+                .inc_by(batch_num_bytes);
 
             let current_position_inclusive: Position = if force_commit {
                 let encoded_mrecords = doc_batch
@@ -548,8 +620,9 @@ impl Ingester {
                 .wal_memory_usage_bytes
                 .set(new_memory_usage.as_u64() as i64);
 
-            INGEST_METRICS.ingested_num_bytes.inc_by(batch_num_bytes);
-            INGEST_METRICS.ingested_num_docs.inc_by(batch_num_docs);
+            // The index UID is formatted as `{index_id}:{incarnation_id}`.
+            let index_id = subrequest.index_uid.split(':').next().unwrap();
+            INGEST_METRICS.record_ingested_docs(index_id, batch_num_docs, batch_num_bytes);
 
             state_guard
                 .shards
@@ -558,6 +631,9 @@ impl Ingester {
                 .set_replication_position_inclusive(current_position_inclusive.clone());
 
             if let Some(follower_id) = follower_id_opt {
+                if let Some(batch_uid) = &subrequest.batch_uid {
+                    replicated_batch_uids.insert(subrequest.subrequest_id, batch_uid.clone());
+                }
                 let replicate_subrequest = ReplicateSubrequest {
                     subrequest_id: subrequest.subrequest_id,
                     index_uid: subrequest.index_uid,
@@ -566,6 +642,7 @@ impl Ingester {
                     from_position_exclusive: Some(from_position_exclusive),
                     to_position_inclusive: Some(current_position_inclusive),
                     doc_batch: Some(doc_batch),
+                    batch_uid: subrequest.batch_uid,
                 };
                 replicate_subrequests
                     .entry(follower_id)
@@ -579,6 +656,11 @@ impl Ingester {
                     shard_id: subrequest.shard_id,
                     replication_position_inclusive: Some(current_position_inclusive),
                 };
+                if let Some(batch_uid) = subrequest.batch_uid {
+                    state_guard
+                        .persisted_batches
+                        .insert(batch_uid, &persist_success);
+                }
                 persist_successes.push(persist_success);
             }
         }
@@ -652,6 +734,19 @@ impl Ingester {
                 persist_failures.push(persist_failure);
             }
         }
+        if !replicated_batch_uids.is_empty() {
+            let mut state_guard = with_lock_metrics!(self.state.write().await, "persist", "write");
+
+            for persist_success in &persist_successes {
+                if let Some(batch_uid) =
+                    replicated_batch_uids.remove(&persist_success.subrequest_id)
+                {
+                    state_guard
+                        .persisted_batches
+                        .insert(batch_uid, persist_success);
+                }
+            }
+        }
         let leader_id = self.self_node_id.to_string();
         let persist_response = PersistResponse {
             leader_id,
@@ -661,6 +756,74 @@ impl Ingester {
         Ok(persist_response)
     }
 
+    /// Handles a hedged persist request, which the router sends to the follower of the shards of a
+    /// persist request that is slow to complete. The follower does not persist the batches itself,
+    /// which could index them twice: it waits until it has replicated them from the leader and
+    /// returns their positions.
+    async fn persist_hedged(
+        &mut self,
+        persist_request: PersistRequest,
+    ) -> IngestV2Result<PersistResponse> {
+        let leader_id: NodeId = persist_request.leader_id.clone().into();
+        let deadline = tokio::time::Instant::now() + PERSIST_REQUEST_TIMEOUT;
+
+        loop {
+            let mut state_guard =
+                with_lock_metrics!(self.state.write().await, "persist_hedged", "write");
+            let mut persist_successes = Vec::with_capacity(persist_request.subrequests.len());
+
+            for subrequest in &persist_request.subrequests {
+                let queue_id = subrequest.queue_id();
+                let is_replica_of_leader = state_guard
+                    .shards
+                    .get(&queue_id)
+                    .and_then(|shard| shard.leader_id_opt())
+                    .is_some_and(|shard_leader_id| *shard_leader_id == leader_id);
+
+                if !is_replica_of_leader {
+                    return Err(IngestV2Error::Internal(format!(
+                        "routing error: ingester `{}` does not replicate shard `{queue_id}` led \
+                         by `{leader_id}`",
+                        self.self_node_id
+                    )));
+                }
+                let Some(batch_uid) = &subrequest.batch_uid else {
+                    return Err(IngestV2Error::Internal(
+                        "hedged persist subrequest is missing its batch UID".to_string(),
+                    ));
+                };
+                if let Some(persist_success) = state_guard
+                    .persisted_batches
+                    .get(batch_uid, subrequest.subrequest_id)
+                {
+                    persist_successes.push(persist_success);
+                }
+            }
+            if persist_successes.len() == persist_request.subrequests.len() {
+                let persist_response = PersistResponse {
+                    leader_id: leader_id.into(),
+                    successes: persist_successes,
+                    failures: Vec::new(),
+                };
+                return Ok(persist_response);
+            }
+            let batch_inserted = state_guard.persisted_batches.inserted();
+            let batch_inserted_notified = batch_inserted.notified();
+            tokio::pin!(batch_inserted_notified);
+            // Registers the waiter before releasing the lock so that no insertion is missed.
+            batch_inserted_notified.as_mut().enable();
+            drop(state_guard);
+
+            if tokio::time::timeout_at(deadline, batch_inserted_notified)
+                .await
+                .is_err()
+            {
+                // The router keeps waiting for the original request.
+                return Err(IngestV2Error::Timeout);
+            }
+        }
+    }
+
     /// Opens a replication stream, which is a bi-directional gRPC stream. The client-side stream
     async fn open_replication_stream_inner(
         &mut self,
@@ -729,11 +892,17 @@ impl Ingester {
             })?
             .shard_status_rx
             .clone();
+        let batch_num_bytes = open_fetch_stream_request
+            .batch_num_bytes
+            .map(|batch_num_bytes| {
+                (batch_num_bytes as usize).clamp(1, FetchStreamTask::MAX_BATCH_NUM_BYTES)
+            })
+            .unwrap_or(FetchStreamTask::DEFAULT_BATCH_NUM_BYTES);
         let (service_stream, _fetch_task_handle) = FetchStreamTask::spawn(
             open_fetch_stream_request,
             self.state.clone(),
             shard_status_rx,
-            FetchStreamTask::DEFAULT_BATCH_NUM_BYTES,
+            batch_num_bytes,
         );
         Ok(service_stream)
     }
@@ -797,6 +966,7 @@ impl Ingester {
             .wal_memory_usage_bytes
             .set(current_memory_usage as i64);
 
+        state_guard.check_disk_watermark();
         self.check_decommissioning_status(&mut state_guard);
         let truncate_response = TruncateShardsResponse {};
         Ok(truncate_response)
@@ -825,6 +995,11 @@ impl Ingester {
         if state_guard.status != IngesterStatus::Ready {
             return Err(IngestV2Error::Internal("node decommissioned".to_string()));
         }
+        if state_guard.disk_watermark.is_exceeded() {
+            return Err(IngestV2Error::Internal(
+                "write-ahead log disk usage exceeds high watermark".to_string(),
+            ));
+        }
         if ping_request.leader_id != self.self_node_id {
             let ping_response = PingResponse {};
             return Ok(ping_response);
@@ -1014,6 +1189,45 @@ impl IngesterState {
         };
     }
 
+    /// Updates the state of the disk watermark from the current disk usage of the WAL. When the
+    /// high watermark is exceeded, the open primary shards are closed: the indexers consume them
+    /// up to their end, after which their queues are deleted, which frees up disk space faster
+    /// than waiting for the queues to be truncated.
+    pub(super) fn check_disk_watermark(&mut self) {
+        let disk_usage = ByteSize(self.mrecordlog.disk_usage() as u64);
+
+        if !self.disk_watermark.update(disk_usage) {
+            return;
+        }
+        if !self.disk_watermark.is_exceeded() {
+            info!("write-ahead log disk usage ({disk_usage}) fell below low watermark");
+            return;
+        }
+        warn!(
+            "write-ahead log disk usage ({disk_usage}) exceeds high watermark: closing primary \
+             shards"
+        );
+        for shard in self.shards.values_mut() {
+            if !shard.is_replica() && shard.shard_state.is_open() {
+                shard.shard_state = ShardState::Closed;
+                shard.notify_shard_status();
+            }
+        }
+    }
+
+    /// Closes the open primary shards that have not been written to for `shard_idle_timeout`. Like
+    /// any closed shard, they are consumed up to their end by the indexers, after which their
+    /// queues are deleted, while the routers open new shards on the next write to their source.
+    pub(super) fn close_idle_shards(&mut self, shard_idle_timeout: Duration, now: Instant) {
+        for (queue_id, shard) in self.shards.iter_mut() {
+            if shard.is_idle(shard_idle_timeout, now) {
+                info!("closing shard `{queue_id}` idle for {shard_idle_timeout:?}");
+                shard.shard_state = ShardState::Closed;
+                shard.notify_shard_status();
+            }
+        }
+    }
+
     /// Deletes the shard identified by `queue_id` from the ingester state. It removes the
     /// mrecordlog queue first and then, if the operation is successful, removes the shard.
     async fn delete_shard(&mut self, queue_id: &QueueId) {
@@ -1034,6 +1248,30 @@ impl IngesterState {
     }
 }
 
+/// Spawns a task that periodically closes the shards idle for `shard_idle_timeout`. The task stops
+/// when the ingester state is dropped.
+fn spawn_close_idle_shards_task(
+    weak_state: Weak<RwLock<IngesterState>>,
+    shard_idle_timeout: Duration,
+) -> JoinHandle<()> {
+    let future = async move {
+        let mut interval = tokio::time::interval(CLOSE_IDLE_SHARDS_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            let Some(state) = weak_state.upgrade() else {
+                debug!("stopping close idle shards task");
+                return;
+            };
+            let mut state_guard =
+                with_lock_metrics!(state.write().await, "close_idle_shards", "write");
+            state_guard.close_idle_shards(shard_idle_timeout, Instant::now());
+        }
+    };
+    tokio::spawn(future)
+}
+
 struct WeakIngesterState(Weak<RwLock<IngesterState>>);
 
 #[async_trait]
@@ -1056,6 +1294,7 @@ impl EventSubscriber<ShardPositionsUpdate> for WeakIngesterState {
                 state_guard.truncate_shard(&queue_id, shard_position).await;
             }
         }
+        state_guard.check_disk_watermark();
     }
 }
 
@@ -1106,7 +1345,7 @@ mod tests {
         IngesterServiceGrpcServer, IngesterServiceGrpcServerAdapter, PersistSubrequest,
         TruncateShardsSubrequest,
     };
-    use quickwit_proto::ingest::{DocBatchV2, ShardIds};
+    use quickwit_proto::ingest::{DocBatchV2, ShardIds, ShardPriority};
     use quickwit_proto::types::{queue_id, SourceUid};
     use tokio::task::yield_now;
     use tokio::time::timeout;
@@ -1122,8 +1361,10 @@ mod tests {
         ingester_pool: IngesterPool,
         disk_capacity: ByteSize,
         memory_capacity: ByteSize,
+        disk_watermark: DiskWatermark,
         rate_limiter_settings: RateLimiterSettings,
         replication_factor: usize,
+        shard_idle_timeout: Duration,
     }
 
     impl Default for IngesterForTest {
@@ -1133,8 +1374,10 @@ mod tests {
                 ingester_pool: IngesterPool::default(),
                 disk_capacity: ByteSize::mb(256),
                 memory_capacity: ByteSize::mb(1),
+                disk_watermark: DiskWatermark::default(),
                 rate_limiter_settings: RateLimiterSettings::default(),
                 replication_factor: 1,
+                shard_idle_timeout: Duration::from_secs(1_800),
             }
         }
     }
@@ -1155,6 +1398,16 @@ mod tests {
             self
         }
 
+        pub fn with_memory_capacity(mut self, memory_capacity: ByteSize) -> Self {
+            self.memory_capacity = memory_capacity;
+            self
+        }
+
+        pub fn with_disk_watermark(mut self, disk_watermark: DiskWatermark) -> Self {
+            self.disk_watermark = disk_watermark;
+            self
+        }
+
         pub fn with_rate_limiter_settings(
             mut self,
             rate_limiter_settings: RateLimiterSettings,
@@ -1168,6 +1421,11 @@ mod tests {
             self
         }
 
+        pub fn with_shard_idle_timeout(mut self, shard_idle_timeout: Duration) -> Self {
+            self.shard_idle_timeout = shard_idle_timeout;
+            self
+        }
+
         pub async fn build(self) -> (IngesterContext, Ingester) {
             static GOSSIP_ADVERTISE_PORT_SEQUENCE: AtomicU16 = AtomicU16::new(1u16);
 
@@ -1196,8 +1454,10 @@ mod tests {
                 wal_dir_path,
                 self.disk_capacity,
                 self.memory_capacity,
+                self.disk_watermark,
                 self.rate_limiter_settings,
                 self.replication_factor,
+                self.shard_idle_timeout,
             )
             .await
             .unwrap();
@@ -1398,6 +1658,7 @@ mod tests {
                     source_id: "test-source".to_string(),
                     shard_id: 1,
                     doc_batch: Some(DocBatchV2::for_test(["test-doc-010"])),
+                    batch_uid: None,
                 },
                 PersistSubrequest {
                     subrequest_id: 1,
@@ -1405,6 +1666,7 @@ mod tests {
                     source_id: "test-source".to_string(),
                     shard_id: 1,
                     doc_batch: Some(DocBatchV2::for_test(["test-doc-110", "test-doc-111"])),
+                    batch_uid: None,
                 },
             ],
         };
@@ -1465,6 +1727,60 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_ingester_persist_deduplicates_batches() {
+        let (ingester_ctx, mut ingester) = IngesterForTest::default().build().await;
+
+        let init_shards_request = InitShardsRequest {
+            shards: vec![Shard {
+                index_uid: "test-index:0".to_string(),
+                source_id: "test-source".to_string(),
+                shard_id: 1,
+                shard_state: ShardState::Open as i32,
+                leader_id: ingester_ctx.node_id.to_string(),
+                ..Default::default()
+            }],
+        };
+        ingester.init_shards(init_shards_request).await.unwrap();
+
+        let persist_request = PersistRequest {
+            leader_id: ingester_ctx.node_id.to_string(),
+            commit_type: CommitTypeV2::Auto as i32,
+            subrequests: vec![PersistSubrequest {
+                subrequest_id: 0,
+                index_uid: "test-index:0".to_string(),
+                source_id: "test-source".to_string(),
+                shard_id: 1,
+                doc_batch: Some(DocBatchV2::for_test(["test-doc-010"])),
+                batch_uid: Some("test-batch".to_string()),
+            }],
+        };
+        let persist_response = ingester.persist(persist_request.clone()).await.unwrap();
+        assert_eq!(persist_response.successes.len(), 1);
+        assert_eq!(persist_response.failures.len(), 0);
+
+        let mut persist_request = persist_request;
+        persist_request.subrequests[0].subrequest_id = 1;
+
+        let persist_response = ingester.persist(persist_request).await.unwrap();
+        assert_eq!(persist_response.successes.len(), 1);
+        assert_eq!(persist_response.failures.len(), 0);
+
+        let persist_success = &persist_response.successes[0];
+        assert_eq!(persist_success.subrequest_id, 1);
+        assert_eq!(persist_success.shard_id, 1);
+        assert_eq!(
+            persist_success.replication_position_inclusive,
+            Some(Position::offset(0u64))
+        );
+
+        let state_guard = ingester.state.read().await;
+        let queue_id_01 = queue_id("test-index:0", "test-source", 1);
+        state_guard
+            .mrecordlog
+            .assert_records_eq(&queue_id_01, .., &[(0, "\0\0test-doc-010")]);
+    }
+
     #[tokio::test]
     async fn test_ingester_persist_replicate() {
         let (leader_ctx, mut leader) = IngesterForTest::default()
@@ -1519,6 +1835,7 @@ mod tests {
                     source_id: "test-source".to_string(),
                     shard_id: 1,
                     doc_batch: Some(DocBatchV2::for_test(["test-doc-010"])),
+                    batch_uid: None,
                 },
                 PersistSubrequest {
                     subrequest_id: 1,
@@ -1526,6 +1843,7 @@ mod tests {
                     source_id: "test-source".to_string(),
                     shard_id: 1,
                     doc_batch: Some(DocBatchV2::for_test(["test-doc-110", "test-doc-111"])),
+                    batch_uid: None,
                 },
             ],
         };
@@ -1615,6 +1933,101 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_ingester_persist_hedged() {
+        let (leader_ctx, mut leader) = IngesterForTest::default()
+            .with_node_id("test-leader")
+            .with_replication()
+            .build()
+            .await;
+
+        let (follower_ctx, follower) = IngesterForTest::default()
+            .with_node_id("test-follower")
+            .with_ingester_pool(&leader_ctx.ingester_pool)
+            .with_replication()
+            .build()
+            .await;
+
+        leader_ctx.ingester_pool.insert(
+            follower_ctx.node_id.clone(),
+            IngesterServiceClient::new(follower.clone()),
+        );
+
+        let init_shards_request = InitShardsRequest {
+            shards: vec![Shard {
+                index_uid: "test-index:0".to_string(),
+                source_id: "test-source".to_string(),
+                shard_id: 1,
+                shard_state: ShardState::Open as i32,
+                leader_id: leader_ctx.node_id.to_string(),
+                follower_id: Some(follower_ctx.node_id.to_string()),
+                ..Default::default()
+            }],
+        };
+        leader.init_shards(init_shards_request).await.unwrap();
+
+        let persist_request = PersistRequest {
+            leader_id: "test-leader".to_string(),
+            commit_type: CommitTypeV2::Auto as i32,
+            subrequests: vec![PersistSubrequest {
+                subrequest_id: 0,
+                index_uid: "test-index:0".to_string(),
+                source_id: "test-source".to_string(),
+                shard_id: 1,
+                doc_batch: Some(DocBatchV2::for_test(["test-doc-010"])),
+                batch_uid: Some("test-batch".to_string()),
+            }],
+        };
+        // The follower has not replicated the batch yet.
+        let mut hedged_follower = follower.clone();
+        let error = hedged_follower
+            .persist(persist_request.clone())
+            .await
+            .unwrap_err();
+        assert!(matches!(error, IngestV2Error::Timeout));
+
+        // Both the original and the hedged requests succeed.
+        let persist_response = leader.persist(persist_request.clone()).await.unwrap();
+        assert_eq!(persist_response.successes.len(), 1);
+
+        let hedged_persist_response = hedged_follower.persist(persist_request).await.unwrap();
+        assert_eq!(hedged_persist_response.successes.len(), 1);
+        assert_eq!(hedged_persist_response.failures.len(), 0);
+        assert_eq!(
+            hedged_persist_response.successes[0],
+            persist_response.successes[0]
+        );
+
+        // The batch is persisted and replicated once.
+        let queue_id_01 = queue_id("test-index:0", "test-source", 1);
+        leader.state.read().await.mrecordlog.assert_records_eq(
+            &queue_id_01,
+            ..,
+            &[(0, "\0\0test-doc-010")],
+        );
+        follower.state.read().await.mrecordlog.assert_records_eq(
+            &queue_id_01,
+            ..,
+            &[(0, "\0\0test-doc-010")],
+        );
+
+        // The follower only acknowledges the shards it replicates.
+        let persist_request = PersistRequest {
+            leader_id: "test-leader".to_string(),
+            commit_type: CommitTypeV2::Auto as i32,
+            subrequests: vec![PersistSubrequest {
+                subrequest_id: 0,
+                index_uid: "test-index:0".to_string(),
+                source_id: "test-source".to_string(),
+                shard_id: 2,
+                doc_batch: Some(DocBatchV2::for_test(["test-doc-020"])),
+                batch_uid: Some("test-other-batch".to_string()),
+            }],
+        };
+        let error = hedged_follower.persist(persist_request).await.unwrap_err();
+        assert!(matches!(error, IngestV2Error::Internal(_)));
+    }
+
     #[tokio::test]
     async fn test_ingester_persist_replicate_grpc() {
         let (leader_ctx, mut leader) = IngesterForTest::default()
@@ -1701,6 +2114,7 @@ mod tests {
                     source_id: "test-source".to_string(),
                     shard_id: 1,
                     doc_batch: Some(DocBatchV2::for_test(["test-doc-010"])),
+                    batch_uid: None,
                 },
                 PersistSubrequest {
                     subrequest_id: 1,
@@ -1708,6 +2122,7 @@ mod tests {
                     source_id: "test-source".to_string(),
                     shard_id: 1,
                     doc_batch: Some(DocBatchV2::for_test(["test-doc-110", "test-doc-111"])),
+                    batch_uid: None,
                 },
             ],
         };
@@ -1811,6 +2226,7 @@ mod tests {
                 source_id: "test-source".to_string(),
                 shard_id: 1,
                 doc_batch: Some(DocBatchV2::for_test(["test-doc-010"])),
+                batch_uid: None,
             }],
         };
         let persist_response = ingester.persist(persist_request).await.unwrap();
@@ -1871,6 +2287,7 @@ mod tests {
                 source_id: "test-source".to_string(),
                 shard_id: 1,
                 doc_batch: Some(DocBatchV2::for_test(["test-doc-010"])),
+                batch_uid: None,
             }],
         };
         let persist_response = ingester.persist(persist_request).await.unwrap();
@@ -1933,6 +2350,7 @@ mod tests {
                 source_id: "test-source".to_string(),
                 shard_id: 1,
                 doc_batch: Some(DocBatchV2::for_test(["test-doc-010"])),
+                batch_uid: None,
             }],
         };
         let persist_response = ingester.persist(persist_request).await.unwrap();
@@ -1963,8 +2381,168 @@ mod tests {
             .mrecordlog
             .assert_records_eq(&queue_id_01, .., &[]);
     }
+
     #[tokio::test]
+    async fn test_ingester_persist_backfill_shard_yields_capacity() {
+        let (ingester_ctx, mut ingester) = IngesterForTest::default()
+            .with_memory_capacity(ByteSize::kb(1))
+            .build()
+            .await;
+
+        let mut state_guard = ingester.state.write().await;
+
+        let realtime_shard = Shard {
+            index_uid: "test-index:0".to_string(),
+            source_id: "test-source".to_string(),
+            shard_id: 1,
+            shard_state: ShardState::Open as i32,
+            leader_id: ingester_ctx.node_id.to_string(),
+            ..Default::default()
+        };
+        ingester
+            .init_primary_shard(&mut state_guard, realtime_shard)
+            .await
+            .unwrap();
+
+        let backfill_shard = Shard {
+            index_uid: "test-index:0".to_string(),
+            source_id: "test-backfill-source".to_string(),
+            shard_id: 1,
+            shard_state: ShardState::Open as i32,
+            leader_id: ingester_ctx.node_id.to_string(),
+            priority: ShardPriority::Backfill as i32,
+            ..Default::default()
+        };
+        ingester
+            .init_primary_shard(&mut state_guard, backfill_shard)
+            .await
+            .unwrap();
+
+        drop(state_guard);
+
+        // The batch fits in the capacity of the WAL but not in the fraction of it available to
+        // backfill shards.
+        let doc_batch = DocBatchV2 {
+            doc_buffer: Bytes::from(vec![b'x'; 850]),
+            doc_lengths: vec![850],
+        };
+        let persist_request = PersistRequest {
+            leader_id: ingester_ctx.node_id.to_string(),
+            commit_type: CommitTypeV2::Auto as i32,
+            subrequests: vec![
+                PersistSubrequest {
+                    subrequest_id: 0,
+                    index_uid: "test-index:0".to_string(),
+                    source_id: "test-backfill-source".to_string(),
+                    shard_id: 1,
+                    doc_batch: Some(doc_batch.clone()),
+                    batch_uid: None,
+                },
+                PersistSubrequest {
+                    subrequest_id: 1,
+                    index_uid: "test-index:0".to_string(),
+                    source_id: "test-source".to_string(),
+                    shard_id: 1,
+                    doc_batch: Some(doc_batch),
+                    batch_uid: None,
+                },
+            ],
+        };
+        let persist_response = ingester.persist(persist_request).await.unwrap();
+        assert_eq!(persist_response.successes.len(), 1);
+        assert_eq!(persist_response.failures.len(), 1);
+
+        let persist_success = &persist_response.successes[0];
+        assert_eq!(persist_success.subrequest_id, 1);
+        assert_eq!(persist_success.source_id, "test-source");
+
+        let persist_failure = &persist_response.failures[0];
+        assert_eq!(persist_failure.subrequest_id, 0);
+        assert_eq!(persist_failure.source_id, "test-backfill-source");
+        assert_eq!(
+            persist_failure.reason(),
+            PersistFailureReason::ResourceExhausted
+        );
+
+        let state_guard = ingester.state.read().await;
+        let queue_id_01 = queue_id("test-index:0", "test-backfill-source", 1);
+        let backfill_shard_01 = state_guard.shards.get(&queue_id_01).unwrap();
+        assert_eq!(backfill_shard_01.priority, ShardPriority::Backfill);
+        backfill_shard_01.assert_replication_position(Position::Beginning);
+    }
+
+    #[tokio::test]
+    async fn test_ingester_persist_disk_watermark_exceeded() {
+        let (ingester_ctx, mut ingester) = IngesterForTest::default().build().await;
+
+        let mut state_guard = ingester.state.write().await;
+
+        let primary_shard = Shard {
+            index_uid: "test-index:0".to_string(),
+            source_id: "test-source".to_string(),
+            shard_id: 1,
+            shard_state: ShardState::Open as i32,
+            leader_id: ingester_ctx.node_id.to_string(),
+            ..Default::default()
+        };
+        ingester
+            .init_primary_shard(&mut state_guard, primary_shard)
+            .await
+            .unwrap();
+
+        drop(state_guard);
+
+        let persist_request = PersistRequest {
+            leader_id: ingester_ctx.node_id.to_string(),
+            commit_type: CommitTypeV2::Auto as i32,
+            subrequests: vec![PersistSubrequest {
+                subrequest_id: 0,
+                index_uid: "test-index:0".to_string(),
+                source_id: "test-source".to_string(),
+                shard_id: 1,
+                doc_batch: Some(DocBatchV2::for_test(["test-doc-010"])),
+                batch_uid: None,
+            }],
+        };
+        let persist_response = ingester.persist(persist_request.clone()).await.unwrap();
+        assert_eq!(persist_response.successes.len(), 1);
+        assert_eq!(persist_response.failures.len(), 0);
+
+        ingester.state.write().await.disk_watermark = DiskWatermark::new(ByteSize(1), ByteSize(0));
+
+        let persist_response = ingester.persist(persist_request).await.unwrap();
+        assert_eq!(persist_response.successes.len(), 0);
+        assert_eq!(persist_response.failures.len(), 1);
 
+        let persist_failure = &persist_response.failures[0];
+        assert_eq!(persist_failure.subrequest_id, 0);
+        assert_eq!(persist_failure.shard_id, 1);
+        assert_eq!(
+            persist_failure.reason(),
+            PersistFailureReason::DiskWatermarkExceeded
+        );
+
+        let state_guard = ingester.state.read().await;
+        assert!(state_guard.disk_watermark.is_exceeded());
+
+        let queue_id_01 = queue_id("test-index:0", "test-source", 1);
+        let solo_shard_01 = state_guard.shards.get(&queue_id_01).unwrap();
+        solo_shard_01.assert_is_closed();
+
+        state_guard
+            .mrecordlog
+            .assert_records_eq(&queue_id_01, .., &[(0, "\0\0test-doc-010")]);
+        drop(state_guard);
+
+        let ping_request = PingRequest {
+            leader_id: ingester_ctx.node_id.to_string(),
+            follower_id: None,
+        };
+        let error = ingester.ping(ping_request).await.unwrap_err();
+        assert!(error.to_string().contains("high watermark"));
+    }
+
+    #[tokio::test]
     async fn test_ingester_open_replication_stream() {
         let (_ingester_ctx, mut ingester) = IngesterForTest::default()
             .with_node_id("test-follower")
@@ -2008,6 +2586,8 @@ mod tests {
             source_id: "test-source".to_string(),
             shard_id: 1337,
             from_position_exclusive: None,
+            batch_num_bytes: None,
+            compress_batches: false,
         };
         let error = ingester
             .open_fetch_stream(open_fetch_stream_request)
@@ -2047,6 +2627,8 @@ mod tests {
             source_id: "test-source".to_string(),
             shard_id: 1,
             from_position_exclusive: None,
+            batch_num_bytes: None,
+            compress_batches: false,
         };
         let mut fetch_stream = ingester
             .open_fetch_stream(open_fetch_stream_request)
@@ -2235,6 +2817,8 @@ mod tests {
             source_id: "test-source".to_string(),
             shard_id: 1,
             from_position_exclusive: None,
+            batch_num_bytes: None,
+            compress_batches: false,
         };
         let mut fetch_stream = ingester
             .open_fetch_stream(open_fetch_stream_request)
@@ -2273,6 +2857,90 @@ mod tests {
         assert_eq!(fetch_eof.eof_position(), Position::Beginning.as_eof());
     }
 
+    #[tokio::test]
+    async fn test_ingester_close_idle_shards() {
+        let (_ingester_ctx, ingester) = IngesterForTest::default().build().await;
+
+        let mut state_guard = ingester.state.write().await;
+
+        for shard_id in [1, 2] {
+            let shard = Shard {
+                index_uid: "test-index:0".to_string(),
+                source_id: "test-source".to_string(),
+                shard_id,
+                shard_state: ShardState::Open as i32,
+                ..Default::default()
+            };
+            ingester
+                .init_primary_shard(&mut state_guard, shard)
+                .await
+                .unwrap();
+        }
+        let queue_id_01 = queue_id("test-index:0", "test-source", 1);
+        let queue_id_02 = queue_id("test-index:0", "test-source", 2);
+
+        let shard_idle_timeout = Duration::from_secs(60);
+        let now = Instant::now();
+
+        state_guard
+            .shards
+            .get_mut(&queue_id_01)
+            .unwrap()
+            .last_write_at = now;
+        state_guard
+            .shards
+            .get_mut(&queue_id_02)
+            .unwrap()
+            .last_write_at = now + shard_idle_timeout / 2;
+
+        state_guard.close_idle_shards(shard_idle_timeout, now + shard_idle_timeout);
+
+        state_guard
+            .shards
+            .get(&queue_id_01)
+            .unwrap()
+            .assert_is_closed();
+        state_guard
+            .shards
+            .get(&queue_id_02)
+            .unwrap()
+            .assert_is_open();
+    }
+
+    #[tokio::test]
+    async fn test_ingester_closes_idle_shards_periodically() {
+        let (_ingester_ctx, ingester) = IngesterForTest::default()
+            .with_shard_idle_timeout(Duration::from_millis(200))
+            .build()
+            .await;
+
+        let shard = Shard {
+            index_uid: "test-index:0".to_string(),
+            source_id: "test-source".to_string(),
+            shard_id: 1,
+            shard_state: ShardState::Open as i32,
+            ..Default::default()
+        };
+        let queue_id = queue_id("test-index:0", "test-source", 1);
+
+        let mut state_guard = ingester.state.write().await;
+        ingester
+            .init_primary_shard(&mut state_guard, shard)
+            .await
+            .unwrap();
+        state_guard.shards.get(&queue_id).unwrap().assert_is_open();
+        drop(state_guard);
+
+        tokio::time::sleep(Duration::from_millis(500)).await;
+
+        let state_guard = ingester.state.read().await;
+        state_guard
+            .shards
+            .get(&queue_id)
+            .unwrap()
+            .assert_is_closed();
+    }
+
     #[tokio::test]
     async fn test_ingester_open_observation_stream() {
         let (ingester_ctx, mut ingester) = IngesterForTest::default().build().await;
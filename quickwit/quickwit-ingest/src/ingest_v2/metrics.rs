@@ -27,11 +27,18 @@ pub(super) struct IngestV2Metrics {
     pub grpc_requests_total: IntCounterVec<4>,
     pub grpc_requests_in_flight: IntGaugeVec<3>,
     pub grpc_request_duration_secs: HistogramVec<4>,
+    pub router_unavailable_retries_total: IntCounterVec<1>,
+    pub router_routed_docs_total: IntCounterVec<1>,
+    pub router_hedged_persist_requests_total: IntCounterVec<1>,
     pub shards: IntGaugeVec<2>,
+    pub fetch_stream_lag_records: IntGauge,
+    pub fetch_stream_in_flight_bytes: IntGauge,
     pub wal_acquire_lock_requests_in_flight: IntGaugeVec<2>,
     pub wal_acquire_lock_request_duration_secs: HistogramVec<2>,
     pub wal_disk_usage_bytes: IntGauge,
     pub wal_memory_usage_bytes: IntGauge,
+    pub persisted_bytes_total: IntCounterVec<1>,
+    pub persist_rejections_total: IntCounterVec<2>,
 }
 
 impl Default for IngestV2Metrics {
@@ -55,12 +62,45 @@ impl Default for IngestV2Metrics {
                 "quickwit_ingest",
                 ["component", "kind", "operation", "status"],
             ),
+            router_unavailable_retries_total: new_counter_vec(
+                "router_unavailable_retries_total",
+                "Number of ingest requests retried by the router while ingesters were \
+                 unavailable, by outcome (`success` or `failure`).",
+                "quickwit_ingest",
+                ["outcome"],
+            ),
+            router_routed_docs_total: new_counter_vec(
+                "router_routed_docs_total",
+                "Number of documents routed by the routing rules of the router, by outcome \
+                 (`routed` or `fallback`).",
+                "quickwit_ingest",
+                ["outcome"],
+            ),
+            router_hedged_persist_requests_total: new_counter_vec(
+                "router_hedged_persist_requests_total",
+                "Number of persist requests hedged by the router, by response kept (`primary` or \
+                 `hedged`).",
+                "quickwit_ingest",
+                ["outcome"],
+            ),
             shards: new_gauge_vec(
                 "shards",
                 "Number of shards.",
                 "quickwit_ingest",
                 ["state", "index_id"],
             ),
+            fetch_stream_lag_records: new_gauge(
+                "fetch_stream_lag_records",
+                "Number of records written to the shards of the ingester and not fetched yet by \
+                 the indexers.",
+                "quickwit_ingest",
+            ),
+            fetch_stream_in_flight_bytes: new_gauge(
+                "fetch_stream_in_flight_bytes",
+                "Number of bytes fetched from the ingesters and not yet consumed by the indexing \
+                 pipelines of the indexer.",
+                "quickwit_ingest",
+            ),
             wal_acquire_lock_requests_in_flight: new_gauge_vec(
                 "wal_acquire_lock_requests_in_flight",
                 "Number of acquire lock requests in flight.",
@@ -83,6 +123,20 @@ impl Default for IngestV2Metrics {
                 "Memory usage of the write-ahead log in bytes.",
                 "quickwit_ingest",
             ),
+            persisted_bytes_total: new_counter_vec(
+                "persisted_bytes_total",
+                "Number of bytes persisted in the write-ahead log, by shard priority (`realtime` \
+                 or `backfill`).",
+                "quickwit_ingest",
+                ["priority"],
+            ),
+            persist_rejections_total: new_counter_vec(
+                "persist_rejections_total",
+                "Number of persist subrequests rejected by the ingester, by shard priority \
+                 (`realtime` or `backfill`) and reason (`resource_exhausted` or `rate_limited`).",
+                "quickwit_ingest",
+                ["priority", "reason"],
+            ),
         }
     }
 }
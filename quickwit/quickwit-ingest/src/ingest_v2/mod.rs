@@ -18,12 +18,14 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 mod broadcast;
+mod doc_routing;
 mod fetch;
 mod ingester;
 mod metrics;
 mod models;
 mod mrecord;
 mod mrecordlog_utils;
+mod persisted_batches;
 mod rate_meter;
 mod replication;
 mod router;
@@ -35,7 +37,10 @@ mod workbench;
 use std::fmt;
 use std::ops::{Add, AddAssign};
 
-pub use broadcast::{setup_local_shards_update_listener, LocalShardsUpdate, ShardInfo, ShardInfos};
+pub use broadcast::{
+    setup_ingester_disk_watermark_listener, setup_local_shards_update_listener,
+    IngesterDiskWatermarkUpdate, LocalShardsUpdate, ShardInfo, ShardInfos,
+};
 use bytesize::ByteSize;
 use quickwit_common::tower::Pool;
 use quickwit_proto::ingest::ingester::IngesterServiceClient;
@@ -46,6 +51,7 @@ pub use self::fetch::{FetchStreamError, MultiFetchStream};
 pub use self::ingester::{wait_for_ingester_decommission, Ingester};
 use self::mrecord::MRECORD_HEADER_LEN;
 pub use self::mrecord::{decoded_mrecords, MRecord};
+pub use self::mrecordlog_utils::DiskWatermark;
 pub use self::router::IngestRouter;
 
 pub type IngesterPool = Pool<NodeId, IngesterServiceClient>;
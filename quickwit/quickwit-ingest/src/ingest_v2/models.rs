@@ -17,7 +17,9 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use quickwit_proto::ingest::ShardState;
+use std::time::{Duration, Instant};
+
+use quickwit_proto::ingest::{ShardPriority, ShardState};
 use quickwit_proto::types::{NodeId, Position};
 use tokio::sync::watch;
 
@@ -38,12 +40,17 @@ pub(super) type ShardStatus = (ShardState, Position);
 pub(super) struct IngesterShard {
     pub shard_type: IngesterShardType,
     pub shard_state: ShardState,
+    /// Priority class of the shard. The shards of backfill sources can only use a fraction of the
+    /// capacity of the write-ahead log.
+    pub priority: ShardPriority,
     /// Position of the last record written in the shard's mrecordlog queue.
     pub replication_position_inclusive: Position,
     /// Position up to which the shard has been truncated.
     pub truncation_position_inclusive: Position,
     pub shard_status_tx: watch::Sender<ShardStatus>,
     pub shard_status_rx: watch::Receiver<ShardStatus>,
+    /// Instant of the last record written in the shard or of its creation.
+    pub last_write_at: Instant,
 }
 
 impl IngesterShard {
@@ -58,10 +65,12 @@ impl IngesterShard {
         Self {
             shard_type: IngesterShardType::Primary { follower_id },
             shard_state,
+            priority: ShardPriority::Realtime,
             replication_position_inclusive,
             truncation_position_inclusive,
             shard_status_tx,
             shard_status_rx,
+            last_write_at: Instant::now(),
         }
     }
 
@@ -76,10 +85,12 @@ impl IngesterShard {
         Self {
             shard_type: IngesterShardType::Replica { leader_id },
             shard_state,
+            priority: ShardPriority::Realtime,
             replication_position_inclusive,
             truncation_position_inclusive,
             shard_status_tx,
             shard_status_rx,
+            last_write_at: Instant::now(),
         }
     }
 
@@ -93,21 +104,44 @@ impl IngesterShard {
         Self {
             shard_type: IngesterShardType::Solo,
             shard_state,
+            priority: ShardPriority::Realtime,
             replication_position_inclusive,
             truncation_position_inclusive,
             shard_status_tx,
             shard_status_rx,
+            last_write_at: Instant::now(),
         }
     }
 
+    pub fn with_priority(mut self, priority: ShardPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
     pub fn is_indexed(&self) -> bool {
         self.shard_state.is_closed() && self.truncation_position_inclusive.is_eof()
     }
 
+    /// Returns whether the shard is an open primary shard that has not been written to for
+    /// `idle_timeout`.
+    pub fn is_idle(&self, idle_timeout: Duration, now: Instant) -> bool {
+        !self.is_replica()
+            && self.shard_state.is_open()
+            && now.saturating_duration_since(self.last_write_at) >= idle_timeout
+    }
+
     pub fn is_replica(&self) -> bool {
         matches!(self.shard_type, IngesterShardType::Replica { .. })
     }
 
+    /// Returns the leader of the shard if the shard is a replica.
+    pub fn leader_id_opt(&self) -> Option<&NodeId> {
+        match &self.shard_type {
+            IngesterShardType::Replica { leader_id } => Some(leader_id),
+            _ => None,
+        }
+    }
+
     pub fn follower_id_opt(&self) -> Option<&NodeId> {
         match &self.shard_type {
             IngesterShardType::Primary { follower_id } => Some(follower_id),
@@ -132,6 +166,7 @@ impl IngesterShard {
             return;
         }
         self.replication_position_inclusive = replication_position_inclusive;
+        self.last_write_at = Instant::now();
         self.notify_shard_status();
     }
 }
@@ -199,6 +234,7 @@ mod tests {
         ));
         assert!(!primary_shard.is_replica());
         assert_eq!(primary_shard.shard_state, ShardState::Closed);
+        assert_eq!(primary_shard.priority, ShardPriority::Realtime);
         assert_eq!(
             primary_shard.replication_position_inclusive,
             Position::offset(42u64)
@@ -252,4 +288,30 @@ mod tests {
             Position::Beginning
         );
     }
+
+    #[test]
+    fn test_shard_is_idle() {
+        let idle_timeout = Duration::from_secs(60);
+
+        let mut solo_shard =
+            IngesterShard::new_solo(ShardState::Open, Position::Beginning, Position::Beginning);
+        let created_at = solo_shard.last_write_at;
+        assert!(!solo_shard.is_idle(idle_timeout, created_at));
+        assert!(solo_shard.is_idle(idle_timeout, created_at + idle_timeout));
+
+        solo_shard.set_replication_position_inclusive(Position::offset(42u64));
+        assert!(solo_shard.last_write_at >= created_at);
+        assert!(!solo_shard.is_idle(idle_timeout, solo_shard.last_write_at));
+
+        solo_shard.shard_state = ShardState::Closed;
+        assert!(!solo_shard.is_idle(idle_timeout, created_at + idle_timeout * 2));
+
+        let replica_shard = IngesterShard::new_replica(
+            "test-leader".into(),
+            ShardState::Open,
+            Position::Beginning,
+            Position::Beginning,
+        );
+        assert!(!replica_shard.is_idle(idle_timeout, created_at + idle_timeout * 2));
+    }
 }
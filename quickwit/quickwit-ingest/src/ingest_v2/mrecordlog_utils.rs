@@ -23,8 +23,13 @@ use std::ops::RangeInclusive;
 use bytesize::ByteSize;
 use mrecordlog::error::DeleteQueueError;
 use mrecordlog::MultiRecordLog;
+use quickwit_proto::ingest::ShardPriority;
 use quickwit_proto::types::QueueId;
 
+/// Fraction of the disk and memory capacity of the write-ahead log that the shards of backfill
+/// sources can use. The remaining capacity is reserved for the shards of realtime sources.
+const BACKFILL_CAPACITY_RATIO: f64 = 0.8;
+
 #[derive(Debug, Clone, Copy)]
 pub(super) struct MRecordLogUsage {
     pub disk: ByteSize,
@@ -88,6 +93,71 @@ pub(super) fn check_enough_capacity(
     Ok(usage)
 }
 
+/// Returns the disk and memory capacity of the write-ahead log available to the shards of the
+/// given priority.
+pub(super) fn capacity_for_priority(
+    disk_capacity: ByteSize,
+    memory_capacity: ByteSize,
+    priority: ShardPriority,
+) -> (ByteSize, ByteSize) {
+    match priority {
+        ShardPriority::Realtime => (disk_capacity, memory_capacity),
+        ShardPriority::Backfill => {
+            let backfill_capacity = |capacity: ByteSize| {
+                ByteSize((capacity.as_u64() as f64 * BACKFILL_CAPACITY_RATIO) as u64)
+            };
+            (
+                backfill_capacity(disk_capacity),
+                backfill_capacity(memory_capacity),
+            )
+        }
+    }
+}
+
+/// Tracks whether the disk usage of the WAL exceeds the high watermark. Once exceeded, the
+/// watermark is only cleared when the disk usage falls below the low watermark, so that the
+/// ingester does not keep flipping between accepting and rejecting writes.
+#[derive(Debug, Clone, Copy)]
+pub struct DiskWatermark {
+    high: ByteSize,
+    low: ByteSize,
+    exceeded: bool,
+}
+
+impl DiskWatermark {
+    pub fn new(high: ByteSize, low: ByteSize) -> Self {
+        Self {
+            high,
+            low,
+            exceeded: false,
+        }
+    }
+
+    pub fn is_exceeded(&self) -> bool {
+        self.exceeded
+    }
+
+    /// Updates the state of the watermark given the current disk usage. Returns `true` if the
+    /// state changed.
+    pub fn update(&mut self, disk_usage: ByteSize) -> bool {
+        let exceeded = if self.exceeded {
+            disk_usage > self.low
+        } else {
+            disk_usage >= self.high
+        };
+        let changed = exceeded != self.exceeded;
+        self.exceeded = exceeded;
+        changed
+    }
+}
+
+impl Default for DiskWatermark {
+    /// Returns a watermark that is never exceeded.
+    fn default() -> Self {
+        Self::new(ByteSize(u64::MAX), ByteSize(u64::MAX))
+    }
+}
+
 /// Deletes a queue from the WAL. Returns without error if the queue does not exist.
 pub async fn force_delete_queue(
     mrecordlog: &mut MultiRecordLog,
@@ -145,6 +215,41 @@ mod tests {
         check_enough_capacity(&mrecordlog, ByteSize::mb(256), ByteSize(12), ByteSize(12)).unwrap();
     }
 
+    #[test]
+    fn test_capacity_for_priority() {
+        let (disk_capacity, memory_capacity) =
+            capacity_for_priority(ByteSize(1_000), ByteSize(100), ShardPriority::Realtime);
+        assert_eq!(disk_capacity, ByteSize(1_000));
+        assert_eq!(memory_capacity, ByteSize(100));
+
+        let (disk_capacity, memory_capacity) =
+            capacity_for_priority(ByteSize(1_000), ByteSize(100), ShardPriority::Backfill);
+        assert_eq!(disk_capacity, ByteSize(800));
+        assert_eq!(memory_capacity, ByteSize(80));
+    }
+
+    #[test]
+    fn test_disk_watermark() {
+        let mut disk_watermark = DiskWatermark::new(ByteSize(90), ByteSize(80));
+        assert!(!disk_watermark.is_exceeded());
+
+        assert!(!disk_watermark.update(ByteSize(85)));
+        assert!(!disk_watermark.is_exceeded());
+
+        assert!(disk_watermark.update(ByteSize(90)));
+        assert!(disk_watermark.is_exceeded());
+
+        assert!(!disk_watermark.update(ByteSize(85)));
+        assert!(disk_watermark.is_exceeded());
+
+        assert!(disk_watermark.update(ByteSize(80)));
+        assert!(!disk_watermark.is_exceeded());
+
+        let mut disk_watermark = DiskWatermark::default();
+        assert!(!disk_watermark.update(ByteSize(u64::MAX - 1)));
+        assert!(!disk_watermark.is_exceeded());
+    }
+
     #[tokio::test]
     async fn test_append_queue_position_range() {
         let tempdir = tempfile::tempdir().unwrap();
@@ -0,0 +1,147 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use quickwit_proto::ingest::ingester::PersistSuccess;
+use quickwit_proto::types::{Position, ShardId, SourceId, SubrequestId};
+use tokio::sync::Notify;
+
+use super::router::INGEST_REQUEST_TIMEOUT;
+
+/// Duration during which the batches persisted by the ingester are remembered. Past the timeout of
+/// the ingest request that sent a batch, the router no longer retries nor hedges it. The TTL is
+/// twice that timeout so that the batches persisted right before the router gave up on the request
+/// are still remembered when its last retries or hedged requests reach the ingester.
+const PERSISTED_BATCH_TTL: Duration =
+    Duration::from_millis(INGEST_REQUEST_TIMEOUT.as_millis() as u64 * 2);
+
+#[derive(Debug, Clone)]
+struct PersistedBatch {
+    index_uid: String,
+    source_id: SourceId,
+    shard_id: ShardId,
+    replication_position_inclusive: Position,
+}
+
+/// Keeps track of the batches recently persisted or replicated by the ingester, identified by
+/// their batch UID, so that a batch sent several times, by a retry or a hedged persist request, is
+/// persisted only once.
+#[derive(Debug, Default)]
+pub(super) struct PersistedBatches {
+    batches: HashMap<String, PersistedBatch>,
+    // Batch UIDs in insertion order, along with their insertion time.
+    batch_uids: VecDeque<(Instant, String)>,
+    // Notified whenever a batch is inserted.
+    inserted: Arc<Notify>,
+}
+
+impl PersistedBatches {
+    /// Returns the success of the original persist request if the batch was already persisted.
+    pub fn get(&mut self, batch_uid: &str, subrequest_id: SubrequestId) -> Option<PersistSuccess> {
+        self.evict_expired(Instant::now());
+
+        let persisted_batch = self.batches.get(batch_uid)?;
+        let persist_success = PersistSuccess {
+            subrequest_id,
+            index_uid: persisted_batch.index_uid.clone(),
+            source_id: persisted_batch.source_id.clone(),
+            shard_id: persisted_batch.shard_id,
+            replication_position_inclusive: Some(
+                persisted_batch.replication_position_inclusive.clone(),
+            ),
+        };
+        Some(persist_success)
+    }
+
+    pub fn insert(&mut self, batch_uid: String, persist_success: &PersistSuccess) {
+        let now = Instant::now();
+        self.evict_expired(now);
+
+        let persisted_batch = PersistedBatch {
+            index_uid: persist_success.index_uid.clone(),
+            source_id: persist_success.source_id.clone(),
+            shard_id: persist_success.shard_id,
+            replication_position_inclusive: persist_success
+                .replication_position_inclusive
+                .clone()
+                .unwrap_or_default(),
+        };
+        if self
+            .batches
+            .insert(batch_uid.clone(), persisted_batch)
+            .is_none()
+        {
+            self.batch_uids.push_back((now, batch_uid));
+        }
+        self.inserted.notify_waiters();
+    }
+
+    /// Returns a handle notified whenever a batch is inserted.
+    pub fn inserted(&self) -> Arc<Notify> {
+        self.inserted.clone()
+    }
+
+    fn evict_expired(&mut self, now: Instant) {
+        while let Some((inserted_at, _)) = self.batch_uids.front() {
+            if now.duration_since(*inserted_at) < PERSISTED_BATCH_TTL {
+                break;
+            }
+            let (_, batch_uid) = self.batch_uids.pop_front().expect("batch UID should exist");
+            self.batches.remove(&batch_uid);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_persisted_batches() {
+        let mut persisted_batches = PersistedBatches::default();
+        assert!(persisted_batches.get("test-batch", 0).is_none());
+
+        let persist_success = PersistSuccess {
+            subrequest_id: 0,
+            index_uid: "test-index:0".to_string(),
+            source_id: "test-source".to_string(),
+            shard_id: 1,
+            replication_position_inclusive: Some(Position::offset(42u64)),
+        };
+        persisted_batches.insert("test-batch".to_string(), &persist_success);
+
+        let persist_success = persisted_batches.get("test-batch", 3).unwrap();
+        assert_eq!(persist_success.subrequest_id, 3);
+        assert_eq!(persist_success.index_uid, "test-index:0");
+        assert_eq!(persist_success.source_id, "test-source");
+        assert_eq!(persist_success.shard_id, 1);
+        assert_eq!(
+            persist_success.replication_position_inclusive,
+            Some(Position::offset(42u64))
+        );
+        assert!(persisted_batches.get("test-other-batch", 0).is_none());
+
+        persisted_batches.evict_expired(Instant::now() + PERSISTED_BATCH_TTL);
+        assert!(persisted_batches.get("test-batch", 0).is_none());
+        assert!(persisted_batches.batch_uids.is_empty());
+    }
+}
@@ -27,9 +27,9 @@ use futures::{Future, StreamExt};
 use quickwit_common::ServiceStream;
 use quickwit_proto::ingest::ingester::{
     ack_replication_message, syn_replication_message, AckReplicationMessage, IngesterStatus,
-    InitReplicaRequest, InitReplicaResponse, ReplicateFailure, ReplicateFailureReason,
-    ReplicateRequest, ReplicateResponse, ReplicateSubrequest, ReplicateSuccess,
-    SynReplicationMessage,
+    InitReplicaRequest, InitReplicaResponse, PersistSuccess, ReplicateFailure,
+    ReplicateFailureReason, ReplicateRequest, ReplicateResponse, ReplicateSubrequest,
+    ReplicateSuccess, SynReplicationMessage,
 };
 use quickwit_proto::ingest::{CommitTypeV2, IngestV2Error, IngestV2Result, Shard, ShardState};
 use quickwit_proto::types::{NodeId, Position};
@@ -471,12 +471,14 @@ impl ReplicationTask {
             .await
             .expect("TODO: Handle IO error");
 
+        let priority = replica_shard.priority();
         let replica_shard = IngesterShard::new_replica(
             replica_shard.leader_id.into(),
             ShardState::Open,
             Position::Beginning,
             Position::Beginning,
-        );
+        )
+        .with_priority(priority);
         state_guard.shards.insert(queue_id, replica_shard);
 
         let init_replica_response = InitReplicaResponse {
@@ -665,6 +667,20 @@ impl ReplicationTask {
                 .expect("replica shard should be initialized");
             replica_shard.set_replication_position_inclusive(current_position_inclusive.clone());
 
+            // The follower remembers the replicated batch so that it can acknowledge the hedged
+            // persist requests sending the same batch.
+            if let Some(batch_uid) = subrequest.batch_uid {
+                let persist_success = PersistSuccess {
+                    subrequest_id: subrequest.subrequest_id,
+                    index_uid: subrequest.index_uid.clone(),
+                    source_id: subrequest.source_id.clone(),
+                    shard_id: subrequest.shard_id,
+                    replication_position_inclusive: Some(current_position_inclusive.clone()),
+                };
+                state_guard
+                    .persisted_batches
+                    .insert(batch_uid, &persist_success);
+            }
             let replicate_success = ReplicateSuccess {
                 subrequest_id: subrequest.subrequest_id,
                 index_uid: subrequest.index_uid,
@@ -749,6 +765,8 @@ mod tests {
     use tokio::sync::watch;
 
     use super::*;
+    use crate::ingest_v2::mrecordlog_utils::DiskWatermark;
+    use crate::ingest_v2::persisted_batches::PersistedBatches;
     use crate::ingest_v2::test_utils::MultiRecordLogTestExt;
 
     fn into_init_replica_request(
@@ -904,6 +922,7 @@ mod tests {
                 doc_batch: Some(DocBatchV2::for_test(["test-doc-foo"])),
                 from_position_exclusive: None,
                 to_position_inclusive: Some(Position::offset(0u64)),
+                batch_uid: None,
             },
             ReplicateSubrequest {
                 subrequest_id: 1,
@@ -913,6 +932,7 @@ mod tests {
                 doc_batch: Some(DocBatchV2::for_test(["test-doc-bar", "test-doc-baz"])),
                 from_position_exclusive: None,
                 to_position_inclusive: Some(Position::offset(1u64)),
+                batch_uid: None,
             },
             ReplicateSubrequest {
                 subrequest_id: 2,
@@ -922,6 +942,7 @@ mod tests {
                 doc_batch: Some(DocBatchV2::for_test(["test-qux", "test-doc-tux"])),
                 from_position_exclusive: Some(Position::offset(0u64)),
                 to_position_inclusive: Some(Position::offset(2u64)),
+                batch_uid: None,
             },
         ];
         let replicate_response = replication_stream_task_handle
@@ -1020,6 +1041,8 @@ mod tests {
             replication_tasks: HashMap::new(),
             status: IngesterStatus::Ready,
             observation_tx,
+            disk_watermark: DiskWatermark::default(),
+            persisted_batches: PersistedBatches::default(),
         }));
         let (syn_replication_stream_tx, syn_replication_stream) =
             ServiceStream::new_bounded(SYN_REPLICATION_STREAM_CAPACITY);
@@ -1151,6 +1174,7 @@ mod tests {
                     doc_batch: Some(DocBatchV2::for_test(["test-doc-foo"])),
                     from_position_exclusive: None,
                     to_position_inclusive: Some(Position::offset(0u64)),
+                    batch_uid: None,
                 },
                 ReplicateSubrequest {
                     subrequest_id: 1,
@@ -1160,6 +1184,7 @@ mod tests {
                     doc_batch: Some(DocBatchV2::for_test(["test-doc-bar", "test-doc-baz"])),
                     from_position_exclusive: None,
                     to_position_inclusive: Some(Position::offset(1u64)),
+                    batch_uid: None,
                 },
                 ReplicateSubrequest {
                     subrequest_id: 2,
@@ -1169,6 +1194,7 @@ mod tests {
                     doc_batch: Some(DocBatchV2::for_test(["test-doc-qux", "test-doc-tux"])),
                     from_position_exclusive: None,
                     to_position_inclusive: Some(Position::offset(1u64)),
+                    batch_uid: None,
                 },
             ],
             replication_seqno: 3,
@@ -1245,6 +1271,7 @@ mod tests {
                 doc_batch: Some(DocBatchV2::for_test(["test-doc-moo"])),
                 from_position_exclusive: Some(Position::offset(0u64)),
                 to_position_inclusive: Some(Position::offset(1u64)),
+                batch_uid: None,
             }],
             replication_seqno: 4,
         };
@@ -1295,6 +1322,8 @@ mod tests {
             replication_tasks: HashMap::new(),
             status: IngesterStatus::Ready,
             observation_tx,
+            disk_watermark: DiskWatermark::default(),
+            persisted_batches: PersistedBatches::default(),
         }));
         let (syn_replication_stream_tx, syn_replication_stream) =
             ServiceStream::new_bounded(SYN_REPLICATION_STREAM_CAPACITY);
@@ -1339,6 +1368,7 @@ mod tests {
                 doc_batch: Some(DocBatchV2::for_test(["test-doc-foo"])),
                 from_position_exclusive: Position::offset(0u64).into(),
                 to_position_inclusive: Some(Position::offset(1u64)),
+                batch_uid: None,
             }],
             replication_seqno: 0,
         };
@@ -1380,6 +1410,8 @@ mod tests {
             replication_tasks: HashMap::new(),
             status: IngesterStatus::Ready,
             observation_tx,
+            disk_watermark: DiskWatermark::default(),
+            persisted_batches: PersistedBatches::default(),
         }));
         let (syn_replication_stream_tx, syn_replication_stream) =
             ServiceStream::new_bounded(SYN_REPLICATION_STREAM_CAPACITY);
@@ -1424,6 +1456,7 @@ mod tests {
                 doc_batch: Some(DocBatchV2::for_test(["test-doc-foo"])),
                 from_position_exclusive: None,
                 to_position_inclusive: Some(Position::offset(0u64)),
+                batch_uid: None,
             }],
             replication_seqno: 0,
         };
@@ -26,13 +26,15 @@ use async_trait::async_trait;
 use futures::stream::FuturesUnordered;
 use futures::{Future, StreamExt};
 use quickwit_common::pubsub::{EventBroker, EventSubscriber};
+use quickwit_config::IngestRoutingRule;
 use quickwit_proto::control_plane::{
     ControlPlaneService, ControlPlaneServiceClient, GetOrCreateOpenShardsRequest,
     GetOrCreateOpenShardsSubrequest,
 };
 use quickwit_proto::indexing::ShardPositionsUpdate;
 use quickwit_proto::ingest::ingester::{
-    IngesterService, PersistFailureReason, PersistRequest, PersistResponse, PersistSubrequest,
+    IngesterService, IngesterServiceClient, PersistFailureReason, PersistRequest, PersistResponse,
+    PersistSubrequest,
 };
 use quickwit_proto::ingest::router::{
     IngestRequestV2, IngestResponseV2, IngestRouterService, IngestSubrequest,
@@ -40,10 +42,13 @@ use quickwit_proto::ingest::router::{
 use quickwit_proto::ingest::{CommitTypeV2, IngestV2Error, IngestV2Result, ShardIds, ShardState};
 use quickwit_proto::types::{IndexUid, NodeId, ShardId, SourceId, SubrequestId};
 use tokio::sync::RwLock;
+use tokio::time::Instant;
 use tracing::{error, info, warn};
 
 use super::broadcast::LocalShardsUpdate;
+use super::doc_routing::DocRouter;
 use super::ingester::PERSIST_REQUEST_TIMEOUT;
+use super::metrics::INGEST_V2_METRICS;
 use super::routing_table::RoutingTable;
 use super::workbench::IngestWorkbench;
 use super::IngesterPool;
@@ -59,6 +64,22 @@ pub(super) const INGEST_REQUEST_TIMEOUT: Duration = if cfg!(any(test, feature =
 
 const MAX_PERSIST_ATTEMPTS: usize = 5;
 
+/// Duration during which the router keeps retrying the subrequests that could not be routed
+/// because their ingesters were unavailable before failing them.
+const UNAVAILABLE_RETRY_DEADLINE: Duration = if cfg!(any(test, feature = "testsuite")) {
+    Duration::from_millis(50)
+} else {
+    Duration::from_secs(5)
+};
+
+const UNAVAILABLE_RETRY_INITIAL_BACKOFF: Duration = if cfg!(any(test, feature = "testsuite")) {
+    Duration::from_millis(1)
+} else {
+    Duration::from_millis(100)
+};
+
+const UNAVAILABLE_RETRY_MAX_BACKOFF: Duration = Duration::from_secs(1);
+
 type PersistResult = (PersistRequestSummary, IngestV2Result<PersistResponse>);
 
 #[derive(Clone)]
@@ -69,6 +90,8 @@ pub struct IngestRouter {
     state: Arc<RwLock<RouterState>>,
     replication_factor: usize,
     write_semaphore: SemaphoreWithMaxWaiters,
+    doc_router: Arc<DocRouter>,
+    persist_hedging_delay_opt: Option<Duration>,
 }
 
 struct RouterState {
@@ -104,9 +127,31 @@ impl IngestRouter {
             state,
             replication_factor,
             write_semaphore: SemaphoreWithMaxWaiters::new(1, 10),
+            doc_router: Arc::default(),
+            persist_hedging_delay_opt: None,
         }
     }
 
+    /// Routes the documents sent to the indexes of the routing rules to the indexes returned by
+    /// their expressions.
+    pub fn with_routing_rules(
+        mut self,
+        routing_rules: &[IngestRoutingRule],
+    ) -> anyhow::Result<Self> {
+        self.doc_router = Arc::new(DocRouter::try_new(routing_rules)?);
+        Ok(self)
+    }
+
+    /// Sends the persist requests that have not completed after `persist_hedging_delay` to the
+    /// follower of their shards as well, and keeps the first successful response.
+    pub fn with_persist_hedging_delay(
+        mut self,
+        persist_hedging_delay_opt: Option<Duration>,
+    ) -> Self {
+        self.persist_hedging_delay_opt = persist_hedging_delay_opt;
+        self
+    }
+
     pub fn subscribe(&self, event_broker: &EventBroker) {
         let weak_router_state = WeakRouterState(Arc::downgrade(&self.state));
 
@@ -231,7 +276,12 @@ impl IngestRouter {
                     for persist_failure in persist_response.failures {
                         workbench.record_persist_failure(&persist_failure);
 
-                        if persist_failure.reason() == PersistFailureReason::ShardClosed {
+                        // Ingesters whose disk usage exceeds the high watermark close their shards.
+                        if matches!(
+                            persist_failure.reason(),
+                            PersistFailureReason::ShardClosed
+                                | PersistFailureReason::DiskWatermarkExceeded
+                        ) {
                             let index_uid: IndexUid = persist_failure.index_uid.into();
                             let source_id: SourceId = persist_failure.source_id;
                             closed_shards
@@ -313,6 +363,9 @@ impl IngestRouter {
 
         let mut per_leader_persist_subrequests: HashMap<&LeaderId, Vec<PersistSubrequest>> =
             HashMap::new();
+        // Followers of the shards of the persist subrequests, used to build hedged persist
+        // requests.
+        let mut per_leader_follower_ids: HashMap<&LeaderId, Vec<Option<&NodeId>>> = HashMap::new();
 
         let state_guard = self.state.read().await;
 
@@ -335,11 +388,16 @@ impl IngestRouter {
                 source_id: shard.source_id.clone(),
                 shard_id: shard.shard_id,
                 doc_batch: subrequest.doc_batch.clone(),
+                batch_uid: Some(workbench.batch_uid(subrequest.subrequest_id)),
             };
             per_leader_persist_subrequests
                 .entry(&shard.leader_id)
                 .or_default()
                 .push(persist_subrequest);
+            per_leader_follower_ids
+                .entry(&shard.leader_id)
+                .or_default()
+                .push(shard.follower_id_opt.as_ref());
         }
         let persist_futures = FuturesUnordered::new();
 
@@ -349,10 +407,30 @@ impl IngestRouter {
                 .iter()
                 .map(|subrequest| subrequest.subrequest_id)
                 .collect();
-            let Some(mut ingester) = self.ingester_pool.get(&leader_id) else {
+            let Some(ingester) = self.ingester_pool.get(&leader_id) else {
                 unavailable_subrequest_ids.extend(subrequest_ids);
                 continue;
             };
+            let hedged_persist_opt = self.persist_hedging_delay_opt.and_then(|hedging_delay| {
+                let follower_ids = per_leader_follower_ids.get(&leader_id)?;
+                let (follower_id, hedged_persist_request) = make_hedged_persist_request(
+                    &leader_id,
+                    &subrequests,
+                    follower_ids,
+                    commit_type,
+                )?;
+                let hedged_ingester = self.ingester_pool.get(&follower_id)?;
+                let hedged_persist_summary = PersistRequestSummary {
+                    leader_id: follower_id,
+                    subrequest_ids: subrequest_ids.clone(),
+                };
+                Some((
+                    hedging_delay,
+                    hedged_persist_summary,
+                    hedged_ingester,
+                    hedged_persist_request,
+                ))
+            });
             let persist_summary = PersistRequestSummary {
                 leader_id: leader_id.clone(),
                 subrequest_ids,
@@ -363,18 +441,51 @@ impl IngestRouter {
                 commit_type: commit_type as i32,
             };
             let persist_future = async move {
-                let persist_result = with_request_metrics!(
-                    tokio::time::timeout(
-                        PERSIST_REQUEST_TIMEOUT,
-                        ingester.persist(persist_request),
-                    )
-                    .await
-                    .unwrap_or_else(|_| Err(IngestV2Error::Timeout)),
-                    "router",
-                    "client",
-                    "persist"
-                );
-                (persist_summary, persist_result)
+                let primary_persist_future = persist(ingester, persist_request);
+
+                let Some((
+                    hedging_delay,
+                    hedged_persist_summary,
+                    hedged_ingester,
+                    hedged_persist_request,
+                )) = hedged_persist_opt
+                else {
+                    return (persist_summary, primary_persist_future.await);
+                };
+                tokio::pin!(primary_persist_future);
+
+                tokio::select! {
+                    persist_result = &mut primary_persist_future => {
+                        return (persist_summary, persist_result);
+                    }
+                    _ = tokio::time::sleep(hedging_delay) => {}
+                }
+                let hedged_persist_future = persist(hedged_ingester, hedged_persist_request);
+                tokio::pin!(hedged_persist_future);
+
+                // We keep the first successful response and drop the other request. If one of
+                // the requests fails, we wait for the other one.
+                let (outcome, persist_result) = tokio::select! {
+                    persist_result = &mut primary_persist_future => {
+                        if persist_result.is_ok() {
+                            ("primary", (persist_summary, persist_result))
+                        } else {
+                            ("hedged", (hedged_persist_summary, hedged_persist_future.await))
+                        }
+                    }
+                    persist_result = &mut hedged_persist_future => {
+                        if persist_result.is_ok() {
+                            ("hedged", (hedged_persist_summary, persist_result))
+                        } else {
+                            ("primary", (persist_summary, primary_persist_future.await))
+                        }
+                    }
+                };
+                INGEST_V2_METRICS
+                    .router_hedged_persist_requests_total
+                    .with_label_values([outcome])
+                    .inc();
+                persist_result
             };
             persist_futures.push(persist_future);
         }
@@ -395,24 +506,92 @@ impl IngestRouter {
         let commit_type = ingest_request.commit_type();
         let mut workbench = IngestWorkbench::new(ingest_request.subrequests, max_num_attempts);
 
-        while !workbench.is_complete() {
-            workbench.new_attempt();
-            self.batch_persist(&mut workbench, commit_type).await;
+        let retry_deadline = Instant::now() + UNAVAILABLE_RETRY_DEADLINE;
+        let mut retry_backoff = UNAVAILABLE_RETRY_INITIAL_BACKOFF;
+        let mut num_unavailable_retries = 0;
+
+        loop {
+            while !workbench.is_complete() {
+                workbench.new_attempt();
+                self.batch_persist(&mut workbench, commit_type).await;
+            }
+            // Ingesters usually come back within seconds after a network blip, so we hold on to
+            // the subrequests that could not be routed and retry them until the deadline.
+            if !workbench.has_pending_no_shards_available()
+                || Instant::now() + retry_backoff > retry_deadline
+            {
+                break;
+            }
+            tokio::time::sleep(retry_backoff).await;
+            retry_backoff = (retry_backoff * 2).min(UNAVAILABLE_RETRY_MAX_BACKOFF);
+            workbench.max_num_attempts += 1;
+            num_unavailable_retries += 1;
+        }
+        if num_unavailable_retries > 0 {
+            let outcome = if workbench.has_pending_no_shards_available() {
+                "failure"
+            } else {
+                "success"
+            };
+            INGEST_V2_METRICS
+                .router_unavailable_retries_total
+                .with_label_values([outcome])
+                .inc();
         }
         workbench.into_ingest_response()
     }
 
+    /// Routes the documents of the subrequests targeting the index of a routing rule, then persists
+    /// the subrequests. The documents routed to indexes that do not exist are persisted to the
+    /// fallback indexes in a second round.
+    async fn route_and_persist(
+        &mut self,
+        ingest_request: IngestRequestV2,
+    ) -> IngestV2Result<IngestResponseV2> {
+        if self.doc_router.is_empty() {
+            return self
+                .retry_batch_persist(ingest_request, MAX_PERSIST_ATTEMPTS)
+                .await;
+        }
+        let commit_type = ingest_request.commit_type;
+        let mut routed_subrequests = self.doc_router.route(ingest_request.subrequests);
+        let routed_ingest_request = IngestRequestV2 {
+            subrequests: routed_subrequests.take_subrequests(),
+            commit_type,
+        };
+        let mut ingest_response = self
+            .retry_batch_persist(routed_ingest_request, MAX_PERSIST_ATTEMPTS)
+            .await?;
+        let fallback_subrequests =
+            routed_subrequests.take_fallback_subrequests(&mut ingest_response);
+
+        if !fallback_subrequests.is_empty() {
+            let fallback_ingest_request = IngestRequestV2 {
+                subrequests: fallback_subrequests,
+                commit_type,
+            };
+            let fallback_ingest_response = self
+                .retry_batch_persist(fallback_ingest_request, MAX_PERSIST_ATTEMPTS)
+                .await?;
+            ingest_response
+                .successes
+                .extend(fallback_ingest_response.successes);
+            ingest_response
+                .failures
+                .extend(fallback_ingest_response.failures);
+        }
+        routed_subrequests.restore_subrequest_ids(&mut ingest_response);
+        Ok(ingest_response)
+    }
+
     async fn ingest_timeout(
         &mut self,
         ingest_request: IngestRequestV2,
         timeout_duration: Duration,
     ) -> IngestV2Result<IngestResponseV2> {
-        tokio::time::timeout(
-            timeout_duration,
-            self.retry_batch_persist(ingest_request, MAX_PERSIST_ATTEMPTS),
-        )
-        .await
-        .map_err(|_| IngestV2Error::Timeout)?
+        tokio::time::timeout(timeout_duration, self.route_and_persist(ingest_request))
+            .await
+            .map_err(|_| IngestV2Error::Timeout)?
     }
 }
 
@@ -502,6 +681,49 @@ impl EventSubscriber<ShardPositionsUpdate> for WeakRouterState {
     }
 }
 
+/// Sends a persist request to an ingester and fails with [`IngestV2Error::Timeout`] if it does not
+/// complete within [`PERSIST_REQUEST_TIMEOUT`].
+async fn persist(
+    mut ingester: IngesterServiceClient,
+    persist_request: PersistRequest,
+) -> IngestV2Result<PersistResponse> {
+    with_request_metrics!(
+        tokio::time::timeout(PERSIST_REQUEST_TIMEOUT, ingester.persist(persist_request))
+            .await
+            .unwrap_or_else(|_| Err(IngestV2Error::Timeout)),
+        "router",
+        "client",
+        "persist"
+    )
+}
+
+/// Builds the request hedging a persist request sent to `leader_id`, along with the ingester to
+/// send it to. The hedged request targets the same shards, via their follower, which must be the
+/// same for all the subrequests. The follower never persists the batches itself: it acknowledges
+/// them once it has replicated them from the leader, so a batch is never persisted twice. Returns
+/// `None` if the shards are not replicated or have different followers.
+fn make_hedged_persist_request(
+    leader_id: &NodeId,
+    subrequests: &[PersistSubrequest],
+    follower_ids: &[Option<&NodeId>],
+    commit_type: CommitTypeV2,
+) -> Option<(NodeId, PersistRequest)> {
+    let follower_id: &NodeId = (*follower_ids.first()?)?;
+
+    if follower_ids
+        .iter()
+        .any(|follower_id_opt| *follower_id_opt != Some(follower_id))
+    {
+        return None;
+    }
+    let hedged_persist_request = PersistRequest {
+        leader_id: leader_id.clone().into(),
+        subrequests: subrequests.to_vec(),
+        commit_type: commit_type as i32,
+    };
+    Some((follower_id.clone(), hedged_persist_request))
+}
+
 struct PersistRequestSummary {
     leader_id: NodeId,
     subrequest_ids: Vec<SubrequestId>,
@@ -562,6 +784,7 @@ mod tests {
                         shard_id: 1,
                         shard_state: ShardState::Closed,
                         leader_id: "test-ingester-0".into(),
+                        follower_id_opt: None,
                     },
                     RoutingEntry {
                         index_uid: "test-index-0:0".into(),
@@ -569,6 +792,7 @@ mod tests {
                         shard_id: 2,
                         shard_state: ShardState::Open,
                         leader_id: "test-ingester-0".into(),
+                        follower_id_opt: None,
                     },
                 ],
                 local_round_robin_idx: AtomicUsize::default(),
@@ -964,6 +1188,13 @@ mod tests {
                     leader_id: "test-ingester-0".to_string(),
                     ..Default::default()
                 },
+                Shard {
+                    index_uid: "test-index-0:0".to_string(),
+                    shard_id: 3,
+                    shard_state: ShardState::Open as i32,
+                    leader_id: "test-ingester-0".to_string(),
+                    ..Default::default()
+                },
             ],
         );
         drop(state_guard);
@@ -994,6 +1225,13 @@ mod tests {
                         shard_id: 2,
                         reason: PersistFailureReason::ShardClosed as i32,
                     },
+                    PersistFailure {
+                        subrequest_id: 2,
+                        index_uid: "test-index-0:0".to_string(),
+                        source_id: "test-source".to_string(),
+                        shard_id: 3,
+                        reason: PersistFailureReason::DiskWatermarkExceeded as i32,
+                    },
                 ],
             });
             (persist_summary, persist_result)
@@ -1007,11 +1245,15 @@ mod tests {
             .routing_table
             .find_entry("test-index-0", "test-source")
             .unwrap();
-        assert_eq!(routing_table_entry.len(), 1);
+        assert_eq!(routing_table_entry.len(), 2);
 
         let shard = routing_table_entry.all_shards()[0];
         assert_eq!(shard.shard_id, 2);
         assert_eq!(shard.shard_state, ShardState::Closed);
+
+        let shard = routing_table_entry.all_shards()[1];
+        assert_eq!(shard.shard_id, 3);
+        assert_eq!(shard.shard_state, ShardState::Closed);
     }
 
     #[tokio::test]
@@ -1409,6 +1651,147 @@ mod tests {
         router.ingest(ingest_request).await.unwrap();
     }
 
+    #[test]
+    fn test_make_hedged_persist_request() {
+        let subrequests = vec![
+            PersistSubrequest {
+                subrequest_id: 0,
+                index_uid: "test-index-0:0".to_string(),
+                source_id: "test-source".to_string(),
+                shard_id: 1,
+                doc_batch: Some(DocBatchV2::for_test(["test-doc-foo"])),
+                batch_uid: Some("test-request:0".to_string()),
+            },
+            PersistSubrequest {
+                subrequest_id: 1,
+                index_uid: "test-index-1:0".to_string(),
+                source_id: "test-source".to_string(),
+                shard_id: 1,
+                doc_batch: Some(DocBatchV2::for_test(["test-doc-bar"])),
+                batch_uid: Some("test-request:1".to_string()),
+            },
+        ];
+        let leader_id: NodeId = "test-ingester-0".into();
+        let follower_id: NodeId = "test-ingester-1".into();
+        let other_follower_id: NodeId = "test-ingester-2".into();
+
+        let (hedged_follower_id, hedged_persist_request) = make_hedged_persist_request(
+            &leader_id,
+            &subrequests,
+            &[Some(&follower_id), Some(&follower_id)],
+            CommitTypeV2::Auto,
+        )
+        .unwrap();
+        assert_eq!(hedged_follower_id, follower_id);
+        // The hedged request targets the same shards, led by the same leader.
+        assert_eq!(hedged_persist_request.leader_id, "test-ingester-0");
+        assert_eq!(hedged_persist_request.commit_type(), CommitTypeV2::Auto);
+        assert_eq!(hedged_persist_request.subrequests, subrequests);
+
+        // The shards are not replicated.
+        assert!(make_hedged_persist_request(
+            &leader_id,
+            &subrequests,
+            &[Some(&follower_id), None],
+            CommitTypeV2::Auto,
+        )
+        .is_none());
+
+        // The shards are replicated on different followers.
+        assert!(make_hedged_persist_request(
+            &leader_id,
+            &subrequests,
+            &[Some(&follower_id), Some(&other_follower_id)],
+            CommitTypeV2::Auto,
+        )
+        .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_router_retry_batch_persist_waits_for_unavailable_ingesters() {
+        let self_node_id = "test-router".into();
+        let ingester_pool = IngesterPool::default();
+
+        let mut ingester_mock_0 = IngesterServiceClient::mock();
+        ingester_mock_0
+            .expect_persist()
+            .once()
+            .returning(|request| {
+                assert_eq!(request.leader_id, "test-ingester-0");
+                assert_eq!(request.subrequests.len(), 1);
+
+                let response = PersistResponse {
+                    leader_id: request.leader_id,
+                    successes: vec![PersistSuccess {
+                        subrequest_id: 0,
+                        index_uid: "test-index-0:0".to_string(),
+                        source_id: "test-source".to_string(),
+                        shard_id: 1,
+                        replication_position_inclusive: Some(Position::offset(0u64)),
+                    }],
+                    failures: Vec::new(),
+                };
+                Ok(response)
+            });
+        let ingester_0: IngesterServiceClient = ingester_mock_0.into();
+
+        // The ingester joins the pool on the third attempt, after the regular attempts are
+        // exhausted.
+        let mut num_attempts = 0;
+        let ingester_pool_clone = ingester_pool.clone();
+
+        let mut control_plane_mock = ControlPlaneServiceClient::mock();
+        control_plane_mock
+            .expect_get_or_create_open_shards()
+            .times(3)
+            .returning(move |request| {
+                assert_eq!(request.subrequests.len(), 1);
+
+                num_attempts += 1;
+
+                if num_attempts == 3 {
+                    ingester_pool_clone.insert("test-ingester-0".into(), ingester_0.clone());
+                }
+                let response = GetOrCreateOpenShardsResponse {
+                    successes: vec![GetOrCreateOpenShardsSuccess {
+                        subrequest_id: 0,
+                        index_uid: "test-index-0:0".to_string(),
+                        source_id: "test-source".to_string(),
+                        open_shards: vec![Shard {
+                            index_uid: "test-index-0:0".to_string(),
+                            source_id: "test-source".to_string(),
+                            shard_id: 1,
+                            shard_state: ShardState::Open as i32,
+                            leader_id: "test-ingester-0".to_string(),
+                            ..Default::default()
+                        }],
+                    }],
+                    failures: Vec::new(),
+                };
+                Ok(response)
+            });
+        let control_plane: ControlPlaneServiceClient = control_plane_mock.into();
+        let replication_factor = 1;
+        let mut router = IngestRouter::new(
+            self_node_id,
+            control_plane,
+            ingester_pool.clone(),
+            replication_factor,
+        );
+        let ingest_request = IngestRequestV2 {
+            subrequests: vec![IngestSubrequest {
+                subrequest_id: 0,
+                index_id: "test-index-0".to_string(),
+                source_id: "test-source".to_string(),
+                doc_batch: Some(DocBatchV2::for_test(["test-doc-foo"])),
+            }],
+            commit_type: CommitTypeV2::Auto as i32,
+        };
+        let ingest_response = router.retry_batch_persist(ingest_request, 2).await.unwrap();
+        assert_eq!(ingest_response.successes.len(), 1);
+        assert!(ingest_response.failures.is_empty());
+    }
+
     #[tokio::test]
     async fn test_router_updates_routing_table_on_chitchat_events() {
         let self_node_id = "test-router".into();
@@ -1449,11 +1832,13 @@ mod tests {
                     shard_id: 1,
                     shard_state: ShardState::Closed,
                     ingestion_rate: RateMibPerSec(0),
+                    replication_position_inclusive: Position::Beginning,
                 },
                 ShardInfo {
                     shard_id: 2,
                     shard_state: ShardState::Open,
                     ingestion_rate: RateMibPerSec(0),
+                    replication_position_inclusive: Position::Beginning,
                 },
             ]),
         };
@@ -34,6 +34,9 @@ pub(super) struct RoutingEntry {
     pub shard_id: ShardId,
     pub shard_state: ShardState,
     pub leader_id: NodeId,
+    /// The follower of the shard, if the shard is replicated and the router knows it. Shards
+    /// learned through gossip do not carry their follower.
+    pub follower_id_opt: Option<NodeId>,
 }
 
 impl From<Shard> for RoutingEntry {
@@ -45,6 +48,7 @@ impl From<Shard> for RoutingEntry {
             shard_id: shard.shard_id,
             shard_state,
             leader_id: shard.leader_id.into(),
+            follower_id_opt: shard.follower_id.map(NodeId::from),
         }
     }
 }
@@ -193,6 +197,7 @@ impl RoutingTableEntry {
                 shard_id,
                 shard_state: ShardState::Open,
                 leader_id: leader_id.clone(),
+                follower_id_opt: None,
             }));
             num_inserted_shards = target_shards.len();
         } else {
@@ -214,6 +219,7 @@ impl RoutingTableEntry {
                         shard_id,
                         shard_state: ShardState::Open,
                         leader_id: leader_id.clone(),
+                        follower_id_opt: None,
                     });
                     num_inserted_shards += 1;
                 }
@@ -556,6 +562,7 @@ mod tests {
                     shard_id: 1,
                     shard_state: ShardState::Closed,
                     leader_id: "test-ingester-0".into(),
+                    follower_id_opt: None,
                 },
                 RoutingEntry {
                     index_uid: "test-index:0".into(),
@@ -563,6 +570,7 @@ mod tests {
                     shard_id: 2,
                     shard_state: ShardState::Open,
                     leader_id: "test-ingester-0".into(),
+                    follower_id_opt: None,
                 },
             ],
             local_round_robin_idx: AtomicUsize::default(),
@@ -592,6 +600,7 @@ mod tests {
                     shard_id: 1,
                     shard_state: ShardState::Closed,
                     leader_id: "test-ingester-1".into(),
+                    follower_id_opt: None,
                 },
                 RoutingEntry {
                     index_uid: "test-index:0".into(),
@@ -599,6 +608,7 @@ mod tests {
                     shard_id: 2,
                     shard_state: ShardState::Open,
                     leader_id: "test-ingester-2".into(),
+                    follower_id_opt: None,
                 },
                 RoutingEntry {
                     index_uid: "test-index:0".into(),
@@ -606,6 +616,7 @@ mod tests {
                     shard_id: 3,
                     shard_state: ShardState::Open,
                     leader_id: "test-ingester-1".into(),
+                    follower_id_opt: None,
                 },
             ],
             remote_round_robin_idx: AtomicUsize::default(),
@@ -650,6 +661,7 @@ mod tests {
                     shard_id: 1,
                     shard_state: ShardState::Closed,
                     leader_id: "test-ingester-0".into(),
+                    follower_id_opt: None,
                 },
                 RoutingEntry {
                     index_uid: "test-index:0".into(),
@@ -657,6 +669,7 @@ mod tests {
                     shard_id: 2,
                     shard_state: ShardState::Open,
                     leader_id: "test-ingester-0".into(),
+                    follower_id_opt: None,
                 },
                 RoutingEntry {
                     index_uid: "test-index:0".into(),
@@ -664,6 +677,7 @@ mod tests {
                     shard_id: 3,
                     shard_state: ShardState::Open,
                     leader_id: "test-ingester-0".into(),
+                    follower_id_opt: None,
                 },
             ],
             local_round_robin_idx: AtomicUsize::default(),
@@ -694,6 +708,7 @@ mod tests {
                 shard_id: 1,
                 shard_state: ShardState::Closed,
                 leader_id: "test-ingester-0".into(),
+                follower_id_opt: None,
             }],
             local_round_robin_idx: AtomicUsize::default(),
             remote_shards: vec![
@@ -703,6 +718,7 @@ mod tests {
                     shard_id: 2,
                     shard_state: ShardState::Open,
                     leader_id: "test-ingester-1".into(),
+                    follower_id_opt: None,
                 },
                 RoutingEntry {
                     index_uid: "test-index:0".into(),
@@ -710,6 +726,7 @@ mod tests {
                     shard_id: 3,
                     shard_state: ShardState::Closed,
                     leader_id: "test-ingester-1".into(),
+                    follower_id_opt: None,
                 },
                 RoutingEntry {
                     index_uid: "test-index:0".into(),
@@ -717,6 +734,7 @@ mod tests {
                     shard_id: 4,
                     shard_state: ShardState::Open,
                     leader_id: "test-ingester-2".into(),
+                    follower_id_opt: None,
                 },
                 RoutingEntry {
                     index_uid: "test-index:0".into(),
@@ -724,6 +742,7 @@ mod tests {
                     shard_id: 5,
                     shard_state: ShardState::Open,
                     leader_id: "test-ingester-1".into(),
+                    follower_id_opt: None,
                 },
             ],
             remote_round_robin_idx: AtomicUsize::default(),
@@ -849,6 +868,7 @@ mod tests {
                     shard_id: 1,
                     shard_state: ShardState::Open,
                     leader_id: "test-ingester-0".into(),
+                    follower_id_opt: None,
                 },
                 RoutingEntry {
                     index_uid: "test-index:0".into(),
@@ -856,6 +876,7 @@ mod tests {
                     shard_id: 2,
                     shard_state: ShardState::Open,
                     leader_id: "test-ingester-0".into(),
+                    follower_id_opt: None,
                 },
                 RoutingEntry {
                     index_uid: "test-index:0".into(),
@@ -863,6 +884,7 @@ mod tests {
                     shard_id: 3,
                     shard_state: ShardState::Open,
                     leader_id: "test-ingester-0".into(),
+                    follower_id_opt: None,
                 },
             ],
             local_round_robin_idx: AtomicUsize::default(),
@@ -873,6 +895,7 @@ mod tests {
                     shard_id: 5,
                     shard_state: ShardState::Open,
                     leader_id: "test-ingester-1".into(),
+                    follower_id_opt: None,
                 },
                 RoutingEntry {
                     index_uid: "test-index:0".into(),
@@ -880,6 +903,7 @@ mod tests {
                     shard_id: 6,
                     shard_state: ShardState::Open,
                     leader_id: "test-ingester-1".into(),
+                    follower_id_opt: None,
                 },
                 RoutingEntry {
                     index_uid: "test-index:0".into(),
@@ -887,6 +911,7 @@ mod tests {
                     shard_id: 7,
                     shard_state: ShardState::Open,
                     leader_id: "test-ingester-1".into(),
+                    follower_id_opt: None,
                 },
             ],
             remote_round_robin_idx: AtomicUsize::default(),
@@ -921,6 +946,7 @@ mod tests {
                     shard_id: 1,
                     shard_state: ShardState::Open,
                     leader_id: "test-ingester-0".into(),
+                    follower_id_opt: None,
                 },
                 RoutingEntry {
                     index_uid: "test-index:0".into(),
@@ -928,6 +954,7 @@ mod tests {
                     shard_id: 2,
                     shard_state: ShardState::Open,
                     leader_id: "test-ingester-0".into(),
+                    follower_id_opt: None,
                 },
                 RoutingEntry {
                     index_uid: "test-index:0".into(),
@@ -935,6 +962,7 @@ mod tests {
                     shard_id: 3,
                     shard_state: ShardState::Open,
                     leader_id: "test-ingester-0".into(),
+                    follower_id_opt: None,
                 },
             ],
             local_round_robin_idx: AtomicUsize::default(),
@@ -945,6 +973,7 @@ mod tests {
                     shard_id: 5,
                     shard_state: ShardState::Open,
                     leader_id: "test-ingester-1".into(),
+                    follower_id_opt: None,
                 },
                 RoutingEntry {
                     index_uid: "test-index:0".into(),
@@ -952,6 +981,7 @@ mod tests {
                     shard_id: 6,
                     shard_state: ShardState::Open,
                     leader_id: "test-ingester-1".into(),
+                    follower_id_opt: None,
                 },
                 RoutingEntry {
                     index_uid: "test-index:0".into(),
@@ -959,6 +989,7 @@ mod tests {
                     shard_id: 7,
                     shard_state: ShardState::Open,
                     leader_id: "test-ingester-1".into(),
+                    follower_id_opt: None,
                 },
             ],
             remote_round_robin_idx: AtomicUsize::default(),
@@ -29,6 +29,7 @@ use quickwit_proto::ingest::router::{
 use quickwit_proto::ingest::IngestV2Result;
 use quickwit_proto::types::{ShardId, SubrequestId};
 use tracing::warn;
+use ulid::Ulid;
 
 /// A helper struct for managing the state of the subrequests of an ingest request during multiple
 /// persist attempts.
@@ -40,6 +41,9 @@ pub(super) struct IngestWorkbench {
     /// subrequest.
     pub num_attempts: usize,
     pub max_num_attempts: usize,
+    /// Unique identifier of the ingest request, from which the batch UIDs of the persist
+    /// subrequests are derived.
+    pub request_uid: String,
 }
 
 impl IngestWorkbench {
@@ -57,10 +61,17 @@ impl IngestWorkbench {
         Self {
             subworkbenches,
             max_num_attempts,
+            request_uid: Ulid::new().to_string(),
             ..Default::default()
         }
     }
 
+    /// Returns the UID of the batch of documents of a subrequest. It remains the same across the
+    /// persist attempts so that ingesters can deduplicate the batches they already persisted.
+    pub fn batch_uid(&self, subrequest_id: SubrequestId) -> String {
+        format!("{}:{subrequest_id}", self.request_uid)
+    }
+
     pub fn new_attempt(&mut self) {
         self.num_attempts += 1;
     }
@@ -75,6 +86,18 @@ impl IngestWorkbench {
         self.num_attempts >= self.max_num_attempts
     }
 
+    /// Returns whether some pending subrequests could not be routed because no shards were
+    /// available, which happens when their leaders are unavailable.
+    pub fn has_pending_no_shards_available(&self) -> bool {
+        self.subworkbenches.values().any(|subworkbench| {
+            subworkbench.is_pending()
+                && matches!(
+                    subworkbench.last_failure_opt,
+                    Some(SubworkbenchFailure::NoShardsAvailable)
+                )
+        })
+    }
+
     fn has_no_pending_subrequests(&self) -> bool {
         self.subworkbenches
             .values()
@@ -176,6 +199,8 @@ impl IngestWorkbench {
         let mut failures = Vec::with_capacity(num_subworkbenches - self.num_successes);
 
         for subworkbench in self.subworkbenches.into_values() {
+            let num_docs = subworkbench.num_docs();
+
             if let Some(persist_success) = subworkbench.persist_success_opt {
                 let success = IngestSuccess {
                     subrequest_id: persist_success.subrequest_id,
@@ -183,6 +208,7 @@ impl IngestWorkbench {
                     source_id: persist_success.source_id,
                     shard_id: persist_success.shard_id,
                     replication_position_inclusive: persist_success.replication_position_inclusive,
+                    num_docs,
                 };
                 successes.push(success);
             } else if let Some(failure) = subworkbench.last_failure_opt {
@@ -191,6 +217,7 @@ impl IngestWorkbench {
                     index_id: subworkbench.subrequest.index_id,
                     source_id: subworkbench.subrequest.source_id,
                     reason: failure.reason() as i32,
+                    num_docs,
                 };
                 failures.push(failure);
             }
@@ -258,6 +285,14 @@ impl IngestSubworkbench {
         }
     }
 
+    fn num_docs(&self) -> u32 {
+        self.subrequest
+            .doc_batch
+            .as_ref()
+            .map(|doc_batch| doc_batch.num_docs() as u32)
+            .unwrap_or(0)
+    }
+
     pub fn is_pending(&self) -> bool {
         self.persist_success_opt.is_none() && self.last_failure_is_transient()
     }
@@ -277,6 +312,7 @@ impl IngestSubworkbench {
 #[cfg(test)]
 mod tests {
     use quickwit_proto::ingest::ingester::PersistFailureReason;
+    use quickwit_proto::ingest::DocBatchV2;
 
     use super::*;
 
@@ -502,4 +538,54 @@ mod tests {
         ));
         assert_eq!(subworkbench.num_attempts, 1);
     }
+
+    #[test]
+    fn test_ingest_workbench_into_ingest_response() {
+        let ingest_subrequests = vec![
+            IngestSubrequest {
+                subrequest_id: 0,
+                index_id: "test-index-foo".to_string(),
+                source_id: "test-source".to_string(),
+                doc_batch: Some(DocBatchV2::for_test(["test-doc-foo", "test-doc-bar"])),
+            },
+            IngestSubrequest {
+                subrequest_id: 1,
+                index_id: "test-index-bar".to_string(),
+                source_id: "test-source".to_string(),
+                doc_batch: Some(DocBatchV2::for_test(["test-doc-baz"])),
+            },
+        ];
+        let mut workbench = IngestWorkbench::new(ingest_subrequests, 1);
+
+        let persist_success = PersistSuccess {
+            subrequest_id: 0,
+            index_uid: "test-index-foo:0".to_string(),
+            source_id: "test-source".to_string(),
+            shard_id: 1,
+            ..Default::default()
+        };
+        workbench.record_persist_success(persist_success);
+
+        let persist_failure = PersistFailure {
+            subrequest_id: 1,
+            shard_id: 2,
+            reason: PersistFailureReason::RateLimited as i32,
+            ..Default::default()
+        };
+        workbench.record_persist_failure(&persist_failure);
+
+        let response = workbench.into_ingest_response().unwrap();
+        assert_eq!(response.successes.len(), 1);
+        assert_eq!(response.successes[0].subrequest_id, 0);
+        assert_eq!(response.successes[0].num_docs, 2);
+
+        assert_eq!(response.failures.len(), 1);
+        assert_eq!(response.failures[0].subrequest_id, 1);
+        assert_eq!(response.failures[0].index_id, "test-index-bar");
+        assert_eq!(
+            response.failures[0].reason(),
+            IngestFailureReason::RateLimited
+        );
+        assert_eq!(response.failures[0].num_docs, 1);
+    }
 }
@@ -21,6 +21,7 @@
 
 mod doc_batch;
 mod errors;
+mod idempotency;
 mod ingest_api_service;
 #[path = "codegen/ingest_service.rs"]
 mod ingest_service;
@@ -75,6 +76,7 @@ pub async fn init_ingest_api(
         queues_dir_path,
         config.max_queue_memory_usage.as_u64() as usize,
         config.max_queue_disk_usage.as_u64() as usize,
+        config.idempotency_key_ttl(),
     )
     .await
     .with_context(|| {
@@ -250,6 +252,8 @@ mod tests {
                 },
             ],
             commit: CommitType::Auto.into(),
+            idempotency_key: None,
+            return_positions: false,
         };
         let ingest_result = ingest_api_service.ask_for_res(ingest_request).await;
         assert!(ingest_result.is_err());
@@ -294,6 +298,8 @@ mod tests {
                 doc_lengths: vec![30; 20],
             }],
             commit: CommitType::Auto.into(),
+            idempotency_key: None,
+            return_positions: false,
         };
 
         ingest_api_service
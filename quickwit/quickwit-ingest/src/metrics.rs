@@ -18,11 +18,15 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 use once_cell::sync::Lazy;
-use quickwit_common::metrics::{new_counter, new_gauge, IntCounter, IntGauge};
+use quickwit_common::metrics::{
+    index_label, new_counter, new_counter_vec, new_gauge, IntCounter, IntCounterVec, IntGauge,
+};
 
 pub struct IngestMetrics {
     pub ingested_num_bytes: IntCounter,
     pub ingested_num_docs: IntCounter,
+    pub ingested_num_bytes_per_index: IntCounterVec<1>,
+    pub ingested_num_docs_per_index: IntCounterVec<1>,
     pub replicated_num_bytes_total: IntCounter,
     pub replicated_num_docs_total: IntCounter,
     pub queue_count: IntGauge,
@@ -41,6 +45,18 @@ impl Default for IngestMetrics {
                 "Number of docs received to be ingested",
                 "quickwit_ingest",
             ),
+            ingested_num_bytes_per_index: new_counter_vec(
+                "ingested_num_bytes_per_index",
+                "Total size of the docs ingested in bytes per index",
+                "quickwit_ingest",
+                ["index"],
+            ),
+            ingested_num_docs_per_index: new_counter_vec(
+                "ingested_num_docs_per_index",
+                "Number of docs received to be ingested per index",
+                "quickwit_ingest",
+                ["index"],
+            ),
             replicated_num_bytes_total: new_counter(
                 "replicated_num_bytes_total",
                 "Total size in bytes of the replicated docs.",
@@ -60,4 +76,22 @@ impl Default for IngestMetrics {
     }
 }
 
+impl IngestMetrics {
+    /// Records a batch of docs ingested into `index_id`. The metrics labeled by index ID are only
+    /// updated if they are enabled.
+    pub fn record_ingested_docs(&self, index_id: &str, num_docs: u64, num_bytes: u64) {
+        self.ingested_num_docs.inc_by(num_docs);
+        self.ingested_num_bytes.inc_by(num_bytes);
+
+        if let Some(index_label) = index_label(index_id) {
+            self.ingested_num_docs_per_index
+                .with_label_values([index_label])
+                .inc_by(num_docs);
+            self.ingested_num_bytes_per_index
+                .with_label_values([index_label])
+                .inc_by(num_bytes);
+        }
+    }
+}
+
 pub static INGEST_METRICS: Lazy<IngestMetrics> = Lazy::new(IngestMetrics::default);
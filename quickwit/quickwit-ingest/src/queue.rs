@@ -34,6 +34,10 @@ const FETCH_PAYLOAD_LIMIT: usize = 2_000_000; // 2MB
 // TODO do we need to keep this?
 const QUICKWIT_CF_PREFIX: &str = ".queue_";
 
+/// Internal queue storing the idempotency key records. It does not have the
+/// `QUICKWIT_CF_PREFIX` prefix so it is not listed along with the index queues.
+const IDEMPOTENCY_KEYS_QUEUE_ID: &str = ".idempotency_keys";
+
 pub struct Queues {
     record_log: MultiRecordLog,
 }
@@ -41,7 +45,14 @@ pub struct Queues {
 impl Queues {
     pub async fn open(queues_dir_path: &Path) -> crate::Result<Queues> {
         tokio::fs::create_dir_all(queues_dir_path).await.unwrap();
-        let record_log = MultiRecordLog::open(queues_dir_path).await?;
+        let mut record_log = MultiRecordLog::open(queues_dir_path).await?;
+
+        // The queue is created on the first start and persists afterwards.
+        if let Err(CreateQueueError::IoError(io_error)) =
+            record_log.create_queue(IDEMPOTENCY_KEYS_QUEUE_ID).await
+        {
+            return Err(io_error.into());
+        }
         Ok(Queues { record_log })
     }
 
@@ -208,6 +219,52 @@ impl Queues {
         })
     }
 
+    /// Returns the idempotency key records along with their positions, in the order they were
+    /// appended.
+    pub(crate) fn idempotency_key_records(&self) -> crate::Result<Vec<(u64, Vec<u8>)>> {
+        let records = self
+            .record_log
+            .range(IDEMPOTENCY_KEYS_QUEUE_ID, ..)
+            .map_err(|_| {
+                IngestServiceError::Corruption("idempotency keys queue is missing".to_string())
+            })?
+            .map(|(position, record)| (position, record.to_vec()))
+            .collect();
+        Ok(records)
+    }
+
+    /// Appends an idempotency key record and returns its position.
+    pub(crate) async fn append_idempotency_key_record(
+        &mut self,
+        record: &[u8],
+        ctx: &ActorContext<IngestApiService>,
+    ) -> crate::Result<u64> {
+        let position_opt = ctx
+            .protect_future(self.record_log.append_records(
+                IDEMPOTENCY_KEYS_QUEUE_ID,
+                None,
+                std::iter::once(record),
+            ))
+            .await?;
+        position_opt.ok_or_else(|| {
+            IngestServiceError::Internal("failed to append idempotency key record".to_string())
+        })
+    }
+
+    /// Removes the idempotency key records up to and including `up_to_position_included`.
+    pub(crate) async fn truncate_idempotency_key_records(
+        &mut self,
+        up_to_position_included: u64,
+        ctx: &ActorContext<IngestApiService>,
+    ) -> crate::Result<()> {
+        ctx.protect_future(
+            self.record_log
+                .truncate(IDEMPOTENCY_KEYS_QUEUE_ID, up_to_position_included),
+        )
+        .await?;
+        Ok(())
+    }
+
     pub(crate) fn disk_usage(&self) -> usize {
         self.record_log.disk_usage()
     }
@@ -431,6 +488,37 @@ mod tests {
         queues.fetch_test(TEST_QUEUE_ID, Some(1), Some(2), &[&b"tax"[..]]);
     }
 
+    #[tokio::test]
+    async fn test_idempotency_key_records() {
+        let (mut queues, ctx) = QueuesForTest::new().await;
+        assert!(queues.idempotency_key_records().unwrap().is_empty());
+
+        queues
+            .append_idempotency_key_record(b"key-1", &ctx)
+            .await
+            .unwrap();
+        queues
+            .append_idempotency_key_record(b"key-2", &ctx)
+            .await
+            .unwrap();
+        queues.reload().await;
+        assert_eq!(
+            queues.idempotency_key_records().unwrap(),
+            [(0, b"key-1".to_vec()), (1, b"key-2".to_vec())]
+        );
+        assert!(queues.list_queues().unwrap().queues.is_empty());
+
+        queues
+            .truncate_idempotency_key_records(0, &ctx)
+            .await
+            .unwrap();
+        queues.reload().await;
+        assert_eq!(
+            queues.idempotency_key_records().unwrap(),
+            [(1, b"key-2".to_vec())]
+        );
+    }
+
     struct Record {
         queue_id: String,
         payload: Vec<u8>,
@@ -256,10 +256,18 @@ async fn test_ingest_v2_index_not_found() {
         )
         .await
         .unwrap_err();
-    let Error::Api(ApiError { message, code }) = missing_index_err else {
+    let Error::Api(ApiError {
+        message,
+        code,
+        error_code,
+        retryable,
+    }) = missing_index_err
+    else {
         panic!("Expected an API error.");
     };
     assert_eq!(code, 404u16);
+    assert_eq!(error_code.unwrap(), "not_found");
+    assert!(!retryable);
     let error_message = message.unwrap();
     assert_eq!(error_message, "index `missing_index` not found");
     sandbox.shutdown().await.unwrap();
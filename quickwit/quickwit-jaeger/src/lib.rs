@@ -109,6 +109,7 @@ impl JaegerService {
             end_timestamp: None,
             start_key: None,
             end_key: None,
+            include_doc_counts: false,
         };
         let search_response = self.search_service.root_list_terms(search_request).await?;
         let services: Vec<String> = search_response
@@ -146,6 +147,7 @@ impl JaegerService {
             end_timestamp: None,
             start_key,
             end_key,
+            include_doc_counts: false,
         };
         let search_response = self.search_service.root_list_terms(search_request).await?;
         let operations: Vec<Operation> = search_response
@@ -2392,6 +2394,7 @@ mod tests {
                     ],
                     elapsed_time_micros: 0,
                     errors: Vec::new(),
+                    doc_counts: Vec::new(),
                 })
             });
 
@@ -44,6 +44,7 @@ use quickwit_search::SearchJobPlacer;
 use quickwit_storage::Storage;
 use serde::Serialize;
 use tokio::join;
+use tokio::sync::Semaphore;
 use tracing::info;
 
 use super::delete_task_planner::DeleteTaskPlanner;
@@ -85,6 +86,8 @@ pub struct DeleteTaskPipeline {
     delete_service_task_dir: PathBuf,
     handles: Option<DeletePipelineHandle>,
     max_concurrent_split_uploads: usize,
+    delete_operation_permits: Arc<Semaphore>,
+    delete_io_controls_opt: Option<IoControls>,
     state: DeleteTaskPipelineState,
     event_broker: EventBroker,
 }
@@ -134,6 +137,8 @@ impl DeleteTaskPipeline {
         index_storage: Arc<dyn Storage>,
         delete_service_task_dir: PathBuf,
         max_concurrent_split_uploads: usize,
+        delete_operation_permits: Arc<Semaphore>,
+        delete_io_controls_opt: Option<IoControls>,
         event_broker: EventBroker,
     ) -> Self {
         Self {
@@ -144,6 +149,8 @@ impl DeleteTaskPipeline {
             delete_service_task_dir,
             handles: Default::default(),
             max_concurrent_split_uploads,
+            delete_operation_permits,
+            delete_io_controls_opt,
             state: DeleteTaskPipelineState::default(),
             event_broker,
         }
@@ -169,8 +176,14 @@ impl DeleteTaskPipeline {
         );
         let (publisher_mailbox, publisher_supervisor_handler) =
             ctx.spawn_actor().supervise(publisher);
+        let encryption_key_id_opt = index_config
+            .encryption
+            .as_ref()
+            .and_then(|encryption_config| encryption_config.data_key_id())
+            .map(str::to_string);
         let split_store =
-            IndexingSplitStore::create_without_local_store_for_test(self.index_storage.clone());
+            IndexingSplitStore::create_without_local_store_for_test(self.index_storage.clone())
+                .with_encryption_key_id(encryption_key_id_opt);
         let merge_policy = merge_policy_from_settings(&index_config.indexing_settings);
         let uploader = Uploader::new(
             UploaderType::DeleteUploader,
@@ -186,7 +199,15 @@ impl DeleteTaskPipeline {
         let doc_mapper =
             build_doc_mapper(&index_config.doc_mapping, &index_config.search_settings)?;
         let tag_fields = doc_mapper.tag_named_fields()?;
-        let packager = Packager::new("MergePackager", tag_fields, uploader_mailbox);
+        let stats_fields = doc_mapper.stats_named_fields()?;
+        let doc_id_field_opt = doc_mapper.doc_id_named_field()?;
+        let packager = Packager::new(
+            "MergePackager",
+            tag_fields,
+            stats_fields,
+            doc_id_field_opt,
+            uploader_mailbox,
+        );
         let (packager_mailbox, packager_supervisor_handler) = ctx.spawn_actor().supervise(packager);
         let index_pipeline_id = IndexingPipelineId {
             index_uid: self.index_uid.clone(),
@@ -201,11 +222,17 @@ impl DeleteTaskPipeline {
             .as_ref()
             .map(|bytes_per_sec| bytes_per_sec.as_u64() as f64)
             .unwrap_or(f64::INFINITY);
-        let delete_executor_io_controls = IoControls::default()
+        let index_io_controls = IoControls::default()
             .set_throughput_limit(throughput_limit)
             .set_index_and_component(self.index_uid.index_id(), "deleter");
-        let split_download_io_controls = delete_executor_io_controls
-            .clone()
+        // The node-wide throughput limit, if any, supersedes the index limit for the executor.
+        let delete_executor_io_controls = match &self.delete_io_controls_opt {
+            Some(delete_io_controls) => delete_io_controls
+                .clone()
+                .set_index_and_component(self.index_uid.index_id(), "deleter"),
+            None => index_io_controls.clone(),
+        };
+        let split_download_io_controls = index_io_controls
             .set_index_and_component(self.index_uid.index_id(), "split_downloader_delete");
         let delete_executor = MergeExecutor::new(
             index_pipeline_id,
@@ -213,7 +240,8 @@ impl DeleteTaskPipeline {
             doc_mapper.clone(),
             delete_executor_io_controls,
             packager_mailbox,
-        );
+        )
+        .set_concurrency_permits(self.delete_operation_permits.clone());
         let (delete_executor_mailbox, task_executor_supervisor_handler) =
             ctx.spawn_actor().supervise(delete_executor);
         let scratch_directory = temp_dir::Builder::default()
@@ -229,15 +257,26 @@ impl DeleteTaskPipeline {
         let (downloader_mailbox, downloader_supervisor_handler) =
             ctx.spawn_actor().supervise(merge_split_downloader);
         let doc_mapper_str = serde_json::to_string(&doc_mapper)?;
+        let encryption_config_str_opt = index_config
+            .encryption
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()?;
         let index_uri: &Uri = &index_config.index_uri;
         let task_planner = DeleteTaskPlanner::new(
             self.index_uid.clone(),
             index_uri.clone(),
             doc_mapper_str,
+            encryption_config_str_opt,
             self.metastore.clone(),
             self.search_job_placer.clone(),
             downloader_mailbox,
         );
+        let task_planner = match doc_mapper.doc_id_field_name() {
+            Some(doc_id_field_name) => task_planner
+                .with_doc_ids_pruning(doc_id_field_name.to_string(), self.index_storage.clone()),
+            None => task_planner,
+        };
         let (_, task_planner_supervisor_handler) = ctx.spawn_actor().supervise(task_planner);
         self.handles = Some(DeletePipelineHandle {
             delete_task_planner: task_planner_supervisor_handler,
@@ -286,6 +325,8 @@ impl Handler<Observe> for DeleteTaskPipeline {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
     use async_trait::async_trait;
     use quickwit_actors::Handler;
     use quickwit_common::pubsub::EventBroker;
@@ -297,6 +338,7 @@ mod tests {
     use quickwit_search::{
         searcher_pool_for_test, MockSearchService, SearchError, SearchJobPlacer,
     };
+    use tokio::sync::Semaphore;
 
     use super::{ActorContext, ActorExitStatus, DeleteTaskPipeline, OBSERVE_PIPELINE_INTERVAL};
 
@@ -394,6 +436,8 @@ mod tests {
             test_sandbox.storage(),
             delete_service_task_dir.path().into(),
             4,
+            Arc::new(Semaphore::new(1)),
+            None,
             EventBroker::default(),
         );
 
@@ -476,6 +520,8 @@ mod tests {
             test_sandbox.storage(),
             delete_service_task_dir.path().into(),
             4,
+            Arc::new(Semaphore::new(1)),
+            None,
             EventBroker::default(),
         );
 
@@ -18,13 +18,16 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::Context;
 use async_trait::async_trait;
 use itertools::Itertools;
 use quickwit_actors::{Actor, ActorContext, ActorExitStatus, Handler, Mailbox, QueueCapacity};
+use quickwit_common::bloom_filter::BloomFilter;
 use quickwit_common::extract_time_range;
 use quickwit_common::uri::Uri;
 use quickwit_doc_mapper::tag_pruning::extract_tags_from_query;
@@ -37,10 +40,12 @@ use quickwit_proto::metastore::{
 };
 use quickwit_proto::search::SearchRequest;
 use quickwit_proto::types::IndexUid;
+use quickwit_query::query_ast::QueryAst;
 use quickwit_search::{jobs_to_leaf_requests, IndexMetasForLeafSearch, SearchJob, SearchJobPlacer};
+use quickwit_storage::Storage;
 use serde::Serialize;
 use tantivy::Inventory;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 use crate::metrics::JANITOR_METRICS;
 
@@ -80,6 +85,8 @@ pub struct DeleteTaskPlanner {
     index_uid: IndexUid,
     index_uri: Uri,
     doc_mapper_str: String,
+    /// Encryption config of the index as a JSON string, if the index is encrypted.
+    encryption_config_str_opt: Option<String>,
     metastore: MetastoreServiceClient,
     search_job_placer: SearchJobPlacer,
     merge_split_downloader_mailbox: Mailbox<MergeSplitDownloader>,
@@ -88,6 +95,9 @@ pub struct DeleteTaskPlanner {
     /// the delete operation.
     /// The inventory is used to avoid sending twice the same delete operation.
     ongoing_delete_operations_inventory: Inventory<MergeOperation>,
+    /// Name of the doc ID field of the index and storage of its splits, used to skip the delete
+    /// by ID tasks whose IDs are absent from the doc IDs sidecar of a split.
+    doc_ids_pruning_opt: Option<(String, Arc<dyn Storage>)>,
 }
 
 #[async_trait]
@@ -124,6 +134,7 @@ impl DeleteTaskPlanner {
         index_uid: IndexUid,
         index_uri: Uri,
         doc_mapper_str: String,
+        encryption_config_str_opt: Option<String>,
         metastore: MetastoreServiceClient,
         search_job_placer: SearchJobPlacer,
         merge_split_downloader_mailbox: Mailbox<MergeSplitDownloader>,
@@ -132,13 +143,25 @@ impl DeleteTaskPlanner {
             index_uid,
             index_uri,
             doc_mapper_str,
+            encryption_config_str_opt,
             metastore,
             search_job_placer,
             merge_split_downloader_mailbox,
             ongoing_delete_operations_inventory: Inventory::new(),
+            doc_ids_pruning_opt: None,
         }
     }
 
+    /// Enables the pruning of delete by ID tasks with the doc IDs sidecars of the splits.
+    pub fn with_doc_ids_pruning(
+        mut self,
+        doc_id_field_name: String,
+        index_storage: Arc<dyn Storage>,
+    ) -> Self {
+        self.doc_ids_pruning_opt = Some((doc_id_field_name, index_storage));
+        self
+    }
+
     /// Send delete operations for a given `index_id`.
     async fn send_delete_operations(&mut self, ctx: &ActorContext<Self>) -> anyhow::Result<()> {
         // Loop until there is no more stale splits.
@@ -255,6 +278,9 @@ impl DeleteTaskPlanner {
                         && split_tag_filter(&stale_split.split_metadata, tags_filter.as_ref())
                 })
                 .collect_vec();
+            let pending_and_matching_metadata_tasks = self
+                .prune_delete_tasks_by_doc_ids(stale_split, pending_and_matching_metadata_tasks)
+                .await;
 
             // If there is no matching delete tasks,
             // there is no document to delete on this split.
@@ -284,6 +310,50 @@ impl DeleteTaskPlanner {
         Ok((splits_with_deletes, splits_without_deletes))
     }
 
+    /// Drops the delete by ID tasks whose IDs are all absent from the doc IDs sidecar of the split.
+    /// The remaining tasks may still not match any document.
+    async fn prune_delete_tasks_by_doc_ids(
+        &self,
+        stale_split: &Split,
+        delete_tasks: Vec<DeleteTask>,
+    ) -> Vec<DeleteTask> {
+        let Some((doc_id_field_name, index_storage)) = &self.doc_ids_pruning_opt else {
+            return delete_tasks;
+        };
+        if !stale_split.split_metadata.has_doc_ids_sidecar
+            || !delete_tasks
+                .iter()
+                .any(|delete_task| extract_doc_ids(delete_task, doc_id_field_name).is_some())
+        {
+            return delete_tasks;
+        }
+        let split_id = stale_split.split_id();
+        let sidecar_path = PathBuf::from(quickwit_common::doc_ids_sidecar_file(split_id));
+        let bloom_filter_res = index_storage
+            .get_all(&sidecar_path)
+            .await
+            .map_err(anyhow::Error::from)
+            .and_then(|sidecar_bytes| BloomFilter::from_bytes(sidecar_bytes.as_slice()));
+        let bloom_filter = match bloom_filter_res {
+            Ok(bloom_filter) => bloom_filter,
+            Err(error) => {
+                warn!(error=?error, split_id=%split_id, "failed to load doc IDs sidecar");
+                return delete_tasks;
+            }
+        };
+        delete_tasks
+            .into_iter()
+            .filter(|delete_task| {
+                let Some(doc_ids) = extract_doc_ids(delete_task, doc_id_field_name) else {
+                    return true;
+                };
+                doc_ids
+                    .iter()
+                    .any(|doc_id| bloom_filter.contains(doc_id.as_bytes()))
+            })
+            .collect()
+    }
+
     /// Executes a `LeafSearchRequest` on the split and returns true
     /// if it matches documents.
     async fn has_split_docs_to_delete(
@@ -321,6 +391,7 @@ impl DeleteTaskPlanner {
                 IndexMetasForLeafSearch {
                     doc_mapper_str: doc_mapper_str.to_string(),
                     index_uri,
+                    encryption_config_str_opt: self.encryption_config_str_opt.clone(),
                 },
             );
             let leaf_search_request = jobs_to_leaf_requests(
@@ -379,6 +450,29 @@ impl DeleteTaskPlanner {
     }
 }
 
+/// Returns the IDs of the documents targeted by a delete task if its query only matches documents
+/// by doc ID.
+fn extract_doc_ids(delete_task: &DeleteTask, doc_id_field_name: &str) -> Option<Vec<String>> {
+    let delete_query = delete_task.delete_query.as_ref()?;
+    let query_ast: QueryAst = serde_json::from_str(&delete_query.query_ast).ok()?;
+
+    match query_ast {
+        QueryAst::Term(term_query) if term_query.field == doc_id_field_name => {
+            Some(vec![term_query.value])
+        }
+        QueryAst::TermSet(term_set_query) => {
+            let mut terms_per_field = term_set_query.terms_per_field.into_iter();
+            let (field_name, terms) = terms_per_field.next()?;
+
+            if field_name != doc_id_field_name || terms_per_field.next().is_some() {
+                return None;
+            }
+            Some(terms.into_iter().collect())
+        }
+        _ => None,
+    }
+}
+
 #[derive(Clone, Debug, Serialize)]
 pub struct DeleteTaskPlannerState {
     ongoing_delete_operations: Vec<MergeOperation>,
@@ -542,6 +636,7 @@ mod tests {
             index_uid.clone(),
             index_config.index_uri.clone(),
             doc_mapper_str,
+            None,
             metastore.clone(),
             search_job_placer,
             downloader_mailbox,
@@ -609,4 +704,57 @@ mod tests {
         test_sandbox.assert_quit().await;
         Ok(())
     }
+
+    #[test]
+    fn test_extract_doc_ids() {
+        use quickwit_query::query_ast::{TermQuery, TermSetQuery};
+
+        fn delete_task_for_test(query_ast: QueryAst) -> DeleteTask {
+            DeleteTask {
+                delete_query: Some(DeleteQuery {
+                    query_ast: serde_json::to_string(&query_ast).unwrap(),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }
+        }
+        let delete_task = delete_task_for_test(QueryAst::Term(TermQuery {
+            field: "id".to_string(),
+            value: "doc-1".to_string(),
+        }));
+        assert_eq!(
+            extract_doc_ids(&delete_task, "id").unwrap(),
+            vec!["doc-1".to_string()]
+        );
+        assert!(extract_doc_ids(&delete_task, "other_id").is_none());
+
+        let delete_task = delete_task_for_test(QueryAst::TermSet(TermSetQuery {
+            terms_per_field: HashMap::from([(
+                "id".to_string(),
+                ["doc-1".to_string(), "doc-2".to_string()]
+                    .into_iter()
+                    .collect(),
+            )]),
+        }));
+        assert_eq!(
+            extract_doc_ids(&delete_task, "id").unwrap(),
+            vec!["doc-1".to_string(), "doc-2".to_string()]
+        );
+        let delete_task = delete_task_for_test(QueryAst::TermSet(TermSetQuery {
+            terms_per_field: HashMap::from([
+                (
+                    "id".to_string(),
+                    ["doc-1".to_string()].into_iter().collect(),
+                ),
+                (
+                    "body".to_string(),
+                    ["delete".to_string()].into_iter().collect(),
+                ),
+            ]),
+        }));
+        assert!(extract_doc_ids(&delete_task, "id").is_none());
+
+        let delete_task = delete_task_for_test(QueryAst::MatchAll);
+        assert!(extract_doc_ids(&delete_task, "id").is_none());
+    }
 }
@@ -19,13 +19,15 @@
 
 use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::Duration;
 
 use async_trait::async_trait;
 use quickwit_actors::{Actor, ActorContext, ActorExitStatus, ActorHandle, Handler};
+use quickwit_common::io::IoControls;
 use quickwit_common::pubsub::EventBroker;
 use quickwit_common::temp_dir::{self};
-use quickwit_config::IndexConfig;
+use quickwit_config::{IndexConfig, JanitorConfig};
 use quickwit_metastore::{IndexMetadataResponseExt, ListIndexesMetadataResponseExt};
 use quickwit_proto::metastore::{
     IndexMetadataRequest, ListIndexesMetadataRequest, MetastoreService, MetastoreServiceClient,
@@ -34,6 +36,7 @@ use quickwit_proto::types::IndexUid;
 use quickwit_search::SearchJobPlacer;
 use quickwit_storage::StorageResolver;
 use serde::Serialize;
+use tokio::sync::Semaphore;
 use tracing::{error, info, warn};
 
 use super::delete_task_pipeline::DeleteTaskPipeline;
@@ -48,9 +51,10 @@ const UPDATE_PIPELINES_INTERVAL: Duration = if cfg!(any(test, feature = "testsui
     Duration::from_secs(30)
 };
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
 pub struct DeleteTaskServiceState {
     pub num_running_pipelines: usize,
+    pub paused: bool,
 }
 
 pub struct DeleteTaskService {
@@ -60,6 +64,12 @@ pub struct DeleteTaskService {
     delete_service_task_dir: PathBuf,
     pipeline_handles_by_index_uid: HashMap<IndexUid, ActorHandle<DeleteTaskPipeline>>,
     max_concurrent_split_uploads: usize,
+    /// Permits shared by the delete executors of all the pipelines.
+    delete_operation_permits: Arc<Semaphore>,
+    /// IO controls shared by the delete executors of all the pipelines when a node-wide
+    /// throughput limit is configured.
+    delete_io_controls_opt: Option<IoControls>,
+    paused: bool,
     event_broker: EventBroker,
 }
 
@@ -70,11 +80,18 @@ impl DeleteTaskService {
         storage_resolver: StorageResolver,
         data_dir_path: PathBuf,
         max_concurrent_split_uploads: usize,
+        janitor_config: &JanitorConfig,
         event_broker: EventBroker,
     ) -> anyhow::Result<Self> {
         let delete_service_task_path = data_dir_path.join(DELETE_SERVICE_TASK_DIR_NAME);
         let delete_service_task_dir =
             temp_dir::create_or_purge_directory(delete_service_task_path.as_path()).await?;
+        let delete_operation_permits = Arc::new(Semaphore::new(
+            janitor_config.max_concurrent_delete_operations.get(),
+        ));
+        let delete_io_controls_opt = janitor_config
+            .delete_throughput_limit()
+            .map(|throughput_limit| IoControls::default().set_throughput_limit(throughput_limit));
         Ok(Self {
             metastore,
             search_job_placer,
@@ -82,6 +99,9 @@ impl DeleteTaskService {
             delete_service_task_dir,
             pipeline_handles_by_index_uid: Default::default(),
             max_concurrent_split_uploads,
+            delete_operation_permits,
+            delete_io_controls_opt,
+            paused: janitor_config.pause_delete_tasks,
             event_broker,
         })
     }
@@ -94,6 +114,7 @@ impl Actor for DeleteTaskService {
     fn observable_state(&self) -> Self::ObservableState {
         DeleteTaskServiceState {
             num_running_pipelines: self.pipeline_handles_by_index_uid.len(),
+            paused: self.paused,
         }
     }
 
@@ -112,6 +133,9 @@ impl DeleteTaskService {
         &mut self,
         ctx: &ActorContext<Self>,
     ) -> anyhow::Result<()> {
+        if self.paused {
+            return Ok(());
+        }
         let mut index_config_by_index_id: HashMap<IndexUid, IndexConfig> = self
             .metastore
             .list_indexes_metadata(ListIndexesMetadataRequest::all())
@@ -165,7 +189,10 @@ impl DeleteTaskService {
         ctx: &ActorContext<Self>,
     ) -> anyhow::Result<()> {
         let index_uri = index_config.index_uri.clone();
-        let index_storage = self.storage_resolver.resolve(&index_uri).await?;
+        let index_storage = self
+            .storage_resolver
+            .resolve_index_storage(&index_uri, index_config.encryption.as_ref())
+            .await?;
         let index_metadata_request =
             IndexMetadataRequest::for_index_id(index_config.index_id.to_string());
         let index_metadata = self
@@ -180,6 +207,8 @@ impl DeleteTaskService {
             index_storage,
             self.delete_service_task_dir.clone(),
             self.max_concurrent_split_uploads,
+            self.delete_operation_permits.clone(),
+            self.delete_io_controls_opt.clone(),
             self.event_broker.clone(),
         );
         let (_pipeline_mailbox, pipeline_handler) = ctx.spawn_actor().spawn(pipeline);
@@ -211,9 +240,61 @@ impl Handler<UpdatePipelines> for DeleteTaskService {
     }
 }
 
+/// Pauses the execution of delete tasks: the running delete task pipelines are killed and no
+/// pipeline is spawned until a [`ResumeDeleteTasks`] message is received.
+#[derive(Debug)]
+pub struct PauseDeleteTasks;
+
+#[async_trait]
+impl Handler<PauseDeleteTasks> for DeleteTaskService {
+    type Reply = DeleteTaskServiceState;
+
+    async fn handle(
+        &mut self,
+        _: PauseDeleteTasks,
+        _ctx: &ActorContext<Self>,
+    ) -> Result<DeleteTaskServiceState, ActorExitStatus> {
+        if !self.paused {
+            info!("pausing delete tasks");
+            self.paused = true;
+        }
+        // Kill the pipelines, this avoids to wait a long time for a delete operation to finish.
+        // Interrupted delete operations are planned again once resumed.
+        for (_, pipeline_handle) in self.pipeline_handles_by_index_uid.drain() {
+            pipeline_handle.kill().await;
+        }
+        Ok(self.observable_state())
+    }
+}
+
+/// Resumes the execution of delete tasks.
+#[derive(Debug)]
+pub struct ResumeDeleteTasks;
+
+#[async_trait]
+impl Handler<ResumeDeleteTasks> for DeleteTaskService {
+    type Reply = DeleteTaskServiceState;
+
+    async fn handle(
+        &mut self,
+        _: ResumeDeleteTasks,
+        ctx: &ActorContext<Self>,
+    ) -> Result<DeleteTaskServiceState, ActorExitStatus> {
+        if self.paused {
+            info!("resuming delete tasks");
+            self.paused = false;
+            if let Err(error) = self.update_pipeline_handles(ctx).await {
+                error!(error=%error, "delete task pipelines update failed");
+            }
+        }
+        Ok(self.observable_state())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use quickwit_common::pubsub::EventBroker;
+    use quickwit_config::JanitorConfig;
     use quickwit_indexing::TestSandbox;
     use quickwit_proto::metastore::{
         DeleteIndexRequest, DeleteQuery, ListDeleteTasksRequest, MetastoreService,
@@ -221,7 +302,9 @@ mod tests {
     use quickwit_search::{searcher_pool_for_test, MockSearchService, SearchJobPlacer};
     use quickwit_storage::StorageResolver;
 
-    use super::{DeleteTaskService, UPDATE_PIPELINES_INTERVAL};
+    use super::{
+        DeleteTaskService, PauseDeleteTasks, ResumeDeleteTasks, UPDATE_PIPELINES_INTERVAL,
+    };
 
     #[tokio::test]
     async fn test_delete_task_service() -> anyhow::Result<()> {
@@ -249,6 +332,7 @@ mod tests {
             StorageResolver::unconfigured(),
             data_dir_path,
             4,
+            &JanitorConfig::default(),
             EventBroker::default(),
         )
         .await
@@ -261,6 +345,7 @@ mod tests {
             .process_pending_and_observe()
             .await;
         assert_eq!(state.num_running_pipelines, 1);
+        assert!(!state.paused);
         let delete_query = DeleteQuery {
             index_uid: index_uid.to_string(),
             start_timestamp: None,
@@ -313,4 +398,69 @@ mod tests {
         test_sandbox.assert_quit().await;
         Ok(())
     }
+    #[tokio::test]
+    async fn test_delete_task_service_pause_and_resume() -> anyhow::Result<()> {
+        quickwit_common::setup_logging_for_tests();
+        let index_id = "test-delete-task-service-pause-index";
+        let doc_mapping_yaml = r#"
+            field_mappings:
+              - name: body
+                type: text
+        "#;
+        let test_sandbox = TestSandbox::create(index_id, doc_mapping_yaml, "{}", &["body"]).await?;
+        let metastore = test_sandbox.metastore();
+        let mock_search_service = MockSearchService::new();
+        let searcher_pool = searcher_pool_for_test([("127.0.0.1:1000", mock_search_service)]);
+        let search_job_placer = SearchJobPlacer::new(searcher_pool);
+        let temp_dir = tempfile::tempdir().unwrap();
+        let janitor_config = JanitorConfig {
+            pause_delete_tasks: true,
+            ..Default::default()
+        };
+        let delete_task_service = DeleteTaskService::new(
+            metastore,
+            search_job_placer,
+            StorageResolver::unconfigured(),
+            temp_dir.path().to_path_buf(),
+            4,
+            &janitor_config,
+            EventBroker::default(),
+        )
+        .await
+        .unwrap();
+        let (delete_task_service_mailbox, delete_task_service_handler) = test_sandbox
+            .universe()
+            .spawn_builder()
+            .spawn(delete_task_service);
+        let state = delete_task_service_handler
+            .process_pending_and_observe()
+            .await;
+        assert_eq!(state.num_running_pipelines, 0);
+        assert!(state.paused);
+
+        let state = delete_task_service_mailbox
+            .ask(ResumeDeleteTasks)
+            .await
+            .unwrap();
+        assert_eq!(state.num_running_pipelines, 1);
+        assert!(!state.paused);
+
+        let state = delete_task_service_mailbox
+            .ask(PauseDeleteTasks)
+            .await
+            .unwrap();
+        assert_eq!(state.num_running_pipelines, 0);
+        assert!(state.paused);
+
+        test_sandbox
+            .universe()
+            .sleep(UPDATE_PIPELINES_INTERVAL * 2)
+            .await;
+        let state = delete_task_service_handler
+            .process_pending_and_observe()
+            .await;
+        assert_eq!(state.num_running_pipelines, 0);
+        test_sandbox.assert_quit().await;
+        Ok(())
+    }
 }
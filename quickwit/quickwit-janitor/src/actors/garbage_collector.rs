@@ -17,7 +17,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 use std::time::Duration;
 
@@ -25,22 +25,25 @@ use async_trait::async_trait;
 use futures::{stream, StreamExt};
 use itertools::Itertools;
 use quickwit_actors::{Actor, ActorContext, Handler};
-use quickwit_common::shared_consts::DELETION_GRACE_PERIOD;
-use quickwit_index_management::run_garbage_collect;
-use quickwit_metastore::ListIndexesMetadataResponseExt;
+use quickwit_common::uri::Uri;
+use quickwit_common::PrettySample;
+use quickwit_index_management::{run_garbage_collect, run_orphan_files_scan};
+use quickwit_metastore::{IndexMetadata, ListIndexesMetadataResponseExt};
 use quickwit_proto::metastore::{
-    ListIndexesMetadataRequest, MetastoreService, MetastoreServiceClient,
+    AddIndexEventRequest, IndexEventType, ListIndexesMetadataRequest, MetastoreService,
+    MetastoreServiceClient,
 };
+use quickwit_proto::types::IndexUid;
 use quickwit_storage::StorageResolver;
 use serde::Serialize;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
-const RUN_INTERVAL: Duration = Duration::from_secs(10 * 60); // 10 minutes
+use crate::metrics::JANITOR_METRICS;
 
-/// Staged files needs to be deleted if there was a failure.
-/// TODO ideally we want clean up all staged splits every time we restart the indexing pipeline, but
-/// the grace period strategy should do the job for the moment.
-const STAGED_GRACE_PERIOD: Duration = Duration::from_secs(60 * 60 * 24); // 24 hours
+/// Maximum delay between two passes of the garbage collector. Each pass refreshes the list of
+/// indexes and only collects the garbage of the indexes whose `garbage_collection.interval` has
+/// elapsed.
+const RUN_INTERVAL: Duration = Duration::from_secs(10 * 60); // 10 minutes
 
 const MAX_CONCURRENT_GC_TASKS: usize = if cfg!(test) { 2 } else { 10 };
 
@@ -60,6 +63,14 @@ pub struct GarbageCollectorCounters {
     pub num_failed_storage_resolution: usize,
     /// The number of splits that were unable to be removed.
     pub num_failed_splits: usize,
+    /// The number of orphan files scans performed on an index storage.
+    pub num_orphan_files_scans: usize,
+    /// The number of failed orphan files scans on an index storage.
+    pub num_failed_orphan_files_scans: usize,
+    /// The number of orphan split files found.
+    pub num_orphan_files: usize,
+    /// The number of orphan split files deleted.
+    pub num_deleted_orphan_files: usize,
 }
 
 #[derive(Debug)]
@@ -69,6 +80,17 @@ struct Loop;
 pub struct GarbageCollector {
     metastore: MetastoreServiceClient,
     storage_resolver: StorageResolver,
+    /// Remaining delay before the next garbage collection of each index. Indexes absent from this
+    /// map have never been garbage collected and are collected on the next pass.
+    time_until_next_gc: HashMap<IndexUid, Duration>,
+    /// Interval between two scans of the indexes' storage looking for orphan split files. Scans
+    /// are disabled when unset.
+    orphan_files_scan_interval_opt: Option<Duration>,
+    /// Period after which the orphan split files are deleted. Orphan files are only reported when
+    /// unset.
+    orphan_files_grace_period_opt: Option<Duration>,
+    /// Remaining delay before the next orphan files scan.
+    time_until_next_orphan_files_scan: Duration,
     counters: GarbageCollectorCounters,
 }
 
@@ -77,10 +99,197 @@ impl GarbageCollector {
         Self {
             metastore,
             storage_resolver,
+            time_until_next_gc: HashMap::new(),
+            orphan_files_scan_interval_opt: None,
+            orphan_files_grace_period_opt: None,
+            time_until_next_orphan_files_scan: Duration::ZERO,
             counters: GarbageCollectorCounters::default(),
         }
     }
 
+    /// Enables the periodic scans of the indexes' storage looking for orphan split files, i.e.
+    /// split files that the metastore does not reference. The first scan runs on the next pass.
+    pub fn with_orphan_files_scan(
+        mut self,
+        scan_interval: Duration,
+        grace_period_opt: Option<Duration>,
+    ) -> Self {
+        self.orphan_files_scan_interval_opt = Some(scan_interval);
+        self.orphan_files_grace_period_opt = grace_period_opt;
+        self
+    }
+
+    /// Returns whether an orphan files scan is due and, if so, schedules the next one.
+    fn select_orphan_files_scan(&mut self) -> bool {
+        let Some(scan_interval) = self.orphan_files_scan_interval_opt else {
+            return false;
+        };
+        if !self.time_until_next_orphan_files_scan.is_zero() {
+            return false;
+        }
+        self.time_until_next_orphan_files_scan = scan_interval;
+        true
+    }
+
+    /// Updates the GC schedule with the current list of indexes and returns the indexes due for
+    /// garbage collection.
+    fn select_due_indexes(&mut self, indexes: Vec<IndexMetadata>) -> Vec<IndexMetadata> {
+        let index_uids: HashSet<&IndexUid> = indexes.iter().map(|index| &index.index_uid).collect();
+        self.time_until_next_gc
+            .retain(|index_uid, _| index_uids.contains(index_uid));
+
+        let mut due_indexes = Vec::new();
+
+        for index in indexes {
+            let gc_interval = index.index_config.gc_settings.interval;
+
+            if let Some(time_until_next_gc) = self.time_until_next_gc.get_mut(&index.index_uid) {
+                // The interval may have been shortened since the last run.
+                *time_until_next_gc = (*time_until_next_gc).min(gc_interval);
+
+                if !time_until_next_gc.is_zero() {
+                    continue;
+                }
+            }
+            self.time_until_next_gc
+                .insert(index.index_uid.clone(), gc_interval);
+            due_indexes.push(index);
+        }
+        due_indexes
+    }
+
+    /// Returns the delay until the next pass and advances the GC schedule accordingly.
+    fn advance_schedule(&mut self) -> Duration {
+        let mut next_pass_delay = self
+            .time_until_next_gc
+            .values()
+            .copied()
+            .min()
+            .unwrap_or(RUN_INTERVAL)
+            .min(RUN_INTERVAL);
+
+        // A scan skipped because the indexes could not be listed is retried on the next pass.
+        if self.orphan_files_scan_interval_opt.is_some()
+            && !self.time_until_next_orphan_files_scan.is_zero()
+        {
+            next_pass_delay = next_pass_delay.min(self.time_until_next_orphan_files_scan);
+        }
+        for time_until_next_gc in self.time_until_next_gc.values_mut() {
+            *time_until_next_gc = time_until_next_gc.saturating_sub(next_pass_delay);
+        }
+        self.time_until_next_orphan_files_scan = self
+            .time_until_next_orphan_files_scan
+            .saturating_sub(next_pass_delay);
+        next_pass_delay
+    }
+
+    /// Scans the storage of the indexes for orphan split files. Indexes sharing the same storage
+    /// URI are scanned together.
+    async fn scan_orphan_files(
+        &mut self,
+        index_uids_per_uri: &HashMap<Uri, Vec<IndexUid>>,
+        ctx: &ActorContext<Self>,
+    ) {
+        info!(
+            num_index_uris = index_uids_per_uri.len(),
+            "scanning indexes storage for orphan files"
+        );
+        let orphan_files_grace_period_opt = self.orphan_files_grace_period_opt;
+        let metastore = self.metastore.clone();
+        let storage_resolver = self.storage_resolver.clone();
+
+        let mut scan_futures = stream::iter(index_uids_per_uri)
+            .map(|(index_uri, index_uids)| {
+                let metastore = metastore.clone();
+                let storage_resolver = storage_resolver.clone();
+                async move {
+                    let storage = match storage_resolver.resolve(index_uri).await {
+                        Ok(storage) => storage,
+                        Err(error) => {
+                            error!(index_uri=%index_uri, error=?error, "failed to resolve the index storage Uri");
+                            return None;
+                        }
+                    };
+                    let index_uid = index_uids[0].clone();
+                    let scan_res = run_orphan_files_scan(
+                        index_uid.clone(),
+                        storage,
+                        metastore,
+                        &index_uids[1..],
+                        orphan_files_grace_period_opt,
+                        Some(ctx.progress()),
+                    )
+                    .await;
+                    Some((index_uri, index_uid, scan_res))
+                }
+            })
+            .buffer_unordered(MAX_CONCURRENT_GC_TASKS);
+
+        while let Some(scan_future_res) = scan_futures.next().await {
+            let Some((index_uri, index_uid, scan_res)) = scan_future_res else {
+                self.counters.num_failed_storage_resolution += 1;
+                continue;
+            };
+            let scan_info = match scan_res {
+                Ok(scan_info) => {
+                    self.counters.num_orphan_files_scans += 1;
+                    scan_info
+                }
+                Err(error) => {
+                    self.counters.num_failed_orphan_files_scans += 1;
+                    error!(index_uri=%index_uri, error=?error, "failed to scan index storage for orphan files");
+                    continue;
+                }
+            };
+            let num_orphan_files = scan_info.orphan_files.len();
+            let num_deleted_orphan_files = scan_info.deleted_files.len();
+
+            self.counters.num_orphan_files += num_orphan_files;
+            self.counters.num_deleted_orphan_files += num_deleted_orphan_files;
+
+            JANITOR_METRICS
+                .num_orphan_files
+                .with_label_values([index_uid.index_id()])
+                .set((num_orphan_files - num_deleted_orphan_files) as i64);
+
+            if num_orphan_files == 0 {
+                continue;
+            }
+            let orphan_file_paths: Vec<&Path> = scan_info
+                .orphan_files
+                .iter()
+                .map(|file_entry| file_entry.path.as_path())
+                .collect();
+            warn!(
+                index_uri=%index_uri,
+                num_orphan_files=num_orphan_files,
+                num_deleted_orphan_files=num_deleted_orphan_files,
+                "found orphan split file(s) {:?} in index storage",
+                PrettySample::new(&orphan_file_paths, 5),
+            );
+            let num_orphan_bytes: u64 = scan_info
+                .orphan_files
+                .iter()
+                .map(|file_entry| file_entry.num_bytes)
+                .sum();
+            let add_index_event_request = AddIndexEventRequest::new(
+                index_uid.clone(),
+                IndexEventType::OrphanFilesDetected,
+                format!(
+                    "{num_orphan_files} orphan files found ({num_orphan_bytes} bytes), \
+                     {num_deleted_orphan_files} deleted"
+                ),
+            );
+            if let Err(error) = self
+                .metastore
+                .add_index_event(add_index_event_request)
+                .await
+            {
+                warn!(index_id=%index_uid.index_id(), error=?error, "failed to record orphan files event");
+            }
+        }
+    }
+
     /// Gc Loop handler logic.
     /// Should not return an error to prevent the actor from crashing.
     async fn handle_inner(&mut self, ctx: &ActorContext<Self>) {
@@ -100,11 +309,39 @@ impl GarbageCollector {
                 return;
             }
         };
-        info!(index_ids=%indexes.iter().map(|im| im.index_id()).join(", "), "garbage collecting indexes");
+        // Clones of an index share its storage URI, and the files of the splits they share must
+        // be kept until no index references them anymore.
+        let mut index_uids_per_uri: HashMap<Uri, Vec<IndexUid>> = HashMap::new();
+
+        for index in &indexes {
+            index_uids_per_uri
+                .entry(index.index_uri().clone())
+                .or_default()
+                .push(index.index_uid.clone());
+        }
+        if self.select_orphan_files_scan() {
+            self.scan_orphan_files(&index_uids_per_uri, ctx).await;
+        }
+        let due_indexes = self.select_due_indexes(indexes);
+
+        if due_indexes.is_empty() {
+            return;
+        }
+        info!(index_ids=%due_indexes.iter().map(|im| im.index_id()).join(", "), "garbage collecting indexes");
 
-        let mut gc_futures = stream::iter(indexes).map(|index| {
-            let metastore = self.metastore.clone();
-            let storage_resolver = self.storage_resolver.clone();
+        let metastore = self.metastore.clone();
+        let storage_resolver = self.storage_resolver.clone();
+
+        let mut gc_futures = stream::iter(due_indexes).map(|index| {
+            let metastore = metastore.clone();
+            let storage_resolver = storage_resolver.clone();
+            let shared_index_uids: Vec<IndexUid> = index_uids_per_uri
+                .get(index.index_uri())
+                .into_iter()
+                .flatten()
+                .filter(|index_uid| **index_uid != index.index_uid)
+                .cloned()
+                .collect();
             async move {
             let index_uri = index.index_uri();
             let storage = match storage_resolver.resolve(index_uri).await {
@@ -114,17 +351,18 @@ impl GarbageCollector {
                     return None;
                 }
             };
-            let index_uid = index.index_uid;
+            let gc_settings = &index.index_config.gc_settings;
             let gc_res = run_garbage_collect(
-                index_uid.clone(),
+                index.index_uid.clone(),
                 storage,
                 metastore,
-                STAGED_GRACE_PERIOD,
-                DELETION_GRACE_PERIOD,
+                &shared_index_uids,
+                gc_settings.staged_grace_period,
+                gc_settings.deletion_grace_period,
                 false,
                 Some(ctx.progress()),
             ).await;
-            Some((index_uid, gc_res))
+            Some((index.index_uid, gc_res))
         }}).buffer_unordered(MAX_CONCURRENT_GC_TASKS);
 
         while let Some(gc_future_res) = gc_futures.next().await {
@@ -158,11 +396,25 @@ impl GarbageCollector {
                     deleted_files,
                     num_deleted_splits,
                 );
-                self.counters.num_deleted_files += deleted_file_entries.len();
-                self.counters.num_deleted_bytes += deleted_file_entries
+                let num_deleted_bytes = deleted_file_entries
                     .iter()
                     .map(|entry| entry.file_size_bytes.as_u64() as usize)
                     .sum::<usize>();
+                self.counters.num_deleted_files += deleted_file_entries.len();
+                self.counters.num_deleted_bytes += num_deleted_bytes;
+
+                let add_index_event_request = AddIndexEventRequest::new(
+                    index_uid.clone(),
+                    IndexEventType::SplitsGarbageCollected,
+                    format!("{num_deleted_splits} splits deleted ({num_deleted_bytes} bytes)"),
+                );
+                if let Err(error) = self
+                    .metastore
+                    .add_index_event(add_index_event_request)
+                    .await
+                {
+                    warn!(index_id=%index_uid.index_id(), error=?error, "failed to record garbage collection event");
+                }
             }
         }
     }
@@ -199,7 +451,8 @@ impl Handler<Loop> for GarbageCollector {
         ctx: &ActorContext<Self>,
     ) -> Result<(), quickwit_actors::ActorExitStatus> {
         self.handle_inner(ctx).await;
-        ctx.schedule_self_msg(RUN_INTERVAL, Loop).await;
+        let next_pass_delay = self.advance_schedule();
+        ctx.schedule_self_msg(next_pass_delay, Loop).await;
         Ok(())
     }
 }
@@ -213,6 +466,7 @@ mod tests {
     use quickwit_actors::Universe;
     use quickwit_common::shared_consts::DELETION_GRACE_PERIOD;
     use quickwit_common::ServiceStream;
+    use quickwit_config::GarbageCollectionSettings;
     use quickwit_metastore::{
         IndexMetadata, ListSplitsRequestExt, ListSplitsResponseExt, Split, SplitMetadata,
         SplitState,
@@ -331,7 +585,8 @@ mod tests {
             "test-index:11111111111111111111111111".to_string().into(),
             Arc::new(mock_storage),
             MetastoreServiceClient::from(mock_metastore),
-            STAGED_GRACE_PERIOD,
+            &[],
+            GarbageCollectionSettings::default().staged_grace_period,
             DELETION_GRACE_PERIOD,
             false,
             None,
@@ -400,6 +655,18 @@ mod tests {
                 assert_eq!(split_ids, expected_split_ids);
                 Ok(EmptyResponse {})
             });
+        mock_metastore
+            .expect_add_index_event()
+            .times(1)
+            .returning(|add_index_event_request| {
+                let index_uid: IndexUid = add_index_event_request.index_uid.clone().into();
+                assert_eq!(index_uid.index_id(), "test-index");
+                assert_eq!(
+                    add_index_event_request.event_type,
+                    "splits_garbage_collected"
+                );
+                Ok(EmptyResponse {})
+            });
 
         let garbage_collect_actor = GarbageCollector::new(
             MetastoreServiceClient::from(mock_metastore),
@@ -476,6 +743,10 @@ mod tests {
                 assert_eq!(split_ids, expected_split_ids);
                 Ok(EmptyResponse {})
             });
+        mock_metastore
+            .expect_add_index_event()
+            .times(..)
+            .returning(|_| Ok(EmptyResponse {}));
 
         let garbage_collect_actor = GarbageCollector::new(
             MetastoreServiceClient::from(mock_metastore),
@@ -517,6 +788,76 @@ mod tests {
         universe.assert_quit().await;
     }
 
+    #[tokio::test]
+    async fn test_garbage_collect_honors_index_gc_settings() {
+        let storage_resolver = StorageResolver::unconfigured();
+        let mut mock_metastore = MetastoreServiceClient::mock();
+        mock_metastore
+            .expect_list_indexes_metadata()
+            .returning(|_list_indexes_request| {
+                let mut index_metadata_1 =
+                    IndexMetadata::for_test("test-index-1", "ram:///indexes/test-index-1");
+                let gc_settings = &mut index_metadata_1.index_config.gc_settings;
+                gc_settings.interval = Duration::from_secs(60);
+                gc_settings.deletion_grace_period = Duration::from_secs(5 * 60);
+
+                let index_metadata_2 =
+                    IndexMetadata::for_test("test-index-2", "ram:///indexes/test-index-2");
+                let indexes_metadata = vec![index_metadata_1, index_metadata_2];
+                Ok(
+                    ListIndexesMetadataResponse::try_from_indexes_metadata(indexes_metadata)
+                        .unwrap(),
+                )
+            });
+        mock_metastore
+            .expect_list_splits()
+            .returning(|list_splits_request| {
+                let query = list_splits_request.deserialize_list_splits_query().unwrap();
+
+                if query.split_states[0] == SplitState::MarkedForDeletion {
+                    let deletion_grace_period = if query.index_uids[0].index_id() == "test-index-1"
+                    {
+                        Duration::from_secs(5 * 60)
+                    } else {
+                        DELETION_GRACE_PERIOD
+                    };
+                    let expected_deletion_timestamp = OffsetDateTime::now_utc().unix_timestamp()
+                        - deletion_grace_period.as_secs() as i64;
+                    let Bound::Included(deletion_timestamp) = query.update_timestamp.end else {
+                        panic!("expected an inclusive update timestamp upper bound");
+                    };
+                    assert!((expected_deletion_timestamp - deletion_timestamp).abs() <= 1);
+                }
+                let splits = ListSplitsResponse::try_from_splits(Vec::new()).unwrap();
+                Ok(ServiceStream::from(vec![Ok(splits)]))
+            });
+
+        let garbage_collect_actor = GarbageCollector::new(
+            MetastoreServiceClient::from(mock_metastore),
+            storage_resolver,
+        );
+        let universe = Universe::with_accelerated_time();
+        let (_mailbox, handle) = universe.spawn_builder().spawn(garbage_collect_actor);
+
+        let counters = handle.process_pending_and_observe().await.state;
+        assert_eq!(counters.num_passes, 1);
+        assert_eq!(counters.num_successful_gc_run_on_index, 2);
+
+        // Only `test-index-1` is due after one minute.
+        universe.sleep(Duration::from_secs(60)).await;
+        let counters = handle.process_pending_and_observe().await.state;
+        assert_eq!(counters.num_passes, 2);
+        assert_eq!(counters.num_successful_gc_run_on_index, 3);
+
+        // Both indexes are due after ten minutes.
+        universe.sleep(RUN_INTERVAL - Duration::from_secs(60)).await;
+        let counters = handle.process_pending_and_observe().await.state;
+        assert_eq!(counters.num_passes, 11);
+        assert_eq!(counters.num_successful_gc_run_on_index, 13);
+        assert_eq!(counters.num_failed_gc_run_on_index, 0);
+        universe.assert_quit().await;
+    }
+
     #[tokio::test]
     async fn test_garbage_collect_get_called_repeatedly_on_failure() {
         let storage_resolver = StorageResolver::unconfigured();
@@ -649,6 +990,10 @@ mod tests {
                 assert_eq!(split_ids, expected_split_ids);
                 Ok(EmptyResponse {})
             });
+        mock_metastore
+            .expect_add_index_event()
+            .times(..)
+            .returning(|_| Ok(EmptyResponse {}));
 
         let garbage_collect_actor = GarbageCollector::new(
             MetastoreServiceClient::from(mock_metastore),
@@ -736,6 +1081,10 @@ mod tests {
                     Ok(EmptyResponse {})
                 }
             });
+        mock_metastore
+            .expect_add_index_event()
+            .times(..)
+            .returning(|_| Ok(EmptyResponse {}));
 
         let garbage_collect_actor = GarbageCollector::new(
             MetastoreServiceClient::from(mock_metastore),
@@ -754,4 +1103,78 @@ mod tests {
         assert_eq!(counters.num_failed_splits, 2);
         universe.assert_quit().await;
     }
+
+    #[tokio::test]
+    async fn test_garbage_collect_scans_orphan_files() {
+        let storage_resolver = StorageResolver::unconfigured();
+        let storage = storage_resolver
+            .resolve(&Uri::for_test("ram:///indexes/test-index"))
+            .await
+            .unwrap();
+        for split_file in ["known.split", "orphan.split"] {
+            storage
+                .put(Path::new(split_file), Box::new(b"split".to_vec()))
+                .await
+                .unwrap();
+        }
+        let mut mock_metastore = MetastoreServiceClient::mock();
+        mock_metastore
+            .expect_list_indexes_metadata()
+            .returning(|_list_indexes_request| {
+                let indexes_metadata = vec![IndexMetadata::for_test(
+                    "test-index",
+                    "ram:///indexes/test-index",
+                )];
+                Ok(
+                    ListIndexesMetadataResponse::try_from_indexes_metadata(indexes_metadata)
+                        .unwrap(),
+                )
+            });
+        mock_metastore
+            .expect_list_splits()
+            .returning(|list_splits_request| {
+                let query = list_splits_request.deserialize_list_splits_query().unwrap();
+                assert_eq!(query.index_uids[0].index_id(), "test-index");
+
+                // The orphan files scan lists the splits in all states.
+                let splits = if query.split_states.is_empty() {
+                    make_splits(&["known"], SplitState::Published)
+                } else {
+                    Vec::new()
+                };
+                let splits = ListSplitsResponse::try_from_splits(splits).unwrap();
+                Ok(ServiceStream::from(vec![Ok(splits)]))
+            });
+        mock_metastore
+            .expect_add_index_event()
+            .times(1)
+            .returning(|add_index_event_request| {
+                assert_eq!(add_index_event_request.event_type, "orphan_files_detected");
+                Ok(EmptyResponse {})
+            });
+
+        let garbage_collect_actor = GarbageCollector::new(
+            MetastoreServiceClient::from(mock_metastore),
+            storage_resolver,
+        )
+        .with_orphan_files_scan(Duration::from_secs(24 * 60 * 60), None);
+        let universe = Universe::with_accelerated_time();
+        let (_mailbox, handle) = universe.spawn_builder().spawn(garbage_collect_actor);
+
+        let counters = handle.process_pending_and_observe().await.state;
+        assert_eq!(counters.num_passes, 1);
+        assert_eq!(counters.num_orphan_files_scans, 1);
+        assert_eq!(counters.num_orphan_files, 1);
+        assert_eq!(counters.num_deleted_orphan_files, 0);
+
+        // The next scan is not due before a day.
+        universe.sleep(RUN_INTERVAL).await;
+        let counters = handle.process_pending_and_observe().await.state;
+        assert_eq!(counters.num_passes, 2);
+        assert_eq!(counters.num_orphan_files_scans, 1);
+
+        // The orphan file is only reported.
+        assert!(storage.exists(Path::new("orphan.split")).await.unwrap());
+        universe.assert_quit().await;
+    }
 }
@@ -0,0 +1,269 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+use std::time::Duration;
+
+use async_trait::async_trait;
+use quickwit_actors::{Actor, ActorContext, ActorExitStatus, Handler};
+use quickwit_metastore::{IndexMetadata, ListIndexesMetadataResponseExt};
+use quickwit_proto::metastore::{
+    FreezeIndexRequest, ListIndexesMetadataRequest, MetastoreResult, MetastoreService,
+    MetastoreServiceClient, ToggleSourceRequest,
+};
+use serde::Serialize;
+use time::OffsetDateTime;
+use tracing::{debug, error, info};
+
+const RUN_INTERVAL: Duration = Duration::from_secs(60 * 60); // 1 hour
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct LifecyclePolicyExecutorCounters {
+    /// The number of evaluation passes.
+    pub num_passes: usize,
+
+    /// The number of indexes frozen.
+    pub num_frozen_indexes: usize,
+
+    /// The number of indexes that failed to be frozen.
+    pub num_failed_freezes: usize,
+}
+
+#[derive(Debug)]
+struct Loop;
+
+/// An actor that periodically applies the lifecycle policy of the indexes: indexes older than
+/// their `lifecycle.freeze_after` period are frozen.
+pub struct LifecyclePolicyExecutor {
+    metastore: MetastoreServiceClient,
+    counters: LifecyclePolicyExecutorCounters,
+}
+
+impl LifecyclePolicyExecutor {
+    pub fn new(metastore: MetastoreServiceClient) -> Self {
+        Self {
+            metastore,
+            counters: LifecyclePolicyExecutorCounters::default(),
+        }
+    }
+
+    /// Freezes the indexes that have outlived their `freeze_after` period.
+    /// Should not return an error to prevent the actor from crashing.
+    async fn apply_lifecycle_policies(&mut self, ctx: &ActorContext<Self>) {
+        debug!("lifecycle-policy-operation");
+        self.counters.num_passes += 1;
+
+        let index_metadatas = match ctx
+            .protect_future(
+                self.metastore
+                    .list_indexes_metadata(ListIndexesMetadataRequest::all()),
+            )
+            .await
+            .and_then(|response| response.deserialize_indexes_metadata())
+        {
+            Ok(index_metadatas) => index_metadatas,
+            Err(error) => {
+                error!(error=?error, "failed to list indexes from the metastore");
+                return;
+            }
+        };
+        let now_timestamp = OffsetDateTime::now_utc().unix_timestamp();
+
+        for index_metadata in index_metadatas {
+            if !should_freeze(&index_metadata, now_timestamp) {
+                continue;
+            }
+            let index_id = index_metadata.index_id().to_string();
+            info!(index_id=%index_id, "freezing index");
+
+            match self.freeze_index(index_metadata, ctx).await {
+                Ok(()) => self.counters.num_frozen_indexes += 1,
+                Err(error) => {
+                    self.counters.num_failed_freezes += 1;
+                    error!(index_id=%index_id, error=?error, "failed to freeze index");
+                }
+            }
+        }
+    }
+
+    async fn freeze_index(
+        &mut self,
+        index_metadata: IndexMetadata,
+        ctx: &ActorContext<Self>,
+    ) -> MetastoreResult<()> {
+        // The sources are disabled one by one through `toggle_source` beforehand so that the
+        // control plane, which intercepts these calls, stops scheduling the indexing pipelines of
+        // the index. The indexers then shut down the merge pipelines left without any indexing
+        // pipeline.
+        for source_config in index_metadata.sources.values() {
+            if !source_config.enabled {
+                continue;
+            }
+            let toggle_source_request = ToggleSourceRequest {
+                index_uid: index_metadata.index_uid.to_string(),
+                source_id: source_config.source_id.clone(),
+                enable: false,
+            };
+            ctx.protect_future(self.metastore.toggle_source(toggle_source_request))
+                .await?;
+        }
+        let freeze_index_request = FreezeIndexRequest {
+            index_uid: index_metadata.index_uid.to_string(),
+        };
+        ctx.protect_future(self.metastore.freeze_index(freeze_index_request))
+            .await?;
+        Ok(())
+    }
+}
+
+/// Returns whether the index is due to be frozen according to its lifecycle policy.
+fn should_freeze(index_metadata: &IndexMetadata, now_timestamp: i64) -> bool {
+    if index_metadata.frozen {
+        return false;
+    }
+    let Some(lifecycle_policy) = &index_metadata.index_config.lifecycle_policy else {
+        return false;
+    };
+    let index_age_secs = now_timestamp.saturating_sub(index_metadata.create_timestamp);
+    index_age_secs >= lifecycle_policy.freeze_after.as_secs() as i64
+}
+
+#[async_trait]
+impl Actor for LifecyclePolicyExecutor {
+    type ObservableState = LifecyclePolicyExecutorCounters;
+
+    fn observable_state(&self) -> Self::ObservableState {
+        self.counters.clone()
+    }
+
+    fn name(&self) -> String {
+        "LifecyclePolicyExecutor".to_string()
+    }
+
+    async fn initialize(&mut self, ctx: &ActorContext<Self>) -> Result<(), ActorExitStatus> {
+        self.handle(Loop, ctx).await
+    }
+}
+
+#[async_trait]
+impl Handler<Loop> for LifecyclePolicyExecutor {
+    type Reply = ();
+
+    async fn handle(&mut self, _: Loop, ctx: &ActorContext<Self>) -> Result<(), ActorExitStatus> {
+        self.apply_lifecycle_policies(ctx).await;
+        ctx.schedule_self_msg(RUN_INTERVAL, Loop).await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_actors::Universe;
+    use quickwit_config::{LifecyclePolicy, SourceConfig, SourceParams};
+    use quickwit_metastore::IndexMetadataResponseExt;
+    use quickwit_proto::metastore::{
+        EmptyResponse, IndexMetadataResponse, ListIndexesMetadataResponse,
+    };
+
+    use super::*;
+
+    const DAY_SECS: i64 = 24 * 60 * 60;
+
+    fn make_index_metadata(
+        index_id: &str,
+        age_secs: i64,
+        freeze_after_opt: Option<Duration>,
+    ) -> IndexMetadata {
+        let index_uri = format!("ram:///indexes/{index_id}");
+        let mut index_metadata = IndexMetadata::for_test(index_id, &index_uri);
+        index_metadata.create_timestamp = OffsetDateTime::now_utc().unix_timestamp() - age_secs;
+        index_metadata.index_config.lifecycle_policy =
+            freeze_after_opt.map(|freeze_after| LifecyclePolicy { freeze_after });
+        index_metadata
+            .add_source(SourceConfig::for_test("my-source", SourceParams::void()))
+            .unwrap();
+        index_metadata
+    }
+
+    #[test]
+    fn test_should_freeze() {
+        let now_timestamp = OffsetDateTime::now_utc().unix_timestamp();
+        let freeze_after_opt = Some(Duration::from_secs(DAY_SECS as u64));
+
+        let index_metadata = make_index_metadata("test-index", 2 * DAY_SECS, freeze_after_opt);
+        assert!(should_freeze(&index_metadata, now_timestamp));
+
+        let index_metadata = make_index_metadata("test-index", DAY_SECS / 2, freeze_after_opt);
+        assert!(!should_freeze(&index_metadata, now_timestamp));
+
+        let index_metadata = make_index_metadata("test-index", 2 * DAY_SECS, None);
+        assert!(!should_freeze(&index_metadata, now_timestamp));
+
+        let mut index_metadata = make_index_metadata("test-index", 2 * DAY_SECS, freeze_after_opt);
+        index_metadata.frozen = true;
+        assert!(!should_freeze(&index_metadata, now_timestamp));
+    }
+
+    #[tokio::test]
+    async fn test_lifecycle_policy_executor_freezes_old_indexes() {
+        let mut mock_metastore = MetastoreServiceClient::mock();
+        mock_metastore
+            .expect_list_indexes_metadata()
+            .times(1)
+            .returning(|_| {
+                let freeze_after_opt = Some(Duration::from_secs(DAY_SECS as u64));
+                let indexes_metadata = vec![
+                    make_index_metadata("index-1", 2 * DAY_SECS, freeze_after_opt),
+                    make_index_metadata("index-2", DAY_SECS / 2, freeze_after_opt),
+                    make_index_metadata("index-3", 2 * DAY_SECS, None),
+                ];
+                Ok(
+                    ListIndexesMetadataResponse::try_from_indexes_metadata(indexes_metadata)
+                        .unwrap(),
+                )
+            });
+        mock_metastore
+            .expect_toggle_source()
+            .times(1)
+            .returning(|request| {
+                assert_eq!(request.index_uid, "index-1:0");
+                assert_eq!(request.source_id, "my-source");
+                assert!(!request.enable);
+                Ok(EmptyResponse {})
+            });
+        mock_metastore
+            .expect_freeze_index()
+            .times(1)
+            .returning(|request| {
+                assert_eq!(request.index_uid, "index-1:0");
+                let mut index_metadata = make_index_metadata("index-1", 2 * DAY_SECS, None);
+                index_metadata.frozen = true;
+                Ok(IndexMetadataResponse::try_from_index_metadata(index_metadata).unwrap())
+            });
+
+        let lifecycle_policy_executor =
+            LifecyclePolicyExecutor::new(MetastoreServiceClient::from(mock_metastore));
+        let universe = Universe::with_accelerated_time();
+        let (_mailbox, handle) = universe.spawn_builder().spawn(lifecycle_policy_executor);
+
+        let counters = handle.process_pending_and_observe().await.state;
+        assert_eq!(counters.num_passes, 1);
+        assert_eq!(counters.num_frozen_indexes, 1);
+        assert_eq!(counters.num_failed_freezes, 0);
+        universe.assert_quit().await;
+    }
+}
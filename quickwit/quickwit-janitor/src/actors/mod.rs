@@ -21,8 +21,13 @@ mod delete_task_pipeline;
 mod delete_task_planner;
 mod delete_task_service;
 mod garbage_collector;
+mod lifecycle_policy_executor;
 mod retention_policy_executor;
 
-pub use delete_task_service::{DeleteTaskService, DELETE_SERVICE_TASK_DIR_NAME};
+pub use delete_task_service::{
+    DeleteTaskService, DeleteTaskServiceState, PauseDeleteTasks, ResumeDeleteTasks,
+    DELETE_SERVICE_TASK_DIR_NAME,
+};
 pub use garbage_collector::GarbageCollector;
+pub use lifecycle_policy_executor::LifecyclePolicyExecutor;
 pub use retention_policy_executor::RetentionPolicyExecutor;
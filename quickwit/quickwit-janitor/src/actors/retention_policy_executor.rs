@@ -489,6 +489,13 @@ mod tests {
                 );
                 Ok(EmptyResponse {})
             });
+        mock_metastore
+            .expect_add_index_event()
+            .times(1..=3)
+            .returning(|add_index_event_request| {
+                assert_eq!(add_index_event_request.event_type, "retention_applied");
+                Ok(EmptyResponse {})
+            });
 
         let retention_policy_executor =
             RetentionPolicyExecutor::new(MetastoreServiceClient::from(mock_metastore));
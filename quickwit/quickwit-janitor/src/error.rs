@@ -32,6 +32,8 @@ pub enum JanitorError {
     Internal(String),
     #[error("metastore error: `{0}`")]
     Metastore(#[from] MetastoreError),
+    #[error("janitor unavailable: {0}")]
+    Unavailable(String),
 }
 
 impl ServiceError for JanitorError {
@@ -40,6 +42,7 @@ impl ServiceError for JanitorError {
             JanitorError::InvalidDeleteQuery(_) => ServiceErrorCode::BadRequest,
             JanitorError::Internal(_) => ServiceErrorCode::Internal,
             JanitorError::Metastore(error) => error.error_code(),
+            JanitorError::Unavailable(_) => ServiceErrorCode::Unavailable,
         }
     }
 }
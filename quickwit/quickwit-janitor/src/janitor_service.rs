@@ -23,12 +23,17 @@ use quickwit_actors::{
 };
 use serde_json::{json, Value as JsonValue};
 
-use crate::actors::{DeleteTaskService, GarbageCollector, RetentionPolicyExecutor};
+use crate::actors::{
+    DeleteTaskService, DeleteTaskServiceState, GarbageCollector, LifecyclePolicyExecutor,
+    PauseDeleteTasks, ResumeDeleteTasks, RetentionPolicyExecutor,
+};
+use crate::error::JanitorError;
 
 pub struct JanitorService {
     delete_task_service_handle: ActorHandle<DeleteTaskService>,
     garbage_collector_handle: ActorHandle<GarbageCollector>,
     retention_policy_executor_handle: ActorHandle<RetentionPolicyExecutor>,
+    lifecycle_policy_executor_handle: ActorHandle<LifecyclePolicyExecutor>,
 }
 
 impl JanitorService {
@@ -36,11 +41,13 @@ impl JanitorService {
         delete_task_service_handle: ActorHandle<DeleteTaskService>,
         garbage_collector_handle: ActorHandle<GarbageCollector>,
         retention_policy_executor_handle: ActorHandle<RetentionPolicyExecutor>,
+        lifecycle_policy_executor_handle: ActorHandle<LifecyclePolicyExecutor>,
     ) -> Self {
         Self {
             delete_task_service_handle,
             garbage_collector_handle,
             retention_policy_executor_handle,
+            lifecycle_policy_executor_handle,
         }
     }
 
@@ -48,6 +55,7 @@ impl JanitorService {
         self.delete_task_service_handle.state() != ActorState::Failure
             && self.garbage_collector_handle.state() != ActorState::Failure
             && self.retention_policy_executor_handle.state() != ActorState::Failure
+            && self.lifecycle_policy_executor_handle.state() != ActorState::Failure
     }
 }
 
@@ -76,3 +84,41 @@ impl Handler<Healthz> for JanitorService {
         Ok(self.is_healthy())
     }
 }
+
+#[async_trait]
+impl Handler<PauseDeleteTasks> for JanitorService {
+    type Reply = Result<DeleteTaskServiceState, JanitorError>;
+
+    async fn handle(
+        &mut self,
+        message: PauseDeleteTasks,
+        _ctx: &ActorContext<Self>,
+    ) -> Result<Self::Reply, ActorExitStatus> {
+        let reply = self
+            .delete_task_service_handle
+            .mailbox()
+            .ask(message)
+            .await
+            .map_err(|error| JanitorError::Internal(error.to_string()));
+        Ok(reply)
+    }
+}
+
+#[async_trait]
+impl Handler<ResumeDeleteTasks> for JanitorService {
+    type Reply = Result<DeleteTaskServiceState, JanitorError>;
+
+    async fn handle(
+        &mut self,
+        message: ResumeDeleteTasks,
+        _ctx: &ActorContext<Self>,
+    ) -> Result<Self::Reply, ActorExitStatus> {
+        let reply = self
+            .delete_task_service_handle
+            .mailbox()
+            .ask(message)
+            .await
+            .map_err(|error| JanitorError::Internal(error.to_string()));
+        Ok(reply)
+    }
+}
@@ -36,7 +36,9 @@ mod retention_policy_execution;
 
 pub use janitor_service::JanitorService;
 
-use crate::actors::{DeleteTaskService, GarbageCollector, RetentionPolicyExecutor};
+use crate::actors::{
+    DeleteTaskService, GarbageCollector, LifecyclePolicyExecutor, RetentionPolicyExecutor,
+};
 
 #[derive(utoipa::OpenApi)]
 #[openapi(components(schemas(SplitInfo)))]
@@ -52,18 +54,29 @@ pub async fn start_janitor_service(
     event_broker: EventBroker,
 ) -> anyhow::Result<Mailbox<JanitorService>> {
     info!("starting janitor service");
-    let garbage_collector = GarbageCollector::new(metastore.clone(), storage_resolver.clone());
+    let mut garbage_collector = GarbageCollector::new(metastore.clone(), storage_resolver.clone());
+
+    if let Some(scan_interval) = config.janitor_config.orphan_files_scan_interval() {
+        garbage_collector = garbage_collector.with_orphan_files_scan(
+            scan_interval,
+            config.janitor_config.orphan_files_grace_period(),
+        );
+    }
     let (_, garbage_collector_handle) = universe.spawn_builder().spawn(garbage_collector);
 
     let retention_policy_executor = RetentionPolicyExecutor::new(metastore.clone());
     let (_, retention_policy_executor_handle) =
         universe.spawn_builder().spawn(retention_policy_executor);
+    let lifecycle_policy_executor = LifecyclePolicyExecutor::new(metastore.clone());
+    let (_, lifecycle_policy_executor_handle) =
+        universe.spawn_builder().spawn(lifecycle_policy_executor);
     let delete_task_service = DeleteTaskService::new(
         metastore,
         search_job_placer,
         storage_resolver,
         config.data_dir_path.clone(),
         config.indexer_config.max_concurrent_split_uploads,
+        &config.janitor_config,
         event_broker,
     )
     .await?;
@@ -73,6 +86,7 @@ pub async fn start_janitor_service(
         delete_task_service_handle,
         garbage_collector_handle,
         retention_policy_executor_handle,
+        lifecycle_policy_executor_handle,
     );
     let (janitor_service_mailbox, _janitor_service_handle) =
         universe.spawn_builder().spawn(janitor_service);
@@ -22,6 +22,7 @@ use quickwit_common::metrics::{new_gauge_vec, IntGaugeVec};
 
 pub struct JanitorMetrics {
     pub ongoing_num_delete_operations_total: IntGaugeVec<1>,
+    pub num_orphan_files: IntGaugeVec<1>,
 }
 
 impl Default for JanitorMetrics {
@@ -33,6 +34,12 @@ impl Default for JanitorMetrics {
                 "quickwit_janitor",
                 ["index"],
             ),
+            num_orphan_files: new_gauge_vec(
+                "num_orphan_files",
+                "Num of orphan split files found by the last storage scan (per index).",
+                "quickwit_janitor",
+                ["index"],
+            ),
         }
     }
 }
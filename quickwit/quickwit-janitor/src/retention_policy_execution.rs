@@ -25,7 +25,8 @@ use quickwit_metastore::{
     SplitState,
 };
 use quickwit_proto::metastore::{
-    ListSplitsRequest, MarkSplitsForDeletionRequest, MetastoreService, MetastoreServiceClient,
+    AddIndexEventRequest, IndexEventType, ListSplitsRequest, MarkSplitsForDeletionRequest,
+    MetastoreService, MetastoreServiceClient,
 };
 use quickwit_proto::types::{IndexUid, SplitId};
 use time::OffsetDateTime;
@@ -90,9 +91,22 @@ pub async fn run_execute_retention_policy(
         "Marking {} splits for deletion based on retention policy.",
         expired_split_ids.len()
     );
+    let num_expired_splits = expired_split_ids.len();
     let mark_splits_for_deletion_request =
-        MarkSplitsForDeletionRequest::new(index_uid, expired_split_ids);
+        MarkSplitsForDeletionRequest::new(index_uid.clone(), expired_split_ids);
     ctx.protect_future(metastore.mark_splits_for_deletion(mark_splits_for_deletion_request))
         .await?;
+
+    let add_index_event_request = AddIndexEventRequest::new(
+        index_uid.clone(),
+        IndexEventType::RetentionApplied,
+        format!("{num_expired_splits} expired splits marked for deletion"),
+    );
+    if let Err(error) = ctx
+        .protect_future(metastore.add_index_event(add_index_event_request))
+        .await
+    {
+        warn!(index_id=%index_uid.index_id(), error=?error, "failed to record retention event");
+    }
     Ok(expired_splits)
 }
@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS index_events (
+    event_id BIGSERIAL PRIMARY KEY,
+    index_uid VARCHAR(282) NOT NULL,
+    event_type VARCHAR(50) NOT NULL,
+    message TEXT NOT NULL,
+    create_timestamp TIMESTAMP NOT NULL DEFAULT (CURRENT_TIMESTAMP AT TIME ZONE 'UTC'),
+
+    FOREIGN KEY(index_uid) REFERENCES indexes(index_uid) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS index_events_index_uid_idx ON index_events (index_uid);
@@ -0,0 +1,2 @@
+ALTER TABLE splits DROP CONSTRAINT splits_pkey;
+ALTER TABLE splits ADD PRIMARY KEY (split_id);
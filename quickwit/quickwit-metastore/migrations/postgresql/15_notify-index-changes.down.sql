@@ -0,0 +1,2 @@
+DROP TRIGGER IF EXISTS quickwit_notify_index_change ON indexes CASCADE;
+DROP FUNCTION IF EXISTS notify_index_change();
@@ -0,0 +1,34 @@
+-- Notifies the nodes listening on the `quickwit_index_changes` channel whenever an index is
+-- created, updated, or deleted. Updates that only modify the index checkpoint (i.e. publish
+-- splits) are not notified.
+CREATE OR REPLACE FUNCTION notify_index_change() RETURNS trigger AS $$
+DECLARE
+    change_type TEXT;
+    index_uid VARCHAR(282);
+BEGIN
+    IF (TG_OP = 'INSERT') THEN
+        change_type := 'created';
+        index_uid := NEW.index_uid;
+    ELSIF (TG_OP = 'UPDATE') THEN
+        IF (OLD.index_metadata_json::JSONB - 'checkpoint' = NEW.index_metadata_json::JSONB - 'checkpoint') THEN
+            RETURN NULL;
+        END IF;
+        change_type := 'updated';
+        index_uid := NEW.index_uid;
+    ELSE
+        change_type := 'deleted';
+        index_uid := OLD.index_uid;
+    END IF;
+    PERFORM pg_notify(
+        'quickwit_index_changes',
+        json_build_object('change_type', change_type, 'index_uid', index_uid)::TEXT
+    );
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS quickwit_notify_index_change ON indexes CASCADE;
+CREATE TRIGGER quickwit_notify_index_change
+    AFTER INSERT OR UPDATE OR DELETE ON indexes
+    FOR EACH ROW
+    EXECUTE PROCEDURE notify_index_change();
@@ -0,0 +1,31 @@
+CREATE TABLE IF NOT EXISTS index_config_versions (
+    index_uid VARCHAR(282) NOT NULL,
+    version BIGINT NOT NULL,
+    index_config_json TEXT NOT NULL,
+    source_configs_json TEXT NOT NULL,
+    description TEXT NOT NULL,
+    author VARCHAR(255),
+    create_timestamp TIMESTAMP NOT NULL DEFAULT (CURRENT_TIMESTAMP AT TIME ZONE 'UTC'),
+
+    PRIMARY KEY (index_uid, version),
+    FOREIGN KEY(index_uid) REFERENCES indexes(index_uid) ON DELETE CASCADE
+);
+
+-- The current config of the existing indexes becomes their first version.
+INSERT INTO index_config_versions (
+    index_uid,
+    version,
+    index_config_json,
+    source_configs_json,
+    description,
+    create_timestamp
+)
+SELECT
+    index_uid,
+    1,
+    (index_metadata_json::JSONB -> 'index_config')::TEXT,
+    COALESCE((index_metadata_json::JSONB -> 'sources')::TEXT, '[]'),
+    'initial version',
+    create_timestamp
+FROM indexes
+ON CONFLICT DO NOTHING;
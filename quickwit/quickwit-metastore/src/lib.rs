@@ -48,16 +48,19 @@ pub(crate) use metastore::index_metadata::serialize::{IndexMetadataV0_6, Version
 #[cfg(feature = "postgres")]
 pub use metastore::postgresql_metastore::PostgresqlMetastore;
 pub use metastore::{
-    file_backed_metastore, AddSourceRequestExt, CreateIndexRequestExt, IndexMetadata,
-    IndexMetadataResponseExt, ListIndexesMetadataResponseExt, ListSplitsQuery,
+    file_backed_metastore, AddSourceRequestExt, CreateIndexRequestExt, IndexConfigVersionExt,
+    IndexMetadata, IndexMetadataResponseExt, ListIndexesMetadataResponseExt, ListSplitsQuery,
     ListSplitsRequestExt, ListSplitsResponseExt, MetastoreServiceExt,
     MetastoreServiceStreamSplitsExt, PublishSplitsRequestExt, StageSplitsRequestExt,
+    UpdateIndexRequestExt,
 };
 pub use metastore_factory::{MetastoreFactory, UnsupportedMetastore};
 pub use metastore_resolver::MetastoreResolver;
 use quickwit_common::is_disjoint;
 use quickwit_doc_mapper::tag_pruning::TagFilterAst;
-pub use split_metadata::{Split, SplitInfo, SplitMaturity, SplitMetadata, SplitState};
+pub use split_metadata::{
+    FieldStats, FieldValueRange, Split, SplitInfo, SplitMaturity, SplitMetadata, SplitState,
+};
 pub(crate) use split_metadata_version::{SplitMetadataV0_6, VersionedSplitMetadata};
 
 #[derive(utoipa::OpenApi)]
@@ -23,17 +23,19 @@ use async_trait::async_trait;
 use quickwit_common::uri::Uri;
 use quickwit_proto::control_plane::{ControlPlaneService, ControlPlaneServiceClient};
 use quickwit_proto::metastore::{
-    AcquireShardsRequest, AcquireShardsResponse, AddSourceRequest, CreateIndexRequest,
-    CreateIndexResponse, DeleteIndexRequest, DeleteQuery, DeleteShardsRequest,
+    AcquireShardsRequest, AcquireShardsResponse, AddIndexEventRequest, AddSourceRequest,
+    CreateIndexRequest, CreateIndexResponse, DeleteIndexRequest, DeleteQuery, DeleteShardsRequest,
     DeleteShardsResponse, DeleteSourceRequest, DeleteSplitsRequest, DeleteTask, EmptyResponse,
-    IndexMetadataRequest, IndexMetadataResponse, LastDeleteOpstampRequest,
+    FreezeIndexRequest, IndexMetadataRequest, IndexMetadataResponse, LastDeleteOpstampRequest,
     LastDeleteOpstampResponse, ListDeleteTasksRequest, ListDeleteTasksResponse,
-    ListIndexesMetadataRequest, ListIndexesMetadataResponse, ListShardsRequest, ListShardsResponse,
-    ListSplitsRequest, ListSplitsResponse, ListStaleSplitsRequest, MarkSplitsForDeletionRequest,
-    MetastoreResult, MetastoreService, MetastoreServiceClient, MetastoreServiceStream,
-    OpenShardsRequest, OpenShardsResponse, PublishSplitsRequest, ResetSourceCheckpointRequest,
-    StageSplitsRequest, ToggleSourceRequest, UpdateSplitsDeleteOpstampRequest,
-    UpdateSplitsDeleteOpstampResponse,
+    ListIndexConfigVersionsRequest, ListIndexConfigVersionsResponse, ListIndexEventsRequest,
+    ListIndexEventsResponse, ListIndexesMetadataRequest, ListIndexesMetadataResponse,
+    ListShardsRequest, ListShardsResponse, ListSplitsRequest, ListSplitsResponse,
+    ListStaleSplitsRequest, MarkSplitsForDeletionRequest, MetastoreResult, MetastoreService,
+    MetastoreServiceClient, MetastoreServiceStream, OpenShardsRequest, OpenShardsResponse,
+    PublishSplitsRequest, ResetSourceCheckpointRequest, RollbackIndexConfigRequest,
+    SetMergesPausedRequest, StageSplitsRequest, ToggleSourceRequest, UpdateIndexRequest,
+    UpdateSplitsDeleteOpstampRequest, UpdateSplitsDeleteOpstampResponse,
 };
 
 /// A [`MetastoreService`] implementation that proxies some requests to the control plane so it can
@@ -121,6 +123,31 @@ impl MetastoreService for ControlPlaneMetastore {
         self.metastore.index_metadata(request).await
     }
 
+    async fn update_index(
+        &mut self,
+        request: UpdateIndexRequest,
+    ) -> MetastoreResult<IndexMetadataResponse> {
+        self.metastore.update_index(request).await
+    }
+
+    // The control plane is not notified: callers are expected to disable the sources of the index
+    // with `toggle_source` before freezing it.
+    async fn freeze_index(
+        &mut self,
+        request: FreezeIndexRequest,
+    ) -> MetastoreResult<IndexMetadataResponse> {
+        self.metastore.freeze_index(request).await
+    }
+
+    // The merge pipelines are not scheduled by the control plane: the indexers pick up the flag
+    // from the index metadata.
+    async fn set_merges_paused(
+        &mut self,
+        request: SetMergesPausedRequest,
+    ) -> MetastoreResult<IndexMetadataResponse> {
+        self.metastore.set_merges_paused(request).await
+    }
+
     async fn list_indexes_metadata(
         &mut self,
         request: ListIndexesMetadataRequest,
@@ -156,6 +183,34 @@ impl MetastoreService for ControlPlaneMetastore {
         self.metastore.list_stale_splits(request).await
     }
 
+    async fn add_index_event(
+        &mut self,
+        request: AddIndexEventRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        self.metastore.add_index_event(request).await
+    }
+
+    async fn list_index_events(
+        &mut self,
+        request: ListIndexEventsRequest,
+    ) -> MetastoreResult<ListIndexEventsResponse> {
+        self.metastore.list_index_events(request).await
+    }
+
+    async fn list_index_config_versions(
+        &mut self,
+        request: ListIndexConfigVersionsRequest,
+    ) -> MetastoreResult<ListIndexConfigVersionsResponse> {
+        self.metastore.list_index_config_versions(request).await
+    }
+
+    async fn rollback_index_config(
+        &mut self,
+        request: RollbackIndexConfigRequest,
+    ) -> MetastoreResult<IndexMetadataResponse> {
+        self.metastore.rollback_index_config(request).await
+    }
+
     async fn mark_splits_for_deletion(
         &mut self,
         request: MarkSplitsForDeletionRequest,
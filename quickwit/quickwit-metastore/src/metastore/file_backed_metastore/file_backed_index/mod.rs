@@ -30,11 +30,12 @@ use std::ops::Bound;
 
 use itertools::Itertools;
 use quickwit_common::PrettySample;
-use quickwit_config::{SourceConfig, INGEST_SOURCE_ID};
+use quickwit_config::{DocMapping, SourceConfig, INGEST_SOURCE_ID};
 use quickwit_proto::metastore::{
     AcquireShardsSubrequest, AcquireShardsSubresponse, DeleteQuery, DeleteShardsSubrequest,
-    DeleteTask, EntityKind, ListShardsSubrequest, ListShardsSubresponse, MetastoreError,
-    MetastoreResult, OpenShardsSubrequest, OpenShardsSubresponse,
+    DeleteTask, EntityKind, IndexConfigVersion, IndexEvent, IndexEventType, ListShardsSubrequest,
+    ListShardsSubresponse, MetastoreError, MetastoreResult, OpenShardsSubrequest,
+    OpenShardsSubresponse,
 };
 use quickwit_proto::types::{IndexUid, PublishToken, SourceId, SplitId};
 use serde::{Deserialize, Serialize};
@@ -45,7 +46,11 @@ use tracing::{info, warn};
 
 use super::MutationOccurred;
 use crate::checkpoint::IndexCheckpointDelta;
-use crate::{split_tag_filter, IndexMetadata, ListSplitsQuery, Split, SplitMetadata, SplitState};
+use crate::metastore::{MAX_NUM_INDEX_CONFIG_VERSIONS, MAX_NUM_INDEX_EVENTS};
+use crate::{
+    split_tag_filter, IndexConfigVersionExt, IndexMetadata, ListSplitsQuery, Split, SplitMetadata,
+    SplitState,
+};
 
 /// A `FileBackedIndex` object carries an index metadata and its split metadata.
 // This struct is meant to be used only within the [`FileBackedMetastore`]. The public visibility is
@@ -61,6 +66,10 @@ pub(crate) struct FileBackedIndex {
     per_source_shards: HashMap<SourceId, Shards>,
     /// Delete tasks.
     delete_tasks: Vec<DeleteTask>,
+    /// Most recent events of the index, oldest first.
+    events: Vec<IndexEvent>,
+    /// Most recent config versions of the index, oldest first.
+    config_versions: Vec<IndexConfigVersion>,
     /// Stamper.
     stamper: Stamper,
     /// Flag used to avoid polling the metastore if
@@ -141,6 +150,8 @@ impl From<IndexMetadata> for FileBackedIndex {
             splits: Default::default(),
             per_source_shards: Default::default(),
             delete_tasks: Default::default(),
+            events: Default::default(),
+            config_versions: Default::default(),
             stamper: Default::default(),
             recently_modified: false,
             discarded: false,
@@ -177,6 +188,8 @@ impl FileBackedIndex {
             splits,
             per_source_shards,
             delete_tasks,
+            events: Vec::new(),
+            config_versions: Vec::new(),
             stamper: Stamper::new(last_opstamp),
             recently_modified: false,
             discarded: false,
@@ -220,6 +233,8 @@ impl FileBackedIndex {
         &mut self,
         split_metadata: SplitMetadata,
     ) -> Result<(), MetastoreError> {
+        self.metadata.ensure_not_frozen()?;
+
         // Check whether the split exists.
         // If the split exists, we check what state it is in. If it's anything other than `Staged`
         // something has gone very wrong and we should abort the operation.
@@ -474,26 +489,133 @@ impl FileBackedIndex {
         let index_uid = self.index_uid().clone();
         let source_id = source_config.source_id.clone();
 
+        self.ensure_config_history()?;
         self.metadata.add_source(source_config)?;
 
+        self.record_event(
+            IndexEventType::SourceAdded,
+            format!("source `{source_id}` added"),
+        );
+        self.record_config_version(format!("source `{source_id}` added"), None)?;
         self.per_source_shards
             .insert(source_id.clone(), Shards::empty(index_uid, source_id));
         Ok(())
     }
 
+    /// Updates the doc mapping of the index. Returns whether a mutation occurred.
+    pub(crate) fn update_doc_mapping(
+        &mut self,
+        doc_mapping: DocMapping,
+        author_opt: Option<String>,
+    ) -> MetastoreResult<bool> {
+        self.ensure_config_history()?;
+        let mutation_occurred = self.metadata.update_doc_mapping(doc_mapping)?;
+
+        if mutation_occurred {
+            self.record_event(IndexEventType::IndexUpdated, "doc mapping updated");
+            self.record_config_version("doc mapping updated", author_opt)?;
+        }
+        Ok(mutation_occurred)
+    }
+
+    /// Restores the doc mapping of a previous config version of the index. Returns whether a
+    /// mutation occurred.
+    pub(crate) fn rollback_doc_mapping(
+        &mut self,
+        version: u64,
+        author_opt: Option<String>,
+    ) -> MetastoreResult<bool> {
+        let Some(config_version) = self
+            .config_versions
+            .iter()
+            .find(|config_version| config_version.version == version)
+        else {
+            let message = format!(
+                "config version {version} of index `{}` does not exist or has been discarded",
+                self.index_id()
+            );
+            return Err(MetastoreError::InvalidArgument { message });
+        };
+        let doc_mapping = config_version.deserialize_index_config()?.doc_mapping;
+        let mutation_occurred = self.metadata.rollback_doc_mapping(doc_mapping)?;
+
+        if mutation_occurred {
+            let description = format!("doc mapping rolled back to version {version}");
+            self.record_event(IndexEventType::IndexUpdated, description.clone());
+            self.record_config_version(description, author_opt)?;
+        }
+        Ok(mutation_occurred)
+    }
+
+    /// Freezes the index. Returns whether a mutation occurred.
+    pub(crate) fn freeze(&mut self) -> MetastoreResult<bool> {
+        self.ensure_config_history()?;
+        let mutation_occurred = self.metadata.freeze();
+
+        if mutation_occurred {
+            self.record_event(IndexEventType::IndexFrozen, "index frozen");
+            self.record_config_version("index frozen", None)?;
+        }
+        Ok(mutation_occurred)
+    }
+
+    /// Pauses or resumes merges. Returns whether a mutation occurred.
+    pub(crate) fn set_merges_paused(&mut self, merges_paused: bool) -> bool {
+        let mutation_occurred = self.metadata.set_merges_paused(merges_paused);
+
+        if mutation_occurred {
+            let message = if merges_paused {
+                "merges paused"
+            } else {
+                "merges resumed"
+            };
+            self.record_event(IndexEventType::IndexUpdated, message);
+        }
+        mutation_occurred
+    }
+
     /// Enables or disables a source. Returns whether a mutation occurred.
     pub(crate) fn toggle_source(&mut self, source_id: &str, enable: bool) -> MetastoreResult<bool> {
-        self.metadata.toggle_source(source_id, enable)
+        self.ensure_config_history()?;
+        let mutation_occurred = self.metadata.toggle_source(source_id, enable)?;
+
+        if mutation_occurred {
+            let (event_type, action) = if enable {
+                (IndexEventType::SourceEnabled, "enabled")
+            } else {
+                (IndexEventType::SourceDisabled, "disabled")
+            };
+            let description = format!("source `{source_id}` {action}");
+            self.record_event(event_type, description.clone());
+            self.record_config_version(description, None)?;
+        }
+        Ok(mutation_occurred)
     }
 
     /// Deletes the source. Returns whether a mutation occurred.
     pub(crate) fn delete_source(&mut self, source_id: &str) -> MetastoreResult<bool> {
-        self.metadata.delete_source(source_id)
+        self.ensure_config_history()?;
+        let mutation_occurred = self.metadata.delete_source(source_id)?;
+
+        if mutation_occurred {
+            let description = format!("source `{source_id}` deleted");
+            self.record_event(IndexEventType::SourceDeleted, description.clone());
+            self.record_config_version(description, None)?;
+        }
+        Ok(mutation_occurred)
     }
 
     /// Resets the checkpoint of a source. Returns whether a mutation occurred.
     pub(crate) fn reset_source_checkpoint(&mut self, source_id: &str) -> MetastoreResult<bool> {
-        Ok(self.metadata.checkpoint.reset_source(source_id))
+        let mutation_occurred = self.metadata.checkpoint.reset_source(source_id);
+
+        if mutation_occurred {
+            self.record_event(
+                IndexEventType::SourceCheckpointReset,
+                format!("checkpoint of source `{source_id}` reset"),
+            );
+        }
+        Ok(mutation_occurred)
     }
 
     /// Creates [`DeleteTask`] from a [`DeleteQuery`].
@@ -501,6 +623,8 @@ impl FileBackedIndex {
         &mut self,
         delete_query: DeleteQuery,
     ) -> MetastoreResult<DeleteTask> {
+        self.metadata.ensure_not_frozen()?;
+
         let now_timestamp = OffsetDateTime::now_utc().unix_timestamp();
         let delete_task = DeleteTask {
             create_timestamp: now_timestamp,
@@ -508,9 +632,94 @@ impl FileBackedIndex {
             delete_query: Some(delete_query),
         };
         self.delete_tasks.push(delete_task.clone());
+        self.record_event(
+            IndexEventType::DeleteTaskCreated,
+            format!("delete task with opstamp {} created", delete_task.opstamp),
+        );
         Ok(delete_task)
     }
 
+    /// Records an event in the activity feed of the index.
+    pub(crate) fn record_event(&mut self, event_type: IndexEventType, message: impl Into<String>) {
+        self.add_event(event_type.as_str().to_string(), message.into());
+    }
+
+    /// Adds an event to the activity feed of the index, discarding the oldest events past
+    /// [`MAX_NUM_INDEX_EVENTS`].
+    pub(crate) fn add_event(&mut self, event_type: String, message: String) {
+        let event = IndexEvent {
+            index_uid: self.index_uid().to_string(),
+            event_type,
+            message,
+            timestamp: OffsetDateTime::now_utc().unix_timestamp(),
+        };
+        self.events.push(event);
+
+        if self.events.len() > MAX_NUM_INDEX_EVENTS {
+            let num_discarded_events = self.events.len() - MAX_NUM_INDEX_EVENTS;
+            self.events.drain(..num_discarded_events);
+        }
+    }
+
+    /// Lists the most recent events of the index, most recent first.
+    pub(crate) fn list_events(&self, limit_opt: Option<usize>) -> Vec<IndexEvent> {
+        let limit = limit_opt.unwrap_or(self.events.len());
+        self.events.iter().rev().take(limit).cloned().collect()
+    }
+
+    /// Records the current index and source configs as a new config version, discarding the
+    /// oldest versions past [`MAX_NUM_INDEX_CONFIG_VERSIONS`].
+    pub(crate) fn record_config_version(
+        &mut self,
+        description: impl Into<String>,
+        author_opt: Option<String>,
+    ) -> MetastoreResult<()> {
+        let version = self
+            .config_versions
+            .last()
+            .map(|config_version| config_version.version + 1)
+            .unwrap_or(1);
+        let config_version = IndexConfigVersion::try_from_index_metadata(
+            &self.metadata,
+            version,
+            description.into(),
+            author_opt,
+        )?;
+        self.config_versions.push(config_version);
+
+        if self.config_versions.len() > MAX_NUM_INDEX_CONFIG_VERSIONS {
+            let num_discarded_versions = self.config_versions.len() - MAX_NUM_INDEX_CONFIG_VERSIONS;
+            self.config_versions.drain(..num_discarded_versions);
+        }
+        Ok(())
+    }
+
+    /// Indexes created before config versioning have no history. Before their config is mutated
+    /// for the first time, their current config is recorded as the initial version so that it
+    /// can be restored later.
+    fn ensure_config_history(&mut self) -> MetastoreResult<()> {
+        if !self.config_versions.is_empty() {
+            return Ok(());
+        }
+        self.record_config_version("initial version", None)?;
+
+        if let Some(config_version) = self.config_versions.last_mut() {
+            config_version.timestamp = self.metadata.create_timestamp;
+        }
+        Ok(())
+    }
+
+    /// Lists the most recent config versions of the index, most recent first.
+    pub(crate) fn list_config_versions(&self, limit_opt: Option<usize>) -> Vec<IndexConfigVersion> {
+        let limit = limit_opt.unwrap_or(self.config_versions.len());
+        self.config_versions
+            .iter()
+            .rev()
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
     /// Returns index last delete opstamp.
     pub(crate) fn last_delete_opstamp(&self) -> u64 {
         self.delete_tasks
@@ -684,6 +893,10 @@ fn split_query_predicate(split: &&Split, query: &ListSplitsQuery) -> bool {
         return false;
     }
 
+    if !query.split_ids.is_empty() && !query.split_ids.contains(&split.split_metadata.split_id) {
+        return false;
+    }
+
     if !query.split_states.is_empty() && !query.split_states.contains(&split.split_state) {
         return false;
     }
@@ -730,10 +943,13 @@ mod tests {
     use std::collections::BTreeSet;
 
     use quickwit_doc_mapper::tag_pruning::TagFilterAst;
+    use quickwit_proto::metastore::IndexEventType;
     use quickwit_proto::types::IndexUid;
 
+    use super::FileBackedIndex;
     use crate::file_backed_metastore::file_backed_index::split_query_predicate;
-    use crate::{ListSplitsQuery, Split, SplitMetadata, SplitState};
+    use crate::metastore::{MAX_NUM_INDEX_CONFIG_VERSIONS, MAX_NUM_INDEX_EVENTS};
+    use crate::{IndexMetadata, ListSplitsQuery, Split, SplitMetadata, SplitState};
 
     fn make_splits() -> [Split; 3] {
         [
@@ -878,4 +1094,81 @@ mod tests {
         assert!(!split_query_predicate(&&split_2, &query));
         assert!(!split_query_predicate(&&split_3, &query));
     }
+
+    #[test]
+    fn test_file_backed_index_events() {
+        let index_metadata = IndexMetadata::for_test("test-index", "ram:///indexes/test-index");
+        let mut index = FileBackedIndex::from(index_metadata);
+        assert!(index.list_events(None).is_empty());
+
+        for i in 0..MAX_NUM_INDEX_EVENTS + 10 {
+            index.record_event(IndexEventType::RetentionApplied, format!("event-{i}"));
+        }
+        let events = index.list_events(None);
+        assert_eq!(events.len(), MAX_NUM_INDEX_EVENTS);
+        assert_eq!(
+            events[0].message,
+            format!("event-{}", MAX_NUM_INDEX_EVENTS + 9)
+        );
+        assert_eq!(events[MAX_NUM_INDEX_EVENTS - 1].message, "event-10");
+
+        let events = index.list_events(Some(2));
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].event_type, "retention_applied");
+        assert_eq!(events[0].index_uid, index.index_uid().to_string());
+
+        // Events survive a serialization round trip.
+        let index_json = serde_json::to_string(&index).unwrap();
+        let deserialized_index: FileBackedIndex = serde_json::from_str(&index_json).unwrap();
+        assert_eq!(
+            deserialized_index.list_events(None),
+            index.list_events(None)
+        );
+    }
+
+    #[test]
+    fn test_file_backed_index_config_versions() {
+        let mut index_metadata = IndexMetadata::for_test("test-index", "ram:///indexes/test-index");
+        index_metadata.create_timestamp = 1337;
+        let mut index = FileBackedIndex::from(index_metadata);
+        assert!(index.list_config_versions(None).is_empty());
+
+        // The config of an index without history is recorded before its first mutation.
+        assert!(index.freeze().unwrap());
+
+        let versions = index.list_config_versions(None);
+        assert_eq!(versions.len(), 2);
+        assert_eq!(versions[0].version, 2);
+        assert_eq!(versions[0].description, "index frozen");
+        assert_eq!(versions[1].version, 1);
+        assert_eq!(versions[1].description, "initial version");
+        assert_eq!(versions[1].timestamp, 1337);
+
+        for i in 0..MAX_NUM_INDEX_CONFIG_VERSIONS {
+            index
+                .record_config_version(format!("version-{i}"), None)
+                .unwrap();
+        }
+        let versions = index.list_config_versions(None);
+        assert_eq!(versions.len(), MAX_NUM_INDEX_CONFIG_VERSIONS);
+        assert_eq!(
+            versions[0].version,
+            MAX_NUM_INDEX_CONFIG_VERSIONS as u64 + 2
+        );
+        assert_eq!(versions[MAX_NUM_INDEX_CONFIG_VERSIONS - 1].version, 3);
+
+        let versions = index.list_config_versions(Some(2));
+        assert_eq!(versions.len(), 2);
+
+        // Versions that have been discarded cannot be restored.
+        index.rollback_doc_mapping(1, None).unwrap_err();
+
+        // Config versions survive a serialization round trip.
+        let index_json = serde_json::to_string(&index).unwrap();
+        let deserialized_index: FileBackedIndex = serde_json::from_str(&index_json).unwrap();
+        assert_eq!(
+            deserialized_index.list_config_versions(None),
+            index.list_config_versions(None)
+        );
+    }
 }
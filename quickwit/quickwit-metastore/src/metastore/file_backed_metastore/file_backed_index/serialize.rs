@@ -20,6 +20,7 @@
 use std::collections::HashMap;
 
 use itertools::Itertools;
+use quickwit_proto::metastore::{IndexConfigVersion, IndexEvent};
 use quickwit_proto::types::SourceId;
 use serde::{Deserialize, Serialize};
 
@@ -61,6 +62,10 @@ pub(crate) struct FileBackedIndexV0_6 {
     shards: HashMap<SourceId, SerdeShards>,
     #[serde(default)]
     delete_tasks: Vec<DeleteTask>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    events: Vec<IndexEvent>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    config_versions: Vec<IndexConfigVersion>,
 }
 
 impl From<FileBackedIndex> for FileBackedIndexV0_6 {
@@ -85,6 +90,8 @@ impl From<FileBackedIndex> for FileBackedIndexV0_6 {
             splits,
             shards,
             delete_tasks,
+            events: index.events,
+            config_versions: index.config_versions,
         }
     }
 }
@@ -108,6 +115,10 @@ impl From<FileBackedIndexV0_6> for FileBackedIndex {
                 )
             })
             .collect();
-        Self::new(index.metadata, index.splits, shards, index.delete_tasks)
+        let mut file_backed_index =
+            Self::new(index.metadata, index.splits, shards, index.delete_tasks);
+        file_backed_index.events = index.events;
+        file_backed_index.config_versions = index.config_versions;
+        file_backed_index
     }
 }
@@ -138,6 +138,7 @@ impl Shards {
                     follower_id: subrequest.follower_id.clone(),
                     publish_position_inclusive: Some(Position::Beginning),
                     publish_token: None,
+                    priority: subrequest.priority,
                 };
                 mutation_occurred = true;
                 entry.insert(shard.clone());
@@ -149,6 +150,7 @@ impl Shards {
                     shard_id=%shard.shard_id,
                     leader_id=%shard.leader_id,
                     follower_id=?shard.follower_id,
+                    priority=shard.priority().as_json_str_name(),
                     "opened shard"
                 );
                 shard
@@ -323,7 +325,7 @@ impl From<Shards> for SerdeShards {
 
 #[cfg(test)]
 mod tests {
-    use quickwit_proto::ingest::ShardState;
+    use quickwit_proto::ingest::{ShardPriority, ShardState};
 
     use super::*;
 
@@ -340,6 +342,7 @@ mod tests {
             leader_id: "leader_id".to_string(),
             follower_id: None,
             next_shard_id: 1,
+            priority: ShardPriority::Realtime as i32,
         };
         let MutationOccurred::Yes(subresponse) = shards.open_shards(subrequest.clone()).unwrap()
         else {
@@ -357,6 +360,7 @@ mod tests {
         assert_eq!(shard.leader_id, "leader_id");
         assert_eq!(shard.follower_id, None);
         assert_eq!(shard.publish_position_inclusive(), Position::Beginning);
+        assert_eq!(shard.priority(), ShardPriority::Realtime);
 
         assert_eq!(shards.shards.get(&1).unwrap(), shard);
         assert_eq!(shards.next_shard_id, 2);
@@ -377,6 +381,7 @@ mod tests {
             leader_id: "leader_id".to_string(),
             follower_id: Some("follower_id".to_string()),
             next_shard_id: 2,
+            priority: ShardPriority::Backfill as i32,
         };
         let MutationOccurred::Yes(subresponse) = shards.open_shards(subrequest).unwrap() else {
             panic!("Expected `MutationOccured::No`");
@@ -393,6 +398,7 @@ mod tests {
         assert_eq!(shard.leader_id, "leader_id");
         assert_eq!(shard.follower_id.as_ref().unwrap(), "follower_id");
         assert_eq!(shard.publish_position_inclusive(), Position::Beginning);
+        assert_eq!(shard.priority(), ShardPriority::Backfill);
 
         assert_eq!(shards.shards.get(&2).unwrap(), shard);
         assert_eq!(shards.next_shard_id, 3);
@@ -404,6 +410,7 @@ mod tests {
             leader_id: "leader_id".to_string(),
             follower_id: Some("follower_id".to_string()),
             next_shard_id: 1,
+            priority: ShardPriority::Realtime as i32,
         };
         let error = shards.open_shards(subrequest).unwrap_err();
         assert_eq!(error, MetastoreError::InconsistentControlPlaneState);
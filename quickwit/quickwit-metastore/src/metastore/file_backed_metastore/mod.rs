@@ -37,17 +37,20 @@ use itertools::Itertools;
 use quickwit_common::ServiceStream;
 use quickwit_config::validate_index_id_pattern;
 use quickwit_proto::metastore::{
-    AcquireShardsRequest, AcquireShardsResponse, AcquireShardsSubrequest, AddSourceRequest,
-    CreateIndexRequest, CreateIndexResponse, DeleteIndexRequest, DeleteQuery, DeleteShardsRequest,
-    DeleteShardsResponse, DeleteShardsSubrequest, DeleteSourceRequest, DeleteSplitsRequest,
-    DeleteTask, EmptyResponse, EntityKind, IndexMetadataRequest, IndexMetadataResponse,
-    LastDeleteOpstampRequest, LastDeleteOpstampResponse, ListDeleteTasksRequest,
-    ListDeleteTasksResponse, ListIndexesMetadataRequest, ListIndexesMetadataResponse,
+    AcquireShardsRequest, AcquireShardsResponse, AcquireShardsSubrequest, AddIndexEventRequest,
+    AddSourceRequest, CreateIndexRequest, CreateIndexResponse, DeleteIndexRequest, DeleteQuery,
+    DeleteShardsRequest, DeleteShardsResponse, DeleteShardsSubrequest, DeleteSourceRequest,
+    DeleteSplitsRequest, DeleteTask, EmptyResponse, EntityKind, FreezeIndexRequest, IndexEventType,
+    IndexMetadataRequest, IndexMetadataResponse, LastDeleteOpstampRequest,
+    LastDeleteOpstampResponse, ListDeleteTasksRequest, ListDeleteTasksResponse,
+    ListIndexConfigVersionsRequest, ListIndexConfigVersionsResponse, ListIndexEventsRequest,
+    ListIndexEventsResponse, ListIndexesMetadataRequest, ListIndexesMetadataResponse,
     ListShardsRequest, ListShardsResponse, ListSplitsRequest, ListSplitsResponse,
     ListStaleSplitsRequest, MarkSplitsForDeletionRequest, MetastoreError, MetastoreResult,
     MetastoreService, MetastoreServiceStream, OpenShardsRequest, OpenShardsResponse,
-    OpenShardsSubrequest, PublishSplitsRequest, ResetSourceCheckpointRequest, StageSplitsRequest,
-    ToggleSourceRequest, UpdateSplitsDeleteOpstampRequest, UpdateSplitsDeleteOpstampResponse,
+    OpenShardsSubrequest, PublishSplitsRequest, ResetSourceCheckpointRequest,
+    RollbackIndexConfigRequest, SetMergesPausedRequest, StageSplitsRequest, ToggleSourceRequest,
+    UpdateIndexRequest, UpdateSplitsDeleteOpstampRequest, UpdateSplitsDeleteOpstampResponse,
 };
 use quickwit_proto::types::IndexUid;
 use quickwit_storage::Storage;
@@ -65,7 +68,8 @@ use self::store_operations::{
 use super::{
     AddSourceRequestExt, CreateIndexRequestExt, IndexMetadataResponseExt,
     ListIndexesMetadataResponseExt, ListSplitsRequestExt, ListSplitsResponseExt,
-    PublishSplitsRequestExt, StageSplitsRequestExt, STREAM_SPLITS_CHUNK_SIZE,
+    PublishSplitsRequestExt, StageSplitsRequestExt, UpdateIndexRequestExt,
+    STREAM_SPLITS_CHUNK_SIZE,
 };
 use crate::checkpoint::IndexCheckpointDelta;
 use crate::{IndexMetadata, ListSplitsQuery, MetastoreServiceExt, Split, SplitState};
@@ -404,7 +408,9 @@ impl MetastoreService for FileBackedMetastore {
         // Put index metadata on storage.
         let index_metadata = IndexMetadata::new(index_config);
         let index_uid = index_metadata.index_uid.clone();
-        let index = FileBackedIndex::from(index_metadata);
+        let mut index = FileBackedIndex::from(index_metadata);
+        index.record_event(IndexEventType::IndexCreated, "index created");
+        index.record_config_version("index created", None)?;
         put_index(&*self.storage, &index).await?;
 
         per_index_metastores_wlock.insert(
@@ -684,6 +690,74 @@ impl MetastoreService for FileBackedMetastore {
         Ok(response)
     }
 
+    async fn update_index(
+        &mut self,
+        request: UpdateIndexRequest,
+    ) -> MetastoreResult<IndexMetadataResponse> {
+        let doc_mapping = request.deserialize_doc_mapping()?;
+        let index_uid: IndexUid = request.index_uid.into();
+        let author_opt = request.author;
+
+        let index_metadata = self
+            .mutate(index_uid, |index| {
+                let mutation_occurred = index.update_doc_mapping(doc_mapping, author_opt)?;
+                let index_metadata = index.metadata().clone();
+
+                if mutation_occurred {
+                    Ok(MutationOccurred::Yes(index_metadata))
+                } else {
+                    Ok(MutationOccurred::No(index_metadata))
+                }
+            })
+            .await?;
+        let response = IndexMetadataResponse::try_from_index_metadata(index_metadata)?;
+        Ok(response)
+    }
+
+    async fn freeze_index(
+        &mut self,
+        request: FreezeIndexRequest,
+    ) -> MetastoreResult<IndexMetadataResponse> {
+        let index_uid: IndexUid = request.index_uid.into();
+
+        let index_metadata = self
+            .mutate(index_uid, |index| {
+                let mutation_occurred = index.freeze()?;
+                let index_metadata = index.metadata().clone();
+
+                if mutation_occurred {
+                    Ok(MutationOccurred::Yes(index_metadata))
+                } else {
+                    Ok(MutationOccurred::No(index_metadata))
+                }
+            })
+            .await?;
+        let response = IndexMetadataResponse::try_from_index_metadata(index_metadata)?;
+        Ok(response)
+    }
+
+    async fn set_merges_paused(
+        &mut self,
+        request: SetMergesPausedRequest,
+    ) -> MetastoreResult<IndexMetadataResponse> {
+        let index_uid: IndexUid = request.index_uid.into();
+
+        let index_metadata = self
+            .mutate(index_uid, |index| {
+                let mutation_occurred = index.set_merges_paused(request.merges_paused);
+                let index_metadata = index.metadata().clone();
+
+                if mutation_occurred {
+                    Ok(MutationOccurred::Yes(index_metadata))
+                } else {
+                    Ok(MutationOccurred::No(index_metadata))
+                }
+            })
+            .await?;
+        let response = IndexMetadataResponse::try_from_index_metadata(index_metadata)?;
+        Ok(response)
+    }
+
     async fn list_indexes_metadata(
         &mut self,
         request: ListIndexesMetadataRequest,
@@ -881,6 +955,74 @@ impl MetastoreService for FileBackedMetastore {
         let response = ListDeleteTasksResponse { delete_tasks };
         Ok(response)
     }
+
+    /// -------------------------------------------------------------------------------
+    /// Index events
+
+    async fn add_index_event(
+        &mut self,
+        request: AddIndexEventRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        let index_uid: IndexUid = request.index_uid.into();
+
+        self.mutate(index_uid, |index| {
+            index.add_event(request.event_type, request.message);
+            Ok(MutationOccurred::Yes(()))
+        })
+        .await?;
+        Ok(EmptyResponse {})
+    }
+
+    async fn list_index_events(
+        &mut self,
+        request: ListIndexEventsRequest,
+    ) -> MetastoreResult<ListIndexEventsResponse> {
+        let index_uid: IndexUid = request.index_uid.into();
+        let limit_opt = request.limit.map(|limit| limit as usize);
+
+        let events = self
+            .read(index_uid, |index| Ok(index.list_events(limit_opt)))
+            .await?;
+        let response = ListIndexEventsResponse { events };
+        Ok(response)
+    }
+
+    async fn list_index_config_versions(
+        &mut self,
+        request: ListIndexConfigVersionsRequest,
+    ) -> MetastoreResult<ListIndexConfigVersionsResponse> {
+        let index_uid: IndexUid = request.index_uid.into();
+        let limit_opt = request.limit.map(|limit| limit as usize);
+
+        let versions = self
+            .read(index_uid, |index| Ok(index.list_config_versions(limit_opt)))
+            .await?;
+        let response = ListIndexConfigVersionsResponse { versions };
+        Ok(response)
+    }
+
+    async fn rollback_index_config(
+        &mut self,
+        request: RollbackIndexConfigRequest,
+    ) -> MetastoreResult<IndexMetadataResponse> {
+        let index_uid: IndexUid = request.index_uid.into();
+
+        let index_metadata = self
+            .mutate(index_uid, |index| {
+                let mutation_occurred =
+                    index.rollback_doc_mapping(request.version, request.author)?;
+                let index_metadata = index.metadata().clone();
+
+                if mutation_occurred {
+                    Ok(MutationOccurred::Yes(index_metadata))
+                } else {
+                    Ok(MutationOccurred::No(index_metadata))
+                }
+            })
+            .await?;
+        let response = IndexMetadataResponse::try_from_index_metadata(index_metadata)?;
+        Ok(response)
+    }
 }
 
 impl MetastoreServiceExt for FileBackedMetastore {}
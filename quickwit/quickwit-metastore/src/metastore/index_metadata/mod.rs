@@ -23,7 +23,9 @@ use std::collections::hash_map::Entry;
 use std::collections::{BTreeMap, HashMap};
 
 use quickwit_common::uri::Uri;
-use quickwit_config::{IndexConfig, SourceConfig, TestableForRegression};
+use quickwit_config::{
+    build_doc_mapper, DocMapping, IndexConfig, SourceConfig, TestableForRegression,
+};
 use quickwit_proto::metastore::{EntityKind, MetastoreError, MetastoreResult};
 use quickwit_proto::types::{IndexUid, Position, SourceId};
 use serde::{Deserialize, Serialize};
@@ -48,6 +50,15 @@ pub struct IndexMetadata {
     pub create_timestamp: i64,
     /// Sources
     pub sources: HashMap<SourceId, SourceConfig>,
+    /// Version of the doc mapping, incremented every time the doc mapping of the index is
+    /// updated.
+    pub doc_mapping_version: u64,
+    /// Whether the index is frozen. The sources of a frozen index are disabled and it no longer
+    /// accepts writes: it can only be searched, garbage collected, or deleted.
+    pub frozen: bool,
+    /// Whether merges are paused for the index. The flag is applied by the merge pipelines of the
+    /// index on every indexer and survives restarts.
+    pub merges_paused: bool,
 }
 
 impl IndexMetadata {
@@ -65,6 +76,9 @@ impl IndexMetadata {
             checkpoint: Default::default(),
             create_timestamp: OffsetDateTime::now_utc().unix_timestamp(),
             sources: HashMap::default(),
+            doc_mapping_version: 0,
+            frozen: false,
+            merges_paused: false,
         }
     }
 
@@ -101,6 +115,8 @@ impl IndexMetadata {
 
     /// Adds a source to the index. Returns an error if the source_id already exists.
     pub fn add_source(&mut self, source_config: SourceConfig) -> MetastoreResult<()> {
+        self.ensure_not_frozen()?;
+
         match self.sources.entry(source_config.source_id.clone()) {
             Entry::Occupied(_) => Err(MetastoreError::AlreadyExists(EntityKind::Source {
                 index_id: self.index_id().to_string(),
@@ -114,7 +130,58 @@ impl IndexMetadata {
         }
     }
 
+    /// Replaces the doc mapping of the index with `doc_mapping` and increments the doc mapping
+    /// version. Only additive updates are accepted. Returns whether the index was modified (true).
+    pub(crate) fn update_doc_mapping(&mut self, doc_mapping: DocMapping) -> MetastoreResult<bool> {
+        if self.index_config.doc_mapping == doc_mapping {
+            return Ok(false);
+        }
+        self.index_config
+            .doc_mapping
+            .validate_additive_update(&doc_mapping)
+            .and_then(|_| {
+                build_doc_mapper(&doc_mapping, &self.index_config.search_settings)?;
+                Ok(())
+            })
+            .map_err(|error| MetastoreError::InvalidArgument {
+                message: format!(
+                    "failed to update doc mapping of index `{}`: {error}",
+                    self.index_id()
+                ),
+            })?;
+        self.index_config.doc_mapping = doc_mapping;
+        self.doc_mapping_version += 1;
+        Ok(true)
+    }
+
+    /// Restores `doc_mapping`, taken from a previous config version of the index, and increments
+    /// the doc mapping version. Unlike [`Self::update_doc_mapping`], non-additive changes are
+    /// accepted since the purpose of a rollback is precisely to undo a previous update. Returns
+    /// whether the index was modified.
+    pub(crate) fn rollback_doc_mapping(
+        &mut self,
+        doc_mapping: DocMapping,
+    ) -> MetastoreResult<bool> {
+        if self.index_config.doc_mapping == doc_mapping {
+            return Ok(false);
+        }
+        build_doc_mapper(&doc_mapping, &self.index_config.search_settings).map_err(|error| {
+            MetastoreError::InvalidArgument {
+                message: format!(
+                    "failed to roll back doc mapping of index `{}`: {error}",
+                    self.index_id()
+                ),
+            }
+        })?;
+        self.index_config.doc_mapping = doc_mapping;
+        self.doc_mapping_version += 1;
+        Ok(true)
+    }
+
     pub(crate) fn toggle_source(&mut self, source_id: &str, enable: bool) -> MetastoreResult<bool> {
+        if enable {
+            self.ensure_not_frozen()?;
+        }
         let Some(source_config) = self.sources.get_mut(source_id) else {
             return Err(MetastoreError::NotFound(EntityKind::Source {
                 index_id: self.index_id().to_string(),
@@ -137,6 +204,39 @@ impl IndexMetadata {
         self.checkpoint.remove_source(source_id);
         Ok(true)
     }
+
+    /// Freezes the index: disables all its sources and rejects subsequent writes. Returns whether
+    /// the index was modified (true).
+    pub(crate) fn freeze(&mut self) -> bool {
+        let mut mutation_occurred = !self.frozen;
+        self.frozen = true;
+
+        for source_config in self.sources.values_mut() {
+            mutation_occurred |= source_config.enabled;
+            source_config.enabled = false;
+        }
+        mutation_occurred
+    }
+
+    /// Pauses or resumes merges for the index. Returns whether a mutation occurred.
+    pub(crate) fn set_merges_paused(&mut self, merges_paused: bool) -> bool {
+        let mutation_occurred = self.merges_paused != merges_paused;
+        self.merges_paused = merges_paused;
+        mutation_occurred
+    }
+
+    /// Returns an error if the index is frozen.
+    pub(crate) fn ensure_not_frozen(&self) -> MetastoreResult<()> {
+        if self.frozen {
+            return Err(MetastoreError::FailedPrecondition {
+                entity: EntityKind::Index {
+                    index_id: self.index_id().to_string(),
+                },
+                message: "index is frozen and no longer accepts writes".to_string(),
+            });
+        }
+        Ok(())
+    }
 }
 
 impl TestableForRegression for IndexMetadata {
@@ -159,6 +259,9 @@ impl TestableForRegression for IndexMetadata {
             checkpoint,
             create_timestamp: 1789,
             sources: Default::default(),
+            doc_mapping_version: 0,
+            frozen: false,
+            merges_paused: false,
         };
         index_metadata
             .add_source(SourceConfig::sample_for_regression())
@@ -171,5 +274,8 @@ impl TestableForRegression for IndexMetadata {
         assert_eq!(self.checkpoint, other.checkpoint);
         assert_eq!(self.create_timestamp, other.create_timestamp);
         assert_eq!(self.sources, other.sources);
+        assert_eq!(self.doc_mapping_version, other.doc_mapping_version);
+        assert_eq!(self.frozen, other.frozen);
+        assert_eq!(self.merges_paused, other.merges_paused);
     }
 }
@@ -64,6 +64,9 @@ impl From<IndexMetadata> for IndexMetadataV0_6 {
             checkpoint: index_metadata.checkpoint,
             create_timestamp: index_metadata.create_timestamp,
             sources,
+            doc_mapping_version: index_metadata.doc_mapping_version,
+            frozen: index_metadata.frozen,
+            merges_paused: index_metadata.merges_paused,
         }
     }
 }
@@ -82,6 +85,20 @@ pub(crate) struct IndexMetadataV0_6 {
     pub create_timestamp: i64,
     #[schema(value_type = Vec<VersionedSourceConfig>)]
     pub sources: Vec<SourceConfig>,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub doc_mapping_version: u64,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub frozen: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub merges_paused: bool,
+}
+
+fn is_zero(value: &u64) -> bool {
+    *value == 0
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
 }
 
 impl TryFrom<IndexMetadataV0_6> for IndexMetadata {
@@ -105,6 +122,9 @@ impl TryFrom<IndexMetadataV0_6> for IndexMetadata {
             checkpoint: v0_6.checkpoint,
             create_timestamp: v0_6.create_timestamp,
             sources,
+            doc_mapping_version: v0_6.doc_mapping_version,
+            frozen: v0_6.frozen,
+            merges_paused: v0_6.merges_paused,
         })
     }
 }
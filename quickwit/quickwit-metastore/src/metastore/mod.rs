@@ -20,6 +20,8 @@
 pub mod file_backed_metastore;
 pub(crate) mod index_metadata;
 #[cfg(feature = "postgres")]
+mod postgresql_listener;
+#[cfg(feature = "postgres")]
 pub mod postgresql_metastore;
 #[cfg(feature = "postgres")]
 mod postgresql_model;
@@ -34,13 +36,13 @@ pub use index_metadata::IndexMetadata;
 use itertools::Itertools;
 use once_cell::sync::Lazy;
 use quickwit_common::tower::PrometheusMetricsLayer;
-use quickwit_config::{IndexConfig, SourceConfig};
+use quickwit_config::{DocMapping, IndexConfig, SourceConfig};
 use quickwit_doc_mapper::tag_pruning::TagFilterAst;
 use quickwit_proto::metastore::{
-    serde_utils, AddSourceRequest, CreateIndexRequest, DeleteTask, IndexMetadataRequest,
-    IndexMetadataResponse, ListIndexesMetadataResponse, ListSplitsRequest, ListSplitsResponse,
-    MetastoreError, MetastoreResult, MetastoreService, MetastoreServiceClient,
-    MetastoreServiceStream, PublishSplitsRequest, StageSplitsRequest,
+    serde_utils, AddSourceRequest, CreateIndexRequest, DeleteTask, IndexConfigVersion,
+    IndexMetadataRequest, IndexMetadataResponse, ListIndexesMetadataResponse, ListSplitsRequest,
+    ListSplitsResponse, MetastoreError, MetastoreResult, MetastoreService, MetastoreServiceClient,
+    MetastoreServiceStream, PublishSplitsRequest, StageSplitsRequest, UpdateIndexRequest,
 };
 use quickwit_proto::types::{IndexUid, SplitId};
 use time::OffsetDateTime;
@@ -51,6 +53,13 @@ use crate::{Split, SplitMetadata, SplitState};
 /// Splits batch size returned by the stream splits API
 const STREAM_SPLITS_CHUNK_SIZE: usize = 1_000;
 
+/// Maximum number of events kept in the activity feed of an index. Older events are discarded.
+pub(crate) const MAX_NUM_INDEX_EVENTS: usize = 100;
+
+/// Maximum number of config versions kept in the history of an index. Older versions are
+/// discarded.
+pub(crate) const MAX_NUM_INDEX_CONFIG_VERSIONS: usize = 50;
+
 static METASTORE_METRICS_LAYER: Lazy<PrometheusMetricsLayer<1>> =
     Lazy::new(|| PrometheusMetricsLayer::new("metastore", ["request"]));
 
@@ -143,6 +152,93 @@ impl CreateIndexRequestExt for CreateIndexRequest {
     }
 }
 
+/// Helper trait to build a [`UpdateIndexRequest`] and deserialize its payload.
+pub trait UpdateIndexRequestExt {
+    /// Creates a new [`UpdateIndexRequest`] from an index UID and a [`DocMapping`].
+    fn try_from_doc_mapping(
+        index_uid: impl Into<IndexUid>,
+        doc_mapping: &DocMapping,
+    ) -> MetastoreResult<UpdateIndexRequest>;
+
+    /// Deserializes the `doc_mapping_json` field of a [`UpdateIndexRequest`] into a
+    /// [`DocMapping`].
+    fn deserialize_doc_mapping(&self) -> MetastoreResult<DocMapping>;
+}
+
+impl UpdateIndexRequestExt for UpdateIndexRequest {
+    fn try_from_doc_mapping(
+        index_uid: impl Into<IndexUid>,
+        doc_mapping: &DocMapping,
+    ) -> MetastoreResult<UpdateIndexRequest> {
+        let doc_mapping_json = serde_utils::to_json_str(doc_mapping)?;
+        let request = Self {
+            index_uid: index_uid.into().to_string(),
+            doc_mapping_json,
+            author: None,
+        };
+        Ok(request)
+    }
+
+    fn deserialize_doc_mapping(&self) -> MetastoreResult<DocMapping> {
+        serde_utils::from_json_str(&self.doc_mapping_json)
+    }
+}
+
+/// Helper trait to build an [`IndexConfigVersion`] and deserialize its payload.
+pub trait IndexConfigVersionExt {
+    /// Creates a new [`IndexConfigVersion`] capturing the current index and source configs of
+    /// an index.
+    fn try_from_index_metadata(
+        index_metadata: &IndexMetadata,
+        version: u64,
+        description: String,
+        author_opt: Option<String>,
+    ) -> MetastoreResult<IndexConfigVersion>;
+
+    /// Deserializes the `index_config_json` field of an [`IndexConfigVersion`] into an
+    /// [`IndexConfig`].
+    fn deserialize_index_config(&self) -> MetastoreResult<IndexConfig>;
+
+    /// Deserializes the `source_configs_json` field of an [`IndexConfigVersion`] into a list of
+    /// [`SourceConfig`].
+    fn deserialize_source_configs(&self) -> MetastoreResult<Vec<SourceConfig>>;
+}
+
+impl IndexConfigVersionExt for IndexConfigVersion {
+    fn try_from_index_metadata(
+        index_metadata: &IndexMetadata,
+        version: u64,
+        description: String,
+        author_opt: Option<String>,
+    ) -> MetastoreResult<IndexConfigVersion> {
+        let index_config_json = serde_utils::to_json_str(&index_metadata.index_config)?;
+        let source_configs: Vec<&SourceConfig> = index_metadata
+            .sources
+            .values()
+            .sorted_by(|left, right| left.source_id.cmp(&right.source_id))
+            .collect();
+        let source_configs_json = serde_utils::to_json_str(&source_configs)?;
+        let config_version = Self {
+            index_uid: index_metadata.index_uid.to_string(),
+            version,
+            index_config_json,
+            source_configs_json,
+            description,
+            author: author_opt,
+            timestamp: OffsetDateTime::now_utc().unix_timestamp(),
+        };
+        Ok(config_version)
+    }
+
+    fn deserialize_index_config(&self) -> MetastoreResult<IndexConfig> {
+        serde_utils::from_json_str(&self.index_config_json)
+    }
+
+    fn deserialize_source_configs(&self) -> MetastoreResult<Vec<SourceConfig>> {
+        serde_utils::from_json_str(&self.source_configs_json)
+    }
+}
+
 /// Helper trait to build a [`IndexMetadataResponse`] and deserialize its payload.
 pub trait IndexMetadataResponseExt {
     /// Creates a new [`IndexMetadataResponse`] from an [`IndexMetadata`].
@@ -405,6 +501,10 @@ pub struct ListSplitsQuery {
     /// The number of splits to skip.
     pub offset: Option<usize>,
 
+    /// A specific set of split ID(s) to filter by.
+    #[serde(default)]
+    pub split_ids: Vec<String>,
+
     /// A specific split state(s) to filter by.
     pub split_states: Vec<SplitState>,
 
@@ -439,6 +539,7 @@ impl ListSplitsQuery {
             index_uids: vec![index_uid],
             limit: None,
             offset: None,
+            split_ids: Vec::new(),
             split_states: Vec::new(),
             tags: None,
             time_range: Default::default(),
@@ -463,6 +564,7 @@ impl ListSplitsQuery {
             index_uids,
             limit: None,
             offset: None,
+            split_ids: Vec::new(),
             split_states: Vec::new(),
             tags: None,
             time_range: Default::default(),
@@ -486,6 +588,12 @@ impl ListSplitsQuery {
         self
     }
 
+    /// Select splits which have one of the given split IDs.
+    pub fn with_split_ids(mut self, split_ids: impl IntoIterator<Item = String>) -> Self {
+        self.split_ids.extend(split_ids);
+        self
+    }
+
     /// Select splits which have the given split state.
     pub fn with_split_state(mut self, state: SplitState) -> Self {
         self.split_states.push(state);
@@ -0,0 +1,189 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::time::Duration;
+
+use quickwit_common::pubsub::EventBroker;
+use quickwit_common::uri::Uri;
+use quickwit_proto::metastore::events::{IndexChangeEvent, IndexChangeType};
+use quickwit_proto::metastore::{MetastoreError, MetastoreResult};
+use quickwit_proto::types::IndexUid;
+use serde::Deserialize;
+use sqlx::postgres::PgListener;
+use tracing::{debug, error, info, warn};
+
+/// Channel on which the `notify_index_change` trigger notifies the index changes. See migration
+/// `15_notify-index-changes`.
+const INDEX_CHANGES_CHANNEL: &str = "quickwit_index_changes";
+
+/// Payload of the notifications sent by the `notify_index_change` trigger.
+#[derive(Debug, Deserialize)]
+struct IndexChangeNotification {
+    index_uid: IndexUid,
+    change_type: IndexChangeType,
+}
+
+impl From<IndexChangeNotification> for IndexChangeEvent {
+    fn from(notification: IndexChangeNotification) -> Self {
+        Self {
+            index_uid: notification.index_uid,
+            change_type: notification.change_type,
+        }
+    }
+}
+
+fn parse_index_change_notification(payload: &str) -> serde_json::Result<IndexChangeEvent> {
+    serde_json::from_str::<IndexChangeNotification>(payload).map(IndexChangeEvent::from)
+}
+
+/// Opens a dedicated connection to the database, listens to the index changes, and publishes them
+/// on the event broker until the process exits. The listener reconnects automatically if the
+/// connection is lost, but the changes made in the meantime are not notified.
+pub(crate) async fn spawn_index_changes_listener(
+    connection_uri: &Uri,
+    event_broker: EventBroker,
+) -> MetastoreResult<()> {
+    let mut listener = PgListener::connect(connection_uri.as_str())
+        .await
+        .map_err(|error| MetastoreError::Connection {
+            message: error.to_string(),
+        })?;
+    listener.listen(INDEX_CHANGES_CHANNEL).await?;
+
+    info!("listening to index changes notified by the PostgreSQL metastore");
+
+    tokio::spawn(async move {
+        loop {
+            match listener.try_recv().await {
+                Ok(Some(notification)) => {
+                    let payload = notification.payload();
+
+                    match parse_index_change_notification(payload) {
+                        Ok(index_change_event) => {
+                            debug!(
+                                index_uid=%index_change_event.index_uid,
+                                change_type=?index_change_event.change_type,
+                                "received index change notification"
+                            );
+                            event_broker.publish(index_change_event);
+                        }
+                        Err(error) => {
+                            error!(%error, payload, "failed to parse index change notification");
+                        }
+                    }
+                }
+                Ok(None) => {
+                    warn!(
+                        "lost connection to PostgreSQL metastore, index changes made until \
+                         reconnection will not be notified"
+                    );
+                }
+                Err(error) => {
+                    error!(%error, "failed to receive index change notification");
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_common::rand::append_random_suffix;
+    use quickwit_config::IndexConfig;
+    use quickwit_proto::metastore::{CreateIndexRequest, DeleteIndexRequest, MetastoreService};
+
+    use super::*;
+    use crate::tests::DefaultForTest;
+    use crate::{CreateIndexRequestExt, PostgresqlMetastore};
+
+    #[test]
+    fn test_parse_index_change_notification() {
+        let payload =
+            r#"{"change_type": "created", "index_uid": "test-index:00000000000000000000000000"}"#;
+        let index_change_event = parse_index_change_notification(payload).unwrap();
+        assert_eq!(
+            index_change_event,
+            IndexChangeEvent {
+                index_uid: "test-index:00000000000000000000000000".into(),
+                change_type: IndexChangeType::Created,
+            }
+        );
+        let payload =
+            r#"{"change_type": "deleted", "index_uid": "test-index:00000000000000000000000000"}"#;
+        let index_change_event = parse_index_change_notification(payload).unwrap();
+        assert_eq!(index_change_event.change_type, IndexChangeType::Deleted);
+
+        let payload =
+            r#"{"change_type": "renamed", "index_uid": "test-index:00000000000000000000000000"}"#;
+        parse_index_change_notification(payload).unwrap_err();
+    }
+
+    #[tokio::test]
+    async fn test_index_changes_listener() {
+        let mut metastore = PostgresqlMetastore::default_for_test().await;
+        let connection_uri: Uri = std::env::var("QW_TEST_DATABASE_URL")
+            .unwrap()
+            .parse()
+            .unwrap();
+        let event_broker = EventBroker::default();
+        let (event_tx, mut event_rx) = tokio::sync::mpsc::unbounded_channel();
+        event_broker
+            .subscribe(move |event: IndexChangeEvent| {
+                let _ = event_tx.send(event);
+            })
+            .forever();
+        spawn_index_changes_listener(&connection_uri, event_broker)
+            .await
+            .unwrap();
+
+        let index_id = append_random_suffix("test-index-changes-listener");
+        let index_uri = format!("ram:///indexes/{index_id}");
+        let index_config = IndexConfig::for_test(&index_id, &index_uri);
+        let create_index_request = CreateIndexRequest::try_from_index_config(index_config).unwrap();
+        let index_uid: IndexUid = metastore
+            .create_index(create_index_request)
+            .await
+            .unwrap()
+            .index_uid
+            .into();
+        let delete_index_request = DeleteIndexRequest {
+            index_uid: index_uid.to_string(),
+        };
+        metastore.delete_index(delete_index_request).await.unwrap();
+
+        // The database may be shared with other tests running concurrently.
+        let mut change_types = Vec::new();
+
+        while change_types.len() < 2 {
+            let event = tokio::time::timeout(Duration::from_secs(10), event_rx.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            if event.index_uid == index_uid {
+                change_types.push(event.change_type);
+            }
+        }
+        assert_eq!(
+            change_types,
+            [IndexChangeType::Created, IndexChangeType::Deleted]
+        );
+    }
+}
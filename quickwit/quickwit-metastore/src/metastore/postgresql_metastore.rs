@@ -28,6 +28,7 @@ use async_trait::async_trait;
 use futures::stream::BoxStream;
 use futures::StreamExt;
 use ouroboros::self_referencing;
+use quickwit_common::pubsub::EventBroker;
 use quickwit_common::uri::Uri;
 use quickwit_common::{PrettySample, ServiceStream};
 use quickwit_config::{
@@ -35,17 +36,20 @@ use quickwit_config::{
 };
 use quickwit_doc_mapper::tag_pruning::TagFilterAst;
 use quickwit_proto::metastore::{
-    AcquireShardsRequest, AcquireShardsResponse, AddSourceRequest, CreateIndexRequest,
-    CreateIndexResponse, DeleteIndexRequest, DeleteQuery, DeleteShardsRequest,
+    AcquireShardsRequest, AcquireShardsResponse, AddIndexEventRequest, AddSourceRequest,
+    CreateIndexRequest, CreateIndexResponse, DeleteIndexRequest, DeleteQuery, DeleteShardsRequest,
     DeleteShardsResponse, DeleteSourceRequest, DeleteSplitsRequest, DeleteTask, EmptyResponse,
-    EntityKind, IndexMetadataRequest, IndexMetadataResponse, LastDeleteOpstampRequest,
+    EntityKind, FreezeIndexRequest, IndexConfigVersion, IndexEvent, IndexEventType,
+    IndexMetadataRequest, IndexMetadataResponse, LastDeleteOpstampRequest,
     LastDeleteOpstampResponse, ListDeleteTasksRequest, ListDeleteTasksResponse,
-    ListIndexesMetadataRequest, ListIndexesMetadataResponse, ListShardsRequest, ListShardsResponse,
-    ListSplitsRequest, ListSplitsResponse, ListStaleSplitsRequest, MarkSplitsForDeletionRequest,
-    MetastoreError, MetastoreResult, MetastoreService, MetastoreServiceClient,
-    MetastoreServiceStream, OpenShardsRequest, OpenShardsResponse, PublishSplitsRequest,
-    ResetSourceCheckpointRequest, StageSplitsRequest, ToggleSourceRequest,
-    UpdateSplitsDeleteOpstampRequest, UpdateSplitsDeleteOpstampResponse,
+    ListIndexConfigVersionsRequest, ListIndexConfigVersionsResponse, ListIndexEventsRequest,
+    ListIndexEventsResponse, ListIndexesMetadataRequest, ListIndexesMetadataResponse,
+    ListShardsRequest, ListShardsResponse, ListSplitsRequest, ListSplitsResponse,
+    ListStaleSplitsRequest, MarkSplitsForDeletionRequest, MetastoreError, MetastoreResult,
+    MetastoreService, MetastoreServiceClient, MetastoreServiceStream, OpenShardsRequest,
+    OpenShardsResponse, PublishSplitsRequest, ResetSourceCheckpointRequest,
+    RollbackIndexConfigRequest, SetMergesPausedRequest, StageSplitsRequest, ToggleSourceRequest,
+    UpdateIndexRequest, UpdateSplitsDeleteOpstampRequest, UpdateSplitsDeleteOpstampResponse,
 };
 use quickwit_proto::types::IndexUid;
 use sea_query::{
@@ -60,15 +64,19 @@ use tokio_stream::Stream;
 use tracing::log::LevelFilter;
 use tracing::{debug, error, info, instrument, warn};
 
-use super::STREAM_SPLITS_CHUNK_SIZE;
+use super::{MAX_NUM_INDEX_CONFIG_VERSIONS, MAX_NUM_INDEX_EVENTS, STREAM_SPLITS_CHUNK_SIZE};
 use crate::checkpoint::IndexCheckpointDelta;
-use crate::metastore::postgresql_model::{PgDeleteTask, PgIndex, PgSplit, Splits, ToTimestampFunc};
+use crate::metastore::postgresql_listener::spawn_index_changes_listener;
+use crate::metastore::postgresql_model::{
+    PgDeleteTask, PgIndex, PgIndexConfigVersion, PgIndexEvent, PgSplit, Splits, ToTimestampFunc,
+};
 use crate::metastore::{instrument_metastore, FilterRange, PublishSplitsRequestExt};
 use crate::{
-    AddSourceRequestExt, CreateIndexRequestExt, IndexMetadata, IndexMetadataResponseExt,
-    ListIndexesMetadataResponseExt, ListSplitsQuery, ListSplitsRequestExt, ListSplitsResponseExt,
-    MetastoreFactory, MetastoreResolverError, MetastoreServiceExt, Split, SplitMaturity,
-    SplitMetadata, SplitState, StageSplitsRequestExt,
+    AddSourceRequestExt, CreateIndexRequestExt, IndexConfigVersionExt, IndexMetadata,
+    IndexMetadataResponseExt, ListIndexesMetadataResponseExt, ListSplitsQuery,
+    ListSplitsRequestExt, ListSplitsResponseExt, MetastoreFactory, MetastoreResolverError,
+    MetastoreServiceExt, Split, SplitMaturity, SplitMetadata, SplitState, StageSplitsRequestExt,
+    UpdateIndexRequestExt,
 };
 
 static MIGRATOR: Migrator = sqlx::migrate!("migrations/postgresql");
@@ -264,6 +272,10 @@ fn append_query_filters(sql: &mut SelectStatement, query: &ListSplitsQuery) {
         });
     sql.cond_where(or_condition);
 
+    if !query.split_ids.is_empty() {
+        sql.cond_where(Expr::col(Splits::SplitId).is_in(query.split_ids.iter().cloned()));
+    };
+
     if !query.split_states.is_empty() {
         sql.cond_where(
             Expr::col(Splits::SplitState)
@@ -474,6 +486,99 @@ where
     Ok(mutation_occurred)
 }
 
+/// Records an event in the activity feed of an index and discards the oldest events past
+/// [`MAX_NUM_INDEX_EVENTS`].
+async fn insert_index_event(
+    tx: &mut Transaction<'_, Postgres>,
+    index_uid: &IndexUid,
+    event_type: &str,
+    message: &str,
+) -> MetastoreResult<()> {
+    sqlx::query("INSERT INTO index_events (index_uid, event_type, message) VALUES ($1, $2, $3)")
+        .bind(index_uid.to_string())
+        .bind(event_type)
+        .bind(message)
+        .execute(tx.as_mut())
+        .await
+        .map_err(|error| convert_sqlx_err(index_uid.index_id(), error))?;
+    sqlx::query(
+        r#"
+        DELETE FROM index_events
+        WHERE
+            index_uid = $1
+            AND event_id NOT IN (
+                SELECT event_id
+                FROM index_events
+                WHERE index_uid = $1
+                ORDER BY event_id DESC
+                LIMIT $2
+            )
+        "#,
+    )
+    .bind(index_uid.to_string())
+    .bind(MAX_NUM_INDEX_EVENTS as i64)
+    .execute(tx.as_mut())
+    .await?;
+    Ok(())
+}
+
+/// Records the current index and source configs of an index as a new config version and discards
+/// the oldest versions past [`MAX_NUM_INDEX_CONFIG_VERSIONS`].
+async fn insert_index_config_version(
+    tx: &mut Transaction<'_, Postgres>,
+    index_metadata: &IndexMetadata,
+    description: &str,
+    author_opt: Option<String>,
+) -> MetastoreResult<()> {
+    // The version number is assigned below, within the transaction that mutated the index.
+    let config_version = IndexConfigVersion::try_from_index_metadata(
+        index_metadata,
+        0,
+        description.to_string(),
+        author_opt,
+    )?;
+    sqlx::query(
+        r#"
+        INSERT INTO index_config_versions (
+            index_uid,
+            version,
+            index_config_json,
+            source_configs_json,
+            description,
+            author
+        )
+        SELECT $1, COALESCE(MAX(version), 0) + 1, $2, $3, $4, $5
+        FROM index_config_versions
+        WHERE index_uid = $1
+        "#,
+    )
+    .bind(&config_version.index_uid)
+    .bind(&config_version.index_config_json)
+    .bind(&config_version.source_configs_json)
+    .bind(&config_version.description)
+    .bind(&config_version.author)
+    .execute(tx.as_mut())
+    .await
+    .map_err(|error| convert_sqlx_err(index_metadata.index_id(), error))?;
+    sqlx::query(
+        r#"
+        DELETE FROM index_config_versions
+        WHERE
+            index_uid = $1
+            AND version <= (
+                SELECT MAX(version)
+                FROM index_config_versions
+                WHERE index_uid = $1
+            ) - $2
+        "#,
+    )
+    .bind(&config_version.index_uid)
+    .bind(MAX_NUM_INDEX_CONFIG_VERSIONS as i64)
+    .execute(tx.as_mut())
+    .await?;
+    Ok(())
+}
+
 #[async_trait]
 impl MetastoreService for PostgresqlMetastore {
     async fn check_connectivity(&mut self) -> anyhow::Result<()> {
@@ -521,17 +626,31 @@ impl MetastoreService for PostgresqlMetastore {
                 message: error.to_string(),
             }
         })?;
-        sqlx::query(
-            "INSERT INTO indexes (index_uid, index_id, index_metadata_json) VALUES ($1, $2, $3)",
-        )
-        .bind(index_metadata.index_uid.to_string())
-        .bind(index_metadata.index_uid.index_id())
-        .bind(&index_metadata_json)
-        .execute(&self.connection_pool)
-        .await
-        .map_err(|error| convert_sqlx_err(index_metadata.index_id(), error))?;
+        let index_uid = run_with_tx!(self.connection_pool, tx, {
+            sqlx::query(
+                r#"
+                INSERT INTO indexes (index_uid, index_id, index_metadata_json)
+                VALUES ($1, $2, $3)
+                "#,
+            )
+            .bind(index_metadata.index_uid.to_string())
+            .bind(index_metadata.index_uid.index_id())
+            .bind(&index_metadata_json)
+            .execute(tx.as_mut())
+            .await
+            .map_err(|error| convert_sqlx_err(index_metadata.index_id(), error))?;
+            insert_index_event(
+                tx,
+                &index_metadata.index_uid,
+                IndexEventType::IndexCreated.as_str(),
+                "index created",
+            )
+            .await?;
+            insert_index_config_version(tx, &index_metadata, "index created", None).await?;
+            Ok(index_metadata.index_uid)
+        })?;
         Ok(CreateIndexResponse {
-            index_uid: index_metadata.index_uid.to_string(),
+            index_uid: index_uid.to_string(),
         })
     }
 
@@ -595,6 +714,10 @@ impl MetastoreService for PostgresqlMetastore {
         tracing::Span::current().record("split_ids", format!("{split_ids:?}"));
 
         run_with_tx!(self.connection_pool, tx, {
+            let index_metadata = index_metadata(tx, index_uid.index_id()).await?;
+            if index_metadata.index_uid == index_uid {
+                index_metadata.ensure_not_frozen()?;
+            }
             let upserted_split_ids: Vec<String> = sqlx::query_scalar(r#"
                 INSERT INTO splits
                     (split_id, time_range_start, time_range_end, tags, split_metadata_json, delete_opstamp, maturity_timestamp, split_state, index_uid)
@@ -611,7 +734,7 @@ impl MetastoreService for PostgresqlMetastore {
                 FROM
                     UNNEST($1, $2, $3, $4, $5, $6, $7)
                     as tr(split_id, time_range_start, time_range_end, tags_json, split_metadata_json, delete_opstamp, maturity_timestamp)
-                ON CONFLICT(split_id) DO UPDATE
+                ON CONFLICT(index_uid, split_id) DO UPDATE
                     SET
                         time_range_start = excluded.time_range_start,
                         time_range_end = excluded.time_range_end,
@@ -619,10 +742,9 @@ impl MetastoreService for PostgresqlMetastore {
                         split_metadata_json = excluded.split_metadata_json,
                         delete_opstamp = excluded.delete_opstamp,
                         maturity_timestamp = excluded.maturity_timestamp,
-                        index_uid = excluded.index_uid,
                         update_timestamp = CURRENT_TIMESTAMP,
                         create_timestamp = CURRENT_TIMESTAMP
-                    WHERE splits.split_id = excluded.split_id AND splits.split_state = 'Staged'
+                    WHERE splits.split_state = 'Staged'
                 RETURNING split_id;
                 "#)
                 .bind(&split_ids)
@@ -1050,22 +1172,146 @@ impl MetastoreService for PostgresqlMetastore {
     #[instrument(skip(self))]
     async fn add_source(&mut self, request: AddSourceRequest) -> MetastoreResult<EmptyResponse> {
         let source_config = request.deserialize_source_config()?;
+        let source_id = source_config.source_id.clone();
         let index_uid: IndexUid = request.index_uid.into();
         run_with_tx!(self.connection_pool, tx, {
+            let mut index_metadata_opt: Option<IndexMetadata> = None;
             mutate_index_metadata::<MetastoreError, _>(
                 tx,
-                index_uid,
+                index_uid.clone(),
                 |index_metadata: &mut IndexMetadata| {
                     index_metadata.add_source(source_config)?;
+                    index_metadata_opt = Some(index_metadata.clone());
                     Ok(true)
                 },
             )
             .await?;
+            let description = format!("source `{source_id}` added");
+            insert_index_event(
+                tx,
+                &index_uid,
+                IndexEventType::SourceAdded.as_str(),
+                &description,
+            )
+            .await?;
+            let index_metadata =
+                index_metadata_opt.expect("index metadata should have been mutated");
+            insert_index_config_version(tx, &index_metadata, &description, None).await?;
             Ok(())
         })?;
         Ok(EmptyResponse {})
     }
 
+    #[instrument(skip(self))]
+    async fn update_index(
+        &mut self,
+        request: UpdateIndexRequest,
+    ) -> MetastoreResult<IndexMetadataResponse> {
+        let doc_mapping = request.deserialize_doc_mapping()?;
+        let index_uid: IndexUid = request.index_uid.into();
+        let author_opt = request.author;
+        let index_metadata = run_with_tx!(self.connection_pool, tx, {
+            let mut index_metadata_opt: Option<IndexMetadata> = None;
+            let mutation_occurred =
+                mutate_index_metadata(tx, index_uid.clone(), |index_metadata| {
+                    let mutation_occurred = index_metadata.update_doc_mapping(doc_mapping)?;
+                    index_metadata_opt = Some(index_metadata.clone());
+                    Ok::<_, MetastoreError>(mutation_occurred)
+                })
+                .await?;
+            if mutation_occurred {
+                insert_index_event(
+                    tx,
+                    &index_uid,
+                    IndexEventType::IndexUpdated.as_str(),
+                    "doc mapping updated",
+                )
+                .await?;
+            }
+            let index_metadata =
+                index_metadata_opt.expect("index metadata should have been mutated");
+            if mutation_occurred {
+                insert_index_config_version(tx, &index_metadata, "doc mapping updated", author_opt)
+                    .await?;
+            }
+            Ok(index_metadata)
+        })?;
+        let response = IndexMetadataResponse::try_from_index_metadata(index_metadata)?;
+        Ok(response)
+    }
+
+    #[instrument(skip(self))]
+    async fn freeze_index(
+        &mut self,
+        request: FreezeIndexRequest,
+    ) -> MetastoreResult<IndexMetadataResponse> {
+        let index_uid: IndexUid = request.index_uid.into();
+        let index_metadata = run_with_tx!(self.connection_pool, tx, {
+            let mut index_metadata_opt: Option<IndexMetadata> = None;
+            let mutation_occurred =
+                mutate_index_metadata(tx, index_uid.clone(), |index_metadata| {
+                    let mutation_occurred = index_metadata.freeze();
+                    index_metadata_opt = Some(index_metadata.clone());
+                    Ok::<_, MetastoreError>(mutation_occurred)
+                })
+                .await?;
+            if mutation_occurred {
+                insert_index_event(
+                    tx,
+                    &index_uid,
+                    IndexEventType::IndexFrozen.as_str(),
+                    "index frozen",
+                )
+                .await?;
+            }
+            let index_metadata =
+                index_metadata_opt.expect("index metadata should have been mutated");
+            if mutation_occurred {
+                insert_index_config_version(tx, &index_metadata, "index frozen", None).await?;
+            }
+            Ok(index_metadata)
+        })?;
+        let response = IndexMetadataResponse::try_from_index_metadata(index_metadata)?;
+        Ok(response)
+    }
+
+    #[instrument(skip(self))]
+    async fn set_merges_paused(
+        &mut self,
+        request: SetMergesPausedRequest,
+    ) -> MetastoreResult<IndexMetadataResponse> {
+        let index_uid: IndexUid = request.index_uid.into();
+        let index_metadata = run_with_tx!(self.connection_pool, tx, {
+            let mut index_metadata_opt: Option<IndexMetadata> = None;
+            let mutation_occurred =
+                mutate_index_metadata(tx, index_uid.clone(), |index_metadata| {
+                    let mutation_occurred = index_metadata.set_merges_paused(request.merges_paused);
+                    index_metadata_opt = Some(index_metadata.clone());
+                    Ok::<_, MetastoreError>(mutation_occurred)
+                })
+                .await?;
+            if mutation_occurred {
+                let message = if request.merges_paused {
+                    "merges paused"
+                } else {
+                    "merges resumed"
+                };
+                insert_index_event(
+                    tx,
+                    &index_uid,
+                    IndexEventType::IndexUpdated.as_str(),
+                    message,
+                )
+                .await?;
+            }
+            let index_metadata =
+                index_metadata_opt.expect("index metadata should have been mutated");
+            Ok(index_metadata)
+        })?;
+        let response = IndexMetadataResponse::try_from_index_metadata(index_metadata)?;
+        Ok(response)
+    }
+
     #[instrument(skip(self))]
     async fn toggle_source(
         &mut self,
@@ -1073,10 +1319,28 @@ impl MetastoreService for PostgresqlMetastore {
     ) -> MetastoreResult<EmptyResponse> {
         let index_uid: IndexUid = request.index_uid.into();
         run_with_tx!(self.connection_pool, tx, {
-            mutate_index_metadata(tx, index_uid, |index_metadata| {
-                index_metadata.toggle_source(&request.source_id, request.enable)
-            })
-            .await?;
+            let mut index_metadata_opt: Option<IndexMetadata> = None;
+            let mutation_occurred =
+                mutate_index_metadata(tx, index_uid.clone(), |index_metadata| {
+                    let mutation_occurred =
+                        index_metadata.toggle_source(&request.source_id, request.enable)?;
+                    index_metadata_opt = Some(index_metadata.clone());
+                    Ok::<_, MetastoreError>(mutation_occurred)
+                })
+                .await?;
+            if mutation_occurred {
+                let (event_type, action) = if request.enable {
+                    (IndexEventType::SourceEnabled, "enabled")
+                } else {
+                    (IndexEventType::SourceDisabled, "disabled")
+                };
+                let message = format!("source `{}` {action}", request.source_id);
+                insert_index_event(tx, &index_uid, event_type.as_str(), &message).await?;
+
+                let index_metadata =
+                    index_metadata_opt.expect("index metadata should have been mutated");
+                insert_index_config_version(tx, &index_metadata, &message, None).await?;
+            }
             Ok(())
         })?;
         Ok(EmptyResponse {})
@@ -1089,10 +1353,27 @@ impl MetastoreService for PostgresqlMetastore {
     ) -> MetastoreResult<EmptyResponse> {
         let index_uid: IndexUid = request.index_uid.into();
         run_with_tx!(self.connection_pool, tx, {
-            mutate_index_metadata(tx, index_uid, |index_metadata| {
-                index_metadata.delete_source(&request.source_id)
-            })
-            .await?;
+            let mut index_metadata_opt: Option<IndexMetadata> = None;
+            let mutation_occurred =
+                mutate_index_metadata(tx, index_uid.clone(), |index_metadata| {
+                    let mutation_occurred = index_metadata.delete_source(&request.source_id)?;
+                    index_metadata_opt = Some(index_metadata.clone());
+                    Ok::<_, MetastoreError>(mutation_occurred)
+                })
+                .await?;
+            if mutation_occurred {
+                let message = format!("source `{}` deleted", request.source_id);
+                insert_index_event(
+                    tx,
+                    &index_uid,
+                    IndexEventType::SourceDeleted.as_str(),
+                    &message,
+                )
+                .await?;
+                let index_metadata =
+                    index_metadata_opt.expect("index metadata should have been mutated");
+                insert_index_config_version(tx, &index_metadata, &message, None).await?;
+            }
             Ok(())
         })?;
         Ok(EmptyResponse {})
@@ -1105,10 +1386,23 @@ impl MetastoreService for PostgresqlMetastore {
     ) -> MetastoreResult<EmptyResponse> {
         let index_uid: IndexUid = request.index_uid.into();
         run_with_tx!(self.connection_pool, tx, {
-            mutate_index_metadata(tx, index_uid, |index_metadata| {
-                Ok::<_, MetastoreError>(index_metadata.checkpoint.reset_source(&request.source_id))
-            })
-            .await?;
+            let mutation_occurred =
+                mutate_index_metadata(tx, index_uid.clone(), |index_metadata| {
+                    Ok::<_, MetastoreError>(
+                        index_metadata.checkpoint.reset_source(&request.source_id),
+                    )
+                })
+                .await?;
+            if mutation_occurred {
+                let message = format!("checkpoint of source `{}` reset", request.source_id);
+                insert_index_event(
+                    tx,
+                    &index_uid,
+                    IndexEventType::SourceCheckpointReset.as_str(),
+                    &message,
+                )
+                .await?;
+            }
             Ok(())
         })?;
         Ok(EmptyResponse {})
@@ -1149,23 +1443,33 @@ impl MetastoreService for PostgresqlMetastore {
                 message: error.to_string(),
             }
         })?;
-        let (create_timestamp, opstamp): (sqlx::types::time::PrimitiveDateTime, i64) =
-            sqlx::query_as(
-                r#"
-            INSERT INTO delete_tasks (index_uid, delete_query_json) VALUES ($1, $2)
-            RETURNING create_timestamp, opstamp
-            "#,
-            )
-            .bind(delete_query.index_uid.to_string())
-            .bind(&delete_query_json)
-            .fetch_one(&self.connection_pool)
-            .await
-            .map_err(|error| {
-                convert_sqlx_err(
-                    IndexUid::from(delete_query.index_uid.to_string()).index_id(),
-                    error,
+        let index_uid = IndexUid::from(delete_query.index_uid.clone());
+        let (create_timestamp, opstamp) = run_with_tx!(self.connection_pool, tx, {
+            let index_metadata = index_metadata(tx, index_uid.index_id()).await?;
+            if index_metadata.index_uid == index_uid {
+                index_metadata.ensure_not_frozen()?;
+            }
+            let (create_timestamp, opstamp): (sqlx::types::time::PrimitiveDateTime, i64) =
+                sqlx::query_as(
+                    r#"
+                INSERT INTO delete_tasks (index_uid, delete_query_json) VALUES ($1, $2)
+                RETURNING create_timestamp, opstamp
+                "#,
                 )
-            })?;
+                .bind(index_uid.to_string())
+                .bind(&delete_query_json)
+                .fetch_one(tx.as_mut())
+                .await
+                .map_err(|error| convert_sqlx_err(index_uid.index_id(), error))?;
+            insert_index_event(
+                tx,
+                &index_uid,
+                IndexEventType::DeleteTaskCreated.as_str(),
+                &format!("delete task with opstamp {opstamp} created"),
+            )
+            .await?;
+            Ok((create_timestamp, opstamp))
+        })?;
 
         Ok(DeleteTask {
             create_timestamp: create_timestamp.assume_utc().unix_timestamp(),
@@ -1245,6 +1549,136 @@ impl MetastoreService for PostgresqlMetastore {
         Ok(ListDeleteTasksResponse { delete_tasks })
     }
 
+    #[instrument(skip(self))]
+    async fn add_index_event(
+        &mut self,
+        request: AddIndexEventRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        let index_uid: IndexUid = request.index_uid.into();
+        run_with_tx!(self.connection_pool, tx, {
+            insert_index_event(tx, &index_uid, &request.event_type, &request.message).await
+        })?;
+        Ok(EmptyResponse {})
+    }
+
+    #[instrument(skip(self))]
+    async fn list_index_events(
+        &mut self,
+        request: ListIndexEventsRequest,
+    ) -> MetastoreResult<ListIndexEventsResponse> {
+        let index_uid: IndexUid = request.index_uid.into();
+        // `LIMIT NULL` is equivalent to omitting the `LIMIT` clause.
+        let limit_opt: Option<i64> = request.limit.map(|limit| limit as i64);
+        let pg_index_events: Vec<PgIndexEvent> = sqlx::query_as::<_, PgIndexEvent>(
+            r#"
+                SELECT *
+                FROM index_events
+                WHERE index_uid = $1
+                ORDER BY event_id DESC
+                LIMIT $2
+                "#,
+        )
+        .bind(index_uid.to_string())
+        .bind(limit_opt)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        let events = pg_index_events.into_iter().map(IndexEvent::from).collect();
+        Ok(ListIndexEventsResponse { events })
+    }
+
+    #[instrument(skip(self))]
+    async fn list_index_config_versions(
+        &mut self,
+        request: ListIndexConfigVersionsRequest,
+    ) -> MetastoreResult<ListIndexConfigVersionsResponse> {
+        let index_uid: IndexUid = request.index_uid.into();
+        // `LIMIT NULL` is equivalent to omitting the `LIMIT` clause.
+        let limit_opt: Option<i64> = request.limit.map(|limit| limit as i64);
+        let pg_index_config_versions: Vec<PgIndexConfigVersion> =
+            sqlx::query_as::<_, PgIndexConfigVersion>(
+                r#"
+                SELECT *
+                FROM index_config_versions
+                WHERE index_uid = $1
+                ORDER BY version DESC
+                LIMIT $2
+                "#,
+            )
+            .bind(index_uid.to_string())
+            .bind(limit_opt)
+            .fetch_all(&self.connection_pool)
+            .await?;
+        let versions = pg_index_config_versions
+            .into_iter()
+            .map(IndexConfigVersion::from)
+            .collect();
+        Ok(ListIndexConfigVersionsResponse { versions })
+    }
+
+    #[instrument(skip(self))]
+    async fn rollback_index_config(
+        &mut self,
+        request: RollbackIndexConfigRequest,
+    ) -> MetastoreResult<IndexMetadataResponse> {
+        let index_uid: IndexUid = request.index_uid.into();
+        let version = request.version;
+        let author_opt = request.author;
+        let index_metadata = run_with_tx!(self.connection_pool, tx, {
+            let pg_index_config_version_opt: Option<PgIndexConfigVersion> =
+                sqlx::query_as::<_, PgIndexConfigVersion>(
+                    r#"
+                    SELECT *
+                    FROM index_config_versions
+                    WHERE index_uid = $1 AND version = $2
+                    "#,
+                )
+                .bind(index_uid.to_string())
+                .bind(version as i64)
+                .fetch_optional(tx.as_mut())
+                .await?;
+            let doc_mapping_opt = match pg_index_config_version_opt {
+                Some(pg_index_config_version) => Some(
+                    IndexConfigVersion::from(pg_index_config_version)
+                        .deserialize_index_config()?
+                        .doc_mapping,
+                ),
+                None => None,
+            };
+            let mut index_metadata_opt: Option<IndexMetadata> = None;
+            let mutation_occurred =
+                mutate_index_metadata(tx, index_uid.clone(), |index_metadata| {
+                    let Some(doc_mapping) = doc_mapping_opt else {
+                        let message = format!(
+                            "config version {version} of index `{}` does not exist or has been \
+                             discarded",
+                            index_metadata.index_id()
+                        );
+                        return Err(MetastoreError::InvalidArgument { message });
+                    };
+                    let mutation_occurred = index_metadata.rollback_doc_mapping(doc_mapping)?;
+                    index_metadata_opt = Some(index_metadata.clone());
+                    Ok(mutation_occurred)
+                })
+                .await?;
+            let index_metadata =
+                index_metadata_opt.expect("index metadata should have been mutated");
+            if mutation_occurred {
+                let description = format!("doc mapping rolled back to version {version}");
+                insert_index_event(
+                    tx,
+                    &index_uid,
+                    IndexEventType::IndexUpdated.as_str(),
+                    &description,
+                )
+                .await?;
+                insert_index_config_version(tx, &index_metadata, &description, author_opt).await?;
+            }
+            Ok(index_metadata)
+        })?;
+        let response = IndexMetadataResponse::try_from_index_metadata(index_metadata)?;
+        Ok(response)
+    }
+
     /// Returns `num_splits` published splits with `split.delete_opstamp` < `delete_opstamp`.
     /// Results are ordered by ascending `split.delete_opstamp` and `split.publish_timestamp`
     /// values.
@@ -1494,6 +1928,28 @@ impl MetastoreFactory for PostgresqlMetastoreFactory {
             .await;
         Ok(unique_metastore_for_uri)
     }
+
+    async fn listen_index_changes(
+        &self,
+        metastore_config: &MetastoreConfig,
+        uri: &Uri,
+        event_broker: EventBroker,
+    ) -> Result<bool, MetastoreResolverError> {
+        let postgresql_metastore_config = metastore_config.as_postgres().ok_or_else(|| {
+            let message = format!(
+                "expected PostgreSQL metastore config, got `{:?}`",
+                metastore_config.backend()
+            );
+            MetastoreResolverError::InvalidConfig(message)
+        })?;
+        if !postgresql_metastore_config.enable_change_notifications {
+            return Ok(false);
+        }
+        spawn_index_changes_listener(uri, event_broker)
+            .await
+            .map_err(MetastoreResolverError::Initialization)?;
+        Ok(true)
+    }
 }
 
 #[cfg(test)]
@@ -1670,6 +2126,20 @@ mod tests {
         let mut select_statement = Query::select();
         let sql = select_statement.column(Asterisk).from(Splits::Table);
 
+        let query = ListSplitsQuery::for_index(index_uid.clone())
+            .with_split_ids(["split-1".to_string(), "split-2".to_string()]);
+        append_query_filters(sql, &query);
+
+        assert_eq!(
+            sql.to_string(PostgresQueryBuilder),
+            format!(
+                r#"SELECT * FROM "splits" WHERE "index_uid" = '{index_uid}' AND "split_id" IN ('split-1', 'split-2')"#
+            )
+        );
+
+        let mut select_statement = Query::select();
+        let sql = select_statement.column(Asterisk).from(Splits::Table);
+
         let query = ListSplitsQuery::for_index(index_uid.clone())
             .with_split_states([SplitState::Published, SplitState::MarkedForDeletion]);
         append_query_filters(sql, &query);
@@ -20,7 +20,9 @@
 use std::convert::TryInto;
 use std::str::FromStr;
 
-use quickwit_proto::metastore::{DeleteQuery, DeleteTask, MetastoreError, MetastoreResult};
+use quickwit_proto::metastore::{
+    DeleteQuery, DeleteTask, IndexConfigVersion, IndexEvent, MetastoreError, MetastoreResult,
+};
 use quickwit_proto::types::IndexUid;
 use sea_query::{Iden, Write};
 use tracing::error;
@@ -210,3 +212,68 @@ impl TryInto<DeleteTask> for PgDeleteTask {
         })
     }
 }
+
+/// A model structure for handling the events of an index in a database.
+#[derive(sqlx::FromRow)]
+pub struct PgIndexEvent {
+    /// Monotonic increasing unique event ID.
+    pub event_id: i64,
+    /// Index UID.
+    pub index_uid: String,
+    /// Type of the event.
+    pub event_type: String,
+    /// Human-readable description of the event.
+    pub message: String,
+    /// Create timestamp.
+    pub create_timestamp: sqlx::types::time::PrimitiveDateTime,
+}
+
+impl From<PgIndexEvent> for IndexEvent {
+    fn from(pg_index_event: PgIndexEvent) -> Self {
+        IndexEvent {
+            index_uid: pg_index_event.index_uid,
+            event_type: pg_index_event.event_type,
+            message: pg_index_event.message,
+            timestamp: pg_index_event
+                .create_timestamp
+                .assume_utc()
+                .unix_timestamp(),
+        }
+    }
+}
+
+/// A model structure for handling the config versions of an index in a database.
+#[derive(sqlx::FromRow)]
+pub struct PgIndexConfigVersion {
+    /// Index UID.
+    pub index_uid: String,
+    /// Version number, starting at 1 and incremented on every config change.
+    pub version: i64,
+    /// Serialized index config.
+    pub index_config_json: String,
+    /// Serialized source configs.
+    pub source_configs_json: String,
+    /// Human-readable description of the change.
+    pub description: String,
+    /// Author of the change, if known.
+    pub author: Option<String>,
+    /// Create timestamp.
+    pub create_timestamp: sqlx::types::time::PrimitiveDateTime,
+}
+
+impl From<PgIndexConfigVersion> for IndexConfigVersion {
+    fn from(pg_index_config_version: PgIndexConfigVersion) -> Self {
+        IndexConfigVersion {
+            index_uid: pg_index_config_version.index_uid,
+            version: pg_index_config_version.version as u64,
+            index_config_json: pg_index_config_version.index_config_json,
+            source_configs_json: pg_index_config_version.source_configs_json,
+            description: pg_index_config_version.description,
+            author: pg_index_config_version.author,
+            timestamp: pg_index_config_version
+                .create_timestamp
+                .assume_utc()
+                .unix_timestamp(),
+        }
+    }
+}
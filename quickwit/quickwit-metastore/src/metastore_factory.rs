@@ -18,6 +18,7 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 use async_trait::async_trait;
+use quickwit_common::pubsub::EventBroker;
 use quickwit_common::uri::Uri;
 use quickwit_config::{MetastoreBackend, MetastoreConfig};
 use quickwit_proto::metastore::MetastoreServiceClient;
@@ -38,6 +39,19 @@ pub trait MetastoreFactory: Send + Sync + 'static {
         metastore_config: &MetastoreConfig,
         uri: &Uri,
     ) -> Result<MetastoreServiceClient, MetastoreResolverError>;
+
+    /// Starts publishing the index changes made to the metastore located at `uri` on the event
+    /// broker as [`IndexChangeEvent`](quickwit_proto::metastore::events::IndexChangeEvent)s.
+    /// Returns `false` if the backend does not support change notifications or if they are
+    /// disabled in the config.
+    async fn listen_index_changes(
+        &self,
+        _metastore_config: &MetastoreConfig,
+        _uri: &Uri,
+        _event_broker: EventBroker,
+    ) -> Result<bool, MetastoreResolverError> {
+        Ok(false)
+    }
 }
 
 /// A metastore factory for handling unsupported or unavailable metastore backends.
@@ -23,6 +23,7 @@ use std::sync::Arc;
 
 use anyhow::ensure;
 use once_cell::sync::Lazy;
+use quickwit_common::pubsub::EventBroker;
 use quickwit_common::uri::{Protocol, Uri};
 use quickwit_config::{MetastoreBackend, MetastoreConfig, MetastoreConfigs};
 use quickwit_proto::metastore::MetastoreServiceClient;
@@ -60,9 +61,30 @@ impl MetastoreResolver {
         &self,
         uri: &Uri,
     ) -> Result<MetastoreServiceClient, MetastoreResolverError> {
+        let (metastore_factory, metastore_config) = self.factory_and_config(uri)?;
+        let metastore = metastore_factory.resolve(metastore_config, uri).await?;
+        Ok(metastore)
+    }
+
+    /// Starts publishing the index changes made to the metastore located at `uri` on the event
+    /// broker. Returns `false` if the metastore backend does not support change notifications or
+    /// if they are disabled.
+    pub async fn listen_index_changes(
+        &self,
+        uri: &Uri,
+        event_broker: EventBroker,
+    ) -> Result<bool, MetastoreResolverError> {
+        let (metastore_factory, metastore_config) = self.factory_and_config(uri)?;
+        metastore_factory
+            .listen_index_changes(metastore_config, uri, event_broker)
+            .await
+    }
+
+    fn factory_and_config(&self, uri: &Uri) -> Result<&FactoryAndConfig, MetastoreResolverError> {
         let backend = match uri.protocol() {
             Protocol::Azure => MetastoreBackend::File,
             Protocol::File => MetastoreBackend::File,
+            Protocol::Hdfs => MetastoreBackend::File,
             Protocol::Ram => MetastoreBackend::File,
             Protocol::S3 => MetastoreBackend::File,
             Protocol::PostgreSQL => MetastoreBackend::PostgreSQL,
@@ -72,14 +94,11 @@ impl MetastoreResolver {
                 ))
             }
         };
-        let (metastore_factory, metastore_config) = self
-            .per_backend_factories
+        self.per_backend_factories
             .get(&backend)
             .ok_or(MetastoreResolverError::UnsupportedBackend(
                 "no metastore factory is registered for this backend".to_string(),
-            ))?;
-        let metastore = metastore_factory.resolve(metastore_config, uri).await?;
-        Ok(metastore)
+            ))
     }
 
     /// Creates and returns a [`MetastoreResolver`] holding the default configuration for each
@@ -189,6 +208,17 @@ mod tests {
         metastore_resolver.resolve(&metastore_uri).await.unwrap();
     }
 
+    #[tokio::test]
+    async fn test_metastore_resolver_listen_index_changes_unsupported_on_file() {
+        let metastore_resolver = MetastoreResolver::unconfigured();
+        let metastore_uri = Uri::for_test("ram:///metastore");
+        let is_listening = metastore_resolver
+            .listen_index_changes(&metastore_uri, EventBroker::default())
+            .await
+            .unwrap();
+        assert!(!is_listening);
+    }
+
     #[cfg(feature = "postgres")]
     #[tokio::test]
     async fn test_postgres_and_postgresql_protocol_accepted() {
@@ -17,7 +17,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
 use std::ops::{Range, RangeInclusive};
 use std::path::PathBuf;
@@ -25,6 +25,7 @@ use std::str::FromStr;
 use std::time::Duration;
 
 use bytesize::ByteSize;
+use quickwit_config::{DocstoreCodec, IndexSortBy};
 use quickwit_proto::types::IndexUid;
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DurationMilliSeconds};
@@ -132,6 +133,35 @@ pub struct SplitMetadata {
     /// Number of merge operations that was involved to create
     /// this split.
     pub num_merge_ops: usize,
+
+    /// Version of the index doc mapping this split was built with.
+    ///
+    /// Splits built with different doc mapping versions have different schemas and must not be
+    /// merged together.
+    pub doc_mapping_version: u64,
+
+    /// Codec used to compress the doc store of the split, if known.
+    ///
+    /// Splits created before the codec was recorded do not carry this information.
+    pub docstore_codec: Option<DocstoreCodec>,
+
+    /// Order in which the documents of the split are sorted, if they are.
+    ///
+    /// Leaf searches rely on it to stop scanning the split early, so it must only be set if the
+    /// split was actually built with index sorting.
+    pub sort_by: Option<IndexSortBy>,
+
+    /// ID of the data key the split files are encrypted with, if the index is encrypted.
+    pub encryption_key_id: Option<String>,
+
+    /// Statistics of the fields registered in the
+    /// [`DocMapping`](quickwit_config::DocMapping) `stats_fields` attribute, keyed by field
+    /// name. Fields without any value in the split have no entry.
+    pub field_stats: BTreeMap<String, FieldStats>,
+
+    /// Whether a doc IDs sidecar file was uploaded next to the split file. Only splits of
+    /// indexes with a [`DocMapping`](quickwit_config::DocMapping) `doc_id_field` have one.
+    pub has_doc_ids_sidecar: bool,
 }
 impl fmt::Debug for SplitMetadata {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -175,6 +205,22 @@ impl fmt::Debug for SplitMetadata {
         debug_struct.field("footer_offsets", &self.footer_offsets);
         debug_struct.field("delete_opstamp", &self.delete_opstamp);
         debug_struct.field("num_merge_ops", &self.num_merge_ops);
+        debug_struct.field("doc_mapping_version", &self.doc_mapping_version);
+        if let Some(docstore_codec) = &self.docstore_codec {
+            debug_struct.field("docstore_codec", docstore_codec);
+        }
+        if let Some(sort_by) = &self.sort_by {
+            debug_struct.field("sort_by", sort_by);
+        }
+        if let Some(encryption_key_id) = &self.encryption_key_id {
+            debug_struct.field("encryption_key_id", encryption_key_id);
+        }
+        if !self.field_stats.is_empty() {
+            debug_struct.field("field_stats", &self.field_stats);
+        }
+        if self.has_doc_ids_sidecar {
+            debug_struct.field("has_doc_ids_sidecar", &self.has_doc_ids_sidecar);
+        }
         debug_struct.finish()
     }
 }
@@ -240,6 +286,57 @@ impl SplitMetadata {
     }
 }
 
+/// Min and max values of a field over the documents of a split.
+///
+/// Datetime values are expressed in microseconds since the Unix epoch.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FieldValueRange {
+    /// Range of an `i64` field.
+    I64 {
+        /// Minimum value.
+        min: i64,
+        /// Maximum value.
+        max: i64,
+    },
+    /// Range of a `u64` field.
+    U64 {
+        /// Minimum value.
+        min: u64,
+        /// Maximum value.
+        max: u64,
+    },
+    /// Range of an `f64` field.
+    F64 {
+        /// Minimum value.
+        min: f64,
+        /// Maximum value.
+        max: f64,
+    },
+    /// Range of a `datetime` field.
+    Datetime {
+        /// Minimum value.
+        min: i64,
+        /// Maximum value.
+        max: i64,
+    },
+}
+
+/// Statistics of a fast field over the documents of a split, used to skip the splits that cannot
+/// match a range query on the field.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FieldStats {
+    /// Min and max values of the field.
+    #[serde(flatten)]
+    pub range: FieldValueRange,
+    /// Number of distinct values of the field. Only recorded for indexed fields.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cardinality: Option<u64>,
+}
+
+// Field stats are computed from indexed values, which are never NaN.
+impl Eq for FieldStats {}
+
 /// A summarized version of the split metadata for display purposes.
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct SplitInfo {
@@ -280,6 +377,12 @@ impl quickwit_config::TestableForRegression for SplitMetadata {
             tags: ["234".to_string(), "aaa".to_string()].into_iter().collect(),
             footer_offsets: 1000..2000,
             num_merge_ops: 3,
+            doc_mapping_version: 0,
+            docstore_codec: None,
+            sort_by: None,
+            encryption_key_id: None,
+            field_stats: BTreeMap::new(),
+            has_doc_ids_sidecar: false,
         }
     }
 
@@ -422,16 +525,20 @@ mod tests {
             footer_offsets: 0..1024,
             delete_opstamp: 0,
             num_merge_ops: 0,
+            doc_mapping_version: 0,
+            docstore_codec: None,
+            sort_by: None,
+            encryption_key_id: None,
         };
 
-        let expected_output = "SplitMetadata { split_id: \"split-1\", index_uid: \
-                               IndexUid(\"00000000-0000-0000-0000-000000000000:\
-                               00000000000000000000000000\"), partition_id: 0, source_id: \
-                               \"source-1\", node_id: \"node-1\", num_docs: 100, \
-                               uncompressed_docs_size_in_bytes: 1024, time_range: Some(0..=100), \
-                               create_timestamp: 1629867600, maturity: Mature, tags: \
-                               \"{\\\"🐱\\\", \\\"😻\\\", \\\"😼\\\", \\\"😿\\\", and 1 more}\", \
-                               footer_offsets: 0..1024, delete_opstamp: 0, num_merge_ops: 0 }";
+        let expected_output =
+            "SplitMetadata { split_id: \"split-1\", index_uid: \
+             IndexUid(\"00000000-0000-0000-0000-000000000000:00000000000000000000000000\"), \
+             partition_id: 0, source_id: \"source-1\", node_id: \"node-1\", num_docs: 100, \
+             uncompressed_docs_size_in_bytes: 1024, time_range: Some(0..=100), create_timestamp: \
+             1629867600, maturity: Mature, tags: \"{\\\"🐱\\\", \\\"😻\\\", \\\"😼\\\", \
+             \\\"😿\\\", and 1 more}\", footer_offsets: 0..1024, delete_opstamp: 0, \
+             num_merge_ops: 0, doc_mapping_version: 0 }";
 
         assert_eq!(format!("{:?}", split_metadata), expected_output);
     }
@@ -17,13 +17,14 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::ops::{Range, RangeInclusive};
 
+use quickwit_config::{DocstoreCodec, IndexSortBy};
 use quickwit_proto::types::IndexUid;
 use serde::{Deserialize, Serialize};
 
-use crate::split_metadata::{utc_now_timestamp, SplitMaturity};
+use crate::split_metadata::{utc_now_timestamp, FieldStats, SplitMaturity};
 use crate::SplitMetadata;
 
 #[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
@@ -91,6 +92,35 @@ pub(crate) struct SplitMetadataV0_6 {
 
     #[serde(default)]
     num_merge_ops: usize,
+
+    #[serde(default, skip_serializing_if = "is_zero")]
+    doc_mapping_version: u64,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    docstore_codec: Option<DocstoreCodec>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>)]
+    sort_by: Option<IndexSortBy>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    encryption_key_id: Option<String>,
+
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    #[schema(value_type = Object)]
+    field_stats: BTreeMap<String, FieldStats>,
+
+    #[serde(default, skip_serializing_if = "is_false")]
+    has_doc_ids_sidecar: bool,
+}
+
+fn is_zero(value: &u64) -> bool {
+    *value == 0
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
 }
 
 impl From<SplitMetadataV0_6> for SplitMetadata {
@@ -126,6 +156,12 @@ impl From<SplitMetadataV0_6> for SplitMetadata {
             tags: v6.tags,
             footer_offsets: v6.footer_offsets,
             num_merge_ops: v6.num_merge_ops,
+            doc_mapping_version: v6.doc_mapping_version,
+            docstore_codec: v6.docstore_codec,
+            sort_by: v6.sort_by,
+            encryption_key_id: v6.encryption_key_id,
+            field_stats: v6.field_stats,
+            has_doc_ids_sidecar: v6.has_doc_ids_sidecar,
         }
     }
 }
@@ -147,6 +183,12 @@ impl From<SplitMetadata> for SplitMetadataV0_6 {
             tags: split.tags,
             footer_offsets: split.footer_offsets,
             num_merge_ops: split.num_merge_ops,
+            doc_mapping_version: split.doc_mapping_version,
+            docstore_codec: split.docstore_codec,
+            sort_by: split.sort_by,
+            encryption_key_id: split.encryption_key_id,
+            field_stats: split.field_stats,
+            has_doc_ids_sidecar: split.has_doc_ids_sidecar,
         }
     }
 }
@@ -22,22 +22,34 @@
 //  - create_index
 //  - index_exists
 //  - index_metadata
+//  - update_index
+//  - freeze_index
+//  - set_merges_paused
+//  - add_index_event
+//  - list_index_events
+//  - list_index_config_versions
+//  - rollback_index_config
 //  - list_indexes
 //  - delete_index
 
 use quickwit_common::rand::append_random_suffix;
-use quickwit_config::IndexConfig;
+use quickwit_config::{IndexConfig, SourceConfig, SourceParams};
+use quickwit_doc_mapper::FieldMappingEntry;
 use quickwit_proto::metastore::{
-    CreateIndexRequest, DeleteIndexRequest, EntityKind, IndexMetadataRequest,
-    ListIndexesMetadataRequest, MetastoreError, MetastoreService, StageSplitsRequest,
+    AddIndexEventRequest, AddSourceRequest, CreateIndexRequest, DeleteIndexRequest, EntityKind,
+    FreezeIndexRequest, IndexEventType, IndexMetadataRequest, ListIndexConfigVersionsRequest,
+    ListIndexEventsRequest, ListIndexesMetadataRequest, MetastoreError, MetastoreService,
+    RollbackIndexConfigRequest, SetMergesPausedRequest, StageSplitsRequest, ToggleSourceRequest,
+    UpdateIndexRequest,
 };
 use quickwit_proto::types::IndexUid;
 
 use super::DefaultForTest;
 use crate::tests::cleanup_index;
 use crate::{
-    CreateIndexRequestExt, IndexMetadataResponseExt, ListIndexesMetadataResponseExt,
-    MetastoreServiceExt, SplitMetadata, StageSplitsRequestExt,
+    AddSourceRequestExt, CreateIndexRequestExt, IndexConfigVersionExt, IndexMetadataResponseExt,
+    ListIndexesMetadataResponseExt, MetastoreServiceExt, SplitMetadata, StageSplitsRequestExt,
+    UpdateIndexRequestExt,
 };
 
 pub async fn test_metastore_create_index<
@@ -169,6 +181,528 @@ pub async fn test_metastore_index_metadata<
     cleanup_index(&mut metastore, index_uid).await;
 }
 
+pub async fn test_metastore_update_index<MetastoreToTest: MetastoreServiceExt + DefaultForTest>() {
+    let mut metastore = MetastoreToTest::default_for_test().await;
+
+    let index_id = append_random_suffix("test-update-index");
+    let index_uri = format!("ram:///indexes/{index_id}");
+    let index_config = IndexConfig::for_test(&index_id, &index_uri);
+
+    let create_index_request =
+        CreateIndexRequest::try_from_index_config(index_config.clone()).unwrap();
+    let index_uid: IndexUid = metastore
+        .create_index(create_index_request)
+        .await
+        .unwrap()
+        .index_uid
+        .into();
+
+    let mut new_doc_mapping = index_config.doc_mapping.clone();
+    let new_field_mapping: FieldMappingEntry =
+        serde_json::from_str(r#"{"name": "severity", "type": "text", "tokenizer": "raw"}"#)
+            .unwrap();
+    new_doc_mapping.field_mappings.push(new_field_mapping);
+
+    let update_index_request =
+        UpdateIndexRequest::try_from_doc_mapping(index_uid.clone(), &new_doc_mapping).unwrap();
+    let index_metadata = metastore
+        .update_index(update_index_request.clone())
+        .await
+        .unwrap()
+        .deserialize_index_metadata()
+        .unwrap();
+    assert_eq!(index_metadata.doc_mapping_version, 1);
+    assert_eq!(index_metadata.index_config.doc_mapping, new_doc_mapping);
+
+    let index_metadata = metastore
+        .index_metadata(IndexMetadataRequest::for_index_id(index_id.to_string()))
+        .await
+        .unwrap()
+        .deserialize_index_metadata()
+        .unwrap();
+    assert_eq!(index_metadata.doc_mapping_version, 1);
+    assert_eq!(index_metadata.index_config.doc_mapping, new_doc_mapping);
+
+    // Applying the same doc mapping twice is a no-op.
+    let index_metadata = metastore
+        .update_index(update_index_request)
+        .await
+        .unwrap()
+        .deserialize_index_metadata()
+        .unwrap();
+    assert_eq!(index_metadata.doc_mapping_version, 1);
+
+    // Removing a field is rejected.
+    let update_index_request =
+        UpdateIndexRequest::try_from_doc_mapping(index_uid.clone(), &index_config.doc_mapping)
+            .unwrap();
+    let error = metastore
+        .update_index(update_index_request)
+        .await
+        .unwrap_err();
+    assert!(matches!(error, MetastoreError::InvalidArgument { .. }));
+
+    let update_index_request = UpdateIndexRequest::try_from_doc_mapping(
+        IndexUid::new_with_random_ulid(&index_id),
+        &new_doc_mapping,
+    )
+    .unwrap();
+    let error = metastore
+        .update_index(update_index_request)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        MetastoreError::NotFound(EntityKind::Index { .. })
+    ));
+
+    cleanup_index(&mut metastore, index_uid).await;
+}
+
+pub async fn test_metastore_freeze_index<MetastoreToTest: MetastoreServiceExt + DefaultForTest>() {
+    let mut metastore = MetastoreToTest::default_for_test().await;
+
+    let index_id = append_random_suffix("test-freeze-index");
+    let index_uri = format!("ram:///indexes/{index_id}");
+    let index_config = IndexConfig::for_test(&index_id, &index_uri);
+
+    let create_index_request = CreateIndexRequest::try_from_index_config(index_config).unwrap();
+    let index_uid: IndexUid = metastore
+        .create_index(create_index_request)
+        .await
+        .unwrap()
+        .index_uid
+        .into();
+
+    let source_id = format!("{index_id}--source");
+    let source_config = SourceConfig::for_test(&source_id, SourceParams::void());
+    let add_source_request =
+        AddSourceRequest::try_from_source_config(index_uid.clone(), source_config).unwrap();
+    metastore.add_source(add_source_request).await.unwrap();
+
+    let freeze_index_request = FreezeIndexRequest {
+        index_uid: index_uid.to_string(),
+    };
+    let index_metadata = metastore
+        .freeze_index(freeze_index_request.clone())
+        .await
+        .unwrap()
+        .deserialize_index_metadata()
+        .unwrap();
+    assert!(index_metadata.frozen);
+    assert!(!index_metadata.sources[&source_id].enabled);
+
+    let index_metadata = metastore
+        .index_metadata(IndexMetadataRequest::for_index_id(index_id.to_string()))
+        .await
+        .unwrap()
+        .deserialize_index_metadata()
+        .unwrap();
+    assert!(index_metadata.frozen);
+    assert!(!index_metadata.sources[&source_id].enabled);
+
+    // Freezing an index twice is a no-op.
+    let index_metadata = metastore
+        .freeze_index(freeze_index_request)
+        .await
+        .unwrap()
+        .deserialize_index_metadata()
+        .unwrap();
+    assert!(index_metadata.frozen);
+
+    // Writes are rejected.
+    let error = metastore
+        .toggle_source(ToggleSourceRequest {
+            index_uid: index_uid.to_string(),
+            source_id: source_id.clone(),
+            enable: true,
+        })
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        MetastoreError::FailedPrecondition {
+            entity: EntityKind::Index { .. },
+            ..
+        }
+    ));
+    metastore
+        .toggle_source(ToggleSourceRequest {
+            index_uid: index_uid.to_string(),
+            source_id: source_id.clone(),
+            enable: false,
+        })
+        .await
+        .unwrap();
+
+    let source_config = SourceConfig::for_test("other-source", SourceParams::void());
+    let add_source_request =
+        AddSourceRequest::try_from_source_config(index_uid.clone(), source_config).unwrap();
+    let error = metastore.add_source(add_source_request).await.unwrap_err();
+    assert!(matches!(
+        error,
+        MetastoreError::FailedPrecondition {
+            entity: EntityKind::Index { .. },
+            ..
+        }
+    ));
+
+    let split_metadata = SplitMetadata {
+        split_id: format!("{index_id}--split"),
+        index_uid: index_uid.clone(),
+        ..Default::default()
+    };
+    let stage_splits_request =
+        StageSplitsRequest::try_from_split_metadata(index_uid.clone(), split_metadata).unwrap();
+    let error = metastore
+        .stage_splits(stage_splits_request)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        MetastoreError::FailedPrecondition {
+            entity: EntityKind::Index { .. },
+            ..
+        }
+    ));
+
+    let error = metastore
+        .freeze_index(FreezeIndexRequest {
+            index_uid: IndexUid::new_with_random_ulid(&index_id).to_string(),
+        })
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        MetastoreError::NotFound(EntityKind::Index { .. })
+    ));
+
+    cleanup_index(&mut metastore, index_uid).await;
+}
+
+pub async fn test_metastore_set_merges_paused<
+    MetastoreToTest: MetastoreServiceExt + DefaultForTest,
+>() {
+    let mut metastore = MetastoreToTest::default_for_test().await;
+
+    let index_id = append_random_suffix("test-set-merges-paused");
+    let index_uri = format!("ram:///indexes/{index_id}");
+    let index_config = IndexConfig::for_test(&index_id, &index_uri);
+
+    let create_index_request = CreateIndexRequest::try_from_index_config(index_config).unwrap();
+    let index_uid: IndexUid = metastore
+        .create_index(create_index_request)
+        .await
+        .unwrap()
+        .index_uid
+        .into();
+
+    let index_metadata = metastore
+        .index_metadata(IndexMetadataRequest::for_index_id(index_id.to_string()))
+        .await
+        .unwrap()
+        .deserialize_index_metadata()
+        .unwrap();
+    assert!(!index_metadata.merges_paused);
+
+    let set_merges_paused_request = SetMergesPausedRequest {
+        index_uid: index_uid.to_string(),
+        merges_paused: true,
+    };
+    let index_metadata = metastore
+        .set_merges_paused(set_merges_paused_request.clone())
+        .await
+        .unwrap()
+        .deserialize_index_metadata()
+        .unwrap();
+    assert!(index_metadata.merges_paused);
+
+    let index_metadata = metastore
+        .index_metadata(IndexMetadataRequest::for_index_id(index_id.to_string()))
+        .await
+        .unwrap()
+        .deserialize_index_metadata()
+        .unwrap();
+    assert!(index_metadata.merges_paused);
+
+    // Pausing merges twice is a no-op.
+    let index_metadata = metastore
+        .set_merges_paused(set_merges_paused_request)
+        .await
+        .unwrap()
+        .deserialize_index_metadata()
+        .unwrap();
+    assert!(index_metadata.merges_paused);
+
+    let index_metadata = metastore
+        .set_merges_paused(SetMergesPausedRequest {
+            index_uid: index_uid.to_string(),
+            merges_paused: false,
+        })
+        .await
+        .unwrap()
+        .deserialize_index_metadata()
+        .unwrap();
+    assert!(!index_metadata.merges_paused);
+
+    let index_metadata = metastore
+        .index_metadata(IndexMetadataRequest::for_index_id(index_id.to_string()))
+        .await
+        .unwrap()
+        .deserialize_index_metadata()
+        .unwrap();
+    assert!(!index_metadata.merges_paused);
+
+    let error = metastore
+        .set_merges_paused(SetMergesPausedRequest {
+            index_uid: IndexUid::new_with_random_ulid(&index_id).to_string(),
+            merges_paused: true,
+        })
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        MetastoreError::NotFound(EntityKind::Index { .. })
+    ));
+
+    cleanup_index(&mut metastore, index_uid).await;
+}
+
+pub async fn test_metastore_index_events<MetastoreToTest: MetastoreServiceExt + DefaultForTest>() {
+    let mut metastore = MetastoreToTest::default_for_test().await;
+
+    let index_id = append_random_suffix("test-index-events");
+    let index_uri = format!("ram:///indexes/{index_id}");
+    let index_config = IndexConfig::for_test(&index_id, &index_uri);
+
+    let create_index_request = CreateIndexRequest::try_from_index_config(index_config).unwrap();
+    let index_uid: IndexUid = metastore
+        .create_index(create_index_request)
+        .await
+        .unwrap()
+        .index_uid
+        .into();
+
+    let source_id = format!("{index_id}--source");
+    let source_config = SourceConfig::for_test(&source_id, SourceParams::void());
+    let add_source_request =
+        AddSourceRequest::try_from_source_config(index_uid.clone(), source_config).unwrap();
+    metastore.add_source(add_source_request).await.unwrap();
+
+    metastore
+        .toggle_source(ToggleSourceRequest {
+            index_uid: index_uid.to_string(),
+            source_id: source_id.clone(),
+            enable: false,
+        })
+        .await
+        .unwrap();
+
+    // Toggling a source to its current state does not record any event.
+    metastore
+        .toggle_source(ToggleSourceRequest {
+            index_uid: index_uid.to_string(),
+            source_id: source_id.clone(),
+            enable: false,
+        })
+        .await
+        .unwrap();
+
+    metastore
+        .add_index_event(AddIndexEventRequest::new(
+            index_uid.clone(),
+            IndexEventType::RetentionApplied,
+            "2 splits marked for deletion",
+        ))
+        .await
+        .unwrap();
+
+    let events = metastore
+        .list_index_events(ListIndexEventsRequest {
+            index_uid: index_uid.to_string(),
+            limit: None,
+        })
+        .await
+        .unwrap()
+        .events;
+    let event_types: Vec<&str> = events
+        .iter()
+        .map(|event| event.event_type.as_str())
+        .collect();
+    assert_eq!(
+        event_types,
+        [
+            "retention_applied",
+            "source_disabled",
+            "source_added",
+            "index_created"
+        ]
+    );
+    assert_eq!(events[0].index_uid, index_uid.to_string());
+    assert_eq!(events[0].message, "2 splits marked for deletion");
+    assert_eq!(events[1].message, format!("source `{source_id}` disabled"));
+    assert!(events[0].timestamp > 0);
+
+    let events = metastore
+        .list_index_events(ListIndexEventsRequest {
+            index_uid: index_uid.to_string(),
+            limit: Some(1),
+        })
+        .await
+        .unwrap()
+        .events;
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].event_type, "retention_applied");
+
+    let error = metastore
+        .add_index_event(AddIndexEventRequest::new(
+            IndexUid::new_with_random_ulid(&index_id),
+            IndexEventType::RetentionApplied,
+            "",
+        ))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        MetastoreError::NotFound(EntityKind::Index { .. })
+    ));
+
+    cleanup_index(&mut metastore, index_uid).await;
+}
+
+pub async fn test_metastore_index_config_versions<
+    MetastoreToTest: MetastoreServiceExt + DefaultForTest,
+>() {
+    let mut metastore = MetastoreToTest::default_for_test().await;
+
+    let index_id = append_random_suffix("test-index-config-versions");
+    let index_uri = format!("ram:///indexes/{index_id}");
+    let index_config = IndexConfig::for_test(&index_id, &index_uri);
+
+    let create_index_request =
+        CreateIndexRequest::try_from_index_config(index_config.clone()).unwrap();
+    let index_uid: IndexUid = metastore
+        .create_index(create_index_request)
+        .await
+        .unwrap()
+        .index_uid
+        .into();
+
+    let source_id = format!("{index_id}--source");
+    let source_config = SourceConfig::for_test(&source_id, SourceParams::void());
+    let add_source_request =
+        AddSourceRequest::try_from_source_config(index_uid.clone(), source_config).unwrap();
+    metastore.add_source(add_source_request).await.unwrap();
+
+    let mut new_doc_mapping = index_config.doc_mapping.clone();
+    let new_field_mapping: FieldMappingEntry =
+        serde_json::from_str(r#"{"name": "severity", "type": "text", "tokenizer": "raw"}"#)
+            .unwrap();
+    new_doc_mapping.field_mappings.push(new_field_mapping);
+
+    let mut update_index_request =
+        UpdateIndexRequest::try_from_doc_mapping(index_uid.clone(), &new_doc_mapping).unwrap();
+    update_index_request.author = Some("alice".to_string());
+    metastore.update_index(update_index_request).await.unwrap();
+
+    let versions = metastore
+        .list_index_config_versions(ListIndexConfigVersionsRequest {
+            index_uid: index_uid.to_string(),
+            limit: None,
+        })
+        .await
+        .unwrap()
+        .versions;
+    let version_numbers: Vec<u64> = versions.iter().map(|version| version.version).collect();
+    assert_eq!(version_numbers, [3, 2, 1]);
+
+    assert_eq!(versions[0].index_uid, index_uid.to_string());
+    assert_eq!(versions[0].description, "doc mapping updated");
+    assert_eq!(versions[0].author.as_deref(), Some("alice"));
+    assert_eq!(
+        versions[0].deserialize_index_config().unwrap().doc_mapping,
+        new_doc_mapping
+    );
+    assert_eq!(
+        versions[1].description,
+        format!("source `{source_id}` added")
+    );
+    assert_eq!(versions[1].deserialize_source_configs().unwrap().len(), 1);
+    assert_eq!(versions[2].description, "index created");
+    assert!(versions[2].deserialize_source_configs().unwrap().is_empty());
+    assert!(versions[2].timestamp > 0);
+
+    let versions = metastore
+        .list_index_config_versions(ListIndexConfigVersionsRequest {
+            index_uid: index_uid.to_string(),
+            limit: Some(1),
+        })
+        .await
+        .unwrap()
+        .versions;
+    assert_eq!(versions.len(), 1);
+    assert_eq!(versions[0].version, 3);
+
+    // Rolling back removes the field added by the update, which a regular update would reject.
+    let index_metadata = metastore
+        .rollback_index_config(RollbackIndexConfigRequest {
+            index_uid: index_uid.to_string(),
+            version: 2,
+            author: Some("bob".to_string()),
+        })
+        .await
+        .unwrap()
+        .deserialize_index_metadata()
+        .unwrap();
+    assert_eq!(index_metadata.doc_mapping_version, 2);
+    assert_eq!(
+        index_metadata.index_config.doc_mapping,
+        index_config.doc_mapping
+    );
+    assert_eq!(index_metadata.sources.len(), 1);
+
+    let versions = metastore
+        .list_index_config_versions(ListIndexConfigVersionsRequest {
+            index_uid: index_uid.to_string(),
+            limit: None,
+        })
+        .await
+        .unwrap()
+        .versions;
+    assert_eq!(versions.len(), 4);
+    assert_eq!(versions[0].version, 4);
+    assert_eq!(
+        versions[0].description,
+        "doc mapping rolled back to version 2"
+    );
+    assert_eq!(versions[0].author.as_deref(), Some("bob"));
+
+    let error = metastore
+        .rollback_index_config(RollbackIndexConfigRequest {
+            index_uid: index_uid.to_string(),
+            version: 42,
+            author: None,
+        })
+        .await
+        .unwrap_err();
+    assert!(matches!(error, MetastoreError::InvalidArgument { .. }));
+
+    let error = metastore
+        .rollback_index_config(RollbackIndexConfigRequest {
+            index_uid: IndexUid::new_with_random_ulid(&index_id).to_string(),
+            version: 2,
+            author: None,
+        })
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        MetastoreError::NotFound(EntityKind::Index { .. })
+    ));
+
+    cleanup_index(&mut metastore, index_uid).await;
+}
+
 pub async fn test_metastore_list_all_indexes<
     MetastoreToTest: MetastoreServiceExt + DefaultForTest,
 >() {
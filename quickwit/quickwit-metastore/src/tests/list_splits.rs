@@ -373,6 +373,21 @@ pub async fn test_metastore_list_splits<MetastoreToTest: MetastoreServiceExt + D
             "Expected 3 splits to be skipped out of the 5 provided splits.",
         );
 
+        let query = ListSplitsQuery::for_index(index_uid.clone()).with_split_ids([
+            split_id_2.clone(),
+            split_id_4.clone(),
+            "unknown".to_string(),
+        ]);
+        let splits = metastore
+            .list_splits(ListSplitsRequest::try_from_list_splits_query(query).unwrap())
+            .await
+            .unwrap()
+            .collect_splits()
+            .await
+            .unwrap();
+        let split_ids = collect_split_ids(&splits);
+        assert_eq!(split_ids, &[&split_id_2, &split_id_4]);
+
         let query = ListSplitsQuery::for_index(index_uid.clone())
             .with_split_state(SplitState::Staged)
             .with_time_range_start_gte(0)
@@ -204,6 +204,37 @@ macro_rules! metastore_test_suite {
                 $crate::tests::index::test_metastore_index_metadata::<$metastore_type>().await;
             }
 
+            #[tokio::test]
+            async fn test_metastore_update_index() {
+                let _ = tracing_subscriber::fmt::try_init();
+                $crate::tests::index::test_metastore_update_index::<$metastore_type>().await;
+            }
+
+            #[tokio::test]
+            async fn test_metastore_freeze_index() {
+                let _ = tracing_subscriber::fmt::try_init();
+                $crate::tests::index::test_metastore_freeze_index::<$metastore_type>().await;
+            }
+
+            #[tokio::test]
+            async fn test_metastore_set_merges_paused() {
+                let _ = tracing_subscriber::fmt::try_init();
+                $crate::tests::index::test_metastore_set_merges_paused::<$metastore_type>().await;
+            }
+
+            #[tokio::test]
+            async fn test_metastore_index_events() {
+                let _ = tracing_subscriber::fmt::try_init();
+                $crate::tests::index::test_metastore_index_events::<$metastore_type>().await;
+            }
+
+            #[tokio::test]
+            async fn test_metastore_index_config_versions() {
+                let _ = tracing_subscriber::fmt::try_init();
+                $crate::tests::index::test_metastore_index_config_versions::<$metastore_type>()
+                    .await;
+            }
+
             #[tokio::test]
             async fn test_metastore_list_indexes() {
                 let _ = tracing_subscriber::fmt::try_init();
@@ -62,6 +62,12 @@ pub async fn test_metastore_add_source<MetastoreToTest: MetastoreServiceExt + De
         source_params: SourceParams::void(),
         transform_config: None,
         input_format: SourceInputFormat::Json,
+        csv_config: Default::default(),
+        dead_letter_queue: None,
+        supervision: Default::default(),
+        fetch_stream: Default::default(),
+        placement_constraints: Default::default(),
+        priority: Default::default(),
     };
 
     assert_eq!(
@@ -164,6 +170,12 @@ pub async fn test_metastore_toggle_source<MetastoreToTest: MetastoreServiceExt +
         source_params: SourceParams::void(),
         transform_config: None,
         input_format: SourceInputFormat::Json,
+        csv_config: Default::default(),
+        dead_letter_queue: None,
+        supervision: Default::default(),
+        fetch_stream: Default::default(),
+        placement_constraints: Default::default(),
+        priority: Default::default(),
     };
     let add_source_request =
         AddSourceRequest::try_from_source_config(index_uid.clone(), source.clone()).unwrap();
@@ -231,6 +243,12 @@ pub async fn test_metastore_delete_source<MetastoreToTest: MetastoreServiceExt +
         source_params: SourceParams::void(),
         transform_config: None,
         input_format: SourceInputFormat::Json,
+        csv_config: Default::default(),
+        dead_letter_queue: None,
+        supervision: Default::default(),
+        fetch_stream: Default::default(),
+        placement_constraints: Default::default(),
+        priority: Default::default(),
     };
 
     let index_config = IndexConfig::for_test(&index_id, index_uri.as_str());
@@ -357,6 +375,12 @@ pub async fn test_metastore_reset_checkpoint<
             source_params: SourceParams::void(),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            csv_config: Default::default(),
+            dead_letter_queue: None,
+            supervision: Default::default(),
+            fetch_stream: Default::default(),
+            placement_constraints: Default::default(),
+            priority: Default::default(),
         };
         metastore
             .add_source(
@@ -1688,7 +1688,30 @@ pub async fn test_metastore_stage_splits<MetastoreToTest: MetastoreServiceExt +
         }
     ),);
 
+    // Stage a split with the same ID on another index, e.g. a clone.
+    let other_index_id = append_random_suffix("test-stage-splits-other");
+    let other_index_config = IndexConfig::for_test(&other_index_id, &index_uri);
+    let create_index_request =
+        CreateIndexRequest::try_from_index_config(other_index_config).unwrap();
+    let other_index_uid: IndexUid = metastore
+        .create_index(create_index_request)
+        .await
+        .unwrap()
+        .index_uid
+        .into();
+    let other_split_metadata_1 = SplitMetadata {
+        index_uid: other_index_uid.clone(),
+        ..split_metadata_1.clone()
+    };
+    let stage_splits_request = StageSplitsRequest::try_from_splits_metadata(
+        other_index_uid.clone(),
+        vec![other_split_metadata_1],
+    )
+    .unwrap();
+    metastore.stage_splits(stage_splits_request).await.unwrap();
+
     cleanup_index(&mut metastore, index_uid).await;
+    cleanup_index(&mut metastore, other_index_uid).await;
 }
 
 pub async fn test_metastore_update_splits_delete_opstamp<
@@ -410,6 +410,8 @@ impl OtlpGrpcLogsService {
         let ingest_request = IngestRequest {
             doc_batches: vec![doc_batch],
             commit: CommitType::Auto.into(),
+            idempotency_key: None,
+            return_positions: false,
         };
         self.ingest_service.ingest(ingest_request).await?;
         Ok(())
@@ -772,6 +772,8 @@ impl OtlpGrpcTracesService {
         let ingest_request = IngestRequest {
             doc_batches: vec![doc_batch],
             commit: self.commit_type.into(),
+            idempotency_key: None,
+            return_positions: false,
         };
         self.ingest_service.ingest(ingest_request).await?;
         Ok(())
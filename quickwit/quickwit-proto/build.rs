@@ -100,6 +100,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             "Shard.publish_token",
             "#[serde(default, skip_serializing_if = \"Option::is_none\")]",
         )
+        .field_attribute(
+            "Shard.priority",
+            "#[serde(default, skip_serializing_if = \"crate::ingest::is_realtime_priority\")]",
+        )
         .field_attribute(
             "Shard.replication_position_inclusive",
             "#[serde(default, skip_serializing_if = \"Option::is_none\")]",
@@ -125,9 +129,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     tonic_build::configure()
         .enum_attribute(".", "#[serde(rename_all=\"snake_case\")]")
         .type_attribute(".", "#[derive(Serialize, Deserialize, utoipa::ToSchema)]")
+        .type_attribute("GroupBy", "#[derive(Eq, Hash)]")
         .type_attribute("PartialHit", "#[derive(Eq, Hash)]")
         .type_attribute("PartialHit.sort_value", "#[derive(Copy)]")
         .type_attribute("SearchRequest", "#[derive(Eq, Hash)]")
+        .type_attribute("SnippetOptions", "#[derive(Eq, Hash)]")
         .type_attribute("SortByValue", "#[derive(Ord, PartialOrd)]")
         .type_attribute("SortField", "#[derive(Eq, Hash)]")
         .out_dir("src/codegen/quickwit")
@@ -56,6 +56,139 @@ pub struct GetOrCreateOpenShardsFailure {
     pub reason: i32,
 }
 #[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetShardScalingStateRequest {
+    #[prost(string, tag = "1")]
+    pub index_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub source_id: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetShardScalingStateResponse {
+    #[prost(string, tag = "1")]
+    pub index_uid: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub source_id: ::prost::alloc::string::String,
+    #[prost(uint32, tag = "3")]
+    pub num_open_shards: u32,
+    /// Most recent scaling decisions, most recent first.
+    #[prost(message, repeated, tag = "4")]
+    pub recent_decisions: ::prost::alloc::vec::Vec<ShardScalingDecision>,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DrainNodeRequest {
+    #[prost(string, tag = "1")]
+    pub node_id: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DrainNodeResponse {
+    #[prost(string, tag = "1")]
+    pub node_id: ::prost::alloc::string::String,
+    /// Number of indexing tasks still running on the node.
+    #[prost(uint32, tag = "2")]
+    pub num_indexing_tasks: u32,
+    /// Number of shards hosted by the node that have not been fully indexed yet.
+    #[prost(uint32, tag = "3")]
+    pub num_shards: u32,
+    /// Whether the node no longer runs indexing tasks nor hosts shards.
+    #[prost(bool, tag = "4")]
+    pub completed: bool,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetSourceBacklogRequest {
+    #[prost(string, tag = "1")]
+    pub index_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub source_id: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetSourceBacklogResponse {
+    #[prost(string, tag = "1")]
+    pub index_uid: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub source_id: ::prost::alloc::string::String,
+    /// Sum of the backlogs of the partitions of the source.
+    #[prost(uint64, tag = "3")]
+    pub total_backlog: u64,
+    #[prost(message, repeated, tag = "4")]
+    pub partition_backlogs: ::prost::alloc::vec::Vec<PartitionBacklog>,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PartitionBacklog {
+    /// Shard ID for ingest sources, partition ID for Kafka sources.
+    #[prost(string, tag = "1")]
+    pub partition_id: ::prost::alloc::string::String,
+    /// Node reporting the backlog: the leader of the shard for ingest sources, the indexer running the
+    /// pipeline for Kafka sources.
+    #[prost(string, tag = "2")]
+    pub node_id: ::prost::alloc::string::String,
+    /// Number of records between the position of the source and the latest position of the partition.
+    #[prost(uint64, tag = "3")]
+    pub backlog: u64,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetPlacementViolationsRequest {}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetPlacementViolationsResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub placement_violations: ::prost::alloc::vec::Vec<PlacementViolation>,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PlacementViolation {
+    #[prost(string, tag = "1")]
+    pub index_uid: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub source_id: ::prost::alloc::string::String,
+    /// Placement constraints of the source merged with the constraints of its index, formatted as
+    /// comma-separated `label=value` pairs.
+    #[prost(string, tag = "3")]
+    pub placement_constraints: ::prost::alloc::string::String,
+    #[prost(enumeration = "PlacementViolationKind", tag = "4")]
+    pub kind: i32,
+    /// Node violating the constraints. Empty when no node satisfies them.
+    #[prost(string, tag = "5")]
+    pub node_id: ::prost::alloc::string::String,
+    /// Shard violating the constraints, if any.
+    #[prost(uint64, optional, tag = "6")]
+    pub shard_id: ::core::option::Option<u64>,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ShardScalingDecision {
+    /// Unix timestamp (in seconds) of the decision.
+    #[prost(int64, tag = "1")]
+    pub timestamp: i64,
+    #[prost(enumeration = "ShardScalingMode", tag = "2")]
+    pub scaling_mode: i32,
+    /// Average ingestion rate of the open shards of the source in MiB/s at the time of the decision.
+    #[prost(float, tag = "3")]
+    pub avg_ingestion_rate: f32,
+    #[prost(uint32, tag = "4")]
+    pub num_open_shards_before: u32,
+    #[prost(uint32, tag = "5")]
+    pub num_open_shards_after: u32,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
 #[serde(rename_all = "snake_case")]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
@@ -98,6 +231,78 @@ impl GetOrCreateOpenShardsFailureReason {
         }
     }
 }
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum PlacementViolationKind {
+    Unspecified = 0,
+    /// No indexer satisfies the placement constraints of the source.
+    NoEligibleNode = 1,
+    /// An indexing pipeline of the source runs on an indexer that does not satisfy the constraints.
+    IndexingPipeline = 2,
+    /// An open shard of the source is led by an ingester that does not satisfy the constraints.
+    Shard = 3,
+}
+impl PlacementViolationKind {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            PlacementViolationKind::Unspecified => "PLACEMENT_VIOLATION_KIND_UNSPECIFIED",
+            PlacementViolationKind::NoEligibleNode => {
+                "PLACEMENT_VIOLATION_KIND_NO_ELIGIBLE_NODE"
+            }
+            PlacementViolationKind::IndexingPipeline => {
+                "PLACEMENT_VIOLATION_KIND_INDEXING_PIPELINE"
+            }
+            PlacementViolationKind::Shard => "PLACEMENT_VIOLATION_KIND_SHARD",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "PLACEMENT_VIOLATION_KIND_UNSPECIFIED" => Some(Self::Unspecified),
+            "PLACEMENT_VIOLATION_KIND_NO_ELIGIBLE_NODE" => Some(Self::NoEligibleNode),
+            "PLACEMENT_VIOLATION_KIND_INDEXING_PIPELINE" => Some(Self::IndexingPipeline),
+            "PLACEMENT_VIOLATION_KIND_SHARD" => Some(Self::Shard),
+            _ => None,
+        }
+    }
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ShardScalingMode {
+    Unspecified = 0,
+    Up = 1,
+    Down = 2,
+}
+impl ShardScalingMode {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            ShardScalingMode::Unspecified => "SHARD_SCALING_MODE_UNSPECIFIED",
+            ShardScalingMode::Up => "SHARD_SCALING_MODE_UP",
+            ShardScalingMode::Down => "SHARD_SCALING_MODE_DOWN",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "SHARD_SCALING_MODE_UNSPECIFIED" => Some(Self::Unspecified),
+            "SHARD_SCALING_MODE_UP" => Some(Self::Up),
+            "SHARD_SCALING_MODE_DOWN" => Some(Self::Down),
+            _ => None,
+        }
+    }
+}
 /// BEGIN quickwit-codegen
 #[allow(unused_imports)]
 use std::str::FromStr;
@@ -136,6 +341,30 @@ pub trait ControlPlaneService: std::fmt::Debug + dyn_clone::DynClone + Send + Sy
         &mut self,
         request: GetOrCreateOpenShardsRequest,
     ) -> crate::control_plane::ControlPlaneResult<GetOrCreateOpenShardsResponse>;
+    /// Returns the number of open shards of a source and the most recent scaling decisions made by the
+    /// control plane for that source.
+    async fn get_shard_scaling_state(
+        &mut self,
+        request: GetShardScalingStateRequest,
+    ) -> crate::control_plane::ControlPlaneResult<GetShardScalingStateResponse>;
+    /// Drains a node: moves its indexing pipelines to the other indexers and decommissions its
+    /// ingester. Returns the progress of the drain. This call is idempotent.
+    async fn drain_node(
+        &mut self,
+        request: DrainNodeRequest,
+    ) -> crate::control_plane::ControlPlaneResult<DrainNodeResponse>;
+    /// Returns the backlog of a source, i.e. the number of records of each partition that the source has
+    /// not indexed yet.
+    async fn get_source_backlog(
+        &mut self,
+        request: GetSourceBacklogRequest,
+    ) -> crate::control_plane::ControlPlaneResult<GetSourceBacklogResponse>;
+    /// Returns the indexing pipelines and ingest shards that do not respect the placement constraints
+    /// of their index or source.
+    async fn get_placement_violations(
+        &mut self,
+        request: GetPlacementViolationsRequest,
+    ) -> crate::control_plane::ControlPlaneResult<GetPlacementViolationsResponse>;
 }
 dyn_clone::clone_trait_object!(ControlPlaneService);
 #[cfg(any(test, feature = "testsuite"))]
@@ -256,6 +485,30 @@ impl ControlPlaneService for ControlPlaneServiceClient {
     ) -> crate::control_plane::ControlPlaneResult<GetOrCreateOpenShardsResponse> {
         self.inner.get_or_create_open_shards(request).await
     }
+    async fn get_shard_scaling_state(
+        &mut self,
+        request: GetShardScalingStateRequest,
+    ) -> crate::control_plane::ControlPlaneResult<GetShardScalingStateResponse> {
+        self.inner.get_shard_scaling_state(request).await
+    }
+    async fn drain_node(
+        &mut self,
+        request: DrainNodeRequest,
+    ) -> crate::control_plane::ControlPlaneResult<DrainNodeResponse> {
+        self.inner.drain_node(request).await
+    }
+    async fn get_source_backlog(
+        &mut self,
+        request: GetSourceBacklogRequest,
+    ) -> crate::control_plane::ControlPlaneResult<GetSourceBacklogResponse> {
+        self.inner.get_source_backlog(request).await
+    }
+    async fn get_placement_violations(
+        &mut self,
+        request: GetPlacementViolationsRequest,
+    ) -> crate::control_plane::ControlPlaneResult<GetPlacementViolationsResponse> {
+        self.inner.get_placement_violations(request).await
+    }
 }
 #[cfg(any(test, feature = "testsuite"))]
 pub mod control_plane_service_mock {
@@ -314,6 +567,38 @@ pub mod control_plane_service_mock {
         > {
             self.inner.lock().await.get_or_create_open_shards(request).await
         }
+        async fn get_shard_scaling_state(
+            &mut self,
+            request: super::GetShardScalingStateRequest,
+        ) -> crate::control_plane::ControlPlaneResult<
+            super::GetShardScalingStateResponse,
+        > {
+            self.inner.lock().await.get_shard_scaling_state(request).await
+        }
+        async fn drain_node(
+            &mut self,
+            request: super::DrainNodeRequest,
+        ) -> crate::control_plane::ControlPlaneResult<
+            super::DrainNodeResponse,
+        > {
+            self.inner.lock().await.drain_node(request).await
+        }
+        async fn get_source_backlog(
+            &mut self,
+            request: super::GetSourceBacklogRequest,
+        ) -> crate::control_plane::ControlPlaneResult<
+            super::GetSourceBacklogResponse,
+        > {
+            self.inner.lock().await.get_source_backlog(request).await
+        }
+        async fn get_placement_violations(
+            &mut self,
+            request: super::GetPlacementViolationsRequest,
+        ) -> crate::control_plane::ControlPlaneResult<
+            super::GetPlacementViolationsResponse,
+        > {
+            self.inner.lock().await.get_placement_violations(request).await
+        }
     }
     impl From<MockControlPlaneService> for ControlPlaneServiceClient {
         fn from(mock: MockControlPlaneService) -> Self {
@@ -428,6 +713,70 @@ impl tower::Service<GetOrCreateOpenShardsRequest> for Box<dyn ControlPlaneServic
         Box::pin(fut)
     }
 }
+impl tower::Service<GetShardScalingStateRequest> for Box<dyn ControlPlaneService> {
+    type Response = GetShardScalingStateResponse;
+    type Error = crate::control_plane::ControlPlaneError;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+    fn call(&mut self, request: GetShardScalingStateRequest) -> Self::Future {
+        let mut svc = self.clone();
+        let fut = async move { svc.get_shard_scaling_state(request).await };
+        Box::pin(fut)
+    }
+}
+impl tower::Service<DrainNodeRequest> for Box<dyn ControlPlaneService> {
+    type Response = DrainNodeResponse;
+    type Error = crate::control_plane::ControlPlaneError;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+    fn call(&mut self, request: DrainNodeRequest) -> Self::Future {
+        let mut svc = self.clone();
+        let fut = async move { svc.drain_node(request).await };
+        Box::pin(fut)
+    }
+}
+impl tower::Service<GetSourceBacklogRequest> for Box<dyn ControlPlaneService> {
+    type Response = GetSourceBacklogResponse;
+    type Error = crate::control_plane::ControlPlaneError;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+    fn call(&mut self, request: GetSourceBacklogRequest) -> Self::Future {
+        let mut svc = self.clone();
+        let fut = async move { svc.get_source_backlog(request).await };
+        Box::pin(fut)
+    }
+}
+impl tower::Service<GetPlacementViolationsRequest> for Box<dyn ControlPlaneService> {
+    type Response = GetPlacementViolationsResponse;
+    type Error = crate::control_plane::ControlPlaneError;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+    fn call(&mut self, request: GetPlacementViolationsRequest) -> Self::Future {
+        let mut svc = self.clone();
+        let fut = async move { svc.get_placement_violations(request).await };
+        Box::pin(fut)
+    }
+}
 /// A tower block is a set of towers. Each tower is stack of layers (middlewares) that are applied to a service.
 #[derive(Debug)]
 struct ControlPlaneServiceTowerBlock {
@@ -462,6 +811,26 @@ struct ControlPlaneServiceTowerBlock {
         GetOrCreateOpenShardsResponse,
         crate::control_plane::ControlPlaneError,
     >,
+    get_shard_scaling_state_svc: quickwit_common::tower::BoxService<
+        GetShardScalingStateRequest,
+        GetShardScalingStateResponse,
+        crate::control_plane::ControlPlaneError,
+    >,
+    drain_node_svc: quickwit_common::tower::BoxService<
+        DrainNodeRequest,
+        DrainNodeResponse,
+        crate::control_plane::ControlPlaneError,
+    >,
+    get_source_backlog_svc: quickwit_common::tower::BoxService<
+        GetSourceBacklogRequest,
+        GetSourceBacklogResponse,
+        crate::control_plane::ControlPlaneError,
+    >,
+    get_placement_violations_svc: quickwit_common::tower::BoxService<
+        GetPlacementViolationsRequest,
+        GetPlacementViolationsResponse,
+        crate::control_plane::ControlPlaneError,
+    >,
 }
 impl Clone for ControlPlaneServiceTowerBlock {
     fn clone(&self) -> Self {
@@ -473,6 +842,10 @@ impl Clone for ControlPlaneServiceTowerBlock {
             toggle_source_svc: self.toggle_source_svc.clone(),
             delete_source_svc: self.delete_source_svc.clone(),
             get_or_create_open_shards_svc: self.get_or_create_open_shards_svc.clone(),
+            get_shard_scaling_state_svc: self.get_shard_scaling_state_svc.clone(),
+            drain_node_svc: self.drain_node_svc.clone(),
+            get_source_backlog_svc: self.get_source_backlog_svc.clone(),
+            get_placement_violations_svc: self.get_placement_violations_svc.clone(),
         }
     }
 }
@@ -516,6 +889,30 @@ impl ControlPlaneService for ControlPlaneServiceTowerBlock {
     ) -> crate::control_plane::ControlPlaneResult<GetOrCreateOpenShardsResponse> {
         self.get_or_create_open_shards_svc.ready().await?.call(request).await
     }
+    async fn get_shard_scaling_state(
+        &mut self,
+        request: GetShardScalingStateRequest,
+    ) -> crate::control_plane::ControlPlaneResult<GetShardScalingStateResponse> {
+        self.get_shard_scaling_state_svc.ready().await?.call(request).await
+    }
+    async fn drain_node(
+        &mut self,
+        request: DrainNodeRequest,
+    ) -> crate::control_plane::ControlPlaneResult<DrainNodeResponse> {
+        self.drain_node_svc.ready().await?.call(request).await
+    }
+    async fn get_source_backlog(
+        &mut self,
+        request: GetSourceBacklogRequest,
+    ) -> crate::control_plane::ControlPlaneResult<GetSourceBacklogResponse> {
+        self.get_source_backlog_svc.ready().await?.call(request).await
+    }
+    async fn get_placement_violations(
+        &mut self,
+        request: GetPlacementViolationsRequest,
+    ) -> crate::control_plane::ControlPlaneResult<GetPlacementViolationsResponse> {
+        self.get_placement_violations_svc.ready().await?.call(request).await
+    }
 }
 #[derive(Debug, Default)]
 pub struct ControlPlaneServiceTowerBlockBuilder {
@@ -573,6 +970,42 @@ pub struct ControlPlaneServiceTowerBlockBuilder {
             crate::control_plane::ControlPlaneError,
         >,
     >,
+    #[allow(clippy::type_complexity)]
+    get_shard_scaling_state_layer: Option<
+        quickwit_common::tower::BoxLayer<
+            Box<dyn ControlPlaneService>,
+            GetShardScalingStateRequest,
+            GetShardScalingStateResponse,
+            crate::control_plane::ControlPlaneError,
+        >,
+    >,
+    #[allow(clippy::type_complexity)]
+    drain_node_layer: Option<
+        quickwit_common::tower::BoxLayer<
+            Box<dyn ControlPlaneService>,
+            DrainNodeRequest,
+            DrainNodeResponse,
+            crate::control_plane::ControlPlaneError,
+        >,
+    >,
+    #[allow(clippy::type_complexity)]
+    get_source_backlog_layer: Option<
+        quickwit_common::tower::BoxLayer<
+            Box<dyn ControlPlaneService>,
+            GetSourceBacklogRequest,
+            GetSourceBacklogResponse,
+            crate::control_plane::ControlPlaneError,
+        >,
+    >,
+    #[allow(clippy::type_complexity)]
+    get_placement_violations_layer: Option<
+        quickwit_common::tower::BoxLayer<
+            Box<dyn ControlPlaneService>,
+            GetPlacementViolationsRequest,
+            GetPlacementViolationsResponse,
+            crate::control_plane::ControlPlaneError,
+        >,
+    >,
 }
 impl ControlPlaneServiceTowerBlockBuilder {
     pub fn shared_layer<L>(mut self, layer: L) -> Self
@@ -626,6 +1059,38 @@ impl ControlPlaneServiceTowerBlockBuilder {
         <L::Service as tower::Service<
             GetOrCreateOpenShardsRequest,
         >>::Future: Send + 'static,
+        L::Service: tower::Service<
+                GetShardScalingStateRequest,
+                Response = GetShardScalingStateResponse,
+                Error = crate::control_plane::ControlPlaneError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<
+            GetShardScalingStateRequest,
+        >>::Future: Send + 'static,
+        L::Service: tower::Service<
+                DrainNodeRequest,
+                Response = DrainNodeResponse,
+                Error = crate::control_plane::ControlPlaneError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<
+            DrainNodeRequest,
+        >>::Future: Send + 'static,
+        L::Service: tower::Service<
+                GetSourceBacklogRequest,
+                Response = GetSourceBacklogResponse,
+                Error = crate::control_plane::ControlPlaneError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<
+            GetSourceBacklogRequest,
+        >>::Future: Send + 'static,
+        L::Service: tower::Service<
+                GetPlacementViolationsRequest,
+                Response = GetPlacementViolationsResponse,
+                Error = crate::control_plane::ControlPlaneError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<
+            GetPlacementViolationsRequest,
+        >>::Future: Send + 'static,
     {
         self
             .create_index_layer = Some(
@@ -649,6 +1114,20 @@ impl ControlPlaneServiceTowerBlockBuilder {
         );
         self
             .get_or_create_open_shards_layer = Some(
+            quickwit_common::tower::BoxLayer::new(layer.clone()),
+        );
+        self
+            .get_shard_scaling_state_layer = Some(
+            quickwit_common::tower::BoxLayer::new(layer.clone()),
+        );
+        self
+            .drain_node_layer = Some(quickwit_common::tower::BoxLayer::new(layer.clone()));
+        self
+            .get_source_backlog_layer = Some(
+            quickwit_common::tower::BoxLayer::new(layer.clone()),
+        );
+        self
+            .get_placement_violations_layer = Some(
             quickwit_common::tower::BoxLayer::new(layer),
         );
         self
@@ -746,10 +1225,67 @@ impl ControlPlaneServiceTowerBlockBuilder {
         );
         self
     }
-    pub fn build<T>(self, instance: T) -> ControlPlaneServiceClient
+    pub fn get_shard_scaling_state_layer<L>(mut self, layer: L) -> Self
     where
-        T: ControlPlaneService,
-    {
+        L: tower::Layer<Box<dyn ControlPlaneService>> + Send + Sync + 'static,
+        L::Service: tower::Service<
+                GetShardScalingStateRequest,
+                Response = GetShardScalingStateResponse,
+                Error = crate::control_plane::ControlPlaneError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<
+            GetShardScalingStateRequest,
+        >>::Future: Send + 'static,
+    {
+        self
+            .get_shard_scaling_state_layer = Some(
+            quickwit_common::tower::BoxLayer::new(layer),
+        );
+        self
+    }
+    pub fn drain_node_layer<L>(mut self, layer: L) -> Self
+    where
+        L: tower::Layer<Box<dyn ControlPlaneService>> + Send + Sync + 'static,
+        L::Service: tower::Service<
+                DrainNodeRequest,
+                Response = DrainNodeResponse,
+                Error = crate::control_plane::ControlPlaneError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<DrainNodeRequest>>::Future: Send + 'static,
+    {
+        self.drain_node_layer = Some(quickwit_common::tower::BoxLayer::new(layer));
+        self
+    }
+    pub fn get_source_backlog_layer<L>(mut self, layer: L) -> Self
+    where
+        L: tower::Layer<Box<dyn ControlPlaneService>> + Send + Sync + 'static,
+        L::Service: tower::Service<
+                GetSourceBacklogRequest,
+                Response = GetSourceBacklogResponse,
+                Error = crate::control_plane::ControlPlaneError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<GetSourceBacklogRequest>>::Future: Send + 'static,
+    {
+        self.get_source_backlog_layer = Some(quickwit_common::tower::BoxLayer::new(layer));
+        self
+    }
+    pub fn get_placement_violations_layer<L>(mut self, layer: L) -> Self
+    where
+        L: tower::Layer<Box<dyn ControlPlaneService>> + Send + Sync + 'static,
+        L::Service: tower::Service<
+                GetPlacementViolationsRequest,
+                Response = GetPlacementViolationsResponse,
+                Error = crate::control_plane::ControlPlaneError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<GetPlacementViolationsRequest>>::Future: Send + 'static,
+    {
+        self.get_placement_violations_layer = Some(quickwit_common::tower::BoxLayer::new(layer));
+        self
+    }
+    pub fn build<T>(self, instance: T) -> ControlPlaneServiceClient
+    where
+        T: ControlPlaneService,
+    {
         self.build_from_boxed(Box::new(instance))
     }
     pub fn build_from_channel(
@@ -815,6 +1351,28 @@ impl ControlPlaneServiceTowerBlockBuilder {
         } else {
             quickwit_common::tower::BoxService::new(boxed_instance.clone())
         };
+        let get_shard_scaling_state_svc = if let Some(layer)
+            = self.get_shard_scaling_state_layer
+        {
+            layer.layer(boxed_instance.clone())
+        } else {
+            quickwit_common::tower::BoxService::new(boxed_instance.clone())
+        };
+        let drain_node_svc = if let Some(layer) = self.drain_node_layer {
+            layer.layer(boxed_instance.clone())
+        } else {
+            quickwit_common::tower::BoxService::new(boxed_instance.clone())
+        };
+        let get_source_backlog_svc = if let Some(layer) = self.get_source_backlog_layer {
+            layer.layer(boxed_instance.clone())
+        } else {
+            quickwit_common::tower::BoxService::new(boxed_instance.clone())
+        };
+        let get_placement_violations_svc = if let Some(layer) = self.get_placement_violations_layer {
+            layer.layer(boxed_instance.clone())
+        } else {
+            quickwit_common::tower::BoxService::new(boxed_instance.clone())
+        };
         let tower_block = ControlPlaneServiceTowerBlock {
             inner: boxed_instance.clone(),
             create_index_svc,
@@ -823,6 +1381,10 @@ impl ControlPlaneServiceTowerBlockBuilder {
             toggle_source_svc,
             delete_source_svc,
             get_or_create_open_shards_svc,
+            get_shard_scaling_state_svc,
+            drain_node_svc,
+            get_source_backlog_svc,
+            get_placement_violations_svc,
         };
         ControlPlaneServiceClient::new(tower_block)
     }
@@ -952,6 +1514,33 @@ where
                 GetOrCreateOpenShardsResponse,
                 crate::control_plane::ControlPlaneError,
             >,
+        >
+        + tower::Service<
+            GetShardScalingStateRequest,
+            Response = GetShardScalingStateResponse,
+            Error = crate::control_plane::ControlPlaneError,
+            Future = BoxFuture<
+                GetShardScalingStateResponse,
+                crate::control_plane::ControlPlaneError,
+            >,
+        >
+        + tower::Service<
+            DrainNodeRequest,
+            Response = DrainNodeResponse,
+            Error = crate::control_plane::ControlPlaneError,
+            Future = BoxFuture<DrainNodeResponse, crate::control_plane::ControlPlaneError>,
+        >
+        + tower::Service<
+            GetSourceBacklogRequest,
+            Response = GetSourceBacklogResponse,
+            Error = crate::control_plane::ControlPlaneError,
+            Future = BoxFuture<GetSourceBacklogResponse, crate::control_plane::ControlPlaneError>,
+        >
+        + tower::Service<
+            GetPlacementViolationsRequest,
+            Response = GetPlacementViolationsResponse,
+            Error = crate::control_plane::ControlPlaneError,
+            Future = BoxFuture<GetPlacementViolationsResponse, crate::control_plane::ControlPlaneError>,
         >,
 {
     async fn create_index(
@@ -992,6 +1581,30 @@ where
     ) -> crate::control_plane::ControlPlaneResult<GetOrCreateOpenShardsResponse> {
         self.call(request).await
     }
+    async fn get_shard_scaling_state(
+        &mut self,
+        request: GetShardScalingStateRequest,
+    ) -> crate::control_plane::ControlPlaneResult<GetShardScalingStateResponse> {
+        self.call(request).await
+    }
+    async fn drain_node(
+        &mut self,
+        request: DrainNodeRequest,
+    ) -> crate::control_plane::ControlPlaneResult<DrainNodeResponse> {
+        self.call(request).await
+    }
+    async fn get_source_backlog(
+        &mut self,
+        request: GetSourceBacklogRequest,
+    ) -> crate::control_plane::ControlPlaneResult<GetSourceBacklogResponse> {
+        self.call(request).await
+    }
+    async fn get_placement_violations(
+        &mut self,
+        request: GetPlacementViolationsRequest,
+    ) -> crate::control_plane::ControlPlaneResult<GetPlacementViolationsResponse> {
+        self.call(request).await
+    }
 }
 #[derive(Debug, Clone)]
 pub struct ControlPlaneServiceGrpcClientAdapter<T> {
@@ -1034,7 +1647,7 @@ where
         super::metastore::CreateIndexResponse,
     > {
         self.inner
-            .create_index(request)
+            .create_index(quickwit_common::deadline::make_grpc_request(request))
             .await
             .map(|response| response.into_inner())
             .map_err(|error| error.into())
@@ -1044,7 +1657,7 @@ where
         request: super::metastore::DeleteIndexRequest,
     ) -> crate::control_plane::ControlPlaneResult<super::metastore::EmptyResponse> {
         self.inner
-            .delete_index(request)
+            .delete_index(quickwit_common::deadline::make_grpc_request(request))
             .await
             .map(|response| response.into_inner())
             .map_err(|error| error.into())
@@ -1054,7 +1667,7 @@ where
         request: super::metastore::AddSourceRequest,
     ) -> crate::control_plane::ControlPlaneResult<super::metastore::EmptyResponse> {
         self.inner
-            .add_source(request)
+            .add_source(quickwit_common::deadline::make_grpc_request(request))
             .await
             .map(|response| response.into_inner())
             .map_err(|error| error.into())
@@ -1064,7 +1677,7 @@ where
         request: super::metastore::ToggleSourceRequest,
     ) -> crate::control_plane::ControlPlaneResult<super::metastore::EmptyResponse> {
         self.inner
-            .toggle_source(request)
+            .toggle_source(quickwit_common::deadline::make_grpc_request(request))
             .await
             .map(|response| response.into_inner())
             .map_err(|error| error.into())
@@ -1074,7 +1687,7 @@ where
         request: super::metastore::DeleteSourceRequest,
     ) -> crate::control_plane::ControlPlaneResult<super::metastore::EmptyResponse> {
         self.inner
-            .delete_source(request)
+            .delete_source(quickwit_common::deadline::make_grpc_request(request))
             .await
             .map(|response| response.into_inner())
             .map_err(|error| error.into())
@@ -1084,7 +1697,47 @@ where
         request: GetOrCreateOpenShardsRequest,
     ) -> crate::control_plane::ControlPlaneResult<GetOrCreateOpenShardsResponse> {
         self.inner
-            .get_or_create_open_shards(request)
+            .get_or_create_open_shards(quickwit_common::deadline::make_grpc_request(request))
+            .await
+            .map(|response| response.into_inner())
+            .map_err(|error| error.into())
+    }
+    async fn get_shard_scaling_state(
+        &mut self,
+        request: GetShardScalingStateRequest,
+    ) -> crate::control_plane::ControlPlaneResult<GetShardScalingStateResponse> {
+        self.inner
+            .get_shard_scaling_state(quickwit_common::deadline::make_grpc_request(request))
+            .await
+            .map(|response| response.into_inner())
+            .map_err(|error| error.into())
+    }
+    async fn drain_node(
+        &mut self,
+        request: DrainNodeRequest,
+    ) -> crate::control_plane::ControlPlaneResult<DrainNodeResponse> {
+        self.inner
+            .drain_node(quickwit_common::deadline::make_grpc_request(request))
+            .await
+            .map(|response| response.into_inner())
+            .map_err(|error| error.into())
+    }
+    async fn get_source_backlog(
+        &mut self,
+        request: GetSourceBacklogRequest,
+    ) -> crate::control_plane::ControlPlaneResult<GetSourceBacklogResponse> {
+        self.inner
+            .get_source_backlog(quickwit_common::deadline::make_grpc_request(request))
+            .await
+            .map(|response| response.into_inner())
+            .map_err(|error| error.into())
+    }
+    async fn get_placement_violations(
+        &mut self,
+        request: GetPlacementViolationsRequest,
+    ) -> crate::control_plane::ControlPlaneResult<GetPlacementViolationsResponse> {
+        self.inner
+            .get_placement_violations(quickwit_common::deadline::make_grpc_request(request))
             .await
             .map(|response| response.into_inner())
             .map_err(|error| error.into())
@@ -1171,6 +1824,50 @@ for ControlPlaneServiceGrpcServerAdapter {
             .map(tonic::Response::new)
             .map_err(|error| error.into())
     }
+    async fn get_shard_scaling_state(
+        &self,
+        request: tonic::Request<GetShardScalingStateRequest>,
+    ) -> Result<tonic::Response<GetShardScalingStateResponse>, tonic::Status> {
+        self.inner
+            .clone()
+            .get_shard_scaling_state(request.into_inner())
+            .await
+            .map(tonic::Response::new)
+            .map_err(|error| error.into())
+    }
+    async fn drain_node(
+        &self,
+        request: tonic::Request<DrainNodeRequest>,
+    ) -> Result<tonic::Response<DrainNodeResponse>, tonic::Status> {
+        self.inner
+            .clone()
+            .drain_node(request.into_inner())
+            .await
+            .map(tonic::Response::new)
+            .map_err(|error| error.into())
+    }
+    async fn get_source_backlog(
+        &self,
+        request: tonic::Request<GetSourceBacklogRequest>,
+    ) -> Result<tonic::Response<GetSourceBacklogResponse>, tonic::Status> {
+        self.inner
+            .clone()
+            .get_source_backlog(request.into_inner())
+            .await
+            .map(tonic::Response::new)
+            .map_err(|error| error.into())
+    }
+    async fn get_placement_violations(
+        &self,
+        request: tonic::Request<GetPlacementViolationsRequest>,
+    ) -> Result<tonic::Response<GetPlacementViolationsResponse>, tonic::Status> {
+        self.inner
+            .clone()
+            .get_placement_violations(request.into_inner())
+            .await
+            .map(tonic::Response::new)
+            .map_err(|error| error.into())
+    }
 }
 /// Generated client implementations.
 pub mod control_plane_service_grpc_client {
@@ -1450,6 +2147,134 @@ pub mod control_plane_service_grpc_client {
                 );
             self.inner.unary(req, path, codec).await
         }
+        /// Returns the number of open shards of a source and the most recent scaling decisions made by the
+        /// control plane for that source.
+        pub async fn get_shard_scaling_state(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetShardScalingStateRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetShardScalingStateResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit.control_plane.ControlPlaneService/GetShardScalingState",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "quickwit.control_plane.ControlPlaneService",
+                        "GetShardScalingState",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        /// Drains a node: moves its indexing pipelines to the other indexers and decommissions its
+        /// ingester. Returns the progress of the drain. This call is idempotent.
+        pub async fn drain_node(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DrainNodeRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::DrainNodeResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit.control_plane.ControlPlaneService/DrainNode",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "quickwit.control_plane.ControlPlaneService",
+                        "DrainNode",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        /// Returns the backlog of a source, i.e. the number of records of each partition that the source has
+        /// not indexed yet.
+        pub async fn get_source_backlog(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetSourceBacklogRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetSourceBacklogResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit.control_plane.ControlPlaneService/GetSourceBacklog",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "quickwit.control_plane.ControlPlaneService",
+                        "GetSourceBacklog",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        /// Returns the indexing pipelines and ingest shards that do not respect the placement constraints
+        /// of their index or source.
+        pub async fn get_placement_violations(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetPlacementViolationsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetPlacementViolationsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit.control_plane.ControlPlaneService/GetPlacementViolations",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "quickwit.control_plane.ControlPlaneService",
+                        "GetPlacementViolations",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
     }
 }
 /// Generated server implementations.
@@ -1508,6 +2333,42 @@ pub mod control_plane_service_grpc_server {
             tonic::Response<super::GetOrCreateOpenShardsResponse>,
             tonic::Status,
         >;
+        /// Returns the number of open shards of a source and the most recent scaling decisions made by the
+        /// control plane for that source.
+        async fn get_shard_scaling_state(
+            &self,
+            request: tonic::Request<super::GetShardScalingStateRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetShardScalingStateResponse>,
+            tonic::Status,
+        >;
+        /// Drains a node: moves its indexing pipelines to the other indexers and decommissions its
+        /// ingester. Returns the progress of the drain. This call is idempotent.
+        async fn drain_node(
+            &self,
+            request: tonic::Request<super::DrainNodeRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::DrainNodeResponse>,
+            tonic::Status,
+        >;
+        /// Returns the backlog of a source, i.e. the number of records of each partition that the source has
+        /// not indexed yet.
+        async fn get_source_backlog(
+            &self,
+            request: tonic::Request<super::GetSourceBacklogRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetSourceBacklogResponse>,
+            tonic::Status,
+        >;
+        /// Returns the indexing pipelines and ingest shards that do not respect the placement constraints
+        /// of their index or source.
+        async fn get_placement_violations(
+            &self,
+            request: tonic::Request<super::GetPlacementViolationsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetPlacementViolationsResponse>,
+            tonic::Status,
+        >;
     }
     #[derive(Debug)]
     pub struct ControlPlaneServiceGrpcServer<T: ControlPlaneServiceGrpc> {
@@ -1880,6 +2741,198 @@ pub mod control_plane_service_grpc_server {
                     };
                     Box::pin(fut)
                 }
+                "/quickwit.control_plane.ControlPlaneService/GetShardScalingState" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetShardScalingStateSvc<T: ControlPlaneServiceGrpc>(
+                        pub Arc<T>,
+                    );
+                    impl<
+                        T: ControlPlaneServiceGrpc,
+                    > tonic::server::UnaryService<super::GetShardScalingStateRequest>
+                    for GetShardScalingStateSvc<T> {
+                        type Response = super::GetShardScalingStateResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetShardScalingStateRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                (*inner).get_shard_scaling_state(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetShardScalingStateSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/quickwit.control_plane.ControlPlaneService/DrainNode" => {
+                    #[allow(non_camel_case_types)]
+                    struct DrainNodeSvc<T: ControlPlaneServiceGrpc>(
+                        pub Arc<T>,
+                    );
+                    impl<
+                        T: ControlPlaneServiceGrpc,
+                    > tonic::server::UnaryService<super::DrainNodeRequest>
+                    for DrainNodeSvc<T> {
+                        type Response = super::DrainNodeResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DrainNodeRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                (*inner).drain_node(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = DrainNodeSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/quickwit.control_plane.ControlPlaneService/GetSourceBacklog" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetSourceBacklogSvc<T: ControlPlaneServiceGrpc>(
+                        pub Arc<T>,
+                    );
+                    impl<
+                        T: ControlPlaneServiceGrpc,
+                    > tonic::server::UnaryService<super::GetSourceBacklogRequest>
+                    for GetSourceBacklogSvc<T> {
+                        type Response = super::GetSourceBacklogResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetSourceBacklogRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                (*inner).get_source_backlog(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetSourceBacklogSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/quickwit.control_plane.ControlPlaneService/GetPlacementViolations" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetPlacementViolationsSvc<T: ControlPlaneServiceGrpc>(
+                        pub Arc<T>,
+                    );
+                    impl<
+                        T: ControlPlaneServiceGrpc,
+                    > tonic::server::UnaryService<super::GetPlacementViolationsRequest>
+                    for GetPlacementViolationsSvc<T> {
+                        type Response = super::GetPlacementViolationsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetPlacementViolationsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                (*inner).get_placement_violations(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetPlacementViolationsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => {
                     Box::pin(async move {
                         Ok(
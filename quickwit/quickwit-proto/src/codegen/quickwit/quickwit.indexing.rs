@@ -416,7 +416,7 @@ where
         request: ApplyIndexingPlanRequest,
     ) -> crate::indexing::IndexingResult<ApplyIndexingPlanResponse> {
         self.inner
-            .apply_indexing_plan(request)
+            .apply_indexing_plan(quickwit_common::deadline::make_grpc_request(request))
             .await
             .map(|response| response.into_inner())
             .map_err(|error| error.into())
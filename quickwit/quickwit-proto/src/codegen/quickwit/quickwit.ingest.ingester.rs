@@ -23,6 +23,10 @@ pub struct PersistSubrequest {
     pub shard_id: u64,
     #[prost(message, optional, tag = "5")]
     pub doc_batch: ::core::option::Option<super::DocBatchV2>,
+    /// Unique identifier of the batch, shared by the retries and the hedged requests sending the same batch.
+    /// An ingester that already persisted the batch returns the position of the batch instead of persisting it again.
+    #[prost(string, optional, tag = "6")]
+    pub batch_uid: ::core::option::Option<::prost::alloc::string::String>,
 }
 #[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -179,6 +183,10 @@ pub struct ReplicateSubrequest {
     pub to_position_inclusive: ::core::option::Option<crate::types::Position>,
     #[prost(message, optional, tag = "7")]
     pub doc_batch: ::core::option::Option<super::DocBatchV2>,
+    /// Batch UID of the persist subrequest. The follower remembers it so that it can acknowledge the hedged persist
+    /// requests sending the same batch.
+    #[prost(string, optional, tag = "8")]
+    pub batch_uid: ::core::option::Option<::prost::alloc::string::String>,
 }
 #[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -268,6 +276,12 @@ pub struct OpenFetchStreamRequest {
     pub shard_id: u64,
     #[prost(message, optional, tag = "5")]
     pub from_position_exclusive: ::core::option::Option<crate::types::Position>,
+    /// Maximum size of the record batches streamed by the ingester. The ingester picks a default if unset.
+    #[prost(uint64, optional, tag = "6")]
+    pub batch_num_bytes: ::core::option::Option<u64>,
+    /// Requests the ingester to compress the record batches with zstd. The client is responsible for decompressing them.
+    #[prost(bool, tag = "7")]
+    pub compress_batches: bool,
 }
 #[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -305,6 +319,10 @@ pub struct FetchPayload {
     pub from_position_exclusive: ::core::option::Option<crate::types::Position>,
     #[prost(message, optional, tag = "6")]
     pub to_position_inclusive: ::core::option::Option<crate::types::Position>,
+    /// Whether the buffer of the record batch is compressed with zstd. The record lengths are those of the
+    /// decompressed records.
+    #[prost(bool, tag = "7")]
+    pub is_compressed: bool,
 }
 #[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -385,6 +403,7 @@ pub enum PersistFailureReason {
     ShardClosed = 2,
     RateLimited = 3,
     ResourceExhausted = 4,
+    DiskWatermarkExceeded = 5,
 }
 impl PersistFailureReason {
     /// String value of the enum field names used in the ProtoBuf definition.
@@ -402,6 +421,9 @@ impl PersistFailureReason {
             PersistFailureReason::ResourceExhausted => {
                 "PERSIST_FAILURE_REASON_RESOURCE_EXHAUSTED"
             }
+            PersistFailureReason::DiskWatermarkExceeded => {
+                "PERSIST_FAILURE_REASON_DISK_WATERMARK_EXCEEDED"
+            }
         }
     }
     /// Creates an enum from field names used in the ProtoBuf definition.
@@ -412,6 +434,9 @@ impl PersistFailureReason {
             "PERSIST_FAILURE_REASON_SHARD_CLOSED" => Some(Self::ShardClosed),
             "PERSIST_FAILURE_REASON_RATE_LIMITED" => Some(Self::RateLimited),
             "PERSIST_FAILURE_REASON_RESOURCE_EXHAUSTED" => Some(Self::ResourceExhausted),
+            "PERSIST_FAILURE_REASON_DISK_WATERMARK_EXCEEDED" => {
+                Some(Self::DiskWatermarkExceeded)
+            }
             _ => None,
         }
     }
@@ -1677,7 +1702,7 @@ where
         request: PersistRequest,
     ) -> crate::ingest::IngestV2Result<PersistResponse> {
         self.inner
-            .persist(request)
+            .persist(quickwit_common::deadline::make_grpc_request(request))
             .await
             .map(|response| response.into_inner())
             .map_err(|error| error.into())
@@ -1729,7 +1754,7 @@ where
         request: InitShardsRequest,
     ) -> crate::ingest::IngestV2Result<InitShardsResponse> {
         self.inner
-            .init_shards(request)
+            .init_shards(quickwit_common::deadline::make_grpc_request(request))
             .await
             .map(|response| response.into_inner())
             .map_err(|error| error.into())
@@ -1739,7 +1764,7 @@ where
         request: TruncateShardsRequest,
     ) -> crate::ingest::IngestV2Result<TruncateShardsResponse> {
         self.inner
-            .truncate_shards(request)
+            .truncate_shards(quickwit_common::deadline::make_grpc_request(request))
             .await
             .map(|response| response.into_inner())
             .map_err(|error| error.into())
@@ -1749,7 +1774,7 @@ where
         request: CloseShardsRequest,
     ) -> crate::ingest::IngestV2Result<CloseShardsResponse> {
         self.inner
-            .close_shards(request)
+            .close_shards(quickwit_common::deadline::make_grpc_request(request))
             .await
             .map(|response| response.into_inner())
             .map_err(|error| error.into())
@@ -1759,7 +1784,7 @@ where
         request: PingRequest,
     ) -> crate::ingest::IngestV2Result<PingResponse> {
         self.inner
-            .ping(request)
+            .ping(quickwit_common::deadline::make_grpc_request(request))
             .await
             .map(|response| response.into_inner())
             .map_err(|error| error.into())
@@ -1769,7 +1794,7 @@ where
         request: DecommissionRequest,
     ) -> crate::ingest::IngestV2Result<DecommissionResponse> {
         self.inner
-            .decommission(request)
+            .decommission(quickwit_common::deadline::make_grpc_request(request))
             .await
             .map(|response| response.into_inner())
             .map_err(|error| error.into())
@@ -23,6 +23,9 @@ pub struct IngestSubrequest {
     #[prost(message, optional, tag = "4")]
     pub doc_batch: ::core::option::Option<super::DocBatchV2>,
 }
+/// A subrequest targeting the index of a routing rule is split into one
+/// subrequest per target index, so it can yield several successes and failures
+/// sharing its subrequest ID.
 #[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -47,6 +50,9 @@ pub struct IngestSuccess {
     /// Replication position inclusive.
     #[prost(message, optional, tag = "5")]
     pub replication_position_inclusive: ::core::option::Option<crate::types::Position>,
+    /// Number of documents of the subrequest persisted to the shard.
+    #[prost(uint32, tag = "6")]
+    pub num_docs: u32,
 }
 #[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -60,6 +66,9 @@ pub struct IngestFailure {
     pub source_id: ::prost::alloc::string::String,
     #[prost(enumeration = "IngestFailureReason", tag = "5")]
     pub reason: i32,
+    /// Number of documents of the subrequest that were not persisted.
+    #[prost(uint32, tag = "6")]
+    pub num_docs: u32,
 }
 #[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
 #[serde(rename_all = "snake_case")]
@@ -67,11 +76,17 @@ pub struct IngestFailure {
 #[repr(i32)]
 pub enum IngestFailureReason {
     Unspecified = 0,
+    /// The index does not exist. Do not retry.
     IndexNotFound = 1,
+    /// The source does not exist. Do not retry.
     SourceNotFound = 2,
+    /// An unexpected error occurred. Retrying may succeed.
     Internal = 3,
+    /// No shard is open for the source yet. Retry with backoff.
     NoShardsAvailable = 4,
+    /// The shards of the source are rate limited. Retry with backoff.
     RateLimited = 5,
+    /// The ingesters are running out of memory or disk. Retry with backoff.
     ResourceExhausted = 6,
 }
 impl IngestFailureReason {
@@ -118,7 +133,10 @@ use tower::{Layer, Service, ServiceExt};
 #[async_trait::async_trait]
 pub trait IngestRouterService: std::fmt::Debug + dyn_clone::DynClone + Send + Sync + 'static {
     /// Ingests batches of documents for one or multiple indexes.
-    /// TODO: Describe error cases and how to handle them.
+    /// Subrequests succeed or fail independently: a failed subrequest is reported
+    /// in `failures` and does not fail the other subrequests. The request as a
+    /// whole fails only if the router cannot process it, in which case the status
+    /// code tells whether it can be retried.
     async fn ingest(
         &mut self,
         request: IngestRequestV2,
@@ -496,7 +514,7 @@ where
         request: IngestRequestV2,
     ) -> crate::ingest::IngestV2Result<IngestResponseV2> {
         self.inner
-            .ingest(request)
+            .ingest(quickwit_common::deadline::make_grpc_request(request))
             .await
             .map(|response| response.into_inner())
             .map_err(|error| error.into())
@@ -617,7 +635,10 @@ pub mod ingest_router_service_grpc_client {
             self
         }
         /// Ingests batches of documents for one or multiple indexes.
-        /// TODO: Describe error cases and how to handle them.
+        /// Subrequests succeed or fail independently: a failed subrequest is reported
+    /// in `failures` and does not fail the other subrequests. The request as a
+    /// whole fails only if the router cannot process it, in which case the status
+    /// code tells whether it can be retried.
         pub async fn ingest(
             &mut self,
             request: impl tonic::IntoRequest<super::IngestRequestV2>,
@@ -658,7 +679,10 @@ pub mod ingest_router_service_grpc_server {
     #[async_trait]
     pub trait IngestRouterServiceGrpc: Send + Sync + 'static {
         /// Ingests batches of documents for one or multiple indexes.
-        /// TODO: Describe error cases and how to handle them.
+        /// Subrequests succeed or fail independently: a failed subrequest is reported
+    /// in `failures` and does not fail the other subrequests. The request as a
+    /// whole fails only if the router cannot process it, in which case the status
+    /// code tells whether it can be retried.
         async fn ingest(
             &self,
             request: tonic::Request<super::IngestRequestV2>,
@@ -50,6 +50,10 @@ pub struct Shard {
     #[prost(string, optional, tag = "10")]
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub publish_token: ::core::option::Option<::prost::alloc::string::String>,
+    /// Priority class of the shard.
+    #[prost(enumeration = "ShardPriority", tag = "11")]
+    #[serde(default, skip_serializing_if = "crate::ingest::is_realtime_priority")]
+    pub priority: i32,
 }
 /// A group of shards belonging to the same index and source.
 #[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
@@ -135,3 +139,34 @@ impl ShardState {
         }
     }
 }
+/// Priority class of a shard, inherited from the priority of its source.
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ShardPriority {
+    /// The shard receives live data and is never starved by backfill shards.
+    Realtime = 0,
+    /// The shard receives historical data and yields WAL capacity to realtime shards under pressure.
+    Backfill = 1,
+}
+impl ShardPriority {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            ShardPriority::Realtime => "SHARD_PRIORITY_REALTIME",
+            ShardPriority::Backfill => "SHARD_PRIORITY_BACKFILL",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "SHARD_PRIORITY_REALTIME" => Some(Self::Realtime),
+            "SHARD_PRIORITY_BACKFILL" => Some(Self::Backfill),
+            _ => None,
+        }
+    }
+}
@@ -60,6 +60,34 @@ pub struct IndexMetadataResponse {
 #[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateIndexRequest {
+    #[prost(string, tag = "1")]
+    pub index_uid: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub doc_mapping_json: ::prost::alloc::string::String,
+    /// Author of the change, if provided by the client.
+    #[prost(string, optional, tag = "3")]
+    pub author: ::core::option::Option<::prost::alloc::string::String>,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FreezeIndexRequest {
+    #[prost(string, tag = "1")]
+    pub index_uid: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SetMergesPausedRequest {
+    #[prost(string, tag = "1")]
+    pub index_uid: ::prost::alloc::string::String,
+    #[prost(bool, tag = "2")]
+    pub merges_paused: bool,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ListSplitsRequest {
     /// Predicate used to filter splits.
     /// The predicate is expressed as a JSON serialized
@@ -249,6 +277,106 @@ pub struct ListDeleteTasksResponse {
 #[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct IndexEvent {
+    #[prost(string, tag = "1")]
+    pub index_uid: ::prost::alloc::string::String,
+    /// Type of the event, for instance `source_disabled` or `retention_applied`.
+    #[prost(string, tag = "2")]
+    pub event_type: ::prost::alloc::string::String,
+    /// Human-readable description of the event.
+    #[prost(string, tag = "3")]
+    pub message: ::prost::alloc::string::String,
+    /// Unix timestamp (in seconds) at which the event was recorded by the metastore.
+    #[prost(int64, tag = "4")]
+    pub timestamp: i64,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AddIndexEventRequest {
+    #[prost(string, tag = "1")]
+    pub index_uid: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub event_type: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub message: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListIndexEventsRequest {
+    #[prost(string, tag = "1")]
+    pub index_uid: ::prost::alloc::string::String,
+    /// Maximum number of events to return. All the events are returned if unset.
+    #[prost(uint32, optional, tag = "2")]
+    pub limit: ::core::option::Option<u32>,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListIndexEventsResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub events: ::prost::alloc::vec::Vec<IndexEvent>,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct IndexConfigVersion {
+    #[prost(string, tag = "1")]
+    pub index_uid: ::prost::alloc::string::String,
+    /// Version number, incremented every time the config of the index or of one of its sources
+    /// changes.
+    #[prost(uint64, tag = "2")]
+    pub version: u64,
+    /// Serialized `IndexConfig`.
+    #[prost(string, tag = "3")]
+    pub index_config_json: ::prost::alloc::string::String,
+    /// Serialized `Vec<SourceConfig>`.
+    #[prost(string, tag = "4")]
+    pub source_configs_json: ::prost::alloc::string::String,
+    /// Human-readable description of the change, for instance `doc mapping updated`.
+    #[prost(string, tag = "5")]
+    pub description: ::prost::alloc::string::String,
+    /// Author of the change, if provided by the client.
+    #[prost(string, optional, tag = "6")]
+    pub author: ::core::option::Option<::prost::alloc::string::String>,
+    /// Unix timestamp (in seconds) at which the version was recorded by the metastore.
+    #[prost(int64, tag = "7")]
+    pub timestamp: i64,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListIndexConfigVersionsRequest {
+    #[prost(string, tag = "1")]
+    pub index_uid: ::prost::alloc::string::String,
+    /// Maximum number of versions to return. All the versions are returned if unset.
+    #[prost(uint32, optional, tag = "2")]
+    pub limit: ::core::option::Option<u32>,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListIndexConfigVersionsResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub versions: ::prost::alloc::vec::Vec<IndexConfigVersion>,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RollbackIndexConfigRequest {
+    #[prost(string, tag = "1")]
+    pub index_uid: ::prost::alloc::string::String,
+    /// Version to restore the doc mapping of.
+    #[prost(uint64, tag = "2")]
+    pub version: u64,
+    /// Author of the rollback, if provided by the client.
+    #[prost(string, optional, tag = "3")]
+    pub author: ::core::option::Option<::prost::alloc::string::String>,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct OpenShardsRequest {
     #[prost(message, repeated, tag = "1")]
     pub subrequests: ::prost::alloc::vec::Vec<OpenShardsSubrequest>,
@@ -269,6 +397,8 @@ pub struct OpenShardsSubrequest {
     pub follower_id: ::core::option::Option<::prost::alloc::string::String>,
     #[prost(uint64, tag = "6")]
     pub next_shard_id: u64,
+    #[prost(enumeration = "super::ingest::ShardPriority", tag = "7")]
+    pub priority: i32,
 }
 #[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -410,6 +540,7 @@ pub enum SourceType {
     Pulsar = 9,
     Vec = 10,
     Void = 11,
+    AzureEventHubs = 12,
 }
 impl SourceType {
     /// String value of the enum field names used in the ProtoBuf definition.
@@ -430,6 +561,7 @@ impl SourceType {
             SourceType::Pulsar => "SOURCE_TYPE_PULSAR",
             SourceType::Vec => "SOURCE_TYPE_VEC",
             SourceType::Void => "SOURCE_TYPE_VOID",
+            SourceType::AzureEventHubs => "SOURCE_TYPE_AZURE_EVENT_HUBS",
         }
     }
     /// Creates an enum from field names used in the ProtoBuf definition.
@@ -447,6 +579,7 @@ impl SourceType {
             "SOURCE_TYPE_PULSAR" => Some(Self::Pulsar),
             "SOURCE_TYPE_VEC" => Some(Self::Vec),
             "SOURCE_TYPE_VOID" => Some(Self::Void),
+            "SOURCE_TYPE_AZURE_EVENT_HUBS" => Some(Self::AzureEventHubs),
             _ => None,
         }
     }
@@ -466,6 +599,21 @@ impl PrometheusLabels<1> for IndexMetadataRequest {
         OwnedPrometheusLabels::new([std::borrow::Cow::Borrowed("index_metadata")])
     }
 }
+impl PrometheusLabels<1> for UpdateIndexRequest {
+    fn labels(&self) -> OwnedPrometheusLabels<1usize> {
+        OwnedPrometheusLabels::new([std::borrow::Cow::Borrowed("update_index")])
+    }
+}
+impl PrometheusLabels<1> for FreezeIndexRequest {
+    fn labels(&self) -> OwnedPrometheusLabels<1usize> {
+        OwnedPrometheusLabels::new([std::borrow::Cow::Borrowed("freeze_index")])
+    }
+}
+impl PrometheusLabels<1> for SetMergesPausedRequest {
+    fn labels(&self) -> OwnedPrometheusLabels<1usize> {
+        OwnedPrometheusLabels::new([std::borrow::Cow::Borrowed("set_merges_paused")])
+    }
+}
 impl PrometheusLabels<1> for ListIndexesMetadataRequest {
     fn labels(&self) -> OwnedPrometheusLabels<1usize> {
         OwnedPrometheusLabels::new([std::borrow::Cow::Borrowed("list_indexes_metadata")])
@@ -552,6 +700,26 @@ impl PrometheusLabels<1> for ListStaleSplitsRequest {
         OwnedPrometheusLabels::new([std::borrow::Cow::Borrowed("list_stale_splits")])
     }
 }
+impl PrometheusLabels<1> for AddIndexEventRequest {
+    fn labels(&self) -> OwnedPrometheusLabels<1usize> {
+        OwnedPrometheusLabels::new([std::borrow::Cow::Borrowed("add_index_event")])
+    }
+}
+impl PrometheusLabels<1> for ListIndexEventsRequest {
+    fn labels(&self) -> OwnedPrometheusLabels<1usize> {
+        OwnedPrometheusLabels::new([std::borrow::Cow::Borrowed("list_index_events")])
+    }
+}
+impl PrometheusLabels<1> for ListIndexConfigVersionsRequest {
+    fn labels(&self) -> OwnedPrometheusLabels<1usize> {
+        OwnedPrometheusLabels::new([std::borrow::Cow::Borrowed("list_index_config_versions")])
+    }
+}
+impl PrometheusLabels<1> for RollbackIndexConfigRequest {
+    fn labels(&self) -> OwnedPrometheusLabels<1usize> {
+        OwnedPrometheusLabels::new([std::borrow::Cow::Borrowed("rollback_index_config")])
+    }
+}
 impl PrometheusLabels<1> for OpenShardsRequest {
     fn labels(&self) -> OwnedPrometheusLabels<1usize> {
         OwnedPrometheusLabels::new([std::borrow::Cow::Borrowed("open_shards")])
@@ -591,6 +759,21 @@ pub trait MetastoreService: std::fmt::Debug + dyn_clone::DynClone + Send + Sync
         &mut self,
         request: IndexMetadataRequest,
     ) -> crate::metastore::MetastoreResult<IndexMetadataResponse>;
+    /// Updates the doc mapping of an index. Only additive changes are supported.
+    async fn update_index(
+        &mut self,
+        request: UpdateIndexRequest,
+    ) -> crate::metastore::MetastoreResult<IndexMetadataResponse>;
+    /// Freezes an index: disables all its sources and rejects subsequent writes.
+    async fn freeze_index(
+        &mut self,
+        request: FreezeIndexRequest,
+    ) -> crate::metastore::MetastoreResult<IndexMetadataResponse>;
+    /// Pauses or resumes the merges of an index.
+    async fn set_merges_paused(
+        &mut self,
+        request: SetMergesPausedRequest,
+    ) -> crate::metastore::MetastoreResult<IndexMetadataResponse>;
     /// Gets an indexes metadatas.
     async fn list_indexes_metadata(
         &mut self,
@@ -671,6 +854,26 @@ pub trait MetastoreService: std::fmt::Debug + dyn_clone::DynClone + Send + Sync
         &mut self,
         request: ListStaleSplitsRequest,
     ) -> crate::metastore::MetastoreResult<ListSplitsResponse>;
+    /// Records an event in the activity feed of an index.
+    async fn add_index_event(
+        &mut self,
+        request: AddIndexEventRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse>;
+    /// Lists the most recent events of an index, most recent first.
+    async fn list_index_events(
+        &mut self,
+        request: ListIndexEventsRequest,
+    ) -> crate::metastore::MetastoreResult<ListIndexEventsResponse>;
+    /// Lists the versions of the config of an index, most recent first.
+    async fn list_index_config_versions(
+        &mut self,
+        request: ListIndexConfigVersionsRequest,
+    ) -> crate::metastore::MetastoreResult<ListIndexConfigVersionsResponse>;
+    /// Restores the doc mapping of an index to the one of a previous config version.
+    async fn rollback_index_config(
+        &mut self,
+        request: RollbackIndexConfigRequest,
+    ) -> crate::metastore::MetastoreResult<IndexMetadataResponse>;
     /// Shard API
     ///
     /// Note that for the file-backed metastore implementation, the requests are not processed atomically.
@@ -792,6 +995,24 @@ impl MetastoreService for MetastoreServiceClient {
     ) -> crate::metastore::MetastoreResult<IndexMetadataResponse> {
         self.inner.index_metadata(request).await
     }
+    async fn update_index(
+        &mut self,
+        request: UpdateIndexRequest,
+    ) -> crate::metastore::MetastoreResult<IndexMetadataResponse> {
+        self.inner.update_index(request).await
+    }
+    async fn freeze_index(
+        &mut self,
+        request: FreezeIndexRequest,
+    ) -> crate::metastore::MetastoreResult<IndexMetadataResponse> {
+        self.inner.freeze_index(request).await
+    }
+    async fn set_merges_paused(
+        &mut self,
+        request: SetMergesPausedRequest,
+    ) -> crate::metastore::MetastoreResult<IndexMetadataResponse> {
+        self.inner.set_merges_paused(request).await
+    }
     async fn list_indexes_metadata(
         &mut self,
         request: ListIndexesMetadataRequest,
@@ -888,6 +1109,30 @@ impl MetastoreService for MetastoreServiceClient {
     ) -> crate::metastore::MetastoreResult<ListSplitsResponse> {
         self.inner.list_stale_splits(request).await
     }
+    async fn add_index_event(
+        &mut self,
+        request: AddIndexEventRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.inner.add_index_event(request).await
+    }
+    async fn list_index_events(
+        &mut self,
+        request: ListIndexEventsRequest,
+    ) -> crate::metastore::MetastoreResult<ListIndexEventsResponse> {
+        self.inner.list_index_events(request).await
+    }
+    async fn list_index_config_versions(
+        &mut self,
+        request: ListIndexConfigVersionsRequest,
+    ) -> crate::metastore::MetastoreResult<ListIndexConfigVersionsResponse> {
+        self.inner.list_index_config_versions(request).await
+    }
+    async fn rollback_index_config(
+        &mut self,
+        request: RollbackIndexConfigRequest,
+    ) -> crate::metastore::MetastoreResult<IndexMetadataResponse> {
+        self.inner.rollback_index_config(request).await
+    }
     async fn open_shards(
         &mut self,
         request: OpenShardsRequest,
@@ -940,6 +1185,24 @@ pub mod metastore_service_mock {
         ) -> crate::metastore::MetastoreResult<super::IndexMetadataResponse> {
             self.inner.lock().await.index_metadata(request).await
         }
+        async fn update_index(
+            &mut self,
+            request: super::UpdateIndexRequest,
+        ) -> crate::metastore::MetastoreResult<super::IndexMetadataResponse> {
+            self.inner.lock().await.update_index(request).await
+        }
+        async fn freeze_index(
+            &mut self,
+            request: super::FreezeIndexRequest,
+        ) -> crate::metastore::MetastoreResult<super::IndexMetadataResponse> {
+            self.inner.lock().await.freeze_index(request).await
+        }
+        async fn set_merges_paused(
+            &mut self,
+            request: super::SetMergesPausedRequest,
+        ) -> crate::metastore::MetastoreResult<super::IndexMetadataResponse> {
+            self.inner.lock().await.set_merges_paused(request).await
+        }
         async fn list_indexes_metadata(
             &mut self,
             request: super::ListIndexesMetadataRequest,
@@ -1040,6 +1303,30 @@ pub mod metastore_service_mock {
         ) -> crate::metastore::MetastoreResult<super::ListSplitsResponse> {
             self.inner.lock().await.list_stale_splits(request).await
         }
+        async fn add_index_event(
+            &mut self,
+            request: super::AddIndexEventRequest,
+        ) -> crate::metastore::MetastoreResult<super::EmptyResponse> {
+            self.inner.lock().await.add_index_event(request).await
+        }
+        async fn list_index_events(
+            &mut self,
+            request: super::ListIndexEventsRequest,
+        ) -> crate::metastore::MetastoreResult<super::ListIndexEventsResponse> {
+            self.inner.lock().await.list_index_events(request).await
+        }
+        async fn list_index_config_versions(
+            &mut self,
+            request: super::ListIndexConfigVersionsRequest,
+        ) -> crate::metastore::MetastoreResult<super::ListIndexConfigVersionsResponse> {
+            self.inner.lock().await.list_index_config_versions(request).await
+        }
+        async fn rollback_index_config(
+            &mut self,
+            request: super::RollbackIndexConfigRequest,
+        ) -> crate::metastore::MetastoreResult<super::IndexMetadataResponse> {
+            self.inner.lock().await.rollback_index_config(request).await
+        }
         async fn open_shards(
             &mut self,
             request: super::OpenShardsRequest,
@@ -1115,6 +1402,54 @@ impl tower::Service<IndexMetadataRequest> for Box<dyn MetastoreService> {
         Box::pin(fut)
     }
 }
+impl tower::Service<UpdateIndexRequest> for Box<dyn MetastoreService> {
+    type Response = IndexMetadataResponse;
+    type Error = crate::metastore::MetastoreError;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+    fn call(&mut self, request: UpdateIndexRequest) -> Self::Future {
+        let mut svc = self.clone();
+        let fut = async move { svc.update_index(request).await };
+        Box::pin(fut)
+    }
+}
+impl tower::Service<FreezeIndexRequest> for Box<dyn MetastoreService> {
+    type Response = IndexMetadataResponse;
+    type Error = crate::metastore::MetastoreError;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+    fn call(&mut self, request: FreezeIndexRequest) -> Self::Future {
+        let mut svc = self.clone();
+        let fut = async move { svc.freeze_index(request).await };
+        Box::pin(fut)
+    }
+}
+impl tower::Service<SetMergesPausedRequest> for Box<dyn MetastoreService> {
+    type Response = IndexMetadataResponse;
+    type Error = crate::metastore::MetastoreError;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+    fn call(&mut self, request: SetMergesPausedRequest) -> Self::Future {
+        let mut svc = self.clone();
+        let fut = async move { svc.set_merges_paused(request).await };
+        Box::pin(fut)
+    }
+}
 impl tower::Service<ListIndexesMetadataRequest> for Box<dyn MetastoreService> {
     type Response = ListIndexesMetadataResponse;
     type Error = crate::metastore::MetastoreError;
@@ -1371,6 +1706,70 @@ impl tower::Service<ListStaleSplitsRequest> for Box<dyn MetastoreService> {
         Box::pin(fut)
     }
 }
+impl tower::Service<AddIndexEventRequest> for Box<dyn MetastoreService> {
+    type Response = EmptyResponse;
+    type Error = crate::metastore::MetastoreError;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+    fn call(&mut self, request: AddIndexEventRequest) -> Self::Future {
+        let mut svc = self.clone();
+        let fut = async move { svc.add_index_event(request).await };
+        Box::pin(fut)
+    }
+}
+impl tower::Service<ListIndexEventsRequest> for Box<dyn MetastoreService> {
+    type Response = ListIndexEventsResponse;
+    type Error = crate::metastore::MetastoreError;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+    fn call(&mut self, request: ListIndexEventsRequest) -> Self::Future {
+        let mut svc = self.clone();
+        let fut = async move { svc.list_index_events(request).await };
+        Box::pin(fut)
+    }
+}
+impl tower::Service<ListIndexConfigVersionsRequest> for Box<dyn MetastoreService> {
+    type Response = ListIndexConfigVersionsResponse;
+    type Error = crate::metastore::MetastoreError;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+    fn call(&mut self, request: ListIndexConfigVersionsRequest) -> Self::Future {
+        let mut svc = self.clone();
+        let fut = async move { svc.list_index_config_versions(request).await };
+        Box::pin(fut)
+    }
+}
+impl tower::Service<RollbackIndexConfigRequest> for Box<dyn MetastoreService> {
+    type Response = IndexMetadataResponse;
+    type Error = crate::metastore::MetastoreError;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+    fn call(&mut self, request: RollbackIndexConfigRequest) -> Self::Future {
+        let mut svc = self.clone();
+        let fut = async move { svc.rollback_index_config(request).await };
+        Box::pin(fut)
+    }
+}
 impl tower::Service<OpenShardsRequest> for Box<dyn MetastoreService> {
     type Response = OpenShardsResponse;
     type Error = crate::metastore::MetastoreError;
@@ -1449,6 +1848,21 @@ struct MetastoreServiceTowerBlock {
         IndexMetadataResponse,
         crate::metastore::MetastoreError,
     >,
+    update_index_svc: quickwit_common::tower::BoxService<
+        UpdateIndexRequest,
+        IndexMetadataResponse,
+        crate::metastore::MetastoreError,
+    >,
+    freeze_index_svc: quickwit_common::tower::BoxService<
+        FreezeIndexRequest,
+        IndexMetadataResponse,
+        crate::metastore::MetastoreError,
+    >,
+    set_merges_paused_svc: quickwit_common::tower::BoxService<
+        SetMergesPausedRequest,
+        IndexMetadataResponse,
+        crate::metastore::MetastoreError,
+    >,
     list_indexes_metadata_svc: quickwit_common::tower::BoxService<
         ListIndexesMetadataRequest,
         ListIndexesMetadataResponse,
@@ -1529,6 +1943,26 @@ struct MetastoreServiceTowerBlock {
         ListSplitsResponse,
         crate::metastore::MetastoreError,
     >,
+    add_index_event_svc: quickwit_common::tower::BoxService<
+        AddIndexEventRequest,
+        EmptyResponse,
+        crate::metastore::MetastoreError,
+    >,
+    list_index_events_svc: quickwit_common::tower::BoxService<
+        ListIndexEventsRequest,
+        ListIndexEventsResponse,
+        crate::metastore::MetastoreError,
+    >,
+    list_index_config_versions_svc: quickwit_common::tower::BoxService<
+        ListIndexConfigVersionsRequest,
+        ListIndexConfigVersionsResponse,
+        crate::metastore::MetastoreError,
+    >,
+    rollback_index_config_svc: quickwit_common::tower::BoxService<
+        RollbackIndexConfigRequest,
+        IndexMetadataResponse,
+        crate::metastore::MetastoreError,
+    >,
     open_shards_svc: quickwit_common::tower::BoxService<
         OpenShardsRequest,
         OpenShardsResponse,
@@ -1556,6 +1990,9 @@ impl Clone for MetastoreServiceTowerBlock {
             inner: self.inner.clone(),
             create_index_svc: self.create_index_svc.clone(),
             index_metadata_svc: self.index_metadata_svc.clone(),
+            update_index_svc: self.update_index_svc.clone(),
+            freeze_index_svc: self.freeze_index_svc.clone(),
+            set_merges_paused_svc: self.set_merges_paused_svc.clone(),
             list_indexes_metadata_svc: self.list_indexes_metadata_svc.clone(),
             delete_index_svc: self.delete_index_svc.clone(),
             list_splits_svc: self.list_splits_svc.clone(),
@@ -1574,6 +2011,10 @@ impl Clone for MetastoreServiceTowerBlock {
                 .clone(),
             list_delete_tasks_svc: self.list_delete_tasks_svc.clone(),
             list_stale_splits_svc: self.list_stale_splits_svc.clone(),
+            add_index_event_svc: self.add_index_event_svc.clone(),
+            list_index_events_svc: self.list_index_events_svc.clone(),
+            list_index_config_versions_svc: self.list_index_config_versions_svc.clone(),
+            rollback_index_config_svc: self.rollback_index_config_svc.clone(),
             open_shards_svc: self.open_shards_svc.clone(),
             acquire_shards_svc: self.acquire_shards_svc.clone(),
             delete_shards_svc: self.delete_shards_svc.clone(),
@@ -1595,9 +2036,27 @@ impl MetastoreService for MetastoreServiceTowerBlock {
     ) -> crate::metastore::MetastoreResult<IndexMetadataResponse> {
         self.index_metadata_svc.ready().await?.call(request).await
     }
-    async fn list_indexes_metadata(
+    async fn update_index(
         &mut self,
-        request: ListIndexesMetadataRequest,
+        request: UpdateIndexRequest,
+    ) -> crate::metastore::MetastoreResult<IndexMetadataResponse> {
+        self.update_index_svc.ready().await?.call(request).await
+    }
+    async fn freeze_index(
+        &mut self,
+        request: FreezeIndexRequest,
+    ) -> crate::metastore::MetastoreResult<IndexMetadataResponse> {
+        self.freeze_index_svc.ready().await?.call(request).await
+    }
+    async fn set_merges_paused(
+        &mut self,
+        request: SetMergesPausedRequest,
+    ) -> crate::metastore::MetastoreResult<IndexMetadataResponse> {
+        self.set_merges_paused_svc.ready().await?.call(request).await
+    }
+    async fn list_indexes_metadata(
+        &mut self,
+        request: ListIndexesMetadataRequest,
     ) -> crate::metastore::MetastoreResult<ListIndexesMetadataResponse> {
         self.list_indexes_metadata_svc.ready().await?.call(request).await
     }
@@ -1691,6 +2150,30 @@ impl MetastoreService for MetastoreServiceTowerBlock {
     ) -> crate::metastore::MetastoreResult<ListSplitsResponse> {
         self.list_stale_splits_svc.ready().await?.call(request).await
     }
+    async fn add_index_event(
+        &mut self,
+        request: AddIndexEventRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.add_index_event_svc.ready().await?.call(request).await
+    }
+    async fn list_index_events(
+        &mut self,
+        request: ListIndexEventsRequest,
+    ) -> crate::metastore::MetastoreResult<ListIndexEventsResponse> {
+        self.list_index_events_svc.ready().await?.call(request).await
+    }
+    async fn list_index_config_versions(
+        &mut self,
+        request: ListIndexConfigVersionsRequest,
+    ) -> crate::metastore::MetastoreResult<ListIndexConfigVersionsResponse> {
+        self.list_index_config_versions_svc.ready().await?.call(request).await
+    }
+    async fn rollback_index_config(
+        &mut self,
+        request: RollbackIndexConfigRequest,
+    ) -> crate::metastore::MetastoreResult<IndexMetadataResponse> {
+        self.rollback_index_config_svc.ready().await?.call(request).await
+    }
     async fn open_shards(
         &mut self,
         request: OpenShardsRequest,
@@ -1743,6 +2226,33 @@ pub struct MetastoreServiceTowerBlockBuilder {
         >,
     >,
     #[allow(clippy::type_complexity)]
+    update_index_layer: Option<
+        quickwit_common::tower::BoxLayer<
+            Box<dyn MetastoreService>,
+            UpdateIndexRequest,
+            IndexMetadataResponse,
+            crate::metastore::MetastoreError,
+        >,
+    >,
+    #[allow(clippy::type_complexity)]
+    freeze_index_layer: Option<
+        quickwit_common::tower::BoxLayer<
+            Box<dyn MetastoreService>,
+            FreezeIndexRequest,
+            IndexMetadataResponse,
+            crate::metastore::MetastoreError,
+        >,
+    >,
+    #[allow(clippy::type_complexity)]
+    set_merges_paused_layer: Option<
+        quickwit_common::tower::BoxLayer<
+            Box<dyn MetastoreService>,
+            SetMergesPausedRequest,
+            IndexMetadataResponse,
+            crate::metastore::MetastoreError,
+        >,
+    >,
+    #[allow(clippy::type_complexity)]
     list_indexes_metadata_layer: Option<
         quickwit_common::tower::BoxLayer<
             Box<dyn MetastoreService>,
@@ -1887,6 +2397,42 @@ pub struct MetastoreServiceTowerBlockBuilder {
         >,
     >,
     #[allow(clippy::type_complexity)]
+    add_index_event_layer: Option<
+        quickwit_common::tower::BoxLayer<
+            Box<dyn MetastoreService>,
+            AddIndexEventRequest,
+            EmptyResponse,
+            crate::metastore::MetastoreError,
+        >,
+    >,
+    #[allow(clippy::type_complexity)]
+    list_index_events_layer: Option<
+        quickwit_common::tower::BoxLayer<
+            Box<dyn MetastoreService>,
+            ListIndexEventsRequest,
+            ListIndexEventsResponse,
+            crate::metastore::MetastoreError,
+        >,
+    >,
+    #[allow(clippy::type_complexity)]
+    list_index_config_versions_layer: Option<
+        quickwit_common::tower::BoxLayer<
+            Box<dyn MetastoreService>,
+            ListIndexConfigVersionsRequest,
+            ListIndexConfigVersionsResponse,
+            crate::metastore::MetastoreError,
+        >,
+    >,
+    #[allow(clippy::type_complexity)]
+    rollback_index_config_layer: Option<
+        quickwit_common::tower::BoxLayer<
+            Box<dyn MetastoreService>,
+            RollbackIndexConfigRequest,
+            IndexMetadataResponse,
+            crate::metastore::MetastoreError,
+        >,
+    >,
+    #[allow(clippy::type_complexity)]
     open_shards_layer: Option<
         quickwit_common::tower::BoxLayer<
             Box<dyn MetastoreService>,
@@ -1939,6 +2485,24 @@ impl MetastoreServiceTowerBlockBuilder {
                 Error = crate::metastore::MetastoreError,
             > + Clone + Send + Sync + 'static,
         <L::Service as tower::Service<IndexMetadataRequest>>::Future: Send + 'static,
+        L::Service: tower::Service<
+                UpdateIndexRequest,
+                Response = IndexMetadataResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<UpdateIndexRequest>>::Future: Send + 'static,
+        L::Service: tower::Service<
+                FreezeIndexRequest,
+                Response = IndexMetadataResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<FreezeIndexRequest>>::Future: Send + 'static,
+        L::Service: tower::Service<
+                SetMergesPausedRequest,
+                Response = IndexMetadataResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<SetMergesPausedRequest>>::Future: Send + 'static,
         L::Service: tower::Service<
                 ListIndexesMetadataRequest,
                 Response = ListIndexesMetadataResponse,
@@ -2043,6 +2607,30 @@ impl MetastoreServiceTowerBlockBuilder {
                 Error = crate::metastore::MetastoreError,
             > + Clone + Send + Sync + 'static,
         <L::Service as tower::Service<ListStaleSplitsRequest>>::Future: Send + 'static,
+        L::Service: tower::Service<
+                AddIndexEventRequest,
+                Response = EmptyResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<AddIndexEventRequest>>::Future: Send + 'static,
+        L::Service: tower::Service<
+                ListIndexEventsRequest,
+                Response = ListIndexEventsResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<ListIndexEventsRequest>>::Future: Send + 'static,
+        L::Service: tower::Service<
+                ListIndexConfigVersionsRequest,
+                Response = ListIndexConfigVersionsResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<ListIndexConfigVersionsRequest>>::Future: Send + 'static,
+        L::Service: tower::Service<
+                RollbackIndexConfigRequest,
+                Response = IndexMetadataResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<RollbackIndexConfigRequest>>::Future: Send + 'static,
         L::Service: tower::Service<
                 OpenShardsRequest,
                 Response = OpenShardsResponse,
@@ -2076,6 +2664,18 @@ impl MetastoreServiceTowerBlockBuilder {
             .index_metadata_layer = Some(
             quickwit_common::tower::BoxLayer::new(layer.clone()),
         );
+        self
+            .update_index_layer = Some(
+            quickwit_common::tower::BoxLayer::new(layer.clone()),
+        );
+        self
+            .freeze_index_layer = Some(
+            quickwit_common::tower::BoxLayer::new(layer.clone()),
+        );
+        self
+            .set_merges_paused_layer = Some(
+            quickwit_common::tower::BoxLayer::new(layer.clone()),
+        );
         self
             .list_indexes_metadata_layer = Some(
             quickwit_common::tower::BoxLayer::new(layer.clone()),
@@ -2140,6 +2740,22 @@ impl MetastoreServiceTowerBlockBuilder {
             .list_stale_splits_layer = Some(
             quickwit_common::tower::BoxLayer::new(layer.clone()),
         );
+        self
+            .add_index_event_layer = Some(
+            quickwit_common::tower::BoxLayer::new(layer.clone()),
+        );
+        self
+            .list_index_events_layer = Some(
+            quickwit_common::tower::BoxLayer::new(layer.clone()),
+        );
+        self
+            .list_index_config_versions_layer = Some(
+            quickwit_common::tower::BoxLayer::new(layer.clone()),
+        );
+        self
+            .rollback_index_config_layer = Some(
+            quickwit_common::tower::BoxLayer::new(layer.clone()),
+        );
         self
             .open_shards_layer = Some(
             quickwit_common::tower::BoxLayer::new(layer.clone()),
@@ -2181,6 +2797,45 @@ impl MetastoreServiceTowerBlockBuilder {
         self.index_metadata_layer = Some(quickwit_common::tower::BoxLayer::new(layer));
         self
     }
+    pub fn update_index_layer<L>(mut self, layer: L) -> Self
+    where
+        L: tower::Layer<Box<dyn MetastoreService>> + Send + Sync + 'static,
+        L::Service: tower::Service<
+                UpdateIndexRequest,
+                Response = IndexMetadataResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<UpdateIndexRequest>>::Future: Send + 'static,
+    {
+        self.update_index_layer = Some(quickwit_common::tower::BoxLayer::new(layer));
+        self
+    }
+    pub fn freeze_index_layer<L>(mut self, layer: L) -> Self
+    where
+        L: tower::Layer<Box<dyn MetastoreService>> + Send + Sync + 'static,
+        L::Service: tower::Service<
+                FreezeIndexRequest,
+                Response = IndexMetadataResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<FreezeIndexRequest>>::Future: Send + 'static,
+    {
+        self.freeze_index_layer = Some(quickwit_common::tower::BoxLayer::new(layer));
+        self
+    }
+    pub fn set_merges_paused_layer<L>(mut self, layer: L) -> Self
+    where
+        L: tower::Layer<Box<dyn MetastoreService>> + Send + Sync + 'static,
+        L::Service: tower::Service<
+                SetMergesPausedRequest,
+                Response = IndexMetadataResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<SetMergesPausedRequest>>::Future: Send + 'static,
+    {
+        self.set_merges_paused_layer = Some(quickwit_common::tower::BoxLayer::new(layer));
+        self
+    }
     pub fn list_indexes_metadata_layer<L>(mut self, layer: L) -> Self
     where
         L: tower::Layer<Box<dyn MetastoreService>> + Send + Sync + 'static,
@@ -2421,6 +3076,58 @@ impl MetastoreServiceTowerBlockBuilder {
         );
         self
     }
+    pub fn add_index_event_layer<L>(mut self, layer: L) -> Self
+    where
+        L: tower::Layer<Box<dyn MetastoreService>> + Send + Sync + 'static,
+        L::Service: tower::Service<
+                AddIndexEventRequest,
+                Response = EmptyResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<AddIndexEventRequest>>::Future: Send + 'static,
+    {
+        self.add_index_event_layer = Some(quickwit_common::tower::BoxLayer::new(layer));
+        self
+    }
+    pub fn list_index_events_layer<L>(mut self, layer: L) -> Self
+    where
+        L: tower::Layer<Box<dyn MetastoreService>> + Send + Sync + 'static,
+        L::Service: tower::Service<
+                ListIndexEventsRequest,
+                Response = ListIndexEventsResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<ListIndexEventsRequest>>::Future: Send + 'static,
+    {
+        self.list_index_events_layer = Some(quickwit_common::tower::BoxLayer::new(layer));
+        self
+    }
+    pub fn list_index_config_versions_layer<L>(mut self, layer: L) -> Self
+    where
+        L: tower::Layer<Box<dyn MetastoreService>> + Send + Sync + 'static,
+        L::Service: tower::Service<
+                ListIndexConfigVersionsRequest,
+                Response = ListIndexConfigVersionsResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<ListIndexConfigVersionsRequest>>::Future: Send + 'static,
+    {
+        self.list_index_config_versions_layer = Some(quickwit_common::tower::BoxLayer::new(layer));
+        self
+    }
+    pub fn rollback_index_config_layer<L>(mut self, layer: L) -> Self
+    where
+        L: tower::Layer<Box<dyn MetastoreService>> + Send + Sync + 'static,
+        L::Service: tower::Service<
+                RollbackIndexConfigRequest,
+                Response = IndexMetadataResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<RollbackIndexConfigRequest>>::Future: Send + 'static,
+    {
+        self.rollback_index_config_layer = Some(quickwit_common::tower::BoxLayer::new(layer));
+        self
+    }
     pub fn open_shards_layer<L>(mut self, layer: L) -> Self
     where
         L: tower::Layer<Box<dyn MetastoreService>> + Send + Sync + 'static,
@@ -2520,6 +3227,21 @@ impl MetastoreServiceTowerBlockBuilder {
         } else {
             quickwit_common::tower::BoxService::new(boxed_instance.clone())
         };
+        let update_index_svc = if let Some(layer) = self.update_index_layer {
+            layer.layer(boxed_instance.clone())
+        } else {
+            quickwit_common::tower::BoxService::new(boxed_instance.clone())
+        };
+        let freeze_index_svc = if let Some(layer) = self.freeze_index_layer {
+            layer.layer(boxed_instance.clone())
+        } else {
+            quickwit_common::tower::BoxService::new(boxed_instance.clone())
+        };
+        let set_merges_paused_svc = if let Some(layer) = self.set_merges_paused_layer {
+            layer.layer(boxed_instance.clone())
+        } else {
+            quickwit_common::tower::BoxService::new(boxed_instance.clone())
+        };
         let list_indexes_metadata_svc = if let Some(layer)
             = self.list_indexes_metadata_layer
         {
@@ -2609,6 +3331,26 @@ impl MetastoreServiceTowerBlockBuilder {
         } else {
             quickwit_common::tower::BoxService::new(boxed_instance.clone())
         };
+        let add_index_event_svc = if let Some(layer) = self.add_index_event_layer {
+            layer.layer(boxed_instance.clone())
+        } else {
+            quickwit_common::tower::BoxService::new(boxed_instance.clone())
+        };
+        let list_index_events_svc = if let Some(layer) = self.list_index_events_layer {
+            layer.layer(boxed_instance.clone())
+        } else {
+            quickwit_common::tower::BoxService::new(boxed_instance.clone())
+        };
+        let list_index_config_versions_svc = if let Some(layer) = self.list_index_config_versions_layer {
+            layer.layer(boxed_instance.clone())
+        } else {
+            quickwit_common::tower::BoxService::new(boxed_instance.clone())
+        };
+        let rollback_index_config_svc = if let Some(layer) = self.rollback_index_config_layer {
+            layer.layer(boxed_instance.clone())
+        } else {
+            quickwit_common::tower::BoxService::new(boxed_instance.clone())
+        };
         let open_shards_svc = if let Some(layer) = self.open_shards_layer {
             layer.layer(boxed_instance.clone())
         } else {
@@ -2633,6 +3375,9 @@ impl MetastoreServiceTowerBlockBuilder {
             inner: boxed_instance.clone(),
             create_index_svc,
             index_metadata_svc,
+            update_index_svc,
+            freeze_index_svc,
+            set_merges_paused_svc,
             list_indexes_metadata_svc,
             delete_index_svc,
             list_splits_svc,
@@ -2649,6 +3394,10 @@ impl MetastoreServiceTowerBlockBuilder {
             update_splits_delete_opstamp_svc,
             list_delete_tasks_svc,
             list_stale_splits_svc,
+            add_index_event_svc,
+            list_index_events_svc,
+            list_index_config_versions_svc,
+            rollback_index_config_svc,
             open_shards_svc,
             acquire_shards_svc,
             delete_shards_svc,
@@ -2741,6 +3490,24 @@ where
             Error = crate::metastore::MetastoreError,
             Future = BoxFuture<IndexMetadataResponse, crate::metastore::MetastoreError>,
         >
+        + tower::Service<
+            UpdateIndexRequest,
+            Response = IndexMetadataResponse,
+            Error = crate::metastore::MetastoreError,
+            Future = BoxFuture<IndexMetadataResponse, crate::metastore::MetastoreError>,
+        >
+        + tower::Service<
+            FreezeIndexRequest,
+            Response = IndexMetadataResponse,
+            Error = crate::metastore::MetastoreError,
+            Future = BoxFuture<IndexMetadataResponse, crate::metastore::MetastoreError>,
+        >
+        + tower::Service<
+            SetMergesPausedRequest,
+            Response = IndexMetadataResponse,
+            Error = crate::metastore::MetastoreError,
+            Future = BoxFuture<IndexMetadataResponse, crate::metastore::MetastoreError>,
+        >
         + tower::Service<
             ListIndexesMetadataRequest,
             Response = ListIndexesMetadataResponse,
@@ -2849,6 +3616,30 @@ where
             Error = crate::metastore::MetastoreError,
             Future = BoxFuture<ListSplitsResponse, crate::metastore::MetastoreError>,
         >
+        + tower::Service<
+            AddIndexEventRequest,
+            Response = EmptyResponse,
+            Error = crate::metastore::MetastoreError,
+            Future = BoxFuture<EmptyResponse, crate::metastore::MetastoreError>,
+        >
+        + tower::Service<
+            ListIndexEventsRequest,
+            Response = ListIndexEventsResponse,
+            Error = crate::metastore::MetastoreError,
+            Future = BoxFuture<ListIndexEventsResponse, crate::metastore::MetastoreError>,
+        >
+        + tower::Service<
+            ListIndexConfigVersionsRequest,
+            Response = ListIndexConfigVersionsResponse,
+            Error = crate::metastore::MetastoreError,
+            Future = BoxFuture<ListIndexConfigVersionsResponse, crate::metastore::MetastoreError>,
+        >
+        + tower::Service<
+            RollbackIndexConfigRequest,
+            Response = IndexMetadataResponse,
+            Error = crate::metastore::MetastoreError,
+            Future = BoxFuture<IndexMetadataResponse, crate::metastore::MetastoreError>,
+        >
         + tower::Service<
             OpenShardsRequest,
             Response = OpenShardsResponse,
@@ -2886,6 +3677,24 @@ where
     ) -> crate::metastore::MetastoreResult<IndexMetadataResponse> {
         self.call(request).await
     }
+    async fn update_index(
+        &mut self,
+        request: UpdateIndexRequest,
+    ) -> crate::metastore::MetastoreResult<IndexMetadataResponse> {
+        self.call(request).await
+    }
+    async fn freeze_index(
+        &mut self,
+        request: FreezeIndexRequest,
+    ) -> crate::metastore::MetastoreResult<IndexMetadataResponse> {
+        self.call(request).await
+    }
+    async fn set_merges_paused(
+        &mut self,
+        request: SetMergesPausedRequest,
+    ) -> crate::metastore::MetastoreResult<IndexMetadataResponse> {
+        self.call(request).await
+    }
     async fn list_indexes_metadata(
         &mut self,
         request: ListIndexesMetadataRequest,
@@ -2982,21 +3791,45 @@ where
     ) -> crate::metastore::MetastoreResult<ListSplitsResponse> {
         self.call(request).await
     }
-    async fn open_shards(
+    async fn add_index_event(
         &mut self,
-        request: OpenShardsRequest,
-    ) -> crate::metastore::MetastoreResult<OpenShardsResponse> {
+        request: AddIndexEventRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
         self.call(request).await
     }
-    async fn acquire_shards(
+    async fn list_index_events(
         &mut self,
-        request: AcquireShardsRequest,
-    ) -> crate::metastore::MetastoreResult<AcquireShardsResponse> {
+        request: ListIndexEventsRequest,
+    ) -> crate::metastore::MetastoreResult<ListIndexEventsResponse> {
         self.call(request).await
     }
-    async fn delete_shards(
+    async fn list_index_config_versions(
         &mut self,
-        request: DeleteShardsRequest,
+        request: ListIndexConfigVersionsRequest,
+    ) -> crate::metastore::MetastoreResult<ListIndexConfigVersionsResponse> {
+        self.call(request).await
+    }
+    async fn rollback_index_config(
+        &mut self,
+        request: RollbackIndexConfigRequest,
+    ) -> crate::metastore::MetastoreResult<IndexMetadataResponse> {
+        self.call(request).await
+    }
+    async fn open_shards(
+        &mut self,
+        request: OpenShardsRequest,
+    ) -> crate::metastore::MetastoreResult<OpenShardsResponse> {
+        self.call(request).await
+    }
+    async fn acquire_shards(
+        &mut self,
+        request: AcquireShardsRequest,
+    ) -> crate::metastore::MetastoreResult<AcquireShardsResponse> {
+        self.call(request).await
+    }
+    async fn delete_shards(
+        &mut self,
+        request: DeleteShardsRequest,
     ) -> crate::metastore::MetastoreResult<DeleteShardsResponse> {
         self.call(request).await
     }
@@ -3058,7 +3891,7 @@ where
         request: CreateIndexRequest,
     ) -> crate::metastore::MetastoreResult<CreateIndexResponse> {
         self.inner
-            .create_index(request)
+            .create_index(quickwit_common::deadline::make_grpc_request(request))
             .await
             .map(|response| response.into_inner())
             .map_err(|error| error.into())
@@ -3068,7 +3901,37 @@ where
         request: IndexMetadataRequest,
     ) -> crate::metastore::MetastoreResult<IndexMetadataResponse> {
         self.inner
-            .index_metadata(request)
+            .index_metadata(quickwit_common::deadline::make_grpc_request(request))
+            .await
+            .map(|response| response.into_inner())
+            .map_err(|error| error.into())
+    }
+    async fn update_index(
+        &mut self,
+        request: UpdateIndexRequest,
+    ) -> crate::metastore::MetastoreResult<IndexMetadataResponse> {
+        self.inner
+            .update_index(quickwit_common::deadline::make_grpc_request(request))
+            .await
+            .map(|response| response.into_inner())
+            .map_err(|error| error.into())
+    }
+    async fn freeze_index(
+        &mut self,
+        request: FreezeIndexRequest,
+    ) -> crate::metastore::MetastoreResult<IndexMetadataResponse> {
+        self.inner
+            .freeze_index(quickwit_common::deadline::make_grpc_request(request))
+            .await
+            .map(|response| response.into_inner())
+            .map_err(|error| error.into())
+    }
+    async fn set_merges_paused(
+        &mut self,
+        request: SetMergesPausedRequest,
+    ) -> crate::metastore::MetastoreResult<IndexMetadataResponse> {
+        self.inner
+            .set_merges_paused(quickwit_common::deadline::make_grpc_request(request))
             .await
             .map(|response| response.into_inner())
             .map_err(|error| error.into())
@@ -3078,7 +3941,7 @@ where
         request: ListIndexesMetadataRequest,
     ) -> crate::metastore::MetastoreResult<ListIndexesMetadataResponse> {
         self.inner
-            .list_indexes_metadata(request)
+            .list_indexes_metadata(quickwit_common::deadline::make_grpc_request(request))
             .await
             .map(|response| response.into_inner())
             .map_err(|error| error.into())
@@ -3088,7 +3951,7 @@ where
         request: DeleteIndexRequest,
     ) -> crate::metastore::MetastoreResult<EmptyResponse> {
         self.inner
-            .delete_index(request)
+            .delete_index(quickwit_common::deadline::make_grpc_request(request))
             .await
             .map(|response| response.into_inner())
             .map_err(|error| error.into())
@@ -3112,7 +3975,7 @@ where
         request: StageSplitsRequest,
     ) -> crate::metastore::MetastoreResult<EmptyResponse> {
         self.inner
-            .stage_splits(request)
+            .stage_splits(quickwit_common::deadline::make_grpc_request(request))
             .await
             .map(|response| response.into_inner())
             .map_err(|error| error.into())
@@ -3122,7 +3985,7 @@ where
         request: PublishSplitsRequest,
     ) -> crate::metastore::MetastoreResult<EmptyResponse> {
         self.inner
-            .publish_splits(request)
+            .publish_splits(quickwit_common::deadline::make_grpc_request(request))
             .await
             .map(|response| response.into_inner())
             .map_err(|error| error.into())
@@ -3132,7 +3995,7 @@ where
         request: MarkSplitsForDeletionRequest,
     ) -> crate::metastore::MetastoreResult<EmptyResponse> {
         self.inner
-            .mark_splits_for_deletion(request)
+            .mark_splits_for_deletion(quickwit_common::deadline::make_grpc_request(request))
             .await
             .map(|response| response.into_inner())
             .map_err(|error| error.into())
@@ -3142,7 +4005,7 @@ where
         request: DeleteSplitsRequest,
     ) -> crate::metastore::MetastoreResult<EmptyResponse> {
         self.inner
-            .delete_splits(request)
+            .delete_splits(quickwit_common::deadline::make_grpc_request(request))
             .await
             .map(|response| response.into_inner())
             .map_err(|error| error.into())
@@ -3152,7 +4015,7 @@ where
         request: AddSourceRequest,
     ) -> crate::metastore::MetastoreResult<EmptyResponse> {
         self.inner
-            .add_source(request)
+            .add_source(quickwit_common::deadline::make_grpc_request(request))
             .await
             .map(|response| response.into_inner())
             .map_err(|error| error.into())
@@ -3162,7 +4025,7 @@ where
         request: ToggleSourceRequest,
     ) -> crate::metastore::MetastoreResult<EmptyResponse> {
         self.inner
-            .toggle_source(request)
+            .toggle_source(quickwit_common::deadline::make_grpc_request(request))
             .await
             .map(|response| response.into_inner())
             .map_err(|error| error.into())
@@ -3172,7 +4035,7 @@ where
         request: DeleteSourceRequest,
     ) -> crate::metastore::MetastoreResult<EmptyResponse> {
         self.inner
-            .delete_source(request)
+            .delete_source(quickwit_common::deadline::make_grpc_request(request))
             .await
             .map(|response| response.into_inner())
             .map_err(|error| error.into())
@@ -3182,7 +4045,7 @@ where
         request: ResetSourceCheckpointRequest,
     ) -> crate::metastore::MetastoreResult<EmptyResponse> {
         self.inner
-            .reset_source_checkpoint(request)
+            .reset_source_checkpoint(quickwit_common::deadline::make_grpc_request(request))
             .await
             .map(|response| response.into_inner())
             .map_err(|error| error.into())
@@ -3192,7 +4055,7 @@ where
         request: LastDeleteOpstampRequest,
     ) -> crate::metastore::MetastoreResult<LastDeleteOpstampResponse> {
         self.inner
-            .last_delete_opstamp(request)
+            .last_delete_opstamp(quickwit_common::deadline::make_grpc_request(request))
             .await
             .map(|response| response.into_inner())
             .map_err(|error| error.into())
@@ -3202,7 +4065,7 @@ where
         request: DeleteQuery,
     ) -> crate::metastore::MetastoreResult<DeleteTask> {
         self.inner
-            .create_delete_task(request)
+            .create_delete_task(quickwit_common::deadline::make_grpc_request(request))
             .await
             .map(|response| response.into_inner())
             .map_err(|error| error.into())
@@ -3212,7 +4075,7 @@ where
         request: UpdateSplitsDeleteOpstampRequest,
     ) -> crate::metastore::MetastoreResult<UpdateSplitsDeleteOpstampResponse> {
         self.inner
-            .update_splits_delete_opstamp(request)
+            .update_splits_delete_opstamp(quickwit_common::deadline::make_grpc_request(request))
             .await
             .map(|response| response.into_inner())
             .map_err(|error| error.into())
@@ -3222,7 +4085,7 @@ where
         request: ListDeleteTasksRequest,
     ) -> crate::metastore::MetastoreResult<ListDeleteTasksResponse> {
         self.inner
-            .list_delete_tasks(request)
+            .list_delete_tasks(quickwit_common::deadline::make_grpc_request(request))
             .await
             .map(|response| response.into_inner())
             .map_err(|error| error.into())
@@ -3232,7 +4095,47 @@ where
         request: ListStaleSplitsRequest,
     ) -> crate::metastore::MetastoreResult<ListSplitsResponse> {
         self.inner
-            .list_stale_splits(request)
+            .list_stale_splits(quickwit_common::deadline::make_grpc_request(request))
+            .await
+            .map(|response| response.into_inner())
+            .map_err(|error| error.into())
+    }
+    async fn add_index_event(
+        &mut self,
+        request: AddIndexEventRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.inner
+            .add_index_event(quickwit_common::deadline::make_grpc_request(request))
+            .await
+            .map(|response| response.into_inner())
+            .map_err(|error| error.into())
+    }
+    async fn list_index_events(
+        &mut self,
+        request: ListIndexEventsRequest,
+    ) -> crate::metastore::MetastoreResult<ListIndexEventsResponse> {
+        self.inner
+            .list_index_events(quickwit_common::deadline::make_grpc_request(request))
+            .await
+            .map(|response| response.into_inner())
+            .map_err(|error| error.into())
+    }
+    async fn list_index_config_versions(
+        &mut self,
+        request: ListIndexConfigVersionsRequest,
+    ) -> crate::metastore::MetastoreResult<ListIndexConfigVersionsResponse> {
+        self.inner
+            .list_index_config_versions(quickwit_common::deadline::make_grpc_request(request))
+            .await
+            .map(|response| response.into_inner())
+            .map_err(|error| error.into())
+    }
+    async fn rollback_index_config(
+        &mut self,
+        request: RollbackIndexConfigRequest,
+    ) -> crate::metastore::MetastoreResult<IndexMetadataResponse> {
+        self.inner
+            .rollback_index_config(quickwit_common::deadline::make_grpc_request(request))
             .await
             .map(|response| response.into_inner())
             .map_err(|error| error.into())
@@ -3242,7 +4145,7 @@ where
         request: OpenShardsRequest,
     ) -> crate::metastore::MetastoreResult<OpenShardsResponse> {
         self.inner
-            .open_shards(request)
+            .open_shards(quickwit_common::deadline::make_grpc_request(request))
             .await
             .map(|response| response.into_inner())
             .map_err(|error| error.into())
@@ -3252,7 +4155,7 @@ where
         request: AcquireShardsRequest,
     ) -> crate::metastore::MetastoreResult<AcquireShardsResponse> {
         self.inner
-            .acquire_shards(request)
+            .acquire_shards(quickwit_common::deadline::make_grpc_request(request))
             .await
             .map(|response| response.into_inner())
             .map_err(|error| error.into())
@@ -3262,7 +4165,7 @@ where
         request: DeleteShardsRequest,
     ) -> crate::metastore::MetastoreResult<DeleteShardsResponse> {
         self.inner
-            .delete_shards(request)
+            .delete_shards(quickwit_common::deadline::make_grpc_request(request))
             .await
             .map(|response| response.into_inner())
             .map_err(|error| error.into())
@@ -3272,7 +4175,7 @@ where
         request: ListShardsRequest,
     ) -> crate::metastore::MetastoreResult<ListShardsResponse> {
         self.inner
-            .list_shards(request)
+            .list_shards(quickwit_common::deadline::make_grpc_request(request))
             .await
             .map(|response| response.into_inner())
             .map_err(|error| error.into())
@@ -3330,6 +4233,39 @@ for MetastoreServiceGrpcServerAdapter {
             .map(tonic::Response::new)
             .map_err(|error| error.into())
     }
+    async fn update_index(
+        &self,
+        request: tonic::Request<UpdateIndexRequest>,
+    ) -> Result<tonic::Response<IndexMetadataResponse>, tonic::Status> {
+        self.inner
+            .clone()
+            .update_index(request.into_inner())
+            .await
+            .map(tonic::Response::new)
+            .map_err(|error| error.into())
+    }
+    async fn freeze_index(
+        &self,
+        request: tonic::Request<FreezeIndexRequest>,
+    ) -> Result<tonic::Response<IndexMetadataResponse>, tonic::Status> {
+        self.inner
+            .clone()
+            .freeze_index(request.into_inner())
+            .await
+            .map(tonic::Response::new)
+            .map_err(|error| error.into())
+    }
+    async fn set_merges_paused(
+        &self,
+        request: tonic::Request<SetMergesPausedRequest>,
+    ) -> Result<tonic::Response<IndexMetadataResponse>, tonic::Status> {
+        self.inner
+            .clone()
+            .set_merges_paused(request.into_inner())
+            .await
+            .map(tonic::Response::new)
+            .map_err(|error| error.into())
+    }
     async fn list_indexes_metadata(
         &self,
         request: tonic::Request<ListIndexesMetadataRequest>,
@@ -3509,6 +4445,50 @@ for MetastoreServiceGrpcServerAdapter {
             .map(tonic::Response::new)
             .map_err(|error| error.into())
     }
+    async fn add_index_event(
+        &self,
+        request: tonic::Request<AddIndexEventRequest>,
+    ) -> Result<tonic::Response<EmptyResponse>, tonic::Status> {
+        self.inner
+            .clone()
+            .add_index_event(request.into_inner())
+            .await
+            .map(tonic::Response::new)
+            .map_err(|error| error.into())
+    }
+    async fn list_index_events(
+        &self,
+        request: tonic::Request<ListIndexEventsRequest>,
+    ) -> Result<tonic::Response<ListIndexEventsResponse>, tonic::Status> {
+        self.inner
+            .clone()
+            .list_index_events(request.into_inner())
+            .await
+            .map(tonic::Response::new)
+            .map_err(|error| error.into())
+    }
+    async fn list_index_config_versions(
+        &self,
+        request: tonic::Request<ListIndexConfigVersionsRequest>,
+    ) -> Result<tonic::Response<ListIndexConfigVersionsResponse>, tonic::Status> {
+        self.inner
+            .clone()
+            .list_index_config_versions(request.into_inner())
+            .await
+            .map(tonic::Response::new)
+            .map_err(|error| error.into())
+    }
+    async fn rollback_index_config(
+        &self,
+        request: tonic::Request<RollbackIndexConfigRequest>,
+    ) -> Result<tonic::Response<IndexMetadataResponse>, tonic::Status> {
+        self.inner
+            .clone()
+            .rollback_index_config(request.into_inner())
+            .await
+            .map(tonic::Response::new)
+            .map_err(|error| error.into())
+    }
     async fn open_shards(
         &self,
         request: tonic::Request<OpenShardsRequest>,
@@ -3747,6 +4727,99 @@ pub mod metastore_service_grpc_client {
                 );
             self.inner.unary(req, path, codec).await
         }
+        /// Updates the doc mapping of an index. Only additive changes are supported.
+        pub async fn update_index(
+            &mut self,
+            request: impl tonic::IntoRequest<super::UpdateIndexRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::IndexMetadataResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit.metastore.MetastoreService/UpdateIndex",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "quickwit.metastore.MetastoreService",
+                        "UpdateIndex",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        /// Freezes an index: disables all its sources and rejects subsequent writes.
+        pub async fn freeze_index(
+            &mut self,
+            request: impl tonic::IntoRequest<super::FreezeIndexRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::IndexMetadataResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit.metastore.MetastoreService/FreezeIndex",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "quickwit.metastore.MetastoreService",
+                        "FreezeIndex",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        /// Pauses or resumes the merges of an index.
+        pub async fn set_merges_paused(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SetMergesPausedRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::IndexMetadataResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit.metastore.MetastoreService/SetMergesPaused",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "quickwit.metastore.MetastoreService",
+                        "SetMergesPaused",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
         /// Gets an indexes metadatas.
         pub async fn list_indexes_metadata(
             &mut self,
@@ -4201,17 +5274,12 @@ pub mod metastore_service_grpc_client {
                 );
             self.inner.unary(req, path, codec).await
         }
-        /// Shard API
-        ///
-        /// Note that for the file-backed metastore implementation, the requests are not processed atomically.
-        /// Indeed, each request comprises one or more subrequests that target different indexes and sources processed
-        /// independently. Responses list the requests that succeeded or failed in the fields `successes` and
-        /// `failures`.
-        pub async fn open_shards(
+        /// Records an event in the activity feed of an index.
+        pub async fn add_index_event(
             &mut self,
-            request: impl tonic::IntoRequest<super::OpenShardsRequest>,
+            request: impl tonic::IntoRequest<super::AddIndexEventRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::OpenShardsResponse>,
+            tonic::Response<super::EmptyResponse>,
             tonic::Status,
         > {
             self.inner
@@ -4225,23 +5293,19 @@ pub mod metastore_service_grpc_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/quickwit.metastore.MetastoreService/OpenShards",
+                "/quickwit.metastore.MetastoreService/AddIndexEvent",
             );
             let mut req = request.into_request();
             req.extensions_mut()
-                .insert(
-                    GrpcMethod::new("quickwit.metastore.MetastoreService", "OpenShards"),
-                );
+                .insert(GrpcMethod::new("quickwit.metastore.MetastoreService", "AddIndexEvent"));
             self.inner.unary(req, path, codec).await
         }
-        /// Acquires a set of shards for indexing. This RPC locks the shards for publishing thanks to a publish token and only
-        /// the last indexer that has acquired the shards is allowed to publish. The response returns for each subrequest the
-        /// list of acquired shards along with the positions to index from.
-        pub async fn acquire_shards(
+        /// Lists the most recent events of an index, most recent first.
+        pub async fn list_index_events(
             &mut self,
-            request: impl tonic::IntoRequest<super::AcquireShardsRequest>,
+            request: impl tonic::IntoRequest<super::ListIndexEventsRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::AcquireShardsResponse>,
+            tonic::Response<super::ListIndexEventsResponse>,
             tonic::Status,
         > {
             self.inner
@@ -4255,25 +5319,19 @@ pub mod metastore_service_grpc_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/quickwit.metastore.MetastoreService/AcquireShards",
+                "/quickwit.metastore.MetastoreService/ListIndexEvents",
             );
             let mut req = request.into_request();
             req.extensions_mut()
-                .insert(
-                    GrpcMethod::new(
-                        "quickwit.metastore.MetastoreService",
-                        "AcquireShards",
-                    ),
-                );
+                .insert(GrpcMethod::new("quickwit.metastore.MetastoreService", "ListIndexEvents"));
             self.inner.unary(req, path, codec).await
         }
-        /// Deletes a set of shards. This RPC deletes the shards from the metastore and the storage.
-        /// If the shard did not exist to begin with, the operation is successful and does not return any error.
-        pub async fn delete_shards(
+        /// Lists the versions of the config of an index, most recent first.
+        pub async fn list_index_config_versions(
             &mut self,
-            request: impl tonic::IntoRequest<super::DeleteShardsRequest>,
+            request: impl tonic::IntoRequest<super::ListIndexConfigVersionsRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::DeleteShardsResponse>,
+            tonic::Response<super::ListIndexConfigVersionsResponse>,
             tonic::Status,
         > {
             self.inner
@@ -4287,23 +5345,19 @@ pub mod metastore_service_grpc_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/quickwit.metastore.MetastoreService/DeleteShards",
+                "/quickwit.metastore.MetastoreService/ListIndexConfigVersions",
             );
             let mut req = request.into_request();
             req.extensions_mut()
-                .insert(
-                    GrpcMethod::new(
-                        "quickwit.metastore.MetastoreService",
-                        "DeleteShards",
-                    ),
-                );
+                .insert(GrpcMethod::new("quickwit.metastore.MetastoreService", "ListIndexConfigVersions"));
             self.inner.unary(req, path, codec).await
         }
-        pub async fn list_shards(
+        /// Restores the doc mapping of an index to the one of a previous config version.
+        pub async fn rollback_index_config(
             &mut self,
-            request: impl tonic::IntoRequest<super::ListShardsRequest>,
+            request: impl tonic::IntoRequest<super::RollbackIndexConfigRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::ListShardsResponse>,
+            tonic::Response<super::IndexMetadataResponse>,
             tonic::Status,
         > {
             self.inner
@@ -4317,25 +5371,148 @@ pub mod metastore_service_grpc_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/quickwit.metastore.MetastoreService/ListShards",
+                "/quickwit.metastore.MetastoreService/RollbackIndexConfig",
             );
             let mut req = request.into_request();
             req.extensions_mut()
-                .insert(
-                    GrpcMethod::new("quickwit.metastore.MetastoreService", "ListShards"),
-                );
+                .insert(GrpcMethod::new("quickwit.metastore.MetastoreService", "RollbackIndexConfig"));
             self.inner.unary(req, path, codec).await
         }
-    }
-}
-/// Generated server implementations.
-pub mod metastore_service_grpc_server {
-    #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
-    use tonic::codegen::*;
-    /// Generated trait containing gRPC methods that should be implemented for use with MetastoreServiceGrpcServer.
-    #[async_trait]
-    pub trait MetastoreServiceGrpc: Send + Sync + 'static {
-        /// Creates an index.
+        /// Shard API
+        ///
+        /// Note that for the file-backed metastore implementation, the requests are not processed atomically.
+        /// Indeed, each request comprises one or more subrequests that target different indexes and sources processed
+        /// independently. Responses list the requests that succeeded or failed in the fields `successes` and
+        /// `failures`.
+        pub async fn open_shards(
+            &mut self,
+            request: impl tonic::IntoRequest<super::OpenShardsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::OpenShardsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit.metastore.MetastoreService/OpenShards",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new("quickwit.metastore.MetastoreService", "OpenShards"),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        /// Acquires a set of shards for indexing. This RPC locks the shards for publishing thanks to a publish token and only
+        /// the last indexer that has acquired the shards is allowed to publish. The response returns for each subrequest the
+        /// list of acquired shards along with the positions to index from.
+        pub async fn acquire_shards(
+            &mut self,
+            request: impl tonic::IntoRequest<super::AcquireShardsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::AcquireShardsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit.metastore.MetastoreService/AcquireShards",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "quickwit.metastore.MetastoreService",
+                        "AcquireShards",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        /// Deletes a set of shards. This RPC deletes the shards from the metastore and the storage.
+        /// If the shard did not exist to begin with, the operation is successful and does not return any error.
+        pub async fn delete_shards(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DeleteShardsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::DeleteShardsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit.metastore.MetastoreService/DeleteShards",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "quickwit.metastore.MetastoreService",
+                        "DeleteShards",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn list_shards(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListShardsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListShardsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit.metastore.MetastoreService/ListShards",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new("quickwit.metastore.MetastoreService", "ListShards"),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+    }
+}
+/// Generated server implementations.
+pub mod metastore_service_grpc_server {
+    #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
+    use tonic::codegen::*;
+    /// Generated trait containing gRPC methods that should be implemented for use with MetastoreServiceGrpcServer.
+    #[async_trait]
+    pub trait MetastoreServiceGrpc: Send + Sync + 'static {
+        /// Creates an index.
         ///
         /// This API creates a new index in the metastore.
         /// An error will occur if an index that already exists in the storage is specified.
@@ -4354,6 +5531,30 @@ pub mod metastore_service_grpc_server {
             tonic::Response<super::IndexMetadataResponse>,
             tonic::Status,
         >;
+        /// Updates the doc mapping of an index. Only additive changes are supported.
+        async fn update_index(
+            &self,
+            request: tonic::Request<super::UpdateIndexRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::IndexMetadataResponse>,
+            tonic::Status,
+        >;
+        /// Freezes an index: disables all its sources and rejects subsequent writes.
+        async fn freeze_index(
+            &self,
+            request: tonic::Request<super::FreezeIndexRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::IndexMetadataResponse>,
+            tonic::Status,
+        >;
+        /// Pauses or resumes the merges of an index.
+        async fn set_merges_paused(
+            &self,
+            request: tonic::Request<super::SetMergesPausedRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::IndexMetadataResponse>,
+            tonic::Status,
+        >;
         /// Gets an indexes metadatas.
         async fn list_indexes_metadata(
             &self,
@@ -4455,6 +5656,38 @@ pub mod metastore_service_grpc_server {
             tonic::Response<super::ListSplitsResponse>,
             tonic::Status,
         >;
+        /// Records an event in the activity feed of an index.
+        async fn add_index_event(
+            &self,
+            request: tonic::Request<super::AddIndexEventRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::EmptyResponse>,
+            tonic::Status,
+        >;
+        /// Lists the most recent events of an index, most recent first.
+        async fn list_index_events(
+            &self,
+            request: tonic::Request<super::ListIndexEventsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListIndexEventsResponse>,
+            tonic::Status,
+        >;
+        /// Lists the versions of the config of an index, most recent first.
+        async fn list_index_config_versions(
+            &self,
+            request: tonic::Request<super::ListIndexConfigVersionsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListIndexConfigVersionsResponse>,
+            tonic::Status,
+        >;
+        /// Restores the doc mapping of an index to the one of a previous config version.
+        async fn rollback_index_config(
+            &self,
+            request: tonic::Request<super::RollbackIndexConfigRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::IndexMetadataResponse>,
+            tonic::Status,
+        >;
         /// Shard API
         ///
         /// Note that for the file-backed metastore implementation, the requests are not processed atomically.
@@ -4713,6 +5946,144 @@ pub mod metastore_service_grpc_server {
                     };
                     Box::pin(fut)
                 }
+                "/quickwit.metastore.MetastoreService/UpdateIndex" => {
+                    #[allow(non_camel_case_types)]
+                    struct UpdateIndexSvc<T: MetastoreServiceGrpc>(pub Arc<T>);
+                    impl<
+                        T: MetastoreServiceGrpc,
+                    > tonic::server::UnaryService<super::UpdateIndexRequest>
+                    for UpdateIndexSvc<T> {
+                        type Response = super::IndexMetadataResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::UpdateIndexRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                (*inner).update_index(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = UpdateIndexSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/quickwit.metastore.MetastoreService/FreezeIndex" => {
+                    #[allow(non_camel_case_types)]
+                    struct FreezeIndexSvc<T: MetastoreServiceGrpc>(pub Arc<T>);
+                    impl<
+                        T: MetastoreServiceGrpc,
+                    > tonic::server::UnaryService<super::FreezeIndexRequest>
+                    for FreezeIndexSvc<T> {
+                        type Response = super::IndexMetadataResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::FreezeIndexRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                (*inner).freeze_index(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = FreezeIndexSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/quickwit.metastore.MetastoreService/SetMergesPaused" => {
+                    #[allow(non_camel_case_types)]
+                    struct SetMergesPausedSvc<T: MetastoreServiceGrpc>(pub Arc<T>);
+                    impl<
+                        T: MetastoreServiceGrpc,
+                    > tonic::server::UnaryService<super::SetMergesPausedRequest>
+                    for SetMergesPausedSvc<T> {
+                        type Response = super::IndexMetadataResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SetMergesPausedRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                (*inner).set_merges_paused(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = SetMergesPausedSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 "/quickwit.metastore.MetastoreService/ListIndexesMetadata" => {
                     #[allow(non_camel_case_types)]
                     struct ListIndexesMetadataSvc<T: MetastoreServiceGrpc>(pub Arc<T>);
@@ -5451,6 +6822,182 @@ pub mod metastore_service_grpc_server {
                     };
                     Box::pin(fut)
                 }
+                "/quickwit.metastore.MetastoreService/AddIndexEvent" => {
+                    #[allow(non_camel_case_types)]
+                    struct AddIndexEventSvc<T: MetastoreServiceGrpc>(pub Arc<T>);
+                    impl<
+                        T: MetastoreServiceGrpc,
+                    > tonic::server::UnaryService<super::AddIndexEventRequest>
+                    for AddIndexEventSvc<T> {
+                        type Response = super::EmptyResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::AddIndexEventRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move { (*inner).add_index_event(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = AddIndexEventSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/quickwit.metastore.MetastoreService/ListIndexEvents" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListIndexEventsSvc<T: MetastoreServiceGrpc>(pub Arc<T>);
+                    impl<
+                        T: MetastoreServiceGrpc,
+                    > tonic::server::UnaryService<super::ListIndexEventsRequest>
+                    for ListIndexEventsSvc<T> {
+                        type Response = super::ListIndexEventsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListIndexEventsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move { (*inner).list_index_events(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ListIndexEventsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/quickwit.metastore.MetastoreService/ListIndexConfigVersions" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListIndexConfigVersionsSvc<T: MetastoreServiceGrpc>(pub Arc<T>);
+                    impl<
+                        T: MetastoreServiceGrpc,
+                    > tonic::server::UnaryService<super::ListIndexConfigVersionsRequest>
+                    for ListIndexConfigVersionsSvc<T> {
+                        type Response = super::ListIndexConfigVersionsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListIndexConfigVersionsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move { (*inner).list_index_config_versions(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ListIndexConfigVersionsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/quickwit.metastore.MetastoreService/RollbackIndexConfig" => {
+                    #[allow(non_camel_case_types)]
+                    struct RollbackIndexConfigSvc<T: MetastoreServiceGrpc>(pub Arc<T>);
+                    impl<
+                        T: MetastoreServiceGrpc,
+                    > tonic::server::UnaryService<super::RollbackIndexConfigRequest>
+                    for RollbackIndexConfigSvc<T> {
+                        type Response = super::IndexMetadataResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::RollbackIndexConfigRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move { (*inner).rollback_index_config(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = RollbackIndexConfigSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 "/quickwit.metastore.MetastoreService/OpenShards" => {
                     #[allow(non_camel_case_types)]
                     struct OpenShardsSvc<T: MetastoreServiceGrpc>(pub Arc<T>);
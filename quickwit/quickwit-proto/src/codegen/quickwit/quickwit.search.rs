@@ -109,6 +109,72 @@ pub struct SearchRequest {
     pub search_after: ::core::option::Option<PartialHit>,
     #[prost(enumeration = "CountHits", tag = "17")]
     pub count_hits: i32,
+    /// Format of the final aggregation result.
+    #[prost(enumeration = "AggregationFormat", tag = "18")]
+    pub aggregation_format: i32,
+    /// If set, hits sharing the same value of this fast field are deduplicated:
+    /// only the best `dedup_count` hits per value are returned.
+    #[prost(string, optional, tag = "19")]
+    pub dedup_field: ::core::option::Option<::prost::alloc::string::String>,
+    /// Maximum number of hits returned per value of `dedup_field`. Defaults to 1.
+    #[prost(uint32, optional, tag = "20")]
+    pub dedup_count: ::core::option::Option<u32>,
+    /// Options used to generate the snippets of `snippet_fields`.
+    #[prost(message, optional, tag = "21")]
+    pub snippet_options: ::core::option::Option<SnippetOptions>,
+    /// If set, at most `max_hits_per_index` hits are returned per index.
+    #[prost(uint64, optional, tag = "22")]
+    pub max_hits_per_index: ::core::option::Option<u64>,
+    /// How the hits of the different indexes are merged into a single list of hits.
+    #[prost(enumeration = "Federation", tag = "23")]
+    pub federation: i32,
+    /// If set, only the most recent splits are searched in order to return quickly.
+    /// The response then reports the fraction of the documents that were covered.
+    #[prost(bool, tag = "24")]
+    pub preview: bool,
+    /// If set, hits are grouped by the value of a fast field and the response holds the
+    /// best groups with their top hits instead of a flat list of hits.
+    #[prost(message, optional, tag = "25")]
+    pub group_by: ::core::option::Option<GroupBy>,
+    /// If set, only the splits that may contain documents carrying all these split tags, of the
+    /// form `key:value`, are searched.
+    #[prost(string, repeated, tag = "26")]
+    pub split_tags: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// If set, the splits that could not be searched are reported in the response instead of
+    /// failing the whole request.
+    #[prost(bool, tag = "27")]
+    pub allow_partial_results: bool,
+}
+/// Groups the hits by the value of a fast field. Groups are ranked by their best hit.
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[derive(Eq, Hash)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GroupBy {
+    /// Fast field whose values define the groups.
+    #[prost(string, tag = "1")]
+    pub field: ::prost::alloc::string::String,
+    /// Maximum number of groups returned.
+    #[prost(uint32, tag = "2")]
+    pub max_groups: u32,
+    /// Maximum number of hits returned per group.
+    #[prost(uint32, tag = "3")]
+    pub max_hits_per_group: u32,
+}
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[derive(Eq, Hash)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SnippetOptions {
+    /// Tag inserted before each highlighted term. Defaults to `<b>`.
+    #[prost(string, optional, tag = "1")]
+    pub pre_tag: ::core::option::Option<::prost::alloc::string::String>,
+    /// Tag inserted after each highlighted term. Defaults to `</b>`.
+    #[prost(string, optional, tag = "2")]
+    pub post_tag: ::core::option::Option<::prost::alloc::string::String>,
+    /// Maximum number of characters of a snippet. Defaults to 150.
+    #[prost(uint32, optional, tag = "3")]
+    pub max_num_chars: ::core::option::Option<u32>,
 }
 #[derive(Serialize, Deserialize, utoipa::ToSchema)]
 #[derive(Eq, Hash)]
@@ -148,6 +214,39 @@ pub struct SearchResponse {
     /// Scroll Id (only set if scroll_secs was set in the request)
     #[prost(string, optional, tag = "6")]
     pub scroll_id: ::core::option::Option<::prost::alloc::string::String>,
+    /// Aggregation response encoded as an Arrow IPC stream
+    /// (only set if the aggregation format of the request is `ARROW`)
+    #[prost(bytes = "vec", optional, tag = "7")]
+    pub aggregation_arrow: ::core::option::Option<::prost::alloc::vec::Vec<u8>>,
+    /// Fraction of the documents of the relevant splits that were searched
+    /// (only set if `preview` was set in the request)
+    #[prost(double, optional, tag = "8")]
+    pub preview_coverage: ::core::option::Option<f64>,
+    /// Best groups of hits (only set if `group_by` was set in the request)
+    #[prost(message, repeated, tag = "9")]
+    pub groups: ::prost::alloc::vec::Vec<HitGroup>,
+    /// The splits that could not be searched (only set if `allow_partial_results` was set in the
+    /// request). The hits and aggregations only cover the other splits.
+    #[prost(message, repeated, tag = "10")]
+    pub failed_splits: ::prost::alloc::vec::Vec<SplitSearchError>,
+    /// Splits that were not searched, or whose search was cancelled, because they could not
+    /// produce hits better than the ones returned.
+    #[prost(string, repeated, tag = "11")]
+    pub skipped_split_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HitGroup {
+    /// Value of the group by field shared by the hits of the group.
+    #[prost(string, tag = "1")]
+    pub key: ::prost::alloc::string::String,
+    /// Number of documents of the group matching the query.
+    #[prost(uint64, tag = "2")]
+    pub num_hits: u64,
+    /// Top hits of the group.
+    #[prost(message, repeated, tag = "3")]
+    pub hits: ::prost::alloc::vec::Vec<Hit>,
 }
 #[derive(Serialize, Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -182,6 +281,10 @@ pub struct LeafSearchRequest {
     /// split files.
     #[prost(string, tag = "6")]
     pub index_uri: ::prost::alloc::string::String,
+    /// Encryption config of the index as json serialized `IndexEncryptionConfig`, if the index is
+    /// encrypted.
+    #[prost(string, optional, tag = "7")]
+    pub encryption_config: ::core::option::Option<::prost::alloc::string::String>,
 }
 #[derive(Serialize, Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -203,6 +306,9 @@ pub struct SplitIdAndFooterOffsets {
     /// The highest timestamp appearing in the split
     #[prost(int64, optional, tag = "5")]
     pub timestamp_end: ::core::option::Option<i64>,
+    /// The order in which the documents of the split are sorted by timestamp, if they are.
+    #[prost(enumeration = "SortOrder", optional, tag = "6")]
+    pub timestamp_sort_order: ::core::option::Option<i32>,
 }
 /// Hits returned by a FetchDocRequest.
 ///
@@ -281,6 +387,11 @@ pub struct PartialHit {
     /// The DocId identifies a unique document at the scale of a tantivy segment.
     #[prost(uint32, tag = "4")]
     pub doc_id: u32,
+    /// Value of the deduplication field of the document, if the request deduplicates hits
+    /// and the document has a value for this field. If the request groups hits, value of the
+    /// group by field of the document.
+    #[prost(string, optional, tag = "5")]
+    pub dedup_key: ::core::option::Option<::prost::alloc::string::String>,
 }
 #[derive(Serialize, Deserialize, utoipa::ToSchema)]
 #[derive(Ord, PartialOrd)]
@@ -329,6 +440,25 @@ pub struct LeafSearchResponse {
     pub intermediate_aggregation_result: ::core::option::Option<
         ::prost::alloc::vec::Vec<u8>,
     >,
+    /// Splits that were not searched, or whose search was cancelled, because they could not
+    /// produce hits better than the ones already collected.
+    #[prost(string, repeated, tag = "7")]
+    pub skipped_split_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Number of matching documents per value of the group by field
+    /// (only set if `group_by` was set in the request).
+    #[prost(message, repeated, tag = "8")]
+    pub group_counts: ::prost::alloc::vec::Vec<GroupCount>,
+}
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GroupCount {
+    /// Value of the group by field.
+    #[prost(string, tag = "1")]
+    pub key: ::prost::alloc::string::String,
+    /// Number of documents matching the query for this value.
+    #[prost(uint64, tag = "2")]
+    pub num_hits: u64,
 }
 #[derive(Serialize, Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -338,6 +468,8 @@ pub struct SnippetRequest {
     pub snippet_fields: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
     #[prost(string, tag = "2")]
     pub query_ast_resolved: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "3")]
+    pub snippet_options: ::core::option::Option<SnippetOptions>,
 }
 #[derive(Serialize, Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -360,6 +492,10 @@ pub struct FetchDocsRequest {
     /// `DocMapper` as json serialized trait.
     #[prost(string, tag = "6")]
     pub doc_mapper: ::prost::alloc::string::String,
+    /// Encryption config of the index as json serialized `IndexEncryptionConfig`, if the index is
+    /// encrypted.
+    #[prost(string, optional, tag = "8")]
+    pub encryption_config: ::core::option::Option<::prost::alloc::string::String>,
 }
 #[derive(Serialize, Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -392,6 +528,9 @@ pub struct ListTermsRequest {
     pub start_key: ::core::option::Option<::prost::alloc::vec::Vec<u8>>,
     #[prost(bytes = "vec", optional, tag = "8")]
     pub end_key: ::core::option::Option<::prost::alloc::vec::Vec<u8>>,
+    /// Whether to return the number of documents containing each term.
+    #[prost(bool, tag = "9")]
+    pub include_doc_counts: bool,
 }
 #[derive(Serialize, Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -410,6 +549,10 @@ pub struct ListTermsResponse {
     /// The searcherrors that occurred formatted as string.
     #[prost(string, repeated, tag = "4")]
     pub errors: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Number of documents containing each term, in the same order as `terms`
+    /// (only set if `include_doc_counts` is set in the request).
+    #[prost(uint64, repeated, tag = "5")]
+    pub doc_counts: ::prost::alloc::vec::Vec<u64>,
 }
 #[derive(Serialize, Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -426,6 +569,10 @@ pub struct LeafListTermsRequest {
     /// split files.
     #[prost(string, tag = "3")]
     pub index_uri: ::prost::alloc::string::String,
+    /// Encryption config of the index as json serialized `IndexEncryptionConfig`, if the index is
+    /// encrypted.
+    #[prost(string, optional, tag = "4")]
+    pub encryption_config: ::core::option::Option<::prost::alloc::string::String>,
 }
 #[derive(Serialize, Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -444,6 +591,10 @@ pub struct LeafListTermsResponse {
     /// num_attempted_splits = num_successful_splits + num_failed_splits.
     #[prost(uint64, tag = "4")]
     pub num_attempted_splits: u64,
+    /// Number of documents containing each term, in the same order as `terms`
+    /// (only set if `include_doc_counts` is set in the request).
+    #[prost(uint64, repeated, tag = "5")]
+    pub doc_counts: ::prost::alloc::vec::Vec<u64>,
 }
 #[derive(Serialize, Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -472,6 +623,10 @@ pub struct SearchStreamRequest {
     /// Fields to extract snippet on.
     #[prost(string, repeated, tag = "10")]
     pub snippet_fields: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Names of the fast fields to extract after `fast_field`. Only supported by the `ARROW_IPC`
+    /// output format.
+    #[prost(string, repeated, tag = "12")]
+    pub additional_fast_fields: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
 }
 #[derive(Serialize, Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -492,6 +647,10 @@ pub struct LeafSearchStreamRequest {
     /// split files.
     #[prost(string, tag = "6")]
     pub index_uri: ::prost::alloc::string::String,
+    /// Encryption config of the index as json serialized `IndexEncryptionConfig`, if the index is
+    /// encrypted.
+    #[prost(string, optional, tag = "7")]
+    pub encryption_config: ::core::option::Option<::prost::alloc::string::String>,
 }
 #[derive(Serialize, Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -539,6 +698,68 @@ impl CountHits {
 #[serde(rename_all = "snake_case")]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
+pub enum Federation {
+    /// Hits are ranked globally, regardless of the index they belong to.
+    Global = 0,
+    /// Hits are interleaved across indexes: the best hit of each index comes first,
+    /// then the second best hit of each index, and so on.
+    Balanced = 1,
+}
+impl Federation {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Federation::Global => "GLOBAL",
+            Federation::Balanced => "BALANCED",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "GLOBAL" => Some(Self::Global),
+            "BALANCED" => Some(Self::Balanced),
+            _ => None,
+        }
+    }
+}
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum AggregationFormat {
+    /// JSON serialized aggregation result, returned in `SearchResponse.aggregation`.
+    Json = 0,
+    /// Arrow IPC stream of the flattened aggregation result, returned in
+    /// `SearchResponse.aggregation_arrow`.
+    Arrow = 1,
+}
+impl AggregationFormat {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            AggregationFormat::Json => "JSON",
+            AggregationFormat::Arrow => "ARROW",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "JSON" => Some(Self::Json),
+            "ARROW" => Some(Self::Arrow),
+            _ => None,
+        }
+    }
+}
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
 pub enum SortOrder {
     /// Ascending order.
     Asc = 0,
@@ -608,6 +829,9 @@ pub enum OutputFormat {
     /// Format data by row in ClickHouse binary format.
     /// <https://clickhouse.tech/docs/en/interfaces/formats/#rowbinary>
     ClickHouseRowBinary = 1,
+    /// Format data as an Arrow IPC stream with one column per fast field.
+    /// <https://arrow.apache.org/docs/format/Columnar.html#ipc-streaming-format>
+    ArrowIpc = 2,
 }
 impl OutputFormat {
     /// String value of the enum field names used in the ProtoBuf definition.
@@ -618,6 +842,7 @@ impl OutputFormat {
         match self {
             OutputFormat::Csv => "CSV",
             OutputFormat::ClickHouseRowBinary => "CLICK_HOUSE_ROW_BINARY",
+            OutputFormat::ArrowIpc => "ARROW_IPC",
         }
     }
     /// Creates an enum from field names used in the ProtoBuf definition.
@@ -625,6 +850,7 @@ impl OutputFormat {
         match value {
             "CSV" => Some(Self::Csv),
             "CLICK_HOUSE_ROW_BINARY" => Some(Self::ClickHouseRowBinary),
+            "ARROW_IPC" => Some(Self::ArrowIpc),
             _ => None,
         }
     }
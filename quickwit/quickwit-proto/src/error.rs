@@ -19,11 +19,17 @@
 
 use std::convert::Infallible;
 
+use serde::Serialize;
+
 /// This enum serves as a Rosetta Stone of
 /// gRPC and HTTP status code.
 ///
 /// It is voluntarily a restricted subset.
-#[derive(Clone, Copy)]
+///
+/// The serialized form of the codes is returned in the body of the REST API error responses. It is
+/// part of the public API and must remain stable.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
 pub enum ServiceErrorCode {
     AlreadyExists,
     BadRequest,
@@ -53,6 +59,16 @@ impl ServiceErrorCode {
             ServiceErrorCode::UnsupportedMediaType => tonic::Code::InvalidArgument,
         }
     }
+    /// Returns whether a request that failed with this error may succeed if retried as is.
+    pub fn is_retryable(self) -> bool {
+        matches!(
+            self,
+            ServiceErrorCode::RateLimited
+                | ServiceErrorCode::Timeout
+                | ServiceErrorCode::Unavailable
+        )
+    }
+
     pub fn to_http_status_code(self) -> http::StatusCode {
         match self {
             ServiceErrorCode::AlreadyExists => http::StatusCode::BAD_REQUEST,
@@ -91,3 +107,44 @@ pub fn convert_to_grpc_result<T, E: ServiceError>(
     res.map(tonic::Response::new)
         .map_err(|error| error.grpc_error())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_service_error_code_serialization() {
+        let codes = [
+            (ServiceErrorCode::AlreadyExists, "already_exists"),
+            (ServiceErrorCode::BadRequest, "bad_request"),
+            (ServiceErrorCode::Internal, "internal"),
+            (ServiceErrorCode::MethodNotAllowed, "method_not_allowed"),
+            (ServiceErrorCode::NotFound, "not_found"),
+            (ServiceErrorCode::NotSupportedYet, "not_supported_yet"),
+            (ServiceErrorCode::RateLimited, "rate_limited"),
+            (ServiceErrorCode::Timeout, "timeout"),
+            (ServiceErrorCode::Unavailable, "unavailable"),
+            (
+                ServiceErrorCode::UnsupportedMediaType,
+                "unsupported_media_type",
+            ),
+        ];
+        for (code, expected_code_str) in codes {
+            assert_eq!(
+                serde_json::to_value(code).unwrap(),
+                serde_json::Value::String(expected_code_str.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn test_service_error_code_is_retryable() {
+        assert!(ServiceErrorCode::RateLimited.is_retryable());
+        assert!(ServiceErrorCode::Timeout.is_retryable());
+        assert!(ServiceErrorCode::Unavailable.is_retryable());
+
+        assert!(!ServiceErrorCode::BadRequest.is_retryable());
+        assert!(!ServiceErrorCode::Internal.is_retryable());
+        assert!(!ServiceErrorCode::NotFound.is_retryable());
+    }
+}
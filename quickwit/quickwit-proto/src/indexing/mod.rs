@@ -17,6 +17,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::BTreeMap;
 use std::fmt::{Display, Formatter};
 use std::hash::Hash;
 use std::ops::{Add, Mul, Sub};
@@ -28,7 +29,7 @@ use quickwit_common::pubsub::Event;
 use serde::{Deserialize, Serialize};
 use thiserror;
 
-use crate::types::{IndexUid, PipelineUid, Position, ShardId, SourceId, SourceUid};
+use crate::types::{IndexUid, NodeId, PipelineUid, Position, ShardId, SourceId, SourceUid};
 use crate::{ServiceError, ServiceErrorCode};
 
 include!("../codegen/quickwit/quickwit.indexing.rs");
@@ -41,6 +42,11 @@ pub enum IndexingError {
     MissingPipeline { pipeline_uid: PipelineUid },
     #[error("indexing merge pipeline `{merge_pipeline_id}` does not exist")]
     MissingMergePipeline { merge_pipeline_id: String },
+    #[error("no indexing pipeline for source `{source_id}` of index `{index_id}` on this node")]
+    MissingSource {
+        index_id: String,
+        source_id: SourceId,
+    },
     #[error(
         "pipeline #{pipeline_uid} for index `{index_id}` and source `{source_id}` already exists"
     )]
@@ -76,6 +82,10 @@ impl From<IndexingError> for tonic::Status {
             IndexingError::MissingMergePipeline { merge_pipeline_id } => {
                 tonic::Status::not_found(format!("missing merge pipeline `{merge_pipeline_id}`"))
             }
+            IndexingError::MissingSource {
+                index_id,
+                source_id,
+            } => tonic::Status::not_found(format!("missing source `{index_id}/{source_id}`")),
             IndexingError::PipelineAlreadyExists {
                 index_id,
                 source_id,
@@ -125,6 +135,7 @@ impl ServiceError for IndexingError {
         match self {
             Self::MissingPipeline { .. } => ServiceErrorCode::NotFound,
             Self::MissingMergePipeline { .. } => ServiceErrorCode::NotFound,
+            Self::MissingSource { .. } => ServiceErrorCode::NotFound,
             Self::PipelineAlreadyExists { .. } => ServiceErrorCode::BadRequest,
             Self::InvalidParams(_) => ServiceErrorCode::BadRequest,
             Self::SpawnPipelinesError { .. } => ServiceErrorCode::Internal,
@@ -337,6 +348,20 @@ pub struct ShardPositionsUpdate {
 
 impl Event for ShardPositionsUpdate {}
 
+/// Whenever an indexer broadcasts via chitchat the backlog of the partitions consumed by one of its
+/// indexing pipelines, as reported by the source of the pipeline, a `SourceBacklogUpdate` event is
+/// published through the cluster's `EventBroker`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceBacklogUpdate {
+    pub indexer_id: NodeId,
+    pub source_uid: SourceUid,
+    pub pipeline_uid: PipelineUid,
+    // Number of records of each partition that the source has not consumed yet.
+    pub partition_backlogs: BTreeMap<String, u64>,
+}
+
+impl Event for SourceBacklogUpdate {}
+
 impl IndexingTask {
     pub fn pipeline_uid(&self) -> PipelineUid {
         self.pipeline_uid
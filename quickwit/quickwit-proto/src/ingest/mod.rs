@@ -31,6 +31,19 @@ pub mod router;
 
 include!("../codegen/quickwit/quickwit.ingest.rs");
 
+/// Protobuf definitions of the ingest v2 API exposed to clients, as `(path, content)` pairs. The
+/// paths are relative to the `protos` directory and match the `import` statements.
+pub const INGEST_V2_PROTOS: [(&str, &str); 2] = [
+    (
+        "quickwit/ingest.proto",
+        include_str!("../../protos/quickwit/ingest.proto"),
+    ),
+    (
+        "quickwit/router.proto",
+        include_str!("../../protos/quickwit/router.proto"),
+    ),
+];
+
 pub type IngestV2Result<T> = std::result::Result<T, IngestV2Error>;
 
 #[derive(Debug, Clone, thiserror::Error)]
@@ -237,6 +250,24 @@ impl ShardState {
     }
 }
 
+impl ShardPriority {
+    pub fn is_backfill(&self) -> bool {
+        *self == ShardPriority::Backfill
+    }
+
+    pub fn as_json_str_name(&self) -> &'static str {
+        match self {
+            ShardPriority::Realtime => "realtime",
+            ShardPriority::Backfill => "backfill",
+        }
+    }
+}
+
+/// Skips serializing the priority of realtime shards, which is the default.
+pub(crate) fn is_realtime_priority(priority: &i32) -> bool {
+    *priority == ShardPriority::Realtime as i32
+}
+
 impl ShardIds {
     pub fn queue_ids(&self) -> impl Iterator<Item = QueueId> + '_ {
         self.shard_ids
@@ -252,6 +283,7 @@ impl From<PersistFailureReason> for IngestFailureReason {
             PersistFailureReason::ShardNotFound => IngestFailureReason::NoShardsAvailable,
             PersistFailureReason::ShardClosed => IngestFailureReason::NoShardsAvailable,
             PersistFailureReason::ResourceExhausted => IngestFailureReason::ResourceExhausted,
+            PersistFailureReason::DiskWatermarkExceeded => IngestFailureReason::ResourceExhausted,
             PersistFailureReason::RateLimited => IngestFailureReason::RateLimited,
         }
     }
@@ -20,6 +20,7 @@
 // use quickwit_common::pubsub::Event;
 
 use quickwit_common::pubsub::Event;
+use serde::{Deserialize, Serialize};
 
 use super::{
     AddSourceRequest, CreateIndexRequest, DeleteIndexRequest, DeleteSourceRequest, SourceType,
@@ -65,8 +66,28 @@ pub struct DeleteSourceEvent {
     pub source_id: SourceId,
 }
 
+/// Type of change made to an index.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexChangeType {
+    Created,
+    Updated,
+    Deleted,
+}
+
+/// Index change notified by the metastore. Unlike the other metastore events, index change events
+/// also report the changes made by other nodes or clusters sharing the same metastore.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct IndexChangeEvent {
+    /// UID of the changed index.
+    pub index_uid: IndexUid,
+    /// Type of change.
+    pub change_type: IndexChangeType,
+}
+
 impl Event for AddSourceRequest {}
 impl Event for CreateIndexRequest {}
 impl Event for DeleteIndexRequest {}
 impl Event for DeleteSourceRequest {}
 impl Event for ToggleSourceRequest {}
+impl Event for IndexChangeEvent {}
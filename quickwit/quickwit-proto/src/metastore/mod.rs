@@ -34,6 +34,13 @@ pub type MetastoreResult<T> = Result<T, MetastoreError>;
 /// Lists the object types stored and managed by the metastore.
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub enum EntityKind {
+    /// A delete task.
+    DeleteTask {
+        /// Index ID.
+        index_id: IndexId,
+        /// Opstamp of the delete task.
+        opstamp: u64,
+    },
     /// A checkpoint delta.
     CheckpointDelta {
         /// Index ID.
@@ -82,6 +89,9 @@ impl fmt::Display for EntityKind {
                 index_id,
                 source_id,
             } => write!(f, "checkpoint delta `{index_id}/{source_id}`"),
+            EntityKind::DeleteTask { index_id, opstamp } => {
+                write!(f, "delete task `{index_id}/{opstamp}`")
+            }
             EntityKind::Index { index_id } => write!(f, "index `{}`", index_id),
             EntityKind::Indexes { index_ids } => write!(f, "indexes `{}`", index_ids.join(", ")),
             EntityKind::Shard { queue_id } => write!(f, "shard `{queue_id}`"),
@@ -205,6 +215,7 @@ impl Retryable for MetastoreError {
 impl SourceType {
     pub fn as_str(&self) -> &'static str {
         match self {
+            SourceType::AzureEventHubs => "azure_event_hubs",
             SourceType::Cli => "ingest-cli",
             SourceType::File => "file",
             SourceType::GcpPubsub => "gcp_pubsub",
@@ -279,6 +290,56 @@ impl ListDeleteTasksRequest {
     }
 }
 
+/// Types of the events recorded in the activity feed of an index.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum IndexEventType {
+    IndexCreated,
+    IndexUpdated,
+    IndexFrozen,
+    SourceAdded,
+    SourceEnabled,
+    SourceDisabled,
+    SourceDeleted,
+    SourceCheckpointReset,
+    DeleteTaskCreated,
+    RetentionApplied,
+    SplitsGarbageCollected,
+    OrphanFilesDetected,
+}
+
+impl IndexEventType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IndexEventType::IndexCreated => "index_created",
+            IndexEventType::IndexUpdated => "index_updated",
+            IndexEventType::IndexFrozen => "index_frozen",
+            IndexEventType::SourceAdded => "source_added",
+            IndexEventType::SourceEnabled => "source_enabled",
+            IndexEventType::SourceDisabled => "source_disabled",
+            IndexEventType::SourceDeleted => "source_deleted",
+            IndexEventType::SourceCheckpointReset => "source_checkpoint_reset",
+            IndexEventType::DeleteTaskCreated => "delete_task_created",
+            IndexEventType::RetentionApplied => "retention_applied",
+            IndexEventType::SplitsGarbageCollected => "splits_garbage_collected",
+            IndexEventType::OrphanFilesDetected => "orphan_files_detected",
+        }
+    }
+}
+
+impl AddIndexEventRequest {
+    pub fn new(
+        index_uid: IndexUid,
+        event_type: IndexEventType,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            index_uid: index_uid.into(),
+            event_type: event_type.as_str().to_string(),
+            message: message.into(),
+        }
+    }
+}
+
 pub mod serde_utils {
     use serde::{Deserialize, Serialize};
 
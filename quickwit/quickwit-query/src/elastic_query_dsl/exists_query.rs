@@ -20,18 +20,21 @@
 use serde::Deserialize;
 
 use crate::elastic_query_dsl::ConvertableToQueryAst;
+use crate::not_nan_f32::NotNaNf32;
 use crate::query_ast::{self, QueryAst};
 
 #[derive(Deserialize, Clone, Eq, PartialEq, Debug)]
 pub struct ExistsQuery {
     field: String,
+    #[serde(default)]
+    boost: Option<NotNaNf32>,
 }
 
 impl ConvertableToQueryAst for ExistsQuery {
     fn convert_to_query_ast(self) -> anyhow::Result<QueryAst> {
-        Ok(QueryAst::FieldPresence(query_ast::FieldPresenceQuery {
-            field: self.field,
-        }))
+        let field_presence_ast =
+            QueryAst::FieldPresence(query_ast::FieldPresenceQuery { field: self.field });
+        Ok(field_presence_ast.boost(self.boost))
     }
 }
 
@@ -49,6 +52,7 @@ mod tests {
             &bool_query,
             &ExistsQuery {
                 field: "privileged".to_string(),
+                boost: None,
             }
         );
     }
@@ -27,19 +27,25 @@ mod match_query;
 mod multi_match;
 mod one_field_map;
 mod phrase_prefix_query;
+mod prefix_query;
 mod query_string_query;
 mod range_query;
+mod regexp_query;
 mod string_or_struct;
 mod term_query;
 mod terms_query;
+mod wildcard_query;
 
 use bool_query::BoolQuery;
 pub use one_field_map::OneFieldMap;
 use phrase_prefix_query::MatchPhrasePrefixQuery;
+use prefix_query::PrefixQuery;
 pub(crate) use query_string_query::QueryStringQuery;
 use range_query::RangeQuery;
+use regexp_query::RegexpQuery;
 pub(crate) use string_or_struct::StringOrStructForSerialization;
 use term_query::TermQuery;
+use wildcard_query::WildcardQuery;
 
 use crate::elastic_query_dsl::exists_query::ExistsQuery;
 use crate::elastic_query_dsl::match_bool_prefix::MatchBoolPrefixQuery;
@@ -78,6 +84,9 @@ pub(crate) enum ElasticQueryDslInner {
     MatchPhrasePrefix(MatchPhrasePrefixQuery),
     MultiMatch(MultiMatchQuery),
     Range(RangeQuery),
+    Regexp(RegexpQuery),
+    Prefix(PrefixQuery),
+    Wildcard(WildcardQuery),
     Exists(ExistsQuery),
 }
 
@@ -123,6 +132,9 @@ impl ConvertableToQueryAst for ElasticQueryDslInner {
                 match_phrase_prefix.convert_to_query_ast()
             }
             Self::Range(range_query) => range_query.convert_to_query_ast(),
+            Self::Regexp(regexp_query) => regexp_query.convert_to_query_ast(),
+            Self::Prefix(prefix_query) => prefix_query.convert_to_query_ast(),
+            Self::Wildcard(wildcard_query) => wildcard_query.convert_to_query_ast(),
             Self::Match(match_query) => match_query.convert_to_query_ast(),
             Self::Exists(exists_query) => exists_query.convert_to_query_ast(),
             Self::MultiMatch(multi_match_query) => multi_match_query.convert_to_query_ast(),
@@ -0,0 +1,132 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+use serde::Deserialize;
+
+use super::StringOrStructForSerialization;
+use crate::elastic_query_dsl::one_field_map::OneFieldMap;
+use crate::elastic_query_dsl::{ConvertableToQueryAst, ElasticQueryDslInner};
+use crate::not_nan_f32::NotNaNf32;
+use crate::query_ast::{self, escape_literal, QueryAst};
+
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(from = "OneFieldMap<StringOrStructForSerialization<PrefixQueryParams>>")]
+pub struct PrefixQuery {
+    pub field: String,
+    pub value: PrefixQueryParams,
+}
+
+impl From<OneFieldMap<StringOrStructForSerialization<PrefixQueryParams>>> for PrefixQuery {
+    fn from(one_field_map: OneFieldMap<StringOrStructForSerialization<PrefixQueryParams>>) -> Self {
+        PrefixQuery {
+            field: one_field_map.field,
+            value: one_field_map.value.inner,
+        }
+    }
+}
+
+impl From<String> for PrefixQueryParams {
+    fn from(prefix: String) -> PrefixQueryParams {
+        PrefixQueryParams {
+            value: prefix,
+            case_insensitive: false,
+            boost: None,
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct PrefixQueryParams {
+    pub value: String,
+    #[serde(default)]
+    pub case_insensitive: bool,
+    #[serde(default)]
+    pub boost: Option<NotNaNf32>,
+}
+
+impl From<PrefixQuery> for ElasticQueryDslInner {
+    fn from(prefix_query: PrefixQuery) -> Self {
+        Self::Prefix(prefix_query)
+    }
+}
+
+impl ConvertableToQueryAst for PrefixQuery {
+    fn convert_to_query_ast(self) -> anyhow::Result<QueryAst> {
+        let PrefixQueryParams {
+            value,
+            case_insensitive,
+            boost,
+        } = self.value;
+
+        // Like in Elasticsearch, the prefix is not normalized: it is matched against the indexed
+        // terms as is.
+        let regex = if case_insensitive {
+            format!("(?i){}.*", escape_literal(&value))
+        } else {
+            format!("{}.*", escape_literal(&value))
+        };
+        let regex_ast: QueryAst = query_ast::RegexQuery {
+            field: self.field,
+            regex,
+        }
+        .into();
+        Ok(regex_ast.boost(boost))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefix_query_deserialization() {
+        let prefix_query: PrefixQuery =
+            serde_json::from_str(r#"{ "user.id": { "value": "ki", "case_insensitive": true } }"#)
+                .unwrap();
+        assert_eq!(&prefix_query.field, "user.id");
+        assert_eq!(&prefix_query.value.value, "ki");
+        assert!(prefix_query.value.case_insensitive);
+
+        let query_ast = prefix_query.convert_to_query_ast().unwrap();
+        assert_eq!(
+            query_ast,
+            query_ast::RegexQuery::from_field_value("user.id", "(?i)ki.*").into()
+        );
+    }
+
+    #[test]
+    fn test_prefix_query_escapes_value() {
+        let prefix_query: PrefixQuery = serde_json::from_str(r#"{ "path": "/var/log" }"#).unwrap();
+        assert!(!prefix_query.value.case_insensitive);
+
+        let query_ast = prefix_query.convert_to_query_ast().unwrap();
+        assert_eq!(
+            query_ast,
+            query_ast::RegexQuery::from_field_value("path", r"\x{2f}var\x{2f}log.*").into()
+        );
+    }
+
+    #[test]
+    fn test_prefix_query_unsupported_parameter() {
+        serde_json::from_str::<PrefixQuery>(
+            r#"{ "user.id": { "value": "ki", "rewrite": "constant_score" } }"#,
+        )
+        .unwrap_err();
+    }
+}
@@ -0,0 +1,126 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+use serde::Deserialize;
+
+use super::StringOrStructForSerialization;
+use crate::elastic_query_dsl::one_field_map::OneFieldMap;
+use crate::elastic_query_dsl::{ConvertableToQueryAst, ElasticQueryDslInner};
+use crate::not_nan_f32::NotNaNf32;
+use crate::query_ast::{self, QueryAst};
+
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(from = "OneFieldMap<StringOrStructForSerialization<RegexpQueryParams>>")]
+pub struct RegexpQuery {
+    pub field: String,
+    pub value: RegexpQueryParams,
+}
+
+impl From<OneFieldMap<StringOrStructForSerialization<RegexpQueryParams>>> for RegexpQuery {
+    fn from(one_field_map: OneFieldMap<StringOrStructForSerialization<RegexpQueryParams>>) -> Self {
+        RegexpQuery {
+            field: one_field_map.field,
+            value: one_field_map.value.inner,
+        }
+    }
+}
+
+impl From<String> for RegexpQueryParams {
+    fn from(regex: String) -> RegexpQueryParams {
+        RegexpQueryParams {
+            value: regex,
+            case_insensitive: false,
+            boost: None,
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct RegexpQueryParams {
+    pub value: String,
+    #[serde(default)]
+    pub case_insensitive: bool,
+    #[serde(default)]
+    pub boost: Option<NotNaNf32>,
+}
+
+impl From<RegexpQuery> for ElasticQueryDslInner {
+    fn from(regexp_query: RegexpQuery) -> Self {
+        Self::Regexp(regexp_query)
+    }
+}
+
+impl ConvertableToQueryAst for RegexpQuery {
+    fn convert_to_query_ast(self) -> anyhow::Result<QueryAst> {
+        let RegexpQueryParams {
+            value,
+            case_insensitive,
+            boost,
+        } = self.value;
+        let regex = if case_insensitive {
+            format!("(?i){value}")
+        } else {
+            value
+        };
+        let regex_ast: QueryAst = query_ast::RegexQuery {
+            field: self.field,
+            regex,
+        }
+        .into();
+        Ok(regex_ast.boost(boost))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_regexp_query_deserialization() {
+        let regexp_query: RegexpQuery =
+            serde_json::from_str(r#"{ "user.id": { "value": "k.*y", "case_insensitive": true } }"#)
+                .unwrap();
+        assert_eq!(&regexp_query.field, "user.id");
+        assert_eq!(&regexp_query.value.value, "k.*y");
+        assert!(regexp_query.value.case_insensitive);
+
+        let query_ast = regexp_query.convert_to_query_ast().unwrap();
+        assert_eq!(
+            query_ast,
+            query_ast::RegexQuery::from_field_value("user.id", "(?i)k.*y").into()
+        );
+    }
+
+    #[test]
+    fn test_regexp_query_deserialization_in_short_format() {
+        let regexp_query: RegexpQuery = serde_json::from_str(r#"{ "user.id": "k.*y" }"#).unwrap();
+        assert_eq!(&regexp_query.field, "user.id");
+        assert_eq!(&regexp_query.value.value, "k.*y");
+        assert!(!regexp_query.value.case_insensitive);
+        assert!(regexp_query.value.boost.is_none());
+    }
+
+    #[test]
+    fn test_regexp_query_unsupported_parameter() {
+        serde_json::from_str::<RegexpQuery>(
+            r#"{ "user.id": { "value": "k.*y", "flags": "ALL" } }"#,
+        )
+        .unwrap_err();
+    }
+}
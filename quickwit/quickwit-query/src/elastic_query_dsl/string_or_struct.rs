@@ -31,7 +31,7 @@ use serde::{de, Deserialize, Deserializer};
 /// and the shorter.
 /// `{"field": "my query"}`
 ///
-/// If a integer is passed, we cast it to string. Floats are not supported.
+/// If a number or a boolean is passed, we cast it to string.
 ///
 /// We don't use untagged enum to support this, in order to keep good errors.
 ///
@@ -83,6 +83,16 @@ where
         self.visit_str(&v.to_string())
     }
 
+    fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E>
+    where E: de::Error {
+        self.visit_str(&v.to_string())
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E>
+    where E: de::Error {
+        self.visit_str(&v.to_string())
+    }
+
     fn visit_str<E>(self, query: &str) -> Result<Self::Value, E>
     where E: serde::de::Error {
         Ok(T::from(query.to_string()))
@@ -50,22 +50,34 @@ impl From<String> for TermQueryParams {
     }
 }
 
+/// Value of a term as accepted by Elasticsearch: filters generated by Kibana pass numbers and
+/// booleans as JSON values rather than strings.
 #[derive(Deserialize)]
 #[serde(untagged)]
-enum TermValue {
+pub(crate) enum TermValue {
     I64(i64),
     U64(u64),
+    F64(f64),
+    Bool(bool),
     Str(String),
 }
 
+impl From<TermValue> for String {
+    fn from(term_value: TermValue) -> String {
+        match term_value {
+            TermValue::I64(i64) => i64.to_string(),
+            TermValue::U64(u64) => u64.to_string(),
+            TermValue::F64(f64) => f64.to_string(),
+            TermValue::Bool(bool) => bool.to_string(),
+            TermValue::Str(str) => str,
+        }
+    }
+}
+
 fn deserialize_term_value<'de, D>(deserializer: D) -> Result<String, D::Error>
 where D: Deserializer<'de> {
     let term_value = TermValue::deserialize(deserializer)?;
-    match term_value {
-        TermValue::I64(i64) => Ok(i64.to_string()),
-        TermValue::U64(u64) => Ok(u64.to_string()),
-        TermValue::Str(str) => Ok(str),
-    }
+    Ok(term_value.into())
 }
 
 #[derive(PartialEq, Eq, Debug, Serialize, Deserialize, Clone)]
@@ -119,6 +131,15 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_term_query_non_string_values() {
+        let term_query: TermQuery = serde_json::from_str(r#"{ "public": true }"#).unwrap();
+        assert_eq!(&term_query, &term_query_from_field_value("public", "true"));
+
+        let term_query: TermQuery = serde_json::from_str(r#"{ "ratio": 0.5 }"#).unwrap();
+        assert_eq!(&term_query, &term_query_from_field_value("ratio", "0.5"));
+    }
+
     #[test]
     fn test_term_query_deserialization_in_short_format() {
         let term_query: TermQuery = serde_json::from_str(
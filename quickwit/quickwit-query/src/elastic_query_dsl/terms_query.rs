@@ -17,14 +17,15 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::{BTreeSet, HashMap};
+
 use serde::Deserialize;
 
-use crate::elastic_query_dsl::bool_query::BoolQuery;
 use crate::elastic_query_dsl::one_field_map::OneFieldMap;
-use crate::elastic_query_dsl::term_query::term_query_from_field_value;
-use crate::elastic_query_dsl::{ConvertableToQueryAst, ElasticQueryDslInner};
+use crate::elastic_query_dsl::term_query::{term_query_from_field_value, TermValue};
+use crate::elastic_query_dsl::{ConvertableToQueryAst, ElasticQueryDsl, ElasticQueryDslInner};
 use crate::not_nan_f32::NotNaNf32;
-use crate::query_ast::QueryAst;
+use crate::query_ast::{QueryAst, TermSetQuery, TermsLookupQuery};
 
 #[derive(PartialEq, Eq, Debug, Deserialize, Clone)]
 #[serde(try_from = "TermsQueryForSerialization")]
@@ -32,6 +33,21 @@ pub struct TermsQuery {
     pub boost: Option<NotNaNf32>,
     pub field: String,
     pub values: Vec<String>,
+    pub lookup: Option<TermsLookup>,
+}
+
+/// Fetches the terms from the documents of another index matching a query.
+///
+/// Unlike Elasticsearch, which fetches the terms from a single document identified by its ID,
+/// the lookup collects the values of `path` across all the documents matching `query`.
+#[derive(PartialEq, Eq, Debug, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct TermsLookup {
+    pub index: String,
+    pub path: String,
+    pub query: Box<ElasticQueryDsl>,
+    #[serde(default)]
+    pub max_terms: Option<usize>,
 }
 
 #[derive(Deserialize)]
@@ -44,44 +60,65 @@ struct TermsQueryForSerialization {
 
 #[derive(Deserialize)]
 #[serde(untagged)]
-enum OneOrMany {
-    One(String),
-    Many(Vec<String>),
-}
-impl From<OneOrMany> for Vec<String> {
-    fn from(one_or_many: OneOrMany) -> Vec<String> {
-        match one_or_many {
-            OneOrMany::One(one_value) => vec![one_value],
-            OneOrMany::Many(values) => values,
-        }
-    }
+enum OneOrManyOrLookup {
+    One(TermValue),
+    Many(Vec<TermValue>),
+    Lookup(TermsLookup),
 }
 
 impl TryFrom<TermsQueryForSerialization> for TermsQuery {
     type Error = serde_json::Error;
 
     fn try_from(value: TermsQueryForSerialization) -> serde_json::Result<TermsQuery> {
-        let one_field: OneFieldMap<OneOrMany> = serde_json::from_value(value.capture_other)?;
-        let one_field_values: Vec<String> = one_field.value.into();
+        let one_field: OneFieldMap<OneOrManyOrLookup> =
+            serde_json::from_value(value.capture_other)?;
+        let (values, lookup) = match one_field.value {
+            OneOrManyOrLookup::One(one_value) => (vec![one_value.into()], None),
+            OneOrManyOrLookup::Many(values) => {
+                (values.into_iter().map(String::from).collect(), None)
+            }
+            OneOrManyOrLookup::Lookup(lookup) => (Vec::new(), Some(lookup)),
+        };
         Ok(TermsQuery {
             boost: value.boost,
             field: one_field.field,
-            values: one_field_values,
+            values,
+            lookup,
         })
     }
 }
 
 impl ConvertableToQueryAst for TermsQuery {
     fn convert_to_query_ast(self) -> anyhow::Result<QueryAst> {
-        let term_queries: Vec<ElasticQueryDslInner> = self
-            .values
-            .into_iter()
-            .map(|value| term_query_from_field_value(self.field.clone(), value))
-            .map(ElasticQueryDslInner::from)
-            .collect();
-        let mut union = BoolQuery::union(term_queries);
-        union.boost = self.boost;
-        union.convert_to_query_ast()
+        if let Some(lookup) = self.lookup {
+            let lookup_query: QueryAst = (*lookup.query).try_into()?;
+            let terms_lookup_query = TermsLookupQuery {
+                field: self.field,
+                lookup_index_id: lookup.index,
+                lookup_field: lookup.path,
+                lookup_query: Box::new(lookup_query),
+                max_terms: lookup
+                    .max_terms
+                    .unwrap_or(TermsLookupQuery::DEFAULT_MAX_TERMS),
+            };
+            return Ok(QueryAst::from(terms_lookup_query).boost(self.boost));
+        }
+        let mut values: BTreeSet<String> = self.values.into_iter().collect();
+
+        if values.len() <= 1 {
+            let Some(value) = values.pop_first() else {
+                return Ok(QueryAst::MatchNone);
+            };
+            let term_query_ast =
+                term_query_from_field_value(self.field, value).convert_to_query_ast()?;
+            return Ok(term_query_ast.boost(self.boost));
+        }
+        // Lists of values generated by Kibana filters can be large: a term set query looks up all
+        // the terms at once instead of scoring a disjunction of as many term queries.
+        let term_set_query = TermSetQuery {
+            terms_per_field: HashMap::from([(self.field, values)]),
+        };
+        Ok(QueryAst::from(term_set_query).boost(self.boost))
     }
 }
 
@@ -126,4 +163,73 @@ mod tests {
         let boost: f32 = terms_query.boost.unwrap().into();
         assert!((boost - 2.0f32).abs() < 0.0001f32);
     }
+
+    #[test]
+    fn test_terms_query_non_string_values() {
+        let terms_query_json = r#"{ "status": [200, -1, 1.5, true, "ok"] }"#;
+        let terms_query: TermsQuery = serde_json::from_str(terms_query_json).unwrap();
+        assert_eq!(&terms_query.values[..], &["200", "-1", "1.5", "true", "ok"]);
+    }
+
+    #[test]
+    fn test_terms_query_to_query_ast() {
+        let terms_query: TermsQuery =
+            serde_json::from_str(r#"{ "user.id": ["happy", "hello", "happy"] }"#).unwrap();
+        let QueryAst::TermSet(term_set_query) = terms_query.convert_to_query_ast().unwrap() else {
+            panic!("expected term set query");
+        };
+        assert_eq!(term_set_query.terms_per_field.len(), 1);
+        assert_eq!(
+            term_set_query.terms_per_field["user.id"],
+            BTreeSet::from(["happy".to_string(), "hello".to_string()])
+        );
+
+        let terms_query: TermsQuery = serde_json::from_str(r#"{ "user.id": "hello" }"#).unwrap();
+        assert!(matches!(
+            terms_query.convert_to_query_ast().unwrap(),
+            QueryAst::Term(_)
+        ));
+
+        let terms_query: TermsQuery = serde_json::from_str(r#"{ "user.id": [] }"#).unwrap();
+        assert_eq!(
+            terms_query.convert_to_query_ast().unwrap(),
+            QueryAst::MatchNone
+        );
+    }
+
+    #[test]
+    fn test_terms_query_lookup() {
+        let terms_query_json = r#"{
+            "host": {
+                "index": "alerts",
+                "path": "host_name",
+                "query": { "term": { "severity": "high" } },
+                "max_terms": 100
+            }
+        }"#;
+        let terms_query: TermsQuery = serde_json::from_str(terms_query_json).unwrap();
+        assert_eq!(&terms_query.field, "host");
+        assert!(terms_query.values.is_empty());
+
+        let QueryAst::TermsLookup(terms_lookup_query) = terms_query.convert_to_query_ast().unwrap()
+        else {
+            panic!("expected terms lookup query");
+        };
+        assert_eq!(terms_lookup_query.field, "host");
+        assert_eq!(terms_lookup_query.lookup_index_id, "alerts");
+        assert_eq!(terms_lookup_query.lookup_field, "host_name");
+        assert_eq!(terms_lookup_query.max_terms, 100);
+        assert!(matches!(
+            *terms_lookup_query.lookup_query,
+            QueryAst::Term(_)
+        ));
+    }
+
+    #[test]
+    fn test_terms_query_lookup_unknown_field() {
+        let terms_query_json = r#"{
+            "host": { "index": "alerts", "id": "1", "path": "host_name" }
+        }"#;
+        serde_json::from_str::<TermsQuery>(terms_query_json).unwrap_err();
+    }
 }
@@ -0,0 +1,197 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+use anyhow::bail;
+use serde::Deserialize;
+
+use super::StringOrStructForSerialization;
+use crate::elastic_query_dsl::one_field_map::OneFieldMap;
+use crate::elastic_query_dsl::{ConvertableToQueryAst, ElasticQueryDslInner};
+use crate::not_nan_f32::NotNaNf32;
+use crate::query_ast::{self, escape_literal, QueryAst};
+
+/// Maximum length, in characters, of a wildcard pattern. Longer patterns are rejected before
+/// compiling them, on top of the limit on the number of states of the resulting automaton.
+const MAX_WILDCARD_PATTERN_LEN: usize = 1_000;
+
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(from = "OneFieldMap<StringOrStructForSerialization<WildcardQueryParams>>")]
+pub struct WildcardQuery {
+    pub field: String,
+    pub value: WildcardQueryParams,
+}
+
+impl From<OneFieldMap<StringOrStructForSerialization<WildcardQueryParams>>> for WildcardQuery {
+    fn from(
+        one_field_map: OneFieldMap<StringOrStructForSerialization<WildcardQueryParams>>,
+    ) -> Self {
+        WildcardQuery {
+            field: one_field_map.field,
+            value: one_field_map.value.inner,
+        }
+    }
+}
+
+impl From<String> for WildcardQueryParams {
+    fn from(pattern: String) -> WildcardQueryParams {
+        WildcardQueryParams {
+            value: pattern,
+            case_insensitive: false,
+            boost: None,
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct WildcardQueryParams {
+    #[serde(alias = "wildcard")]
+    pub value: String,
+    #[serde(default)]
+    pub case_insensitive: bool,
+    #[serde(default)]
+    pub boost: Option<NotNaNf32>,
+}
+
+impl From<WildcardQuery> for ElasticQueryDslInner {
+    fn from(wildcard_query: WildcardQuery) -> Self {
+        Self::Wildcard(wildcard_query)
+    }
+}
+
+/// Translates a wildcard pattern, where `*` matches any sequence of characters, `?` matches
+/// any character, and `\` escapes the next character, into a regular expression.
+fn wildcard_pattern_to_regex(pattern: &str) -> String {
+    let mut regex = String::with_capacity(pattern.len());
+    let mut chars = pattern.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            '\\' => {
+                if let Some(escaped_char) = chars.next() {
+                    regex.push_str(&escape_literal(&escaped_char.to_string()));
+                }
+            }
+            _ => regex.push_str(&escape_literal(&c.to_string())),
+        }
+    }
+    regex
+}
+
+impl ConvertableToQueryAst for WildcardQuery {
+    fn convert_to_query_ast(self) -> anyhow::Result<QueryAst> {
+        let WildcardQueryParams {
+            value,
+            case_insensitive,
+            boost,
+        } = self.value;
+
+        if value.chars().count() > MAX_WILDCARD_PATTERN_LEN {
+            bail!(
+                "wildcard pattern on field `{}` exceeds the maximum length of \
+                 {MAX_WILDCARD_PATTERN_LEN} characters",
+                self.field
+            );
+        }
+        // `*` matches all the documents with a value for the field.
+        if !value.is_empty() && value.chars().all(|c| c == '*') {
+            let field_presence_ast: QueryAst =
+                query_ast::FieldPresenceQuery { field: self.field }.into();
+            return Ok(field_presence_ast.boost(boost));
+        }
+        // Like in Elasticsearch, the pattern is not normalized: it is matched against the indexed
+        // terms as is.
+        let regex = if case_insensitive {
+            format!("(?i){}", wildcard_pattern_to_regex(&value))
+        } else {
+            wildcard_pattern_to_regex(&value)
+        };
+        let regex_ast: QueryAst = query_ast::RegexQuery {
+            field: self.field,
+            regex,
+        }
+        .into();
+        Ok(regex_ast.boost(boost))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wildcard_pattern_to_regex() {
+        assert_eq!(wildcard_pattern_to_regex("ki*y"), "ki.*y");
+        assert_eq!(wildcard_pattern_to_regex("?ushevent"), ".ushevent");
+        assert_eq!(wildcard_pattern_to_regex(r"a\*b."), r"a\x{2a}b\x{2e}");
+        assert_eq!(wildcard_pattern_to_regex(""), "");
+    }
+
+    #[test]
+    fn test_wildcard_query_deserialization() {
+        let wildcard_query: WildcardQuery = serde_json::from_str(
+            r#"{ "user.id": { "wildcard": "ki*y", "case_insensitive": true } }"#,
+        )
+        .unwrap();
+        assert_eq!(&wildcard_query.field, "user.id");
+        assert_eq!(&wildcard_query.value.value, "ki*y");
+        assert!(wildcard_query.value.case_insensitive);
+
+        let query_ast = wildcard_query.convert_to_query_ast().unwrap();
+        assert_eq!(
+            query_ast,
+            query_ast::RegexQuery::from_field_value("user.id", "(?i)ki.*y").into()
+        );
+
+        let wildcard_query: WildcardQuery =
+            serde_json::from_str(r#"{ "user.id": "k?y" }"#).unwrap();
+        assert!(!wildcard_query.value.case_insensitive);
+        assert_eq!(
+            wildcard_query.convert_to_query_ast().unwrap(),
+            query_ast::RegexQuery::from_field_value("user.id", "k.y").into()
+        );
+    }
+
+    #[test]
+    fn test_wildcard_query_match_all_values() {
+        let wildcard_query: WildcardQuery = serde_json::from_str(r#"{ "user.id": "**" }"#).unwrap();
+        assert_eq!(
+            wildcard_query.convert_to_query_ast().unwrap(),
+            query_ast::FieldPresenceQuery {
+                field: "user.id".to_string()
+            }
+            .into()
+        );
+    }
+
+    #[test]
+    fn test_wildcard_query_guardrails() {
+        let pattern = "a*".repeat(MAX_WILDCARD_PATTERN_LEN);
+        let wildcard_query: WildcardQuery =
+            serde_json::from_value(serde_json::json!({ "user.id": pattern })).unwrap();
+        let error = wildcard_query.convert_to_query_ast().unwrap_err();
+        assert!(error.to_string().contains("exceeds the maximum length"));
+
+        serde_json::from_str::<WildcardQuery>(
+            r#"{ "user.id": { "value": "ki*y", "rewrite": "constant_score" } }"#,
+        )
+        .unwrap_err();
+    }
+}
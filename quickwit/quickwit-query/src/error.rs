@@ -45,6 +45,8 @@ pub enum InvalidQuery {
     FieldDoesNotExist { full_path: String },
     #[error("Json field root is not a valid search field: `{full_path}`")]
     JsonFieldRootNotSearchable { full_path: String },
+    #[error("invalid regex `{pattern}`: {reason}")]
+    InvalidRegex { pattern: String, reason: String },
     #[error("user query should have been parsed")]
     UserQueryNotParsed,
     #[error("{0}")]
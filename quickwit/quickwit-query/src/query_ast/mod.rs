@@ -17,6 +17,8 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
 use serde::{Deserialize, Serialize};
 use tantivy::query::BoostQuery as TantivyBoostQuery;
 use tantivy::schema::Schema as TantivySchema;
@@ -28,9 +30,11 @@ mod field_presence;
 mod full_text_query;
 mod phrase_prefix_query;
 mod range_query;
+mod regex_query;
 mod tantivy_query_ast;
 mod term_query;
 mod term_set_query;
+mod terms_lookup_query;
 mod user_input_query;
 pub(crate) mod utils;
 mod visitor;
@@ -41,9 +45,15 @@ pub use field_presence::FieldPresenceQuery;
 pub use full_text_query::{FullTextMode, FullTextParams, FullTextQuery};
 pub use phrase_prefix_query::PhrasePrefixQuery;
 pub use range_query::RangeQuery;
+pub(crate) use regex_query::escape_literal;
+pub use regex_query::{
+    max_regex_automaton_states, set_max_regex_automaton_states, RegexQuery,
+    DEFAULT_MAX_REGEX_AUTOMATON_STATES,
+};
 use tantivy_query_ast::TantivyQueryAst;
 pub use term_query::TermQuery;
 pub use term_set_query::TermSetQuery;
+pub use terms_lookup_query::TermsLookupQuery;
 pub use user_input_query::UserInputQuery;
 pub use visitor::QueryAstVisitor;
 pub use wildcard_query::WildcardQuery;
@@ -57,10 +67,12 @@ pub enum QueryAst {
     Bool(BoolQuery),
     Term(TermQuery),
     TermSet(TermSetQuery),
+    TermsLookup(TermsLookupQuery),
     FieldPresence(FieldPresenceQuery),
     FullText(FullTextQuery),
     PhrasePrefix(PhrasePrefixQuery),
     Range(RangeQuery),
+    Regex(RegexQuery),
     UserInput(UserInputQuery),
     Wildcard(WildcardQuery),
     MatchAll,
@@ -97,12 +109,15 @@ impl QueryAst {
             }
             ast @ QueryAst::Term(_)
             | ast @ QueryAst::TermSet(_)
+            // The lookup query targets another index and is parsed when the lookup is executed.
+            | ast @ QueryAst::TermsLookup(_)
             | ast @ QueryAst::FullText(_)
             | ast @ QueryAst::PhrasePrefix(_)
             | ast @ QueryAst::MatchAll
             | ast @ QueryAst::MatchNone
             | ast @ QueryAst::FieldPresence(_)
             | ast @ QueryAst::Range(_)
+            | ast @ QueryAst::Regex(_)
             | ast @ QueryAst::Wildcard(_) => Ok(ast),
             QueryAst::UserInput(user_text_query) => {
                 user_text_query.parse_user_query(default_search_fields)
@@ -117,6 +132,88 @@ impl QueryAst {
         }
     }
 
+    /// Replaces the field aliases targeted by the query with their target fields.
+    ///
+    /// This is meant to be called after [`QueryAst::parse_user_query`].
+    pub fn resolve_field_aliases(self, field_aliases: &BTreeMap<String, String>) -> QueryAst {
+        if field_aliases.is_empty() {
+            return self;
+        }
+        let resolve = |field: String| resolve_field_alias(field, field_aliases);
+        match self {
+            QueryAst::Bool(BoolQuery {
+                must,
+                must_not,
+                should,
+                filter,
+            }) => BoolQuery {
+                must: resolve_field_aliases_in_asts(must, field_aliases),
+                must_not: resolve_field_aliases_in_asts(must_not, field_aliases),
+                should: resolve_field_aliases_in_asts(should, field_aliases),
+                filter: resolve_field_aliases_in_asts(filter, field_aliases),
+            }
+            .into(),
+            QueryAst::Term(mut term_query) => {
+                term_query.field = resolve(term_query.field);
+                term_query.into()
+            }
+            QueryAst::TermSet(TermSetQuery { terms_per_field }) => {
+                let mut resolved_terms_per_field: HashMap<String, BTreeSet<String>> =
+                    HashMap::with_capacity(terms_per_field.len());
+                for (field, terms) in terms_per_field {
+                    resolved_terms_per_field
+                        .entry(resolve(field))
+                        .or_default()
+                        .extend(terms);
+                }
+                TermSetQuery {
+                    terms_per_field: resolved_terms_per_field,
+                }
+                .into()
+            }
+            // The lookup field and query target another index and are left untouched.
+            QueryAst::TermsLookup(mut terms_lookup_query) => {
+                terms_lookup_query.field = resolve(terms_lookup_query.field);
+                terms_lookup_query.into()
+            }
+            QueryAst::FieldPresence(mut field_presence_query) => {
+                field_presence_query.field = resolve(field_presence_query.field);
+                field_presence_query.into()
+            }
+            QueryAst::FullText(mut full_text_query) => {
+                full_text_query.field = resolve(full_text_query.field);
+                full_text_query.into()
+            }
+            QueryAst::PhrasePrefix(mut phrase_prefix_query) => {
+                phrase_prefix_query.field = resolve(phrase_prefix_query.field);
+                phrase_prefix_query.into()
+            }
+            QueryAst::Range(mut range_query) => {
+                range_query.field = resolve(range_query.field);
+                range_query.into()
+            }
+            QueryAst::Regex(mut regex_query) => {
+                regex_query.field = resolve(regex_query.field);
+                regex_query.into()
+            }
+            QueryAst::Wildcard(mut wildcard_query) => {
+                wildcard_query.field = resolve(wildcard_query.field);
+                wildcard_query.into()
+            }
+            QueryAst::UserInput(mut user_input_query) => {
+                user_input_query.default_fields = user_input_query
+                    .default_fields
+                    .map(|default_fields| default_fields.into_iter().map(resolve).collect());
+                user_input_query.into()
+            }
+            ast @ QueryAst::MatchAll | ast @ QueryAst::MatchNone => ast,
+            QueryAst::Boost { underlying, boost } => QueryAst::Boost {
+                underlying: Box::new(underlying.resolve_field_aliases(field_aliases)),
+                boost,
+            },
+        }
+    }
+
     pub fn boost(self, scale_boost_opt: Option<NotNaNf32>) -> Self {
         let Some(scale_boost) = scale_boost_opt else {
             return self;
@@ -221,6 +318,12 @@ impl BuildTantivyAst for QueryAst {
                 search_fields,
                 with_validation,
             ),
+            QueryAst::TermsLookup(terms_lookup) => terms_lookup.build_tantivy_ast_call(
+                schema,
+                tokenizer_manager,
+                search_fields,
+                with_validation,
+            ),
             QueryAst::FullText(full_text_query) => full_text_query.build_tantivy_ast_call(
                 schema,
                 tokenizer_manager,
@@ -247,6 +350,12 @@ impl BuildTantivyAst for QueryAst {
                 search_fields,
                 with_validation,
             ),
+            QueryAst::Regex(regex) => regex.build_tantivy_ast_call(
+                schema,
+                tokenizer_manager,
+                search_fields,
+                with_validation,
+            ),
         }
     }
 }
@@ -274,6 +383,32 @@ fn parse_user_query_in_asts(
         .collect::<anyhow::Result<_>>()
 }
 
+fn resolve_field_aliases_in_asts(
+    asts: Vec<QueryAst>,
+    field_aliases: &BTreeMap<String, String>,
+) -> Vec<QueryAst> {
+    asts.into_iter()
+        .map(|ast| ast.resolve_field_aliases(field_aliases))
+        .collect()
+}
+
+/// Returns the field targeted by a field name that may be an alias, or a path within an alias of
+/// a JSON field.
+pub fn resolve_field_alias(field_name: String, field_aliases: &BTreeMap<String, String>) -> String {
+    if let Some(target) = field_aliases.get(&field_name) {
+        return target.clone();
+    }
+    for (alias, target) in field_aliases {
+        if let Some(subpath) = field_name
+            .strip_prefix(alias.as_str())
+            .and_then(|suffix| suffix.strip_prefix('.'))
+        {
+            return format!("{target}.{subpath}");
+        }
+    }
+    field_name
+}
+
 /// Parses a user query and returns a JSON query AST.
 ///
 /// The resulting query does not include `UserInputQuery` nodes.
@@ -322,9 +457,12 @@ pub fn query_ast_from_user_text(user_text: &str, default_fields: Option<Vec<Stri
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeMap;
+
     use crate::query_ast::tantivy_query_ast::TantivyQueryAst;
     use crate::query_ast::{
-        query_ast_from_user_text, BoolQuery, BuildTantivyAst, QueryAst, UserInputQuery,
+        qast_helper, query_ast_from_user_text, resolve_field_alias, BoolQuery, BuildTantivyAst,
+        QueryAst, UserInputQuery,
     };
     use crate::{create_default_quickwit_tokenizer_manager, BooleanOperand, InvalidQuery};
 
@@ -444,4 +582,36 @@ mod tests {
         };
         assert_eq!(input_query.default_operator, BooleanOperand::And);
     }
+
+    #[test]
+    fn test_resolve_field_aliases() {
+        let field_aliases = BTreeMap::from([
+            ("message".to_string(), "body".to_string()),
+            ("ts".to_string(), "timestamp".to_string()),
+            ("labels".to_string(), "attributes.labels".to_string()),
+        ]);
+        let query_ast = qast_helper(
+            "message:hello AND ts:[1 TO 10] AND labels.env:prod AND messages:world",
+            &[],
+        )
+        .resolve_field_aliases(&field_aliases);
+        let expected_query_ast = qast_helper(
+            "body:hello AND timestamp:[1 TO 10] AND attributes.labels.env:prod AND messages:world",
+            &[],
+        );
+        assert_eq!(query_ast, expected_query_ast);
+
+        assert_eq!(
+            resolve_field_alias("message".to_string(), &field_aliases),
+            "body"
+        );
+        assert_eq!(
+            resolve_field_alias("labels.env".to_string(), &field_aliases),
+            "attributes.labels.env"
+        );
+        assert_eq!(
+            resolve_field_alias("messages".to_string(), &field_aliases),
+            "messages"
+        );
+    }
 }
@@ -0,0 +1,288 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use tantivy::json_utils::JsonTermWriter;
+use tantivy::schema::{Field, FieldType, Schema as TantivySchema};
+use tantivy::Term;
+use tantivy_fst::{Automaton, Regex};
+
+use super::{BuildTantivyAst, QueryAst};
+use crate::query_ast::TantivyQueryAst;
+use crate::tokenizers::TokenizerManager;
+use crate::{find_field_or_hit_dynamic, InvalidQuery};
+
+/// Default maximum number of states of the automaton compiled for a regex query.
+pub const DEFAULT_MAX_REGEX_AUTOMATON_STATES: usize = 10_000;
+
+static MAX_REGEX_AUTOMATON_STATES: AtomicUsize =
+    AtomicUsize::new(DEFAULT_MAX_REGEX_AUTOMATON_STATES);
+
+/// Sets the maximum number of states of the automaton compiled for a regex query. Regex queries
+/// exceeding this limit are rejected.
+pub fn set_max_regex_automaton_states(max_num_states: usize) {
+    MAX_REGEX_AUTOMATON_STATES.store(max_num_states, Ordering::Relaxed);
+}
+
+/// Returns the maximum number of states of the automaton compiled for a regex query.
+pub fn max_regex_automaton_states() -> usize {
+    MAX_REGEX_AUTOMATON_STATES.load(Ordering::Relaxed)
+}
+
+/// A Regex query matches the terms of a field against a regular expression.
+///
+/// The regular expression must match the whole term. It is applied to the indexed terms
+/// as is: the query value is not tokenized.
+#[derive(PartialEq, Eq, Debug, Serialize, Deserialize, Clone)]
+pub struct RegexQuery {
+    pub field: String,
+    pub regex: String,
+}
+
+impl From<RegexQuery> for QueryAst {
+    fn from(regex_query: RegexQuery) -> Self {
+        Self::Regex(regex_query)
+    }
+}
+
+impl RegexQuery {
+    pub fn from_field_value(field: impl ToString, regex: impl ToString) -> Self {
+        Self {
+            field: field.to_string(),
+            regex: regex.to_string(),
+        }
+    }
+}
+
+/// Escapes a literal so that it can be embedded in a regular expression.
+pub(crate) fn escape_literal(literal: &str) -> String {
+    let mut escaped = String::with_capacity(literal.len());
+    for c in literal.chars() {
+        if c.is_ascii_alphanumeric() {
+            escaped.push(c);
+        } else {
+            escaped.push_str(&format!("\\x{{{:x}}}", c as u32));
+        }
+    }
+    escaped
+}
+
+/// Returns the number of states reachable from the start state of the automaton, or `None` if
+/// it exceeds `max_num_states`.
+fn count_automaton_states(regex: &Regex, max_num_states: usize) -> Option<usize> {
+    let mut visited: HashSet<usize> = HashSet::new();
+    let mut stack: Vec<usize> = Vec::new();
+
+    if let Some(start_state) = regex.start() {
+        visited.insert(start_state);
+        stack.push(start_state);
+    }
+    while let Some(state) = stack.pop() {
+        for byte in 0..=u8::MAX {
+            let Some(next_state) = regex.accept(&Some(state), byte) else {
+                continue;
+            };
+            if visited.insert(next_state) {
+                if visited.len() > max_num_states {
+                    return None;
+                }
+                stack.push(next_state);
+            }
+        }
+    }
+    Some(visited.len())
+}
+
+/// Compiles the regular expression into an automaton, making sure it does not exceed
+/// `max_num_states` states.
+fn compile_regex(
+    user_pattern: &str,
+    pattern: &str,
+    max_num_states: usize,
+) -> Result<Regex, InvalidQuery> {
+    let regex = Regex::new(pattern).map_err(|error| InvalidQuery::InvalidRegex {
+        pattern: user_pattern.to_string(),
+        reason: error.to_string(),
+    })?;
+    if count_automaton_states(&regex, max_num_states).is_none() {
+        return Err(InvalidQuery::InvalidRegex {
+            pattern: user_pattern.to_string(),
+            reason: format!("automaton exceeds the limit of {max_num_states} states"),
+        });
+    }
+    Ok(regex)
+}
+
+impl RegexQuery {
+    /// Returns the regular expression matched against the term dictionary of the field.
+    ///
+    /// For JSON fields, terms are prefixed by their path and type, so the user pattern is
+    /// prefixed accordingly.
+    fn term_pattern(&self, schema: &TantivySchema) -> Result<(Field, String), InvalidQuery> {
+        let (field, field_entry, json_path) = find_field_or_hit_dynamic(&self.field, schema)?;
+
+        match field_entry.field_type() {
+            FieldType::Str(text_options) => {
+                if text_options.get_indexing_options().is_none() {
+                    return Err(InvalidQuery::SchemaError(format!(
+                        "field {} is not full-text searchable",
+                        field_entry.name()
+                    )));
+                }
+                Ok((field, self.regex.clone()))
+            }
+            FieldType::JsonObject(json_options) => {
+                if json_options.get_text_indexing_options().is_none() {
+                    return Err(InvalidQuery::SchemaError(format!(
+                        "field {} is not full-text searchable",
+                        field_entry.name()
+                    )));
+                }
+                let mut term = Term::with_capacity(100);
+                let mut json_term_writer = JsonTermWriter::from_field_and_json_path(
+                    field,
+                    json_path,
+                    json_options.is_expand_dots_enabled(),
+                    &mut term,
+                );
+                json_term_writer.set_str("");
+                let term_prefix =
+                    std::str::from_utf8(json_term_writer.term().serialized_value_bytes())
+                        .context("JSON path is not valid UTF-8")?;
+                let pattern = format!("{}(?:{})", escape_literal(term_prefix), self.regex);
+                Ok((field, pattern))
+            }
+            _ => Err(InvalidQuery::SchemaError(
+                "trying to run a Regex query on a non-text field".to_string(),
+            )),
+        }
+    }
+}
+
+impl BuildTantivyAst for RegexQuery {
+    fn build_tantivy_ast_impl(
+        &self,
+        schema: &TantivySchema,
+        _tokenizer_manager: &TokenizerManager,
+        _search_fields: &[String],
+        _with_validation: bool,
+    ) -> Result<TantivyQueryAst, InvalidQuery> {
+        let (field, pattern) = self.term_pattern(schema)?;
+        let regex = compile_regex(&self.regex, &pattern, max_regex_automaton_states())?;
+        let regex_query = tantivy::query::RegexQuery::from_regex(regex, field);
+        Ok(regex_query.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tantivy::schema::{Schema as TantivySchema, STORED, TEXT};
+
+    use super::*;
+    use crate::create_default_quickwit_tokenizer_manager;
+
+    #[test]
+    fn test_regex_query_text_field() {
+        let mut schema_builder = TantivySchema::builder();
+        schema_builder.add_text_field("body", TEXT);
+        schema_builder.add_u64_field("count", STORED);
+        let schema = schema_builder.build();
+        let tokenizer_manager = create_default_quickwit_tokenizer_manager();
+
+        let regex_query = RegexQuery::from_field_value("body", "ab[cd]+");
+        regex_query
+            .build_tantivy_ast_call(&schema, &tokenizer_manager, &[], true)
+            .unwrap();
+
+        let invalid_regex_query = RegexQuery::from_field_value("body", "ab[cd");
+        let error = invalid_regex_query
+            .build_tantivy_ast_call(&schema, &tokenizer_manager, &[], true)
+            .unwrap_err();
+        assert!(matches!(error, InvalidQuery::InvalidRegex { .. }));
+
+        let non_text_regex_query = RegexQuery::from_field_value("count", "1.*");
+        let error = non_text_regex_query
+            .build_tantivy_ast_call(&schema, &tokenizer_manager, &[], true)
+            .unwrap_err();
+        assert!(matches!(error, InvalidQuery::SchemaError(_)));
+    }
+
+    fn regex_matches(regex: &Regex, bytes: &[u8]) -> bool {
+        let mut state = regex.start();
+        for &byte in bytes {
+            state = regex.accept(&state, byte);
+        }
+        regex.is_match(&state)
+    }
+
+    #[test]
+    fn test_regex_query_json_field() {
+        let mut schema_builder = TantivySchema::builder();
+        schema_builder.add_json_field("attributes", TEXT);
+        let schema = schema_builder.build();
+
+        let regex_query = RegexQuery::from_field_value("attributes.service.name", "front.*");
+        let (field, pattern) = regex_query.term_pattern(&schema).unwrap();
+        assert!(pattern.ends_with("(?:front.*)"));
+        let regex = compile_regex(&regex_query.regex, &pattern, 1_000).unwrap();
+
+        let mut term = Term::with_capacity(100);
+        let mut json_term_writer =
+            JsonTermWriter::from_field_and_json_path(field, "service.name", false, &mut term);
+        json_term_writer.set_str("frontend");
+        assert!(regex_matches(
+            &regex,
+            json_term_writer.term().serialized_value_bytes()
+        ));
+        json_term_writer.set_str("backend");
+        assert!(!regex_matches(
+            &regex,
+            json_term_writer.term().serialized_value_bytes()
+        ));
+
+        let mut term = Term::with_capacity(100);
+        let mut json_term_writer =
+            JsonTermWriter::from_field_and_json_path(field, "service.version", false, &mut term);
+        json_term_writer.set_str("frontend");
+        assert!(!regex_matches(
+            &regex,
+            json_term_writer.term().serialized_value_bytes()
+        ));
+    }
+
+    #[test]
+    fn test_compile_regex_enforces_max_automaton_states() {
+        let pattern = "[a-z]{0,20}";
+        let num_states = count_automaton_states(&Regex::new(pattern).unwrap(), usize::MAX).unwrap();
+        assert!(num_states > 20);
+        compile_regex(pattern, pattern, num_states).unwrap();
+
+        let error = compile_regex(pattern, pattern, num_states - 1).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "invalid regex `[a-z]{{0,20}}`: automaton exceeds the limit of {} states",
+                num_states - 1
+            )
+        );
+    }
+}
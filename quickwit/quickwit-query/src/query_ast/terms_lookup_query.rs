@@ -0,0 +1,143 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{BTreeSet, HashMap};
+
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use tantivy::schema::Schema as TantivySchema;
+
+use crate::query_ast::{BuildTantivyAst, QueryAst, TantivyQueryAst, TermSetQuery};
+use crate::tokenizers::TokenizerManager;
+use crate::InvalidQuery;
+
+/// TermsLookupQuery matches the documents whose `field` contains one of the values taken by
+/// `lookup_field` in the documents of `lookup_index_id` matching `lookup_query`.
+///
+/// The lookup is executed by the root searcher, which replaces this node by the equivalent
+/// [`TermSetQuery`] before dispatching the search to the leaves.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TermsLookupQuery {
+    pub field: String,
+    pub lookup_index_id: String,
+    pub lookup_field: String,
+    pub lookup_query: Box<QueryAst>,
+    #[serde(default = "TermsLookupQuery::default_max_terms")]
+    pub max_terms: usize,
+}
+
+impl TermsLookupQuery {
+    /// Default maximum number of distinct values a lookup can return.
+    pub const DEFAULT_MAX_TERMS: usize = 1_000;
+
+    /// Upper bound of `max_terms`.
+    pub const MAX_MAX_TERMS: usize = 10_000;
+
+    fn default_max_terms() -> usize {
+        Self::DEFAULT_MAX_TERMS
+    }
+
+    /// Builds the query substituted to this node once the lookup has been executed.
+    pub fn resolve(&self, terms: BTreeSet<String>) -> QueryAst {
+        let terms_per_field = HashMap::from([(self.field.clone(), terms)]);
+        TermSetQuery { terms_per_field }.into()
+    }
+}
+
+impl BuildTantivyAst for TermsLookupQuery {
+    fn build_tantivy_ast_impl(
+        &self,
+        _schema: &TantivySchema,
+        _tokenizer_manager: &TokenizerManager,
+        _search_fields: &[String],
+        _with_validation: bool,
+    ) -> Result<TantivyQueryAst, InvalidQuery> {
+        Err(InvalidQuery::Other(anyhow!(
+            "terms lookup on index `{}` should have been resolved by the root searcher",
+            self.lookup_index_id
+        )))
+    }
+}
+
+impl From<TermsLookupQuery> for QueryAst {
+    fn from(terms_lookup_query: TermsLookupQuery) -> Self {
+        QueryAst::TermsLookup(terms_lookup_query)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_terms_lookup_query_serde() {
+        let terms_lookup_query_json = r#"{
+            "type": "terms_lookup",
+            "field": "host",
+            "lookup_index_id": "alerts",
+            "lookup_field": "host_name",
+            "lookup_query": { "type": "match_all" }
+        }"#;
+        let query_ast: QueryAst = serde_json::from_str(terms_lookup_query_json).unwrap();
+        let QueryAst::TermsLookup(terms_lookup_query) = query_ast else {
+            panic!("expected terms lookup query");
+        };
+        assert_eq!(terms_lookup_query.field, "host");
+        assert_eq!(terms_lookup_query.lookup_index_id, "alerts");
+        assert_eq!(terms_lookup_query.lookup_field, "host_name");
+        assert_eq!(*terms_lookup_query.lookup_query, QueryAst::MatchAll);
+        assert_eq!(
+            terms_lookup_query.max_terms,
+            TermsLookupQuery::DEFAULT_MAX_TERMS
+        );
+    }
+
+    #[test]
+    fn test_terms_lookup_query_resolve() {
+        let terms_lookup_query = TermsLookupQuery {
+            field: "host".to_string(),
+            lookup_index_id: "alerts".to_string(),
+            lookup_field: "host_name".to_string(),
+            lookup_query: Box::new(QueryAst::MatchAll),
+            max_terms: 10,
+        };
+        let terms = BTreeSet::from(["host-1".to_string(), "host-2".to_string()]);
+        let QueryAst::TermSet(term_set_query) = terms_lookup_query.resolve(terms.clone()) else {
+            panic!("expected term set query");
+        };
+        assert_eq!(term_set_query.terms_per_field.len(), 1);
+        assert_eq!(term_set_query.terms_per_field["host"], terms);
+    }
+
+    #[test]
+    fn test_unresolved_terms_lookup_query_is_invalid() {
+        let terms_lookup_query = TermsLookupQuery {
+            field: "host".to_string(),
+            lookup_index_id: "alerts".to_string(),
+            lookup_field: "host_name".to_string(),
+            lookup_query: Box::new(QueryAst::MatchAll),
+            max_terms: 10,
+        };
+        let schema = TantivySchema::builder().build();
+        let tokenizer_manager = crate::create_default_quickwit_tokenizer_manager();
+        terms_lookup_query
+            .build_tantivy_ast_call(&schema, &tokenizer_manager, &[], true)
+            .unwrap_err();
+    }
+}
@@ -37,6 +37,10 @@ use crate::{BooleanOperand, InvalidQuery, JsonLiteral};
 
 const DEFAULT_PHRASE_QUERY_MAX_EXPANSION: u32 = 50;
 
+/// The tantivy query grammar does not support regexes. Before parsing the user query, the
+/// `field:/pattern/` regexes are replaced by quoted placeholders starting with this prefix.
+const REGEX_PLACEHOLDER_PREFIX: &str = "\u{1}regex";
+
 /// A query expressed in the tantivy query grammar DSL.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct UserInputQuery {
@@ -67,14 +71,83 @@ impl UserInputQuery {
             .as_ref()
             .map(|search_fields| &search_fields[..])
             .unwrap_or(default_search_fields);
-        let user_input_ast = tantivy::query_grammar::parse_query(&self.user_text)
+        let (user_text, regexes) = extract_regexes(&self.user_text)?;
+        let user_input_ast = tantivy::query_grammar::parse_query(&user_text)
             .map_err(|_| anyhow::anyhow!("failed to parse query: `{}`", &self.user_text))?;
         let default_occur = match self.default_operator {
             BooleanOperand::And => Occur::Must,
             BooleanOperand::Or => Occur::Should,
         };
-        convert_user_input_ast_to_query_ast(user_input_ast, default_occur, search_fields)
+        convert_user_input_ast_to_query_ast(user_input_ast, default_occur, search_fields, &regexes)
+    }
+}
+
+/// Extracts the `field:/pattern/` regexes of the user query and replaces them with quoted
+/// placeholders that the tantivy query grammar can parse.
+///
+/// Within a regex, `\/` stands for a literal `/`.
+fn extract_regexes(user_text: &str) -> anyhow::Result<(String, Vec<String>)> {
+    let mut output = String::with_capacity(user_text.len());
+    let mut regexes = Vec::new();
+    let mut quote_opt: Option<char> = None;
+    let mut chars = user_text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let previous_char_opt = output.chars().next_back();
+        output.push(c);
+
+        if c == '\\' {
+            if let Some(escaped_char) = chars.next() {
+                output.push(escaped_char);
+            }
+            continue;
+        }
+        if let Some(quote) = quote_opt {
+            if c == quote {
+                quote_opt = None;
+            }
+            continue;
+        }
+        let is_start_of_term = previous_char_opt
+            .map(|previous_char| previous_char.is_whitespace() || "():+-".contains(previous_char))
+            .unwrap_or(true);
+        if (c == '"' || c == '\'') && is_start_of_term {
+            quote_opt = Some(c);
+            continue;
+        }
+        let is_field_separator = c == ':' && !is_start_of_term;
+        if !is_field_separator || chars.peek() != Some(&'/') {
+            continue;
+        }
+        chars.next();
+        let mut regex = String::new();
+        loop {
+            match chars.next() {
+                Some('/') => break,
+                Some('\\') => match chars.next() {
+                    Some('/') => regex.push('/'),
+                    Some(escaped_char) => {
+                        regex.push('\\');
+                        regex.push(escaped_char);
+                    }
+                    None => regex.push('\\'),
+                },
+                Some(regex_char) => regex.push(regex_char),
+                None => anyhow::bail!("unterminated regex in query: `{user_text}`"),
+            }
+        }
+        output.push_str(&format!("\"{REGEX_PLACEHOLDER_PREFIX}{}\"", regexes.len()));
+        regexes.push(regex);
     }
+    Ok((output, regexes))
+}
+
+fn lookup_regex<'a>(phrase: &str, regexes: &'a [String]) -> Option<&'a str> {
+    let regex_ord: usize = phrase
+        .strip_prefix(REGEX_PLACEHOLDER_PREFIX)?
+        .parse()
+        .ok()?;
+    regexes.get(regex_ord).map(String::as_str)
 }
 
 impl From<UserInputQuery> for QueryAst {
@@ -99,6 +172,7 @@ fn convert_user_input_ast_to_query_ast(
     user_input_ast: UserInputAst,
     default_occur: Occur,
     default_search_fields: &[String],
+    regexes: &[String],
 ) -> anyhow::Result<QueryAst> {
     match user_input_ast {
         UserInputAst::Clause(clause) => {
@@ -108,6 +182,7 @@ fn convert_user_input_ast_to_query_ast(
                     sub_ast,
                     default_occur,
                     default_search_fields,
+                    regexes,
                 )?;
                 let children_ast_for_occur: &mut Vec<QueryAst> =
                     match occur_opt.unwrap_or(default_occur) {
@@ -121,7 +196,7 @@ fn convert_user_input_ast_to_query_ast(
         }
         UserInputAst::Leaf(leaf) => match *leaf {
             UserInputLeaf::Literal(literal) => {
-                convert_user_input_literal(literal, default_search_fields)
+                convert_user_input_literal(literal, default_search_fields, regexes)
             }
             UserInputLeaf::All => Ok(QueryAst::MatchAll),
             UserInputLeaf::Range {
@@ -170,6 +245,7 @@ fn convert_user_input_ast_to_query_ast(
                 *underlying,
                 default_occur,
                 default_search_fields,
+                regexes,
             )?;
             let boost: NotNaNf32 = (boost as f32)
                 .try_into()
@@ -210,6 +286,7 @@ fn is_wildcard(phrase: &str) -> bool {
 fn convert_user_input_literal(
     user_input_literal: UserInputLiteral,
     default_search_fields: &[String],
+    regexes: &[String],
 ) -> anyhow::Result<QueryAst> {
     let UserInputLiteral {
         field_name,
@@ -218,6 +295,11 @@ fn convert_user_input_literal(
         delimiter,
         slop,
     } = user_input_literal;
+    if delimiter == Delimiter::DoubleQuotes {
+        if let (Some(field_name), Some(regex)) = (&field_name, lookup_regex(&phrase, regexes)) {
+            return Ok(query_ast::RegexQuery::from_field_value(field_name, regex).into());
+        }
+    }
     let field_names: Vec<String> = if let Some(field_name) = field_name {
         vec![field_name]
     } else {
@@ -285,7 +367,8 @@ fn convert_user_input_literal(
 #[cfg(test)]
 mod tests {
     use crate::query_ast::{
-        BoolQuery, BuildTantivyAst, FullTextMode, FullTextQuery, QueryAst, UserInputQuery,
+        BoolQuery, BuildTantivyAst, FullTextMode, FullTextQuery, QueryAst, RegexQuery,
+        UserInputQuery,
     };
     use crate::{create_default_quickwit_tokenizer_manager, BooleanOperand, InvalidQuery};
 
@@ -505,4 +588,48 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_extract_regexes() {
+        let (user_text, regexes) =
+            super::extract_regexes(r#"title:/ab.*/ AND body:"a:/b/" url:/\/path\/[0-9]+\./"#)
+                .unwrap();
+        assert_eq!(
+            user_text,
+            "title:\"\u{1}regex0\" AND body:\"a:/b/\" url:\"\u{1}regex1\""
+        );
+        assert_eq!(
+            regexes,
+            vec!["ab.*".to_string(), r"/path/[0-9]+\.".to_string()]
+        );
+
+        let (user_text, regexes) = super::extract_regexes("date:2023 /abc/").unwrap();
+        assert_eq!(user_text, "date:2023 /abc/");
+        assert!(regexes.is_empty());
+
+        super::extract_regexes("title:/abc").unwrap_err();
+    }
+
+    #[test]
+    fn test_user_input_query_regex() {
+        let ast = UserInputQuery {
+            user_text: "title:/ab[cd]+/ OR body:hello".to_string(),
+            default_fields: None,
+            default_operator: BooleanOperand::Or,
+        }
+        .parse_user_query(&[])
+        .unwrap();
+        let QueryAst::Bool(BoolQuery { should, .. }) = ast else {
+            panic!()
+        };
+        assert_eq!(should.len(), 2);
+        assert_eq!(
+            should[0],
+            RegexQuery::from_field_value("title", "ab[cd]+").into()
+        );
+        let QueryAst::FullText(full_text_query) = &should[1] else {
+            panic!()
+        };
+        assert_eq!(&full_text_query.field, "body");
+    }
 }
@@ -21,8 +21,8 @@ use crate::not_nan_f32::NotNaNf32;
 use crate::query_ast::field_presence::FieldPresenceQuery;
 use crate::query_ast::user_input_query::UserInputQuery;
 use crate::query_ast::{
-    BoolQuery, FullTextQuery, PhrasePrefixQuery, QueryAst, RangeQuery, TermQuery, TermSetQuery,
-    WildcardQuery,
+    BoolQuery, FullTextQuery, PhrasePrefixQuery, QueryAst, RangeQuery, RegexQuery, TermQuery,
+    TermSetQuery, TermsLookupQuery, WildcardQuery,
 };
 
 /// Simple trait to implement a Visitor over the QueryAst.
@@ -34,6 +34,9 @@ pub trait QueryAstVisitor<'a> {
             QueryAst::Bool(bool_query) => self.visit_bool(bool_query),
             QueryAst::Term(term_query) => self.visit_term(term_query),
             QueryAst::TermSet(term_set_query) => self.visit_term_set(term_set_query),
+            QueryAst::TermsLookup(terms_lookup_query) => {
+                self.visit_terms_lookup(terms_lookup_query)
+            }
             QueryAst::FullText(full_text_query) => self.visit_full_text(full_text_query),
             QueryAst::PhrasePrefix(phrase_prefix_query) => {
                 self.visit_phrase_prefix(phrase_prefix_query)
@@ -45,6 +48,7 @@ pub trait QueryAstVisitor<'a> {
             QueryAst::UserInput(user_text_query) => self.visit_user_text(user_text_query),
             QueryAst::FieldPresence(exists) => self.visit_exists(exists),
             QueryAst::Wildcard(wildcard) => self.visit_wildcard(wildcard),
+            QueryAst::Regex(regex) => self.visit_regex(regex),
         }
     }
 
@@ -69,6 +73,14 @@ pub trait QueryAstVisitor<'a> {
         Ok(())
     }
 
+    /// The lookup query targets another index, so it is not visited by default.
+    fn visit_terms_lookup(
+        &mut self,
+        _terms_lookup_query: &'a TermsLookupQuery,
+    ) -> Result<(), Self::Err> {
+        Ok(())
+    }
+
     fn visit_full_text(&mut self, _full_text: &'a FullTextQuery) -> Result<(), Self::Err> {
         Ok(())
     }
@@ -111,4 +123,8 @@ pub trait QueryAstVisitor<'a> {
     fn visit_wildcard(&mut self, _wildcard_query: &'a WildcardQuery) -> Result<(), Self::Err> {
         Ok(())
     }
+
+    fn visit_regex(&mut self, _regex_query: &'a RegexQuery) -> Result<(), Self::Err> {
+        Ok(())
+    }
 }
@@ -60,6 +60,11 @@ impl Error {
 pub struct ApiError {
     pub message: Option<String>,
     pub code: StatusCode,
+    /// Machine-readable code of the error returned by Quickwit, for instance `rate_limited` or
+    /// `not_found`.
+    pub error_code: Option<String>,
+    /// Whether the request may succeed if retried as is.
+    pub retryable: bool,
 }
 
 // Implement `Display` for `ApiError`.
@@ -76,4 +81,8 @@ impl std::fmt::Display for ApiError {
 #[derive(Deserialize)]
 pub(crate) struct ErrorResponsePayload {
     pub message: String,
+    #[serde(default)]
+    pub code: Option<String>,
+    #[serde(default)]
+    pub retryable: bool,
 }
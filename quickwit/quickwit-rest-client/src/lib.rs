@@ -17,12 +17,13 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use std::io::SeekFrom;
 use std::path::Path;
 use std::{io, mem};
 
 use bytes::Bytes;
 use tokio::fs::File;
-use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncSeekExt, BufReader};
 use tracing::warn;
 
 pub mod error;
@@ -39,13 +40,27 @@ pub(crate) struct BatchLineReader {
     alloc_num_bytes: usize,
     max_batch_num_bytes: usize,
     num_lines: usize,
+    /// Offset in the source of the end of the last line read.
+    num_bytes_read: u64,
     has_next: bool,
 }
 
 impl BatchLineReader {
     pub async fn from_file(filepath: &Path, max_batch_num_bytes: usize) -> io::Result<Self> {
-        let file = File::open(&filepath).await?;
-        Ok(Self::new(Box::new(file), max_batch_num_bytes))
+        Self::from_file_at_offset(filepath, 0, max_batch_num_bytes).await
+    }
+
+    /// Reads the file starting at `offset`, which must be located at the beginning of a line.
+    pub async fn from_file_at_offset(
+        filepath: &Path,
+        offset: u64,
+        max_batch_num_bytes: usize,
+    ) -> io::Result<Self> {
+        let mut file = File::open(&filepath).await?;
+        file.seek(SeekFrom::Start(offset)).await?;
+        let mut batch_reader = Self::new(Box::new(file), max_batch_num_bytes);
+        batch_reader.num_bytes_read = offset;
+        Ok(batch_reader)
     }
 
     pub fn from_stdin(max_batch_num_bytes: usize) -> Self {
@@ -63,6 +78,7 @@ impl BatchLineReader {
             alloc_num_bytes,
             max_batch_num_bytes,
             num_lines: 0,
+            num_bytes_read: 0,
             has_next: true,
         }
     }
@@ -70,6 +86,7 @@ impl BatchLineReader {
     pub async fn next_batch(&mut self) -> io::Result<Option<Bytes>> {
         loop {
             let line_num_bytes = self.buf_reader.read_until(b'\n', &mut self.buffer).await?;
+            self.num_bytes_read += line_num_bytes as u64;
 
             if line_num_bytes > self.max_batch_num_bytes {
                 warn!(
@@ -111,6 +128,12 @@ impl BatchLineReader {
         self.has_next
     }
 
+    /// Returns the offset in the source right after the last line of the last batch returned,
+    /// skipped lines included. Reading the source again from this offset yields the next batches.
+    pub fn source_offset(&self) -> u64 {
+        self.num_bytes_read - self.buffer.len() as u64
+    }
+
     fn from_string(payload: impl ToString, max_batch_num_bytes: usize) -> Self {
         Self::new(
             Box::new(std::io::Cursor::new(payload.to_string().into_bytes())),
@@ -194,4 +217,42 @@ mod tests {
             assert!(batch_reader.next_batch().await.unwrap().is_none());
         }
     }
+
+    #[tokio::test]
+    async fn test_batch_reader_source_offset() {
+        let mut batch_reader = BatchLineReader::from_string("foo\nfoobarquxbaz\nbar\nqux\n", 10);
+        assert_eq!(batch_reader.source_offset(), 0);
+        assert_eq!(
+            &batch_reader.next_batch().await.unwrap().unwrap()[..],
+            b"foo\nbar\n"
+        );
+        assert_eq!(batch_reader.source_offset(), 21);
+        assert_eq!(
+            &batch_reader.next_batch().await.unwrap().unwrap()[..],
+            b"qux\n"
+        );
+        assert_eq!(batch_reader.source_offset(), 25);
+        assert!(batch_reader.next_batch().await.unwrap().is_none());
+        assert_eq!(batch_reader.source_offset(), 25);
+    }
+
+    #[tokio::test]
+    async fn test_batch_reader_from_file_at_offset() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let filepath = temp_dir.path().join("docs.ndjson");
+        tokio::fs::write(&filepath, b"foo\nbar\nqux\n")
+            .await
+            .unwrap();
+
+        let mut batch_reader = BatchLineReader::from_file_at_offset(&filepath, 4, 10)
+            .await
+            .unwrap();
+        assert_eq!(batch_reader.source_offset(), 4);
+        assert_eq!(
+            &batch_reader.next_batch().await.unwrap().unwrap()[..],
+            b"bar\nqux\n"
+        );
+        assert_eq!(batch_reader.source_offset(), 12);
+        assert!(batch_reader.next_batch().await.unwrap().is_none());
+    }
 }
@@ -47,22 +47,35 @@ impl ApiResponse {
         Ok(())
     }
 
-    async fn extract_error_message(self) -> Option<String> {
+    async fn extract_error_payload(self) -> Option<ErrorResponsePayload> {
         let error_body_bytes = self.inner.bytes().await.ok()?;
         let error_body_text = std::str::from_utf8(&error_body_bytes).ok()?;
         if let Ok(error_payload) = serde_json::from_str::<ErrorResponsePayload>(error_body_text) {
-            Some(error_payload.message)
+            Some(error_payload)
         } else {
-            Some(error_body_text.to_string())
+            Some(ErrorResponsePayload {
+                message: error_body_text.to_string(),
+                code: None,
+                retryable: false,
+            })
         }
     }
 
     async fn api_error(self) -> Error {
         let code = self.inner.status();
-        let error_message = self.extract_error_message().await;
+        let (message, error_code, retryable) = match self.extract_error_payload().await {
+            Some(error_payload) => (
+                Some(error_payload.message),
+                error_payload.code,
+                error_payload.retryable,
+            ),
+            None => (None, None, false),
+        };
         Error::from(ApiError {
-            message: error_message,
+            message,
             code,
+            error_code,
+            retryable,
         })
     }
 
@@ -80,6 +93,8 @@ impl ApiResponse {
 pub enum IngestSource {
     Str(String),
     File(PathBuf),
+    /// Reads the file starting at the given byte offset, for instance, to resume an ingestion.
+    FileAtOffset(PathBuf, u64),
     Stdin,
 }
 
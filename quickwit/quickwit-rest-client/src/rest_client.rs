@@ -17,16 +17,23 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Duration;
 
 use bytes::Bytes;
 use quickwit_cluster::ClusterSnapshot;
+use quickwit_common::uri::Uri;
 use quickwit_config::{ConfigFormat, SourceConfig};
+use quickwit_index_management::IndexSnapshotSummary;
 use quickwit_indexing::actors::IndexingServiceCounters;
 pub use quickwit_ingest::CommitType;
 use quickwit_metastore::{IndexMetadata, Split, SplitInfo};
-use quickwit_search::SearchResponseRest;
-use quickwit_serve::{ListSplitsQueryParams, ListSplitsResponse, SearchRequestQueryString};
+use quickwit_proto::control_plane::GetSourceBacklogResponse;
+use quickwit_search::{SearchCostEstimate, SearchResponseRest};
+use quickwit_serve::{
+    CloneIndexRequest, ListSplitsQueryParams, ListSplitsResponse, MergesStatus,
+    RestoreIndexRequest, SearchRequestQueryString, SnapshotIndexRequest,
+};
 use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
 use reqwest::{Client, ClientBuilder, Method, StatusCode, Url};
 use serde::Serialize;
@@ -46,25 +53,54 @@ pub const DEFAULT_CLIENT_SEARCH_TIMEOUT: Timeout = Timeout::from_mins(1);
 pub const DEFAULT_CLIENT_INGEST_TIMEOUT: Timeout = Timeout::from_mins(1);
 pub const DEFAULT_CLIENT_COMMIT_TIMEOUT: Timeout = Timeout::from_mins(30);
 
-struct Transport {
+struct Endpoint {
     base_url: Url,
     api_url: Url,
-    client: Client,
 }
 
-impl Transport {
-    fn new(endpoint: Url, connect_timeout: Timeout) -> Self {
-        let base_url = endpoint;
+impl Endpoint {
+    fn new(base_url: Url) -> Self {
         let api_url = base_url
             .join("api/v1/")
             .expect("Endpoint should not be malformed.");
+        Self { base_url, api_url }
+    }
+
+    fn join(&self, path: &str) -> Result<Url, Error> {
+        if path.starts_with('/') {
+            self.base_url.join(path)
+        } else {
+            self.api_url.join(path)
+        }
+        .map_err(|error| Error::UrlParse(error.to_string()))
+    }
+}
+
+/// Sends requests to one or several Quickwit nodes.
+///
+/// Requests are spread across the endpoints in a round-robin fashion. When a node cannot be
+/// reached, the request is retried on the next endpoint. Since the connection could not be
+/// established, the request was never delivered and retrying it is safe.
+struct Transport {
+    endpoints: Vec<Endpoint>,
+    next_endpoint_idx: AtomicUsize,
+    client: Client,
+}
+
+impl Transport {
+    fn new(endpoints: Vec<Url>, connect_timeout: Timeout) -> Self {
+        assert!(
+            !endpoints.is_empty(),
+            "At least one endpoint should be provided."
+        );
+        let endpoints = endpoints.into_iter().map(Endpoint::new).collect();
         let mut client_builder = ClientBuilder::new();
         if let Some(duration) = connect_timeout.as_duration_opt() {
             client_builder = client_builder.connect_timeout(duration);
         }
         Self {
-            base_url,
-            api_url,
+            endpoints,
+            next_endpoint_idx: AtomicUsize::new(0),
             client: client_builder.build().expect("Client should be built."),
         }
     }
@@ -79,37 +115,47 @@ impl Transport {
         body: Option<Bytes>,
         timeout: Timeout,
     ) -> Result<ApiResponse, Error> {
-        let url = if path.starts_with('/') {
-            self.base_url.join(path)
-        } else {
-            self.api_url.join(path)
-        }
-        .map_err(|error| Error::UrlParse(error.to_string()))?;
-        let mut request_builder = self.client.request(method, url);
-        if let Some(duration) = timeout.as_duration_opt() {
-            request_builder = request_builder.timeout(duration);
-        }
         let mut request_headers = HeaderMap::new();
         request_headers.insert(CONTENT_TYPE, HeaderValue::from_static(DEFAULT_CONTENT_TYPE));
         if let Some(header_map_val) = header_map {
             request_headers.extend(header_map_val.into_iter());
         }
-        request_builder = request_builder.headers(request_headers);
-        if let Some(bytes) = body {
-            request_builder = request_builder.body(bytes);
-        };
-        if let Some(qs) = query_string {
-            request_builder = request_builder.query(qs);
+        let num_endpoints = self.endpoints.len();
+        let first_endpoint_idx = self.next_endpoint_idx.fetch_add(1, Ordering::Relaxed);
+
+        for attempt in 0..num_endpoints {
+            let endpoint = &self.endpoints[(first_endpoint_idx + attempt) % num_endpoints];
+            let url = endpoint.join(path)?;
+            let mut request_builder = self.client.request(method.clone(), url);
+            if let Some(duration) = timeout.as_duration_opt() {
+                request_builder = request_builder.timeout(duration);
+            }
+            request_builder = request_builder.headers(request_headers.clone());
+            if let Some(bytes) = &body {
+                request_builder = request_builder.body(bytes.clone());
+            };
+            if let Some(qs) = query_string {
+                request_builder = request_builder.query(qs);
+            }
+            match request_builder.send().await {
+                Ok(response) => return Ok(ApiResponse::new(response)),
+                Err(error) if error.is_connect() && attempt + 1 < num_endpoints => {
+                    warn!(
+                        endpoint=%endpoint.base_url,
+                        error=%error,
+                        "failed to connect to endpoint, trying next endpoint"
+                    );
+                }
+                Err(error) => return Err(error.into()),
+            }
         }
-        let response = request_builder.send().await?;
-
-        Ok(ApiResponse::new(response))
+        unreachable!("the last attempt should always return");
     }
 }
 
 pub struct QuickwitClientBuilder {
-    /// Base url for the client
-    base_url: Url,
+    /// Base urls of the nodes the client sends requests to.
+    endpoints: Vec<Url>,
     /// Connection timeout.
     connect_timeout: Timeout,
     /// Timeout for most operations except search and ingest.
@@ -127,7 +173,7 @@ pub struct QuickwitClientBuilder {
 impl QuickwitClientBuilder {
     pub fn new(endpoint: Url) -> Self {
         QuickwitClientBuilder {
-            base_url: endpoint,
+            endpoints: vec![endpoint],
             connect_timeout: DEFAULT_CLIENT_CONNECT_TIMEOUT,
             timeout: DEFAULT_CLIENT_TIMEOUT,
             search_timeout: DEFAULT_CLIENT_SEARCH_TIMEOUT,
@@ -137,6 +183,12 @@ impl QuickwitClientBuilder {
         }
     }
 
+    /// Adds an endpoint to the list of nodes requests are load balanced across.
+    pub fn add_endpoint(mut self, endpoint: Url) -> Self {
+        self.endpoints.push(endpoint);
+        self
+    }
+
     pub fn connect_timeout(mut self, timeout: Timeout) -> Self {
         self.connect_timeout = timeout;
         self
@@ -169,7 +221,7 @@ impl QuickwitClientBuilder {
     }
 
     pub fn build(self) -> QuickwitClient {
-        let transport = Transport::new(self.base_url, self.connect_timeout);
+        let transport = Transport::new(self.endpoints, self.connect_timeout);
         QuickwitClient {
             transport,
             timeout: self.timeout,
@@ -228,6 +280,25 @@ impl QuickwitClient {
         Ok(search_response)
     }
 
+    pub async fn search_estimate(
+        &self,
+        index_id: &str,
+        search_query: SearchRequestQueryString,
+    ) -> Result<SearchCostEstimate, Error> {
+        let path = format!("{index_id}/search/estimate");
+        let bytes = serde_json::to_string(&search_query)
+            .unwrap()
+            .as_bytes()
+            .to_vec();
+        let body = Bytes::from(bytes);
+        let response = self
+            .transport
+            .send::<()>(Method::POST, &path, None, None, Some(body), self.timeout)
+            .await?;
+        let search_cost_estimate = response.deserialize().await?;
+        Ok(search_cost_estimate)
+    }
+
     pub fn indexes(&self) -> IndexClient {
         IndexClient::new(&self.transport, self.timeout)
     }
@@ -270,12 +341,16 @@ impl QuickwitClient {
             IngestSource::File(filepath) => {
                 BatchLineReader::from_file(&filepath, batch_size_limit).await?
             }
+            IngestSource::FileAtOffset(filepath, offset) => {
+                BatchLineReader::from_file_at_offset(&filepath, offset, batch_size_limit).await?
+            }
             IngestSource::Stdin => BatchLineReader::from_stdin(batch_size_limit),
             IngestSource::Str(ingest_payload) => {
                 BatchLineReader::from_string(ingest_payload, batch_size_limit)
             }
         };
         while let Some(batch) = batch_reader.next_batch().await? {
+            let source_offset = batch_reader.source_offset();
             loop {
                 let (query_params, timeout) =
                     if !batch_reader.has_next() && last_block_commit != CommitType::Auto {
@@ -305,7 +380,8 @@ impl QuickwitClient {
                 }
             }
             if let Some(event_fn) = on_ingest_event.as_ref() {
-                event_fn(IngestEvent::IngestedDocBatch(batch.len()))
+                event_fn(IngestEvent::IngestedDocBatch(batch.len()));
+                event_fn(IngestEvent::Checkpoint(source_offset));
             }
         }
 
@@ -315,6 +391,8 @@ impl QuickwitClient {
 
 pub enum IngestEvent {
     IngestedDocBatch(usize),
+    /// All the documents located before this byte offset of the source have been ingested.
+    Checkpoint(u64),
     Sleep,
 }
 
@@ -381,6 +459,96 @@ impl<'a> IndexClient<'a> {
         Ok(())
     }
 
+    /// Takes a snapshot of the index and writes it to `output_uri`.
+    pub async fn snapshot(
+        &self,
+        index_id: &str,
+        output_uri: &Uri,
+        copy_split_files: bool,
+    ) -> Result<IndexSnapshotSummary, Error> {
+        let path = format!("indexes/{index_id}/snapshot");
+        let snapshot_index_request = SnapshotIndexRequest {
+            output_uri: output_uri.clone(),
+            copy_split_files,
+        };
+        let body = Bytes::from(serde_json::to_vec(&snapshot_index_request)?);
+        let response = self
+            .transport
+            .send::<()>(Method::POST, &path, None, None, Some(body), self.timeout)
+            .await?;
+        let snapshot_summary = response.deserialize().await?;
+        Ok(snapshot_summary)
+    }
+
+    /// Restores an index from the snapshot stored at `snapshot_uri`.
+    pub async fn restore(
+        &self,
+        snapshot_uri: &Uri,
+        index_uri_opt: Option<&Uri>,
+    ) -> Result<IndexMetadata, Error> {
+        let restore_index_request = RestoreIndexRequest {
+            snapshot_uri: snapshot_uri.clone(),
+            index_uri: index_uri_opt.cloned(),
+        };
+        let body = Bytes::from(serde_json::to_vec(&restore_index_request)?);
+        let response = self
+            .transport
+            .send::<()>(
+                Method::POST,
+                "indexes/restore",
+                None,
+                None,
+                Some(body),
+                self.timeout,
+            )
+            .await?;
+        let index_metadata = response.deserialize().await?;
+        Ok(index_metadata)
+    }
+
+    /// Clones the index `index_id` into a new index `clone_index_id` sharing its split files.
+    pub async fn clone_index(
+        &self,
+        index_id: &str,
+        clone_index_id: &str,
+    ) -> Result<IndexMetadata, Error> {
+        let path = format!("indexes/{index_id}/clone");
+        let clone_index_request = CloneIndexRequest {
+            clone_index_id: clone_index_id.to_string(),
+            search_settings: None,
+            retention: None,
+        };
+        let body = Bytes::from(serde_json::to_vec(&clone_index_request)?);
+        let response = self
+            .transport
+            .send::<()>(Method::POST, &path, None, None, Some(body), self.timeout)
+            .await?;
+        let index_metadata = response.deserialize().await?;
+        Ok(index_metadata)
+    }
+
+    /// Pauses the merges of the index on every indexer.
+    pub async fn pause_merges(&self, index_id: &str) -> Result<MergesStatus, Error> {
+        let path = format!("indexing/{index_id}/merges/pause");
+        let response = self
+            .transport
+            .send::<()>(Method::PUT, &path, None, None, None, self.timeout)
+            .await?;
+        let merges_status = response.deserialize().await?;
+        Ok(merges_status)
+    }
+
+    /// Resumes the merges of the index on every indexer.
+    pub async fn resume_merges(&self, index_id: &str) -> Result<MergesStatus, Error> {
+        let path = format!("indexing/{index_id}/merges/resume");
+        let response = self
+            .transport
+            .send::<()>(Method::PUT, &path, None, None, None, self.timeout)
+            .await?;
+        let merges_status = response.deserialize().await?;
+        Ok(merges_status)
+    }
+
     pub async fn delete(&self, index_id: &str, dry_run: bool) -> Result<Vec<SplitInfo>, Error> {
         let path = format!("indexes/{index_id}");
         let response = self
@@ -532,6 +700,16 @@ impl<'a> SourceClient<'a> {
         Ok(())
     }
 
+    pub async fn backlog(&self, source_id: &str) -> Result<GetSourceBacklogResponse, Error> {
+        let path = format!("{}/{source_id}/backlog", self.sources_root_url());
+        let response = self
+            .transport
+            .send::<()>(Method::GET, &path, None, None, None, self.timeout)
+            .await?;
+        let source_backlog = response.deserialize().await?;
+        Ok(source_backlog)
+    }
+
     pub async fn list(&self) -> Result<Vec<SourceConfig>, Error> {
         let response = self
             .transport
@@ -655,11 +833,12 @@ mod test {
     use std::path::PathBuf;
     use std::str::FromStr;
 
+    use quickwit_common::uri::Uri;
     use quickwit_config::{ConfigFormat, SourceConfig};
     use quickwit_indexing::mock_split;
     use quickwit_ingest::CommitType;
     use quickwit_metastore::IndexMetadata;
-    use quickwit_search::SearchResponseRest;
+    use quickwit_search::{LatencyBand, SearchCostEstimate, SearchResponseRest};
     use quickwit_serve::{ListSplitsQueryParams, ListSplitsResponse, SearchRequestQueryString};
     use reqwest::header::CONTENT_TYPE;
     use reqwest::{StatusCode, Url};
@@ -686,6 +865,45 @@ mod test {
         assert!(error.to_string().contains("tcp connect error"));
     }
 
+    #[tokio::test]
+    async fn test_client_round_robin_across_endpoints() {
+        let mock_server_1 = MockServer::start().await;
+        let mock_server_2 = MockServer::start().await;
+        for mock_server in [&mock_server_1, &mock_server_2] {
+            Mock::given(method("GET"))
+                .and(path("/api/v1/indexes"))
+                .respond_with(ResponseTemplate::new(StatusCode::OK).set_body_json(json!([])))
+                .expect(2)
+                .mount(mock_server)
+                .await;
+        }
+        let qw_client = QuickwitClientBuilder::new(Url::parse(&mock_server_1.uri()).unwrap())
+            .add_endpoint(Url::parse(&mock_server_2.uri()).unwrap())
+            .build();
+        for _ in 0..4 {
+            assert!(qw_client.indexes().list().await.unwrap().is_empty());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_client_failover_on_unreachable_endpoint() {
+        let port = quickwit_common::net::find_available_tcp_port().unwrap();
+        let unreachable_url = Url::parse(&format!("http://127.0.0.1:{port}")).unwrap();
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/indexes"))
+            .respond_with(ResponseTemplate::new(StatusCode::OK).set_body_json(json!([])))
+            .expect(3)
+            .mount(&mock_server)
+            .await;
+        let qw_client = QuickwitClientBuilder::new(unreachable_url)
+            .add_endpoint(Url::parse(&mock_server.uri()).unwrap())
+            .build();
+        for _ in 0..3 {
+            assert!(qw_client.indexes().list().await.unwrap().is_empty());
+        }
+    }
+
     #[tokio::test]
     async fn test_search_endpoint() {
         let mock_server = MockServer::start().await;
@@ -702,6 +920,12 @@ mod test {
             aggregations: None,
             elapsed_time_micros: 100,
             errors: Vec::new(),
+            preview_coverage: None,
+            groups: None,
+            next_search_after: None,
+            partial: false,
+            failed_splits: Vec::new(),
+            skipped_split_ids: Vec::new(),
         };
         Mock::given(method("POST"))
             .and(path("/api/v1/my-index/search"))
@@ -720,6 +944,38 @@ mod test {
         );
     }
 
+    #[tokio::test]
+    async fn test_search_estimate_endpoint() {
+        let mock_server = MockServer::start().await;
+        let server_url = Url::parse(&mock_server.uri()).unwrap();
+        let qw_client = QuickwitClientBuilder::new(server_url).build();
+        let expected_search_cost_estimate = SearchCostEstimate {
+            num_splits: 2,
+            num_docs: 100,
+            num_footer_bytes: 200,
+            num_warmup_bytes: 800,
+            num_fetch_docs_bytes: 1_000,
+            num_bytes: 2_000,
+            latency_band: LatencyBand::Fast,
+        };
+        Mock::given(method("POST"))
+            .and(path("/api/v1/my-index/search/estimate"))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::OK)
+                    .set_body_json(json!(expected_search_cost_estimate)),
+            )
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        assert_eq!(
+            qw_client
+                .search_estimate("my-index", SearchRequestQueryString::default())
+                .await
+                .unwrap(),
+            expected_search_cost_estimate
+        );
+    }
+
     fn get_ndjson_filepath(ndjson_dataset_filename: &str) -> String {
         format!(
             "{}/resources/tests/{}",
@@ -821,6 +1077,49 @@ mod test {
             .unwrap();
     }
 
+    #[tokio::test]
+    async fn test_ingest_endpoint_from_offset() {
+        let mock_server = MockServer::start().await;
+        let server_url = Url::parse(&mock_server.uri()).unwrap();
+        let qw_client = QuickwitClientBuilder::new(server_url).build();
+        let ndjson_filepath = get_ndjson_filepath("documents_to_ingest.json");
+        let mut buffer = Vec::new();
+        File::open(&ndjson_filepath)
+            .await
+            .unwrap()
+            .read_to_end(&mut buffer)
+            .await
+            .unwrap();
+        let offset = buffer.iter().position(|byte| *byte == b'\n').unwrap() + 1;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/my-index/ingest"))
+            .and(body_bytes(buffer[offset..].to_vec()))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let ingest_source =
+            IngestSource::FileAtOffset(PathBuf::from_str(&ndjson_filepath).unwrap(), offset as u64);
+        let checkpoints = std::sync::Mutex::new(Vec::new());
+        let on_ingest_event = |ingest_event: IngestEvent| {
+            if let IngestEvent::Checkpoint(source_offset) = ingest_event {
+                checkpoints.lock().unwrap().push(source_offset);
+            }
+        };
+        qw_client
+            .ingest(
+                "my-index",
+                ingest_source,
+                None,
+                Some(&on_ingest_event),
+                CommitType::Auto,
+            )
+            .await
+            .unwrap();
+        assert_eq!(*checkpoints.lock().unwrap(), vec![buffer.len() as u64]);
+    }
+
     #[tokio::test]
     async fn test_ingest_endpoint_should_return_api_error() {
         let mock_server = MockServer::start().await;
@@ -936,6 +1235,105 @@ mod test {
             .await;
         qw_client.indexes().clear("my-index").await.unwrap_err();
 
+        // POST snapshot index
+        Mock::given(method("POST"))
+            .and(path("/api/v1/indexes/my-index/snapshot"))
+            .and(body_json(json!({
+                "output_uri": "s3://backup/my-index",
+                "copy_split_files": true,
+            })))
+            .respond_with(ResponseTemplate::new(StatusCode::OK).set_body_json(json!({
+                "index_id": "my-index",
+                "snapshot_uri": "s3://backup/my-index",
+                "num_splits": 2,
+                "num_docs": 100,
+                "num_copied_bytes": 1000,
+            })))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        let snapshot_uri = Uri::from_str("s3://backup/my-index").unwrap();
+        let snapshot_summary = qw_client
+            .indexes()
+            .snapshot("my-index", &snapshot_uri, true)
+            .await
+            .unwrap();
+        assert_eq!(snapshot_summary.num_splits, 2);
+        assert_eq!(snapshot_summary.num_copied_bytes, 1000);
+
+        // POST restore index
+        Mock::given(method("POST"))
+            .and(path("/api/v1/indexes/restore"))
+            .and(body_json(json!({
+                "snapshot_uri": "s3://backup/my-index",
+            })))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::OK).set_body_json(index_metadata.clone()),
+            )
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        assert_eq!(
+            qw_client
+                .indexes()
+                .restore(&snapshot_uri, None)
+                .await
+                .unwrap()
+                .index_id(),
+            "test-index"
+        );
+
+        // POST clone index
+        Mock::given(method("POST"))
+            .and(path("/api/v1/indexes/my-index/clone"))
+            .and(body_json(json!({
+                "clone_index_id": "my-clone",
+            })))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::OK).set_body_json(index_metadata.clone()),
+            )
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        assert_eq!(
+            qw_client
+                .indexes()
+                .clone_index("my-index", "my-clone")
+                .await
+                .unwrap()
+                .index_id(),
+            "test-index"
+        );
+
+        // PUT pause merges
+        Mock::given(method("PUT"))
+            .and(path("/api/v1/indexing/my-index/merges/pause"))
+            .respond_with(ResponseTemplate::new(StatusCode::OK).set_body_json(json!({
+                "index_id": "my-index",
+                "merges_paused": true,
+                "num_merge_pipelines": 1,
+            })))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        let merges_status = qw_client.indexes().pause_merges("my-index").await.unwrap();
+        assert!(merges_status.merges_paused);
+        assert_eq!(merges_status.num_merge_pipelines, 1);
+
+        // PUT resume merges
+        Mock::given(method("PUT"))
+            .and(path("/api/v1/indexing/my-index/merges/resume"))
+            .respond_with(ResponseTemplate::new(StatusCode::OK).set_body_json(json!({
+                "index_id": "my-index",
+                "merges_paused": false,
+                "num_merge_pipelines": 1,
+            })))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        let merges_status = qw_client.indexes().resume_merges("my-index").await.unwrap();
+        assert!(!merges_status.merges_paused);
+
         // DELETE index
         Mock::given(method("DELETE"))
             .and(path("/api/v1/indexes/my-index"))
@@ -1125,6 +1523,30 @@ mod test {
             .await
             .unwrap_err();
 
+        // GET source backlog
+        let source_backlog = GetSourceBacklogResponse {
+            index_uid: "my-index:0".to_string(),
+            source_id: "my-source".to_string(),
+            total_backlog: 42,
+            partition_backlogs: Vec::new(),
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/v1/indexes/my-index/sources/my-source/backlog"))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::OK).set_body_json(source_backlog.clone()),
+            )
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        assert_eq!(
+            qw_client
+                .sources("my-index")
+                .backlog("my-source")
+                .await
+                .unwrap(),
+            source_backlog
+        );
+
         // DELETE source
         Mock::given(method("DELETE"))
             .and(path("/api/v1/indexes/my-index/sources/my-source"))
@@ -0,0 +1,424 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Encodes final aggregation results as an Arrow IPC stream.
+//!
+//! This is an output format only: leaves still return intermediate aggregation results, and the
+//! root converts the final result once they have been merged.
+//!
+//! The aggregation tree is flattened into a single table: each bucket aggregation contributes a
+//! key column named after its path (e.g. `by_host.by_status`) and a `<path>.doc_count` column,
+//! and each metric aggregation contributes one `Float64` column per value. Each row corresponds
+//! to a bucket of the innermost bucket aggregation.
+
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, Float64Array, StringArray, UInt64Array};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::ipc::writer::StreamWriter;
+use arrow::record_batch::RecordBatch;
+use itertools::Itertools;
+use tantivy::aggregation::agg_result::{
+    AggregationResult, AggregationResults, BucketEntries, BucketResult, MetricResult,
+};
+use tantivy::aggregation::Key;
+
+use crate::SearchError;
+
+/// Maximum number of rows per record batch of the Arrow IPC stream.
+const ARROW_BATCH_NUM_ROWS: usize = 8_192;
+
+#[derive(Clone, Debug, PartialEq)]
+enum CellValue {
+    Str(String),
+    F64(f64),
+    U64(u64),
+}
+
+impl From<&Key> for CellValue {
+    fn from(key: &Key) -> Self {
+        match key {
+            Key::Str(key) => CellValue::Str(key.clone()),
+            Key::F64(key) => CellValue::F64(*key),
+        }
+    }
+}
+
+type Row = Vec<(String, Option<CellValue>)>;
+
+/// Columns of the flattened aggregation table, in order of first appearance.
+#[derive(Default)]
+struct AggregationTable {
+    num_rows: usize,
+    columns: Vec<(String, Vec<Option<CellValue>>)>,
+}
+
+impl AggregationTable {
+    fn push_row(&mut self, row: Row) {
+        for (column_name, value) in row {
+            let column_idx = match self
+                .columns
+                .iter()
+                .position(|(name, _)| *name == column_name)
+            {
+                Some(column_idx) => column_idx,
+                None => {
+                    self.columns.push((column_name, vec![None; self.num_rows]));
+                    self.columns.len() - 1
+                }
+            };
+            self.columns[column_idx].1.push(value);
+        }
+        self.num_rows += 1;
+        // Columns absent from the row are null.
+        for (_, values) in &mut self.columns {
+            values.resize(self.num_rows, None);
+        }
+    }
+
+    fn into_record_batch(self) -> Result<RecordBatch, SearchError> {
+        let mut fields = Vec::with_capacity(self.columns.len());
+        let mut arrays = Vec::with_capacity(self.columns.len());
+
+        for (column_name, values) in self.columns {
+            let (data_type, array) = column_to_array(values);
+            fields.push(Field::new(column_name, data_type, true));
+            arrays.push(array);
+        }
+        let schema = Arc::new(Schema::new(fields));
+        RecordBatch::try_new(schema, arrays).map_err(|error| {
+            SearchError::Internal(format!(
+                "failed to build Arrow record batch from aggregation result: {error}"
+            ))
+        })
+    }
+}
+
+/// Columns holding only unsigned integers are `UInt64`, columns holding only numbers are
+/// `Float64`, and any other column is `Utf8`.
+fn column_to_array(values: Vec<Option<CellValue>>) -> (DataType, ArrayRef) {
+    let all_u64 = values
+        .iter()
+        .flatten()
+        .all(|value| matches!(value, CellValue::U64(_)));
+    if all_u64 {
+        let array: UInt64Array = values
+            .into_iter()
+            .map(|value_opt| match value_opt {
+                Some(CellValue::U64(value)) => Some(value),
+                _ => None,
+            })
+            .collect();
+        return (DataType::UInt64, Arc::new(array));
+    }
+    let all_numeric = values
+        .iter()
+        .flatten()
+        .all(|value| !matches!(value, CellValue::Str(_)));
+    if all_numeric {
+        let array: Float64Array = values
+            .into_iter()
+            .map(|value_opt| match value_opt {
+                Some(CellValue::U64(value)) => Some(value as f64),
+                Some(CellValue::F64(value)) => Some(value),
+                _ => None,
+            })
+            .collect();
+        return (DataType::Float64, Arc::new(array));
+    }
+    let array: StringArray = values
+        .into_iter()
+        .map(|value_opt| {
+            value_opt.map(|value| match value {
+                CellValue::Str(value) => value,
+                CellValue::F64(value) => value.to_string(),
+                CellValue::U64(value) => value.to_string(),
+            })
+        })
+        .collect();
+    (DataType::Utf8, Arc::new(array))
+}
+
+fn column_name(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{path}.{name}")
+    }
+}
+
+fn push_metric_cells(
+    column_name: String,
+    metric_result: &MetricResult,
+    row: &mut Row,
+) -> Result<(), SearchError> {
+    match metric_result {
+        MetricResult::Average(single_metric_result)
+        | MetricResult::Count(single_metric_result)
+        | MetricResult::Max(single_metric_result)
+        | MetricResult::Min(single_metric_result)
+        | MetricResult::Sum(single_metric_result) => {
+            row.push((column_name, single_metric_result.value.map(CellValue::F64)));
+        }
+        MetricResult::Stats(stats) => {
+            row.push((
+                format!("{column_name}.count"),
+                Some(CellValue::U64(stats.count)),
+            ));
+            row.push((
+                format!("{column_name}.sum"),
+                Some(CellValue::F64(stats.sum)),
+            ));
+            row.push((format!("{column_name}.min"), stats.min.map(CellValue::F64)));
+            row.push((format!("{column_name}.max"), stats.max.map(CellValue::F64)));
+            row.push((format!("{column_name}.avg"), stats.avg.map(CellValue::F64)));
+        }
+        MetricResult::Percentiles(_) => {
+            return Err(SearchError::InvalidAggregationRequest(format!(
+                "the Arrow aggregation format does not support percentiles aggregations, found \
+                 `{column_name}`"
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn bucket_cells(bucket_result: &BucketResult) -> Vec<(CellValue, u64, &AggregationResults)> {
+    fn entries<T>(bucket_entries: &BucketEntries<T>) -> Vec<&T> {
+        match bucket_entries {
+            BucketEntries::Vec(entries) => entries.iter().collect(),
+            BucketEntries::HashMap(entries) => entries
+                .iter()
+                .sorted_by(|(left, _), (right, _)| left.cmp(right))
+                .map(|(_, entry)| entry)
+                .collect(),
+        }
+    }
+    match bucket_result {
+        BucketResult::Range { buckets } => entries(buckets)
+            .into_iter()
+            .map(|entry| ((&entry.key).into(), entry.doc_count, &entry.sub_aggregation))
+            .collect(),
+        BucketResult::Histogram { buckets } => entries(buckets)
+            .into_iter()
+            .map(|entry| ((&entry.key).into(), entry.doc_count, &entry.sub_aggregation))
+            .collect(),
+        BucketResult::Terms { buckets, .. } => buckets
+            .iter()
+            .map(|entry| ((&entry.key).into(), entry.doc_count, &entry.sub_aggregation))
+            .collect(),
+    }
+}
+
+fn collect_rows(
+    aggregation_results: &AggregationResults,
+    path: &str,
+    mut row: Row,
+    table: &mut AggregationTable,
+) -> Result<(), SearchError> {
+    let mut bucket_aggregation_opt: Option<(&String, &BucketResult)> = None;
+
+    for (name, aggregation_result) in aggregation_results
+        .0
+        .iter()
+        .sorted_by(|(left, _), (right, _)| left.cmp(right))
+    {
+        match aggregation_result {
+            AggregationResult::MetricResult(metric_result) => {
+                push_metric_cells(column_name(path, name), metric_result, &mut row)?;
+            }
+            AggregationResult::BucketResult(bucket_result) => {
+                if let Some((other_name, _)) = bucket_aggregation_opt {
+                    return Err(SearchError::InvalidAggregationRequest(format!(
+                        "the Arrow aggregation format supports at most one bucket aggregation per \
+                         level, found `{}` and `{}`",
+                        column_name(path, other_name),
+                        column_name(path, name),
+                    )));
+                }
+                bucket_aggregation_opt = Some((name, bucket_result));
+            }
+        }
+    }
+    let Some((name, bucket_result)) = bucket_aggregation_opt else {
+        table.push_row(row);
+        return Ok(());
+    };
+    let key_column_name = column_name(path, name);
+    let buckets = bucket_cells(bucket_result);
+
+    if buckets.is_empty() {
+        table.push_row(row);
+        return Ok(());
+    }
+    for (key, doc_count, sub_aggregation_results) in buckets {
+        let mut bucket_row = row.clone();
+        bucket_row.push((key_column_name.clone(), Some(key)));
+        bucket_row.push((
+            format!("{key_column_name}.doc_count"),
+            Some(CellValue::U64(doc_count)),
+        ));
+        collect_rows(sub_aggregation_results, &key_column_name, bucket_row, table)?;
+    }
+    Ok(())
+}
+
+/// Flattens the final aggregation results and serializes them as an Arrow IPC stream.
+pub(crate) fn aggregation_results_to_arrow_ipc(
+    aggregation_results: &AggregationResults,
+) -> Result<Vec<u8>, SearchError> {
+    let mut table = AggregationTable::default();
+    collect_rows(aggregation_results, "", Row::new(), &mut table)?;
+    let record_batch = table.into_record_batch()?;
+
+    let arrow_error_to_search_error = |error: arrow::error::ArrowError| {
+        SearchError::Internal(format!(
+            "failed to write aggregation result as Arrow IPC stream: {error}"
+        ))
+    };
+    let mut stream_writer = StreamWriter::try_new(Vec::new(), &record_batch.schema())
+        .map_err(arrow_error_to_search_error)?;
+    let mut offset = 0;
+
+    while offset < record_batch.num_rows() {
+        let length = ARROW_BATCH_NUM_ROWS.min(record_batch.num_rows() - offset);
+        stream_writer
+            .write(&record_batch.slice(offset, length))
+            .map_err(arrow_error_to_search_error)?;
+        offset += length;
+    }
+    stream_writer
+        .into_inner()
+        .map_err(arrow_error_to_search_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::Array;
+    use arrow::ipc::reader::StreamReader;
+    use tantivy::aggregation::agg_result::{BucketEntry, SingleMetricResult};
+
+    use super::*;
+
+    fn read_arrow_ipc(ipc_bytes: &[u8]) -> Vec<RecordBatch> {
+        StreamReader::try_new(ipc_bytes, None)
+            .unwrap()
+            .map(|record_batch_res| record_batch_res.unwrap())
+            .collect()
+    }
+
+    fn terms_bucket(key: &str, doc_count: u64, avg: f64) -> BucketEntry {
+        let mut sub_aggregation = AggregationResults::default();
+        sub_aggregation.0.insert(
+            "avg_latency".to_string(),
+            AggregationResult::MetricResult(MetricResult::Average(SingleMetricResult {
+                value: Some(avg),
+            })),
+        );
+        BucketEntry {
+            key_as_string: None,
+            key: Key::Str(key.to_string()),
+            doc_count,
+            sub_aggregation,
+        }
+    }
+
+    #[test]
+    fn test_terms_aggregation_to_arrow_ipc() {
+        let mut aggregation_results = AggregationResults::default();
+        aggregation_results.0.insert(
+            "by_host".to_string(),
+            AggregationResult::BucketResult(BucketResult::Terms {
+                buckets: vec![
+                    terms_bucket("host-1", 3, 1.5),
+                    terms_bucket("host-2", 1, 4.0),
+                ],
+                sum_other_doc_count: 0,
+                doc_count_error_upper_bound: Some(0),
+            }),
+        );
+        let ipc_bytes = aggregation_results_to_arrow_ipc(&aggregation_results).unwrap();
+        let record_batches = read_arrow_ipc(&ipc_bytes);
+        assert_eq!(record_batches.len(), 1);
+
+        let record_batch = &record_batches[0];
+        let schema = record_batch.schema();
+        let column_names: Vec<&str> = schema
+            .fields()
+            .iter()
+            .map(|field| field.name().as_str())
+            .collect();
+        assert_eq!(
+            column_names,
+            ["by_host", "by_host.doc_count", "by_host.avg_latency"]
+        );
+        let keys = record_batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(keys.value(0), "host-1");
+        assert_eq!(keys.value(1), "host-2");
+
+        let doc_counts = record_batch
+            .column(1)
+            .as_any()
+            .downcast_ref::<UInt64Array>()
+            .unwrap();
+        assert_eq!(doc_counts.values(), &[3, 1]);
+
+        let avg_latencies = record_batch
+            .column(2)
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .unwrap();
+        assert_eq!(avg_latencies.values(), &[1.5, 4.0]);
+    }
+
+    #[test]
+    fn test_metric_aggregations_to_arrow_ipc() {
+        let mut aggregation_results = AggregationResults::default();
+        aggregation_results.0.insert(
+            "max_latency".to_string(),
+            AggregationResult::MetricResult(MetricResult::Max(SingleMetricResult { value: None })),
+        );
+        let ipc_bytes = aggregation_results_to_arrow_ipc(&aggregation_results).unwrap();
+        let record_batches = read_arrow_ipc(&ipc_bytes);
+        assert_eq!(record_batches.len(), 1);
+        assert_eq!(record_batches[0].num_rows(), 1);
+        assert!(record_batches[0].column(0).is_null(0));
+    }
+
+    #[test]
+    fn test_sibling_bucket_aggregations_are_rejected() {
+        let mut aggregation_results = AggregationResults::default();
+        for name in ["by_host", "by_status"] {
+            aggregation_results.0.insert(
+                name.to_string(),
+                AggregationResult::BucketResult(BucketResult::Terms {
+                    buckets: Vec::new(),
+                    sum_other_doc_count: 0,
+                    doc_count_error_upper_bound: None,
+                }),
+            );
+        }
+        let error = aggregation_results_to_arrow_ipc(&aggregation_results).unwrap_err();
+        assert!(matches!(error, SearchError::InvalidAggregationRequest(_)));
+    }
+}
@@ -0,0 +1,498 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Aggregations that tantivy does not support natively are rewritten by the root into tantivy
+//! aggregations before being dispatched to the leaves, and their results are formatted back once
+//! merged.
+//!
+//! - `duration_histogram` buckets durations into ranges with human-friendly boundaries (1ms, 10ms,
+//!   100ms, 1s, ...). It is rewritten into a `range` aggregation.
+//! - `percentiles` on datetime fields are computed by tantivy over the raw timestamps, in
+//!   nanoseconds. They are converted into milliseconds and formatted as RFC 3339 strings, like
+//!   Elasticsearch does.
+
+use std::collections::HashSet;
+
+use quickwit_datetime::{DateTimeOutputFormat, TantivyDateTime};
+use quickwit_doc_mapper::build_doc_mapper;
+use quickwit_metastore::IndexMetadata;
+use quickwit_proto::search::SearchRequest;
+use serde_json::{json, Map as JsonMap, Value as JsonValue};
+use tantivy::schema::FieldType;
+
+use crate::SearchError;
+
+/// Boundaries of the buckets of the `duration_histogram` aggregation, in nanoseconds.
+const DURATION_HISTOGRAM_BOUNDARIES: [(&str, u64); 12] = [
+    ("1µs", 1_000),
+    ("10µs", 10_000),
+    ("100µs", 100_000),
+    ("1ms", 1_000_000),
+    ("10ms", 10_000_000),
+    ("100ms", 100_000_000),
+    ("1s", 1_000_000_000),
+    ("10s", 10_000_000_000),
+    ("1m", 60_000_000_000),
+    ("10m", 600_000_000_000),
+    ("1h", 3_600_000_000_000),
+    ("1d", 86_400_000_000_000),
+];
+
+const NANOS_PER_MILLI: f64 = 1_000_000.0;
+
+/// Names of the aggregations leading, through sub-aggregations, to an aggregation.
+pub(crate) type AggregationPath = Vec<String>;
+
+/// Rewrites the aggregations of a search request that tantivy does not support natively. Returns
+/// the paths of the percentiles aggregations on datetime fields, whose results must be formatted
+/// with [`format_datetime_percentiles`].
+pub(crate) fn rewrite_aggregation_request(
+    search_request: &mut SearchRequest,
+    indexes_metadata: &[IndexMetadata],
+) -> crate::Result<Vec<AggregationPath>> {
+    let Some(aggregation_request) = &search_request.aggregation_request else {
+        return Ok(Vec::new());
+    };
+    let mut aggregations: JsonValue = serde_json::from_str(aggregation_request)
+        .map_err(|error| SearchError::InvalidAggregationRequest(error.to_string()))?;
+    let Some(aggregations_map) = aggregations.as_object_mut() else {
+        return Ok(Vec::new());
+    };
+    let datetime_field_names = datetime_field_names(indexes_metadata)?;
+    let mut rewriter = AggregationRewriter {
+        datetime_field_names,
+        datetime_percentiles_paths: Vec::new(),
+        is_rewritten: false,
+    };
+    rewriter.rewrite_aggregations(aggregations_map, &mut Vec::new())?;
+
+    if rewriter.is_rewritten {
+        search_request.aggregation_request = Some(serde_json::to_string(&aggregations)?);
+    }
+    Ok(rewriter.datetime_percentiles_paths)
+}
+
+/// Returns the names of the datetime fields of the indexes.
+fn datetime_field_names(indexes_metadata: &[IndexMetadata]) -> crate::Result<HashSet<String>> {
+    let mut datetime_field_names = HashSet::new();
+
+    for index_metadata in indexes_metadata {
+        let doc_mapper = build_doc_mapper(
+            &index_metadata.index_config.doc_mapping,
+            &index_metadata.index_config.search_settings,
+        )
+        .map_err(|error| {
+            SearchError::Internal(format!("failed to build doc mapper. cause: {error}"))
+        })?;
+        for (_field, field_entry) in doc_mapper.schema().fields() {
+            if matches!(field_entry.field_type(), FieldType::Date(_)) {
+                datetime_field_names.insert(field_entry.name().to_string());
+            }
+        }
+    }
+    Ok(datetime_field_names)
+}
+
+struct AggregationRewriter {
+    datetime_field_names: HashSet<String>,
+    datetime_percentiles_paths: Vec<AggregationPath>,
+    is_rewritten: bool,
+}
+
+impl AggregationRewriter {
+    fn rewrite_aggregations(
+        &mut self,
+        aggregations: &mut JsonMap<String, JsonValue>,
+        path: &mut AggregationPath,
+    ) -> crate::Result<()> {
+        for (aggregation_name, aggregation) in aggregations.iter_mut() {
+            let Some(aggregation) = aggregation.as_object_mut() else {
+                continue;
+            };
+            path.push(aggregation_name.clone());
+
+            if let Some(params) = aggregation.remove("duration_histogram") {
+                let range_params = duration_histogram_to_range(aggregation_name, params)?;
+                aggregation.insert("range".to_string(), range_params);
+                self.is_rewritten = true;
+            }
+            if let Some(field_name) = aggregation
+                .get("percentiles")
+                .and_then(|params| params.get("field"))
+                .and_then(JsonValue::as_str)
+            {
+                if self.datetime_field_names.contains(field_name) {
+                    self.datetime_percentiles_paths.push(path.clone());
+                }
+            }
+            for key in ["aggs", "aggregations"] {
+                if let Some(sub_aggregations) =
+                    aggregation.get_mut(key).and_then(JsonValue::as_object_mut)
+                {
+                    self.rewrite_aggregations(sub_aggregations, path)?;
+                }
+            }
+            path.pop();
+        }
+        Ok(())
+    }
+}
+
+/// Returns the number of nanoseconds in a duration unit.
+fn duration_unit_nanos(unit: &str) -> Option<u64> {
+    let unit_nanos = match unit {
+        "ns" => 1,
+        "us" | "µs" => 1_000,
+        "ms" => 1_000_000,
+        "s" => 1_000_000_000,
+        _ => return None,
+    };
+    Some(unit_nanos)
+}
+
+/// Converts the parameters of a `duration_histogram` aggregation into the parameters of the
+/// equivalent `range` aggregation. The field holds durations expressed in `unit`, milliseconds by
+/// default. Boundaries shorter than one unit are skipped.
+fn duration_histogram_to_range(
+    aggregation_name: &str,
+    params: JsonValue,
+) -> crate::Result<JsonValue> {
+    let invalid_request = |reason: String| {
+        SearchError::InvalidAggregationRequest(format!(
+            "invalid duration histogram aggregation `{aggregation_name}`: {reason}"
+        ))
+    };
+    let JsonValue::Object(mut params) = params else {
+        return Err(invalid_request("expected an object".to_string()));
+    };
+    let Some(field) = params.remove("field") else {
+        return Err(invalid_request("missing field `field`".to_string()));
+    };
+    let unit = match params.remove("unit") {
+        Some(JsonValue::String(unit)) => unit,
+        Some(unit) => return Err(invalid_request(format!("invalid unit `{unit}`"))),
+        None => "ms".to_string(),
+    };
+    let Some(unit_nanos) = duration_unit_nanos(&unit) else {
+        return Err(invalid_request(format!(
+            "unknown unit `{unit}`, expected one of `ns`, `us`, `ms`, `s`"
+        )));
+    };
+    let keyed = params.remove("keyed");
+
+    if let Some(unknown_param) = params.keys().next() {
+        return Err(invalid_request(format!("unknown field `{unknown_param}`")));
+    }
+    let boundaries: Vec<(&str, f64)> = DURATION_HISTOGRAM_BOUNDARIES
+        .iter()
+        .filter(|(_, boundary_nanos)| *boundary_nanos >= unit_nanos)
+        .map(|(label, boundary_nanos)| (*label, *boundary_nanos as f64 / unit_nanos as f64))
+        .collect();
+
+    let mut ranges = Vec::with_capacity(boundaries.len() + 1);
+    let mut previous_boundary_opt: Option<(&str, f64)> = None;
+
+    for (label, boundary) in boundaries {
+        let range = if let Some((previous_label, previous_boundary)) = previous_boundary_opt {
+            json!({"key": format!("{previous_label}-{label}"), "from": previous_boundary, "to": boundary})
+        } else {
+            json!({"key": format!("*-{label}"), "to": boundary})
+        };
+        ranges.push(range);
+        previous_boundary_opt = Some((label, boundary));
+    }
+    if let Some((previous_label, previous_boundary)) = previous_boundary_opt {
+        ranges.push(json!({"key": format!("{previous_label}-*"), "from": previous_boundary}));
+    }
+    let mut range_params = json!({
+        "field": field,
+        "ranges": ranges,
+    });
+    if let Some(keyed) = keyed {
+        range_params["keyed"] = keyed;
+    }
+    Ok(range_params)
+}
+
+/// Converts the values of the percentiles aggregations on datetime fields, located by `paths`,
+/// from nanoseconds to milliseconds and formats them as RFC 3339 strings.
+pub(crate) fn format_datetime_percentiles(
+    aggregation_result_json: &str,
+    paths: &[AggregationPath],
+) -> crate::Result<String> {
+    let mut aggregation_results: JsonValue = serde_json::from_str(aggregation_result_json)?;
+
+    for path in paths {
+        format_datetime_percentiles_at_path(&mut aggregation_results, path);
+    }
+    let aggregation_result_json = serde_json::to_string(&aggregation_results)?;
+    Ok(aggregation_result_json)
+}
+
+fn format_datetime_percentiles_at_path(aggregation_results: &mut JsonValue, path: &[String]) {
+    let Some((aggregation_name, sub_path)) = path.split_first() else {
+        return;
+    };
+    let Some(aggregation_result) = aggregation_results.get_mut(aggregation_name) else {
+        return;
+    };
+    if sub_path.is_empty() {
+        format_percentiles_values(aggregation_result);
+        return;
+    }
+    // The results of the sub-aggregations are nested in the buckets, which are either a list or,
+    // for keyed aggregations, a map.
+    match aggregation_result.get_mut("buckets") {
+        Some(JsonValue::Array(buckets)) => {
+            for bucket in buckets {
+                format_datetime_percentiles_at_path(bucket, sub_path);
+            }
+        }
+        Some(JsonValue::Object(buckets)) => {
+            for bucket in buckets.values_mut() {
+                format_datetime_percentiles_at_path(bucket, sub_path);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn format_percentiles_values(percentiles_result: &mut JsonValue) {
+    match percentiles_result.get_mut("values") {
+        Some(JsonValue::Object(values)) => {
+            let mut formatted_values = JsonMap::with_capacity(values.len() * 2);
+
+            for (percent, value) in std::mem::take(values) {
+                let (millis_value, value_as_string) = format_timestamp_nanos(&value);
+                formatted_values.insert(format!("{percent}_as_string"), value_as_string);
+                formatted_values.insert(percent, millis_value);
+            }
+            *values = formatted_values;
+        }
+        Some(JsonValue::Array(values)) => {
+            for entry in values {
+                let Some(value) = entry.get("value") else {
+                    continue;
+                };
+                let (millis_value, value_as_string) = format_timestamp_nanos(value);
+                entry["value"] = millis_value;
+                entry["value_as_string"] = value_as_string;
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Returns the timestamp in milliseconds and as an RFC 3339 string, or null values if the
+/// percentile is not defined, for instance because no document matched.
+fn format_timestamp_nanos(timestamp_nanos: &JsonValue) -> (JsonValue, JsonValue) {
+    let Some(timestamp_nanos) = timestamp_nanos.as_f64() else {
+        return (JsonValue::Null, JsonValue::Null);
+    };
+    let timestamp_millis = timestamp_nanos / NANOS_PER_MILLI;
+    let date_time = TantivyDateTime::from_timestamp_nanos(timestamp_nanos as i64);
+    let value_as_string = DateTimeOutputFormat::Rfc3339
+        .format_to_json(date_time)
+        .unwrap_or(JsonValue::Null);
+    (json!(timestamp_millis), value_as_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_config::DocMapping;
+
+    use super::*;
+
+    fn index_metadata_with_datetime_field() -> IndexMetadata {
+        let mut index_metadata = IndexMetadata::for_test("test-index", "ram:///indexes/test-index");
+        let doc_mapping_json = json!({
+            "field_mappings": [
+                {"name": "created_at", "type": "datetime", "fast": true},
+                {"name": "latency_ms", "type": "u64", "fast": true}
+            ]
+        });
+        let doc_mapping: DocMapping = serde_json::from_value(doc_mapping_json).unwrap();
+        index_metadata.index_config.doc_mapping = doc_mapping;
+        index_metadata
+            .index_config
+            .search_settings
+            .default_search_fields = Vec::new();
+        index_metadata
+    }
+
+    #[test]
+    fn test_rewrite_aggregation_request_duration_histogram() {
+        let mut search_request = SearchRequest {
+            aggregation_request: Some(
+                json!({
+                    "by_status": {
+                        "terms": {"field": "status"},
+                        "aggs": {
+                            "latency": {"duration_histogram": {"field": "latency_ms", "keyed": true}}
+                        }
+                    }
+                })
+                .to_string(),
+            ),
+            ..Default::default()
+        };
+        let datetime_percentiles_paths =
+            rewrite_aggregation_request(&mut search_request, &[]).unwrap();
+        assert!(datetime_percentiles_paths.is_empty());
+
+        let aggregations: JsonValue =
+            serde_json::from_str(search_request.aggregation_request.as_ref().unwrap()).unwrap();
+        let range_params = &aggregations["by_status"]["aggs"]["latency"]["range"];
+        assert_eq!(range_params["field"], "latency_ms");
+        assert_eq!(range_params["keyed"], true);
+
+        let ranges = range_params["ranges"].as_array().unwrap();
+        assert_eq!(ranges.len(), 10);
+        assert_eq!(ranges[0], json!({"key": "*-1ms", "to": 1.0}));
+        assert_eq!(
+            ranges[1],
+            json!({"key": "1ms-10ms", "from": 1.0, "to": 10.0})
+        );
+        assert_eq!(
+            ranges[3],
+            json!({"key": "100ms-1s", "from": 100.0, "to": 1_000.0})
+        );
+        assert_eq!(ranges[9], json!({"key": "1d-*", "from": 86_400_000.0}));
+
+        // The rewritten request is a valid tantivy aggregation request.
+        serde_json::from_value::<tantivy::aggregation::agg_req::Aggregations>(aggregations)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_rewrite_aggregation_request_duration_histogram_units() {
+        let range_params =
+            duration_histogram_to_range("latency", json!({"field": "latency", "unit": "s"}))
+                .unwrap();
+        let ranges = range_params["ranges"].as_array().unwrap();
+        assert_eq!(ranges.len(), 7);
+        assert_eq!(ranges[0], json!({"key": "*-1s", "to": 1.0}));
+        assert_eq!(
+            ranges[2],
+            json!({"key": "10s-1m", "from": 10.0, "to": 60.0})
+        );
+
+        let range_params =
+            duration_histogram_to_range("latency", json!({"field": "latency", "unit": "ns"}))
+                .unwrap();
+        let ranges = range_params["ranges"].as_array().unwrap();
+        assert_eq!(ranges.len(), 13);
+        assert_eq!(ranges[0], json!({"key": "*-1µs", "to": 1_000.0}));
+
+        let error =
+            duration_histogram_to_range("latency", json!({"field": "latency", "unit": "days"}))
+                .unwrap_err();
+        assert!(error.to_string().contains("unknown unit `days`"));
+
+        let error = duration_histogram_to_range("latency", json!({"unit": "ms"})).unwrap_err();
+        assert!(error.to_string().contains("missing field `field`"));
+
+        let error =
+            duration_histogram_to_range("latency", json!({"field": "latency", "interval": 10}))
+                .unwrap_err();
+        assert!(error.to_string().contains("unknown field `interval`"));
+    }
+
+    #[test]
+    fn test_rewrite_aggregation_request_datetime_percentiles() {
+        let index_metadata = index_metadata_with_datetime_field();
+        let aggregation_request = json!({
+            "created_at_percentiles": {"percentiles": {"field": "created_at"}},
+            "latency_percentiles": {"percentiles": {"field": "latency_ms"}},
+            "by_day": {
+                "date_histogram": {"field": "created_at", "fixed_interval": "1d"},
+                "aggs": {
+                    "created_at_percentiles": {"percentiles": {"field": "created_at"}}
+                }
+            }
+        })
+        .to_string();
+        let mut search_request = SearchRequest {
+            aggregation_request: Some(aggregation_request.clone()),
+            ..Default::default()
+        };
+        let mut datetime_percentiles_paths =
+            rewrite_aggregation_request(&mut search_request, &[index_metadata]).unwrap();
+        datetime_percentiles_paths.sort();
+
+        assert_eq!(
+            datetime_percentiles_paths,
+            [
+                vec!["by_day".to_string(), "created_at_percentiles".to_string()],
+                vec!["created_at_percentiles".to_string()],
+            ]
+        );
+        // Percentiles are computed by tantivy: the request is left untouched.
+        assert_eq!(
+            search_request.aggregation_request,
+            Some(aggregation_request)
+        );
+    }
+
+    #[test]
+    fn test_format_datetime_percentiles() {
+        let aggregation_results = json!({
+            "created_at_percentiles": {
+                "values": {"50.0": 1_420_070_400_000_000_000.0_f64, "99.0": null}
+            },
+            "by_day": {
+                "buckets": [
+                    {
+                        "key": 1_420_070_400_000.0,
+                        "doc_count": 1,
+                        "created_at_percentiles": {
+                            "values": [{"key": 50.0, "value": 1_420_070_400_000_000_000.0_f64}]
+                        }
+                    }
+                ]
+            }
+        })
+        .to_string();
+        let paths = [
+            vec!["created_at_percentiles".to_string()],
+            vec!["by_day".to_string(), "created_at_percentiles".to_string()],
+        ];
+        let formatted_results: JsonValue = serde_json::from_str(
+            &format_datetime_percentiles(&aggregation_results, &paths).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            formatted_results["created_at_percentiles"]["values"],
+            json!({
+                "50.0": 1_420_070_400_000.0,
+                "50.0_as_string": "2015-01-01T00:00:00Z",
+                "99.0": null,
+                "99.0_as_string": null,
+            })
+        );
+        assert_eq!(
+            formatted_results["by_day"]["buckets"][0]["created_at_percentiles"]["values"],
+            json!([{
+                "key": 50.0,
+                "value": 1_420_070_400_000.0,
+                "value_as_string": "2015-01-01T00:00:00Z",
+            }])
+        );
+    }
+}
@@ -0,0 +1,337 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+use bytesize::ByteSize;
+use quickwit_config::SearcherConfig;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tantivy::TantivyError;
+
+/// Settings of the aggregations spilling their partial buckets to disk once they hold too many
+/// buckets in memory.
+#[derive(Clone, Debug)]
+pub(crate) struct AggregationSpillConfig {
+    /// Maximum number of buckets of an aggregation kept in memory.
+    pub max_num_buckets_in_memory: usize,
+    /// Directory of the spill files.
+    pub spill_dir: PathBuf,
+    /// Maximum number of bytes an aggregation can spill to disk. Spilling is disabled if zero.
+    pub spill_budget: ByteSize,
+}
+
+impl AggregationSpillConfig {
+    pub fn from_searcher_config(searcher_config: &SearcherConfig) -> Self {
+        Self {
+            max_num_buckets_in_memory: searcher_config.aggregation_bucket_limit as usize,
+            spill_dir: searcher_config
+                .aggregation_spill_dir
+                .clone()
+                .unwrap_or_else(std::env::temp_dir),
+            spill_budget: searcher_config.aggregation_spill_budget,
+        }
+    }
+
+    fn is_spill_enabled(&self) -> bool {
+        self.spill_budget.as_u64() > 0
+    }
+
+    /// Returns an error if an aggregation holds more buckets than it is allowed to keep in memory
+    /// and cannot spill them.
+    pub fn check_num_buckets(&self, num_buckets: usize) -> tantivy::Result<()> {
+        if num_buckets > self.max_num_buckets_in_memory && !self.is_spill_enabled() {
+            let error_msg = format!(
+                "aggregation limit exceeded: the aggregation holds more than {} buckets and \
+                 spilling to disk is disabled",
+                self.max_num_buckets_in_memory
+            );
+            return Err(TantivyError::InvalidArgument(error_msg));
+        }
+        Ok(())
+    }
+}
+
+impl Default for AggregationSpillConfig {
+    fn default() -> Self {
+        Self::from_searcher_config(&SearcherConfig::default())
+    }
+}
+
+/// Partial bucket of an aggregation, mergeable with the partial buckets of the same key computed
+/// on other segments or splits.
+pub(crate) trait PartialBucket: Default + Serialize + DeserializeOwned {
+    fn merge(&mut self, other: Self);
+}
+
+/// Merges the partial buckets of an aggregation, keyed by term. Once the number of buckets kept
+/// in memory exceeds the limit, they are sorted by key and spilled to a temporary file. The sorted
+/// runs are eventually merged back with an external sort.
+pub(crate) struct SpillableBuckets<B> {
+    spill_config: AggregationSpillConfig,
+    buckets: HashMap<String, B>,
+    spill_files: Vec<File>,
+    num_spilled_bytes: u64,
+}
+
+impl<B: PartialBucket> SpillableBuckets<B> {
+    pub fn new(spill_config: AggregationSpillConfig) -> Self {
+        Self {
+            spill_config,
+            buckets: HashMap::new(),
+            spill_files: Vec::new(),
+            num_spilled_bytes: 0,
+        }
+    }
+
+    pub fn add(&mut self, key: String, bucket: B) -> tantivy::Result<()> {
+        self.buckets.entry(key).or_default().merge(bucket);
+
+        if self.buckets.len() > self.spill_config.max_num_buckets_in_memory {
+            self.spill_config.check_num_buckets(self.buckets.len())?;
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    /// Writes the buckets held in memory to a new spill file, sorted by key. Each entry is
+    /// prefixed with its length.
+    fn spill(&mut self) -> tantivy::Result<()> {
+        let mut buckets: Vec<(String, B)> = self.buckets.drain().collect();
+        buckets.sort_unstable_by(|(left_key, _), (right_key, _)| left_key.cmp(right_key));
+
+        let spill_file = tempfile::tempfile_in(&self.spill_config.spill_dir)?;
+        let mut spill_writer = BufWriter::new(spill_file);
+
+        for entry in &buckets {
+            let entry_bytes = postcard::to_allocvec(entry).map_err(map_error)?;
+            self.num_spilled_bytes += 4 + entry_bytes.len() as u64;
+
+            if self.num_spilled_bytes > self.spill_config.spill_budget.as_u64() {
+                let error_msg = format!(
+                    "aggregation limit exceeded: the aggregation spilled more than {} to disk",
+                    self.spill_config.spill_budget
+                );
+                return Err(TantivyError::InvalidArgument(error_msg));
+            }
+            spill_writer.write_all(&(entry_bytes.len() as u32).to_le_bytes())?;
+            spill_writer.write_all(&entry_bytes)?;
+        }
+        let mut spill_file = spill_writer
+            .into_inner()
+            .map_err(|error| error.into_error())?;
+        spill_file.seek(SeekFrom::Start(0))?;
+        self.spill_files.push(spill_file);
+        Ok(())
+    }
+
+    /// Returns the merged buckets sorted by key.
+    pub fn into_sorted_buckets(self) -> tantivy::Result<SortedBuckets<B>> {
+        let mut buckets: Vec<(String, B)> = self.buckets.into_iter().collect();
+        buckets.sort_unstable_by(|(left_key, _), (right_key, _)| left_key.cmp(right_key));
+
+        let mut sorted_runs = Vec::with_capacity(self.spill_files.len() + 1);
+        sorted_runs.push(SortedRun::InMemory(buckets.into_iter()));
+
+        for spill_file in self.spill_files {
+            sorted_runs.push(SortedRun::Spilled(BufReader::new(spill_file)));
+        }
+        let num_sorted_runs = sorted_runs.len();
+        let mut sorted_buckets = SortedBuckets {
+            sorted_runs,
+            heads: (0..num_sorted_runs).map(|_| None).collect(),
+            heap: BinaryHeap::with_capacity(num_sorted_runs),
+        };
+        for run_ord in 0..num_sorted_runs {
+            sorted_buckets.advance(run_ord)?;
+        }
+        Ok(sorted_buckets)
+    }
+}
+
+enum SortedRun<B> {
+    InMemory(std::vec::IntoIter<(String, B)>),
+    Spilled(BufReader<File>),
+}
+
+impl<B: PartialBucket> SortedRun<B> {
+    fn next_entry(&mut self) -> tantivy::Result<Option<(String, B)>> {
+        let spill_reader = match self {
+            SortedRun::InMemory(entries) => return Ok(entries.next()),
+            SortedRun::Spilled(spill_reader) => spill_reader,
+        };
+        let mut len_bytes = [0u8; 4];
+
+        match spill_reader.read_exact(&mut len_bytes) {
+            Ok(()) => {}
+            Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(error) => return Err(error.into()),
+        }
+        let mut entry_bytes = vec![0u8; u32::from_le_bytes(len_bytes) as usize];
+        spill_reader.read_exact(&mut entry_bytes)?;
+        let entry = postcard::from_bytes(&entry_bytes).map_err(map_error)?;
+        Ok(Some(entry))
+    }
+}
+
+/// Iterator over the buckets of [`SpillableBuckets`] sorted by key, merging the sorted runs held
+/// in memory and spilled to disk.
+pub(crate) struct SortedBuckets<B> {
+    sorted_runs: Vec<SortedRun<B>>,
+    // Next bucket of each sorted run, whose key is in the heap.
+    heads: Vec<Option<B>>,
+    heap: BinaryHeap<Reverse<(String, usize)>>,
+}
+
+impl<B: PartialBucket> SortedBuckets<B> {
+    fn advance(&mut self, run_ord: usize) -> tantivy::Result<()> {
+        if let Some((key, bucket)) = self.sorted_runs[run_ord].next_entry()? {
+            self.heads[run_ord] = Some(bucket);
+            self.heap.push(Reverse((key, run_ord)));
+        }
+        Ok(())
+    }
+
+    fn next_bucket(&mut self) -> tantivy::Result<Option<(String, B)>> {
+        let Some(Reverse((key, run_ord))) = self.heap.pop() else {
+            return Ok(None);
+        };
+        let mut bucket = self.heads[run_ord]
+            .take()
+            .expect("the head of the run should be set");
+        self.advance(run_ord)?;
+
+        // The keys of a run are unique, but the same key may appear in several runs.
+        while let Some(Reverse((next_key, _))) = self.heap.peek() {
+            if *next_key != key {
+                break;
+            }
+            let Reverse((_, next_run_ord)) = self.heap.pop().expect("the heap should not be empty");
+            let next_bucket = self.heads[next_run_ord]
+                .take()
+                .expect("the head of the run should be set");
+            bucket.merge(next_bucket);
+            self.advance(next_run_ord)?;
+        }
+        Ok(Some((key, bucket)))
+    }
+}
+
+impl<B: PartialBucket> Iterator for SortedBuckets<B> {
+    type Item = tantivy::Result<(String, B)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_bucket().transpose()
+    }
+}
+
+fn map_error(error: postcard::Error) -> TantivyError {
+    TantivyError::InternalError(format!("aggregation spill postcard error: {error}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    impl PartialBucket for u64 {
+        fn merge(&mut self, other: Self) {
+            *self += other;
+        }
+    }
+
+    fn spill_config_for_test(
+        spill_dir: PathBuf,
+        max_num_buckets_in_memory: usize,
+        spill_budget: ByteSize,
+    ) -> AggregationSpillConfig {
+        AggregationSpillConfig {
+            max_num_buckets_in_memory,
+            spill_dir,
+            spill_budget,
+        }
+    }
+
+    #[test]
+    fn test_spillable_buckets() {
+        let spill_dir = tempfile::tempdir().unwrap();
+        let spill_config =
+            spill_config_for_test(spill_dir.path().to_path_buf(), 2, ByteSize::mb(1));
+        let mut spillable_buckets = SpillableBuckets::<u64>::new(spill_config);
+
+        for key in ["d", "a", "c", "a", "b", "d", "e", "c", "a"] {
+            spillable_buckets.add(key.to_string(), 1).unwrap();
+        }
+        assert!(!spillable_buckets.spill_files.is_empty());
+
+        let sorted_buckets: Vec<(String, u64)> = spillable_buckets
+            .into_sorted_buckets()
+            .unwrap()
+            .collect::<tantivy::Result<_>>()
+            .unwrap();
+        let expected_buckets: Vec<(String, u64)> =
+            [("a", 3), ("b", 1), ("c", 2), ("d", 2), ("e", 1)]
+                .into_iter()
+                .map(|(key, count)| (key.to_string(), count))
+                .collect();
+        assert_eq!(sorted_buckets, expected_buckets);
+    }
+
+    #[test]
+    fn test_spillable_buckets_without_spill() {
+        let spill_dir = tempfile::tempdir().unwrap();
+        let spill_config =
+            spill_config_for_test(spill_dir.path().to_path_buf(), 10, ByteSize::mb(1));
+        let mut spillable_buckets = SpillableBuckets::<u64>::new(spill_config);
+
+        for key in ["b", "a", "b"] {
+            spillable_buckets.add(key.to_string(), 1).unwrap();
+        }
+        assert!(spillable_buckets.spill_files.is_empty());
+
+        let sorted_buckets: Vec<(String, u64)> = spillable_buckets
+            .into_sorted_buckets()
+            .unwrap()
+            .collect::<tantivy::Result<_>>()
+            .unwrap();
+        assert_eq!(sorted_buckets, [("a".to_string(), 1), ("b".to_string(), 2)]);
+    }
+
+    #[test]
+    fn test_spillable_buckets_limits() {
+        let spill_dir = tempfile::tempdir().unwrap();
+
+        let spill_config = spill_config_for_test(spill_dir.path().to_path_buf(), 2, ByteSize(0));
+        let mut spillable_buckets = SpillableBuckets::<u64>::new(spill_config);
+        spillable_buckets.add("a".to_string(), 1).unwrap();
+        spillable_buckets.add("b".to_string(), 1).unwrap();
+        let error = spillable_buckets.add("c".to_string(), 1).unwrap_err();
+        assert!(error.to_string().contains("spilling to disk is disabled"));
+
+        let spill_config = spill_config_for_test(spill_dir.path().to_path_buf(), 2, ByteSize(16));
+        let mut spillable_buckets = SpillableBuckets::<u64>::new(spill_config);
+        spillable_buckets.add("a".to_string(), 1).unwrap();
+        spillable_buckets.add("b".to_string(), 1).unwrap();
+        let error = spillable_buckets.add("c".to_string(), 1).unwrap_err();
+        assert!(error.to_string().contains("spilled more than 16 B to disk"));
+    }
+}
@@ -24,6 +24,7 @@ use std::time::Duration;
 
 use futures::{StreamExt, TryStreamExt};
 use http::Uri;
+use quickwit_common::deadline::make_grpc_request;
 use quickwit_proto::search::{
     GetKvRequest, LeafSearchStreamResponse, PutKvRequest, ReportSplitsRequest,
 };
@@ -111,7 +112,7 @@ impl SearchServiceClient {
     ) -> crate::Result<quickwit_proto::search::SearchResponse> {
         match &mut self.client_impl {
             SearchServiceClientImpl::Grpc(grpc_client) => {
-                let tonic_request = Request::new(request);
+                let tonic_request = make_grpc_request(request);
                 let tonic_response = grpc_client
                     .root_search(tonic_request)
                     .await
@@ -129,7 +130,7 @@ impl SearchServiceClient {
     ) -> crate::Result<quickwit_proto::search::LeafSearchResponse> {
         match &mut self.client_impl {
             SearchServiceClientImpl::Grpc(grpc_client) => {
-                let tonic_request = Request::new(request);
+                let tonic_request = make_grpc_request(request);
                 let tonic_response = grpc_client
                     .leaf_search(tonic_request)
                     .await
@@ -201,7 +202,7 @@ impl SearchServiceClient {
     ) -> crate::Result<quickwit_proto::search::FetchDocsResponse> {
         match &mut self.client_impl {
             SearchServiceClientImpl::Grpc(grpc_client) => {
-                let tonic_request = Request::new(request);
+                let tonic_request = make_grpc_request(request);
                 let tonic_response = grpc_client
                     .fetch_docs(tonic_request)
                     .await
@@ -219,7 +220,7 @@ impl SearchServiceClient {
     ) -> crate::Result<quickwit_proto::search::LeafListTermsResponse> {
         match &mut self.client_impl {
             SearchServiceClientImpl::Grpc(grpc_client) => {
-                let tonic_request = Request::new(request);
+                let tonic_request = make_grpc_request(request);
                 let tonic_response = grpc_client
                     .leaf_list_terms(tonic_request)
                     .await
@@ -67,6 +67,8 @@ impl ClusterClient {
         request: FetchDocsRequest,
         mut client: SearchServiceClient,
     ) -> crate::Result<FetchDocsResponse> {
+        let start_instant = tokio::time::Instant::now();
+        crate::SEARCH_METRICS.root_fetch_docs_requests_total.inc();
         let mut response_res = client.fetch_docs(request.clone()).await;
         let retry_policy = DefaultRetryPolicy {};
         if let Some(retry_request) = retry_policy.retry_request(request, &response_res) {
@@ -83,6 +85,9 @@ impl ClusterClient {
             );
             response_res = client.fetch_docs(retry_request).await;
         }
+        crate::SEARCH_METRICS
+            .root_fetch_docs_request_duration_secs
+            .observe(start_instant.elapsed().as_secs_f64());
         response_res
     }
 
@@ -272,6 +277,12 @@ fn merge_leaf_search_response(
     left_response
         .partial_hits
         .extend(right_response.partial_hits);
+    left_response
+        .skipped_split_ids
+        .extend(right_response.skipped_split_ids);
+    left_response
+        .group_counts
+        .extend(right_response.group_counts);
     let intermediate_aggregation_result: Option<Vec<u8>> = match (
         left_response.intermediate_aggregation_result,
         right_response.intermediate_aggregation_result,
@@ -292,6 +303,8 @@ fn merge_leaf_search_response(
             + right_response.num_attempted_splits,
         failed_splits: right_response.failed_splits,
         partial_hits: left_response.partial_hits,
+        skipped_split_ids: left_response.skipped_split_ids,
+        group_counts: left_response.group_counts,
     })
 }
 
@@ -358,6 +371,7 @@ mod tests {
             split_id: split_id.to_string(),
             segment_ord: 1,
             doc_id,
+            dedup_key: None,
         }
     }
 
@@ -365,12 +379,14 @@ mod tests {
         FetchDocsRequest {
             partial_hits: Vec::new(),
             index_uri: "uri".to_string(),
+            encryption_config: None,
             split_offsets: vec![SplitIdAndFooterOffsets {
                 split_id: split_id.to_string(),
                 split_footer_end: 100,
                 split_footer_start: 0,
                 timestamp_start: None,
                 timestamp_end: None,
+                timestamp_sort_order: None,
             }],
             ..Default::default()
         }
@@ -387,6 +403,7 @@ mod tests {
             search_request: Some(search_request),
             doc_mapper: "doc_mapper".to_string(),
             index_uri: "uri".to_string(),
+            encryption_config: None,
             split_offsets: vec![
                 SplitIdAndFooterOffsets {
                     split_id: "split_1".to_string(),
@@ -394,6 +411,7 @@ mod tests {
                     split_footer_end: 100,
                     timestamp_start: None,
                     timestamp_end: None,
+                    timestamp_sort_order: None,
                 },
                 SplitIdAndFooterOffsets {
                     split_id: "split_2".to_string(),
@@ -401,6 +419,7 @@ mod tests {
                     split_footer_end: 100,
                     timestamp_start: None,
                     timestamp_end: None,
+                    timestamp_sort_order: None,
                 },
             ],
         }
@@ -416,11 +435,13 @@ mod tests {
             fast_field: "fast".to_string(),
             output_format: 0,
             partition_by_field: None,
+            additional_fast_fields: Vec::new(),
         };
         LeafSearchStreamRequest {
             request: Some(search_request),
             doc_mapper: "doc_mapper".to_string(),
             index_uri: "uri".to_string(),
+            encryption_config: None,
             split_offsets: vec![
                 SplitIdAndFooterOffsets {
                     split_id: "split_1".to_string(),
@@ -428,6 +449,7 @@ mod tests {
                     split_footer_end: 100,
                     timestamp_start: None,
                     timestamp_end: None,
+                    timestamp_sort_order: None,
                 },
                 SplitIdAndFooterOffsets {
                     split_id: "split_2".to_string(),
@@ -435,6 +457,7 @@ mod tests {
                     split_footer_end: 100,
                     timestamp_start: None,
                     timestamp_end: None,
+                    timestamp_sort_order: None,
                 },
             ],
         }
@@ -617,6 +640,39 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_merge_leaf_search_retry_keeps_skipped_splits() -> anyhow::Result<()> {
+        let split_error = SplitSearchError {
+            error: "error".to_string(),
+            split_id: "split_3".to_string(),
+            retryable_error: true,
+        };
+        let leaf_response = LeafSearchResponse {
+            num_hits: 1,
+            partial_hits: vec![mock_partial_hit("split_1", 3, 1)],
+            failed_splits: vec![split_error],
+            num_attempted_splits: 3,
+            skipped_split_ids: vec!["split_2".to_string()],
+            ..Default::default()
+        };
+        let leaf_response_retry = LeafSearchResponse {
+            num_hits: 0,
+            partial_hits: Vec::new(),
+            failed_splits: Vec::new(),
+            num_attempted_splits: 1,
+            skipped_split_ids: vec!["split_3".to_string()],
+            ..Default::default()
+        };
+        let merged_leaf_search_response =
+            merge_leaf_search_results(Ok(leaf_response), Ok(leaf_response_retry)).unwrap();
+        assert_eq!(merged_leaf_search_response.failed_splits.len(), 0);
+        assert_eq!(
+            merged_leaf_search_response.skipped_split_ids,
+            ["split_2", "split_3"]
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_merge_leaf_search_retry_on_error() -> anyhow::Result<()> {
         let split_error = SplitSearchError {
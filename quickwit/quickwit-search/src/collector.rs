@@ -19,13 +19,13 @@
 
 use std::borrow::Cow;
 use std::cmp::Ordering;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use itertools::Itertools;
 use quickwit_common::binary_heap::{SortKeyMapper, TopK};
 use quickwit_doc_mapper::{DocMapper, WarmupInfo};
 use quickwit_proto::search::{
-    LeafSearchResponse, PartialHit, SearchRequest, SortByValue, SortOrder, SortValue,
+    GroupCount, LeafSearchResponse, PartialHit, SearchRequest, SortByValue, SortOrder, SortValue,
     SplitSearchError,
 };
 use serde::Deserialize;
@@ -35,10 +35,21 @@ use tantivy::aggregation::{AggregationLimits, AggregationSegmentCollector};
 use tantivy::collector::{Collector, SegmentCollector};
 use tantivy::columnar::{ColumnType, MonotonicallyMappableToU64};
 use tantivy::fastfield::Column;
-use tantivy::{DocId, Score, SegmentOrdinal, SegmentReader, TantivyError};
+use tantivy::query::{EnableScoring, Query, Scorer};
+use tantivy::{
+    DocId, DocSet, Score, Searcher, SegmentOrdinal, SegmentReader, TantivyError, TERMINATED,
+};
 
+use crate::aggregation_spill::AggregationSpillConfig;
 use crate::filters::{create_timestamp_filter_builder, TimestampFilter, TimestampFilterBuilder};
 use crate::find_trace_ids_collector::{FindTraceIdsCollector, FindTraceIdsSegmentCollector, Span};
+use crate::hit_dedup::{DedupKeyColumn, DedupTopK, HitDedup};
+use crate::hit_grouping::{GroupCounts, GroupTopK, HitGrouping};
+use crate::sort_expression::SortExpression;
+use crate::term_frequency_collector::{
+    self, IntermediateTermFrequencyResults, TermFrequencyAggregations,
+    TermFrequencySegmentCollector,
+};
 use crate::GlobalDocAddress;
 
 #[derive(Clone, Debug)]
@@ -53,6 +64,11 @@ pub(crate) enum SortByComponent {
     Score {
         order: SortOrder,
     },
+    /// Arithmetic expression over fast fields, evaluated for every hit.
+    Expression {
+        expression: SortExpression,
+        order: SortOrder,
+    },
 }
 impl From<SortByComponent> for SortByPair {
     fn from(value: SortByComponent) -> Self {
@@ -101,6 +117,25 @@ impl SortByComponent {
                 })
             }
             SortByComponent::Score { .. } => Ok(SortingFieldExtractorComponent::Score),
+            SortByComponent::Expression { expression, .. } => {
+                let mut field_names = HashSet::new();
+                expression.add_field_names(&mut field_names);
+                let mut columns = HashMap::with_capacity(field_names.len());
+
+                for field_name in field_names {
+                    // Fields missing from the segment are treated as fields without values.
+                    if let Some((column, column_type)) =
+                        segment_reader.fast_fields().u64_lenient(&field_name)?
+                    {
+                        let sort_field_type = SortFieldType::try_from(column_type)?;
+                        columns.insert(field_name, (column, sort_field_type));
+                    }
+                }
+                Ok(SortingFieldExtractorComponent::Expression {
+                    expression: expression.clone(),
+                    columns,
+                })
+            }
         }
     }
     pub fn requires_scoring(&self) -> bool {
@@ -108,15 +143,21 @@ impl SortByComponent {
             SortByComponent::DocId { .. } => false,
             SortByComponent::FastField { .. } => false,
             SortByComponent::Score { .. } => true,
+            SortByComponent::Expression { .. } => false,
         }
     }
     pub fn add_fast_field(&self, set: &mut HashSet<String>) {
-        if let SortByComponent::FastField {
-            field_name,
-            order: _,
-        } = self
-        {
-            set.insert(field_name.clone());
+        match self {
+            SortByComponent::FastField {
+                field_name,
+                order: _,
+            } => {
+                set.insert(field_name.clone());
+            }
+            SortByComponent::Expression { expression, .. } => {
+                expression.add_field_names(set);
+            }
+            SortByComponent::DocId { .. } | SortByComponent::Score { .. } => {}
         }
     }
     pub fn sort_order(&self) -> SortOrder {
@@ -124,6 +165,7 @@ impl SortByComponent {
             SortByComponent::DocId { order } => *order,
             SortByComponent::FastField { order, .. } => *order,
             SortByComponent::Score { order } => *order,
+            SortByComponent::Expression { order, .. } => *order,
         }
     }
 }
@@ -137,6 +179,18 @@ enum SortFieldType {
     Bool,
 }
 
+impl SortFieldType {
+    /// Converts a fast field value to a float, for use in sort expressions.
+    fn to_f64(self, fast_field_value: u64) -> f64 {
+        match self {
+            SortFieldType::U64 => fast_field_value as f64,
+            SortFieldType::I64 | SortFieldType::DateTime => i64::from_u64(fast_field_value) as f64,
+            SortFieldType::F64 => f64::from_u64(fast_field_value),
+            SortFieldType::Bool => (fast_field_value != 0) as u64 as f64,
+        }
+    }
+}
+
 /// The `SortingFieldExtractor` is used to extract a score, which can either be a true score,
 /// a value from a fast field, or nothing (sort by DocId).
 enum SortingFieldExtractorComponent {
@@ -147,13 +201,18 @@ enum SortingFieldExtractorComponent {
         sort_field_type: SortFieldType,
     },
     Score,
+    Expression {
+        expression: SortExpression,
+        columns: HashMap<String, (Column<u64>, SortFieldType)>,
+    },
 }
 
 impl SortingFieldExtractorComponent {
     /// Returns the sort value for the given element
     ///
     /// The function returns None if the sort key is a fast field, for which we have no value
-    /// for the given doc_id, or we sort by DocId.
+    /// for the given doc_id, an expression that cannot be evaluated for the given doc_id, or we
+    /// sort by DocId.
     fn extract_typed_sort_value_opt(&self, doc_id: DocId, score: Score) -> Option<SortValue> {
         let map_fast_field_to_value = |fast_field_value, field_type| match field_type {
             SortFieldType::U64 => SortValue::U64(fast_field_value),
@@ -173,6 +232,17 @@ impl SortingFieldExtractorComponent {
                 .first(doc_id)
                 .map(|field_val| map_fast_field_to_value(field_val, *sort_field_type)),
             SortingFieldExtractorComponent::Score { .. } => Some(SortValue::F64(score as f64)),
+            SortingFieldExtractorComponent::Expression {
+                expression,
+                columns,
+            } => {
+                let field_value_fn = |field_name: &str| {
+                    let (column, sort_field_type) = columns.get(field_name)?;
+                    let fast_field_value = column.first(doc_id)?;
+                    Some(sort_field_type.to_f64(fast_field_value))
+                };
+                expression.eval(&field_value_fn).map(SortValue::F64)
+            }
         }
     }
 }
@@ -291,6 +361,103 @@ impl Eq for PartialHitHeapItem {}
 enum AggregationSegmentCollectors {
     FindTraceIdsSegmentCollector(Box<FindTraceIdsSegmentCollector>),
     TantivyAggregationSegmentCollector(AggregationSegmentCollector),
+    TermFrequencySegmentCollector(TermFrequencySegmentCollector),
+}
+
+/// Top hits of a segment for a request deduplicating hits.
+struct SegmentDedupTopK {
+    // None if no document of the segment has a value for the dedup field.
+    dedup_key_column_opt: Option<DedupKeyColumn>,
+    top_k_hits: DedupTopK<SegmentPartialHit, u64, HitSortingMapper>,
+}
+
+impl SegmentDedupTopK {
+    fn add_entry(&mut self, hit: SegmentPartialHit) {
+        let dedup_key_opt = self
+            .dedup_key_column_opt
+            .as_ref()
+            .and_then(|dedup_key_column| dedup_key_column.dedup_key(hit.doc_id));
+        self.top_k_hits.add_entry(dedup_key_opt, hit);
+    }
+
+    fn into_partial_hits(
+        self,
+        split_id: &str,
+        segment_ord: SegmentOrdinal,
+    ) -> tantivy::Result<Vec<PartialHit>> {
+        let dedup_key_column_opt = self.dedup_key_column_opt;
+        self.top_k_hits
+            .finalize()
+            .into_iter()
+            .map(|(dedup_key_opt, segment_partial_hit)| {
+                let mut partial_hit =
+                    segment_partial_hit.into_partial_hit(split_id.to_string(), segment_ord);
+                if let (Some(dedup_key), Some(dedup_key_column)) =
+                    (dedup_key_opt, &dedup_key_column_opt)
+                {
+                    partial_hit.dedup_key = Some(dedup_key_column.dedup_key_to_string(dedup_key)?);
+                }
+                Ok(partial_hit)
+            })
+            .collect()
+    }
+}
+
+/// Top hits and hit counts of the groups of a segment for a request grouping hits.
+struct SegmentGroupTopK {
+    // None if no document of the segment has a value for the group by field. The group keys are
+    // read like dedup keys.
+    group_key_column_opt: Option<DedupKeyColumn>,
+    num_hits_per_group: HashMap<u64, u64>,
+    top_k_hits: GroupTopK<SegmentPartialHit, u64, SegmentPartialHitSortingKey, HitSortingMapper>,
+}
+
+impl SegmentGroupTopK {
+    fn add_entry(&mut self, hit: SegmentPartialHit) {
+        // Documents without a value for the group by field do not belong to any group.
+        let Some(group_key) = self
+            .group_key_column_opt
+            .as_ref()
+            .and_then(|group_key_column| group_key_column.dedup_key(hit.doc_id))
+        else {
+            return;
+        };
+        *self.num_hits_per_group.entry(group_key).or_default() += 1;
+        self.top_k_hits.add_entry(group_key, hit);
+    }
+
+    fn into_partial_hits_and_group_counts(
+        self,
+        split_id: &str,
+        segment_ord: SegmentOrdinal,
+    ) -> tantivy::Result<(Vec<PartialHit>, Vec<GroupCount>)> {
+        let Some(group_key_column) = self.group_key_column_opt else {
+            return Ok((Vec::new(), Vec::new()));
+        };
+        let mut partial_hits = Vec::new();
+
+        for (group_key, segment_partial_hits) in self.top_k_hits.finalize() {
+            let group_key_str = group_key_column.dedup_key_to_string(group_key)?;
+
+            for segment_partial_hit in segment_partial_hits {
+                let mut partial_hit =
+                    segment_partial_hit.into_partial_hit(split_id.to_string(), segment_ord);
+                partial_hit.dedup_key = Some(group_key_str.clone());
+                partial_hits.push(partial_hit);
+            }
+        }
+        let group_counts = self
+            .num_hits_per_group
+            .into_iter()
+            .map(|(group_key, num_hits)| {
+                Ok(GroupCount {
+                    key: group_key_column.dedup_key_to_string(group_key)?,
+                    num_hits,
+                })
+            })
+            .collect::<tantivy::Result<_>>()?;
+        Ok((partial_hits, group_counts))
+    }
 }
 
 /// Quickwit collector working at the scale of the segment.
@@ -300,6 +467,10 @@ pub struct QuickwitSegmentCollector {
     score_extractor: SortingFieldExtractorPair,
     // PartialHits in this heap don't contain a split_id yet.
     top_k_hits: TopK<SegmentPartialHit, SegmentPartialHitSortingKey, HitSortingMapper>,
+    // Replaces `top_k_hits` if the request deduplicates hits.
+    dedup_top_k_hits_opt: Option<SegmentDedupTopK>,
+    // Replaces `top_k_hits` if the request groups hits.
+    group_top_k_hits_opt: Option<SegmentGroupTopK>,
     segment_ord: u32,
     timestamp_filter_opt: Option<TimestampFilter>,
     aggregation: Option<AggregationSegmentCollectors>,
@@ -345,7 +516,34 @@ impl QuickwitSegmentCollector {
             sort_value2: sort_value2.map(Into::into),
             doc_id,
         };
-        self.top_k_hits.add_entry(hit);
+        if let Some(dedup_top_k_hits) = &mut self.dedup_top_k_hits_opt {
+            dedup_top_k_hits.add_entry(hit);
+        } else if let Some(group_top_k_hits) = &mut self.group_top_k_hits_opt {
+            group_top_k_hits.add_entry(hit);
+        } else {
+            self.top_k_hits.add_entry(hit);
+        }
+    }
+
+    /// Returns true if neither the given document nor the following ones can make it into the
+    /// top hits, assuming the documents of the segment are sorted by the first sort field.
+    #[inline]
+    fn is_saturated(&self, doc_id: DocId, score: Score) -> bool {
+        if !self.top_k_hits.at_capacity() {
+            return false;
+        }
+        let Some(worst_hit) = self.top_k_hits.peek_worst() else {
+            return false;
+        };
+        let (sort_value, _) = self.score_extractor.extract_typed_sort_value(doc_id, score);
+        // Documents without sort value are not necessarily sorted last by tantivy.
+        sort_value.is_some()
+            && self
+                .top_k_hits
+                .sort_key_mapper
+                .order1
+                .compare_opt(&sort_value, &worst_hit.sort_value)
+                == Ordering::Less
     }
 
     #[inline]
@@ -376,6 +574,7 @@ impl SegmentPartialHit {
             doc_id: self.doc_id,
             split_id,
             segment_ord,
+            dedup_key: None,
         }
     }
 }
@@ -399,19 +598,33 @@ impl SegmentCollector for QuickwitSegmentCollector {
             Some(AggregationSegmentCollectors::TantivyAggregationSegmentCollector(collector)) => {
                 collector.collect(doc_id, score)
             }
+            Some(AggregationSegmentCollectors::TermFrequencySegmentCollector(collector)) => {
+                collector.collect(doc_id)
+            }
             None => (),
         }
     }
 
     fn harvest(self) -> Self::Fruit {
-        let partial_hits: Vec<PartialHit> = self
-            .top_k_hits
-            .finalize()
-            .into_iter()
-            .map(|segment_partial_hit: SegmentPartialHit| {
-                segment_partial_hit.into_partial_hit(self.split_id.clone(), self.segment_ord)
-            })
-            .collect();
+        let mut group_counts = Vec::new();
+        let partial_hits: Vec<PartialHit> = if let Some(dedup_top_k_hits) =
+            self.dedup_top_k_hits_opt
+        {
+            dedup_top_k_hits.into_partial_hits(&self.split_id, self.segment_ord)?
+        } else if let Some(group_top_k_hits) = self.group_top_k_hits_opt {
+            let (partial_hits, segment_group_counts) = group_top_k_hits
+                .into_partial_hits_and_group_counts(&self.split_id, self.segment_ord)?;
+            group_counts = segment_group_counts;
+            partial_hits
+        } else {
+            self.top_k_hits
+                .finalize()
+                .into_iter()
+                .map(|segment_partial_hit: SegmentPartialHit| {
+                    segment_partial_hit.into_partial_hit(self.split_id.clone(), self.segment_ord)
+                })
+                .collect()
+        };
 
         let intermediate_aggregation_result = match self.aggregation {
             Some(AggregationSegmentCollectors::FindTraceIdsSegmentCollector(collector)) => {
@@ -425,6 +638,11 @@ impl SegmentCollector for QuickwitSegmentCollector {
                     .expect("Collector fruit should be serializable.");
                 Some(serialized)
             }
+            Some(AggregationSegmentCollectors::TermFrequencySegmentCollector(collector)) => {
+                let serialized = postcard::to_allocvec(&collector.harvest()?)
+                    .expect("Collector fruit should be serializable.");
+                Some(serialized)
+            }
             None => None,
         };
         Ok(LeafSearchResponse {
@@ -433,6 +651,8 @@ impl SegmentCollector for QuickwitSegmentCollector {
             partial_hits,
             failed_splits: Vec::new(),
             num_attempted_splits: 1,
+            skipped_split_ids: Vec::new(),
+            group_counts,
         })
     }
 }
@@ -446,6 +666,8 @@ pub enum QuickwitAggregations {
     FindTraceIdsAggregation(FindTraceIdsCollector),
     /// Your classic Tantivy aggregation.
     TantivyAggregations(Aggregations),
+    /// Rare terms and significant terms aggregations.
+    TermFrequencyAggregations(TermFrequencyAggregations),
 }
 
 impl QuickwitAggregations {
@@ -457,6 +679,9 @@ impl QuickwitAggregations {
             QuickwitAggregations::TantivyAggregations(aggregations) => {
                 get_fast_field_names(aggregations)
             }
+            QuickwitAggregations::TermFrequencyAggregations(aggregations) => {
+                term_frequency_collector::fast_field_names(aggregations)
+            }
         }
     }
 
@@ -468,6 +693,12 @@ impl QuickwitAggregations {
             QuickwitAggregations::TantivyAggregations(aggreg) => {
                 QuickwitIncrementalAggregations::TantivyAggregations(aggreg.clone(), Vec::new())
             }
+            QuickwitAggregations::TermFrequencyAggregations(aggreg) => {
+                QuickwitIncrementalAggregations::TermFrequencyAggregations(
+                    aggreg.clone(),
+                    Vec::new(),
+                )
+            }
         }
     }
 }
@@ -476,6 +707,7 @@ impl QuickwitAggregations {
 enum QuickwitIncrementalAggregations {
     FindTraceIdsAggregation(FindTraceIdsCollector, Vec<Vec<Span>>),
     TantivyAggregations(Aggregations, Vec<Vec<u8>>),
+    TermFrequencyAggregations(TermFrequencyAggregations, Vec<Vec<u8>>),
     NoAggregation,
 }
 
@@ -491,7 +723,8 @@ impl QuickwitIncrementalAggregations {
                     state.push(new_state);
                 }
             }
-            QuickwitIncrementalAggregations::TantivyAggregations(_, state) => {
+            QuickwitIncrementalAggregations::TantivyAggregations(_, state)
+            | QuickwitIncrementalAggregations::TermFrequencyAggregations(_, state) => {
                 state.push(intermediate_result);
             }
             QuickwitIncrementalAggregations::NoAggregation => (),
@@ -514,6 +747,7 @@ impl QuickwitIncrementalAggregations {
                                 split_id: String::new(),
                                 segment_ord: 0,
                                 doc_id: 0,
+                                dedup_key: None,
                             });
                         }
                     }
@@ -521,11 +755,12 @@ impl QuickwitIncrementalAggregations {
                 None
             }
             QuickwitIncrementalAggregations::TantivyAggregations(_, _) => None,
+            QuickwitIncrementalAggregations::TermFrequencyAggregations(_, _) => None,
             QuickwitIncrementalAggregations::NoAggregation => None,
         }
     }
 
-    fn finalize(self) -> tantivy::Result<Option<Vec<u8>>> {
+    fn finalize(self, spill_config: &AggregationSpillConfig) -> tantivy::Result<Option<Vec<u8>>> {
         match self {
             QuickwitIncrementalAggregations::FindTraceIdsAggregation(collector, mut state) => {
                 let merged_fruit = if state.len() > 1 {
@@ -540,6 +775,14 @@ impl QuickwitIncrementalAggregations {
                 merge_intermediate_aggregation_result(
                     &Some(QuickwitAggregations::TantivyAggregations(aggregation)),
                     state.iter().map(|vec| vec.as_slice()),
+                    spill_config,
+                )
+            }
+            QuickwitIncrementalAggregations::TermFrequencyAggregations(aggregation, state) => {
+                merge_intermediate_aggregation_result(
+                    &Some(QuickwitAggregations::TermFrequencyAggregations(aggregation)),
+                    state.iter().map(|vec| vec.as_slice()),
+                    spill_config,
                 )
             }
             QuickwitIncrementalAggregations::NoAggregation => Ok(None),
@@ -560,7 +803,10 @@ pub(crate) struct QuickwitCollector {
     timestamp_filter_builder_opt: Option<TimestampFilterBuilder>,
     pub aggregation: Option<QuickwitAggregations>,
     pub aggregation_limits: AggregationLimits,
+    pub aggregation_spill_config: AggregationSpillConfig,
     search_after: Option<PartialHit>,
+    pub dedup_opt: Option<HitDedup>,
+    pub grouping_opt: Option<HitGrouping>,
 }
 
 impl QuickwitCollector {
@@ -576,6 +822,12 @@ impl QuickwitCollector {
         if let Some(timestamp_filter_builder) = &self.timestamp_filter_builder_opt {
             fast_field_names.insert(timestamp_filter_builder.timestamp_field_name.clone());
         }
+        if let Some(dedup) = &self.dedup_opt {
+            fast_field_names.insert(dedup.field_name.clone());
+        }
+        if let Some(grouping) = &self.grouping_opt {
+            fast_field_names.insert(grouping.field_name.clone());
+        }
         fast_field_names
     }
 
@@ -586,6 +838,61 @@ impl QuickwitCollector {
             ..WarmupInfo::default()
         }
     }
+
+    /// Returns true if the top hits of a split whose documents are sorted by timestamp in the
+    /// given order can be collected with [`search_sorted_split`].
+    pub fn can_terminate_early(&self, timestamp_field_name: &str, split_order: SortOrder) -> bool {
+        if self.aggregation.is_some()
+            || self.dedup_opt.is_some()
+            || self.grouping_opt.is_some()
+            || self.sort_by.second.is_some()
+        {
+            return false;
+        }
+        matches!(
+            &self.sort_by.first,
+            SortByComponent::FastField { field_name, order }
+                if field_name == timestamp_field_name && *order == split_order
+        )
+    }
+}
+
+/// Searches a split whose documents are sorted in the order of the first sort field of the
+/// collector, and stops scanning a segment as soon as its remaining documents can no longer make
+/// it into the top hits. As a result, the number of hits of the response is a lower bound.
+pub(crate) fn search_sorted_split(
+    searcher: &Searcher,
+    query: &dyn Query,
+    collector: &QuickwitCollector,
+) -> tantivy::Result<LeafSearchResponse> {
+    let enable_scoring = if collector.requires_scoring() {
+        EnableScoring::enabled_from_searcher(searcher)
+    } else {
+        EnableScoring::disabled_from_searcher(searcher)
+    };
+    let weight = query.weight(enable_scoring)?;
+    let mut segment_fruits = Vec::with_capacity(searcher.segment_readers().len());
+
+    for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
+        let mut segment_collector =
+            collector.for_segment(segment_ord as SegmentOrdinal, segment_reader)?;
+        let mut scorer = weight.scorer(segment_reader, 1.0)?;
+        let alive_bitset_opt = segment_reader.alive_bitset();
+
+        let mut doc_id = scorer.doc();
+        while doc_id != TERMINATED {
+            if alive_bitset_opt.map_or(true, |alive_bitset| alive_bitset.is_alive(doc_id)) {
+                let score = scorer.score();
+                if segment_collector.is_saturated(doc_id, score) {
+                    break;
+                }
+                segment_collector.collect(doc_id, score);
+            }
+            doc_id = scorer.advance();
+        }
+        segment_fruits.push(segment_collector.harvest());
+    }
+    collector.merge_fruits(segment_fruits)
 }
 
 impl Collector for QuickwitCollector {
@@ -620,6 +927,15 @@ impl Collector for QuickwitCollector {
                     )?,
                 ),
             ),
+            Some(QuickwitAggregations::TermFrequencyAggregations(aggs)) => {
+                Some(AggregationSegmentCollectors::TermFrequencySegmentCollector(
+                    TermFrequencySegmentCollector::for_segment(
+                        aggs,
+                        segment_reader,
+                        &self.aggregation_spill_config,
+                    )?,
+                ))
+            }
             None => None,
         };
         let score_extractor = get_score_extractor(&self.sort_by, segment_reader)?;
@@ -637,11 +953,36 @@ impl Collector for QuickwitCollector {
             // this value isn't actually used.
             Ordering::Equal
         };
+        let dedup_top_k_hits_opt = match &self.dedup_opt {
+            Some(dedup) => Some(SegmentDedupTopK {
+                dedup_key_column_opt: DedupKeyColumn::open(segment_reader, &dedup.field_name)?,
+                top_k_hits: DedupTopK::new(
+                    leaf_max_hits,
+                    dedup.max_hits_per_key,
+                    sort_key_mapper.clone(),
+                ),
+            }),
+            None => None,
+        };
+        let group_top_k_hits_opt = match &self.grouping_opt {
+            Some(grouping) => Some(SegmentGroupTopK {
+                group_key_column_opt: DedupKeyColumn::open(segment_reader, &grouping.field_name)?,
+                num_hits_per_group: HashMap::new(),
+                top_k_hits: GroupTopK::new(
+                    grouping.max_groups,
+                    grouping.max_hits_per_group,
+                    sort_key_mapper.clone(),
+                ),
+            }),
+            None => None,
+        };
         Ok(QuickwitSegmentCollector {
             num_hits: 0u64,
             split_id: self.split_id.clone(),
             score_extractor,
             top_k_hits: TopK::new(leaf_max_hits, sort_key_mapper),
+            dedup_top_k_hits_opt,
+            group_top_k_hits_opt,
             segment_ord,
             timestamp_filter_opt,
             aggregation,
@@ -676,10 +1017,13 @@ impl Collector for QuickwitCollector {
         let (sort_order1, sort_order2) = self.sort_by.sort_orders();
         let mut merged_leaf_response = merge_leaf_responses(
             &self.aggregation,
+            &self.aggregation_spill_config,
             segment_fruits?,
             sort_order1,
             sort_order2,
             num_hits,
+            self.dedup_opt.as_ref(),
+            self.grouping_opt.as_ref(),
         )?;
         // ... and drop the first [..start_offsets) hits.
         // note that self.start_offset is 0 when merging from leaf_search, and is only set when
@@ -704,6 +1048,7 @@ fn map_error(err: postcard::Error) -> TantivyError {
 fn merge_intermediate_aggregation_result<'a>(
     aggregations_opt: &Option<QuickwitAggregations>,
     intermediate_aggregation_results: impl Iterator<Item = &'a [u8]>,
+    spill_config: &AggregationSpillConfig,
 ) -> tantivy::Result<Option<Vec<u8>>> {
     let merged_intermediate_aggregation_result = match aggregations_opt {
         Some(QuickwitAggregations::FindTraceIdsAggregation(collector)) => {
@@ -738,6 +1083,26 @@ fn merge_intermediate_aggregation_result<'a>(
                 None
             }
         }
+        Some(QuickwitAggregations::TermFrequencyAggregations(_)) => {
+            // The intermediate results are deserialized lazily, one at a time.
+            let mut fruits = intermediate_aggregation_results
+                .map(|intermediate_aggregation_result| {
+                    postcard::from_bytes::<IntermediateTermFrequencyResults>(
+                        intermediate_aggregation_result,
+                    )
+                    .map_err(map_error)
+                })
+                .peekable();
+
+            if fruits.peek().is_none() {
+                None
+            } else {
+                let merged_fruit =
+                    term_frequency_collector::merge_intermediate_results(fruits, spill_config)?;
+                let serialized = postcard::to_allocvec(&merged_fruit).map_err(map_error)?;
+                Some(serialized)
+            }
+        }
         None => None,
     };
 
@@ -745,12 +1110,16 @@ fn merge_intermediate_aggregation_result<'a>(
 }
 
 /// Merges a set of Leaf Results.
+#[allow(clippy::too_many_arguments)]
 fn merge_leaf_responses(
     aggregations_opt: &Option<QuickwitAggregations>,
+    spill_config: &AggregationSpillConfig,
     mut leaf_responses: Vec<LeafSearchResponse>,
     sort_order1: SortOrder,
     sort_order2: SortOrder,
     max_hits: usize,
+    dedup_opt: Option<&HitDedup>,
+    grouping_opt: Option<&HitGrouping>,
 ) -> tantivy::Result<LeafSearchResponse> {
     // Optimization: No merging needed if there is only one result.
     if leaf_responses.len() == 1 {
@@ -763,6 +1132,7 @@ fn merge_leaf_responses(
             leaf_responses.iter().filter_map(|leaf_response| {
                 leaf_response.intermediate_aggregation_result.as_deref()
             }),
+            spill_config,
         )?;
     let num_attempted_splits = leaf_responses
         .iter()
@@ -777,36 +1147,70 @@ fn merge_leaf_responses(
         .flat_map(|leaf_response| leaf_response.failed_splits.iter())
         .cloned()
         .collect_vec();
+    let skipped_split_ids = leaf_responses
+        .iter()
+        .flat_map(|leaf_response| leaf_response.skipped_split_ids.iter())
+        .cloned()
+        .collect_vec();
+    let mut group_counts = GroupCounts::default();
+    for leaf_response in &mut leaf_responses {
+        group_counts.add(std::mem::take(&mut leaf_response.group_counts));
+    }
     let all_partial_hits: Vec<PartialHit> = leaf_responses
         .into_iter()
         .flat_map(|leaf_response| leaf_response.partial_hits)
         .collect();
-    let top_k_partial_hits: Vec<PartialHit> = top_k_partial_hits(
-        all_partial_hits.into_iter(),
-        sort_order1,
-        sort_order2,
-        max_hits,
-    );
+    let top_k_partial_hits: Vec<PartialHit> = if let Some(grouping) = grouping_opt {
+        top_k_grouped_partial_hits(
+            all_partial_hits.into_iter(),
+            sort_order1,
+            sort_order2,
+            grouping,
+        )
+    } else {
+        top_k_partial_hits(
+            all_partial_hits.into_iter(),
+            sort_order1,
+            sort_order2,
+            max_hits,
+            dedup_opt,
+        )
+    };
     Ok(LeafSearchResponse {
         intermediate_aggregation_result: merged_intermediate_aggregation_result,
         num_hits,
         partial_hits: top_k_partial_hits,
         failed_splits,
         num_attempted_splits,
+        skipped_split_ids,
+        group_counts: group_counts.finalize(),
     })
 }
 
 /// Mutates partial_hits so that it contains the top-num_hitso hits,
 /// and so that these elements are sorted.
 ///
+/// If `dedup_opt` is set, at most `max_hits_per_key` hits sharing the same dedup key are kept.
+///
 /// TODO we could possibly optimize the sort away (but I doubt it matters).
 fn top_k_partial_hits(
     partial_hits: impl Iterator<Item = PartialHit>,
     order1: SortOrder,
     order2: SortOrder,
     num_hits: usize,
+    dedup_opt: Option<&HitDedup>,
 ) -> Vec<PartialHit> {
     let sort_key_mapper = HitSortingMapper { order1, order2 };
+
+    if let Some(dedup) = dedup_opt {
+        let mut top_k_hits = DedupTopK::new(num_hits, dedup.max_hits_per_key, sort_key_mapper);
+        partial_hits.for_each(|hit| top_k_hits.add_entry(hit.dedup_key.clone(), hit));
+        return top_k_hits
+            .finalize()
+            .into_iter()
+            .map(|(_, hit)| hit)
+            .collect();
+    }
     let mut top_k_hits = TopK::new(num_hits, sort_key_mapper);
 
     partial_hits.for_each(|hit| top_k_hits.add_entry(hit));
@@ -814,12 +1218,46 @@ fn top_k_partial_hits(
     top_k_hits.finalize()
 }
 
+/// Returns the hits of the best `max_groups` groups, sorted by group and then by hit.
+///
+/// The hits are expected to be tagged with their group key.
+fn top_k_grouped_partial_hits(
+    partial_hits: impl Iterator<Item = PartialHit>,
+    order1: SortOrder,
+    order2: SortOrder,
+    grouping: &HitGrouping,
+) -> Vec<PartialHit> {
+    let sort_key_mapper = HitSortingMapper { order1, order2 };
+    let mut top_k_hits = GroupTopK::new(
+        grouping.max_groups,
+        grouping.max_hits_per_group,
+        sort_key_mapper,
+    );
+    for partial_hit in partial_hits {
+        if let Some(group_key) = partial_hit.dedup_key.clone() {
+            top_k_hits.add_entry(group_key, partial_hit);
+        }
+    }
+    top_k_hits
+        .finalize()
+        .into_iter()
+        .flat_map(|(_, partial_hits)| partial_hits)
+        .collect()
+}
+
 pub(crate) fn sort_by_from_request(search_request: &SearchRequest) -> SortByPair {
     let to_sort_by_component = |field_name: &str, order| {
         if field_name == "_score" {
             SortByComponent::Score { order }
         } else if field_name == "_shard_doc" || field_name == "_doc" {
             SortByComponent::DocId { order }
+        } else if let Some(expression) = SortExpression::is_expression(field_name)
+            .then(|| SortExpression::parse(field_name).ok())
+            .flatten()
+        {
+            // Invalid expressions are rejected by the root. Here, they fall back to a fast field
+            // that does not exist, i.e. without values.
+            SortByComponent::Expression { expression, order }
         } else {
             SortByComponent::FastField {
                 field_name: field_name.to_string(),
@@ -858,6 +1296,7 @@ pub(crate) fn make_collector_for_split(
     doc_mapper: &dyn DocMapper,
     search_request: &SearchRequest,
     aggregation_limits: AggregationLimits,
+    aggregation_spill_config: AggregationSpillConfig,
 ) -> crate::Result<QuickwitCollector> {
     let aggregation = match &search_request.aggregation_request {
         Some(aggregation) => Some(serde_json::from_str(aggregation)?),
@@ -877,7 +1316,10 @@ pub(crate) fn make_collector_for_split(
         timestamp_filter_builder_opt,
         aggregation,
         aggregation_limits,
+        aggregation_spill_config,
         search_after: search_request.search_after.clone(),
+        dedup_opt: HitDedup::from_search_request(search_request),
+        grouping_opt: HitGrouping::from_search_request(search_request),
     })
 }
 
@@ -885,6 +1327,7 @@ pub(crate) fn make_collector_for_split(
 pub(crate) fn make_merge_collector(
     search_request: &SearchRequest,
     aggregation_limits: &AggregationLimits,
+    aggregation_spill_config: &AggregationSpillConfig,
 ) -> crate::Result<QuickwitCollector> {
     let aggregation = match &search_request.aggregation_request {
         Some(aggregation) => Some(serde_json::from_str(aggregation)?),
@@ -899,7 +1342,10 @@ pub(crate) fn make_merge_collector(
         timestamp_filter_builder_opt: None,
         aggregation,
         aggregation_limits: aggregation_limits.clone(),
+        aggregation_spill_config: aggregation_spill_config.clone(),
         search_after: search_request.search_after.clone(),
+        dedup_opt: HitDedup::from_search_request(search_request),
+        grouping_opt: HitGrouping::from_search_request(search_request),
     })
 }
 
@@ -1019,10 +1465,17 @@ impl SortKeyMapper<SegmentPartialHit> for HitSortingMapper {
 pub(crate) struct IncrementalCollector {
     inner: QuickwitCollector,
     top_k_hits: TopK<PartialHit, PartialHitSortingKey, HitSortingMapper>,
+    // Replaces `top_k_hits` if the request deduplicates hits.
+    dedup_top_k_hits_opt: Option<DedupTopK<PartialHit, String, HitSortingMapper>>,
+    // Replaces `top_k_hits` if the request groups hits.
+    group_top_k_hits_opt:
+        Option<GroupTopK<PartialHit, String, PartialHitSortingKey, HitSortingMapper>>,
+    group_counts: GroupCounts,
     incremental_aggregation: QuickwitIncrementalAggregations,
     num_hits: u64,
     failed_splits: Vec<SplitSearchError>,
     num_attempted_splits: u64,
+    skipped_split_ids: Vec<String>,
 }
 
 impl IncrementalCollector {
@@ -1035,13 +1488,29 @@ impl IncrementalCollector {
             .unwrap_or(QuickwitIncrementalAggregations::NoAggregation);
         let (order1, order2) = inner.sort_by.sort_orders();
         let sort_key_mapper = HitSortingMapper { order1, order2 };
+        let num_hits = inner.max_hits + inner.start_offset;
+        let dedup_top_k_hits_opt = inner
+            .dedup_opt
+            .as_ref()
+            .map(|dedup| DedupTopK::new(num_hits, dedup.max_hits_per_key, sort_key_mapper.clone()));
+        let group_top_k_hits_opt = inner.grouping_opt.as_ref().map(|grouping| {
+            GroupTopK::new(
+                grouping.max_groups,
+                grouping.max_hits_per_group,
+                sort_key_mapper.clone(),
+            )
+        });
         IncrementalCollector {
-            top_k_hits: TopK::new(inner.max_hits + inner.start_offset, sort_key_mapper),
+            top_k_hits: TopK::new(num_hits, sort_key_mapper),
+            dedup_top_k_hits_opt,
+            group_top_k_hits_opt,
+            group_counts: GroupCounts::default(),
             inner,
             incremental_aggregation,
             num_hits: 0,
             failed_splits: Vec::new(),
             num_attempted_splits: 0,
+            skipped_split_ids: Vec::new(),
         }
     }
 
@@ -1053,12 +1522,30 @@ impl IncrementalCollector {
             failed_splits,
             num_attempted_splits,
             intermediate_aggregation_result,
+            skipped_split_ids,
+            group_counts,
         } = leaf_response;
 
         self.num_hits += num_hits;
-        self.top_k_hits.add_entries(partial_hits.into_iter());
+        if let Some(dedup_top_k_hits) = &mut self.dedup_top_k_hits_opt {
+            for partial_hit in partial_hits {
+                dedup_top_k_hits.add_entry(partial_hit.dedup_key.clone(), partial_hit);
+            }
+            // Compacting makes the worst hit available to `peek_worst_hit`.
+            dedup_top_k_hits.compact();
+        } else if let Some(group_top_k_hits) = &mut self.group_top_k_hits_opt {
+            for partial_hit in partial_hits {
+                if let Some(group_key) = partial_hit.dedup_key.clone() {
+                    group_top_k_hits.add_entry(group_key, partial_hit);
+                }
+            }
+            self.group_counts.add(group_counts);
+        } else {
+            self.top_k_hits.add_entries(partial_hits.into_iter());
+        }
         self.failed_splits.extend(failed_splits);
         self.num_attempted_splits += num_attempted_splits;
+        self.skipped_split_ids.extend(skipped_split_ids);
         if let Some(intermediate_aggregation_result) = intermediate_aggregation_result {
             self.incremental_aggregation
                 .add(intermediate_aggregation_result)?;
@@ -1071,10 +1558,20 @@ impl IncrementalCollector {
         self.failed_splits.push(split_error)
     }
 
+    /// Add a split that was not searched, or whose search was cancelled, because it could not
+    /// produce better hits than the ones already collected.
+    pub(crate) fn add_skipped_split(&mut self, split_id: String) {
+        self.skipped_split_ids.push(split_id)
+    }
+
     /// Get the worst top-hit. Can be used to skip splits if they can't possibly do better.
     ///
     /// Only returns a result if enough hits were recorded already.
     pub(crate) fn peek_worst_hit(&self) -> Option<Cow<PartialHit>> {
+        // Any split may hold hits of any group, so no split can be skipped.
+        if self.group_top_k_hits_opt.is_some() {
+            return None;
+        }
         if self.top_k_hits.max_len() == 0 {
             return self
                 .incremental_aggregation
@@ -1082,6 +1579,9 @@ impl IncrementalCollector {
                 .map(Cow::Owned);
         }
 
+        if let Some(dedup_top_k_hits) = &self.dedup_top_k_hits_opt {
+            return dedup_top_k_hits.peek_worst().map(Cow::Borrowed);
+        }
         if self.top_k_hits.at_capacity() {
             self.top_k_hits.peek_worst().map(Cow::Borrowed)
         } else {
@@ -1091,8 +1591,24 @@ impl IncrementalCollector {
 
     /// Finalize the merge, creating a LeafSearchResponse.
     pub(crate) fn finalize(self) -> tantivy::Result<LeafSearchResponse> {
-        let intermediate_aggregation_result = self.incremental_aggregation.finalize()?;
-        let mut partial_hits = self.top_k_hits.finalize();
+        let intermediate_aggregation_result = self
+            .incremental_aggregation
+            .finalize(&self.inner.aggregation_spill_config)?;
+        let mut partial_hits = if let Some(dedup_top_k_hits) = self.dedup_top_k_hits_opt {
+            dedup_top_k_hits
+                .finalize()
+                .into_iter()
+                .map(|(_, partial_hit)| partial_hit)
+                .collect()
+        } else if let Some(group_top_k_hits) = self.group_top_k_hits_opt {
+            group_top_k_hits
+                .finalize()
+                .into_iter()
+                .flat_map(|(_, partial_hits)| partial_hits)
+                .collect()
+        } else {
+            self.top_k_hits.finalize()
+        };
         if self.inner.start_offset != 0 {
             partial_hits.drain(0..self.inner.start_offset.min(partial_hits.len()));
         }
@@ -1102,6 +1618,8 @@ impl IncrementalCollector {
             failed_splits: self.failed_splits,
             num_attempted_splits: self.num_attempted_splits,
             intermediate_aggregation_result,
+            skipped_split_ids: self.skipped_split_ids,
+            group_counts: self.group_counts.finalize(),
         })
     }
 }
@@ -1119,6 +1637,7 @@ mod tests {
 
     use super::{make_merge_collector, IncrementalCollector, PartialHitHeapItem};
     use crate::collector::top_k_partial_hits;
+    use crate::hit_dedup::HitDedup;
 
     #[test]
     fn test_partial_hit_ordered_by_sorting_field() {
@@ -1171,13 +1690,15 @@ mod tests {
             split_id: "split1".to_string(),
             segment_ord: 0u32,
             doc_id: 0u32,
+            dedup_key: None,
         };
         assert_eq!(
             top_k_partial_hits(
                 vec![make_doc(1u64), make_doc(3u64), make_doc(2u64),].into_iter(),
                 SortOrder::Asc,
                 SortOrder::Asc,
-                2
+                2,
+                None
             ),
             vec![make_doc(1), make_doc(2)]
         );
@@ -1191,6 +1712,7 @@ mod tests {
             split_id: format!("split_{split_id}"),
             segment_ord: 0u32,
             doc_id: 0u32,
+            dedup_key: None,
         };
         assert_eq!(
             &top_k_partial_hits(
@@ -1202,7 +1724,8 @@ mod tests {
                 .into_iter(),
                 SortOrder::Desc,
                 SortOrder::Desc,
-                2
+                2,
+                None
             ),
             &[make_hit_given_split_id(3), make_hit_given_split_id(2)]
         );
@@ -1216,12 +1739,71 @@ mod tests {
                 .into_iter(),
                 SortOrder::Asc,
                 SortOrder::Asc,
-                2
+                2,
+                None
             ),
             &[make_hit_given_split_id(1), make_hit_given_split_id(2)]
         );
     }
 
+    #[test]
+    fn test_merge_partial_hits_with_dedup() {
+        let make_hit = |sort_value: u64, dedup_key: Option<&str>| PartialHit {
+            sort_value: Some(SortValue::U64(sort_value).into()),
+            sort_value2: None,
+            split_id: format!("split_{sort_value}"),
+            segment_ord: 0u32,
+            doc_id: 0u32,
+            dedup_key: dedup_key.map(ToString::to_string),
+        };
+        let partial_hits = vec![
+            make_hit(1, Some("a")),
+            make_hit(5, Some("a")),
+            make_hit(4, Some("b")),
+            make_hit(3, Some("a")),
+            make_hit(2, None),
+            make_hit(6, None),
+        ];
+        let dedup = HitDedup {
+            field_name: "field".to_string(),
+            max_hits_per_key: 1,
+        };
+        assert_eq!(
+            top_k_partial_hits(
+                partial_hits.clone().into_iter(),
+                SortOrder::Desc,
+                SortOrder::Desc,
+                4,
+                Some(&dedup)
+            ),
+            vec![
+                make_hit(6, None),
+                make_hit(5, Some("a")),
+                make_hit(4, Some("b")),
+                make_hit(2, None),
+            ]
+        );
+        let dedup = HitDedup {
+            field_name: "field".to_string(),
+            max_hits_per_key: 2,
+        };
+        assert_eq!(
+            top_k_partial_hits(
+                partial_hits.into_iter(),
+                SortOrder::Desc,
+                SortOrder::Desc,
+                4,
+                Some(&dedup)
+            ),
+            vec![
+                make_hit(6, None),
+                make_hit(5, Some("a")),
+                make_hit(4, Some("b")),
+                make_hit(3, Some("a")),
+            ]
+        );
+    }
+
     // TODO figure out a way to remove this boilerplate and use mockall
     #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
     struct MockDocMapper;
@@ -1482,6 +2064,7 @@ mod tests {
                     &MockDocMapper,
                     &make_request(len as u64, sort_str),
                     Default::default(),
+                    Default::default(),
                 )
                 .unwrap();
                 let res = searcher
@@ -1498,6 +2081,147 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_search_sorted_split() {
+        use tantivy::schema::{NumericOptions, Schema};
+        use tantivy::{doc, Index, IndexSettings, IndexSortByField, Order};
+
+        let mut schema_builder = Schema::builder();
+        let opts = NumericOptions::default().set_fast();
+        let sort_field = schema_builder.add_u64_field("sort1", opts);
+        let schema = schema_builder.build();
+
+        let index_settings = IndexSettings {
+            sort_by_field: Some(IndexSortByField {
+                field: "sort1".to_string(),
+                order: Order::Desc,
+            }),
+            ..Default::default()
+        };
+        let index = Index::builder()
+            .schema(schema)
+            .settings(index_settings)
+            .create_in_ram()
+            .unwrap();
+        let mut index_writer = index.writer_with_num_threads(1, 15_000_000).unwrap();
+
+        for segment_values in [[3u64, 7, 7, 1], [5, 7, 2, 9]] {
+            for value in segment_values {
+                index_writer.add_document(doc!(sort_field=>value)).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        let searcher = index.reader().unwrap().searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+
+        for len in 1..=8 {
+            let collector = super::make_collector_for_split(
+                "fake_split_id".to_string(),
+                &MockDocMapper,
+                &make_request(len, "sort1"),
+                Default::default(),
+                Default::default(),
+            )
+            .unwrap();
+            assert!(collector.can_terminate_early("sort1", SortOrder::Desc));
+
+            let expected_response = searcher
+                .search(&tantivy::query::AllQuery, &collector)
+                .unwrap();
+            let response =
+                super::search_sorted_split(&searcher, &tantivy::query::AllQuery, &collector)
+                    .unwrap();
+            assert_eq!(response.partial_hits, expected_response.partial_hits);
+            assert!(response.num_hits <= expected_response.num_hits);
+
+            if len == 1 {
+                // Each segment stops after the documents tied with the top hit.
+                assert_eq!(response.num_hits, 3);
+            }
+        }
+        let collector = super::make_collector_for_split(
+            "fake_split_id".to_string(),
+            &MockDocMapper,
+            &make_request(1, "-sort1"),
+            Default::default(),
+            Default::default(),
+        )
+        .unwrap();
+        assert!(!collector.can_terminate_early("sort1", SortOrder::Desc));
+        assert!(collector.can_terminate_early("sort1", SortOrder::Asc));
+
+        let collector = super::make_collector_for_split(
+            "fake_split_id".to_string(),
+            &MockDocMapper,
+            &make_request(1, "sort1,sort2"),
+            Default::default(),
+            Default::default(),
+        )
+        .unwrap();
+        assert!(!collector.can_terminate_early("sort1", SortOrder::Desc));
+    }
+
+    #[test]
+    fn test_single_split_sorting_by_expression() {
+        let index = make_index();
+
+        let reader = index.reader().unwrap();
+        let searcher = reader.searcher();
+
+        let expression_value =
+            |(val1, val2): (Option<u64>, Option<u64>)| Some(val1? as f64 + val2? as f64 * 10.0);
+        for (sort_str, order) in [
+            ("sort1 + sort2 * 10", SortOrder::Desc),
+            ("-sort1 + sort2 * 10", SortOrder::Asc),
+        ] {
+            let mut expected: Vec<(u32, Option<f64>)> = sort_dataset()
+                .into_iter()
+                .enumerate()
+                .map(|(doc_id, values)| (doc_id as u32, expression_value(values)))
+                .collect();
+            // Documents without a value come last.
+            expected.sort_by(|(left_doc_id, left_value), (right_doc_id, right_value)| {
+                let cmp_doc_id = match order {
+                    SortOrder::Desc => right_doc_id.cmp(left_doc_id),
+                    SortOrder::Asc => left_doc_id.cmp(right_doc_id),
+                };
+                match (left_value, right_value) {
+                    (Some(left_value), Some(right_value)) => {
+                        let cmp_value = match order {
+                            SortOrder::Desc => right_value.partial_cmp(left_value),
+                            SortOrder::Asc => left_value.partial_cmp(right_value),
+                        };
+                        cmp_value.unwrap().then(cmp_doc_id)
+                    }
+                    (Some(_), None) => Ordering::Less,
+                    (None, Some(_)) => Ordering::Greater,
+                    (None, None) => cmp_doc_id,
+                }
+            });
+            let collector = super::make_collector_for_split(
+                "fake_split_id".to_string(),
+                &MockDocMapper,
+                &make_request(expected.len() as u64, sort_str),
+                Default::default(),
+                Default::default(),
+            )
+            .unwrap();
+            let res = searcher
+                .search(&tantivy::query::AllQuery, &collector)
+                .unwrap();
+            let doc_ids: Vec<u32> = res.partial_hits.iter().map(|hit| hit.doc_id).collect();
+            let expected_doc_ids: Vec<u32> = expected.iter().map(|(doc_id, _)| *doc_id).collect();
+            assert_eq!(
+                doc_ids, expected_doc_ids,
+                "missmatch ordering for \"{sort_str}\""
+            );
+            assert_eq!(
+                res.partial_hits[0].sort_value(),
+                expected[0].1.map(SortValue::F64)
+            );
+        }
+    }
+
     #[test]
     fn test_search_after() {
         let index = make_index();
@@ -1530,6 +2254,7 @@ mod tests {
                 sort_value2: Some(SortByValue {
                     sort_value: val2.map(SortValue::U64),
                 }),
+                dedup_key: None,
             })
             .collect::<Vec<_>>();
         // we eliminte based on sort value
@@ -1556,6 +2281,7 @@ mod tests {
                 &MockDocMapper,
                 &request,
                 Default::default(),
+                Default::default(),
             )
             .unwrap();
             let res = searcher
@@ -1578,6 +2304,7 @@ mod tests {
                 doc_id: 5,
                 sort_value: None,
                 sort_value2: None,
+                dedup_key: None,
             };
             let request = SearchRequest {
                 max_hits: 1000,
@@ -1595,6 +2322,7 @@ mod tests {
                 &MockDocMapper,
                 &request,
                 Default::default(),
+                Default::default(),
             )
             .unwrap();
             let res = searcher
@@ -1610,6 +2338,7 @@ mod tests {
                 &MockDocMapper,
                 &request,
                 Default::default(),
+                Default::default(),
             )
             .unwrap();
             let res = searcher
@@ -1624,6 +2353,7 @@ mod tests {
                 &MockDocMapper,
                 &request,
                 Default::default(),
+                Default::default(),
             )
             .unwrap();
             let res = searcher
@@ -1636,11 +2366,52 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_single_split_dedup() {
+        let index = make_index();
+        let reader = index.reader().unwrap();
+        let searcher = reader.searcher();
+
+        let mut request = make_request(20, "sort2");
+        request.dedup_field = Some("sort1".to_string());
+        let collector = super::make_collector_for_split(
+            "fake_split_id".to_string(),
+            &MockDocMapper,
+            &request,
+            Default::default(),
+            Default::default(),
+        )
+        .unwrap();
+        let res = searcher
+            .search(&tantivy::query::AllQuery, &collector)
+            .unwrap();
+        assert_eq!(res.num_hits, sort_dataset().len() as u64);
+
+        // For each value of `sort1`, only the document with the highest `sort2` is kept. Documents
+        // without a value for `sort1` are not deduplicated.
+        let mut doc_ids: Vec<u32> = res.partial_hits.iter().map(|hit| hit.doc_id).collect();
+        doc_ids.sort_unstable();
+        assert_eq!(doc_ids, vec![4, 5, 7, 9, 11, 12, 14]);
+
+        let mut dedup_keys: Vec<(u32, Option<&str>)> = res
+            .partial_hits
+            .iter()
+            .filter(|hit| [7, 11, 12, 14].contains(&hit.doc_id))
+            .map(|hit| (hit.doc_id, hit.dedup_key.as_deref()))
+            .collect();
+        dedup_keys.sort_unstable();
+        assert_eq!(
+            dedup_keys,
+            vec![(7, Some("1")), (11, Some("2")), (12, Some("0")), (14, None)]
+        );
+    }
+
     fn merge_collector_equal_results(
         request: &SearchRequest,
         results: Vec<LeafSearchResponse>,
     ) -> LeafSearchResponse {
-        let collector = make_merge_collector(request, &Default::default()).unwrap();
+        let collector =
+            make_merge_collector(request, &Default::default(), &Default::default()).unwrap();
         let mut incremental_collector = IncrementalCollector::new(collector.clone());
 
         let result = collector
@@ -1678,10 +2449,13 @@ mod tests {
                     doc_id: 123,
                     sort_value: Some(SortValue::I64(1234).into()),
                     sort_value2: None,
+                    dedup_key: None,
                 }],
                 failed_splits: Vec::new(),
                 num_attempted_splits: 3,
                 intermediate_aggregation_result: None,
+                skipped_split_ids: Vec::new(),
+                group_counts: Vec::new(),
             }],
         );
 
@@ -1695,10 +2469,13 @@ mod tests {
                     doc_id: 123,
                     sort_value: Some(SortValue::I64(1234).into()),
                     sort_value2: None,
+                    dedup_key: None,
                 }],
                 failed_splits: Vec::new(),
                 num_attempted_splits: 3,
-                intermediate_aggregation_result: None
+                intermediate_aggregation_result: None,
+                skipped_split_ids: Vec::new(),
+                group_counts: Vec::new(),
             }
         );
 
@@ -1724,6 +2501,7 @@ mod tests {
                             doc_id: 123,
                             sort_value: Some(SortValue::I64(1234).into()),
                             sort_value2: None,
+                            dedup_key: None,
                         },
                         PartialHit {
                             split_id: "1".to_string(),
@@ -1731,11 +2509,14 @@ mod tests {
                             doc_id: 125,
                             sort_value: Some(SortValue::I64(1236).into()),
                             sort_value2: None,
+                            dedup_key: None,
                         },
                     ],
                     failed_splits: Vec::new(),
                     num_attempted_splits: 3,
                     intermediate_aggregation_result: None,
+                    skipped_split_ids: Vec::new(),
+                    group_counts: Vec::new(),
                 },
                 LeafSearchResponse {
                     num_hits: 10,
@@ -1745,6 +2526,7 @@ mod tests {
                         doc_id: 3,
                         sort_value: Some(SortValue::I64(1235).into()),
                         sort_value2: None,
+                        dedup_key: None,
                     }],
                     failed_splits: vec![SplitSearchError {
                         error: "fake error".to_string(),
@@ -1753,6 +2535,8 @@ mod tests {
                     }],
                     num_attempted_splits: 2,
                     intermediate_aggregation_result: None,
+                    skipped_split_ids: Vec::new(),
+                    group_counts: Vec::new(),
                 },
             ],
         );
@@ -1768,6 +2552,7 @@ mod tests {
                         doc_id: 125,
                         sort_value: Some(SortValue::I64(1236).into()),
                         sort_value2: None,
+                        dedup_key: None,
                     },
                     PartialHit {
                         split_id: "2".to_string(),
@@ -1775,6 +2560,7 @@ mod tests {
                         doc_id: 3,
                         sort_value: Some(SortValue::I64(1235).into()),
                         sort_value2: None,
+                        dedup_key: None,
                     },
                 ],
                 failed_splits: vec![SplitSearchError {
@@ -1783,7 +2569,9 @@ mod tests {
                     retryable_error: true,
                 }],
                 num_attempted_splits: 5,
-                intermediate_aggregation_result: None
+                intermediate_aggregation_result: None,
+                skipped_split_ids: Vec::new(),
+                group_counts: Vec::new(),
             }
         );
 
@@ -1810,6 +2598,7 @@ mod tests {
                             doc_id: 123,
                             sort_value: Some(SortValue::I64(1234).into()),
                             sort_value2: None,
+                            dedup_key: None,
                         },
                         PartialHit {
                             split_id: "1".to_string(),
@@ -1817,11 +2606,14 @@ mod tests {
                             doc_id: 125,
                             sort_value: Some(SortValue::I64(1236).into()),
                             sort_value2: None,
+                            dedup_key: None,
                         },
                     ],
                     failed_splits: Vec::new(),
                     num_attempted_splits: 3,
                     intermediate_aggregation_result: None,
+                    skipped_split_ids: Vec::new(),
+                    group_counts: Vec::new(),
                 },
                 LeafSearchResponse {
                     num_hits: 10,
@@ -1831,6 +2623,7 @@ mod tests {
                         doc_id: 3,
                         sort_value: Some(SortValue::I64(1235).into()),
                         sort_value2: None,
+                        dedup_key: None,
                     }],
                     failed_splits: vec![SplitSearchError {
                         error: "fake error".to_string(),
@@ -1839,6 +2632,8 @@ mod tests {
                     }],
                     num_attempted_splits: 2,
                     intermediate_aggregation_result: None,
+                    skipped_split_ids: Vec::new(),
+                    group_counts: Vec::new(),
                 },
             ],
         );
@@ -1854,6 +2649,7 @@ mod tests {
                         doc_id: 123,
                         sort_value: Some(SortValue::I64(1234).into()),
                         sort_value2: None,
+                        dedup_key: None,
                     },
                     PartialHit {
                         split_id: "2".to_string(),
@@ -1861,6 +2657,7 @@ mod tests {
                         doc_id: 3,
                         sort_value: Some(SortValue::I64(1235).into()),
                         sort_value2: None,
+                        dedup_key: None,
                     },
                 ],
                 failed_splits: vec![SplitSearchError {
@@ -1869,7 +2666,9 @@ mod tests {
                     retryable_error: true,
                 }],
                 num_attempted_splits: 5,
-                intermediate_aggregation_result: None
+                intermediate_aggregation_result: None,
+                skipped_split_ids: Vec::new(),
+                group_counts: Vec::new(),
             }
         );
         // TODO would be nice to test aggregation too.
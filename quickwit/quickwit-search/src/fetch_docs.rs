@@ -25,12 +25,12 @@ use futures::{StreamExt, TryStreamExt};
 use itertools::Itertools;
 use quickwit_doc_mapper::DocMapper;
 use quickwit_proto::search::{
-    FetchDocsResponse, PartialHit, SnippetRequest, SplitIdAndFooterOffsets,
+    FetchDocsResponse, PartialHit, SnippetOptions, SnippetRequest, SplitIdAndFooterOffsets,
 };
 use quickwit_storage::Storage;
 use tantivy::query::Query;
 use tantivy::schema::{Document as DocumentTrait, Field, OwnedValue, TantivyDocument, Value};
-use tantivy::{ReloadPolicy, Score, Searcher, SnippetGenerator, Term};
+use tantivy::{ReloadPolicy, Score, Searcher, Snippet, SnippetGenerator, Term};
 use tracing::{error, Instrument};
 
 use crate::leaf::open_index_with_caches;
@@ -39,6 +39,9 @@ use crate::{convert_document_to_json_string, GlobalDocAddress};
 
 const SNIPPET_MAX_NUM_CHARS: usize = 150;
 
+const DEFAULT_SNIPPET_PRE_TAG: &str = "<b>";
+const DEFAULT_SNIPPET_POST_TAG: &str = "</b>";
+
 /// Given a list of global doc address, fetches all the documents and
 /// returns them as a hashmap.
 async fn fetch_docs_to_map(
@@ -260,6 +263,9 @@ async fn fetch_docs_in_split(
 #[derive(Clone)]
 struct FieldsSnippetGenerator {
     field_generators: Arc<HashMap<String, SnippetGenerator>>,
+    // Custom highlighting tags. If not set, snippets are rendered with tantivy's default `<b>`
+    // tags.
+    tags_opt: Option<Arc<(String, String)>>,
 }
 
 impl FieldsSnippetGenerator {
@@ -275,10 +281,15 @@ impl FieldsSnippetGenerator {
                 .filter_map(|value| {
                     value.as_str().and_then(|text| {
                         let snippet = snippet_generator.snippet(text);
-                        match snippet.is_empty() {
-                            false => Some(snippet.to_html()),
-                            _ => None,
+                        if snippet.is_empty() {
+                            return None;
                         }
+                        let snippet_html = if let Some(tags) = &self.tags_opt {
+                            snippet_to_html_with_tags(&snippet, &tags.0, &tags.1)
+                        } else {
+                            snippet.to_html()
+                        };
+                        Some(snippet_html)
                     })
                 })
                 .collect();
@@ -303,23 +314,73 @@ async fn create_fields_snippet_generator(
     let query_ast_resolved = serde_json::from_str(&snippet_request.query_ast_resolved)
         .context("failed to deserialize QueryAst")?;
     let (query, _) = doc_mapper.query(schema.clone(), &query_ast_resolved, false)?;
+    let snippet_options = snippet_request.snippet_options.clone().unwrap_or_default();
+    let max_num_chars = snippet_options
+        .max_num_chars
+        .map(|max_num_chars| max_num_chars as usize)
+        .unwrap_or(SNIPPET_MAX_NUM_CHARS);
     let mut snippet_generators = HashMap::new();
     for field_name in &snippet_request.snippet_fields {
         let field = schema.get_field(field_name)?;
-        let snippet_generator = create_snippet_generator(searcher, &query, field).await?;
+        let snippet_generator =
+            create_snippet_generator(searcher, &query, field, max_num_chars).await?;
         snippet_generators.insert(field_name.clone(), snippet_generator);
     }
-
     Ok(FieldsSnippetGenerator {
         field_generators: Arc::new(snippet_generators),
+        tags_opt: snippet_tags(snippet_options).map(Arc::new),
     })
 }
 
+/// Returns the custom highlighting tags of the snippet options, if any.
+fn snippet_tags(snippet_options: SnippetOptions) -> Option<(String, String)> {
+    if snippet_options.pre_tag.is_none() && snippet_options.post_tag.is_none() {
+        return None;
+    }
+    let pre_tag = snippet_options
+        .pre_tag
+        .unwrap_or_else(|| DEFAULT_SNIPPET_PRE_TAG.to_string());
+    let post_tag = snippet_options
+        .post_tag
+        .unwrap_or_else(|| DEFAULT_SNIPPET_POST_TAG.to_string());
+    Some((pre_tag, post_tag))
+}
+
+/// Renders a snippet like [`Snippet::to_html`] does, but wraps the highlighted terms with the
+/// given tags.
+fn snippet_to_html_with_tags(snippet: &Snippet, pre_tag: &str, post_tag: &str) -> String {
+    let fragment = snippet.fragment();
+    let mut html = String::with_capacity(fragment.len());
+    let mut start_from = 0;
+
+    for highlighted_range in snippet.highlighted() {
+        escape_html_into(&fragment[start_from..highlighted_range.start], &mut html);
+        html.push_str(pre_tag);
+        escape_html_into(&fragment[highlighted_range.clone()], &mut html);
+        html.push_str(post_tag);
+        start_from = highlighted_range.end;
+    }
+    escape_html_into(&fragment[start_from..], &mut html);
+    html
+}
+
+fn escape_html_into(text: &str, html: &mut String) {
+    for character in text.chars() {
+        match character {
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            _ => html.push(character),
+        }
+    }
+}
+
 // Creates a snippet generator associated to a field.
 async fn create_snippet_generator(
     searcher: &Searcher,
     query: &dyn Query,
     field: Field,
+    max_num_chars: usize,
 ) -> anyhow::Result<SnippetGenerator> {
     let mut terms: Vec<&Term> = Vec::new();
     // TODO ok with termset?
@@ -345,6 +406,6 @@ async fn create_snippet_generator(
         terms_text,
         tokenizer,
         field,
-        SNIPPET_MAX_NUM_CHARS,
+        max_num_chars,
     ))
 }
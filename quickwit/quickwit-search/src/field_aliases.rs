@@ -0,0 +1,220 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Field aliases declared in the doc mapping are resolved by the root before the request is
+//! validated. The query AST is resolved per index along with the user query, while the sort,
+//! snippet, and aggregation fields are resolved here.
+
+use std::collections::BTreeMap;
+
+use quickwit_metastore::IndexMetadata;
+use quickwit_proto::search::SearchRequest;
+use quickwit_query::query_ast::resolve_field_alias;
+use serde_json::{Map as JsonMap, Value as JsonValue};
+
+use crate::SearchError;
+
+/// Replaces the field aliases targeted by the sort fields, snippet fields, and aggregations of a
+/// search request with their target fields.
+///
+/// Returns an error if a field resolves to different fields across the searched indexes.
+pub(crate) fn resolve_field_aliases(
+    search_request: &mut SearchRequest,
+    indexes_metadata: &[IndexMetadata],
+) -> crate::Result<()> {
+    let field_aliases_per_index: Vec<&BTreeMap<String, String>> = indexes_metadata
+        .iter()
+        .map(|index_metadata| &index_metadata.index_config.doc_mapping.field_aliases)
+        .collect();
+    if field_aliases_per_index
+        .iter()
+        .all(|field_aliases| field_aliases.is_empty())
+    {
+        return Ok(());
+    }
+    let resolver = FieldAliasResolver {
+        field_aliases_per_index,
+    };
+    for sort_field in &mut search_request.sort_fields {
+        sort_field.field_name = resolver.resolve(&sort_field.field_name)?;
+    }
+    for snippet_field in &mut search_request.snippet_fields {
+        *snippet_field = resolver.resolve(snippet_field)?;
+    }
+    let Some(aggregation_request) = &search_request.aggregation_request else {
+        return Ok(());
+    };
+    let mut aggregations: JsonValue = serde_json::from_str(aggregation_request)
+        .map_err(|error| SearchError::InvalidAggregationRequest(error.to_string()))?;
+    let Some(aggregations_map) = aggregations.as_object_mut() else {
+        return Ok(());
+    };
+    if resolver.resolve_aggregations(aggregations_map)? {
+        search_request.aggregation_request = Some(serde_json::to_string(&aggregations)?);
+    }
+    Ok(())
+}
+
+struct FieldAliasResolver<'a> {
+    field_aliases_per_index: Vec<&'a BTreeMap<String, String>>,
+}
+
+impl FieldAliasResolver<'_> {
+    fn resolve(&self, field_name: &str) -> crate::Result<String> {
+        let mut resolved_field_name_opt: Option<String> = None;
+
+        for field_aliases in &self.field_aliases_per_index {
+            let resolved_field_name = resolve_field_alias(field_name.to_string(), field_aliases);
+
+            match &resolved_field_name_opt {
+                Some(other_resolved_field_name)
+                    if *other_resolved_field_name != resolved_field_name =>
+                {
+                    return Err(SearchError::InvalidQuery(format!(
+                        "field `{field_name}` resolves to different fields across indexes: \
+                         `{other_resolved_field_name}` and `{resolved_field_name}`"
+                    )));
+                }
+                Some(_) => {}
+                None => resolved_field_name_opt = Some(resolved_field_name),
+            }
+        }
+        Ok(resolved_field_name_opt.unwrap_or_else(|| field_name.to_string()))
+    }
+
+    /// Resolves the `field` parameter of the aggregations and their sub-aggregations. Returns
+    /// `true` if at least one field was resolved.
+    fn resolve_aggregations(
+        &self,
+        aggregations: &mut JsonMap<String, JsonValue>,
+    ) -> crate::Result<bool> {
+        let mut is_resolved = false;
+
+        for aggregation in aggregations.values_mut() {
+            let Some(aggregation) = aggregation.as_object_mut() else {
+                continue;
+            };
+            for (key, params) in aggregation.iter_mut() {
+                if key == "aggs" || key == "aggregations" {
+                    if let Some(sub_aggregations) = params.as_object_mut() {
+                        is_resolved |= self.resolve_aggregations(sub_aggregations)?;
+                    }
+                    continue;
+                }
+                let Some(JsonValue::String(field_name)) = params.get_mut("field") else {
+                    continue;
+                };
+                let resolved_field_name = self.resolve(field_name)?;
+
+                if *field_name != resolved_field_name {
+                    *field_name = resolved_field_name;
+                    is_resolved = true;
+                }
+            }
+        }
+        Ok(is_resolved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_proto::search::SortField;
+    use serde_json::json;
+
+    use super::*;
+
+    fn index_metadata_with_field_aliases(
+        index_id: &str,
+        field_aliases: &[(&str, &str)],
+    ) -> IndexMetadata {
+        let mut index_metadata =
+            IndexMetadata::for_test(index_id, &format!("ram:///indexes/{index_id}"));
+        index_metadata.index_config.doc_mapping.field_aliases = field_aliases
+            .iter()
+            .map(|(alias, target)| (alias.to_string(), target.to_string()))
+            .collect();
+        index_metadata
+    }
+
+    #[test]
+    fn test_resolve_field_aliases() {
+        let indexes_metadata = vec![index_metadata_with_field_aliases(
+            "test-index",
+            &[("@timestamp", "timestamp"), ("message", "body")],
+        )];
+        let aggregations = json!({
+            "per_day": {
+                "date_histogram": {"field": "@timestamp", "fixed_interval": "1d"},
+                "aggs": {
+                    "top_messages": {"terms": {"field": "message"}},
+                    "max_latency": {"max": {"field": "latency"}}
+                }
+            }
+        });
+        let mut search_request = SearchRequest {
+            sort_fields: vec![SortField {
+                field_name: "@timestamp".to_string(),
+                ..Default::default()
+            }],
+            snippet_fields: vec!["message".to_string()],
+            aggregation_request: Some(aggregations.to_string()),
+            ..Default::default()
+        };
+        resolve_field_aliases(&mut search_request, &indexes_metadata).unwrap();
+
+        assert_eq!(search_request.sort_fields[0].field_name, "timestamp");
+        assert_eq!(search_request.snippet_fields, ["body"]);
+
+        let resolved_aggregations: JsonValue =
+            serde_json::from_str(search_request.aggregation_request.as_ref().unwrap()).unwrap();
+        let expected_aggregations = json!({
+            "per_day": {
+                "date_histogram": {"field": "timestamp", "fixed_interval": "1d"},
+                "aggs": {
+                    "top_messages": {"terms": {"field": "body"}},
+                    "max_latency": {"max": {"field": "latency"}}
+                }
+            }
+        });
+        assert_eq!(resolved_aggregations, expected_aggregations);
+    }
+
+    #[test]
+    fn test_resolve_field_aliases_conflict() {
+        let indexes_metadata = vec![
+            index_metadata_with_field_aliases("test-index-1", &[("message", "body")]),
+            index_metadata_with_field_aliases("test-index-2", &[("message", "text")]),
+        ];
+        let mut search_request = SearchRequest {
+            sort_fields: vec![SortField {
+                field_name: "timestamp".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        resolve_field_aliases(&mut search_request, &indexes_metadata).unwrap();
+
+        search_request.snippet_fields = vec!["message".to_string()];
+        let error = resolve_field_aliases(&mut search_request, &indexes_metadata).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "field `message` resolves to different fields across indexes: `body` and `text`"
+        );
+    }
+}
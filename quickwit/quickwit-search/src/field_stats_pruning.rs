@@ -0,0 +1,268 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::ops::Bound;
+
+use quickwit_metastore::{FieldStats, FieldValueRange, SplitMetadata};
+use quickwit_query::query_ast::{BoolQuery, QueryAst, QueryAstVisitor, RangeQuery};
+use quickwit_query::{InterpretUserInput, JsonLiteral};
+use tracing::debug;
+
+/// Returns the range queries that every document matching the query must satisfy, i.e. the range
+/// queries only nested in `must` and `filter` clauses.
+pub(crate) fn extract_required_ranges(query_ast: &QueryAst) -> Vec<RangeQuery> {
+    let mut required_ranges_extractor = ExtractRequiredRanges::default();
+    required_ranges_extractor
+        .visit(query_ast)
+        .expect("can't fail unwrapping Infallible");
+    required_ranges_extractor
+        .range_queries
+        .into_iter()
+        .cloned()
+        .collect()
+}
+
+#[derive(Default)]
+struct ExtractRequiredRanges<'a> {
+    range_queries: Vec<&'a RangeQuery>,
+}
+
+impl<'a> QueryAstVisitor<'a> for ExtractRequiredRanges<'a> {
+    type Err = std::convert::Infallible;
+
+    fn visit_bool(&mut self, bool_query: &'a BoolQuery) -> Result<(), Self::Err> {
+        // we only want to visit sub-queries which are strict (positive) requirements
+        for ast in bool_query.must.iter().chain(bool_query.filter.iter()) {
+            self.visit(ast)?;
+        }
+        Ok(())
+    }
+
+    fn visit_range(&mut self, range_query: &'a RangeQuery) -> Result<(), Self::Err> {
+        self.range_queries.push(range_query);
+        Ok(())
+    }
+}
+
+/// Removes the splits that cannot contain any document matching the required range queries
+/// because the values of the queried field in the split all fall outside of the range.
+///
+/// Splits without stats for the queried field are always kept.
+pub(crate) fn prune_splits_by_field_stats(
+    split_metadatas: &mut Vec<SplitMetadata>,
+    required_ranges: &[RangeQuery],
+) {
+    if required_ranges.is_empty() {
+        return;
+    }
+    let num_splits_before_pruning = split_metadatas.len();
+    split_metadatas.retain(|split_metadata| {
+        required_ranges.iter().all(|range_query| {
+            split_metadata
+                .field_stats
+                .get(&range_query.field)
+                .map_or(true, |field_stats| {
+                    may_match_range(field_stats, range_query)
+                })
+        })
+    });
+    debug!(
+        num_splits_before_pruning,
+        num_splits_after_pruning = split_metadatas.len(),
+        "pruned splits by field stats"
+    );
+}
+
+fn may_match_range(field_stats: &FieldStats, range_query: &RangeQuery) -> bool {
+    match field_stats.range {
+        FieldValueRange::I64 { min, max } => {
+            overlaps_range(min, max, range_query, |bound| i64::interpret_json(bound))
+        }
+        FieldValueRange::U64 { min, max } => {
+            overlaps_range(min, max, range_query, |bound| u64::interpret_json(bound))
+        }
+        FieldValueRange::F64 { min, max } => {
+            overlaps_range(min, max, range_query, |bound| f64::interpret_json(bound))
+        }
+        // Datetime stats are expressed in microseconds, and the bounds are compared in
+        // nanoseconds so that they are not rounded.
+        FieldValueRange::Datetime { min, max } => overlaps_range(
+            min.saturating_mul(1_000),
+            max.saturating_mul(1_000),
+            range_query,
+            |bound| {
+                tantivy::DateTime::interpret_json(bound)
+                    .map(|date_time| date_time.into_timestamp_nanos())
+            },
+        ),
+    }
+}
+
+/// Returns whether the `[min, max]` interval intersects the range of the query. Bounds that cannot
+/// be interpreted as values of the field are ignored.
+fn overlaps_range<T: PartialOrd>(
+    min: T,
+    max: T,
+    range_query: &RangeQuery,
+    interpret_bound: impl Fn(&JsonLiteral) -> Option<T>,
+) -> bool {
+    let satisfies_lower_bound = match &range_query.lower_bound {
+        Bound::Included(lower_bound) => {
+            interpret_bound(lower_bound).map_or(true, |lower_bound| max >= lower_bound)
+        }
+        Bound::Excluded(lower_bound) => {
+            interpret_bound(lower_bound).map_or(true, |lower_bound| max > lower_bound)
+        }
+        Bound::Unbounded => true,
+    };
+    let satisfies_upper_bound = match &range_query.upper_bound {
+        Bound::Included(upper_bound) => {
+            interpret_bound(upper_bound).map_or(true, |upper_bound| min <= upper_bound)
+        }
+        Bound::Excluded(upper_bound) => {
+            interpret_bound(upper_bound).map_or(true, |upper_bound| min < upper_bound)
+        }
+        Bound::Unbounded => true,
+    };
+    satisfies_lower_bound && satisfies_upper_bound
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_query::query_ast::qast_helper;
+
+    use super::*;
+
+    fn split_with_stats(split_id: &str, field_stats: &[(&str, FieldValueRange)]) -> SplitMetadata {
+        SplitMetadata {
+            split_id: split_id.to_string(),
+            field_stats: field_stats
+                .iter()
+                .map(|(field_name, range)| {
+                    let field_stats = FieldStats {
+                        range: *range,
+                        cardinality: None,
+                    };
+                    (field_name.to_string(), field_stats)
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn pruned_split_ids(split_metadatas: &[SplitMetadata], user_query: &str) -> Vec<String> {
+        let query_ast = qast_helper(user_query, &[]);
+        let required_ranges = extract_required_ranges(&query_ast);
+        let mut split_metadatas = split_metadatas.to_vec();
+        prune_splits_by_field_stats(&mut split_metadatas, &required_ranges);
+        split_metadatas
+            .into_iter()
+            .map(|split_metadata| split_metadata.split_id)
+            .collect()
+    }
+
+    #[test]
+    fn test_extract_required_ranges() {
+        let query_ast = qast_helper("status:[400 TO 499] AND latency:{100 TO *}", &[]);
+        let required_ranges = extract_required_ranges(&query_ast);
+        assert_eq!(required_ranges.len(), 2);
+        assert_eq!(required_ranges[0].field, "status");
+        assert_eq!(required_ranges[1].field, "latency");
+
+        let query_ast = qast_helper("status:[400 TO 499] OR latency:{100 TO *}", &[]);
+        assert!(extract_required_ranges(&query_ast).is_empty());
+
+        let query_ast = qast_helper("NOT status:[400 TO 499]", &[]);
+        assert!(extract_required_ranges(&query_ast).is_empty());
+    }
+
+    #[test]
+    fn test_prune_splits_by_field_stats() {
+        let split_metadatas = vec![
+            split_with_stats(
+                "split-1",
+                &[("status", FieldValueRange::U64 { min: 200, max: 204 })],
+            ),
+            split_with_stats(
+                "split-2",
+                &[("status", FieldValueRange::U64 { min: 200, max: 404 })],
+            ),
+            split_with_stats(
+                "split-3",
+                &[("latency", FieldValueRange::F64 { min: 0.5, max: 9.5 })],
+            ),
+            split_with_stats("split-4", &[]),
+        ];
+        assert_eq!(
+            pruned_split_ids(&split_metadatas, "*"),
+            ["split-1", "split-2", "split-3", "split-4"]
+        );
+        assert_eq!(
+            pruned_split_ids(&split_metadatas, "status:[400 TO 499]"),
+            ["split-2", "split-3", "split-4"]
+        );
+        assert_eq!(
+            pruned_split_ids(&split_metadatas, "status:{404 TO *}"),
+            ["split-3", "split-4"]
+        );
+        assert_eq!(
+            pruned_split_ids(&split_metadatas, "status:[* TO 200]"),
+            ["split-1", "split-2", "split-3", "split-4"]
+        );
+        assert_eq!(
+            pruned_split_ids(&split_metadatas, "latency:[10 TO *] AND status:[200 TO *]"),
+            ["split-1", "split-2", "split-4"]
+        );
+        // Bounds that are not valid values of the field never prune splits.
+        assert_eq!(
+            pruned_split_ids(&split_metadatas, "status:{1000.5 TO *}"),
+            ["split-1", "split-2", "split-3", "split-4"]
+        );
+    }
+
+    #[test]
+    fn test_prune_splits_by_field_stats_datetime() {
+        // 2023-01-01T00:00:00Z and 2023-01-31T00:00:00Z.
+        let split_metadatas = vec![split_with_stats(
+            "split-1",
+            &[(
+                "created_at",
+                FieldValueRange::Datetime {
+                    min: 1_672_531_200_000_000,
+                    max: 1_675_123_200_000_000,
+                },
+            )],
+        )];
+        assert_eq!(
+            pruned_split_ids(
+                &split_metadatas,
+                "created_at:[2023-01-15T00:00:00Z TO 2023-02-15T00:00:00Z]"
+            ),
+            ["split-1"]
+        );
+        assert!(pruned_split_ids(
+            &split_metadatas,
+            "created_at:[2023-02-01T00:00:00Z TO 2023-02-15T00:00:00Z]"
+        )
+        .is_empty());
+        assert!(
+            pruned_split_ids(&split_metadatas, "created_at:{* TO 2023-01-01T00:00:00Z}").is_empty()
+        );
+    }
+}
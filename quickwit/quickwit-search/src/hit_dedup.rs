@@ -0,0 +1,276 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use quickwit_common::binary_heap::SortKeyMapper;
+use quickwit_proto::search::SearchRequest;
+use tantivy::columnar::{ColumnType, MonotonicallyMappableToU64, StrColumn};
+use tantivy::fastfield::Column;
+use tantivy::{DocId, SegmentReader};
+
+/// Deduplication of the hits sharing the same value of a fast field.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct HitDedup {
+    pub field_name: String,
+    /// Maximum number of hits kept per value of the field.
+    pub max_hits_per_key: usize,
+}
+
+impl HitDedup {
+    pub fn from_search_request(search_request: &SearchRequest) -> Option<Self> {
+        let field_name = search_request.dedup_field.clone()?;
+        let max_hits_per_key = search_request.dedup_count.unwrap_or(1) as usize;
+        Some(HitDedup {
+            field_name,
+            max_hits_per_key,
+        })
+    }
+}
+
+/// Top-K computer keeping at most `max_per_key` entries sharing the same dedup key.
+///
+/// Entries without a dedup key are never deduplicated. Entries are buffered, and the buffer is
+/// compacted into the deduplicated top-K whenever it reaches twice its capacity.
+#[derive(Clone)]
+pub(crate) struct DedupTopK<T, K, S> {
+    k: usize,
+    max_per_key: usize,
+    pub sort_key_mapper: S,
+    entries: Vec<(Option<K>, T)>,
+    // Whether `entries` holds the sorted deduplicated top-K of the entries added so far.
+    is_compacted: bool,
+}
+
+impl<T, K, S> DedupTopK<T, K, S>
+where
+    K: Clone + Eq + Hash,
+    S: SortKeyMapper<T>,
+    S::Key: Ord,
+{
+    pub fn new(k: usize, max_per_key: usize, sort_key_mapper: S) -> Self {
+        DedupTopK {
+            k,
+            max_per_key,
+            sort_key_mapper,
+            entries: Vec::new(),
+            is_compacted: true,
+        }
+    }
+
+    pub fn add_entry(&mut self, dedup_key_opt: Option<K>, entry: T) {
+        if self.k == 0 || self.max_per_key == 0 {
+            return;
+        }
+        self.entries.push((dedup_key_opt, entry));
+        self.is_compacted = false;
+
+        if self.entries.len() >= 2 * self.k {
+            self.compact();
+        }
+    }
+
+    /// Sorts the buffered entries and only retains the deduplicated top-K.
+    ///
+    /// An entry dropped here can never make it back into the top-K: either `max_per_key` better
+    /// entries share its key, or `k` better entries are retained.
+    pub fn compact(&mut self) {
+        if self.is_compacted {
+            return;
+        }
+        let sort_key_mapper = &self.sort_key_mapper;
+        self.entries
+            .sort_by_cached_key(|(_, entry)| Reverse(sort_key_mapper.get_sort_key(entry)));
+
+        let k = self.k;
+        let max_per_key = self.max_per_key;
+        let mut num_entries_per_key: HashMap<K, usize> = HashMap::new();
+        let mut num_retained_entries = 0;
+
+        self.entries.retain(|(dedup_key_opt, _)| {
+            if num_retained_entries == k {
+                return false;
+            }
+            if let Some(dedup_key) = dedup_key_opt {
+                let num_entries = num_entries_per_key.entry(dedup_key.clone()).or_default();
+
+                if *num_entries == max_per_key {
+                    return false;
+                }
+                *num_entries += 1;
+            }
+            num_retained_entries += 1;
+            true
+        });
+        self.is_compacted = true;
+    }
+
+    /// Returns the worst entry of the top-K, if the top-K is compacted and at capacity.
+    pub fn peek_worst(&self) -> Option<&T> {
+        if self.is_compacted && self.entries.len() == self.k {
+            self.entries.last().map(|(_, entry)| entry)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the deduplicated top-K entries, sorted from best to worst.
+    pub fn finalize(mut self) -> Vec<(Option<K>, T)> {
+        self.compact();
+        self.entries
+    }
+}
+
+/// Fast field column holding the dedup keys of the documents of a segment.
+///
+/// Within a segment, the dedup key of a document is the term ordinal for string columns and the
+/// value mapped to `u64` for the other columns. Keys are converted to strings, which are
+/// comparable across segments and splits, once the top hits of the segment are known.
+pub(crate) enum DedupKeyColumn {
+    Str(StrColumn),
+    Numeric {
+        column: Column<u64>,
+        column_type: ColumnType,
+    },
+}
+
+impl DedupKeyColumn {
+    pub fn open(segment_reader: &SegmentReader, field_name: &str) -> tantivy::Result<Option<Self>> {
+        let fast_fields = segment_reader.fast_fields();
+
+        if let Some(str_column) = fast_fields.str(field_name)? {
+            return Ok(Some(Self::Str(str_column)));
+        }
+        if let Some((column, column_type)) = fast_fields.u64_lenient(field_name)? {
+            return Ok(Some(Self::Numeric {
+                column,
+                column_type,
+            }));
+        }
+        Ok(None)
+    }
+
+    pub fn dedup_key(&self, doc_id: DocId) -> Option<u64> {
+        match self {
+            Self::Str(str_column) => str_column.ords().first(doc_id),
+            Self::Numeric { column, .. } => column.first(doc_id),
+        }
+    }
+
+    pub fn dedup_key_to_string(&self, dedup_key: u64) -> tantivy::Result<String> {
+        match self {
+            Self::Str(str_column) => {
+                let mut buffer = String::new();
+                str_column.ord_to_str(dedup_key, &mut buffer)?;
+                Ok(buffer)
+            }
+            Self::Numeric { column_type, .. } => {
+                let dedup_key_str = match column_type {
+                    ColumnType::I64 | ColumnType::DateTime => i64::from_u64(dedup_key).to_string(),
+                    ColumnType::F64 => f64::from_u64(dedup_key).to_string(),
+                    ColumnType::Bool => (dedup_key != 0).to_string(),
+                    _ => dedup_key.to_string(),
+                };
+                Ok(dedup_key_str)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_common::binary_heap::SortKeyMapper;
+
+    use super::DedupTopK;
+
+    struct IdentityMapper;
+
+    impl SortKeyMapper<u64> for IdentityMapper {
+        type Key = u64;
+
+        fn get_sort_key(&self, value: &u64) -> u64 {
+            *value
+        }
+    }
+
+    fn dedup_top_k(
+        entries: &[(Option<&'static str>, u64)],
+        k: usize,
+        max_per_key: usize,
+    ) -> Vec<u64> {
+        let mut top_k = DedupTopK::new(k, max_per_key, IdentityMapper);
+        for (dedup_key_opt, entry) in entries {
+            top_k.add_entry(*dedup_key_opt, *entry);
+        }
+        top_k
+            .finalize()
+            .into_iter()
+            .map(|(_, entry)| entry)
+            .collect()
+    }
+
+    #[test]
+    fn test_dedup_top_k() {
+        let entries = [
+            (Some("a"), 1),
+            (Some("b"), 7),
+            (Some("a"), 9),
+            (None, 3),
+            (Some("a"), 5),
+            (Some("b"), 2),
+            (None, 4),
+        ];
+        assert_eq!(dedup_top_k(&entries, 10, 1), vec![9, 7, 4, 3]);
+        assert_eq!(dedup_top_k(&entries, 10, 2), vec![9, 7, 5, 4, 3, 2]);
+        assert_eq!(dedup_top_k(&entries, 3, 1), vec![9, 7, 4]);
+        assert_eq!(dedup_top_k(&entries, 0, 1), Vec::<u64>::new());
+    }
+
+    #[test]
+    fn test_dedup_top_k_compaction_is_exact() {
+        // The buffer is compacted several times: the dropped entries must not affect the result.
+        let mut entries = Vec::new();
+        for value in 0..100u64 {
+            let dedup_key = ["a", "b", "c", "d"][(value % 4) as usize];
+            entries.push((Some(dedup_key), value));
+        }
+        assert_eq!(dedup_top_k(&entries, 3, 1), vec![99, 98, 97]);
+        assert_eq!(dedup_top_k(&entries, 6, 1), vec![99, 98, 97, 96]);
+        assert_eq!(dedup_top_k(&entries, 6, 2), vec![99, 98, 97, 96, 95, 94]);
+    }
+
+    #[test]
+    fn test_dedup_top_k_peek_worst() {
+        let mut top_k = DedupTopK::new(2, 1, IdentityMapper);
+        top_k.add_entry(Some("a"), 5);
+        top_k.compact();
+        assert_eq!(top_k.peek_worst(), None);
+
+        top_k.add_entry(Some("a"), 6);
+        top_k.compact();
+        assert_eq!(top_k.peek_worst(), None);
+
+        top_k.add_entry(Some("b"), 3);
+        assert_eq!(top_k.peek_worst(), None);
+        top_k.compact();
+        assert_eq!(top_k.peek_worst(), Some(&3));
+    }
+}
@@ -0,0 +1,225 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+
+use quickwit_proto::search::{Federation, PartialHit, SearchRequest};
+
+/// Merging of the hits of several indexes into a single list of hits.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct HitFederation {
+    pub federation: Federation,
+    /// Maximum number of hits kept per index.
+    pub max_hits_per_index_opt: Option<usize>,
+}
+
+impl HitFederation {
+    /// Returns `None` if the request ranks hits globally without any per-index quota, which is
+    /// the default merge behavior.
+    pub fn from_search_request(search_request: &SearchRequest) -> Option<Self> {
+        let federation = search_request.federation();
+        let max_hits_per_index_opt = search_request
+            .max_hits_per_index
+            .map(|max_hits_per_index| max_hits_per_index as usize);
+        if federation == Federation::Global && max_hits_per_index_opt.is_none() {
+            return None;
+        }
+        Some(HitFederation {
+            federation,
+            max_hits_per_index_opt,
+        })
+    }
+
+    /// Federates hits sorted from best to worst, and returns the hits with rank
+    /// `[start_offset..start_offset + max_hits)`.
+    ///
+    /// `index_uid_per_split_id` maps the split IDs of the hits to the UID of their index.
+    pub fn federate(
+        &self,
+        partial_hits: Vec<PartialHit>,
+        index_uid_per_split_id: &HashMap<&str, &str>,
+        start_offset: usize,
+        max_hits: usize,
+    ) -> Vec<PartialHit> {
+        let mut num_hits_per_index: HashMap<&str, usize> = HashMap::new();
+        let mut ranked_hits: Vec<(usize, PartialHit)> = Vec::with_capacity(partial_hits.len());
+
+        for partial_hit in partial_hits {
+            let index_uid = index_uid_per_split_id
+                .get(partial_hit.split_id.as_str())
+                .copied()
+                .unwrap_or_default();
+            let num_hits = num_hits_per_index.entry(index_uid).or_default();
+            let rank_in_index = *num_hits;
+            *num_hits += 1;
+
+            if let Some(max_hits_per_index) = self.max_hits_per_index_opt {
+                if rank_in_index >= max_hits_per_index {
+                    continue;
+                }
+            }
+            ranked_hits.push((rank_in_index, partial_hit));
+        }
+        if self.federation == Federation::Balanced {
+            // The sort is stable: hits of the same rank remain sorted from best to worst.
+            ranked_hits.sort_by_key(|(rank_in_index, _)| *rank_in_index);
+        }
+        ranked_hits
+            .into_iter()
+            .map(|(_, partial_hit)| partial_hit)
+            .skip(start_offset)
+            .take(max_hits)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mock_partial_hit(split_id: &str, doc_id: u32) -> PartialHit {
+        PartialHit {
+            split_id: split_id.to_string(),
+            doc_id,
+            ..Default::default()
+        }
+    }
+
+    fn federate(
+        hit_federation: &HitFederation,
+        start_offset: usize,
+        max_hits: usize,
+    ) -> Vec<(String, u32)> {
+        // Hits of the large index `index-a` (splits `a1`, `a2`) rank before the hits of the small
+        // index `index-b` (split `b1`).
+        let partial_hits = vec![
+            mock_partial_hit("a1", 0),
+            mock_partial_hit("a2", 1),
+            mock_partial_hit("a1", 2),
+            mock_partial_hit("a2", 3),
+            mock_partial_hit("b1", 4),
+            mock_partial_hit("b1", 5),
+        ];
+        let index_uid_per_split_id = HashMap::from_iter([
+            ("a1", "index-a:0"),
+            ("a2", "index-a:0"),
+            ("b1", "index-b:0"),
+        ]);
+        hit_federation
+            .federate(
+                partial_hits,
+                &index_uid_per_split_id,
+                start_offset,
+                max_hits,
+            )
+            .into_iter()
+            .map(|partial_hit| (partial_hit.split_id, partial_hit.doc_id))
+            .collect()
+    }
+
+    fn hits(expected: &[(&str, u32)]) -> Vec<(String, u32)> {
+        expected
+            .iter()
+            .map(|(split_id, doc_id)| (split_id.to_string(), *doc_id))
+            .collect()
+    }
+
+    #[test]
+    fn test_hit_federation_from_search_request() {
+        assert!(HitFederation::from_search_request(&SearchRequest::default()).is_none());
+
+        let search_request = SearchRequest {
+            max_hits_per_index: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(
+            HitFederation::from_search_request(&search_request).unwrap(),
+            HitFederation {
+                federation: Federation::Global,
+                max_hits_per_index_opt: Some(3),
+            }
+        );
+        let search_request = SearchRequest {
+            federation: Federation::Balanced.into(),
+            ..Default::default()
+        };
+        assert_eq!(
+            HitFederation::from_search_request(&search_request).unwrap(),
+            HitFederation {
+                federation: Federation::Balanced,
+                max_hits_per_index_opt: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_hit_federation_global_with_quota() {
+        let hit_federation = HitFederation {
+            federation: Federation::Global,
+            max_hits_per_index_opt: Some(2),
+        };
+        assert_eq!(
+            federate(&hit_federation, 0, 10),
+            hits(&[("a1", 0), ("a2", 1), ("b1", 4), ("b1", 5)])
+        );
+        assert_eq!(
+            federate(&hit_federation, 1, 2),
+            hits(&[("a2", 1), ("b1", 4)])
+        );
+    }
+
+    #[test]
+    fn test_hit_federation_balanced() {
+        let hit_federation = HitFederation {
+            federation: Federation::Balanced,
+            max_hits_per_index_opt: None,
+        };
+        assert_eq!(
+            federate(&hit_federation, 0, 10),
+            hits(&[
+                ("a1", 0),
+                ("b1", 4),
+                ("a2", 1),
+                ("b1", 5),
+                ("a1", 2),
+                ("a2", 3)
+            ])
+        );
+        assert_eq!(
+            federate(&hit_federation, 0, 2),
+            hits(&[("a1", 0), ("b1", 4)])
+        );
+        assert_eq!(
+            federate(&hit_federation, 4, 10),
+            hits(&[("a1", 2), ("a2", 3)])
+        );
+    }
+
+    #[test]
+    fn test_hit_federation_balanced_with_quota() {
+        let hit_federation = HitFederation {
+            federation: Federation::Balanced,
+            max_hits_per_index_opt: Some(1),
+        };
+        assert_eq!(
+            federate(&hit_federation, 0, 10),
+            hits(&[("a1", 0), ("b1", 4)])
+        );
+    }
+}
@@ -0,0 +1,252 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use quickwit_common::binary_heap::{top_k, SortKeyMapper, TopK};
+use quickwit_proto::search::{GroupCount, Hit, HitGroup, SearchRequest};
+
+/// Grouping of the hits by the value of a fast field.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct HitGrouping {
+    pub field_name: String,
+    /// Maximum number of groups returned.
+    pub max_groups: usize,
+    /// Maximum number of hits returned per group.
+    pub max_hits_per_group: usize,
+}
+
+impl HitGrouping {
+    pub fn from_search_request(search_request: &SearchRequest) -> Option<Self> {
+        let group_by = search_request.group_by.as_ref()?;
+        Some(HitGrouping {
+            field_name: group_by.field.clone(),
+            max_groups: group_by.max_groups as usize,
+            max_hits_per_group: group_by.max_hits_per_group as usize,
+        })
+    }
+}
+
+/// Top-K computer retaining the best `max_hits_per_group` entries of every group.
+///
+/// Groups are ranked by their best entry. Unlike the hits, groups cannot be discarded before all
+/// the entries are known, so every group is retained until `finalize` is called.
+#[derive(Clone)]
+pub(crate) struct GroupTopK<T, K, O: Ord, S> {
+    max_groups: usize,
+    max_hits_per_group: usize,
+    sort_key_mapper: S,
+    groups: HashMap<K, TopK<T, O, S>>,
+}
+
+impl<T, K, O, S> GroupTopK<T, K, O, S>
+where
+    K: Eq + Hash,
+    O: Ord,
+    S: SortKeyMapper<T, Key = O> + Clone,
+{
+    pub fn new(max_groups: usize, max_hits_per_group: usize, sort_key_mapper: S) -> Self {
+        GroupTopK {
+            max_groups,
+            max_hits_per_group,
+            sort_key_mapper,
+            groups: HashMap::new(),
+        }
+    }
+
+    pub fn add_entry(&mut self, group_key: K, entry: T) {
+        if self.max_groups == 0 || self.max_hits_per_group == 0 {
+            return;
+        }
+        let max_hits_per_group = self.max_hits_per_group;
+        let sort_key_mapper = &self.sort_key_mapper;
+        self.groups
+            .entry(group_key)
+            .or_insert_with(|| TopK::new(max_hits_per_group, sort_key_mapper.clone()))
+            .add_entry(entry);
+    }
+
+    /// Returns the best `max_groups` groups, sorted from best to worst, with their entries, also
+    /// sorted from best to worst.
+    pub fn finalize(self) -> Vec<(K, Vec<T>)> {
+        let sort_key_mapper = self.sort_key_mapper;
+        let groups = self
+            .groups
+            .into_iter()
+            .map(|(group_key, top_k_entries)| (group_key, top_k_entries.finalize()));
+        // Groups hold at least one entry.
+        top_k(groups, self.max_groups, |(_, entries)| {
+            sort_key_mapper.get_sort_key(&entries[0])
+        })
+    }
+}
+
+/// Number of hits per group, summed over several responses.
+#[derive(Clone, Default)]
+pub(crate) struct GroupCounts {
+    num_hits_per_group: HashMap<String, u64>,
+}
+
+impl GroupCounts {
+    pub fn add(&mut self, group_counts: impl IntoIterator<Item = GroupCount>) {
+        for group_count in group_counts {
+            *self.num_hits_per_group.entry(group_count.key).or_default() += group_count.num_hits;
+        }
+    }
+
+    /// Returns the number of hits of every group, sorted by group key.
+    pub fn finalize(self) -> Vec<GroupCount> {
+        let mut group_counts: Vec<GroupCount> = self
+            .num_hits_per_group
+            .into_iter()
+            .map(|(key, num_hits)| GroupCount { key, num_hits })
+            .collect();
+        group_counts.sort_unstable_by(|left, right| left.key.cmp(&right.key));
+        group_counts
+    }
+}
+
+/// Builds the groups of the response from hits sorted by group, as returned by the collectors.
+pub(crate) fn build_hit_groups(hits: Vec<Hit>, group_counts: &[GroupCount]) -> Vec<HitGroup> {
+    let num_hits_per_group: HashMap<&str, u64> = group_counts
+        .iter()
+        .map(|group_count| (group_count.key.as_str(), group_count.num_hits))
+        .collect();
+    let mut hit_groups: Vec<HitGroup> = Vec::new();
+
+    for hit in hits {
+        let group_key = hit
+            .partial_hit
+            .as_ref()
+            .and_then(|partial_hit| partial_hit.dedup_key.clone())
+            .unwrap_or_default();
+        match hit_groups.last_mut() {
+            Some(hit_group) if hit_group.key == group_key => hit_group.hits.push(hit),
+            _ => {
+                let num_hits = num_hits_per_group
+                    .get(group_key.as_str())
+                    .copied()
+                    .unwrap_or_default();
+                hit_groups.push(HitGroup {
+                    key: group_key,
+                    num_hits,
+                    hits: vec![hit],
+                });
+            }
+        }
+    }
+    hit_groups
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_common::binary_heap::SortKeyMapper;
+    use quickwit_proto::search::{GroupCount, Hit, PartialHit};
+
+    use super::{build_hit_groups, GroupCounts, GroupTopK};
+
+    #[derive(Clone)]
+    struct IdentityMapper;
+
+    impl SortKeyMapper<u64> for IdentityMapper {
+        type Key = u64;
+
+        fn get_sort_key(&self, value: &u64) -> u64 {
+            *value
+        }
+    }
+
+    #[test]
+    fn test_group_top_k() {
+        let entries = [
+            ("a", 1),
+            ("b", 7),
+            ("a", 9),
+            ("c", 3),
+            ("a", 5),
+            ("b", 2),
+            ("c", 4),
+        ];
+        let group_top_k = |max_groups: usize, max_hits_per_group: usize| {
+            let mut group_top_k = GroupTopK::new(max_groups, max_hits_per_group, IdentityMapper);
+            for (group_key, entry) in entries {
+                group_top_k.add_entry(group_key, entry);
+            }
+            group_top_k.finalize()
+        };
+        assert_eq!(
+            group_top_k(10, 1),
+            vec![("a", vec![9]), ("b", vec![7]), ("c", vec![4])]
+        );
+        assert_eq!(
+            group_top_k(2, 2),
+            vec![("a", vec![9, 5]), ("b", vec![7, 2])]
+        );
+        assert_eq!(group_top_k(0, 2), Vec::new());
+        assert_eq!(group_top_k(2, 0), Vec::new());
+    }
+
+    #[test]
+    fn test_group_counts() {
+        let group_count = |key: &str, num_hits: u64| GroupCount {
+            key: key.to_string(),
+            num_hits,
+        };
+        let mut group_counts = GroupCounts::default();
+        group_counts.add([group_count("b", 1), group_count("a", 2)]);
+        group_counts.add([group_count("a", 3)]);
+        assert_eq!(
+            group_counts.finalize(),
+            vec![group_count("a", 5), group_count("b", 1)]
+        );
+    }
+
+    #[test]
+    fn test_build_hit_groups() {
+        let hit = |doc_id: u32, group_key: &str| Hit {
+            json: format!("{{\"doc\": {doc_id}}}"),
+            partial_hit: Some(PartialHit {
+                doc_id,
+                dedup_key: Some(group_key.to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let group_counts = vec![
+            GroupCount {
+                key: "a".to_string(),
+                num_hits: 12,
+            },
+            GroupCount {
+                key: "b".to_string(),
+                num_hits: 3,
+            },
+        ];
+        let hit_groups =
+            build_hit_groups(vec![hit(1, "b"), hit(2, "b"), hit(3, "a")], &group_counts);
+        assert_eq!(hit_groups.len(), 2);
+        assert_eq!(hit_groups[0].key, "b");
+        assert_eq!(hit_groups[0].num_hits, 3);
+        assert_eq!(hit_groups[0].hits, vec![hit(1, "b"), hit(2, "b")]);
+        assert_eq!(hit_groups[1].key, "a");
+        assert_eq!(hit_groups[1].num_hits, 12);
+        assert_eq!(hit_groups[1].hits, vec![hit(3, "a")]);
+    }
+}
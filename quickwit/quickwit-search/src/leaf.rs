@@ -41,9 +41,12 @@ use tantivy::directory::FileSlice;
 use tantivy::fastfield::FastFieldReaders;
 use tantivy::schema::{Field, FieldType};
 use tantivy::{Index, ReloadPolicy, Searcher, Term};
+use tokio::sync::watch;
 use tracing::*;
 
-use crate::collector::{make_collector_for_split, make_merge_collector, IncrementalCollector};
+use crate::collector::{
+    make_collector_for_split, make_merge_collector, search_sorted_split, IncrementalCollector,
+};
 use crate::service::SearcherContext;
 use crate::SearchError;
 
@@ -343,6 +346,7 @@ async fn leaf_search_single_split(
         doc_mapper.as_ref(),
         &search_request,
         searcher_context.get_aggregation_limits(),
+        searcher_context.get_aggregation_spill_config(),
     )?;
     let query_ast: QueryAst = serde_json::from_str(search_request.query_ast.as_str())
         .map_err(|err| SearchError::InvalidQuery(err.to_string()))?;
@@ -357,11 +361,26 @@ async fn leaf_search_single_split(
     warmup_info.merge(collector_warmup_info);
     warmup_info.simplify();
 
+    // If the documents of the split are sorted the way the hits are, the leaf can stop scanning
+    // a segment as soon as it has collected enough hits. The number of hits is then a lower
+    // bound, which is only acceptable if the request does not count all of them.
+    let split_sort_order_opt = split.timestamp_sort_order.and_then(SortOrder::from_i32);
+    let terminate_early = search_request.count_hits() != CountHits::CountAll
+        && split_sort_order_opt
+            .zip(doc_mapper.timestamp_field_name())
+            .map_or(false, |(split_sort_order, timestamp_field_name)| {
+                quickwit_collector.can_terminate_early(timestamp_field_name, split_sort_order)
+            });
+
     warmup(&searcher, &warmup_info).await?;
-    let span = info_span!("tantivy_search");
+    let span = info_span!("tantivy_search", terminate_early);
     let leaf_search_response = crate::run_cpu_intensive(move || {
         let _span_guard = span.enter();
-        searcher.search(&query, &quickwit_collector)
+        if terminate_early {
+            search_sorted_split(&searcher, &query, &quickwit_collector)
+        } else {
+            searcher.search(&query, &quickwit_collector)
+        }
     })
     .await
     .map_err(|_| {
@@ -380,7 +399,7 @@ async fn leaf_search_single_split(
 /// This include things such as sorting result by a field or _score when no document is requested,
 /// or applying date range when the range covers the entire split.
 fn rewrite_request(search_request: &mut SearchRequest, split: &SplitIdAndFooterOffsets) {
-    if search_request.max_hits == 0 {
+    if search_request.max_hits == 0 && search_request.group_by.is_none() {
         search_request.sort_fields = vec![];
     }
     rewrite_start_end_time_bounds(
@@ -498,6 +517,34 @@ impl CanSplitDoBetter {
         }
     }
 
+    /// Returns whether the given split can possibly hold documents sorted after the `search_after`
+    /// hit of the request. Splits whose time range lies entirely before the hit can be skipped
+    /// when the documents are sorted by timestamp.
+    fn can_have_hits_after(
+        &self,
+        split: &SplitIdAndFooterOffsets,
+        search_after_opt: Option<&PartialHit>,
+    ) -> bool {
+        let Some(SortValue::I64(timestamp_ns)) =
+            search_after_opt.and_then(|search_after| search_after.sort_value())
+        else {
+            return true;
+        };
+        match self {
+            CanSplitDoBetter::SplitTimestampHigher(_) => split
+                .timestamp_start
+                .map(|timestamp_start| {
+                    timestamp_start <= quickwit_common::div_ceil(timestamp_ns, 1_000_000_000)
+                })
+                .unwrap_or(true),
+            CanSplitDoBetter::SplitTimestampLower(_) => split
+                .timestamp_end
+                .map(|timestamp_end| timestamp_end >= timestamp_ns / 1_000_000_000)
+                .unwrap_or(true),
+            _ => true,
+        }
+    }
+
     /// Record the new worst-of-the-top document, that is, the document which would first be
     /// evicted from the list of best documents, if a better document was found. Only call this
     /// funciton if you have at least max_hits documents already.
@@ -548,18 +595,31 @@ pub async fn leaf_search(
 
     // if client wants full count, or we are doing an aggregation, we want to run every splits.
     // However if the aggregation is the tracing aggregation, we don't actually need all splits.
+    // Group counts are exact, so grouping also requires every split.
     let run_all_splits = request.count_hits() == CountHits::CountAll
+        || request.group_by.is_some()
         || (request.aggregation_request.is_some()
             && !matches!(split_filter, CanSplitDoBetter::FindTraceIdsAggregation(_)));
 
     // Creates a collector which merges responses into one
-    let merge_collector =
-        make_merge_collector(&request, &searcher_context.get_aggregation_limits())?;
+    let merge_collector = make_merge_collector(
+        &request,
+        &searcher_context.get_aggregation_limits(),
+        &searcher_context.get_aggregation_spill_config(),
+    )?;
     let incremental_merge_collector = IncrementalCollector::new(merge_collector);
 
     let split_filter = Arc::new(Mutex::new(split_filter));
     let incremental_merge_collector = Arc::new(Mutex::new(incremental_merge_collector));
 
+    // Notifies the running split searches that the worst hit of the top K changed, so that they
+    // can be cancelled once they can no longer produce better hits.
+    let worst_hit_tx_opt = if run_all_splits {
+        None
+    } else {
+        Some(Arc::new(watch::channel(()).0))
+    };
+
     let mut leaf_search_single_split_futures: Vec<_> = Vec::with_capacity(splits.len());
 
     for split in splits {
@@ -571,8 +631,17 @@ pub async fn leaf_search(
 
         let mut request = (*request).clone();
 
-        if !split_filter.lock().unwrap().can_be_better(&split) {
+        let can_be_better = {
+            let split_filter_guard = split_filter.lock().unwrap();
+            split_filter_guard.can_be_better(&split)
+                && split_filter_guard.can_have_hits_after(&split, request.search_after.as_ref())
+        };
+        if !can_be_better {
             if !run_all_splits {
+                incremental_merge_collector
+                    .lock()
+                    .unwrap()
+                    .add_skipped_split(split.split_id);
                 continue;
             }
             request.max_hits = 0;
@@ -589,14 +658,13 @@ pub async fn leaf_search(
                 split,
                 split_filter.clone(),
                 incremental_merge_collector.clone(),
+                worst_hit_tx_opt.clone(),
                 leaf_split_search_permit,
             )
             .in_current_span(),
         ));
     }
 
-    // TODO we could cancel running splits when !run_all_splits and the running split can no longer
-    // give better results after some other split answered.
     let split_search_results: Vec<Result<(), _>> =
         futures::future::join_all(leaf_search_single_split_futures).await;
 
@@ -634,24 +702,45 @@ async fn leaf_search_single_split_wrapper(
     split: SplitIdAndFooterOffsets,
     split_filter: Arc<Mutex<CanSplitDoBetter>>,
     incremental_merge_collector: Arc<Mutex<IncrementalCollector>>,
+    worst_hit_tx_opt: Option<Arc<watch::Sender<()>>>,
     leaf_split_search_permit: tokio::sync::OwnedSemaphorePermit,
 ) {
     crate::SEARCH_METRICS.leaf_searches_splits_total.inc();
     let timer = crate::SEARCH_METRICS
         .leaf_search_split_duration_secs
         .start_timer();
-    let leaf_search_single_split_res = leaf_search_single_split(
+    let leaf_search_single_split_fut = leaf_search_single_split(
         &searcher_context,
         request,
         index_storage,
         split.clone(),
         doc_mapper,
-    )
-    .await;
+    );
+    let leaf_search_single_split_res_opt = if let Some(worst_hit_tx) = &worst_hit_tx_opt {
+        let cannot_be_better_fut =
+            wait_until_split_cannot_be_better(&split_filter, &split, worst_hit_tx.subscribe());
+        tokio::select! {
+            leaf_search_single_split_res = leaf_search_single_split_fut => {
+                Some(leaf_search_single_split_res)
+            }
+            _ = cannot_be_better_fut => None,
+        }
+    } else {
+        Some(leaf_search_single_split_fut.await)
+    };
 
     // We explicitly drop it, to highlight it to the reader
     std::mem::drop(leaf_split_search_permit);
 
+    let Some(leaf_search_single_split_res) = leaf_search_single_split_res_opt else {
+        debug!(split_id = split.split_id, "cancelled split search");
+        incremental_merge_collector
+            .lock()
+            .unwrap()
+            .add_skipped_split(split.split_id);
+        return;
+    };
+
     if leaf_search_single_split_res.is_ok() {
         timer.observe_duration();
     }
@@ -678,6 +767,28 @@ async fn leaf_search_single_split_wrapper(
             .lock()
             .unwrap()
             .record_new_worst_hit(last_hit.as_ref());
+
+        if let Some(worst_hit_tx) = &worst_hit_tx_opt {
+            worst_hit_tx.send_replace(());
+        }
+    }
+}
+
+/// Resolves once the split can no longer produce hits better than the ones already collected,
+/// which is re-evaluated every time the worst hit of the top K changes.
+async fn wait_until_split_cannot_be_better(
+    split_filter: &Mutex<CanSplitDoBetter>,
+    split: &SplitIdAndFooterOffsets,
+    mut worst_hit_rx: watch::Receiver<()>,
+) {
+    loop {
+        if !split_filter.lock().unwrap().can_be_better(split) {
+            return;
+        }
+        if worst_hit_rx.changed().await.is_err() {
+            // The leaf search is over, there is nothing left to wait for.
+            return futures::future::pending().await;
+        }
     }
 }
 
@@ -752,27 +863,77 @@ async fn leaf_list_terms_single_split(
         let mut stream = range
             .into_stream()
             .with_context(|| "failed to create stream over sstable")?;
-        let mut segment_result: Vec<Vec<u8>> =
+        let mut segment_result: Vec<(Vec<u8>, u64)> =
             Vec::with_capacity(search_request.max_hits.unwrap_or(0) as usize);
         while stream.advance() {
-            segment_result.push(term_to_data(field, field_type, stream.key()));
+            let term = term_to_data(field, field_type, stream.key());
+            segment_result.push((term, stream.value().doc_freq as u64));
         }
         segment_results.push(segment_result);
     }
+    let merged_term_doc_counts = merge_term_doc_counts(segment_results, search_request.max_hits);
+    Ok(make_leaf_list_terms_response(
+        merged_term_doc_counts,
+        search_request.include_doc_counts,
+        1,
+        Vec::new(),
+    ))
+}
 
-    let merged_iter = segment_results.into_iter().kmerge().dedup();
-    let merged_results: Vec<Vec<u8>> = if let Some(limit) = search_request.max_hits {
+/// Merges lists of terms sorted in ascending order along with their document counts, summing
+/// the counts of the terms present in several lists, and keeps the first `max_hits` terms.
+///
+/// The counts of the kept terms are exact as long as each list contains the first `max_hits`
+/// terms of its source: a term among the first `max_hits` merged terms is necessarily among the
+/// first `max_hits` terms of every list it belongs to.
+pub(crate) fn merge_term_doc_counts(
+    term_doc_counts_lists: Vec<Vec<(Vec<u8>, u64)>>,
+    max_hits: Option<u64>,
+) -> Vec<(Vec<u8>, u64)> {
+    let merged_iter = term_doc_counts_lists
+        .into_iter()
+        .kmerge_by(|(left_term, _), (right_term, _)| left_term < right_term)
+        .coalesce(|(left_term, left_count), (right_term, right_count)| {
+            if left_term == right_term {
+                Ok((left_term, left_count + right_count))
+            } else {
+                Err(((left_term, left_count), (right_term, right_count)))
+            }
+        });
+    if let Some(limit) = max_hits {
         merged_iter.take(limit as usize).collect()
     } else {
         merged_iter.collect()
-    };
+    }
+}
 
-    Ok(LeafListTermsResponse {
-        num_hits: merged_results.len() as u64,
-        terms: merged_results,
-        num_attempted_splits: 1,
-        failed_splits: Vec::new(),
-    })
+/// Pairs the terms of a list terms response with their document counts. The counts default to 0
+/// if the response does not carry any.
+pub(crate) fn term_doc_counts(terms: Vec<Vec<u8>>, doc_counts: Vec<u64>) -> Vec<(Vec<u8>, u64)> {
+    if doc_counts.len() == terms.len() {
+        terms.into_iter().zip(doc_counts).collect()
+    } else {
+        terms.into_iter().map(|term| (term, 0)).collect()
+    }
+}
+
+fn make_leaf_list_terms_response(
+    term_doc_counts: Vec<(Vec<u8>, u64)>,
+    include_doc_counts: bool,
+    num_attempted_splits: u64,
+    failed_splits: Vec<SplitSearchError>,
+) -> LeafListTermsResponse {
+    let (terms, mut doc_counts): (Vec<Vec<u8>>, Vec<u64>) = term_doc_counts.into_iter().unzip();
+    if !include_doc_counts {
+        doc_counts.clear();
+    }
+    LeafListTermsResponse {
+        num_hits: terms.len() as u64,
+        terms,
+        num_attempted_splits,
+        failed_splits,
+        doc_counts,
+    }
 }
 
 fn term_from_data(field: Field, field_type: &FieldType, data: &[u8]) -> Term {
@@ -836,16 +997,13 @@ pub async fn leaf_list_terms(
                 Err(err) => Either::Right(err),
             });
 
-    let merged_iter = split_search_responses
+    let term_doc_counts_lists: Vec<Vec<(Vec<u8>, u64)>> = split_search_responses
         .into_iter()
-        .map(|leaf_search_response| leaf_search_response.terms)
-        .kmerge()
-        .dedup();
-    let terms: Vec<Vec<u8>> = if let Some(limit) = request.max_hits {
-        merged_iter.take(limit as usize).collect()
-    } else {
-        merged_iter.collect()
-    };
+        .map(|leaf_search_response| {
+            term_doc_counts(leaf_search_response.terms, leaf_search_response.doc_counts)
+        })
+        .collect();
+    let merged_term_doc_counts = merge_term_doc_counts(term_doc_counts_lists, request.max_hits);
 
     let failed_splits = errors
         .into_iter()
@@ -855,12 +1013,104 @@ pub async fn leaf_list_terms(
             retryable_error: true,
         })
         .collect();
-    let merged_search_response = LeafListTermsResponse {
-        num_hits: terms.len() as u64,
-        terms,
-        num_attempted_splits: splits.len() as u64,
+    let merged_search_response = make_leaf_list_terms_response(
+        merged_term_doc_counts,
+        request.include_doc_counts,
+        splits.len() as u64,
         failed_splits,
-    };
-
+    );
     Ok(merged_search_response)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use quickwit_proto::search::SortByValue;
+
+    use super::*;
+
+    fn split_for_test(split_id: &str) -> SplitIdAndFooterOffsets {
+        SplitIdAndFooterOffsets {
+            split_id: split_id.to_string(),
+            split_footer_start: 0,
+            split_footer_end: 100,
+            timestamp_start: None,
+            timestamp_end: None,
+            timestamp_sort_order: None,
+        }
+    }
+
+    #[test]
+    fn test_can_split_have_hits_after() {
+        let split = SplitIdAndFooterOffsets {
+            timestamp_start: Some(100),
+            timestamp_end: Some(200),
+            ..split_for_test("split-a")
+        };
+        let search_after = |timestamp_secs: i64| PartialHit {
+            sort_value: Some(SortByValue {
+                sort_value: Some(SortValue::I64(timestamp_secs * 1_000_000_000)),
+            }),
+            ..Default::default()
+        };
+        let split_filter = CanSplitDoBetter::SplitTimestampHigher(None);
+        assert!(split_filter.can_have_hits_after(&split, None));
+        assert!(split_filter.can_have_hits_after(&split, Some(&search_after(150))));
+        assert!(split_filter.can_have_hits_after(&split, Some(&search_after(100))));
+        assert!(!split_filter.can_have_hits_after(&split, Some(&search_after(99))));
+
+        let split_filter = CanSplitDoBetter::SplitTimestampLower(None);
+        assert!(split_filter.can_have_hits_after(&split, Some(&search_after(150))));
+        assert!(split_filter.can_have_hits_after(&split, Some(&search_after(200))));
+        assert!(!split_filter.can_have_hits_after(&split, Some(&search_after(201))));
+
+        let split_without_timestamps = split_for_test("split-b");
+        assert!(
+            split_filter.can_have_hits_after(&split_without_timestamps, Some(&search_after(201)))
+        );
+
+        let split_filter = CanSplitDoBetter::Uninformative;
+        assert!(split_filter.can_have_hits_after(&split, Some(&search_after(99))));
+    }
+
+    #[tokio::test]
+    async fn test_wait_until_split_cannot_be_better() {
+        let split_filter = Arc::new(Mutex::new(CanSplitDoBetter::SplitIdHigher(None)));
+        let (worst_hit_tx, _worst_hit_rx) = watch::channel(());
+
+        let split_filter_clone = split_filter.clone();
+        let worst_hit_rx = worst_hit_tx.subscribe();
+        let lower_split_handle = tokio::spawn(async move {
+            let split = split_for_test("split-a");
+            wait_until_split_cannot_be_better(&split_filter_clone, &split, worst_hit_rx).await
+        });
+        let split_filter_clone = split_filter.clone();
+        let worst_hit_rx = worst_hit_tx.subscribe();
+        let higher_split_handle = tokio::spawn(async move {
+            let split = split_for_test("split-c");
+            wait_until_split_cannot_be_better(&split_filter_clone, &split, worst_hit_rx).await
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!lower_split_handle.is_finished());
+        assert!(!higher_split_handle.is_finished());
+
+        let worst_hit = PartialHit {
+            split_id: "split-b".to_string(),
+            ..Default::default()
+        };
+        split_filter
+            .lock()
+            .unwrap()
+            .record_new_worst_hit(&worst_hit);
+        worst_hit_tx.send_replace(());
+
+        tokio::time::timeout(Duration::from_secs(1), lower_split_handle)
+            .await
+            .unwrap()
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!higher_split_handle.is_finished());
+        higher_split_handle.abort();
+    }
+}
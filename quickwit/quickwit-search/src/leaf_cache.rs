@@ -21,7 +21,7 @@ use std::ops::Bound;
 
 use prost::Message;
 use quickwit_proto::search::{
-    CountHits, LeafSearchResponse, SearchRequest, SplitIdAndFooterOffsets,
+    AggregationFormat, CountHits, LeafSearchResponse, SearchRequest, SplitIdAndFooterOffsets,
 };
 use quickwit_storage::{MemorySizedCache, OwnedBytes};
 
@@ -104,6 +104,9 @@ impl CacheKey {
         // it doesn't matter whether or not we count all hits at the scale of a
         // single split: either we did process it and got everything, or we didn't.
         search_request.count_hits = CountHits::CountAll.into();
+        // leaves always return intermediate aggregation results, the format only matters at
+        // the root.
+        search_request.aggregation_format = AggregationFormat::Json.into();
 
         CacheKey {
             split_id: split_info.split_id,
@@ -206,6 +209,7 @@ mod tests {
             split_footer_end: 100,
             timestamp_start: None,
             timestamp_end: None,
+            timestamp_sort_order: None,
         };
 
         let split_2 = SplitIdAndFooterOffsets {
@@ -214,6 +218,7 @@ mod tests {
             split_footer_end: 100,
             timestamp_start: None,
             timestamp_end: None,
+            timestamp_sort_order: None,
         };
 
         let query_1 = SearchRequest {
@@ -247,7 +252,10 @@ mod tests {
                 sort_value: Some(SortValue::U64(0u64).into()),
                 sort_value2: None,
                 split_id: "split_1".to_string(),
+                dedup_key: None,
             }],
+            skipped_split_ids: Vec::new(),
+            group_counts: Vec::new(),
         };
 
         assert!(cache.get(split_1.clone(), query_1.clone()).is_none());
@@ -268,6 +276,7 @@ mod tests {
             split_footer_end: 100,
             timestamp_start: Some(100),
             timestamp_end: Some(199),
+            timestamp_sort_order: None,
         };
         let split_2 = SplitIdAndFooterOffsets {
             split_id: "split_2".to_string(),
@@ -275,6 +284,7 @@ mod tests {
             split_footer_end: 100,
             timestamp_start: Some(150),
             timestamp_end: Some(249),
+            timestamp_sort_order: None,
         };
         let split_3 = SplitIdAndFooterOffsets {
             split_id: "split_3".to_string(),
@@ -282,6 +292,7 @@ mod tests {
             split_footer_end: 100,
             timestamp_start: Some(150),
             timestamp_end: Some(249),
+            timestamp_sort_order: None,
         };
 
         let query_1 = SearchRequest {
@@ -333,7 +344,10 @@ mod tests {
                 sort_value: Some(SortValue::U64(0).into()),
                 sort_value2: None,
                 split_id: "split_1".to_string(),
+                dedup_key: None,
             }],
+            skipped_split_ids: Vec::new(),
+            group_counts: Vec::new(),
         };
 
         // for split_1, 1 and 1bis cover different timestamp ranges
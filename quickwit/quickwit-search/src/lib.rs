@@ -22,22 +22,36 @@
 #![allow(clippy::bool_assert_comparison)]
 #![deny(clippy::disallowed_methods)]
 
+mod aggregation_arrow;
+mod aggregation_rewrite;
+mod aggregation_spill;
 mod client;
 mod cluster_client;
 mod collector;
 mod error;
 mod fetch_docs;
+mod field_aliases;
+mod field_stats_pruning;
 mod filters;
 mod find_trace_ids_collector;
+mod hit_dedup;
+mod hit_federation;
+mod hit_grouping;
 mod leaf;
 mod leaf_cache;
+mod list_fields;
 mod retry;
 mod root;
 mod scroll_context;
+mod search_cost;
+mod search_guardrails;
 mod search_job_placer;
 mod search_response_rest;
 mod search_stream;
 mod service;
+mod sort_expression;
+mod term_frequency_collector;
+mod terms_lookup;
 mod thread_pool;
 
 mod metrics;
@@ -78,13 +92,24 @@ pub use crate::cluster_client::ClusterClient;
 pub use crate::error::{parse_grpc_error, SearchError};
 use crate::fetch_docs::fetch_docs;
 use crate::leaf::{leaf_list_terms, leaf_search};
+pub use crate::list_fields::{
+    ListFieldType, ListFieldsEntry, ListFieldsRequest, ListFieldsResponse,
+};
 pub use crate::root::{
-    jobs_to_leaf_requests, root_list_terms, root_search, IndexMetasForLeafSearch, SearchJob,
+    jobs_to_leaf_requests, root_count, root_estimate_search_cost, root_list_fields,
+    root_list_terms, root_search, IndexMetasForLeafSearch, SearchJob,
 };
+pub use crate::search_cost::{LatencyBand, SearchCostEstimate};
 pub use crate::search_job_placer::{Job, SearchJobPlacer};
-pub use crate::search_response_rest::SearchResponseRest;
+pub use crate::search_response_rest::{
+    CountResponseRest, HitGroupRest, ListTermsResponseRest, SearchResponseRest, TermRest,
+};
 pub use crate::search_stream::root_search_stream;
 pub use crate::service::{MockSearchService, SearchService, SearchServiceImpl};
+pub use crate::term_frequency_collector::{
+    RareTermsAggregation, SignificantTermsAggregation, TermFrequencyAggregation,
+    TermFrequencyAggregations,
+};
 use crate::thread_pool::run_cpu_intensive;
 
 /// A pool of searcher clients identified by their gRPC socket address.
@@ -163,6 +188,9 @@ fn extract_split_and_footer_offsets(split_metadata: &SplitMetadata) -> SplitIdAn
             .time_range
             .as_ref()
             .map(|time_range| *time_range.end()),
+        timestamp_sort_order: split_metadata
+            .sort_by
+            .map(|sort_by| sort_by.sort_order() as i32),
     }
 }
 
@@ -0,0 +1,276 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+
+use quickwit_common::shared_consts::FIELD_PRESENCE_FIELD_NAME;
+use quickwit_doc_mapper::{DYNAMIC_FIELD_NAME, SOURCE_FIELD_NAME};
+use serde::{Deserialize, Serialize};
+use tantivy::schema::{FieldType, Schema};
+
+/// Request listing the fields of the indexes matching some patterns.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ListFieldsRequest {
+    /// Patterns of the IDs of the indexes whose fields are listed.
+    pub index_id_patterns: Vec<String>,
+    /// Patterns, possibly containing the `*` wildcard, that the names of the listed fields must
+    /// match. All the fields are listed when empty.
+    pub field_patterns: Vec<String>,
+}
+
+/// Type of a listed field.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ListFieldType {
+    /// Text field tokenized for full-text search.
+    Text,
+    /// Text field indexed as a single term.
+    Keyword,
+    U64,
+    I64,
+    F64,
+    Bool,
+    Datetime,
+    Bytes,
+    Ip,
+    /// JSON field. Its subfields are not listed.
+    Json,
+}
+
+/// A field with the same name and type in one or several indexes.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ListFieldsEntry {
+    pub field_name: String,
+    pub field_type: ListFieldType,
+    /// Whether the field is indexed in all the indexes.
+    pub searchable: bool,
+    /// Whether the field is a fast field in all the indexes.
+    pub aggregatable: bool,
+    /// IDs of the indexes containing the field with this type.
+    pub index_ids: Vec<String>,
+    /// IDs of the indexes in which the field is not indexed.
+    pub non_searchable_index_ids: Vec<String>,
+    /// IDs of the indexes in which the field is not a fast field.
+    pub non_aggregatable_index_ids: Vec<String>,
+}
+
+/// Fields of the indexes matching the patterns of a [`ListFieldsRequest`].
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ListFieldsResponse {
+    /// IDs of the indexes matching the patterns.
+    pub index_ids: Vec<String>,
+    /// Fields sorted by name and type.
+    pub fields: Vec<ListFieldsEntry>,
+}
+
+/// Returns whether `field_name` matches one of the patterns. Empty patterns match all the fields.
+fn matches_any_field_pattern(field_patterns: &[String], field_name: &str) -> bool {
+    field_patterns.is_empty()
+        || field_patterns
+            .iter()
+            .any(|field_pattern| matches_field_pattern(field_pattern, field_name))
+}
+
+fn matches_field_pattern(field_pattern: &str, field_name: &str) -> bool {
+    let mut parts = field_pattern.split('*');
+    // `split` always yields at least one part.
+    let prefix = parts.next().unwrap_or_default();
+
+    let Some(mut remaining) = field_name.strip_prefix(prefix) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = parts.collect();
+
+    let Some(suffix) = parts.pop() else {
+        // The pattern does not contain any wildcard.
+        return remaining.is_empty();
+    };
+    for part in parts {
+        let Some(position) = remaining.find(part) else {
+            return false;
+        };
+        remaining = &remaining[position + part.len()..];
+    }
+    remaining.ends_with(suffix)
+}
+
+fn list_field_type(field_type: &FieldType) -> Option<ListFieldType> {
+    let list_field_type = match field_type {
+        FieldType::Str(text_options) => {
+            let is_raw = text_options
+                .get_indexing_options()
+                .map(|indexing_options| indexing_options.tokenizer() == "raw")
+                // Text fields that are not indexed can only be aggregated on.
+                .unwrap_or(true);
+            if is_raw {
+                ListFieldType::Keyword
+            } else {
+                ListFieldType::Text
+            }
+        }
+        FieldType::U64(_) => ListFieldType::U64,
+        FieldType::I64(_) => ListFieldType::I64,
+        FieldType::F64(_) => ListFieldType::F64,
+        FieldType::Bool(_) => ListFieldType::Bool,
+        FieldType::Date(_) => ListFieldType::Datetime,
+        FieldType::Bytes(_) => ListFieldType::Bytes,
+        FieldType::IpAddr(_) => ListFieldType::Ip,
+        FieldType::JsonObject(_) => ListFieldType::Json,
+        FieldType::Facet(_) => return None,
+    };
+    Some(list_field_type)
+}
+
+/// Merges the fields of several indexes, described by their schemas, into a list of fields
+/// grouped by name and type.
+pub(crate) fn list_fields<'a>(
+    index_schemas: impl IntoIterator<Item = (&'a str, &'a Schema)>,
+    field_patterns: &[String],
+) -> ListFieldsResponse {
+    let mut index_ids = Vec::new();
+    let mut fields: BTreeMap<(String, ListFieldType), ListFieldsEntry> = BTreeMap::new();
+
+    for (index_id, schema) in index_schemas {
+        index_ids.push(index_id.to_string());
+
+        for (_field, field_entry) in schema.fields() {
+            let field_name = field_entry.name();
+
+            if [
+                SOURCE_FIELD_NAME,
+                DYNAMIC_FIELD_NAME,
+                FIELD_PRESENCE_FIELD_NAME,
+            ]
+            .contains(&field_name)
+                || !matches_any_field_pattern(field_patterns, field_name)
+            {
+                continue;
+            }
+            let Some(field_type) = list_field_type(field_entry.field_type()) else {
+                continue;
+            };
+            let entry = fields
+                .entry((field_name.to_string(), field_type))
+                .or_insert_with(|| ListFieldsEntry {
+                    field_name: field_name.to_string(),
+                    field_type,
+                    searchable: true,
+                    aggregatable: true,
+                    index_ids: Vec::new(),
+                    non_searchable_index_ids: Vec::new(),
+                    non_aggregatable_index_ids: Vec::new(),
+                });
+            entry.index_ids.push(index_id.to_string());
+
+            if !field_entry.is_indexed() {
+                entry.searchable = false;
+                entry.non_searchable_index_ids.push(index_id.to_string());
+            }
+            if !field_entry.is_fast() {
+                entry.aggregatable = false;
+                entry.non_aggregatable_index_ids.push(index_id.to_string());
+            }
+        }
+    }
+    ListFieldsResponse {
+        index_ids,
+        fields: fields.into_values().collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tantivy::schema::{SchemaBuilder, TextFieldIndexing, TextOptions, FAST, INDEXED, STRING};
+
+    use super::*;
+
+    #[test]
+    fn test_matches_field_pattern() {
+        assert!(matches_field_pattern("*", "host"));
+        assert!(matches_field_pattern("host", "host"));
+        assert!(!matches_field_pattern("host", "hostname"));
+        assert!(matches_field_pattern("host*", "hostname"));
+        assert!(matches_field_pattern("*name", "hostname"));
+        assert!(matches_field_pattern("h*t*e", "hostname"));
+        assert!(!matches_field_pattern("h*x*e", "hostname"));
+        assert!(matches_field_pattern("resource.*", "resource.service"));
+        assert!(!matches_field_pattern("resource.*", "attributes.service"));
+
+        assert!(matches_any_field_pattern(&[], "host"));
+        assert!(matches_any_field_pattern(
+            &["status".to_string(), "host*".to_string()],
+            "hostname"
+        ));
+    }
+
+    #[test]
+    fn test_list_fields() {
+        let mut schema_builder = SchemaBuilder::new();
+        schema_builder.add_text_field(SOURCE_FIELD_NAME, STRING);
+        schema_builder.add_text_field("host", STRING | FAST);
+        let text_options = TextOptions::default()
+            .set_indexing_options(TextFieldIndexing::default().set_tokenizer("default"));
+        schema_builder.add_text_field("message", text_options);
+        schema_builder.add_u64_field("status", INDEXED | FAST);
+        let schema_0 = schema_builder.build();
+
+        let mut schema_builder = SchemaBuilder::new();
+        schema_builder.add_text_field("host", STRING);
+        schema_builder.add_i64_field("status", INDEXED | FAST);
+        let schema_1 = schema_builder.build();
+
+        let list_fields_response = list_fields(
+            [("index-0", &schema_0), ("index-1", &schema_1)],
+            &Vec::new(),
+        );
+        assert_eq!(list_fields_response.index_ids, ["index-0", "index-1"]);
+        assert_eq!(list_fields_response.fields.len(), 4);
+
+        let host_entry = &list_fields_response.fields[0];
+        assert_eq!(host_entry.field_name, "host");
+        assert_eq!(host_entry.field_type, ListFieldType::Keyword);
+        assert!(host_entry.searchable);
+        assert!(!host_entry.aggregatable);
+        assert_eq!(host_entry.index_ids, ["index-0", "index-1"]);
+        assert!(host_entry.non_searchable_index_ids.is_empty());
+        assert_eq!(host_entry.non_aggregatable_index_ids, ["index-1"]);
+
+        let message_entry = &list_fields_response.fields[1];
+        assert_eq!(message_entry.field_name, "message");
+        assert_eq!(message_entry.field_type, ListFieldType::Text);
+        assert!(message_entry.searchable);
+        assert!(!message_entry.aggregatable);
+
+        let status_entry = &list_fields_response.fields[2];
+        assert_eq!(status_entry.field_name, "status");
+        assert_eq!(status_entry.field_type, ListFieldType::U64);
+        assert_eq!(status_entry.index_ids, ["index-0"]);
+
+        let status_entry = &list_fields_response.fields[3];
+        assert_eq!(status_entry.field_name, "status");
+        assert_eq!(status_entry.field_type, ListFieldType::I64);
+        assert_eq!(status_entry.index_ids, ["index-1"]);
+
+        let list_fields_response = list_fields(
+            [("index-0", &schema_0), ("index-1", &schema_1)],
+            &["st*".to_string()],
+        );
+        assert_eq!(list_fields_response.fields.len(), 2);
+    }
+}
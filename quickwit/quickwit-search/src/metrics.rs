@@ -21,13 +21,19 @@
 
 use once_cell::sync::Lazy;
 use quickwit_common::metrics::{
-    new_counter, new_gauge, new_histogram, Histogram, IntCounter, IntGauge,
+    index_label, new_counter, new_counter_vec, new_gauge, new_histogram, new_histogram_vec,
+    Histogram, HistogramVec, IntCounter, IntCounterVec, IntGauge,
 };
 
 pub struct SearchMetrics {
     pub leaf_searches_splits_total: IntCounter,
     pub leaf_search_split_duration_secs: Histogram,
     pub active_search_threads_count: IntGauge,
+    pub root_search_requests_per_index: IntCounterVec<1>,
+    pub root_search_request_duration_secs_per_index: HistogramVec<1>,
+    pub root_fetch_docs_phase_duration_secs: Histogram,
+    pub root_fetch_docs_request_duration_secs: Histogram,
+    pub root_fetch_docs_requests_total: IntCounter,
 }
 
 impl Default for SearchMetrics {
@@ -49,6 +55,64 @@ impl Default for SearchMetrics {
                 "Number of threads in use in the CPU thread pool",
                 "quickwit_search",
             ),
+            root_search_requests_per_index: new_counter_vec(
+                "root_search_requests_per_index",
+                "Number of search requests received by the root searcher per index.",
+                "quickwit_search",
+                ["index"],
+            ),
+            root_search_request_duration_secs_per_index: new_histogram_vec(
+                "root_search_request_duration_secs_per_index",
+                "Number of seconds required to run the successful search requests per index.",
+                "quickwit_search",
+                ["index"],
+            ),
+            root_fetch_docs_phase_duration_secs: new_histogram(
+                "root_fetch_docs_phase_duration_secs",
+                "Number of seconds required by the root to fetch the documents of the hits of a \
+                 search request.",
+                "quickwit_search",
+            ),
+            root_fetch_docs_request_duration_secs: new_histogram(
+                "root_fetch_docs_request_duration_secs",
+                "Number of seconds required to run a fetch docs request sent by the root to a \
+                 searcher, retries included.",
+                "quickwit_search",
+            ),
+            root_fetch_docs_requests_total: new_counter(
+                "root_fetch_docs_requests_total",
+                "Number of fetch docs requests sent by the root to searchers.",
+                "quickwit_search",
+            ),
+        }
+    }
+}
+
+impl SearchMetrics {
+    /// Records a search request targeting `index_ids`. The metrics labeled by index ID are only
+    /// updated if they are enabled.
+    pub fn record_root_search_request<'a>(&self, index_ids: impl IntoIterator<Item = &'a str>) {
+        for index_id in index_ids {
+            if let Some(index_label) = index_label(index_id) {
+                self.root_search_requests_per_index
+                    .with_label_values([index_label])
+                    .inc();
+            }
+        }
+    }
+
+    /// Records the duration of a successful search request targeting `index_ids`.
+    pub fn record_root_search_duration<'a>(
+        &self,
+        index_ids: impl IntoIterator<Item = &'a str>,
+        duration_secs: f64,
+    ) {
+        for index_id in index_ids {
+            if let Some(index_label) = index_label(index_id) {
+                self.root_search_request_duration_secs_per_index
+                    .with_label_values([index_label])
+                    .observe(duration_secs);
+            }
         }
     }
 }
@@ -17,18 +17,24 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::{HashMap, HashSet};
+use std::borrow::Cow;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::mem;
 use std::time::Duration;
 
 use anyhow::Context;
 use futures::future::try_join_all;
+use futures::{StreamExt, TryStreamExt};
 use itertools::Itertools;
 use quickwit_common::shared_consts::{DELETION_GRACE_PERIOD, SCROLL_BATCH_LEN};
 use quickwit_common::uri::Uri;
 use quickwit_common::PrettySample;
 use quickwit_config::{build_doc_mapper, IndexConfig};
-use quickwit_doc_mapper::tag_pruning::extract_tags_from_query;
-use quickwit_doc_mapper::{DocMapper, DYNAMIC_FIELD_NAME};
+use quickwit_doc_mapper::tag_pruning::{
+    and_tag_filters, extract_tags_from_query, tag_filter_from_split_tags, TagFilterAst,
+};
+use quickwit_doc_mapper::{DocMapper, DYNAMIC_FIELD_NAME, OTHER_PARTITION_ID};
 use quickwit_metastore::{
     IndexMetadata, IndexMetadataResponseExt, ListIndexesMetadataResponseExt, ListSplitsRequestExt,
     MetastoreServiceStreamSplitsExt, SplitMetadata,
@@ -38,10 +44,10 @@ use quickwit_proto::metastore::{
     MetastoreServiceClient,
 };
 use quickwit_proto::search::{
-    FetchDocsRequest, FetchDocsResponse, Hit, LeafHit, LeafListTermsRequest, LeafListTermsResponse,
-    LeafSearchRequest, LeafSearchResponse, ListTermsRequest, ListTermsResponse, PartialHit,
-    SearchRequest, SearchResponse, SnippetRequest, SortDatetimeFormat, SortField, SortValue,
-    SplitIdAndFooterOffsets,
+    AggregationFormat, CountHits, Federation, FetchDocsRequest, FetchDocsResponse, Hit, LeafHit,
+    LeafListTermsRequest, LeafListTermsResponse, LeafSearchRequest, LeafSearchResponse,
+    ListTermsRequest, ListTermsResponse, PartialHit, SearchRequest, SearchResponse, SnippetRequest,
+    SortDatetimeFormat, SortField, SortValue, SplitIdAndFooterOffsets,
 };
 use quickwit_proto::types::{IndexUid, SplitId};
 use quickwit_query::query_ast::{
@@ -55,12 +61,26 @@ use tantivy::schema::{FieldType, Schema};
 use tantivy::TantivyError;
 use tracing::{debug, error, info, info_span, instrument};
 
+use crate::aggregation_arrow::aggregation_results_to_arrow_ipc;
+use crate::aggregation_rewrite::{format_datetime_percentiles, rewrite_aggregation_request};
 use crate::cluster_client::ClusterClient;
 use crate::collector::{make_merge_collector, QuickwitAggregations};
+use crate::field_aliases::resolve_field_aliases;
+use crate::field_stats_pruning::{extract_required_ranges, prune_splits_by_field_stats};
 use crate::find_trace_ids_collector::Span;
+use crate::hit_federation::HitFederation;
+use crate::hit_grouping::build_hit_groups;
+use crate::leaf::{merge_term_doc_counts, term_doc_counts};
+use crate::list_fields::{list_fields, ListFieldsRequest, ListFieldsResponse};
 use crate::scroll_context::{ScrollContext, ScrollKeyAndStartOffset};
+use crate::search_cost::{estimate_search_cost, SearchCostEstimate, WarmupFootprint};
+use crate::search_guardrails::check_search_guardrails;
 use crate::search_job_placer::Job;
+use crate::search_response_rest::CountResponseRest;
 use crate::service::SearcherContext;
+use crate::sort_expression::SortExpression;
+use crate::term_frequency_collector::{self, IntermediateTermFrequencyResults};
+use crate::terms_lookup::resolve_terms_lookups;
 use crate::{
     extract_split_and_footer_offsets, list_relevant_splits, SearchError, SearchJobPlacer,
     SearchServiceClient,
@@ -146,24 +166,35 @@ pub struct IndexMetasForLeafSearch {
     pub index_uri: Uri,
     /// Doc mapper json string.
     pub doc_mapper_str: String,
+    /// Encryption config json string, if the index is encrypted.
+    pub encryption_config_str_opt: Option<String>,
 }
 
 pub(crate) type IndexesMetasForLeafSearch = HashMap<IndexUid, IndexMetasForLeafSearch>;
 type TimestampFieldOpt = Option<String>;
+/// Partition IDs the documents matching the query belong to, for the indexes whose partition key
+/// is pinned by the query.
+type QueryPartitionIds = HashMap<IndexUid, u64>;
 
 /// Validates request against each index's doc mapper and ensures that:
 /// - timestamp fields (if any) are equal across indexes.
 /// - resolved query ASTs are the same across indexes.
 /// - if a sort field has a datetime format specified, it must be a datetime field on all indexes.
-/// Returns the timestamp field, the resolved query AST and the indexes metadatas
-/// needed for leaf search requests.
+/// Returns the timestamp field, the resolved query AST, the indexes metadatas
+/// needed for leaf search requests, and the partition IDs pinned by the query.
 /// Note: the requirements on timestamp fields and resolved query ASTs can be lifted
 /// but it adds complexity that does not seem needed right now.
 fn validate_request_and_build_metadatas(
     indexes_metadata: &[IndexMetadata],
     search_request: &SearchRequest,
-) -> crate::Result<(TimestampFieldOpt, QueryAst, IndexesMetasForLeafSearch)> {
+) -> crate::Result<(
+    TimestampFieldOpt,
+    QueryAst,
+    IndexesMetasForLeafSearch,
+    QueryPartitionIds,
+)> {
     let mut metadatas_for_leaf: HashMap<IndexUid, IndexMetasForLeafSearch> = HashMap::new();
+    let mut query_partition_ids: QueryPartitionIds = HashMap::new();
     let query_ast: QueryAst = serde_json::from_str(&search_request.query_ast)
         .map_err(|err| SearchError::InvalidQuery(err.to_string()))?;
     let mut query_ast_resolved_opt: Option<QueryAst> = None;
@@ -181,7 +212,8 @@ fn validate_request_and_build_metadatas(
             .clone()
             .parse_user_query(doc_mapper.default_search_fields())
             // We convert the error to return a 400 to the user (and not a 500).
-            .map_err(|err| SearchError::InvalidQuery(err.to_string()))?;
+            .map_err(|err| SearchError::InvalidQuery(err.to_string()))?
+            .resolve_field_aliases(&doc_mapper.field_aliases());
 
         // Validate uniqueness of resolved query AST.
         if let Some(query_ast_resolved) = &query_ast_resolved_opt {
@@ -217,11 +249,16 @@ fn validate_request_and_build_metadatas(
         // Validates the query by effectively building it against the current schema.
         doc_mapper.query(doc_mapper.schema(), &query_ast_resolved_for_index, true)?;
 
+        if let Some(partition_id) = doc_mapper.query_partition_id(&query_ast_resolved_for_index) {
+            query_partition_ids.insert(index_metadata.index_uid.clone(), partition_id);
+        }
+
         let index_metadata_for_leaf_search = IndexMetasForLeafSearch {
             index_uri: index_metadata.index_uri().clone(),
             doc_mapper_str: serde_json::to_string(&doc_mapper).map_err(|err| {
                 SearchError::Internal(format!("failed to serialize doc mapper. cause: {err}"))
             })?,
+            encryption_config_str_opt: serialize_encryption_config(&index_metadata.index_config)?,
         };
         metadatas_for_leaf.insert(
             index_metadata.index_uid.clone(),
@@ -235,7 +272,55 @@ fn validate_request_and_build_metadatas(
         )
     })?;
 
-    Ok((timestamp_field_opt, query_ast_resolved, metadatas_for_leaf))
+    Ok((
+        timestamp_field_opt,
+        query_ast_resolved,
+        metadatas_for_leaf,
+        query_partition_ids,
+    ))
+}
+
+/// Serializes the encryption config of an index, if any, so that leaves can decrypt its splits.
+pub(crate) fn serialize_encryption_config(
+    index_config: &IndexConfig,
+) -> crate::Result<Option<String>> {
+    index_config
+        .encryption
+        .as_ref()
+        .map(serde_json::to_string)
+        .transpose()
+        .map_err(|err| {
+            SearchError::Internal(format!(
+                "failed to serialize encryption config. cause: {err}"
+            ))
+        })
+}
+
+/// Removes the splits that cannot contain any document matching the query because the query pins
+/// the partition key of their index to another partition.
+///
+/// Splits gathering the partitions that exceeded the maximum number of partitions are always
+/// kept.
+fn prune_splits_by_partition(
+    split_metadatas: &mut Vec<SplitMetadata>,
+    query_partition_ids: &QueryPartitionIds,
+) {
+    if query_partition_ids.is_empty() {
+        return;
+    }
+    let num_splits_before_pruning = split_metadatas.len();
+    split_metadatas.retain(|split_metadata| {
+        let Some(&partition_id) = query_partition_ids.get(&split_metadata.index_uid) else {
+            return true;
+        };
+        split_metadata.partition_id == partition_id
+            || split_metadata.partition_id == OTHER_PARTITION_ID
+    });
+    debug!(
+        num_splits_before_pruning,
+        num_splits_after_pruning = split_metadatas.len(),
+        "pruned splits by partition"
+    );
 }
 
 fn validate_requested_snippet_fields(
@@ -273,6 +358,22 @@ fn simplify_search_request_for_scroll_api(req: &SearchRequest) -> crate::Result<
             "search_after cannot be used in a scroll context".to_string(),
         ));
     }
+    if req.dedup_field.is_some() {
+        return Err(SearchError::InvalidArgument(
+            "dedup_field cannot be used in a scroll context".to_string(),
+        ));
+    }
+    if req.group_by.is_some() {
+        return Err(SearchError::InvalidArgument(
+            "group_by cannot be used in a scroll context".to_string(),
+        ));
+    }
+    if HitFederation::from_search_request(req).is_some() {
+        return Err(SearchError::InvalidArgument(
+            "max_hits_per_index and balanced federation cannot be used in a scroll context"
+                .to_string(),
+        ));
+    }
 
     // We do not mutate
     Ok(SearchRequest {
@@ -292,9 +393,33 @@ fn simplify_search_request_for_scroll_api(req: &SearchRequest) -> crate::Result<
         scroll_ttl_secs: None,
         search_after: None,
         count_hits: req.count_hits,
+        aggregation_format: req.aggregation_format,
+        dedup_field: None,
+        dedup_count: None,
+        snippet_options: None,
+        max_hits_per_index: None,
+        federation: Federation::Global.into(),
+        preview: false,
+        group_by: None,
+        split_tags: req.split_tags.clone(),
+        allow_partial_results: req.allow_partial_results,
     })
 }
 
+/// Builds the predicate used to prune the splits from the tags extracted from the query and the
+/// split tags of the request.
+fn build_tag_filter_ast(
+    query_ast: QueryAst,
+    split_tags: &[String],
+) -> crate::Result<Option<TagFilterAst>> {
+    let split_tags_filter_ast = tag_filter_from_split_tags(split_tags)
+        .map_err(|error| SearchError::InvalidArgument(error.to_string()))?;
+    Ok(and_tag_filters(
+        extract_tags_from_query(query_ast),
+        split_tags_filter_ast,
+    ))
+}
+
 /// Validates sort fields and search after values.
 /// - validate sort fields, see [`validate_sort_by_field`].
 /// - search after values must be set for all sort fields.
@@ -356,6 +481,9 @@ fn validate_sort_by_field(
     if ["_score", "_shard_doc", "_doc"].contains(&field_name) {
         return Ok(());
     }
+    if SortExpression::is_expression(field_name) {
+        return validate_sort_by_expression(field_name, has_timestamp_format, schema);
+    }
     let dynamic_field_opt = schema.get_field(DYNAMIC_FIELD_NAME).ok();
     let (sort_by_field, _json_path) = schema
         .find_field_with_default(field_name, dynamic_field_opt)
@@ -383,6 +511,131 @@ fn validate_sort_by_field(
     Ok(())
 }
 
+/// Validates a sort expression, which must be well formed and reference numeric fast fields only.
+fn validate_sort_by_expression(
+    expression: &str,
+    has_timestamp_format: bool,
+    schema: &Schema,
+) -> crate::Result<()> {
+    let sort_expression = SortExpression::parse(expression)
+        .map_err(|error| SearchError::InvalidArgument(format!("{error:#}")))?;
+    if has_timestamp_format {
+        return Err(SearchError::InvalidArgument(format!(
+            "sort by expression `{expression}` cannot have a timestamp format"
+        )));
+    }
+    let mut field_names = HashSet::new();
+    sort_expression.add_field_names(&mut field_names);
+
+    if field_names.is_empty() {
+        return Err(SearchError::InvalidArgument(format!(
+            "sort by expression `{expression}` must reference at least one field"
+        )));
+    }
+    let dynamic_field_opt = schema.get_field(DYNAMIC_FIELD_NAME).ok();
+
+    for field_name in field_names.iter().sorted() {
+        let (field, _json_path) = schema
+            .find_field_with_default(field_name, dynamic_field_opt)
+            .ok_or_else(|| {
+                SearchError::InvalidArgument(format!(
+                    "unknown field used in sort by expression `{expression}`: {field_name}"
+                ))
+            })?;
+        let field_entry = schema.get_field_entry(field);
+        if !matches!(
+            field_entry.field_type(),
+            FieldType::U64(_)
+                | FieldType::I64(_)
+                | FieldType::F64(_)
+                | FieldType::Date(_)
+                | FieldType::Bool(_)
+                | FieldType::JsonObject(_)
+        ) {
+            return Err(SearchError::InvalidArgument(format!(
+                "sort by expression `{expression}` must reference numeric fields and the field \
+                 `{field_name}` is not"
+            )));
+        }
+        if !field_entry.is_fast() {
+            return Err(SearchError::InvalidArgument(format!(
+                "sort by expression `{expression}` must reference fast fields, please add the \
+                 fast property to your field `{field_name}`"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Validates the deduplication field, which must be a fast field.
+fn validate_dedup_field(field_name: &str, schema: &Schema) -> crate::Result<()> {
+    let dynamic_field_opt = schema.get_field(DYNAMIC_FIELD_NAME).ok();
+    let (dedup_field, _json_path) = schema
+        .find_field_with_default(field_name, dynamic_field_opt)
+        .ok_or_else(|| {
+            SearchError::InvalidArgument(format!(
+                "unknown field used in `dedup_field`: {field_name}"
+            ))
+        })?;
+    if !schema.get_field_entry(dedup_field).is_fast() {
+        return Err(SearchError::InvalidArgument(format!(
+            "dedup field must be a fast field, please add the fast property to your field \
+             `{field_name}`",
+        )));
+    }
+    Ok(())
+}
+
+/// Validates the group by request: the field must be a fast field, and the request must not
+/// combine grouping with the options ordering or paginating a flat list of hits.
+fn validate_group_by(search_request: &SearchRequest, schema: &Schema) -> crate::Result<()> {
+    let Some(group_by) = &search_request.group_by else {
+        return Ok(());
+    };
+    let dynamic_field_opt = schema.get_field(DYNAMIC_FIELD_NAME).ok();
+    let (group_by_field, _json_path) = schema
+        .find_field_with_default(&group_by.field, dynamic_field_opt)
+        .ok_or_else(|| {
+            SearchError::InvalidArgument(format!(
+                "unknown field used in `group_by`: {}",
+                group_by.field
+            ))
+        })?;
+    if !schema.get_field_entry(group_by_field).is_fast() {
+        return Err(SearchError::InvalidArgument(format!(
+            "group by field must be a fast field, please add the fast property to your field `{}`",
+            group_by.field
+        )));
+    }
+    if group_by.max_groups == 0 || group_by.max_hits_per_group == 0 {
+        return Err(SearchError::InvalidArgument(
+            "group by max_groups and max_hits_per_group must be strictly positive".to_string(),
+        ));
+    }
+    let max_num_hits = group_by.max_groups as u64 * group_by.max_hits_per_group as u64;
+    if max_num_hits > 10_000 {
+        return Err(SearchError::InvalidArgument(format!(
+            "max value for max_groups * max_hits_per_group is 10_000, but got {max_num_hits}"
+        )));
+    }
+    if search_request.dedup_field.is_some() {
+        return Err(SearchError::InvalidArgument(
+            "group_by cannot be used with dedup_field".to_string(),
+        ));
+    }
+    if search_request.search_after.is_some() || search_request.start_offset != 0 {
+        return Err(SearchError::InvalidArgument(
+            "group_by cannot be used with search_after or start_offset".to_string(),
+        ));
+    }
+    if HitFederation::from_search_request(search_request).is_some() {
+        return Err(SearchError::InvalidArgument(
+            "group_by cannot be used with max_hits_per_index or balanced federation".to_string(),
+        ));
+    }
+    Ok(())
+}
+
 fn validate_request(
     doc_mapper: &dyn DocMapper,
     search_request: &SearchRequest,
@@ -407,13 +660,43 @@ fn validate_request(
     )?;
 
     if let Some(agg) = search_request.aggregation_request.as_ref() {
-        let _aggs: QuickwitAggregations = serde_json::from_str(agg).map_err(|_err| {
+        let aggs: QuickwitAggregations = serde_json::from_str(agg).map_err(|_err| {
             let err = serde_json::from_str::<tantivy::aggregation::agg_req::Aggregations>(agg)
                 .unwrap_err();
             SearchError::InvalidAggregationRequest(err.to_string())
         })?;
+        if search_request.aggregation_format() == AggregationFormat::Arrow
+            && !matches!(aggs, QuickwitAggregations::TantivyAggregations(_))
+        {
+            return Err(SearchError::InvalidAggregationRequest(
+                "the Arrow aggregation format only supports tantivy aggregations".to_string(),
+            ));
+        }
     };
 
+    if let Some(dedup_field) = &search_request.dedup_field {
+        validate_dedup_field(dedup_field, &schema)?;
+    }
+    if search_request.dedup_count == Some(0) {
+        return Err(SearchError::InvalidArgument(
+            "dedup_count must be strictly positive".to_string(),
+        ));
+    }
+    validate_group_by(search_request, &schema)?;
+    if search_request.max_hits_per_index == Some(0) {
+        return Err(SearchError::InvalidArgument(
+            "max_hits_per_index must be strictly positive".to_string(),
+        ));
+    }
+    if search_request.search_after.is_some()
+        && HitFederation::from_search_request(search_request).is_some()
+    {
+        return Err(SearchError::InvalidArgument(
+            "max_hits_per_index and balanced federation cannot be used with search_after"
+                .to_string(),
+        ));
+    }
+
     if search_request.start_offset > 10_000 {
         return Err(SearchError::InvalidArgument(format!(
             "max value for start_offset is 10_000, but got {}",
@@ -533,9 +816,27 @@ pub(crate) async fn search_partial_hits_phase(
     }
     let leaf_search_responses: Vec<LeafSearchResponse> = try_join_all(leaf_request_tasks).await?;
 
+    let hit_federation_opt = HitFederation::from_search_request(search_request);
+    // When federating hits, the merge must retain the hits of every index, so that the hits of
+    // small indexes are not evicted by the ones of large indexes before federation.
+    let merge_search_request: Cow<SearchRequest> = if hit_federation_opt.is_some() {
+        let num_partial_hits: usize = leaf_search_responses
+            .iter()
+            .map(|leaf_search_response| leaf_search_response.partial_hits.len())
+            .sum();
+        let mut merge_search_request = search_request.clone();
+        merge_search_request.start_offset = 0;
+        merge_search_request.max_hits = num_partial_hits as u64;
+        Cow::Owned(merge_search_request)
+    } else {
+        Cow::Borrowed(search_request)
+    };
     // Creates a collector which merges responses into one
-    let merge_collector =
-        make_merge_collector(search_request, &searcher_context.get_aggregation_limits())?;
+    let merge_collector = make_merge_collector(
+        &merge_search_request,
+        &searcher_context.get_aggregation_limits(),
+        &searcher_context.get_aggregation_spill_config(),
+    )?;
 
     // Merging is a cpu-bound task.
     // It should be executed by Tokio's blocking threads.
@@ -544,24 +845,46 @@ pub(crate) async fn search_partial_hits_phase(
     let leaf_search_responses: Vec<tantivy::Result<LeafSearchResponse>> =
         leaf_search_responses.into_iter().map(Ok).collect_vec();
     let span = info_span!("merge_fruits");
-    let leaf_search_response = crate::run_cpu_intensive(move || {
+    let mut leaf_search_response = crate::run_cpu_intensive(move || {
         let _span_guard = span.enter();
         merge_collector.merge_fruits(leaf_search_responses)
     })
     .await
     .context("failed to merge leaf search responses")?
     .map_err(|error: TantivyError| crate::SearchError::Internal(error.to_string()))?;
+
+    if let Some(hit_federation) = hit_federation_opt {
+        let index_uid_per_split_id: HashMap<&str, &str> = split_metadatas
+            .iter()
+            .map(|split_metadata| {
+                (
+                    split_metadata.split_id.as_str(),
+                    split_metadata.index_uid.as_str(),
+                )
+            })
+            .collect();
+        leaf_search_response.partial_hits = hit_federation.federate(
+            mem::take(&mut leaf_search_response.partial_hits),
+            &index_uid_per_split_id,
+            search_request.start_offset as usize,
+            search_request.max_hits as usize,
+        );
+    }
     debug!(
         num_hits = leaf_search_response.num_hits,
         failed_splits = ?leaf_search_response.failed_splits,
         num_attempted_splits = leaf_search_response.num_attempted_splits,
+        num_skipped_splits = leaf_search_response.skipped_split_ids.len(),
         has_intermediate_aggregation_result = leaf_search_response.intermediate_aggregation_result.is_some(),
         "Merged leaf search response."
     );
     if !leaf_search_response.failed_splits.is_empty() {
         error!(failed_splits = ?leaf_search_response.failed_splits, "leaf search response contains at least one failed split");
-        let errors: String = leaf_search_response.failed_splits.iter().join(", ");
-        return Err(SearchError::Internal(errors));
+
+        if !search_request.allow_partial_results {
+            let errors: String = leaf_search_response.failed_splits.iter().join(", ");
+            return Err(SearchError::Internal(errors));
+        }
     }
     Ok(leaf_search_response)
 }
@@ -573,11 +896,13 @@ pub(crate) fn get_snippet_request(search_request: &SearchRequest) -> Option<Snip
     Some(SnippetRequest {
         snippet_fields: search_request.snippet_fields.clone(),
         query_ast_resolved: search_request.query_ast.clone(),
+        snippet_options: search_request.snippet_options.clone(),
     })
 }
 
 #[instrument(skip_all, fields(partial_hits_num=partial_hits.len()))]
 pub(crate) async fn fetch_docs_phase(
+    searcher_context: &SearcherContext,
     indexes_metas_for_leaf_search: &IndexesMetasForLeafSearch,
     partial_hits: &[PartialHit],
     split_metadatas: &[SplitMetadata],
@@ -605,18 +930,37 @@ pub(crate) async fn fetch_docs_phase(
     )
     .await?;
 
+    let searcher_config = &searcher_context.searcher_config;
+    let max_num_concurrent_requests_per_node =
+        searcher_config.max_num_concurrent_fetch_docs_requests_per_node;
+    let start_instant = tokio::time::Instant::now();
+
+    // The requests sent to a given node are throttled so that a search returning the hits of many
+    // splits does not overload the nodes hosting them.
     let mut fetch_docs_tasks = Vec::new();
     for (client, client_jobs) in assigned_fetch_docs_jobs {
-        let fetch_jobs_requests = jobs_to_fetch_docs_requests(
+        let fetch_docs_requests = jobs_to_fetch_docs_requests(
             snippet_request.clone(),
             indexes_metas_for_leaf_search,
             client_jobs,
+            searcher_config.max_num_splits_per_fetch_docs_request,
         )?;
-        for fetch_docs_request in fetch_jobs_requests {
-            fetch_docs_tasks.push(cluster_client.fetch_docs(fetch_docs_request, client.clone()));
-        }
+        let fetch_docs_task = futures::stream::iter(fetch_docs_requests)
+            .map(move |fetch_docs_request| {
+                cluster_client.fetch_docs(fetch_docs_request, client.clone())
+            })
+            .buffer_unordered(max_num_concurrent_requests_per_node)
+            .try_collect::<Vec<FetchDocsResponse>>();
+        fetch_docs_tasks.push(fetch_docs_task);
     }
-    let fetch_docs_responses: Vec<FetchDocsResponse> = try_join_all(fetch_docs_tasks).await?;
+    let fetch_docs_responses: Vec<FetchDocsResponse> = try_join_all(fetch_docs_tasks)
+        .await?
+        .into_iter()
+        .flatten()
+        .collect();
+    crate::SEARCH_METRICS
+        .root_fetch_docs_phase_duration_secs
+        .observe(start_instant.elapsed().as_secs_f64());
 
     // Merge the fetched docs.
     let leaf_hits = fetch_docs_responses
@@ -751,7 +1095,8 @@ async fn root_search_aux(
     )
     .await?;
 
-    let hits = fetch_docs_phase(
+    let mut hits = fetch_docs_phase(
+        searcher_context,
         indexes_metas_for_leaf_search,
         &first_phase_result.partial_hits,
         &split_metadatas[..],
@@ -759,15 +1104,30 @@ async fn root_search_aux(
         cluster_client,
     )
     .await?;
+    let groups = if search_request.group_by.is_some() {
+        build_hit_groups(mem::take(&mut hits), &first_phase_result.group_counts)
+    } else {
+        Vec::new()
+    };
 
-    let aggregation_result_json_opt = finalize_aggregation_if_any(
-        &search_request,
-        first_phase_result.intermediate_aggregation_result,
-        searcher_context,
-    )?;
+    let (aggregation_result_json_opt, aggregation_result_arrow_opt) =
+        match finalize_aggregation_if_any(
+            &search_request,
+            first_phase_result.intermediate_aggregation_result,
+            searcher_context,
+        )? {
+            Some(FinalAggregationResult::Json(aggregation_result_json)) => {
+                (Some(aggregation_result_json), None)
+            }
+            Some(FinalAggregationResult::Arrow(aggregation_result_arrow)) => {
+                (None, Some(aggregation_result_arrow))
+            }
+            None => (None, None),
+        };
 
     Ok(SearchResponse {
         aggregation: aggregation_result_json_opt,
+        aggregation_arrow: aggregation_result_arrow_opt,
         num_hits: first_phase_result.num_hits,
         hits,
         elapsed_time_micros: 0u64,
@@ -775,14 +1135,26 @@ async fn root_search_aux(
         scroll_id: scroll_key_and_start_offset_opt
             .as_ref()
             .map(ToString::to_string),
+        preview_coverage: None,
+        groups,
+        failed_splits: first_phase_result.failed_splits,
+        skipped_split_ids: first_phase_result.skipped_split_ids,
     })
 }
 
+/// Final aggregation result, serialized in the aggregation format of the search request.
+enum FinalAggregationResult {
+    Json(String),
+    /// Arrow IPC stream, only available for tantivy aggregations.
+    Arrow(Vec<u8>),
+}
+
 fn finalize_aggregation(
     intermediate_aggregation_result_bytes: &[u8],
     aggregations: QuickwitAggregations,
+    aggregation_format: AggregationFormat,
     searcher_context: &SearcherContext,
-) -> crate::Result<String> {
+) -> crate::Result<FinalAggregationResult> {
     let merge_aggregation_result = match aggregations {
         QuickwitAggregations::FindTraceIdsAggregation(_) => {
             // The merge collector has already merged the intermediate results.
@@ -794,17 +1166,29 @@ fn finalize_aggregation(
                 postcard::from_bytes(intermediate_aggregation_result_bytes)?;
             let final_aggregation_results: AggregationResults = intermediate_aggregation_results
                 .into_final_result(aggregations, &searcher_context.get_aggregation_limits())?;
+            if aggregation_format == AggregationFormat::Arrow {
+                let aggregation_result_arrow =
+                    aggregation_results_to_arrow_ipc(&final_aggregation_results)?;
+                return Ok(FinalAggregationResult::Arrow(aggregation_result_arrow));
+            }
             serde_json::to_string(&final_aggregation_results)?
         }
+        QuickwitAggregations::TermFrequencyAggregations(aggregations) => {
+            let intermediate_results: IntermediateTermFrequencyResults =
+                postcard::from_bytes(intermediate_aggregation_result_bytes)?;
+            let final_results =
+                term_frequency_collector::finalize_results(&aggregations, intermediate_results);
+            serde_json::to_string(&final_results)?
+        }
     };
-    Ok(merge_aggregation_result)
+    Ok(FinalAggregationResult::Json(merge_aggregation_result))
 }
 
 fn finalize_aggregation_if_any(
     search_request: &SearchRequest,
     intermediate_aggregation_result_bytes_opt: Option<Vec<u8>>,
     searcher_context: &SearcherContext,
-) -> crate::Result<Option<String>> {
+) -> crate::Result<Option<FinalAggregationResult>> {
     let Some(aggregations_json) = search_request.aggregation_request.as_ref() else {
         return Ok(None);
     };
@@ -812,12 +1196,13 @@ fn finalize_aggregation_if_any(
     let Some(intermediate_result_bytes) = intermediate_aggregation_result_bytes_opt else {
         return Ok(None);
     };
-    let aggregation_result_json = finalize_aggregation(
+    let final_aggregation_result = finalize_aggregation(
         &intermediate_result_bytes[..],
         aggregations,
+        search_request.aggregation_format(),
         searcher_context,
     )?;
-    Ok(Some(aggregation_result_json))
+    Ok(Some(final_aggregation_result))
 }
 
 /// Checks that all of the index researched as found.
@@ -884,6 +1269,10 @@ pub async fn root_search(
 
     check_all_index_metadata_found(&indexes_metadata[..], &search_request.index_id_patterns[..])?;
 
+    resolve_field_aliases(&mut search_request, &indexes_metadata)?;
+    let datetime_percentiles_paths =
+        rewrite_aggregation_request(&mut search_request, &indexes_metadata)?;
+
     if indexes_metadata.is_empty() {
         // We go through root_search_aux instead of directly
         // returning an empty response to make sure we generate
@@ -904,8 +1293,24 @@ pub async fn root_search(
         .iter()
         .map(|index_metadata| index_metadata.index_uid.clone())
         .collect_vec();
-    let (timestamp_field_opt, query_ast_resolved, indexes_metas_for_leaf_search) =
-        validate_request_and_build_metadatas(&indexes_metadata, &search_request)?;
+    crate::SEARCH_METRICS.record_root_search_request(
+        indexes_metadata
+            .iter()
+            .map(|index_metadata| index_metadata.index_id()),
+    );
+    resolve_terms_lookups(
+        searcher_context,
+        &mut search_request,
+        &metastore,
+        cluster_client,
+    )
+    .await?;
+    let (
+        timestamp_field_opt,
+        query_ast_resolved,
+        indexes_metas_for_leaf_search,
+        query_partition_ids,
+    ) = validate_request_and_build_metadatas(&indexes_metadata, &search_request)?;
     search_request.query_ast = serde_json::to_string(&query_ast_resolved)?;
 
     // convert search_after datetime values from input datetime format to nanos.
@@ -920,11 +1325,19 @@ pub async fn root_search(
             &mut search_request.end_timestamp,
         );
     }
-    let tag_filter_ast = extract_tags_from_query(query_ast_resolved);
+    for index_metadata in &indexes_metadata {
+        check_search_guardrails(
+            &index_metadata.index_config,
+            &query_ast_resolved,
+            &search_request,
+        )?;
+    }
+    let required_ranges = extract_required_ranges(&query_ast_resolved);
+    let tag_filter_ast = build_tag_filter_ast(query_ast_resolved, &search_request.split_tags)?;
 
     // TODO if search after is set, we sort by timestamp and we don't want to count all results,
     // we can refine more here. Same if we sort by _shard_doc
-    let split_metadatas: Vec<SplitMetadata> = list_relevant_splits(
+    let mut split_metadatas: Vec<SplitMetadata> = list_relevant_splits(
         index_uids,
         search_request.start_timestamp,
         search_request.end_timestamp,
@@ -932,7 +1345,17 @@ pub async fn root_search(
         &mut metastore,
     )
     .await?;
-
+    prune_splits_by_partition(&mut split_metadatas, &query_partition_ids);
+    prune_splits_by_field_stats(&mut split_metadatas, &required_ranges);
+
+    let mut preview_coverage_opt = None;
+    if search_request.preview {
+        let preview_coverage = select_preview_splits(
+            &mut split_metadatas,
+            searcher_context.searcher_config.preview_num_splits,
+        );
+        preview_coverage_opt = Some(preview_coverage);
+    }
     let mut search_response = root_search_aux(
         searcher_context,
         &indexes_metas_for_leaf_search,
@@ -941,84 +1364,439 @@ pub async fn root_search(
         cluster_client,
     )
     .await?;
+    search_response.preview_coverage = preview_coverage_opt;
+
+    if !datetime_percentiles_paths.is_empty() {
+        if let Some(aggregation_result_json) = &search_response.aggregation {
+            search_response.aggregation = Some(format_datetime_percentiles(
+                aggregation_result_json,
+                &datetime_percentiles_paths,
+            )?);
+        }
+    }
 
-    search_response.elapsed_time_micros = start_instant.elapsed().as_micros() as u64;
+    let elapsed = start_instant.elapsed();
+    crate::SEARCH_METRICS.record_root_search_duration(
+        indexes_metadata
+            .iter()
+            .map(|index_metadata| index_metadata.index_id()),
+        elapsed.as_secs_f64(),
+    );
+    search_response.elapsed_time_micros = elapsed.as_micros() as u64;
     Ok(search_response)
 }
 
-/// Converts search after with datetime format to nanoseconds (representation in tantivy).
-fn convert_search_after_datetime_values(search_request: &mut SearchRequest) -> crate::Result<()> {
-    if let Some(partial_hit) = search_request.search_after.as_mut() {
-        let search_after_values = [
-            partial_hit.sort_value.as_mut(),
-            partial_hit.sort_value2.as_mut(),
-        ];
-        for (sort_field, search_after_value_opt) in
-            search_request.sort_fields.iter().zip(search_after_values)
-        {
-            let Some(search_after_sort_by_value) = search_after_value_opt else {
-                continue;
-            };
-            let Some(search_after_sort_value) = search_after_sort_by_value.sort_value.as_mut()
-            else {
-                continue;
-            };
-            let Some(datetime_format_int) = sort_field.sort_datetime_format else {
-                continue;
-            };
-            let input_datetime_format = SortDatetimeFormat::from_i32(datetime_format_int)
-                .context("invalid sort datetime format")?;
-            convert_sort_datetime_value_into_nanos(search_after_sort_value, input_datetime_format)?;
-        }
-    }
-    Ok(())
-}
+/// Lists the fields of the indexes matching the patterns of the request, as described by their doc
+/// mappings.
+pub async fn root_list_fields(
+    list_fields_request: ListFieldsRequest,
+    mut metastore: MetastoreServiceClient,
+) -> crate::Result<ListFieldsResponse> {
+    info!(list_fields_request = ?list_fields_request, "list-fields");
+    let list_indexes_metadatas_request = ListIndexesMetadataRequest {
+        index_id_patterns: list_fields_request.index_id_patterns.clone(),
+    };
+    let indexes_metadata: Vec<IndexMetadata> = metastore
+        .list_indexes_metadata(list_indexes_metadatas_request)
+        .await?
+        .deserialize_indexes_metadata()?;
 
-/// Convert sort values from input datetime format into nanoseconds.
-/// The conversion is done only for U64 and I64 sort values, an error is returned for other types.
-fn convert_sort_datetime_value_into_nanos(
-    sort_value: &mut SortValue,
-    input_format: SortDatetimeFormat,
-) -> crate::Result<()> {
-    match sort_value {
-        SortValue::U64(value) => match input_format {
-            SortDatetimeFormat::UnixTimestampMillis => {
-                *value = value.checked_mul(1_000_000).ok_or_else(|| {
-                    SearchError::Internal(format!(
-                        "sort value defined in milliseconds is too large and cannot be converted \
-                         into nanoseconds: {}",
-                        value
-                    ))
-                })?;
-            }
-        },
-        SortValue::I64(value) => match input_format {
-            SortDatetimeFormat::UnixTimestampMillis => {
-                *value = value.checked_mul(1_000_000).ok_or_else(|| {
-                    SearchError::Internal(format!(
-                        "sort value defined in milliseconds is too large and cannot be converted \
-                         into nanoseconds: {}",
-                        value
-                    ))
-                })?;
-            }
-        },
-        _ => {
-            return Err(SearchError::Internal(format!(
-                "datetime conversion are only support for u64 and i64 sort values, not `{:?}`",
-                sort_value
-            )));
-        }
+    check_all_index_metadata_found(
+        &indexes_metadata[..],
+        &list_fields_request.index_id_patterns[..],
+    )?;
+
+    let mut index_schemas = Vec::with_capacity(indexes_metadata.len());
+
+    for index_metadata in &indexes_metadata {
+        let doc_mapper = build_doc_mapper(
+            &index_metadata.index_config.doc_mapping,
+            &index_metadata.index_config.search_settings,
+        )
+        .map_err(|err| {
+            SearchError::Internal(format!("failed to build doc mapper. cause: {err}"))
+        })?;
+        index_schemas.push((index_metadata.index_id(), doc_mapper.schema()));
     }
-    Ok(())
+    let list_fields_response = list_fields(
+        index_schemas
+            .iter()
+            .map(|(index_id, schema)| (*index_id, schema)),
+        &list_fields_request.field_patterns,
+    );
+    Ok(list_fields_response)
 }
 
-/// Convert sort values from nanoseconds to the requested output format.
-/// The conversion is done only for U64 and I64 sort values, an error is returned for other types.
-fn convert_sort_datetime_value(
-    sort_value: &mut SortValue,
-    output_format: SortDatetimeFormat,
-) -> crate::Result<()> {
+/// Estimates the cost of a search request without executing it: the splits that would be searched
+/// are listed, and the bytes to download are derived from their metadata and from the warmup info
+/// of the query.
+pub async fn root_estimate_search_cost(
+    searcher_context: &SearcherContext,
+    mut search_request: SearchRequest,
+    mut metastore: MetastoreServiceClient,
+    cluster_client: &ClusterClient,
+) -> crate::Result<SearchCostEstimate> {
+    info!(search_request = ?search_request, "estimate-search-cost");
+    let max_num_concurrent_split_searches = searcher_context
+        .effective_searcher_config()
+        .max_num_concurrent_split_searches;
+    let list_indexes_metadatas_request = ListIndexesMetadataRequest {
+        index_id_patterns: search_request.index_id_patterns.clone(),
+    };
+    let indexes_metadata: Vec<IndexMetadata> = metastore
+        .list_indexes_metadata(list_indexes_metadatas_request)
+        .await?
+        .deserialize_indexes_metadata()?;
+
+    check_all_index_metadata_found(&indexes_metadata[..], &search_request.index_id_patterns[..])?;
+    resolve_field_aliases(&mut search_request, &indexes_metadata)?;
+    rewrite_aggregation_request(&mut search_request, &indexes_metadata)?;
+
+    if indexes_metadata.is_empty() {
+        return Ok(estimate_search_cost(
+            &[],
+            &HashMap::new(),
+            0,
+            max_num_concurrent_split_searches,
+        ));
+    }
+    resolve_terms_lookups(
+        searcher_context,
+        &mut search_request,
+        &metastore,
+        cluster_client,
+    )
+    .await?;
+    let (
+        timestamp_field_opt,
+        query_ast_resolved,
+        _indexes_metas_for_leaf_search,
+        query_partition_ids,
+    ) = validate_request_and_build_metadatas(&indexes_metadata, &search_request)?;
+
+    let collector_warmup_info = make_merge_collector(
+        &search_request,
+        &searcher_context.get_aggregation_limits(),
+        &searcher_context.get_aggregation_spill_config(),
+    )?
+    .warmup_info();
+    let mut warmup_footprints = HashMap::with_capacity(indexes_metadata.len());
+
+    for index_metadata in &indexes_metadata {
+        let doc_mapper = build_doc_mapper(
+            &index_metadata.index_config.doc_mapping,
+            &index_metadata.index_config.search_settings,
+        )
+        .map_err(|err| {
+            SearchError::Internal(format!("failed to build doc mapper. cause: {err}"))
+        })?;
+        let (_query, mut warmup_info) =
+            doc_mapper.query(doc_mapper.schema(), &query_ast_resolved, false)?;
+        warmup_info.merge(collector_warmup_info.clone());
+        warmup_footprints.insert(
+            index_metadata.index_uid.clone(),
+            WarmupFootprint::from_warmup_info(&warmup_info),
+        );
+    }
+    if let Some(timestamp_field) = &timestamp_field_opt {
+        refine_start_end_timestamp_from_ast(
+            &query_ast_resolved,
+            timestamp_field,
+            &mut search_request.start_timestamp,
+            &mut search_request.end_timestamp,
+        );
+    }
+    for index_metadata in &indexes_metadata {
+        check_search_guardrails(
+            &index_metadata.index_config,
+            &query_ast_resolved,
+            &search_request,
+        )?;
+    }
+    let required_ranges = extract_required_ranges(&query_ast_resolved);
+    let tag_filter_ast = build_tag_filter_ast(query_ast_resolved, &search_request.split_tags)?;
+    let index_uids = indexes_metadata
+        .into_iter()
+        .map(|index_metadata| index_metadata.index_uid)
+        .collect_vec();
+    let mut split_metadatas: Vec<SplitMetadata> = list_relevant_splits(
+        index_uids,
+        search_request.start_timestamp,
+        search_request.end_timestamp,
+        tag_filter_ast,
+        &mut metastore,
+    )
+    .await?;
+    prune_splits_by_partition(&mut split_metadatas, &query_partition_ids);
+    prune_splits_by_field_stats(&mut split_metadatas, &required_ranges);
+
+    if search_request.preview {
+        select_preview_splits(
+            &mut split_metadatas,
+            searcher_context.searcher_config.preview_num_splits,
+        );
+    }
+    let num_hits_to_fetch = match &search_request.group_by {
+        Some(group_by) => group_by.max_groups as u64 * group_by.max_hits_per_group as u64,
+        None => search_request.max_hits,
+    };
+    Ok(estimate_search_cost(
+        &split_metadatas,
+        &warmup_footprints,
+        num_hits_to_fetch,
+        max_num_concurrent_split_searches,
+    ))
+}
+
+/// Counts the documents matching a search request.
+///
+/// Unlike `root_search`, the leaves neither sort nor collect hits, no documents are fetched, and
+/// the leaf responses are not merged: their number of hits is simply summed up per index.
+pub async fn root_count(
+    searcher_context: &SearcherContext,
+    mut search_request: SearchRequest,
+    mut metastore: MetastoreServiceClient,
+    cluster_client: &ClusterClient,
+) -> crate::Result<CountResponseRest> {
+    info!(search_request = ?search_request, "count");
+    let start_instant = tokio::time::Instant::now();
+    simplify_search_request_for_count(&mut search_request);
+
+    let list_indexes_metadatas_request = ListIndexesMetadataRequest {
+        index_id_patterns: search_request.index_id_patterns.clone(),
+    };
+    let indexes_metadata: Vec<IndexMetadata> = metastore
+        .list_indexes_metadata(list_indexes_metadatas_request)
+        .await?
+        .deserialize_indexes_metadata()?;
+
+    check_all_index_metadata_found(&indexes_metadata[..], &search_request.index_id_patterns[..])?;
+
+    if indexes_metadata.is_empty() {
+        return Ok(CountResponseRest {
+            num_hits: 0,
+            num_hits_per_index: None,
+            elapsed_time_micros: start_instant.elapsed().as_micros() as u64,
+        });
+    }
+    resolve_terms_lookups(
+        searcher_context,
+        &mut search_request,
+        &metastore,
+        cluster_client,
+    )
+    .await?;
+    let (
+        timestamp_field_opt,
+        query_ast_resolved,
+        indexes_metas_for_leaf_search,
+        query_partition_ids,
+    ) = validate_request_and_build_metadatas(&indexes_metadata, &search_request)?;
+    search_request.query_ast = serde_json::to_string(&query_ast_resolved)?;
+
+    if let Some(timestamp_field) = &timestamp_field_opt {
+        refine_start_end_timestamp_from_ast(
+            &query_ast_resolved,
+            timestamp_field,
+            &mut search_request.start_timestamp,
+            &mut search_request.end_timestamp,
+        );
+    }
+    for index_metadata in &indexes_metadata {
+        check_search_guardrails(
+            &index_metadata.index_config,
+            &query_ast_resolved,
+            &search_request,
+        )?;
+    }
+    let required_ranges = extract_required_ranges(&query_ast_resolved);
+    let tag_filter_ast = build_tag_filter_ast(query_ast_resolved, &search_request.split_tags)?;
+    let index_uids = indexes_metadata
+        .iter()
+        .map(|index_metadata| index_metadata.index_uid.clone())
+        .collect_vec();
+    let mut split_metadatas: Vec<SplitMetadata> = list_relevant_splits(
+        index_uids,
+        search_request.start_timestamp,
+        search_request.end_timestamp,
+        tag_filter_ast,
+        &mut metastore,
+    )
+    .await?;
+    prune_splits_by_partition(&mut split_metadatas, &query_partition_ids);
+    prune_splits_by_field_stats(&mut split_metadatas, &required_ranges);
+
+    let jobs: Vec<SearchJob> = split_metadatas.iter().map(SearchJob::from).collect();
+    let assigned_leaf_search_jobs = cluster_client
+        .search_job_placer
+        .assign_jobs(jobs, &HashSet::default())
+        .await?;
+    let index_id_per_index_uri: HashMap<String, &str> = indexes_metadata
+        .iter()
+        .map(|index_metadata| {
+            (
+                index_metadata.index_uri().to_string(),
+                index_metadata.index_id(),
+            )
+        })
+        .collect();
+    let mut leaf_index_ids = Vec::new();
+    let mut leaf_request_tasks = Vec::new();
+    for (client, client_jobs) in assigned_leaf_search_jobs {
+        let leaf_requests =
+            jobs_to_leaf_requests(&search_request, &indexes_metas_for_leaf_search, client_jobs)?;
+        for leaf_request in leaf_requests {
+            let index_id = index_id_per_index_uri
+                .get(&leaf_request.index_uri)
+                .copied()
+                .unwrap_or_default();
+            leaf_index_ids.push(index_id);
+            leaf_request_tasks.push(cluster_client.leaf_search(leaf_request, client.clone()));
+        }
+    }
+    let leaf_search_responses: Vec<LeafSearchResponse> = try_join_all(leaf_request_tasks).await?;
+
+    let mut num_hits = 0;
+    let mut num_hits_per_index: BTreeMap<String, u64> = indexes_metadata
+        .iter()
+        .map(|index_metadata| (index_metadata.index_id().to_string(), 0))
+        .collect();
+    for (index_id, leaf_search_response) in leaf_index_ids.into_iter().zip(leaf_search_responses) {
+        if !leaf_search_response.failed_splits.is_empty() {
+            error!(failed_splits = ?leaf_search_response.failed_splits, "leaf search response contains at least one failed split");
+            let errors: String = leaf_search_response.failed_splits.iter().join(", ");
+            return Err(SearchError::Internal(errors));
+        }
+        num_hits += leaf_search_response.num_hits;
+        *num_hits_per_index.entry(index_id.to_string()).or_default() +=
+            leaf_search_response.num_hits;
+    }
+    Ok(CountResponseRest {
+        num_hits,
+        num_hits_per_index: (indexes_metadata.len() > 1).then_some(num_hits_per_index),
+        elapsed_time_micros: start_instant.elapsed().as_micros() as u64,
+    })
+}
+
+/// Strips a search request of everything that is not needed to count the documents matching its
+/// query.
+fn simplify_search_request_for_count(search_request: &mut SearchRequest) {
+    search_request.max_hits = 0;
+    search_request.start_offset = 0;
+    search_request.count_hits = CountHits::CountAll.into();
+    search_request.sort_fields.clear();
+    search_request.search_after = None;
+    search_request.aggregation_request = None;
+    search_request.snippet_fields.clear();
+    search_request.snippet_options = None;
+    search_request.scroll_ttl_secs = None;
+    search_request.dedup_field = None;
+    search_request.dedup_count = None;
+    search_request.group_by = None;
+    search_request.max_hits_per_index = None;
+    search_request.federation = Federation::Global.into();
+    search_request.preview = false;
+}
+
+/// Keeps the `num_splits` most recent splits, i.e. the splits whose time range ends last, and
+/// returns the fraction of the documents they hold.
+fn select_preview_splits(split_metadatas: &mut Vec<SplitMetadata>, num_splits: usize) -> f64 {
+    let total_num_docs: usize = split_metadatas
+        .iter()
+        .map(|split_metadata| split_metadata.num_docs)
+        .sum();
+    split_metadatas.sort_unstable_by_key(|split_metadata| {
+        let time_range_end_opt = split_metadata
+            .time_range
+            .as_ref()
+            .map(|time_range| *time_range.end());
+        Reverse((time_range_end_opt, split_metadata.create_timestamp))
+    });
+    split_metadatas.truncate(num_splits);
+
+    if total_num_docs == 0 {
+        return 1.0;
+    }
+    let num_docs: usize = split_metadatas
+        .iter()
+        .map(|split_metadata| split_metadata.num_docs)
+        .sum();
+    num_docs as f64 / total_num_docs as f64
+}
+
+/// Converts search after with datetime format to nanoseconds (representation in tantivy).
+fn convert_search_after_datetime_values(search_request: &mut SearchRequest) -> crate::Result<()> {
+    if let Some(partial_hit) = search_request.search_after.as_mut() {
+        let search_after_values = [
+            partial_hit.sort_value.as_mut(),
+            partial_hit.sort_value2.as_mut(),
+        ];
+        for (sort_field, search_after_value_opt) in
+            search_request.sort_fields.iter().zip(search_after_values)
+        {
+            let Some(search_after_sort_by_value) = search_after_value_opt else {
+                continue;
+            };
+            let Some(search_after_sort_value) = search_after_sort_by_value.sort_value.as_mut()
+            else {
+                continue;
+            };
+            let Some(datetime_format_int) = sort_field.sort_datetime_format else {
+                continue;
+            };
+            let input_datetime_format = SortDatetimeFormat::from_i32(datetime_format_int)
+                .context("invalid sort datetime format")?;
+            convert_sort_datetime_value_into_nanos(search_after_sort_value, input_datetime_format)?;
+        }
+    }
+    Ok(())
+}
+
+/// Convert sort values from input datetime format into nanoseconds.
+/// The conversion is done only for U64 and I64 sort values, an error is returned for other types.
+fn convert_sort_datetime_value_into_nanos(
+    sort_value: &mut SortValue,
+    input_format: SortDatetimeFormat,
+) -> crate::Result<()> {
+    match sort_value {
+        SortValue::U64(value) => match input_format {
+            SortDatetimeFormat::UnixTimestampMillis => {
+                *value = value.checked_mul(1_000_000).ok_or_else(|| {
+                    SearchError::Internal(format!(
+                        "sort value defined in milliseconds is too large and cannot be converted \
+                         into nanoseconds: {}",
+                        value
+                    ))
+                })?;
+            }
+        },
+        SortValue::I64(value) => match input_format {
+            SortDatetimeFormat::UnixTimestampMillis => {
+                *value = value.checked_mul(1_000_000).ok_or_else(|| {
+                    SearchError::Internal(format!(
+                        "sort value defined in milliseconds is too large and cannot be converted \
+                         into nanoseconds: {}",
+                        value
+                    ))
+                })?;
+            }
+        },
+        _ => {
+            return Err(SearchError::Internal(format!(
+                "datetime conversion are only support for u64 and i64 sort values, not `{:?}`",
+                sort_value
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Convert sort values from nanoseconds to the requested output format.
+/// The conversion is done only for U64 and I64 sort values, an error is returned for other types.
+fn convert_sort_datetime_value(
+    sort_value: &mut SortValue,
+    output_format: SortDatetimeFormat,
+) -> crate::Result<()> {
     match sort_value {
         SortValue::U64(value) => match output_format {
             SortDatetimeFormat::UnixTimestampMillis => {
@@ -1228,6 +2006,7 @@ pub async fn root_list_terms(
         .await?;
 
     let index_uri = &index_config.index_uri;
+    let encryption_config_str_opt = serialize_encryption_config(&index_config)?;
 
     let jobs: Vec<SearchJob> = split_metadatas.iter().map(SearchJob::from).collect();
     let assigned_leaf_search_jobs = cluster_client
@@ -1241,6 +2020,7 @@ pub async fn root_list_terms(
                     list_terms_request: Some(list_terms_request.clone()),
                     split_offsets: client_jobs.into_iter().map(|job| job.offsets).collect(),
                     index_uri: index_uri.to_string(),
+                    encryption_config: encryption_config_str_opt.clone(),
                 },
                 client,
             )
@@ -1264,29 +2044,32 @@ pub async fn root_list_terms(
 
     // Merging is a cpu-bound task, but probably fast enough to not require
     // spawning it on a blocking thread.
-    let merged_iter = leaf_search_responses
+    let term_doc_counts_lists: Vec<Vec<(Vec<u8>, u64)>> = leaf_search_responses
         .into_iter()
-        .map(|leaf_search_response| leaf_search_response.terms)
-        .kmerge()
-        .dedup();
-    let leaf_list_terms_response: Vec<Vec<u8>> = if let Some(limit) = list_terms_request.max_hits {
-        merged_iter.take(limit as usize).collect()
-    } else {
-        merged_iter.collect()
-    };
+        .map(|leaf_search_response| {
+            term_doc_counts(leaf_search_response.terms, leaf_search_response.doc_counts)
+        })
+        .collect();
+    let merged_term_doc_counts =
+        merge_term_doc_counts(term_doc_counts_lists, list_terms_request.max_hits);
 
     debug!(
-        leaf_list_terms_response_count = leaf_list_terms_response.len(),
+        leaf_list_terms_response_count = merged_term_doc_counts.len(),
         "Merged leaf search response."
     );
-
+    let (terms, mut doc_counts): (Vec<Vec<u8>>, Vec<u64>) =
+        merged_term_doc_counts.into_iter().unzip();
+    if !list_terms_request.include_doc_counts {
+        doc_counts.clear();
+    }
     let elapsed = start_instant.elapsed();
 
     Ok(ListTermsResponse {
-        num_hits: leaf_list_terms_response.len() as u64,
-        terms: leaf_list_terms_response,
+        num_hits: terms.len() as u64,
+        terms,
         elapsed_time_micros: elapsed.as_micros() as u64,
         errors: Vec::new(),
+        doc_counts,
     })
 }
 
@@ -1309,7 +2092,8 @@ async fn assign_client_fetch_docs_jobs(
             })
             .collect();
 
-    // Group the partial hits per split
+    // Group the partial hits per split, so that each split is fetched by a single node with a
+    // single request.
     let mut partial_hits_map: HashMap<String, Vec<PartialHit>> = HashMap::new();
     for partial_hit in partial_hits.iter() {
         partial_hits_map
@@ -1371,20 +2155,25 @@ pub fn jobs_to_leaf_requests(
             split_offsets: job_group.into_iter().map(|job| job.offsets).collect(),
             doc_mapper: search_index_meta.doc_mapper_str.clone(),
             index_uri: search_index_meta.index_uri.to_string(),
+            encryption_config: search_index_meta.encryption_config_str_opt.clone(),
         };
         leaf_search_requests.push(leaf_search_request);
     }
     Ok(leaf_search_requests)
 }
 
-/// Builds a list of [`FetchDocsRequest`], one per index, from a list of [`FetchDocsJob`].
+/// Builds a list of [`FetchDocsRequest`] from a list of [`FetchDocsJob`]. The jobs are grouped per
+/// index, and each request fetches the docs of at most `max_num_splits_per_request` splits.
 pub fn jobs_to_fetch_docs_requests(
     snippet_request_opt: Option<SnippetRequest>,
     indexes_metas_for_leaf_search: &IndexesMetasForLeafSearch,
-    jobs: Vec<FetchDocsJob>,
+    mut jobs: Vec<FetchDocsJob>,
+    max_num_splits_per_request: usize,
 ) -> crate::Result<Vec<FetchDocsRequest>> {
     let mut fetch_docs_requests = Vec::new();
     // Group jobs by index uid.
+    jobs.sort_by(|left, right| left.index_uid.cmp(&right.index_uid));
+
     for (index_uid, job_group) in &jobs.into_iter().group_by(|job| job.index_uid.clone()) {
         let index_meta = indexes_metas_for_leaf_search
             .get(&index_uid)
@@ -1393,23 +2182,26 @@ pub fn jobs_to_fetch_docs_requests(
                     "received search job for an unknown index {index_uid}"
                 ))
             })?;
-        let fetch_docs_jobs: Vec<FetchDocsJob> = job_group.collect();
-        let partial_hits: Vec<PartialHit> = fetch_docs_jobs
-            .iter()
-            .flat_map(|fetch_doc_job| fetch_doc_job.partial_hits.iter().cloned())
-            .collect();
-        let split_offsets: Vec<SplitIdAndFooterOffsets> = fetch_docs_jobs
-            .into_iter()
-            .map(|fetch_doc_job| fetch_doc_job.into())
-            .collect();
-        let fetch_docs_req = FetchDocsRequest {
-            partial_hits,
-            split_offsets,
-            index_uri: index_meta.index_uri.to_string(),
-            snippet_request: snippet_request_opt.clone(),
-            doc_mapper: index_meta.doc_mapper_str.clone(),
-        };
-        fetch_docs_requests.push(fetch_docs_req);
+        for job_chunk in &job_group.chunks(max_num_splits_per_request.max(1)) {
+            let fetch_docs_jobs: Vec<FetchDocsJob> = job_chunk.collect();
+            let partial_hits: Vec<PartialHit> = fetch_docs_jobs
+                .iter()
+                .flat_map(|fetch_doc_job| fetch_doc_job.partial_hits.iter().cloned())
+                .collect();
+            let split_offsets: Vec<SplitIdAndFooterOffsets> = fetch_docs_jobs
+                .into_iter()
+                .map(|fetch_doc_job| fetch_doc_job.into())
+                .collect();
+            let fetch_docs_req = FetchDocsRequest {
+                partial_hits,
+                split_offsets,
+                index_uri: index_meta.index_uri.to_string(),
+                snippet_request: snippet_request_opt.clone(),
+                doc_mapper: index_meta.doc_mapper_str.clone(),
+                encryption_config: index_meta.encryption_config_str_opt.clone(),
+            };
+            fetch_docs_requests.push(fetch_docs_req);
+        }
     }
     Ok(fetch_docs_requests)
 }
@@ -1427,13 +2219,13 @@ mod tests {
     use quickwit_metastore::{IndexMetadata, ListSplitsResponseExt};
     use quickwit_proto::metastore::{ListIndexesMetadataResponse, ListSplitsResponse};
     use quickwit_proto::search::{
-        ScrollRequest, SortByValue, SortOrder, SortValue, SplitSearchError,
+        GroupBy, ScrollRequest, SortByValue, SortOrder, SortValue, SplitSearchError,
     };
     use quickwit_query::query_ast::{qast_helper, qast_json_helper, query_ast_from_user_text};
-    use tantivy::schema::{FAST, STORED, TEXT};
+    use tantivy::schema::{FAST, INDEXED, STORED, STRING, TEXT};
 
     use super::*;
-    use crate::{searcher_pool_for_test, MockSearchService};
+    use crate::{searcher_pool_for_test, LatencyBand, MockSearchService};
 
     #[track_caller]
     fn check_snippet_fields_validation(snippet_fields: &[String]) -> anyhow::Result<()> {
@@ -1491,6 +2283,7 @@ mod tests {
         let indexing_settings = IndexingSettings::default();
         let search_settings = SearchSettings {
             default_search_fields: vec!["body".to_string()],
+            ..Default::default()
         };
         IndexMetadata::new(IndexConfig {
             index_id: index_id.to_string(),
@@ -1499,6 +2292,10 @@ mod tests {
             indexing_settings,
             search_settings,
             retention_policy: Default::default(),
+            ingest_settings: Default::default(),
+            gc_settings: Default::default(),
+            lifecycle_policy: None,
+            encryption: None,
         })
     }
 
@@ -1521,7 +2318,7 @@ mod tests {
             .index_config
             .doc_mapping
             .timestamp_field = None;
-        let (timestamp_field, query_ast, indexes_metas_for_leaf_req) =
+        let (timestamp_field, query_ast, indexes_metas_for_leaf_req, query_partition_ids) =
             validate_request_and_build_metadatas(
                 &[
                     index_metadata,
@@ -1534,6 +2331,7 @@ mod tests {
         assert_eq!(timestamp_field, Some("timestamp".to_string()));
         assert_eq!(query_ast, request_query_ast);
         assert_eq!(indexes_metas_for_leaf_req.len(), 3);
+        assert!(query_partition_ids.is_empty());
     }
 
     #[test]
@@ -1723,11 +2521,60 @@ mod tests {
             split_id: "split1".to_string(),
             segment_ord: 1,
             doc_id: 1,
+            dedup_key: None,
         };
         validate_sort_by_fields_and_search_after(&sort_fields, &Some(partial_hit), &schema)
             .unwrap();
     }
 
+    #[test]
+    fn test_validate_sort_by_expression() {
+        let sort_field = |field_name: &str| SortField {
+            field_name: field_name.to_string(),
+            sort_order: 0,
+            sort_datetime_format: None,
+        };
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_u64_field("response_bytes", FAST);
+        schema_builder.add_f64_field("duration_ms", FAST);
+        schema_builder.add_u64_field("status", INDEXED);
+        schema_builder.add_text_field("body", TEXT | FAST);
+        let schema = schema_builder.build();
+
+        validate_sort_by_fields_and_search_after(
+            &[sort_field("response_bytes / (duration_ms + 1)")],
+            &None,
+            &schema,
+        )
+        .unwrap();
+
+        for (expression, expected_error) in [
+            ("response_bytes / ", "failed to parse sort expression"),
+            ("1 + 2", "must reference at least one field"),
+            ("response_bytes / latency", "unknown field"),
+            ("status * 2", "please add the fast property"),
+            ("body * 2", "must reference numeric fields"),
+        ] {
+            let error =
+                validate_sort_by_fields_and_search_after(&[sort_field(expression)], &None, &schema)
+                    .unwrap_err();
+            assert!(
+                error.to_string().contains(expected_error),
+                "`{expression}`: {error}"
+            );
+        }
+        let error = validate_sort_by_fields_and_search_after(
+            &[SortField {
+                sort_datetime_format: Some(SortDatetimeFormat::UnixTimestampMillis as i32),
+                ..sort_field("response_bytes * 2")
+            }],
+            &None,
+            &schema,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("cannot have a timestamp format"));
+    }
+
     #[test]
     fn test_validate_sort_by_docid() {
         let sort_fields = vec![
@@ -1776,6 +2623,7 @@ mod tests {
             split_id: "split1".to_string(),
             segment_ord: 1,
             doc_id: 1,
+            dedup_key: None,
         };
         let error =
             validate_sort_by_fields_and_search_after(&sort_fields, &Some(partial_hit), &schema)
@@ -1806,6 +2654,7 @@ mod tests {
             split_id: "split1".to_string(),
             segment_ord: 1,
             doc_id: 1,
+            dedup_key: None,
         };
         let error =
             validate_sort_by_fields_and_search_after(&sort_fields, &Some(partial_hit), &schema)
@@ -1846,6 +2695,80 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_validate_dedup_field() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("host", STRING | FAST);
+        schema_builder.add_u64_field("id", FAST);
+        schema_builder.add_text_field("body", TEXT);
+        let schema = schema_builder.build();
+
+        validate_dedup_field("host", &schema).unwrap();
+        validate_dedup_field("id", &schema).unwrap();
+
+        let error = validate_dedup_field("body", &schema).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid argument: dedup field must be a fast field, please add the fast property to \
+             your field `body`"
+        );
+        let error = validate_dedup_field("unknown", &schema).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid argument: unknown field used in `dedup_field`: unknown"
+        );
+    }
+
+    #[test]
+    fn test_validate_group_by() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("host", STRING | FAST);
+        schema_builder.add_text_field("body", TEXT);
+        let schema = schema_builder.build();
+
+        let search_request =
+            |field: &str, max_groups: u32, max_hits_per_group: u32| SearchRequest {
+                group_by: Some(GroupBy {
+                    field: field.to_string(),
+                    max_groups,
+                    max_hits_per_group,
+                }),
+                ..Default::default()
+            };
+        validate_group_by(&search_request("host", 10, 3), &schema).unwrap();
+
+        let error = validate_group_by(&search_request("body", 10, 3), &schema).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid argument: group by field must be a fast field, please add the fast property \
+             to your field `body`"
+        );
+        let error = validate_group_by(&search_request("unknown", 10, 3), &schema).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid argument: unknown field used in `group_by`: unknown"
+        );
+        let error = validate_group_by(&search_request("host", 0, 3), &schema).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid argument: group by max_groups and max_hits_per_group must be strictly \
+             positive"
+        );
+        let error = validate_group_by(&search_request("host", 1_000, 11), &schema).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid argument: max value for max_groups * max_hits_per_group is 10_000, but got \
+             11000"
+        );
+        let mut request_with_offset = search_request("host", 10, 3);
+        request_with_offset.start_offset = 10;
+        let error = validate_group_by(&request_with_offset, &schema).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid argument: group_by cannot be used with search_after or start_offset"
+        );
+    }
+
     fn mock_partial_hit(
         split_id: &str,
         sort_value: u64,
@@ -1857,9 +2780,68 @@ mod tests {
             split_id: split_id.to_string(),
             segment_ord: 1,
             doc_id,
+            dedup_key: None,
         }
     }
 
+    #[test]
+    fn test_jobs_to_fetch_docs_requests() {
+        let index_uid_1 = IndexUid::new_with_random_ulid("test-index-1");
+        let index_uid_2 = IndexUid::new_with_random_ulid("test-index-2");
+        let indexes_metas_for_leaf_search: IndexesMetasForLeafSearch = [&index_uid_1, &index_uid_2]
+            .into_iter()
+            .map(|index_uid| {
+                let index_metas_for_leaf_search = IndexMetasForLeafSearch {
+                    index_uri: Uri::for_test("ram:///indexes/test-index"),
+                    doc_mapper_str: String::new(),
+                    encryption_config_str_opt: None,
+                };
+                (index_uid.clone(), index_metas_for_leaf_search)
+            })
+            .collect();
+        let fetch_docs_job = |index_uid: &IndexUid, split_id: &str| FetchDocsJob {
+            index_uid: index_uid.clone(),
+            offsets: SplitIdAndFooterOffsets {
+                split_id: split_id.to_string(),
+                ..Default::default()
+            },
+            partial_hits: vec![
+                mock_partial_hit(split_id, 1, 1),
+                mock_partial_hit(split_id, 2, 2),
+            ],
+        };
+        // The jobs of an index are coalesced even if they are not contiguous.
+        let jobs = vec![
+            fetch_docs_job(&index_uid_1, "split-1"),
+            fetch_docs_job(&index_uid_2, "split-2"),
+            fetch_docs_job(&index_uid_1, "split-3"),
+            fetch_docs_job(&index_uid_1, "split-4"),
+        ];
+        let fetch_docs_requests =
+            jobs_to_fetch_docs_requests(None, &indexes_metas_for_leaf_search, jobs, 2).unwrap();
+        assert_eq!(fetch_docs_requests.len(), 3);
+
+        let mut split_ids_per_request: Vec<Vec<&str>> = fetch_docs_requests
+            .iter()
+            .map(|fetch_docs_request| {
+                assert_eq!(
+                    fetch_docs_request.partial_hits.len(),
+                    2 * fetch_docs_request.split_offsets.len()
+                );
+                fetch_docs_request
+                    .split_offsets
+                    .iter()
+                    .map(|split_offsets| split_offsets.split_id.as_str())
+                    .collect()
+            })
+            .collect();
+        split_ids_per_request.sort();
+        assert_eq!(
+            split_ids_per_request,
+            [vec!["split-1", "split-3"], vec!["split-2"], vec!["split-4"]]
+        );
+    }
+
     fn mock_partial_hit_opt_sort_value(
         split_id: &str,
         sort_value: Option<u64>,
@@ -1871,6 +2853,7 @@ mod tests {
             split_id: split_id.to_string(),
             segment_ord: 1,
             doc_id,
+            dedup_key: None,
         }
     }
 
@@ -2061,6 +3044,66 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_root_estimate_search_cost() {
+        let search_request = quickwit_proto::search::SearchRequest {
+            index_id_patterns: vec!["test-index".to_string()],
+            query_ast: qast_json_helper("test", &["body"]),
+            max_hits: 10,
+            ..Default::default()
+        };
+        let mut metastore = MetastoreServiceClient::mock();
+        let index_metadata = IndexMetadata::for_test("test-index", "ram:///test-index");
+        let index_uid = index_metadata.index_uid.clone();
+        metastore
+            .expect_list_indexes_metadata()
+            .returning(move |_index_ids_query| {
+                Ok(ListIndexesMetadataResponse::try_from_indexes_metadata(vec![
+                    index_metadata.clone()
+                ])
+                .unwrap())
+            });
+        metastore
+            .expect_list_splits()
+            .returning(move |_list_splits_request| {
+                let splits = vec![
+                    MockSplitBuilder::new("split1")
+                        .with_index_uid(&index_uid)
+                        .build(),
+                    MockSplitBuilder::new("split2")
+                        .with_index_uid(&index_uid)
+                        .build(),
+                ];
+                let splits_response = ListSplitsResponse::try_from_splits(splits).unwrap();
+                Ok(ServiceStream::from(vec![Ok(splits_response)]))
+            });
+        // The estimate must not reach the searchers.
+        let mock_search_service = MockSearchService::new();
+        let searcher_pool = searcher_pool_for_test([("127.0.0.1:1001", mock_search_service)]);
+        let search_job_placer = SearchJobPlacer::new(searcher_pool);
+        let cluster_client = ClusterClient::new(search_job_placer);
+
+        let searcher_context = SearcherContext::for_test();
+        let search_cost_estimate = root_estimate_search_cost(
+            &searcher_context,
+            search_request,
+            MetastoreServiceClient::from(metastore),
+            &cluster_client,
+        )
+        .await
+        .unwrap();
+        assert_eq!(search_cost_estimate.num_splits, 2);
+        assert_eq!(search_cost_estimate.num_docs, 20);
+        assert_eq!(search_cost_estimate.num_footer_bytes, 200);
+        assert!(search_cost_estimate.num_warmup_bytes > 0);
+        assert_eq!(search_cost_estimate.num_fetch_docs_bytes, 10 * 25);
+        assert_eq!(
+            search_cost_estimate.num_bytes,
+            200 + search_cost_estimate.num_warmup_bytes + 250
+        );
+        assert_eq!(search_cost_estimate.latency_band, LatencyBand::Fast);
+    }
+
     #[tokio::test]
     async fn test_root_search_multiple_splits() -> anyhow::Result<()> {
         let search_request = quickwit_proto::search::SearchRequest {
@@ -2201,6 +3244,7 @@ mod tests {
                             split_id: "split1".to_string(),
                             segment_ord: 0,
                             doc_id: 0,
+                            dedup_key: None,
                         },
                         quickwit_proto::search::PartialHit {
                             sort_value: None,
@@ -2208,6 +3252,7 @@ mod tests {
                             split_id: "split1".to_string(),
                             segment_ord: 0,
                             doc_id: 1,
+                            dedup_key: None,
                         },
                     ],
                     failed_splits: Vec::new(),
@@ -2235,6 +3280,7 @@ mod tests {
                             split_id: "split2".to_string(),
                             segment_ord: 0,
                             doc_id: 1,
+                            dedup_key: None,
                         },
                         quickwit_proto::search::PartialHit {
                             sort_value: Some(SortValue::I64(1i64).into()),
@@ -2242,6 +3288,7 @@ mod tests {
                             split_id: "split2".to_string(),
                             segment_ord: 0,
                             doc_id: 0,
+                            dedup_key: None,
                         },
                         quickwit_proto::search::PartialHit {
                             sort_value: None,
@@ -2249,6 +3296,7 @@ mod tests {
                             split_id: "split2".to_string(),
                             segment_ord: 0,
                             doc_id: 2,
+                            dedup_key: None,
                         },
                     ],
                     failed_splits: Vec::new(),
@@ -2288,6 +3336,7 @@ mod tests {
                 doc_id: 1,
                 sort_value: Some(SortValue::I64(-1i64).into()),
                 sort_value2: None,
+                dedup_key: None,
             }
         );
         assert_eq!(
@@ -2298,6 +3347,7 @@ mod tests {
                 doc_id: 0,
                 sort_value: Some(SortValue::I64(1i64).into()),
                 sort_value2: None,
+                dedup_key: None,
             }
         );
         assert_eq!(
@@ -2308,6 +3358,7 @@ mod tests {
                 doc_id: 0,
                 sort_value: Some(SortValue::U64(2u64).into()),
                 sort_value2: None,
+                dedup_key: None,
             }
         );
         assert_eq!(
@@ -2318,6 +3369,7 @@ mod tests {
                 doc_id: 1,
                 sort_value: None,
                 sort_value2: None,
+                dedup_key: None,
             }
         );
         assert_eq!(
@@ -2328,6 +3380,7 @@ mod tests {
                 doc_id: 2,
                 sort_value: None,
                 sort_value2: None,
+                dedup_key: None,
             }
         );
         Ok(())
@@ -2382,6 +3435,7 @@ mod tests {
                             split_id: "split1".to_string(),
                             segment_ord: 0,
                             doc_id: 0,
+                            dedup_key: None,
                         },
                         quickwit_proto::search::PartialHit {
                             sort_value: None,
@@ -2389,6 +3443,7 @@ mod tests {
                             split_id: "split1".to_string(),
                             segment_ord: 0,
                             doc_id: 1,
+                            dedup_key: None,
                         },
                     ],
                     failed_splits: Vec::new(),
@@ -2416,6 +3471,7 @@ mod tests {
                             split_id: "split2".to_string(),
                             segment_ord: 0,
                             doc_id: 0,
+                            dedup_key: None,
                         },
                         quickwit_proto::search::PartialHit {
                             sort_value: Some(SortValue::I64(-1i64).into()),
@@ -2423,6 +3479,7 @@ mod tests {
                             split_id: "split2".to_string(),
                             segment_ord: 0,
                             doc_id: 1,
+                            dedup_key: None,
                         },
                         quickwit_proto::search::PartialHit {
                             sort_value: None,
@@ -2430,6 +3487,7 @@ mod tests {
                             split_id: "split2".to_string(),
                             segment_ord: 0,
                             doc_id: 2,
+                            dedup_key: None,
                         },
                     ],
                     failed_splits: Vec::new(),
@@ -2469,6 +3527,7 @@ mod tests {
                 doc_id: 0,
                 sort_value: Some(SortValue::U64(2u64).into()),
                 sort_value2: None,
+                dedup_key: None,
             }
         );
         assert_eq!(
@@ -2479,6 +3538,7 @@ mod tests {
                 doc_id: 0,
                 sort_value: Some(SortValue::I64(1i64).into()),
                 sort_value2: None,
+                dedup_key: None,
             }
         );
         assert_eq!(
@@ -2489,6 +3549,7 @@ mod tests {
                 doc_id: 1,
                 sort_value: Some(SortValue::I64(-1i64).into()),
                 sort_value2: None,
+                dedup_key: None,
             }
         );
         assert_eq!(
@@ -2499,6 +3560,7 @@ mod tests {
                 doc_id: 2,
                 sort_value: None,
                 sort_value2: None,
+                dedup_key: None,
             }
         );
         assert_eq!(
@@ -2509,6 +3571,7 @@ mod tests {
                 doc_id: 1,
                 sort_value: None,
                 sort_value2: None,
+                dedup_key: None,
             }
         );
         Ok(())
@@ -2796,31 +3859,172 @@ mod tests {
             });
         let mut first_call = true;
         let mut mock_search_service = MockSearchService::new();
-        mock_search_service.expect_leaf_search().times(2).returning(
-            move |_leaf_search_req: quickwit_proto::search::LeafSearchRequest| {
-                // requests from split 2 arrive here - simulate failure, then success
-                if first_call {
-                    first_call = false;
-                    Ok(quickwit_proto::search::LeafSearchResponse {
-                        num_hits: 0,
-                        partial_hits: Vec::new(),
-                        failed_splits: vec![SplitSearchError {
-                            error: "mock_error".to_string(),
-                            split_id: "split1".to_string(),
-                            retryable_error: true,
-                        }],
-                        num_attempted_splits: 1,
-                        ..Default::default()
-                    })
-                } else {
-                    Ok(quickwit_proto::search::LeafSearchResponse {
-                        num_hits: 1,
-                        partial_hits: vec![mock_partial_hit("split1", 2, 2)],
-                        failed_splits: Vec::new(),
-                        num_attempted_splits: 1,
-                        ..Default::default()
-                    })
+        mock_search_service.expect_leaf_search().times(2).returning(
+            move |_leaf_search_req: quickwit_proto::search::LeafSearchRequest| {
+                // requests from split 2 arrive here - simulate failure, then success
+                if first_call {
+                    first_call = false;
+                    Ok(quickwit_proto::search::LeafSearchResponse {
+                        num_hits: 0,
+                        partial_hits: Vec::new(),
+                        failed_splits: vec![SplitSearchError {
+                            error: "mock_error".to_string(),
+                            split_id: "split1".to_string(),
+                            retryable_error: true,
+                        }],
+                        num_attempted_splits: 1,
+                        ..Default::default()
+                    })
+                } else {
+                    Ok(quickwit_proto::search::LeafSearchResponse {
+                        num_hits: 1,
+                        partial_hits: vec![mock_partial_hit("split1", 2, 2)],
+                        failed_splits: Vec::new(),
+                        num_attempted_splits: 1,
+                        ..Default::default()
+                    })
+                }
+            },
+        );
+        mock_search_service.expect_fetch_docs().returning(
+            |fetch_docs_req: quickwit_proto::search::FetchDocsRequest| {
+                Ok(quickwit_proto::search::FetchDocsResponse {
+                    hits: get_doc_for_fetch_req(fetch_docs_req),
+                })
+            },
+        );
+        let searcher_pool = searcher_pool_for_test([("127.0.0.1:1001", mock_search_service)]);
+        let search_job_placer = SearchJobPlacer::new(searcher_pool);
+        let cluster_client = ClusterClient::new(search_job_placer.clone());
+        let search_response = root_search(
+            &SearcherContext::for_test(),
+            search_request,
+            MetastoreServiceClient::from(metastore),
+            &cluster_client,
+        )
+        .await
+        .unwrap();
+        assert_eq!(search_response.num_hits, 1);
+        assert_eq!(search_response.hits.len(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_root_search_single_split_retry_single_node_fails() -> anyhow::Result<()> {
+        let search_request = quickwit_proto::search::SearchRequest {
+            index_id_patterns: vec!["test-index".to_string()],
+            query_ast: qast_json_helper("test", &["body"]),
+            max_hits: 10,
+            ..Default::default()
+        };
+        let mut metastore = MetastoreServiceClient::mock();
+        let index_metadata = IndexMetadata::for_test("test-index", "ram:///test-index");
+        let index_uid = index_metadata.index_uid.clone();
+        metastore
+            .expect_list_indexes_metadata()
+            .returning(move |_index_ids_query| {
+                Ok(ListIndexesMetadataResponse::try_from_indexes_metadata(vec![
+                    index_metadata.clone()
+                ])
+                .unwrap())
+            });
+        metastore.expect_list_splits().returning(move |_filter| {
+            let splits = vec![MockSplitBuilder::new("split1")
+                .with_index_uid(&index_uid)
+                .build()];
+            let splits_response = ListSplitsResponse::try_from_splits(splits).unwrap();
+            Ok(ServiceStream::from(vec![Ok(splits_response)]))
+        });
+
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service.expect_leaf_search().times(2).returning(
+            move |_leaf_search_req: quickwit_proto::search::LeafSearchRequest| {
+                Ok(quickwit_proto::search::LeafSearchResponse {
+                    num_hits: 0,
+                    partial_hits: Vec::new(),
+                    failed_splits: vec![SplitSearchError {
+                        error: "mock_error".to_string(),
+                        split_id: "split1".to_string(),
+                        retryable_error: true,
+                    }],
+                    num_attempted_splits: 1,
+                    ..Default::default()
+                })
+            },
+        );
+        mock_search_service.expect_fetch_docs().returning(
+            |_fetch_docs_req: quickwit_proto::search::FetchDocsRequest| {
+                Err(SearchError::Internal("mockerr docs".to_string()))
+            },
+        );
+        let searcher_pool = searcher_pool_for_test([("127.0.0.1:1001", mock_search_service)]);
+        let search_job_placer = SearchJobPlacer::new(searcher_pool);
+        let cluster_client = ClusterClient::new(search_job_placer.clone());
+        let search_response = root_search(
+            &SearcherContext::for_test(),
+            search_request,
+            MetastoreServiceClient::from(metastore),
+            &cluster_client,
+        )
+        .await;
+        assert!(search_response.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_root_search_reports_skipped_splits() -> anyhow::Result<()> {
+        let search_request = quickwit_proto::search::SearchRequest {
+            index_id_patterns: vec!["test-index".to_string()],
+            query_ast: qast_json_helper("test", &["body"]),
+            max_hits: 10,
+            ..Default::default()
+        };
+        let mut metastore = MetastoreServiceClient::mock();
+        let index_metadata = IndexMetadata::for_test("test-index", "ram:///test-index");
+        let index_uid = index_metadata.index_uid.clone();
+        metastore
+            .expect_list_indexes_metadata()
+            .returning(move |_index_ids_query| {
+                Ok(ListIndexesMetadataResponse::try_from_indexes_metadata(vec![
+                    index_metadata.clone()
+                ])
+                .unwrap())
+            });
+        metastore.expect_list_splits().returning(move |_filter| {
+            let splits = vec![
+                MockSplitBuilder::new("split1")
+                    .with_index_uid(&index_uid)
+                    .build(),
+                MockSplitBuilder::new("split2")
+                    .with_index_uid(&index_uid)
+                    .build(),
+            ];
+            let splits_response = ListSplitsResponse::try_from_splits(splits).unwrap();
+            Ok(ServiceStream::from(vec![Ok(splits_response)]))
+        });
+
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service.expect_leaf_search().returning(
+            move |leaf_search_req: quickwit_proto::search::LeafSearchRequest| {
+                let mut leaf_search_response = quickwit_proto::search::LeafSearchResponse {
+                    num_attempted_splits: leaf_search_req.split_offsets.len() as u64,
+                    ..Default::default()
+                };
+                for split_offsets in &leaf_search_req.split_offsets {
+                    if split_offsets.split_id == "split2" {
+                        leaf_search_response
+                            .skipped_split_ids
+                            .push(split_offsets.split_id.clone());
+                    } else {
+                        leaf_search_response.num_hits += 1;
+                        leaf_search_response.partial_hits.push(mock_partial_hit(
+                            &split_offsets.split_id,
+                            1,
+                            1,
+                        ));
+                    }
                 }
+                Ok(leaf_search_response)
             },
         );
         mock_search_service.expect_fetch_docs().returning(
@@ -2832,26 +4036,29 @@ mod tests {
         );
         let searcher_pool = searcher_pool_for_test([("127.0.0.1:1001", mock_search_service)]);
         let search_job_placer = SearchJobPlacer::new(searcher_pool);
-        let cluster_client = ClusterClient::new(search_job_placer.clone());
+        let cluster_client = ClusterClient::new(search_job_placer);
+
         let search_response = root_search(
             &SearcherContext::for_test(),
             search_request,
             MetastoreServiceClient::from(metastore),
             &cluster_client,
         )
-        .await
-        .unwrap();
+        .await?;
         assert_eq!(search_response.num_hits, 1);
         assert_eq!(search_response.hits.len(), 1);
+        assert!(search_response.failed_splits.is_empty());
+        assert_eq!(search_response.skipped_split_ids, ["split2"]);
         Ok(())
     }
 
     #[tokio::test]
-    async fn test_root_search_single_split_retry_single_node_fails() -> anyhow::Result<()> {
+    async fn test_root_search_allow_partial_results() -> anyhow::Result<()> {
         let search_request = quickwit_proto::search::SearchRequest {
             index_id_patterns: vec!["test-index".to_string()],
             query_ast: qast_json_helper("test", &["body"]),
             max_hits: 10,
+            allow_partial_results: true,
             ..Default::default()
         };
         let mut metastore = MetastoreServiceClient::mock();
@@ -2866,45 +4073,81 @@ mod tests {
                 .unwrap())
             });
         metastore.expect_list_splits().returning(move |_filter| {
-            let splits = vec![MockSplitBuilder::new("split1")
-                .with_index_uid(&index_uid)
-                .build()];
+            let splits = vec![
+                MockSplitBuilder::new("split1")
+                    .with_index_uid(&index_uid)
+                    .build(),
+                MockSplitBuilder::new("split2")
+                    .with_index_uid(&index_uid)
+                    .build(),
+            ];
             let splits_response = ListSplitsResponse::try_from_splits(splits).unwrap();
             Ok(ServiceStream::from(vec![Ok(splits_response)]))
         });
 
         let mut mock_search_service = MockSearchService::new();
-        mock_search_service.expect_leaf_search().times(2).returning(
-            move |_leaf_search_req: quickwit_proto::search::LeafSearchRequest| {
-                Ok(quickwit_proto::search::LeafSearchResponse {
-                    num_hits: 0,
-                    partial_hits: Vec::new(),
-                    failed_splits: vec![SplitSearchError {
-                        error: "mock_error".to_string(),
-                        split_id: "split1".to_string(),
-                        retryable_error: true,
-                    }],
-                    num_attempted_splits: 1,
+        mock_search_service.expect_leaf_search().returning(
+            move |leaf_search_req: quickwit_proto::search::LeafSearchRequest| {
+                let mut leaf_search_response = quickwit_proto::search::LeafSearchResponse {
+                    num_attempted_splits: leaf_search_req.split_offsets.len() as u64,
                     ..Default::default()
-                })
+                };
+                for split_offsets in &leaf_search_req.split_offsets {
+                    if split_offsets.split_id == "split1" {
+                        leaf_search_response.failed_splits.push(SplitSearchError {
+                            error: "mock_error".to_string(),
+                            split_id: "split1".to_string(),
+                            retryable_error: false,
+                        });
+                    } else {
+                        leaf_search_response.num_hits += 1;
+                        leaf_search_response.partial_hits.push(mock_partial_hit(
+                            &split_offsets.split_id,
+                            1,
+                            1,
+                        ));
+                    }
+                }
+                Ok(leaf_search_response)
             },
         );
         mock_search_service.expect_fetch_docs().returning(
-            |_fetch_docs_req: quickwit_proto::search::FetchDocsRequest| {
-                Err(SearchError::Internal("mockerr docs".to_string()))
+            |fetch_docs_req: quickwit_proto::search::FetchDocsRequest| {
+                Ok(quickwit_proto::search::FetchDocsResponse {
+                    hits: get_doc_for_fetch_req(fetch_docs_req),
+                })
             },
         );
         let searcher_pool = searcher_pool_for_test([("127.0.0.1:1001", mock_search_service)]);
         let search_job_placer = SearchJobPlacer::new(searcher_pool);
         let cluster_client = ClusterClient::new(search_job_placer.clone());
+        let metastore = MetastoreServiceClient::from(metastore);
+
         let search_response = root_search(
+            &SearcherContext::for_test(),
+            search_request.clone(),
+            metastore.clone(),
+            &cluster_client,
+        )
+        .await?;
+        assert_eq!(search_response.num_hits, 1);
+        assert_eq!(search_response.hits.len(), 1);
+        assert_eq!(search_response.failed_splits.len(), 1);
+        assert_eq!(search_response.failed_splits[0].split_id, "split1");
+
+        let search_request = quickwit_proto::search::SearchRequest {
+            allow_partial_results: false,
+            ..search_request
+        };
+        let search_error = root_search(
             &SearcherContext::for_test(),
             search_request,
-            MetastoreServiceClient::from(metastore),
+            metastore,
             &cluster_client,
         )
-        .await;
-        assert!(search_response.is_err());
+        .await
+        .unwrap_err();
+        assert!(matches!(search_error, SearchError::Internal(_)));
         Ok(())
     }
 
@@ -3705,4 +4948,254 @@ mod tests {
         );
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_root_count_multi_indices() {
+        let search_request = quickwit_proto::search::SearchRequest {
+            index_id_patterns: vec!["test-index-*".to_string()],
+            query_ast: qast_json_helper("test", &["body"]),
+            max_hits: 10,
+            sort_fields: vec![SortField {
+                field_name: "timestamp".to_string(),
+                sort_order: SortOrder::Desc as i32,
+                sort_datetime_format: None,
+            }],
+            ..Default::default()
+        };
+        let mut metastore = MetastoreServiceClient::mock();
+        let index_metadata_1 = IndexMetadata::for_test("test-index-1", "ram:///test-index-1");
+        let index_uid_1 = index_metadata_1.index_uid.clone();
+        let index_metadata_2 =
+            index_metadata_for_multi_indexes_test("test-index-2", "ram:///test-index-2");
+        let index_metadata_3 =
+            index_metadata_for_multi_indexes_test("test-index-3", "ram:///test-index-3");
+        let index_uid_3 = index_metadata_3.index_uid.clone();
+        metastore.expect_list_indexes_metadata().return_once(
+            move |_list_indexes_metadata_request| {
+                Ok(ListIndexesMetadataResponse::try_from_indexes_metadata(vec![
+                    index_metadata_1,
+                    index_metadata_2,
+                    index_metadata_3,
+                ])
+                .unwrap())
+            },
+        );
+        metastore
+            .expect_list_splits()
+            .return_once(move |_list_splits_request| {
+                let splits = vec![
+                    MockSplitBuilder::new("index-1-split-1")
+                        .with_index_uid(&index_uid_1)
+                        .build(),
+                    MockSplitBuilder::new("index-1-split-2")
+                        .with_index_uid(&index_uid_1)
+                        .build(),
+                    MockSplitBuilder::new("index-3-split-1")
+                        .with_index_uid(&index_uid_3)
+                        .build(),
+                ];
+                let splits_response = ListSplitsResponse::try_from_splits(splits).unwrap();
+                Ok(ServiceStream::from(vec![Ok(splits_response)]))
+            });
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_leaf_search()
+            .times(2)
+            .withf(|leaf_search_req| {
+                let search_request = leaf_search_req.search_request.as_ref().unwrap();
+                search_request.max_hits == 0
+                    && search_request.sort_fields.is_empty()
+                    && search_request.count_hits() == CountHits::CountAll
+            })
+            .returning(
+                |leaf_search_req: quickwit_proto::search::LeafSearchRequest| {
+                    Ok(quickwit_proto::search::LeafSearchResponse {
+                        num_hits: leaf_search_req.split_offsets.len() as u64 * 5,
+                        num_attempted_splits: leaf_search_req.split_offsets.len() as u64,
+                        ..Default::default()
+                    })
+                },
+            );
+        // Counting must not fetch any document.
+        mock_search_service.expect_fetch_docs().never();
+        let searcher_pool = searcher_pool_for_test([("127.0.0.1:1001", mock_search_service)]);
+        let search_job_placer = SearchJobPlacer::new(searcher_pool);
+        let cluster_client = ClusterClient::new(search_job_placer);
+        let count_response = root_count(
+            &SearcherContext::for_test(),
+            search_request,
+            MetastoreServiceClient::from(metastore),
+            &cluster_client,
+        )
+        .await
+        .unwrap();
+        assert_eq!(count_response.num_hits, 15);
+        assert_eq!(
+            count_response.num_hits_per_index.unwrap(),
+            BTreeMap::from_iter([
+                ("test-index-1".to_string(), 10),
+                ("test-index-2".to_string(), 0),
+                ("test-index-3".to_string(), 5),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_root_search_multi_indices_balanced_federation() -> anyhow::Result<()> {
+        let search_request = quickwit_proto::search::SearchRequest {
+            index_id_patterns: vec!["test-index-*".to_string()],
+            query_ast: qast_json_helper("test", &["body"]),
+            max_hits: 2,
+            federation: Federation::Balanced.into(),
+            ..Default::default()
+        };
+        let mut metastore = MetastoreServiceClient::mock();
+        let index_metadata_1 = IndexMetadata::for_test("test-index-1", "ram:///test-index-1");
+        let index_uid_1 = index_metadata_1.index_uid.clone();
+        let index_metadata_2 =
+            index_metadata_for_multi_indexes_test("test-index-2", "ram:///test-index-2");
+        let index_uid_2 = index_metadata_2.index_uid.clone();
+        metastore.expect_list_indexes_metadata().return_once(
+            move |_list_indexes_metadata_request| {
+                Ok(ListIndexesMetadataResponse::try_from_indexes_metadata(vec![
+                    index_metadata_1,
+                    index_metadata_2,
+                ])
+                .unwrap())
+            },
+        );
+        metastore
+            .expect_list_splits()
+            .return_once(move |_list_splits_request| {
+                let splits = vec![
+                    MockSplitBuilder::new("index-1-split-1")
+                        .with_index_uid(&index_uid_1)
+                        .build(),
+                    MockSplitBuilder::new("index-2-split-1")
+                        .with_index_uid(&index_uid_2)
+                        .build(),
+                ];
+                let splits_response = ListSplitsResponse::try_from_splits(splits).unwrap();
+                Ok(ServiceStream::from(vec![Ok(splits_response)]))
+            });
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service.expect_leaf_search().times(2).returning(
+            |leaf_search_req: quickwit_proto::search::LeafSearchRequest| {
+                // The hits of the first index all rank before the hits of the second index.
+                let split_id = &leaf_search_req.split_offsets[0].split_id;
+                let partial_hits = if split_id == "index-1-split-1" {
+                    vec![
+                        mock_partial_hit(split_id, 30, 1),
+                        mock_partial_hit(split_id, 20, 2),
+                    ]
+                } else {
+                    vec![
+                        mock_partial_hit(split_id, 10, 1),
+                        mock_partial_hit(split_id, 5, 2),
+                    ]
+                };
+                Ok(quickwit_proto::search::LeafSearchResponse {
+                    num_hits: 2,
+                    partial_hits,
+                    failed_splits: Vec::new(),
+                    num_attempted_splits: 1,
+                    ..Default::default()
+                })
+            },
+        );
+        mock_search_service
+            .expect_fetch_docs()
+            .times(2)
+            .withf(|fetch_docs_req: &FetchDocsRequest| {
+                fetch_docs_req.partial_hits.len() == 1 && fetch_docs_req.partial_hits[0].doc_id == 1
+            })
+            .returning(|fetch_docs_req| {
+                Ok(quickwit_proto::search::FetchDocsResponse {
+                    hits: get_doc_for_fetch_req(fetch_docs_req),
+                })
+            });
+        let searcher_pool = searcher_pool_for_test([("127.0.0.1:1001", mock_search_service)]);
+        let search_job_placer = SearchJobPlacer::new(searcher_pool);
+        let cluster_client = ClusterClient::new(search_job_placer.clone());
+        let search_response = root_search(
+            &SearcherContext::for_test(),
+            search_request,
+            MetastoreServiceClient::from(metastore),
+            &cluster_client,
+        )
+        .await
+        .unwrap();
+        assert_eq!(search_response.num_hits, 4);
+        assert_eq!(
+            search_response
+                .hits
+                .iter()
+                .map(|hit| &hit.index_id)
+                .collect_vec(),
+            vec!["test-index-1", "test-index-2"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_select_preview_splits() {
+        let split_metadata =
+            |split_id: &str, time_range_end: Option<i64>, num_docs: usize| SplitMetadata {
+                split_id: split_id.to_string(),
+                num_docs,
+                time_range: time_range_end.map(|end| 0..=end),
+                ..Default::default()
+            };
+        let mut split_metadatas = vec![
+            split_metadata("split-1", Some(100), 10),
+            split_metadata("split-2", None, 20),
+            split_metadata("split-3", Some(300), 30),
+            split_metadata("split-4", Some(200), 40),
+        ];
+        let preview_coverage = select_preview_splits(&mut split_metadatas, 2);
+        let split_ids: Vec<&str> = split_metadatas
+            .iter()
+            .map(|split_metadata| split_metadata.split_id.as_str())
+            .collect();
+        assert_eq!(split_ids, ["split-3", "split-4"]);
+        assert_eq!(preview_coverage, 0.7);
+
+        let mut split_metadatas = vec![split_metadata("split-1", Some(100), 10)];
+        let preview_coverage = select_preview_splits(&mut split_metadatas, 2);
+        assert_eq!(split_metadatas.len(), 1);
+        assert_eq!(preview_coverage, 1.0);
+
+        let mut split_metadatas = Vec::new();
+        let preview_coverage = select_preview_splits(&mut split_metadatas, 2);
+        assert_eq!(preview_coverage, 1.0);
+    }
+
+    #[test]
+    fn test_prune_splits_by_partition() {
+        let index_uid_1 = IndexUid::from("test-index-1:0");
+        let index_uid_2 = IndexUid::from("test-index-2:0");
+        let split_metadata =
+            |split_id: &str, index_uid: &IndexUid, partition_id: u64| SplitMetadata {
+                split_id: split_id.to_string(),
+                index_uid: index_uid.clone(),
+                partition_id,
+                ..Default::default()
+            };
+        let mut split_metadatas = vec![
+            split_metadata("split-1", &index_uid_1, 1),
+            split_metadata("split-2", &index_uid_1, 2),
+            split_metadata("split-3", &index_uid_1, OTHER_PARTITION_ID),
+            split_metadata("split-4", &index_uid_2, 2),
+        ];
+        prune_splits_by_partition(&mut split_metadatas, &HashMap::new());
+        assert_eq!(split_metadatas.len(), 4);
+
+        let query_partition_ids = HashMap::from_iter([(index_uid_1.clone(), 1)]);
+        prune_splits_by_partition(&mut split_metadatas, &query_partition_ids);
+        let split_ids: Vec<&str> = split_metadatas
+            .iter()
+            .map(|split_metadata| split_metadata.split_id.as_str())
+            .collect();
+        assert_eq!(split_ids, ["split-1", "split-3", "split-4"]);
+    }
 }
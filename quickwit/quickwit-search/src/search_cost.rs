@@ -0,0 +1,280 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{HashMap, HashSet};
+
+use quickwit_doc_mapper::WarmupInfo;
+use quickwit_metastore::SplitMetadata;
+use quickwit_proto::types::IndexUid;
+use serde::{Deserialize, Serialize};
+
+// The sizes of the data structures loaded by a search are not recorded in the metastore, so they
+// are derived from the number of documents of the splits. The constants below are deliberately
+// pessimistic.
+
+/// Bytes per document of the posting list of a term (doc IDs and term frequencies).
+const POSTINGS_NUM_BYTES_PER_DOC: u64 = 4;
+/// Bytes per document of the positions of a term.
+const POSITIONS_NUM_BYTES_PER_DOC: u64 = 4;
+/// Bytes per document of the term dictionary and posting lists of an entire field.
+const FULL_FIELD_NUM_BYTES_PER_DOC: u64 = 16;
+/// Bytes per document of a fast field column.
+const FAST_FIELD_NUM_BYTES_PER_DOC: u64 = 8;
+/// Bytes per document of the field norms of a field.
+const FIELD_NORMS_NUM_BYTES_PER_DOC: u64 = 1;
+
+/// Time spent searching a split whose data is not cached, excluding downloads.
+const SPLIT_SEARCH_SECS: f64 = 0.2;
+/// Download throughput of a searcher from the object storage.
+const DOWNLOAD_NUM_BYTES_PER_SEC: f64 = 100_000_000.0;
+
+/// Rough latency of a search request.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LatencyBand {
+    /// Under a second.
+    #[default]
+    Fast,
+    /// Between one and ten seconds.
+    Moderate,
+    /// Over ten seconds.
+    Slow,
+}
+
+impl LatencyBand {
+    fn from_secs(secs: f64) -> Self {
+        if secs < 1.0 {
+            LatencyBand::Fast
+        } else if secs < 10.0 {
+            LatencyBand::Moderate
+        } else {
+            LatencyBand::Slow
+        }
+    }
+}
+
+/// Estimated cost of a search request, computed without executing it.
+///
+/// The byte counts assume that none of the data is cached by the searchers.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct SearchCostEstimate {
+    /// Number of splits to search.
+    pub num_splits: u64,
+    /// Number of documents of the splits to search.
+    pub num_docs: u64,
+    /// Number of bytes of the split footers, including the hotcaches, to download.
+    pub num_footer_bytes: u64,
+    /// Estimated number of bytes to download to warm up the data structures used by the query.
+    pub num_warmup_bytes: u64,
+    /// Estimated number of bytes to download to fetch the documents returned.
+    pub num_fetch_docs_bytes: u64,
+    /// Estimated total number of bytes to download.
+    pub num_bytes: u64,
+    /// Rough latency of the request.
+    pub latency_band: LatencyBand,
+}
+
+/// Data structures loaded by a search from every split of an index, as described by the warmup
+/// info of the query and collector.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(crate) struct WarmupFootprint {
+    num_full_fields: u64,
+    num_terms: u64,
+    num_terms_with_positions: u64,
+    num_fast_fields: u64,
+    num_field_norms: u64,
+}
+
+impl WarmupFootprint {
+    pub fn from_warmup_info(warmup_info: &WarmupInfo) -> Self {
+        let num_terms_with_positions = warmup_info
+            .terms_grouped_by_field
+            .values()
+            .flat_map(|terms| terms.values())
+            .filter(|&&with_positions| with_positions)
+            .count();
+        let num_terms = warmup_info
+            .terms_grouped_by_field
+            .values()
+            .map(|terms| terms.len())
+            .sum::<usize>();
+        let num_term_ranges = warmup_info
+            .term_ranges_grouped_by_field
+            .values()
+            .map(|term_ranges| term_ranges.len())
+            .sum::<usize>();
+        let num_field_norms = if warmup_info.field_norms {
+            let indexed_fields: HashSet<_> = warmup_info
+                .terms_grouped_by_field
+                .keys()
+                .chain(warmup_info.term_ranges_grouped_by_field.keys())
+                .chain(warmup_info.term_dict_fields.iter())
+                .collect();
+            indexed_fields.len()
+        } else {
+            0
+        };
+        WarmupFootprint {
+            // A term range may span the whole term dictionary.
+            num_full_fields: (warmup_info.term_dict_fields.len() + num_term_ranges) as u64,
+            num_terms: num_terms as u64,
+            num_terms_with_positions: num_terms_with_positions as u64,
+            num_fast_fields: warmup_info.fast_field_names.len() as u64,
+            num_field_norms: num_field_norms as u64,
+        }
+    }
+
+    fn num_bytes_per_doc(&self) -> u64 {
+        self.num_full_fields * FULL_FIELD_NUM_BYTES_PER_DOC
+            + self.num_terms * POSTINGS_NUM_BYTES_PER_DOC
+            + self.num_terms_with_positions * POSITIONS_NUM_BYTES_PER_DOC
+            + self.num_fast_fields * FAST_FIELD_NUM_BYTES_PER_DOC
+            + self.num_field_norms * FIELD_NORMS_NUM_BYTES_PER_DOC
+    }
+}
+
+/// Estimates the cost of searching `split_metadatas` and fetching `num_hits_to_fetch` documents.
+pub(crate) fn estimate_search_cost(
+    split_metadatas: &[SplitMetadata],
+    warmup_footprints: &HashMap<IndexUid, WarmupFootprint>,
+    num_hits_to_fetch: u64,
+    max_num_concurrent_split_searches: usize,
+) -> SearchCostEstimate {
+    let mut num_docs = 0;
+    let mut num_footer_bytes = 0;
+    let mut num_warmup_bytes = 0;
+    let mut num_uncompressed_docs_bytes = 0;
+
+    for split_metadata in split_metadatas {
+        let split_num_docs = split_metadata.num_docs as u64;
+        let warmup_footprint = warmup_footprints
+            .get(&split_metadata.index_uid)
+            .copied()
+            .unwrap_or_default();
+        num_docs += split_num_docs;
+        num_footer_bytes += split_metadata.footer_offsets.end - split_metadata.footer_offsets.start;
+        num_warmup_bytes += split_num_docs * warmup_footprint.num_bytes_per_doc();
+        num_uncompressed_docs_bytes += split_metadata.uncompressed_docs_size_in_bytes;
+    }
+    let num_fetch_docs_bytes = if num_docs > 0 {
+        num_hits_to_fetch.min(num_docs) * (num_uncompressed_docs_bytes / num_docs)
+    } else {
+        0
+    };
+    let num_bytes = num_footer_bytes + num_warmup_bytes + num_fetch_docs_bytes;
+
+    let max_num_concurrent_split_searches = max_num_concurrent_split_searches.max(1);
+    let num_split_search_rounds = (split_metadatas.len() + max_num_concurrent_split_searches - 1)
+        / max_num_concurrent_split_searches;
+    let latency_secs = num_split_search_rounds as f64 * SPLIT_SEARCH_SECS
+        + num_bytes as f64 / DOWNLOAD_NUM_BYTES_PER_SEC;
+
+    SearchCostEstimate {
+        num_splits: split_metadatas.len() as u64,
+        num_docs,
+        num_footer_bytes,
+        num_warmup_bytes,
+        num_fetch_docs_bytes,
+        num_bytes,
+        latency_band: LatencyBand::from_secs(latency_secs),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tantivy::schema::Field;
+    use tantivy::Term;
+
+    use super::*;
+
+    #[test]
+    fn test_warmup_footprint() {
+        let field = Field::from_field_id(0);
+        let mut warmup_info = WarmupInfo {
+            fast_field_names: ["timestamp".to_string(), "host".to_string()]
+                .into_iter()
+                .collect(),
+            field_norms: true,
+            ..Default::default()
+        };
+        warmup_info.terms_grouped_by_field.insert(
+            field,
+            [
+                (Term::from_field_text(field, "foo"), false),
+                (Term::from_field_text(field, "bar"), true),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        let warmup_footprint = WarmupFootprint::from_warmup_info(&warmup_info);
+        assert_eq!(
+            warmup_footprint,
+            WarmupFootprint {
+                num_full_fields: 0,
+                num_terms: 2,
+                num_terms_with_positions: 1,
+                num_fast_fields: 2,
+                num_field_norms: 1,
+            }
+        );
+        assert_eq!(warmup_footprint.num_bytes_per_doc(), 2 * 4 + 4 + 2 * 8 + 1);
+    }
+
+    #[test]
+    fn test_estimate_search_cost() {
+        let index_uid = IndexUid::from("test-index:0");
+        let split_metadata = |num_docs: usize| SplitMetadata {
+            index_uid: index_uid.clone(),
+            num_docs,
+            uncompressed_docs_size_in_bytes: num_docs as u64 * 100,
+            footer_offsets: 1_000..1_500,
+            ..Default::default()
+        };
+        let split_metadatas = vec![split_metadata(1_000), split_metadata(3_000)];
+        let warmup_footprints = HashMap::from_iter([(
+            index_uid.clone(),
+            WarmupFootprint {
+                num_fast_fields: 1,
+                ..Default::default()
+            },
+        )]);
+        let search_cost_estimate =
+            estimate_search_cost(&split_metadatas, &warmup_footprints, 20, 100);
+        assert_eq!(
+            search_cost_estimate,
+            SearchCostEstimate {
+                num_splits: 2,
+                num_docs: 4_000,
+                num_footer_bytes: 1_000,
+                num_warmup_bytes: 32_000,
+                num_fetch_docs_bytes: 2_000,
+                num_bytes: 35_000,
+                latency_band: LatencyBand::Fast,
+            }
+        );
+        let empty_search_cost_estimate = estimate_search_cost(&[], &warmup_footprints, 20, 100);
+        assert_eq!(empty_search_cost_estimate, SearchCostEstimate::default());
+
+        // 1,000 rounds of split searches.
+        let many_split_metadatas = vec![split_metadata(1); 1_000];
+        let search_cost_estimate =
+            estimate_search_cost(&many_split_metadatas, &warmup_footprints, 20, 1);
+        assert_eq!(search_cost_estimate.latency_band, LatencyBand::Slow);
+    }
+}
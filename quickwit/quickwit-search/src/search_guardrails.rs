@@ -0,0 +1,374 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use quickwit_config::IndexConfig;
+use quickwit_proto::search::SearchRequest;
+use quickwit_query::query_ast::{QueryAst, QueryAstVisitor, WildcardQuery};
+use serde_json::{Map as JsonMap, Value as JsonValue};
+
+use crate::SearchError;
+
+/// Default number of buckets of the `terms` and `significant_terms` aggregations.
+const DEFAULT_TERMS_SIZE: u64 = 10;
+
+/// Checks that a search request complies with the search guardrails of an index. The query AST
+/// must be resolved and the time range of the request refined from the query.
+pub(crate) fn check_search_guardrails(
+    index_config: &IndexConfig,
+    query_ast: &QueryAst,
+    search_request: &SearchRequest,
+) -> crate::Result<()> {
+    let index_id = &index_config.index_id;
+    let guardrails = &index_config.search_settings.guardrails;
+
+    if guardrails.forbid_leading_wildcard {
+        if let Err(wildcard_query) = LeadingWildcardFinder.visit(query_ast) {
+            return Err(SearchError::InvalidQuery(format!(
+                "index `{index_id}` forbids queries starting with a wildcard, got `{}:{}`",
+                wildcard_query.field, wildcard_query.value
+            )));
+        }
+    }
+    let max_time_range_opt = guardrails.max_time_range().map_err(|error| {
+        SearchError::Internal(format!(
+            "invalid search guardrails for index `{index_id}`: {error}"
+        ))
+    })?;
+    if let Some(max_time_range) = max_time_range_opt {
+        let (Some(start_timestamp), Some(end_timestamp)) =
+            (search_request.start_timestamp, search_request.end_timestamp)
+        else {
+            return Err(SearchError::InvalidArgument(format!(
+                "index `{index_id}` requires search requests to be restricted to a time range: \
+                 set both `start_timestamp` and `end_timestamp`, or filter on the timestamp field"
+            )));
+        };
+        let time_range_secs = end_timestamp.saturating_sub(start_timestamp);
+
+        if time_range_secs > max_time_range.as_secs() as i64 {
+            return Err(SearchError::InvalidArgument(format!(
+                "index `{index_id}` limits the time range of search requests to {}, got {} seconds",
+                guardrails.max_time_range.as_deref().unwrap_or_default(),
+                time_range_secs
+            )));
+        }
+    }
+    if let (Some(max_aggregation_buckets), Some(aggregation_request)) = (
+        guardrails.max_aggregation_buckets,
+        &search_request.aggregation_request,
+    ) {
+        let aggregations: JsonValue = serde_json::from_str(aggregation_request)
+            .map_err(|error| SearchError::InvalidAggregationRequest(error.to_string()))?;
+        let Some(aggregations) = aggregations.as_object() else {
+            return Ok(());
+        };
+        let timestamp_field_opt = index_config.doc_mapping.timestamp_field.as_deref();
+        let max_num_buckets = max_num_buckets(aggregations, search_request, timestamp_field_opt)
+            .map_err(|aggregation_name| {
+                SearchError::InvalidAggregationRequest(format!(
+                    "index `{index_id}` limits the number of aggregation buckets to \
+                     {max_aggregation_buckets}, but the number of buckets of aggregation \
+                     `{aggregation_name}` cannot be bounded: set `hard_bounds`"
+                ))
+            })?;
+        if max_num_buckets > max_aggregation_buckets.get() as u64 {
+            return Err(SearchError::InvalidAggregationRequest(format!(
+                "index `{index_id}` limits the number of aggregation buckets to \
+                 {max_aggregation_buckets}, but the aggregation request may produce up to \
+                 {max_num_buckets} buckets"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Finds the first wildcard query whose pattern starts with a wildcard.
+struct LeadingWildcardFinder;
+
+impl<'a> QueryAstVisitor<'a> for LeadingWildcardFinder {
+    type Err = &'a WildcardQuery;
+
+    fn visit_wildcard(&mut self, wildcard_query: &'a WildcardQuery) -> Result<(), Self::Err> {
+        if wildcard_query.value.starts_with(['*', '?']) {
+            return Err(wildcard_query);
+        }
+        Ok(())
+    }
+}
+
+/// Returns an upper bound of the number of buckets, including the buckets of the
+/// sub-aggregations, produced by the aggregations of a request, or the name of an aggregation
+/// whose number of buckets cannot be bounded from the request.
+fn max_num_buckets(
+    aggregations: &JsonMap<String, JsonValue>,
+    search_request: &SearchRequest,
+    timestamp_field_opt: Option<&str>,
+) -> Result<u64, String> {
+    let mut num_buckets: u64 = 0;
+
+    for (aggregation_name, aggregation) in aggregations {
+        let Some(aggregation) = aggregation.as_object() else {
+            continue;
+        };
+        let mut num_sub_buckets: u64 = 0;
+        let mut num_agg_buckets: Option<u64> = Some(0);
+
+        for (key, params) in aggregation {
+            match key.as_str() {
+                "aggs" | "aggregations" => {
+                    if let Some(sub_aggregations) = params.as_object() {
+                        num_sub_buckets =
+                            max_num_buckets(sub_aggregations, search_request, timestamp_field_opt)?;
+                    }
+                }
+                "terms" | "significant_terms" => {
+                    num_agg_buckets = Some(
+                        params
+                            .get("size")
+                            .and_then(JsonValue::as_u64)
+                            .unwrap_or(DEFAULT_TERMS_SIZE),
+                    );
+                }
+                "range" => {
+                    num_agg_buckets = Some(
+                        params
+                            .get("ranges")
+                            .and_then(JsonValue::as_array)
+                            .map(|ranges| ranges.len() as u64)
+                            .unwrap_or(0),
+                    );
+                }
+                "histogram" => {
+                    let interval_opt = params.get("interval").and_then(JsonValue::as_f64);
+                    num_agg_buckets =
+                        interval_opt.and_then(|interval| num_histogram_buckets(params, interval));
+                }
+                "date_histogram" => {
+                    let interval_ms_opt = params
+                        .get("fixed_interval")
+                        .and_then(JsonValue::as_str)
+                        .and_then(parse_fixed_interval_ms);
+                    num_agg_buckets = interval_ms_opt.and_then(|interval_ms| {
+                        num_histogram_buckets(params, interval_ms).or_else(|| {
+                            // The buckets of a date histogram on the timestamp field are bounded
+                            // by the time range of the request.
+                            let field_opt = params.get("field").and_then(JsonValue::as_str);
+                            if field_opt.is_none() || field_opt != timestamp_field_opt {
+                                return None;
+                            }
+                            let start_timestamp = search_request.start_timestamp?;
+                            let end_timestamp = search_request.end_timestamp?;
+                            let time_range_ms =
+                                end_timestamp.saturating_sub(start_timestamp) as f64 * 1_000.0;
+                            Some((time_range_ms / interval_ms).floor() as u64 + 1)
+                        })
+                    });
+                }
+                "rare_terms" => {
+                    num_agg_buckets = None;
+                }
+                // Metric aggregations do not produce buckets.
+                _ => {}
+            }
+        }
+        let Some(num_agg_buckets) = num_agg_buckets else {
+            return Err(aggregation_name.clone());
+        };
+        num_buckets = num_buckets
+            .saturating_add(num_agg_buckets.saturating_mul(num_sub_buckets.saturating_add(1)));
+    }
+    Ok(num_buckets)
+}
+
+/// Returns the number of buckets of a histogram restricted with `hard_bounds`.
+fn num_histogram_buckets(params: &JsonValue, interval: f64) -> Option<u64> {
+    let hard_bounds = params.get("hard_bounds")?;
+    let min = hard_bounds.get("min")?.as_f64()?;
+    let max = hard_bounds.get("max")?.as_f64()?;
+
+    if interval <= 0.0 || max < min {
+        return None;
+    }
+    Some(((max - min) / interval).floor() as u64 + 1)
+}
+
+/// Parses the fixed interval of a date histogram, e.g. `30s` or `1d`, into milliseconds.
+fn parse_fixed_interval_ms(fixed_interval: &str) -> Option<f64> {
+    let unit_position = fixed_interval.find(|ch: char| !ch.is_ascii_digit())?;
+    let (value, unit) = fixed_interval.split_at(unit_position);
+    let value: f64 = value.parse().ok()?;
+    let unit_ms = match unit {
+        "ms" => 1.0,
+        "s" => 1_000.0,
+        "m" => 60_000.0,
+        "h" => 3_600_000.0,
+        "d" => 86_400_000.0,
+        _ => return None,
+    };
+    Some(value * unit_ms)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU32;
+
+    use quickwit_config::SearchGuardrails;
+    use quickwit_metastore::IndexMetadata;
+    use quickwit_query::query_ast::qast_helper;
+
+    use super::*;
+
+    fn index_config_with_guardrails(guardrails: SearchGuardrails) -> IndexConfig {
+        let mut index_config =
+            IndexMetadata::for_test("test-index", "ram:///indexes/test-index").index_config;
+        index_config.doc_mapping.timestamp_field = Some("timestamp".to_string());
+        index_config.search_settings.guardrails = guardrails;
+        index_config
+    }
+
+    #[test]
+    fn test_check_search_guardrails_leading_wildcard() {
+        let index_config = index_config_with_guardrails(SearchGuardrails {
+            forbid_leading_wildcard: true,
+            ..Default::default()
+        });
+        let search_request = SearchRequest::default();
+
+        check_search_guardrails(
+            &index_config,
+            &qast_helper("body:err*", &[]),
+            &search_request,
+        )
+        .unwrap();
+        check_search_guardrails(
+            &index_config,
+            &qast_helper("body:e*r", &[]),
+            &search_request,
+        )
+        .unwrap();
+
+        let error = check_search_guardrails(
+            &index_config,
+            &qast_helper("severity:ERROR AND body:*rror", &[]),
+            &search_request,
+        )
+        .unwrap_err();
+        assert!(matches!(error, SearchError::InvalidQuery(_)));
+        assert!(error
+            .to_string()
+            .contains("forbids queries starting with a wildcard"));
+    }
+
+    #[test]
+    fn test_check_search_guardrails_max_time_range() {
+        let index_config = index_config_with_guardrails(SearchGuardrails {
+            max_time_range: Some("1 day".to_string()),
+            ..Default::default()
+        });
+        let query_ast = QueryAst::MatchAll;
+
+        let error = check_search_guardrails(&index_config, &query_ast, &SearchRequest::default())
+            .unwrap_err();
+        assert!(matches!(error, SearchError::InvalidArgument(_)));
+
+        let search_request = SearchRequest {
+            start_timestamp: Some(0),
+            end_timestamp: Some(86_400),
+            ..Default::default()
+        };
+        check_search_guardrails(&index_config, &query_ast, &search_request).unwrap();
+
+        let search_request = SearchRequest {
+            start_timestamp: Some(0),
+            end_timestamp: Some(86_401),
+            ..Default::default()
+        };
+        let error =
+            check_search_guardrails(&index_config, &query_ast, &search_request).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("limits the time range of search requests to 1 day"));
+    }
+
+    #[test]
+    fn test_check_search_guardrails_max_aggregation_buckets() {
+        let index_config = index_config_with_guardrails(SearchGuardrails {
+            max_aggregation_buckets: NonZeroU32::new(100),
+            ..Default::default()
+        });
+        let query_ast = QueryAst::MatchAll;
+        let search_request_with_aggs = |aggs: JsonValue| SearchRequest {
+            aggregation_request: Some(aggs.to_string()),
+            start_timestamp: Some(0),
+            end_timestamp: Some(3_600),
+            ..Default::default()
+        };
+        let within_limit_aggs = [
+            serde_json::json!({
+                "hosts": {
+                    "terms": {"field": "host", "size": 9},
+                    "aggs": {"avg_latency": {"avg": {"field": "latency"}}, "top": {"terms": {"field": "status"}}}
+                }
+            }),
+            serde_json::json!({
+                "latency": {"histogram": {"field": "latency", "interval": 10, "hard_bounds": {"min": 0, "max": 990}}}
+            }),
+            serde_json::json!({
+                "over_time": {"date_histogram": {"field": "timestamp", "fixed_interval": "1m"}}
+            }),
+        ];
+        for aggs in within_limit_aggs {
+            check_search_guardrails(&index_config, &query_ast, &search_request_with_aggs(aggs))
+                .unwrap();
+        }
+        let error = check_search_guardrails(
+            &index_config,
+            &query_ast,
+            &search_request_with_aggs(serde_json::json!({
+                "hosts": {
+                    "terms": {"field": "host", "size": 10},
+                    "aggs": {"top": {"terms": {"field": "status"}}}
+                }
+            })),
+        )
+        .unwrap_err();
+        assert!(matches!(error, SearchError::InvalidAggregationRequest(_)));
+        assert!(error.to_string().contains("may produce up to 110 buckets"));
+
+        let error = check_search_guardrails(
+            &index_config,
+            &query_ast,
+            &search_request_with_aggs(serde_json::json!({
+                "latency": {"histogram": {"field": "latency", "interval": 10}}
+            })),
+        )
+        .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("the number of buckets of aggregation `latency` cannot be bounded"));
+    }
+
+    #[test]
+    fn test_parse_fixed_interval_ms() {
+        assert_eq!(parse_fixed_interval_ms("30s"), Some(30_000.0));
+        assert_eq!(parse_fixed_interval_ms("1d"), Some(86_400_000.0));
+        assert_eq!(parse_fixed_interval_ms("1w"), None);
+        assert_eq!(parse_fixed_interval_ms("m"), None);
+    }
+}
@@ -17,12 +17,16 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::BTreeMap;
 use std::convert::TryFrom;
 
-use quickwit_common::truncate_str;
-use quickwit_proto::search::SearchResponse;
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use quickwit_common::{is_false, truncate_str};
+use quickwit_proto::search::{ListTermsResponse, SearchResponse, SplitSearchError};
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
+use tantivy::Term;
 
 use crate::error::SearchError;
 
@@ -47,6 +51,41 @@ pub struct SearchResponseRest {
     #[schema(value_type = Object)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub aggregations: Option<JsonValue>,
+    /// Fraction of the documents searched by a preview request.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preview_coverage: Option<f64>,
+    /// Groups of hits, returned instead of `hits` when the request sets `group_by`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub groups: Option<Vec<HitGroupRest>>,
+    /// Value of the `search_after` parameter fetching the next page, set when the page is full.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_search_after: Option<String>,
+    /// Whether some splits could not be searched, in which case the hits and aggregations only
+    /// cover the other splits.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub partial: bool,
+    /// Splits that could not be searched.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub failed_splits: Vec<SplitSearchError>,
+    /// Splits that were not searched, or whose search was cancelled, because they could not
+    /// produce hits better than the ones returned.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub skipped_split_ids: Vec<String>,
+}
+
+/// Hits sharing the same value of the `group_by` field.
+#[derive(Serialize, Deserialize, PartialEq, Debug, utoipa::ToSchema)]
+pub struct HitGroupRest {
+    /// Value of the `group_by` field shared by the hits of the group.
+    pub key: String,
+    /// Number of documents of the group matching the query.
+    pub num_hits: u64,
+    /// Best hits of the group.
+    #[schema(value_type = Vec<Object>)]
+    pub hits: Vec<JsonValue>,
 }
 
 impl TryFrom<SearchResponse> for SearchResponseRest {
@@ -56,13 +95,7 @@ impl TryFrom<SearchResponse> for SearchResponseRest {
         let mut documents = Vec::with_capacity(search_response.hits.len());
         let mut snippets = Vec::new();
         for hit in search_response.hits {
-            let document: JsonValue = serde_json::from_str(&hit.json).map_err(|err| {
-                SearchError::Internal(format!(
-                    "failed to serialize document `{}` to JSON: `{}`",
-                    truncate_str(&hit.json, 100),
-                    err
-                ))
-            })?;
+            let document = hit_to_json(&hit.json)?;
             documents.push(document);
 
             if let Some(snippet_json) = hit.snippet {
@@ -82,6 +115,28 @@ impl TryFrom<SearchResponse> for SearchResponseRest {
             None
         };
 
+        let groups_opt = if !search_response.groups.is_empty() {
+            let groups = search_response
+                .groups
+                .into_iter()
+                .map(|hit_group| {
+                    let hits = hit_group
+                        .hits
+                        .iter()
+                        .map(|hit| hit_to_json(&hit.json))
+                        .collect::<Result<Vec<JsonValue>, SearchError>>()?;
+                    Ok(HitGroupRest {
+                        key: hit_group.key,
+                        num_hits: hit_group.num_hits,
+                        hits,
+                    })
+                })
+                .collect::<Result<Vec<HitGroupRest>, SearchError>>()?;
+            Some(groups)
+        } else {
+            None
+        };
+
         let aggregations_opt = if let Some(aggregation_json) = search_response.aggregation {
             let aggregation: JsonValue = serde_json::from_str(&aggregation_json)
                 .map_err(|err| SearchError::Internal(err.to_string()))?;
@@ -97,6 +152,140 @@ impl TryFrom<SearchResponse> for SearchResponseRest {
             elapsed_time_micros: search_response.elapsed_time_micros,
             errors: search_response.errors,
             aggregations: aggregations_opt,
+            preview_coverage: search_response.preview_coverage,
+            groups: groups_opt,
+            next_search_after: None,
+            partial: !search_response.failed_splits.is_empty(),
+            failed_splits: search_response.failed_splits,
+            skipped_split_ids: search_response.skipped_split_ids,
         })
     }
 }
+
+fn hit_to_json(hit_json: &str) -> Result<JsonValue, SearchError> {
+    serde_json::from_str(hit_json).map_err(|err| {
+        SearchError::Internal(format!(
+            "failed to serialize document `{}` to JSON: `{}`",
+            truncate_str(hit_json, 100),
+            err
+        ))
+    })
+}
+
+/// CountResponseRest represents the response returned by the REST count API
+/// and is meant to be serialized into JSON.
+#[derive(Serialize, Deserialize, PartialEq, Debug, utoipa::ToSchema)]
+pub struct CountResponseRest {
+    /// Overall number of documents matching the query.
+    pub num_hits: u64,
+    /// Number of documents matching the query per index, only returned when several indexes are
+    /// searched.
+    #[schema(value_type = Object)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_hits_per_index: Option<BTreeMap<String, u64>>,
+    /// Elapsed time.
+    pub elapsed_time_micros: u64,
+}
+
+/// ListTermsResponseRest represents the response returned by the REST list terms API
+/// and is meant to be serialized into JSON.
+#[derive(Serialize, Deserialize, PartialEq, Debug, utoipa::ToSchema)]
+pub struct ListTermsResponseRest {
+    /// Number of terms returned.
+    pub num_hits: u64,
+    /// Terms sorted in ascending order.
+    pub terms: Vec<TermRest>,
+    /// Elapsed time.
+    pub elapsed_time_micros: u64,
+    /// Search errors.
+    pub errors: Vec<String>,
+}
+
+/// A term listed by the REST list terms API.
+#[derive(Serialize, Deserialize, PartialEq, Debug, utoipa::ToSchema)]
+pub struct TermRest {
+    /// Value of the term. Bytes and other binary values are encoded in base64.
+    #[schema(value_type = Object)]
+    pub term: JsonValue,
+    /// Number of documents containing the term. Only set if the document counts were requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub doc_count: Option<u64>,
+}
+
+impl From<ListTermsResponse> for ListTermsResponseRest {
+    fn from(list_terms_response: ListTermsResponse) -> Self {
+        let mut doc_counts_iter = list_terms_response.doc_counts.into_iter();
+        let terms = list_terms_response
+            .terms
+            .iter()
+            .map(|term_bytes| TermRest {
+                term: term_to_json_value(term_bytes),
+                doc_count: doc_counts_iter.next(),
+            })
+            .collect();
+        ListTermsResponseRest {
+            num_hits: list_terms_response.num_hits,
+            terms,
+            elapsed_time_micros: list_terms_response.elapsed_time_micros,
+            errors: list_terms_response.errors,
+        }
+    }
+}
+
+/// Converts a serialized term, as returned by the list terms API, to a JSON value.
+fn term_to_json_value(term_bytes: &[u8]) -> JsonValue {
+    let term = Term::wrap(term_bytes);
+    let value = term.value();
+
+    if let Some(text) = value.as_str() {
+        return JsonValue::from(text);
+    }
+    if let Some(bool_value) = value.as_bool() {
+        return JsonValue::from(bool_value);
+    }
+    if let Some(u64_value) = value.as_u64() {
+        return JsonValue::from(u64_value);
+    }
+    if let Some(i64_value) = value.as_i64() {
+        return JsonValue::from(i64_value);
+    }
+    if let Some(f64_value) = value.as_f64() {
+        return JsonValue::from(f64_value);
+    }
+    JsonValue::from(BASE64_STANDARD.encode(term.serialized_value_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_terms_response_rest_from_list_terms_response() {
+        let field = tantivy::schema::Field::from_field_id(0);
+        let list_terms_response = ListTermsResponse {
+            num_hits: 2,
+            terms: vec![
+                Term::from_field_text(field, "beagle")
+                    .serialized_term()
+                    .to_vec(),
+                Term::from_field_u64(field, 42).serialized_term().to_vec(),
+            ],
+            elapsed_time_micros: 10,
+            errors: Vec::new(),
+            doc_counts: vec![3, 1],
+        };
+        let list_terms_response_rest = ListTermsResponseRest::from(list_terms_response);
+        let expected_terms = vec![
+            TermRest {
+                term: JsonValue::from("beagle"),
+                doc_count: Some(3),
+            },
+            TermRest {
+                term: JsonValue::from(42u64),
+                doc_count: Some(1),
+            },
+        ];
+        assert_eq!(list_terms_response_rest.terms, expected_terms);
+        assert_eq!(list_terms_response_rest.num_hits, 2);
+    }
+}
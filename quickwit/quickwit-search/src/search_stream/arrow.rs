@@ -0,0 +1,396 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Collects the values of several fast fields as Arrow columns and serializes them as an Arrow IPC
+//! stream.
+//!
+//! Each matching document contributes one row. Multivalued fields contribute their first value,
+//! and documents without a value for a field contribute a null.
+
+use std::sync::Arc;
+
+use arrow::array::{
+    ArrayRef, BooleanArray, Float64Array, Int64Array, StringArray, TimestampMicrosecondArray,
+    UInt64Array,
+};
+use arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+use arrow::ipc::writer::StreamWriter;
+use arrow::record_batch::RecordBatch;
+use tantivy::collector::{Collector, SegmentCollector};
+use tantivy::columnar::StrColumn;
+use tantivy::fastfield::Column;
+use tantivy::schema::Type;
+use tantivy::{DateTime, DocId, Score, SegmentOrdinal, SegmentReader};
+
+use crate::filters::{TimestampFilter, TimestampFilterBuilder};
+use crate::SearchError;
+
+/// Maximum number of rows per record batch of the Arrow IPC stream.
+const ARROW_BATCH_NUM_ROWS: usize = 8_192;
+
+/// Returns the Arrow data type of the column holding the values of a fast field of the given
+/// type, or `None` if the type is not supported.
+pub(crate) fn arrow_data_type(field_type: Type) -> Option<DataType> {
+    let data_type = match field_type {
+        Type::Bool => DataType::Boolean,
+        Type::Date => DataType::Timestamp(TimeUnit::Microsecond, None),
+        Type::F64 => DataType::Float64,
+        Type::I64 => DataType::Int64,
+        Type::Str => DataType::Utf8,
+        Type::U64 => DataType::UInt64,
+        _ => return None,
+    };
+    Some(data_type)
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum ColumnValues {
+    Bool(Vec<Option<bool>>),
+    Date(Vec<Option<i64>>),
+    F64(Vec<Option<f64>>),
+    I64(Vec<Option<i64>>),
+    Str(Vec<Option<String>>),
+    U64(Vec<Option<u64>>),
+}
+
+impl ColumnValues {
+    fn with_type(field_type: Type) -> Self {
+        match field_type {
+            Type::Bool => Self::Bool(Vec::new()),
+            Type::Date => Self::Date(Vec::new()),
+            Type::F64 => Self::F64(Vec::new()),
+            Type::I64 => Self::I64(Vec::new()),
+            Type::Str => Self::Str(Vec::new()),
+            _ => Self::U64(Vec::new()),
+        }
+    }
+
+    fn extend(&mut self, other: ColumnValues) {
+        match (self, other) {
+            (Self::Bool(values), Self::Bool(other_values)) => values.extend(other_values),
+            (Self::Date(values), Self::Date(other_values)) => values.extend(other_values),
+            (Self::F64(values), Self::F64(other_values)) => values.extend(other_values),
+            (Self::I64(values), Self::I64(other_values)) => values.extend(other_values),
+            (Self::Str(values), Self::Str(other_values)) => values.extend(other_values),
+            (Self::U64(values), Self::U64(other_values)) => values.extend(other_values),
+            _ => panic!("columns of the same field should have the same type"),
+        }
+    }
+
+    fn into_array(self) -> ArrayRef {
+        match self {
+            Self::Bool(values) => Arc::new(BooleanArray::from(values)),
+            Self::Date(values) => Arc::new(TimestampMicrosecondArray::from(values)),
+            Self::F64(values) => Arc::new(Float64Array::from(values)),
+            Self::I64(values) => Arc::new(Int64Array::from(values)),
+            Self::Str(values) => Arc::new(StringArray::from(values)),
+            Self::U64(values) => Arc::new(UInt64Array::from(values)),
+        }
+    }
+}
+
+enum ColumnReader {
+    Bool(Option<Column<bool>>),
+    Date(Option<Column<DateTime>>),
+    F64(Option<Column<f64>>),
+    I64(Option<Column<i64>>),
+    Str(Option<StrColumn>),
+    U64(Option<Column<u64>>),
+}
+
+impl ColumnReader {
+    fn open(
+        segment_reader: &SegmentReader,
+        field_name: &str,
+        field_type: Type,
+    ) -> tantivy::Result<Self> {
+        let fast_fields = segment_reader.fast_fields();
+        let column_reader = match field_type {
+            Type::Bool => Self::Bool(fast_fields.column_opt(field_name)?),
+            Type::Date => Self::Date(fast_fields.column_opt(field_name)?),
+            Type::F64 => Self::F64(fast_fields.column_opt(field_name)?),
+            Type::I64 => Self::I64(fast_fields.column_opt(field_name)?),
+            Type::Str => Self::Str(fast_fields.str(field_name)?),
+            _ => Self::U64(fast_fields.column_opt(field_name)?),
+        };
+        Ok(column_reader)
+    }
+
+    fn push_value(&self, doc_id: DocId, column_values: &mut ColumnValues) -> tantivy::Result<()> {
+        match (self, column_values) {
+            (Self::Bool(column_opt), ColumnValues::Bool(values)) => {
+                values.push(column_opt.as_ref().and_then(|column| column.first(doc_id)));
+            }
+            (Self::Date(column_opt), ColumnValues::Date(values)) => {
+                let value_opt = column_opt
+                    .as_ref()
+                    .and_then(|column| column.first(doc_id))
+                    .map(|date_time| date_time.into_timestamp_micros());
+                values.push(value_opt);
+            }
+            (Self::F64(column_opt), ColumnValues::F64(values)) => {
+                values.push(column_opt.as_ref().and_then(|column| column.first(doc_id)));
+            }
+            (Self::I64(column_opt), ColumnValues::I64(values)) => {
+                values.push(column_opt.as_ref().and_then(|column| column.first(doc_id)));
+            }
+            (Self::Str(str_column_opt), ColumnValues::Str(values)) => {
+                let Some(term_ord) = str_column_opt
+                    .as_ref()
+                    .and_then(|str_column| str_column.ords().first(doc_id))
+                else {
+                    values.push(None);
+                    return Ok(());
+                };
+                let mut value = String::new();
+                // Expect: the column is not `None` since we just read a term ordinal from it.
+                str_column_opt
+                    .as_ref()
+                    .expect("column should be open")
+                    .ord_to_str(term_ord, &mut value)?;
+                values.push(Some(value));
+            }
+            (Self::U64(column_opt), ColumnValues::U64(values)) => {
+                values.push(column_opt.as_ref().and_then(|column| column.first(doc_id)));
+            }
+            _ => panic!("column reader and column values should have the same type"),
+        }
+        Ok(())
+    }
+}
+
+pub struct ArrowSegmentCollector {
+    column_readers: Vec<ColumnReader>,
+    columns_values: Vec<ColumnValues>,
+    timestamp_filter_opt: Option<TimestampFilter>,
+    error_opt: Option<tantivy::TantivyError>,
+}
+
+impl SegmentCollector for ArrowSegmentCollector {
+    type Fruit = tantivy::Result<Vec<ColumnValues>>;
+
+    fn collect(&mut self, doc_id: DocId, _score: Score) {
+        if self.error_opt.is_some() {
+            return;
+        }
+        if let Some(timestamp_filter) = &self.timestamp_filter_opt {
+            if !timestamp_filter.is_within_range(doc_id) {
+                return;
+            }
+        }
+        for (column_reader, column_values) in self
+            .column_readers
+            .iter()
+            .zip(self.columns_values.iter_mut())
+        {
+            if let Err(error) = column_reader.push_value(doc_id, column_values) {
+                self.error_opt = Some(error);
+                return;
+            }
+        }
+    }
+
+    fn harvest(self) -> Self::Fruit {
+        if let Some(error) = self.error_opt {
+            return Err(error);
+        }
+        Ok(self.columns_values)
+    }
+}
+
+/// Collects the values of the fast fields `fields`, given as `(field name, field type)` pairs, as
+/// one column per field.
+pub struct ArrowCollector {
+    pub fields: Vec<(String, Type)>,
+    pub timestamp_filter_builder_opt: Option<TimestampFilterBuilder>,
+}
+
+impl Collector for ArrowCollector {
+    type Child = ArrowSegmentCollector;
+    type Fruit = Vec<ColumnValues>;
+
+    fn for_segment(
+        &self,
+        _segment_ord: SegmentOrdinal,
+        segment_reader: &SegmentReader,
+    ) -> tantivy::Result<Self::Child> {
+        let timestamp_filter_opt =
+            if let Some(timestamp_filter_builder) = &self.timestamp_filter_builder_opt {
+                timestamp_filter_builder.build(segment_reader)?
+            } else {
+                None
+            };
+        let column_readers = self
+            .fields
+            .iter()
+            .map(|(field_name, field_type)| {
+                ColumnReader::open(segment_reader, field_name, *field_type)
+            })
+            .collect::<tantivy::Result<Vec<_>>>()?;
+        let columns_values = self
+            .fields
+            .iter()
+            .map(|(_, field_type)| ColumnValues::with_type(*field_type))
+            .collect();
+        Ok(ArrowSegmentCollector {
+            column_readers,
+            columns_values,
+            timestamp_filter_opt,
+            error_opt: None,
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        // We do not need BM25 scoring in Quickwit.
+        false
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<tantivy::Result<Vec<ColumnValues>>>,
+    ) -> tantivy::Result<Self::Fruit> {
+        let mut columns_values: Vec<ColumnValues> = self
+            .fields
+            .iter()
+            .map(|(_, field_type)| ColumnValues::with_type(*field_type))
+            .collect();
+        for segment_fruit in segment_fruits {
+            for (column_values, segment_column_values) in
+                columns_values.iter_mut().zip(segment_fruit?)
+            {
+                column_values.extend(segment_column_values);
+            }
+        }
+        Ok(columns_values)
+    }
+}
+
+/// Serializes the columns collected by an [`ArrowCollector`] as an Arrow IPC stream. Columns are
+/// named after their fields.
+pub(crate) fn serialize_arrow_ipc(
+    fields: &[(String, Type)],
+    columns_values: Vec<ColumnValues>,
+) -> crate::Result<Vec<u8>> {
+    let schema_fields: Vec<Field> = fields
+        .iter()
+        .map(|(field_name, field_type)| {
+            let data_type = arrow_data_type(*field_type).ok_or_else(|| {
+                SearchError::Internal(format!(
+                    "search stream does not support fast field of type `{field_type:?}`"
+                ))
+            })?;
+            Ok(Field::new(field_name, data_type, true))
+        })
+        .collect::<crate::Result<_>>()?;
+    let schema = Arc::new(Schema::new(schema_fields));
+    let arrays: Vec<ArrayRef> = columns_values
+        .into_iter()
+        .map(ColumnValues::into_array)
+        .collect();
+
+    let arrow_error_to_search_error = |error: arrow::error::ArrowError| {
+        SearchError::Internal(format!(
+            "failed to write fast field values as Arrow IPC stream: {error}"
+        ))
+    };
+    let record_batch =
+        RecordBatch::try_new(schema.clone(), arrays).map_err(arrow_error_to_search_error)?;
+    let mut stream_writer =
+        StreamWriter::try_new(Vec::new(), &schema).map_err(arrow_error_to_search_error)?;
+    let mut offset = 0;
+
+    while offset < record_batch.num_rows() {
+        let length = ARROW_BATCH_NUM_ROWS.min(record_batch.num_rows() - offset);
+        stream_writer
+            .write(&record_batch.slice(offset, length))
+            .map_err(arrow_error_to_search_error)?;
+        offset += length;
+    }
+    stream_writer
+        .into_inner()
+        .map_err(arrow_error_to_search_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use arrow::array::Array;
+    use arrow::ipc::reader::StreamReader;
+
+    use super::*;
+
+    #[test]
+    fn test_serialize_arrow_ipc() {
+        let fields = vec![
+            ("ts".to_string(), Type::Date),
+            ("severity".to_string(), Type::Str),
+            ("latency".to_string(), Type::F64),
+        ];
+        let columns_values = vec![
+            ColumnValues::Date(vec![Some(1_000_000), Some(2_000_000)]),
+            ColumnValues::Str(vec![Some("ERROR".to_string()), None]),
+            ColumnValues::F64(vec![None, Some(0.5)]),
+        ];
+        let arrow_ipc = serialize_arrow_ipc(&fields, columns_values).unwrap();
+
+        let stream_reader = StreamReader::try_new(Cursor::new(arrow_ipc), None).unwrap();
+        let schema = stream_reader.schema();
+        assert_eq!(schema.fields().len(), 3);
+        assert_eq!(schema.field(0).name(), "ts");
+        assert_eq!(
+            schema.field(0).data_type(),
+            &DataType::Timestamp(TimeUnit::Microsecond, None)
+        );
+        assert_eq!(schema.field(1).data_type(), &DataType::Utf8);
+        assert_eq!(schema.field(2).data_type(), &DataType::Float64);
+
+        let record_batches: Vec<RecordBatch> = stream_reader.collect::<Result<_, _>>().unwrap();
+        assert_eq!(record_batches.len(), 1);
+
+        let record_batch = &record_batches[0];
+        assert_eq!(record_batch.num_rows(), 2);
+
+        let severities = record_batch
+            .column(1)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(severities.value(0), "ERROR");
+        assert!(severities.is_null(1));
+
+        let latencies = record_batch
+            .column(2)
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .unwrap();
+        assert!(latencies.is_null(0));
+        assert_eq!(latencies.value(1), 0.5);
+    }
+
+    #[test]
+    fn test_serialize_arrow_ipc_unsupported_type() {
+        let fields = vec![("ip".to_string(), Type::IpAddr)];
+        let columns_values = vec![ColumnValues::U64(Vec::new())];
+        let error = serialize_arrow_ipc(&fields, columns_values).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("does not support fast field of type"));
+    }
+}
@@ -38,6 +38,7 @@ use tantivy::{DateTime, ReloadPolicy, Searcher};
 use tokio_stream::wrappers::UnboundedReceiverStream;
 use tracing::*;
 
+use super::arrow::{arrow_data_type, serialize_arrow_ipc, ArrowCollector};
 use super::collector::{PartionnedFastFieldCollector, PartitionValues};
 use super::FastFieldCollector;
 use crate::filters::{create_timestamp_filter_builder, TimestampFilterBuilder};
@@ -155,6 +156,12 @@ async fn leaf_search_stream_single_split(
                 .to_string(),
         ));
     }
+    if !request_fields.additional_fast_fields.is_empty() && output_format != OutputFormat::ArrowIpc
+    {
+        return Err(SearchError::InvalidArgument(
+            "additional fast fields are only supported by the ArrowIpc output format".to_string(),
+        ));
+    }
 
     let search_request = Arc::new(SearchRequest::try_from(stream_request.clone())?);
     let query_ast = serde_json::from_str(&search_request.query_ast)
@@ -197,6 +204,14 @@ async fn leaf_search_stream_single_split(
     let _ = span.enter();
     let m_request_fields = request_fields.clone();
     let collect_handle = crate::run_cpu_intensive(move || {
+        if output_format == OutputFormat::ArrowIpc {
+            return collect_arrow_ipc(
+                &m_request_fields,
+                timestamp_filter_builder_opt,
+                &searcher,
+                &query,
+            );
+        }
         let mut buffer = Vec::new();
         match m_request_fields.fast_field_types() {
             (Type::I64, None) => {
@@ -312,6 +327,29 @@ where
     Ok(result)
 }
 
+fn collect_arrow_ipc(
+    request_fields: &SearchStreamRequestFields,
+    timestamp_filter_builder_opt: Option<TimestampFilterBuilder>,
+    searcher: &Searcher,
+    query: &dyn Query,
+) -> crate::Result<Vec<u8>> {
+    let fields = request_fields.arrow_fields();
+
+    for (field_name, field_type) in &fields {
+        if arrow_data_type(*field_type).is_none() {
+            return Err(SearchError::InvalidArgument(format!(
+                "search stream does not support fast field `{field_name}` of type `{field_type:?}`"
+            )));
+        }
+    }
+    let collector = ArrowCollector {
+        fields: fields.clone(),
+        timestamp_filter_builder_opt,
+    };
+    let columns_values = searcher.search(query, &collector)?;
+    serialize_arrow_ipc(&fields, columns_values)
+}
+
 fn collect_partitioned_values<
     Item: HasAssociatedColumnType,
     TPartitionValue: HasAssociatedColumnType + Eq + Hash,
@@ -341,6 +379,7 @@ where
 // TODO move to owned values, implement Send + Sync
 struct SearchStreamRequestFields {
     fast_field: Field,
+    additional_fast_fields: Vec<Field>,
     partition_by_fast_field: Option<Field>,
     timestamp_field_name: Option<String>,
     schema: Schema,
@@ -377,6 +416,19 @@ impl<'a> SearchStreamRequestFields {
             )));
         }
 
+        let mut additional_fast_fields =
+            Vec::with_capacity(stream_request.additional_fast_fields.len());
+
+        for field_name in &stream_request.additional_fast_fields {
+            let field = schema.get_field(field_name)?;
+
+            if !Self::is_fast_field(schema, &field) {
+                return Err(SearchError::InvalidQuery(format!(
+                    "field `{field_name}` is not a fast field"
+                )));
+            }
+            additional_fast_fields.push(field);
+        }
         let timestamp_field_name = doc_mapper.timestamp_field_name().map(ToString::to_string);
         let partition_by_fast_field = stream_request
             .partition_by_field
@@ -395,6 +447,7 @@ impl<'a> SearchStreamRequestFields {
         Ok(SearchStreamRequestFields {
             schema: schema.to_owned(),
             fast_field,
+            additional_fast_fields,
             partition_by_fast_field,
             timestamp_field_name,
         })
@@ -411,12 +464,30 @@ impl<'a> SearchStreamRequestFields {
         )
     }
 
+    /// Returns the names and types of the fast fields exported as Arrow columns, i.e. the fast
+    /// field followed by the additional fast fields.
+    fn arrow_fields(&self) -> Vec<(String, Type)> {
+        std::iter::once(self.fast_field)
+            .chain(self.additional_fast_fields.iter().copied())
+            .map(|field| {
+                let field_entry = self.schema.get_field_entry(field);
+                (
+                    field_entry.name().to_string(),
+                    field_entry.field_type().value_type(),
+                )
+            })
+            .collect()
+    }
+
     fn fast_fields_for_request(
         &self,
         timestamp_filter_builder_opt: Option<&TimestampFilterBuilder>,
     ) -> HashSet<String> {
         let mut set = HashSet::new();
         set.insert(self.fast_field_name().to_string());
+        for field in &self.additional_fast_fields {
+            set.insert(self.schema.get_field_name(*field).to_string());
+        }
         if let Some(timestamp_filter_builder) = timestamp_filter_builder_opt {
             set.insert(timestamp_filter_builder.timestamp_field_name.clone());
         }
@@ -448,8 +519,12 @@ impl<'a> SearchStreamRequestFields {
 mod tests {
     use std::collections::HashMap;
     use std::convert::TryInto;
+    use std::io::Cursor;
     use std::str::from_utf8;
 
+    use arrow::array::{Array, Float64Array, StringArray, TimestampMicrosecondArray};
+    use arrow::ipc::reader::StreamReader;
+    use arrow::record_batch::RecordBatch;
     use itertools::Itertools;
     use quickwit_indexing::TestSandbox;
     use quickwit_metastore::{ListSplitsRequestExt, MetastoreServiceStreamSplitsExt};
@@ -498,6 +573,7 @@ mod tests {
             fast_field: "ts".to_string(),
             output_format: 0,
             partition_by_field: None,
+            additional_fast_fields: Vec::new(),
         };
         let splits = test_sandbox
             .metastore()
@@ -577,6 +653,7 @@ mod tests {
             fast_field: "ts".to_string(),
             output_format: 0,
             partition_by_field: None,
+            additional_fast_fields: Vec::new(),
         };
         let splits = test_sandbox
             .metastore()
@@ -634,6 +711,7 @@ mod tests {
             fast_field: "app".to_string(),
             output_format: 0,
             partition_by_field: None,
+            additional_fast_fields: Vec::new(),
         };
         let splits = test_sandbox
             .metastore()
@@ -661,6 +739,112 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_leaf_search_stream_to_arrow_ipc_output() -> anyhow::Result<()> {
+        let index_id = "single-node-simple-arrow-ipc";
+        let doc_mapping_yaml = r#"
+            field_mappings:
+              - name: body
+                type: text
+              - name: ts
+                type: datetime
+                fast: true
+              - name: app
+                type: text
+                tokenizer: raw
+                fast: true
+              - name: latency
+                type: f64
+                fast: true
+            timestamp_field: ts
+        "#;
+        let test_sandbox = TestSandbox::create(index_id, doc_mapping_yaml, "", &["body"]).await?;
+        test_sandbox
+            .add_documents(vec![
+                json!({"body": "info", "ts": 72057596, "app": "my-app", "latency": 0.5}),
+                json!({"body": "info", "ts": 72057597}),
+                json!({"body": "warn", "ts": 72057598, "app": "my-app", "latency": 1.5}),
+            ])
+            .await?;
+
+        let request = SearchStreamRequest {
+            index_id: index_id.to_string(),
+            query_ast: qast_json_helper("info", &["body"]),
+            snippet_fields: Vec::new(),
+            start_timestamp: None,
+            end_timestamp: None,
+            fast_field: "ts".to_string(),
+            output_format: OutputFormat::ArrowIpc as i32,
+            partition_by_field: None,
+            additional_fast_fields: vec!["app".to_string(), "latency".to_string()],
+        };
+        let splits = test_sandbox
+            .metastore()
+            .list_splits(ListSplitsRequest::try_from_index_uid(test_sandbox.index_uid()).unwrap())
+            .await?
+            .collect_splits()
+            .await?;
+        let splits_offsets = splits
+            .into_iter()
+            .map(|split| extract_split_and_footer_offsets(&split.split_metadata))
+            .collect();
+        let searcher_context = Arc::new(SearcherContext::for_test());
+        let mut single_node_stream = leaf_search_stream(
+            searcher_context,
+            request,
+            test_sandbox.storage(),
+            splits_offsets,
+            test_sandbox.doc_mapper(),
+        )
+        .await;
+        let res = single_node_stream.next().await.expect("no leaf result")?;
+
+        let stream_reader = StreamReader::try_new(Cursor::new(res.data), None)?;
+        let record_batches: Vec<RecordBatch> = stream_reader.collect::<Result<_, _>>()?;
+        assert_eq!(record_batches.len(), 1);
+
+        let record_batch = &record_batches[0];
+        assert_eq!(record_batch.num_rows(), 2);
+        assert_eq!(record_batch.schema().field(0).name(), "ts");
+        assert_eq!(record_batch.schema().field(1).name(), "app");
+        assert_eq!(record_batch.schema().field(2).name(), "latency");
+
+        let timestamps = record_batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<TimestampMicrosecondArray>()
+            .unwrap();
+        let mut rows: Vec<(i64, Option<&str>, Option<f64>)> = (0..record_batch.num_rows())
+            .map(|row| {
+                let app = record_batch
+                    .column(1)
+                    .as_any()
+                    .downcast_ref::<StringArray>()
+                    .unwrap();
+                let latency = record_batch
+                    .column(2)
+                    .as_any()
+                    .downcast_ref::<Float64Array>()
+                    .unwrap();
+                (
+                    timestamps.value(row),
+                    (!app.is_null(row)).then(|| app.value(row)),
+                    (!latency.is_null(row)).then(|| latency.value(row)),
+                )
+            })
+            .collect();
+        rows.sort_by_key(|(timestamp, _, _)| *timestamp);
+        assert_eq!(
+            rows,
+            vec![
+                (72057596_000_000, Some("my-app"), Some(0.5)),
+                (72057597_000_000, None, None),
+            ]
+        );
+        test_sandbox.assert_quit().await;
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_leaf_search_stream_to_partitionned_clickhouse_binary_output_with_filtering(
     ) -> anyhow::Result<()> {
@@ -724,6 +908,7 @@ mod tests {
             fast_field: "fast_field".to_string(),
             output_format: 1,
             partition_by_field: Some(String::from("partition_by_fast_field")),
+            additional_fast_fields: Vec::new(),
         };
         let splits = test_sandbox
             .metastore()
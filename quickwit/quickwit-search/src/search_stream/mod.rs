@@ -17,6 +17,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+mod arrow;
 mod collector;
 mod leaf;
 mod root;
@@ -66,6 +67,10 @@ pub fn serialize<T: ToLittleEndian + Display>(
     match format {
         OutputFormat::Csv => serialize_csv(values, buffer),
         OutputFormat::ClickHouseRowBinary => serialize_click_house_row_binary(values, buffer),
+        OutputFormat::ArrowIpc => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Arrow IPC streams are built from the fast field columns",
+        )),
     }
 }
 
@@ -32,7 +32,7 @@ use tokio_stream::StreamMap;
 use tracing::*;
 
 use crate::cluster_client::ClusterClient;
-use crate::root::{refine_start_end_timestamp_from_ast, SearchJob};
+use crate::root::{refine_start_end_timestamp_from_ast, serialize_encryption_config, SearchJob};
 use crate::{list_relevant_splits, SearchError};
 
 /// Perform a distributed search stream.
@@ -60,7 +60,9 @@ pub async fn root_search_stream(
 
     let query_ast: QueryAst = serde_json::from_str(&search_stream_request.query_ast)
         .map_err(|err| SearchError::InvalidQuery(err.to_string()))?;
-    let query_ast_resolved = query_ast.parse_user_query(doc_mapper.default_search_fields())?;
+    let query_ast_resolved = query_ast
+        .parse_user_query(doc_mapper.default_search_fields())?
+        .resolve_field_aliases(&doc_mapper.field_aliases());
     let tags_filter_ast = extract_tags_from_query(query_ast_resolved.clone());
 
     if let Some(timestamp_field) = doc_mapper.timestamp_field_name() {
@@ -90,6 +92,8 @@ pub async fn root_search_stream(
         SearchError::Internal(format!("failed to serialize doc mapper: cause {err}"))
     })?;
 
+    let encryption_config_str_opt = serialize_encryption_config(&index_config)?;
+
     let index_uri: &Uri = &index_config.index_uri;
     let leaf_search_jobs: Vec<SearchJob> = split_metadatas.iter().map(SearchJob::from).collect();
     let assigned_leaf_search_jobs = cluster_client
@@ -103,6 +107,7 @@ pub async fn root_search_stream(
             &search_stream_request,
             &doc_mapper_str,
             index_uri.as_ref(),
+            encryption_config_str_opt.as_deref(),
             client_jobs,
         );
         let leaf_stream = cluster_client
@@ -119,6 +124,7 @@ fn jobs_to_leaf_request(
     request: &SearchStreamRequest,
     doc_mapper_str: &str,
     index_uri: &str, // TODO make Uri
+    encryption_config_str_opt: Option<&str>,
     jobs: Vec<SearchJob>,
 ) -> LeafSearchStreamRequest {
     LeafSearchStreamRequest {
@@ -126,6 +132,7 @@ fn jobs_to_leaf_request(
         split_offsets: jobs.into_iter().map(Into::into).collect(),
         doc_mapper: doc_mapper_str.to_string(),
         index_uri: index_uri.to_string(),
+        encryption_config: encryption_config_str_opt.map(str::to_string),
     }
 }
 
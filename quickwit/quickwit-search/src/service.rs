@@ -19,13 +19,13 @@
 
 use std::pin::Pin;
 use std::str::FromStr;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
 use bytes::Bytes;
 use quickwit_common::uri::Uri;
-use quickwit_config::SearcherConfig;
+use quickwit_config::{IndexEncryptionConfig, SearcherConfig, SearcherConfigOverrides};
 use quickwit_doc_mapper::DocMapper;
 use quickwit_proto::metastore::MetastoreServiceClient;
 use quickwit_proto::search::{
@@ -41,14 +41,17 @@ use quickwit_storage::{
 use tantivy::aggregation::AggregationLimits;
 use tokio::sync::Semaphore;
 use tokio_stream::wrappers::UnboundedReceiverStream;
+use tracing::info;
 
+use crate::aggregation_spill::AggregationSpillConfig;
 use crate::leaf_cache::LeafSearchCache;
 use crate::root::fetch_docs_phase;
 use crate::scroll_context::{MiniKV, ScrollContext, ScrollKeyAndStartOffset};
 use crate::search_stream::{leaf_search_stream, root_search_stream};
 use crate::{
-    fetch_docs, leaf_list_terms, leaf_search, root_list_terms, root_search, ClusterClient,
-    SearchError,
+    fetch_docs, leaf_list_terms, leaf_search, root_count, root_estimate_search_cost,
+    root_list_fields, root_list_terms, root_search, ClusterClient, CountResponseRest,
+    ListFieldsRequest, ListFieldsResponse, SearchCostEstimate, SearchError,
 };
 
 #[derive(Clone)]
@@ -124,6 +127,21 @@ pub trait SearchService: 'static + Send + Sync {
     /// Performs a scroll request.
     async fn scroll(&self, scroll_request: ScrollRequest) -> crate::Result<SearchResponse>;
 
+    /// Estimates the cost of a search request without executing it.
+    async fn root_estimate_search_cost(
+        &self,
+        request: SearchRequest,
+    ) -> crate::Result<SearchCostEstimate>;
+
+    /// Counts the documents matching a search request, without collecting or fetching hits.
+    async fn root_count(&self, request: SearchRequest) -> crate::Result<CountResponseRest>;
+
+    /// Lists the fields of the indexes matching some patterns.
+    async fn root_list_fields(
+        &self,
+        request: ListFieldsRequest,
+    ) -> crate::Result<ListFieldsResponse>;
+
     /// Stores a Key value in the local cache.
     /// This operation is not distributed. The distribution logic lives in
     /// the `ClusterClient`.
@@ -163,6 +181,17 @@ fn deserialize_doc_mapper(doc_mapper_str: &str) -> crate::Result<Arc<dyn DocMapp
     Ok(doc_mapper)
 }
 
+fn deserialize_encryption_config(
+    encryption_config_str_opt: Option<&str>,
+) -> crate::Result<Option<IndexEncryptionConfig>> {
+    encryption_config_str_opt
+        .map(serde_json::from_str::<IndexEncryptionConfig>)
+        .transpose()
+        .map_err(|err| {
+            SearchError::Internal(format!("failed to deserialize encryption config: `{err}`"))
+        })
+}
+
 #[async_trait]
 impl SearchService for SearchServiceImpl {
     async fn root_search(&self, search_request: SearchRequest) -> crate::Result<SearchResponse> {
@@ -185,7 +214,12 @@ impl SearchService for SearchServiceImpl {
             .ok_or_else(|| SearchError::Internal("no search request".to_string()))?
             .into();
         let index_uri = Uri::from_str(&leaf_search_request.index_uri)?;
-        let storage = self.storage_resolver.resolve(&index_uri).await?;
+        let encryption_config_opt =
+            deserialize_encryption_config(leaf_search_request.encryption_config.as_deref())?;
+        let storage = self
+            .storage_resolver
+            .resolve_index_storage(&index_uri, encryption_config_opt.as_ref())
+            .await?;
         let doc_mapper = deserialize_doc_mapper(&leaf_search_request.doc_mapper)?;
 
         let leaf_search_response = leaf_search(
@@ -205,7 +239,12 @@ impl SearchService for SearchServiceImpl {
         fetch_docs_request: FetchDocsRequest,
     ) -> crate::Result<FetchDocsResponse> {
         let index_uri = Uri::from_str(&fetch_docs_request.index_uri)?;
-        let storage = self.storage_resolver.resolve(&index_uri).await?;
+        let encryption_config_opt =
+            deserialize_encryption_config(fetch_docs_request.encryption_config.as_deref())?;
+        let storage = self
+            .storage_resolver
+            .resolve_index_storage(&index_uri, encryption_config_opt.as_ref())
+            .await?;
         let snippet_request_opt: Option<&SnippetRequest> =
             fetch_docs_request.snippet_request.as_ref();
         let doc_mapper = deserialize_doc_mapper(&fetch_docs_request.doc_mapper)?;
@@ -243,7 +282,12 @@ impl SearchService for SearchServiceImpl {
             .request
             .ok_or_else(|| SearchError::Internal("no search request".to_string()))?;
         let index_uri = Uri::from_str(&leaf_stream_request.index_uri)?;
-        let storage = self.storage_resolver.resolve(&index_uri).await?;
+        let encryption_config_opt =
+            deserialize_encryption_config(leaf_stream_request.encryption_config.as_deref())?;
+        let storage = self
+            .storage_resolver
+            .resolve_index_storage(&index_uri, encryption_config_opt.as_ref())
+            .await?;
         let doc_mapper = deserialize_doc_mapper(&leaf_stream_request.doc_mapper)?;
         let leaf_receiver = leaf_search_stream(
             self.searcher_context.clone(),
@@ -278,7 +322,12 @@ impl SearchService for SearchServiceImpl {
             .list_terms_request
             .ok_or_else(|| SearchError::Internal("no search request".to_string()))?;
         let index_uri = Uri::from_str(&leaf_search_request.index_uri)?;
-        let storage = self.storage_resolver.resolve(&index_uri).await?;
+        let encryption_config_opt =
+            deserialize_encryption_config(leaf_search_request.encryption_config.as_deref())?;
+        let storage = self
+            .storage_resolver
+            .resolve_index_storage(&index_uri, encryption_config_opt.as_ref())
+            .await?;
         let split_ids = leaf_search_request.split_offsets;
 
         let leaf_search_response = leaf_list_terms(
@@ -296,6 +345,36 @@ impl SearchService for SearchServiceImpl {
         scroll(scroll_request, &self.cluster_client, &self.searcher_context).await
     }
 
+    async fn root_estimate_search_cost(
+        &self,
+        search_request: SearchRequest,
+    ) -> crate::Result<SearchCostEstimate> {
+        root_estimate_search_cost(
+            &self.searcher_context,
+            search_request,
+            self.metastore.clone(),
+            &self.cluster_client,
+        )
+        .await
+    }
+
+    async fn root_count(&self, search_request: SearchRequest) -> crate::Result<CountResponseRest> {
+        root_count(
+            &self.searcher_context,
+            search_request,
+            self.metastore.clone(),
+            &self.cluster_client,
+        )
+        .await
+    }
+
+    async fn root_list_fields(
+        &self,
+        list_fields_request: ListFieldsRequest,
+    ) -> crate::Result<ListFieldsResponse> {
+        root_list_fields(list_fields_request, self.metastore.clone()).await
+    }
+
     async fn put_kv(&self, put_request: PutKvRequest) {
         let ttl = Duration::from_secs(put_request.ttl_secs as u64);
         self.search_after_cache
@@ -367,6 +446,7 @@ pub(crate) async fn scroll(
 
     // Fetch the actual documents.
     let hits: Vec<Hit> = fetch_docs_phase(
+        searcher_context,
         &scroll_context.indexes_metas_for_leaf_search,
         &partial_hits[..],
         &scroll_context.split_metadatas[..],
@@ -384,6 +464,11 @@ pub(crate) async fn scroll(
         scroll_id: next_scroll_id.as_ref().map(ToString::to_string),
         errors: Vec::new(),
         aggregation: None,
+        aggregation_arrow: None,
+        preview_coverage: None,
+        groups: Vec::new(),
+        failed_splits: Vec::new(),
+        skipped_split_ids: Vec::new(),
     })
 }
 /// [`SearcherContext`] provides a common set of variables
@@ -404,6 +489,8 @@ pub struct SearcherContext {
     pub leaf_search_cache: LeafSearchCache,
     /// Search split cache. `None` if no split cache is configured.
     pub split_cache_opt: Option<Arc<SplitCache>>,
+    /// Overrides of the searcher config applied at runtime, along with their version.
+    config_overrides: RwLock<(u64, SearcherConfigOverrides)>,
 }
 
 impl std::fmt::Debug for SearcherContext {
@@ -451,14 +538,153 @@ impl SearcherContext {
             split_stream_semaphore,
             leaf_search_cache,
             split_cache_opt,
+            config_overrides: RwLock::default(),
         }
     }
 
+    /// Returns the searcher config with the overrides applied at runtime.
+    pub fn effective_searcher_config(&self) -> SearcherConfig {
+        let config_overrides_guard = self.config_overrides.read().unwrap();
+        self.effective_searcher_config_with(&config_overrides_guard.1)
+    }
+
+    /// Returns the overrides of the searcher config applied at runtime, along with their version.
+    pub fn config_overrides(&self) -> (u64, SearcherConfigOverrides) {
+        self.config_overrides.read().unwrap().clone()
+    }
+
+    /// Applies overrides of the searcher config, replacing the previous ones. Overrides older than
+    /// the ones currently applied, i.e. with a lower or equal version, are ignored.
+    ///
+    /// Returns `true` if the overrides were applied.
+    pub fn apply_config_overrides(&self, version: u64, overrides: SearcherConfigOverrides) -> bool {
+        let mut config_overrides_guard = self.config_overrides.write().unwrap();
+
+        if version <= config_overrides_guard.0 {
+            return false;
+        }
+        let previous_searcher_config =
+            self.effective_searcher_config_with(&config_overrides_guard.1);
+        let new_searcher_config = self.effective_searcher_config_with(&overrides);
+
+        let previous_num_permits = previous_searcher_config.max_num_concurrent_split_searches;
+        let new_num_permits = new_searcher_config.max_num_concurrent_split_searches;
+
+        if new_num_permits > previous_num_permits {
+            self.leaf_search_split_semaphore
+                .add_permits(new_num_permits - previous_num_permits);
+        } else if new_num_permits < previous_num_permits {
+            // Permits in use cannot be revoked: the excess permits are withdrawn as soon as they
+            // are released.
+            let num_permits_to_forget = (previous_num_permits - new_num_permits) as u32;
+            let semaphore = self.leaf_search_split_semaphore.clone();
+            tokio::spawn(async move {
+                if let Ok(permits) = semaphore.acquire_many_owned(num_permits_to_forget).await {
+                    permits.forget();
+                }
+            });
+        }
+        if let Some(split_cache) = &self.split_cache_opt {
+            let previous_max_num_bytes_opt = previous_searcher_config
+                .split_cache
+                .map(|split_cache_limits| split_cache_limits.max_num_bytes);
+            let new_max_num_bytes_opt = new_searcher_config
+                .split_cache
+                .map(|split_cache_limits| split_cache_limits.max_num_bytes);
+
+            if let Some(new_max_num_bytes) = new_max_num_bytes_opt {
+                if previous_max_num_bytes_opt != Some(new_max_num_bytes) {
+                    split_cache.set_max_num_bytes(new_max_num_bytes);
+                }
+            }
+        }
+        info!(version, overrides=?overrides, "applied searcher config overrides");
+        *config_overrides_guard = (version, overrides);
+        true
+    }
+
+    fn effective_searcher_config_with(
+        &self,
+        overrides: &SearcherConfigOverrides,
+    ) -> SearcherConfig {
+        let mut searcher_config = self.searcher_config.clone();
+        overrides.apply(&mut searcher_config);
+        searcher_config
+    }
+
     /// Returns a new instance to track the aggregation memory usage.
     pub fn get_aggregation_limits(&self) -> AggregationLimits {
+        let searcher_config = self.effective_searcher_config();
         AggregationLimits::new(
-            Some(self.searcher_config.aggregation_memory_limit.as_u64()),
-            Some(self.searcher_config.aggregation_bucket_limit),
+            Some(searcher_config.aggregation_memory_limit.as_u64()),
+            Some(searcher_config.aggregation_bucket_limit),
         )
     }
+
+    /// Returns the settings of the aggregations spilling to disk.
+    pub(crate) fn get_aggregation_spill_config(&self) -> AggregationSpillConfig {
+        AggregationSpillConfig::from_searcher_config(&self.effective_searcher_config())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytesize::ByteSize;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_searcher_context_apply_config_overrides() {
+        let searcher_context = SearcherContext::for_test();
+        assert_eq!(
+            searcher_context
+                .leaf_search_split_semaphore
+                .available_permits(),
+            100
+        );
+        let overrides = SearcherConfigOverrides {
+            aggregation_memory_limit: Some(ByteSize::mb(100)),
+            max_num_concurrent_split_searches: Some(150),
+            ..Default::default()
+        };
+        assert!(searcher_context.apply_config_overrides(2, overrides.clone()));
+        assert_eq!(
+            searcher_context
+                .leaf_search_split_semaphore
+                .available_permits(),
+            150
+        );
+        let effective_searcher_config = searcher_context.effective_searcher_config();
+        assert_eq!(
+            effective_searcher_config.aggregation_memory_limit,
+            ByteSize::mb(100)
+        );
+        assert_eq!(searcher_context.config_overrides(), (2, overrides));
+
+        // Stale overrides are ignored.
+        assert!(!searcher_context.apply_config_overrides(1, SearcherConfigOverrides::default()));
+
+        let overrides = SearcherConfigOverrides {
+            max_num_concurrent_split_searches: Some(50),
+            ..Default::default()
+        };
+        assert!(searcher_context.apply_config_overrides(3, overrides));
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while searcher_context
+                .leaf_search_split_semaphore
+                .available_permits()
+                != 50
+            {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(
+            searcher_context
+                .effective_searcher_config()
+                .aggregation_memory_limit,
+            SearcherConfig::default().aggregation_memory_limit
+        );
+    }
 }
@@ -0,0 +1,390 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashSet;
+use std::iter::Peekable;
+use std::str::Chars;
+
+use anyhow::{bail, Context};
+
+/// Arithmetic expression over numeric fast fields, used to sort hits on a value computed at
+/// search time, e.g. `response_bytes / duration_ms`.
+///
+/// Expressions support numeric literals, the `+`, `-`, `*`, and `/` operators, and parentheses.
+/// Field names may contain hyphens, so a `-` operator must be followed by a whitespace, a digit,
+/// or an opening parenthesis.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum SortExpression {
+    Field(String),
+    Literal(f64),
+    Neg(Box<SortExpression>),
+    BinaryOp {
+        op: BinaryOp,
+        left: Box<SortExpression>,
+        right: Box<SortExpression>,
+    },
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl BinaryOp {
+    fn apply(&self, left: f64, right: f64) -> f64 {
+        match self {
+            BinaryOp::Add => left + right,
+            BinaryOp::Sub => left - right,
+            BinaryOp::Mul => left * right,
+            BinaryOp::Div => left / right,
+        }
+    }
+}
+
+impl SortExpression {
+    /// Returns whether a sort field is an expression rather than a field name. Field names cannot
+    /// contain whitespaces, operators other than `-`, or parentheses.
+    pub fn is_expression(sort_field: &str) -> bool {
+        sort_field.contains(|ch: char| ch.is_whitespace() || "+*/()".contains(ch))
+    }
+
+    pub fn parse(expression: &str) -> anyhow::Result<Self> {
+        let tokens = tokenize(expression)
+            .with_context(|| format!("failed to parse sort expression `{expression}`"))?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let sort_expression = parser
+            .parse_sum()
+            .and_then(|sort_expression| {
+                if let Some(token) = parser.peek() {
+                    bail!("unexpected token `{token}`");
+                }
+                Ok(sort_expression)
+            })
+            .with_context(|| format!("failed to parse sort expression `{expression}`"))?;
+        Ok(sort_expression)
+    }
+
+    /// Adds the names of the fields referenced by the expression to `field_names`.
+    pub fn add_field_names(&self, field_names: &mut HashSet<String>) {
+        match self {
+            SortExpression::Field(field_name) => {
+                field_names.insert(field_name.clone());
+            }
+            SortExpression::Literal(_) => {}
+            SortExpression::Neg(operand) => operand.add_field_names(field_names),
+            SortExpression::BinaryOp { left, right, .. } => {
+                left.add_field_names(field_names);
+                right.add_field_names(field_names);
+            }
+        }
+    }
+
+    /// Evaluates the expression, resolving the values of the fields with `field_value_fn`.
+    ///
+    /// Returns `None` if a field has no value or if the result is not a number, for instance
+    /// when dividing zero by zero.
+    pub fn eval<F>(&self, field_value_fn: &F) -> Option<f64>
+    where F: Fn(&str) -> Option<f64> {
+        let value = match self {
+            SortExpression::Field(field_name) => field_value_fn(field_name)?,
+            SortExpression::Literal(value) => *value,
+            SortExpression::Neg(operand) => -operand.eval(field_value_fn)?,
+            SortExpression::BinaryOp { op, left, right } => {
+                op.apply(left.eval(field_value_fn)?, right.eval(field_value_fn)?)
+            }
+        };
+        if value.is_nan() {
+            return None;
+        }
+        Some(value)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Op(BinaryOp),
+    OpenParen,
+    CloseParen,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Number(value) => write!(f, "{value}"),
+            Token::Ident(ident) => write!(f, "{ident}"),
+            Token::Op(BinaryOp::Add) => write!(f, "+"),
+            Token::Op(BinaryOp::Sub) => write!(f, "-"),
+            Token::Op(BinaryOp::Mul) => write!(f, "*"),
+            Token::Op(BinaryOp::Div) => write!(f, "/"),
+            Token::OpenParen => write!(f, "("),
+            Token::CloseParen => write!(f, ")"),
+        }
+    }
+}
+
+fn is_ident_start(ch: char) -> bool {
+    ch.is_ascii_alphabetic() || ch == '_' || ch == '-'
+}
+
+fn is_ident_char(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || ch == '_' || ch == '-' || ch == '.'
+}
+
+fn take_while(chars: &mut Peekable<Chars>, predicate: impl Fn(char) -> bool) -> String {
+    let mut taken = String::new();
+
+    while let Some(&ch) = chars.peek() {
+        if !predicate(ch) {
+            break;
+        }
+        taken.push(ch);
+        chars.next();
+    }
+    taken
+}
+
+fn tokenize(expression: &str) -> anyhow::Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = expression.chars().peekable();
+
+    while let Some(&ch) = chars.peek() {
+        if ch.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        let token = match ch {
+            '+' => Token::Op(BinaryOp::Add),
+            '*' => Token::Op(BinaryOp::Mul),
+            '/' => Token::Op(BinaryOp::Div),
+            '(' => Token::OpenParen,
+            ')' => Token::CloseParen,
+            '-' => {
+                let mut lookahead = chars.clone();
+                lookahead.next();
+                match lookahead.peek() {
+                    Some(&next_ch) if is_ident_char(next_ch) && !next_ch.is_ascii_digit() => {
+                        tokens.push(Token::Ident(take_while(&mut chars, is_ident_char)));
+                        continue;
+                    }
+                    _ => Token::Op(BinaryOp::Sub),
+                }
+            }
+            _ if ch.is_ascii_digit() || ch == '.' => {
+                let number_str = take_while(&mut chars, |ch| ch.is_ascii_digit() || ch == '.');
+                let number = number_str
+                    .parse::<f64>()
+                    .with_context(|| format!("invalid number `{number_str}`"))?;
+                tokens.push(Token::Number(number));
+                continue;
+            }
+            _ if is_ident_start(ch) => {
+                tokens.push(Token::Ident(take_while(&mut chars, is_ident_char)));
+                continue;
+            }
+            _ => bail!("unexpected character `{ch}`"),
+        };
+        tokens.push(token);
+        chars.next();
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token_opt = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token_opt
+    }
+
+    fn parse_binary_ops(
+        &mut self,
+        ops: [BinaryOp; 2],
+        parse_operand: fn(&mut Self) -> anyhow::Result<SortExpression>,
+    ) -> anyhow::Result<SortExpression> {
+        let mut expression = parse_operand(self)?;
+
+        while let Some(Token::Op(op)) = self.peek() {
+            let op = *op;
+            if !ops.contains(&op) {
+                break;
+            }
+            self.pos += 1;
+            let right = parse_operand(self)?;
+            expression = SortExpression::BinaryOp {
+                op,
+                left: Box::new(expression),
+                right: Box::new(right),
+            };
+        }
+        Ok(expression)
+    }
+
+    fn parse_sum(&mut self) -> anyhow::Result<SortExpression> {
+        self.parse_binary_ops([BinaryOp::Add, BinaryOp::Sub], Self::parse_product)
+    }
+
+    fn parse_product(&mut self) -> anyhow::Result<SortExpression> {
+        self.parse_binary_ops([BinaryOp::Mul, BinaryOp::Div], Self::parse_unary)
+    }
+
+    fn parse_unary(&mut self) -> anyhow::Result<SortExpression> {
+        match self.next() {
+            Some(Token::Op(BinaryOp::Sub)) => {
+                let operand = self.parse_unary()?;
+                Ok(SortExpression::Neg(Box::new(operand)))
+            }
+            Some(Token::Number(value)) => Ok(SortExpression::Literal(value)),
+            Some(Token::Ident(field_name)) => Ok(SortExpression::Field(field_name)),
+            Some(Token::OpenParen) => {
+                let expression = self.parse_sum()?;
+                match self.next() {
+                    Some(Token::CloseParen) => Ok(expression),
+                    Some(token) => bail!("expected `)`, got `{token}`"),
+                    None => bail!("expected `)`, got end of expression"),
+                }
+            }
+            Some(token) => bail!("unexpected token `{token}`"),
+            None => bail!("unexpected end of expression"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(field_name: &str) -> Box<SortExpression> {
+        Box::new(SortExpression::Field(field_name.to_string()))
+    }
+
+    #[test]
+    fn test_sort_expression_is_expression() {
+        assert!(!SortExpression::is_expression("response_bytes"));
+        assert!(!SortExpression::is_expression("response-bytes"));
+        assert!(!SortExpression::is_expression("attributes.duration_ms"));
+        assert!(SortExpression::is_expression("response_bytes/duration_ms"));
+        assert!(SortExpression::is_expression(
+            "response_bytes - duration_ms"
+        ));
+        assert!(SortExpression::is_expression("(response_bytes)"));
+    }
+
+    #[test]
+    fn test_sort_expression_parse() {
+        assert_eq!(
+            SortExpression::parse("response_bytes / duration_ms").unwrap(),
+            SortExpression::BinaryOp {
+                op: BinaryOp::Div,
+                left: field("response_bytes"),
+                right: field("duration_ms"),
+            }
+        );
+        assert_eq!(
+            SortExpression::parse("a + b * 2").unwrap(),
+            SortExpression::BinaryOp {
+                op: BinaryOp::Add,
+                left: field("a"),
+                right: Box::new(SortExpression::BinaryOp {
+                    op: BinaryOp::Mul,
+                    left: field("b"),
+                    right: Box::new(SortExpression::Literal(2.0)),
+                }),
+            }
+        );
+        assert_eq!(
+            SortExpression::parse("(a-b - c) * -1.5").unwrap(),
+            SortExpression::BinaryOp {
+                op: BinaryOp::Mul,
+                left: Box::new(SortExpression::BinaryOp {
+                    op: BinaryOp::Sub,
+                    left: field("a-b"),
+                    right: field("c"),
+                }),
+                right: Box::new(SortExpression::Neg(Box::new(SortExpression::Literal(1.5)))),
+            }
+        );
+        assert_eq!(
+            SortExpression::parse("a - b - c").unwrap(),
+            SortExpression::BinaryOp {
+                op: BinaryOp::Sub,
+                left: Box::new(SortExpression::BinaryOp {
+                    op: BinaryOp::Sub,
+                    left: field("a"),
+                    right: field("b"),
+                }),
+                right: field("c"),
+            }
+        );
+        for invalid_expression in ["a +", "(a + b", "a b", "a + $", "a / ()", "1..2 * a"] {
+            let error = SortExpression::parse(invalid_expression).unwrap_err();
+            assert!(
+                error
+                    .to_string()
+                    .contains("failed to parse sort expression"),
+                "{invalid_expression}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_sort_expression_field_names() {
+        let sort_expression = SortExpression::parse("(a + b) / a * 10").unwrap();
+        let mut field_names = HashSet::new();
+        sort_expression.add_field_names(&mut field_names);
+        assert_eq!(
+            field_names,
+            HashSet::from_iter(["a".to_string(), "b".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_sort_expression_eval() {
+        let field_value_fn = |field_name: &str| match field_name {
+            "a" => Some(6.0),
+            "b" => Some(3.0),
+            "zero" => Some(0.0),
+            _ => None,
+        };
+        let eval = |expression: &str| {
+            SortExpression::parse(expression)
+                .unwrap()
+                .eval(&field_value_fn)
+        };
+        assert_eq!(eval("a / b"), Some(2.0));
+        assert_eq!(eval("a - b * 2"), Some(0.0));
+        assert_eq!(eval("-(a + b) / 3"), Some(-3.0));
+        assert_eq!(eval("a / zero"), Some(f64::INFINITY));
+        assert_eq!(eval("zero / zero"), None);
+        assert_eq!(eval("a + missing"), None);
+    }
+}
@@ -0,0 +1,674 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use fnv::FnvHashMap;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use tantivy::columnar::StrColumn;
+use tantivy::fastfield::Column;
+use tantivy::{DocId, SegmentReader};
+
+use crate::aggregation_spill::{AggregationSpillConfig, PartialBucket, SpillableBuckets};
+
+/// Aggregations counting the occurrences of the terms of a fast field in the matching documents
+/// and, for significant terms, in all the documents of the searched splits.
+///
+/// ```json
+/// {
+///     "unusual_error_codes": {
+///         "significant_terms": { "field": "error_code", "size": 5 }
+///     }
+/// }
+/// ```
+pub type TermFrequencyAggregations = HashMap<String, TermFrequencyAggregation>;
+
+/// Available term frequency aggregations.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TermFrequencyAggregation {
+    /// Returns the terms occurring in at most `max_doc_count` matching documents.
+    RareTerms(RareTermsAggregation),
+    /// Returns the terms occurring unusually often in the matching documents compared to all the
+    /// documents of the searched splits, i.e. the splits left after pruning by time range and tags.
+    SignificantTerms(SignificantTermsAggregation),
+}
+
+impl TermFrequencyAggregation {
+    fn field(&self) -> &str {
+        match self {
+            Self::RareTerms(rare_terms) => &rare_terms.field,
+            Self::SignificantTerms(significant_terms) => &significant_terms.field,
+        }
+    }
+
+    fn requires_background_counts(&self) -> bool {
+        matches!(self, Self::SignificantTerms(_))
+    }
+}
+
+/// Parameters of the `rare_terms` aggregation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RareTermsAggregation {
+    /// The name of the fast field to aggregate on.
+    pub field: String,
+    /// The maximum number of matching documents a term can occur in to be considered rare.
+    #[serde(default = "RareTermsAggregation::default_max_doc_count")]
+    pub max_doc_count: u64,
+}
+
+impl RareTermsAggregation {
+    fn default_max_doc_count() -> u64 {
+        1
+    }
+}
+
+/// Parameters of the `significant_terms` aggregation. Terms are scored with the JLH heuristic.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SignificantTermsAggregation {
+    /// The name of the fast field to aggregate on.
+    pub field: String,
+    /// The number of terms to return.
+    #[serde(default = "SignificantTermsAggregation::default_size")]
+    pub size: usize,
+    /// The minimum number of matching documents a term must occur in to be returned.
+    #[serde(default = "SignificantTermsAggregation::default_min_doc_count")]
+    pub min_doc_count: u64,
+}
+
+impl SignificantTermsAggregation {
+    fn default_size() -> usize {
+        10
+    }
+
+    fn default_min_doc_count() -> u64 {
+        3
+    }
+}
+
+/// The names of the fast fields accessed by the term frequency aggregations.
+pub(crate) fn fast_field_names(aggregations: &TermFrequencyAggregations) -> HashSet<String> {
+    aggregations
+        .values()
+        .map(|aggregation| aggregation.field().to_string())
+        .collect()
+}
+
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub(crate) struct TermCounts {
+    /// Number of matching documents containing the term.
+    doc_count: u64,
+    /// Number of documents of the searched splits containing the term.
+    bg_count: u64,
+}
+
+impl PartialBucket for TermCounts {
+    fn merge(&mut self, other: TermCounts) {
+        self.doc_count += other.doc_count;
+        self.bg_count += other.bg_count;
+    }
+}
+
+/// Term counts of a single aggregation, mergeable across segments and splits.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub(crate) struct IntermediateTermFrequencies {
+    num_docs: u64,
+    num_bg_docs: u64,
+    term_counts: HashMap<String, TermCounts>,
+}
+
+/// Intermediate results of the term frequency aggregations, keyed by aggregation name.
+pub(crate) type IntermediateTermFrequencyResults = HashMap<String, IntermediateTermFrequencies>;
+
+/// Merges the term counts of a single aggregation. The term counts exceeding the in-memory limit
+/// are spilled to disk.
+struct TermFrequenciesMerger {
+    num_docs: u64,
+    num_bg_docs: u64,
+    term_counts: SpillableBuckets<TermCounts>,
+}
+
+impl TermFrequenciesMerger {
+    fn new(spill_config: AggregationSpillConfig) -> Self {
+        Self {
+            num_docs: 0,
+            num_bg_docs: 0,
+            term_counts: SpillableBuckets::new(spill_config),
+        }
+    }
+
+    fn add(&mut self, term_frequencies: IntermediateTermFrequencies) -> tantivy::Result<()> {
+        self.num_docs += term_frequencies.num_docs;
+        self.num_bg_docs += term_frequencies.num_bg_docs;
+
+        for (term, term_counts) in term_frequencies.term_counts {
+            self.term_counts.add(term, term_counts)?;
+        }
+        Ok(())
+    }
+
+    fn finalize(self) -> tantivy::Result<IntermediateTermFrequencies> {
+        let term_counts = self
+            .term_counts
+            .into_sorted_buckets()?
+            .collect::<tantivy::Result<_>>()?;
+        Ok(IntermediateTermFrequencies {
+            num_docs: self.num_docs,
+            num_bg_docs: self.num_bg_docs,
+            term_counts,
+        })
+    }
+}
+
+/// Merges the intermediate results of several segments or splits. The intermediate results are
+/// consumed one at a time so that only the merged term counts and the current result are held in
+/// memory.
+pub(crate) fn merge_intermediate_results(
+    intermediate_results: impl IntoIterator<Item = tantivy::Result<IntermediateTermFrequencyResults>>,
+    spill_config: &AggregationSpillConfig,
+) -> tantivy::Result<IntermediateTermFrequencyResults> {
+    let mut mergers: HashMap<String, TermFrequenciesMerger> = HashMap::new();
+
+    for intermediate_result in intermediate_results {
+        for (aggregation_name, term_frequencies) in intermediate_result? {
+            mergers
+                .entry(aggregation_name)
+                .or_insert_with(|| TermFrequenciesMerger::new(spill_config.clone()))
+                .add(term_frequencies)?;
+        }
+    }
+    mergers
+        .into_iter()
+        .map(|(aggregation_name, merger)| Ok((aggregation_name, merger.finalize()?)))
+        .collect()
+}
+
+enum TermsColumn {
+    Str(StrColumn),
+    I64(Column<i64>),
+    U64(Column<u64>),
+}
+
+impl TermsColumn {
+    fn open(segment_reader: &SegmentReader, field_name: &str) -> tantivy::Result<Option<Self>> {
+        let fast_fields = segment_reader.fast_fields();
+
+        if let Some(str_column) = fast_fields.str(field_name)? {
+            return Ok(Some(Self::Str(str_column)));
+        }
+        if let Some(i64_column) = fast_fields.column_opt::<i64>(field_name)? {
+            return Ok(Some(Self::I64(i64_column)));
+        }
+        if let Some(u64_column) = fast_fields.column_opt::<u64>(field_name)? {
+            return Ok(Some(Self::U64(u64_column)));
+        }
+        Ok(None)
+    }
+
+    /// Appends the distinct term keys of `doc` to `keys`. For string columns, the keys are the
+    /// term ordinals. For numeric columns, the keys are the values mapped to `u64`.
+    fn distinct_keys(&self, doc: DocId, keys: &mut Vec<u64>) {
+        keys.clear();
+
+        match self {
+            Self::Str(str_column) => keys.extend(str_column.term_ords(doc)),
+            Self::I64(i64_column) => {
+                keys.extend(i64_column.values_for_doc(doc).map(|value| value as u64))
+            }
+            Self::U64(u64_column) => keys.extend(u64_column.values_for_doc(doc)),
+        }
+        keys.sort_unstable();
+        keys.dedup();
+    }
+
+    fn key_to_term(&self, key: u64, buffer: &mut String) -> tantivy::Result<String> {
+        match self {
+            Self::Str(str_column) => {
+                buffer.clear();
+                str_column.ord_to_str(key, buffer)?;
+                Ok(buffer.clone())
+            }
+            Self::I64(_) => Ok((key as i64).to_string()),
+            Self::U64(_) => Ok(key.to_string()),
+        }
+    }
+}
+
+struct TermFrequencySegmentCollectorEntry {
+    aggregation_name: String,
+    requires_background_counts: bool,
+    terms_column_opt: Option<TermsColumn>,
+    num_docs: u64,
+    doc_counts: FnvHashMap<u64, u64>,
+}
+
+/// Counts the terms of the matching documents of a segment for each term frequency aggregation.
+pub(crate) struct TermFrequencySegmentCollector {
+    entries: Vec<TermFrequencySegmentCollectorEntry>,
+    segment_reader: SegmentReader,
+    spill_config: AggregationSpillConfig,
+    keys_buffer: Vec<u64>,
+}
+
+impl TermFrequencySegmentCollector {
+    pub fn for_segment(
+        aggregations: &TermFrequencyAggregations,
+        segment_reader: &SegmentReader,
+        spill_config: &AggregationSpillConfig,
+    ) -> tantivy::Result<Self> {
+        let mut entries = Vec::with_capacity(aggregations.len());
+
+        for (aggregation_name, aggregation) in aggregations {
+            let terms_column_opt = TermsColumn::open(segment_reader, aggregation.field())?;
+            let entry = TermFrequencySegmentCollectorEntry {
+                aggregation_name: aggregation_name.clone(),
+                requires_background_counts: aggregation.requires_background_counts(),
+                terms_column_opt,
+                num_docs: 0,
+                doc_counts: FnvHashMap::default(),
+            };
+            entries.push(entry);
+        }
+        Ok(Self {
+            entries,
+            segment_reader: segment_reader.clone(),
+            spill_config: spill_config.clone(),
+            keys_buffer: Vec::new(),
+        })
+    }
+
+    pub fn collect(&mut self, doc: DocId) {
+        for entry in &mut self.entries {
+            entry.num_docs += 1;
+
+            let Some(terms_column) = &entry.terms_column_opt else {
+                continue;
+            };
+            terms_column.distinct_keys(doc, &mut self.keys_buffer);
+
+            for key in &self.keys_buffer {
+                *entry.doc_counts.entry(*key).or_default() += 1;
+            }
+        }
+    }
+
+    pub fn harvest(self) -> tantivy::Result<IntermediateTermFrequencyResults> {
+        let mut intermediate_results = IntermediateTermFrequencyResults::default();
+        let mut keys_buffer = self.keys_buffer;
+        let mut term_buffer = String::new();
+
+        for entry in self.entries {
+            // Past the in-memory limit, the term counts of the segment are spilled when merged
+            // with the ones of the other segments, if spilling is enabled.
+            self.spill_config
+                .check_num_buckets(entry.doc_counts.len())?;
+
+            let mut term_frequencies = IntermediateTermFrequencies {
+                num_docs: entry.num_docs,
+                ..Default::default()
+            };
+            let Some(terms_column) = entry.terms_column_opt else {
+                if entry.requires_background_counts {
+                    term_frequencies.num_bg_docs = self.segment_reader.num_docs() as u64;
+                }
+                intermediate_results.insert(entry.aggregation_name, term_frequencies);
+                continue;
+            };
+            // The background counts are only computed for the terms present in the matching
+            // documents.
+            let mut bg_counts: FnvHashMap<u64, u64> = FnvHashMap::default();
+
+            if entry.requires_background_counts {
+                for doc in self.segment_reader.doc_ids_alive() {
+                    term_frequencies.num_bg_docs += 1;
+                    terms_column.distinct_keys(doc, &mut keys_buffer);
+
+                    for key in &keys_buffer {
+                        if entry.doc_counts.contains_key(key) {
+                            *bg_counts.entry(*key).or_default() += 1;
+                        }
+                    }
+                }
+            }
+            for (key, doc_count) in entry.doc_counts {
+                let term = terms_column.key_to_term(key, &mut term_buffer)?;
+                let term_counts = TermCounts {
+                    doc_count,
+                    bg_count: bg_counts.get(&key).copied().unwrap_or_default(),
+                };
+                term_frequencies.term_counts.insert(term, term_counts);
+            }
+            intermediate_results.insert(entry.aggregation_name, term_frequencies);
+        }
+        Ok(intermediate_results)
+    }
+}
+
+/// Final result of a term frequency aggregation.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub(crate) enum TermFrequencyAggregationResult {
+    RareTerms(RareTermsResult),
+    SignificantTerms(SignificantTermsResult),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct RareTermsResult {
+    buckets: Vec<RareTermsBucket>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct RareTermsBucket {
+    key: String,
+    doc_count: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct SignificantTermsResult {
+    doc_count: u64,
+    bg_count: u64,
+    buckets: Vec<SignificantTermsBucket>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct SignificantTermsBucket {
+    key: String,
+    doc_count: u64,
+    bg_count: u64,
+    score: f64,
+}
+
+/// Computes the JLH score of a term, which rewards terms whose frequency in the matching documents
+/// increases both absolutely and relatively compared to their frequency in the searched splits.
+fn jlh_score(doc_count: u64, num_docs: u64, bg_count: u64, num_bg_docs: u64) -> f64 {
+    if num_docs == 0 || bg_count == 0 || num_bg_docs == 0 {
+        return 0.0;
+    }
+    let fg_ratio = doc_count as f64 / num_docs as f64;
+    let bg_ratio = bg_count as f64 / num_bg_docs as f64;
+
+    if fg_ratio <= bg_ratio {
+        return 0.0;
+    }
+    (fg_ratio - bg_ratio) * (fg_ratio / bg_ratio)
+}
+
+fn finalize_rare_terms(
+    rare_terms: &RareTermsAggregation,
+    term_frequencies: IntermediateTermFrequencies,
+) -> RareTermsResult {
+    let buckets = term_frequencies
+        .term_counts
+        .into_iter()
+        .filter(|(_, term_counts)| term_counts.doc_count <= rare_terms.max_doc_count)
+        .map(|(key, term_counts)| RareTermsBucket {
+            key,
+            doc_count: term_counts.doc_count,
+        })
+        .sorted_unstable_by(|left, right| {
+            left.doc_count
+                .cmp(&right.doc_count)
+                .then_with(|| left.key.cmp(&right.key))
+        })
+        .collect();
+    RareTermsResult { buckets }
+}
+
+fn finalize_significant_terms(
+    significant_terms: &SignificantTermsAggregation,
+    term_frequencies: IntermediateTermFrequencies,
+) -> SignificantTermsResult {
+    let num_docs = term_frequencies.num_docs;
+    let num_bg_docs = term_frequencies.num_bg_docs;
+    let buckets = term_frequencies
+        .term_counts
+        .into_iter()
+        .filter(|(_, term_counts)| term_counts.doc_count >= significant_terms.min_doc_count)
+        .map(|(key, term_counts)| SignificantTermsBucket {
+            key,
+            doc_count: term_counts.doc_count,
+            bg_count: term_counts.bg_count,
+            score: jlh_score(
+                term_counts.doc_count,
+                num_docs,
+                term_counts.bg_count,
+                num_bg_docs,
+            ),
+        })
+        .filter(|bucket| bucket.score > 0.0)
+        .sorted_unstable_by(|left, right| {
+            right
+                .score
+                .partial_cmp(&left.score)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| left.key.cmp(&right.key))
+        })
+        .take(significant_terms.size)
+        .collect();
+    SignificantTermsResult {
+        doc_count: num_docs,
+        bg_count: num_bg_docs,
+        buckets,
+    }
+}
+
+/// Turns the merged intermediate results into the final results, keyed by aggregation name.
+pub(crate) fn finalize_results(
+    aggregations: &TermFrequencyAggregations,
+    mut intermediate_results: IntermediateTermFrequencyResults,
+) -> BTreeMap<String, TermFrequencyAggregationResult> {
+    aggregations
+        .iter()
+        .map(|(aggregation_name, aggregation)| {
+            let term_frequencies = intermediate_results
+                .remove(aggregation_name)
+                .unwrap_or_default();
+            let result = match aggregation {
+                TermFrequencyAggregation::RareTerms(rare_terms) => {
+                    TermFrequencyAggregationResult::RareTerms(finalize_rare_terms(
+                        rare_terms,
+                        term_frequencies,
+                    ))
+                }
+                TermFrequencyAggregation::SignificantTerms(significant_terms) => {
+                    TermFrequencyAggregationResult::SignificantTerms(finalize_significant_terms(
+                        significant_terms,
+                        term_frequencies,
+                    ))
+                }
+            };
+            (aggregation_name.clone(), result)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collector::QuickwitAggregations;
+
+    fn term_frequencies_for_test(
+        num_docs: u64,
+        num_bg_docs: u64,
+        term_counts: &[(&str, u64, u64)],
+    ) -> IntermediateTermFrequencies {
+        IntermediateTermFrequencies {
+            num_docs,
+            num_bg_docs,
+            term_counts: term_counts
+                .iter()
+                .map(|(term, doc_count, bg_count)| {
+                    let term_counts = TermCounts {
+                        doc_count: *doc_count,
+                        bg_count: *bg_count,
+                    };
+                    (term.to_string(), term_counts)
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_term_frequency_aggregations_serde() {
+        let aggregations_json = r#"{
+            "rare_error_codes": {
+                "rare_terms": { "field": "error_code", "max_doc_count": 2 }
+            },
+            "unusual_error_codes": {
+                "significant_terms": { "field": "error_code" }
+            }
+        }"#;
+        let aggregations: QuickwitAggregations = serde_json::from_str(aggregations_json).unwrap();
+        let QuickwitAggregations::TermFrequencyAggregations(aggregations) = aggregations else {
+            panic!("Expected TermFrequencyAggregations");
+        };
+        let TermFrequencyAggregation::RareTerms(rare_terms) = &aggregations["rare_error_codes"]
+        else {
+            panic!("Expected RareTerms");
+        };
+        assert_eq!(rare_terms.field, "error_code");
+        assert_eq!(rare_terms.max_doc_count, 2);
+
+        let TermFrequencyAggregation::SignificantTerms(significant_terms) =
+            &aggregations["unusual_error_codes"]
+        else {
+            panic!("Expected SignificantTerms");
+        };
+        assert_eq!(significant_terms.field, "error_code");
+        assert_eq!(significant_terms.size, 10);
+        assert_eq!(significant_terms.min_doc_count, 3);
+
+        assert_eq!(
+            fast_field_names(&aggregations),
+            HashSet::from_iter(["error_code".to_string()])
+        );
+        let aggregations_json = r#"{
+            "rare_error_codes": {
+                "rare_terms": { "field": "error_code", "size": 2 }
+            }
+        }"#;
+        serde_json::from_str::<QuickwitAggregations>(aggregations_json).unwrap_err();
+    }
+
+    #[test]
+    fn test_merge_intermediate_results() {
+        let left = IntermediateTermFrequencyResults::from_iter([(
+            "agg".to_string(),
+            term_frequencies_for_test(10, 100, &[("a", 1, 10), ("b", 2, 20)]),
+        )]);
+        let right = IntermediateTermFrequencyResults::from_iter([(
+            "agg".to_string(),
+            term_frequencies_for_test(5, 50, &[("b", 3, 5), ("c", 1, 1)]),
+        )]);
+        let merged_results =
+            merge_intermediate_results([Ok(left), Ok(right)], &AggregationSpillConfig::default())
+                .unwrap();
+        assert_eq!(
+            merged_results["agg"],
+            term_frequencies_for_test(15, 150, &[("a", 1, 10), ("b", 5, 25), ("c", 1, 1)])
+        );
+    }
+
+    #[test]
+    fn test_merge_intermediate_results_with_spill() {
+        let spill_dir = tempfile::tempdir().unwrap();
+        let spill_config = AggregationSpillConfig {
+            max_num_buckets_in_memory: 1,
+            spill_dir: spill_dir.path().to_path_buf(),
+            spill_budget: bytesize::ByteSize::mb(1),
+        };
+        let left = IntermediateTermFrequencyResults::from_iter([(
+            "agg".to_string(),
+            term_frequencies_for_test(10, 100, &[("a", 1, 10), ("b", 2, 20)]),
+        )]);
+        let right = IntermediateTermFrequencyResults::from_iter([(
+            "agg".to_string(),
+            term_frequencies_for_test(5, 50, &[("b", 3, 5), ("c", 1, 1)]),
+        )]);
+        let merged_results =
+            merge_intermediate_results([Ok(left.clone()), Ok(right.clone())], &spill_config)
+                .unwrap();
+        assert_eq!(
+            merged_results["agg"],
+            term_frequencies_for_test(15, 150, &[("a", 1, 10), ("b", 5, 25), ("c", 1, 1)])
+        );
+        let spill_config = AggregationSpillConfig {
+            spill_budget: bytesize::ByteSize(0),
+            ..spill_config
+        };
+        let error = merge_intermediate_results([Ok(left), Ok(right)], &spill_config).unwrap_err();
+        assert!(error.to_string().contains("aggregation limit exceeded"));
+    }
+
+    #[test]
+    fn test_finalize_rare_terms() {
+        let rare_terms = RareTermsAggregation {
+            field: "error_code".to_string(),
+            max_doc_count: 2,
+        };
+        let term_frequencies =
+            term_frequencies_for_test(10, 0, &[("a", 3, 0), ("b", 2, 0), ("c", 1, 0), ("d", 1, 0)]);
+        let rare_terms_result = finalize_rare_terms(&rare_terms, term_frequencies);
+        let buckets: Vec<(&str, u64)> = rare_terms_result
+            .buckets
+            .iter()
+            .map(|bucket| (bucket.key.as_str(), bucket.doc_count))
+            .collect();
+        assert_eq!(buckets, [("c", 1), ("d", 1), ("b", 2)]);
+    }
+
+    #[test]
+    fn test_finalize_significant_terms() {
+        let significant_terms = SignificantTermsAggregation {
+            field: "error_code".to_string(),
+            size: 2,
+            min_doc_count: 2,
+        };
+        // `a` is as frequent in the matching documents as in the index, `b` and `c` are
+        // overrepresented, and `d` does not occur often enough.
+        let term_frequencies = term_frequencies_for_test(
+            10,
+            1_000,
+            &[("a", 5, 500), ("b", 4, 10), ("c", 2, 100), ("d", 1, 1)],
+        );
+        let significant_terms_result =
+            finalize_significant_terms(&significant_terms, term_frequencies);
+        assert_eq!(significant_terms_result.doc_count, 10);
+        assert_eq!(significant_terms_result.bg_count, 1_000);
+
+        let keys: Vec<&str> = significant_terms_result
+            .buckets
+            .iter()
+            .map(|bucket| bucket.key.as_str())
+            .collect();
+        assert_eq!(keys, ["b", "c"]);
+    }
+
+    #[test]
+    fn test_jlh_score() {
+        assert_eq!(jlh_score(0, 0, 0, 0), 0.0);
+        assert_eq!(jlh_score(5, 10, 500, 1_000), 0.0);
+        assert_eq!(jlh_score(1, 10, 500, 1_000), 0.0);
+        assert!(jlh_score(4, 10, 10, 1_000) > jlh_score(2, 10, 100, 1_000));
+    }
+}
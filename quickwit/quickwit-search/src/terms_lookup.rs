@@ -0,0 +1,377 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{BTreeSet, HashMap};
+use std::convert::Infallible;
+
+use futures::future::BoxFuture;
+use quickwit_proto::metastore::MetastoreServiceClient;
+use quickwit_proto::search::SearchRequest;
+use quickwit_query::query_ast::{BoolQuery, QueryAst, QueryAstVisitor, TermsLookupQuery};
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+use tracing::info;
+
+use crate::root::root_search;
+use crate::service::SearcherContext;
+use crate::{ClusterClient, SearchError};
+
+const TERMS_LOOKUP_AGGREGATION_NAME: &str = "terms_lookup";
+
+#[derive(Default)]
+struct ContainsTermsLookup {
+    contains_terms_lookup: bool,
+}
+
+impl<'a> QueryAstVisitor<'a> for ContainsTermsLookup {
+    type Err = Infallible;
+
+    fn visit_terms_lookup(&mut self, _: &'a TermsLookupQuery) -> Result<(), Infallible> {
+        self.contains_terms_lookup = true;
+        Ok(())
+    }
+}
+
+fn contains_terms_lookup(query_ast: &QueryAst) -> bool {
+    let mut visitor = ContainsTermsLookup::default();
+    visitor
+        .visit(query_ast)
+        .expect("can't fail unwrapping Infallible");
+    visitor.contains_terms_lookup
+}
+
+/// Executes the terms lookups of the request query AST, if any, and substitutes them with the
+/// equivalent term set queries.
+pub(crate) async fn resolve_terms_lookups(
+    searcher_context: &SearcherContext,
+    search_request: &mut SearchRequest,
+    metastore: &MetastoreServiceClient,
+    cluster_client: &ClusterClient,
+) -> crate::Result<()> {
+    let query_ast: QueryAst = serde_json::from_str(&search_request.query_ast)
+        .map_err(|err| SearchError::InvalidQuery(err.to_string()))?;
+
+    if !contains_terms_lookup(&query_ast) {
+        return Ok(());
+    }
+    let query_ast_resolved =
+        resolve_terms_lookups_in_ast(query_ast, searcher_context, metastore, cluster_client)
+            .await?;
+    search_request.query_ast = serde_json::to_string(&query_ast_resolved)?;
+    Ok(())
+}
+
+fn resolve_terms_lookups_in_asts<'a>(
+    query_asts: Vec<QueryAst>,
+    searcher_context: &'a SearcherContext,
+    metastore: &'a MetastoreServiceClient,
+    cluster_client: &'a ClusterClient,
+) -> BoxFuture<'a, crate::Result<Vec<QueryAst>>> {
+    Box::pin(async move {
+        let mut query_asts_resolved = Vec::with_capacity(query_asts.len());
+
+        for query_ast in query_asts {
+            let query_ast_resolved = resolve_terms_lookups_in_ast(
+                query_ast,
+                searcher_context,
+                metastore,
+                cluster_client,
+            )
+            .await?;
+            query_asts_resolved.push(query_ast_resolved);
+        }
+        Ok(query_asts_resolved)
+    })
+}
+
+fn resolve_terms_lookups_in_ast<'a>(
+    query_ast: QueryAst,
+    searcher_context: &'a SearcherContext,
+    metastore: &'a MetastoreServiceClient,
+    cluster_client: &'a ClusterClient,
+) -> BoxFuture<'a, crate::Result<QueryAst>> {
+    Box::pin(async move {
+        match query_ast {
+            QueryAst::Bool(BoolQuery {
+                must,
+                must_not,
+                should,
+                filter,
+            }) => {
+                let must = resolve_terms_lookups_in_asts(
+                    must,
+                    searcher_context,
+                    metastore,
+                    cluster_client,
+                )
+                .await?;
+                let must_not = resolve_terms_lookups_in_asts(
+                    must_not,
+                    searcher_context,
+                    metastore,
+                    cluster_client,
+                )
+                .await?;
+                let should = resolve_terms_lookups_in_asts(
+                    should,
+                    searcher_context,
+                    metastore,
+                    cluster_client,
+                )
+                .await?;
+                let filter = resolve_terms_lookups_in_asts(
+                    filter,
+                    searcher_context,
+                    metastore,
+                    cluster_client,
+                )
+                .await?;
+                Ok(BoolQuery {
+                    must,
+                    must_not,
+                    should,
+                    filter,
+                }
+                .into())
+            }
+            QueryAst::Boost { underlying, boost } => {
+                let underlying = resolve_terms_lookups_in_ast(
+                    *underlying,
+                    searcher_context,
+                    metastore,
+                    cluster_client,
+                )
+                .await?;
+                Ok(QueryAst::Boost {
+                    underlying: Box::new(underlying),
+                    boost,
+                })
+            }
+            QueryAst::TermsLookup(terms_lookup_query) => {
+                execute_terms_lookup(
+                    terms_lookup_query,
+                    searcher_context,
+                    metastore,
+                    cluster_client,
+                )
+                .await
+            }
+            query_ast => Ok(query_ast),
+        }
+    })
+}
+
+async fn execute_terms_lookup(
+    terms_lookup_query: TermsLookupQuery,
+    searcher_context: &SearcherContext,
+    metastore: &MetastoreServiceClient,
+    cluster_client: &ClusterClient,
+) -> crate::Result<QueryAst> {
+    let max_terms = terms_lookup_query.max_terms;
+
+    if max_terms > TermsLookupQuery::MAX_MAX_TERMS {
+        return Err(SearchError::InvalidArgument(format!(
+            "max value for terms lookup `max_terms` is {}, but got {max_terms}",
+            TermsLookupQuery::MAX_MAX_TERMS
+        )));
+    }
+    if contains_terms_lookup(&terms_lookup_query.lookup_query) {
+        return Err(SearchError::InvalidQuery(
+            "nested terms lookups are not supported".to_string(),
+        ));
+    }
+    // Requesting one more term than allowed lets us detect lookups exceeding the limit: a split
+    // holding more than `max_terms + 1` distinct values reports a non-zero
+    // `sum_other_doc_count`.
+    let aggregation_request = serde_json::json!({
+        TERMS_LOOKUP_AGGREGATION_NAME: {
+            "terms": {
+                "field": terms_lookup_query.lookup_field,
+                "size": max_terms + 1,
+                "split_size": max_terms + 1,
+            }
+        }
+    });
+    let lookup_search_request = SearchRequest {
+        index_id_patterns: vec![terms_lookup_query.lookup_index_id.clone()],
+        query_ast: serde_json::to_string(&terms_lookup_query.lookup_query)?,
+        max_hits: 0,
+        aggregation_request: Some(aggregation_request.to_string()),
+        ..Default::default()
+    };
+    let lookup_search_response = root_search(
+        searcher_context,
+        lookup_search_request,
+        metastore.clone(),
+        cluster_client,
+    )
+    .await?;
+    let terms = extract_lookup_terms(
+        lookup_search_response.aggregation.as_deref(),
+        &terms_lookup_query,
+    )?;
+    info!(
+        lookup_index_id=%terms_lookup_query.lookup_index_id,
+        lookup_field=%terms_lookup_query.lookup_field,
+        num_terms=terms.len(),
+        "executed terms lookup"
+    );
+    Ok(terms_lookup_query.resolve(terms))
+}
+
+#[derive(Deserialize)]
+struct TermsLookupAggregationResult {
+    #[serde(default)]
+    sum_other_doc_count: u64,
+    buckets: Vec<TermsLookupBucket>,
+}
+
+#[derive(Deserialize)]
+struct TermsLookupBucket {
+    key: JsonValue,
+}
+
+fn json_key_to_term(key: JsonValue) -> String {
+    match key {
+        JsonValue::String(key) => key,
+        JsonValue::Number(number) => {
+            // Numeric keys are returned as floats by the terms aggregation.
+            match number.as_f64() {
+                Some(float) if float.fract() == 0.0 && float.abs() < i64::MAX as f64 => {
+                    (float as i64).to_string()
+                }
+                _ => number.to_string(),
+            }
+        }
+        key => key.to_string(),
+    }
+}
+
+fn extract_lookup_terms(
+    aggregation_json_opt: Option<&str>,
+    terms_lookup_query: &TermsLookupQuery,
+) -> crate::Result<BTreeSet<String>> {
+    let Some(aggregation_json) = aggregation_json_opt else {
+        return Ok(BTreeSet::new());
+    };
+    let mut aggregation_results: HashMap<String, TermsLookupAggregationResult> =
+        serde_json::from_str(aggregation_json)?;
+    let Some(aggregation_result) = aggregation_results.remove(TERMS_LOOKUP_AGGREGATION_NAME) else {
+        return Ok(BTreeSet::new());
+    };
+    if aggregation_result.sum_other_doc_count > 0
+        || aggregation_result.buckets.len() > terms_lookup_query.max_terms
+    {
+        return Err(SearchError::InvalidQuery(format!(
+            "terms lookup on field `{}` of index `{}` returned more than {} terms",
+            terms_lookup_query.lookup_field,
+            terms_lookup_query.lookup_index_id,
+            terms_lookup_query.max_terms
+        )));
+    }
+    let terms = aggregation_result
+        .buckets
+        .into_iter()
+        .map(|bucket| json_key_to_term(bucket.key))
+        .collect();
+    Ok(terms)
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_query::query_ast::qast_helper;
+
+    use super::*;
+
+    fn terms_lookup_query_for_test(max_terms: usize) -> TermsLookupQuery {
+        TermsLookupQuery {
+            field: "host".to_string(),
+            lookup_index_id: "alerts".to_string(),
+            lookup_field: "host_name".to_string(),
+            lookup_query: Box::new(QueryAst::MatchAll),
+            max_terms,
+        }
+    }
+
+    #[test]
+    fn test_contains_terms_lookup() {
+        assert!(!contains_terms_lookup(&qast_helper("host:foo", &[])));
+
+        let query_ast: QueryAst = BoolQuery {
+            must: vec![
+                qast_helper("host:foo", &[]),
+                terms_lookup_query_for_test(10).into(),
+            ],
+            ..Default::default()
+        }
+        .into();
+        assert!(contains_terms_lookup(&query_ast));
+    }
+
+    #[test]
+    fn test_extract_lookup_terms() {
+        let terms_lookup_query = terms_lookup_query_for_test(3);
+
+        let terms = extract_lookup_terms(None, &terms_lookup_query).unwrap();
+        assert!(terms.is_empty());
+
+        let aggregation_json = r#"{
+            "terms_lookup": {
+                "doc_count_error_upper_bound": 0,
+                "sum_other_doc_count": 0,
+                "buckets": [
+                    { "key": "host-1", "doc_count": 3 },
+                    { "key": 443.0, "doc_count": 2 },
+                    { "key": 1.5, "doc_count": 1 }
+                ]
+            }
+        }"#;
+        let terms = extract_lookup_terms(Some(aggregation_json), &terms_lookup_query).unwrap();
+        assert_eq!(
+            terms,
+            BTreeSet::from(["1.5".to_string(), "443".to_string(), "host-1".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_extract_lookup_terms_exceeds_max_terms() {
+        let terms_lookup_query = terms_lookup_query_for_test(1);
+
+        let aggregation_json = r#"{
+            "terms_lookup": {
+                "sum_other_doc_count": 0,
+                "buckets": [
+                    { "key": "host-1", "doc_count": 3 },
+                    { "key": "host-2", "doc_count": 2 }
+                ]
+            }
+        }"#;
+        let error = extract_lookup_terms(Some(aggregation_json), &terms_lookup_query).unwrap_err();
+        assert!(matches!(error, SearchError::InvalidQuery(_)));
+
+        let aggregation_json = r#"{
+            "terms_lookup": {
+                "sum_other_doc_count": 5,
+                "buckets": [{ "key": "host-1", "doc_count": 3 }]
+            }
+        }"#;
+        let error = extract_lookup_terms(Some(aggregation_json), &terms_lookup_query).unwrap_err();
+        assert!(matches!(error, SearchError::InvalidQuery(_)));
+    }
+}
@@ -18,20 +18,28 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 use std::collections::{BTreeMap, BTreeSet};
+use std::num::NonZeroU32;
+use std::path::PathBuf;
+use std::time::Duration;
 
 use assert_json_diff::{assert_json_eq, assert_json_include};
-use quickwit_config::SearcherConfig;
+use bytesize::ByteSize;
+use quickwit_common::uri::Uri;
+use quickwit_config::{IndexEncryptionConfig, SearcherConfig, SplitCacheLimits};
 use quickwit_doc_mapper::tag_pruning::extract_tags_from_query;
-use quickwit_doc_mapper::DefaultDocMapper;
+use quickwit_doc_mapper::{DefaultDocMapper, FieldMappingEntry};
 use quickwit_indexing::TestSandbox;
+use quickwit_metastore::{IndexMetadataResponseExt, UpdateIndexRequestExt};
 use quickwit_opentelemetry::otlp::TraceId;
+use quickwit_proto::metastore::{IndexMetadataRequest, MetastoreService, UpdateIndexRequest};
 use quickwit_proto::search::{
-    LeafListTermsResponse, ListTermsRequest, SearchRequest, SortByValue, SortField, SortOrder,
-    SortValue,
+    LeafListTermsResponse, ListTermsRequest, ReportSplit, SearchRequest, SnippetOptions,
+    SortByValue, SortField, SortOrder, SortValue,
 };
 use quickwit_query::query_ast::{
     qast_helper, qast_json_helper, query_ast_from_user_text, QueryAst,
 };
+use quickwit_storage::{generate_data_key, SplitCache, Storage};
 use serde_json::{json, Value as JsonValue};
 use tantivy::schema::OwnedValue as TantivyValue;
 use tantivy::time::OffsetDateTime;
@@ -131,6 +139,78 @@ async fn test_single_node_termset() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_single_node_search_field_added_to_doc_mapping() -> anyhow::Result<()> {
+    let index_id = "single-node-field-added-to-doc-mapping";
+    let doc_mapping_yaml = r#"
+            field_mappings:
+              - name: body
+                type: text
+        "#;
+    let test_sandbox = TestSandbox::create(index_id, doc_mapping_yaml, "{}", &["body"]).await?;
+    test_sandbox
+        .add_documents(vec![json!({"body": "written before the update"})])
+        .await?;
+
+    let mut metastore = test_sandbox.metastore();
+    let index_metadata = metastore
+        .index_metadata(IndexMetadataRequest::for_index_id(index_id.to_string()))
+        .await?
+        .deserialize_index_metadata()?;
+    let mut doc_mapping = index_metadata.index_config.doc_mapping;
+    let field_mapping: FieldMappingEntry =
+        serde_json::from_str(r#"{"name": "severity", "type": "text", "tokenizer": "raw"}"#)?;
+    doc_mapping.field_mappings.push(field_mapping);
+    let update_index_request =
+        UpdateIndexRequest::try_from_doc_mapping(test_sandbox.index_uid(), &doc_mapping)?;
+    metastore.update_index(update_index_request).await?;
+
+    test_sandbox
+        .add_documents(vec![
+            json!({"body": "written after the update", "severity": "error"}),
+        ])
+        .await?;
+
+    // The split created before the update does not have the field: the queries on the field
+    // match nothing in this split instead of failing.
+    for query in [
+        "severity:error",
+        "severity: IN [error warning]",
+        "severity:err*",
+        "body:written AND severity:error",
+    ] {
+        let search_request = SearchRequest {
+            index_id_patterns: vec![index_id.to_string()],
+            query_ast: qast_json_helper(query, &[]),
+            max_hits: 10,
+            ..Default::default()
+        };
+        let search_response = single_node_search(
+            search_request,
+            test_sandbox.metastore(),
+            test_sandbox.storage_resolver(),
+        )
+        .await?;
+        assert_eq!(search_response.num_hits, 1, "query `{query}`");
+        assert!(search_response.hits[0].json.contains("after the update"));
+    }
+    let search_request = SearchRequest {
+        index_id_patterns: vec![index_id.to_string()],
+        query_ast: qast_json_helper("body:written", &[]),
+        max_hits: 10,
+        ..Default::default()
+    };
+    let search_response = single_node_search(
+        search_request,
+        test_sandbox.metastore(),
+        test_sandbox.storage_resolver(),
+    )
+    .await?;
+    assert_eq!(search_response.num_hits, 2);
+    test_sandbox.assert_quit().await;
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_single_search_with_snippet() -> anyhow::Result<()> {
     let index_id = "single-node-with-snippet";
@@ -181,6 +261,48 @@ async fn test_single_search_with_snippet() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_single_search_with_snippet_options() -> anyhow::Result<()> {
+    let index_id = "single-node-with-snippet-options";
+    let doc_mapping_yaml = r#"
+            field_mappings:
+              - name: body
+                type: text
+        "#;
+    let test_sandbox = TestSandbox::create(index_id, doc_mapping_yaml, "{}", &["body"]).await?;
+    let docs = vec![json!({"body": "The beagle is a breed of small scent hound & a <pet>."})];
+    test_sandbox.add_documents(docs.clone()).await?;
+    let search_request = SearchRequest {
+        index_id_patterns: vec![index_id.to_string()],
+        query_ast: qast_json_helper("beagle pet", &["body"]),
+        snippet_fields: vec!["body".to_string()],
+        snippet_options: Some(SnippetOptions {
+            pre_tag: Some("<em>".to_string()),
+            post_tag: Some("</em>".to_string()),
+            max_num_chars: Some(1_000),
+        }),
+        max_hits: 1,
+        ..Default::default()
+    };
+    let single_node_result = single_node_search(
+        search_request,
+        test_sandbox.metastore(),
+        test_sandbox.storage_resolver(),
+    )
+    .await?;
+    assert_eq!(single_node_result.num_hits, 1);
+
+    let highlight_json: JsonValue =
+        serde_json::from_str(single_node_result.hits[0].snippet.as_ref().unwrap())?;
+    let expected_json: JsonValue = json!({
+        "body": ["The <em>beagle</em> is a breed of small scent hound &amp; a &lt;<em>pet</em>"]
+    });
+    assert_json_eq!(highlight_json, expected_json);
+
+    test_sandbox.assert_quit().await;
+    Ok(())
+}
+
 async fn slop_search_and_check(
     test_sandbox: &TestSandbox,
     index_id: &str,
@@ -911,6 +1033,79 @@ async fn test_sort_by_2_field() {
     test_sandbox.assert_quit().await;
 }
 
+#[tokio::test]
+async fn test_single_node_dedup() {
+    let index_id = "single-node-dedup";
+    let doc_mapping_yaml = r#"
+            field_mappings:
+              - name: host
+                type: text
+                tokenizer: raw
+                fast: true
+              - name: seq
+                type: u64
+                fast: true
+        "#;
+    let test_sandbox = TestSandbox::create(index_id, doc_mapping_yaml, "{}", &[])
+        .await
+        .unwrap();
+    // Each batch of documents ends up in its own split.
+    test_sandbox
+        .add_documents(vec![
+            json!({"host": "a", "seq": 1}),
+            json!({"host": "b", "seq": 2}),
+            json!({"host": "a", "seq": 5}),
+        ])
+        .await
+        .unwrap();
+    test_sandbox
+        .add_documents(vec![
+            json!({"host": "a", "seq": 4}),
+            json!({"host": "b", "seq": 3}),
+            json!({"host": "c", "seq": 6}),
+            json!({"seq": 0}),
+        ])
+        .await
+        .unwrap();
+    let search_seqs = |dedup_count: Option<u32>, max_hits: u64, start_offset: u64| {
+        let search_request = SearchRequest {
+            index_id_patterns: vec![index_id.to_string()],
+            query_ast: serde_json::to_string(&QueryAst::MatchAll).unwrap(),
+            max_hits,
+            start_offset,
+            sort_fields: vec![SortField {
+                field_name: "seq".to_string(),
+                sort_order: SortOrder::Desc as i32,
+                sort_datetime_format: None,
+            }],
+            dedup_field: Some("host".to_string()),
+            dedup_count,
+            ..Default::default()
+        };
+        let metastore = test_sandbox.metastore();
+        let storage_resolver = test_sandbox.storage_resolver();
+        async move {
+            let search_response = single_node_search(search_request, metastore, storage_resolver)
+                .await
+                .unwrap();
+            // The number of hits counts the matching documents before deduplication.
+            assert_eq!(search_response.num_hits, 7);
+            search_response
+                .hits
+                .into_iter()
+                .map(|hit| {
+                    let doc: JsonValue = serde_json::from_str(&hit.json).unwrap();
+                    doc["seq"].as_u64().unwrap()
+                })
+                .collect::<Vec<u64>>()
+        }
+    };
+    assert_eq!(search_seqs(None, 10, 0).await, vec![6, 5, 3, 0]);
+    assert_eq!(search_seqs(Some(2), 10, 0).await, vec![6, 5, 4, 3, 2, 0]);
+    assert_eq!(search_seqs(None, 2, 1).await, vec![5, 3]);
+    test_sandbox.assert_quit().await;
+}
+
 #[tokio::test]
 async fn test_single_node_invalid_sorting_with_query() {
     let index_id = "single-node-invalid-sorting";
@@ -1069,6 +1264,126 @@ async fn test_search_util(test_sandbox: &TestSandbox, query: &str) -> Vec<u32> {
         .collect::<Vec<u32>>()
 }
 
+#[tokio::test]
+async fn test_leaf_search_with_split_cache_on_encrypted_index() {
+    let doc_mapping_yaml = r#"
+            field_mappings:
+              - name: body
+                type: text
+        "#;
+    let test_sandbox = TestSandbox::create("leaf-search-encrypted", doc_mapping_yaml, "{}", &[])
+        .await
+        .unwrap();
+    test_sandbox
+        .add_documents(vec![
+            json!({"body": "snoopy is a beagle"}),
+            json!({"body": "garfield is a cat"}),
+        ])
+        .await
+        .unwrap();
+    let splits = test_sandbox
+        .metastore()
+        .list_splits(ListSplitsRequest::try_from_index_uid(test_sandbox.index_uid()).unwrap())
+        .await
+        .unwrap()
+        .collect_splits()
+        .await
+        .unwrap();
+
+    // Copies the splits to an encrypted index.
+    std::env::set_var(
+        "QW_TEST_LEAF_SEARCH_MASTER_KEY",
+        "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=",
+    );
+    let master_key_id = "env:QW_TEST_LEAF_SEARCH_MASTER_KEY".to_string();
+    let encryption_config = IndexEncryptionConfig {
+        data_key: Some(generate_data_key(&master_key_id).await.unwrap()),
+        master_key_id,
+    };
+    let storage_resolver = test_sandbox.storage_resolver();
+    let encrypted_index_uri = Uri::for_test("ram:///indexes/leaf-search-encrypted-copy");
+    let encrypted_storage = storage_resolver
+        .resolve_index_storage(&encrypted_index_uri, Some(&encryption_config))
+        .await
+        .unwrap();
+    for split in &splits {
+        let split_path = PathBuf::from(format!("{}.split", split.split_id()));
+        let split_bytes = test_sandbox.storage().get_all(&split_path).await.unwrap();
+        encrypted_storage
+            .put(&split_path, Box::new(split_bytes.to_vec()))
+            .await
+            .unwrap();
+    }
+
+    // Lets the split cache download the encrypted splits.
+    let split_cache_dir = tempfile::tempdir().unwrap();
+    let split_cache_limits = SplitCacheLimits {
+        max_num_bytes: ByteSize::mb(100),
+        max_num_splits: NonZeroU32::new(100).unwrap(),
+        num_concurrent_downloads: NonZeroU32::new(1).unwrap(),
+        max_num_bytes_partial_ranges: ByteSize::mb(100),
+        max_partial_range_num_bytes: ByteSize::mb(10),
+    };
+    let split_cache = SplitCache::with_root_path(
+        split_cache_dir.path().to_path_buf(),
+        storage_resolver,
+        split_cache_limits,
+    )
+    .unwrap();
+    split_cache.report_splits(
+        splits
+            .iter()
+            .map(|split| ReportSplit {
+                split_id: split.split_id().to_string(),
+                storage_uri: encrypted_index_uri.to_string(),
+            })
+            .collect(),
+    );
+    let cached_split_path =
+        |split_id: &str| -> PathBuf { split_cache_dir.path().join(format!("{split_id}.split")) };
+    for _ in 0..100 {
+        if splits
+            .iter()
+            .all(|split| cached_split_path(split.split_id()).exists())
+        {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    // Leaves some time to the download task to register the splits as downloaded.
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let searcher_context = Arc::new(SearcherContext::new(
+        SearcherConfig::default(),
+        Some(Arc::new(split_cache)),
+    ));
+    let request = Arc::new(SearchRequest {
+        index_id_patterns: vec!["leaf-search-encrypted".to_string()],
+        query_ast: qast_json_helper("beagle", &["body"]),
+        max_hits: 10,
+        ..Default::default()
+    });
+    let splits_offsets: Vec<_> = splits
+        .iter()
+        .map(|split| extract_split_and_footer_offsets(&split.split_metadata))
+        .collect();
+    // The same request is run twice so that the second run reads the cached ranges, if any.
+    for _ in 0..2 {
+        let search_response = leaf_search(
+            searcher_context.clone(),
+            request.clone(),
+            encrypted_storage.clone(),
+            splits_offsets.clone(),
+            test_sandbox.doc_mapper(),
+        )
+        .await
+        .unwrap();
+        assert!(search_response.failed_splits.is_empty());
+        assert_eq!(search_response.num_hits, 1);
+    }
+    test_sandbox.assert_quit().await;
+}
+
 #[tokio::test]
 async fn test_search_dynamic_mode() -> anyhow::Result<()> {
     let doc_mapping_yaml = r#"
@@ -1695,6 +2010,7 @@ async fn test_single_node_list_terms() -> anyhow::Result<()> {
             start_timestamp: None,
             end_timestamp: None,
             max_hits: Some(100),
+            include_doc_counts: false,
         };
         let search_response = leaf_list_terms(
             searcher_context.clone(),
@@ -1716,6 +2032,7 @@ async fn test_single_node_list_terms() -> anyhow::Result<()> {
             start_timestamp: None,
             end_timestamp: None,
             max_hits: Some(1),
+            include_doc_counts: false,
         };
         let search_response = leaf_list_terms(
             searcher_context.clone(),
@@ -1737,6 +2054,7 @@ async fn test_single_node_list_terms() -> anyhow::Result<()> {
             start_timestamp: None,
             end_timestamp: None,
             max_hits: Some(100),
+            include_doc_counts: false,
         };
         let search_response = leaf_list_terms(
             searcher_context.clone(),
@@ -1758,6 +2076,7 @@ async fn test_single_node_list_terms() -> anyhow::Result<()> {
             start_timestamp: None,
             end_timestamp: None,
             max_hits: Some(100),
+            include_doc_counts: false,
         };
         let search_response = leaf_list_terms(
             searcher_context.clone(),
@@ -1770,6 +2089,29 @@ async fn test_single_node_list_terms() -> anyhow::Result<()> {
         let terms = collect_str_terms(search_response);
         assert_eq!(terms, &["beagle"]);
     }
+    {
+        let request = ListTermsRequest {
+            index_id: test_sandbox.index_uid().index_id().to_string(),
+            field: "body".to_string(),
+            start_key: Some("beagle".as_bytes().to_vec()),
+            end_key: None,
+            start_timestamp: None,
+            end_timestamp: None,
+            max_hits: Some(2),
+            include_doc_counts: true,
+        };
+        let search_response = leaf_list_terms(
+            searcher_context.clone(),
+            &request,
+            test_sandbox.storage(),
+            &splits_offsets,
+        )
+        .await
+        .unwrap();
+        assert_eq!(search_response.doc_counts, &[2, 1]);
+        let terms = collect_str_terms(search_response);
+        assert_eq!(terms, &["beagle", "breed"]);
+    }
     test_sandbox.assert_quit().await;
     Ok(())
 }
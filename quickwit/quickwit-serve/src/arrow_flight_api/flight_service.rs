@@ -0,0 +1,344 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::io::Cursor;
+use std::sync::Arc;
+
+use arrow::ipc::reader::StreamReader;
+use arrow::record_batch::RecordBatch;
+use arrow_flight::encode::FlightDataEncoderBuilder;
+use arrow_flight::error::FlightError;
+use arrow_flight::flight_service_server::FlightService;
+use arrow_flight::{
+    Action, ActionType, Criteria, Empty, FlightData, FlightDescriptor, FlightInfo,
+    HandshakeRequest, HandshakeResponse, PutResult, SchemaResult, Ticket,
+};
+use bytes::Bytes;
+use futures::stream::BoxStream;
+use futures::{stream, StreamExt, TryStreamExt};
+use quickwit_proto::search::{OutputFormat, SearchStreamRequest};
+use quickwit_proto::tonic::{self, Request, Response, Status, Streaming};
+use quickwit_query::query_ast::query_ast_from_user_text;
+use quickwit_search::SearchService;
+use serde::Deserialize;
+use tracing::instrument;
+
+/// Ticket of a `DoGet` request, serialized as JSON.
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+struct ExportTicket {
+    index_id: String,
+    query: String,
+    #[serde(default)]
+    search_fields: Option<Vec<String>>,
+    /// Fast fields exported as columns of the record batches, in that order.
+    fast_fields: Vec<String>,
+    #[serde(default)]
+    start_timestamp: Option<i64>,
+    #[serde(default)]
+    end_timestamp: Option<i64>,
+}
+
+impl ExportTicket {
+    fn parse(ticket: &[u8]) -> Result<Self, Status> {
+        let export_ticket: ExportTicket = serde_json::from_slice(ticket).map_err(|error| {
+            Status::invalid_argument(format!("failed to parse ticket: {error}"))
+        })?;
+        if export_ticket.fast_fields.is_empty() {
+            return Err(Status::invalid_argument(
+                "ticket must specify at least one fast field",
+            ));
+        }
+        Ok(export_ticket)
+    }
+
+    fn into_search_stream_request(self) -> Result<SearchStreamRequest, Status> {
+        let query_ast = query_ast_from_user_text(&self.query, self.search_fields);
+        let query_ast_json = serde_json::to_string(&query_ast)
+            .map_err(|error| Status::internal(format!("failed to serialize query: {error}")))?;
+        let mut fast_fields = self.fast_fields.into_iter();
+        // Expect: the ticket was validated when parsed.
+        let fast_field = fast_fields.next().expect("ticket should have a fast field");
+
+        let search_stream_request = SearchStreamRequest {
+            index_id: self.index_id,
+            query_ast: query_ast_json,
+            snippet_fields: Vec::new(),
+            start_timestamp: self.start_timestamp,
+            end_timestamp: self.end_timestamp,
+            fast_field,
+            output_format: OutputFormat::ArrowIpc as i32,
+            partition_by_field: None,
+            additional_fast_fields: fast_fields.collect(),
+        };
+        Ok(search_stream_request)
+    }
+}
+
+/// Arrow Flight service exporting the fast field values of the documents matching a query as
+/// record batches.
+///
+/// Only `DoGet` is supported. Its ticket is a JSON object such as:
+/// `{"index_id": "hdfs-logs", "query": "severity_text:ERROR", "fast_fields": ["timestamp",
+/// "tenant_id"]}`.
+#[derive(Clone)]
+pub struct ArrowFlightService {
+    search_service: Arc<dyn SearchService>,
+}
+
+impl ArrowFlightService {
+    pub fn new(search_service: Arc<dyn SearchService>) -> Self {
+        Self { search_service }
+    }
+}
+
+/// Decodes the Arrow IPC stream returned for a split.
+fn decode_arrow_ipc(arrow_ipc: Bytes) -> Result<Vec<RecordBatch>, FlightError> {
+    let stream_reader = StreamReader::try_new(Cursor::new(arrow_ipc), None)?;
+    let record_batches = stream_reader.collect::<Result<Vec<_>, _>>()?;
+    Ok(record_batches)
+}
+
+fn flight_error_to_status(error: FlightError) -> Status {
+    match error {
+        FlightError::Tonic(status) => status,
+        other => Status::internal(other.to_string()),
+    }
+}
+
+#[tonic::async_trait]
+impl FlightService for ArrowFlightService {
+    type HandshakeStream = BoxStream<'static, Result<HandshakeResponse, Status>>;
+    type ListFlightsStream = BoxStream<'static, Result<FlightInfo, Status>>;
+    type DoGetStream = BoxStream<'static, Result<FlightData, Status>>;
+    type DoPutStream = BoxStream<'static, Result<PutResult, Status>>;
+    type DoActionStream = BoxStream<'static, Result<arrow_flight::Result, Status>>;
+    type ListActionsStream = BoxStream<'static, Result<ActionType, Status>>;
+    type DoExchangeStream = BoxStream<'static, Result<FlightData, Status>>;
+
+    async fn handshake(
+        &self,
+        _request: Request<Streaming<HandshakeRequest>>,
+    ) -> Result<Response<Self::HandshakeStream>, Status> {
+        Err(Status::unimplemented("`handshake` is not supported"))
+    }
+
+    async fn list_flights(
+        &self,
+        _request: Request<Criteria>,
+    ) -> Result<Response<Self::ListFlightsStream>, Status> {
+        Err(Status::unimplemented("`list_flights` is not supported"))
+    }
+
+    async fn get_flight_info(
+        &self,
+        _request: Request<FlightDescriptor>,
+    ) -> Result<Response<FlightInfo>, Status> {
+        Err(Status::unimplemented("`get_flight_info` is not supported"))
+    }
+
+    async fn get_schema(
+        &self,
+        _request: Request<FlightDescriptor>,
+    ) -> Result<Response<SchemaResult>, Status> {
+        Err(Status::unimplemented("`get_schema` is not supported"))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn do_get(
+        &self,
+        request: Request<Ticket>,
+    ) -> Result<Response<Self::DoGetStream>, Status> {
+        let export_ticket = ExportTicket::parse(&request.into_inner().ticket)?;
+        let search_stream_request = export_ticket.into_search_stream_request()?;
+        let arrow_ipc_stream = self
+            .search_service
+            .root_search_stream(search_stream_request)
+            .await?;
+        let record_batch_stream = arrow_ipc_stream.flat_map(|arrow_ipc_result| {
+            let record_batch_results = match arrow_ipc_result
+                .map_err(|search_error| FlightError::Tonic(search_error.into()))
+                .and_then(decode_arrow_ipc)
+            {
+                Ok(record_batches) => record_batches.into_iter().map(Ok).collect(),
+                Err(flight_error) => vec![Err(flight_error)],
+            };
+            stream::iter(record_batch_results)
+        });
+        let flight_data_stream = FlightDataEncoderBuilder::new()
+            .build(record_batch_stream)
+            .map_err(flight_error_to_status);
+        Ok(Response::new(flight_data_stream.boxed()))
+    }
+
+    async fn do_put(
+        &self,
+        _request: Request<Streaming<FlightData>>,
+    ) -> Result<Response<Self::DoPutStream>, Status> {
+        Err(Status::unimplemented("`do_put` is not supported"))
+    }
+
+    async fn do_action(
+        &self,
+        _request: Request<Action>,
+    ) -> Result<Response<Self::DoActionStream>, Status> {
+        Err(Status::unimplemented("`do_action` is not supported"))
+    }
+
+    async fn list_actions(
+        &self,
+        _request: Request<Empty>,
+    ) -> Result<Response<Self::ListActionsStream>, Status> {
+        Err(Status::unimplemented("`list_actions` is not supported"))
+    }
+
+    async fn do_exchange(
+        &self,
+        _request: Request<Streaming<FlightData>>,
+    ) -> Result<Response<Self::DoExchangeStream>, Status> {
+        Err(Status::unimplemented("`do_exchange` is not supported"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::{Int64Array, StringArray};
+    use arrow::datatypes::{DataType, Field, Schema};
+    use arrow::ipc::writer::StreamWriter;
+    use arrow_flight::decode::FlightRecordBatchStream;
+    use quickwit_search::{MockSearchService, SearchError};
+
+    use super::*;
+
+    fn arrow_ipc_for_test(values: &[i64], apps: &[Option<&str>]) -> Bytes {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("ts", DataType::Int64, true),
+            Field::new("app", DataType::Utf8, true),
+        ]));
+        let record_batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int64Array::from(values.to_vec())),
+                Arc::new(StringArray::from(apps.to_vec())),
+            ],
+        )
+        .unwrap();
+        let mut stream_writer = StreamWriter::try_new(Vec::new(), &schema).unwrap();
+        stream_writer.write(&record_batch).unwrap();
+        Bytes::from(stream_writer.into_inner().unwrap())
+    }
+
+    #[test]
+    fn test_export_ticket_into_search_stream_request() {
+        let ticket = br#"{
+            "index_id": "hdfs-logs",
+            "query": "severity_text:ERROR",
+            "fast_fields": ["ts", "app"],
+            "end_timestamp": 1000
+        }"#;
+        let search_stream_request = ExportTicket::parse(ticket)
+            .unwrap()
+            .into_search_stream_request()
+            .unwrap();
+        assert_eq!(search_stream_request.index_id, "hdfs-logs");
+        assert_eq!(search_stream_request.fast_field, "ts");
+        assert_eq!(search_stream_request.additional_fast_fields, vec!["app"]);
+        assert_eq!(
+            search_stream_request.output_format,
+            OutputFormat::ArrowIpc as i32
+        );
+        assert_eq!(search_stream_request.start_timestamp, None);
+        assert_eq!(search_stream_request.end_timestamp, Some(1000));
+        assert!(search_stream_request
+            .query_ast
+            .contains("severity_text:ERROR"));
+
+        let error =
+            ExportTicket::parse(br#"{"index_id": "hdfs-logs", "query": "*", "fast_fields": []}"#)
+                .unwrap_err();
+        assert_eq!(error.code(), tonic::Code::InvalidArgument);
+
+        let error = ExportTicket::parse(br#"{"index_id": "hdfs-logs"}"#).unwrap_err();
+        assert_eq!(error.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_flight_service_do_get() {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_root_search_stream()
+            .withf(|search_stream_request| {
+                search_stream_request.index_id == "hdfs-logs"
+                    && search_stream_request.fast_field == "ts"
+                    && search_stream_request.additional_fast_fields == ["app"]
+            })
+            .returning(|_| {
+                let arrow_ipc_chunks = vec![
+                    Ok(arrow_ipc_for_test(&[1, 2], &[Some("my-app"), None])),
+                    Ok(arrow_ipc_for_test(&[3], &[Some("my-other-app")])),
+                ];
+                Ok(Box::pin(stream::iter(arrow_ipc_chunks)))
+            });
+        let flight_service = ArrowFlightService::new(Arc::new(mock_search_service));
+        let ticket = Ticket {
+            ticket: br#"{"index_id": "hdfs-logs", "query": "*", "fast_fields": ["ts", "app"]}"#
+                .to_vec()
+                .into(),
+        };
+        let flight_data_stream = flight_service
+            .do_get(Request::new(ticket))
+            .await
+            .unwrap()
+            .into_inner();
+        let record_batches: Vec<RecordBatch> = FlightRecordBatchStream::new_from_flight_data(
+            flight_data_stream.map_err(FlightError::Tonic),
+        )
+        .try_collect()
+        .await
+        .unwrap();
+        assert_eq!(record_batches.len(), 2);
+        assert_eq!(record_batches[0].num_rows(), 2);
+        assert_eq!(record_batches[1].num_rows(), 1);
+        assert_eq!(record_batches[0].schema().field(1).name(), "app");
+    }
+
+    #[tokio::test]
+    async fn test_flight_service_do_get_search_error() {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_root_search_stream()
+            .returning(|_| {
+                Err(SearchError::IndexesNotFound {
+                    index_ids: vec!["hdfs-logs".to_string()],
+                })
+            });
+        let flight_service = ArrowFlightService::new(Arc::new(mock_search_service));
+        let ticket = Ticket {
+            ticket: br#"{"index_id": "hdfs-logs", "query": "*", "fast_fields": ["ts"]}"#
+                .to_vec()
+                .into(),
+        };
+        let error = flight_service
+            .do_get(Request::new(ticket))
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(error.code(), tonic::Code::NotFound);
+    }
+}
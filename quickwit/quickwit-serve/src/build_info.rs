@@ -18,7 +18,9 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 use once_cell::sync::OnceCell;
+use quickwit_common::cgroup::CgroupLimits;
 use quickwit_common::runtimes::RuntimesConfig;
+use quickwit_indexing::IndexerAutotuning;
 use serde::Serialize;
 
 #[derive(Debug, Eq, PartialEq, Serialize, utoipa::ToSchema)]
@@ -89,6 +91,11 @@ pub struct RuntimeInfo {
     pub num_cpus_physical: usize,
     pub num_threads_blocking: usize,
     pub num_threads_non_blocking: usize,
+    /// Resource limits enforced by cgroup v2 on the node, typically set by the container runtime.
+    #[schema(value_type = Object)]
+    pub cgroup_limits: CgroupLimits,
+    /// Indexer resource settings derived from the cgroup limits.
+    pub indexer_autotuning: IndexerAutotuning,
 }
 
 impl RuntimeInfo {
@@ -99,12 +106,17 @@ impl RuntimeInfo {
         INSTANCE.get_or_init(|| {
             let num_cpus_logical = num_cpus::get();
             let runtimes_config = RuntimesConfig::with_num_cpus(num_cpus_logical);
+            let cgroup_limits = *CgroupLimits::get();
+            let indexer_autotuning =
+                IndexerAutotuning::new(runtimes_config.num_threads_blocking, &cgroup_limits);
 
             Self {
                 num_cpus_logical,
                 num_cpus_physical: num_cpus::get_physical(),
                 num_threads_blocking: runtimes_config.num_threads_blocking,
                 num_threads_non_blocking: runtimes_config.num_threads_non_blocking,
+                cgroup_limits,
+                indexer_autotuning,
             }
         })
     }
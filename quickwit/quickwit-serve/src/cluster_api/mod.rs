@@ -19,4 +19,7 @@
 
 mod rest_handler;
 
-pub use rest_handler::{cluster_handler, ClusterApi};
+pub use rest_handler::{
+    cluster_events_handler, cluster_handler, cluster_state_snapshot_handler, drain_node_handler,
+    ClusterApi,
+};
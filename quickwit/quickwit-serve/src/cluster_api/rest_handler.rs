@@ -19,19 +19,84 @@
 
 use std::convert::Infallible;
 
-use quickwit_cluster::{Cluster, ClusterSnapshot, NodeIdSchema};
+use quickwit_actors::{Mailbox, Observe};
+use quickwit_cluster::{
+    Cluster, ClusterEvent, ClusterEventKind, ClusterSnapshot, NodeIdSchema, NodeKeyValues,
+};
+use quickwit_common::metrics::metrics_text_payload;
+use quickwit_config::service::QuickwitService;
+use quickwit_indexing::actors::{IndexingService, IndexingServiceCounters};
+use quickwit_metastore::ListIndexesMetadataResponseExt;
+use quickwit_proto::control_plane::{
+    ControlPlaneResult, ControlPlaneService, ControlPlaneServiceClient, DrainNodeRequest,
+    DrainNodeResponse,
+};
+use quickwit_proto::indexing::{CpuCapacity, IndexingTask};
+use quickwit_proto::metastore::{
+    ListIndexesMetadataRequest, ListShardsRequest, ListShardsSubrequest, ListShardsSubresponse,
+    MetastoreResult, MetastoreService, MetastoreServiceClient, SourceType,
+};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tracing::info;
 use warp::{Filter, Rejection};
 
 use crate::format::extract_format_from_qs;
 use crate::json_api_response::make_json_api_response;
+use crate::with_arg;
 
 #[derive(utoipa::OpenApi)]
 #[openapi(
-    paths(get_cluster),
-    components(schemas(ClusterSnapshot, NodeIdSchema,))
+    paths(
+        get_cluster,
+        get_cluster_events,
+        get_cluster_state_snapshot,
+        drain_node
+    ),
+    components(schemas(
+        ClusterEvent,
+        ClusterEventKind,
+        ClusterEvents,
+        ClusterSnapshot,
+        ClusterStateSnapshot,
+        DrainNodeResponse,
+        NodeIdSchema,
+        NodeKeyValues,
+    ))
 )]
 pub struct ClusterApi;
 
+/// Point-in-time capture of the cluster state, meant to be archived for postmortems.
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct ClusterStateSnapshot {
+    /// Unix timestamp (seconds) at which the snapshot was taken.
+    pub snapshot_timestamp: i64,
+    /// Membership of the cluster as seen by the node serving the request.
+    pub cluster: ClusterSnapshot,
+    /// Indexing plan applied by the control plane, as reported by each ready indexer.
+    #[schema(value_type = Vec<Object>)]
+    pub indexing_plan: Vec<IndexerPlan>,
+    /// Counters of the indexing pipelines running on the node serving the request, if it is an
+    /// indexer.
+    #[schema(value_type = Option<Object>)]
+    pub local_indexing_pipelines: Option<IndexingServiceCounters>,
+    /// Shards of the ingest sources, grouped by index and source.
+    pub shards: Vec<ListShardsSubresponse>,
+    /// Metrics of the node serving the request in the Prometheus text format.
+    pub metrics: String,
+    /// Sections that could not be captured, with the reason why.
+    pub errors: Vec<String>,
+}
+
+/// Indexing tasks assigned to an indexer.
+#[derive(Debug, Serialize)]
+pub struct IndexerPlan {
+    pub node_id: String,
+    pub enabled_services: Vec<QuickwitService>,
+    pub indexing_cpu_capacity: CpuCapacity,
+    pub indexing_tasks: Vec<IndexingTask>,
+}
+
 /// Cluster handler.
 pub fn cluster_handler(
     cluster: Cluster,
@@ -59,3 +124,432 @@ async fn get_cluster(cluster: Cluster) -> Result<ClusterSnapshot, Infallible> {
     let snapshot = cluster.snapshot().await;
     Ok(snapshot)
 }
+
+#[derive(Debug, Default, Clone, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(deny_unknown_fields)]
+pub struct ClusterEventsQueryParams {
+    /// If set, only the events and the state of this node are returned.
+    #[serde(default)]
+    pub node_id: Option<String>,
+}
+
+/// Membership and state changes observed by the node serving the request, along with the current
+/// key-value state of each live node.
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct ClusterEvents {
+    /// Latest events, from the oldest to the most recent.
+    pub events: Vec<ClusterEvent>,
+    /// Key-value state of the live nodes, sorted by node ID.
+    pub nodes: Vec<NodeKeyValues>,
+}
+
+/// Cluster events handler.
+pub fn cluster_events_handler(
+    cluster: Cluster,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("cluster" / "events")
+        .and(warp::get())
+        .and(serde_qs::warp::query(serde_qs::Config::default()))
+        .and(with_arg(cluster))
+        .then(get_cluster_events)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    get,
+    tag = "Cluster Info",
+    path = "/cluster/events",
+    responses(
+        (status = 200, description = "Successfully fetched the cluster events.", body = ClusterEvents)
+    ),
+    params(
+        ClusterEventsQueryParams,
+    )
+)]
+
+/// Get the latest membership changes (nodes joining or leaving) and state key updates observed by
+/// the node, along with the key-value state of each live node.
+///
+/// The events are retained in a bounded ring buffer, so only the most recent ones are returned.
+async fn get_cluster_events(
+    query_params: ClusterEventsQueryParams,
+    cluster: Cluster,
+) -> Result<ClusterEvents, Infallible> {
+    let mut events = cluster.events().await;
+    let mut nodes = cluster.live_nodes_key_values().await;
+
+    if let Some(node_id) = &query_params.node_id {
+        events.retain(|event| &event.node_id == node_id);
+        nodes.retain(|node| &node.node_id == node_id);
+    }
+    nodes.sort_by(|left, right| left.node_id.cmp(&right.node_id));
+    Ok(ClusterEvents { events, nodes })
+}
+
+/// Node drain handler.
+pub fn drain_node_handler(
+    control_plane: ControlPlaneServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("cluster" / "nodes" / String / "drain")
+        .and(warp::post())
+        .and(with_arg(control_plane))
+        .then(drain_node)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    post,
+    tag = "Cluster Info",
+    path = "/cluster/nodes/{node_id}/drain",
+    responses(
+        (status = 200, description = "Successfully started or polled the drain of the node.", body = DrainNodeResponse)
+    ),
+    params(
+        ("node_id" = String, Path, description = "The ID of the node to drain."),
+    )
+)]
+
+/// Moves the indexing pipelines off a node and decommissions its ingester, then reports the drain
+/// progress.
+///
+/// The call is idempotent: it can be repeated until `completed` is true, at which point the node
+/// can be shut down without losing ingested data.
+async fn drain_node(
+    node_id: String,
+    mut control_plane: ControlPlaneServiceClient,
+) -> ControlPlaneResult<DrainNodeResponse> {
+    info!(node_id = %node_id, "drain-node");
+    let drain_node_request = DrainNodeRequest { node_id };
+    control_plane.drain_node(drain_node_request).await
+}
+
+/// Cluster state snapshot handler.
+pub fn cluster_state_snapshot_handler(
+    cluster: Cluster,
+    metastore: MetastoreServiceClient,
+    indexing_service_mailbox_opt: Option<Mailbox<IndexingService>>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("cluster" / "state" / "snapshot")
+        .and(warp::get())
+        .and(with_arg(cluster))
+        .and(with_arg(metastore))
+        .and(with_arg(indexing_service_mailbox_opt))
+        .then(get_cluster_state_snapshot)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+        .map(|reply| {
+            warp::reply::with_header(
+                reply,
+                "content-disposition",
+                "attachment; filename=\"quickwit-cluster-state.json\"",
+            )
+        })
+}
+
+#[utoipa::path(
+    get,
+    tag = "Cluster Info",
+    path = "/cluster/state/snapshot",
+    responses(
+        (status = 200, description = "Successfully captured the cluster state.", body = ClusterStateSnapshot)
+    )
+)]
+
+/// Captures membership, indexing plan, pipeline states, shard tables, and metrics into a single
+/// JSON document.
+///
+/// Sections that cannot be captured are reported in `errors` rather than failing the whole
+/// snapshot: postmortems usually need it most when part of the cluster is unhealthy.
+async fn get_cluster_state_snapshot(
+    cluster: Cluster,
+    metastore: MetastoreServiceClient,
+    indexing_service_mailbox_opt: Option<Mailbox<IndexingService>>,
+) -> Result<ClusterStateSnapshot, Infallible> {
+    let snapshot_timestamp = OffsetDateTime::now_utc().unix_timestamp();
+    let mut errors = Vec::new();
+
+    let cluster_snapshot = cluster.snapshot().await;
+    let mut indexing_plan: Vec<IndexerPlan> = cluster
+        .ready_members()
+        .await
+        .into_iter()
+        .filter(|member| member.enabled_services.contains(&QuickwitService::Indexer))
+        .map(|member| {
+            let mut enabled_services: Vec<QuickwitService> =
+                member.enabled_services.into_iter().collect();
+            enabled_services.sort_by_key(|service| service.as_str());
+            IndexerPlan {
+                node_id: member.node_id.to_string(),
+                enabled_services,
+                indexing_cpu_capacity: member.indexing_cpu_capacity,
+                indexing_tasks: member.indexing_tasks,
+            }
+        })
+        .collect();
+    indexing_plan.sort_by(|left, right| left.node_id.cmp(&right.node_id));
+
+    let local_indexing_pipelines =
+        if let Some(indexing_service_mailbox) = indexing_service_mailbox_opt {
+            match indexing_service_mailbox.ask(Observe).await {
+                Ok(counters) => Some(counters),
+                Err(error) => {
+                    errors.push(format!("failed to observe indexing service: {error}"));
+                    None
+                }
+            }
+        } else {
+            None
+        };
+    let shards = match list_ingest_shards(metastore).await {
+        Ok(shards) => shards,
+        Err(error) => {
+            errors.push(format!("failed to list shards: {error}"));
+            Vec::new()
+        }
+    };
+    let snapshot = ClusterStateSnapshot {
+        snapshot_timestamp,
+        cluster: cluster_snapshot,
+        indexing_plan,
+        local_indexing_pipelines,
+        shards,
+        metrics: metrics_text_payload(),
+        errors,
+    };
+    Ok(snapshot)
+}
+
+/// Lists the shards of all the ingest sources.
+async fn list_ingest_shards(
+    mut metastore: MetastoreServiceClient,
+) -> MetastoreResult<Vec<ListShardsSubresponse>> {
+    let indexes_metadata = metastore
+        .list_indexes_metadata(ListIndexesMetadataRequest::all())
+        .await?
+        .deserialize_indexes_metadata()?;
+    let subrequests: Vec<ListShardsSubrequest> = indexes_metadata
+        .iter()
+        .flat_map(|index_metadata| {
+            index_metadata
+                .sources
+                .values()
+                .filter(|source_config| source_config.source_type() == SourceType::IngestV2)
+                .map(|source_config| ListShardsSubrequest {
+                    index_uid: index_metadata.index_uid.to_string(),
+                    source_id: source_config.source_id.clone(),
+                    shard_state: None,
+                })
+        })
+        .collect();
+    if subrequests.is_empty() {
+        return Ok(Vec::new());
+    }
+    let list_shards_request = ListShardsRequest { subrequests };
+    let list_shards_response = metastore.list_shards(list_shards_request).await?;
+    Ok(list_shards_response.subresponses)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use quickwit_cluster::{create_cluster_for_test, ChannelTransport};
+    use quickwit_config::SourceConfig;
+    use quickwit_metastore::IndexMetadata;
+    use quickwit_proto::control_plane::ControlPlaneError;
+    use quickwit_proto::ingest::Shard;
+    use quickwit_proto::metastore::{
+        ListIndexesMetadataResponse, ListShardsResponse, MetastoreError,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cluster_state_snapshot() {
+        let transport = ChannelTransport::default();
+        let cluster = create_cluster_for_test(Vec::new(), &["indexer"], &transport, true)
+            .await
+            .unwrap();
+        cluster
+            .wait_for_ready_members(|members| members.len() == 1, Duration::from_secs(5))
+            .await
+            .unwrap();
+        let mut mock_metastore = MetastoreServiceClient::mock();
+        mock_metastore
+            .expect_list_indexes_metadata()
+            .return_once(|_| {
+                let mut index_metadata =
+                    IndexMetadata::for_test("test-index", "ram:///indexes/test-index");
+                index_metadata
+                    .add_source(SourceConfig::ingest_v2_default())
+                    .unwrap();
+                Ok(
+                    ListIndexesMetadataResponse::try_from_indexes_metadata(vec![index_metadata])
+                        .unwrap(),
+                )
+            });
+        mock_metastore
+            .expect_list_shards()
+            .return_once(|list_shards_request| {
+                assert_eq!(list_shards_request.subrequests.len(), 1);
+                let subrequest = &list_shards_request.subrequests[0];
+                assert_eq!(subrequest.source_id, "_ingest-source");
+
+                let subresponses = vec![ListShardsSubresponse {
+                    index_uid: subrequest.index_uid.clone(),
+                    source_id: subrequest.source_id.clone(),
+                    shards: vec![Shard {
+                        index_uid: subrequest.index_uid.clone(),
+                        source_id: subrequest.source_id.clone(),
+                        shard_id: 1,
+                        leader_id: "test-ingester".to_string(),
+                        ..Default::default()
+                    }],
+                    next_shard_id: 2,
+                }];
+                Ok(ListShardsResponse { subresponses })
+            });
+        let handler = cluster_state_snapshot_handler(
+            cluster.clone(),
+            MetastoreServiceClient::from(mock_metastore),
+            None,
+        );
+        let resp = warp::test::request()
+            .path("/cluster/state/snapshot")
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        assert_eq!(
+            resp.headers().get("content-disposition").unwrap(),
+            "attachment; filename=\"quickwit-cluster-state.json\""
+        );
+        let snapshot: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+        assert!(snapshot["snapshot_timestamp"].as_i64().unwrap() > 0);
+        assert_eq!(snapshot["cluster"]["self_node_id"], cluster.self_node_id());
+
+        let indexing_plan = snapshot["indexing_plan"].as_array().unwrap();
+        assert_eq!(indexing_plan.len(), 1);
+        assert_eq!(indexing_plan[0]["node_id"], cluster.self_node_id());
+
+        assert!(snapshot["local_indexing_pipelines"].is_null());
+
+        let shards = snapshot["shards"].as_array().unwrap();
+        assert_eq!(shards.len(), 1);
+        assert_eq!(shards[0]["source_id"], "_ingest-source");
+        assert_eq!(shards[0]["shards"][0]["leader_id"], "test-ingester");
+
+        assert!(snapshot["metrics"].is_string());
+        assert!(snapshot["errors"].as_array().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cluster_state_snapshot_reports_errors() {
+        let transport = ChannelTransport::default();
+        let cluster = create_cluster_for_test(Vec::new(), &[], &transport, true)
+            .await
+            .unwrap();
+        let mut mock_metastore = MetastoreServiceClient::mock();
+        mock_metastore
+            .expect_list_indexes_metadata()
+            .return_once(|_| Err(MetastoreError::Unavailable("metastore is down".to_string())));
+        let handler = cluster_state_snapshot_handler(
+            cluster,
+            MetastoreServiceClient::from(mock_metastore),
+            None,
+        );
+        let resp = warp::test::request()
+            .path("/cluster/state/snapshot")
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let snapshot: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+        assert!(snapshot["shards"].as_array().unwrap().is_empty());
+        let errors = snapshot["errors"].as_array().unwrap();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0]
+            .as_str()
+            .unwrap()
+            .contains("failed to list shards"));
+    }
+
+    #[tokio::test]
+    async fn test_cluster_events() {
+        let transport = ChannelTransport::default();
+        let cluster = create_cluster_for_test(Vec::new(), &["searcher"], &transport, true)
+            .await
+            .unwrap();
+        cluster.set_self_key_value("foo", "bar").await;
+
+        let handler = cluster_events_handler(cluster.clone());
+        let resp = warp::test::request()
+            .path("/cluster/events")
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let cluster_events: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+        assert!(cluster_events["events"].is_array());
+
+        let nodes = cluster_events["nodes"].as_array().unwrap();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0]["node_id"], cluster.self_node_id());
+        assert_eq!(nodes[0]["key_values"]["foo"], "bar");
+        assert_eq!(nodes[0]["key_values"]["enabled_services"], "searcher");
+
+        let resp = warp::test::request()
+            .path("/cluster/events?node_id=unknown-node")
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let cluster_events: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+        assert!(cluster_events["events"].as_array().unwrap().is_empty());
+        assert!(cluster_events["nodes"].as_array().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_drain_node() {
+        let mut mock_control_plane = ControlPlaneServiceClient::mock();
+        mock_control_plane
+            .expect_drain_node()
+            .return_once(|drain_node_request| {
+                assert_eq!(drain_node_request.node_id, "test-node");
+                Ok(DrainNodeResponse {
+                    node_id: "test-node".to_string(),
+                    num_indexing_tasks: 1,
+                    num_shards: 2,
+                    completed: false,
+                })
+            });
+        let handler = drain_node_handler(ControlPlaneServiceClient::from(mock_control_plane));
+        let resp = warp::test::request()
+            .method("POST")
+            .path("/cluster/nodes/test-node/drain")
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let drain_node_response: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+        let expected_response = serde_json::json!({
+            "node_id": "test-node",
+            "num_indexing_tasks": 1,
+            "num_shards": 2,
+            "completed": false,
+        });
+        assert_eq!(drain_node_response, expected_response);
+
+        let mut mock_control_plane = ControlPlaneServiceClient::mock();
+        mock_control_plane.expect_drain_node().return_once(|_| {
+            Err(ControlPlaneError::Unavailable(
+                "control plane is down".to_string(),
+            ))
+        });
+        let handler = drain_node_handler(ControlPlaneServiceClient::from(mock_control_plane));
+        let resp = warp::test::request()
+            .method("POST")
+            .path("/cluster/nodes/test-node/drain")
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 503);
+    }
+}
@@ -17,17 +17,24 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use quickwit_actors::{AskError, Mailbox};
 use quickwit_config::build_doc_mapper;
+use quickwit_janitor::actors::{DeleteTaskServiceState, PauseDeleteTasks, ResumeDeleteTasks};
 use quickwit_janitor::error::JanitorError;
-use quickwit_metastore::IndexMetadataResponseExt;
+use quickwit_janitor::JanitorService;
+use quickwit_metastore::{
+    IndexMetadataResponseExt, ListSplitsQuery, ListSplitsRequestExt, ListSplitsResponseExt,
+    SplitMetadata, SplitState,
+};
 use quickwit_proto::metastore::{
-    DeleteQuery, DeleteTask, IndexMetadataRequest, ListDeleteTasksRequest, MetastoreResult,
-    MetastoreService, MetastoreServiceClient,
+    DeleteQuery, DeleteTask, EntityKind, IndexMetadataRequest, ListDeleteTasksRequest,
+    ListSplitsRequest, MetastoreError, MetastoreResult, MetastoreService, MetastoreServiceClient,
 };
 use quickwit_proto::search::SearchRequest;
 use quickwit_proto::types::IndexUid;
-use quickwit_query::query_ast::{query_ast_from_user_text, QueryAst};
-use serde::Deserialize;
+use quickwit_query::query_ast::{query_ast_from_user_text, QueryAst, TermQuery};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
 use warp::{Filter, Rejection};
 
 use crate::format::extract_format_from_qs;
@@ -36,8 +43,22 @@ use crate::with_arg;
 
 #[derive(utoipa::OpenApi)]
 #[openapi(
-    paths(get_delete_tasks, post_delete_request),
-    components(schemas(DeleteQueryRequest, DeleteTask, DeleteQuery,))
+    paths(
+        get_delete_tasks,
+        post_delete_request,
+        delete_doc_by_id,
+        list_delete_task_statuses,
+        get_delete_task_status,
+        pause_delete_tasks,
+        resume_delete_tasks
+    ),
+    components(schemas(
+        DeleteQueryRequest,
+        DeleteTask,
+        DeleteQuery,
+        DeleteTaskStatus,
+        DeleteTaskServiceState,
+    ))
 )]
 pub struct DeleteTaskApi;
 
@@ -57,11 +78,81 @@ pub struct DeleteQueryRequest {
     pub end_timestamp: Option<i64>,
 }
 
+/// Progress of a delete task.
+///
+/// Progress is derived from the delete opstamp of the published splits, which the janitor's
+/// delete task pipelines update in the metastore as they apply the delete tasks.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, utoipa::ToSchema)]
+pub struct DeleteTaskStatus {
+    #[serde(flatten)]
+    pub delete_task: DeleteTask,
+    /// Number of published splits the delete task applies to, i.e. the splits created before
+    /// the delete task, and the splits merged from them.
+    pub num_affected_splits: usize,
+    /// Number of affected splits the delete task has not been applied to yet.
+    pub num_remaining_splits: usize,
+    /// Whether the delete task has been applied to all the published splits.
+    pub completed: bool,
+    /// Fraction of the affected splits processed, between 0 and 1.
+    pub progress: f64,
+    /// Estimated completion time in seconds since epoch, extrapolated from the rate at which
+    /// splits have been processed so far. Unknown if no split has been processed yet.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimated_completion_timestamp: Option<i64>,
+}
+
+impl DeleteTaskStatus {
+    fn compute(delete_task: DeleteTask, splits: &[SplitMetadata], now_timestamp: i64) -> Self {
+        let mut num_affected_splits = 0;
+        let mut num_remaining_splits = 0;
+
+        for split in splits {
+            let is_remaining = split.delete_opstamp < delete_task.opstamp;
+            if is_remaining || split.create_timestamp <= delete_task.create_timestamp {
+                num_affected_splits += 1;
+            }
+            if is_remaining {
+                num_remaining_splits += 1;
+            }
+        }
+        let num_processed_splits = num_affected_splits - num_remaining_splits;
+        let progress = if num_affected_splits == 0 {
+            1.0
+        } else {
+            num_processed_splits as f64 / num_affected_splits as f64
+        };
+        let elapsed_secs = (now_timestamp - delete_task.create_timestamp).max(0);
+        let estimated_completion_timestamp =
+            if num_remaining_splits == 0 || num_processed_splits == 0 {
+                None
+            } else {
+                let remaining_secs =
+                    elapsed_secs as f64 * num_remaining_splits as f64 / num_processed_splits as f64;
+                Some(now_timestamp + remaining_secs.ceil() as i64)
+            };
+        DeleteTaskStatus {
+            delete_task,
+            num_affected_splits,
+            num_remaining_splits,
+            completed: num_remaining_splits == 0,
+            progress,
+            estimated_completion_timestamp,
+        }
+    }
+}
+
 /// Delete query API handlers.
 pub fn delete_task_api_handlers(
     metastore: MetastoreServiceClient,
+    janitor_service_opt: Option<Mailbox<JanitorService>>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
-    get_delete_tasks_handler(metastore.clone()).or(post_delete_tasks_handler(metastore.clone()))
+    get_delete_tasks_handler(metastore.clone())
+        .or(post_delete_tasks_handler(metastore.clone()))
+        .or(delete_doc_by_id_handler(metastore.clone()))
+        .or(list_delete_task_statuses_handler(metastore.clone()))
+        .or(get_delete_task_status_handler(metastore.clone()))
+        .or(pause_delete_tasks_handler(janitor_service_opt.clone()))
+        .or(resume_delete_tasks_handler(janitor_service_opt))
 }
 
 pub fn get_delete_tasks_handler(
@@ -111,6 +202,110 @@ pub async fn get_delete_tasks(
     Ok(delete_tasks)
 }
 
+pub fn list_delete_task_statuses_handler(
+    metastore: MetastoreServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexes" / String / "delete-tasks")
+        .and(warp::get())
+        .and(with_arg(metastore))
+        .then(list_delete_task_statuses)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    get,
+    tag = "Delete Tasks",
+    path = "/indexes/{index_id}/delete-tasks",
+    responses(
+        (status = 200, description = "Successfully fetched the status of the delete tasks.", body = [DeleteTaskStatus])
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID to retrieve delete tasks for."),
+    )
+)]
+/// Lists the delete tasks of an index along with their progress.
+pub async fn list_delete_task_statuses(
+    index_id: String,
+    metastore: MetastoreServiceClient,
+) -> MetastoreResult<Vec<DeleteTaskStatus>> {
+    let (delete_tasks, splits) = fetch_delete_tasks_and_splits(&index_id, metastore).await?;
+    let now_timestamp = OffsetDateTime::now_utc().unix_timestamp();
+    let delete_task_statuses = delete_tasks
+        .into_iter()
+        .map(|delete_task| DeleteTaskStatus::compute(delete_task, &splits, now_timestamp))
+        .collect();
+    Ok(delete_task_statuses)
+}
+
+pub fn get_delete_task_status_handler(
+    metastore: MetastoreServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexes" / String / "delete-tasks" / u64)
+        .and(warp::get())
+        .and(with_arg(metastore))
+        .then(get_delete_task_status)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    get,
+    tag = "Delete Tasks",
+    path = "/indexes/{index_id}/delete-tasks/{opstamp}",
+    responses(
+        (status = 200, description = "Successfully fetched the status of the delete task.", body = DeleteTaskStatus)
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID of the delete task."),
+        ("opstamp" = u64, Path, description = "The opstamp of the delete task."),
+    )
+)]
+/// Returns the progress of a delete task.
+pub async fn get_delete_task_status(
+    index_id: String,
+    opstamp: u64,
+    metastore: MetastoreServiceClient,
+) -> MetastoreResult<DeleteTaskStatus> {
+    let (delete_tasks, splits) = fetch_delete_tasks_and_splits(&index_id, metastore).await?;
+    let delete_task = delete_tasks
+        .into_iter()
+        .find(|delete_task| delete_task.opstamp == opstamp)
+        .ok_or_else(|| MetastoreError::NotFound(EntityKind::DeleteTask { index_id, opstamp }))?;
+    let now_timestamp = OffsetDateTime::now_utc().unix_timestamp();
+    Ok(DeleteTaskStatus::compute(
+        delete_task,
+        &splits,
+        now_timestamp,
+    ))
+}
+
+/// Fetches the delete tasks and the published splits of an index.
+async fn fetch_delete_tasks_and_splits(
+    index_id: &str,
+    mut metastore: MetastoreServiceClient,
+) -> MetastoreResult<(Vec<DeleteTask>, Vec<SplitMetadata>)> {
+    let index_metadata_request = IndexMetadataRequest::for_index_id(index_id.to_string());
+    let index_uid: IndexUid = metastore
+        .index_metadata(index_metadata_request)
+        .await?
+        .deserialize_index_metadata()?
+        .index_uid;
+    let list_delete_tasks_request = ListDeleteTasksRequest::new(index_uid.clone(), 0);
+    let delete_tasks = metastore
+        .list_delete_tasks(list_delete_tasks_request)
+        .await?
+        .delete_tasks;
+    let query = ListSplitsQuery::for_index(index_uid).with_split_state(SplitState::Published);
+    let list_splits_request = ListSplitsRequest::try_from_list_splits_query(query)?;
+    let splits = metastore
+        .list_splits(list_splits_request)
+        .await?
+        .collect_splits_metadata()
+        .await?;
+    Ok((delete_tasks, splits))
+}
+
 pub fn post_delete_tasks_handler(
     metastore: MetastoreServiceClient,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
@@ -179,14 +374,214 @@ pub async fn post_delete_request(
     Ok(delete_task)
 }
 
+pub fn delete_doc_by_id_handler(
+    metastore: MetastoreServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!(String / "docs" / String)
+        .and(warp::delete())
+        .and(with_arg(metastore))
+        .then(delete_doc_by_id)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    delete,
+    tag = "Delete Tasks",
+    path = "/{index_id}/docs/{doc_id}",
+    responses(
+        (status = 200, description = "Successfully added a new delete task.", body = DeleteTask)
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID to delete the document from."),
+        ("doc_id" = String, Path, description = "The percent-encoded ID of the document to delete."),
+    )
+)]
+/// Delete Document by ID
+///
+/// Adds a delete task removing the documents whose doc ID field, declared in the doc mapping of
+/// the index, has the value `doc_id`. Like other delete tasks, it is not executed immediately.
+pub async fn delete_doc_by_id(
+    index_id: String,
+    doc_id: String,
+    mut metastore: MetastoreServiceClient,
+) -> Result<DeleteTask, JanitorError> {
+    let doc_id = percent_encoding::percent_decode_str(&doc_id)
+        .decode_utf8()
+        .map_err(|error| JanitorError::InvalidDeleteQuery(format!("invalid doc ID: {error}")))?
+        .into_owned();
+    let index_metadata_request = IndexMetadataRequest::for_index_id(index_id.to_string());
+    let metadata = metastore
+        .index_metadata(index_metadata_request)
+        .await?
+        .deserialize_index_metadata()?;
+    let index_uid: IndexUid = metadata.index_uid.clone();
+    let Some(doc_id_field) = metadata.index_config.doc_mapping.doc_id_field.clone() else {
+        return Err(JanitorError::InvalidDeleteQuery(format!(
+            "index `{index_id}` has no doc ID field"
+        )));
+    };
+    let query_ast: QueryAst = TermQuery {
+        field: doc_id_field,
+        value: doc_id,
+    }
+    .into();
+    let query_ast_json = serde_json::to_string(&query_ast).map_err(|_err| {
+        JanitorError::Internal("failed to serialized delete query ast".to_string())
+    })?;
+    let delete_query = DeleteQuery {
+        index_uid: index_uid.to_string(),
+        start_timestamp: None,
+        end_timestamp: None,
+        query_ast: query_ast_json,
+    };
+    let index_config = metadata.into_index_config();
+    let doc_mapper = build_doc_mapper(&index_config.doc_mapping, &index_config.search_settings)
+        .map_err(|error| JanitorError::Internal(error.to_string()))?;
+    // Validates the doc ID against the type of the doc ID field.
+    doc_mapper
+        .query(doc_mapper.schema(), &query_ast, true)
+        .map_err(|error| JanitorError::InvalidDeleteQuery(error.to_string()))?;
+    let delete_task = metastore.create_delete_task(delete_query).await?;
+    Ok(delete_task)
+}
+
+pub fn pause_delete_tasks_handler(
+    janitor_service_opt: Option<Mailbox<JanitorService>>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("delete-tasks" / "pause")
+        .and(warp::put())
+        .and(with_arg(janitor_service_opt))
+        .then(pause_delete_tasks)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    put,
+    tag = "Delete Tasks",
+    path = "/delete-tasks/pause",
+    responses(
+        (status = 200, description = "Successfully paused delete tasks.", body = DeleteTaskServiceState)
+    ),
+)]
+/// Pause Delete Tasks
+///
+/// Stops the execution of delete tasks on the janitor node. Delete tasks are still accepted and
+/// executed once resumed. This endpoint must be called on the node running the janitor service.
+pub async fn pause_delete_tasks(
+    janitor_service_opt: Option<Mailbox<JanitorService>>,
+) -> Result<DeleteTaskServiceState, JanitorError> {
+    let janitor_service = janitor_service_opt.ok_or_else(janitor_service_unavailable)?;
+    janitor_service
+        .ask_for_res(PauseDeleteTasks)
+        .await
+        .map_err(ask_error_to_janitor_error)
+}
+
+pub fn resume_delete_tasks_handler(
+    janitor_service_opt: Option<Mailbox<JanitorService>>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("delete-tasks" / "resume")
+        .and(warp::put())
+        .and(with_arg(janitor_service_opt))
+        .then(resume_delete_tasks)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    put,
+    tag = "Delete Tasks",
+    path = "/delete-tasks/resume",
+    responses(
+        (status = 200, description = "Successfully resumed delete tasks.", body = DeleteTaskServiceState)
+    ),
+)]
+/// Resume Delete Tasks
+///
+/// Resumes the execution of delete tasks on the janitor node. This endpoint must be called on the
+/// node running the janitor service.
+pub async fn resume_delete_tasks(
+    janitor_service_opt: Option<Mailbox<JanitorService>>,
+) -> Result<DeleteTaskServiceState, JanitorError> {
+    let janitor_service = janitor_service_opt.ok_or_else(janitor_service_unavailable)?;
+    janitor_service
+        .ask_for_res(ResumeDeleteTasks)
+        .await
+        .map_err(ask_error_to_janitor_error)
+}
+
+fn ask_error_to_janitor_error(ask_error: AskError<JanitorError>) -> JanitorError {
+    match ask_error {
+        AskError::ErrorReply(error) => error,
+        AskError::MessageNotDelivered | AskError::ProcessMessageError => {
+            janitor_service_unavailable()
+        }
+    }
+}
+
+fn janitor_service_unavailable() -> JanitorError {
+    JanitorError::Unavailable("the janitor service is not running on this node".to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use quickwit_indexing::TestSandbox;
+    use quickwit_metastore::SplitMetadata;
     use quickwit_proto::metastore::DeleteTask;
     use warp::Filter;
 
+    use super::DeleteTaskStatus;
     use crate::rest::recover_fn;
 
+    fn split_for_test(split_id: &str, create_timestamp: i64, delete_opstamp: u64) -> SplitMetadata {
+        let mut split_metadata = SplitMetadata::for_test(split_id.to_string());
+        split_metadata.create_timestamp = create_timestamp;
+        split_metadata.delete_opstamp = delete_opstamp;
+        split_metadata
+    }
+
+    #[test]
+    fn test_delete_task_status_compute() {
+        let delete_task = DeleteTask {
+            create_timestamp: 1_000,
+            opstamp: 5,
+            delete_query: None,
+        };
+        let status = DeleteTaskStatus::compute(delete_task.clone(), &[], 1_100);
+        assert!(status.completed);
+        assert_eq!(status.num_affected_splits, 0);
+        assert_eq!(status.progress, 1.0);
+        assert_eq!(status.estimated_completion_timestamp, None);
+
+        let splits = [
+            // Already processed.
+            split_for_test("split-1", 900, 5),
+            // Remaining.
+            split_for_test("split-2", 900, 4),
+            split_for_test("split-3", 900, 0),
+            // Created after the delete task, unaffected.
+            split_for_test("split-4", 1_050, 5),
+            // Merged after the delete task from splits not processed yet.
+            split_for_test("split-5", 1_050, 3),
+            // Processed by a later delete task.
+            split_for_test("split-6", 900, 7),
+        ];
+        let status = DeleteTaskStatus::compute(delete_task.clone(), &splits, 1_100);
+        assert!(!status.completed);
+        assert_eq!(status.num_affected_splits, 5);
+        assert_eq!(status.num_remaining_splits, 3);
+        assert_eq!(status.progress, 0.4);
+        // 2 splits processed in 100s, 3 remaining.
+        assert_eq!(status.estimated_completion_timestamp, Some(1_250));
+
+        let splits = [split_for_test("split-1", 900, 0)];
+        let status = DeleteTaskStatus::compute(delete_task, &splits, 1_100);
+        assert_eq!(status.progress, 0.0);
+        assert_eq!(status.estimated_completion_timestamp, None);
+    }
+
     #[tokio::test]
     async fn test_delete_task_api() {
         quickwit_common::setup_logging_for_tests();
@@ -203,9 +598,13 @@ mod tests {
         let test_sandbox = TestSandbox::create(index_id, doc_mapping_yaml, "{}", &["body"])
             .await
             .unwrap();
+        test_sandbox
+            .add_documents(vec![serde_json::json!({"body": "myterm", "ts": 5})])
+            .await
+            .unwrap();
         let metastore = test_sandbox.metastore();
         let delete_query_api_handlers =
-            super::delete_task_api_handlers(metastore).recover(recover_fn);
+            super::delete_task_api_handlers(metastore, None).recover(recover_fn);
         let resp = warp::test::request()
             .path("/test-delete-task-rest/delete-tasks")
             .method("POST")
@@ -216,7 +615,7 @@ mod tests {
         assert_eq!(resp.status(), 200);
         let created_delete_task: DeleteTask = serde_json::from_slice(resp.body()).unwrap();
         assert_eq!(created_delete_task.opstamp, 1);
-        let created_delete_query = created_delete_task.delete_query.unwrap();
+        let created_delete_query = created_delete_task.delete_query.clone().unwrap();
         assert_eq!(
             created_delete_query.index_uid,
             test_sandbox.index_uid().to_string()
@@ -247,6 +646,98 @@ mod tests {
         assert_eq!(resp.status(), 200);
         let delete_tasks: Vec<DeleteTask> = serde_json::from_slice(resp.body()).unwrap();
         assert_eq!(delete_tasks.len(), 1);
+
+        // GET delete task statuses.
+        let resp = warp::test::request()
+            .path("/indexes/test-delete-task-rest/delete-tasks")
+            .reply(&delete_query_api_handlers)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let delete_task_statuses: Vec<DeleteTaskStatus> =
+            serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(delete_task_statuses.len(), 1);
+        assert_eq!(delete_task_statuses[0].delete_task, created_delete_task);
+
+        let resp = warp::test::request()
+            .path("/indexes/test-delete-task-rest/delete-tasks/1")
+            .reply(&delete_query_api_handlers)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let delete_task_status: DeleteTaskStatus = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(delete_task_status.delete_task.opstamp, 1);
+        assert_eq!(delete_task_status.num_affected_splits, 1);
+        assert_eq!(delete_task_status.num_remaining_splits, 1);
+        assert!(!delete_task_status.completed);
+
+        let resp = warp::test::request()
+            .path("/indexes/test-delete-task-rest/delete-tasks/2")
+            .reply(&delete_query_api_handlers)
+            .await;
+        assert_eq!(resp.status(), 404);
+
+        // Pause delete tasks on a node without janitor service.
+        let resp = warp::test::request()
+            .path("/delete-tasks/pause")
+            .method("PUT")
+            .reply(&delete_query_api_handlers)
+            .await;
+        assert_eq!(resp.status(), 503);
+        assert!(String::from_utf8_lossy(resp.body()).contains("janitor unavailable"));
+        test_sandbox.assert_quit().await;
+    }
+
+    #[tokio::test]
+    async fn test_delete_doc_by_id_api() {
+        let index_id = "test-delete-doc-by-id-rest";
+        let doc_mapping_yaml = r#"
+            field_mappings:
+              - name: id
+                type: text
+                tokenizer: raw
+                fast: true
+              - name: body
+                type: text
+            doc_id_field: id
+        "#;
+        let test_sandbox = TestSandbox::create(index_id, doc_mapping_yaml, "{}", &["body"])
+            .await
+            .unwrap();
+        let metastore = test_sandbox.metastore();
+        let delete_query_api_handlers =
+            super::delete_task_api_handlers(metastore, None).recover(recover_fn);
+        let resp = warp::test::request()
+            .path("/test-delete-doc-by-id-rest/docs/doc%2F1")
+            .method("DELETE")
+            .reply(&delete_query_api_handlers)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let created_delete_task: DeleteTask = serde_json::from_slice(resp.body()).unwrap();
+        let created_delete_query = created_delete_task.delete_query.unwrap();
+        assert_eq!(
+            created_delete_query.query_ast,
+            r#"{"type":"term","field":"id","value":"doc/1"}"#
+        );
+        test_sandbox.assert_quit().await;
+
+        let index_id = "test-delete-doc-by-id-rest-no-doc-id";
+        let doc_mapping_yaml = r#"
+            field_mappings:
+              - name: body
+                type: text
+        "#;
+        let test_sandbox = TestSandbox::create(index_id, doc_mapping_yaml, "{}", &["body"])
+            .await
+            .unwrap();
+        let metastore = test_sandbox.metastore();
+        let delete_query_api_handlers =
+            super::delete_task_api_handlers(metastore, None).recover(recover_fn);
+        let resp = warp::test::request()
+            .path("/test-delete-doc-by-id-rest-no-doc-id/docs/doc-1")
+            .method("DELETE")
+            .reply(&delete_query_api_handlers)
+            .await;
+        assert_eq!(resp.status(), 400);
+        assert!(String::from_utf8_lossy(resp.body()).contains("has no doc ID field"));
         test_sandbox.assert_quit().await;
     }
 }
@@ -17,18 +17,23 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::time::Instant;
 
 use bytes::Bytes;
 use hyper::StatusCode;
 use quickwit_ingest::{
-    CommitType, DocBatchBuilder, IngestRequest, IngestResponse, IngestService, IngestServiceClient,
+    CommitType, DocBatch, DocBatchBuilder, IngestRequest, IngestService, IngestServiceClient,
+    IngestServiceError,
 };
 use warp::{Filter, Rejection};
 
 use crate::elastic_search_api::filter::{elastic_bulk_filter, elastic_index_bulk_filter};
 use crate::elastic_search_api::make_elastic_api_response;
-use crate::elastic_search_api::model::{BulkAction, ElasticIngestOptions, ElasticSearchError};
+use crate::elastic_search_api::model::{
+    BulkAction, ElasticBulkItem, ElasticBulkItemError, ElasticBulkItemOutcome, ElasticBulkResponse,
+    ElasticIngestOptions, ElasticSearchError,
+};
 use crate::format::extract_format_from_qs;
 use crate::ingest_api::lines;
 use crate::with_arg;
@@ -64,8 +69,10 @@ async fn elastic_ingest_bulk(
     body: Bytes,
     ingest_options: ElasticIngestOptions,
     mut ingest_service: IngestServiceClient,
-) -> Result<IngestResponse, ElasticSearchError> {
+) -> Result<ElasticBulkResponse, ElasticSearchError> {
+    let now = Instant::now();
     let mut doc_batch_builders = HashMap::new();
+    let mut items = Vec::new();
     let mut lines = lines(&body).enumerate();
 
     while let Some((line_number, line)) = lines.next() {
@@ -81,11 +88,13 @@ async fn elastic_ingest_bulk(
                 "expected source for the action".to_string(),
             )
         })?;
+        let is_create_action = matches!(action, BulkAction::Create(_));
+        let action_meta = action.into_meta();
         // when ingesting on /my-index/_bulk, if _index: is set to something else than my-index,
         // ES honors it and create the doc in the requested index. That is, `my-index` is a default
         // value in case _index: is missing, but not a constraint on each sub-action.
-        let index_id = action
-            .into_index()
+        let index_id = action_meta
+            .index_id
             .or_else(|| index.clone())
             .ok_or_else(|| {
                 ElasticSearchError::new(
@@ -95,21 +104,72 @@ async fn elastic_ingest_bulk(
             })?;
         let doc_batch_builder = doc_batch_builders
             .entry(index_id.clone())
-            .or_insert(DocBatchBuilder::new(index_id));
+            .or_insert(DocBatchBuilder::new(index_id.clone()));
 
         doc_batch_builder.ingest_doc(source);
+
+        let outcome = ElasticBulkItemOutcome {
+            index_id,
+            doc_id: action_meta.doc_id,
+            status: StatusCode::CREATED,
+            error: None,
+        };
+        let item = if is_create_action {
+            ElasticBulkItem::Create(outcome)
+        } else {
+            ElasticBulkItem::Index(outcome)
+        };
+        items.push(item);
     }
-    let doc_batches = doc_batch_builders
+    let mut doc_batches: Vec<DocBatch> = doc_batch_builders
         .into_values()
         .map(|builder| builder.build())
         .collect();
     let commit_type: CommitType = ingest_options.refresh.into();
-    let ingest_request = IngestRequest {
-        doc_batches,
-        commit: commit_type.into(),
+    let mut unknown_index_ids = HashSet::new();
+
+    // The ingest service rejects the whole request as soon as one of the targeted indexes does not
+    // exist, before ingesting anything. We exclude the unknown indexes one at a time and report an
+    // error for each of their documents instead, like Elasticsearch does.
+    while !doc_batches.is_empty() {
+        let ingest_request = IngestRequest {
+            doc_batches: doc_batches.clone(),
+            commit: commit_type.into(),
+            idempotency_key: None,
+            return_positions: false,
+        };
+        match ingest_service.ingest(ingest_request).await {
+            Ok(_) => break,
+            Err(IngestServiceError::IndexNotFound { index_id }) => {
+                let num_doc_batches = doc_batches.len();
+                doc_batches.retain(|doc_batch| doc_batch.index_id != index_id);
+
+                if doc_batches.len() == num_doc_batches {
+                    return Err(IngestServiceError::IndexNotFound { index_id }.into());
+                }
+                unknown_index_ids.insert(index_id);
+            }
+            Err(error) => return Err(error.into()),
+        }
+    }
+    for item in &mut items {
+        let outcome = item.outcome_mut();
+
+        if unknown_index_ids.contains(&outcome.index_id) {
+            outcome.status = StatusCode::NOT_FOUND;
+            outcome.error = Some(ElasticBulkItemError {
+                error_type: "index_not_found_exception".to_string(),
+                reason: format!("no such index [{}]", outcome.index_id),
+            });
+        }
+    }
+    let errors = items.iter().any(|item| item.outcome().error.is_some());
+    let bulk_response = ElasticBulkResponse {
+        took: now.elapsed().as_millis() as u64,
+        errors,
+        items,
     };
-    let ingest_response = ingest_service.ingest(ingest_request).await?;
-    Ok(ingest_response)
+    Ok(bulk_response)
 }
 
 #[cfg(test)]
@@ -119,34 +179,87 @@ mod tests {
 
     use hyper::StatusCode;
     use quickwit_config::{IngestApiConfig, NodeConfig};
-    use quickwit_ingest::{
-        FetchRequest, IngestResponse, IngestServiceClient, SuggestTruncateRequest,
-    };
+    use quickwit_ingest::{FetchRequest, IngestServiceClient, SuggestTruncateRequest};
     use quickwit_search::MockSearchService;
 
     use crate::elastic_search_api::elastic_api_handlers;
-    use crate::elastic_search_api::model::ElasticSearchError;
+    use crate::elastic_search_api::model::{
+        ElasticBulkItem, ElasticBulkResponse, ElasticSearchError,
+    };
     use crate::ingest_api::setup_ingest_service;
+    use crate::search_query_limiter::SearchQueryLimiter;
 
     #[tokio::test]
-    async fn test_bulk_api_returns_404_if_index_id_does_not_exist() {
+    async fn test_bulk_api_returns_item_errors_for_unknown_indexes() {
         let config = Arc::new(NodeConfig::for_test());
         let search_service = Arc::new(MockSearchService::new());
-        let (universe, _temp_dir, ingest_service, _) =
+        let (universe, _temp_dir, ingest_service, ingest_service_mailbox) =
             setup_ingest_service(&["my-index"], &IngestApiConfig::default()).await;
-        let elastic_api_handlers = elastic_api_handlers(config, search_service, ingest_service);
+        let elastic_api_handlers = elastic_api_handlers(
+            config,
+            search_service,
+            ingest_service,
+            SearchQueryLimiter::default(),
+        );
         let payload = r#"
             { "create" : { "_index" : "my-index", "_id" : "1"} }
             {"id": 1, "message": "push"}
-            { "create" : { "_index" : "index-2", "_id" : "1" } }
-            {"id": 1, "message": "push"}"#;
+            { "index" : { "_index" : "index-2", "_id" : "1" } }
+            {"id": 1, "message": "push"}
+            { "create" : { "_index" : "index-3" } }
+            {"id": 2, "message": "push"}
+            { "create" : { "_index" : "my-index" } }
+            {"id": 2, "message": "push"}"#;
         let resp = warp::test::request()
             .path("/_elastic/_bulk")
             .method("POST")
             .body(payload)
             .reply(&elastic_api_handlers)
             .await;
-        assert_eq!(resp.status(), 404);
+        assert_eq!(resp.status(), 200);
+        let bulk_response: ElasticBulkResponse = serde_json::from_slice(resp.body()).unwrap();
+        assert!(bulk_response.errors);
+        assert_eq!(bulk_response.items.len(), 4);
+
+        let ElasticBulkItem::Create(outcome) = &bulk_response.items[0] else {
+            panic!("expected create item");
+        };
+        assert_eq!(outcome.index_id, "my-index");
+        assert_eq!(outcome.doc_id.as_deref(), Some("1"));
+        assert_eq!(outcome.status, StatusCode::CREATED);
+        assert!(outcome.error.is_none());
+
+        let ElasticBulkItem::Index(outcome) = &bulk_response.items[1] else {
+            panic!("expected index item");
+        };
+        assert_eq!(outcome.index_id, "index-2");
+        assert_eq!(outcome.status, StatusCode::NOT_FOUND);
+        let error = outcome.error.as_ref().unwrap();
+        assert_eq!(error.error_type, "index_not_found_exception");
+        assert_eq!(error.reason, "no such index [index-2]");
+
+        assert_eq!(bulk_response.items[2].outcome().index_id, "index-3");
+        assert_eq!(
+            bulk_response.items[2].outcome().status,
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(bulk_response.items[3].outcome().status, StatusCode::CREATED);
+
+        // The documents targeting existing indexes are ingested.
+        assert_eq!(
+            ingest_service_mailbox
+                .ask_for_res(FetchRequest {
+                    index_id: "my-index".to_string(),
+                    start_after: None,
+                    num_bytes_limit: None,
+                })
+                .await
+                .unwrap()
+                .doc_batch
+                .unwrap()
+                .num_docs(),
+            2
+        );
         universe.assert_quit().await;
     }
 
@@ -156,7 +269,12 @@ mod tests {
         let search_service = Arc::new(MockSearchService::new());
         let (universe, _temp_dir, ingest_service, _) =
             setup_ingest_service(&["my-index-1", "my-index-2"], &IngestApiConfig::default()).await;
-        let elastic_api_handlers = elastic_api_handlers(config, search_service, ingest_service);
+        let elastic_api_handlers = elastic_api_handlers(
+            config,
+            search_service,
+            ingest_service,
+            SearchQueryLimiter::default(),
+        );
         let payload = r#"
             { "create" : { "_index" : "my-index-1", "_id" : "1"} }
             {"id": 1, "message": "push"}
@@ -171,8 +289,9 @@ mod tests {
             .reply(&elastic_api_handlers)
             .await;
         assert_eq!(resp.status(), 200);
-        let ingest_response: IngestResponse = serde_json::from_slice(resp.body()).unwrap();
-        assert_eq!(ingest_response.num_docs_for_processing, 3);
+        let bulk_response: ElasticBulkResponse = serde_json::from_slice(resp.body()).unwrap();
+        assert!(!bulk_response.errors);
+        assert_eq!(bulk_response.items.len(), 3);
         universe.assert_quit().await;
     }
 
@@ -182,7 +301,12 @@ mod tests {
         let search_service = Arc::new(MockSearchService::new());
         let (universe, _temp_dir, ingest_service, _) =
             setup_ingest_service(&["my-index-1"], &IngestApiConfig::default()).await;
-        let elastic_api_handlers = elastic_api_handlers(config, search_service, ingest_service);
+        let elastic_api_handlers = elastic_api_handlers(
+            config,
+            search_service,
+            ingest_service,
+            SearchQueryLimiter::default(),
+        );
         let payload = "
             {\"create\": {\"_index\": \"my-index-1\", \"_id\": \"1674834324802805760\"}}
             \u{20}\u{20}\u{20}\u{20}\n
@@ -194,8 +318,9 @@ mod tests {
             .reply(&elastic_api_handlers)
             .await;
         assert_eq!(resp.status(), 200);
-        let ingest_response: IngestResponse = serde_json::from_slice(resp.body()).unwrap();
-        assert_eq!(ingest_response.num_docs_for_processing, 1);
+        let bulk_response: ElasticBulkResponse = serde_json::from_slice(resp.body()).unwrap();
+        assert!(!bulk_response.errors);
+        assert_eq!(bulk_response.items.len(), 1);
         universe.assert_quit().await;
     }
 
@@ -205,7 +330,12 @@ mod tests {
         let search_service = Arc::new(MockSearchService::new());
         let (universe, _temp_dir, ingest_service, _) =
             setup_ingest_service(&["my-index-1", "my-index-2"], &IngestApiConfig::default()).await;
-        let elastic_api_handlers = elastic_api_handlers(config, search_service, ingest_service);
+        let elastic_api_handlers = elastic_api_handlers(
+            config,
+            search_service,
+            ingest_service,
+            SearchQueryLimiter::default(),
+        );
         let payload = r#"
             { "create" : { "_index" : "my-index-1", "_id" : "1"} }
             {"id": 1, "message": "push"}
@@ -220,8 +350,9 @@ mod tests {
             .reply(&elastic_api_handlers)
             .await;
         assert_eq!(resp.status(), 200);
-        let ingest_response: IngestResponse = serde_json::from_slice(resp.body()).unwrap();
-        assert_eq!(ingest_response.num_docs_for_processing, 3);
+        let bulk_response: ElasticBulkResponse = serde_json::from_slice(resp.body()).unwrap();
+        assert!(!bulk_response.errors);
+        assert_eq!(bulk_response.items.len(), 3);
         universe.assert_quit().await;
     }
 
@@ -231,7 +362,12 @@ mod tests {
         let search_service = Arc::new(MockSearchService::new());
         let (universe, _temp_dir, ingest_service, ingest_service_mailbox) =
             setup_ingest_service(&["my-index-1", "my-index-2"], &IngestApiConfig::default()).await;
-        let elastic_api_handlers = elastic_api_handlers(config, search_service, ingest_service);
+        let elastic_api_handlers = elastic_api_handlers(
+            config,
+            search_service,
+            ingest_service,
+            SearchQueryLimiter::default(),
+        );
         let payload = r#"
             { "create" : { "_index" : "my-index-1", "_id" : "1"} }
             {"id": 1, "message": "push"}
@@ -248,8 +384,9 @@ mod tests {
                 .await;
 
             assert_eq!(resp.status(), 200);
-            let ingest_response: IngestResponse = serde_json::from_slice(resp.body()).unwrap();
-            assert_eq!(ingest_response.num_docs_for_processing, 3);
+            let bulk_response: ElasticBulkResponse = serde_json::from_slice(resp.body()).unwrap();
+            assert!(!bulk_response.errors);
+            assert_eq!(bulk_response.items.len(), 3);
         });
         universe.sleep(Duration::from_secs(10)).await;
         assert!(!handle.is_finished());
@@ -308,7 +445,12 @@ mod tests {
         let search_service = Arc::new(MockSearchService::new());
         let (universe, _temp_dir, ingest_service, ingest_service_mailbox) =
             setup_ingest_service(&["my-index-1", "my-index-2"], &IngestApiConfig::default()).await;
-        let elastic_api_handlers = elastic_api_handlers(config, search_service, ingest_service);
+        let elastic_api_handlers = elastic_api_handlers(
+            config,
+            search_service,
+            ingest_service,
+            SearchQueryLimiter::default(),
+        );
         let payload = r#"
             { "create" : { "_index" : "my-index-1", "_id" : "1"} }
             {"id": 1, "message": "push"}
@@ -325,8 +467,9 @@ mod tests {
                 .await;
 
             assert_eq!(resp.status(), 200);
-            let ingest_response: IngestResponse = serde_json::from_slice(resp.body()).unwrap();
-            assert_eq!(ingest_response.num_docs_for_processing, 3);
+            let bulk_response: ElasticBulkResponse = serde_json::from_slice(resp.body()).unwrap();
+            assert!(!bulk_response.errors);
+            assert_eq!(bulk_response.items.len(), 3);
         });
         universe.sleep(Duration::from_secs(10)).await;
         assert!(!handle.is_finished());
@@ -383,7 +526,12 @@ mod tests {
         let config = Arc::new(NodeConfig::for_test());
         let search_service = Arc::new(MockSearchService::new());
         let ingest_service = IngestServiceClient::from(IngestServiceClient::mock());
-        let elastic_api_handlers = elastic_api_handlers(config, search_service, ingest_service);
+        let elastic_api_handlers = elastic_api_handlers(
+            config,
+            search_service,
+            ingest_service,
+            SearchQueryLimiter::default(),
+        );
         let payload = r#"
             {"create": {"_index": "my-index", "_id": "1"},}
             {"id": 1, "message": "my-doc"}"#;
@@ -0,0 +1,418 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Elasticsearch `_cat` endpoints, printing the indexes, splits, and nodes of the cluster as plain
+//! text columns. Splits are reported as primary shards.
+
+use std::collections::HashMap;
+
+use hyper::header::CONTENT_TYPE;
+use hyper::http::HeaderValue;
+use quickwit_cluster::Cluster;
+use quickwit_metastore::{
+    IndexMetadata, ListIndexesMetadataResponseExt, ListSplitsQuery, ListSplitsRequestExt,
+    MetastoreServiceStreamSplitsExt, Split, SplitState,
+};
+use quickwit_proto::metastore::{
+    ListIndexesMetadataRequest, ListSplitsRequest, MetastoreService, MetastoreServiceClient,
+};
+use quickwit_proto::types::IndexUid;
+use warp::reply::Response;
+use warp::{Filter, Rejection, Reply};
+
+use crate::elastic_search_api::filter::{
+    elastic_cat_indices_filter, elastic_cat_nodes_filter, elastic_cat_shards_filter,
+};
+use crate::elastic_search_api::make_elastic_api_response;
+use crate::elastic_search_api::model::{
+    format_num_bytes, CatQueryParams, CatResponse, CatTable, ElasticSearchError,
+};
+use crate::{with_arg, BodyFormat};
+
+const CAT_INDICES_COLUMNS: &[&str] = &[
+    "health",
+    "status",
+    "index",
+    "uuid",
+    "pri",
+    "rep",
+    "docs.count",
+    "docs.deleted",
+    "store.size",
+    "pri.store.size",
+];
+
+const CAT_SHARDS_COLUMNS: &[&str] = &["index", "shard", "prirep", "state", "docs", "store"];
+
+const CAT_NODES_COLUMNS: &[&str] = &["ip", "node.role", "name"];
+
+/// `_cat` endpoints handlers.
+pub fn es_compat_cat_handlers(
+    metastore: MetastoreServiceClient,
+    cluster: Cluster,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    es_compat_cat_indices_handler(metastore.clone())
+        .or(es_compat_cat_shards_handler(metastore))
+        .or(es_compat_cat_nodes_handler(cluster))
+}
+
+/// GET `_elastic/_cat/indices` and `_elastic/_cat/indices/<index>`
+fn es_compat_cat_indices_handler(
+    metastore: MetastoreServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    elastic_cat_indices_filter()
+        .and(with_arg(metastore))
+        .then(es_compat_cat_indices)
+}
+
+async fn es_compat_cat_indices(
+    index_id_patterns: Vec<String>,
+    cat_params: CatQueryParams,
+    metastore: MetastoreServiceClient,
+) -> Response {
+    let result = cat_indices(index_id_patterns, metastore).await;
+    make_cat_response(result, &cat_params)
+}
+
+/// GET `_elastic/_cat/shards` and `_elastic/_cat/shards/<index>`
+fn es_compat_cat_shards_handler(
+    metastore: MetastoreServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    elastic_cat_shards_filter()
+        .and(with_arg(metastore))
+        .then(es_compat_cat_shards)
+}
+
+async fn es_compat_cat_shards(
+    index_id_patterns: Vec<String>,
+    cat_params: CatQueryParams,
+    metastore: MetastoreServiceClient,
+) -> Response {
+    let result = cat_shards(index_id_patterns, metastore).await;
+    make_cat_response(result, &cat_params)
+}
+
+/// GET `_elastic/_cat/nodes`
+fn es_compat_cat_nodes_handler(
+    cluster: Cluster,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    elastic_cat_nodes_filter()
+        .and(with_arg(cluster))
+        .then(es_compat_cat_nodes)
+}
+
+async fn es_compat_cat_nodes(cat_params: CatQueryParams, cluster: Cluster) -> Response {
+    let result = cat_nodes(cluster).await;
+    make_cat_response(result, &cat_params)
+}
+
+fn make_cat_response(
+    result: Result<CatTable, ElasticSearchError>,
+    cat_params: &CatQueryParams,
+) -> Response {
+    match result.and_then(|cat_table| cat_table.render(cat_params)) {
+        Ok(CatResponse::Text(text)) => {
+            let mut response = Response::new(text.into());
+            response.headers_mut().insert(
+                CONTENT_TYPE,
+                HeaderValue::from_static("text/plain; charset=UTF-8"),
+            );
+            response
+        }
+        Ok(CatResponse::Json(json_value)) => {
+            make_elastic_api_response(Ok(json_value), BodyFormat::default()).into_response()
+        }
+        Err(error) => {
+            make_elastic_api_response::<()>(Err(error), BodyFormat::default()).into_response()
+        }
+    }
+}
+
+/// Returns the metadata of the indexes matching the patterns and their published splits.
+async fn list_indexes_and_splits(
+    index_id_patterns: Vec<String>,
+    mut metastore: MetastoreServiceClient,
+) -> Result<(Vec<IndexMetadata>, Vec<Split>), ElasticSearchError> {
+    let list_indexes_metadata_request = ListIndexesMetadataRequest { index_id_patterns };
+    let mut indexes_metadata = metastore
+        .list_indexes_metadata(list_indexes_metadata_request)
+        .await?
+        .deserialize_indexes_metadata()?;
+    if indexes_metadata.is_empty() {
+        return Ok((Vec::new(), Vec::new()));
+    }
+    indexes_metadata.sort_by(|left, right| left.index_id().cmp(right.index_id()));
+
+    let index_uids: Vec<IndexUid> = indexes_metadata
+        .iter()
+        .map(|index_metadata| index_metadata.index_uid.clone())
+        .collect();
+    let list_splits_query =
+        ListSplitsQuery::try_from_index_uids(index_uids)?.with_split_state(SplitState::Published);
+    let list_splits_request = ListSplitsRequest::try_from_list_splits_query(list_splits_query)?;
+    let splits = metastore
+        .list_splits(list_splits_request)
+        .await?
+        .collect_splits()
+        .await?;
+    Ok((indexes_metadata, splits))
+}
+
+async fn cat_indices(
+    index_id_patterns: Vec<String>,
+    metastore: MetastoreServiceClient,
+) -> Result<CatTable, ElasticSearchError> {
+    let (indexes_metadata, splits) = list_indexes_and_splits(index_id_patterns, metastore).await?;
+
+    // Number of splits, documents, and bytes per index.
+    let mut index_stats: HashMap<IndexUid, (usize, u64, u64)> = HashMap::new();
+    for split in splits {
+        let stats = index_stats
+            .entry(split.split_metadata.index_uid.clone())
+            .or_default();
+        stats.0 += 1;
+        stats.1 += split.split_metadata.num_docs as u64;
+        stats.2 += split.split_metadata.footer_offsets.end;
+    }
+    let mut cat_table = CatTable::new(CAT_INDICES_COLUMNS);
+
+    for index_metadata in indexes_metadata {
+        let (num_splits, num_docs, num_bytes) = index_stats
+            .get(&index_metadata.index_uid)
+            .copied()
+            .unwrap_or_default();
+        let store_size = format_num_bytes(num_bytes);
+        cat_table.push_row(vec![
+            "green".to_string(),
+            "open".to_string(),
+            index_metadata.index_id().to_string(),
+            index_metadata.index_uid.to_string(),
+            num_splits.to_string(),
+            "0".to_string(),
+            num_docs.to_string(),
+            "0".to_string(),
+            store_size.clone(),
+            store_size,
+        ]);
+    }
+    Ok(cat_table)
+}
+
+async fn cat_shards(
+    index_id_patterns: Vec<String>,
+    metastore: MetastoreServiceClient,
+) -> Result<CatTable, ElasticSearchError> {
+    let (indexes_metadata, mut splits) =
+        list_indexes_and_splits(index_id_patterns, metastore).await?;
+
+    let index_ids: HashMap<IndexUid, String> = indexes_metadata
+        .into_iter()
+        .map(|index_metadata| {
+            let index_id = index_metadata.index_id().to_string();
+            (index_metadata.index_uid, index_id)
+        })
+        .collect();
+    splits.sort_by(|left, right| {
+        (
+            &index_ids[&left.split_metadata.index_uid],
+            &left.split_metadata.split_id,
+        )
+            .cmp(&(
+                &index_ids[&right.split_metadata.index_uid],
+                &right.split_metadata.split_id,
+            ))
+    });
+    let mut cat_table = CatTable::new(CAT_SHARDS_COLUMNS);
+
+    for split in splits {
+        let split_metadata = split.split_metadata;
+        cat_table.push_row(vec![
+            index_ids[&split_metadata.index_uid].clone(),
+            split_metadata.split_id,
+            "p".to_string(),
+            "STARTED".to_string(),
+            split_metadata.num_docs.to_string(),
+            format_num_bytes(split_metadata.footer_offsets.end),
+        ]);
+    }
+    Ok(cat_table)
+}
+
+async fn cat_nodes(cluster: Cluster) -> Result<CatTable, ElasticSearchError> {
+    let mut members = cluster.ready_members().await;
+    members.sort_by(|left, right| left.node_id.cmp(&right.node_id));
+
+    let mut cat_table = CatTable::new(CAT_NODES_COLUMNS);
+
+    for member in members {
+        let mut services: Vec<&str> = member
+            .enabled_services
+            .iter()
+            .map(|service| service.as_str())
+            .collect();
+        services.sort_unstable();
+        cat_table.push_row(vec![
+            member.grpc_advertise_addr.ip().to_string(),
+            services.join(","),
+            member.node_id.to_string(),
+        ]);
+    }
+    Ok(cat_table)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use quickwit_cluster::{create_cluster_for_test, ChannelTransport};
+    use quickwit_common::ServiceStream;
+    use quickwit_indexing::MockSplitBuilder;
+    use quickwit_metastore::{
+        IndexMetadata, ListIndexesMetadataResponseExt, ListSplitsResponseExt,
+    };
+    use quickwit_proto::metastore::{
+        ListIndexesMetadataResponse, ListSplitsResponse, MetastoreServiceClient,
+    };
+    use serde_json::Value as JsonValue;
+
+    use super::*;
+
+    fn mock_metastore_for_test() -> MetastoreServiceClient {
+        let mut mock_metastore = MetastoreServiceClient::mock();
+        let index_metadata_foo = IndexMetadata::for_test("foo", "ram:///indexes/foo");
+        let index_metadata_bar = IndexMetadata::for_test("bar", "ram:///indexes/bar");
+        let index_uid_foo = index_metadata_foo.index_uid.clone();
+        mock_metastore
+            .expect_list_indexes_metadata()
+            .returning(move |_| {
+                Ok(ListIndexesMetadataResponse::try_from_indexes_metadata(vec![
+                    index_metadata_foo.clone(),
+                    index_metadata_bar.clone(),
+                ])
+                .unwrap())
+            });
+        mock_metastore.expect_list_splits().returning(move |_| {
+            let splits = vec![
+                MockSplitBuilder::new("split_2")
+                    .with_index_uid(&index_uid_foo)
+                    .build(),
+                MockSplitBuilder::new("split_1")
+                    .with_index_uid(&index_uid_foo)
+                    .build(),
+            ];
+            let list_splits_response = ListSplitsResponse::try_from_splits(splits).unwrap();
+            Ok(ServiceStream::from(vec![Ok(list_splits_response)]))
+        });
+        MetastoreServiceClient::from(mock_metastore)
+    }
+
+    async fn cluster_for_test() -> Cluster {
+        let transport = ChannelTransport::default();
+        let cluster = create_cluster_for_test(Vec::new(), &["indexer"], &transport, true)
+            .await
+            .unwrap();
+        cluster
+            .wait_for_ready_members(|members| members.len() == 1, Duration::from_secs(5))
+            .await
+            .unwrap();
+        cluster
+    }
+
+    #[tokio::test]
+    async fn test_cat_indices() {
+        let metastore = mock_metastore_for_test();
+        let cluster = cluster_for_test().await;
+        let cat_handlers = es_compat_cat_handlers(metastore, cluster);
+
+        let resp = warp::test::request()
+            .path("/_elastic/_cat/indices?v&h=index,pri,docs.count,store.size")
+            .reply(&cat_handlers)
+            .await;
+        assert_eq!(resp.status(), 200);
+        assert_eq!(
+            resp.headers().get(CONTENT_TYPE).unwrap(),
+            "text/plain; charset=UTF-8"
+        );
+        let body = std::str::from_utf8(resp.body()).unwrap();
+        assert_eq!(
+            body,
+            "index pri docs.count store.size\nbar   0   0          0b\nfoo   2   20         \
+             1.6kb\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cat_indices_json() {
+        let metastore = mock_metastore_for_test();
+        let cluster = cluster_for_test().await;
+        let cat_handlers = es_compat_cat_handlers(metastore, cluster);
+
+        let resp = warp::test::request()
+            .path("/_elastic/_cat/indices?format=json&h=index,health,docs.count")
+            .reply(&cat_handlers)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let body: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        let expected_body = serde_json::json!([
+            {"index": "bar", "health": "green", "docs.count": "0"},
+            {"index": "foo", "health": "green", "docs.count": "20"},
+        ]);
+        assert_eq!(body, expected_body);
+
+        let resp = warp::test::request()
+            .path("/_elastic/_cat/indices?h=index,unknown")
+            .reply(&cat_handlers)
+            .await;
+        assert_eq!(resp.status(), 400);
+    }
+
+    #[tokio::test]
+    async fn test_cat_shards() {
+        let metastore = mock_metastore_for_test();
+        let cluster = cluster_for_test().await;
+        let cat_handlers = es_compat_cat_handlers(metastore, cluster);
+
+        let resp = warp::test::request()
+            .path("/_elastic/_cat/shards")
+            .reply(&cat_handlers)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let body = std::str::from_utf8(resp.body()).unwrap();
+        assert_eq!(
+            body,
+            "foo split_1 p STARTED 10 800b\nfoo split_2 p STARTED 10 800b\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cat_nodes() {
+        let metastore = MetastoreServiceClient::mock().into();
+        let cluster = cluster_for_test().await;
+        let self_node_id = cluster.self_node_id().to_string();
+        let cat_handlers = es_compat_cat_handlers(metastore, cluster);
+
+        let resp = warp::test::request()
+            .path("/_elastic/_cat/nodes?h=name,node.role")
+            .reply(&cat_handlers)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let body = std::str::from_utf8(resp.body()).unwrap();
+        assert_eq!(body, format!("{self_node_id} indexer\n"));
+    }
+}
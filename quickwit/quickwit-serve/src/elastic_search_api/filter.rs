@@ -25,7 +25,8 @@ use warp::{Filter, Rejection};
 
 use super::model::MultiSearchQueryParams;
 use crate::elastic_search_api::model::{
-    ElasticIngestOptions, ScrollQueryParams, SearchBody, SearchQueryParams,
+    CatQueryParams, ElasticIngestOptions, FieldCapabilityQueryParams, ScrollQueryParams,
+    SearchBody, SearchQueryParams,
 };
 use crate::search_api::extract_index_id_patterns;
 
@@ -77,6 +78,45 @@ pub(crate) fn elastic_bulk_filter(
         .and(serde_qs::warp::query(serde_qs::Config::default()))
 }
 
+/// Matches `_elastic/_cat/{name}` and `_elastic/_cat/{name}/{index}`, returning the index ID
+/// patterns, `*` if none.
+fn elastic_cat_filter(
+    name: &'static str,
+) -> impl Filter<Extract = (Vec<String>, CatQueryParams), Error = Rejection> + Clone {
+    warp::path!("_elastic" / "_cat" / ..)
+        .and(warp::path(name))
+        .and(
+            warp::path::end()
+                .map(|| vec!["*".to_string()])
+                .or(warp::path::param::<String>()
+                    .and(warp::path::end())
+                    .and_then(extract_index_id_patterns))
+                .unify(),
+        )
+        .and(warp::get())
+        .and(serde_qs::warp::query(serde_qs::Config::default()))
+}
+
+#[utoipa::path(get, tag = "Indexes", path = "/_cat/indices")]
+pub(crate) fn elastic_cat_indices_filter(
+) -> impl Filter<Extract = (Vec<String>, CatQueryParams), Error = Rejection> + Clone {
+    elastic_cat_filter("indices")
+}
+
+#[utoipa::path(get, tag = "Splits", path = "/_cat/shards")]
+pub(crate) fn elastic_cat_shards_filter(
+) -> impl Filter<Extract = (Vec<String>, CatQueryParams), Error = Rejection> + Clone {
+    elastic_cat_filter("shards")
+}
+
+#[utoipa::path(get, tag = "Cluster Info", path = "/_cat/nodes")]
+pub(crate) fn elastic_cat_nodes_filter(
+) -> impl Filter<Extract = (CatQueryParams,), Error = Rejection> + Clone {
+    warp::path!("_elastic" / "_cat" / "nodes")
+        .and(warp::get())
+        .and(serde_qs::warp::query(serde_qs::Config::default()))
+}
+
 /// Like the warp json filter, but accepts an empty body and interprets it as `T::default`.
 fn json_or_empty<T: DeserializeOwned + Send + Default>(
 ) -> impl Filter<Extract = (T,), Error = Rejection> + Copy {
@@ -111,6 +151,34 @@ pub(crate) fn elastic_index_search_filter(
         .and(json_or_empty())
 }
 
+#[utoipa::path(get, tag = "Search", path = "/{index}/_count")]
+pub(crate) fn elastic_index_count_filter(
+) -> impl Filter<Extract = (Vec<String>, SearchQueryParams, SearchBody), Error = Rejection> + Clone
+{
+    warp::path!("_elastic" / String / "_count")
+        .and_then(extract_index_id_patterns)
+        .and(warp::get().or(warp::post()).unify())
+        .and(serde_qs::warp::query(serde_qs::Config::default()))
+        .and(json_or_empty())
+}
+
+#[utoipa::path(get, tag = "Search", path = "/_field_caps")]
+pub(crate) fn elastic_field_capabilities_filter(
+) -> impl Filter<Extract = (FieldCapabilityQueryParams,), Error = Rejection> + Clone {
+    warp::path!("_elastic" / "_field_caps")
+        .and(warp::get().or(warp::post()).unify())
+        .and(serde_qs::warp::query(serde_qs::Config::default()))
+}
+
+#[utoipa::path(get, tag = "Search", path = "/{index}/_field_caps")]
+pub(crate) fn elastic_index_field_capabilities_filter(
+) -> impl Filter<Extract = (Vec<String>, FieldCapabilityQueryParams), Error = Rejection> + Clone {
+    warp::path!("_elastic" / String / "_field_caps")
+        .and_then(extract_index_id_patterns)
+        .and(warp::get().or(warp::post()).unify())
+        .and(serde_qs::warp::query(serde_qs::Config::default()))
+}
+
 #[utoipa::path(
     post,
     tag = "Ingest",
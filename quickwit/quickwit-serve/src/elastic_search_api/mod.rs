@@ -18,6 +18,7 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 mod bulk;
+mod cat;
 mod filter;
 mod model;
 mod rest_handler;
@@ -25,20 +26,24 @@ mod rest_handler;
 use std::sync::Arc;
 
 use bulk::{es_compat_bulk_handler, es_compat_index_bulk_handler};
+pub(crate) use cat::es_compat_cat_handlers;
 pub use filter::ElasticCompatibleApi;
 use hyper::StatusCode;
 use quickwit_config::NodeConfig;
 use quickwit_ingest::IngestServiceClient;
 use quickwit_search::SearchService;
 use rest_handler::{
-    es_compat_cluster_info_handler, es_compat_index_multi_search_handler,
-    es_compat_index_search_handler, es_compat_scroll_handler, es_compat_search_handler,
+    es_compat_cluster_info_handler, es_compat_field_capabilities_handler,
+    es_compat_index_count_handler, es_compat_index_field_capabilities_handler,
+    es_compat_index_multi_search_handler, es_compat_index_search_handler, es_compat_scroll_handler,
+    es_compat_search_handler,
 };
 use serde::{Deserialize, Serialize};
 use warp::{Filter, Rejection};
 
 use crate::elastic_search_api::model::ElasticSearchError;
 use crate::json_api_response::JsonApiResponse;
+use crate::search_query_limiter::SearchQueryLimiter;
 use crate::{BodyFormat, BuildInfo};
 
 /// Setup Elasticsearch API handlers
@@ -49,12 +54,34 @@ pub fn elastic_api_handlers(
     node_config: Arc<NodeConfig>,
     search_service: Arc<dyn SearchService>,
     ingest_service: IngestServiceClient,
+    search_query_limiter: SearchQueryLimiter,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    let max_num_concurrent_multi_searches = node_config
+        .searcher_config
+        .max_num_concurrent_multi_searches;
     es_compat_cluster_info_handler(node_config, BuildInfo::get())
         .or(es_compat_search_handler(search_service.clone()))
-        .or(es_compat_index_search_handler(search_service.clone()))
-        .or(es_compat_scroll_handler(search_service.clone()))
-        .or(es_compat_index_multi_search_handler(search_service))
+        .or(es_compat_index_search_handler(
+            search_service.clone(),
+            search_query_limiter.clone(),
+        ))
+        .or(es_compat_index_count_handler(
+            search_service.clone(),
+            search_query_limiter.clone(),
+        ))
+        .or(es_compat_field_capabilities_handler(search_service.clone()))
+        .or(es_compat_index_field_capabilities_handler(
+            search_service.clone(),
+        ))
+        .or(es_compat_scroll_handler(
+            search_service.clone(),
+            search_query_limiter.clone(),
+        ))
+        .or(es_compat_index_multi_search_handler(
+            search_service,
+            search_query_limiter,
+            max_num_concurrent_multi_searches,
+        ))
         .or(es_compat_bulk_handler(ingest_service.clone()))
         .or(es_compat_index_bulk_handler(ingest_service))
     // Register newly created handlers here.
@@ -118,6 +145,7 @@ mod tests {
     use crate::elastic_search_api::model::MultiSearchResponse;
     use crate::elastic_search_api::rest_handler::es_compat_cluster_info_handler;
     use crate::rest::recover_fn;
+    use crate::search_query_limiter::SearchQueryLimiter;
     use crate::BuildInfo;
 
     fn ingest_service_client() -> IngestServiceClient {
@@ -147,6 +175,7 @@ mod tests {
             config,
             Arc::new(mock_search_service),
             ingest_service_client(),
+            SearchQueryLimiter::default(),
         );
         let msearch_payload = r#"
             {"index":"index-1"}
@@ -194,6 +223,7 @@ mod tests {
             config,
             Arc::new(mock_search_service),
             ingest_service_client(),
+            SearchQueryLimiter::default(),
         );
         let msearch_payload = r#"
             {"index":"index-1"}
@@ -221,6 +251,84 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_msearch_api_returns_responses_in_order() {
+        let config = Arc::new(NodeConfig::for_test());
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_root_search()
+            .times(5)
+            .returning(|search_request| {
+                let index_idx: u64 = search_request.index_id_patterns[0]
+                    .strip_prefix("index-")
+                    .unwrap()
+                    .parse()
+                    .unwrap();
+                Ok(quickwit_proto::search::SearchResponse {
+                    num_hits: index_idx,
+                    ..Default::default()
+                })
+            });
+        let es_search_api_handler = super::elastic_api_handlers(
+            config,
+            Arc::new(mock_search_service),
+            ingest_service_client(),
+            SearchQueryLimiter::default(),
+        );
+        // Grafana sends the `search_type` in the request headers.
+        let msearch_payload: String = (0..5)
+            .map(|index_idx| {
+                format!(
+                    "{{\"search_type\":\"query_then_fetch\",\"ignore_unavailable\":true,\"index\":\
+                     \"index-{index_idx}\"}}\n{{\"query\":{{\"match_all\":{{}}}}}}\n"
+                )
+            })
+            .collect();
+        let resp = warp::test::request()
+            .path("/_elastic/_msearch?max_concurrent_searches=2")
+            .method("POST")
+            .body(msearch_payload)
+            .reply(&es_search_api_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let es_msearch_response: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        let responses = es_msearch_response["responses"].as_array().unwrap();
+        assert_eq!(responses.len(), 5);
+
+        for (index_idx, response) in responses.iter().enumerate() {
+            assert_eq!(response["status"], 200);
+            assert_eq!(response["hits"]["total"]["value"], index_idx);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_msearch_api_return_400_with_zero_max_concurrent_searches() {
+        let config = Arc::new(NodeConfig::for_test());
+        let mock_search_service = MockSearchService::new();
+        let es_search_api_handler = super::elastic_api_handlers(
+            config,
+            Arc::new(mock_search_service),
+            ingest_service_client(),
+            SearchQueryLimiter::default(),
+        );
+        let msearch_payload = r#"
+            {"index":"index-1"}
+            {"query":{"query_string":{"query":"test"}}}
+            "#;
+        let resp = warp::test::request()
+            .path("/_elastic/_msearch?max_concurrent_searches=0")
+            .method("POST")
+            .body(msearch_payload)
+            .reply(&es_search_api_handler)
+            .await;
+        assert_eq!(resp.status(), 400);
+        let es_error: ElasticSearchError = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(
+            es_error.error.reason.unwrap(),
+            "Invalid argument: `max_concurrent_searches` must be strictly positive"
+        );
+    }
+
     #[tokio::test]
     async fn test_msearch_api_return_400_with_malformed_request_header() {
         let config = Arc::new(NodeConfig::for_test());
@@ -229,6 +337,7 @@ mod tests {
             config,
             Arc::new(mock_search_service),
             ingest_service_client(),
+            SearchQueryLimiter::default(),
         );
         let msearch_payload = r#"
             {"index":"index-1"
@@ -257,6 +366,7 @@ mod tests {
             config,
             Arc::new(mock_search_service),
             ingest_service_client(),
+            SearchQueryLimiter::default(),
         );
         let msearch_payload = r#"
             {"index":"index-1"}
@@ -285,6 +395,7 @@ mod tests {
             config,
             Arc::new(mock_search_service),
             ingest_service_client(),
+            SearchQueryLimiter::default(),
         );
         let msearch_payload = r#"
             {"index":"index-1"}
@@ -312,6 +423,7 @@ mod tests {
             config,
             Arc::new(mock_search_service),
             ingest_service_client(),
+            SearchQueryLimiter::default(),
         );
         let msearch_payload = r#"
             {}
@@ -352,6 +464,7 @@ mod tests {
             config,
             Arc::new(mock_search_service),
             ingest_service_client(),
+            SearchQueryLimiter::default(),
         );
         let msearch_payload = r#"
             {"index": ["index-1", "index-2"]}
@@ -366,6 +479,141 @@ mod tests {
         assert_eq!(resp.status(), 200);
     }
 
+    #[tokio::test]
+    async fn test_count_api() {
+        let config = Arc::new(NodeConfig::for_test());
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_root_count()
+            .with(predicate::function(
+                |search_request: &quickwit_proto::search::SearchRequest| {
+                    search_request.index_id_patterns
+                        == vec!["index-1".to_string(), "index-2".to_string()]
+                        && search_request.query_ast.contains("severity:ERROR")
+                },
+            ))
+            .returning(|_| {
+                Ok(quickwit_search::CountResponseRest {
+                    num_hits: 12,
+                    num_hits_per_index: None,
+                    elapsed_time_micros: 16,
+                })
+            });
+        let es_search_api_handler = elastic_api_handlers(
+            config,
+            Arc::new(mock_search_service),
+            ingest_service_client(),
+            SearchQueryLimiter::default(),
+        );
+        let resp = warp::test::request()
+            .path("/_elastic/index-1,index-2/_count?q=severity:ERROR")
+            .method("GET")
+            .reply(&es_search_api_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        let expected_response_json = serde_json::json!({
+            "count": 12,
+            "_shards": {"total": 0, "successful": 0, "skipped": 0, "failed": 0},
+        });
+        assert_json_include!(actual: resp_json, expected: expected_response_json);
+    }
+
+    #[tokio::test]
+    async fn test_field_capabilities_api() {
+        let config = Arc::new(NodeConfig::for_test());
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_root_list_fields()
+            .with(predicate::function(
+                |list_fields_request: &quickwit_search::ListFieldsRequest| {
+                    list_fields_request.index_id_patterns
+                        == vec!["index-1".to_string(), "index-2".to_string()]
+                        && list_fields_request.field_patterns
+                            == vec!["host".to_string(), "status*".to_string()]
+                },
+            ))
+            .returning(|_| {
+                Ok(quickwit_search::ListFieldsResponse {
+                    index_ids: vec!["index-1".to_string(), "index-2".to_string()],
+                    fields: vec![
+                        quickwit_search::ListFieldsEntry {
+                            field_name: "host".to_string(),
+                            field_type: quickwit_search::ListFieldType::Keyword,
+                            searchable: true,
+                            aggregatable: false,
+                            index_ids: vec!["index-1".to_string(), "index-2".to_string()],
+                            non_searchable_index_ids: Vec::new(),
+                            non_aggregatable_index_ids: vec!["index-2".to_string()],
+                        },
+                        quickwit_search::ListFieldsEntry {
+                            field_name: "status".to_string(),
+                            field_type: quickwit_search::ListFieldType::U64,
+                            searchable: true,
+                            aggregatable: true,
+                            index_ids: vec!["index-1".to_string()],
+                            non_searchable_index_ids: Vec::new(),
+                            non_aggregatable_index_ids: Vec::new(),
+                        },
+                        quickwit_search::ListFieldsEntry {
+                            field_name: "status".to_string(),
+                            field_type: quickwit_search::ListFieldType::I64,
+                            searchable: true,
+                            aggregatable: true,
+                            index_ids: vec!["index-2".to_string()],
+                            non_searchable_index_ids: Vec::new(),
+                            non_aggregatable_index_ids: Vec::new(),
+                        },
+                    ],
+                })
+            });
+        let es_search_api_handler = elastic_api_handlers(
+            config,
+            Arc::new(mock_search_service),
+            ingest_service_client(),
+            SearchQueryLimiter::default(),
+        );
+        let resp = warp::test::request()
+            .path("/_elastic/index-1,index-2/_field_caps?fields=host,status*")
+            .method("GET")
+            .reply(&es_search_api_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        let expected_response_json = serde_json::json!({
+            "indices": ["index-1", "index-2"],
+            "fields": {
+                "host": {
+                    "keyword": {
+                        "type": "keyword",
+                        "metadata_field": false,
+                        "searchable": true,
+                        "aggregatable": false,
+                        "non_aggregatable_indices": ["index-2"],
+                    }
+                },
+                "status": {
+                    "unsigned_long": {
+                        "type": "unsigned_long",
+                        "searchable": true,
+                        "aggregatable": true,
+                        "indices": ["index-1"],
+                    },
+                    "long": {
+                        "type": "long",
+                        "searchable": true,
+                        "aggregatable": true,
+                        "indices": ["index-2"],
+                    }
+                }
+            }
+        });
+        assert_json_include!(actual: resp_json, expected: expected_response_json);
+        assert!(resp_json["fields"]["host"]["keyword"]
+            .get("indices")
+            .is_none());
+    }
+
     #[tokio::test]
     async fn test_es_compat_cluster_info_handler() {
         let build_info = BuildInfo::get();
@@ -27,10 +27,10 @@ pub enum BulkAction {
 }
 
 impl BulkAction {
-    pub fn into_index(self) -> Option<String> {
+    pub fn into_meta(self) -> BulkActionMeta {
         match self {
-            BulkAction::Index(meta) => meta.index_id,
-            BulkAction::Create(meta) => meta.index_id,
+            BulkAction::Index(meta) => meta,
+            BulkAction::Create(meta) => meta,
         }
     }
 }
@@ -0,0 +1,78 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use hyper::StatusCode;
+use serde::{Deserialize, Serialize};
+
+/// Response of the bulk API, following the format of Elasticsearch.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ElasticBulkResponse {
+    /// Time spent processing the request, in milliseconds.
+    pub took: u64,
+    /// Whether at least one of the actions failed.
+    pub errors: bool,
+    /// Outcome of each action, in the order of the request.
+    pub items: Vec<ElasticBulkItem>,
+}
+
+/// Outcome of an action of a bulk request, keyed by the name of the action.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ElasticBulkItem {
+    Index(ElasticBulkItemOutcome),
+    Create(ElasticBulkItemOutcome),
+}
+
+impl ElasticBulkItem {
+    pub fn outcome(&self) -> &ElasticBulkItemOutcome {
+        match self {
+            ElasticBulkItem::Index(outcome) => outcome,
+            ElasticBulkItem::Create(outcome) => outcome,
+        }
+    }
+
+    pub fn outcome_mut(&mut self) -> &mut ElasticBulkItemOutcome {
+        match self {
+            ElasticBulkItem::Index(outcome) => outcome,
+            ElasticBulkItem::Create(outcome) => outcome,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ElasticBulkItemOutcome {
+    #[serde(rename = "_index")]
+    pub index_id: String,
+    #[serde(rename = "_id")]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub doc_id: Option<String>,
+    #[serde(with = "http_serde::status_code")]
+    pub status: StatusCode,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ElasticBulkItemError>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ElasticBulkItemError {
+    #[serde(rename = "type")]
+    pub error_type: String,
+    pub reason: String,
+}
@@ -0,0 +1,249 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use hyper::StatusCode;
+use serde::Deserialize;
+use serde_json::{Map as JsonMap, Value as JsonValue};
+
+use super::ElasticSearchError;
+
+/// Output format of the `_cat` endpoints.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CatFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+/// Query parameters of the `_cat` endpoints.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CatQueryParams {
+    /// Comma-separated list of the columns to display.
+    #[serde(default)]
+    pub h: Option<String>,
+    /// Output format, plain text columns by default.
+    #[serde(default)]
+    pub format: Option<CatFormat>,
+    /// Prints the column headers if present, unless set to `false`.
+    #[serde(default)]
+    pub v: Option<String>,
+}
+
+impl CatQueryParams {
+    fn verbose(&self) -> bool {
+        self.v
+            .as_deref()
+            .map_or(false, |verbose| verbose != "false")
+    }
+}
+
+/// Rows of a `_cat` endpoint response.
+#[derive(Debug)]
+pub struct CatTable {
+    columns: &'static [&'static str],
+    rows: Vec<Vec<String>>,
+}
+
+/// Body of a `_cat` endpoint response.
+#[derive(Debug, Eq, PartialEq)]
+pub enum CatResponse {
+    Text(String),
+    Json(JsonValue),
+}
+
+impl CatTable {
+    pub fn new(columns: &'static [&'static str]) -> Self {
+        CatTable {
+            columns,
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn push_row(&mut self, row: Vec<String>) {
+        debug_assert_eq!(row.len(), self.columns.len());
+        self.rows.push(row);
+    }
+
+    /// Renders the columns requested by the query parameters.
+    pub fn render(self, params: &CatQueryParams) -> Result<CatResponse, ElasticSearchError> {
+        let column_ords: Vec<usize> = if let Some(column_names) = &params.h {
+            column_names
+                .split(',')
+                .map(|column_name| {
+                    let column_name = column_name.trim();
+                    self.columns
+                        .iter()
+                        .position(|column| *column == column_name)
+                        .ok_or_else(|| {
+                            ElasticSearchError::new(
+                                StatusCode::BAD_REQUEST,
+                                format!("unknown column `{column_name}`"),
+                            )
+                        })
+                })
+                .collect::<Result<_, _>>()?
+        } else {
+            (0..self.columns.len()).collect()
+        };
+        let response = match params.format.unwrap_or_default() {
+            CatFormat::Text => CatResponse::Text(self.render_text(&column_ords, params.verbose())),
+            CatFormat::Json => CatResponse::Json(self.render_json(&column_ords)),
+        };
+        Ok(response)
+    }
+
+    fn render_text(&self, column_ords: &[usize], verbose: bool) -> String {
+        let header: Vec<&str> = column_ords.iter().map(|&ord| self.columns[ord]).collect();
+        let lines: Vec<Vec<&str>> = verbose
+            .then(|| header.clone())
+            .into_iter()
+            .chain(
+                self.rows
+                    .iter()
+                    .map(|row| column_ords.iter().map(|&ord| row[ord].as_str()).collect()),
+            )
+            .collect();
+        let column_widths: Vec<usize> = (0..column_ords.len())
+            .map(|i| lines.iter().map(|line| line[i].len()).max().unwrap_or(0))
+            .collect();
+        let mut text = String::new();
+        for line in lines {
+            let padded_cells: Vec<String> = line
+                .iter()
+                .zip(&column_widths)
+                .map(|(cell, width)| format!("{cell:width$}"))
+                .collect();
+            text.push_str(padded_cells.join(" ").trim_end());
+            text.push('\n');
+        }
+        text
+    }
+
+    fn render_json(&self, column_ords: &[usize]) -> JsonValue {
+        let json_rows = self
+            .rows
+            .iter()
+            .map(|row| {
+                let json_row: JsonMap<String, JsonValue> = column_ords
+                    .iter()
+                    .map(|&ord| {
+                        (
+                            self.columns[ord].to_string(),
+                            JsonValue::String(row[ord].clone()),
+                        )
+                    })
+                    .collect();
+                JsonValue::Object(json_row)
+            })
+            .collect();
+        JsonValue::Array(json_rows)
+    }
+}
+
+/// Formats a number of bytes the way Elasticsearch does, for instance `1.5kb`.
+pub fn format_num_bytes(num_bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["kb", "mb", "gb", "tb", "pb"];
+
+    if num_bytes < 1024 {
+        return format!("{num_bytes}b");
+    }
+    let mut value = num_bytes as f64 / 1024.0;
+    let mut unit_ord = 0;
+    while value >= 1024.0 && unit_ord + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit_ord += 1;
+    }
+    let formatted_value = format!("{value:.1}");
+    let formatted_value = formatted_value
+        .strip_suffix(".0")
+        .unwrap_or(&formatted_value);
+    format!("{formatted_value}{}", UNITS[unit_ord])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_table() -> CatTable {
+        let mut table = CatTable::new(&["index", "docs.count"]);
+        table.push_row(vec!["my-index".to_string(), "10".to_string()]);
+        table.push_row(vec!["other-index-long".to_string(), "1000".to_string()]);
+        table
+    }
+
+    #[test]
+    fn test_cat_table_render_text() {
+        let params = CatQueryParams::default();
+        let CatResponse::Text(text) = test_table().render(&params).unwrap() else {
+            panic!("expected a text response");
+        };
+        assert_eq!(text, "my-index         10\nother-index-long 1000\n");
+
+        let params = CatQueryParams {
+            v: Some("".to_string()),
+            h: Some("docs.count,index".to_string()),
+            ..Default::default()
+        };
+        let CatResponse::Text(text) = test_table().render(&params).unwrap() else {
+            panic!("expected a text response");
+        };
+        assert_eq!(
+            text,
+            "docs.count index\n10         my-index\n1000       other-index-long\n"
+        );
+    }
+
+    #[test]
+    fn test_cat_table_render_json() {
+        let params = CatQueryParams {
+            format: Some(CatFormat::Json),
+            h: Some("index".to_string()),
+            ..Default::default()
+        };
+        let response = test_table().render(&params).unwrap();
+        assert_eq!(
+            response,
+            CatResponse::Json(serde_json::json!([
+                {"index": "my-index"},
+                {"index": "other-index-long"},
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_cat_table_render_unknown_column() {
+        let params = CatQueryParams {
+            h: Some("index,foo".to_string()),
+            ..Default::default()
+        };
+        let error = test_table().render(&params).unwrap_err();
+        assert_eq!(error.status, StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn test_format_num_bytes() {
+        assert_eq!(format_num_bytes(0), "0b");
+        assert_eq!(format_num_bytes(1023), "1023b");
+        assert_eq!(format_num_bytes(1024), "1kb");
+        assert_eq!(format_num_bytes(1536), "1.5kb");
+        assert_eq!(format_num_bytes(5 * 1024 * 1024 * 1024), "5gb");
+    }
+}
@@ -20,6 +20,7 @@
 use elasticsearch_dsl::search::ErrorCause;
 use hyper::StatusCode;
 use quickwit_ingest::IngestServiceError;
+use quickwit_proto::metastore::MetastoreError;
 use quickwit_proto::ServiceError;
 use quickwit_search::SearchError;
 use serde::{Deserialize, Serialize};
@@ -87,3 +88,10 @@ impl From<IngestServiceError> for ElasticSearchError {
         }
     }
 }
+
+impl From<MetastoreError> for ElasticSearchError {
+    fn from(metastore_error: MetastoreError) -> Self {
+        let status = metastore_error.error_code().to_http_status_code();
+        ElasticSearchError::new(status, metastore_error.to_string())
+    }
+}
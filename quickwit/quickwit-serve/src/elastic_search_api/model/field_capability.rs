@@ -0,0 +1,143 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+
+use quickwit_search::{ListFieldType, ListFieldsEntry, ListFieldsResponse};
+use serde::{Deserialize, Serialize};
+
+use super::search_query_params::ExpandWildcards;
+use crate::simple_list::{from_simple_list, to_simple_list};
+
+#[serde_with::skip_serializing_none]
+#[derive(Default, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FieldCapabilityQueryParams {
+    #[serde(default)]
+    pub allow_no_indices: Option<bool>,
+    #[serde(serialize_with = "to_simple_list")]
+    #[serde(deserialize_with = "from_simple_list")]
+    #[serde(default)]
+    pub expand_wildcards: Option<Vec<ExpandWildcards>>,
+    #[serde(serialize_with = "to_simple_list")]
+    #[serde(deserialize_with = "from_simple_list")]
+    #[serde(default)]
+    pub fields: Option<Vec<String>>,
+    #[serde(default)]
+    pub ignore_unavailable: Option<bool>,
+    #[serde(default)]
+    pub include_unmapped: Option<bool>,
+}
+
+/// Response of the `_field_caps` endpoint.
+#[derive(Serialize, Debug, Default)]
+pub struct FieldCapabilityResponse {
+    pub indices: Vec<String>,
+    /// Capabilities of the fields, keyed by field name, then by Elasticsearch field type.
+    pub fields: BTreeMap<String, BTreeMap<&'static str, FieldCapabilityEntry>>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct FieldCapabilityEntry {
+    #[serde(rename = "type")]
+    pub field_type: &'static str,
+    pub metadata_field: bool,
+    pub searchable: bool,
+    pub aggregatable: bool,
+    /// Indexes containing the field with this type. Only set when the field has several types
+    /// across the indexes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub indices: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub non_searchable_indices: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub non_aggregatable_indices: Option<Vec<String>>,
+}
+
+fn elastic_field_type(list_field_type: ListFieldType) -> &'static str {
+    match list_field_type {
+        ListFieldType::Text => "text",
+        ListFieldType::Keyword => "keyword",
+        ListFieldType::U64 => "unsigned_long",
+        ListFieldType::I64 => "long",
+        ListFieldType::F64 => "double",
+        ListFieldType::Bool => "boolean",
+        ListFieldType::Datetime => "date",
+        ListFieldType::Bytes => "binary",
+        ListFieldType::Ip => "ip",
+        ListFieldType::Json => "object",
+    }
+}
+
+/// Elasticsearch only lists the indexes in which a field is not searchable or not aggregatable
+/// when the capability differs across the indexes containing the field.
+fn non_uniform_indices(index_ids: Vec<String>, num_indices: usize) -> Option<Vec<String>> {
+    if index_ids.is_empty() || index_ids.len() == num_indices {
+        None
+    } else {
+        Some(index_ids)
+    }
+}
+
+pub(crate) fn convert_to_es_field_capabilities_response(
+    list_fields_response: ListFieldsResponse,
+) -> FieldCapabilityResponse {
+    let mut num_types_per_field: BTreeMap<String, usize> = BTreeMap::new();
+
+    for list_fields_entry in &list_fields_response.fields {
+        *num_types_per_field
+            .entry(list_fields_entry.field_name.clone())
+            .or_default() += 1;
+    }
+    let mut fields: BTreeMap<String, BTreeMap<&'static str, FieldCapabilityEntry>> =
+        BTreeMap::new();
+
+    for list_fields_entry in list_fields_response.fields {
+        let ListFieldsEntry {
+            field_name,
+            field_type,
+            searchable,
+            aggregatable,
+            index_ids,
+            non_searchable_index_ids,
+            non_aggregatable_index_ids,
+        } = list_fields_entry;
+        let has_several_types = num_types_per_field[&field_name] > 1;
+        let field_type = elastic_field_type(field_type);
+        let num_indices = index_ids.len();
+        let field_capability_entry = FieldCapabilityEntry {
+            field_type,
+            metadata_field: false,
+            searchable,
+            aggregatable,
+            indices: has_several_types.then_some(index_ids),
+            non_searchable_indices: non_uniform_indices(non_searchable_index_ids, num_indices),
+            non_aggregatable_indices: non_uniform_indices(non_aggregatable_index_ids, num_indices),
+        };
+        // Two Quickwit types, e.g. `u64` and `i64`, never map to the same Elasticsearch type.
+        fields
+            .entry(field_name)
+            .or_default()
+            .insert(field_type, field_capability_entry);
+    }
+    FieldCapabilityResponse {
+        indices: list_fields_response.index_ids,
+        fields,
+    }
+}
@@ -0,0 +1,163 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use quickwit_proto::search::SnippetOptions;
+use serde::{Deserialize, Deserializer};
+use serde_json::{Map as JsonMap, Value as JsonValue};
+
+const DEFAULT_PRE_TAG: &str = "<em>";
+const DEFAULT_POST_TAG: &str = "</em>";
+const DEFAULT_FRAGMENT_SIZE: u32 = 100;
+
+/// The `highlight` section of an Elasticsearch search request.
+///
+/// Highlighting is mapped onto Quickwit's snippet generation. Only the request-level
+/// `pre_tags`, `post_tags` and `fragment_size` options are supported: field-level options
+/// and any other setting are ignored.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+pub struct Highlight {
+    #[serde(default)]
+    pub pre_tags: Vec<String>,
+    #[serde(default)]
+    pub post_tags: Vec<String>,
+    #[serde(default)]
+    pub fragment_size: Option<u32>,
+    #[serde(default)]
+    #[serde(deserialize_with = "deserialize_highlight_fields")]
+    pub fields: Vec<String>,
+}
+
+impl Highlight {
+    /// Returns the fields to highlight. Wildcard patterns, such as the `*` sent by Kibana, are
+    /// skipped because they cannot be resolved without the doc mapping.
+    pub fn snippet_fields(&self) -> Vec<String> {
+        self.fields
+            .iter()
+            .filter(|field_name| !field_name.contains('*'))
+            .cloned()
+            .collect()
+    }
+
+    /// Builds the snippet options, falling back to Elasticsearch's defaults. Like Elasticsearch,
+    /// only the first pre and post tags are used.
+    pub fn snippet_options(&self) -> SnippetOptions {
+        let pre_tag = self
+            .pre_tags
+            .first()
+            .cloned()
+            .unwrap_or_else(|| DEFAULT_PRE_TAG.to_string());
+        let post_tag = self
+            .post_tags
+            .first()
+            .cloned()
+            .unwrap_or_else(|| DEFAULT_POST_TAG.to_string());
+        let max_num_chars = self.fragment_size.unwrap_or(DEFAULT_FRAGMENT_SIZE);
+        SnippetOptions {
+            pre_tag: Some(pre_tag),
+            post_tag: Some(post_tag),
+            max_num_chars: Some(max_num_chars),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum HighlightFieldForDeser {
+    Name(String),
+    Object(JsonMap<String, JsonValue>),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum HighlightFieldsForDeser {
+    Object(JsonMap<String, JsonValue>),
+    Array(Vec<HighlightFieldForDeser>),
+}
+
+/// ES accepts the highlighted fields either as an object keyed by field name, or as an array of
+/// field names or single-key objects.
+fn deserialize_highlight_fields<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where D: Deserializer<'de> {
+    let field_names = match HighlightFieldsForDeser::deserialize(deserializer)? {
+        HighlightFieldsForDeser::Object(fields) => {
+            fields.into_iter().map(|(name, _)| name).collect()
+        }
+        HighlightFieldsForDeser::Array(fields) => fields
+            .into_iter()
+            .flat_map(|field| match field {
+                HighlightFieldForDeser::Name(name) => vec![name],
+                HighlightFieldForDeser::Object(field) => {
+                    field.into_iter().map(|(name, _)| name).collect()
+                }
+            })
+            .collect(),
+    };
+    Ok(field_names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highlight_deser_fields_object() {
+        let json = r#"
+        {
+            "pre_tags": ["<mark>"],
+            "post_tags": ["</mark>"],
+            "fragment_size": 50,
+            "number_of_fragments": 3,
+            "fields": {
+                "title": {},
+                "body": { "fragment_size": 20 },
+                "*": {}
+            }
+        }
+        "#;
+        let highlight: Highlight = serde_json::from_str(json).unwrap();
+        assert_eq!(highlight.fields.len(), 3);
+
+        let mut snippet_fields = highlight.snippet_fields();
+        snippet_fields.sort();
+        assert_eq!(snippet_fields, ["body", "title"]);
+        assert_eq!(
+            highlight.snippet_options(),
+            SnippetOptions {
+                pre_tag: Some("<mark>".to_string()),
+                post_tag: Some("</mark>".to_string()),
+                max_num_chars: Some(50),
+            }
+        );
+    }
+
+    #[test]
+    fn test_highlight_deser_fields_array() {
+        let json = r#"{ "fields": ["title", { "body": {} }] }"#;
+        let highlight: Highlight = serde_json::from_str(json).unwrap();
+        assert_eq!(highlight.snippet_fields(), ["title", "body"]);
+        assert_eq!(
+            highlight.snippet_options(),
+            SnippetOptions {
+                pre_tag: Some("<em>".to_string()),
+                post_tag: Some("</em>".to_string()),
+                max_num_chars: Some(100),
+            }
+        );
+    }
+}
@@ -19,7 +19,12 @@
 
 mod bulk_body;
 mod bulk_query_params;
+mod bulk_response;
+mod cat;
+mod count;
 mod error;
+mod field_capability;
+mod highlight;
 mod multi_search;
 mod scroll;
 mod search_body;
@@ -27,7 +32,17 @@ mod search_query_params;
 
 pub use bulk_body::{BulkAction, BulkActionMeta};
 pub use bulk_query_params::{ElasticIngestOptions, ElasticRefresh};
+pub use bulk_response::{
+    ElasticBulkItem, ElasticBulkItemError, ElasticBulkItemOutcome, ElasticBulkResponse,
+};
+pub use cat::{format_num_bytes, CatQueryParams, CatResponse, CatTable};
+pub use count::{CountResponse, ShardStatistics};
 pub use error::ElasticSearchError;
+pub(crate) use field_capability::convert_to_es_field_capabilities_response;
+pub use field_capability::{
+    FieldCapabilityEntry, FieldCapabilityQueryParams, FieldCapabilityResponse,
+};
+pub use highlight::Highlight;
 pub use multi_search::{
     MultiSearchHeader, MultiSearchQueryParams, MultiSearchResponse, MultiSearchSingleResponse,
 };
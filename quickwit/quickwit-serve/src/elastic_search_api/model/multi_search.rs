@@ -82,6 +82,10 @@ pub struct MultiSearchHeader {
     pub request_cache: Option<bool>,
     #[serde(default)]
     pub routing: Option<Vec<String>>,
+    // Sent by clients such as Grafana. Quickwit does not distribute term frequencies before
+    // searching, so the search type has no effect.
+    #[serde(default)]
+    pub search_type: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
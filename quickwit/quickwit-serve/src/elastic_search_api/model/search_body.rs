@@ -25,7 +25,7 @@ use quickwit_query::{ElasticQueryDsl, OneFieldMap};
 use serde::de::{MapAccess, Visitor};
 use serde::{Deserialize, Deserializer, Serialize};
 
-use super::ElasticDateFormat;
+use super::{ElasticDateFormat, Highlight};
 use crate::elastic_search_api::model::{default_elasticsearch_sort_order, SortField};
 use crate::elastic_search_api::TrackTotalHits;
 
@@ -86,6 +86,8 @@ pub struct SearchBody {
     pub stored_fields: Option<BTreeSet<String>>,
     #[serde(default)]
     pub search_after: Vec<serde_json::Value>,
+    #[serde(default)]
+    pub highlight: Option<Highlight>,
 }
 
 struct FieldSortVecVisitor;
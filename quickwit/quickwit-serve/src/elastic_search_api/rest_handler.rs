@@ -31,26 +31,31 @@ use itertools::Itertools;
 use quickwit_common::truncate_str;
 use quickwit_config::{validate_index_id_pattern, NodeConfig};
 use quickwit_proto::search::{
-    CountHits, PartialHit, ScrollRequest, SearchResponse, SortByValue, SortDatetimeFormat,
+    AggregationFormat, CountHits, Federation, PartialHit, ScrollRequest, SearchResponse,
+    SortByValue, SortDatetimeFormat,
 };
 use quickwit_proto::ServiceErrorCode;
 use quickwit_query::query_ast::{QueryAst, UserInputQuery};
 use quickwit_query::BooleanOperand;
-use quickwit_search::{SearchError, SearchService};
+use quickwit_search::{ListFieldsRequest, SearchError, SearchService};
 use serde_json::json;
 use warp::{Filter, Rejection};
 
 use super::filter::{
-    elastic_cluster_info_filter, elastic_index_search_filter, elastic_multi_search_filter,
-    elastic_scroll_filter, elastic_search_filter,
+    elastic_cluster_info_filter, elastic_field_capabilities_filter, elastic_index_count_filter,
+    elastic_index_field_capabilities_filter, elastic_index_search_filter,
+    elastic_multi_search_filter, elastic_scroll_filter, elastic_search_filter,
 };
 use super::model::{
-    ElasticSearchError, MultiSearchHeader, MultiSearchQueryParams, MultiSearchResponse,
-    MultiSearchSingleResponse, ScrollQueryParams, SearchBody, SearchQueryParams,
+    convert_to_es_field_capabilities_response, CountResponse, ElasticSearchError,
+    FieldCapabilityQueryParams, FieldCapabilityResponse, MultiSearchHeader, MultiSearchQueryParams,
+    MultiSearchResponse, MultiSearchSingleResponse, ScrollQueryParams, SearchBody,
+    SearchQueryParams, ShardStatistics,
 };
 use super::{make_elastic_api_response, TrackTotalHits};
 use crate::format::BodyFormat;
 use crate::json_api_response::{make_json_api_response, ApiError, JsonApiResponse};
+use crate::search_query_limiter::{search_permit_filter, SearchPermit, SearchQueryLimiter};
 use crate::{with_arg, BuildInfo};
 
 /// Elastic compatible cluster info handler.
@@ -83,12 +88,12 @@ pub fn es_compat_search_handler(
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
     elastic_search_filter().then(|_params: SearchQueryParams| async move {
         // TODO
-        let api_error = ApiError {
-            service_code: ServiceErrorCode::NotSupportedYet,
-            message: "_elastic/_search is not supported yet. Please try the index search endpoint \
-                      (_elastic/{index}/search)"
+        let api_error = ApiError::new(
+            ServiceErrorCode::NotSupportedYet,
+            "_elastic/_search is not supported yet. Please try the index search endpoint \
+             (_elastic/{index}/search)"
                 .to_string(),
-        };
+        );
         make_json_api_response::<(), _>(Err(api_error), BodyFormat::default())
     })
 }
@@ -96,29 +101,71 @@ pub fn es_compat_search_handler(
 /// GET or POST _elastic/{index}/_search
 pub fn es_compat_index_search_handler(
     search_service: Arc<dyn SearchService>,
+    search_query_limiter: SearchQueryLimiter,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
     elastic_index_search_filter()
         .and(with_arg(search_service))
+        .and(search_permit_filter(search_query_limiter))
         .then(es_compat_index_search)
         .map(|result| make_elastic_api_response(result, BodyFormat::default()))
 }
 
+/// GET or POST _elastic/{index}/_count
+pub fn es_compat_index_count_handler(
+    search_service: Arc<dyn SearchService>,
+    search_query_limiter: SearchQueryLimiter,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    elastic_index_count_filter()
+        .and(with_arg(search_service))
+        .and(search_permit_filter(search_query_limiter))
+        .then(es_compat_index_count)
+        .map(|result| make_elastic_api_response(result, BodyFormat::default()))
+}
+
+/// GET or POST _elastic/_field_caps
+pub fn es_compat_field_capabilities_handler(
+    search_service: Arc<dyn SearchService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    elastic_field_capabilities_filter()
+        .map(|field_capability_params| (vec!["*".to_string()], field_capability_params))
+        .untuple_one()
+        .and(with_arg(search_service))
+        .then(es_compat_index_field_capabilities)
+        .map(|result| make_elastic_api_response(result, BodyFormat::default()))
+}
+
+/// GET or POST _elastic/{index}/_field_caps
+pub fn es_compat_index_field_capabilities_handler(
+    search_service: Arc<dyn SearchService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    elastic_index_field_capabilities_filter()
+        .and(with_arg(search_service))
+        .then(es_compat_index_field_capabilities)
+        .map(|result| make_elastic_api_response(result, BodyFormat::default()))
+}
+
 /// GET or POST _elastic/_search/scroll
 pub fn es_compat_scroll_handler(
     search_service: Arc<dyn SearchService>,
+    search_query_limiter: SearchQueryLimiter,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
     elastic_scroll_filter()
         .and(with_arg(search_service))
+        .and(search_permit_filter(search_query_limiter))
         .then(es_scroll)
         .map(|result| make_elastic_api_response(result, BodyFormat::default()))
 }
 
-/// POST _elastic/_search
+/// POST _elastic/_msearch
 pub fn es_compat_index_multi_search_handler(
     search_service: Arc<dyn SearchService>,
+    search_query_limiter: SearchQueryLimiter,
+    max_num_concurrent_multi_searches: usize,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
     elastic_multi_search_filter()
         .and(with_arg(search_service))
+        .and(search_permit_filter(search_query_limiter))
+        .and(with_arg(max_num_concurrent_multi_searches))
         .then(es_compat_index_multi_search)
         .map(|result: Result<MultiSearchResponse, ElasticSearchError>| {
             let status_code = match &result {
@@ -191,6 +238,14 @@ fn build_request_for_es_api(
     let scroll_duration: Option<Duration> = search_params.parse_scroll_ttl()?;
     let scroll_ttl_secs: Option<u32> = scroll_duration.map(|duration| duration.as_secs() as u32);
 
+    let (snippet_fields, snippet_options) = match &search_body.highlight {
+        Some(highlight) => {
+            let snippet_fields = highlight.snippet_fields();
+            let snippet_options = (!snippet_fields.is_empty()).then(|| highlight.snippet_options());
+            (snippet_fields, snippet_options)
+        }
+        None => (Vec::new(), None),
+    };
     let has_doc_id_field = sort_fields.iter().any(is_doc_field);
     let search_after = partial_hit_from_search_after_param(search_body.search_after, &sort_fields)?;
 
@@ -204,10 +259,20 @@ fn build_request_for_es_api(
             sort_fields,
             start_timestamp: None,
             end_timestamp: None,
-            snippet_fields: Vec::new(),
+            snippet_fields,
             scroll_ttl_secs,
             search_after,
             count_hits,
+            aggregation_format: AggregationFormat::Json.into(),
+            dedup_field: None,
+            dedup_count: None,
+            snippet_options,
+            max_hits_per_index: None,
+            federation: Federation::Global.into(),
+            preview: false,
+            group_by: None,
+            split_tags: Vec::new(),
+            allow_partial_results: search_params.allow_partial_search_results.unwrap_or(true),
         },
         has_doc_id_field,
     ))
@@ -276,6 +341,7 @@ async fn es_compat_index_search(
     search_params: SearchQueryParams,
     search_body: SearchBody,
     search_service: Arc<dyn SearchService>,
+    _search_permit: SearchPermit,
 ) -> Result<ElasticSearchResponse, ElasticSearchError> {
     let start_instant = Instant::now();
     let (search_request, append_shard_doc) =
@@ -288,9 +354,46 @@ async fn es_compat_index_search(
     Ok(search_response_rest)
 }
 
+async fn es_compat_index_count(
+    index_id_patterns: Vec<String>,
+    search_params: SearchQueryParams,
+    search_body: SearchBody,
+    search_service: Arc<dyn SearchService>,
+    _search_permit: SearchPermit,
+) -> Result<CountResponse, ElasticSearchError> {
+    let (search_request, _append_shard_doc) =
+        build_request_for_es_api(index_id_patterns, search_params, search_body)?;
+    // The root rewrites the request so that only the hits are counted.
+    let count_response = search_service.root_count(search_request).await?;
+    Ok(CountResponse {
+        count: count_response.num_hits,
+        shards: ShardStatistics::default(),
+    })
+}
+
+async fn es_compat_index_field_capabilities(
+    index_id_patterns: Vec<String>,
+    field_capability_params: FieldCapabilityQueryParams,
+    search_service: Arc<dyn SearchService>,
+) -> Result<FieldCapabilityResponse, ElasticSearchError> {
+    let list_fields_request = ListFieldsRequest {
+        index_id_patterns,
+        field_patterns: field_capability_params.fields.unwrap_or_default(),
+    };
+    let list_fields_response = search_service.root_list_fields(list_fields_request).await?;
+    Ok(convert_to_es_field_capabilities_response(
+        list_fields_response,
+    ))
+}
+
 fn convert_hit(hit: quickwit_proto::search::Hit, append_shard_doc: bool) -> ElasticHit {
     let fields: BTreeMap<String, serde_json::Value> =
         serde_json::from_str(&hit.json).unwrap_or_default();
+    let highlight = hit
+        .snippet
+        .as_deref()
+        .map(convert_snippet_to_highlight)
+        .unwrap_or_default();
     let mut sort = Vec::new();
     if let Some(partial_hit) = hit.partial_hit {
         if let Some(sort_value) = partial_hit.sort_value {
@@ -315,18 +418,43 @@ fn convert_hit(hit: quickwit_proto::search::Hit, append_shard_doc: bool) -> Elas
         nested: None,
         source: Source::from_string(hit.json)
             .unwrap_or_else(|_| Source::from_string("{}".to_string()).unwrap()),
-        highlight: Default::default(),
+        highlight,
         inner_hits: Default::default(),
         matched_queries: Vec::default(),
         sort,
     }
 }
 
+/// Converts the snippets of a hit into an Elasticsearch `highlight` object. Fields without any
+/// matching fragment are omitted, as Elasticsearch does.
+fn convert_snippet_to_highlight(snippet_json: &str) -> BTreeMap<String, Vec<String>> {
+    let snippets: BTreeMap<String, Vec<String>> =
+        serde_json::from_str(snippet_json).unwrap_or_default();
+    snippets
+        .into_iter()
+        .filter(|(_, fragments)| !fragments.is_empty())
+        .collect()
+}
+
 async fn es_compat_index_multi_search(
     payload: Bytes,
     multi_search_params: MultiSearchQueryParams,
     search_service: Arc<dyn SearchService>,
+    _search_permit: SearchPermit,
+    max_num_concurrent_multi_searches: usize,
 ) -> Result<MultiSearchResponse, ElasticSearchError> {
+    // The node config caps the concurrency that requests can ask for.
+    let max_concurrent_searches = match multi_search_params.max_concurrent_searches {
+        Some(0) => {
+            return Err(ElasticSearchError::from(SearchError::InvalidArgument(
+                "`max_concurrent_searches` must be strictly positive".to_string(),
+            )));
+        }
+        Some(max_concurrent_searches) => {
+            (max_concurrent_searches as usize).min(max_num_concurrent_multi_searches)
+        }
+        None => max_num_concurrent_multi_searches,
+    };
     let mut search_requests = Vec::new();
     let str_payload = from_utf8(&payload)
         .map_err(|err| SearchError::InvalidQuery(format!("invalid UTF-8: {}", err)))?;
@@ -390,10 +518,9 @@ async fn es_compat_index_multi_search(
                 Ok::<_, ElasticSearchError>(search_response_rest)
             }
         });
-    let max_concurrent_searches =
-        multi_search_params.max_concurrent_searches.unwrap_or(10) as usize;
+    // Responses must be returned in the order of the requests.
     let search_responses = futures::stream::iter(futures)
-        .buffer_unordered(max_concurrent_searches)
+        .buffered(max_concurrent_searches)
         .collect::<Vec<_>>()
         .await;
     let responses = search_responses
@@ -410,6 +537,7 @@ async fn es_compat_index_multi_search(
 async fn es_scroll(
     scroll_query_params: ScrollQueryParams,
     search_service: Arc<dyn SearchService>,
+    _search_permit: SearchPermit,
 ) -> Result<ElasticSearchResponse, ElasticSearchError> {
     let start_instant = Instant::now();
     let Some(scroll_id) = scroll_query_params.scroll_id.clone() else {
@@ -448,7 +576,7 @@ fn convert_to_es_search_response(
     } else {
         None
     };
-    ElasticSearchResponse {
+    let mut es_search_response = ElasticSearchResponse {
         timed_out: false,
         hits: HitsMetadata {
             total: Some(TotalHits {
@@ -461,7 +589,10 @@ fn convert_to_es_search_response(
         aggregations,
         scroll_id: resp.scroll_id,
         ..Default::default()
-    }
+    };
+    // Splits are reported as shards.
+    es_search_response.shards.failed = resp.failed_splits.len() as _;
+    es_search_response
 }
 
 pub(crate) fn str_lines(body: &str) -> impl Iterator<Item = &str> {
@@ -473,8 +604,57 @@ pub(crate) fn str_lines(body: &str) -> impl Iterator<Item = &str> {
 #[cfg(test)]
 mod tests {
     use hyper::StatusCode;
+    use quickwit_proto::search::SnippetOptions;
 
-    use super::partial_hit_from_search_after_param;
+    use super::{
+        build_request_for_es_api, convert_snippet_to_highlight, partial_hit_from_search_after_param,
+    };
+    use crate::elastic_search_api::model::{SearchBody, SearchQueryParams};
+
+    #[test]
+    fn test_build_request_for_es_api_with_highlight() {
+        let search_body: SearchBody = serde_json::from_str(
+            r#"{
+                "highlight": { "pre_tags": ["<mark>"], "post_tags": ["</mark>"], "fields": { "body": {} } }
+            }"#,
+        )
+        .unwrap();
+        let (search_request, _) = build_request_for_es_api(
+            vec!["my-index".to_string()],
+            SearchQueryParams::default(),
+            search_body,
+        )
+        .unwrap();
+        assert_eq!(search_request.snippet_fields, ["body"]);
+        assert_eq!(
+            search_request.snippet_options,
+            Some(SnippetOptions {
+                pre_tag: Some("<mark>".to_string()),
+                post_tag: Some("</mark>".to_string()),
+                max_num_chars: Some(100),
+            })
+        );
+
+        let search_body: SearchBody =
+            serde_json::from_str(r#"{ "highlight": { "fields": { "*": {} } } }"#).unwrap();
+        let (search_request, _) = build_request_for_es_api(
+            vec!["my-index".to_string()],
+            SearchQueryParams::default(),
+            search_body,
+        )
+        .unwrap();
+        assert!(search_request.snippet_fields.is_empty());
+        assert!(search_request.snippet_options.is_none());
+    }
+
+    #[test]
+    fn test_convert_snippet_to_highlight() {
+        let highlight = convert_snippet_to_highlight(
+            r#"{"title": [], "body": ["The <em>beagle</em> is a breed"]}"#,
+        );
+        assert_eq!(highlight.len(), 1);
+        assert_eq!(highlight["body"], ["The <em>beagle</em> is a breed"]);
+    }
 
     #[test]
     fn test_partial_hit_from_search_after_param_invalid_length() {
@@ -28,6 +28,9 @@ pub enum BodyFormat {
     Json,
     #[default]
     PrettyJson,
+    /// Arrow IPC stream. Only the aggregation results of the search endpoint are encoded in
+    /// Arrow, all other responses, including errors, are rendered as compact JSON.
+    Arrow,
 }
 
 impl BodyFormat {
@@ -43,7 +46,7 @@ impl BodyFormat {
 
     fn value_to_vec(&self, value: &impl serde::Serialize) -> Result<Vec<u8>, ()> {
         match &self {
-            Self::Json => serde_json::to_vec(value).map_err(|_| {
+            Self::Json | Self::Arrow => serde_json::to_vec(value).map_err(|_| {
                 tracing::error!("the response serialization failed");
             }),
             Self::PrettyJson => serde_json::to_vec_pretty(value).map_err(|_| {
@@ -58,6 +61,7 @@ impl ToString for BodyFormat {
         match &self {
             Self::Json => "json".to_string(),
             Self::PrettyJson => "pretty_json".to_string(),
+            Self::Arrow => "arrow".to_string(),
         }
     }
 }
@@ -21,7 +21,9 @@ use std::collections::BTreeSet;
 use std::net::SocketAddr;
 use std::sync::Arc;
 
-use quickwit_common::tower::BoxFutureInfaillible;
+use arrow_flight::flight_service_server::FlightServiceServer;
+use futures::future::BoxFuture;
+use quickwit_common::tower::{BoxFutureInfaillible, DeadlineLayer};
 use quickwit_config::service::QuickwitService;
 use quickwit_jaeger::JaegerService;
 use quickwit_opentelemetry::otlp::{OtlpGrpcLogsService, OtlpGrpcTracesService};
@@ -32,20 +34,25 @@ use quickwit_proto::opentelemetry::proto::collector::trace::v1::trace_service_se
 use quickwit_proto::search::search_service_server::SearchServiceServer;
 use quickwit_proto::tonic::codegen::CompressionEncoding;
 use quickwit_proto::tonic::transport::Server;
+use tokio_rustls::TlsAcceptor;
 use tracing::*;
 
+use crate::arrow_flight_api::ArrowFlightService;
 use crate::search_api::GrpcSearchAdapter;
+use crate::tls::tls_incoming;
 use crate::QuickwitServices;
 
-/// Starts and binds gRPC services to `grpc_listen_addr`.
+/// Starts and binds gRPC services to `grpc_listen_addr`. Connections are served over TLS when
+/// `tls_acceptor_opt` is set.
 pub(crate) async fn start_grpc_server(
     grpc_listen_addr: SocketAddr,
+    tls_acceptor_opt: Option<TlsAcceptor>,
     services: Arc<QuickwitServices>,
     readiness_trigger: BoxFutureInfaillible<()>,
     shutdown_signal: BoxFutureInfaillible<()>,
 ) -> anyhow::Result<()> {
     let mut enabled_grpc_services = BTreeSet::new();
-    let mut server = Server::builder();
+    let mut server = Server::builder().layer(DeadlineLayer::grpc());
 
     // Mount gRPC metastore service if `QuickwitService::Metastore` is enabled on node.
     let metastore_grpc_service = if let Some(metastore_server) = &services.metastore_server_opt {
@@ -153,6 +160,19 @@ pub(crate) async fn start_grpc_server(
     } else {
         None
     };
+    // Mount Arrow Flight service if `QuickwitService::Searcher` is enabled on node.
+    let arrow_flight_grpc_service = if services
+        .node_config
+        .is_service_enabled(QuickwitService::Searcher)
+    {
+        enabled_grpc_services.insert("arrow-flight");
+        let search_service = services.search_service.clone();
+        Some(FlightServiceServer::new(ArrowFlightService::new(
+            search_service,
+        )))
+    } else {
+        None
+    };
     let enable_jaeger_endpoint = services.node_config.jaeger_config.enable_endpoint;
     let jaeger_grpc_service = if enable_jaeger_endpoint
         && services
@@ -169,6 +189,7 @@ pub(crate) async fn start_grpc_server(
         None
     };
     let server_router = server
+        .add_optional_service(arrow_flight_grpc_service)
         .add_optional_service(control_plane_grpc_service)
         .add_optional_service(indexing_grpc_service)
         .add_optional_service(ingest_api_grpc_service)
@@ -183,9 +204,15 @@ pub(crate) async fn start_grpc_server(
     info!(
         enabled_grpc_services=?enabled_grpc_services,
         grpc_listen_addr=?grpc_listen_addr,
+        tls_enabled=tls_acceptor_opt.is_some(),
         "Starting gRPC server listening on {grpc_listen_addr}."
     );
-    let serve_fut = server_router.serve_with_shutdown(grpc_listen_addr, shutdown_signal);
+    let serve_fut: BoxFuture<'_, Result<(), _>> = if let Some(tls_acceptor) = tls_acceptor_opt {
+        let incoming = tls_incoming(grpc_listen_addr, tls_acceptor).await?;
+        Box::pin(server_router.serve_with_incoming_shutdown(incoming, shutdown_signal))
+    } else {
+        Box::pin(server_router.serve_with_shutdown(grpc_listen_addr, shutdown_signal))
+    };
     let (serve_res, _trigger_res) = tokio::join!(serve_fut, readiness_trigger);
     serve_res?;
     Ok(())
@@ -20,6 +20,7 @@
 mod rest_handler;
 
 pub use self::rest_handler::{
-    index_management_handlers, IndexApi, ListSplitsQueryParams, ListSplitsResponse,
-    UnsupportedContentType,
+    get_placement_violations_handler, get_shard_scaling_state_handler, get_source_backlog_handler,
+    index_management_handlers, CloneIndexRequest, IndexApi, ListSplitsQueryParams,
+    ListSplitsResponse, RestoreIndexRequest, SnapshotIndexRequest, UnsupportedContentType,
 };
@@ -21,21 +21,38 @@ use std::sync::Arc;
 
 use bytes::Bytes;
 use hyper::header::CONTENT_TYPE;
+use quickwit_common::split_file;
 use quickwit_common::uri::Uri;
 use quickwit_config::{
-    load_source_config_from_user_config, ConfigFormat, NodeConfig, SourceConfig, SourceParams,
-    CLI_INGEST_SOURCE_ID, INGEST_API_SOURCE_ID,
+    build_doc_mapper, load_source_config_from_user_config, ConfigFormat, DocMapping, IndexConfig,
+    NodeConfig, RetentionPolicy, SearchSettings, SourceConfig, SourceParams, CLI_INGEST_SOURCE_ID,
+    INGEST_API_SOURCE_ID,
+};
+use quickwit_doc_mapper::tag_pruning::tag_filter_from_split_tags;
+use quickwit_doc_mapper::{
+    analyze_field_text, analyze_text, FieldAnalysis, JsonObject, TokenizerConfig,
+};
+use quickwit_index_management::{
+    IndexService, IndexServiceError, IndexSnapshotSummary, SplitRemovalInfo,
 };
-use quickwit_doc_mapper::{analyze_text, TokenizerConfig};
-use quickwit_index_management::{IndexService, IndexServiceError};
 use quickwit_metastore::{
-    IndexMetadata, IndexMetadataResponseExt, ListIndexesMetadataResponseExt, ListSplitsQuery,
-    ListSplitsRequestExt, MetastoreServiceStreamSplitsExt, Split, SplitInfo, SplitState,
+    IndexConfigVersionExt, IndexMetadata, IndexMetadataResponseExt, ListIndexesMetadataResponseExt,
+    ListSplitsQuery, ListSplitsRequestExt, MetastoreServiceStreamSplitsExt, Split, SplitInfo,
+    SplitState, UpdateIndexRequestExt,
+};
+use quickwit_proto::control_plane::{
+    ControlPlaneResult, ControlPlaneService, ControlPlaneServiceClient,
+    GetPlacementViolationsRequest, GetPlacementViolationsResponse, GetShardScalingStateRequest,
+    GetShardScalingStateResponse, GetSourceBacklogRequest, GetSourceBacklogResponse,
+    PartitionBacklog, PlacementViolation, PlacementViolationKind, ShardScalingDecision,
+    ShardScalingMode,
 };
 use quickwit_proto::metastore::{
-    DeleteSourceRequest, EntityKind, IndexMetadataRequest, ListIndexesMetadataRequest,
+    DeleteSourceRequest, EntityKind, IndexEvent, IndexMetadataRequest,
+    ListIndexConfigVersionsRequest, ListIndexEventsRequest, ListIndexesMetadataRequest,
     ListSplitsRequest, MarkSplitsForDeletionRequest, MetastoreError, MetastoreResult,
-    MetastoreService, MetastoreServiceClient, ResetSourceCheckpointRequest, ToggleSourceRequest,
+    MetastoreService, MetastoreServiceClient, ResetSourceCheckpointRequest,
+    RollbackIndexConfigRequest, ToggleSourceRequest, UpdateIndexRequest,
 };
 use quickwit_proto::types::IndexUid;
 use serde::de::DeserializeOwned;
@@ -55,16 +72,62 @@ use crate::with_arg;
         create_index,
         clear_index,
         delete_index,
+        garbage_collect_index,
+        snapshot_index,
+        restore_index,
+        clone_index,
+        bulk_index_action,
         get_indexes_metadatas,
+        list_index_events,
+        list_index_config_versions,
+        rollback_index_config,
+        update_doc_mapping,
+        suggest_doc_mapping,
         list_splits,
+        get_split_metadata,
+        get_splits_metadata,
         describe_index,
         mark_splits_for_deletion,
         create_source,
         reset_source_checkpoint,
         toggle_source,
         delete_source,
+        get_shard_scaling_state,
+        get_source_backlog,
+        get_placement_violations,
+        analyze_index_field,
     ),
-    components(schemas(ToggleSource, SplitsForDeletion, IndexStats))
+    components(schemas(
+        ToggleSource,
+        SplitsForDeletion,
+        SplitIds,
+        SplitMetadataResponse,
+        DocMappingSuggestionResponse,
+        IndexStats,
+        GarbageCollectionSummary,
+        SnapshotIndexRequest,
+        RestoreIndexRequest,
+        CloneIndexRequest,
+        BulkIndexAction,
+        BulkIndexActionRequest,
+        BulkIndexActionStatus,
+        BulkIndexActionResult,
+        BulkIndexActionResponse,
+        IndexSnapshotSummary,
+        GetShardScalingStateResponse,
+        GetSourceBacklogResponse,
+        PartitionBacklog,
+        GetPlacementViolationsResponse,
+        PlacementViolation,
+        AnalyzeIndexFieldRequest,
+        FieldAnalysis,
+        PlacementViolationKind,
+        IndexEvent,
+        IndexConfigVersionResponse,
+        RollbackIndexConfig,
+        ShardScalingDecision,
+        ShardScalingMode,
+    ))
 )]
 pub struct IndexApi;
 
@@ -73,13 +136,27 @@ pub fn index_management_handlers(
     node_config: Arc<NodeConfig>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
     // Indexes handlers.
-    get_index_metadata_handler(index_service.metastore())
+    suggest_doc_mapping_handler()
+        .or(get_index_metadata_handler(index_service.metastore()))
         .or(get_indexes_metadatas_handler(index_service.metastore()))
         .or(create_index_handler(index_service.clone(), node_config))
         .or(clear_index_handler(index_service.clone()))
         .or(delete_index_handler(index_service.clone()))
+        .or(garbage_collect_index_handler(index_service.clone()))
+        .or(snapshot_index_handler(index_service.clone()))
+        .or(restore_index_handler(index_service.clone()))
+        .or(clone_index_handler(index_service.clone()))
+        .or(bulk_index_action_handler(index_service.clone()))
+        .or(update_doc_mapping_handler(index_service.clone()))
+        .or(list_index_events_handler(index_service.metastore()))
+        .or(list_index_config_versions_handler(
+            index_service.metastore(),
+        ))
+        .or(rollback_index_config_handler(index_service.metastore()))
         // Splits handlers
         .or(list_splits_handler(index_service.metastore()))
+        .or(get_split_metadata_handler(index_service.metastore()))
+        .or(get_splits_metadata_handler(index_service.metastore()))
         .or(describe_index_handler(index_service.metastore()))
         .or(mark_splits_for_deletion_handler(index_service.metastore()))
         // Sources handlers.
@@ -90,8 +167,13 @@ pub fn index_management_handlers(
         .or(delete_source_handler(index_service.metastore()))
         // Tokenizer handlers.
         .or(analyze_request_handler())
+        .or(analyze_index_field_handler(index_service.metastore()))
 }
 
+/// Optional header identifying the author of a config change, recorded in the config history of
+/// the index.
+const AUTHOR_HEADER: &str = "x-quickwit-author";
+
 fn json_body<T: DeserializeOwned + Send>(
 ) -> impl Filter<Extract = (T,), Error = warp::Rejection> + Clone {
     warp::body::content_length_limit(1024 * 1024).and(warp::body::json())
@@ -148,6 +230,202 @@ async fn get_index_metadata(
     Ok(index_metadata)
 }
 
+#[derive(Debug, Deserialize, utoipa::IntoParams, utoipa::ToSchema)]
+#[into_params(parameter_in = Query)]
+struct ListIndexEventsQueryParams {
+    /// Maximum number of events to return.
+    #[serde(default)]
+    limit: Option<u32>,
+}
+
+fn list_index_events_handler(
+    metastore: MetastoreServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexes" / String / "events")
+        .and(warp::get())
+        .and(serde_qs::warp::query(serde_qs::Config::default()))
+        .and(with_arg(metastore))
+        .then(list_index_events)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    get,
+    tag = "Indexes",
+    path = "/indexes/{index_id}/events",
+    responses(
+        (status = 200, description = "Successfully fetched index events.", body = [IndexEvent])
+    ),
+    params(
+        ListIndexEventsQueryParams,
+        ("index_id" = String, Path, description = "The index ID to retrieve events for."),
+    )
+)]
+/// Returns the recent events of an index, newest first.
+async fn list_index_events(
+    index_id: String,
+    list_index_events_query: ListIndexEventsQueryParams,
+    mut metastore: MetastoreServiceClient,
+) -> MetastoreResult<Vec<IndexEvent>> {
+    info!(index_id = %index_id, "list-index-events");
+    let index_metadata_request = IndexMetadataRequest::for_index_id(index_id);
+    let index_uid: IndexUid = metastore
+        .index_metadata(index_metadata_request)
+        .await?
+        .deserialize_index_metadata()?
+        .index_uid;
+    let list_index_events_request = ListIndexEventsRequest {
+        index_uid: index_uid.to_string(),
+        limit: list_index_events_query.limit,
+    };
+    let events = metastore
+        .list_index_events(list_index_events_request)
+        .await?
+        .events;
+    Ok(events)
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams, utoipa::ToSchema)]
+#[into_params(parameter_in = Query)]
+struct ListIndexConfigVersionsQueryParams {
+    /// Maximum number of config versions to return.
+    #[serde(default)]
+    limit: Option<u32>,
+}
+
+/// A version of the index and source configs of an index.
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+struct IndexConfigVersionResponse {
+    pub version: u64,
+    pub description: String,
+    pub author: Option<String>,
+    pub timestamp: i64,
+    #[schema(value_type = Object)]
+    pub index_config: IndexConfig,
+    #[schema(value_type = Vec<Object>)]
+    pub sources: Vec<SourceConfig>,
+}
+
+fn list_index_config_versions_handler(
+    metastore: MetastoreServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexes" / String / "config" / "history")
+        .and(warp::get())
+        .and(serde_qs::warp::query(serde_qs::Config::default()))
+        .and(with_arg(metastore))
+        .then(list_index_config_versions)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    get,
+    tag = "Indexes",
+    path = "/indexes/{index_id}/config/history",
+    responses(
+        (status = 200, description = "Successfully fetched the config history of the index.", body = [IndexConfigVersionResponse])
+    ),
+    params(
+        ListIndexConfigVersionsQueryParams,
+        ("index_id" = String, Path, description = "The index ID to retrieve the config history for."),
+    )
+)]
+/// Returns the config versions of an index, newest first.
+async fn list_index_config_versions(
+    index_id: String,
+    list_index_config_versions_query: ListIndexConfigVersionsQueryParams,
+    mut metastore: MetastoreServiceClient,
+) -> MetastoreResult<Vec<IndexConfigVersionResponse>> {
+    info!(index_id = %index_id, "list-index-config-versions");
+    let index_metadata_request = IndexMetadataRequest::for_index_id(index_id);
+    let index_uid: IndexUid = metastore
+        .index_metadata(index_metadata_request)
+        .await?
+        .deserialize_index_metadata()?
+        .index_uid;
+    let list_index_config_versions_request = ListIndexConfigVersionsRequest {
+        index_uid: index_uid.to_string(),
+        limit: list_index_config_versions_query.limit,
+    };
+    metastore
+        .list_index_config_versions(list_index_config_versions_request)
+        .await?
+        .versions
+        .into_iter()
+        .map(|config_version| {
+            Ok(IndexConfigVersionResponse {
+                index_config: config_version.deserialize_index_config()?,
+                sources: config_version.deserialize_source_configs()?,
+                version: config_version.version,
+                description: config_version.description,
+                author: config_version.author,
+                timestamp: config_version.timestamp,
+            })
+        })
+        .collect()
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+struct RollbackIndexConfig {
+    version: u64,
+}
+
+fn rollback_index_config_handler(
+    metastore: MetastoreServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexes" / String / "config" / "rollback")
+        .and(warp::post())
+        .and(json_body())
+        .and(warp::header::optional::<String>(AUTHOR_HEADER))
+        .and(with_arg(metastore))
+        .then(rollback_index_config)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    post,
+    tag = "Indexes",
+    path = "/indexes/{index_id}/config/rollback",
+    request_body = RollbackIndexConfig,
+    responses(
+        // We return `VersionedIndexMetadata` as it's the serialized model view.
+        (status = 200, description = "Successfully rolled back the doc mapping.", body = VersionedIndexMetadata)
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID to roll back the config of."),
+        ("x-quickwit-author" = Option<String>, Header, description = "Author of the change, recorded in the config history of the index."),
+    )
+)]
+/// Restores the doc mapping of a previous config version of an index. Unlike doc mapping updates,
+/// rollbacks may remove or modify fields. Sources are left untouched.
+async fn rollback_index_config(
+    index_id: String,
+    rollback_index_config: RollbackIndexConfig,
+    author_opt: Option<String>,
+    mut metastore: MetastoreServiceClient,
+) -> MetastoreResult<IndexMetadata> {
+    info!(index_id = %index_id, version = rollback_index_config.version, "rollback-index-config");
+    let index_metadata_request = IndexMetadataRequest::for_index_id(index_id);
+    let index_uid: IndexUid = metastore
+        .index_metadata(index_metadata_request)
+        .await?
+        .deserialize_index_metadata()?
+        .index_uid;
+    let rollback_index_config_request = RollbackIndexConfigRequest {
+        index_uid: index_uid.to_string(),
+        version: rollback_index_config.version,
+        author: author_opt,
+    };
+    let index_metadata = metastore
+        .rollback_index_config(rollback_index_config_request)
+        .await?
+        .deserialize_index_metadata()?;
+    Ok(index_metadata)
+}
+
 fn get_indexes_metadatas_handler(
     metastore: MetastoreServiceClient,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
@@ -288,6 +566,13 @@ pub struct ListSplitsQueryParams {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
     pub end_create_timestamp: Option<i64>,
+    /// If set, restrict splits to those that may contain documents carrying all these tags, of
+    /// the form `key:value`.
+    #[serde(deserialize_with = "from_simple_list")]
+    #[serde(serialize_with = "to_simple_list")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
 }
 
 #[derive(Serialize, Deserialize, Debug, utoipa::ToSchema)]
@@ -347,6 +632,15 @@ async fn list_splits(
     if let Some(end_created_timestamp) = list_split_query.end_create_timestamp {
         query = query.with_create_timestamp_lt(end_created_timestamp);
     }
+    if let Some(tags) = list_split_query.tags {
+        let tags_filter_opt =
+            tag_filter_from_split_tags(&tags).map_err(|error| MetastoreError::InvalidArgument {
+                message: error.to_string(),
+            })?;
+        if let Some(tags_filter) = tags_filter_opt {
+            query = query.with_tags_filter(tags_filter);
+        }
+    }
     let list_splits_request = ListSplitsRequest::try_from_list_splits_query(query)?;
     let splits = metastore
         .list_splits(list_splits_request)
@@ -372,6 +666,141 @@ fn list_splits_handler(
         .map(make_json_api_response)
 }
 
+/// Split metadata along with the URI of the split file, for external readers and data catalogs
+/// locating split files without accessing the metastore.
+#[derive(Serialize, Deserialize, Debug, utoipa::ToSchema)]
+pub struct SplitMetadataResponse {
+    #[serde(flatten)]
+    pub split: Split,
+    /// URI of the split file in the index storage.
+    pub split_uri: String,
+}
+
+impl SplitMetadataResponse {
+    fn new(split: Split, index_uri: &Uri) -> MetastoreResult<Self> {
+        let split_uri = index_uri
+            .join(split_file(split.split_id()))
+            .map_err(|error| MetastoreError::Internal {
+                message: format!("failed to build URI of split `{}`", split.split_id()),
+                cause: error.to_string(),
+            })?;
+        Ok(Self {
+            split,
+            split_uri: split_uri.to_string(),
+        })
+    }
+}
+
+async fn fetch_splits_metadata(
+    index_id: &str,
+    split_ids: Vec<String>,
+    metastore: &mut MetastoreServiceClient,
+) -> MetastoreResult<Vec<SplitMetadataResponse>> {
+    let index_metadata_request = IndexMetadataRequest::for_index_id(index_id.to_string());
+    let index_metadata = metastore
+        .index_metadata(index_metadata_request)
+        .await?
+        .deserialize_index_metadata()?;
+    let query =
+        ListSplitsQuery::for_index(index_metadata.index_uid.clone()).with_split_ids(split_ids);
+    let list_splits_request = ListSplitsRequest::try_from_list_splits_query(query)?;
+    let splits = metastore
+        .list_splits(list_splits_request)
+        .await?
+        .collect_splits()
+        .await?;
+    splits
+        .into_iter()
+        .map(|split| SplitMetadataResponse::new(split, index_metadata.index_uri()))
+        .collect()
+}
+
+#[utoipa::path(
+    get,
+    tag = "Splits",
+    path = "/indexes/{index_id}/splits/{split_id}/metadata",
+    responses(
+        (status = 200, description = "Successfully fetched split metadata.", body = SplitMetadataResponse)
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID of the split."),
+        ("split_id" = String, Path, description = "The split ID."),
+    )
+)]
+/// Get split metadata.
+///
+/// Returns the metadata of a split, including its footer offsets, and the URI of the split file.
+async fn get_split_metadata(
+    index_id: String,
+    split_id: String,
+    mut metastore: MetastoreServiceClient,
+) -> MetastoreResult<SplitMetadataResponse> {
+    info!(index_id = %index_id, split_id = %split_id, "get-split-metadata");
+    let splits_metadata =
+        fetch_splits_metadata(&index_id, vec![split_id.clone()], &mut metastore).await?;
+    splits_metadata
+        .into_iter()
+        .next()
+        .ok_or_else(|| MetastoreError::NotFound(EntityKind::Split { split_id }))
+}
+
+fn get_split_metadata_handler(
+    metastore: MetastoreServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexes" / String / "splits" / String / "metadata")
+        .and(warp::get())
+        .and(with_arg(metastore))
+        .then(get_split_metadata)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+struct SplitIds {
+    pub split_ids: Vec<String>,
+}
+
+#[utoipa::path(
+    post,
+    tag = "Splits",
+    path = "/indexes/{index_id}/splits/metadata",
+    request_body = SplitIds,
+    responses(
+        (status = 200, description = "Successfully fetched splits metadata.", body = [SplitMetadataResponse])
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID of the splits."),
+    )
+)]
+/// Get splits metadata.
+///
+/// Returns the metadata and the URI of the split file of each of the requested splits. Unknown
+/// splits are omitted from the response.
+async fn get_splits_metadata(
+    index_id: String,
+    split_ids: SplitIds,
+    mut metastore: MetastoreServiceClient,
+) -> MetastoreResult<Vec<SplitMetadataResponse>> {
+    info!(index_id = %index_id, num_splits = split_ids.split_ids.len(), "get-splits-metadata");
+    if split_ids.split_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    fetch_splits_metadata(&index_id, split_ids.split_ids, &mut metastore).await
+}
+
+fn get_splits_metadata_handler(
+    metastore: MetastoreServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexes" / String / "splits" / "metadata")
+        .and(warp::post())
+        .and(json_body())
+        .and(with_arg(metastore))
+        .then(get_splits_metadata)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
 #[derive(Deserialize, utoipa::ToSchema)]
 #[serde(deny_unknown_fields)]
 struct SplitsForDeletion {
@@ -580,79 +1009,676 @@ async fn delete_index(
         .await
 }
 
-fn create_source_handler(
+/// Summary of a garbage collection run on an index.
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+struct GarbageCollectionSummary {
+    /// Number of splits deleted from the storage and the metastore.
+    num_removed_splits: usize,
+    /// Total size in bytes of the deleted split files.
+    num_removed_bytes: u64,
+    /// IDs of the deleted splits.
+    removed_split_ids: Vec<String>,
+    /// IDs of the splits that could not be deleted. They are retried on the next run.
+    failed_split_ids: Vec<String>,
+}
+
+impl From<SplitRemovalInfo> for GarbageCollectionSummary {
+    fn from(removal_info: SplitRemovalInfo) -> Self {
+        let num_removed_bytes = removal_info
+            .removed_split_entries
+            .iter()
+            .map(|split_info| split_info.file_size_bytes.as_u64())
+            .sum();
+        Self {
+            num_removed_splits: removal_info.removed_split_entries.len(),
+            num_removed_bytes,
+            removed_split_ids: removal_info
+                .removed_split_entries
+                .into_iter()
+                .map(|split_info| split_info.split_id)
+                .collect(),
+            failed_split_ids: removal_info
+                .failed_splits
+                .into_iter()
+                .map(|split_info| split_info.split_id)
+                .collect(),
+        }
+    }
+}
+
+fn garbage_collect_index_handler(
     index_service: IndexService,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
-    warp::path!("indexes" / String / "sources")
+    warp::path!("indexes" / String / "gc")
         .and(warp::post())
-        .and(config_format_filter())
-        .and(warp::body::content_length_limit(1024 * 1024))
-        .and(warp::filters::body::bytes())
         .and(with_arg(index_service))
-        .then(create_source)
+        .then(garbage_collect_index)
         .and(extract_format_from_qs())
         .map(make_json_api_response)
 }
 
 #[utoipa::path(
     post,
-    tag = "Sources",
-    path = "/indexes/{index_id}/sources",
-    request_body = VersionedSourceConfig,
+    tag = "Indexes",
+    path = "/indexes/{index_id}/gc",
     responses(
-        // We return `VersionedSourceConfig` as it's the serialized model view.
-        (status = 200, description = "Successfully created source.", body = VersionedSourceConfig)
+        (status = 200, description = "Successfully garbage collected the index.", body = GarbageCollectionSummary)
     ),
     params(
-        ("index_id" = String, Path, description = "The index ID to create a source for."),
+        ("index_id" = String, Path, description = "The index ID to garbage collect."),
     )
 )]
-/// Creates Source.
-async fn create_source(
+/// Runs the garbage collection on an index immediately, without waiting for the next scheduled run
+/// of the janitor. The grace periods configured in the `garbage_collection` settings of the index
+/// apply.
+async fn garbage_collect_index(
     index_id: String,
-    config_format: ConfigFormat,
-    source_config_bytes: Bytes,
     mut index_service: IndexService,
-) -> Result<SourceConfig, IndexServiceError> {
-    let source_config: SourceConfig =
-        load_source_config_from_user_config(config_format, &source_config_bytes)
-            .map_err(IndexServiceError::InvalidConfig)?;
-    if let SourceParams::File(_) = &source_config.source_params {
-        return Err(IndexServiceError::OperationNotAllowed(
-            "file sources are limited to a local usage. please use the CLI command `quickwit tool \
-             local-ingest` to ingest data from a file"
-                .to_string(),
-        ));
-    }
-    let index_metadata_request = IndexMetadataRequest::for_index_id(index_id.to_string());
-    let index_uid: IndexUid = index_service
-        .metastore()
-        .index_metadata(index_metadata_request)
-        .await?
-        .deserialize_index_metadata()?
-        .index_uid;
-    info!(index_id = %index_id, source_id = %source_config.source_id, "create-source");
-    index_service.create_source(index_uid, source_config).await
+) -> Result<GarbageCollectionSummary, IndexServiceError> {
+    info!(index_id = %index_id, "garbage-collect-index");
+    let removal_info = index_service
+        .run_index_garbage_collection(&index_id)
+        .await?;
+    Ok(removal_info.into())
 }
 
-fn get_source_handler(
-    metastore: MetastoreServiceClient,
+/// Request body of the snapshot index endpoint.
+#[derive(Debug, Clone, Deserialize, Serialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SnapshotIndexRequest {
+    /// URI of the directory the snapshot is written to.
+    #[schema(value_type = String)]
+    pub output_uri: Uri,
+    /// If set, the split files are copied into the snapshot. Otherwise, the snapshot references
+    /// the split files of the index.
+    #[serde(default)]
+    pub copy_split_files: bool,
+}
+
+fn snapshot_index_handler(
+    index_service: IndexService,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
-    warp::path!("indexes" / String / "sources" / String)
-        .and(warp::get())
-        .and(with_arg(metastore))
-        .then(get_source)
+    warp::path!("indexes" / String / "snapshot")
+        .and(warp::post())
+        .and(json_body())
+        .and(with_arg(index_service))
+        .then(snapshot_index)
         .and(extract_format_from_qs())
         .map(make_json_api_response)
 }
 
-async fn get_source(
-    index_id: String,
-    source_id: String,
-    mut metastore: MetastoreServiceClient,
-) -> MetastoreResult<SourceConfig> {
-    info!(index_id = %index_id, source_id = %source_id, "get-source");
-    let index_metadata_request = IndexMetadataRequest::for_index_id(index_id.to_string());
+#[utoipa::path(
+    post,
+    tag = "Indexes",
+    path = "/indexes/{index_id}/snapshot",
+    request_body = SnapshotIndexRequest,
+    responses(
+        (status = 200, description = "Successfully took a snapshot of the index.", body = IndexSnapshotSummary)
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID to snapshot."),
+    )
+)]
+/// Takes a snapshot of the config, sources, checkpoints, and published splits of an index. The
+/// snapshot does not depend on the metastore backend and can be restored into another cluster.
+async fn snapshot_index(
+    index_id: String,
+    snapshot_index_request: SnapshotIndexRequest,
+    mut index_service: IndexService,
+) -> Result<IndexSnapshotSummary, IndexServiceError> {
+    info!(index_id = %index_id, output_uri = %snapshot_index_request.output_uri, "snapshot-index");
+    index_service
+        .snapshot_index(
+            &index_id,
+            &snapshot_index_request.output_uri,
+            snapshot_index_request.copy_split_files,
+        )
+        .await
+}
+
+/// Request body of the restore index endpoint.
+#[derive(Debug, Clone, Deserialize, Serialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RestoreIndexRequest {
+    /// URI of the directory of the snapshot.
+    #[schema(value_type = String)]
+    pub snapshot_uri: Uri,
+    /// URI of the restored index. Defaults to the URI of the snapshotted index.
+    #[schema(value_type = Option<String>)]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index_uri: Option<Uri>,
+}
+
+fn restore_index_handler(
+    index_service: IndexService,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexes" / "restore")
+        .and(warp::post())
+        .and(json_body())
+        .and(with_arg(index_service))
+        .then(restore_index)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    post,
+    tag = "Indexes",
+    path = "/indexes/restore",
+    request_body = RestoreIndexRequest,
+    responses(
+        // We return `VersionedIndexMetadata` as it's the serialized model view.
+        (status = 200, description = "Successfully restored the index.", body = VersionedIndexMetadata)
+    ),
+)]
+/// Restores an index from a snapshot. The index must not exist.
+async fn restore_index(
+    restore_index_request: RestoreIndexRequest,
+    mut index_service: IndexService,
+) -> Result<IndexMetadata, IndexServiceError> {
+    info!(snapshot_uri = %restore_index_request.snapshot_uri, "restore-index");
+    index_service
+        .restore_index(
+            &restore_index_request.snapshot_uri,
+            restore_index_request.index_uri,
+        )
+        .await
+}
+
+/// Request body of the clone index endpoint.
+#[derive(Debug, Clone, Deserialize, Serialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct CloneIndexRequest {
+    /// ID of the clone.
+    pub clone_index_id: String,
+    /// Search settings of the clone. Defaults to the search settings of the index.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search_settings: Option<SearchSettings>,
+    /// Retention policy of the clone. Defaults to the retention policy of the index.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retention: Option<RetentionPolicy>,
+}
+
+fn clone_index_handler(
+    index_service: IndexService,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexes" / String / "clone")
+        .and(warp::post())
+        .and(json_body())
+        .and(with_arg(index_service))
+        .then(clone_index)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    post,
+    tag = "Indexes",
+    path = "/indexes/{index_id}/clone",
+    request_body = CloneIndexRequest,
+    responses(
+        // We return `VersionedIndexMetadata` as it's the serialized model view.
+        (status = 200, description = "Successfully cloned the index.", body = VersionedIndexMetadata)
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID to clone."),
+    )
+)]
+/// Clones an index without copying its data: the clone shares the split files of the index.
+async fn clone_index(
+    index_id: String,
+    clone_index_request: CloneIndexRequest,
+    mut index_service: IndexService,
+) -> Result<IndexMetadata, IndexServiceError> {
+    info!(index_id = %index_id, clone_index_id = %clone_index_request.clone_index_id, "clone-index");
+    index_service
+        .clone_index(
+            &index_id,
+            &clone_index_request.clone_index_id,
+            clone_index_request.search_settings,
+            clone_index_request.retention,
+        )
+        .await
+}
+
+/// Action applied to each index matched by a bulk index action.
+#[derive(Debug, Clone, Deserialize, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkIndexAction {
+    /// Deletes the index.
+    Delete,
+    /// Removes all the data of the index but keeps its configuration.
+    Clear,
+    /// Enables or disables a source of the index.
+    ToggleSource { source_id: String, enable: bool },
+}
+
+impl BulkIndexAction {
+    /// Returns whether the action removes data, in which case applying it to all the indexes must
+    /// be confirmed.
+    fn is_destructive(&self) -> bool {
+        matches!(self, BulkIndexAction::Delete | BulkIndexAction::Clear)
+    }
+}
+
+/// Request body of the bulk index action endpoint.
+#[derive(Debug, Clone, Deserialize, Serialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct BulkIndexActionRequest {
+    /// Patterns of the IDs of the indexes to apply the action to, e.g. `logs-2023-*`.
+    pub index_id_patterns: Vec<String>,
+    pub action: BulkIndexAction,
+    /// If set, reports the indexes the action would be applied to without applying it.
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Must be set to delete or clear all the indexes, i.e. when a pattern is `*` or when the
+    /// patterns match every index.
+    #[serde(default)]
+    pub confirm_match_all: bool,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Deserialize, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkIndexActionStatus {
+    /// The action would be applied to the index (dry run).
+    Planned,
+    /// The action was applied to the index.
+    Applied,
+    /// The action could not be applied to the index.
+    Failed,
+}
+
+/// Outcome of a bulk index action on one index.
+#[derive(Debug, Clone, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct BulkIndexActionResult {
+    pub index_id: String,
+    pub status: BulkIndexActionStatus,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct BulkIndexActionResponse {
+    pub dry_run: bool,
+    pub results: Vec<BulkIndexActionResult>,
+}
+
+fn bulk_index_action_handler(
+    index_service: IndexService,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexes" / "_bulk-action")
+        .and(warp::post())
+        .and(json_body())
+        .and(with_arg(index_service))
+        .then(bulk_index_action)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    post,
+    tag = "Indexes",
+    path = "/indexes/_bulk-action",
+    request_body = BulkIndexActionRequest,
+    responses(
+        (status = 200, description = "Successfully applied the action to the matching indexes.", body = BulkIndexActionResponse)
+    ),
+)]
+/// Deletes, clears, or toggles a source of all the indexes matching a list of patterns. The
+/// indexes are processed one at a time, and a failure on an index does not prevent the action
+/// from being applied to the next ones. Deleting or clearing all the indexes is rejected unless
+/// `confirm_match_all` or `dry_run` is set.
+async fn bulk_index_action(
+    bulk_index_action_request: BulkIndexActionRequest,
+    index_service: IndexService,
+) -> Result<BulkIndexActionResponse, IndexServiceError> {
+    info!(
+        index_id_patterns = ?bulk_index_action_request.index_id_patterns,
+        action = ?bulk_index_action_request.action,
+        dry_run = bulk_index_action_request.dry_run,
+        "bulk-index-action"
+    );
+    if bulk_index_action_request.index_id_patterns.is_empty() {
+        return Err(IndexServiceError::InvalidConfig(anyhow::anyhow!(
+            "`index_id_patterns` must contain at least one pattern"
+        )));
+    }
+    let dry_run = bulk_index_action_request.dry_run;
+    let requires_match_all_confirmation = bulk_index_action_request.action.is_destructive()
+        && !dry_run
+        && !bulk_index_action_request.confirm_match_all;
+
+    if requires_match_all_confirmation
+        && bulk_index_action_request
+            .index_id_patterns
+            .iter()
+            .any(|index_id_pattern| index_id_pattern == "*")
+    {
+        return Err(match_all_not_confirmed_error());
+    }
+    let list_indexes_metadata_request = ListIndexesMetadataRequest {
+        index_id_patterns: bulk_index_action_request.index_id_patterns,
+    };
+    let indexes_metadata = index_service
+        .metastore()
+        .list_indexes_metadata(list_indexes_metadata_request)
+        .await?
+        .deserialize_indexes_metadata()?;
+
+    if requires_match_all_confirmation && !indexes_metadata.is_empty() {
+        let num_indexes = index_service
+            .metastore()
+            .list_indexes_metadata(ListIndexesMetadataRequest::all())
+            .await?
+            .deserialize_indexes_metadata()?
+            .len();
+
+        if indexes_metadata.len() >= num_indexes {
+            return Err(match_all_not_confirmed_error());
+        }
+    }
+
+    let mut results = Vec::with_capacity(indexes_metadata.len());
+
+    for index_metadata in indexes_metadata {
+        let index_id = index_metadata.index_id().to_string();
+        let outcome = apply_bulk_index_action(
+            &index_metadata,
+            &bulk_index_action_request.action,
+            dry_run,
+            index_service.clone(),
+        )
+        .await;
+        let result = match outcome {
+            Ok(()) if dry_run => BulkIndexActionResult {
+                index_id,
+                status: BulkIndexActionStatus::Planned,
+                error: None,
+            },
+            Ok(()) => BulkIndexActionResult {
+                index_id,
+                status: BulkIndexActionStatus::Applied,
+                error: None,
+            },
+            Err(error) => BulkIndexActionResult {
+                index_id,
+                status: BulkIndexActionStatus::Failed,
+                error: Some(error.to_string()),
+            },
+        };
+        results.push(result);
+    }
+    let bulk_index_action_response = BulkIndexActionResponse { dry_run, results };
+    Ok(bulk_index_action_response)
+}
+
+fn match_all_not_confirmed_error() -> IndexServiceError {
+    IndexServiceError::InvalidConfig(anyhow::anyhow!(
+        "`index_id_patterns` match all the indexes: set `confirm_match_all` to delete or clear \
+         all the indexes, or `dry_run` to list them"
+    ))
+}
+
+async fn apply_bulk_index_action(
+    index_metadata: &IndexMetadata,
+    action: &BulkIndexAction,
+    dry_run: bool,
+    index_service: IndexService,
+) -> Result<(), IndexServiceError> {
+    let index_id = index_metadata.index_id().to_string();
+
+    match action {
+        BulkIndexAction::Delete => {
+            let delete_index_query_param = DeleteIndexQueryParam { dry_run };
+            delete_index(index_id, delete_index_query_param, index_service).await?;
+        }
+        BulkIndexAction::Clear if dry_run => {}
+        BulkIndexAction::Clear => {
+            clear_index(index_id, index_service).await?;
+        }
+        BulkIndexAction::ToggleSource { source_id, .. } if dry_run => {
+            if [CLI_INGEST_SOURCE_ID, INGEST_API_SOURCE_ID].contains(&source_id.as_str()) {
+                return Err(IndexServiceError::OperationNotAllowed(format!(
+                    "source `{source_id}` is managed by Quickwit, you cannot enable or disable a \
+                     source managed by Quickwit"
+                )));
+            }
+            if !index_metadata.sources.contains_key(source_id) {
+                return Err(IndexServiceError::Metastore(MetastoreError::NotFound(
+                    EntityKind::Source {
+                        index_id,
+                        source_id: source_id.clone(),
+                    },
+                )));
+            }
+        }
+        BulkIndexAction::ToggleSource { source_id, enable } => {
+            let toggle_source_request = ToggleSource { enable: *enable };
+            toggle_source(
+                index_id,
+                source_id.clone(),
+                toggle_source_request,
+                index_service.metastore(),
+            )
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+fn update_doc_mapping_handler(
+    index_service: IndexService,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexes" / String / "doc-mapping")
+        .and(warp::put())
+        .and(config_format_filter())
+        .and(warp::body::content_length_limit(1024 * 1024))
+        .and(warp::filters::body::bytes())
+        .and(warp::header::optional::<String>(AUTHOR_HEADER))
+        .and(with_arg(index_service))
+        .then(update_doc_mapping)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    put,
+    tag = "Indexes",
+    path = "/indexes/{index_id}/doc-mapping",
+    request_body = DocMapping,
+    responses(
+        // We return `VersionedIndexMetadata` as it's the serialized model view.
+        (status = 200, description = "Successfully updated the doc mapping.", body = VersionedIndexMetadata)
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID to update the doc mapping of."),
+        ("x-quickwit-author" = Option<String>, Header, description = "Author of the change, recorded in the config history of the index."),
+    )
+)]
+/// Updates the doc mapping of an index. Only additive changes are supported: new fields can be
+/// added, but existing fields cannot be modified or removed.
+async fn update_doc_mapping(
+    index_id: String,
+    config_format: ConfigFormat,
+    doc_mapping_bytes: Bytes,
+    author_opt: Option<String>,
+    index_service: IndexService,
+) -> Result<IndexMetadata, IndexServiceError> {
+    let mut doc_mapping: DocMapping = config_format
+        .parse(&doc_mapping_bytes)
+        .map_err(IndexServiceError::InvalidConfig)?;
+    info!(index_id = %index_id, "update-doc-mapping");
+    index_service
+        .load_tokenizer_word_lists(&mut doc_mapping)
+        .await?;
+    let mut metastore = index_service.metastore();
+    let index_metadata_request = IndexMetadataRequest::for_index_id(index_id.to_string());
+    let index_uid: IndexUid = metastore
+        .index_metadata(index_metadata_request)
+        .await?
+        .deserialize_index_metadata()?
+        .index_uid;
+    let mut update_index_request =
+        UpdateIndexRequest::try_from_doc_mapping(index_uid, &doc_mapping)?;
+    update_index_request.author = author_opt;
+    let index_metadata = metastore
+        .update_index(update_index_request)
+        .await?
+        .deserialize_index_metadata()?;
+    Ok(index_metadata)
+}
+
+/// Maximum number of documents sampled to suggest a doc mapping.
+const MAX_DOC_MAPPING_SUGGESTION_NUM_DOCS: usize = 10_000;
+
+fn suggest_doc_mapping_handler(
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexes" / "doc-mapping" / "suggest")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(10 * 1024 * 1024))
+        .and(warp::filters::body::bytes())
+        .then(suggest_doc_mapping)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+/// Doc mapping suggested from a sample of documents.
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct DocMappingSuggestionResponse {
+    /// The suggested doc mapping. It uses the dynamic mode so that the fields left out of the
+    /// suggestion, such as fields with mixed types, are still indexed.
+    pub doc_mapping: DocMapping,
+    /// The fields that can serve as timestamp field, most likely first.
+    pub timestamp_field_candidates: Vec<String>,
+    /// The number of documents sampled.
+    pub num_sampled_docs: usize,
+}
+
+#[utoipa::path(
+    post,
+    tag = "Indexes",
+    path = "/indexes/doc-mapping/suggest",
+    request_body(content = String, description = "Sample of documents in NDJSON format", content_type = "application/x-ndjson"),
+    responses(
+        (status = 200, description = "Successfully suggested a doc mapping.", body = DocMappingSuggestionResponse)
+    ),
+)]
+/// Suggests a doc mapping from a sample of NDJSON documents. Only the first 10,000 documents are
+/// sampled.
+async fn suggest_doc_mapping(
+    docs_bytes: Bytes,
+) -> Result<DocMappingSuggestionResponse, IndexServiceError> {
+    let mut json_objs: Vec<JsonObject> = Vec::new();
+
+    for (line_idx, line) in docs_bytes.split(|byte| *byte == b'\n').enumerate() {
+        if json_objs.len() >= MAX_DOC_MAPPING_SUGGESTION_NUM_DOCS {
+            break;
+        }
+        if line.iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+        let json_obj: JsonObject = serde_json::from_slice(line).map_err(|error| {
+            IndexServiceError::InvalidConfig(anyhow::anyhow!(
+                "line {} is not a valid JSON object: {error}",
+                line_idx + 1
+            ))
+        })?;
+        json_objs.push(json_obj);
+    }
+    let doc_mapping_suggestion = quickwit_doc_mapper::suggest_doc_mapping(&json_objs)
+        .map_err(|error| IndexServiceError::Internal(format!("{error:#}")))?;
+    let doc_mapping_json = serde_json::json!({
+        "field_mappings": doc_mapping_suggestion.field_mappings,
+        "timestamp_field": doc_mapping_suggestion.timestamp_field_candidates.first(),
+        "mode": "dynamic",
+    });
+    let doc_mapping: DocMapping = serde_json::from_value(doc_mapping_json).map_err(|error| {
+        IndexServiceError::Internal(format!("failed to build suggested doc mapping: {error}"))
+    })?;
+    Ok(DocMappingSuggestionResponse {
+        doc_mapping,
+        timestamp_field_candidates: doc_mapping_suggestion.timestamp_field_candidates,
+        num_sampled_docs: json_objs.len(),
+    })
+}
+
+fn create_source_handler(
+    index_service: IndexService,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexes" / String / "sources")
+        .and(warp::post())
+        .and(config_format_filter())
+        .and(warp::body::content_length_limit(1024 * 1024))
+        .and(warp::filters::body::bytes())
+        .and(with_arg(index_service))
+        .then(create_source)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    post,
+    tag = "Sources",
+    path = "/indexes/{index_id}/sources",
+    request_body = VersionedSourceConfig,
+    responses(
+        // We return `VersionedSourceConfig` as it's the serialized model view.
+        (status = 200, description = "Successfully created source.", body = VersionedSourceConfig)
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID to create a source for."),
+    )
+)]
+/// Creates Source.
+async fn create_source(
+    index_id: String,
+    config_format: ConfigFormat,
+    source_config_bytes: Bytes,
+    mut index_service: IndexService,
+) -> Result<SourceConfig, IndexServiceError> {
+    let source_config: SourceConfig =
+        load_source_config_from_user_config(config_format, &source_config_bytes)
+            .map_err(IndexServiceError::InvalidConfig)?;
+    if let SourceParams::File(_) = &source_config.source_params {
+        return Err(IndexServiceError::OperationNotAllowed(
+            "file sources are limited to a local usage. please use the CLI command `quickwit tool \
+             local-ingest` to ingest data from a file"
+                .to_string(),
+        ));
+    }
+    let index_metadata_request = IndexMetadataRequest::for_index_id(index_id.to_string());
+    let index_uid: IndexUid = index_service
+        .metastore()
+        .index_metadata(index_metadata_request)
+        .await?
+        .deserialize_index_metadata()?
+        .index_uid;
+    info!(index_id = %index_id, source_id = %source_config.source_id, "create-source");
+    index_service.create_source(index_uid, source_config).await
+}
+
+fn get_source_handler(
+    metastore: MetastoreServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexes" / String / "sources" / String)
+        .and(warp::get())
+        .and(with_arg(metastore))
+        .then(get_source)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+async fn get_source(
+    index_id: String,
+    source_id: String,
+    mut metastore: MetastoreServiceClient,
+) -> MetastoreResult<SourceConfig> {
+    info!(index_id = %index_id, source_id = %source_id, "get-source");
+    let index_metadata_request = IndexMetadataRequest::for_index_id(index_id.to_string());
     let source_config = metastore
         .index_metadata(index_metadata_request)
         .await?
@@ -668,6 +1694,120 @@ async fn get_source(
     Ok(source_config)
 }
 
+/// Exposes the shard scaling state of an ingest v2 source. The number of open shards and the
+/// recent scaling decisions are tracked by the control plane.
+pub fn get_shard_scaling_state_handler(
+    control_plane: ControlPlaneServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexes" / String / "sources" / String / "shard-scaling")
+        .and(warp::get())
+        .and(with_arg(control_plane))
+        .then(get_shard_scaling_state)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    get,
+    tag = "Sources",
+    path = "/indexes/{index_id}/sources/{source_id}/shard-scaling",
+    responses(
+        (status = 200, description = "Successfully fetched shard scaling state.", body = GetShardScalingStateResponse)
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID of the source."),
+        ("source_id" = String, Path, description = "The source ID."),
+    )
+)]
+/// Returns the number of open shards of the source and the recent scaling decisions.
+async fn get_shard_scaling_state(
+    index_id: String,
+    source_id: String,
+    mut control_plane: ControlPlaneServiceClient,
+) -> ControlPlaneResult<GetShardScalingStateResponse> {
+    info!(index_id = %index_id, source_id = %source_id, "get-shard-scaling-state");
+    let get_shard_scaling_state_request = GetShardScalingStateRequest {
+        index_id,
+        source_id,
+    };
+    control_plane
+        .get_shard_scaling_state(get_shard_scaling_state_request)
+        .await
+}
+
+/// Exposes the backlog of a source, i.e. the number of records of each partition that the source
+/// has not indexed yet, as estimated by the control plane.
+pub fn get_source_backlog_handler(
+    control_plane: ControlPlaneServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexes" / String / "sources" / String / "backlog")
+        .and(warp::get())
+        .and(with_arg(control_plane))
+        .then(get_source_backlog)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    get,
+    tag = "Sources",
+    path = "/indexes/{index_id}/sources/{source_id}/backlog",
+    responses(
+        (status = 200, description = "Successfully fetched source backlog.", body = GetSourceBacklogResponse)
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID of the source."),
+        ("source_id" = String, Path, description = "The source ID."),
+    )
+)]
+/// Returns the total backlog of the source and the backlog of each of its partitions.
+async fn get_source_backlog(
+    index_id: String,
+    source_id: String,
+    mut control_plane: ControlPlaneServiceClient,
+) -> ControlPlaneResult<GetSourceBacklogResponse> {
+    info!(index_id = %index_id, source_id = %source_id, "get-source-backlog");
+    let get_source_backlog_request = GetSourceBacklogRequest {
+        index_id,
+        source_id,
+    };
+    control_plane
+        .get_source_backlog(get_source_backlog_request)
+        .await
+}
+
+/// Exposes the indexing pipelines and ingest shards that do not respect the placement constraints
+/// of their index or source, as observed by the control plane.
+pub fn get_placement_violations_handler(
+    control_plane: ControlPlaneServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexing" / "placement-violations")
+        .and(warp::get())
+        .and(with_arg(control_plane))
+        .then(get_placement_violations)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    get,
+    tag = "Indexing",
+    path = "/indexing/placement-violations",
+    responses(
+        (status = 200, description = "Successfully fetched placement violations.", body = GetPlacementViolationsResponse)
+    ),
+)]
+/// Returns the sources that no indexer can host, and the indexing pipelines and open shards placed
+/// on nodes that do not satisfy the placement constraints of their index or source.
+async fn get_placement_violations(
+    mut control_plane: ControlPlaneServiceClient,
+) -> ControlPlaneResult<GetPlacementViolationsResponse> {
+    info!("get-placement-violations");
+    control_plane
+        .get_placement_violations(GetPlacementViolationsRequest {})
+        .await
+}
+
 fn reset_source_checkpoint_handler(
     metastore: MetastoreServiceClient,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
@@ -865,19 +2005,71 @@ async fn analyze_request(request: AnalyzeRequest) -> Result<serde_json::Value, I
     Ok(json_value)
 }
 
+#[derive(Debug, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+struct AnalyzeIndexFieldRequest {
+    /// The field whose analyzers are used, as named in queries.
+    pub field: String,
+    /// The text to analyze.
+    pub text: String,
+}
+
+fn analyze_index_field_handler(
+    metastore: MetastoreServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexes" / String / "analyze")
+        .and(warp::post())
+        .and(json_body())
+        .and(with_arg(metastore))
+        .then(analyze_index_field)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+/// Analyzes text with the tokenizer and the fast field normalizer of a field of an index.
+#[utoipa::path(
+    post,
+    tag = "analyze",
+    path = "/indexes/{index_id}/analyze",
+    request_body = AnalyzeIndexFieldRequest,
+    responses(
+        (status = 200, description = "Successfully analyzed text.", body = FieldAnalysis)
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID whose doc mapping is used."),
+    )
+)]
+async fn analyze_index_field(
+    index_id: String,
+    request: AnalyzeIndexFieldRequest,
+    mut metastore: MetastoreServiceClient,
+) -> Result<FieldAnalysis, IndexServiceError> {
+    let index_metadata_request = IndexMetadataRequest::for_index_id(index_id);
+    let index_metadata = metastore
+        .index_metadata(index_metadata_request)
+        .await?
+        .deserialize_index_metadata()?;
+    let index_config = &index_metadata.index_config;
+    let doc_mapper = build_doc_mapper(&index_config.doc_mapping, &index_config.search_settings)
+        .map_err(|error| IndexServiceError::Internal(format!("{error:#}")))?;
+    analyze_field_text(&*doc_mapper, &request.field, &request.text)
+        .map_err(IndexServiceError::InvalidConfig)
+}
+
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeSet;
     use std::ops::{Bound, RangeInclusive};
 
     use assert_json_diff::assert_json_include;
     use quickwit_common::uri::Uri;
     use quickwit_common::ServiceStream;
-    use quickwit_config::{SourceParams, VecSourceParams};
+    use quickwit_config::{IndexConfig, SourceParams, VecSourceParams};
     use quickwit_indexing::{mock_split, MockSplitBuilder};
     use quickwit_metastore::{metastore_for_test, IndexMetadata, ListSplitsResponseExt};
     use quickwit_proto::metastore::{
-        EmptyResponse, IndexMetadataResponse, ListIndexesMetadataResponse, ListSplitsResponse,
-        MetastoreServiceClient, SourceType,
+        EmptyResponse, IndexMetadataResponse, ListIndexEventsResponse, ListIndexesMetadataResponse,
+        ListSplitsResponse, MetastoreServiceClient, SourceType,
     };
     use quickwit_storage::StorageResolver;
     use serde_json::Value as JsonValue;
@@ -935,6 +2127,52 @@ mod tests {
         assert_eq!(resp.status(), 404);
     }
 
+    #[tokio::test]
+    async fn test_list_index_events() {
+        let mut mock_metastore = MetastoreServiceClient::mock();
+        let index_metadata = IndexMetadata::for_test("test-index", "ram:///indexes/test-index");
+        let index_uid = index_metadata.index_uid.clone();
+        mock_metastore
+            .expect_index_metadata()
+            .return_once(move |_| {
+                Ok(IndexMetadataResponse::try_from_index_metadata(index_metadata).unwrap())
+            });
+        mock_metastore
+            .expect_list_index_events()
+            .withf(move |request| {
+                request.index_uid == index_uid.to_string() && request.limit == Some(1)
+            })
+            .return_once(|request| {
+                Ok(ListIndexEventsResponse {
+                    events: vec![IndexEvent {
+                        index_uid: request.index_uid,
+                        event_type: "source_added".to_string(),
+                        message: "source `my-source` added".to_string(),
+                        timestamp: 1_700_000_000,
+                    }],
+                })
+            });
+        let index_service = IndexService::new(
+            MetastoreServiceClient::from(mock_metastore),
+            StorageResolver::unconfigured(),
+        );
+        let index_management_handler =
+            super::index_management_handlers(index_service, Arc::new(NodeConfig::for_test()))
+                .recover(recover_fn);
+        let resp = warp::test::request()
+            .path("/indexes/test-index/events?limit=1")
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let actual_response_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        let expected_response_json = serde_json::json!([{
+            "event_type": "source_added",
+            "message": "source `my-source` added",
+            "timestamp": 1_700_000_000,
+        }]);
+        assert_json_include!(actual: actual_response_json, expected: expected_response_json);
+    }
+
     #[tokio::test]
     async fn test_get_splits() {
         let mut metastore = MetastoreServiceClient::mock();
@@ -1012,6 +2250,161 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_get_splits_with_tags() {
+        let mut metastore = MetastoreServiceClient::mock();
+        let index_metadata =
+            IndexMetadata::for_test("quickwit-demo-index", "ram:///indexes/quickwit-demo-index");
+        let index_uid = index_metadata.index_uid.clone();
+        metastore
+            .expect_index_metadata()
+            .returning(move |_| {
+                Ok(IndexMetadataResponse::try_from_index_metadata(index_metadata.clone()).unwrap())
+            })
+            .times(2);
+        metastore
+            .expect_list_splits()
+            .returning(move |list_splits_request: ListSplitsRequest| {
+                let list_split_query = list_splits_request.deserialize_list_splits_query().unwrap();
+                let tags_filter = list_split_query.tags.unwrap();
+                assert_eq!(tags_filter.to_string(), "(¬env! ∨ env:prod)");
+
+                let mut split = MockSplitBuilder::new("split_1")
+                    .with_index_uid(&index_uid)
+                    .build();
+                split.split_metadata.tags =
+                    BTreeSet::from(["env!".to_string(), "env:prod".to_string()]);
+                let splits = ListSplitsResponse::try_from_splits(vec![split]).unwrap();
+                Ok(ServiceStream::from(vec![Ok(splits)]))
+            })
+            .times(1);
+        let index_service = IndexService::new(
+            MetastoreServiceClient::from(metastore),
+            StorageResolver::unconfigured(),
+        );
+        let index_management_handler =
+            super::index_management_handlers(index_service, Arc::new(NodeConfig::for_test()))
+                .recover(recover_fn);
+        {
+            let resp = warp::test::request()
+                .path("/indexes/quickwit-demo-index/splits?tags=env:prod")
+                .reply(&index_management_handler)
+                .await;
+            assert_eq!(resp.status(), 200);
+            let actual_response_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+            let expected_response_json = serde_json::json!({
+                "splits": [
+                    {
+                        "split_id": "split_1",
+                        "tags": ["env!", "env:prod"],
+                    }
+                ]
+            });
+            assert_json_include!(
+                actual: actual_response_json,
+                expected: expected_response_json
+            );
+        }
+        {
+            let resp = warp::test::request()
+                .path("/indexes/quickwit-demo-index/splits?tags=env")
+                .reply(&index_management_handler)
+                .await;
+            assert_eq!(resp.status(), 400);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_split_metadata() {
+        let mut metastore = MetastoreServiceClient::mock();
+        let index_metadata =
+            IndexMetadata::for_test("quickwit-demo-index", "ram:///indexes/quickwit-demo-index");
+        let index_uid = index_metadata.index_uid.clone();
+        metastore
+            .expect_index_metadata()
+            .returning(move |_| {
+                Ok(IndexMetadataResponse::try_from_index_metadata(index_metadata.clone()).unwrap())
+            })
+            .times(3);
+        metastore
+            .expect_list_splits()
+            .returning(move |list_splits_request: ListSplitsRequest| {
+                let list_split_query = list_splits_request.deserialize_list_splits_query().unwrap();
+                assert!(list_split_query.index_uids.contains(&index_uid));
+
+                let splits = list_split_query
+                    .split_ids
+                    .iter()
+                    .filter(|split_id| split_id.starts_with("split_"))
+                    .map(|split_id| {
+                        MockSplitBuilder::new(split_id)
+                            .with_index_uid(&index_uid)
+                            .build()
+                    })
+                    .collect();
+                let splits = ListSplitsResponse::try_from_splits(splits).unwrap();
+                Ok(ServiceStream::from(vec![Ok(splits)]))
+            })
+            .times(3);
+        let index_service = IndexService::new(
+            MetastoreServiceClient::from(metastore),
+            StorageResolver::unconfigured(),
+        );
+        let index_management_handler =
+            super::index_management_handlers(index_service, Arc::new(NodeConfig::for_test()))
+                .recover(recover_fn);
+        {
+            let resp = warp::test::request()
+                .path("/indexes/quickwit-demo-index/splits/split_1/metadata")
+                .reply(&index_management_handler)
+                .await;
+            assert_eq!(resp.status(), 200);
+            let actual_response_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+            let expected_response_json = serde_json::json!({
+                "split_id": "split_1",
+                "split_state": "Published",
+                "split_uri": "ram:///indexes/quickwit-demo-index/split_1.split",
+            });
+            assert_json_include!(
+                actual: actual_response_json,
+                expected: expected_response_json
+            );
+            assert!(actual_response_json.get("footer_offsets").is_some());
+        }
+        {
+            let resp = warp::test::request()
+                .path("/indexes/quickwit-demo-index/splits/unknown/metadata")
+                .reply(&index_management_handler)
+                .await;
+            assert_eq!(resp.status(), 404);
+        }
+        {
+            let resp = warp::test::request()
+                .method("POST")
+                .path("/indexes/quickwit-demo-index/splits/metadata")
+                .json(&true)
+                .body(r#"{"split_ids": ["split_1", "unknown", "split_2"]}"#)
+                .reply(&index_management_handler)
+                .await;
+            assert_eq!(resp.status(), 200);
+            let actual_response_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+            let expected_response_json = serde_json::json!([
+                {
+                    "split_id": "split_1",
+                    "split_uri": "ram:///indexes/quickwit-demo-index/split_1.split",
+                },
+                {
+                    "split_id": "split_2",
+                    "split_uri": "ram:///indexes/quickwit-demo-index/split_2.split",
+                }
+            ]);
+            assert_json_include!(
+                actual: actual_response_json,
+                expected: expected_response_json
+            );
+        }
+    }
+
     #[tokio::test]
     async fn test_describe_index() -> anyhow::Result<()> {
         let mut mock_metastore = MetastoreServiceClient::mock();
@@ -1238,6 +2631,18 @@ mod tests {
         mock_metastore
             .expect_delete_splits()
             .return_once(|_| Ok(EmptyResponse {}));
+        mock_metastore
+            .expect_list_indexes_metadata()
+            .return_once(|_| {
+                let indexes_metadata = vec![IndexMetadata::for_test(
+                    "quickwit-demo-index",
+                    "file:///path/to/index/quickwit-demo-index",
+                )];
+                Ok(
+                    ListIndexesMetadataResponse::try_from_indexes_metadata(indexes_metadata)
+                        .unwrap(),
+                )
+            });
         mock_metastore
             .expect_reset_source_checkpoint()
             .return_once(|_| Ok(EmptyResponse {}));
@@ -1286,6 +2691,18 @@ mod tests {
         mock_metastore
             .expect_delete_splits()
             .return_once(|_| Ok(EmptyResponse {}));
+        mock_metastore
+            .expect_list_indexes_metadata()
+            .return_once(|_| {
+                let indexes_metadata = vec![IndexMetadata::for_test(
+                    "quickwit-demo-index",
+                    "file:///path/to/index/quickwit-demo-index",
+                )];
+                Ok(
+                    ListIndexesMetadataResponse::try_from_indexes_metadata(indexes_metadata)
+                        .unwrap(),
+                )
+            });
         mock_metastore
             .expect_delete_index()
             .return_once(|_| Ok(EmptyResponse {}));
@@ -1327,6 +2744,179 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_garbage_collect_index() {
+        let mut mock_metastore = MetastoreServiceClient::mock();
+        mock_metastore.expect_index_metadata().return_once(|_| {
+            Ok(
+                IndexMetadataResponse::try_from_index_metadata(IndexMetadata::for_test(
+                    "quickwit-demo-index",
+                    "file:///path/to/index/quickwit-demo-index",
+                ))
+                .unwrap(),
+            )
+        });
+        mock_metastore
+            .expect_list_splits()
+            .returning(|list_splits_request| {
+                let query = list_splits_request.deserialize_list_splits_query().unwrap();
+                let splits = match query.split_states[0] {
+                    SplitState::Staged => Vec::new(),
+                    SplitState::MarkedForDeletion => vec![mock_split("split_1")],
+                    _ => panic!("only Staged and MarkedForDeletion expected"),
+                };
+                let splits = ListSplitsResponse::try_from_splits(splits).unwrap();
+                Ok(ServiceStream::from(vec![Ok(splits)]))
+            })
+            .times(2);
+        mock_metastore
+            .expect_list_indexes_metadata()
+            .return_once(|_| {
+                let indexes_metadata = vec![IndexMetadata::for_test(
+                    "quickwit-demo-index",
+                    "file:///path/to/index/quickwit-demo-index",
+                )];
+                Ok(
+                    ListIndexesMetadataResponse::try_from_indexes_metadata(indexes_metadata)
+                        .unwrap(),
+                )
+            });
+        mock_metastore
+            .expect_delete_splits()
+            .return_once(|delete_splits_request| {
+                assert_eq!(delete_splits_request.split_ids, vec!["split_1"]);
+                Ok(EmptyResponse {})
+            });
+        let index_service = IndexService::new(
+            MetastoreServiceClient::from(mock_metastore),
+            StorageResolver::unconfigured(),
+        );
+        let index_management_handler =
+            super::index_management_handlers(index_service, Arc::new(NodeConfig::for_test()))
+                .recover(recover_fn);
+        let resp = warp::test::request()
+            .path("/indexes/quickwit-demo-index/gc")
+            .method("POST")
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+        let expected_response_json = serde_json::json!({
+            "num_removed_splits": 1,
+            "num_removed_bytes": 800,
+            "removed_split_ids": ["split_1"],
+            "failed_split_ids": [],
+        });
+        assert_eq!(resp_json, expected_response_json);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_and_restore_index() {
+        let metastore = metastore_for_test();
+        let mut index_service = IndexService::new(metastore, StorageResolver::for_test());
+        let index_config = IndexConfig::for_test("test-index", "ram:///indexes/test-index");
+        index_service
+            .create_index(index_config, false)
+            .await
+            .unwrap();
+        let index_management_handler = super::index_management_handlers(
+            index_service.clone(),
+            Arc::new(NodeConfig::for_test()),
+        )
+        .recover(recover_fn);
+        let resp = warp::test::request()
+            .path("/indexes/test-index/snapshot")
+            .method("POST")
+            .json(&true)
+            .body(r#"{"output_uri": "ram:///snapshots/test-index"}"#)
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+        let expected_response_json = serde_json::json!({
+            "index_id": "test-index",
+            "snapshot_uri": "ram:///snapshots/test-index",
+            "num_splits": 0,
+            "num_docs": 0,
+            "num_copied_bytes": 0,
+        });
+        assert_eq!(resp_json, expected_response_json);
+
+        index_service
+            .delete_index("test-index", false)
+            .await
+            .unwrap();
+
+        let resp = warp::test::request()
+            .path("/indexes/restore")
+            .method("POST")
+            .json(&true)
+            .body(r#"{"snapshot_uri": "ram:///snapshots/test-index"}"#)
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(resp_json["index_config"]["index_id"], "test-index");
+        assert_eq!(
+            resp_json["index_config"]["index_uri"],
+            "ram:///indexes/test-index"
+        );
+
+        // The index already exists.
+        let resp = warp::test::request()
+            .path("/indexes/restore")
+            .method("POST")
+            .json(&true)
+            .body(r#"{"snapshot_uri": "ram:///snapshots/test-index"}"#)
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 400);
+    }
+
+    #[tokio::test]
+    async fn test_clone_index() {
+        let metastore = metastore_for_test();
+        let mut index_service = IndexService::new(metastore, StorageResolver::for_test());
+        let index_config = IndexConfig::for_test("test-index", "ram:///indexes/test-index");
+        index_service
+            .create_index(index_config, false)
+            .await
+            .unwrap();
+        let index_management_handler =
+            super::index_management_handlers(index_service, Arc::new(NodeConfig::for_test()))
+                .recover(recover_fn);
+        let resp = warp::test::request()
+            .path("/indexes/test-index/clone")
+            .method("POST")
+            .json(&true)
+            .body(
+                r#"{"clone_index_id": "test-clone", "search_settings": {"default_search_fields": ["body"]}}"#,
+            )
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(resp_json["index_config"]["index_id"], "test-clone");
+        assert_eq!(
+            resp_json["index_config"]["index_uri"],
+            "ram:///indexes/test-index"
+        );
+        assert_eq!(
+            resp_json["index_config"]["search_settings"]["default_search_fields"],
+            serde_json::json!(["body"])
+        );
+
+        // The index to clone does not exist.
+        let resp = warp::test::request()
+            .path("/indexes/index-not-found/clone")
+            .method("POST")
+            .json(&true)
+            .body(r#"{"clone_index_id": "another-clone"}"#)
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 404);
+    }
+
     #[tokio::test]
     async fn test_delete_on_non_existing_index() {
         let metastore = metastore_for_test();
@@ -1461,47 +3051,222 @@ mod tests {
             .unwrap();
         assert!(!index_metadata.sources.contains_key("file-source"));
 
-        // Check cannot delete source managed by Quickwit.
+        // Check cannot delete source managed by Quickwit.
+        let resp = warp::test::request()
+            .path(format!("/indexes/hdfs-logs/sources/{INGEST_API_SOURCE_ID}").as_str())
+            .method("DELETE")
+            .body(source_config_body)
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 405);
+
+        let resp = warp::test::request()
+            .path(format!("/indexes/hdfs-logs/sources/{CLI_INGEST_SOURCE_ID}").as_str())
+            .method("DELETE")
+            .body(source_config_body)
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 405);
+
+        // Check get a non existing source returns 404.
+        let resp = warp::test::request()
+            .path("/indexes/hdfs-logs/sources/file-source")
+            .method("GET")
+            .body(source_config_body)
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 404);
+
+        // Check delete index.
+        let resp = warp::test::request()
+            .path("/indexes/hdfs-logs")
+            .method("DELETE")
+            .body(source_config_body)
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let indexes = metastore
+            .list_indexes_metadata(ListIndexesMetadataRequest::all())
+            .await
+            .unwrap()
+            .deserialize_indexes_metadata()
+            .unwrap();
+        assert!(indexes.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_update_doc_mapping() {
+        let index_service =
+            IndexService::new(metastore_for_test(), StorageResolver::unconfigured());
+        let mut node_config = NodeConfig::for_test();
+        node_config.default_index_root_uri = Uri::for_test("file:///default-index-root-uri");
+        let index_management_handler =
+            super::index_management_handlers(index_service, Arc::new(node_config));
+        let resp = warp::test::request()
+            .path("/indexes")
+            .method("POST")
+            .json(&true)
+            .body(r#"{"version": "0.6", "index_id": "hdfs-logs", "doc_mapping": {"field_mappings":[{"name": "timestamp", "type": "i64", "fast": true, "indexed": true}]}}"#)
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+
+        // Add a field.
+        let resp = warp::test::request()
+            .path("/indexes/hdfs-logs/doc-mapping")
+            .method("PUT")
+            .json(&true)
+            .body(r#"{"field_mappings":[{"name": "timestamp", "type": "i64", "fast": true, "indexed": true}, {"name": "severity", "type": "text", "tokenizer": "raw"}]}"#)
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(resp_json["doc_mapping_version"], 1);
+        assert_eq!(
+            resp_json["index_config"]["doc_mapping"]["field_mappings"][1]["name"],
+            "severity"
+        );
+
+        // Remove a field.
+        let resp = warp::test::request()
+            .path("/indexes/hdfs-logs/doc-mapping")
+            .method("PUT")
+            .json(&true)
+            .body(r#"{"field_mappings":[{"name": "timestamp", "type": "i64", "fast": true, "indexed": true}]}"#)
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 400);
+
+        // Modify a field.
+        let resp = warp::test::request()
+            .path("/indexes/hdfs-logs/doc-mapping")
+            .method("PUT")
+            .json(&true)
+            .body(r#"{"field_mappings":[{"name": "timestamp", "type": "i64", "fast": false, "indexed": true}, {"name": "severity", "type": "text", "tokenizer": "raw"}]}"#)
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 400);
+
+        let resp = warp::test::request()
+            .path("/indexes/does-not-exist/doc-mapping")
+            .method("PUT")
+            .json(&true)
+            .body(r#"{"field_mappings":[]}"#)
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn test_index_config_history_and_rollback() {
+        let index_service =
+            IndexService::new(metastore_for_test(), StorageResolver::unconfigured());
+        let mut node_config = NodeConfig::for_test();
+        node_config.default_index_root_uri = Uri::for_test("file:///default-index-root-uri");
+        let index_management_handler =
+            super::index_management_handlers(index_service, Arc::new(node_config))
+                .recover(recover_fn);
+        let resp = warp::test::request()
+            .path("/indexes")
+            .method("POST")
+            .json(&true)
+            .body(r#"{"version": "0.6", "index_id": "hdfs-logs", "doc_mapping": {"field_mappings":[{"name": "timestamp", "type": "i64", "fast": true, "indexed": true}]}}"#)
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+
+        let resp = warp::test::request()
+            .path("/indexes/hdfs-logs/doc-mapping")
+            .method("PUT")
+            .header("x-quickwit-author", "alice")
+            .json(&true)
+            .body(r#"{"field_mappings":[{"name": "timestamp", "type": "i64", "fast": true, "indexed": true}, {"name": "severity", "type": "text", "tokenizer": "raw"}]}"#)
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+
+        let resp = warp::test::request()
+            .path("/indexes/hdfs-logs/config/history")
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        let expected_response_json = serde_json::json!([
+            {
+                "version": 2,
+                "description": "doc mapping updated",
+                "author": "alice",
+                "index_config": {
+                    "index_id": "hdfs-logs",
+                    "doc_mapping": {
+                        "field_mappings": [{"name": "timestamp"}, {"name": "severity"}]
+                    }
+                },
+                "sources": []
+            },
+            {
+                "version": 1,
+                "description": "index created",
+                "author": null,
+            }
+        ]);
+        assert_json_include!(actual: resp_json, expected: expected_response_json);
+
+        let resp = warp::test::request()
+            .path("/indexes/hdfs-logs/config/history?limit=1")
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(resp_json.as_array().unwrap().len(), 1);
+
+        // Rolling back removes the `severity` field.
         let resp = warp::test::request()
-            .path(format!("/indexes/hdfs-logs/sources/{INGEST_API_SOURCE_ID}").as_str())
-            .method("DELETE")
-            .body(source_config_body)
+            .path("/indexes/hdfs-logs/config/rollback")
+            .method("POST")
+            .header("x-quickwit-author", "bob")
+            .json(&true)
+            .body(r#"{"version": 1}"#)
             .reply(&index_management_handler)
             .await;
-        assert_eq!(resp.status(), 405);
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(resp_json["doc_mapping_version"], 2);
+        assert_eq!(
+            resp_json["index_config"]["doc_mapping"]["field_mappings"]
+                .as_array()
+                .unwrap()
+                .len(),
+            1
+        );
 
         let resp = warp::test::request()
-            .path(format!("/indexes/hdfs-logs/sources/{CLI_INGEST_SOURCE_ID}").as_str())
-            .method("DELETE")
-            .body(source_config_body)
+            .path("/indexes/hdfs-logs/config/history?limit=1")
             .reply(&index_management_handler)
             .await;
-        assert_eq!(resp.status(), 405);
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        let expected_response_json = serde_json::json!([{
+            "version": 3,
+            "description": "doc mapping rolled back to version 1",
+            "author": "bob",
+        }]);
+        assert_json_include!(actual: resp_json, expected: expected_response_json);
 
-        // Check get a non existing source returns 404.
         let resp = warp::test::request()
-            .path("/indexes/hdfs-logs/sources/file-source")
-            .method("GET")
-            .body(source_config_body)
+            .path("/indexes/hdfs-logs/config/rollback")
+            .method("POST")
+            .json(&true)
+            .body(r#"{"version": 42}"#)
             .reply(&index_management_handler)
             .await;
-        assert_eq!(resp.status(), 404);
+        assert_eq!(resp.status(), 400);
 
-        // Check delete index.
         let resp = warp::test::request()
-            .path("/indexes/hdfs-logs")
-            .method("DELETE")
-            .body(source_config_body)
+            .path("/indexes/does-not-exist/config/history")
             .reply(&index_management_handler)
             .await;
-        assert_eq!(resp.status(), 200);
-        let indexes = metastore
-            .list_indexes_metadata(ListIndexesMetadataRequest::all())
-            .await
-            .unwrap()
-            .deserialize_indexes_metadata()
-            .unwrap();
-        assert!(indexes.is_empty());
+        assert_eq!(resp.status(), 404);
     }
 
     #[tokio::test]
@@ -1684,8 +3449,8 @@ mod tests {
             assert_eq!(resp.status(), 400);
             let body = std::str::from_utf8(resp.body()).unwrap();
             assert!(body.contains(
-                "Quickwit currently supports multiple pipelines only for GCP PubSub or Kafka \
-                 sources"
+                "Quickwit currently supports multiple pipelines only for Azure Event Hubs, GCP \
+                 PubSub, or Kafka sources"
             ));
         }
     }
@@ -1868,6 +3633,335 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_bulk_index_action_toggle_source() {
+        let mut mock_metastore = MetastoreServiceClient::mock();
+        mock_metastore
+            .expect_list_indexes_metadata()
+            .returning(|list_indexes_metadata_request| {
+                assert_eq!(
+                    list_indexes_metadata_request.index_id_patterns,
+                    ["logs-2023-*"]
+                );
+                let mut index_metadata_1 =
+                    IndexMetadata::for_test("logs-2023-01", "ram:///indexes/logs-2023-01");
+                index_metadata_1
+                    .add_source(SourceConfig::for_test("my-source", SourceParams::void()))
+                    .unwrap();
+                let index_metadata_2 =
+                    IndexMetadata::for_test("logs-2023-02", "ram:///indexes/logs-2023-02");
+                let indexes_metadata = vec![index_metadata_1, index_metadata_2];
+                Ok(
+                    ListIndexesMetadataResponse::try_from_indexes_metadata(indexes_metadata)
+                        .unwrap(),
+                )
+            })
+            .times(2);
+        mock_metastore
+            .expect_index_metadata()
+            .returning(|index_metadata_request| {
+                let index_id = index_metadata_request.index_id.unwrap();
+                let index_uri = format!("ram:///indexes/{index_id}");
+                Ok(
+                    IndexMetadataResponse::try_from_index_metadata(IndexMetadata::for_test(
+                        &index_id, &index_uri,
+                    ))
+                    .unwrap(),
+                )
+            })
+            .times(2);
+        mock_metastore
+            .expect_toggle_source()
+            .returning(|toggle_source_request| {
+                let index_uid: IndexUid = toggle_source_request.index_uid.into();
+                assert_eq!(toggle_source_request.source_id, "my-source");
+                assert!(!toggle_source_request.enable);
+
+                if index_uid.index_id() == "logs-2023-01" {
+                    return Ok(EmptyResponse {});
+                }
+                Err(MetastoreError::NotFound(EntityKind::Source {
+                    index_id: index_uid.index_id().to_string(),
+                    source_id: "my-source".to_string(),
+                }))
+            })
+            .times(2);
+        let index_service = IndexService::new(
+            MetastoreServiceClient::from(mock_metastore),
+            StorageResolver::unconfigured(),
+        );
+        let index_management_handler =
+            super::index_management_handlers(index_service, Arc::new(NodeConfig::for_test()))
+                .recover(recover_fn);
+        {
+            let resp = warp::test::request()
+                .path("/indexes/_bulk-action")
+                .method("POST")
+                .json(&true)
+                .body(
+                    r#"{"index_id_patterns": ["logs-2023-*"], "action": {"toggle_source": {"source_id": "my-source", "enable": false}}, "dry_run": true}"#,
+                )
+                .reply(&index_management_handler)
+                .await;
+            assert_eq!(resp.status(), 200);
+            let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+            assert_eq!(resp_json["dry_run"], true);
+            assert_eq!(resp_json["results"][0]["index_id"], "logs-2023-01");
+            assert_eq!(resp_json["results"][0]["status"], "planned");
+            assert_eq!(resp_json["results"][1]["index_id"], "logs-2023-02");
+            assert_eq!(resp_json["results"][1]["status"], "failed");
+            assert!(resp_json["results"][1]["error"]
+                .as_str()
+                .unwrap()
+                .contains("do(es) not exist"));
+        }
+        {
+            let resp = warp::test::request()
+                .path("/indexes/_bulk-action")
+                .method("POST")
+                .json(&true)
+                .body(
+                    r#"{"index_id_patterns": ["logs-2023-*"], "action": {"toggle_source": {"source_id": "my-source", "enable": false}}}"#,
+                )
+                .reply(&index_management_handler)
+                .await;
+            assert_eq!(resp.status(), 200);
+            let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+            assert_eq!(resp_json["dry_run"], false);
+            assert_eq!(resp_json["results"][0]["status"], "applied");
+            assert_eq!(resp_json["results"][1]["status"], "failed");
+        }
+        {
+            let resp = warp::test::request()
+                .path("/indexes/_bulk-action")
+                .method("POST")
+                .json(&true)
+                .body(r#"{"index_id_patterns": [], "action": "delete"}"#)
+                .reply(&index_management_handler)
+                .await;
+            assert_eq!(resp.status(), 400);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bulk_index_action_rejects_match_all_patterns() {
+        let mut mock_metastore = MetastoreServiceClient::mock();
+        mock_metastore
+            .expect_list_indexes_metadata()
+            .returning(|list_indexes_metadata_request| {
+                let index_id_patterns = list_indexes_metadata_request.index_id_patterns;
+                assert!(index_id_patterns == ["logs-*"] || index_id_patterns == ["*"]);
+
+                let indexes_metadata = vec![
+                    IndexMetadata::for_test("logs-2023-01", "ram:///indexes/logs-2023-01"),
+                    IndexMetadata::for_test("logs-2023-02", "ram:///indexes/logs-2023-02"),
+                ];
+                Ok(
+                    ListIndexesMetadataResponse::try_from_indexes_metadata(indexes_metadata)
+                        .unwrap(),
+                )
+            })
+            .times(4);
+        mock_metastore
+            .expect_index_metadata()
+            .returning(|index_metadata_request| {
+                Err(MetastoreError::NotFound(EntityKind::Index {
+                    index_id: index_metadata_request.index_id.unwrap(),
+                }))
+            })
+            .times(2);
+        let index_service = IndexService::new(
+            MetastoreServiceClient::from(mock_metastore),
+            StorageResolver::unconfigured(),
+        );
+        let index_management_handler =
+            super::index_management_handlers(index_service, Arc::new(NodeConfig::for_test()))
+                .recover(recover_fn);
+        {
+            // The `*` pattern is rejected before listing the indexes.
+            let resp = warp::test::request()
+                .path("/indexes/_bulk-action")
+                .method("POST")
+                .json(&true)
+                .body(r#"{"index_id_patterns": ["*"], "action": "delete"}"#)
+                .reply(&index_management_handler)
+                .await;
+            assert_eq!(resp.status(), 400);
+            let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+            assert!(resp_json["message"]
+                .as_str()
+                .unwrap()
+                .contains("confirm_match_all"));
+        }
+        {
+            // So are the patterns matching every index.
+            let resp = warp::test::request()
+                .path("/indexes/_bulk-action")
+                .method("POST")
+                .json(&true)
+                .body(r#"{"index_id_patterns": ["logs-*"], "action": "clear"}"#)
+                .reply(&index_management_handler)
+                .await;
+            assert_eq!(resp.status(), 400);
+        }
+        {
+            let resp = warp::test::request()
+                .path("/indexes/_bulk-action")
+                .method("POST")
+                .json(&true)
+                .body(r#"{"index_id_patterns": ["*"], "action": "clear", "dry_run": true}"#)
+                .reply(&index_management_handler)
+                .await;
+            assert_eq!(resp.status(), 200);
+            let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+            assert_eq!(resp_json["results"][0]["status"], "planned");
+            assert_eq!(resp_json["results"][1]["status"], "planned");
+        }
+        {
+            let resp = warp::test::request()
+                .path("/indexes/_bulk-action")
+                .method("POST")
+                .json(&true)
+                .body(
+                    r#"{"index_id_patterns": ["*"], "action": "clear", "confirm_match_all": true}"#,
+                )
+                .reply(&index_management_handler)
+                .await;
+            assert_eq!(resp.status(), 200);
+            let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+            assert_eq!(resp_json["results"][0]["status"], "failed");
+            assert_eq!(resp_json["results"][1]["status"], "failed");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_source_backlog() {
+        let mut mock_control_plane = ControlPlaneServiceClient::mock();
+        mock_control_plane
+            .expect_get_source_backlog()
+            .return_once(|get_source_backlog_request| {
+                assert_eq!(get_source_backlog_request.index_id, "test-index");
+                assert_eq!(get_source_backlog_request.source_id, "test-source");
+                Ok(GetSourceBacklogResponse {
+                    index_uid: "test-index:0".to_string(),
+                    source_id: "test-source".to_string(),
+                    total_backlog: 42,
+                    partition_backlogs: vec![PartitionBacklog {
+                        partition_id: "0".to_string(),
+                        node_id: "test-indexer".to_string(),
+                        backlog: 42,
+                    }],
+                })
+            });
+        let handler =
+            get_source_backlog_handler(ControlPlaneServiceClient::from(mock_control_plane));
+        let resp = warp::test::request()
+            .path("/indexes/test-index/sources/test-source/backlog")
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let actual_response_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        let expected_response_json = serde_json::json!({
+            "index_uid": "test-index:0",
+            "source_id": "test-source",
+            "total_backlog": 42,
+            "partition_backlogs": [{
+                "partition_id": "0",
+                "node_id": "test-indexer",
+                "backlog": 42,
+            }],
+        });
+        assert_eq!(actual_response_json, expected_response_json);
+    }
+
+    #[tokio::test]
+    async fn test_get_placement_violations() {
+        let mut mock_control_plane = ControlPlaneServiceClient::mock();
+        mock_control_plane
+            .expect_get_placement_violations()
+            .return_once(|_| {
+                Ok(GetPlacementViolationsResponse {
+                    placement_violations: vec![PlacementViolation {
+                        index_uid: "test-index:0".to_string(),
+                        source_id: "test-source".to_string(),
+                        placement_constraints: "zone=eu-west-1a".to_string(),
+                        kind: PlacementViolationKind::Shard as i32,
+                        node_id: "test-ingester".to_string(),
+                        shard_id: Some(1),
+                    }],
+                })
+            });
+        let handler =
+            get_placement_violations_handler(ControlPlaneServiceClient::from(mock_control_plane));
+        let resp = warp::test::request()
+            .path("/indexing/placement-violations")
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let actual_response_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        let expected_response_json = serde_json::json!({
+            "placement_violations": [{
+                "index_uid": "test-index:0",
+                "source_id": "test-source",
+                "placement_constraints": "zone=eu-west-1a",
+                "kind": 3,
+                "node_id": "test-ingester",
+                "shard_id": 1,
+            }],
+        });
+        assert_eq!(actual_response_json, expected_response_json);
+    }
+
+    #[tokio::test]
+    async fn test_suggest_doc_mapping() {
+        let metastore = MetastoreServiceClient::mock();
+        let index_service = IndexService::new(
+            MetastoreServiceClient::from(metastore),
+            StorageResolver::unconfigured(),
+        );
+        let index_management_handler =
+            super::index_management_handlers(index_service, Arc::new(NodeConfig::for_test()))
+                .recover(recover_fn);
+        let resp = warp::test::request()
+            .path("/indexes/doc-mapping/suggest")
+            .method("POST")
+            .body(
+                r#"{"timestamp": "2023-10-10T10:10:10Z", "severity": "INFO", "latency": 1.5}
+
+{"timestamp": "2023-10-10T10:10:11Z", "severity": "WARN", "latency": 2}"#,
+            )
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let actual_response_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        let expected_response_json = serde_json::json!({
+            "doc_mapping": {
+                "mode": "dynamic",
+                "timestamp_field": "timestamp",
+            },
+            "timestamp_field_candidates": ["timestamp"],
+            "num_sampled_docs": 2,
+        });
+        assert_json_include!(
+            actual: actual_response_json,
+            expected: expected_response_json
+        );
+        let field_mappings = actual_response_json["doc_mapping"]["field_mappings"]
+            .as_array()
+            .unwrap();
+        assert_eq!(field_mappings.len(), 3);
+
+        let resp = warp::test::request()
+            .path("/indexes/doc-mapping/suggest")
+            .method("POST")
+            .body("{\"timestamp\": 1}\nnot-json")
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 400);
+        let body = std::str::from_utf8(resp.body()).unwrap();
+        assert!(body.contains("line 2 is not a valid JSON object"));
+    }
+
     #[tokio::test]
     async fn test_analyze_request() {
         let mut metastore = MetastoreServiceClient::mock();
@@ -1913,4 +4007,53 @@ mod tests {
             expected: expected_response_json
         );
     }
+
+    #[tokio::test]
+    async fn test_analyze_index_field() {
+        let mut metastore = MetastoreServiceClient::mock();
+        metastore.expect_index_metadata().times(2).returning(|_| {
+            Ok(
+                IndexMetadataResponse::try_from_index_metadata(IndexMetadata::for_test(
+                    "test-index",
+                    "ram:///indexes/test-index",
+                ))
+                .unwrap(),
+            )
+        });
+        let index_service = IndexService::new(
+            MetastoreServiceClient::from(metastore),
+            StorageResolver::unconfigured(),
+        );
+        let index_management_handler =
+            super::index_management_handlers(index_service, Arc::new(NodeConfig::for_test()))
+                .recover(recover_fn);
+        let resp = warp::test::request()
+            .path("/indexes/test-index/analyze")
+            .method("POST")
+            .json(&serde_json::json!({"field": "body", "text": "Hello, World!"}))
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let actual_response_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        let expected_response_json = serde_json::json!({
+            "field": "body",
+            "tokenizer": "default",
+            "tokens": [
+                {"offset_from": 0, "offset_to": 5, "position": 0, "text": "hello"},
+                {"offset_from": 7, "offset_to": 12, "position": 1, "text": "world"}
+            ]
+        });
+        assert_json_include!(
+            actual: actual_response_json,
+            expected: expected_response_json
+        );
+
+        let resp = warp::test::request()
+            .path("/indexes/test-index/analyze")
+            .method("POST")
+            .json(&serde_json::json!({"field": "response_time", "text": "10"}))
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 400);
+    }
 }
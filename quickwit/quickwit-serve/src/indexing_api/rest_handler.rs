@@ -21,16 +21,41 @@ use std::convert::Infallible;
 
 use quickwit_actors::{AskError, Mailbox, Observe};
 use quickwit_indexing::actors::{IndexingService, IndexingServiceCounters};
+use quickwit_indexing::models::{GetDeadLetters, GetSourceHealth, PauseMerges, ResumeMerges};
+use quickwit_indexing::{DeadLetter, SourceHealthReport};
+use quickwit_proto::indexing::IndexingError;
+use serde::{Deserialize, Serialize};
 use warp::{Filter, Rejection};
 
 use crate::format::extract_format_from_qs;
 use crate::json_api_response::make_json_api_response;
 use crate::require;
 
+/// Default number of dead letters returned by the dead letters endpoint.
+const DEFAULT_NUM_DEAD_LETTERS: usize = 10;
+
 #[derive(utoipa::OpenApi)]
-#[openapi(paths(indexing_endpoint))]
+#[openapi(
+    paths(
+        indexing_endpoint,
+        dead_letters_endpoint,
+        source_health_endpoint,
+        pause_merges_endpoint,
+        resume_merges_endpoint
+    ),
+    components(schemas(MergesStatus))
+)]
 pub struct IndexingApi;
 
+/// Merge status of an index, returned by the pause and resume merges endpoints.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct MergesStatus {
+    pub index_id: String,
+    pub merges_paused: bool,
+    /// Number of merge pipelines of the index running on the node handling the request.
+    pub num_merge_pipelines: usize,
+}
+
 #[utoipa::path(
     get,
     tag = "Indexing",
@@ -52,12 +77,322 @@ fn indexing_get_filter() -> impl Filter<Extract = (), Error = Rejection> + Clone
     warp::path!("indexing").and(warp::get())
 }
 
-pub fn indexing_get_handler(
+pub fn indexing_handlers(
     indexing_service_mailbox_opt: Option<Mailbox<IndexingService>>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
     indexing_get_filter()
-        .and(require(indexing_service_mailbox_opt))
+        .and(require(indexing_service_mailbox_opt.clone()))
         .then(indexing_endpoint)
         .and(extract_format_from_qs())
         .map(make_json_api_response)
+        .or(dead_letters_handler(indexing_service_mailbox_opt.clone()))
+        .or(source_health_handler(indexing_service_mailbox_opt.clone()))
+        .or(pause_merges_handler(indexing_service_mailbox_opt.clone()))
+        .or(resume_merges_handler(indexing_service_mailbox_opt))
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+struct DeadLettersQueryParams {
+    /// Maximum number of dead letters to return. Defaults to 10.
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+#[utoipa::path(
+    get,
+    tag = "Indexing",
+    path = "/indexing/{index_id}/sources/{source_id}/dead-letters",
+    responses(
+        (status = 200, description = "Successfully sampled the most recent invalid documents.", body = [DeadLetter]),
+        (status = 404, description = "No pipeline of the source was spawned on this node."),
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID of the source."),
+        ("source_id" = String, Path, description = "The source ID."),
+        DeadLettersQueryParams,
+    )
+)]
+/// Sample Recent Invalid Documents
+///
+/// Returns the most recent documents rejected by the indexing pipelines of the source running on
+/// this node, most recent first, along with the reason of the rejection.
+async fn dead_letters_endpoint(
+    index_id: String,
+    source_id: String,
+    query_params: DeadLettersQueryParams,
+    indexing_service_mailbox: Mailbox<IndexingService>,
+) -> Result<Vec<DeadLetter>, IndexingError> {
+    let get_dead_letters = GetDeadLetters {
+        index_id: index_id.clone(),
+        source_id: source_id.clone(),
+        limit: query_params.limit.unwrap_or(DEFAULT_NUM_DEAD_LETTERS),
+    };
+    let dead_letters_opt = indexing_service_mailbox
+        .ask(get_dead_letters)
+        .await
+        .map_err(|_| IndexingError::Unavailable)?;
+    dead_letters_opt.ok_or(IndexingError::MissingSource {
+        index_id,
+        source_id,
+    })
+}
+
+fn dead_letters_handler(
+    indexing_service_mailbox_opt: Option<Mailbox<IndexingService>>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexing" / String / "sources" / String / "dead-letters")
+        .and(warp::get())
+        .and(serde_qs::warp::query(serde_qs::Config::default()))
+        .and(require(indexing_service_mailbox_opt))
+        .then(dead_letters_endpoint)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    get,
+    tag = "Indexing",
+    path = "/indexes/{index_id}/sources/{source_id}/health",
+    responses(
+        (status = 200, description = "Successfully fetched the health of the source.", body = SourceHealthReport)
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID of the source."),
+        ("source_id" = String, Path, description = "The source ID."),
+    )
+)]
+/// Get Source Health
+///
+/// Returns the health of the indexing pipelines of the source running on this node: status,
+/// restart counters, and most recent failures.
+async fn source_health_endpoint(
+    index_id: String,
+    source_id: String,
+    indexing_service_mailbox: Mailbox<IndexingService>,
+) -> Result<SourceHealthReport, IndexingError> {
+    let get_source_health = GetSourceHealth {
+        index_id: index_id.clone(),
+        source_id: source_id.clone(),
+    };
+    let source_health_report_opt = indexing_service_mailbox
+        .ask(get_source_health)
+        .await
+        .map_err(|_| IndexingError::Unavailable)?;
+    source_health_report_opt.ok_or(IndexingError::MissingSource {
+        index_id,
+        source_id,
+    })
+}
+
+fn source_health_handler(
+    indexing_service_mailbox_opt: Option<Mailbox<IndexingService>>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexes" / String / "sources" / String / "health")
+        .and(warp::get())
+        .and(require(indexing_service_mailbox_opt))
+        .then(source_health_endpoint)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    put,
+    tag = "Indexing",
+    path = "/indexing/{index_id}/merges/pause",
+    responses(
+        (status = 200, description = "Successfully paused merges.", body = MergesStatus)
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID."),
+    )
+)]
+/// Pause Merges
+///
+/// Pauses the merges of the index on every indexer. Ongoing merges run to completion, while newly
+/// published splits accumulate until merges are resumed. The pause is recorded in the index
+/// metadata, so it applies to respawned merge pipelines and survives restarts.
+async fn pause_merges_endpoint(
+    index_id: String,
+    indexing_service_mailbox: Mailbox<IndexingService>,
+) -> Result<MergesStatus, IndexingError> {
+    let pause_merges = PauseMerges {
+        index_id: index_id.clone(),
+    };
+    let num_merge_pipelines = indexing_service_mailbox.ask_for_res(pause_merges).await?;
+    Ok(MergesStatus {
+        index_id,
+        merges_paused: true,
+        num_merge_pipelines,
+    })
+}
+
+fn pause_merges_handler(
+    indexing_service_mailbox_opt: Option<Mailbox<IndexingService>>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexing" / String / "merges" / "pause")
+        .and(warp::put())
+        .and(require(indexing_service_mailbox_opt))
+        .then(pause_merges_endpoint)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    put,
+    tag = "Indexing",
+    path = "/indexing/{index_id}/merges/resume",
+    responses(
+        (status = 200, description = "Successfully resumed merges.", body = MergesStatus)
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID."),
+    )
+)]
+/// Resume Merges
+///
+/// Resumes the merges of the index on every indexer. The splits published while merges were paused
+/// are planned for merge right away.
+async fn resume_merges_endpoint(
+    index_id: String,
+    indexing_service_mailbox: Mailbox<IndexingService>,
+) -> Result<MergesStatus, IndexingError> {
+    let resume_merges = ResumeMerges {
+        index_id: index_id.clone(),
+    };
+    let num_merge_pipelines = indexing_service_mailbox.ask_for_res(resume_merges).await?;
+    Ok(MergesStatus {
+        index_id,
+        merges_paused: false,
+        num_merge_pipelines,
+    })
+}
+
+fn resume_merges_handler(
+    indexing_service_mailbox_opt: Option<Mailbox<IndexingService>>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexing" / String / "merges" / "resume")
+        .and(warp::put())
+        .and(require(indexing_service_mailbox_opt))
+        .then(resume_merges_endpoint)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+    use std::time::Duration;
+
+    use bytes::Bytes;
+    use quickwit_actors::Universe;
+    use quickwit_cluster::{create_cluster_for_test, ChannelTransport};
+    use quickwit_common::pubsub::EventBroker;
+    use quickwit_config::{
+        DeadLetterQueueConfig, IndexConfig, IndexerConfig, SourceConfig, SourceInputFormat,
+        SourceParams, VecSourceParams,
+    };
+    use quickwit_indexing::models::SpawnPipeline;
+    use quickwit_ingest::IngesterPool;
+    use quickwit_metastore::{metastore_for_test, CreateIndexRequestExt};
+    use quickwit_proto::metastore::{CreateIndexRequest, MetastoreService};
+    use quickwit_proto::types::PipelineUid;
+    use quickwit_storage::StorageResolver;
+    use serde_json::Value as JsonValue;
+
+    use super::*;
+    use crate::recover_fn;
+
+    #[tokio::test]
+    async fn test_dead_letters_handler() {
+        let universe = Universe::with_accelerated_time();
+        let transport = ChannelTransport::default();
+        let cluster = create_cluster_for_test(Vec::new(), &["indexer"], &transport, true)
+            .await
+            .unwrap();
+        let mut metastore = metastore_for_test();
+
+        let index_config = IndexConfig::for_test("test-index", "ram:///indexes/test-index");
+        let create_index_request = CreateIndexRequest::try_from_index_config(index_config).unwrap();
+        metastore.create_index(create_index_request).await.unwrap();
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let indexing_service = IndexingService::new(
+            "test-node".to_string(),
+            temp_dir.path().to_path_buf(),
+            IndexerConfig::for_test().unwrap(),
+            1,
+            cluster,
+            metastore,
+            None,
+            IngesterPool::default(),
+            StorageResolver::unconfigured(),
+            EventBroker::default(),
+        )
+        .await
+        .unwrap();
+        let (indexing_service_mailbox, _indexing_service_handle) =
+            universe.spawn_builder().spawn(indexing_service);
+
+        let source_config = SourceConfig {
+            source_id: "test-source".to_string(),
+            max_num_pipelines_per_indexer: NonZeroUsize::new(1).unwrap(),
+            desired_num_pipelines: NonZeroUsize::new(1).unwrap(),
+            enabled: true,
+            source_params: SourceParams::Vec(VecSourceParams {
+                docs: vec![Bytes::from_static(b"{")],
+                batch_num_docs: 10,
+                partition: "0".to_string(),
+            }),
+            transform_config: None,
+            input_format: SourceInputFormat::Json,
+            csv_config: Default::default(),
+            dead_letter_queue: Some(DeadLetterQueueConfig::File),
+            supervision: Default::default(),
+            fetch_stream: Default::default(),
+            placement_constraints: Default::default(),
+            priority: Default::default(),
+        };
+        indexing_service_mailbox
+            .ask_for_res(SpawnPipeline {
+                index_id: "test-index".to_string(),
+                source_config,
+                pipeline_uid: PipelineUid::default(),
+            })
+            .await
+            .unwrap();
+
+        let indexing_handler =
+            super::indexing_handlers(Some(indexing_service_mailbox)).recover(recover_fn);
+
+        let mut dead_letters_json = JsonValue::Null;
+        for _ in 0..100 {
+            let resp = warp::test::request()
+                .path("/indexing/test-index/sources/test-source/dead-letters")
+                .reply(&indexing_handler)
+                .await;
+            assert_eq!(resp.status(), 200);
+            dead_letters_json = serde_json::from_slice(resp.body()).unwrap();
+
+            if !dead_letters_json.as_array().unwrap().is_empty() {
+                break;
+            }
+            universe.sleep(Duration::from_millis(100)).await;
+        }
+        let dead_letters = dead_letters_json.as_array().unwrap();
+        assert_eq!(dead_letters.len(), 1);
+        assert_eq!(dead_letters[0]["doc"], "{");
+        assert!(dead_letters[0]["reason"]
+            .as_str()
+            .unwrap()
+            .starts_with("parse error"));
+
+        let resp = warp::test::request()
+            .path("/indexing/test-index/sources/unknown-source/dead-letters")
+            .reply(&indexing_handler)
+            .await;
+        assert_eq!(resp.status(), 404);
+
+        universe.assert_quit().await;
+    }
 }
@@ -20,29 +20,31 @@
 use bytes::{Buf, BufMut, Bytes, BytesMut};
 use quickwit_config::{IngestApiConfig, INGEST_SOURCE_ID};
 use quickwit_ingest::{
-    CommitType, DocBatchBuilder, FetchResponse, IngestRequest, IngestResponse, IngestService,
-    IngestServiceClient, IngestServiceError, TailRequest,
+    BatchFailure, BatchPosition, CommitType, DocBatchBuilder, FetchResponse, IngestRequest,
+    IngestResponse, IngestService, IngestServiceClient, IngestServiceError, TailRequest,
 };
 use quickwit_proto::ingest::router::{
     IngestFailureReason, IngestRequestV2, IngestResponseV2, IngestRouterService,
     IngestRouterServiceClient, IngestSubrequest,
 };
-use quickwit_proto::ingest::{DocBatchV2, IngestV2Error};
-use quickwit_proto::types::IndexId;
+use quickwit_proto::ingest::DocBatchV2;
+use quickwit_proto::types::{IndexId, IndexUid};
 use serde::Deserialize;
 use thiserror::Error;
 use warp::{Filter, Rejection};
 
 use crate::format::extract_format_from_qs;
-use crate::json_api_response::make_json_api_response;
+use crate::json_api_response::{make_json_api_response, ApiError};
+use crate::rest::InvalidArgument;
 use crate::{with_arg, BodyFormat};
 
 #[derive(utoipa::OpenApi)]
-#[openapi(paths(ingest, tail_endpoint,))]
+#[openapi(paths(ingest, ingest_v2, tail_endpoint,))]
 pub struct IngestApi;
 
 #[derive(utoipa::OpenApi)]
 #[openapi(components(schemas(
+    quickwit_ingest::BatchFailure,
     quickwit_ingest::DocBatch,
     quickwit_ingest::FetchResponse,
     quickwit_ingest::IngestResponse,
@@ -50,6 +52,10 @@ pub struct IngestApi;
 )))]
 pub struct IngestApiSchemas;
 
+/// Header carrying the idempotency key of an ingest request. Retrying a request with the same key
+/// returns the response of the original request instead of ingesting the documents again.
+const IDEMPOTENCY_KEY_HEADER: &str = "x-idempotency-key";
+
 #[derive(Debug, Error)]
 #[error("request body contains invalid UTF-8 characters")]
 struct InvalidUtf8;
@@ -61,6 +67,9 @@ struct IngestOptions {
     #[serde(alias = "commit")]
     #[serde(default)]
     commit_type: CommitType,
+    /// If set, the response includes the position assigned to each accepted batch.
+    #[serde(default)]
+    return_positions: bool,
 }
 
 pub(crate) fn ingest_api_handlers(
@@ -75,7 +84,8 @@ pub(crate) fn ingest_api_handlers(
 
 fn ingest_filter(
     config: IngestApiConfig,
-) -> impl Filter<Extract = (String, Bytes, IngestOptions), Error = Rejection> + Clone {
+) -> impl Filter<Extract = (String, Bytes, IngestOptions, Option<String>), Error = Rejection> + Clone
+{
     warp::path!(String / "ingest")
         .and(warp::post())
         .and(warp::body::content_length_limit(
@@ -85,6 +95,7 @@ fn ingest_filter(
         .and(serde_qs::warp::query::<IngestOptions>(
             serde_qs::Config::default(),
         ))
+        .and(warp::header::optional::<String>(IDEMPOTENCY_KEY_HEADER))
 }
 
 fn ingest_handler(
@@ -102,6 +113,7 @@ fn ingest_v2_filter(
 ) -> impl Filter<Extract = (String, Bytes, IngestOptions), Error = Rejection> + Clone {
     warp::path!(String / "ingest-v2")
         .and(warp::post())
+        .and(reject_idempotency_key())
         .and(warp::body::content_length_limit(
             config.content_length_limit.as_u64(),
         ))
@@ -111,6 +123,21 @@ fn ingest_v2_filter(
         ))
 }
 
+/// Ingest v2 does not support idempotency keys: requests carrying one are rejected rather than
+/// ingested without deduplication.
+fn reject_idempotency_key() -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::header::optional::<String>(IDEMPOTENCY_KEY_HEADER)
+        .and_then(|idempotency_key_opt: Option<String>| async move {
+            if idempotency_key_opt.map_or(false, |idempotency_key| !idempotency_key.is_empty()) {
+                return Err(warp::reject::custom(InvalidArgument(format!(
+                    "the `{IDEMPOTENCY_KEY_HEADER}` header is not supported by the ingest v2 API"
+                ))));
+            }
+            Ok(())
+        })
+        .untuple_one()
+}
+
 fn ingest_v2_handler(
     ingest_router: IngestRouterServiceClient,
     config: IngestApiConfig,
@@ -122,6 +149,29 @@ fn ingest_v2_handler(
         .map(make_json_api_response)
 }
 
+#[utoipa::path(
+    post,
+    tag = "Ingest",
+    path = "/{index_id}/ingest-v2",
+    request_body(content = String, description = "Documents to ingest in NDJSON format and limited to 10MB", content_type = "application/json"),
+    responses(
+        (status = 200, description = "Successfully ingested some or all of the documents. Rejected documents are reported in `failures`.", body = IngestResponse),
+        (status = 400, description = "Invalid request, for instance carrying an `x-idempotency-key` header. Do not retry.", body = ApiError),
+        (status = 404, description = "Index not found. Do not retry.", body = ApiError),
+        (status = 413, description = "Payload above the content length limit. Split the documents into smaller requests.", body = ApiError),
+        (status = 429, description = "Rate limited. Retry with exponential backoff.", body = ApiError),
+        (status = 500, description = "Internal error. Retrying may ingest the documents twice.", body = ApiError),
+        (status = 503, description = "Ingest service unavailable or no shards available yet. Retry with exponential backoff.", body = ApiError),
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID to add docs to."),
+        ("commit" = Option<CommitType>, Query, description = "Force or wait for commit at the end of the indexing operation."),
+        ("return_positions" = Option<bool>, Query, description = "Include the replication position of the shard that accepted the documents in the response."),
+    )
+)]
+/// Ingest documents (v2)
+///
+/// Documents are persisted and replicated on the ingesters before the response is returned.
 async fn ingest_v2(
     index_id: IndexId,
     body: Bytes,
@@ -150,59 +200,125 @@ async fn ingest_v2(
         commit_type: ingest_options.commit_type as i32,
         subrequests: vec![subrequest],
     };
-    let response = ingest_router
-        .ingest(request)
-        .await
-        .map_err(|err: IngestV2Error| IngestServiceError::Internal(err.to_string()))?;
-    convert_ingest_response_v2(response, num_docs)
+    let response = ingest_router.ingest(request).await?;
+    convert_ingest_response_v2(response, num_docs, ingest_options.return_positions)
 }
 
 fn convert_ingest_response_v2(
     mut response: IngestResponseV2,
     num_docs: usize,
+    return_positions: bool,
 ) -> Result<IngestResponse, IngestServiceError> {
-    let num_responses = response.successes.len() + response.failures.len();
-    if num_responses != 1 {
-        return Err(IngestServiceError::Internal(format!(
-            "Expected a single failure/success, got {}.",
-            num_responses
-        )));
+    // The documents of a subrequest split by a routing rule yield one success or failure per
+    // target index.
+    if response.successes.is_empty() && response.failures.is_empty() {
+        return Err(IngestServiceError::Internal(
+            "Expected at least one failure/success, got none.".to_string(),
+        ));
     }
-    if response.successes.pop().is_some() {
-        return Ok(IngestResponse {
-            num_docs_for_processing: num_docs as u64,
+    // The request fails as a whole only if none of its documents were ingested. Otherwise, the
+    // failures are reported in the response so that clients can retry only the rejected documents.
+    if response.successes.is_empty() {
+        let ingest_failure = response.failures.pop().unwrap();
+        return Err(match ingest_failure.reason() {
+            IngestFailureReason::Unspecified => {
+                IngestServiceError::Internal("Unknown reason".to_string())
+            }
+            IngestFailureReason::IndexNotFound => IngestServiceError::IndexNotFound {
+                index_id: ingest_failure.index_id,
+            },
+            IngestFailureReason::SourceNotFound => IngestServiceError::Internal(format!(
+                "Ingest v2 source not found for index {}",
+                ingest_failure.index_id
+            )),
+            IngestFailureReason::Internal => {
+                IngestServiceError::Internal("Internal error".to_string())
+            }
+            IngestFailureReason::NoShardsAvailable => IngestServiceError::Unavailable,
+            IngestFailureReason::RateLimited => IngestServiceError::RateLimited,
+            IngestFailureReason::ResourceExhausted => IngestServiceError::RateLimited,
         });
     }
-    let ingest_failure = response.failures.pop().unwrap();
-    Err(match ingest_failure.reason() {
-        IngestFailureReason::Unspecified => {
-            IngestServiceError::Internal("Unknown reason".to_string())
-        }
-        IngestFailureReason::IndexNotFound => IngestServiceError::IndexNotFound {
+    let mut num_rejected_docs = 0;
+    let mut failures = Vec::with_capacity(response.failures.len());
+
+    for ingest_failure in response.failures {
+        let reason = ingest_failure.reason();
+        num_rejected_docs += ingest_failure.num_docs as u64;
+
+        failures.push(BatchFailure {
             index_id: ingest_failure.index_id,
-        },
-        IngestFailureReason::SourceNotFound => IngestServiceError::Internal(format!(
-            "Ingest v2 source not found for index {}",
-            ingest_failure.index_id
-        )),
-        IngestFailureReason::Internal => IngestServiceError::Internal("Internal error".to_string()),
-        IngestFailureReason::NoShardsAvailable => IngestServiceError::Unavailable,
-        IngestFailureReason::RateLimited => IngestServiceError::RateLimited,
-        IngestFailureReason::ResourceExhausted => IngestServiceError::RateLimited,
+            source_id: ingest_failure.source_id,
+            num_docs: ingest_failure.num_docs as u64,
+            reason: ingest_failure_reason_name(reason).to_string(),
+            retryable: !matches!(
+                reason,
+                IngestFailureReason::IndexNotFound | IngestFailureReason::SourceNotFound
+            ),
+        });
+    }
+    let mut positions = Vec::new();
+
+    let successes = if return_positions {
+        response.successes
+    } else {
+        Vec::new()
+    };
+    for ingest_success in successes {
+        let position_inclusive_opt = ingest_success
+            .replication_position_inclusive
+            .as_ref()
+            .and_then(|position| position.as_u64());
+
+        if let Some(position_inclusive) = position_inclusive_opt {
+            positions.push(BatchPosition {
+                index_id: IndexUid::from(ingest_success.index_uid)
+                    .index_id()
+                    .to_string(),
+                source_id: Some(ingest_success.source_id),
+                shard_id: Some(ingest_success.shard_id),
+                position_inclusive,
+            });
+        }
+    }
+    Ok(IngestResponse {
+        num_docs_for_processing: (num_docs as u64).saturating_sub(num_rejected_docs),
+        positions,
+        failures,
     })
 }
 
+fn ingest_failure_reason_name(reason: IngestFailureReason) -> &'static str {
+    match reason {
+        IngestFailureReason::Unspecified => "unspecified",
+        IngestFailureReason::IndexNotFound => "index_not_found",
+        IngestFailureReason::SourceNotFound => "source_not_found",
+        IngestFailureReason::Internal => "internal",
+        IngestFailureReason::NoShardsAvailable => "no_shards_available",
+        IngestFailureReason::RateLimited => "rate_limited",
+        IngestFailureReason::ResourceExhausted => "resource_exhausted",
+    }
+}
+
 #[utoipa::path(
     post,
     tag = "Ingest",
     path = "/{index_id}/ingest",
     request_body(content = String, description = "Documents to ingest in NDJSON format and limited to 10MB", content_type = "application/json"),
     responses(
-        (status = 200, description = "Successfully ingested documents.", body = IngestResponse)
+        (status = 200, description = "Successfully ingested documents.", body = IngestResponse),
+        (status = 400, description = "Invalid request. Do not retry.", body = ApiError),
+        (status = 404, description = "Index not found. Do not retry.", body = ApiError),
+        (status = 413, description = "Payload above the content length limit. Split the documents into smaller requests.", body = ApiError),
+        (status = 429, description = "Rate limited. Retry with exponential backoff.", body = ApiError),
+        (status = 500, description = "Internal error. Retry with the same idempotency key.", body = ApiError),
+        (status = 503, description = "Ingest service unavailable. Retry with exponential backoff.", body = ApiError),
     ),
     params(
         ("index_id" = String, Path, description = "The index ID to add docs to."),
         ("commit" = Option<CommitType>, Query, description = "Force or wait for commit at the end of the indexing operation."),
+        ("return_positions" = Option<bool>, Query, description = "Include the queue position assigned to each accepted batch in the response."),
+        ("x-idempotency-key" = Option<String>, Header, description = "Key identifying the request. Retrying a request with the same key returns the original response without ingesting the documents again."),
     )
 )]
 /// Ingest documents
@@ -210,8 +326,13 @@ async fn ingest(
     index_id: String,
     body: Bytes,
     ingest_options: IngestOptions,
+    idempotency_key_opt: Option<String>,
     mut ingest_service: IngestServiceClient,
 ) -> Result<IngestResponse, IngestServiceError> {
+    // Idempotency keys are scoped to the target index.
+    let idempotency_key = idempotency_key_opt
+        .filter(|idempotency_key| !idempotency_key.is_empty())
+        .map(|idempotency_key| format!("{index_id}/{idempotency_key}"));
     // The size of the body should be an upper bound of the size of the batch. The removal of the
     // end of line character for each doc compensates the addition of the `DocCommand` header.
     let mut doc_batch_builder = DocBatchBuilder::with_capacity(index_id, body.remaining());
@@ -221,6 +342,8 @@ async fn ingest(
     let ingest_req = IngestRequest {
         doc_batches: vec![doc_batch_builder.build()],
         commit: ingest_options.commit_type.into(),
+        idempotency_key,
+        return_positions: ingest_options.return_positions,
     };
     let ingest_response = ingest_service.ingest(ingest_req).await?;
     Ok(ingest_response)
@@ -274,16 +397,22 @@ pub(crate) mod tests {
     use bytes::Bytes;
     use bytesize::ByteSize;
     use quickwit_actors::{Mailbox, Universe};
-    use quickwit_config::IngestApiConfig;
+    use quickwit_config::{IngestApiConfig, INGEST_SOURCE_ID};
     use quickwit_ingest::{
-        init_ingest_api, CreateQueueIfNotExistsRequest, FetchRequest, FetchResponse,
-        IngestApiService, IngestResponse, IngestServiceClient, SuggestTruncateRequest,
-        QUEUES_DIR_NAME,
+        init_ingest_api, BatchFailure, BatchPosition, CreateQueueIfNotExistsRequest, FetchRequest,
+        FetchResponse, IngestApiService, IngestResponse, IngestServiceClient, IngestServiceError,
+        SuggestTruncateRequest, QUEUES_DIR_NAME,
+    };
+    use quickwit_proto::ingest::router::{
+        IngestFailure, IngestFailureReason, IngestResponseV2, IngestRouterServiceClient,
+        IngestSuccess,
     };
-    use quickwit_proto::ingest::router::IngestRouterServiceClient;
+    use quickwit_proto::types::Position;
+    use serde_json::Value as JsonValue;
 
-    use super::ingest_api_handlers;
+    use super::{convert_ingest_response_v2, ingest_api_handlers, ingest_v2_filter};
     use crate::ingest_api::lines;
+    use crate::rest::InvalidArgument;
 
     #[test]
     fn test_process_lines() {
@@ -391,6 +520,229 @@ pub(crate) mod tests {
         universe.assert_quit().await;
     }
 
+    #[tokio::test]
+    async fn test_ingest_api_with_idempotency_key() {
+        let (universe, _temp_dir, ingest_service, _) =
+            setup_ingest_service(&["my-index"], &IngestApiConfig::default()).await;
+        let ingest_router = IngestRouterServiceClient::mock().into();
+        let ingest_api_handlers =
+            ingest_api_handlers(ingest_router, ingest_service, IngestApiConfig::default());
+
+        for payload in [
+            r#"{"id": 1, "message": "push"}"#,
+            // The original response is returned even if the payload differs.
+            "{\"id\": 1, \"message\": \"push\"}\n{\"id\": 2, \"message\": \"push\"}",
+        ] {
+            let resp = warp::test::request()
+                .path("/my-index/ingest")
+                .method("POST")
+                .header("x-idempotency-key", "my-idempotency-key")
+                .body(payload)
+                .reply(&ingest_api_handlers)
+                .await;
+            assert_eq!(resp.status(), 200);
+            let ingest_response: IngestResponse = serde_json::from_slice(resp.body()).unwrap();
+            assert_eq!(ingest_response.num_docs_for_processing, 1);
+        }
+        let resp = warp::test::request()
+            .path("/my-index/tail")
+            .method("GET")
+            .reply(&ingest_api_handlers)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let fetch_response: FetchResponse = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(fetch_response.doc_batch.unwrap().num_docs(), 1);
+
+        universe.assert_quit().await;
+    }
+
+    #[tokio::test]
+    async fn test_ingest_v2_rejects_idempotency_key() {
+        let ingest_v2_filter = ingest_v2_filter(IngestApiConfig::default());
+        let rejection = warp::test::request()
+            .path("/my-index/ingest-v2")
+            .method("POST")
+            .header("x-idempotency-key", "my-idempotency-key")
+            .body(r#"{"id": 1, "message": "push"}"#)
+            .filter(&ingest_v2_filter)
+            .await
+            .unwrap_err();
+        let invalid_argument = rejection.find::<InvalidArgument>().unwrap();
+        assert_eq!(
+            invalid_argument.0,
+            "the `x-idempotency-key` header is not supported by the ingest v2 API"
+        );
+
+        let (index_id, _body, _ingest_options) = warp::test::request()
+            .path("/my-index/ingest-v2")
+            .method("POST")
+            .body(r#"{"id": 1, "message": "push"}"#)
+            .filter(&ingest_v2_filter)
+            .await
+            .unwrap();
+        assert_eq!(index_id, "my-index");
+    }
+
+    #[tokio::test]
+    async fn test_ingest_api_returns_positions() {
+        let (universe, _temp_dir, ingest_service, _) =
+            setup_ingest_service(&["my-index"], &IngestApiConfig::default()).await;
+        let ingest_router = IngestRouterServiceClient::mock().into();
+        let ingest_api_handlers =
+            ingest_api_handlers(ingest_router, ingest_service, IngestApiConfig::default());
+
+        let resp = warp::test::request()
+            .path("/my-index/ingest")
+            .method("POST")
+            .body(r#"{"id": 1, "message": "push"}"#)
+            .reply(&ingest_api_handlers)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let ingest_response: IngestResponse = serde_json::from_slice(resp.body()).unwrap();
+        assert!(ingest_response.positions.is_empty());
+
+        let resp = warp::test::request()
+            .path("/my-index/ingest?return_positions=true")
+            .method("POST")
+            .body("{\"id\": 2, \"message\": \"push\"}\n{\"id\": 3, \"message\": \"push\"}")
+            .reply(&ingest_api_handlers)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let ingest_response: IngestResponse = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(ingest_response.num_docs_for_processing, 2);
+        assert_eq!(
+            ingest_response.positions,
+            [BatchPosition {
+                index_id: "my-index".to_string(),
+                source_id: None,
+                shard_id: None,
+                position_inclusive: 2,
+            }]
+        );
+        universe.assert_quit().await;
+    }
+
+    #[test]
+    fn test_convert_ingest_response_v2_with_positions() {
+        let response = IngestResponseV2 {
+            successes: vec![IngestSuccess {
+                subrequest_id: 0,
+                index_uid: "my-index:00000000000000000000000000".to_string(),
+                source_id: INGEST_SOURCE_ID.to_string(),
+                shard_id: 1,
+                replication_position_inclusive: Some(Position::offset(41u64)),
+                num_docs: 42,
+            }],
+            failures: Vec::new(),
+        };
+        let ingest_response = convert_ingest_response_v2(response.clone(), 42, false).unwrap();
+        assert_eq!(ingest_response.num_docs_for_processing, 42);
+        assert!(ingest_response.positions.is_empty());
+
+        let ingest_response = convert_ingest_response_v2(response, 42, true).unwrap();
+        assert_eq!(
+            ingest_response.positions,
+            [BatchPosition {
+                index_id: "my-index".to_string(),
+                source_id: Some(INGEST_SOURCE_ID.to_string()),
+                shard_id: Some(1),
+                position_inclusive: 41,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_convert_ingest_response_v2_with_routed_docs() {
+        let success = |index_uid: &str, shard_id: u64| IngestSuccess {
+            subrequest_id: 0,
+            index_uid: index_uid.to_string(),
+            source_id: INGEST_SOURCE_ID.to_string(),
+            shard_id,
+            replication_position_inclusive: Some(Position::offset(shard_id)),
+            num_docs: 20,
+        };
+        let response = IngestResponseV2 {
+            successes: vec![
+                success("logs-foo:00000000000000000000000000", 1),
+                success("logs-bar:00000000000000000000000000", 2),
+            ],
+            failures: Vec::new(),
+        };
+        let ingest_response = convert_ingest_response_v2(response.clone(), 42, true).unwrap();
+        assert_eq!(ingest_response.num_docs_for_processing, 42);
+        assert_eq!(ingest_response.positions.len(), 2);
+        assert_eq!(ingest_response.positions[1].index_id, "logs-bar");
+        assert!(ingest_response.failures.is_empty());
+
+        let response = IngestResponseV2 {
+            failures: vec![IngestFailure {
+                subrequest_id: 0,
+                index_id: "logs-unrouted".to_string(),
+                source_id: INGEST_SOURCE_ID.to_string(),
+                reason: IngestFailureReason::IndexNotFound as i32,
+                num_docs: 2,
+            }],
+            ..response
+        };
+        let ingest_response = convert_ingest_response_v2(response, 42, true).unwrap();
+        assert_eq!(ingest_response.num_docs_for_processing, 40);
+        assert_eq!(ingest_response.positions.len(), 2);
+        assert_eq!(
+            ingest_response.failures,
+            [BatchFailure {
+                index_id: "logs-unrouted".to_string(),
+                source_id: INGEST_SOURCE_ID.to_string(),
+                num_docs: 2,
+                reason: "index_not_found".to_string(),
+                retryable: false,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_convert_ingest_response_v2_with_failures() {
+        let response = IngestResponseV2 {
+            successes: vec![IngestSuccess {
+                subrequest_id: 0,
+                index_uid: "logs-foo:00000000000000000000000000".to_string(),
+                source_id: INGEST_SOURCE_ID.to_string(),
+                shard_id: 1,
+                replication_position_inclusive: Some(Position::offset(9u64)),
+                num_docs: 10,
+            }],
+            failures: vec![IngestFailure {
+                subrequest_id: 0,
+                index_id: "logs-bar".to_string(),
+                source_id: INGEST_SOURCE_ID.to_string(),
+                reason: IngestFailureReason::RateLimited as i32,
+                num_docs: 32,
+            }],
+        };
+        let ingest_response = convert_ingest_response_v2(response.clone(), 42, false).unwrap();
+        assert_eq!(ingest_response.num_docs_for_processing, 10);
+        assert!(ingest_response.positions.is_empty());
+        assert_eq!(
+            ingest_response.failures,
+            [BatchFailure {
+                index_id: "logs-bar".to_string(),
+                source_id: INGEST_SOURCE_ID.to_string(),
+                num_docs: 32,
+                reason: "rate_limited".to_string(),
+                retryable: true,
+            }]
+        );
+        let response_json = serde_json::to_value(&ingest_response).unwrap();
+        assert_eq!(response_json["failures"][0]["reason"], "rate_limited");
+
+        // The request fails as a whole when none of the documents were ingested.
+        let response = IngestResponseV2 {
+            successes: Vec::new(),
+            ..response
+        };
+        let error = convert_ingest_response_v2(response, 32, false).unwrap_err();
+        assert!(matches!(error, IngestServiceError::RateLimited));
+    }
+
     #[tokio::test]
     async fn test_ingest_api_return_429_if_above_limits() {
         let config = IngestApiConfig {
@@ -410,6 +762,9 @@ pub(crate) mod tests {
             .reply(&ingest_api_handlers)
             .await;
         assert_eq!(resp.status(), 429);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(resp_json["code"], "rate_limited");
+        assert_eq!(resp_json["retryable"], true);
         universe.assert_quit().await;
     }
 
@@ -22,19 +22,44 @@ use hyper::http::{status, HeaderValue};
 use hyper::{Body, Response};
 use quickwit_proto::{ServiceError, ServiceErrorCode};
 use serde::{self, Serialize};
+use serde_json::Value as JsonValue;
 use warp::Reply;
 
 use crate::format::BodyFormat;
 
 const JSON_SERIALIZATION_ERROR: &str = "JSON serialization failed.";
 
-#[derive(Serialize)]
+/// Body of the responses of the failed REST API requests.
+#[derive(Debug, Serialize, utoipa::ToSchema)]
 pub(crate) struct ApiError {
-    // For now, we want to keep ApiError as simple as possible
-    // and return just a message.
-    #[serde(skip_serializing)]
+    /// Machine-readable code of the error.
+    #[serde(rename = "code")]
     pub service_code: ServiceErrorCode,
+    /// Human-readable description of the error.
     pub message: String,
+    /// Additional context about the error, for instance how long to wait before retrying a
+    /// rate-limited request.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub details: Option<JsonValue>,
+    /// Whether the request may succeed if retried as is.
+    pub retryable: bool,
+}
+
+impl ApiError {
+    pub fn new(service_code: ServiceErrorCode, message: impl Into<String>) -> Self {
+        ApiError {
+            service_code,
+            message: message.into(),
+            details: None,
+            retryable: service_code.is_retryable(),
+        }
+    }
+
+    pub fn with_details(mut self, details: JsonValue) -> Self {
+        self.details = Some(details);
+        self
+    }
 }
 
 impl ServiceError for ApiError {
@@ -49,6 +74,10 @@ impl ToString for ApiError {
     }
 }
 
+#[derive(utoipa::OpenApi)]
+#[openapi(components(schemas(ApiError, ServiceErrorCode)))]
+pub struct ApiErrorSchemas;
+
 /// Makes a JSON API response from a result.
 /// The error is wrapped into an [`ApiError`] to publicly expose
 /// a consistent error format.
@@ -56,10 +85,8 @@ pub(crate) fn make_json_api_response<T: serde::Serialize, E: ServiceError>(
     result: Result<T, E>,
     format: BodyFormat,
 ) -> JsonApiResponse {
-    let result_with_api_error = result.map_err(|err| ApiError {
-        service_code: err.error_code(),
-        message: err.to_string(),
-    });
+    let result_with_api_error =
+        result.map_err(|err| ApiError::new(err.error_code(), err.to_string()));
     let status_code = match &result_with_api_error {
         Ok(_) => status::StatusCode::OK,
         Err(err) => err.error_code().to_http_status_code(),
@@ -96,11 +123,47 @@ impl Reply for JsonApiResponse {
                 *response.status_mut() = self.status_code;
                 response
             }
-            Err(()) => warp::reply::json(&ApiError {
-                service_code: ServiceErrorCode::Internal,
-                message: JSON_SERIALIZATION_ERROR.to_string(),
-            })
+            Err(()) => warp::reply::json(&ApiError::new(
+                ServiceErrorCode::Internal,
+                JSON_SERIALIZATION_ERROR,
+            ))
             .into_response(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_make_json_api_response_error() {
+        let api_error = ApiError::new(ServiceErrorCode::NotFound, "index `foo` not found");
+        let response =
+            make_json_api_response::<(), _>(Err(api_error), BodyFormat::default()).into_response();
+        assert_eq!(response.status(), 404);
+    }
+
+    #[test]
+    fn test_api_error_serialization() {
+        let api_error = ApiError::new(ServiceErrorCode::BadRequest, "failed to parse query");
+        let api_error_json = serde_json::to_value(&api_error).unwrap();
+        let expected_api_error_json = serde_json::json!({
+            "code": "bad_request",
+            "message": "failed to parse query",
+            "retryable": false,
+        });
+        assert_eq!(api_error_json, expected_api_error_json);
+
+        let api_error = ApiError::new(ServiceErrorCode::RateLimited, "too many requests")
+            .with_details(serde_json::json!({"retry_after_secs": 3}));
+        let api_error_json = serde_json::to_value(&api_error).unwrap();
+        let expected_api_error_json = serde_json::json!({
+            "code": "rate_limited",
+            "message": "too many requests",
+            "details": {"retry_after_secs": 3},
+            "retryable": true,
+        });
+        assert_eq!(api_error_json, expected_api_error_json);
+    }
+}
@@ -17,6 +17,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+mod arrow_flight_api;
 mod build_info;
 mod cluster_api;
 mod delete_task_api;
@@ -35,7 +36,10 @@ mod openapi;
 mod rate_modulator;
 mod rest;
 mod search_api;
+mod search_query_limiter;
+mod searcher_config_api;
 pub(crate) mod simple_list;
+mod tls;
 mod ui_handler;
 
 use std::collections::{HashMap, HashSet};
@@ -56,6 +60,7 @@ use quickwit_actors::{ActorExitStatus, Mailbox, Universe};
 use quickwit_cluster::{
     start_cluster_service, Cluster, ClusterChange, ClusterMember, ListenerHandle,
 };
+use quickwit_common::metrics::enable_index_labels;
 use quickwit_common::pubsub::{EventBroker, EventSubscriptionHandle};
 use quickwit_common::rate_limiter::RateLimiterSettings;
 use quickwit_common::runtimes::RuntimesConfig;
@@ -70,11 +75,12 @@ use quickwit_control_plane::{IndexerNodeInfo, IndexerPool};
 use quickwit_index_management::{IndexService as IndexManager, IndexServiceError};
 use quickwit_indexing::actors::IndexingService;
 use quickwit_indexing::models::ShardPositionsService;
-use quickwit_indexing::start_indexing_service;
+use quickwit_indexing::{setup_source_backlog_listener, start_indexing_service};
 use quickwit_ingest::{
-    setup_local_shards_update_listener, start_ingest_api_service, wait_for_ingester_decommission,
-    GetMemoryCapacity, IngestApiService, IngestRequest, IngestRouter, IngestServiceClient,
-    Ingester, IngesterPool, LocalShardsUpdate,
+    setup_ingester_disk_watermark_listener, setup_local_shards_update_listener,
+    start_ingest_api_service, wait_for_ingester_decommission, DiskWatermark, GetMemoryCapacity,
+    IngestApiService, IngestRequest, IngestRouter, IngestServiceClient, Ingester,
+    IngesterDiskWatermarkUpdate, IngesterPool, LocalShardsUpdate,
 };
 use quickwit_janitor::{start_janitor_service, JanitorService};
 use quickwit_metastore::{
@@ -82,9 +88,10 @@ use quickwit_metastore::{
 };
 use quickwit_opentelemetry::otlp::{OtlpGrpcLogsService, OtlpGrpcTracesService};
 use quickwit_proto::control_plane::ControlPlaneServiceClient;
-use quickwit_proto::indexing::{IndexingServiceClient, ShardPositionsUpdate};
+use quickwit_proto::indexing::{IndexingServiceClient, ShardPositionsUpdate, SourceBacklogUpdate};
 use quickwit_proto::ingest::ingester::IngesterServiceClient;
 use quickwit_proto::ingest::router::IngestRouterServiceClient;
+use quickwit_proto::metastore::events::IndexChangeEvent;
 use quickwit_proto::metastore::{
     EntityKind, ListIndexesMetadataRequest, MetastoreError, MetastoreService,
     MetastoreServiceClient,
@@ -103,12 +110,18 @@ use tracing::{debug, error, info, warn};
 use warp::{Filter, Rejection};
 
 pub use crate::build_info::{BuildInfo, RuntimeInfo};
-pub use crate::index_api::{ListSplitsQueryParams, ListSplitsResponse};
+pub use crate::index_api::{
+    CloneIndexRequest, ListSplitsQueryParams, ListSplitsResponse, RestoreIndexRequest,
+    SnapshotIndexRequest,
+};
+pub use crate::indexing_api::MergesStatus;
 pub use crate::metrics::SERVE_METRICS;
+pub use crate::openapi::build_docs;
 use crate::rate_modulator::RateModulator;
 #[cfg(test)]
 use crate::rest::recover_fn;
 pub use crate::search_api::{search_request_from_api_request, SearchRequestQueryString, SortBy};
+use crate::searcher_config_api::setup_searcher_config_overrides_listener;
 
 const READINESS_REPORTING_INTERVAL: Duration = if cfg!(any(test, feature = "testsuite")) {
     Duration::from_millis(25)
@@ -134,11 +147,15 @@ struct QuickwitServices {
     /// It is only used to serve the rest API calls and will only execute
     /// the root requests.
     pub search_service: Arc<dyn SearchService>,
+    pub searcher_context: Arc<SearcherContext>,
 
     /// The control plane listens to various events.
     /// We must maintain a reference to the subscription handles to continue receiving
     /// notifications. Otherwise, the subscriptions are dropped.
     _local_shards_update_listener_handle_opt: Option<ListenerHandle>,
+    _ingester_disk_watermark_listener_handle_opt: Option<ListenerHandle>,
+    _source_backlog_listener_handle_opt: Option<ListenerHandle>,
+    _searcher_config_overrides_listener_handle_opt: Option<ListenerHandle>,
     _report_splits_subscription_handle_opt: Option<EventSubscriptionHandle>,
 }
 
@@ -264,6 +281,15 @@ pub async fn serve_quickwit(
     storage_resolver: StorageResolver,
     shutdown_signal: BoxFutureInfaillible<()>,
 ) -> anyhow::Result<HashMap<String, ActorExitStatus>> {
+    if node_config.metrics_config.per_index_labels {
+        enable_index_labels(node_config.metrics_config.max_index_labels);
+    }
+    // TLS must be enabled before the cluster service starts creating channels to the other nodes.
+    let tls_cert_resolver_opt = node_config
+        .tls_config
+        .as_ref()
+        .map(tls::start_tls)
+        .transpose()?;
     let cluster = start_cluster_service(&node_config).await?;
 
     let event_broker = EventBroker::default();
@@ -330,6 +356,14 @@ pub async fn serve_quickwit(
     )
     .await?;
 
+    // The control plane syncs its model with the index changes made by other nodes or clusters
+    // sharing the same metastore if the metastore backend notifies them.
+    if node_config.is_service_enabled(QuickwitService::ControlPlane) {
+        metastore_resolver
+            .listen_index_changes(&node_config.metastore_uri, event_broker.clone())
+            .await?;
+    }
+
     // If one of the two following service is enabled, we need to enable the shard position service:
     // - the control plane: as it is in charge of cleaning up shard reach eof.
     // - the indexer: as it hosts ingesters, and ingesters use the shard positions to truncate
@@ -434,6 +468,9 @@ pub async fn serve_quickwit(
             None
         };
 
+    quickwit_query::query_ast::set_max_regex_automaton_states(
+        node_config.searcher_config.max_regex_automaton_states,
+    );
     let searcher_context = Arc::new(SearcherContext::new(
         node_config.searcher_config.clone(),
         split_cache_opt,
@@ -443,10 +480,15 @@ pub async fn serve_quickwit(
         cluster_change_stream,
         metastore_through_control_plane.clone(),
         storage_resolver.clone(),
-        searcher_context,
+        searcher_context.clone(),
     )
     .await?;
 
+    // All the nodes apply the searcher config overrides adjusted at runtime with the searcher
+    // config API.
+    let searcher_config_overrides_listener_handle =
+        setup_searcher_config_overrides_listener(cluster.clone(), searcher_context.clone()).await;
+
     // The control plane listens for local shards updates to learn about each shard's ingestion
     // throughput. Ingesters (routers) do so to update their shard table.
     let local_shards_update_listener_handle_opt = if node_config
@@ -458,6 +500,24 @@ pub async fn serve_quickwit(
         None
     };
 
+    // The control plane does not place new shards on ingesters whose disk usage exceeds the high
+    // watermark.
+    let ingester_disk_watermark_listener_handle_opt = if node_config
+        .is_service_enabled(QuickwitService::ControlPlane)
+    {
+        Some(setup_ingester_disk_watermark_listener(cluster.clone(), event_broker.clone()).await)
+    } else {
+        None
+    };
+
+    // The control plane aggregates the partition backlogs reported by the indexing pipelines.
+    let source_backlog_listener_handle_opt =
+        if node_config.is_service_enabled(QuickwitService::ControlPlane) {
+            Some(setup_source_backlog_listener(cluster.clone(), event_broker.clone()).await)
+        } else {
+            None
+        };
+
     let report_splits_subscription_handle_opt =
         // DISCLAIMER: This is quirky here: We base our decision to forward the split report depending
         // on the current searcher configuration.
@@ -492,6 +552,11 @@ pub async fn serve_quickwit(
         metastore_client: metastore_through_control_plane.clone(),
         control_plane_service,
         _local_shards_update_listener_handle_opt: local_shards_update_listener_handle_opt,
+        _ingester_disk_watermark_listener_handle_opt: ingester_disk_watermark_listener_handle_opt,
+        _source_backlog_listener_handle_opt: source_backlog_listener_handle_opt,
+        _searcher_config_overrides_listener_handle_opt: Some(
+            searcher_config_overrides_listener_handle,
+        ),
         _report_splits_subscription_handle_opt: report_splits_subscription_handle_opt,
         index_manager,
         indexing_service_opt,
@@ -500,6 +565,7 @@ pub async fn serve_quickwit(
         ingester_service_opt: ingester_service_opt.clone(),
         janitor_service_opt,
         search_service,
+        searcher_context,
     });
     // Setup and start gRPC server.
     let (grpc_readiness_trigger_tx, grpc_readiness_signal_rx) = oneshot::channel::<()>();
@@ -514,8 +580,12 @@ pub async fn serve_quickwit(
             debug!("gRPC server shutdown trigger sender was dropped");
         }
     });
+    let grpc_tls_acceptor_opt = tls_cert_resolver_opt
+        .clone()
+        .map(|cert_resolver| tls::make_tls_acceptor(cert_resolver, tls::GRPC_ALPN_PROTOCOLS));
     let grpc_server = grpc::start_grpc_server(
         grpc_listen_addr,
+        grpc_tls_acceptor_opt,
         quickwit_services.clone(),
         grpc_readiness_trigger,
         grpc_shutdown_signal,
@@ -533,8 +603,11 @@ pub async fn serve_quickwit(
             debug!("REST server shutdown trigger sender was dropped");
         }
     });
+    let rest_tls_acceptor_opt = tls_cert_resolver_opt
+        .map(|cert_resolver| tls::make_tls_acceptor(cert_resolver, tls::REST_ALPN_PROTOCOLS));
     let rest_server = rest::start_rest_server(
         rest_listen_addr,
+        rest_tls_acceptor_opt,
         quickwit_services,
         rest_readiness_trigger,
         rest_shutdown_signal,
@@ -599,7 +672,9 @@ async fn setup_ingest_v2(
         control_plane,
         ingester_pool.clone(),
         replication_factor,
-    );
+    )
+    .with_routing_rules(&config.ingest_api_config.routing_rules)?
+    .with_persist_hedging_delay(config.ingest_api_config.persist_hedging_delay());
     ingest_router.subscribe(event_broker);
     let ingest_router_service = IngestRouterServiceClient::new(ingest_router);
 
@@ -616,14 +691,20 @@ async fn setup_ingest_v2(
     let ingester_service_opt = if config.is_service_enabled(QuickwitService::Indexer) {
         let wal_dir_path = config.data_dir_path.join("wal");
         fs::create_dir_all(&wal_dir_path)?;
+        let disk_watermark = DiskWatermark::new(
+            config.ingest_api_config.disk_high_watermark(),
+            config.ingest_api_config.disk_low_watermark(),
+        );
         let ingester = Ingester::try_new(
             cluster.clone(),
             ingester_pool.clone(),
             &wal_dir_path,
             config.ingest_api_config.max_queue_disk_usage,
             config.ingest_api_config.max_queue_memory_usage,
+            disk_watermark,
             rate_limiter_settings,
             replication_factor,
+            config.ingest_api_config.shard_idle_timeout(),
         )
         .await?;
         ingester.subscribe(event_broker);
@@ -736,7 +817,16 @@ async fn setup_control_plane(
         .subscribe::<LocalShardsUpdate>(subscriber.clone())
         .forever();
     event_broker
-        .subscribe::<ShardPositionsUpdate>(subscriber)
+        .subscribe::<IngesterDiskWatermarkUpdate>(subscriber.clone())
+        .forever();
+    event_broker
+        .subscribe::<ShardPositionsUpdate>(subscriber.clone())
+        .forever();
+    event_broker
+        .subscribe::<SourceBacklogUpdate>(subscriber.clone())
+        .forever();
+    event_broker
+        .subscribe::<IndexChangeEvent>(subscriber)
         .forever();
 
     Ok(control_plane_mailbox)
@@ -757,6 +847,7 @@ fn setup_indexer_pool(
                     let node_id = node.node_id().to_string();
                     let indexing_tasks = node.indexing_tasks().to_vec();
                     let indexing_capacity = node.indexing_capacity();
+                    let node_labels = node.node_labels().clone();
                     if node.is_self_node() {
                         if let Some(indexing_service_clone) = indexing_service_clone_opt {
                             let client =
@@ -767,6 +858,7 @@ fn setup_indexer_pool(
                                     client,
                                     indexing_tasks,
                                     indexing_capacity,
+                                    node_labels,
                                 },
                             ))
                         } else {
@@ -785,6 +877,7 @@ fn setup_indexer_pool(
                                 client,
                                 indexing_tasks,
                                 indexing_capacity,
+                                node_labels,
                             },
                         ))
                     }
@@ -18,10 +18,16 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 use once_cell::sync::Lazy;
-use quickwit_common::metrics::{new_counter, IntCounter};
+use quickwit_common::metrics::{
+    new_counter, new_counter_vec, new_gauge_vec, IntCounter, IntCounterVec, IntGaugeVec,
+};
 
 pub struct RestMetrics {
     pub http_requests_total: IntCounter,
+    pub search_queries_total: IntCounterVec<1>,
+    pub search_queries_rejected_total: IntCounterVec<1>,
+    pub search_queries_in_flight: IntGaugeVec<1>,
+    pub search_queries_queued: IntGaugeVec<1>,
 }
 
 impl Default for RestMetrics {
@@ -32,6 +38,31 @@ impl Default for RestMetrics {
                 "Total number of HTTP requests received",
                 "quickwit",
             ),
+            search_queries_total: new_counter_vec(
+                "search_queries_total",
+                "Total number of search queries admitted per API key.",
+                "quickwit",
+                ["api_key"],
+            ),
+            search_queries_rejected_total: new_counter_vec(
+                "search_queries_rejected_total",
+                "Total number of search queries rejected per API key because of the API key query \
+                 limits.",
+                "quickwit",
+                ["api_key"],
+            ),
+            search_queries_in_flight: new_gauge_vec(
+                "search_queries_in_flight",
+                "Number of search queries currently executing per API key.",
+                "quickwit",
+                ["api_key"],
+            ),
+            search_queries_queued: new_gauge_vec(
+                "search_queries_queued",
+                "Number of search queries waiting for an execution slot per API key.",
+                "quickwit",
+                ["api_key"],
+            ),
         }
     }
 }
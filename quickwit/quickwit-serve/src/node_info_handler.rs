@@ -104,8 +104,14 @@ mod tests {
         assert_json_include!(actual: build_info_json, expected: expected_build_info_json);
 
         let runtime_info_json = info_json.get("runtime").unwrap();
+        let num_cooperative_indexing_permits = runtime_info
+            .indexer_autotuning
+            .num_cooperative_indexing_permits;
         let expected_runtime_info_json = serde_json::json!({
             "num_cpus_physical": runtime_info.num_cpus_physical,
+            "indexer_autotuning": {
+                "num_cooperative_indexing_permits": num_cooperative_indexing_permits,
+            },
         });
         assert_json_include!(
             actual: runtime_info_json,
@@ -34,9 +34,11 @@ use crate::health_check_api::HealthCheckApi;
 use crate::index_api::IndexApi;
 use crate::indexing_api::IndexingApi;
 use crate::ingest_api::{IngestApi, IngestApiSchemas};
+use crate::json_api_response::ApiErrorSchemas;
 use crate::metrics_api::MetricsApi;
 use crate::node_info_handler::NodeInfoApi;
 use crate::search_api::SearchApi;
+use crate::searcher_config_api::SearcherConfigApi;
 
 /// Builds the OpenApi docs structure using the registered/merged docs.
 pub fn build_docs() -> utoipa::openapi::OpenApi {
@@ -74,6 +76,7 @@ pub fn build_docs() -> utoipa::openapi::OpenApi {
         Tag::new("Node Info"),
         Tag::new("Indexing"),
         Tag::new("Splits"),
+        Tag::new("Searcher Config"),
     ];
     docs_base.tags = Some(tags);
 
@@ -89,6 +92,7 @@ pub fn build_docs() -> utoipa::openapi::OpenApi {
     docs_base
         .merge_components_and_paths(ElasticCompatibleApi::openapi().with_path_prefix("/api/v1"));
     docs_base.merge_components_and_paths(NodeInfoApi::openapi().with_path_prefix("/api/v1"));
+    docs_base.merge_components_and_paths(SearcherConfigApi::openapi().with_path_prefix("/api/v1"));
 
     // Schemas
     docs_base.merge_components_and_paths(MetastoreApiSchemas::openapi());
@@ -97,6 +101,7 @@ pub fn build_docs() -> utoipa::openapi::OpenApi {
     docs_base.merge_components_and_paths(DocMapperApiSchemas::openapi());
     docs_base.merge_components_and_paths(IndexingApiSchemas::openapi());
     docs_base.merge_components_and_paths(IngestApiSchemas::openapi());
+    docs_base.merge_components_and_paths(ApiErrorSchemas::openapi());
 
     docs_base
 }
@@ -170,6 +175,31 @@ mod openapi_schema_tests {
         resolve_openapi_schemas(&docs).expect("All schemas should be resolved.");
     }
 
+    #[test]
+    fn test_ingest_v2_path() {
+        let docs = build_docs();
+        let path_item = docs
+            .paths
+            .paths
+            .get("/api/v1/{index_id}/ingest-v2")
+            .expect("The ingest v2 path should be documented.");
+        let operation = path_item
+            .operations
+            .values()
+            .next()
+            .expect("The ingest v2 path should have an operation.");
+        let status_codes: Vec<&str> = operation
+            .responses
+            .responses
+            .keys()
+            .map(|status_code| status_code.as_str())
+            .collect();
+        assert_eq!(
+            status_codes,
+            ["200", "400", "404", "413", "429", "500", "503"]
+        );
+    }
+
     fn resolve_openapi_schemas(openapi: &utoipa::openapi::OpenApi) -> anyhow::Result<()> {
         let schemas_lookup = if let Some(components) = &openapi.components {
             resolve_component_schemas(components)?
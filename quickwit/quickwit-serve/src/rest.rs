@@ -20,29 +20,46 @@
 use std::net::SocketAddr;
 use std::sync::Arc;
 
+use futures::future::BoxFuture;
+use hyper::header::RETRY_AFTER;
 use hyper::http::HeaderValue;
+use hyper::server::accept;
 use hyper::{http, Method};
-use quickwit_common::tower::BoxFutureInfaillible;
+use quickwit_common::tower::{BoxFutureInfaillible, DeadlineLayer};
+use quickwit_config::{RestConfig, RouteHeadersConfig};
 use quickwit_proto::ServiceErrorCode;
+use tokio_rustls::TlsAcceptor;
 use tower::make::Shared;
 use tower::ServiceBuilder;
 use tower_http::compression::predicate::{DefaultPredicate, Predicate, SizeAbove};
 use tower_http::compression::CompressionLayer;
 use tower_http::cors::CorsLayer;
 use tracing::{error, info};
+use warp::path::FullPath;
 use warp::{redirect, Filter, Rejection, Reply};
 
-use crate::cluster_api::cluster_handler;
+use crate::cluster_api::{
+    cluster_events_handler, cluster_handler, cluster_state_snapshot_handler, drain_node_handler,
+};
 use crate::delete_task_api::delete_task_api_handlers;
-use crate::elastic_search_api::elastic_api_handlers;
+use crate::elastic_search_api::{elastic_api_handlers, es_compat_cat_handlers};
 use crate::health_check_api::health_check_handlers;
-use crate::index_api::index_management_handlers;
-use crate::indexing_api::indexing_get_handler;
+use crate::index_api::{
+    get_placement_violations_handler, get_shard_scaling_state_handler, get_source_backlog_handler,
+    index_management_handlers,
+};
+use crate::indexing_api::indexing_handlers;
 use crate::ingest_api::ingest_api_handlers;
 use crate::json_api_response::{ApiError, JsonApiResponse};
 use crate::metrics_api::metrics_handler;
 use crate::node_info_handler::node_info_handler;
-use crate::search_api::{search_get_handler, search_post_handler, search_stream_handler};
+use crate::search_api::{
+    count_handler, list_terms_handler, search_estimate_handler, search_get_handler,
+    search_post_handler, search_stream_handler,
+};
+use crate::search_query_limiter::{SearchQueryLimiter, TooManySearchQueries};
+use crate::searcher_config_api::searcher_config_handlers;
+use crate::tls::tls_incoming;
 use crate::ui_handler::ui_handler;
 use crate::{BodyFormat, BuildInfo, QuickwitServices, RuntimeInfo};
 
@@ -63,6 +80,7 @@ impl warp::reject::Reject for InvalidArgument {}
 /// Starts REST services.
 pub(crate) async fn start_rest_server(
     rest_listen_addr: SocketAddr,
+    tls_acceptor_opt: Option<TlsAcceptor>,
     quickwit_services: Arc<QuickwitServices>,
     readiness_trigger: BoxFutureInfaillible<()>,
     shutdown_signal: BoxFutureInfaillible<()>,
@@ -92,21 +110,38 @@ pub(crate) async fn start_rest_server(
         .and(warp::get())
         .map(|| redirect(http::Uri::from_static("/ui/search")));
 
+    let route_headers = Arc::new(
+        quickwit_services
+            .node_config
+            .rest_config
+            .route_headers
+            .clone(),
+    );
+    if !route_headers.is_empty() {
+        info!(route_headers=?route_headers, "route headers will be added to the matching responses");
+    }
+
     // Combine all the routes together.
-    let rest_routes = api_v1_root_route
-        .or(api_doc)
-        .or(redirect_root_to_ui_route)
-        .or(ui_handler())
-        .or(health_check_routes)
-        .or(metrics_routes)
-        .with(request_counter)
-        .recover(recover_fn)
+    let rest_routes = warp::path::full()
+        .and(
+            api_v1_root_route
+                .or(api_doc)
+                .or(redirect_root_to_ui_route)
+                .or(ui_handler())
+                .or(health_check_routes)
+                .or(metrics_routes)
+                .with(request_counter)
+                .recover(recover_fn),
+        )
+        .map(move |full_path: FullPath, reply| {
+            add_route_headers(&route_headers, full_path.as_str(), reply)
+        })
         .boxed();
 
     let warp_service = warp::service(rest_routes);
     let compression_predicate =
         DefaultPredicate::new().and(SizeAbove::new(MINIMUM_RESPONSE_COMPRESSION_SIZE));
-    let cors = build_cors(&quickwit_services.node_config.rest_config.cors_allow_origins);
+    let cors = build_cors(&quickwit_services.node_config.rest_config);
 
     let service = ServiceBuilder::new()
         .layer(
@@ -115,12 +150,21 @@ pub(crate) async fn start_rest_server(
                 .compress_when(compression_predicate),
         )
         .layer(cors)
+        .layer(DeadlineLayer::rest())
         .service(warp_service);
 
     info!(
         rest_listen_addr=?rest_listen_addr,
+        tls_enabled=tls_acceptor_opt.is_some(),
         "Starting REST server listening on {rest_listen_addr}."
     );
+    let server_fut: BoxFuture<'_, hyper::Result<()>> = if let Some(tls_acceptor) = tls_acceptor_opt
+    {
+        let incoming = tls_incoming(rest_listen_addr, tls_acceptor).await?;
+        Box::pin(hyper::Server::builder(accept::from_stream(incoming)).serve(Shared::new(service)))
+    } else {
+        Box::pin(hyper::Server::bind(&rest_listen_addr).serve(Shared::new(service)))
+    };
 
     // `graceful_shutdown()` seems to be blocking in presence of existing connections.
     // The following approach of dropping the serve supposedly is not bullet proof, but it seems to
@@ -130,7 +174,7 @@ pub(crate) async fn start_rest_server(
     // https://github.com/hyperium/hyper/issues/2386
     let serve_fut = async move {
         tokio::select! {
-             res = hyper::Server::bind(&rest_listen_addr).serve(Shared::new(service)) => { res }
+             res = server_fut => { res }
              _ = shutdown_signal => { Ok(()) }
         }
     };
@@ -154,24 +198,56 @@ fn api_v1_routes(
             quickwit_services.node_config.rest_config.extra_headers
         );
     }
+    let search_query_limiter = SearchQueryLimiter::new(
+        quickwit_services
+            .node_config
+            .searcher_config
+            .api_key_query_limits
+            .clone(),
+    );
     let api_v1_root_url = warp::path!("api" / "v1" / ..);
     api_v1_root_url
         .and(
             cluster_handler(quickwit_services.cluster.clone())
+                .or(cluster_events_handler(quickwit_services.cluster.clone()))
+                .or(cluster_state_snapshot_handler(
+                    quickwit_services.cluster.clone(),
+                    quickwit_services.metastore_client.clone(),
+                    quickwit_services.indexing_service_opt.clone(),
+                ))
+                .or(drain_node_handler(
+                    quickwit_services.control_plane_service.clone(),
+                ))
                 .or(node_info_handler(
                     BuildInfo::get(),
                     RuntimeInfo::get(),
                     quickwit_services.node_config.clone(),
                 ))
-                .or(indexing_get_handler(
+                .or(indexing_handlers(
                     quickwit_services.indexing_service_opt.clone(),
                 ))
-                .or(search_get_handler(quickwit_services.search_service.clone()))
+                .or(search_get_handler(
+                    quickwit_services.search_service.clone(),
+                    search_query_limiter.clone(),
+                ))
                 .or(search_post_handler(
                     quickwit_services.search_service.clone(),
+                    search_query_limiter.clone(),
                 ))
                 .or(search_stream_handler(
                     quickwit_services.search_service.clone(),
+                    search_query_limiter.clone(),
+                ))
+                .or(search_estimate_handler(
+                    quickwit_services.search_service.clone(),
+                ))
+                .or(count_handler(
+                    quickwit_services.search_service.clone(),
+                    search_query_limiter.clone(),
+                ))
+                .or(list_terms_handler(
+                    quickwit_services.search_service.clone(),
+                    search_query_limiter.clone(),
                 ))
                 .or(ingest_api_handlers(
                     quickwit_services.ingest_router_service.clone(),
@@ -182,13 +258,32 @@ fn api_v1_routes(
                     quickwit_services.index_manager.clone(),
                     quickwit_services.node_config.clone(),
                 ))
+                .or(get_shard_scaling_state_handler(
+                    quickwit_services.control_plane_service.clone(),
+                ))
+                .or(get_source_backlog_handler(
+                    quickwit_services.control_plane_service.clone(),
+                ))
+                .or(get_placement_violations_handler(
+                    quickwit_services.control_plane_service.clone(),
+                ))
                 .or(delete_task_api_handlers(
                     quickwit_services.metastore_client.clone(),
+                    quickwit_services.janitor_service_opt.clone(),
                 ))
                 .or(elastic_api_handlers(
                     quickwit_services.node_config.clone(),
                     quickwit_services.search_service.clone(),
                     quickwit_services.ingest_service.clone(),
+                    search_query_limiter,
+                ))
+                .or(es_compat_cat_handlers(
+                    quickwit_services.metastore_client.clone(),
+                    quickwit_services.cluster.clone(),
+                ))
+                .or(searcher_config_handlers(
+                    quickwit_services.cluster.clone(),
+                    quickwit_services.searcher_context.clone(),
                 )),
         )
         .with(warp::reply::with::headers(
@@ -212,101 +307,104 @@ fn api_v1_routes(
 // More on this here: https://github.com/seanmonstar/warp/issues/388.
 // We may use this work on the PR is merged: https://github.com/seanmonstar/warp/pull/909.
 pub async fn recover_fn(rejection: Rejection) -> Result<impl Reply, Rejection> {
+    let retry_after_secs_opt = rejection
+        .find::<TooManySearchQueries>()
+        .map(|error| error.retry_after_secs);
     let err = get_status_with_error(rejection);
     let status_code = err.service_code.to_http_status_code();
-    Ok(JsonApiResponse::new::<(), _>(
-        &Err(err),
-        status_code,
-        &BodyFormat::default(),
-    ))
+    let mut response =
+        JsonApiResponse::new::<(), _>(&Err(err), status_code, &BodyFormat::default())
+            .into_response();
+    if let Some(retry_after_secs) = retry_after_secs_opt {
+        response
+            .headers_mut()
+            .insert(RETRY_AFTER, HeaderValue::from(retry_after_secs));
+    }
+    Ok(response)
 }
 
 fn get_status_with_error(rejection: Rejection) -> ApiError {
     if let Some(error) = rejection.find::<crate::index_api::UnsupportedContentType>() {
-        ApiError {
-            service_code: ServiceErrorCode::UnsupportedMediaType,
-            message: error.to_string(),
-        }
+        ApiError::new(ServiceErrorCode::UnsupportedMediaType, error.to_string())
+    } else if let Some(error) = rejection.find::<TooManySearchQueries>() {
+        ApiError::new(
+            ServiceErrorCode::RateLimited,
+            "too many concurrent search queries for this API key",
+        )
+        .with_details(serde_json::json!({ "retry_after_secs": error.retry_after_secs }))
     } else if rejection.is_not_found() {
-        ApiError {
-            service_code: ServiceErrorCode::NotFound,
-            message: "Route not found".to_string(),
-        }
+        ApiError::new(ServiceErrorCode::NotFound, "Route not found")
     } else if let Some(error) = rejection.find::<serde_qs::Error>() {
-        ApiError {
-            service_code: ServiceErrorCode::BadRequest,
-            message: error.to_string(),
-        }
+        ApiError::new(ServiceErrorCode::BadRequest, error.to_string())
     } else if let Some(error) = rejection.find::<InvalidJsonRequest>() {
         // Happens when the request body could not be deserialized correctly.
-        ApiError {
-            service_code: ServiceErrorCode::BadRequest,
-            message: error.0.to_string(),
-        }
+        ApiError::new(ServiceErrorCode::BadRequest, error.0.to_string())
     } else if let Some(error) = rejection.find::<InvalidArgument>() {
         // Happens when the url path or request body contains invalid argument(s).
-        ApiError {
-            service_code: ServiceErrorCode::BadRequest,
-            message: error.0.to_string(),
-        }
+        ApiError::new(ServiceErrorCode::BadRequest, error.0.to_string())
     } else if let Some(error) = rejection.find::<warp::filters::body::BodyDeserializeError>() {
         // Happens when the request body could not be deserialized correctly.
-        ApiError {
-            service_code: ServiceErrorCode::BadRequest,
-            message: error.to_string(),
-        }
+        ApiError::new(ServiceErrorCode::BadRequest, error.to_string())
     } else if let Some(error) = rejection.find::<warp::reject::UnsupportedMediaType>() {
-        ApiError {
-            service_code: ServiceErrorCode::UnsupportedMediaType,
-            message: error.to_string(),
-        }
+        ApiError::new(ServiceErrorCode::UnsupportedMediaType, error.to_string())
     } else if let Some(error) = rejection.find::<warp::reject::InvalidQuery>() {
-        ApiError {
-            service_code: ServiceErrorCode::BadRequest,
-            message: error.to_string(),
-        }
+        ApiError::new(ServiceErrorCode::BadRequest, error.to_string())
     } else if let Some(error) = rejection.find::<warp::reject::LengthRequired>() {
-        ApiError {
-            service_code: ServiceErrorCode::BadRequest,
-            message: error.to_string(),
-        }
+        ApiError::new(ServiceErrorCode::BadRequest, error.to_string())
     } else if let Some(error) = rejection.find::<warp::reject::MissingHeader>() {
-        ApiError {
-            service_code: ServiceErrorCode::BadRequest,
-            message: error.to_string(),
-        }
+        ApiError::new(ServiceErrorCode::BadRequest, error.to_string())
     } else if let Some(error) = rejection.find::<warp::reject::InvalidHeader>() {
-        ApiError {
-            service_code: ServiceErrorCode::BadRequest,
-            message: error.to_string(),
-        }
+        ApiError::new(ServiceErrorCode::BadRequest, error.to_string())
     } else if let Some(error) = rejection.find::<warp::reject::MethodNotAllowed>() {
-        ApiError {
-            service_code: ServiceErrorCode::MethodNotAllowed,
-            message: error.to_string(),
-        }
+        ApiError::new(ServiceErrorCode::MethodNotAllowed, error.to_string())
     } else if let Some(error) = rejection.find::<warp::reject::PayloadTooLarge>() {
-        ApiError {
-            service_code: ServiceErrorCode::BadRequest,
-            message: error.to_string(),
-        }
+        ApiError::new(ServiceErrorCode::BadRequest, error.to_string())
     } else {
         error!("REST server error: {:?}", rejection);
-        ApiError {
-            service_code: ServiceErrorCode::Internal,
-            message: "internal server error".to_string(),
+        ApiError::new(ServiceErrorCode::Internal, "internal server error")
+    }
+}
+
+/// Adds the headers of the route groups matching `path` to the response.
+fn add_route_headers(
+    route_headers: &[RouteHeadersConfig],
+    path: &str,
+    reply: impl Reply,
+) -> warp::reply::Response {
+    let mut response = reply.into_response();
+    for route_headers_config in route_headers {
+        if !path.starts_with(&route_headers_config.path_prefix) {
+            continue;
+        }
+        for (header_name, header_value) in &route_headers_config.headers {
+            response
+                .headers_mut()
+                .insert(header_name.clone(), header_value.clone());
         }
     }
+    response
 }
 
-fn build_cors(cors_origins: &[String]) -> CorsLayer {
-    let mut cors = CorsLayer::new().allow_methods([
-        Method::GET,
-        Method::POST,
-        Method::PUT,
-        Method::DELETE,
-        Method::OPTIONS,
-    ]);
+fn build_cors(rest_config: &RestConfig) -> CorsLayer {
+    // The methods and headers are validated when the node config is loaded.
+    let cors_methods: Vec<Method> = rest_config
+        .cors_allow_methods
+        .iter()
+        .filter_map(|method| method.parse().ok())
+        .collect();
+    let mut cors = CorsLayer::new().allow_methods(cors_methods);
+
+    let cors_headers = &rest_config.cors_allow_headers;
+    if cors_headers.iter().any(|header| header.as_str() == "*") {
+        cors = cors.allow_headers(tower_http::cors::Any);
+    } else if !cors_headers.is_empty() {
+        let headers: Vec<http::HeaderName> = cors_headers
+            .iter()
+            .filter_map(|header| header.parse().ok())
+            .collect();
+        cors = cors.allow_headers(headers);
+    }
+    let cors_origins = &rest_config.cors_allow_origins;
     if !cors_origins.is_empty() {
         let allow_any = cors_origins.iter().any(|origin| origin.as_str() == "*");
 
@@ -335,13 +433,13 @@ mod tests {
     use http::HeaderName;
     use hyper::{Request, Response, StatusCode};
     use quickwit_cluster::{create_cluster_for_test, ChannelTransport};
-    use quickwit_config::NodeConfig;
+    use quickwit_config::{NodeConfig, SearcherConfig};
     use quickwit_index_management::IndexService;
     use quickwit_ingest::{IngestApiService, IngestServiceClient};
     use quickwit_proto::control_plane::ControlPlaneServiceClient;
     use quickwit_proto::ingest::router::IngestRouterServiceClient;
     use quickwit_proto::metastore::MetastoreServiceClient;
-    use quickwit_search::MockSearchService;
+    use quickwit_search::{MockSearchService, SearcherContext};
     use quickwit_storage::StorageResolver;
     use tower::Service;
 
@@ -357,7 +455,7 @@ mod tests {
     async fn test_cors() {
         // No cors enabled
         {
-            let cors = build_cors(&[]);
+            let cors = build_cors(&rest_config_for_test(&[]));
 
             let mut layer = ServiceBuilder::new().layer(cors).service(HelloWorld);
 
@@ -388,7 +486,7 @@ mod tests {
 
         // Wildcard cors enabled
         {
-            let cors = build_cors(&["*".to_string()]);
+            let cors = build_cors(&rest_config_for_test(&["*"]));
 
             let mut layer = ServiceBuilder::new().layer(cors).service(HelloWorld);
 
@@ -425,7 +523,7 @@ mod tests {
 
         // Specific origin cors enabled
         {
-            let cors = build_cors(&["https://quickwit.io".to_string()]);
+            let cors = build_cors(&rest_config_for_test(&["https://quickwit.io"]));
 
             let mut layer = ServiceBuilder::new().layer(cors).service(HelloWorld);
 
@@ -476,10 +574,10 @@ mod tests {
 
         // Specific multiple-origin cors enabled
         {
-            let cors = build_cors(&[
-                "https://quickwit.io".to_string(),
-                "http://localhost:3000".to_string(),
-            ]);
+            let cors = build_cors(&rest_config_for_test(&[
+                "https://quickwit.io",
+                "http://localhost:3000",
+            ]));
 
             let mut layer = ServiceBuilder::new().layer(cors).service(HelloWorld);
 
@@ -532,6 +630,92 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_cors_allow_headers_and_methods() {
+        let rest_config = RestConfig {
+            cors_allow_headers: vec!["content-type".to_string(), "authorization".to_string()],
+            cors_allow_methods: vec!["GET".to_string(), "POST".to_string()],
+            ..rest_config_for_test(&["*"])
+        };
+        let cors = build_cors(&rest_config);
+        let mut layer = ServiceBuilder::new().layer(cors).service(HelloWorld);
+
+        let resp = layer
+            .call(cors_request("http://localhost:3000"))
+            .await
+            .unwrap();
+        let headers = resp.headers();
+        assert_eq!(headers.get("Access-Control-Allow-Origin").unwrap(), "*");
+        assert_eq!(
+            headers.get("Access-Control-Allow-Methods").unwrap(),
+            "GET,POST"
+        );
+        assert_eq!(
+            headers.get("Access-Control-Allow-Headers").unwrap(),
+            "content-type,authorization"
+        );
+
+        let rest_config = RestConfig {
+            cors_allow_headers: vec!["*".to_string()],
+            ..rest_config_for_test(&["*"])
+        };
+        let cors = build_cors(&rest_config);
+        let mut layer = ServiceBuilder::new().layer(cors).service(HelloWorld);
+
+        let resp = layer
+            .call(cors_request("http://localhost:3000"))
+            .await
+            .unwrap();
+        assert_eq!(
+            resp.headers().get("Access-Control-Allow-Headers").unwrap(),
+            "*"
+        );
+    }
+
+    #[test]
+    fn test_add_route_headers() {
+        let mut elastic_headers = http::HeaderMap::new();
+        elastic_headers.insert(
+            HeaderName::from_static("cache-control"),
+            HeaderValue::from_static("no-store"),
+        );
+        let mut search_headers = http::HeaderMap::new();
+        search_headers.insert(
+            HeaderName::from_static("x-route"),
+            HeaderValue::from_static("search"),
+        );
+        let route_headers = [
+            RouteHeadersConfig {
+                path_prefix: "/api/v1/_elastic".to_string(),
+                headers: elastic_headers,
+            },
+            RouteHeadersConfig {
+                path_prefix: "/api/v1/my-index/search".to_string(),
+                headers: search_headers,
+            },
+        ];
+        let response = add_route_headers(&route_headers, "/api/v1/_elastic/_search", "hello");
+        assert_eq!(response.headers().get("cache-control").unwrap(), "no-store");
+        assert!(response.headers().get("x-route").is_none());
+
+        let response = add_route_headers(&route_headers, "/api/v1/my-index/search", "hello");
+        assert!(response.headers().get("cache-control").is_none());
+        assert_eq!(response.headers().get("x-route").unwrap(), "search");
+
+        let response = add_route_headers(&route_headers, "/health/livez", "hello");
+        assert!(response.headers().is_empty());
+    }
+
+    fn rest_config_for_test(cors_origins: &[&str]) -> RestConfig {
+        RestConfig {
+            cors_allow_origins: cors_origins
+                .iter()
+                .map(|origin| origin.to_string())
+                .collect(),
+            ..NodeConfig::for_test().rest_config
+        }
+    }
+
     fn cors_request(origin: &'static str) -> Request<()> {
         let mut request = Request::new(());
         (*request.method_mut()) = Method::OPTIONS;
@@ -588,6 +772,7 @@ mod tests {
         let quickwit_services = QuickwitServices {
             _report_splits_subscription_handle_opt: None,
             _local_shards_update_listener_handle_opt: None,
+            _searcher_config_overrides_listener_handle_opt: None,
             cluster,
             control_plane_service,
             indexing_service_opt: None,
@@ -603,6 +788,7 @@ mod tests {
             metastore_server_opt: None,
             node_config: Arc::new(node_config.clone()),
             search_service: Arc::new(MockSearchService::new()),
+            searcher_context: Arc::new(SearcherContext::new(SearcherConfig::default(), None)),
         };
         let handler = api_v1_routes(Arc::new(quickwit_services));
         let resp = warp::test::request()
@@ -23,8 +23,9 @@ mod rest_handler;
 pub use self::grpc_adapter::GrpcSearchAdapter;
 pub(crate) use self::rest_handler::extract_index_id_patterns;
 pub use self::rest_handler::{
-    search_get_handler, search_post_handler, search_request_from_api_request,
-    search_stream_handler, SearchApi, SearchRequestQueryString, SortBy,
+    count_handler, list_terms_handler, search_estimate_handler, search_get_handler,
+    search_post_handler, search_request_from_api_request, search_stream_handler, SearchApi,
+    SearchRequestQueryString, SortBy,
 };
 
 #[cfg(test)]
@@ -79,6 +80,7 @@ mod tests {
             fast_field: "timestamp".to_string(),
             output_format: OutputFormat::Csv as i32,
             partition_by_field: None,
+            additional_fast_fields: Vec::new(),
         };
         let mut metastore = MetastoreServiceClient::mock();
         let index_metadata = IndexMetadata::for_test("test-index", "ram:///indexes/test-index");
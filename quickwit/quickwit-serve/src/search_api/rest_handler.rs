@@ -23,12 +23,20 @@ use std::sync::Arc;
 use futures::stream::StreamExt;
 use hyper::header::HeaderValue;
 use hyper::HeaderMap;
+use itertools::Itertools;
 use once_cell::sync::Lazy;
+use quickwit_common::is_false;
 use quickwit_config::validate_index_id_pattern;
-use quickwit_proto::search::{CountHits, OutputFormat, SortField, SortOrder};
+use quickwit_proto::search::{
+    AggregationFormat, CountHits, Federation, GroupBy, ListTermsRequest, OutputFormat, PartialHit,
+    SortByValue, SortField, SortOrder, SplitSearchError,
+};
 use quickwit_proto::ServiceError;
 use quickwit_query::query_ast::query_ast_from_user_text;
-use quickwit_search::{SearchError, SearchResponseRest, SearchService};
+use quickwit_search::{
+    CountResponseRest, GlobalDocAddress, HitGroupRest, LatencyBand, ListTermsResponseRest,
+    SearchCostEstimate, SearchError, SearchResponseRest, SearchService, TermRest,
+};
 use regex::Regex;
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value as JsonValue;
@@ -37,21 +45,38 @@ use warp::hyper::header::CONTENT_TYPE;
 use warp::hyper::StatusCode;
 use warp::{reply, Filter, Rejection, Reply};
 
+use crate::format::extract_format_from_qs;
 use crate::json_api_response::make_json_api_response;
+use crate::search_query_limiter::{search_permit_filter, SearchPermit, SearchQueryLimiter};
 use crate::simple_list::{from_simple_list, to_simple_list};
 use crate::{with_arg, BodyFormat};
 
 #[derive(utoipa::OpenApi)]
 #[openapi(
-    paths(search_get_handler, search_post_handler, search_stream_handler,),
+    paths(
+        count_handler,
+        search_get_handler,
+        search_post_handler,
+        search_stream_handler,
+        search_estimate_handler,
+        list_terms_handler,
+    ),
     components(schemas(
         BodyFormat,
+        CountRequestQueryString,
+        CountResponseRest,
+        HitGroupRest,
+        LatencyBand,
+        ListTermsResponseRest,
         OutputFormat,
+        SearchCostEstimate,
         SearchRequestQueryString,
         SearchResponseRest,
         SortBy,
         SortField,
         SortOrder,
+        SplitSearchError,
+        TermRest,
     ),)
 )]
 pub struct SearchApi;
@@ -208,7 +233,8 @@ pub struct SearchRequestQueryString {
     /// The results with rank [start_offset..start_offset + max_hits) are returned
     #[serde(default)] // Default to 0. (We are 0-indexed)
     pub start_offset: u64,
-    /// The output format.
+    /// The output format. With `arrow`, the response body is the aggregation result encoded as
+    /// an Arrow IPC stream.
     #[serde(default)]
     pub format: BodyFormat,
     /// Specifies how documents are sorted.
@@ -223,6 +249,65 @@ pub struct SearchRequestQueryString {
     #[serde(with = "count_hits_from_bool")]
     #[serde(default = "count_hits_from_bool::default")]
     pub count_all: CountHits,
+    /// Fast field used to deduplicate hits: only the best `dedup_count` hits sharing the same
+    /// value of this field are returned.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dedup_field: Option<String>,
+    /// Maximum number of hits returned per value of `dedup_field` (by default 1).
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dedup_count: Option<u32>,
+    /// Fast field used to group hits. The response then holds, instead of `hits`, the best
+    /// `max_hits` groups with their best `group_size` hits and their number of hits.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_by: Option<String>,
+    /// Maximum number of hits returned per group (by default 1).
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_size: Option<u32>,
+    /// Maximum number of hits returned per index when searching several indexes.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_hits_per_index: Option<u64>,
+    /// How the hits of the different indexes are merged: `global` ranks hits regardless of
+    /// their index (default), `balanced` interleaves the best hits of each index.
+    #[param(value_type = String)]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_global_federation")]
+    pub federation: Federation,
+    /// If set, only the most recent splits are searched in order to return quickly. The
+    /// response then reports the fraction of the documents covered in `preview_coverage`.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub preview: bool,
+    /// Split tags, of the form `key:value`. Only the splits that may contain documents carrying
+    /// all these tags are searched. Several values for the same key are alternatives.
+    #[serde(default)]
+    #[serde(deserialize_with = "from_simple_list")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(serialize_with = "to_simple_list")]
+    pub split_tags: Option<Vec<String>>,
+    /// Comma-separated sort values of the last hit of the previous page, as returned in
+    /// `next_search_after`: one value per `sort_by` field, optionally followed by the address of
+    /// the hit to break ties. Only the hits sorted after it are returned, which paginates forward
+    /// without keeping a scroll context.
+    #[param(value_type = String)]
+    #[serde(default)]
+    #[serde(deserialize_with = "from_simple_list")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(serialize_with = "to_simple_list")]
+    pub search_after: Option<Vec<String>>,
+    /// If true (default), the splits that could not be searched are reported in `failed_splits`
+    /// and the response is flagged as `partial`. If false, any split failure fails the request.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_partial_results: Option<bool>,
+}
+
+fn is_global_federation(federation: &Federation) -> bool {
+    *federation == Federation::Global
 }
 
 mod count_hits_from_bool {
@@ -262,6 +347,22 @@ pub fn search_request_from_api_request(
     // the user of the docmapper default fields (which we do not have at this point).
     let query_ast = query_ast_from_user_text(&search_request.query, search_request.search_fields);
     let query_ast_json = serde_json::to_string(&query_ast)?;
+    let aggregation_format = if search_request.format == BodyFormat::Arrow {
+        AggregationFormat::Arrow
+    } else {
+        AggregationFormat::Json
+    };
+    let search_after = search_request
+        .search_after
+        .map(|search_after| {
+            partial_hit_from_search_after(search_after, &search_request.sort_by.sort_fields)
+        })
+        .transpose()?;
+    let group_by = search_request.group_by.map(|field| GroupBy {
+        field,
+        max_groups: search_request.max_hits.try_into().unwrap_or(u32::MAX),
+        max_hits_per_group: search_request.group_size.unwrap_or(1),
+    });
     let search_request = quickwit_proto::search::SearchRequest {
         index_id_patterns,
         query_ast: query_ast_json,
@@ -275,20 +376,108 @@ pub fn search_request_from_api_request(
             .map(|agg| serde_json::to_string(&agg).expect("could not serialize JsonValue")),
         sort_fields: search_request.sort_by.sort_fields,
         scroll_ttl_secs: None,
-        search_after: None,
+        search_after,
         count_hits: search_request.count_all.into(),
+        aggregation_format: aggregation_format.into(),
+        dedup_field: search_request.dedup_field,
+        dedup_count: search_request.dedup_count,
+        snippet_options: None,
+        max_hits_per_index: search_request.max_hits_per_index,
+        federation: search_request.federation.into(),
+        preview: search_request.preview,
+        group_by,
+        split_tags: search_request.split_tags.unwrap_or_default(),
+        allow_partial_results: search_request.allow_partial_results.unwrap_or(true),
     };
     Ok(search_request)
 }
 
+/// Parses the `search_after` parameter into the partial hit the leaves skip the documents up to.
+fn partial_hit_from_search_after(
+    search_after: Vec<String>,
+    sort_fields: &[SortField],
+) -> Result<PartialHit, SearchError> {
+    if search_after.len() != sort_fields.len() && search_after.len() != sort_fields.len() + 1 {
+        return Err(SearchError::InvalidArgument(format!(
+            "`search_after` must have one value per sort field, optionally followed by a hit \
+             address, expected {} values, got {}",
+            sort_fields.len(),
+            search_after.len()
+        )));
+    }
+    let mut partial_hit = PartialHit::default();
+    let mut search_after_values = search_after.into_iter();
+
+    for (sort_field_idx, value) in search_after_values
+        .by_ref()
+        .take(sort_fields.len())
+        .enumerate()
+    {
+        let json_value: JsonValue =
+            serde_json::from_str(&value).unwrap_or(JsonValue::String(value));
+        let sort_by_value = SortByValue::try_from_json(json_value).ok_or_else(|| {
+            SearchError::InvalidArgument(
+                "invalid `search_after` value, expected a boolean or a number".to_string(),
+            )
+        })?;
+        if sort_field_idx == 0 {
+            partial_hit.sort_value = Some(sort_by_value);
+        } else {
+            partial_hit.sort_value2 = Some(sort_by_value);
+        }
+    }
+    if let Some(hit_address) = search_after_values.next() {
+        let global_doc_address: GlobalDocAddress = hit_address.parse().map_err(|_| {
+            SearchError::InvalidArgument(format!(
+                "invalid `search_after` hit address `{hit_address}`, expected \
+                 `{{split_id}}:{{segment_ord}}:{{doc_id}}`"
+            ))
+        })?;
+        partial_hit.split_id = global_doc_address.split;
+        partial_hit.segment_ord = global_doc_address.doc_addr.segment_ord;
+        partial_hit.doc_id = global_doc_address.doc_addr.doc_id;
+    }
+    Ok(partial_hit)
+}
+
+/// Formats the sort values and the address of a hit into the `search_after` parameter fetching
+/// the hits sorted after it.
+fn search_after_from_partial_hit(partial_hit: &PartialHit) -> String {
+    partial_hit
+        .sort_value
+        .iter()
+        .chain(partial_hit.sort_value2.iter())
+        .map(|sort_by_value| sort_by_value.clone().into_json().to_string())
+        .chain(std::iter::once(
+            GlobalDocAddress::from_partial_hit(partial_hit).to_string(),
+        ))
+        .join(",")
+}
+
 async fn search_endpoint(
     index_id_patterns: Vec<String>,
     search_request: SearchRequestQueryString,
     search_service: &dyn SearchService,
 ) -> Result<SearchResponseRest, SearchError> {
     let search_request = search_request_from_api_request(index_id_patterns, search_request)?;
+    let max_hits = search_request.max_hits;
     let search_response = search_service.root_search(search_request).await?;
-    let search_response_rest = SearchResponseRest::try_from(search_response)?;
+
+    // When the page is full, the client may fetch the next one with `search_after`.
+    let next_search_after_opt = if max_hits > 0
+        && search_response.hits.len() as u64 == max_hits
+        && search_response.groups.is_empty()
+    {
+        search_response
+            .hits
+            .last()
+            .and_then(|hit| hit.partial_hit.as_ref())
+            .map(search_after_from_partial_hit)
+    } else {
+        None
+    };
+    let mut search_response_rest = SearchResponseRest::try_from(search_response)?;
+    search_response_rest.next_search_after = next_search_after_opt;
     Ok(search_response_rest)
 }
 
@@ -309,15 +498,46 @@ fn search_post_filter(
         .and(warp::body::json())
 }
 
+async fn search_arrow_endpoint(
+    index_id_patterns: Vec<String>,
+    search_request: SearchRequestQueryString,
+    search_service: &dyn SearchService,
+) -> Result<Vec<u8>, SearchError> {
+    if search_request.aggs.is_none() {
+        return Err(SearchError::InvalidArgument(
+            "the `arrow` format requires an aggregation request".to_string(),
+        ));
+    }
+    let search_request = search_request_from_api_request(index_id_patterns, search_request)?;
+    let search_response = search_service.root_search(search_request).await?;
+    // The aggregation result is missing when no split matched the request.
+    Ok(search_response.aggregation_arrow.unwrap_or_default())
+}
+
 async fn search(
     index_id_patterns: Vec<String>,
     search_request: SearchRequestQueryString,
     search_service: Arc<dyn SearchService>,
-) -> impl warp::Reply {
+    _search_permit: SearchPermit,
+) -> warp::reply::Response {
     info!(request =? search_request, "search");
     let body_format = search_request.format;
+
+    if body_format == BodyFormat::Arrow {
+        return match search_arrow_endpoint(index_id_patterns, search_request, &*search_service)
+            .await
+        {
+            Ok(aggregation_arrow) => reply::with_header(
+                aggregation_arrow,
+                CONTENT_TYPE,
+                "application/vnd.apache.arrow.stream",
+            )
+            .into_response(),
+            Err(error) => make_json_api_response::<(), _>(Err(error), body_format).into_response(),
+        };
+    }
     let result = search_endpoint(index_id_patterns, search_request, &*search_service).await;
-    make_json_api_response(result, body_format)
+    make_json_api_response(result, body_format).into_response()
 }
 
 #[utoipa::path(
@@ -337,9 +557,11 @@ async fn search(
 /// Parses the search request from the request query string.
 pub fn search_get_handler(
     search_service: Arc<dyn SearchService>,
+    search_query_limiter: SearchQueryLimiter,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
     search_get_filter()
         .and(with_arg(search_service))
+        .and(search_permit_filter(search_query_limiter))
         .then(search)
 }
 
@@ -362,9 +584,11 @@ pub fn search_get_handler(
 /// Parses the search request from the request body.
 pub fn search_post_handler(
     search_service: Arc<dyn SearchService>,
+    search_query_limiter: SearchQueryLimiter,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
     search_post_filter()
         .and(with_arg(search_service))
+        .and(search_permit_filter(search_query_limiter))
         .then(search)
 }
 
@@ -383,12 +607,168 @@ pub fn search_post_handler(
 /// Stream Search Index
 pub fn search_stream_handler(
     search_service: Arc<dyn SearchService>,
+    search_query_limiter: SearchQueryLimiter,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
     search_stream_filter()
         .and(with_arg(search_service))
+        .and(search_permit_filter(search_query_limiter))
         .then(search_stream)
 }
 
+async fn search_estimate(
+    index_id_patterns: Vec<String>,
+    search_request: SearchRequestQueryString,
+    search_service: Arc<dyn SearchService>,
+) -> Result<SearchCostEstimate, SearchError> {
+    info!(request =? search_request, "search_estimate");
+    let search_request = search_request_from_api_request(index_id_patterns, search_request)?;
+    let search_cost_estimate = search_service
+        .root_estimate_search_cost(search_request)
+        .await?;
+    Ok(search_cost_estimate)
+}
+
+fn search_estimate_filter(
+) -> impl Filter<Extract = (Vec<String>, SearchRequestQueryString), Error = Rejection> + Clone {
+    warp::path!(String / "search" / "estimate")
+        .and_then(extract_index_id_patterns)
+        .and(
+            warp::get()
+                .and(serde_qs::warp::query(serde_qs::Config::default()))
+                .or(warp::post()
+                    .and(warp::body::content_length_limit(1024 * 1024))
+                    .and(warp::body::json()))
+                .unify(),
+        )
+}
+
+#[utoipa::path(
+    get,
+    tag = "Search",
+    path = "/{index_id}/search/estimate",
+    responses(
+        (status = 200, description = "Successfully estimated the search cost.", body = SearchCostEstimate)
+    ),
+    params(
+        SearchRequestQueryString,
+        ("index_id" = String, Path, description = "The index ID to search."),
+    )
+)]
+/// Estimate Search Cost
+///
+/// Estimates the number of splits to search, the number of bytes to download, and the latency of
+/// a search request without executing it. The request is parsed from the query string (GET) or
+/// from the request body (POST), like a regular search request.
+pub fn search_estimate_handler(
+    search_service: Arc<dyn SearchService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    search_estimate_filter()
+        .and(with_arg(search_service))
+        .then(search_estimate)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+/// This struct represents the count query passed to the REST API.
+#[derive(
+    Debug, Default, Eq, PartialEq, Serialize, Deserialize, utoipa::IntoParams, utoipa::ToSchema,
+)]
+#[into_params(parameter_in = Query)]
+#[serde(deny_unknown_fields)]
+pub struct CountRequestQueryString {
+    /// Query text. The query language is that of tantivy.
+    pub query: String,
+    // Fields to search on
+    #[param(rename = "search_field")]
+    #[schema(rename = "search_field")]
+    #[serde(default)]
+    #[serde(rename = "search_field")]
+    #[serde(deserialize_with = "from_simple_list")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(serialize_with = "to_simple_list")]
+    pub search_fields: Option<Vec<String>>,
+    /// If set, restrict the count to documents with a `timestamp >= start_timestamp`.
+    /// This timestamp is expressed in seconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_timestamp: Option<i64>,
+    /// If set, restrict the count to documents with a `timestamp < end_timestamp`.
+    /// This timestamp is expressed in seconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_timestamp: Option<i64>,
+}
+
+fn count_request_from_api_request(
+    index_id_patterns: Vec<String>,
+    count_request: CountRequestQueryString,
+) -> Result<quickwit_proto::search::SearchRequest, SearchError> {
+    let query_ast = query_ast_from_user_text(&count_request.query, count_request.search_fields);
+    let query_ast_json = serde_json::to_string(&query_ast)?;
+    let search_request = quickwit_proto::search::SearchRequest {
+        index_id_patterns,
+        query_ast: query_ast_json,
+        start_timestamp: count_request.start_timestamp,
+        end_timestamp: count_request.end_timestamp,
+        max_hits: 0,
+        count_hits: CountHits::CountAll.into(),
+        ..Default::default()
+    };
+    Ok(search_request)
+}
+
+async fn count(
+    index_id_patterns: Vec<String>,
+    count_request: CountRequestQueryString,
+    search_service: Arc<dyn SearchService>,
+    _search_permit: SearchPermit,
+) -> Result<CountResponseRest, SearchError> {
+    info!(request =? count_request, "count");
+    let search_request = count_request_from_api_request(index_id_patterns, count_request)?;
+    let count_response = search_service.root_count(search_request).await?;
+    Ok(count_response)
+}
+
+fn count_filter(
+) -> impl Filter<Extract = (Vec<String>, CountRequestQueryString), Error = Rejection> + Clone {
+    warp::path!(String / "count")
+        .and_then(extract_index_id_patterns)
+        .and(
+            warp::get()
+                .and(serde_qs::warp::query(serde_qs::Config::default()))
+                .or(warp::post()
+                    .and(warp::body::content_length_limit(1024 * 1024))
+                    .and(warp::body::json()))
+                .unify(),
+        )
+}
+
+#[utoipa::path(
+    post,
+    tag = "Search",
+    path = "/{index_id}/count",
+    request_body = CountRequestQueryString,
+    responses(
+        (status = 200, description = "Successfully counted the matching documents.", body = CountResponseRest)
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID to search."),
+    )
+)]
+/// Count Documents
+///
+/// Counts the documents matching a query without collecting, sorting, or fetching any hit. When
+/// several indexes are searched, the count of each index is also returned. The request is parsed
+/// from the request body (POST) or from the query string (GET).
+pub fn count_handler(
+    search_service: Arc<dyn SearchService>,
+    search_query_limiter: SearchQueryLimiter,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    count_filter()
+        .and(with_arg(search_service))
+        .and(search_permit_filter(search_query_limiter))
+        .then(count)
+        .map(|count_result| make_json_api_response(count_result, BodyFormat::default()))
+}
+
 /// This struct represents the search stream query passed to
 /// the REST API.
 #[derive(Deserialize, Debug, Eq, PartialEq, utoipa::IntoParams)]
@@ -426,7 +806,14 @@ async fn search_stream_endpoint(
     index_id: String,
     search_request: SearchStreamRequestQueryString,
     search_service: &dyn SearchService,
+    search_permit: SearchPermit,
 ) -> Result<hyper::Body, SearchError> {
+    if search_request.output_format == OutputFormat::ArrowIpc {
+        return Err(SearchError::InvalidArgument(
+            "the `arrow_ipc` output format is only supported by the Arrow Flight service"
+                .to_string(),
+        ));
+    }
     let query_ast = query_ast_from_user_text(&search_request.query, search_request.search_fields);
     let query_ast_json = serde_json::to_string(&query_ast)?;
     let request = quickwit_proto::search::SearchStreamRequest {
@@ -438,10 +825,13 @@ async fn search_stream_endpoint(
         fast_field: search_request.fast_field,
         output_format: search_request.output_format as i32,
         partition_by_field: search_request.partition_by_field,
+        additional_fast_fields: Vec::new(),
     };
     let mut data = search_service.root_search_stream(request).await?;
     let (mut sender, body) = hyper::Body::channel();
     tokio::spawn(async move {
+        // The permit is held until the stream is exhausted.
+        let _search_permit = search_permit;
         while let Some(result) = data.next().await {
             match result {
                 Ok(bytes) => {
@@ -493,14 +883,17 @@ async fn search_stream(
     index_id: String,
     request: SearchStreamRequestQueryString,
     search_service: Arc<dyn SearchService>,
+    search_permit: SearchPermit,
 ) -> impl warp::Reply {
     info!(index_id=%index_id,request=?request, "search_stream");
     let content_type = match request.output_format {
         OutputFormat::ClickHouseRowBinary => "application/octet-stream",
         OutputFormat::Csv => "text/csv",
+        OutputFormat::ArrowIpc => "application/vnd.apache.arrow.stream",
     };
-    let reply =
-        make_streaming_reply(search_stream_endpoint(index_id, request, &*search_service).await);
+    let reply = make_streaming_reply(
+        search_stream_endpoint(index_id, request, &*search_service, search_permit).await,
+    );
     reply::with_header(reply, CONTENT_TYPE, content_type)
 }
 
@@ -511,11 +904,119 @@ fn search_stream_filter(
         .and(serde_qs::warp::query(serde_qs::Config::default()))
 }
 
+fn default_list_terms_limit() -> u64 {
+    100
+}
+
+/// This struct represents the list terms query string passed to
+/// the REST API.
+#[derive(Debug, Deserialize, Eq, PartialEq, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+struct ListTermsQueryString {
+    /// Field to list the terms of.
+    #[serde(deserialize_with = "deserialize_non_empty_string")]
+    pub field: String,
+    /// If set, only lists the terms starting with this prefix.
+    #[serde(default)]
+    pub prefix: Option<String>,
+    /// Maximum number of terms to return.
+    #[param(default = 100)]
+    #[serde(default = "default_list_terms_limit")]
+    pub limit: u64,
+    /// If set, returns the number of documents containing each term.
+    #[serde(default)]
+    pub include_doc_counts: bool,
+    /// If set, restricts the listing to the splits with documents with a
+    /// `timestamp >= start_timestamp`.
+    pub start_timestamp: Option<i64>,
+    /// If set, restricts the listing to the splits with documents with a
+    /// `timestamp < end_timestamp`.
+    pub end_timestamp: Option<i64>,
+}
+
+/// Returns the smallest key greater than all the keys starting with `prefix`, or `None` if no
+/// such key exists.
+fn prefix_end_key(prefix: &[u8]) -> Option<Vec<u8>> {
+    let mut end_key = prefix.to_vec();
+
+    while let Some(last_byte) = end_key.pop() {
+        if last_byte < u8::MAX {
+            end_key.push(last_byte + 1);
+            return Some(end_key);
+        }
+    }
+    None
+}
+
+fn list_terms_request_from_query_string(
+    index_id: String,
+    query_string: ListTermsQueryString,
+) -> ListTermsRequest {
+    let prefix = query_string
+        .prefix
+        .filter(|prefix| !prefix.is_empty())
+        .map(String::into_bytes);
+    let end_key = prefix.as_deref().and_then(prefix_end_key);
+    ListTermsRequest {
+        index_id,
+        field: query_string.field,
+        start_timestamp: query_string.start_timestamp,
+        end_timestamp: query_string.end_timestamp,
+        max_hits: Some(query_string.limit),
+        start_key: prefix,
+        end_key,
+        include_doc_counts: query_string.include_doc_counts,
+    }
+}
+
+async fn list_terms(
+    index_id: String,
+    query_string: ListTermsQueryString,
+    search_service: Arc<dyn SearchService>,
+    _search_permit: SearchPermit,
+) -> Result<ListTermsResponseRest, SearchError> {
+    info!(index_id=%index_id, request=?query_string, "list_terms");
+    let list_terms_request = list_terms_request_from_query_string(index_id, query_string);
+    let list_terms_response = search_service.root_list_terms(list_terms_request).await?;
+    Ok(list_terms_response.into())
+}
+
+#[utoipa::path(
+    get,
+    tag = "Search",
+    path = "/indexes/{index_id}/terms",
+    responses(
+        (status = 200, description = "Successfully listed the terms.", body = ListTermsResponseRest)
+    ),
+    params(
+        ListTermsQueryString,
+        ("index_id" = String, Path, description = "The index ID to list the terms of."),
+    )
+)]
+/// List Terms
+///
+/// Lists the terms of an indexed field in ascending order, optionally restricted to a prefix and
+/// along with the number of documents containing each term.
+pub fn list_terms_handler(
+    search_service: Arc<dyn SearchService>,
+    search_query_limiter: SearchQueryLimiter,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexes" / String / "terms")
+        .and(warp::get())
+        .and(serde_qs::warp::query(serde_qs::Config::default()))
+        .and(with_arg(search_service))
+        .and(search_permit_filter(search_query_limiter))
+        .then(list_terms)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
 #[cfg(test)]
 mod tests {
     use assert_json_diff::{assert_json_eq, assert_json_include};
     use bytes::Bytes;
     use mockall::predicate;
+    use quickwit_proto::search::SortValue;
     use quickwit_search::{MockSearchService, SearchError};
     use serde_json::{json, Value as JsonValue};
 
@@ -526,10 +1027,19 @@ mod tests {
         mock_search_service: MockSearchService,
     ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
         let mock_search_service_in_arc = Arc::new(mock_search_service);
-        search_get_handler(mock_search_service_in_arc.clone())
-            .or(search_post_handler(mock_search_service_in_arc.clone()))
-            .or(search_stream_handler(mock_search_service_in_arc))
-            .recover(recover_fn)
+        search_get_handler(
+            mock_search_service_in_arc.clone(),
+            SearchQueryLimiter::default(),
+        )
+        .or(search_post_handler(
+            mock_search_service_in_arc.clone(),
+            SearchQueryLimiter::default(),
+        ))
+        .or(search_stream_handler(
+            mock_search_service_in_arc,
+            SearchQueryLimiter::default(),
+        ))
+        .recover(recover_fn)
     }
 
     #[tokio::test]
@@ -564,6 +1074,12 @@ mod tests {
             elapsed_time_micros: 0u64,
             errors: Vec::new(),
             aggregations: None,
+            preview_coverage: None,
+            groups: None,
+            next_search_after: None,
+            partial: false,
+            failed_splits: Vec::new(),
+            skipped_split_ids: Vec::new(),
         };
         let search_response_json: JsonValue = serde_json::to_value(search_response)?;
         let expected_search_response_json: JsonValue = json!({
@@ -689,6 +1205,213 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_rest_search_api_route_dedup() {
+        let rest_search_api_filter = search_get_filter();
+        let (_indexes, req) = warp::test::request()
+            .path("/quickwit-demo-index/search?query=*&dedup_field=event_id&dedup_count=2")
+            .filter(&rest_search_api_filter)
+            .await
+            .unwrap();
+        assert_eq!(req.dedup_field.as_deref(), Some("event_id"));
+        assert_eq!(req.dedup_count, Some(2));
+
+        let search_request =
+            search_request_from_api_request(vec!["quickwit-demo-index".to_string()], req).unwrap();
+        assert_eq!(search_request.dedup_field.as_deref(), Some("event_id"));
+        assert_eq!(search_request.dedup_count, Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_rest_search_api_route_group_by() {
+        let rest_search_api_filter = search_get_filter();
+        let (_indexes, req) = warp::test::request()
+            .path("/quickwit-demo-index/search?query=*&max_hits=5&group_by=host&group_size=3")
+            .filter(&rest_search_api_filter)
+            .await
+            .unwrap();
+        assert_eq!(req.group_by.as_deref(), Some("host"));
+        assert_eq!(req.group_size, Some(3));
+
+        let search_request =
+            search_request_from_api_request(vec!["quickwit-demo-index".to_string()], req).unwrap();
+        assert_eq!(
+            search_request.group_by,
+            Some(GroupBy {
+                field: "host".to_string(),
+                max_groups: 5,
+                max_hits_per_group: 3,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rest_search_api_route_split_tags() {
+        let rest_search_api_filter = search_get_filter();
+        let (_indexes, req) = warp::test::request()
+            .path("/quickwit-demo-index/search?query=*&split_tags=env:prod,dc:us-east")
+            .filter(&rest_search_api_filter)
+            .await
+            .unwrap();
+        let search_request =
+            search_request_from_api_request(vec!["quickwit-demo-index".to_string()], req).unwrap();
+        assert_eq!(search_request.split_tags, ["env:prod", "dc:us-east"]);
+    }
+
+    #[tokio::test]
+    async fn test_rest_search_api_route_allow_partial_results() {
+        let rest_search_api_filter = search_get_filter();
+        let (_indexes, req) = warp::test::request()
+            .path("/quickwit-demo-index/search?query=*")
+            .filter(&rest_search_api_filter)
+            .await
+            .unwrap();
+        let search_request =
+            search_request_from_api_request(vec!["quickwit-demo-index".to_string()], req).unwrap();
+        assert!(search_request.allow_partial_results);
+
+        let (_indexes, req) = warp::test::request()
+            .path("/quickwit-demo-index/search?query=*&allow_partial_results=false")
+            .filter(&rest_search_api_filter)
+            .await
+            .unwrap();
+        let search_request =
+            search_request_from_api_request(vec!["quickwit-demo-index".to_string()], req).unwrap();
+        assert!(!search_request.allow_partial_results);
+    }
+
+    #[tokio::test]
+    async fn test_rest_search_api_route_search_after() {
+        let rest_search_api_filter = search_get_filter();
+        let (_indexes, req) = warp::test::request()
+            .path(
+                "/quickwit-demo-index/search?query=*&sort_by=-timestamp&search_after=1700000000,\
+                 split-1:00000002:00000003",
+            )
+            .filter(&rest_search_api_filter)
+            .await
+            .unwrap();
+        assert_eq!(
+            req.search_after.as_deref(),
+            Some(
+                &[
+                    "1700000000".to_string(),
+                    "split-1:00000002:00000003".to_string()
+                ][..]
+            )
+        );
+        let search_request =
+            search_request_from_api_request(vec!["quickwit-demo-index".to_string()], req).unwrap();
+        let search_after = search_request.search_after.unwrap();
+        assert_eq!(
+            search_after.sort_value,
+            Some(SortByValue {
+                sort_value: Some(SortValue::U64(1_700_000_000))
+            })
+        );
+        assert!(search_after.sort_value2.is_none());
+        assert_eq!(search_after.split_id, "split-1");
+        assert_eq!(search_after.segment_ord, 2);
+        assert_eq!(search_after.doc_id, 3);
+    }
+
+    #[test]
+    fn test_partial_hit_from_search_after() {
+        let sort_fields = [
+            SortField {
+                field_name: "timestamp".to_string(),
+                sort_order: SortOrder::Desc as i32,
+                sort_datetime_format: None,
+            },
+            SortField {
+                field_name: "severity".to_string(),
+                sort_order: SortOrder::Asc as i32,
+                sort_datetime_format: None,
+            },
+        ];
+        let partial_hit =
+            partial_hit_from_search_after(vec!["-3".to_string(), "true".to_string()], &sort_fields)
+                .unwrap();
+        assert_eq!(
+            partial_hit.sort_value,
+            Some(SortByValue {
+                sort_value: Some(SortValue::I64(-3))
+            })
+        );
+        assert_eq!(
+            partial_hit.sort_value2,
+            Some(SortByValue {
+                sort_value: Some(SortValue::Boolean(true))
+            })
+        );
+        assert!(partial_hit.split_id.is_empty());
+
+        let search_after = search_after_from_partial_hit(&PartialHit {
+            split_id: "split-1".to_string(),
+            segment_ord: 2,
+            doc_id: 3,
+            ..partial_hit
+        });
+        assert_eq!(search_after, "-3,true,split-1:00000002:00000003");
+
+        let error = partial_hit_from_search_after(vec!["1".to_string()], &sort_fields).unwrap_err();
+        assert!(error.to_string().contains("expected 2 values, got 1"));
+
+        let error = partial_hit_from_search_after(
+            vec!["1".to_string(), "2".to_string(), "split-1".to_string()],
+            &sort_fields,
+        )
+        .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("invalid `search_after` hit address"));
+
+        let error =
+            partial_hit_from_search_after(vec!["warn".to_string(), "2".to_string()], &sort_fields)
+                .unwrap_err();
+        assert!(error.to_string().contains("invalid `search_after` value"));
+    }
+
+    #[tokio::test]
+    async fn test_rest_search_api_route_federation() {
+        let rest_search_api_filter = search_get_filter();
+        let (_indexes, req) = warp::test::request()
+            .path("/logs-*/search?query=*&max_hits_per_index=5&federation=balanced")
+            .filter(&rest_search_api_filter)
+            .await
+            .unwrap();
+        assert_eq!(req.max_hits_per_index, Some(5));
+        assert_eq!(req.federation, Federation::Balanced);
+
+        let search_request =
+            search_request_from_api_request(vec!["logs-*".to_string()], req).unwrap();
+        assert_eq!(search_request.max_hits_per_index, Some(5));
+        assert_eq!(search_request.federation(), Federation::Balanced);
+
+        let (_indexes, req) = warp::test::request()
+            .path("/logs-*/search?query=*")
+            .filter(&rest_search_api_filter)
+            .await
+            .unwrap();
+        assert_eq!(req.max_hits_per_index, None);
+        assert_eq!(req.federation, Federation::Global);
+    }
+
+    #[tokio::test]
+    async fn test_rest_search_api_route_preview() {
+        let rest_search_api_filter = search_get_filter();
+        let (_indexes, req) = warp::test::request()
+            .path("/logs/search?query=*&preview=true")
+            .filter(&rest_search_api_filter)
+            .await
+            .unwrap();
+        assert!(req.preview);
+
+        let search_request =
+            search_request_from_api_request(vec!["logs".to_string()], req).unwrap();
+        assert!(search_request.preview);
+    }
+
     #[tokio::test]
     async fn test_rest_search_api_route_count_all() {
         let rest_search_api_filter = search_get_filter();
@@ -880,6 +1603,14 @@ mod tests {
                     },
                 ],
             ),
+            (
+                "-(response_bytes%20%2F%20duration_ms)",
+                vec![SortField {
+                    field_name: "(response_bytes / duration_ms)".to_string(),
+                    sort_order: SortOrder::Asc as i32,
+                    sort_datetime_format: None,
+                }],
+            ),
         ] {
             let path = format!(
                 "/quickwit-demo-index/search?query=*&format=json&sort_by={}",
@@ -975,6 +1706,113 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_rest_search_api_partial_results() -> anyhow::Result<()> {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service.expect_root_search().returning(|_| {
+            Ok(quickwit_proto::search::SearchResponse {
+                num_hits: 3,
+                failed_splits: vec![quickwit_proto::search::SplitSearchError {
+                    error: "failed to open split".to_string(),
+                    split_id: "split-1".to_string(),
+                    retryable_error: false,
+                }],
+                ..Default::default()
+            })
+        });
+        let rest_search_api_handler = search_handler(mock_search_service);
+        let resp = warp::test::request()
+            .path("/quickwit-demo-index/search?query=*")
+            .reply(&rest_search_api_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body())?;
+        let expected_response_json = serde_json::json!({
+            "num_hits": 3,
+            "partial": true,
+            "failed_splits": [{
+                "error": "failed to open split",
+                "split_id": "split-1",
+                "retryable_error": false,
+            }],
+        });
+        assert_json_include!(actual: resp_json, expected: expected_response_json);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rest_search_api_skipped_splits() -> anyhow::Result<()> {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service.expect_root_search().returning(|_| {
+            Ok(quickwit_proto::search::SearchResponse {
+                num_hits: 3,
+                skipped_split_ids: vec!["split-1".to_string()],
+                ..Default::default()
+            })
+        });
+        let rest_search_api_handler = search_handler(mock_search_service);
+        let resp = warp::test::request()
+            .path("/quickwit-demo-index/search?query=*")
+            .reply(&rest_search_api_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body())?;
+        let expected_response_json = serde_json::json!({
+            "num_hits": 3,
+            "skipped_split_ids": ["split-1"],
+        });
+        assert_json_include!(actual: resp_json, expected: expected_response_json);
+        assert!(resp_json.get("partial").is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rest_search_api_next_search_after() -> anyhow::Result<()> {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service.expect_root_search().returning(|_| {
+            let hits = (0..2)
+                .map(|doc_id| quickwit_proto::search::Hit {
+                    json: r#"{"title": "foo"}"#.to_string(),
+                    partial_hit: Some(PartialHit {
+                        sort_value: Some(SortByValue {
+                            sort_value: Some(SortValue::I64(1_700_000_000 - doc_id as i64)),
+                        }),
+                        split_id: "split-1".to_string(),
+                        segment_ord: 0,
+                        doc_id,
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                })
+                .collect();
+            Ok(quickwit_proto::search::SearchResponse {
+                hits,
+                num_hits: 10,
+                ..Default::default()
+            })
+        });
+        let rest_search_api_handler = search_handler(mock_search_service);
+        let resp = warp::test::request()
+            .path("/quickwit-demo-index/search?query=*&sort_by=-timestamp&max_hits=2")
+            .reply(&rest_search_api_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body())?;
+        assert_eq!(
+            resp_json["next_search_after"],
+            "1699999999,split-1:00000000:00000001"
+        );
+
+        let resp = warp::test::request()
+            .path("/quickwit-demo-index/search?query=*&sort_by=-timestamp&max_hits=3")
+            .reply(&rest_search_api_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body())?;
+        assert!(resp_json.get("next_search_after").is_none());
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_rest_search_api_start_offset_and_num_hits_parameter() -> anyhow::Result<()> {
         let mut mock_search_service = MockSearchService::new();
@@ -998,6 +1836,52 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_rest_search_api_arrow_format() {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_root_search()
+            .with(predicate::function(
+                |search_request: &quickwit_proto::search::SearchRequest| {
+                    search_request.aggregation_format() == AggregationFormat::Arrow
+                },
+            ))
+            .returning(|_| {
+                Ok(quickwit_proto::search::SearchResponse {
+                    aggregation_arrow: Some(b"arrow-ipc-stream".to_vec()),
+                    ..Default::default()
+                })
+            });
+        let rest_search_api_handler = search_handler(mock_search_service);
+        let response = warp::test::request()
+            .method("POST")
+            .path("/my-index/search")
+            .json(&json!({
+                "query": "*",
+                "max_hits": 0,
+                "format": "arrow",
+                "aggs": {"by_host": {"terms": {"field": "host"}}}
+            }))
+            .reply(&rest_search_api_handler)
+            .await;
+        assert_eq!(response.status(), 200);
+        assert_eq!(
+            response.headers().get(CONTENT_TYPE).unwrap(),
+            "application/vnd.apache.arrow.stream"
+        );
+        assert_eq!(response.body(), &Bytes::from_static(b"arrow-ipc-stream"));
+
+        let response = warp::test::request()
+            .path("/my-index/search?query=*&format=arrow")
+            .reply(&rest_search_api_handler)
+            .await;
+        assert_eq!(response.status(), 400);
+        assert_eq!(
+            response.headers().get(CONTENT_TYPE).unwrap(),
+            "application/json"
+        );
+    }
+
     #[tokio::test]
     async fn test_rest_search_api_with_index_does_not_exist() -> anyhow::Result<()> {
         let mut mock_search_service = MockSearchService::new();
@@ -1139,7 +2023,8 @@ mod tests {
         let parse_error = rejection.find::<serde_qs::Error>().unwrap();
         assert_eq!(
             parse_error.to_string(),
-            "unknown variant `ClickHouseRowBinary`, expected `csv` or `click_house_row_binary`"
+            "unknown variant `ClickHouseRowBinary`, expected one of `csv`, \
+             `click_house_row_binary`, `arrow_ipc`"
         );
     }
 
@@ -1256,4 +2141,215 @@ mod tests {
             );
         }
     }
+
+    #[tokio::test]
+    async fn test_rest_search_api_api_key_query_limits() {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_root_search()
+            .returning(|_| Ok(Default::default()));
+        let api_key_query_limits = serde_json::from_value(json!({
+            "max_num_concurrent_queries": 1,
+            "retry_after_secs": 5,
+        }))
+        .unwrap();
+        let search_query_limiter = SearchQueryLimiter::new(Some(api_key_query_limits));
+        let rest_search_api_handler =
+            search_get_handler(Arc::new(mock_search_service), search_query_limiter.clone())
+                .recover(recover_fn);
+
+        let _search_permit = search_query_limiter
+            .acquire(Some("dashboard-api-key"))
+            .await
+            .ok()
+            .unwrap();
+        let response = warp::test::request()
+            .path("/quickwit-demo-index/search?query=*")
+            .header("x-api-key", "dashboard-api-key")
+            .reply(&rest_search_api_handler)
+            .await;
+        assert_eq!(response.status(), 429);
+        assert_eq!(response.headers().get("retry-after").unwrap(), "5");
+        let response_json: JsonValue = serde_json::from_slice(response.body()).unwrap();
+        let expected_response_json = json!({
+            "code": "rate_limited",
+            "message": "too many concurrent search queries for this API key",
+            "details": {"retry_after_secs": 5},
+            "retryable": true,
+        });
+        assert_eq!(response_json, expected_response_json);
+
+        let response = warp::test::request()
+            .path("/quickwit-demo-index/search?query=*")
+            .header("x-api-key", "other-api-key")
+            .reply(&rest_search_api_handler)
+            .await;
+        assert_eq!(response.status(), 200);
+    }
+
+    #[test]
+    fn test_prefix_end_key() {
+        assert_eq!(prefix_end_key(b""), None);
+        assert_eq!(prefix_end_key(b"bea"), Some(b"beb".to_vec()));
+        assert_eq!(prefix_end_key(b"be\xff"), Some(b"bf".to_vec()));
+        assert_eq!(prefix_end_key(b"\xff\xff"), None);
+    }
+
+    #[tokio::test]
+    async fn test_rest_count_api() {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_root_count()
+            .with(predicate::function(
+                |search_request: &quickwit_proto::search::SearchRequest| {
+                    search_request.index_id_patterns
+                        == vec!["logs-1".to_string(), "logs-2".to_string()]
+                        && search_request.max_hits == 0
+                        && search_request.count_hits() == CountHits::CountAll
+                        && search_request.start_timestamp == Some(1_000)
+                },
+            ))
+            .returning(|_| {
+                Ok(CountResponseRest {
+                    num_hits: 12,
+                    num_hits_per_index: Some(std::collections::BTreeMap::from_iter([
+                        ("logs-1".to_string(), 10),
+                        ("logs-2".to_string(), 2),
+                    ])),
+                    elapsed_time_micros: 16,
+                })
+            });
+        let rest_search_api_handler =
+            count_handler(Arc::new(mock_search_service), SearchQueryLimiter::default())
+                .recover(recover_fn);
+        let response = warp::test::request()
+            .method("POST")
+            .path("/logs-1,logs-2/count")
+            .json(&json!({"query": "severity:ERROR", "start_timestamp": 1_000}))
+            .reply(&rest_search_api_handler)
+            .await;
+        assert_eq!(response.status(), 200);
+        let response_json: JsonValue = serde_json::from_slice(response.body()).unwrap();
+        let expected_response_json = json!({
+            "num_hits": 12,
+            "num_hits_per_index": {"logs-1": 10, "logs-2": 2},
+            "elapsed_time_micros": 16,
+        });
+        assert_json_eq!(response_json, expected_response_json);
+
+        let response = warp::test::request()
+            .method("POST")
+            .path("/logs-1,logs-2/count")
+            .json(&json!({"query": "severity:ERROR", "max_hits": 10}))
+            .reply(&rest_search_api_handler)
+            .await;
+        assert_eq!(response.status(), 400);
+    }
+
+    #[tokio::test]
+    async fn test_rest_search_estimate_api() {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_root_estimate_search_cost()
+            .with(predicate::function(
+                |search_request: &quickwit_proto::search::SearchRequest| {
+                    search_request.index_id_patterns == vec!["quickwit-demo-index".to_string()]
+                        && search_request.max_hits == 10
+                },
+            ))
+            .returning(|_| {
+                Ok(SearchCostEstimate {
+                    num_splits: 3,
+                    num_docs: 3_000,
+                    num_footer_bytes: 300,
+                    num_warmup_bytes: 12_000,
+                    num_fetch_docs_bytes: 1_000,
+                    num_bytes: 13_300,
+                    latency_band: LatencyBand::Fast,
+                })
+            });
+        let rest_search_api_handler =
+            search_estimate_handler(Arc::new(mock_search_service)).recover(recover_fn);
+        let expected_response_json = json!({
+            "num_splits": 3,
+            "num_docs": 3_000,
+            "num_footer_bytes": 300,
+            "num_warmup_bytes": 12_000,
+            "num_fetch_docs_bytes": 1_000,
+            "num_bytes": 13_300,
+            "latency_band": "fast",
+        });
+        let response = warp::test::request()
+            .path("/quickwit-demo-index/search/estimate?query=*&max_hits=10")
+            .reply(&rest_search_api_handler)
+            .await;
+        assert_eq!(response.status(), 200);
+        let response_json: JsonValue = serde_json::from_slice(response.body()).unwrap();
+        assert_json_eq!(response_json, expected_response_json);
+
+        let response = warp::test::request()
+            .method("POST")
+            .path("/quickwit-demo-index/search/estimate")
+            .json(&json!({"query": "*", "max_hits": 10}))
+            .reply(&rest_search_api_handler)
+            .await;
+        assert_eq!(response.status(), 200);
+        let response_json: JsonValue = serde_json::from_slice(response.body()).unwrap();
+        assert_json_eq!(response_json, expected_response_json);
+    }
+
+    #[tokio::test]
+    async fn test_rest_list_terms_api() {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_root_list_terms()
+            .with(predicate::function(
+                |list_terms_request: &ListTermsRequest| {
+                    list_terms_request.index_id == "quickwit-demo-index"
+                        && list_terms_request.field == "service"
+                        && list_terms_request.start_key.as_deref() == Some(b"bea".as_slice())
+                        && list_terms_request.end_key.as_deref() == Some(b"beb".as_slice())
+                        && list_terms_request.max_hits == Some(10)
+                        && list_terms_request.include_doc_counts
+                },
+            ))
+            .returning(|_| {
+                Ok(quickwit_proto::search::ListTermsResponse {
+                    num_hits: 2,
+                    // Serialized tantivy terms: field ID, type code, and value.
+                    terms: vec![b"\0\0\0\0sbeagle".to_vec(), b"\0\0\0\0sbear".to_vec()],
+                    elapsed_time_micros: 16,
+                    errors: Vec::new(),
+                    doc_counts: vec![3, 1],
+                })
+            });
+        let rest_search_api_handler =
+            list_terms_handler(Arc::new(mock_search_service), SearchQueryLimiter::default())
+                .recover(recover_fn);
+        let response = warp::test::request()
+            .path(
+                "/indexes/quickwit-demo-index/terms?field=service&prefix=bea&limit=10&\
+                 include_doc_counts=true",
+            )
+            .reply(&rest_search_api_handler)
+            .await;
+        assert_eq!(response.status(), 200);
+        let response_json: JsonValue = serde_json::from_slice(response.body()).unwrap();
+        let expected_response_json = json!({
+            "num_hits": 2,
+            "terms": [
+                {"term": "beagle", "doc_count": 3},
+                {"term": "bear", "doc_count": 1},
+            ],
+            "elapsed_time_micros": 16,
+            "errors": [],
+        });
+        assert_json_eq!(response_json, expected_response_json);
+
+        let response = warp::test::request()
+            .path("/indexes/quickwit-demo-index/terms?prefix=bea")
+            .reply(&rest_search_api_handler)
+            .await;
+        assert_eq!(response.status(), 400);
+    }
 }
@@ -0,0 +1,377 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use hyper::HeaderMap;
+use quickwit_common::metrics::IntGauge;
+use quickwit_config::ApiKeyQueryLimitsConfig;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use warp::{Filter, Rejection};
+
+use crate::SERVE_METRICS;
+
+/// Number of tracked API keys above which the state of idle API keys is evicted.
+const MAX_NUM_TRACKED_API_KEYS: usize = 1_000;
+
+/// Rejection returned when an API key exceeds its query limits.
+#[derive(Debug)]
+pub(crate) struct TooManySearchQueries {
+    pub retry_after_secs: u64,
+}
+
+impl warp::reject::Reject for TooManySearchQueries {}
+
+/// Limits the number of in-flight search queries per API key. Queries exceeding the limit wait
+/// in a bounded queue and are rejected once the queue is full.
+#[derive(Clone, Default)]
+pub struct SearchQueryLimiter {
+    inner_opt: Option<Arc<InnerSearchQueryLimiter>>,
+}
+
+struct InnerSearchQueryLimiter {
+    config: ApiKeyQueryLimitsConfig,
+    api_key_states: Mutex<HashMap<String, Arc<ApiKeyState>>>,
+}
+
+struct ApiKeyState {
+    semaphore: Arc<Semaphore>,
+    max_num_queued_queries: usize,
+    num_queued_queries: AtomicUsize,
+    api_key_label: String,
+    in_flight_gauge: IntGauge,
+    queued_gauge: IntGauge,
+}
+
+impl ApiKeyState {
+    fn new(
+        api_key: &str,
+        max_num_concurrent_queries: NonZeroUsize,
+        max_num_queued_queries: usize,
+    ) -> Self {
+        let api_key_label = api_key_label(api_key);
+        let in_flight_gauge = SERVE_METRICS
+            .search_queries_in_flight
+            .with_label_values([&api_key_label]);
+        let queued_gauge = SERVE_METRICS
+            .search_queries_queued
+            .with_label_values([&api_key_label]);
+        Self {
+            semaphore: Arc::new(Semaphore::new(max_num_concurrent_queries.get())),
+            max_num_queued_queries,
+            num_queued_queries: AtomicUsize::new(0),
+            api_key_label,
+            in_flight_gauge,
+            queued_gauge,
+        }
+    }
+}
+
+/// Identifies an API key in metrics without leaking it.
+fn api_key_label(api_key: &str) -> String {
+    if api_key.is_empty() {
+        return "none".to_string();
+    }
+    let num_chars = api_key.chars().count();
+
+    if num_chars <= 8 {
+        return "****".to_string();
+    }
+    let suffix: String = api_key.chars().skip(num_chars - 4).collect();
+    format!("****{suffix}")
+}
+
+/// Accounts for a query waiting in the queue of an API key.
+struct QueuedQueryGuard<'a> {
+    api_key_state: &'a ApiKeyState,
+}
+
+impl<'a> QueuedQueryGuard<'a> {
+    fn try_new(api_key_state: &'a ApiKeyState) -> Option<Self> {
+        api_key_state
+            .num_queued_queries
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |num_queued_queries| {
+                (num_queued_queries < api_key_state.max_num_queued_queries)
+                    .then_some(num_queued_queries + 1)
+            })
+            .ok()?;
+        api_key_state.queued_gauge.inc();
+        Some(Self { api_key_state })
+    }
+}
+
+impl Drop for QueuedQueryGuard<'_> {
+    fn drop(&mut self) {
+        self.api_key_state
+            .num_queued_queries
+            .fetch_sub(1, Ordering::SeqCst);
+        self.api_key_state.queued_gauge.dec();
+    }
+}
+
+/// Grants the execution of a search query. The slot is released when the permit is dropped.
+pub(crate) struct SearchPermit {
+    api_key_state_opt: Option<Arc<ApiKeyState>>,
+    _semaphore_permit_opt: Option<OwnedSemaphorePermit>,
+}
+
+impl SearchPermit {
+    fn unlimited() -> Self {
+        Self {
+            api_key_state_opt: None,
+            _semaphore_permit_opt: None,
+        }
+    }
+
+    fn new(api_key_state: Arc<ApiKeyState>, semaphore_permit: OwnedSemaphorePermit) -> Self {
+        api_key_state.in_flight_gauge.inc();
+        SERVE_METRICS
+            .search_queries_total
+            .with_label_values([&api_key_state.api_key_label])
+            .inc();
+        Self {
+            api_key_state_opt: Some(api_key_state),
+            _semaphore_permit_opt: Some(semaphore_permit),
+        }
+    }
+}
+
+impl Drop for SearchPermit {
+    fn drop(&mut self) {
+        if let Some(api_key_state) = &self.api_key_state_opt {
+            api_key_state.in_flight_gauge.dec();
+        }
+    }
+}
+
+impl SearchQueryLimiter {
+    pub fn new(config_opt: Option<ApiKeyQueryLimitsConfig>) -> Self {
+        let inner_opt = config_opt.map(|config| {
+            Arc::new(InnerSearchQueryLimiter {
+                config,
+                api_key_states: Mutex::default(),
+            })
+        });
+        Self { inner_opt }
+    }
+
+    fn api_key_state(inner: &InnerSearchQueryLimiter, api_key: &str) -> Arc<ApiKeyState> {
+        let mut api_key_states = inner
+            .api_key_states
+            .lock()
+            .expect("lock should not be poisoned");
+
+        if let Some(api_key_state) = api_key_states.get(api_key) {
+            return api_key_state.clone();
+        }
+        if api_key_states.len() >= MAX_NUM_TRACKED_API_KEYS {
+            // The state of an idle API key is only referenced by this map.
+            api_key_states.retain(|_, api_key_state| Arc::strong_count(api_key_state) > 1);
+        }
+        let (max_num_concurrent_queries, max_num_queued_queries) =
+            inner.config.limits_for_api_key(api_key);
+        let api_key_state = Arc::new(ApiKeyState::new(
+            api_key,
+            max_num_concurrent_queries,
+            max_num_queued_queries,
+        ));
+        api_key_states.insert(api_key.to_string(), api_key_state.clone());
+        api_key_state
+    }
+
+    /// Waits for an execution slot for a query submitted with `api_key_opt`, or fails right away
+    /// if the queue of the API key is full.
+    pub async fn acquire(
+        &self,
+        api_key_opt: Option<&str>,
+    ) -> Result<SearchPermit, TooManySearchQueries> {
+        let Some(inner) = &self.inner_opt else {
+            return Ok(SearchPermit::unlimited());
+        };
+        let api_key_state = Self::api_key_state(inner, api_key_opt.unwrap_or_default());
+
+        if let Ok(semaphore_permit) = api_key_state.semaphore.clone().try_acquire_owned() {
+            return Ok(SearchPermit::new(api_key_state, semaphore_permit));
+        }
+        let Some(queued_query_guard) = QueuedQueryGuard::try_new(&api_key_state) else {
+            SERVE_METRICS
+                .search_queries_rejected_total
+                .with_label_values([&api_key_state.api_key_label])
+                .inc();
+            return Err(TooManySearchQueries {
+                retry_after_secs: inner.config.retry_after_secs.get(),
+            });
+        };
+        let semaphore_permit = api_key_state
+            .semaphore
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("semaphore should not be closed");
+        drop(queued_query_guard);
+        Ok(SearchPermit::new(api_key_state, semaphore_permit))
+    }
+
+    fn api_key_from_headers<'a>(&self, headers: &'a HeaderMap) -> Option<&'a str> {
+        let inner = self.inner_opt.as_ref()?;
+        headers
+            .get(inner.config.api_key_header.as_str())
+            .and_then(|header_value| header_value.to_str().ok())
+    }
+}
+
+/// Extracts the API key of the request and waits for an execution slot for the query.
+pub(crate) fn search_permit_filter(
+    search_query_limiter: SearchQueryLimiter,
+) -> impl Filter<Extract = (SearchPermit,), Error = Rejection> + Clone {
+    warp::header::headers_cloned().and_then(move |headers: HeaderMap| {
+        let search_query_limiter = search_query_limiter.clone();
+        async move {
+            let api_key_opt = search_query_limiter.api_key_from_headers(&headers);
+            search_query_limiter
+                .acquire(api_key_opt)
+                .await
+                .map_err(warp::reject::custom)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn limits_config_for_test(
+        max_num_concurrent_queries: usize,
+        max_num_queued_queries: usize,
+    ) -> ApiKeyQueryLimitsConfig {
+        serde_json::from_value(serde_json::json!({
+            "max_num_concurrent_queries": max_num_concurrent_queries,
+            "max_num_queued_queries": max_num_queued_queries,
+            "retry_after_secs": 3,
+            "overrides": [{
+                "api_key": "privileged-api-key",
+                "max_num_concurrent_queries": 2,
+            }]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_api_key_label() {
+        assert_eq!(api_key_label(""), "none");
+        assert_eq!(api_key_label("short"), "****");
+        assert_eq!(api_key_label("my-secret-api-key"), "****-key");
+    }
+
+    #[tokio::test]
+    async fn test_search_query_limiter_disabled() {
+        let search_query_limiter = SearchQueryLimiter::default();
+        let _permits: Vec<SearchPermit> = futures::future::try_join_all(
+            (0..10).map(|_| search_query_limiter.acquire(Some("api-key"))),
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_search_query_limiter_rejects_when_queue_is_full() {
+        let search_query_limiter = SearchQueryLimiter::new(Some(limits_config_for_test(1, 0)));
+
+        let permit = search_query_limiter.acquire(Some("api-key")).await.unwrap();
+        let rejection = search_query_limiter
+            .acquire(Some("api-key"))
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(rejection.retry_after_secs, 3);
+
+        // Other API keys are not affected.
+        let _other_permit = search_query_limiter
+            .acquire(Some("other-api-key"))
+            .await
+            .unwrap();
+        let _anonymous_permit = search_query_limiter.acquire(None).await.unwrap();
+
+        // Overrides take precedence over the default limits.
+        let _privileged_permits = (
+            search_query_limiter
+                .acquire(Some("privileged-api-key"))
+                .await
+                .unwrap(),
+            search_query_limiter
+                .acquire(Some("privileged-api-key"))
+                .await
+                .unwrap(),
+        );
+        drop(permit);
+        search_query_limiter.acquire(Some("api-key")).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_search_query_limiter_queues_queries() {
+        let search_query_limiter = SearchQueryLimiter::new(Some(limits_config_for_test(1, 1)));
+
+        let permit = search_query_limiter.acquire(Some("api-key")).await.unwrap();
+
+        let search_query_limiter_clone = search_query_limiter.clone();
+        let queued_query_handle = tokio::spawn(async move {
+            search_query_limiter_clone
+                .acquire(Some("api-key"))
+                .await
+                .is_ok()
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        // The queue is full.
+        assert!(search_query_limiter.acquire(Some("api-key")).await.is_err());
+
+        drop(permit);
+        assert!(queued_query_handle.await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_search_permit_filter() {
+        let search_query_limiter = SearchQueryLimiter::new(Some(limits_config_for_test(1, 0)));
+        let filter = search_permit_filter(search_query_limiter.clone());
+
+        let _permit = warp::test::request()
+            .header("x-api-key", "api-key")
+            .filter(&filter)
+            .await
+            .unwrap();
+        let rejection = warp::test::request()
+            .header("x-api-key", "api-key")
+            .filter(&filter)
+            .await
+            .err()
+            .unwrap();
+        assert!(rejection.find::<TooManySearchQueries>().is_some());
+
+        warp::test::request()
+            .header("x-api-key", "other-api-key")
+            .filter(&filter)
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,127 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+
+use quickwit_cluster::{Cluster, ListenerHandle};
+use quickwit_common::shared_consts::SEARCHER_CONFIG_OVERRIDES_KEY;
+use quickwit_config::SearcherConfigOverrides;
+use quickwit_search::SearcherContext;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+/// Overrides of the searcher config broadcast in chitchat, along with their version.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct VersionedSearcherConfigOverrides {
+    pub version: u64,
+    pub overrides: SearcherConfigOverrides,
+}
+
+/// Broadcasts the overrides of the searcher config to the other nodes of the cluster.
+pub(super) async fn broadcast_searcher_config_overrides(
+    cluster: &Cluster,
+    versioned_overrides: &VersionedSearcherConfigOverrides,
+) {
+    let value = serde_json::to_string(versioned_overrides)
+        .expect("searcher config overrides should be JSON serializable");
+    cluster
+        .set_self_key_value(SEARCHER_CONFIG_OVERRIDES_KEY, value)
+        .await;
+}
+
+/// Applies the overrides of the searcher config broadcast by the other nodes of the cluster.
+///
+/// The node broadcasts in turn the overrides it applies so that they outlive the node that
+/// received them and reach the nodes joining the cluster later on.
+pub(crate) async fn setup_searcher_config_overrides_listener(
+    cluster: Cluster,
+    searcher_context: Arc<SearcherContext>,
+) -> ListenerHandle {
+    let cluster_clone = cluster.clone();
+
+    cluster
+        .subscribe(SEARCHER_CONFIG_OVERRIDES_KEY, move |event| {
+            if event.key != SEARCHER_CONFIG_OVERRIDES_KEY {
+                return;
+            }
+            let Ok(versioned_overrides) =
+                serde_json::from_str::<VersionedSearcherConfigOverrides>(event.value)
+            else {
+                warn!(
+                    "failed to parse searcher config overrides `{}`",
+                    event.value
+                );
+                return;
+            };
+            if !searcher_context.apply_config_overrides(
+                versioned_overrides.version,
+                versioned_overrides.overrides.clone(),
+            ) {
+                return;
+            }
+            let cluster = cluster_clone.clone();
+
+            tokio::spawn(async move {
+                broadcast_searcher_config_overrides(&cluster, &versioned_overrides).await;
+            });
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bytesize::ByteSize;
+    use quickwit_cluster::{create_cluster_for_test, ChannelTransport};
+    use quickwit_config::SearcherConfig;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_searcher_config_overrides_listener() {
+        let transport = ChannelTransport::default();
+        let cluster = create_cluster_for_test(Vec::new(), &["searcher"], &transport, true)
+            .await
+            .unwrap();
+        let searcher_context = Arc::new(SearcherContext::new(SearcherConfig::default(), None));
+        let _listener_handle =
+            setup_searcher_config_overrides_listener(cluster.clone(), searcher_context.clone())
+                .await;
+
+        let overrides = SearcherConfigOverrides {
+            aggregation_memory_limit: Some(ByteSize::mb(100)),
+            ..Default::default()
+        };
+        let versioned_overrides = VersionedSearcherConfigOverrides {
+            version: 1,
+            overrides: overrides.clone(),
+        };
+        broadcast_searcher_config_overrides(&cluster, &versioned_overrides).await;
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while searcher_context.config_overrides().0 != 1 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(searcher_context.config_overrides(), (1, overrides));
+    }
+}
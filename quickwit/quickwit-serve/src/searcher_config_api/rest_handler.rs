@@ -0,0 +1,227 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use quickwit_cluster::Cluster;
+use quickwit_config::SearcherConfigOverrides;
+use quickwit_proto::ServiceErrorCode;
+use quickwit_search::SearcherContext;
+use serde::Serialize;
+use time::OffsetDateTime;
+use tracing::info;
+use warp::{Filter, Rejection};
+
+use super::broadcast::{broadcast_searcher_config_overrides, VersionedSearcherConfigOverrides};
+use crate::format::extract_format_from_qs;
+use crate::json_api_response::{make_json_api_response, ApiError};
+use crate::with_arg;
+
+#[derive(utoipa::OpenApi)]
+#[openapi(
+    paths(get_searcher_config, update_searcher_config),
+    components(schemas(SearcherConfigResponse, SearcherConfigOverrides))
+)]
+pub struct SearcherConfigApi;
+
+/// Searcher settings adjusted at runtime, as applied by the node serving the request.
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct SearcherConfigResponse {
+    /// Version of the overrides, 0 if no overrides were ever applied.
+    pub version: u64,
+    /// Overrides applied at runtime.
+    pub overrides: SearcherConfigOverrides,
+    /// Value of the settings once the overrides are applied to the searcher config of the node.
+    pub effective_config: SearcherConfigOverrides,
+}
+
+impl SearcherConfigResponse {
+    fn from_searcher_context(searcher_context: &SearcherContext) -> Self {
+        let (version, overrides) = searcher_context.config_overrides();
+        let effective_config = SearcherConfigOverrides::from_searcher_config(
+            &searcher_context.effective_searcher_config(),
+        );
+        Self {
+            version,
+            overrides,
+            effective_config,
+        }
+    }
+}
+
+pub fn searcher_config_handlers(
+    cluster: Cluster,
+    searcher_context: Arc<SearcherContext>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    get_searcher_config_handler(searcher_context.clone())
+        .or(update_searcher_config_handler(cluster, searcher_context))
+}
+
+fn get_searcher_config_handler(
+    searcher_context: Arc<SearcherContext>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("config" / "searcher")
+        .and(warp::get())
+        .and(with_arg(searcher_context))
+        .then(get_searcher_config)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    get,
+    tag = "Searcher Config",
+    path = "/config/searcher",
+    responses(
+        (status = 200, description = "Successfully fetched the searcher config.", body = SearcherConfigResponse)
+    )
+)]
+
+/// Get the searcher settings adjusted at runtime.
+async fn get_searcher_config(
+    searcher_context: Arc<SearcherContext>,
+) -> Result<SearcherConfigResponse, Infallible> {
+    Ok(SearcherConfigResponse::from_searcher_context(
+        &searcher_context,
+    ))
+}
+
+fn update_searcher_config_handler(
+    cluster: Cluster,
+    searcher_context: Arc<SearcherContext>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("config" / "searcher")
+        .and(warp::put())
+        .and(warp::body::content_length_limit(1024 * 1024))
+        .and(warp::body::json())
+        .and(with_arg(cluster))
+        .and(with_arg(searcher_context))
+        .then(update_searcher_config)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    put,
+    tag = "Searcher Config",
+    path = "/config/searcher",
+    request_body = SearcherConfigOverrides,
+    responses(
+        (status = 200, description = "Successfully updated the searcher config.", body = SearcherConfigResponse)
+    )
+)]
+
+/// Overrides searcher settings across the cluster without restarting the nodes.
+///
+/// The overrides replace the previous ones: the settings missing from the request fall back to
+/// the searcher config of each node. They are broadcast to the other nodes and are not persisted:
+/// they are lost once all the nodes of the cluster restart.
+async fn update_searcher_config(
+    overrides: SearcherConfigOverrides,
+    cluster: Cluster,
+    searcher_context: Arc<SearcherContext>,
+) -> Result<SearcherConfigResponse, ApiError> {
+    overrides
+        .validate()
+        .map_err(|error| ApiError::new(ServiceErrorCode::BadRequest, error.to_string()))?;
+    info!(overrides=?overrides, "update-searcher-config");
+
+    // The version must increase with each update, even if the clocks of the nodes drift apart.
+    let now_millis = (OffsetDateTime::now_utc().unix_timestamp_nanos() / 1_000_000) as u64;
+    let current_version = searcher_context.config_overrides().0;
+    let version = now_millis.max(current_version + 1);
+
+    searcher_context.apply_config_overrides(version, overrides.clone());
+
+    let versioned_overrides = VersionedSearcherConfigOverrides { version, overrides };
+    broadcast_searcher_config_overrides(&cluster, &versioned_overrides).await;
+
+    Ok(SearcherConfigResponse::from_searcher_context(
+        &searcher_context,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_cluster::{create_cluster_for_test, ChannelTransport};
+    use quickwit_config::SearcherConfig;
+    use serde_json::{json, Value as JsonValue};
+
+    use super::*;
+    use crate::recover_fn;
+
+    #[tokio::test]
+    async fn test_searcher_config_handlers() {
+        let transport = ChannelTransport::default();
+        let cluster = create_cluster_for_test(Vec::new(), &["searcher"], &transport, true)
+            .await
+            .unwrap();
+        let searcher_context = Arc::new(SearcherContext::new(SearcherConfig::default(), None));
+        let handler =
+            searcher_config_handlers(cluster, searcher_context.clone()).recover(recover_fn);
+
+        let resp = warp::test::request()
+            .path("/config/searcher")
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(resp_json["version"], 0);
+        assert_eq!(resp_json["overrides"], json!({}));
+        assert_eq!(
+            resp_json["effective_config"]["max_num_concurrent_split_searches"],
+            100
+        );
+
+        let resp = warp::test::request()
+            .method("PUT")
+            .path("/config/searcher")
+            .json(&json!({"max_num_concurrent_split_searches": 0}))
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 400);
+
+        let resp = warp::test::request()
+            .method("PUT")
+            .path("/config/searcher")
+            .json(
+                &json!({"max_num_concurrent_split_searches": 50, "aggregation_bucket_limit": 1000}),
+            )
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        assert!(resp_json["version"].as_u64().unwrap() > 0);
+        assert_eq!(
+            resp_json["overrides"],
+            json!({"max_num_concurrent_split_searches": 50, "aggregation_bucket_limit": 1000})
+        );
+        assert_eq!(
+            resp_json["effective_config"]["max_num_concurrent_split_searches"],
+            50
+        );
+        assert_eq!(
+            searcher_context
+                .effective_searcher_config()
+                .aggregation_bucket_limit,
+            1000
+        );
+    }
+}
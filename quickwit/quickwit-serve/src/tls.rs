@@ -0,0 +1,337 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::io;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{bail, Context};
+use arc_swap::ArcSwap;
+use futures::Stream;
+use quickwit_common::tower::enable_client_tls;
+use quickwit_config::TlsConfig;
+use quickwit_proto::tonic::transport::{Certificate, ClientTlsConfig};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::signal::unix::{signal, Signal, SignalKind};
+use tokio::sync::mpsc;
+use tokio::time::MissedTickBehavior;
+use tokio_rustls::rustls::server::{ClientHello, ResolvesServerCert};
+use tokio_rustls::rustls::sign::{any_supported_type, CertifiedKey};
+use tokio_rustls::rustls::{self, ServerConfig};
+use tokio_rustls::server::TlsStream;
+use tokio_rustls::TlsAcceptor;
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::{debug, error, info, warn};
+
+/// ALPN protocols advertised by the gRPC listener.
+pub(crate) const GRPC_ALPN_PROTOCOLS: &[&[u8]] = &[b"h2"];
+
+/// ALPN protocols advertised by the REST listener.
+pub(crate) const REST_ALPN_PROTOCOLS: &[&[u8]] = &[b"h2", b"http/1.1"];
+
+/// Maximum number of established TLS connections waiting to be picked up by the server.
+const INCOMING_QUEUE_CAPACITY: usize = 128;
+
+/// Maximum duration of a TLS handshake.
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Resolves the certificate presented by the REST and gRPC listeners. The certificate is swapped
+/// in place when it is reloaded, so new connections pick it up without restarting the node while
+/// established connections keep going.
+pub(crate) struct ReloadableCertResolver {
+    certified_key: ArcSwap<CertifiedKey>,
+}
+
+impl ReloadableCertResolver {
+    fn load(cert_path: &Path, key_path: &Path) -> anyhow::Result<Self> {
+        let certified_key = load_certified_key(cert_path, key_path)?;
+        Ok(Self {
+            certified_key: ArcSwap::from_pointee(certified_key),
+        })
+    }
+
+    /// Reloads the certificate and private key from disk. The current certificate is kept if the
+    /// files cannot be loaded.
+    fn reload(&self, cert_path: &Path, key_path: &Path) -> anyhow::Result<()> {
+        let certified_key = load_certified_key(cert_path, key_path)?;
+        self.certified_key.store(Arc::new(certified_key));
+        Ok(())
+    }
+}
+
+impl ResolvesServerCert for ReloadableCertResolver {
+    fn resolve(&self, _client_hello: ClientHello) -> Option<Arc<CertifiedKey>> {
+        Some(self.certified_key.load_full())
+    }
+}
+
+fn load_certified_key(cert_path: &Path, key_path: &Path) -> anyhow::Result<CertifiedKey> {
+    let cert_pem = std::fs::read(cert_path)
+        .with_context(|| format!("failed to read certificate file `{}`", cert_path.display()))?;
+    let cert_chain: Vec<rustls::Certificate> = rustls_pemfile::certs(&mut cert_pem.as_slice())
+        .with_context(|| format!("failed to parse certificate file `{}`", cert_path.display()))?
+        .into_iter()
+        .map(rustls::Certificate)
+        .collect();
+    if cert_chain.is_empty() {
+        bail!("no certificate found in `{}`", cert_path.display());
+    }
+    let key_pem = std::fs::read(key_path)
+        .with_context(|| format!("failed to read private key file `{}`", key_path.display()))?;
+    let private_key = rustls_pemfile::read_all(&mut key_pem.as_slice())
+        .with_context(|| format!("failed to parse private key file `{}`", key_path.display()))?
+        .into_iter()
+        .find_map(|item| match item {
+            rustls_pemfile::Item::RSAKey(key_der)
+            | rustls_pemfile::Item::PKCS8Key(key_der)
+            | rustls_pemfile::Item::ECKey(key_der) => Some(rustls::PrivateKey(key_der)),
+            _ => None,
+        })
+        .with_context(|| format!("no private key found in `{}`", key_path.display()))?;
+    let signing_key = any_supported_type(&private_key)
+        .with_context(|| format!("unsupported private key type in `{}`", key_path.display()))?;
+    Ok(CertifiedKey::new(cert_chain, signing_key))
+}
+
+/// Loads the certificate of the node, starts the task that reloads it when the files change or
+/// when the process receives a `SIGHUP` signal, and enables TLS on the gRPC clients connecting to
+/// the other nodes.
+pub(crate) fn start_tls(tls_config: &TlsConfig) -> anyhow::Result<Arc<ReloadableCertResolver>> {
+    let cert_resolver = Arc::new(ReloadableCertResolver::load(
+        &tls_config.cert_path,
+        &tls_config.key_path,
+    )?);
+    let ca_pem = std::fs::read(&tls_config.ca_path).with_context(|| {
+        format!(
+            "failed to read CA certificate file `{}`",
+            tls_config.ca_path.display()
+        )
+    })?;
+    let mut client_tls_config =
+        ClientTlsConfig::new().ca_certificate(Certificate::from_pem(ca_pem));
+
+    if let Some(domain_name) = &tls_config.domain_name {
+        client_tls_config = client_tls_config.domain_name(domain_name);
+    }
+    enable_client_tls(client_tls_config).context("failed to configure gRPC client TLS")?;
+
+    tokio::spawn(reload_certs_loop(cert_resolver.clone(), tls_config.clone()));
+    Ok(cert_resolver)
+}
+
+fn files_modified_at(tls_config: &TlsConfig) -> [Option<SystemTime>; 2] {
+    [&tls_config.cert_path, &tls_config.key_path].map(|path| {
+        std::fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    })
+}
+
+async fn recv_signal(signal_opt: &mut Option<Signal>) {
+    match signal_opt {
+        Some(signal) => {
+            signal.recv().await;
+        }
+        None => futures::future::pending().await,
+    }
+}
+
+async fn reload_certs_loop(cert_resolver: Arc<ReloadableCertResolver>, tls_config: TlsConfig) {
+    let mut sighup_opt = match signal(SignalKind::hangup()) {
+        Ok(sighup) => Some(sighup),
+        Err(error) => {
+            warn!(error=%error, "failed to register `SIGHUP` signal handler");
+            None
+        }
+    };
+    let mut interval = tokio::time::interval(tls_config.reload_interval());
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    interval.tick().await;
+
+    let mut last_modified_at = files_modified_at(&tls_config);
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {
+                let modified_at = files_modified_at(&tls_config);
+
+                if modified_at == last_modified_at {
+                    continue;
+                }
+                last_modified_at = modified_at;
+                debug!("TLS certificate files changed");
+            }
+            _ = recv_signal(&mut sighup_opt) => {
+                last_modified_at = files_modified_at(&tls_config);
+                debug!("received `SIGHUP` signal");
+            }
+        }
+        match cert_resolver.reload(&tls_config.cert_path, &tls_config.key_path) {
+            Ok(()) => info!(
+                cert_path=%tls_config.cert_path.display(),
+                "reloaded TLS certificate"
+            ),
+            Err(error) => error!(
+                error=?error,
+                "failed to reload TLS certificate, keeping the current one"
+            ),
+        }
+    }
+}
+
+/// Builds a TLS acceptor presenting the certificate of `cert_resolver`.
+pub(crate) fn make_tls_acceptor(
+    cert_resolver: Arc<ReloadableCertResolver>,
+    alpn_protocols: &[&[u8]],
+) -> TlsAcceptor {
+    let mut server_config = ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_cert_resolver(cert_resolver);
+    server_config.alpn_protocols = alpn_protocols
+        .iter()
+        .map(|protocol| protocol.to_vec())
+        .collect();
+    TlsAcceptor::from(Arc::new(server_config))
+}
+
+/// Binds a TCP listener to `listen_addr` and returns the stream of the TLS connections
+/// established with it. Handshakes are performed concurrently so that a slow or misbehaving
+/// client does not hold back the others, and failed handshakes are logged and dropped rather
+/// than surfaced to the server, which would otherwise stop accepting connections.
+pub(crate) async fn tls_incoming(
+    listen_addr: SocketAddr,
+    tls_acceptor: TlsAcceptor,
+) -> io::Result<impl Stream<Item = io::Result<TlsStream<TcpStream>>>> {
+    let listener = TcpListener::bind(listen_addr).await?;
+    let (incoming_tx, incoming_rx) = mpsc::channel(INCOMING_QUEUE_CAPACITY);
+
+    tokio::spawn(async move {
+        loop {
+            let (tcp_stream, peer_addr) = match listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(error) => {
+                    warn!(error=%error, "failed to accept TCP connection");
+                    // Errors such as running out of file descriptors are usually transient.
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    continue;
+                }
+            };
+            if incoming_tx.is_closed() {
+                return;
+            }
+            if let Err(error) = tcp_stream.set_nodelay(true) {
+                debug!(error=%error, "failed to set `TCP_NODELAY` on connection");
+            }
+            let tls_acceptor = tls_acceptor.clone();
+            let incoming_tx = incoming_tx.clone();
+
+            tokio::spawn(async move {
+                let handshake_res =
+                    tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, tls_acceptor.accept(tcp_stream))
+                        .await;
+                match handshake_res {
+                    Ok(Ok(tls_stream)) => {
+                        let _ = incoming_tx.send(Ok(tls_stream)).await;
+                    }
+                    Ok(Err(error)) => {
+                        debug!(peer_addr=%peer_addr, error=%error, "TLS handshake failed");
+                    }
+                    Err(_) => {
+                        debug!(peer_addr=%peer_addr, "TLS handshake timed out");
+                    }
+                }
+            });
+        }
+    });
+    Ok(ReceiverStream::new(incoming_rx))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn tls_resource_path(filename: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("resources/tests/tls")
+            .join(filename)
+    }
+
+    #[test]
+    fn test_load_certified_key() {
+        let certified_key = load_certified_key(
+            &tls_resource_path("cert-1.pem"),
+            &tls_resource_path("key-1.pem"),
+        )
+        .unwrap();
+        assert_eq!(certified_key.cert.len(), 1);
+
+        let error = load_certified_key(
+            &tls_resource_path("cert-1.pem"),
+            &tls_resource_path("does-not-exist.pem"),
+        )
+        .unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("failed to read private key file"));
+
+        let error = load_certified_key(
+            &tls_resource_path("key-1.pem"),
+            &tls_resource_path("key-1.pem"),
+        )
+        .unwrap_err();
+        assert!(error.to_string().starts_with("no certificate found"));
+
+        let error = load_certified_key(
+            &tls_resource_path("cert-1.pem"),
+            &tls_resource_path("cert-1.pem"),
+        )
+        .unwrap_err();
+        assert!(error.to_string().starts_with("no private key found"));
+    }
+
+    #[test]
+    fn test_reloadable_cert_resolver() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let cert_path = temp_dir.path().join("cert.pem");
+        let key_path = temp_dir.path().join("key.pem");
+
+        std::fs::copy(tls_resource_path("cert-1.pem"), &cert_path).unwrap();
+        std::fs::copy(tls_resource_path("key-1.pem"), &key_path).unwrap();
+
+        let cert_resolver = ReloadableCertResolver::load(&cert_path, &key_path).unwrap();
+        let cert_1 = cert_resolver.certified_key.load().cert.clone();
+
+        std::fs::copy(tls_resource_path("cert-2.pem"), &cert_path).unwrap();
+        std::fs::copy(tls_resource_path("key-2.pem"), &key_path).unwrap();
+
+        cert_resolver.reload(&cert_path, &key_path).unwrap();
+        let cert_2 = cert_resolver.certified_key.load().cert.clone();
+        assert_ne!(cert_1, cert_2);
+
+        // A broken certificate file does not replace the current certificate.
+        std::fs::write(&cert_path, b"not a certificate").unwrap();
+        cert_resolver.reload(&cert_path, &key_path).unwrap_err();
+        assert_eq!(cert_resolver.certified_key.load().cert, cert_2);
+    }
+}
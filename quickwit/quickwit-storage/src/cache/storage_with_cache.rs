@@ -28,7 +28,7 @@ use tokio::io::AsyncRead;
 
 use crate::cache::StorageCache;
 use crate::storage::SendableAsync;
-use crate::{BulkDeleteError, OwnedBytes, Storage, StorageResult};
+use crate::{BulkDeleteError, OwnedBytes, Storage, StorageFileEntry, StorageResult};
 
 /// Use with care, StorageWithCache is read-only.
 pub struct StorageWithCache {
@@ -109,6 +109,10 @@ impl Storage for StorageWithCache {
         self.storage.file_num_bytes(path).await
     }
 
+    async fn list(&self, prefix: &Path) -> StorageResult<Vec<StorageFileEntry>> {
+        self.storage.list(prefix).await
+    }
+
     fn uri(&self) -> &Uri {
         self.storage.uri()
     }
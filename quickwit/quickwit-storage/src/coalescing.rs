@@ -0,0 +1,338 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures::future::join_all;
+use quickwit_common::uri::Uri;
+use tantivy::directory::OwnedBytes;
+use tokio::io::AsyncRead;
+use tokio::sync::oneshot;
+
+use crate::storage::SendableAsync;
+use crate::{
+    BulkDeleteError, Storage, StorageErrorKind, StorageFileEntry, StorageResult, STORAGE_METRICS,
+};
+
+/// Settings of the [`CoalescingStorage`].
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ReadCoalescingSettings {
+    /// Reads separated by a gap smaller than or equal to this threshold are merged. The bytes of
+    /// the gap are fetched and discarded.
+    pub max_gap_num_bytes: usize,
+    /// Reads are not merged beyond this size. Reads larger than this size bypass the coalescing
+    /// layer.
+    pub max_merged_num_bytes: usize,
+    /// Duration during which reads are accumulated before being issued.
+    pub window: Duration,
+}
+
+impl Default for ReadCoalescingSettings {
+    fn default() -> Self {
+        Self {
+            max_gap_num_bytes: 64 * 1024,
+            max_merged_num_bytes: 8 * 1024 * 1024,
+            window: Duration::from_millis(1),
+        }
+    }
+}
+
+struct PendingRead {
+    range: Range<usize>,
+    response_tx: oneshot::Sender<StorageResult<OwnedBytes>>,
+}
+
+type PendingReads = Arc<Mutex<HashMap<PathBuf, Vec<PendingRead>>>>;
+
+/// Merges the adjacent or overlapping byte range reads of a same file issued within a short window
+/// into fewer, larger reads. During warmup, a searcher emits many small concurrent reads (term
+/// dictionaries, postings, fast fields...) that often sit next to each other in the split file.
+/// Merging them reduces the number of requests sent to the object store, which are billed per
+/// request.
+pub(crate) struct CoalescingStorage<T> {
+    // wrap in Arc, because the reads are issued from a spawned task
+    underlying: Arc<T>,
+    settings: ReadCoalescingSettings,
+    pending_reads: PendingReads,
+}
+
+impl<T> fmt::Debug for CoalescingStorage<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CoalescingStorage")
+            .field("settings", &self.settings)
+            .finish()
+    }
+}
+
+impl<T: Storage> CoalescingStorage<T> {
+    pub(crate) fn new(underlying: T, settings: ReadCoalescingSettings) -> Self {
+        Self {
+            underlying: Arc::new(underlying),
+            settings,
+            pending_reads: Default::default(),
+        }
+    }
+}
+
+/// Groups the reads sorted by start offset so that the reads of a group are separated by at most
+/// `max_gap_num_bytes` and a group spans at most `max_merged_num_bytes`.
+fn group_reads(
+    mut reads: Vec<PendingRead>,
+    settings: &ReadCoalescingSettings,
+) -> Vec<(Range<usize>, Vec<PendingRead>)> {
+    reads.sort_by_key(|read| (read.range.start, read.range.end));
+
+    let mut groups: Vec<(Range<usize>, Vec<PendingRead>)> = Vec::new();
+
+    for read in reads {
+        if let Some((group_range, group_reads)) = groups.last_mut() {
+            let merged_end = group_range.end.max(read.range.end);
+
+            if read.range.start <= group_range.end + settings.max_gap_num_bytes
+                && merged_end - group_range.start <= settings.max_merged_num_bytes
+            {
+                group_range.end = merged_end;
+                group_reads.push(read);
+                continue;
+            }
+        }
+        groups.push((read.range.clone(), vec![read]));
+    }
+    groups
+}
+
+async fn execute_reads<T: Storage>(
+    underlying: &T,
+    path: &Path,
+    reads: Vec<PendingRead>,
+    settings: &ReadCoalescingSettings,
+) {
+    let groups = group_reads(reads, settings);
+    let num_coalesced_reads = groups
+        .iter()
+        .map(|(_, group_reads)| group_reads.len() - 1)
+        .sum::<usize>();
+    STORAGE_METRICS
+        .object_storage_coalesced_reads_total
+        .inc_by(num_coalesced_reads as u64);
+
+    let group_futures = groups
+        .into_iter()
+        .map(|(group_range, group_reads)| async move {
+            let get_slice_result = underlying.get_slice(path, group_range.clone()).await;
+
+            for read in group_reads {
+                let read_result = match &get_slice_result {
+                    Ok(bytes) => {
+                        let start = read.range.start - group_range.start;
+                        let end = read.range.end - group_range.start;
+                        Ok(bytes.slice(start..end))
+                    }
+                    Err(error) => Err(error.clone()),
+                };
+                // The caller may have been cancelled in the meantime.
+                let _ = read.response_tx.send(read_result);
+            }
+        });
+    join_all(group_futures).await;
+}
+
+#[async_trait]
+impl<T: Storage> Storage for CoalescingStorage<T> {
+    async fn check_connectivity(&self) -> anyhow::Result<()> {
+        self.underlying.check_connectivity().await
+    }
+
+    async fn put(
+        &self,
+        path: &Path,
+        payload: Box<dyn crate::PutPayload>,
+    ) -> crate::StorageResult<()> {
+        self.underlying.put(path, payload).await
+    }
+
+    async fn copy_to(&self, path: &Path, output: &mut dyn SendableAsync) -> StorageResult<()> {
+        self.underlying.copy_to(path, output).await
+    }
+
+    async fn get_slice(&self, path: &Path, range: Range<usize>) -> StorageResult<OwnedBytes> {
+        if range.is_empty() || range.len() >= self.settings.max_merged_num_bytes {
+            return self.underlying.get_slice(path, range).await;
+        }
+        let (response_tx, response_rx) = oneshot::channel();
+        let pending_read = PendingRead { range, response_tx };
+
+        let is_first_read = {
+            let mut pending_reads_guard = self.pending_reads.lock().unwrap();
+            let reads = pending_reads_guard.entry(path.to_path_buf()).or_default();
+            reads.push(pending_read);
+            reads.len() == 1
+        };
+        // The first read of a batch schedules its execution. The reads are issued from a spawned
+        // task so that the cancellation of one of the callers does not impact the others.
+        if is_first_read {
+            let underlying = self.underlying.clone();
+            let pending_reads = self.pending_reads.clone();
+            let settings = self.settings;
+            let path = path.to_path_buf();
+
+            tokio::spawn(async move {
+                tokio::time::sleep(settings.window).await;
+                let reads = pending_reads
+                    .lock()
+                    .unwrap()
+                    .remove(&path)
+                    .unwrap_or_default();
+                execute_reads(&*underlying, &path, reads, &settings).await;
+            });
+        }
+        response_rx.await.map_err(|_| {
+            StorageErrorKind::Internal.with_error(anyhow::anyhow!(
+                "coalesced read was dropped before completion"
+            ))
+        })?
+    }
+
+    async fn get_slice_stream(
+        &self,
+        path: &Path,
+        range: Range<usize>,
+    ) -> StorageResult<Box<dyn AsyncRead + Send + Unpin>> {
+        // Getting a stream bypasses the coalescing layer
+        self.underlying.get_slice_stream(path, range).await
+    }
+
+    async fn delete(&self, path: &Path) -> StorageResult<()> {
+        self.underlying.delete(path).await
+    }
+
+    async fn bulk_delete<'a>(&self, paths: &[&'a Path]) -> Result<(), BulkDeleteError> {
+        self.underlying.bulk_delete(paths).await
+    }
+
+    async fn get_all(&self, path: &Path) -> StorageResult<OwnedBytes> {
+        self.underlying.get_all(path).await
+    }
+
+    fn uri(&self) -> &Uri {
+        self.underlying.uri()
+    }
+
+    async fn file_num_bytes(&self, path: &Path) -> StorageResult<u64> {
+        self.underlying.file_num_bytes(path).await
+    }
+
+    async fn list(&self, prefix: &Path) -> StorageResult<Vec<StorageFileEntry>> {
+        self.underlying.list(prefix).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::future;
+
+    use super::*;
+    use crate::{MockStorage, StorageError};
+
+    const PAYLOAD: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789";
+
+    fn mock_storage(get_slice_ranges: Arc<Mutex<Vec<Range<usize>>>>) -> MockStorage {
+        let mut storage = MockStorage::default();
+        storage.expect_get_slice().returning(move |_, range| {
+            get_slice_ranges.lock().unwrap().push(range.clone());
+            Box::pin(future::ok(OwnedBytes::new(PAYLOAD[range].to_vec())))
+        });
+        storage
+    }
+
+    #[tokio::test]
+    async fn test_coalescing_storage_merges_nearby_reads() {
+        let get_slice_ranges = Arc::new(Mutex::new(Vec::new()));
+        let settings = ReadCoalescingSettings {
+            max_gap_num_bytes: 2,
+            max_merged_num_bytes: 10,
+            window: Duration::from_millis(10),
+        };
+        let storage = CoalescingStorage::new(mock_storage(get_slice_ranges.clone()), settings);
+        let path = Path::new("split.split");
+
+        let (slice_0, slice_1, slice_2, slice_3, slice_4) = tokio::join!(
+            storage.get_slice(path, 0..4),
+            storage.get_slice(path, 2..6),
+            storage.get_slice(path, 8..10),
+            // The gap is too large.
+            storage.get_slice(path, 20..22),
+            // The merged read would be too large.
+            storage.get_slice(path, 23..32),
+        );
+        assert_eq!(slice_0.unwrap().as_slice(), b"abcd");
+        assert_eq!(slice_1.unwrap().as_slice(), b"cdef");
+        assert_eq!(slice_2.unwrap().as_slice(), b"ij");
+        assert_eq!(slice_3.unwrap().as_slice(), b"uv");
+        assert_eq!(slice_4.unwrap().as_slice(), b"xyz012345");
+
+        let mut get_slice_ranges = get_slice_ranges.lock().unwrap().clone();
+        get_slice_ranges.sort_by_key(|range| range.start);
+        assert_eq!(get_slice_ranges, [0..10, 20..22, 23..32]);
+    }
+
+    #[tokio::test]
+    async fn test_coalescing_storage_bypasses_large_reads() {
+        let get_slice_ranges = Arc::new(Mutex::new(Vec::new()));
+        let settings = ReadCoalescingSettings {
+            max_merged_num_bytes: 8,
+            ..Default::default()
+        };
+        let storage = CoalescingStorage::new(mock_storage(get_slice_ranges.clone()), settings);
+        let path = Path::new("split.split");
+
+        let (slice_0, slice_1) = tokio::join!(
+            storage.get_slice(path, 0..10),
+            storage.get_slice(path, 10..20),
+        );
+        assert_eq!(slice_0.unwrap().as_slice(), b"abcdefghij");
+        assert_eq!(slice_1.unwrap().as_slice(), b"klmnopqrst");
+        assert_eq!(*get_slice_ranges.lock().unwrap(), [0..10, 10..20]);
+    }
+
+    #[tokio::test]
+    async fn test_coalescing_storage_propagates_errors() {
+        let mut storage = MockStorage::default();
+        storage.expect_get_slice().times(1).returning(|_, _| {
+            Box::pin(future::err(
+                StorageErrorKind::Service.with_error(anyhow::anyhow!("service unavailable")),
+            ))
+        });
+        let storage = CoalescingStorage::new(storage, ReadCoalescingSettings::default());
+        let path = Path::new("split.split");
+
+        let (slice_0, slice_1) =
+            tokio::join!(storage.get_slice(path, 0..4), storage.get_slice(path, 6..8),);
+        let error_0: StorageError = slice_0.unwrap_err();
+        let error_1: StorageError = slice_1.unwrap_err();
+        assert_eq!(error_0.kind(), StorageErrorKind::Service);
+        assert_eq!(error_1.kind(), StorageErrorKind::Service);
+    }
+}
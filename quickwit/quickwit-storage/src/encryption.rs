@@ -0,0 +1,844 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Client-side envelope encryption of the split files.
+//!
+//! Each index is assigned a data key, generated at index creation and stored in the index config
+//! wrapped by a master key (AWS KMS key or key read from an environment variable). Files are
+//! encrypted with AES-256-GCM in chunks of [`PLAINTEXT_CHUNK_NUM_BYTES`] so that byte ranges can
+//! be read and decrypted without downloading the entire file.
+//!
+//! File layout: `magic (4 bytes) | nonce prefix (8 bytes) | chunk 0 | chunk 1 | ...`, where each
+//! chunk is followed by its 16-byte authentication tag. The nonce of a chunk is the nonce prefix,
+//! drawn at random for each file, followed by the index of the chunk. The last chunk is flagged in
+//! the associated data of the chunks, so a file truncated at a chunk boundary fails to
+//! authenticate instead of decrypting as a shorter file. Empty files consist of a single empty
+//! chunk for the same reason.
+
+use std::collections::HashMap;
+use std::io::{self, Cursor};
+use std::num::NonZeroUsize;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::{cmp, fmt};
+
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use anyhow::Context;
+use async_trait::async_trait;
+use aws_smithy_http::byte_stream::ByteStream;
+use aws_smithy_types::Blob;
+use base64::prelude::{Engine, BASE64_STANDARD};
+use lru::LruCache;
+use once_cell::sync::{Lazy, OnceCell};
+use quickwit_aws::get_aws_config;
+use quickwit_common::uri::Uri;
+use quickwit_config::{IndexEncryptionConfig, WrappedDataKey};
+use tokio::io::{AsyncRead, AsyncWriteExt};
+use ulid::Ulid;
+
+use crate::storage::SendableAsync;
+use crate::{
+    BulkDeleteError, OwnedBytes, PutPayload, Storage, StorageErrorKind, StorageFileEntry,
+    StorageResult,
+};
+
+const MAGIC: &[u8; 4] = b"QWE2";
+
+const NONCE_PREFIX_NUM_BYTES: usize = 8;
+
+const HEADER_NUM_BYTES: usize = MAGIC.len() + NONCE_PREFIX_NUM_BYTES;
+
+const PLAINTEXT_CHUNK_NUM_BYTES: usize = 64 * 1024;
+
+const TAG_NUM_BYTES: usize = 16;
+
+const CIPHERTEXT_CHUNK_NUM_BYTES: usize = PLAINTEXT_CHUNK_NUM_BYTES + TAG_NUM_BYTES;
+
+/// Data keys unwrapped so far, indexed by key ID, to avoid calling the key management service on
+/// every request.
+static DATA_KEYS: Lazy<Mutex<HashMap<String, DataKey>>> = Lazy::new(Default::default);
+
+/// Headers of the encrypted files read so far, indexed by file URI. Rewriting or deleting a file
+/// through an [`EncryptedStorage`] evicts its entry. Files rewritten by another node are not
+/// evicted, which is fine for split files since they are written once under a unique name.
+static FILE_HEADERS: Lazy<Mutex<LruCache<String, FileHeader>>> = Lazy::new(|| {
+    let capacity = NonZeroUsize::new(10_000).expect("capacity should be non-zero");
+    Mutex::new(LruCache::new(capacity))
+});
+
+/// Key encrypting the files of an index.
+#[derive(Clone)]
+pub struct DataKey {
+    key_id: String,
+    cipher: Aes256Gcm,
+}
+
+impl fmt::Debug for DataKey {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter
+            .debug_struct("DataKey")
+            .field("key_id", &self.key_id)
+            .finish()
+    }
+}
+
+impl DataKey {
+    fn new(key_id: String, key_bytes: &[u8]) -> anyhow::Result<Self> {
+        let cipher = Aes256Gcm::new_from_slice(key_bytes)
+            .map_err(|_| anyhow::anyhow!("data key `{key_id}` is not a 256-bit key"))?;
+        Ok(Self { key_id, cipher })
+    }
+
+    /// Returns the ID of the key, recorded in the metadata of the splits it encrypts.
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    fn encrypt_chunk(
+        &self,
+        nonce_prefix: &[u8; NONCE_PREFIX_NUM_BYTES],
+        chunk_ord: usize,
+        is_last_chunk: bool,
+        plaintext: &[u8],
+    ) -> io::Result<Vec<u8>> {
+        let nonce = chunk_nonce(nonce_prefix, chunk_ord);
+        let payload = Payload {
+            msg: plaintext,
+            aad: &chunk_associated_data(is_last_chunk),
+        };
+        self.cipher
+            .encrypt(Nonce::from_slice(&nonce), payload)
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "failed to encrypt chunk"))
+    }
+
+    fn decrypt_chunk(
+        &self,
+        nonce_prefix: &[u8; NONCE_PREFIX_NUM_BYTES],
+        chunk_ord: usize,
+        is_last_chunk: bool,
+        ciphertext: &[u8],
+    ) -> StorageResult<Vec<u8>> {
+        let nonce = chunk_nonce(nonce_prefix, chunk_ord);
+        let payload = Payload {
+            msg: ciphertext,
+            aad: &chunk_associated_data(is_last_chunk),
+        };
+        self.cipher
+            .decrypt(Nonce::from_slice(&nonce), payload)
+            .map_err(|_| {
+                StorageErrorKind::Internal.with_error(anyhow::anyhow!(
+                    "failed to decrypt chunk {chunk_ord} with data key `{}`: the file is \
+                     truncated, corrupted, or was encrypted with another key",
+                    self.key_id
+                ))
+            })
+    }
+
+    /// Decrypts the consecutive chunks of `ciphertext`, starting at chunk `first_chunk_ord`, of
+    /// a file whose last chunk is `last_chunk_ord`.
+    fn decrypt_chunks(
+        &self,
+        nonce_prefix: &[u8; NONCE_PREFIX_NUM_BYTES],
+        first_chunk_ord: usize,
+        last_chunk_ord: usize,
+        ciphertext: &[u8],
+    ) -> StorageResult<Vec<u8>> {
+        let mut plaintext = Vec::with_capacity(ciphertext.len());
+
+        for (chunk_offset, chunk) in ciphertext.chunks(CIPHERTEXT_CHUNK_NUM_BYTES).enumerate() {
+            let chunk_ord = first_chunk_ord + chunk_offset;
+            let chunk_plaintext =
+                self.decrypt_chunk(nonce_prefix, chunk_ord, chunk_ord == last_chunk_ord, chunk)?;
+            plaintext.extend_from_slice(&chunk_plaintext);
+        }
+        Ok(plaintext)
+    }
+}
+
+fn chunk_associated_data(is_last_chunk: bool) -> [u8; 1] {
+    [is_last_chunk as u8]
+}
+
+fn chunk_nonce(nonce_prefix: &[u8; NONCE_PREFIX_NUM_BYTES], chunk_ord: usize) -> [u8; 12] {
+    let mut nonce = [0u8; 12];
+    nonce[..NONCE_PREFIX_NUM_BYTES].copy_from_slice(nonce_prefix);
+    nonce[NONCE_PREFIX_NUM_BYTES..].copy_from_slice(&(chunk_ord as u32).to_be_bytes());
+    nonce
+}
+
+/// Returns the number of chunks of a file. Empty files have a single empty chunk.
+fn num_chunks(plaintext_num_bytes: u64) -> u64 {
+    let num_chunks = (plaintext_num_bytes + PLAINTEXT_CHUNK_NUM_BYTES as u64 - 1)
+        / PLAINTEXT_CHUNK_NUM_BYTES as u64;
+    num_chunks.max(1)
+}
+
+fn ciphertext_num_bytes(plaintext_num_bytes: u64) -> u64 {
+    HEADER_NUM_BYTES as u64
+        + plaintext_num_bytes
+        + num_chunks(plaintext_num_bytes) * TAG_NUM_BYTES as u64
+}
+
+/// Returns the ordinal of the last chunk of an encrypted file of `ciphertext_num_bytes` bytes.
+fn last_chunk_ord(ciphertext_num_bytes: u64) -> StorageResult<usize> {
+    let plaintext_num_bytes = plaintext_num_bytes(ciphertext_num_bytes)?;
+    Ok(num_chunks(plaintext_num_bytes) as usize - 1)
+}
+
+fn plaintext_num_bytes(ciphertext_num_bytes: u64) -> StorageResult<u64> {
+    let malformed_file_error = || {
+        StorageErrorKind::Internal
+            .with_error(anyhow::anyhow!("encrypted file is truncated or malformed"))
+    };
+    let body_num_bytes = ciphertext_num_bytes
+        .checked_sub(HEADER_NUM_BYTES as u64)
+        .filter(|body_num_bytes| *body_num_bytes > 0)
+        .ok_or_else(malformed_file_error)?;
+    let num_full_chunks = body_num_bytes / CIPHERTEXT_CHUNK_NUM_BYTES as u64;
+    let last_chunk_num_bytes = body_num_bytes % CIPHERTEXT_CHUNK_NUM_BYTES as u64;
+
+    let last_chunk_plaintext_num_bytes = if last_chunk_num_bytes == 0 {
+        0
+    } else {
+        last_chunk_num_bytes
+            .checked_sub(TAG_NUM_BYTES as u64)
+            .ok_or_else(malformed_file_error)?
+    };
+    Ok(num_full_chunks * PLAINTEXT_CHUNK_NUM_BYTES as u64 + last_chunk_plaintext_num_bytes)
+}
+
+#[derive(Clone, Copy, Debug)]
+struct FileHeader {
+    nonce_prefix: [u8; NONCE_PREFIX_NUM_BYTES],
+    ciphertext_num_bytes: u64,
+}
+
+fn parse_nonce_prefix(header_bytes: &[u8]) -> StorageResult<[u8; NONCE_PREFIX_NUM_BYTES]> {
+    if header_bytes.len() < HEADER_NUM_BYTES || &header_bytes[..MAGIC.len()] != MAGIC {
+        return Err(StorageErrorKind::Internal.with_error(anyhow::anyhow!(
+            "file is not encrypted or was encrypted with an unsupported format"
+        )));
+    }
+    let mut nonce_prefix = [0u8; NONCE_PREFIX_NUM_BYTES];
+    nonce_prefix.copy_from_slice(&header_bytes[MAGIC.len()..HEADER_NUM_BYTES]);
+    Ok(nonce_prefix)
+}
+
+/// Payload encrypted on the fly, chunk by chunk, as it is uploaded.
+#[derive(Clone)]
+struct EncryptedPayload {
+    underlying: Box<dyn PutPayload>,
+    data_key: DataKey,
+    nonce_prefix: [u8; NONCE_PREFIX_NUM_BYTES],
+}
+
+#[async_trait]
+impl PutPayload for EncryptedPayload {
+    fn len(&self) -> u64 {
+        ciphertext_num_bytes(self.underlying.len())
+    }
+
+    async fn range_byte_stream(&self, range: Range<u64>) -> io::Result<ByteStream> {
+        let mut ciphertext = Vec::with_capacity((range.end - range.start) as usize);
+
+        if range.start < HEADER_NUM_BYTES as u64 {
+            let mut header = Vec::with_capacity(HEADER_NUM_BYTES);
+            header.extend_from_slice(MAGIC);
+            header.extend_from_slice(&self.nonce_prefix);
+            let header_end = cmp::min(range.end, HEADER_NUM_BYTES as u64);
+            ciphertext.extend_from_slice(&header[range.start as usize..header_end as usize]);
+        }
+        if range.end > HEADER_NUM_BYTES as u64 {
+            let body_start = range.start.saturating_sub(HEADER_NUM_BYTES as u64) as usize;
+            let body_end = (range.end - HEADER_NUM_BYTES as u64) as usize;
+            let first_chunk_ord = body_start / CIPHERTEXT_CHUNK_NUM_BYTES;
+            let last_chunk_ord = (body_end - 1) / CIPHERTEXT_CHUNK_NUM_BYTES;
+            let file_last_chunk_ord = num_chunks(self.underlying.len()) as usize - 1;
+
+            // Reads the plaintext of all the chunks overlapping the range at once.
+            let plaintext_start = (first_chunk_ord * PLAINTEXT_CHUNK_NUM_BYTES) as u64;
+            let plaintext_end = cmp::min(
+                ((last_chunk_ord + 1) * PLAINTEXT_CHUNK_NUM_BYTES) as u64,
+                self.underlying.len(),
+            );
+            let plaintext = self
+                .underlying
+                .range_byte_stream(plaintext_start..plaintext_end)
+                .await?
+                .collect()
+                .await
+                .map_err(|error| io::Error::new(io::ErrorKind::Other, error))?
+                .into_bytes();
+
+            let mut chunks_ciphertext = Vec::with_capacity(plaintext.len() + TAG_NUM_BYTES);
+            for chunk_ord in first_chunk_ord..=last_chunk_ord {
+                let chunk_start = (chunk_ord - first_chunk_ord) * PLAINTEXT_CHUNK_NUM_BYTES;
+                let chunk_end = cmp::min(chunk_start + PLAINTEXT_CHUNK_NUM_BYTES, plaintext.len());
+                let chunk_ciphertext = self.data_key.encrypt_chunk(
+                    &self.nonce_prefix,
+                    chunk_ord,
+                    chunk_ord == file_last_chunk_ord,
+                    &plaintext[chunk_start..chunk_end],
+                )?;
+                chunks_ciphertext.extend_from_slice(&chunk_ciphertext);
+            }
+            let chunks_start = first_chunk_ord * CIPHERTEXT_CHUNK_NUM_BYTES;
+            ciphertext.extend_from_slice(
+                &chunks_ciphertext[body_start - chunks_start..body_end - chunks_start],
+            );
+        }
+        Ok(ByteStream::from(ciphertext))
+    }
+}
+
+/// Storage encrypting the files it writes and decrypting the files it reads with the data key
+/// of an index.
+#[derive(Clone)]
+pub(crate) struct EncryptedStorage {
+    underlying: Arc<dyn Storage>,
+    data_key: DataKey,
+}
+
+impl fmt::Debug for EncryptedStorage {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter
+            .debug_struct("EncryptedStorage")
+            .field("uri", self.underlying.uri())
+            .field("data_key", &self.data_key)
+            .finish()
+    }
+}
+
+impl EncryptedStorage {
+    pub fn new(underlying: Arc<dyn Storage>, data_key: DataKey) -> Self {
+        Self {
+            underlying,
+            data_key,
+        }
+    }
+
+    fn file_header_cache_key(&self, path: &Path) -> String {
+        format!("{}/{}", self.underlying.uri(), path.display())
+    }
+
+    fn evict_file_header(&self, path: &Path) {
+        let cache_key = self.file_header_cache_key(path);
+        FILE_HEADERS.lock().unwrap().pop(&cache_key);
+    }
+
+    async fn file_header(&self, path: &Path) -> StorageResult<FileHeader> {
+        let cache_key = self.file_header_cache_key(path);
+
+        if let Some(file_header) = FILE_HEADERS.lock().unwrap().get(&cache_key) {
+            return Ok(*file_header);
+        }
+        let (ciphertext_num_bytes, header_bytes) = futures::try_join!(
+            self.underlying.file_num_bytes(path),
+            self.underlying.get_slice(path, 0..HEADER_NUM_BYTES)
+        )?;
+        let file_header = FileHeader {
+            nonce_prefix: parse_nonce_prefix(&header_bytes)?,
+            ciphertext_num_bytes,
+        };
+        FILE_HEADERS.lock().unwrap().put(cache_key, file_header);
+        Ok(file_header)
+    }
+}
+
+#[async_trait]
+impl Storage for EncryptedStorage {
+    async fn check_connectivity(&self) -> anyhow::Result<()> {
+        self.underlying.check_connectivity().await
+    }
+
+    async fn put(&self, path: &Path, payload: Box<dyn PutPayload>) -> StorageResult<()> {
+        let encrypted_payload = EncryptedPayload {
+            underlying: payload,
+            data_key: self.data_key.clone(),
+            nonce_prefix: rand::random(),
+        };
+        // The file gets a new nonce prefix, so the header cached for the previous version, if
+        // any, must be evicted, including when the upload fails halfway.
+        self.evict_file_header(path);
+        let put_res = self.underlying.put(path, Box::new(encrypted_payload)).await;
+        self.evict_file_header(path);
+        put_res
+    }
+
+    async fn copy_to(&self, path: &Path, output: &mut dyn SendableAsync) -> StorageResult<()> {
+        let plaintext = self.get_all(path).await?;
+        output.write_all(&plaintext).await?;
+        output.flush().await?;
+        Ok(())
+    }
+
+    async fn get_slice(&self, path: &Path, range: Range<usize>) -> StorageResult<OwnedBytes> {
+        if range.is_empty() {
+            return Ok(OwnedBytes::empty());
+        }
+        let file_header = self.file_header(path).await?;
+        let plaintext_num_bytes = plaintext_num_bytes(file_header.ciphertext_num_bytes)? as usize;
+
+        if range.end > plaintext_num_bytes {
+            return Err(StorageErrorKind::Internal.with_error(anyhow::anyhow!(
+                "range {range:?} is out of bounds for file `{}` of {plaintext_num_bytes} bytes",
+                path.display()
+            )));
+        }
+        let first_chunk_ord = range.start / PLAINTEXT_CHUNK_NUM_BYTES;
+        let last_chunk_ord = (range.end - 1) / PLAINTEXT_CHUNK_NUM_BYTES;
+
+        let ciphertext_start = HEADER_NUM_BYTES + first_chunk_ord * CIPHERTEXT_CHUNK_NUM_BYTES;
+        let ciphertext_end = cmp::min(
+            HEADER_NUM_BYTES + (last_chunk_ord + 1) * CIPHERTEXT_CHUNK_NUM_BYTES,
+            file_header.ciphertext_num_bytes as usize,
+        );
+        let ciphertext = self
+            .underlying
+            .get_slice(path, ciphertext_start..ciphertext_end)
+            .await?;
+        let plaintext = self.data_key.decrypt_chunks(
+            &file_header.nonce_prefix,
+            first_chunk_ord,
+            last_chunk_ord(file_header.ciphertext_num_bytes)?,
+            &ciphertext,
+        )?;
+        let plaintext_start = first_chunk_ord * PLAINTEXT_CHUNK_NUM_BYTES;
+        let plaintext_range = range.start - plaintext_start..range.end - plaintext_start;
+        Ok(OwnedBytes::new(plaintext[plaintext_range].to_vec()))
+    }
+
+    async fn get_slice_stream(
+        &self,
+        path: &Path,
+        range: Range<usize>,
+    ) -> StorageResult<Box<dyn AsyncRead + Send + Unpin>> {
+        let bytes = self.get_slice(path, range).await?;
+        Ok(Box::new(Cursor::new(bytes)))
+    }
+
+    async fn get_all(&self, path: &Path) -> StorageResult<OwnedBytes> {
+        let ciphertext = self.underlying.get_all(path).await?;
+        let nonce_prefix = parse_nonce_prefix(&ciphertext)?;
+        let last_chunk_ord = last_chunk_ord(ciphertext.len() as u64)?;
+        let plaintext = self.data_key.decrypt_chunks(
+            &nonce_prefix,
+            0,
+            last_chunk_ord,
+            &ciphertext[HEADER_NUM_BYTES..],
+        )?;
+        Ok(OwnedBytes::new(plaintext))
+    }
+
+    async fn delete(&self, path: &Path) -> StorageResult<()> {
+        self.evict_file_header(path);
+        self.underlying.delete(path).await
+    }
+
+    async fn bulk_delete<'a>(&self, paths: &[&'a Path]) -> Result<(), BulkDeleteError> {
+        for path in paths {
+            self.evict_file_header(path);
+        }
+        self.underlying.bulk_delete(paths).await
+    }
+
+    async fn exists(&self, path: &Path) -> StorageResult<bool> {
+        self.underlying.exists(path).await
+    }
+
+    async fn file_num_bytes(&self, path: &Path) -> StorageResult<u64> {
+        let file_header = self.file_header(path).await?;
+        plaintext_num_bytes(file_header.ciphertext_num_bytes)
+    }
+
+    async fn list(&self, prefix: &Path) -> StorageResult<Vec<StorageFileEntry>> {
+        self.underlying.list(prefix).await
+    }
+
+    fn uri(&self) -> &Uri {
+        self.underlying.uri()
+    }
+
+    fn is_encrypted(&self) -> bool {
+        true
+    }
+}
+
+/// Master key wrapping the data keys, parsed from a master key ID.
+enum MasterKey<'a> {
+    AwsKms { key_id: &'a str },
+    Env { var_name: &'a str },
+}
+
+impl<'a> MasterKey<'a> {
+    fn parse(master_key_id: &'a str) -> anyhow::Result<Self> {
+        if let Some(key_id) = master_key_id.strip_prefix("aws-kms:") {
+            return Ok(Self::AwsKms { key_id });
+        }
+        if let Some(var_name) = master_key_id.strip_prefix("env:") {
+            return Ok(Self::Env { var_name });
+        }
+        anyhow::bail!("unsupported master key ID `{master_key_id}`")
+    }
+
+    async fn wrap(&self, data_key_bytes: &[u8]) -> anyhow::Result<Vec<u8>> {
+        match self {
+            Self::AwsKms { key_id } => {
+                let encrypt_output = kms_client()
+                    .await
+                    .encrypt()
+                    .key_id(*key_id)
+                    .plaintext(Blob::new(data_key_bytes))
+                    .send()
+                    .await
+                    .with_context(|| format!("failed to wrap data key with KMS key `{key_id}`"))?;
+                let ciphertext = encrypt_output
+                    .ciphertext_blob()
+                    .context("KMS response is missing the ciphertext")?;
+                Ok(ciphertext.as_ref().to_vec())
+            }
+            Self::Env { var_name } => {
+                let master_key = env_master_key(var_name)?;
+                let nonce: [u8; 12] = rand::random();
+                let ciphertext = master_key
+                    .encrypt(Nonce::from_slice(&nonce), data_key_bytes)
+                    .map_err(|_| anyhow::anyhow!("failed to wrap data key"))?;
+                Ok([&nonce[..], &ciphertext].concat())
+            }
+        }
+    }
+
+    async fn unwrap(&self, wrapped_data_key: &[u8]) -> anyhow::Result<Vec<u8>> {
+        match self {
+            Self::AwsKms { key_id } => {
+                let decrypt_output = kms_client()
+                    .await
+                    .decrypt()
+                    .key_id(*key_id)
+                    .ciphertext_blob(Blob::new(wrapped_data_key))
+                    .send()
+                    .await
+                    .with_context(|| {
+                        format!("failed to unwrap data key with KMS key `{key_id}`")
+                    })?;
+                let plaintext = decrypt_output
+                    .plaintext()
+                    .context("KMS response is missing the plaintext")?;
+                Ok(plaintext.as_ref().to_vec())
+            }
+            Self::Env { var_name } => {
+                let master_key = env_master_key(var_name)?;
+                if wrapped_data_key.len() < 12 {
+                    anyhow::bail!("wrapped data key is truncated");
+                }
+                let (nonce, ciphertext) = wrapped_data_key.split_at(12);
+                master_key
+                    .decrypt(Nonce::from_slice(nonce), ciphertext)
+                    .map_err(|_| {
+                        anyhow::anyhow!(
+                            "failed to unwrap data key with the master key of environment \
+                             variable `{var_name}`"
+                        )
+                    })
+            }
+        }
+    }
+}
+
+async fn kms_client() -> &'static aws_sdk_kms::Client {
+    static KMS_CLIENT: OnceCell<aws_sdk_kms::Client> = OnceCell::new();
+
+    if let Some(kms_client) = KMS_CLIENT.get() {
+        return kms_client;
+    }
+    let aws_config = get_aws_config().await;
+    KMS_CLIENT.get_or_init(|| aws_sdk_kms::Client::new(aws_config))
+}
+
+fn env_master_key(var_name: &str) -> anyhow::Result<Aes256Gcm> {
+    let master_key_base64 = std::env::var(var_name)
+        .with_context(|| format!("environment variable `{var_name}` is not set"))?;
+    let master_key_bytes = BASE64_STANDARD
+        .decode(master_key_base64.trim())
+        .with_context(|| format!("environment variable `{var_name}` is not valid base64"))?;
+    Aes256Gcm::new_from_slice(&master_key_bytes)
+        .map_err(|_| anyhow::anyhow!("environment variable `{var_name}` is not a 256-bit key"))
+}
+
+/// Generates a new data key and wraps it with the master key `master_key_id`.
+pub async fn generate_data_key(master_key_id: &str) -> anyhow::Result<WrappedDataKey> {
+    let master_key = MasterKey::parse(master_key_id)?;
+    let data_key_bytes: [u8; 32] = rand::random();
+    let ciphertext = master_key.wrap(&data_key_bytes).await?;
+    let wrapped_data_key = WrappedDataKey {
+        key_id: Ulid::new().to_string(),
+        ciphertext: BASE64_STANDARD.encode(ciphertext),
+    };
+    Ok(wrapped_data_key)
+}
+
+/// Unwraps the data key of an index. Unwrapped keys are cached, so the key management service
+/// is only called once per key.
+pub async fn unwrap_data_key(encryption_config: &IndexEncryptionConfig) -> anyhow::Result<DataKey> {
+    let wrapped_data_key = encryption_config
+        .data_key
+        .as_ref()
+        .context("index encryption config is missing the data key")?;
+
+    if let Some(data_key) = DATA_KEYS.lock().unwrap().get(&wrapped_data_key.key_id) {
+        return Ok(data_key.clone());
+    }
+    let master_key = MasterKey::parse(&encryption_config.master_key_id)?;
+    let ciphertext = BASE64_STANDARD
+        .decode(&wrapped_data_key.ciphertext)
+        .context("wrapped data key is not valid base64")?;
+    let data_key_bytes = master_key.unwrap(&ciphertext).await?;
+    let data_key = DataKey::new(wrapped_data_key.key_id.clone(), &data_key_bytes)?;
+
+    DATA_KEYS
+        .lock()
+        .unwrap()
+        .insert(wrapped_data_key.key_id.clone(), data_key.clone());
+    Ok(data_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RamStorage;
+
+    const TEST_MASTER_KEY_VAR: &str = "QW_TEST_ENCRYPTION_MASTER_KEY";
+
+    fn data_key_for_test() -> DataKey {
+        DataKey::new("test-key".to_string(), &[7u8; 32]).unwrap()
+    }
+
+    #[test]
+    fn test_ciphertext_and_plaintext_num_bytes() {
+        for plaintext_num_bytes_ in [
+            0,
+            1,
+            PLAINTEXT_CHUNK_NUM_BYTES as u64 - 1,
+            PLAINTEXT_CHUNK_NUM_BYTES as u64,
+            PLAINTEXT_CHUNK_NUM_BYTES as u64 + 1,
+            10 * PLAINTEXT_CHUNK_NUM_BYTES as u64 + 123,
+        ] {
+            let ciphertext_num_bytes_ = ciphertext_num_bytes(plaintext_num_bytes_);
+            assert_eq!(
+                plaintext_num_bytes(ciphertext_num_bytes_).unwrap(),
+                plaintext_num_bytes_
+            );
+        }
+        plaintext_num_bytes(HEADER_NUM_BYTES as u64 - 1).unwrap_err();
+        plaintext_num_bytes(HEADER_NUM_BYTES as u64).unwrap_err();
+        plaintext_num_bytes(HEADER_NUM_BYTES as u64 + TAG_NUM_BYTES as u64 - 1).unwrap_err();
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_storage() {
+        let underlying: Arc<dyn Storage> = Arc::new(RamStorage::default());
+        let encrypted_storage = EncryptedStorage::new(underlying.clone(), data_key_for_test());
+
+        let plaintext: Vec<u8> = (0..3 * PLAINTEXT_CHUNK_NUM_BYTES + 100)
+            .map(|i| (i % 251) as u8)
+            .collect();
+        let path = Path::new("test-encrypted-storage.split");
+        encrypted_storage
+            .put(path, Box::new(plaintext.clone()))
+            .await
+            .unwrap();
+
+        // The underlying storage only sees the ciphertext.
+        let ciphertext = underlying.get_all(path).await.unwrap();
+        assert_eq!(
+            ciphertext.len() as u64,
+            ciphertext_num_bytes(plaintext.len() as u64)
+        );
+        let plaintext_prefix = &plaintext[..64];
+        assert!(!ciphertext
+            .windows(64)
+            .any(|window| window == plaintext_prefix));
+
+        assert_eq!(
+            encrypted_storage.file_num_bytes(path).await.unwrap(),
+            plaintext.len() as u64
+        );
+        assert_eq!(
+            encrypted_storage.get_all(path).await.unwrap().as_slice(),
+            &plaintext[..]
+        );
+        for range in [
+            0..1,
+            10..PLAINTEXT_CHUNK_NUM_BYTES + 10,
+            PLAINTEXT_CHUNK_NUM_BYTES - 1..2 * PLAINTEXT_CHUNK_NUM_BYTES + 1,
+            3 * PLAINTEXT_CHUNK_NUM_BYTES..plaintext.len(),
+        ] {
+            let slice = encrypted_storage
+                .get_slice(path, range.clone())
+                .await
+                .unwrap();
+            assert_eq!(slice.as_slice(), &plaintext[range]);
+        }
+        encrypted_storage
+            .get_slice(path, 0..plaintext.len() + 1)
+            .await
+            .unwrap_err();
+
+        // Another key cannot decrypt the file.
+        let other_data_key = DataKey::new("other-key".to_string(), &[8u8; 32]).unwrap();
+        let other_encrypted_storage = EncryptedStorage::new(underlying, other_data_key);
+        other_encrypted_storage.get_all(path).await.unwrap_err();
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_storage_rejects_file_truncated_at_chunk_boundary() {
+        let underlying: Arc<dyn Storage> = Arc::new(RamStorage::default());
+        let encrypted_storage = EncryptedStorage::new(underlying.clone(), data_key_for_test());
+
+        let plaintext = vec![42u8; 2 * PLAINTEXT_CHUNK_NUM_BYTES];
+        let path = Path::new("test-truncated.split");
+        encrypted_storage
+            .put(path, Box::new(plaintext))
+            .await
+            .unwrap();
+
+        // Drops the last chunk: the remaining chunks are well-formed, but none of them is flagged
+        // as the last one.
+        let ciphertext = underlying.get_all(path).await.unwrap();
+        let truncated_path = Path::new("test-truncated-copy.split");
+        underlying
+            .put(
+                truncated_path,
+                Box::new(ciphertext[..HEADER_NUM_BYTES + CIPHERTEXT_CHUNK_NUM_BYTES].to_vec()),
+            )
+            .await
+            .unwrap();
+        encrypted_storage.get_all(truncated_path).await.unwrap_err();
+        encrypted_storage
+            .get_slice(truncated_path, 0..10)
+            .await
+            .unwrap_err();
+
+        // The same goes for an empty file.
+        let empty_path = Path::new("test-empty.split");
+        encrypted_storage
+            .put(empty_path, Box::new(Vec::new()))
+            .await
+            .unwrap();
+        assert!(encrypted_storage
+            .get_all(empty_path)
+            .await
+            .unwrap()
+            .is_empty());
+
+        underlying
+            .put(
+                truncated_path,
+                Box::new(ciphertext[..HEADER_NUM_BYTES].to_vec()),
+            )
+            .await
+            .unwrap();
+        encrypted_storage.get_all(truncated_path).await.unwrap_err();
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_storage_evicts_file_header_on_rewrite() {
+        let underlying: Arc<dyn Storage> = Arc::new(RamStorage::default());
+        let encrypted_storage = EncryptedStorage::new(underlying, data_key_for_test());
+
+        let path = Path::new("test-rewritten.json");
+        encrypted_storage
+            .put(path, Box::new(b"first version".to_vec()))
+            .await
+            .unwrap();
+        let slice = encrypted_storage.get_slice(path, 0..5).await.unwrap();
+        assert_eq!(slice.as_slice(), b"first");
+
+        // The new version has a new nonce prefix and a different length.
+        encrypted_storage
+            .put(path, Box::new(b"second version".to_vec()))
+            .await
+            .unwrap();
+        let slice = encrypted_storage.get_slice(path, 0..6).await.unwrap();
+        assert_eq!(slice.as_slice(), b"second");
+
+        encrypted_storage.delete(path).await.unwrap();
+        encrypted_storage.get_slice(path, 0..6).await.unwrap_err();
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_payload_range_byte_stream() {
+        let plaintext: Vec<u8> = (0..2 * PLAINTEXT_CHUNK_NUM_BYTES + 7)
+            .map(|i| (i % 13) as u8)
+            .collect();
+        let encrypted_payload = EncryptedPayload {
+            underlying: Box::new(plaintext),
+            data_key: data_key_for_test(),
+            nonce_prefix: [1u8; NONCE_PREFIX_NUM_BYTES],
+        };
+        let ciphertext = encrypted_payload.read_all().await.unwrap();
+        assert_eq!(ciphertext.len() as u64, encrypted_payload.len());
+
+        // Multipart uploads request arbitrary ranges, which must be consistent with the whole.
+        for range in [0..5, 3..100_000, 70_000..encrypted_payload.len()] {
+            let range_bytes = encrypted_payload
+                .range_byte_stream(range.clone())
+                .await
+                .unwrap()
+                .collect()
+                .await
+                .unwrap()
+                .into_bytes();
+            assert_eq!(
+                &range_bytes[..],
+                &ciphertext[range.start as usize..range.end as usize]
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_generate_and_unwrap_data_key() {
+        std::env::set_var(TEST_MASTER_KEY_VAR, BASE64_STANDARD.encode([3u8; 32]));
+
+        let master_key_id = format!("env:{TEST_MASTER_KEY_VAR}");
+        let wrapped_data_key = generate_data_key(&master_key_id).await.unwrap();
+        let encryption_config = IndexEncryptionConfig {
+            master_key_id: master_key_id.clone(),
+            data_key: Some(wrapped_data_key.clone()),
+        };
+        let data_key = unwrap_data_key(&encryption_config).await.unwrap();
+        assert_eq!(data_key.key_id(), wrapped_data_key.key_id);
+
+        let mut tampered_wrapped_data_key = wrapped_data_key;
+        tampered_wrapped_data_key.key_id = Ulid::new().to_string();
+        tampered_wrapped_data_key.ciphertext = BASE64_STANDARD.encode([0u8; 60]);
+        let tampered_encryption_config = IndexEncryptionConfig {
+            master_key_id,
+            data_key: Some(tampered_wrapped_data_key),
+        };
+        unwrap_data_key(&tampered_encryption_config)
+            .await
+            .unwrap_err();
+
+        let error = generate_data_key("vault:quickwit").await.unwrap_err();
+        assert!(error.to_string().contains("unsupported master key ID"));
+    }
+}
@@ -0,0 +1,695 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::{fmt, io};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::stream::{StreamExt, TryStreamExt};
+use once_cell::sync::OnceCell;
+use quickwit_common::retry::{retry, RetryParams, Retryable};
+use quickwit_common::uri::Uri;
+use quickwit_common::{chunk_range, ignore_error_kind, into_u64_range};
+use quickwit_config::{HdfsStorageConfig, StorageBackend};
+use regex::Regex;
+use reqwest::header::LOCATION;
+use reqwest::redirect::Policy;
+use reqwest::{Client, Method, Response, StatusCode, Url};
+use serde::Deserialize;
+use tantivy::directory::OwnedBytes;
+use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncWriteExt};
+use tokio_util::io::StreamReader;
+use tracing::{instrument, warn};
+
+use crate::debouncer::DebouncedStorage;
+use crate::storage::SendableAsync;
+use crate::{
+    BulkDeleteError, DeleteFailure, MultiPartPolicy, PutPayload, Storage, StorageError,
+    StorageErrorKind, StorageFactory, StorageResolverError, StorageResult, STORAGE_METRICS,
+};
+
+/// Default port of the namenode HTTP server exposing the WebHDFS API (Hadoop 3.x).
+const DEFAULT_WEBHDFS_PORT: u16 = 9870;
+
+/// HDFS storage resolver.
+pub struct WebHdfsStorageFactory {
+    storage_config: HdfsStorageConfig,
+}
+
+impl WebHdfsStorageFactory {
+    /// Creates a new HDFS storage factory.
+    pub fn new(storage_config: HdfsStorageConfig) -> Self {
+        Self { storage_config }
+    }
+}
+
+#[async_trait]
+impl StorageFactory for WebHdfsStorageFactory {
+    fn backend(&self) -> StorageBackend {
+        StorageBackend::Hdfs
+    }
+
+    async fn resolve(&self, uri: &Uri) -> Result<Arc<dyn Storage>, StorageResolverError> {
+        let storage = WebHdfsStorage::from_uri(&self.storage_config, uri)?;
+        Ok(Arc::new(DebouncedStorage::new(storage)))
+    }
+}
+
+/// HDFS storage implementation talking to the namenode and datanodes via the WebHDFS REST API.
+///
+/// Operations that read or write file content are redirected by the namenode to a datanode. The
+/// redirects are followed manually because the request body must only be sent to the datanode.
+pub struct WebHdfsStorage {
+    client: Client,
+    endpoint: Url,
+    user_name_opt: Option<String>,
+    uri: Uri,
+    prefix: PathBuf,
+    multipart_policy: MultiPartPolicy,
+    disable_multipart_upload: bool,
+    retry_params: RetryParams,
+}
+
+impl fmt::Debug for WebHdfsStorage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("WebHdfsStorage")
+            .field("endpoint", &self.endpoint.as_str())
+            .field("uri", &self.uri)
+            .field("prefix", &self.prefix)
+            .finish()
+    }
+}
+
+impl WebHdfsStorage {
+    /// Creates a new [`WebHdfsStorage`] instance.
+    pub fn new(endpoint: Url, user_name_opt: Option<String>, uri: Uri, prefix: PathBuf) -> Self {
+        let client = Client::builder()
+            .redirect(Policy::none())
+            .build()
+            .expect("the HTTP client should be valid");
+        Self {
+            client,
+            endpoint,
+            user_name_opt,
+            uri,
+            prefix,
+            multipart_policy: MultiPartPolicy::default(),
+            disable_multipart_upload: false,
+            retry_params: RetryParams {
+                max_attempts: 3,
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Sets the multipart policy.
+    ///
+    /// See `MultiPartPolicy`.
+    pub fn set_policy(&mut self, multipart_policy: MultiPartPolicy) {
+        self.multipart_policy = multipart_policy;
+    }
+
+    /// Builds instance from URI.
+    pub fn from_uri(
+        hdfs_storage_config: &HdfsStorageConfig,
+        uri: &Uri,
+    ) -> Result<WebHdfsStorage, StorageResolverError> {
+        let (authority_opt, prefix) = parse_hdfs_uri(uri).ok_or_else(|| {
+            let message = format!("failed to parse HDFS URI: {uri}");
+            StorageResolverError::InvalidUri(message)
+        })?;
+        let endpoint_str = hdfs_storage_config
+            .endpoint()
+            .or_else(|| authority_opt.map(|authority| default_endpoint(&authority)))
+            .ok_or_else(|| {
+                let message = format!(
+                    "could not find WebHDFS endpoint in URI, environment variable `{}`, or \
+                     storage config",
+                    HdfsStorageConfig::HDFS_ENDPOINT_ENV_VAR
+                );
+                StorageResolverError::InvalidConfig(message)
+            })?;
+        let endpoint = Url::parse(&endpoint_str).map_err(|error| {
+            let message = format!("failed to parse WebHDFS endpoint `{endpoint_str}`: {error}");
+            StorageResolverError::InvalidConfig(message)
+        })?;
+        if endpoint.cannot_be_a_base() {
+            let message = format!("WebHDFS endpoint `{endpoint_str}` must be an HTTP(S) URL");
+            return Err(StorageResolverError::InvalidConfig(message));
+        }
+        let mut hdfs_storage = WebHdfsStorage::new(
+            endpoint,
+            hdfs_storage_config.resolve_user_name(),
+            uri.clone(),
+            prefix,
+        );
+        hdfs_storage.disable_multipart_upload = hdfs_storage_config.disable_multipart_upload;
+        Ok(hdfs_storage)
+    }
+
+    /// Builds the WebHDFS URL of the operation `op` applied to the file located at
+    /// `relative_path`.
+    fn operation_url(&self, relative_path: &Path, op: &str, params: &[(&str, String)]) -> Url {
+        let mut url = self.endpoint.clone();
+        {
+            let mut path_segments = url
+                .path_segments_mut()
+                .expect("the endpoint URL should be a base URL");
+            path_segments.pop_if_empty().extend(["webhdfs", "v1"]);
+
+            let mut num_components = 0;
+            for component in self.prefix.join(relative_path).components() {
+                if let Component::Normal(component) = component {
+                    path_segments.push(&component.to_string_lossy());
+                    num_components += 1;
+                }
+            }
+            if num_components == 0 {
+                // The root directory is addressed as `/webhdfs/v1/`.
+                path_segments.push("");
+            }
+        }
+        {
+            let mut query_pairs = url.query_pairs_mut();
+            query_pairs.append_pair("op", op);
+
+            if let Some(user_name) = &self.user_name_opt {
+                query_pairs.append_pair("user.name", user_name);
+            }
+            for (key, value) in params {
+                query_pairs.append_pair(key, value);
+            }
+        }
+        url
+    }
+
+    /// Sends a request to the namenode and, if the namenode redirects it, to the datanode. The
+    /// body, if any, is only sent to the datanode.
+    async fn send_request(
+        &self,
+        method: Method,
+        url: Url,
+        body_opt: Option<Bytes>,
+    ) -> Result<Response, WebHdfsError> {
+        let response = self.client.request(method.clone(), url).send().await?;
+
+        if response.status() != StatusCode::TEMPORARY_REDIRECT {
+            if body_opt.is_some() && response.status().is_success() {
+                return Err(WebHdfsError::InvalidResponse(
+                    "namenode did not redirect the write request to a datanode".to_string(),
+                ));
+            }
+            return check_response_status(response).await;
+        }
+        let location = response
+            .headers()
+            .get(LOCATION)
+            .and_then(|location| location.to_str().ok())
+            .ok_or_else(|| {
+                WebHdfsError::InvalidResponse(
+                    "redirect response is missing the `Location` header".to_string(),
+                )
+            })?;
+        let mut request = self.client.request(method, location);
+
+        if let Some(body) = body_opt {
+            request = request.body(body);
+        }
+        let response = request.send().await?;
+        check_response_status(response).await
+    }
+
+    /// Downloads a file, or a range of it, as a vector of bytes.
+    async fn get_to_bytes(
+        &self,
+        path: &Path,
+        range_opt: Option<Range<usize>>,
+    ) -> StorageResult<Bytes> {
+        let params = range_opt
+            .as_ref()
+            .map(|range| {
+                vec![
+                    ("offset", range.start.to_string()),
+                    ("length", range.len().to_string()),
+                ]
+            })
+            .unwrap_or_default();
+        let bytes = retry(&self.retry_params, || async {
+            let url = self.operation_url(path, "OPEN", &params);
+            let response = self.send_request(Method::GET, url, None).await?;
+            let bytes = response.bytes().await?;
+            Result::<_, WebHdfsError>::Ok(bytes)
+        })
+        .await?;
+        STORAGE_METRICS
+            .object_storage_download_num_bytes
+            .inc_by(bytes.len() as u64);
+
+        if let Some(range) = range_opt {
+            if bytes.len() != range.len() {
+                let message = format!(
+                    "expected {} bytes, but WebHDFS returned {} bytes",
+                    range.len(),
+                    bytes.len()
+                );
+                return Err(StorageErrorKind::Internal.with_error(anyhow::anyhow!(message)));
+            }
+        }
+        Ok(bytes)
+    }
+
+    /// Uploads the payload. HDFS files are append-only, so the first part creates (or overwrites)
+    /// the file and the following parts are appended to it sequentially. Appends are not
+    /// idempotent, so the whole upload is retried on failure.
+    async fn upload(
+        &self,
+        path: &Path,
+        payload: &dyn PutPayload,
+        part_num_bytes: u64,
+    ) -> Result<(), WebHdfsError> {
+        let total_len = payload.len();
+        let multipart_ranges = if part_num_bytes >= total_len {
+            vec![0..total_len]
+        } else {
+            chunk_range(0..total_len as usize, part_num_bytes as usize)
+                .map(into_u64_range)
+                .collect()
+        };
+        for (part_idx, range) in multipart_ranges.into_iter().enumerate() {
+            STORAGE_METRICS.object_storage_put_parts.inc();
+            STORAGE_METRICS
+                .object_storage_upload_num_bytes
+                .inc_by(range.end - range.start);
+
+            let data = read_range(payload, range).await?;
+            let (method, url) = if part_idx == 0 {
+                let params = [("overwrite", "true".to_string())];
+                (Method::PUT, self.operation_url(path, "CREATE", &params))
+            } else {
+                (Method::POST, self.operation_url(path, "APPEND", &[]))
+            };
+            self.send_request(method, url, Some(data)).await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Storage for WebHdfsStorage {
+    async fn check_connectivity(&self) -> anyhow::Result<()> {
+        let url = self.operation_url(Path::new(""), "GETFILESTATUS", &[]);
+        let get_file_status_res = self
+            .send_request(Method::GET, url, None)
+            .await
+            .map_err(StorageError::from);
+        // The prefix directory is created lazily when the first file is written.
+        ignore_error_kind!(StorageErrorKind::NotFound, get_file_status_res)?;
+        Ok(())
+    }
+
+    async fn put(&self, path: &Path, payload: Box<dyn PutPayload>) -> StorageResult<()> {
+        STORAGE_METRICS.object_storage_put_total.inc();
+        let total_len = payload.len();
+        let part_num_bytes = if self.disable_multipart_upload {
+            total_len
+        } else {
+            self.multipart_policy.part_num_bytes(total_len)
+        };
+        retry(&self.retry_params, || {
+            self.upload(path, payload.as_ref(), part_num_bytes)
+        })
+        .await?;
+        Ok(())
+    }
+
+    async fn copy_to(&self, path: &Path, output: &mut dyn SendableAsync) -> StorageResult<()> {
+        let url = self.operation_url(path, "OPEN", &[]);
+        let response = self.send_request(Method::GET, url, None).await?;
+        let mut chunk_stream = response.bytes_stream();
+
+        while let Some(chunk_res) = chunk_stream.next().await {
+            let chunk = chunk_res.map_err(WebHdfsError::from)?;
+            output.write_all(&chunk).await?;
+            STORAGE_METRICS
+                .object_storage_download_num_bytes
+                .inc_by(chunk.len() as u64);
+        }
+        output.flush().await?;
+        Ok(())
+    }
+
+    async fn delete(&self, path: &Path) -> StorageResult<()> {
+        let params = [("recursive", "false".to_string())];
+        let url = self.operation_url(path, "DELETE", &params);
+        // Deleting a file that does not exist returns `{"boolean": false}`, which we ignore.
+        let delete_res = self
+            .send_request(Method::DELETE, url, None)
+            .await
+            .map_err(StorageError::from);
+        ignore_error_kind!(StorageErrorKind::NotFound, delete_res)?;
+        Ok(())
+    }
+
+    async fn bulk_delete<'a>(&self, paths: &[&'a Path]) -> Result<(), BulkDeleteError> {
+        warn!(
+            num_files = paths.len(),
+            "`WebHdfsStorage` does not support batch delete. Falling back to sequential delete, \
+             which might be slow and issue many requests."
+        );
+        let mut successes = Vec::with_capacity(paths.len());
+        let mut failures = HashMap::new();
+
+        let futures = paths
+            .iter()
+            .map(|path| async move {
+                let delete_res = self.delete(path).await;
+                (path, delete_res)
+            })
+            .collect::<Vec<_>>();
+        let mut stream = futures::stream::iter(futures).buffer_unordered(10);
+
+        while let Some((path, delete_res)) = stream.next().await {
+            match delete_res {
+                Ok(_) => successes.push(path.to_path_buf()),
+                Err(error) => {
+                    let failure = DeleteFailure {
+                        error: Some(error),
+                        ..Default::default()
+                    };
+                    failures.insert(path.to_path_buf(), failure);
+                }
+            };
+        }
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(BulkDeleteError {
+                successes,
+                failures,
+                ..Default::default()
+            })
+        }
+    }
+
+    #[instrument(level = "debug", skip(self, range), fields(range.start = range.start, range.end = range.end))]
+    async fn get_slice(&self, path: &Path, range: Range<usize>) -> StorageResult<OwnedBytes> {
+        self.get_to_bytes(path, Some(range.clone()))
+            .await
+            .map(|bytes| OwnedBytes::new(bytes.to_vec()))
+            .map_err(|err| {
+                err.add_context(format!(
+                    "failed to fetch slice {:?} for file: {}/{}",
+                    range,
+                    self.uri,
+                    path.display(),
+                ))
+            })
+    }
+
+    #[instrument(level = "debug", skip(self, range), fields(range.start = range.start, range.end = range.end))]
+    async fn get_slice_stream(
+        &self,
+        path: &Path,
+        range: Range<usize>,
+    ) -> StorageResult<Box<dyn AsyncRead + Send + Unpin>> {
+        let params = [
+            ("offset", range.start.to_string()),
+            ("length", range.len().to_string()),
+        ];
+        let response = retry(&self.retry_params, || async {
+            let url = self.operation_url(path, "OPEN", &params);
+            self.send_request(Method::GET, url, None).await
+        })
+        .await?;
+        let bytes_stream = response
+            .bytes_stream()
+            .map_err(|error| io::Error::new(io::ErrorKind::Other, error));
+        Ok(Box::new(StreamReader::new(Box::pin(bytes_stream))))
+    }
+
+    #[instrument(level = "debug", skip(self), fields(fetched_bytes_len))]
+    async fn get_all(&self, path: &Path) -> StorageResult<OwnedBytes> {
+        let data = self
+            .get_to_bytes(path, None)
+            .await
+            .map(|bytes| OwnedBytes::new(bytes.to_vec()))
+            .map_err(|err| {
+                err.add_context(format!(
+                    "failed to fetch file: {}/{}",
+                    self.uri,
+                    path.display()
+                ))
+            })?;
+        tracing::Span::current().record("fetched_bytes_len", data.len());
+        Ok(data)
+    }
+
+    async fn file_num_bytes(&self, path: &Path) -> StorageResult<u64> {
+        let file_status_response: FileStatusResponse = retry(&self.retry_params, || async {
+            let url = self.operation_url(path, "GETFILESTATUS", &[]);
+            let response = self.send_request(Method::GET, url, None).await?;
+            let file_status_response = response.json().await?;
+            Result::<_, WebHdfsError>::Ok(file_status_response)
+        })
+        .await?;
+        Ok(file_status_response.file_status.length)
+    }
+
+    fn uri(&self) -> &Uri {
+        &self.uri
+    }
+}
+
+/// Loads a range of the payload into memory.
+async fn read_range(payload: &dyn PutPayload, range: Range<u64>) -> io::Result<Bytes> {
+    let mut reader = payload
+        .range_byte_stream(range.clone())
+        .await?
+        .into_async_read();
+    let mut buf: Vec<u8> = Vec::with_capacity((range.end - range.start) as usize);
+    tokio::io::copy(&mut reader, &mut buf).await?;
+    Ok(Bytes::from(buf))
+}
+
+/// Returns the WebHDFS endpoint for a namenode authority. When the authority does not specify a
+/// port, the default WebHDFS port is used.
+fn default_endpoint(authority: &str) -> String {
+    if authority.contains(':') {
+        format!("http://{authority}")
+    } else {
+        format!("http://{authority}:{DEFAULT_WEBHDFS_PORT}")
+    }
+}
+
+/// Parses an HDFS URI into its optional authority (namenode host and port) and its absolute path.
+fn parse_hdfs_uri(uri: &Uri) -> Option<(Option<String>, PathBuf)> {
+    // Ex: hdfs://namenode:9870/prefix or hdfs:///prefix.
+    static URI_PTN: OnceCell<Regex> = OnceCell::new();
+
+    let captures = URI_PTN
+        .get_or_init(|| {
+            Regex::new(r"^hdfs://(?P<authority>[^/]*)(?P<path>/.*)?$")
+                .expect("The regular expression should compile.")
+        })
+        .captures(uri.as_str())?;
+
+    let authority_opt = captures
+        .name("authority")
+        .map(|authority_match| authority_match.as_str())
+        .filter(|authority| !authority.is_empty())
+        .map(|authority| authority.to_string());
+    let path = captures
+        .name("path")
+        .map(|path_match| PathBuf::from(path_match.as_str()))
+        .unwrap_or_else(|| PathBuf::from("/"));
+    Some((authority_opt, path))
+}
+
+/// Converts error responses into [`WebHdfsError::Status`] errors.
+async fn check_response_status(response: Response) -> Result<Response, WebHdfsError> {
+    let status = response.status();
+
+    if status.is_success() {
+        return Ok(response);
+    }
+    let message = match response.json::<RemoteExceptionResponse>().await {
+        Ok(RemoteExceptionResponse { remote_exception }) => {
+            format!(
+                "{}: {}",
+                remote_exception.exception, remote_exception.message
+            )
+        }
+        Err(_) => status.canonical_reason().unwrap_or("unknown").to_string(),
+    };
+    Err(WebHdfsError::Status { status, message })
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct RemoteExceptionResponse {
+    remote_exception: RemoteException,
+}
+
+#[derive(Debug, Deserialize)]
+struct RemoteException {
+    exception: String,
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct FileStatusResponse {
+    file_status: FileStatus,
+}
+
+#[derive(Debug, Deserialize)]
+struct FileStatus {
+    length: u64,
+}
+
+#[derive(Error, Debug)]
+enum WebHdfsError {
+    #[error("WebHDFS request failed with status `{status}`: {message}")]
+    Status { status: StatusCode, message: String },
+    #[error("WebHDFS request failed: {0}")]
+    Request(#[from] reqwest::Error),
+    #[error("invalid WebHDFS response: {0}")]
+    InvalidResponse(String),
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+}
+
+impl Retryable for WebHdfsError {
+    fn is_retryable(&self) -> bool {
+        match self {
+            WebHdfsError::Status { status, .. } => {
+                status.is_server_error() || *status == StatusCode::TOO_MANY_REQUESTS
+            }
+            WebHdfsError::Request(error) => error.is_connect() || error.is_timeout(),
+            WebHdfsError::InvalidResponse(_) => false,
+            WebHdfsError::Io(_) => true,
+        }
+    }
+}
+
+impl From<WebHdfsError> for StorageError {
+    fn from(err: WebHdfsError) -> Self {
+        match &err {
+            WebHdfsError::Status { status, .. } => match *status {
+                StatusCode::NOT_FOUND => StorageErrorKind::NotFound.with_error(err),
+                StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+                    StorageErrorKind::Unauthorized.with_error(err)
+                }
+                _ => StorageErrorKind::Service.with_error(err),
+            },
+            WebHdfsError::Request(error) if error.is_timeout() => {
+                StorageErrorKind::Timeout.with_error(err)
+            }
+            WebHdfsError::Request(_) | WebHdfsError::Io(_) => StorageErrorKind::Io.with_error(err),
+            WebHdfsError::InvalidResponse(_) => StorageErrorKind::Internal.with_error(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use quickwit_common::uri::Uri;
+    use quickwit_config::HdfsStorageConfig;
+
+    use super::{parse_hdfs_uri, WebHdfsStorage};
+
+    #[test]
+    fn test_parse_hdfs_uri() {
+        let (authority_opt, path) = parse_hdfs_uri(&Uri::for_test("hdfs://namenode:9870")).unwrap();
+        assert_eq!(authority_opt.unwrap(), "namenode:9870");
+        assert_eq!(path, PathBuf::from("/"));
+
+        let (authority_opt, path) =
+            parse_hdfs_uri(&Uri::for_test("hdfs://namenode/indexes/hdfs-logs")).unwrap();
+        assert_eq!(authority_opt.unwrap(), "namenode");
+        assert_eq!(path, PathBuf::from("/indexes/hdfs-logs"));
+
+        let (authority_opt, path) = parse_hdfs_uri(&Uri::for_test("hdfs:///indexes")).unwrap();
+        assert!(authority_opt.is_none());
+        assert_eq!(path, PathBuf::from("/indexes"));
+    }
+
+    #[test]
+    fn test_hdfs_storage_from_uri() {
+        let hdfs_storage_config = HdfsStorageConfig::default();
+        let hdfs_storage = WebHdfsStorage::from_uri(
+            &hdfs_storage_config,
+            &Uri::for_test("hdfs://namenode/indexes"),
+        )
+        .unwrap();
+        assert_eq!(hdfs_storage.endpoint.as_str(), "http://namenode:9870/");
+        assert_eq!(hdfs_storage.prefix, PathBuf::from("/indexes"));
+
+        WebHdfsStorage::from_uri(&hdfs_storage_config, &Uri::for_test("hdfs:///indexes"))
+            .unwrap_err();
+
+        let hdfs_storage_config = HdfsStorageConfig {
+            endpoint: Some("https://httpfs:14000".to_string()),
+            user_name: Some("quickwit".to_string()),
+            disable_multipart_upload: true,
+        };
+        let hdfs_storage =
+            WebHdfsStorage::from_uri(&hdfs_storage_config, &Uri::for_test("hdfs:///indexes"))
+                .unwrap();
+        assert_eq!(hdfs_storage.endpoint.as_str(), "https://httpfs:14000/");
+        assert!(hdfs_storage.disable_multipart_upload);
+    }
+
+    #[test]
+    fn test_hdfs_storage_operation_url() {
+        let hdfs_storage_config = HdfsStorageConfig {
+            user_name: Some("quickwit".to_string()),
+            ..Default::default()
+        };
+        let hdfs_storage = WebHdfsStorage::from_uri(
+            &hdfs_storage_config,
+            &Uri::for_test("hdfs://namenode:9870/indexes/hdfs-logs"),
+        )
+        .unwrap();
+        let params = [("offset", "10".to_string()), ("length", "20".to_string())];
+        assert_eq!(
+            hdfs_storage
+                .operation_url(Path::new("split 01.split"), "OPEN", &params)
+                .as_str(),
+            "http://namenode:9870/webhdfs/v1/indexes/hdfs-logs/split%2001.split?op=OPEN&user.name=quickwit&offset=10&length=20"
+        );
+
+        let hdfs_storage =
+            WebHdfsStorage::from_uri(&hdfs_storage_config, &Uri::for_test("hdfs://namenode"))
+                .unwrap();
+        assert_eq!(
+            hdfs_storage
+                .operation_url(Path::new(""), "GETFILESTATUS", &[])
+                .as_str(),
+            "http://namenode:9870/webhdfs/v1/?op=GETFILESTATUS&user.name=quickwit"
+        );
+    }
+}
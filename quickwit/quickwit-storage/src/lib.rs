@@ -31,18 +31,22 @@
 //!
 //! - The `BundleStorage` bundles together multiple files into a single file.
 mod cache;
+mod coalescing;
 mod debouncer;
+mod encryption;
 mod metrics;
 mod storage;
+pub(crate) use coalescing::{CoalescingStorage, ReadCoalescingSettings};
 pub use debouncer::AsyncDebouncer;
 pub(crate) use debouncer::DebouncedStorage;
 
 pub use self::metrics::STORAGE_METRICS;
 pub use self::payload::PutPayload;
-pub use self::storage::Storage;
+pub use self::storage::{Storage, StorageFileEntry};
 
 mod bundle_storage;
 mod error;
+mod hdfs_storage;
 mod local_file_storage;
 mod object_storage;
 mod payload;
@@ -65,6 +69,8 @@ pub use self::cache::MockStorageCache;
 pub use self::cache::{
     wrap_storage_with_cache, ByteRangeCache, MemorySizedCache, QuickwitCache, StorageCache,
 };
+pub use self::encryption::{generate_data_key, unwrap_data_key, DataKey};
+pub use self::hdfs_storage::{WebHdfsStorage, WebHdfsStorageFactory};
 pub use self::local_file_storage::{LocalFileStorage, LocalFileStorageFactory};
 #[cfg(feature = "azure")]
 pub use self::object_storage::{AzureBlobStorage, AzureBlobStorageFactory};
@@ -72,7 +78,7 @@ pub use self::object_storage::{
     MultiPartPolicy, S3CompatibleObjectStorage, S3CompatibleObjectStorageFactory,
 };
 pub use self::ram_storage::{RamStorage, RamStorageBuilder};
-pub use self::split::{SplitPayload, SplitPayloadBuilder};
+pub use self::split::{FilePayload, SplitPayload, SplitPayloadBuilder};
 #[cfg(any(test, feature = "testsuite"))]
 pub use self::storage::MockStorage;
 #[cfg(any(test, feature = "testsuite"))]
@@ -23,6 +23,7 @@ use std::io::{ErrorKind, SeekFrom};
 use std::ops::Range;
 use std::path::{Component, Path, PathBuf};
 use std::sync::Arc;
+use std::time::UNIX_EPOCH;
 
 use async_trait::async_trait;
 use futures::future::{BoxFuture, FutureExt};
@@ -36,7 +37,7 @@ use tracing::warn;
 use crate::storage::SendableAsync;
 use crate::{
     BulkDeleteError, DebouncedStorage, DeleteFailure, OwnedBytes, Storage, StorageError,
-    StorageErrorKind, StorageFactory, StorageResolverError, StorageResult,
+    StorageErrorKind, StorageFactory, StorageFileEntry, StorageResolverError, StorageResult,
 };
 
 /// File system compatible storage implementation.
@@ -344,6 +345,48 @@ impl Storage for LocalFileStorage {
             }
         }
     }
+
+    async fn list(&self, prefix: &Path) -> StorageResult<Vec<StorageFileEntry>> {
+        let mut file_entries = Vec::new();
+        let mut dir_paths = vec![self.full_path(prefix)?];
+
+        while let Some(dir_path) = dir_paths.pop() {
+            let mut read_dir = match tokio::fs::read_dir(&dir_path).await {
+                Ok(read_dir) => read_dir,
+                // The directory may have been deleted by a concurrent task.
+                Err(error) if error.kind() == ErrorKind::NotFound => continue,
+                Err(error) => return Err(error.into()),
+            };
+            while let Some(dir_entry) = read_dir.next_entry().await? {
+                let metadata = match dir_entry.metadata().await {
+                    Ok(metadata) => metadata,
+                    Err(error) if error.kind() == ErrorKind::NotFound => continue,
+                    Err(error) => return Err(error.into()),
+                };
+                if metadata.is_dir() {
+                    dir_paths.push(dir_entry.path());
+                    continue;
+                }
+                let path = dir_entry
+                    .path()
+                    .strip_prefix(&self.root)
+                    .expect("The listed paths should be located under the storage root.")
+                    .to_path_buf();
+                let last_modified_timestamp_opt = metadata
+                    .modified()
+                    .ok()
+                    .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                    .map(|elapsed| elapsed.as_secs() as i64);
+                let file_entry = StorageFileEntry {
+                    path,
+                    num_bytes: metadata.len(),
+                    last_modified_timestamp_opt,
+                };
+                file_entries.push(file_entry);
+            }
+        }
+        Ok(file_entries)
+    }
 }
 
 /// A File storage resolver
@@ -450,6 +493,44 @@ mod tests {
         assert!(!temp_dir.path().join("foo-dir").try_exists().unwrap());
     }
 
+    #[tokio::test]
+    async fn test_local_file_storage_list() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let uri = Uri::from_str(&format!("{}", temp_dir.path().display())).unwrap();
+        let local_file_storage = LocalFileStorage::from_uri(&uri).unwrap();
+
+        let file_entries = local_file_storage.list(Path::new("")).await.unwrap();
+        assert!(file_entries.is_empty());
+
+        local_file_storage
+            .put(Path::new("foo.split"), Box::new(b"foo".to_vec()))
+            .await
+            .unwrap();
+        local_file_storage
+            .put(
+                Path::new("bar-dir/bar.split"),
+                Box::new(b"bar-bar".to_vec()),
+            )
+            .await
+            .unwrap();
+
+        let mut file_entries = local_file_storage.list(Path::new("")).await.unwrap();
+        file_entries.sort_by(|left, right| left.path.cmp(&right.path));
+        assert_eq!(file_entries.len(), 2);
+        assert_eq!(file_entries[0].path, Path::new("bar-dir/bar.split"));
+        assert_eq!(file_entries[0].num_bytes, 7);
+        assert!(file_entries[0].last_modified_timestamp_opt.is_some());
+        assert_eq!(file_entries[1].path, Path::new("foo.split"));
+        assert_eq!(file_entries[1].num_bytes, 3);
+
+        let file_entries = local_file_storage.list(Path::new("bar-dir")).await.unwrap();
+        assert_eq!(file_entries.len(), 1);
+        assert_eq!(file_entries[0].path, Path::new("bar-dir/bar.split"));
+
+        let file_entries = local_file_storage.list(Path::new("qux-dir")).await.unwrap();
+        assert!(file_entries.is_empty());
+    }
+
     #[tokio::test]
     async fn test_try_delete_dir_all() -> anyhow::Result<()> {
         let path_root = tempfile::tempdir()?.into_path();
@@ -20,7 +20,9 @@
 // See https://prometheus.io/docs/practices/naming/
 
 use once_cell::sync::Lazy;
-use quickwit_common::metrics::{new_counter, new_gauge, IntCounter, IntGauge};
+use quickwit_common::metrics::{
+    new_counter, new_counter_vec, new_gauge, IntCounter, IntCounterVec, IntGauge,
+};
 
 /// Counters associated to storage operations.
 pub struct StorageMetrics {
@@ -29,7 +31,9 @@ pub struct StorageMetrics {
     pub fast_field_cache: CacheMetrics,
     pub split_footer_cache: CacheMetrics,
     pub searcher_split_cache: CacheMetrics,
+    pub searcher_split_cache_per_index: SplitCacheIndexMetrics,
     pub object_storage_get_total: IntCounter,
+    pub object_storage_coalesced_reads_total: IntCounter,
     pub object_storage_put_total: IntCounter,
     pub object_storage_put_parts: IntCounter,
     pub object_storage_download_num_bytes: IntCounter,
@@ -43,6 +47,7 @@ impl Default for StorageMetrics {
             shortlived_cache: CacheMetrics::for_component("shortlived"),
             partial_request_cache: CacheMetrics::for_component("partial_request"),
             searcher_split_cache: CacheMetrics::for_component("searcher_split"),
+            searcher_split_cache_per_index: SplitCacheIndexMetrics::default(),
 
             split_footer_cache: CacheMetrics::for_component("splitfooter"),
             object_storage_get_total: new_counter(
@@ -50,6 +55,11 @@ impl Default for StorageMetrics {
                 "Number of objects fetched.",
                 "quickwit_storage",
             ),
+            object_storage_coalesced_reads_total: new_counter(
+                "object_storage_coalesced_reads_total",
+                "Number of byte range reads merged into another read by the read coalescing layer.",
+                "quickwit_storage",
+            ),
             object_storage_put_total: new_counter(
                 "object_storage_puts_total",
                 "Number of objects uploaded. May differ from object_storage_requests_parts due to \
@@ -120,6 +130,46 @@ impl CacheMetrics {
     }
 }
 
+/// Counters of the searcher split cache, labeled by the storage URI of the index of the splits.
+pub struct SplitCacheIndexMetrics {
+    pub hits_num_items: IntCounterVec<1>,
+    pub misses_num_items: IntCounterVec<1>,
+    pub evicted_num_items: IntCounterVec<1>,
+    pub evicted_num_bytes: IntCounterVec<1>,
+}
+
+impl Default for SplitCacheIndexMetrics {
+    fn default() -> Self {
+        let namespace = "quickwit_cache_searcher_split";
+        SplitCacheIndexMetrics {
+            hits_num_items: new_counter_vec(
+                "index_cache_hit_total",
+                "Number of searcher split cache hits per index",
+                namespace,
+                ["index_uri"],
+            ),
+            misses_num_items: new_counter_vec(
+                "index_cache_miss_total",
+                "Number of searcher split cache misses per index",
+                namespace,
+                ["index_uri"],
+            ),
+            evicted_num_items: new_counter_vec(
+                "index_evicted_total",
+                "Number of splits evicted from the searcher split cache per index",
+                namespace,
+                ["index_uri"],
+            ),
+            evicted_num_bytes: new_counter_vec(
+                "index_evicted_bytes",
+                "Number of bytes evicted from the searcher split cache per index",
+                namespace,
+                ["index_uri"],
+            ),
+        }
+    }
+}
+
 /// Storage counters exposes a bunch a set of storage/cache related metrics through a prometheus
 /// endpoint.
 pub static STORAGE_METRICS: Lazy<StorageMetrics> = Lazy::new(StorageMetrics::default);
@@ -26,6 +26,7 @@ use std::{env, fmt, io};
 
 use anyhow::anyhow;
 use async_trait::async_trait;
+use aws_config::sts::AssumeRoleProvider;
 use aws_credential_types::provider::SharedCredentialsProvider;
 use aws_sdk_s3::config::{Credentials, Region};
 use aws_sdk_s3::error::{ProvideErrorMetadata, SdkError};
@@ -51,7 +52,7 @@ use crate::object_storage::MultiPartPolicy;
 use crate::storage::SendableAsync;
 use crate::{
     BulkDeleteError, DeleteFailure, OwnedBytes, Storage, StorageError, StorageErrorKind,
-    StorageResolverError, StorageResult, STORAGE_METRICS,
+    StorageFileEntry, StorageResolverError, StorageResult, STORAGE_METRICS,
 };
 
 /// Semaphore to limit the number of concurent requests to the object store. Some object stores
@@ -129,11 +130,55 @@ fn get_region(s3_storage_config: &S3StorageConfig) -> Option<Region> {
     })
 }
 
-async fn create_s3_client(s3_storage_config: &S3StorageConfig) -> S3Client {
+/// Wraps the base credentials provider into an STS assume role provider if an IAM role is
+/// configured for `uri`.
+fn maybe_assume_role(
+    s3_storage_config: &S3StorageConfig,
+    uri: &Uri,
+    region: Option<&Region>,
+    base_credentials_provider: Option<SharedCredentialsProvider>,
+) -> Option<SharedCredentialsProvider> {
+    let Some(assume_role_config) = s3_storage_config.find_assume_role(uri.as_str()) else {
+        return base_credentials_provider;
+    };
+    let Some(base_credentials_provider) = base_credentials_provider else {
+        warn!(
+            uri=%uri,
+            role_arn=%assume_role_config.role_arn,
+            "no base credentials available to assume IAM role"
+        );
+        return None;
+    };
+    info!(
+        uri=%uri,
+        role_arn=%assume_role_config.role_arn,
+        "assuming IAM role defined in storage config"
+    );
+    let mut assume_role_provider_builder =
+        AssumeRoleProvider::builder(&assume_role_config.role_arn)
+            .session_name(assume_role_config.session_name());
+
+    if let Some(external_id) = &assume_role_config.external_id {
+        assume_role_provider_builder = assume_role_provider_builder.external_id(external_id);
+    }
+    if let Some(region) = region {
+        assume_role_provider_builder = assume_role_provider_builder.region(region.clone());
+    }
+    let assume_role_provider = assume_role_provider_builder.build(base_credentials_provider);
+    Some(SharedCredentialsProvider::new(assume_role_provider))
+}
+
+async fn create_s3_client(s3_storage_config: &S3StorageConfig, uri: &Uri) -> S3Client {
     let aws_config = get_aws_config().await;
-    let credentials_provider =
-        get_credentials_provider(s3_storage_config).or(aws_config.credentials_provider().cloned());
     let region = get_region(s3_storage_config).or(aws_config.region().cloned());
+    let base_credentials_provider =
+        get_credentials_provider(s3_storage_config).or(aws_config.credentials_provider().cloned());
+    let credentials_provider = maybe_assume_role(
+        s3_storage_config,
+        uri,
+        region.as_ref(),
+        base_credentials_provider,
+    );
     let mut s3_config = aws_sdk_s3::Config::builder().region(region);
 
     s3_config.set_credentials_cache(aws_config.credentials_cache().cloned());
@@ -158,7 +203,7 @@ impl S3CompatibleObjectStorage {
         uri: Uri,
         bucket: String,
     ) -> Result<Self, StorageResolverError> {
-        let s3_client = create_s3_client(s3_storage_config).await;
+        let s3_client = create_s3_client(s3_storage_config, &uri).await;
         let retry_params = RetryParams {
             max_attempts: 3,
             ..Default::default()
@@ -820,6 +865,53 @@ impl Storage for S3CompatibleObjectStorage {
         Ok(head_object_output.content_length() as u64)
     }
 
+    async fn list(&self, prefix: &Path) -> StorageResult<Vec<StorageFileEntry>> {
+        let bucket = self.bucket.clone();
+        let mut key_prefix = self.key(prefix);
+
+        if !key_prefix.is_empty() && !key_prefix.ends_with('/') {
+            key_prefix.push('/');
+        }
+        let mut file_entries = Vec::new();
+        let mut continuation_token_opt: Option<String> = None;
+
+        loop {
+            let _permit = REQUEST_SEMAPHORE.acquire().await;
+            let list_objects_output = aws_retry(&self.retry_params, || async {
+                self.s3_client
+                    .list_objects_v2()
+                    .bucket(&bucket)
+                    .prefix(&key_prefix)
+                    .set_continuation_token(continuation_token_opt.clone())
+                    .send()
+                    .await
+            })
+            .await?;
+
+            for object in list_objects_output.contents().unwrap_or_default() {
+                let Some(key) = object.key() else {
+                    continue;
+                };
+                let file_entry = StorageFileEntry {
+                    path: self.relative_path(key),
+                    num_bytes: object.size() as u64,
+                    last_modified_timestamp_opt: object
+                        .last_modified()
+                        .map(|last_modified| last_modified.secs()),
+                };
+                file_entries.push(file_entry);
+            }
+            continuation_token_opt = list_objects_output
+                .next_continuation_token()
+                .map(|continuation_token| continuation_token.to_string());
+
+            if continuation_token_opt.is_none() {
+                break;
+            }
+        }
+        Ok(file_entries)
+    }
+
     fn uri(&self) -> &Uri {
         &self.uri
     }
@@ -895,6 +987,46 @@ mod tests {
         assert_eq!(parse_s3_uri(&Uri::for_test("ram://path/to/file")), None);
     }
 
+    #[test]
+    fn test_maybe_assume_role() {
+        let s3_storage_config = S3StorageConfig {
+            assume_roles: vec![quickwit_config::S3AssumeRoleConfig {
+                uri_prefix: "s3://other-account-bucket".to_string(),
+                role_arn: "arn:aws:iam::123456789012:role/quickwit".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let credentials =
+            Credentials::from_keys("test-access-key-id", "test-secret-access-key", None);
+        let base_credentials_provider = SharedCredentialsProvider::new(credentials);
+        let region = Region::new("us-east-1");
+
+        let credentials_provider_opt = maybe_assume_role(
+            &s3_storage_config,
+            &Uri::for_test("s3://bucket/indexes"),
+            Some(&region),
+            None,
+        );
+        assert!(credentials_provider_opt.is_none());
+
+        let credentials_provider_opt = maybe_assume_role(
+            &s3_storage_config,
+            &Uri::for_test("s3://other-account-bucket/indexes"),
+            Some(&region),
+            None,
+        );
+        assert!(credentials_provider_opt.is_none());
+
+        let credentials_provider_opt = maybe_assume_role(
+            &s3_storage_config,
+            &Uri::for_test("s3://other-account-bucket/indexes"),
+            Some(&region),
+            Some(base_credentials_provider),
+        );
+        assert!(credentials_provider_opt.is_some());
+    }
+
     #[tokio::test]
     async fn test_s3_compatible_storage_relative_path() {
         let sdk_config = aws_config::load_from_env().await;
@@ -980,6 +1112,100 @@ mod tests {
             .ends_with("DeleteObject"));
     }
 
+    #[tokio::test]
+    async fn test_s3_compatible_storage_list() {
+        let client = TestConnection::new(vec![
+            (
+                http::Request::builder()
+                    .body(SdkBody::from(Body::empty()))
+                    .unwrap(),
+                http::Response::builder()
+                    .body(SdkBody::from(Body::from(
+                        r#"<?xml version="1.0" encoding="UTF-8"?>
+                        <ListBucketResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+                            <Name>bucket</Name>
+                            <Prefix>indexes/</Prefix>
+                            <KeyCount>1</KeyCount>
+                            <MaxKeys>1</MaxKeys>
+                            <IsTruncated>true</IsTruncated>
+                            <NextContinuationToken>next-token</NextContinuationToken>
+                            <Contents>
+                                <Key>indexes/foo.split</Key>
+                                <LastModified>2023-01-01T00:00:00.000Z</LastModified>
+                                <Size>3</Size>
+                            </Contents>
+                        </ListBucketResult>"#,
+                    )))
+                    .unwrap(),
+            ),
+            (
+                http::Request::builder()
+                    .body(SdkBody::from(Body::empty()))
+                    .unwrap(),
+                http::Response::builder()
+                    .body(SdkBody::from(Body::from(
+                        r#"<?xml version="1.0" encoding="UTF-8"?>
+                        <ListBucketResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+                            <Name>bucket</Name>
+                            <Prefix>indexes/</Prefix>
+                            <KeyCount>1</KeyCount>
+                            <MaxKeys>1</MaxKeys>
+                            <IsTruncated>false</IsTruncated>
+                            <Contents>
+                                <Key>indexes/bar/bar.split</Key>
+                                <LastModified>2023-01-01T00:00:00.000Z</LastModified>
+                                <Size>7</Size>
+                            </Contents>
+                        </ListBucketResult>"#,
+                    )))
+                    .unwrap(),
+            ),
+        ]);
+        let credentials = Credentials::new("mock_key", "mock_secret", None, None, "mock_provider");
+        let config = aws_sdk_s3::Config::builder()
+            .region(Some(Region::new("Foo")))
+            .http_connector(client.clone())
+            .credentials_provider(credentials)
+            .build();
+        let s3_client = S3Client::from_conf(config);
+        let uri = Uri::for_test("s3://bucket/indexes");
+        let bucket = "bucket".to_string();
+        let prefix = PathBuf::from("indexes");
+
+        let s3_storage = S3CompatibleObjectStorage {
+            s3_client,
+            uri,
+            bucket,
+            prefix,
+            multipart_policy: MultiPartPolicy::default(),
+            retry_params: RetryParams::default(),
+            disable_multi_object_delete: false,
+            disable_multipart_upload: false,
+        };
+        let file_entries = s3_storage.list(Path::new("")).await.unwrap();
+        assert_eq!(
+            file_entries,
+            [
+                StorageFileEntry {
+                    path: PathBuf::from("foo.split"),
+                    num_bytes: 3,
+                    last_modified_timestamp_opt: Some(1_672_531_200),
+                },
+                StorageFileEntry {
+                    path: PathBuf::from("bar/bar.split"),
+                    num_bytes: 7,
+                    last_modified_timestamp_opt: Some(1_672_531_200),
+                },
+            ]
+        );
+        let requests = client.requests();
+        assert_eq!(requests.len(), 2);
+
+        let second_request_uri = requests[1].actual.uri().to_string();
+        assert!(second_request_uri.contains("continuation-token=next-token"));
+        assert!(second_request_uri.contains("prefix=indexes%2F"));
+    }
+
     #[tokio::test]
     async fn test_s3_compatible_storage_bulk_delete_multi() {
         let client = TestConnection::new(vec![(
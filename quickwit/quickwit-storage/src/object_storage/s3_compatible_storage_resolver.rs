@@ -24,7 +24,8 @@ use quickwit_common::uri::Uri;
 use quickwit_config::{S3StorageConfig, StorageBackend};
 
 use crate::{
-    DebouncedStorage, S3CompatibleObjectStorage, Storage, StorageFactory, StorageResolverError,
+    CoalescingStorage, DebouncedStorage, ReadCoalescingSettings, S3CompatibleObjectStorage,
+    Storage, StorageFactory, StorageResolverError,
 };
 
 /// S3 compatible object storage resolver.
@@ -47,6 +48,17 @@ impl StorageFactory for S3CompatibleObjectStorageFactory {
 
     async fn resolve(&self, uri: &Uri) -> Result<Arc<dyn Storage>, StorageResolverError> {
         let storage = S3CompatibleObjectStorage::from_uri(&self.storage_config, uri).await?;
-        Ok(Arc::new(DebouncedStorage::new(storage)))
+        if self.storage_config.disable_read_coalescing {
+            return Ok(Arc::new(DebouncedStorage::new(storage)));
+        }
+        let read_coalescing_settings = ReadCoalescingSettings {
+            max_gap_num_bytes: quickwit_common::get_from_env(
+                "QW_S3_READ_COALESCING_MAX_GAP",
+                ReadCoalescingSettings::default().max_gap_num_bytes,
+            ),
+            ..Default::default()
+        };
+        let coalescing_storage = CoalescingStorage::new(storage, read_coalescing_settings);
+        Ok(Arc::new(DebouncedStorage::new(coalescing_storage)))
     }
 }
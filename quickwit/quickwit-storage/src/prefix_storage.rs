@@ -27,7 +27,7 @@ use quickwit_common::uri::Uri;
 use tokio::io::AsyncRead;
 
 use crate::storage::SendableAsync;
-use crate::{BulkDeleteError, OwnedBytes, Storage};
+use crate::{BulkDeleteError, OwnedBytes, Storage, StorageFileEntry};
 
 /// This storage acts as a proxy to another storage that simply modifies each API call
 /// by preceding each path with a given a prefix.
@@ -119,6 +119,24 @@ impl Storage for PrefixStorage {
     async fn file_num_bytes(&self, path: &Path) -> crate::StorageResult<u64> {
         self.storage.file_num_bytes(&self.prefix.join(path)).await
     }
+
+    async fn list(&self, prefix: &Path) -> crate::StorageResult<Vec<StorageFileEntry>> {
+        let file_entries = self
+            .storage
+            .list(&self.prefix.join(prefix))
+            .await?
+            .into_iter()
+            .map(|file_entry| StorageFileEntry {
+                path: file_entry
+                    .path
+                    .strip_prefix(&self.prefix)
+                    .expect("The listed paths should be located under the prefix.")
+                    .to_path_buf(),
+                ..file_entry
+            })
+            .collect();
+        Ok(file_entries)
+    }
 }
 
 /// Creates a [`PrefixStorage`] using an underlying storage and a prefix.
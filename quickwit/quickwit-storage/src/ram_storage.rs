@@ -33,8 +33,8 @@ use tokio::sync::RwLock;
 use crate::prefix_storage::add_prefix_to_storage;
 use crate::storage::SendableAsync;
 use crate::{
-    BulkDeleteError, OwnedBytes, Storage, StorageErrorKind, StorageFactory, StorageResolverError,
-    StorageResult,
+    BulkDeleteError, OwnedBytes, Storage, StorageErrorKind, StorageFactory, StorageFileEntry,
+    StorageResolverError, StorageResult,
 };
 
 /// In Ram implementation of quickwit's storage.
@@ -160,6 +160,22 @@ impl Storage for RamStorage {
             Err(StorageErrorKind::NotFound.with_error(err))
         }
     }
+
+    async fn list(&self, prefix: &Path) -> StorageResult<Vec<StorageFileEntry>> {
+        let file_entries = self
+            .files
+            .read()
+            .await
+            .iter()
+            .filter(|(path, _)| path.starts_with(prefix))
+            .map(|(path, file_bytes)| StorageFileEntry {
+                path: path.clone(),
+                num_bytes: file_bytes.len() as u64,
+                last_modified_timestamp_opt: None,
+            })
+            .collect();
+        Ok(file_entries)
+    }
 }
 
 /// Builder to create a prepopulated [`RamStorage`]. This is mostly useful for tests.
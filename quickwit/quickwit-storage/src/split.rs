@@ -75,12 +75,24 @@ impl PutPayload for SplitPayload {
     }
 }
 
+/// Payload streaming the content of a local file.
 #[derive(Clone)]
-struct FilePayload {
+pub struct FilePayload {
     len: u64,
     path: PathBuf,
 }
 
+impl FilePayload {
+    /// Creates a payload for the file at `path`.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let metadata = std::fs::metadata(path)?;
+        Ok(Self {
+            len: metadata.len(),
+            path: path.to_owned(),
+        })
+    }
+}
+
 #[async_trait]
 impl PutPayload for FilePayload {
     fn len(&self) -> u64 {
@@ -142,7 +154,7 @@ impl SplitPayloadBuilder {
 
     /// Adds the file to the bundle file.
     pub fn add_file(&mut self, path: &Path) -> io::Result<()> {
-        let file = std::fs::metadata(path)?;
+        let file_payload = FilePayload::open(path)?;
         let file_name = path
             .file_name()
             .and_then(std::ffi::OsStr::to_str)
@@ -154,11 +166,6 @@ impl SplitPayloadBuilder {
                 )
             })?;
 
-        let file_payload = FilePayload {
-            path: path.to_owned(),
-            len: file.len(),
-        };
-
         self.add_payload(file_name, Box::new(file_payload));
 
         Ok(())
@@ -27,6 +27,7 @@ use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 use tracing::{error, instrument};
 use ulid::Ulid;
 
+use crate::split_cache::manifest::compute_checksum;
 use crate::split_cache::split_table::{CandidateSplit, DownloadOpportunity, SplitTable};
 use crate::StorageResolver;
 
@@ -47,11 +48,12 @@ pub(crate) fn delete_evicted_splits(root_path: &Path, splits_to_delete: &[Ulid])
     }
 }
 
+/// Downloads a split, and returns its size and checksum.
 async fn download_split(
     root_path: &Path,
     candidate_split: &CandidateSplit,
     storage_resolver: StorageResolver,
-) -> anyhow::Result<u64> {
+) -> anyhow::Result<(u64, String)> {
     let CandidateSplit {
         split_ulid,
         storage_uri,
@@ -63,7 +65,10 @@ async fn download_split(
     let num_bytes = storage
         .copy_to_file(Path::new(&split_filename), &target_filepath)
         .await?;
-    Ok(num_bytes)
+    // The file has just been written, so reading it back mostly hits the page cache.
+    let checksum =
+        tokio::task::spawn_blocking(move || compute_checksum(&target_filepath)).await??;
+    Ok((num_bytes, checksum))
 }
 
 async fn perform_eviction_and_download(
@@ -84,9 +89,11 @@ async fn perform_eviction_and_download(
         delete_evicted_splits(&root_path_clone, &splits_to_delete[..]);
     })
     .await;
-    let num_bytes = download_split(&root_path, &split_to_download, storage_resolver).await?;
+    let (num_bytes, checksum) =
+        download_split(&root_path, &split_to_download, storage_resolver).await?;
     let mut shared_split_table_lock = shared_split_table.lock().unwrap();
     shared_split_table_lock.register_as_downloaded(split_ulid, num_bytes);
+    shared_split_table_lock.set_checksum(split_ulid, checksum);
     Ok(())
 }
 
@@ -0,0 +1,314 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use quickwit_common::split_file;
+use quickwit_common::uri::Uri;
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+use ulid::Ulid;
+
+use crate::split_cache::split_table::SplitTable;
+
+/// Name of the file, in the split cache directory, listing the cached splits.
+pub(crate) const MANIFEST_FILENAME: &str = "split-cache-manifest.json";
+
+/// Interval at which the manifest is written to disk.
+pub(crate) const MANIFEST_FLUSH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// A split present in the cache directory.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) struct OnDiskSplit {
+    pub num_bytes: u64,
+    /// Unknown for splits found on disk but missing from the manifest.
+    pub last_accessed: Option<SystemTime>,
+    /// Hex-encoded MD5 digest of the split file. Unknown until the split has been validated.
+    pub checksum: Option<String>,
+    /// Storage URI of the index of the split. Unknown for splits found on disk but missing from
+    /// the manifest.
+    pub storage_uri: Option<Uri>,
+}
+
+#[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
+struct ManifestEntry {
+    num_bytes: u64,
+    last_accessed_unix_micros: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    checksum: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    storage_uri: Option<String>,
+}
+
+/// Persisted list of the splits of the cache, with their size, checksum, and last access
+/// date.
+///
+/// The manifest lets the cache restore its eviction order after a restart, instead of treating
+/// all the splits found on disk as equally old.
+#[derive(Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub(crate) struct SplitCacheManifest {
+    splits: BTreeMap<String, ManifestEntry>,
+}
+
+impl SplitCacheManifest {
+    pub fn from_on_disk_splits(on_disk_splits: &BTreeMap<Ulid, OnDiskSplit>) -> Self {
+        let splits = on_disk_splits
+            .iter()
+            .map(|(split_ulid, on_disk_split)| {
+                let last_accessed_unix_micros = on_disk_split
+                    .last_accessed
+                    .and_then(|last_accessed| last_accessed.duration_since(UNIX_EPOCH).ok())
+                    .map(|since_epoch| since_epoch.as_micros() as u64)
+                    .unwrap_or(0);
+                let manifest_entry = ManifestEntry {
+                    num_bytes: on_disk_split.num_bytes,
+                    last_accessed_unix_micros,
+                    checksum: on_disk_split.checksum.clone(),
+                    storage_uri: on_disk_split
+                        .storage_uri
+                        .as_ref()
+                        .map(|storage_uri| storage_uri.to_string()),
+                };
+                (split_ulid.to_string(), manifest_entry)
+            })
+            .collect();
+        SplitCacheManifest { splits }
+    }
+
+    /// Returns the splits listed in the manifest. Entries with an invalid split ID are ignored.
+    pub fn into_on_disk_splits(self) -> BTreeMap<Ulid, OnDiskSplit> {
+        self.splits
+            .into_iter()
+            .filter_map(|(split_id, manifest_entry)| {
+                let split_ulid = Ulid::from_str(&split_id).ok()?;
+                let last_accessed = (manifest_entry.last_accessed_unix_micros > 0).then(|| {
+                    UNIX_EPOCH + Duration::from_micros(manifest_entry.last_accessed_unix_micros)
+                });
+                let storage_uri = manifest_entry
+                    .storage_uri
+                    .and_then(|storage_uri| Uri::from_str(&storage_uri).ok());
+                let on_disk_split = OnDiskSplit {
+                    num_bytes: manifest_entry.num_bytes,
+                    last_accessed,
+                    checksum: manifest_entry.checksum,
+                    storage_uri,
+                };
+                Some((split_ulid, on_disk_split))
+            })
+            .collect()
+    }
+
+    /// Loads the manifest of the cache directory. A missing or corrupted manifest is
+    /// treated as empty.
+    pub fn load(root_path: &Path) -> SplitCacheManifest {
+        let manifest_path = root_path.join(MANIFEST_FILENAME);
+        let manifest_bytes = match std::fs::read(&manifest_path) {
+            Ok(manifest_bytes) => manifest_bytes,
+            Err(io_err) => {
+                if io_err.kind() != io::ErrorKind::NotFound {
+                    warn!(path=%manifest_path.display(), error=?io_err, "failed to read split cache manifest");
+                }
+                return SplitCacheManifest::default();
+            }
+        };
+        match serde_json::from_slice(&manifest_bytes) {
+            Ok(manifest) => manifest,
+            Err(error) => {
+                warn!(path=%manifest_path.display(), error=?error, "failed to parse split cache manifest, ignoring");
+                SplitCacheManifest::default()
+            }
+        }
+    }
+
+    /// Atomically writes the manifest to the cache directory.
+    pub fn save(&self, root_path: &Path) -> io::Result<()> {
+        let manifest_path = root_path.join(MANIFEST_FILENAME);
+        let temp_manifest_path = manifest_path.with_extension("json.temp");
+        let manifest_bytes = serde_json::to_vec(self)?;
+        std::fs::write(&temp_manifest_path, manifest_bytes)?;
+        std::fs::rename(&temp_manifest_path, &manifest_path)
+    }
+}
+
+/// Computes the hex-encoded MD5 digest of a file.
+pub(crate) fn compute_checksum(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut context = md5::Context::new();
+    let mut buffer = vec![0u8; 1 << 20];
+    loop {
+        let num_bytes = file.read(&mut buffer)?;
+        if num_bytes == 0 {
+            break;
+        }
+        context.consume(&buffer[..num_bytes]);
+    }
+    Ok(format!("{:x}", context.compute()))
+}
+
+/// Periodically writes the manifest of the split cache, if it has changed.
+pub(crate) fn spawn_manifest_flush_task(
+    root_path: PathBuf,
+    shared_split_table: Arc<Mutex<SplitTable>>,
+) {
+    tokio::task::spawn(async move {
+        let mut last_saved_manifest_opt: Option<SplitCacheManifest> = None;
+        let mut interval = tokio::time::interval(MANIFEST_FLUSH_INTERVAL);
+        loop {
+            interval.tick().await;
+            let on_disk_splits = shared_split_table.lock().unwrap().on_disk_splits();
+            let manifest = SplitCacheManifest::from_on_disk_splits(&on_disk_splits);
+            if last_saved_manifest_opt.as_ref() == Some(&manifest) {
+                continue;
+            }
+            let root_path_clone = root_path.clone();
+            let Ok((manifest, save_res)) = tokio::task::spawn_blocking(move || {
+                let save_res = manifest.save(&root_path_clone);
+                (manifest, save_res)
+            })
+            .await
+            else {
+                continue;
+            };
+            match save_res {
+                Ok(()) => last_saved_manifest_opt = Some(manifest),
+                Err(io_err) => {
+                    warn!(error=?io_err, "failed to write split cache manifest");
+                }
+            }
+        }
+    });
+}
+
+/// Checks in the background that the split files restored on startup match their checksum.
+///
+/// Restored splits are served right away: verifying the checksums requires reading all the
+/// cached files, which would otherwise delay the startup of the searcher. Corrupted splits are
+/// evicted, and the checksum of the splits that were missing from the manifest is recorded.
+pub(crate) fn spawn_revalidation_task(
+    root_path: PathBuf,
+    shared_split_table: Arc<Mutex<SplitTable>>,
+    splits_to_validate: Vec<(Ulid, Option<String>)>,
+) {
+    if splits_to_validate.is_empty() {
+        return;
+    }
+    tokio::task::spawn_blocking(move || {
+        let num_splits = splits_to_validate.len();
+        let mut num_corrupted_splits = 0;
+        for (split_ulid, expected_checksum_opt) in splits_to_validate {
+            let split_file_path = root_path.join(split_file(split_ulid));
+            let checksum_res = compute_checksum(&split_file_path);
+            let mut split_table_lock = shared_split_table.lock().unwrap();
+            let is_valid = match (&checksum_res, &expected_checksum_opt) {
+                (Ok(checksum), Some(expected_checksum)) => checksum == expected_checksum,
+                (Ok(_), None) => true,
+                (Err(io_err), _) => {
+                    if io_err.kind() == io::ErrorKind::NotFound {
+                        // The split has been evicted in the meantime.
+                        continue;
+                    }
+                    false
+                }
+            };
+            if !is_valid {
+                num_corrupted_splits += 1;
+                if split_table_lock.evict_on_disk_split(split_ulid) {
+                    drop(split_table_lock);
+                    warn!(path=%split_file_path.display(), "evicting corrupted split from split cache");
+                    if let Err(io_err) = std::fs::remove_file(&split_file_path) {
+                        error!(path=%split_file_path.display(), error=?io_err, "failed to remove corrupted split file from cache directory");
+                    }
+                }
+                continue;
+            }
+            if let Ok(checksum) = checksum_res {
+                split_table_lock.set_checksum(split_ulid, checksum);
+            }
+        }
+        info!(
+            num_splits,
+            num_corrupted_splits, "revalidated splits restored in split cache"
+        );
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_cache_manifest_save_and_load() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        assert_eq!(
+            SplitCacheManifest::load(temp_dir.path()),
+            SplitCacheManifest::default()
+        );
+        let mut on_disk_splits = BTreeMap::new();
+        on_disk_splits.insert(
+            Ulid::new(),
+            OnDiskSplit {
+                num_bytes: 100,
+                last_accessed: Some(UNIX_EPOCH + Duration::from_secs(1_700_000_000)),
+                checksum: Some("abc".to_string()),
+                storage_uri: Some(Uri::for_test("s3://bucket/test-index")),
+            },
+        );
+        on_disk_splits.insert(
+            Ulid::new(),
+            OnDiskSplit {
+                num_bytes: 200,
+                last_accessed: None,
+                checksum: None,
+                storage_uri: None,
+            },
+        );
+        let manifest = SplitCacheManifest::from_on_disk_splits(&on_disk_splits);
+        manifest.save(temp_dir.path()).unwrap();
+        let loaded_manifest = SplitCacheManifest::load(temp_dir.path());
+        assert_eq!(loaded_manifest, manifest);
+        assert_eq!(loaded_manifest.into_on_disk_splits(), on_disk_splits);
+    }
+
+    #[test]
+    fn test_split_cache_manifest_load_corrupted() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::fs::write(temp_dir.path().join(MANIFEST_FILENAME), b"{not json").unwrap();
+        assert_eq!(
+            SplitCacheManifest::load(temp_dir.path()),
+            SplitCacheManifest::default()
+        );
+    }
+
+    #[test]
+    fn test_compute_checksum() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("test.split");
+        std::fs::write(&path, b"hello").unwrap();
+        assert_eq!(
+            compute_checksum(&path).unwrap(),
+            "5d41402abc4b2a76b9719d911017c592"
+        );
+    }
+}
@@ -18,6 +18,8 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 mod download_task;
+mod manifest;
+mod partial_range_cache;
 mod split_table;
 
 use std::collections::BTreeMap;
@@ -30,6 +32,7 @@ use std::str::FromStr;
 use std::sync::{Arc, Mutex};
 
 use async_trait::async_trait;
+use bytesize::ByteSize;
 use quickwit_common::uri::Uri;
 use quickwit_config::SplitCacheLimits;
 use quickwit_proto::search::ReportSplit;
@@ -38,6 +41,13 @@ use tracing::{error, info, warn};
 use ulid::Ulid;
 
 use crate::split_cache::download_task::{delete_evicted_splits, spawn_download_task};
+use crate::split_cache::manifest::{
+    spawn_manifest_flush_task, spawn_revalidation_task, OnDiskSplit, SplitCacheManifest,
+    MANIFEST_FILENAME,
+};
+use crate::split_cache::partial_range_cache::{
+    delete_files, write_range_file, PartialRangeCache, RangeKey, PARTIAL_RANGES_DIRECTORY,
+};
 use crate::split_cache::split_table::{SplitGuard, SplitTable};
 use crate::{wrap_storage_with_cache, Storage, StorageCache};
 
@@ -51,6 +61,9 @@ pub struct SplitCache {
     // In memory structure, listing the splits we know about regardless
     // of whether they are in cache, being downloaded, or just available for download.
     split_table: Arc<Mutex<SplitTable>>,
+    // Byte ranges of splits that are not cached in full. `None` if partial range caching is
+    // disabled.
+    partial_range_cache: Option<Arc<Mutex<PartialRangeCache>>>,
 }
 
 impl SplitCache {
@@ -67,7 +80,7 @@ impl SplitCache {
             let dir_entry = dir_entry_res?;
             let path = dir_entry.path();
             let meta = std::fs::metadata(&path)?;
-            if meta.is_dir() {
+            if meta.is_dir() || path.file_name() == Some(OsStr::new(MANIFEST_FILENAME)) {
                 continue;
             }
             let ext = path.extension().and_then(OsStr::to_str).unwrap_or("");
@@ -94,7 +107,30 @@ impl SplitCache {
                 }
             }
         }
-        let mut split_table = SplitTable::with_limits_and_existing_splits(limits, existing_splits);
+        // The manifest restores the last access date and the checksum of the splits. Splits
+        // missing from it are kept, splits whose size does not match are dropped.
+        let mut manifest_splits = SplitCacheManifest::load(&root_path).into_on_disk_splits();
+        let mut on_disk_splits: BTreeMap<Ulid, OnDiskSplit> = BTreeMap::new();
+        let mut splits_to_validate: Vec<(Ulid, Option<String>)> = Vec::new();
+        for (split_ulid, num_bytes) in existing_splits {
+            let on_disk_split = match manifest_splits.remove(&split_ulid) {
+                Some(manifest_split) if manifest_split.num_bytes == num_bytes => manifest_split,
+                Some(_) => {
+                    warn!(split_id=%split_ulid, "split file size does not match the split cache manifest, evicting");
+                    delete_evicted_splits(&root_path, &[split_ulid]);
+                    continue;
+                }
+                None => OnDiskSplit {
+                    num_bytes,
+                    last_accessed: None,
+                    checksum: None,
+                    storage_uri: None,
+                },
+            };
+            splits_to_validate.push((split_ulid, on_disk_split.checksum.clone()));
+            on_disk_splits.insert(split_ulid, on_disk_split);
+        }
+        let mut split_table = SplitTable::with_limits_and_existing_splits(limits, on_disk_splits);
 
         // In case of a setting change, it could be useful to evict some splits on startup.
         let splits_to_remove_opt = split_table.make_room_for_split_if_necessary(u64::MAX);
@@ -108,6 +144,23 @@ impl SplitCache {
         }
         let split_table_arc = Arc::new(Mutex::new(split_table));
 
+        spawn_revalidation_task(
+            root_path.clone(),
+            split_table_arc.clone(),
+            splits_to_validate,
+        );
+        spawn_manifest_flush_task(root_path.clone(), split_table_arc.clone());
+
+        let partial_range_cache_opt = if limits.max_num_bytes_partial_ranges.as_u64() > 0 {
+            let partial_range_cache = PartialRangeCache::with_root_path(
+                root_path.join(PARTIAL_RANGES_DIRECTORY),
+                &limits,
+            )?;
+            Some(Arc::new(Mutex::new(partial_range_cache)))
+        } else {
+            None
+        };
+
         spawn_download_task(
             root_path.clone(),
             split_table_arc.clone(),
@@ -118,11 +171,18 @@ impl SplitCache {
         Ok(SplitCache {
             root_path,
             split_table: split_table_arc,
+            partial_range_cache: partial_range_cache_opt,
         })
     }
 
     /// Wraps a storage with our split cache.
+    ///
+    /// Encrypted storages are returned as is: the split cache downloads the raw split files, so
+    /// it would serve ciphertext where the bundle storage expects plaintext.
     pub fn wrap_storage(self_arc: Arc<Self>, storage: Arc<dyn Storage>) -> Arc<dyn Storage> {
+        if storage.is_encrypted() {
+            return storage;
+        }
         let cache = Arc::new(SplitCacheBackingStorage {
             split_cache: self_arc,
             storage_root_uri: storage.uri().clone(),
@@ -146,6 +206,26 @@ impl SplitCache {
         }
     }
 
+    /// Updates the maximum number of bytes of the cached splits, evicting the coldest splits if
+    /// the cache exceeds the new limit.
+    pub fn set_max_num_bytes(&self, max_num_bytes: ByteSize) {
+        let splits_to_remove_opt = {
+            let mut split_table = self.split_table.lock().unwrap();
+            split_table.set_max_num_bytes(max_num_bytes);
+            split_table.make_room_for_split_if_necessary(u64::MAX)
+        };
+        if let Some(splits_to_remove) = splits_to_remove_opt {
+            if !splits_to_remove.is_empty() {
+                info!(
+                    num_splits = splits_to_remove.len(),
+                    max_num_bytes = %max_num_bytes,
+                    "evicting splits from the searcher cache after a limit change"
+                );
+            }
+            delete_evicted_splits(&self.root_path, &splits_to_remove[..]);
+        }
+    }
+
     fn cached_split_filepath(&self, split_id: Ulid) -> PathBuf {
         let split_filename = quickwit_common::split_file(split_id);
         self.root_path.join(split_filename)
@@ -164,6 +244,56 @@ impl SplitCache {
             cached_split_file_path: self.cached_split_filepath(split_id),
         })
     }
+
+    async fn get_partial_range(
+        &self,
+        split_id: Ulid,
+        byte_range: Range<usize>,
+    ) -> Option<OwnedBytes> {
+        let partial_range_file = self
+            .partial_range_cache
+            .as_ref()?
+            .lock()
+            .unwrap()
+            .find(split_id, byte_range)?;
+        tokio::task::spawn_blocking(move || {
+            read_file_range(&partial_range_file.file_path, partial_range_file.byte_range)
+        })
+        .await
+        .ok()?
+    }
+
+    async fn put_partial_range(&self, split_id: Ulid, byte_range: Range<usize>, bytes: OwnedBytes) {
+        let Some(partial_range_cache) = self.partial_range_cache.clone() else {
+            return;
+        };
+        let range_key = RangeKey {
+            split_ulid: split_id,
+            start: byte_range.start,
+            end: byte_range.end,
+        };
+        let file_path = {
+            let partial_range_cache_lock = partial_range_cache.lock().unwrap();
+            if bytes.len() != byte_range.len()
+                || !partial_range_cache_lock.accepts(bytes.len())
+                || partial_range_cache_lock.contains(&range_key)
+            {
+                return;
+            }
+            partial_range_cache_lock.file_path(&range_key)
+        };
+        // The file is written before the range gets registered, so that concurrent readers never
+        // find a range that is not on disk yet.
+        let _ = tokio::task::spawn_blocking(move || {
+            if let Err(io_err) = write_range_file(&file_path, bytes.as_slice()) {
+                warn!(path=%file_path.display(), error=?io_err, "failed to write range to split cache");
+                return;
+            }
+            let evicted_files = partial_range_cache.lock().unwrap().register(range_key);
+            delete_files(&evicted_files);
+        })
+        .await;
+    }
 }
 
 pub struct SplitFilepath {
@@ -188,27 +318,39 @@ struct SplitCacheBackingStorage {
     storage_root_uri: Uri,
 }
 
+/// Reads `byte_range` from the file at `path`. Returns `None` if the file is missing or too short.
+fn read_file_range(path: &Path, byte_range: Range<usize>) -> Option<OwnedBytes> {
+    let mut file = File::open(path).ok()?;
+    file.seek(SeekFrom::Start(byte_range.start as u64)).ok()?;
+    let mut buf = Vec::with_capacity(byte_range.len());
+    file.take(byte_range.len() as u64)
+        .read_to_end(&mut buf)
+        .ok()?;
+    if buf.len() != byte_range.len() {
+        return None;
+    }
+    Some(OwnedBytes::new(buf))
+}
+
 impl SplitCacheBackingStorage {
     async fn get_impl(&self, path: &Path, byte_range: Range<usize>) -> Option<OwnedBytes> {
         let split_id = split_id_from_path(path)?;
-        let split_guard = self
+        let Some(split_guard) = self
             .split_cache
-            .get_split_guard(split_id, &self.storage_root_uri)?;
+            .get_split_guard(split_id, &self.storage_root_uri)
+        else {
+            return self
+                .split_cache
+                .get_partial_range(split_id, byte_range)
+                .await;
+        };
         // TODO touch file in cache.
         // We don't use async file io here because it spawn blocks anyway, and it feels dumb to
         // spawn block 3 times in a row.
-        tokio::task::spawn_blocking(move || {
-            let mut file = File::open(&split_guard).ok()?;
-            file.seek(SeekFrom::Start(byte_range.start as u64)).ok()?;
-            let mut buf = Vec::with_capacity(byte_range.len());
-            file.take(byte_range.len() as u64)
-                .read_to_end(&mut buf)
-                .ok()?;
-            Some(OwnedBytes::new(buf))
-        })
-        .await
-        // TODO Remove file from cache if io error?
-        .ok()?
+        tokio::task::spawn_blocking(move || read_file_range(split_guard.as_ref(), byte_range))
+            .await
+            // TODO Remove file from cache if io error?
+            .ok()?
     }
 
     async fn get_all_impl(&self, path: &Path) -> Option<OwnedBytes> {
@@ -230,11 +372,21 @@ impl SplitCacheBackingStorage {
 
     fn record_hit_metrics(&self, result_opt: Option<&OwnedBytes>) {
         let split_metrics = &crate::STORAGE_METRICS.searcher_split_cache;
+        let index_metrics = &crate::STORAGE_METRICS.searcher_split_cache_per_index;
+        let index_uri = self.storage_root_uri.as_str();
         if let Some(result) = result_opt {
             split_metrics.hits_num_items.inc();
             split_metrics.hits_num_bytes.inc_by(result.len() as u64);
+            index_metrics
+                .hits_num_items
+                .with_label_values([index_uri])
+                .inc();
         } else {
             split_metrics.misses_num_items.inc();
+            index_metrics
+                .misses_num_items
+                .with_label_values([index_uri])
+                .inc();
         }
     }
 }
@@ -253,6 +405,14 @@ impl StorageCache for SplitCacheBackingStorage {
         result
     }
 
-    async fn put(&self, _path: PathBuf, _byte_range: Range<usize>, _bytes: OwnedBytes) {}
+    async fn put(&self, path: PathBuf, byte_range: Range<usize>, bytes: OwnedBytes) {
+        let Some(split_id) = split_id_from_path(&path) else {
+            return;
+        };
+        self.split_cache
+            .put_partial_range(split_id, byte_range, bytes)
+            .await;
+    }
+
     async fn put_all(&self, _path: PathBuf, _bytes: OwnedBytes) {}
 }
@@ -0,0 +1,354 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use quickwit_config::SplitCacheLimits;
+use tracing::{error, warn};
+use ulid::Ulid;
+
+/// Name of the subdirectory of the split cache directory holding the partial ranges.
+pub(crate) const PARTIAL_RANGES_DIRECTORY: &str = "ranges";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub(crate) struct RangeKey {
+    pub split_ulid: Ulid,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl RangeKey {
+    fn num_bytes(&self) -> u64 {
+        (self.end - self.start) as u64
+    }
+
+    fn filename(&self) -> String {
+        format!("{}_{}_{}.range", self.split_ulid, self.start, self.end)
+    }
+
+    fn from_path(path: &Path) -> Option<RangeKey> {
+        let filename = path.file_name()?.to_str()?;
+        let key_str = filename.strip_suffix(".range")?;
+        let mut parts = key_str.split('_');
+        let split_ulid = Ulid::from_str(parts.next()?).ok()?;
+        let start = parts.next()?.parse().ok()?;
+        let end = parts.next()?.parse().ok()?;
+        if parts.next().is_some() || start >= end {
+            return None;
+        }
+        Some(RangeKey {
+            split_ulid,
+            start,
+            end,
+        })
+    }
+}
+
+/// Location of a cached range on disk.
+pub(crate) struct PartialRangeFile {
+    pub file_path: PathBuf,
+    /// Range to read, relative to the start of the file.
+    pub byte_range: Range<usize>,
+}
+
+/// In memory index of the byte ranges of splits cached on disk.
+///
+/// Searchers only read a small fraction of a split: its footer, the term dictionary blocks
+/// and the fast field headers of the fields they query. Caching these hot ranges instead of
+/// the whole split offers most of the latency benefit for a fraction of the disk space.
+///
+/// Ranges are evicted in LRU order once `max_num_bytes` is exceeded.
+pub(crate) struct PartialRangeCache {
+    root_path: PathBuf,
+    max_num_bytes: u64,
+    max_range_num_bytes: u64,
+    num_bytes: u64,
+    access_counter: u64,
+    // Maps each cached range to its last access.
+    ranges: BTreeMap<RangeKey, u64>,
+    // Maps each last access to its range. This is used to find eviction candidates.
+    lru: BTreeMap<u64, RangeKey>,
+}
+
+impl PartialRangeCache {
+    /// Creates the partial range cache, loading the ranges already present in `root_path`.
+    pub fn with_root_path(
+        root_path: PathBuf,
+        limits: &SplitCacheLimits,
+    ) -> io::Result<PartialRangeCache> {
+        std::fs::create_dir_all(&root_path)?;
+        let mut partial_range_cache = PartialRangeCache {
+            root_path,
+            max_num_bytes: limits.max_num_bytes_partial_ranges.as_u64(),
+            max_range_num_bytes: limits.max_partial_range_num_bytes.as_u64(),
+            num_bytes: 0,
+            access_counter: 0,
+            ranges: BTreeMap::new(),
+            lru: BTreeMap::new(),
+        };
+        for dir_entry_res in std::fs::read_dir(&partial_range_cache.root_path)? {
+            let path = dir_entry_res?.path();
+            let ext = path.extension().and_then(OsStr::to_str).unwrap_or("");
+            let range_key_opt = if ext == "range" {
+                RangeKey::from_path(&path)
+            } else {
+                None
+            };
+            let Some(range_key) = range_key_opt else {
+                // Temporary files left behind by an interrupted write end up here too.
+                warn!(path=%path.display(), "removing unexpected file from partial range cache directory");
+                remove_file(&path);
+                continue;
+            };
+            if std::fs::metadata(&path)?.len() != range_key.num_bytes() {
+                remove_file(&path);
+                continue;
+            }
+            let evicted_files = partial_range_cache.register(range_key);
+            delete_files(&evicted_files);
+        }
+        Ok(partial_range_cache)
+    }
+
+    /// Returns true if a range of `num_bytes` is small enough to be cached.
+    pub fn accepts(&self, num_bytes: usize) -> bool {
+        num_bytes > 0
+            && num_bytes as u64 <= self.max_range_num_bytes
+            && num_bytes as u64 <= self.max_num_bytes
+    }
+
+    pub fn contains(&self, range_key: &RangeKey) -> bool {
+        self.ranges.contains_key(range_key)
+    }
+
+    pub fn num_bytes(&self) -> u64 {
+        self.num_bytes
+    }
+
+    pub fn file_path(&self, range_key: &RangeKey) -> PathBuf {
+        self.root_path.join(range_key.filename())
+    }
+
+    /// Looks for a cached range covering `byte_range` and marks it as accessed.
+    pub fn find(&mut self, split_ulid: Ulid, byte_range: Range<usize>) -> Option<PartialRangeFile> {
+        let lower_bound = RangeKey {
+            split_ulid,
+            start: 0,
+            end: 0,
+        };
+        let upper_bound = RangeKey {
+            split_ulid,
+            start: byte_range.start,
+            end: usize::MAX,
+        };
+        let range_key = self
+            .ranges
+            .range(lower_bound..=upper_bound)
+            .rev()
+            .map(|(range_key, _)| *range_key)
+            .find(|range_key| range_key.end >= byte_range.end)?;
+        self.touch(range_key);
+        Some(PartialRangeFile {
+            file_path: self.file_path(&range_key),
+            byte_range: byte_range.start - range_key.start..byte_range.end - range_key.start,
+        })
+    }
+
+    /// Registers a range whose file has been written to disk.
+    ///
+    /// Returns the files of the ranges evicted to make room for it. It is up to the caller to
+    /// delete them.
+    #[must_use]
+    pub fn register(&mut self, range_key: RangeKey) -> Vec<PathBuf> {
+        if self.contains(&range_key) {
+            self.touch(range_key);
+            return Vec::new();
+        }
+        self.touch(range_key);
+        self.num_bytes += range_key.num_bytes();
+        let mut evicted_files = Vec::new();
+        while self.num_bytes > self.max_num_bytes {
+            let Some((_, evicted_range_key)) = self.lru.pop_first() else {
+                break;
+            };
+            self.ranges.remove(&evicted_range_key);
+            self.num_bytes -= evicted_range_key.num_bytes();
+            evicted_files.push(self.file_path(&evicted_range_key));
+        }
+        evicted_files
+    }
+
+    fn touch(&mut self, range_key: RangeKey) {
+        self.access_counter += 1;
+        if let Some(previous_access) = self.ranges.insert(range_key, self.access_counter) {
+            self.lru.remove(&previous_access);
+        }
+        self.lru.insert(self.access_counter, range_key);
+    }
+}
+
+fn remove_file(path: &Path) {
+    if let Err(io_err) = std::fs::remove_file(path) {
+        if io_err.kind() != io::ErrorKind::NotFound {
+            error!(path=%path.display(), error=?io_err, "failed to remove file from partial range cache directory");
+        }
+    }
+}
+
+/// Removes the evicted range files from the file system, logging and swallowing errors.
+pub(crate) fn delete_files(file_paths: &[PathBuf]) {
+    for file_path in file_paths {
+        remove_file(file_path);
+    }
+}
+
+/// Writes a range to the cache directory. The bytes are first written to a temporary file so
+/// that readers never observe a partially written range.
+pub(crate) fn write_range_file(file_path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let temp_file_path = file_path.with_extension("temp");
+    std::fs::write(&temp_file_path, bytes)?;
+    if let Err(io_err) = std::fs::rename(&temp_file_path, file_path) {
+        remove_file(&temp_file_path);
+        return Err(io_err);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use bytesize::ByteSize;
+
+    use super::*;
+
+    fn test_limits(max_num_bytes_partial_ranges: u64) -> SplitCacheLimits {
+        SplitCacheLimits {
+            max_num_bytes_partial_ranges: ByteSize::b(max_num_bytes_partial_ranges),
+            max_partial_range_num_bytes: ByteSize::b(100),
+            ..Default::default()
+        }
+    }
+
+    fn range_key(split_ulid: Ulid, byte_range: Range<usize>) -> RangeKey {
+        RangeKey {
+            split_ulid,
+            start: byte_range.start,
+            end: byte_range.end,
+        }
+    }
+
+    #[test]
+    fn test_range_key_filename_round_trip() {
+        let key = range_key(Ulid::new(), 10..20);
+        let path = PathBuf::from("/tmp").join(key.filename());
+        assert_eq!(RangeKey::from_path(&path), Some(key));
+        assert_eq!(RangeKey::from_path(Path::new("abc_1_2.range")), None);
+        let ulid = Ulid::new();
+        assert_eq!(
+            RangeKey::from_path(Path::new(&format!("{ulid}_2_1.range"))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_partial_range_cache_find() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut cache =
+            PartialRangeCache::with_root_path(temp_dir.path().to_path_buf(), &test_limits(1_000))
+                .unwrap();
+        let split_ulid = Ulid::new();
+        let other_split_ulid = Ulid::new();
+        assert!(cache.register(range_key(split_ulid, 10..50)).is_empty());
+        assert!(cache.register(range_key(split_ulid, 60..70)).is_empty());
+        assert_eq!(cache.num_bytes(), 50);
+
+        let range_file = cache.find(split_ulid, 20..30).unwrap();
+        assert_eq!(range_file.byte_range, 10..20);
+        assert!(range_file
+            .file_path
+            .ends_with(range_key(split_ulid, 10..50).filename()));
+
+        let range_file = cache.find(split_ulid, 60..70).unwrap();
+        assert_eq!(range_file.byte_range, 0..10);
+
+        assert!(cache.find(split_ulid, 40..60).is_none());
+        assert!(cache.find(split_ulid, 0..20).is_none());
+        assert!(cache.find(other_split_ulid, 20..30).is_none());
+    }
+
+    #[test]
+    fn test_partial_range_cache_lru_eviction() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut cache =
+            PartialRangeCache::with_root_path(temp_dir.path().to_path_buf(), &test_limits(100))
+                .unwrap();
+        let split_ulid = Ulid::new();
+        assert!(cache.register(range_key(split_ulid, 0..40)).is_empty());
+        assert!(cache.register(range_key(split_ulid, 100..140)).is_empty());
+        // Accessing the first range makes the second one the eviction candidate.
+        assert!(cache.find(split_ulid, 0..10).is_some());
+        let evicted_files = cache.register(range_key(split_ulid, 200..240));
+        assert_eq!(
+            evicted_files,
+            vec![cache.file_path(&range_key(split_ulid, 100..140))]
+        );
+        assert_eq!(cache.num_bytes(), 80);
+        assert!(cache.find(split_ulid, 100..110).is_none());
+        assert!(cache.find(split_ulid, 0..10).is_some());
+    }
+
+    #[test]
+    fn test_partial_range_cache_accepts() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let cache =
+            PartialRangeCache::with_root_path(temp_dir.path().to_path_buf(), &test_limits(1_000))
+                .unwrap();
+        assert!(!cache.accepts(0));
+        assert!(cache.accepts(100));
+        assert!(!cache.accepts(101));
+    }
+
+    #[test]
+    fn test_partial_range_cache_loads_existing_ranges() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let split_ulid = Ulid::new();
+        let key = range_key(split_ulid, 5..15);
+        write_range_file(&temp_dir.path().join(key.filename()), &[1u8; 10]).unwrap();
+        // Truncated range files are removed.
+        let truncated_key = range_key(split_ulid, 20..40);
+        let truncated_path = temp_dir.path().join(truncated_key.filename());
+        std::fs::write(&truncated_path, [1u8; 5]).unwrap();
+        let leftover_temp_path = temp_dir.path().join("leftover.temp");
+        std::fs::write(&leftover_temp_path, [1u8; 5]).unwrap();
+
+        let mut cache =
+            PartialRangeCache::with_root_path(temp_dir.path().to_path_buf(), &test_limits(1_000))
+                .unwrap();
+        assert_eq!(cache.num_bytes(), 10);
+        assert!(cache.contains(&key));
+        assert!(!cache.contains(&truncated_key));
+        assert!(cache.find(split_ulid, 6..8).is_some());
+        assert!(!truncated_path.exists());
+        assert!(!leftover_temp_path.exists());
+    }
+}
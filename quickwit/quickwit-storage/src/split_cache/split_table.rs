@@ -20,23 +20,55 @@
 use std::cmp::Ordering;
 use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::sync::{Arc, Weak};
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
 
+use bytesize::ByteSize;
 use quickwit_common::uri::Uri;
 use quickwit_config::SplitCacheLimits;
 use ulid::Ulid;
 
+use crate::split_cache::manifest::OnDiskSplit;
+
 type LastAccessDate = u64;
 
+/// Hotness of a split, expressed as an access date: a split accessed once has a hotness equal to
+/// its last access date, and each additional access makes it hotter. Past accesses count for less
+/// and less as time goes by, so that a split that used to be frequently queried eventually cools
+/// down.
+type Hotness = u64;
+
 /// Maximum number of splits to track.
 const MAX_NUM_CANDIDATES: usize = 1_000;
 
 /// Splits that are freshly reported get a last access time of `now - NEWLY_REPORT_SPLIT_LAST_TIME`.
 const NEWLY_REPORTED_SPLIT_LAST_TIME: Duration = Duration::from_secs(60 * 10); // 10mn
 
+/// Time after which an access counts for half as much in the hotness of a split.
+const HOTNESS_HALF_LIFE: Duration = Duration::from_secs(60 * 60); // 1h
+
+/// A single query reads many byte ranges of a split, so accesses to a split closer than this are
+/// counted as one.
+const MIN_ACCESS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Returns the hotness of a split after an access at `timestamp`.
+///
+/// The hotness `h` is defined by `2^(h / half_life) = sum(2^(t_i / half_life))` over the access
+/// dates `t_i`, so that adding an access never requires decaying the hotness of the other splits.
+fn add_access(hotness: Hotness, timestamp: LastAccessDate) -> Hotness {
+    let half_life_micros = HOTNESS_HALF_LIFE.as_micros() as f64;
+    let (hottest, coldest) = if hotness >= timestamp {
+        (hotness, timestamp)
+    } else {
+        (timestamp, hotness)
+    };
+    let gap = (hottest - coldest) as f64 / half_life_micros;
+    let boost_micros = half_life_micros * (1.0 + (-gap).exp2()).log2();
+    hottest.saturating_add(boost_micros as u64)
+}
+
 #[derive(Clone, Copy)]
 pub(crate) struct SplitKey {
-    pub last_accessed: LastAccessDate,
+    pub hotness: Hotness,
     pub split_ulid: Ulid,
 }
 
@@ -48,13 +80,13 @@ impl PartialOrd for SplitKey {
 
 impl Ord for SplitKey {
     fn cmp(&self, other: &Self) -> Ordering {
-        (self.last_accessed, &self.split_ulid).cmp(&(other.last_accessed, &other.split_ulid))
+        (self.hotness, &self.split_ulid).cmp(&(other.hotness, &other.split_ulid))
     }
 }
 
 impl PartialEq for SplitKey {
     fn eq(&self, other: &Self) -> bool {
-        (self.last_accessed, &self.split_ulid) == (other.last_accessed, &other.split_ulid)
+        (self.hotness, &self.split_ulid) == (other.hotness, &other.split_ulid)
     }
 }
 
@@ -63,8 +95,16 @@ impl Eq for SplitKey {}
 #[derive(Clone, Debug)]
 enum Status {
     Candidate(CandidateSplit),
-    Downloading { alive_token: Weak<()> },
-    OnDisk { num_bytes: u64 },
+    Downloading {
+        alive_token: Weak<()>,
+        storage_uri: Uri,
+    },
+    OnDisk {
+        num_bytes: u64,
+        checksum: Option<String>,
+        // Unknown for splits found on disk but missing from the manifest.
+        storage_uri: Option<Uri>,
+    },
 }
 
 impl PartialEq for Status {
@@ -75,11 +115,21 @@ impl PartialEq for Status {
             }
             (Status::Downloading { .. }, Status::Downloading { .. }) => true,
             (
-                Status::OnDisk { num_bytes },
+                Status::OnDisk {
+                    num_bytes,
+                    checksum,
+                    storage_uri,
+                },
                 Status::OnDisk {
                     num_bytes: other_num_bytes,
+                    checksum: other_checksum,
+                    storage_uri: other_storage_uri,
                 },
-            ) => num_bytes == other_num_bytes,
+            ) => {
+                num_bytes == other_num_bytes
+                    && checksum == other_checksum
+                    && storage_uri == other_storage_uri
+            }
             _ => false,
         }
     }
@@ -87,6 +137,7 @@ impl PartialEq for Status {
 
 pub struct SplitInfo {
     pub(crate) split_key: SplitKey,
+    last_accessed: LastAccessDate,
     status: Status,
 }
 
@@ -107,6 +158,8 @@ pub struct SplitTable {
     candidate_splits: BTreeSet<SplitKey>,
     split_to_status: HashMap<Ulid, SplitInfo>,
     origin_time: Instant,
+    // Wall clock time matching `origin_time`, used to persist last access dates.
+    origin_system_time: SystemTime,
     limits: SplitCacheLimits,
     on_disk_bytes: u64,
 }
@@ -114,40 +167,115 @@ pub struct SplitTable {
 impl SplitTable {
     pub(crate) fn with_limits_and_existing_splits(
         limits: SplitCacheLimits,
-        existing_filepaths: BTreeMap<Ulid, u64>,
+        existing_splits: BTreeMap<Ulid, OnDiskSplit>,
     ) -> SplitTable {
-        let origin_time = Instant::now() - NEWLY_REPORTED_SPLIT_LAST_TIME;
+        // The origin time has to predate the last access of the existing splits, so that their
+        // relative order is preserved.
+        let now = SystemTime::now();
+        let oldest_access_age = existing_splits
+            .values()
+            .filter_map(|on_disk_split| on_disk_split.last_accessed)
+            .filter_map(|last_accessed| now.duration_since(last_accessed).ok())
+            .max()
+            .unwrap_or_default();
+        let origin_time = Instant::now()
+            .checked_sub(oldest_access_age.max(NEWLY_REPORTED_SPLIT_LAST_TIME))
+            .unwrap_or_else(|| Instant::now() - NEWLY_REPORTED_SPLIT_LAST_TIME);
+        let origin_system_time = now - origin_time.elapsed();
         let mut split_table = SplitTable {
             on_disk_splits: BTreeSet::default(),
             candidate_splits: BTreeSet::default(),
             downloading_splits: BTreeSet::default(),
             split_to_status: HashMap::default(),
             origin_time,
+            origin_system_time,
             limits,
             on_disk_bytes: 0u64,
         };
-        split_table.acknowledge_on_disk_splits(existing_filepaths);
+        split_table.acknowledge_on_disk_splits(existing_splits);
         split_table
     }
 
-    fn acknowledge_on_disk_splits(&mut self, existing_filepaths: BTreeMap<Ulid, u64>) {
-        for (split_ulid, num_bytes) in existing_filepaths {
+    fn acknowledge_on_disk_splits(&mut self, existing_splits: BTreeMap<Ulid, OnDiskSplit>) {
+        for (split_ulid, on_disk_split) in existing_splits {
+            // Splits without a known last access date are the first to be evicted.
+            let last_accessed = on_disk_split
+                .last_accessed
+                .and_then(|last_accessed| {
+                    last_accessed.duration_since(self.origin_system_time).ok()
+                })
+                .map(|since_origin| since_origin.as_micros() as u64)
+                .unwrap_or(0);
+            // The hotness is not persisted: the splits restart from their last access date.
             let split_info = SplitInfo {
                 split_key: SplitKey {
-                    last_accessed: 0,
+                    hotness: last_accessed,
                     split_ulid,
                 },
-                status: Status::OnDisk { num_bytes },
+                last_accessed,
+                status: Status::OnDisk {
+                    num_bytes: on_disk_split.num_bytes,
+                    checksum: on_disk_split.checksum,
+                    storage_uri: on_disk_split.storage_uri,
+                },
             };
             self.insert(split_info);
         }
     }
+
+    /// Returns the splits currently on disk, with their last access date and checksum.
+    pub(crate) fn on_disk_splits(&self) -> BTreeMap<Ulid, OnDiskSplit> {
+        self.on_disk_splits
+            .iter()
+            .filter_map(|split_key| {
+                let split_info = self.split_to_status.get(&split_key.split_ulid)?;
+                let Status::OnDisk {
+                    num_bytes,
+                    checksum,
+                    storage_uri,
+                } = &split_info.status
+                else {
+                    return None;
+                };
+                let on_disk_split = OnDiskSplit {
+                    num_bytes: *num_bytes,
+                    last_accessed: Some(
+                        self.origin_system_time + Duration::from_micros(split_info.last_accessed),
+                    ),
+                    checksum: checksum.clone(),
+                    storage_uri: storage_uri.clone(),
+                };
+                Some((split_key.split_ulid, on_disk_split))
+            })
+            .collect()
+    }
 }
 
 fn compute_timestamp(start: Instant) -> LastAccessDate {
     start.elapsed().as_micros() as u64
 }
 
+fn record_eviction(split_info: &SplitInfo) {
+    let Status::OnDisk {
+        num_bytes,
+        storage_uri,
+        ..
+    } = &split_info.status
+    else {
+        return;
+    };
+    let index_uri = storage_uri.as_ref().map(Uri::as_str).unwrap_or("unknown");
+    let index_metrics = &crate::metrics::STORAGE_METRICS.searcher_split_cache_per_index;
+    index_metrics
+        .evicted_num_items
+        .with_label_values([index_uri])
+        .inc();
+    index_metrics
+        .evicted_num_bytes
+        .with_label_values([index_uri])
+        .inc_by(*num_bytes);
+}
+
 // TODO improve SplitGuard with Atomic
 // Right only touch is helping.
 pub(super) struct SplitGuard;
@@ -170,7 +298,7 @@ impl SplitTable {
         let split_queue: &mut BTreeSet<SplitKey> = match split_info.status {
             Status::Candidate { .. } => &mut self.candidate_splits,
             Status::Downloading { .. } => &mut self.downloading_splits,
-            Status::OnDisk { num_bytes } => {
+            Status::OnDisk { num_bytes, .. } => {
                 self.on_disk_bytes -= num_bytes;
                 crate::metrics::STORAGE_METRICS
                     .searcher_split_cache
@@ -185,7 +313,7 @@ impl SplitTable {
         };
         let is_in_queue = split_queue.remove(&split_info.split_key);
         assert!(is_in_queue);
-        if let Status::Downloading { alive_token } = &split_info.status {
+        if let Status::Downloading { alive_token, .. } = &split_info.status {
             if alive_token.strong_count() == 0 {
                 return None;
             }
@@ -202,7 +330,7 @@ impl SplitTable {
         let mut splits_to_remove = Vec::new();
         for split in &self.downloading_splits {
             if let Some(split_info) = self.split_to_status.get(&split.split_ulid) {
-                if let Status::Downloading { alive_token } = &split_info.status {
+                if let Status::Downloading { alive_token, .. } = &split_info.status {
                     if alive_token.strong_count() == 0 {
                         splits_to_remove.push(split.split_ulid);
                     }
@@ -226,7 +354,7 @@ impl SplitTable {
                 was_not_in_queue
             }
             Status::Downloading { .. } => self.downloading_splits.insert(split_info.split_key),
-            Status::OnDisk { num_bytes } => {
+            Status::OnDisk { num_bytes, .. } => {
                 self.on_disk_bytes += num_bytes;
                 crate::metrics::STORAGE_METRICS
                     .searcher_split_cache
@@ -250,16 +378,32 @@ impl SplitTable {
 
     fn touch(&mut self, split_ulid: Ulid, storage_uri: &Uri) -> Status {
         let timestamp = compute_timestamp(self.origin_time);
+        self.touch_at(split_ulid, storage_uri, timestamp)
+    }
+
+    fn touch_at(
+        &mut self,
+        split_ulid: Ulid,
+        storage_uri: &Uri,
+        timestamp: LastAccessDate,
+    ) -> Status {
         self.mutate_split(split_ulid, |old_split_info| {
             if let Some(mut split_info) = old_split_info {
-                split_info.split_key.last_accessed = timestamp;
+                if timestamp.saturating_sub(split_info.last_accessed)
+                    >= MIN_ACCESS_INTERVAL.as_micros() as u64
+                {
+                    split_info.split_key.hotness =
+                        add_access(split_info.split_key.hotness, timestamp);
+                }
+                split_info.last_accessed = timestamp;
                 split_info
             } else {
                 SplitInfo {
                     split_key: SplitKey {
                         split_ulid,
-                        last_accessed: timestamp,
+                        hotness: timestamp,
                     },
+                    last_accessed: timestamp,
                     status: Status::Candidate(CandidateSplit {
                         storage_uri: storage_uri.clone(),
                         split_ulid,
@@ -293,11 +437,13 @@ impl SplitTable {
                 split_info.status = status;
                 split_info
             } else {
+                let timestamp = compute_timestamp(start_time);
                 SplitInfo {
                     split_key: SplitKey {
-                        last_accessed: compute_timestamp(start_time),
+                        hotness: timestamp,
                         split_ulid,
                     },
+                    last_accessed: timestamp,
                     status,
                 }
             }
@@ -310,12 +456,14 @@ impl SplitTable {
             if let Some(split_info) = split_info_opt {
                 return split_info;
             }
+            let last_accessed = compute_timestamp(origin_time)
+                .saturating_sub(NEWLY_REPORTED_SPLIT_LAST_TIME.as_micros() as u64);
             SplitInfo {
                 split_key: SplitKey {
-                    last_accessed: compute_timestamp(origin_time)
-                        .saturating_sub(NEWLY_REPORTED_SPLIT_LAST_TIME.as_micros() as u64),
+                    hotness: last_accessed,
                     split_ulid,
                 },
+                last_accessed,
                 status: Status::Candidate(CandidateSplit {
                     storage_uri,
                     split_ulid,
@@ -334,7 +482,52 @@ impl SplitTable {
     }
 
     pub(crate) fn register_as_downloaded(&mut self, split_ulid: Ulid, num_bytes: u64) {
-        self.change_split_status(split_ulid, Status::OnDisk { num_bytes });
+        let storage_uri = self
+            .split_to_status
+            .get(&split_ulid)
+            .and_then(|split_info| match &split_info.status {
+                Status::Candidate(candidate_split) => Some(candidate_split.storage_uri.clone()),
+                Status::Downloading { storage_uri, .. } => Some(storage_uri.clone()),
+                Status::OnDisk { storage_uri, .. } => storage_uri.clone(),
+            });
+        self.change_split_status(
+            split_ulid,
+            Status::OnDisk {
+                num_bytes,
+                checksum: None,
+                storage_uri,
+            },
+        );
+    }
+
+    /// Records the checksum of a split on disk. Returns false if the split is not on disk.
+    pub(crate) fn set_checksum(&mut self, split_ulid: Ulid, new_checksum: String) -> bool {
+        let Some(split_info) = self.split_to_status.get_mut(&split_ulid) else {
+            return false;
+        };
+        let Status::OnDisk { checksum, .. } = &mut split_info.status else {
+            return false;
+        };
+        *checksum = Some(new_checksum);
+        true
+    }
+
+    /// Removes a split on disk from the table, for instance because its file is corrupted.
+    /// Returns false if the split is not on disk. It is up to the caller to delete the file.
+    pub(crate) fn evict_on_disk_split(&mut self, split_ulid: Ulid) -> bool {
+        let is_on_disk = matches!(
+            self.split_to_status.get(&split_ulid),
+            Some(SplitInfo {
+                status: Status::OnDisk { .. },
+                ..
+            })
+        );
+        if is_on_disk {
+            if let Some(split_info) = self.remove(split_ulid) {
+                record_eviction(&split_info);
+            }
+        }
+        is_on_disk
     }
 
     /// Change the state of the given split from candidate to downloading state,
@@ -351,7 +544,11 @@ impl SplitTable {
         let alive_token = Arc::downgrade(&candidate_split.living_token);
         self.insert(SplitInfo {
             split_key: split_info.split_key,
-            status: Status::Downloading { alive_token },
+            last_accessed: split_info.last_accessed,
+            status: Status::Downloading {
+                alive_token,
+                storage_uri: candidate_split.storage_uri.clone(),
+            },
         });
         Some(candidate_split)
     }
@@ -360,6 +557,12 @@ impl SplitTable {
         self.candidate_splits.last().copied()
     }
 
+    /// Updates the maximum number of bytes of the splits on disk. The splits exceeding the new
+    /// limit are not evicted: see [`SplitTable::make_room_for_split_if_necessary`].
+    pub(crate) fn set_max_num_bytes(&mut self, max_num_bytes: ByteSize) {
+        self.limits.max_num_bytes = max_num_bytes;
+    }
+
     fn is_out_of_limits(&self) -> bool {
         if self.on_disk_splits.is_empty() {
             return false;
@@ -375,21 +578,18 @@ impl SplitTable {
         false
     }
 
-    /// Evicts splits to reach the target limits.
-    ///
-    /// Returns false if the first candidate for eviction is
-    /// fresher that the candidate split. (Note this is suboptimal.
+    /// Evicts the coldest splits to reach the target limits.
     ///
     /// Returns `None` if this would mean evicting splits that
-    /// have been accessed more recently than the candidate split.
+    /// are hotter than the candidate split.
     pub(crate) fn make_room_for_split_if_necessary(
         &mut self,
-        last_access_date: LastAccessDate,
+        hotness: Hotness,
     ) -> Option<Vec<Ulid>> {
         let mut split_infos = Vec::new();
         while self.is_out_of_limits() {
             if let Some(first_split) = self.on_disk_splits.first() {
-                if first_split.last_accessed > last_access_date {
+                if first_split.hotness > hotness {
                     // This is not worth doing the eviction.
                     break;
                 }
@@ -409,7 +609,10 @@ impl SplitTable {
             Some(
                 split_infos
                     .into_iter()
-                    .map(|split_info| split_info.split_key.split_ulid)
+                    .map(|split_info| {
+                        record_eviction(&split_info);
+                        split_info.split_key.split_ulid
+                    })
                     .collect(),
             )
         }
@@ -418,7 +621,7 @@ impl SplitTable {
     pub(crate) fn find_download_opportunity(&mut self) -> Option<DownloadOpportunity> {
         let best_candidate_split_key = self.best_candidate()?;
         let splits_to_delete: Vec<Ulid> =
-            self.make_room_for_split_if_necessary(best_candidate_split_key.last_accessed)?;
+            self.make_room_for_split_if_necessary(best_candidate_split_key.hotness)?;
         let split_to_download: CandidateSplit =
             self.start_download(best_candidate_split_key.split_ulid)?;
         Some(DownloadOpportunity {
@@ -449,14 +652,19 @@ pub(crate) struct DownloadOpportunity {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeMap;
     use std::num::NonZeroU32;
+    use std::time::{Duration, SystemTime};
 
     use bytesize::ByteSize;
     use quickwit_common::uri::Uri;
     use quickwit_config::SplitCacheLimits;
     use ulid::Ulid;
 
-    use crate::split_cache::split_table::{DownloadOpportunity, SplitTable};
+    use crate::split_cache::manifest::OnDiskSplit;
+    use crate::split_cache::split_table::{
+        add_access, DownloadOpportunity, SplitTable, HOTNESS_HALF_LIFE,
+    };
 
     const TEST_STORAGE_URI: &str = "s3://test";
 
@@ -474,6 +682,7 @@ mod tests {
                 max_num_bytes: ByteSize::kb(1),
                 max_num_splits: NonZeroU32::new(1).unwrap(),
                 num_concurrent_downloads: NonZeroU32::new(1).unwrap(),
+                ..Default::default()
             },
             Default::default(),
         );
@@ -493,6 +702,7 @@ mod tests {
                 max_num_bytes: ByteSize::kb(1),
                 max_num_splits: NonZeroU32::new(1).unwrap(),
                 num_concurrent_downloads: NonZeroU32::new(1).unwrap(),
+                ..Default::default()
             },
             Default::default(),
         );
@@ -507,6 +717,63 @@ mod tests {
         assert_eq!(candidate.split_ulid, ulid1);
     }
 
+    #[test]
+    fn test_add_access() {
+        let half_life_micros = HOTNESS_HALF_LIFE.as_micros() as u64;
+        // Two simultaneous accesses are worth one access one half-life later.
+        assert_eq!(add_access(1_000, 1_000), 1_000 + half_life_micros);
+        // An access long forgotten barely counts.
+        let hotness = add_access(0, 100 * half_life_micros);
+        assert!(hotness - 100 * half_life_micros < 1_000);
+        assert_eq!(
+            add_access(0, 100 * half_life_micros),
+            add_access(100 * half_life_micros, 0)
+        );
+    }
+
+    #[test]
+    fn test_split_table_prefer_hot_splits() {
+        let mut split_table = SplitTable::with_limits_and_existing_splits(
+            SplitCacheLimits {
+                max_num_bytes: ByteSize::kb(1),
+                max_num_splits: NonZeroU32::new(1).unwrap(),
+                num_concurrent_downloads: NonZeroU32::new(1).unwrap(),
+                ..Default::default()
+            },
+            Default::default(),
+        );
+        let storage_uri = Uri::for_test(TEST_STORAGE_URI);
+        let ulids = sorted_split_ulids(2);
+        let ulid1 = ulids[0];
+        let ulid2 = ulids[1];
+        split_table.report(ulid1, storage_uri.clone());
+        split_table.report(ulid2, storage_uri.clone());
+
+        let second_micros = Duration::from_secs(1).as_micros() as u64;
+        // The first split is queried three times, the second split once but more recently.
+        for i in 1..=3 {
+            split_table.touch_at(ulid1, &storage_uri, i * 10 * second_micros);
+        }
+        split_table.touch_at(ulid2, &storage_uri, 60 * second_micros);
+        let candidate = split_table.best_candidate().unwrap();
+        assert_eq!(candidate.split_ulid, ulid1);
+
+        // Accesses within the same query do not make a split hotter.
+        let hotness = split_table.split_to_status[&ulid2].split_key.hotness;
+        split_table.touch_at(ulid2, &storage_uri, 60 * second_micros + 1_000);
+        assert_eq!(
+            split_table.split_to_status[&ulid2].split_key.hotness,
+            hotness
+        );
+
+        split_table.start_download(ulid1).unwrap();
+        split_table.register_as_downloaded(ulid1, 100);
+        assert_eq!(
+            split_table.on_disk_splits()[&ulid1].storage_uri,
+            Some(storage_uri)
+        );
+    }
+
     #[test]
     fn test_split_table_prefer_start_download_prevent_new_report() {
         let mut split_table = SplitTable::with_limits_and_existing_splits(
@@ -514,6 +781,7 @@ mod tests {
                 max_num_bytes: ByteSize::kb(1),
                 max_num_splits: NonZeroU32::new(1).unwrap(),
                 num_concurrent_downloads: NonZeroU32::new(1).unwrap(),
+                ..Default::default()
             },
             Default::default(),
         );
@@ -543,6 +811,7 @@ mod tests {
                 max_num_bytes: ByteSize::mb(1),
                 max_num_splits: NonZeroU32::new(30).unwrap(),
                 num_concurrent_downloads: NonZeroU32::new(1).unwrap(),
+                ..Default::default()
             },
             Default::default(),
         );
@@ -580,6 +849,7 @@ mod tests {
                 max_num_bytes: ByteSize::mb(10),
                 max_num_splits: NonZeroU32::new(5).unwrap(),
                 num_concurrent_downloads: NonZeroU32::new(1).unwrap(),
+                ..Default::default()
             },
             Default::default(),
         );
@@ -614,6 +884,7 @@ mod tests {
                 max_num_bytes: ByteSize::mb(10),
                 max_num_splits: NonZeroU32::new(5).unwrap(),
                 num_concurrent_downloads: NonZeroU32::new(1).unwrap(),
+                ..Default::default()
             },
             Default::default(),
         );
@@ -643,6 +914,7 @@ mod tests {
                 max_num_bytes: ByteSize::mb(10),
                 max_num_splits: NonZeroU32::new(5).unwrap(),
                 num_concurrent_downloads: NonZeroU32::new(1).unwrap(),
+                ..Default::default()
             },
             Default::default(),
         );
@@ -655,4 +927,82 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_split_table_restores_existing_splits() {
+        let now = SystemTime::now();
+        let ulids = sorted_split_ulids(3);
+        let mut existing_splits = BTreeMap::new();
+        // Splits without a known last access date are evicted first.
+        existing_splits.insert(
+            ulids[0],
+            OnDiskSplit {
+                num_bytes: 400_000,
+                last_accessed: Some(now - Duration::from_secs(60)),
+                checksum: Some("abc".to_string()),
+                storage_uri: None,
+            },
+        );
+        existing_splits.insert(
+            ulids[1],
+            OnDiskSplit {
+                num_bytes: 400_000,
+                last_accessed: Some(now - Duration::from_secs(3_600)),
+                checksum: None,
+                storage_uri: None,
+            },
+        );
+        existing_splits.insert(
+            ulids[2],
+            OnDiskSplit {
+                num_bytes: 400_000,
+                last_accessed: None,
+                checksum: None,
+                storage_uri: None,
+            },
+        );
+        let mut split_table = SplitTable::with_limits_and_existing_splits(
+            SplitCacheLimits {
+                max_num_bytes: ByteSize::mb(1),
+                max_num_splits: NonZeroU32::new(30).unwrap(),
+                num_concurrent_downloads: NonZeroU32::new(1).unwrap(),
+                ..Default::default()
+            },
+            existing_splits,
+        );
+        assert_eq!(split_table.num_bytes(), 1_200_000);
+
+        let on_disk_splits = split_table.on_disk_splits();
+        assert_eq!(on_disk_splits.len(), 3);
+        let restored_last_accessed = on_disk_splits[&ulids[0]].last_accessed.unwrap();
+        let drift = now
+            .duration_since(restored_last_accessed)
+            .unwrap()
+            .abs_diff(Duration::from_secs(60));
+        assert!(drift < Duration::from_secs(1));
+        assert_eq!(on_disk_splits[&ulids[0]].checksum.as_deref(), Some("abc"));
+
+        assert!(split_table.set_checksum(ulids[1], "def".to_string()));
+        assert!(!split_table.set_checksum(Ulid::new(), "ghi".to_string()));
+        assert_eq!(
+            split_table.on_disk_splits()[&ulids[1]].checksum.as_deref(),
+            Some("def")
+        );
+
+        let splits_to_delete = split_table
+            .make_room_for_split_if_necessary(u64::MAX)
+            .unwrap();
+        assert_eq!(splits_to_delete, vec![ulids[2]]);
+
+        assert!(split_table.evict_on_disk_split(ulids[1]));
+        assert!(!split_table.evict_on_disk_split(ulids[1]));
+        assert_eq!(split_table.num_bytes(), 400_000);
+
+        split_table.set_max_num_bytes(ByteSize::kb(300));
+        let splits_to_delete = split_table
+            .make_room_for_split_if_necessary(u64::MAX)
+            .unwrap();
+        assert_eq!(splits_to_delete, vec![ulids[0]]);
+        assert_eq!(split_table.num_bytes(), 0);
+    }
 }
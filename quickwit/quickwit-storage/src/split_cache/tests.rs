@@ -34,6 +34,7 @@ fn test_split_table() {
         max_num_bytes: ByteSize::kb(1),
         max_num_splits: NonZeroU32::new(1).unwrap(),
         num_concurrent_downloads: NonZeroU32::new(1).unwrap(),
+        ..Default::default()
     });
     let ulid1 = Ulid::new();
     let ulid2 = Ulid::new();
@@ -49,6 +50,7 @@ fn test_split_table_prefer_last_touched() {
         max_num_bytes: ByteSize::kb(1),
         max_num_splits: NonZeroU32::new(1).unwrap(),
         num_concurrent_downloads: NonZeroU32::new(1).unwrap(),
+        ..Default::default()
     });
     let ulid1 = Ulid::new();
     let ulid2 = Ulid::new();
@@ -66,6 +68,7 @@ fn test_split_table_prefer_start_download_prevent_new_report() {
         max_num_bytes: ByteSize::kb(1),
         max_num_splits: NonZeroU32::new(1).unwrap(),
         num_concurrent_downloads: NonZeroU32::new(1).unwrap(),
+        ..Default::default()
     });
     let ulid1 = Ulid::new();
     split_table.report(ulid1, Uri::for_test(TEST_STORAGE_URI));
@@ -92,6 +95,7 @@ fn test_eviction_due_to_size() {
         max_num_bytes: ByteSize::mb(1),
         max_num_splits: NonZeroU32::new(30).unwrap(),
         num_concurrent_downloads: NonZeroU32::new(1).unwrap(),
+        ..Default::default()
     });
     let mut split_ulids: Vec<Ulid> = std::iter::repeat_with(Ulid::new).take(6).collect();
     split_ulids.sort();
@@ -126,6 +130,7 @@ fn test_eviction_due_to_num_splits() {
         max_num_bytes: ByteSize::mb(10),
         max_num_splits: NonZeroU32::new(5).unwrap(),
         num_concurrent_downloads: NonZeroU32::new(1).unwrap(),
+        ..Default::default()
     });
     let mut split_ulids: Vec<Ulid> = std::iter::repeat_with(Ulid::new).take(6).collect();
     split_ulids.sort();
@@ -157,6 +162,7 @@ fn test_failed_download_can_be_re_reported() {
         max_num_bytes: ByteSize::mb(10),
         max_num_splits: NonZeroU32::new(5).unwrap(),
         num_concurrent_downloads: NonZeroU32::new(1).unwrap(),
+        ..Default::default()
     });
     let split_ulid = Ulid::new();
     split_table.report(split_ulid, Uri::for_test(TEST_STORAGE_URI));
@@ -138,8 +138,40 @@ pub trait Storage: fmt::Debug + Send + Sync + 'static {
     /// Returns a file size.
     async fn file_num_bytes(&self, path: &Path) -> StorageResult<u64>;
 
+    /// Lists recursively the files located under the directory `prefix`. An empty prefix lists
+    /// all the files of the storage. The paths of the returned entries are relative to the
+    /// storage root.
+    ///
+    /// Listing is only used for maintenance operations, such as detecting orphan split files, and
+    /// some implementations do not support it.
+    async fn list(&self, prefix: &Path) -> StorageResult<Vec<StorageFileEntry>> {
+        Err(StorageErrorKind::Service.with_error(anyhow::anyhow!(
+            "storage `{}` does not support listing files under `{}`",
+            self.uri(),
+            prefix.display()
+        )))
+    }
+
     /// Returns an URI identifying the storage
     fn uri(&self) -> &Uri;
+
+    /// Returns true if the storage decrypts the files of the storage at [`Storage::uri`]. Caches
+    /// that fetch files from that URI on their own, bypassing this storage, must not wrap it.
+    fn is_encrypted(&self) -> bool {
+        false
+    }
+}
+
+/// File returned by [`Storage::list`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StorageFileEntry {
+    /// Path of the file relative to the storage root.
+    pub path: PathBuf,
+    /// Size of the file in bytes.
+    pub num_bytes: u64,
+    /// Last modification time of the file as a Unix timestamp in seconds, when the storage
+    /// exposes it.
+    pub last_modified_timestamp_opt: Option<i64>,
 }
 
 async fn default_copy_to_file<S: Storage + ?Sized>(
@@ -23,13 +23,17 @@ use std::sync::Arc;
 
 use once_cell::sync::Lazy;
 use quickwit_common::uri::{Protocol, Uri};
-use quickwit_config::{StorageBackend, StorageConfigs};
+use quickwit_config::{IndexEncryptionConfig, StorageBackend, StorageConfigs};
 
+use crate::encryption::{unwrap_data_key, EncryptedStorage};
 use crate::local_file_storage::LocalFileStorageFactory;
 use crate::ram_storage::RamStorageFactory;
 #[cfg(feature = "azure")]
 use crate::AzureBlobStorageFactory;
-use crate::{S3CompatibleObjectStorageFactory, Storage, StorageFactory, StorageResolverError};
+use crate::{
+    S3CompatibleObjectStorageFactory, Storage, StorageErrorKind, StorageFactory,
+    StorageResolverError, WebHdfsStorageFactory,
+};
 
 /// Returns the [`Storage`] instance associated with the protocol of a URI. The actual creation of
 /// storage objects is delegated to pre-registered [`StorageFactory`]. The resolver is only
@@ -56,6 +60,7 @@ impl StorageResolver {
         let backend = match uri.protocol() {
             Protocol::Azure => StorageBackend::Azure,
             Protocol::File => StorageBackend::File,
+            Protocol::Hdfs => StorageBackend::Hdfs,
             Protocol::Ram => StorageBackend::Ram,
             Protocol::S3 => StorageBackend::S3,
             _ => {
@@ -74,6 +79,28 @@ impl StorageResolver {
         Ok(storage)
     }
 
+    /// Resolves the storage of an index. If the index is encrypted, the storage returned
+    /// encrypts and decrypts the files with the data key of the index.
+    pub async fn resolve_index_storage(
+        &self,
+        index_uri: &Uri,
+        encryption_config_opt: Option<&IndexEncryptionConfig>,
+    ) -> Result<Arc<dyn Storage>, StorageResolverError> {
+        let storage = self.resolve(index_uri).await?;
+
+        let Some(encryption_config) = encryption_config_opt else {
+            return Ok(storage);
+        };
+        let data_key = unwrap_data_key(encryption_config).await.map_err(|error| {
+            StorageResolverError::FailedToOpenStorage {
+                kind: StorageErrorKind::Unauthorized,
+                message: format!("failed to unwrap data key of index `{index_uri}`: {error:#}"),
+            }
+        })?;
+        let encrypted_storage = EncryptedStorage::new(storage, data_key);
+        Ok(Arc::new(encrypted_storage))
+    }
+
     /// Creates and returns a default [`StorageResolver`] with the default storage configuration for
     /// each backend. Note that if the environment (env vars, instance metadata, ...) fails to
     /// provide the necessary credentials, the default Azure or S3 storage returned by this
@@ -93,6 +120,9 @@ impl StorageResolver {
             .register(RamStorageFactory::default())
             .register(S3CompatibleObjectStorageFactory::new(
                 storage_configs.find_s3().cloned().unwrap_or_default(),
+            ))
+            .register(WebHdfsStorageFactory::new(
+                storage_configs.find_hdfs().cloned().unwrap_or_default(),
             ));
         #[cfg(feature = "azure")]
         {